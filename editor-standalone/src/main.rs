@@ -1,7 +1,19 @@
 use fyrox::event_loop::EventLoop;
-use fyroxed_base::Editor;
+use fyroxed_base::{run_merge_driver, Editor};
+use std::path::Path;
 
 fn main() {
+    // `fyroxed merge <base> <ours> <theirs>` - merges scenes without opening the editor, it is
+    // meant to be used as a git merge driver.
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() == 5 && args[1] == "merge" {
+        std::process::exit(run_merge_driver(
+            Path::new(&args[2]),
+            Path::new(&args[3]),
+            Path::new(&args[4]),
+        ));
+    }
+
     let event_loop = EventLoop::new();
     let editor = Editor::new(&event_loop, None);
     editor.run(event_loop)
@@ -0,0 +1,523 @@
+use crate::menu::create_menu_item;
+use fyrox::{
+    animation::machine::BlendSpace,
+    core::{
+        algebra::{Matrix3, Vector2},
+        color::Color,
+        math::Rect,
+        pool::Handle,
+    },
+    gui::{
+        brush::Brush,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        formatted_text::{FormattedText, FormattedTextBuilder},
+        menu::MenuItemMessage,
+        message::{KeyCode, MessageDirection, MouseButton, UiMessage},
+        popup::PopupBuilder,
+        stack_panel::StackPanelBuilder,
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+};
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+};
+
+const MARGIN: f32 = 24.0;
+const POINT_RADIUS: f32 = 6.0;
+const GRID_DIVISIONS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlendSpaceFieldMessage {
+    /// Syncs the field with the given blend space.
+    Sync(BlendSpace),
+    /// Sets labels of the points of the field.
+    Labels(Vec<String>),
+    /// Selects a point with the given index.
+    SelectPoint(Option<usize>),
+    /// Emitted by the field when a point was dragged to a new position (in the parameter space).
+    MovePoint {
+        index: usize,
+        position: Vector2<f32>,
+    },
+    /// Emitted by the field when a user wants to add a point at the given position (in the
+    /// parameter space).
+    AddPoint(Vector2<f32>),
+    /// Emitted by the field when a user wants to remove a point with the given index.
+    RemovePoint(usize),
+}
+
+impl BlendSpaceFieldMessage {
+    define_constructor!(BlendSpaceFieldMessage:Sync => fn sync(BlendSpace), layout: false);
+    define_constructor!(BlendSpaceFieldMessage:Labels => fn labels(Vec<String>), layout: false);
+    define_constructor!(BlendSpaceFieldMessage:SelectPoint => fn select_point(Option<usize>), layout: false);
+    define_constructor!(BlendSpaceFieldMessage:MovePoint => fn move_point(index: usize, position: Vector2<f32>), layout: false);
+    define_constructor!(BlendSpaceFieldMessage:AddPoint => fn add_point(Vector2<f32>), layout: false);
+    define_constructor!(BlendSpaceFieldMessage:RemovePoint => fn remove_point(usize), layout: false);
+}
+
+#[derive(Clone)]
+struct DragContext {
+    index: usize,
+    initial_position: Vector2<f32>,
+}
+
+#[derive(Clone)]
+struct ContextMenu {
+    menu: Handle<UiNode>,
+    add_point: Handle<UiNode>,
+    remove_point: Handle<UiNode>,
+}
+
+/// 2D field, that shows sample points of a blend space in its parameter space. Points could be
+/// dragged with the mouse, the weights of the points for a sample under the cursor are shown
+/// next to each contributing point.
+#[derive(Clone)]
+pub struct BlendSpaceField {
+    widget: Widget,
+    blend_space: BlendSpace,
+    labels: Vec<String>,
+    selected_point: Option<usize>,
+    drag_context: Option<DragContext>,
+    // A sample under the cursor (in the parameter space).
+    cursor_sample: Option<Vector2<f32>>,
+    context_menu: ContextMenu,
+    text: RefCell<FormattedText>,
+    grid_brush: Brush,
+    point_brush: Brush,
+    selected_point_brush: Brush,
+    weight_brush: Brush,
+}
+
+define_widget_deref!(BlendSpaceField);
+
+impl BlendSpaceField {
+    fn field_bounds(&self) -> Rect<f32> {
+        let bounds = self.screen_bounds();
+        Rect::new(
+            bounds.x() + MARGIN,
+            bounds.y() + MARGIN,
+            (bounds.w() - 2.0 * MARGIN).max(1.0),
+            (bounds.h() - 2.0 * MARGIN).max(1.0),
+        )
+    }
+
+    fn space_size(&self) -> Vector2<f32> {
+        let size = self.blend_space.max_values - self.blend_space.min_values;
+        Vector2::new(
+            if size.x.abs() > f32::EPSILON {
+                size.x
+            } else {
+                1.0
+            },
+            if size.y.abs() > f32::EPSILON {
+                size.y
+            } else {
+                1.0
+            },
+        )
+    }
+
+    /// Transforms a point from the parameter space to screen space. Y axis of the parameter space
+    /// points up.
+    fn point_to_screen_space(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let bounds = self.field_bounds();
+        let size = self.space_size();
+        let t = (point - self.blend_space.min_values).component_div(&size);
+        Vector2::new(
+            bounds.x() + t.x * bounds.w(),
+            bounds.y() + bounds.h() - t.y * bounds.h(),
+        )
+    }
+
+    /// Transforms a point from screen space to the parameter space.
+    fn point_to_space(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let bounds = self.field_bounds();
+        let size = self.space_size();
+        let t = Vector2::new(
+            (point.x - bounds.x()) / bounds.w(),
+            (bounds.y() + bounds.h() - point.y) / bounds.h(),
+        );
+        self.blend_space.min_values + t.component_mul(&size)
+    }
+
+    fn pick(&self, screen_position: Vector2<f32>) -> Option<usize> {
+        self.blend_space
+            .points
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, point)| {
+                (self.point_to_screen_space(point.position) - screen_position).norm()
+                    <= POINT_RADIUS * 1.5
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn set_selected_point(&mut self, selected_point: Option<usize>, ui: &UserInterface) {
+        if self.selected_point != selected_point {
+            self.selected_point = selected_point;
+
+            ui.send_message(BlendSpaceFieldMessage::select_point(
+                self.handle(),
+                MessageDirection::FromWidget,
+                selected_point,
+            ));
+        }
+    }
+
+    fn draw_grid(&self, ctx: &mut DrawingContext) {
+        let screen_bounds = self.screen_bounds();
+        let bounds = self.field_bounds();
+
+        ctx.push_rect_filled(&screen_bounds, None);
+        ctx.commit(
+            self.clip_bounds(),
+            self.background(),
+            CommandTexture::None,
+            None,
+        );
+
+        for i in 0..=GRID_DIVISIONS {
+            let k = i as f32 / GRID_DIVISIONS as f32;
+            let x = bounds.x() + k * bounds.w();
+            let y = bounds.y() + k * bounds.h();
+            ctx.push_line(
+                Vector2::new(x, bounds.y()),
+                Vector2::new(x, bounds.y() + bounds.h()),
+                1.0,
+            );
+            ctx.push_line(
+                Vector2::new(bounds.x(), y),
+                Vector2::new(bounds.x() + bounds.w(), y),
+                1.0,
+            );
+        }
+        ctx.commit(
+            self.clip_bounds(),
+            self.grid_brush.clone(),
+            CommandTexture::None,
+            None,
+        );
+
+        let mut text = self.text.borrow_mut();
+        let min = self.blend_space.min_values;
+        let max = self.blend_space.max_values;
+        for i in 0..=GRID_DIVISIONS {
+            let k = i as f32 / GRID_DIVISIONS as f32;
+
+            text.set_text(format!("{:.2}", min.x + k * (max.x - min.x)))
+                .build();
+            ctx.draw_text(
+                self.clip_bounds(),
+                Vector2::new(bounds.x() + k * bounds.w(), bounds.y() + bounds.h() + 2.0),
+                &text,
+            );
+
+            text.set_text(format!("{:.2}", min.y + k * (max.y - min.y)))
+                .build();
+            ctx.draw_text(
+                self.clip_bounds(),
+                Vector2::new(
+                    screen_bounds.x() + 1.0,
+                    bounds.y() + bounds.h() - k * bounds.h(),
+                ),
+                &text,
+            );
+        }
+
+        text.set_text(self.blend_space.x_axis_name.clone()).build();
+        ctx.draw_text(
+            self.clip_bounds(),
+            Vector2::new(
+                bounds.x() + bounds.w() * 0.5,
+                bounds.y() + bounds.h() + 12.0,
+            ),
+            &text,
+        );
+
+        text.set_text(self.blend_space.y_axis_name.clone()).build();
+        ctx.draw_text(
+            self.clip_bounds(),
+            Vector2::new(bounds.x() + 2.0, screen_bounds.y() + 2.0),
+            &text,
+        );
+    }
+
+    fn draw_points(&self, ctx: &mut DrawingContext) {
+        for (index, point) in self.blend_space.points.iter().enumerate() {
+            ctx.push_circle(
+                self.point_to_screen_space(point.position),
+                POINT_RADIUS,
+                16,
+                Default::default(),
+            );
+            ctx.commit(
+                self.clip_bounds(),
+                if self.selected_point == Some(index) {
+                    self.selected_point_brush.clone()
+                } else {
+                    self.point_brush.clone()
+                },
+                CommandTexture::None,
+                None,
+            );
+        }
+
+        let mut text = self.text.borrow_mut();
+        for (index, point) in self.blend_space.points.iter().enumerate() {
+            if let Some(label) = self.labels.get(index) {
+                text.set_text(label.clone()).build();
+                ctx.draw_text(
+                    self.clip_bounds(),
+                    self.point_to_screen_space(point.position)
+                        + Vector2::new(POINT_RADIUS + 2.0, -POINT_RADIUS * 2.0),
+                    &text,
+                );
+            }
+        }
+    }
+
+    fn draw_weights(&self, ctx: &mut DrawingContext) {
+        let sample = match self.cursor_sample {
+            Some(sample) if self.drag_context.is_none() => sample,
+            _ => return,
+        };
+
+        let weights = self.blend_space.fetch_weights(sample);
+        let sample_position = self.point_to_screen_space(sample);
+
+        // Connect the sample with every contributing point, thicker lines mean bigger weights.
+        for &(index, weight) in weights.iter() {
+            ctx.push_line(
+                sample_position,
+                self.point_to_screen_space(self.blend_space.points[index].position),
+                1.0 + 3.0 * weight,
+            );
+        }
+        ctx.push_circle(sample_position, POINT_RADIUS * 0.5, 8, Default::default());
+        ctx.commit(
+            self.clip_bounds(),
+            self.weight_brush.clone(),
+            CommandTexture::None,
+            None,
+        );
+
+        let mut text = self.text.borrow_mut();
+        for &(index, weight) in weights.iter() {
+            text.set_text(format!("{:.0}%", weight * 100.0)).build();
+            ctx.draw_text(
+                self.clip_bounds(),
+                self.point_to_screen_space(self.blend_space.points[index].position)
+                    + Vector2::new(POINT_RADIUS + 2.0, POINT_RADIUS * 0.5),
+                &text,
+            );
+        }
+
+        text.set_text(format!("({:.2}; {:.2})", sample.x, sample.y))
+            .build();
+        ctx.draw_text(
+            self.clip_bounds(),
+            sample_position + Vector2::new(POINT_RADIUS, POINT_RADIUS),
+            &text,
+        );
+    }
+}
+
+impl Control for BlendSpaceField {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, ctx: &mut DrawingContext) {
+        ctx.transform_stack.push(Matrix3::identity());
+        self.draw_grid(ctx);
+        self.draw_points(ctx);
+        self.draw_weights(ctx);
+        ctx.transform_stack.pop();
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if message.destination() == self.handle() {
+            if let Some(msg) = message.data::<WidgetMessage>() {
+                match msg {
+                    WidgetMessage::MouseDown { pos, button } => {
+                        if *button == MouseButton::Left {
+                            let picked = self.pick(*pos);
+                            self.set_selected_point(picked, ui);
+
+                            if let Some(index) = picked {
+                                self.drag_context = Some(DragContext {
+                                    index,
+                                    initial_position: self.blend_space.points[index].position,
+                                });
+
+                                ui.capture_mouse(self.handle());
+                            }
+                        } else if *button == MouseButton::Right {
+                            // Allows to remove a point under the cursor using context menu.
+                            if let Some(picked) = self.pick(*pos) {
+                                self.set_selected_point(Some(picked), ui);
+                            }
+                        }
+                    }
+                    WidgetMessage::MouseUp { button, .. } => {
+                        if *button == MouseButton::Left {
+                            if let Some(drag_context) = self.drag_context.take() {
+                                ui.release_mouse_capture();
+
+                                let position = self.blend_space.points[drag_context.index].position;
+                                if position != drag_context.initial_position {
+                                    ui.send_message(BlendSpaceFieldMessage::move_point(
+                                        self.handle(),
+                                        MessageDirection::FromWidget,
+                                        drag_context.index,
+                                        position,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                    WidgetMessage::MouseMove { pos, .. } => {
+                        let sample = self.blend_space.clamp_sample(self.point_to_space(*pos));
+
+                        if let Some(drag_context) = self.drag_context.as_ref() {
+                            self.blend_space.points[drag_context.index].position = sample;
+                        }
+
+                        self.cursor_sample = Some(sample);
+                    }
+                    WidgetMessage::MouseLeave => {
+                        self.cursor_sample = None;
+                    }
+                    WidgetMessage::KeyUp(KeyCode::Delete) => {
+                        if let Some(selected_point) = self.selected_point {
+                            ui.send_message(BlendSpaceFieldMessage::remove_point(
+                                self.handle(),
+                                MessageDirection::FromWidget,
+                                selected_point,
+                            ));
+                        }
+                    }
+                    _ => (),
+                }
+            } else if let Some(msg) = message.data::<BlendSpaceFieldMessage>() {
+                if message.direction() == MessageDirection::ToWidget {
+                    match msg {
+                        BlendSpaceFieldMessage::Sync(blend_space) => {
+                            if self.drag_context.is_none() {
+                                self.blend_space = blend_space.clone();
+                            }
+
+                            if self
+                                .selected_point
+                                .map_or(false, |i| i >= self.blend_space.points.len())
+                            {
+                                self.set_selected_point(None, ui);
+                            }
+                        }
+                        BlendSpaceFieldMessage::Labels(labels) => {
+                            self.labels = labels.clone();
+                        }
+                        &BlendSpaceFieldMessage::SelectPoint(selected_point) => {
+                            self.set_selected_point(selected_point, ui);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
+            if message.destination() == self.context_menu.add_point {
+                let screen_position = ui.node(self.context_menu.menu).screen_position();
+                ui.send_message(BlendSpaceFieldMessage::add_point(
+                    self.handle(),
+                    MessageDirection::FromWidget,
+                    self.blend_space
+                        .clamp_sample(self.point_to_space(screen_position)),
+                ));
+            } else if message.destination() == self.context_menu.remove_point {
+                if let Some(selected_point) = self.selected_point {
+                    ui.send_message(BlendSpaceFieldMessage::remove_point(
+                        self.handle(),
+                        MessageDirection::FromWidget,
+                        selected_point,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub struct BlendSpaceFieldBuilder {
+    widget_builder: WidgetBuilder,
+}
+
+impl BlendSpaceFieldBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self { widget_builder }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let add_point;
+        let remove_point;
+        let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            add_point = create_menu_item("Add Point", vec![], ctx);
+                            add_point
+                        })
+                        .with_child({
+                            remove_point = create_menu_item("Remove Point", vec![], ctx);
+                            remove_point
+                        }),
+                )
+                .build(ctx),
+            )
+            .build(ctx);
+
+        let field = BlendSpaceField {
+            widget: self
+                .widget_builder
+                .with_context_menu(menu)
+                .with_preview_messages(true)
+                .with_background(Brush::Solid(Color::opaque(40, 40, 40)))
+                .build(),
+            blend_space: Default::default(),
+            labels: Default::default(),
+            selected_point: None,
+            drag_context: None,
+            cursor_sample: None,
+            context_menu: ContextMenu {
+                menu,
+                add_point,
+                remove_point,
+            },
+            text: RefCell::new(
+                FormattedTextBuilder::new(ctx.default_font())
+                    .with_brush(Brush::Solid(Color::opaque(180, 180, 180)))
+                    .build(),
+            ),
+            grid_brush: Brush::Solid(Color::opaque(70, 70, 70)),
+            point_brush: Brush::Solid(Color::opaque(140, 140, 140)),
+            selected_point_brush: Brush::Solid(Color::opaque(230, 160, 40)),
+            weight_brush: Brush::Solid(Color::opaque(80, 160, 220)),
+        };
+
+        ctx.add_node(UiNode::new(field))
+    }
+}
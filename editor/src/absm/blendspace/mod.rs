@@ -0,0 +1,405 @@
+use crate::{
+    absm::{
+        blendspace::field::{BlendSpaceFieldBuilder, BlendSpaceFieldMessage},
+        command::blend::{
+            AddBlendSpacePointCommand, RemoveBlendSpacePointCommand,
+            SetBlendSpacePointPoseSourceCommand, SetBlendSpacePointPositionCommand,
+        },
+        selection::SelectedEntity,
+    },
+    gui::make_dropdown_list_option,
+    scene::{EditorScene, Selection},
+    send_sync_message, Message, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    animation::{
+        machine::{BlendSpacePoint, Machine, PoseNode},
+        AnimationContainer,
+    },
+    core::pool::Handle,
+    gui::{
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{
+        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        graph::Graph,
+        node::Node,
+    },
+};
+use std::sync::mpsc::Sender;
+
+mod field;
+
+/// Returns a short description of a pose node, that is used as a label of a point of a blend
+/// space.
+fn describe_pose_node(
+    machine: &Machine,
+    animations: Option<&AnimationContainer>,
+    handle: Handle<PoseNode>,
+) -> String {
+    match machine.nodes().try_borrow(handle) {
+        Some(PoseNode::PlayAnimation(play_animation)) => animations
+            .and_then(|animations| animations.try_get(play_animation.animation))
+            .map(|animation| animation.name().to_owned())
+            .unwrap_or_else(|| "Play Animation".to_owned()),
+        Some(PoseNode::BlendAnimations(_)) => "Blend Animations".to_owned(),
+        Some(PoseNode::BlendAnimationsByIndex(_)) => "Blend Animations By Index".to_owned(),
+        Some(PoseNode::AddAnimation(_)) => "Add Animation".to_owned(),
+        Some(PoseNode::BlendSpace(_)) => "Blend Space".to_owned(),
+        None => "<None>".to_owned(),
+    }
+}
+
+/// Blend space editor shows sample points of a selected blend space node in its parameter space.
+/// It allows to add, remove and drag the points and to assign pose sources to them, every change
+/// is done via undoable commands.
+pub struct BlendSpaceEditor {
+    pub window: Handle<UiNode>,
+    field: Handle<UiNode>,
+    pose_sources: Handle<UiNode>,
+    blend_space: Handle<PoseNode>,
+    selected_point: Option<usize>,
+    // Pose nodes, that could be assigned to the selected point, with their descriptions. First
+    // entry is always empty pose source.
+    pose_source_list: Vec<(Handle<PoseNode>, String)>,
+}
+
+impl BlendSpaceEditor {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let field;
+        let pose_sources;
+        let window = WindowBuilder::new(WidgetBuilder::new())
+            .with_title(WindowTitle::text("Blend Space"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .with_text("Point Pose")
+                                        .build(ctx),
+                                    )
+                                    .with_child({
+                                        pose_sources = DropdownListBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_enabled(false)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .build(ctx);
+                                        pose_sources
+                                    }),
+                            )
+                            .add_row(Row::stretch())
+                            .add_column(Column::strict(80.0))
+                            .add_column(Column::stretch())
+                            .build(ctx),
+                        )
+                        .with_child({
+                            field = BlendSpaceFieldBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            field
+                        }),
+                )
+                .add_row(Row::strict(24.0))
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .can_close(false)
+            .can_minimize(false)
+            .build(ctx);
+
+        Self {
+            window,
+            field,
+            pose_sources,
+            blend_space: Default::default(),
+            selected_point: None,
+            pose_source_list: Default::default(),
+        }
+    }
+
+    pub fn clear(&mut self, ui: &mut UserInterface) {
+        self.blend_space = Handle::NONE;
+        self.selected_point = None;
+        self.pose_source_list.clear();
+
+        send_sync_message(
+            ui,
+            BlendSpaceFieldMessage::sync(
+                self.field,
+                MessageDirection::ToWidget,
+                Default::default(),
+            ),
+        );
+        send_sync_message(
+            ui,
+            BlendSpaceFieldMessage::labels(self.field, MessageDirection::ToWidget, vec![]),
+        );
+        send_sync_message(
+            ui,
+            DropdownListMessage::items(self.pose_sources, MessageDirection::ToWidget, vec![]),
+        );
+        send_sync_message(
+            ui,
+            WidgetMessage::enabled(self.pose_sources, MessageDirection::ToWidget, false),
+        );
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text("Blend Space - No Node"),
+        ));
+    }
+
+    pub fn sync_to_model(
+        &mut self,
+        ui: &mut UserInterface,
+        absm_node: &AnimationBlendingStateMachine,
+        graph: &Graph,
+        editor_scene: &EditorScene,
+    ) {
+        let machine = absm_node.machine();
+
+        let blend_space_handle = if let Selection::Absm(ref selection) = editor_scene.selection {
+            selection
+                .entities
+                .iter()
+                .find_map(|entity| match entity {
+                    SelectedEntity::PoseNode(handle)
+                        if matches!(
+                            machine.nodes().try_borrow(*handle),
+                            Some(PoseNode::BlendSpace(_))
+                        ) =>
+                    {
+                        Some(*handle)
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+
+        let blend_space = match machine.nodes().try_borrow(blend_space_handle) {
+            Some(PoseNode::BlendSpace(blend_space)) => blend_space,
+            _ => {
+                if self.blend_space.is_some() {
+                    self.clear(ui);
+                }
+                return;
+            }
+        };
+
+        if self.blend_space != blend_space_handle {
+            self.blend_space = blend_space_handle;
+            self.selected_point = None;
+
+            ui.send_message(WindowMessage::title(
+                self.window,
+                MessageDirection::ToWidget,
+                WindowTitle::text(format!(
+                    "Blend Space - {}:{}",
+                    blend_space_handle.index(),
+                    blend_space_handle.generation()
+                )),
+            ));
+        }
+
+        let animations = graph
+            .try_get(absm_node.animation_player())
+            .and_then(|n| n.query_component_ref::<AnimationPlayer>())
+            .map(|animation_player| &**animation_player.animations());
+
+        send_sync_message(
+            ui,
+            BlendSpaceFieldMessage::sync(
+                self.field,
+                MessageDirection::ToWidget,
+                blend_space.clone(),
+            ),
+        );
+        send_sync_message(
+            ui,
+            BlendSpaceFieldMessage::labels(
+                self.field,
+                MessageDirection::ToWidget,
+                blend_space
+                    .points
+                    .iter()
+                    .map(|point| describe_pose_node(machine, animations, point.pose_source))
+                    .collect(),
+            ),
+        );
+
+        // Any other node of the same state could be a pose source of a point.
+        let pose_source_list = std::iter::once((Handle::NONE, "<None>".to_owned()))
+            .chain(
+                machine
+                    .nodes()
+                    .pair_iter()
+                    .filter(|(handle, node)| {
+                        *handle != blend_space_handle
+                            && node.parent_state == blend_space.parent_state
+                    })
+                    .map(|(handle, _)| {
+                        (
+                            handle,
+                            format!(
+                                "{} ({}:{})",
+                                describe_pose_node(machine, animations, handle),
+                                handle.index(),
+                                handle.generation()
+                            ),
+                        )
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        if pose_source_list != self.pose_source_list {
+            let items = pose_source_list
+                .iter()
+                .map(|(_, name)| make_dropdown_list_option(&mut ui.build_ctx(), name))
+                .collect::<Vec<_>>();
+
+            send_sync_message(
+                ui,
+                DropdownListMessage::items(self.pose_sources, MessageDirection::ToWidget, items),
+            );
+
+            self.pose_source_list = pose_source_list;
+        }
+
+        self.sync_selected_point(ui, machine);
+    }
+
+    fn sync_selected_point(&self, ui: &UserInterface, machine: &Machine) {
+        let pose_source = match (
+            machine.nodes().try_borrow(self.blend_space),
+            self.selected_point,
+        ) {
+            (Some(PoseNode::BlendSpace(blend_space)), Some(selected_point)) => blend_space
+                .points
+                .get(selected_point)
+                .map(|point| point.pose_source),
+            _ => None,
+        };
+
+        send_sync_message(
+            ui,
+            WidgetMessage::enabled(
+                self.pose_sources,
+                MessageDirection::ToWidget,
+                pose_source.is_some(),
+            ),
+        );
+
+        send_sync_message(
+            ui,
+            DropdownListMessage::selection(
+                self.pose_sources,
+                MessageDirection::ToWidget,
+                pose_source.and_then(|pose_source| {
+                    self.pose_source_list
+                        .iter()
+                        .position(|(handle, _)| *handle == pose_source)
+                }),
+            ),
+        );
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        sender: &Sender<Message>,
+        absm_node_handle: Handle<Node>,
+        absm_node: &AnimationBlendingStateMachine,
+    ) {
+        if self.blend_space.is_none() {
+            return;
+        }
+
+        if let Some(msg) = message.data::<BlendSpaceFieldMessage>() {
+            if message.destination() == self.field
+                && message.direction() == MessageDirection::FromWidget
+            {
+                match msg {
+                    BlendSpaceFieldMessage::MovePoint { index, position } => {
+                        sender
+                            .send(Message::do_scene_command(
+                                SetBlendSpacePointPositionCommand {
+                                    node_handle: absm_node_handle,
+                                    handle: self.blend_space,
+                                    index: *index,
+                                    value: *position,
+                                },
+                            ))
+                            .unwrap();
+                    }
+                    BlendSpaceFieldMessage::AddPoint(position) => {
+                        sender
+                            .send(Message::do_scene_command(AddBlendSpacePointCommand::new(
+                                absm_node_handle,
+                                self.blend_space,
+                                BlendSpacePoint::new(*position, Handle::NONE),
+                            )))
+                            .unwrap();
+                    }
+                    BlendSpaceFieldMessage::RemovePoint(index) => {
+                        sender
+                            .send(Message::do_scene_command(
+                                RemoveBlendSpacePointCommand::new(
+                                    absm_node_handle,
+                                    self.blend_space,
+                                    *index,
+                                ),
+                            ))
+                            .unwrap();
+                    }
+                    BlendSpaceFieldMessage::SelectPoint(selected_point) => {
+                        self.selected_point = *selected_point;
+                        self.sync_selected_point(ui, absm_node.machine());
+                    }
+                    _ => (),
+                }
+            }
+        } else if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.pose_sources
+                && message.direction() == MessageDirection::FromWidget
+                && !message.has_flags(MSG_SYNC_FLAG)
+            {
+                if let (Some(selected_point), Some((pose_source, _))) =
+                    (self.selected_point, self.pose_source_list.get(*index))
+                {
+                    sender
+                        .send(Message::do_scene_command(
+                            SetBlendSpacePointPoseSourceCommand {
+                                node_handle: absm_node_handle,
+                                handle: self.blend_space,
+                                index: selected_point,
+                                value: *pose_source,
+                            },
+                        ))
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
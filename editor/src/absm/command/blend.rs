@@ -5,9 +5,10 @@ use crate::{
 use fyrox::{
     animation::machine::node::{
         blend::{BlendPose, IndexedBlendInput},
+        blendspace::BlendSpacePoint,
         PoseNode,
     },
-    core::pool::Handle,
+    core::{algebra::Vector2, pool::Handle},
     scene::node::Node,
 };
 
@@ -50,3 +51,91 @@ define_set_collection_element_command!(
         }
     }
 );
+
+define_set_collection_element_command!(
+    SetAddAnimationInputCommand<Handle<PoseNode>, Handle<PoseNode>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        match machine.nodes_mut()[self.handle] {
+            PoseNode::AddAnimation(ref mut definition) => match self.index {
+                0 => &mut definition.base_pose,
+                1 => &mut definition.additive_pose,
+                _ => &mut definition.reference_pose,
+            },
+            _ => unreachable!(),
+        }
+    }
+);
+
+define_push_element_to_collection_command!(AddBlendSpacePointCommand<Handle<PoseNode>, BlendSpacePoint>(self, context) {
+    let machine = fetch_machine(context, self.node_handle);
+    match &mut machine.nodes_mut()[self.handle] {
+        PoseNode::BlendSpace(definition) => &mut definition.points,
+        _ => unreachable!(),
+    }
+});
+
+define_set_collection_element_command!(
+    SetBlendSpacePointPoseSourceCommand<Handle<PoseNode>, Handle<PoseNode>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        match machine.nodes_mut()[self.handle] {
+            PoseNode::BlendSpace(ref mut definition) => {
+                &mut definition.points[self.index].pose_source
+            }
+            _ => unreachable!(),
+        }
+    }
+);
+
+define_set_collection_element_command!(
+    SetBlendSpacePointPositionCommand<Handle<PoseNode>, Vector2<f32>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        match machine.nodes_mut()[self.handle] {
+            PoseNode::BlendSpace(ref mut definition) => {
+                &mut definition.points[self.index].position
+            }
+            _ => unreachable!(),
+        }
+    }
+);
+
+#[derive(Debug)]
+pub struct RemoveBlendSpacePointCommand {
+    pub node_handle: Handle<Node>,
+    pub handle: Handle<PoseNode>,
+    pub index: usize,
+    pub value: Option<BlendSpacePoint>,
+}
+
+impl RemoveBlendSpacePointCommand {
+    pub fn new(node_handle: Handle<Node>, handle: Handle<PoseNode>, index: usize) -> Self {
+        Self {
+            node_handle,
+            handle,
+            index,
+            value: None,
+        }
+    }
+
+    fn fetch_points<'a>(&self, context: &'a mut SceneContext) -> &'a mut Vec<BlendSpacePoint> {
+        let machine = fetch_machine(context, self.node_handle);
+        match machine.nodes_mut()[self.handle] {
+            PoseNode::BlendSpace(ref mut definition) => &mut definition.points,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Command for RemoveBlendSpacePointCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Remove Blend Space Point".to_string()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.value = Some(self.fetch_points(context).remove(self.index));
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        let value = self.value.take().unwrap();
+        self.fetch_points(context).insert(self.index, value);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
     absm::{
-        command::blend::{AddInputCommand, AddPoseSourceCommand},
+        blendspace::BlendSpaceEditor,
+        command::blend::{AddBlendSpacePointCommand, AddInputCommand, AddPoseSourceCommand},
         node::{AbsmNode, AbsmNodeMessage},
         parameter::ParameterPanel,
+        preview::AbsmPreview,
         state_graph::StateGraphViewer,
         state_viewer::StateViewer,
         toolbar::{Toolbar, ToolbarAction},
@@ -11,7 +13,7 @@ use crate::{
     Message,
 };
 use fyrox::{
-    animation::machine::{BlendPose, Event, IndexedBlendInput, Machine, PoseNode, State},
+    animation::machine::{BlendPose, BlendSpacePoint, Event, IndexedBlendInput, PoseNode, State},
     core::{color::Color, pool::Handle},
     engine::Engine,
     gui::{
@@ -21,25 +23,20 @@ use fyrox::{
         message::{MessageDirection, UiMessage},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowMessage, WindowTitle},
-        BuildContext, UiNode, UserInterface,
-    },
-    scene::{
-        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
-        node::Node,
-        Scene,
+        UiNode, UserInterface,
     },
+    scene::{animation::absm::AnimationBlendingStateMachine, node::Node},
 };
 use std::sync::mpsc::Sender;
 
-mod canvas;
+mod blendspace;
 pub mod command;
-mod connection;
 mod node;
 mod parameter;
+mod preview;
 mod segment;
 mod selectable;
 pub mod selection;
-mod socket;
 mod state_graph;
 mod state_viewer;
 mod toolbar;
@@ -51,25 +48,27 @@ const BORDER_COLOR: Color = Color::opaque(70, 70, 70);
 const NORMAL_ROOT_COLOR: Color = Color::opaque(40, 80, 0);
 const SELECTED_ROOT_COLOR: Color = Color::opaque(60, 100, 0);
 
-struct PreviewModeData {
-    machine: Machine,
-    nodes: Vec<(Handle<Node>, Node)>,
-}
-
 pub struct AbsmEditor {
     pub window: Handle<UiNode>,
     state_graph_viewer: StateGraphViewer,
     state_viewer: StateViewer,
+    blend_space_editor: BlendSpaceEditor,
     parameter_panel: ParameterPanel,
     absm: Handle<Node>,
     toolbar: Toolbar,
-    preview_mode_data: Option<PreviewModeData>,
+    preview: AbsmPreview,
+    // Preview must be re-instantiated after every change of the scene to reflect the changes.
+    preview_outdated: bool,
 }
 
 impl AbsmEditor {
-    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>) -> Self {
+    pub fn new(engine: &mut Engine, sender: Sender<Message>) -> Self {
+        let preview = AbsmPreview::new(engine);
+
+        let ctx = &mut engine.user_interface.build_ctx();
         let state_graph_viewer = StateGraphViewer::new(ctx);
         let state_viewer = StateViewer::new(ctx);
+        let blend_space_editor = BlendSpaceEditor::new(ctx);
         let parameter_panel = ParameterPanel::new(ctx, sender);
 
         let docking_manager = DockingManagerBuilder::new(
@@ -79,7 +78,19 @@ impl AbsmEditor {
                         splitter: 0.3,
                         tiles: [
                             TileBuilder::new(WidgetBuilder::new())
-                                .with_content(TileContent::Window(parameter_panel.window))
+                                .with_content(TileContent::VerticalTiles {
+                                    splitter: 0.5,
+                                    tiles: [
+                                        TileBuilder::new(WidgetBuilder::new())
+                                            .with_content(TileContent::Window(
+                                                parameter_panel.window,
+                                            ))
+                                            .build(ctx),
+                                        TileBuilder::new(WidgetBuilder::new())
+                                            .with_content(TileContent::Window(preview.window))
+                                            .build(ctx),
+                                    ],
+                                })
                                 .build(ctx),
                             TileBuilder::new(WidgetBuilder::new())
                                 .with_content(TileContent::HorizontalTiles {
@@ -91,7 +102,21 @@ impl AbsmEditor {
                                             ))
                                             .build(ctx),
                                         TileBuilder::new(WidgetBuilder::new())
-                                            .with_content(TileContent::Window(state_viewer.window))
+                                            .with_content(TileContent::VerticalTiles {
+                                                splitter: 0.6,
+                                                tiles: [
+                                                    TileBuilder::new(WidgetBuilder::new())
+                                                        .with_content(TileContent::Window(
+                                                            state_viewer.window,
+                                                        ))
+                                                        .build(ctx),
+                                                    TileBuilder::new(WidgetBuilder::new())
+                                                        .with_content(TileContent::Window(
+                                                            blend_space_editor.window,
+                                                        ))
+                                                        .build(ctx),
+                                                ],
+                                            })
                                             .build(ctx),
                                     ],
                                 })
@@ -125,100 +150,44 @@ impl AbsmEditor {
             window,
             state_graph_viewer,
             state_viewer,
+            blend_space_editor,
             parameter_panel,
             absm: Default::default(),
             toolbar,
-            preview_mode_data: None,
+            preview,
+            preview_outdated: false,
         }
     }
 
-    fn enter_preview_mode(
-        &mut self,
-        machine: Machine,
-        animation_targets: Vec<Handle<Node>>,
-        scene: &Scene,
-        ui: &UserInterface,
-    ) {
-        assert!(self.preview_mode_data.is_none());
+    fn enter_preview_mode(&mut self, editor_scene: &EditorScene, engine: &mut Engine) {
+        self.preview
+            .instantiate(self.absm, editor_scene.scene, engine);
 
-        ui.send_message(CheckBoxMessage::checked(
+        engine.user_interface.send_message(CheckBoxMessage::checked(
             self.toolbar.preview,
             MessageDirection::ToWidget,
-            Some(true),
+            Some(self.preview.is_active()),
         ));
-
-        // Save state of affected nodes.
-        self.preview_mode_data = Some(PreviewModeData {
-            machine,
-            nodes: animation_targets
-                .into_iter()
-                .map(|t| (t, scene.graph[t].clone_box()))
-                .collect(),
-        });
     }
 
-    fn leave_preview_mode(&mut self, scene: &mut Scene, ui: &mut UserInterface) {
-        ui.send_message(CheckBoxMessage::checked(
+    fn leave_preview_mode(&mut self, engine: &mut Engine) {
+        self.preview.clear(engine);
+
+        engine.user_interface.send_message(CheckBoxMessage::checked(
             self.toolbar.preview,
             MessageDirection::ToWidget,
             Some(false),
         ));
-
-        let preview_data = self
-            .preview_mode_data
-            .take()
-            .expect("Unable to leave ABSM preview mode!");
-
-        // Revert state of nodes.
-        for (handle, node) in preview_data.nodes {
-            scene.graph[handle] = node;
-        }
-
-        let absm_node = scene.graph[self.absm]
-            .query_component_mut::<AnimationBlendingStateMachine>()
-            .unwrap();
-
-        *absm_node.machine_mut().get_mut_silent() = preview_data.machine;
-
-        self.parameter_panel.sync_to_model(ui, absm_node);
     }
 
-    pub fn handle_message(
-        &mut self,
-        message: &Message,
-        editor_scene: &EditorScene,
-        engine: &mut Engine,
-    ) {
-        // Leave preview mode before execution of any scene command.
+    pub fn handle_message(&mut self, message: &Message) {
+        // Any scene command could change the machine or animated nodes, so the preview must be
+        // re-created when the command is executed.
         if let Message::DoSceneCommand(_) | Message::UndoSceneCommand | Message::RedoSceneCommand =
             message
         {
-            if let Selection::Absm(ref selection) = editor_scene.selection {
-                let scene = &mut engine.scenes[editor_scene.scene];
-
-                if let Some(absm) = scene
-                    .graph
-                    .try_get_mut(selection.absm_node_handle)
-                    .and_then(|n| n.query_component_mut::<AnimationBlendingStateMachine>())
-                {
-                    absm.set_enabled(false);
-
-                    let animation_player_handle = absm.animation_player();
-
-                    if let Some(animation_player) = scene
-                        .graph
-                        .try_get_mut(animation_player_handle)
-                        .and_then(|n| n.query_component_mut::<AnimationPlayer>())
-                    {
-                        if self.preview_mode_data.is_some() {
-                            for animation in animation_player.animations_mut().iter_mut() {
-                                animation.set_enabled(false);
-                            }
-
-                            self.leave_preview_mode(scene, &mut engine.user_interface);
-                        }
-                    }
-                }
+            if self.preview.is_active() {
+                self.preview_outdated = true;
             }
         }
     }
@@ -244,15 +213,29 @@ impl AbsmEditor {
             self.parameter_panel.on_selection_changed(ui, absm_node);
         }
 
+        let has_absm = absm_node.is_some();
+
         if let Some(absm_node) = absm_node {
             self.parameter_panel.sync_to_model(ui, absm_node);
             self.state_graph_viewer
                 .sync_to_model(absm_node, ui, editor_scene);
             self.state_viewer.sync_to_model(ui, absm_node, editor_scene);
+            self.blend_space_editor
+                .sync_to_model(ui, absm_node, &scene.graph, editor_scene);
         } else {
             self.parameter_panel.reset(ui);
             self.state_graph_viewer.clear(ui);
             self.state_viewer.clear(ui);
+            self.blend_space_editor.clear(ui);
+        }
+
+        let preview_outdated = std::mem::take(&mut self.preview_outdated);
+        if self.preview.is_active() {
+            if self.absm != prev_absm || !has_absm {
+                self.leave_preview_mode(engine);
+            } else if preview_outdated {
+                self.enter_preview_mode(editor_scene, engine);
+            }
         }
     }
 
@@ -265,15 +248,23 @@ impl AbsmEditor {
     }
 
     pub fn update(&mut self, editor_scene: &EditorScene, engine: &mut Engine) {
+        self.preview.update(engine);
         self.handle_machine_events(editor_scene, engine);
     }
 
     pub fn handle_machine_events(&self, editor_scene: &EditorScene, engine: &mut Engine) {
-        let scene = &mut engine.scenes[editor_scene.scene];
+        // The machine is evaluated only in the preview, so events are taken from there.
+        let (scene, absm) = if self.preview.is_active() {
+            (self.preview.scene(), self.preview.absm())
+        } else {
+            (editor_scene.scene, self.absm)
+        };
+
+        let scene = &mut engine.scenes[scene];
 
         if let Some(absm) = scene
             .graph
-            .try_get_mut(self.absm)
+            .try_get_mut(absm)
             .and_then(|n| n.query_component_mut::<AnimationBlendingStateMachine>())
         {
             let machine = absm.machine_mut();
@@ -301,6 +292,22 @@ impl AbsmEditor {
         sender: &Sender<Message>,
         editor_scene: &EditorScene,
     ) {
+        self.preview.handle_ui_message(message, engine);
+
+        match self.toolbar.handle_ui_message(message) {
+            ToolbarAction::None => {}
+            ToolbarAction::EnterPreviewMode => {
+                if !self.preview.is_active() {
+                    self.enter_preview_mode(editor_scene, engine);
+                }
+            }
+            ToolbarAction::LeavePreviewMode => {
+                if self.preview.is_active() {
+                    self.leave_preview_mode(engine);
+                }
+            }
+        }
+
         let scene = &mut engine.scenes[editor_scene.scene];
         let ui = &mut engine.user_interface;
 
@@ -325,63 +332,10 @@ impl AbsmEditor {
                 absm_node,
                 editor_scene,
             );
-            self.parameter_panel.handle_ui_message(
-                message,
-                sender,
-                self.absm,
-                absm_node,
-                self.preview_mode_data.is_some(),
-            );
-
-            let action = self.toolbar.handle_ui_message(message);
-
-            match action {
-                ToolbarAction::None => {}
-                ToolbarAction::EnterPreviewMode => {
-                    absm_node.set_enabled(true);
-
-                    let machine = (**absm_node.machine()).clone();
-
-                    // Enable all animations in the player.
-                    let animation_player = absm_node.animation_player();
-
-                    if let Some(animation_player) = scene
-                        .graph
-                        .try_get_mut(animation_player)
-                        .and_then(|n| n.query_component_mut::<AnimationPlayer>())
-                    {
-                        let mut animation_targets = Vec::new();
-                        for animation in animation_player.animations_mut().iter_mut() {
-                            animation.set_enabled(true);
-
-                            for track in animation.tracks() {
-                                animation_targets.push(track.target());
-                            }
-                        }
-
-                        self.enter_preview_mode(machine, animation_targets, scene, ui);
-                    }
-                }
-                ToolbarAction::LeavePreviewMode => {
-                    if self.preview_mode_data.is_some() {
-                        absm_node.set_enabled(false);
-
-                        // Disable all animations in the player.
-                        let animation_player = absm_node.animation_player();
-                        if let Some(animation_player) = scene
-                            .graph
-                            .try_get_mut(animation_player)
-                            .and_then(|n| n.query_component_mut::<AnimationPlayer>())
-                        {
-                            for animation in animation_player.animations_mut().iter_mut() {
-                                animation.set_enabled(false);
-                            }
-
-                            self.leave_preview_mode(scene, ui);
-                        }
-                    }
-                }
-            }
+            self.blend_space_editor
+                .handle_ui_message(message, ui, sender, self.absm, absm_node);
+            self.parameter_panel
+                .handle_ui_message(message, sender, self.absm);
         }
 
         if let Some(msg) = message.data::<AbsmNodeMessage>() {
@@ -409,8 +363,8 @@ impl AbsmEditor {
                             let model_ref = &absm_node.machine().nodes()[node.model_handle];
 
                             match model_ref {
-                                PoseNode::PlayAnimation(_) => {
-                                    // No input sockets
+                                PoseNode::PlayAnimation(_) | PoseNode::AddAnimation(_) => {
+                                    // No input sockets or fixed set of input sockets.
                                 }
                                 PoseNode::BlendAnimations(_) => {
                                     sender
@@ -430,6 +384,17 @@ impl AbsmEditor {
                                         )))
                                         .unwrap();
                                 }
+                                PoseNode::BlendSpace(_) => {
+                                    sender
+                                        .send(Message::do_scene_command(
+                                            AddBlendSpacePointCommand::new(
+                                                self.absm,
+                                                node.model_handle,
+                                                BlendSpacePoint::default(),
+                                            ),
+                                        ))
+                                        .unwrap();
+                                }
                             }
                         }
                     }
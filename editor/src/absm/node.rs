@@ -1,21 +1,15 @@
-use crate::absm::{
-    selectable::{Selectable, SelectableMessage},
-    BORDER_COLOR, NORMAL_BACKGROUND, SELECTED_BACKGROUND,
-};
+use crate::absm::{BORDER_COLOR, NORMAL_BACKGROUND, SELECTED_BACKGROUND};
 use fyrox::{
     core::{color::Color, pool::Handle},
     gui::{
-        border::{BorderBuilder, BorderMessage},
+        border::BorderMessage,
         brush::Brush,
         button::{ButtonBuilder, ButtonMessage},
         define_constructor,
-        grid::{Column, GridBuilder, Row},
         message::{MessageDirection, MouseButton, UiMessage},
-        stack_panel::StackPanelBuilder,
-        text::{TextBuilder, TextMessage},
+        node_graph::node::{GraphNode, GraphNodeBuilder, GraphNodeMessage},
         widget::{Widget, WidgetBuilder, WidgetMessage},
-        BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface,
-        VerticalAlignment,
+        BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface,
     },
 };
 use std::{
@@ -23,27 +17,15 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-#[derive(Clone)]
-pub struct AbsmBaseNode {
-    pub input_sockets: Vec<Handle<UiNode>>,
-    pub output_socket: Handle<UiNode>,
-}
-
+/// A node of a graph of ABSM editor, it is a [`GraphNode`] bound to an entity of a machine.
 pub struct AbsmNode<T>
 where
     T: 'static,
 {
-    widget: Widget,
-    background: Handle<UiNode>,
-    selectable: Selectable,
+    pub node: GraphNode,
     pub name_value: String,
     pub model_handle: Handle<T>,
-    pub base: AbsmBaseNode,
     pub add_input: Handle<UiNode>,
-    input_sockets_panel: Handle<UiNode>,
-    normal_color: Color,
-    selected_color: Color,
-    name: Handle<UiNode>,
 }
 
 impl<T> Clone for AbsmNode<T>
@@ -52,17 +34,10 @@ where
 {
     fn clone(&self) -> Self {
         Self {
-            widget: self.widget.clone(),
-            background: self.background,
-            selectable: self.selectable.clone(),
+            node: self.node.clone(),
             name_value: self.name_value.clone(),
             model_handle: self.model_handle,
-            base: self.base.clone(),
             add_input: self.add_input,
-            input_sockets_panel: self.input_sockets_panel,
-            normal_color: self.normal_color,
-            selected_color: self.selected_color,
-            name: self.name,
         }
     }
 }
@@ -74,7 +49,7 @@ where
     type Target = Widget;
 
     fn deref(&self) -> &Self::Target {
-        &self.widget
+        &self.node
     }
 }
 
@@ -83,24 +58,7 @@ where
     T: 'static,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.widget
-    }
-}
-
-impl<T> AbsmNode<T>
-where
-    T: 'static,
-{
-    fn update_colors(&self, ui: &UserInterface) {
-        ui.send_message(WidgetMessage::background(
-            self.background,
-            MessageDirection::ToWidget,
-            Brush::Solid(if self.selectable.selected {
-                self.selected_color
-            } else {
-                self.normal_color
-            }),
-        ));
+        &mut self.node
     }
 }
 
@@ -109,9 +67,6 @@ pub enum AbsmNodeMessage {
     Name(String),
     Enter,
     AddInput,
-    InputSockets(Vec<Handle<UiNode>>),
-    NormalColor(Color),
-    SelectedColor(Color),
     SetActive(bool),
 }
 
@@ -119,9 +74,6 @@ impl AbsmNodeMessage {
     define_constructor!(AbsmNodeMessage:Name => fn name(String), layout: false);
     define_constructor!(AbsmNodeMessage:Enter => fn enter(), layout: false);
     define_constructor!(AbsmNodeMessage:AddInput => fn add_input(), layout: false);
-    define_constructor!(AbsmNodeMessage:InputSockets => fn input_sockets(Vec<Handle<UiNode>>), layout: false);
-    define_constructor!(AbsmNodeMessage:NormalColor => fn normal_color(Color), layout: false);
-    define_constructor!(AbsmNodeMessage:SelectedColor => fn selected_color(Color), layout: false);
     define_constructor!(AbsmNodeMessage:SetActive => fn set_active(bool), layout: false);
 }
 
@@ -132,33 +84,22 @@ where
     fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
         if type_id == TypeId::of::<Self>() {
             Some(self)
-        } else if type_id == TypeId::of::<Selectable>() {
-            Some(&self.selectable)
-        } else if type_id == TypeId::of::<AbsmBaseNode>() {
-            Some(&self.base)
+        } else if type_id == TypeId::of::<GraphNode>() {
+            Some(&self.node)
         } else {
             None
         }
     }
 
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        self.node.resolve(node_map);
+        node_map.resolve(&mut self.add_input);
+    }
+
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
-        self.widget.handle_routed_message(ui, message);
-        self.selectable
-            .handle_routed_message(self.handle(), ui, message);
+        self.node.handle_routed_message(ui, message);
 
-        if let Some(SelectableMessage::Select(selected)) = message.data() {
-            if message.destination() == self.handle()
-                && message.direction() == MessageDirection::FromWidget
-            {
-                self.update_colors(ui);
-                if *selected {
-                    ui.send_message(WidgetMessage::topmost(
-                        self.handle(),
-                        MessageDirection::ToWidget,
-                    ));
-                }
-            }
-        } else if let Some(WidgetMessage::DoubleClick { button }) = message.data() {
+        if let Some(WidgetMessage::DoubleClick { button }) = message.data() {
             if !message.handled() && *button == MouseButton::Left {
                 ui.send_message(AbsmNodeMessage::enter(
                     self.handle(),
@@ -173,48 +114,16 @@ where
                 ));
             }
         } else if let Some(msg) = message.data::<AbsmNodeMessage>() {
-            if message.destination == self.handle()
+            if message.destination() == self.handle()
                 && message.direction() == MessageDirection::ToWidget
             {
                 match msg {
-                    AbsmNodeMessage::InputSockets(input_sockets) => {
-                        if input_sockets != &self.base.input_sockets {
-                            for &child in ui.node(self.input_sockets_panel).children() {
-                                ui.send_message(WidgetMessage::remove(
-                                    child,
-                                    MessageDirection::ToWidget,
-                                ));
-                            }
-
-                            for &socket in input_sockets {
-                                ui.send_message(WidgetMessage::link(
-                                    socket,
-                                    MessageDirection::ToWidget,
-                                    self.input_sockets_panel,
-                                ));
-                            }
-
-                            self.base.input_sockets = input_sockets.clone();
-                        }
-                    }
-                    AbsmNodeMessage::NormalColor(color) => {
-                        if &self.normal_color != color {
-                            self.normal_color = *color;
-                            self.update_colors(ui);
-                        }
-                    }
-                    AbsmNodeMessage::SelectedColor(color) => {
-                        if &self.selected_color != color {
-                            self.selected_color = *color;
-                            self.update_colors(ui);
-                        }
-                    }
                     AbsmNodeMessage::Name(name) => {
                         if &self.name_value != name {
                             self.name_value = name.clone();
 
-                            ui.send_message(TextMessage::text(
-                                self.name,
+                            ui.send_message(GraphNodeMessage::title(
+                                self.handle(),
                                 MessageDirection::ToWidget,
                                 name.clone(),
                             ));
@@ -228,12 +137,12 @@ where
                         };
 
                         ui.send_message(BorderMessage::stroke_thickness(
-                            self.background,
+                            self.node.background(),
                             MessageDirection::ToWidget,
                             thickness,
                         ));
                         ui.send_message(WidgetMessage::foreground(
-                            self.background,
+                            self.node.background(),
                             MessageDirection::ToWidget,
                             Brush::Solid(color),
                         ));
@@ -303,6 +212,7 @@ where
         self
     }
 
+    /// Sets a title of the node, the name of the node is used as the title if it is not set.
     pub fn with_title(mut self, title: String) -> Self {
         self.title = Some(title);
         self
@@ -319,132 +229,33 @@ where
     }
 
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
-        let input_sockets_panel;
-        let add_input;
-        let name;
-        let grid = GridBuilder::new(
+        let add_input = ButtonBuilder::new(
             WidgetBuilder::new()
-                .on_row(1)
-                .on_column(0)
-                .with_child(
-                    GridBuilder::new(
-                        WidgetBuilder::new()
-                            .with_child({
-                                input_sockets_panel = StackPanelBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_row(0)
-                                        .on_column(0)
-                                        .with_margin(Thickness::uniform(2.0))
-                                        .with_vertical_alignment(VerticalAlignment::Center)
-                                        .with_children(self.input_sockets.iter().cloned())
-                                        .on_column(0),
-                                )
-                                .build(ctx);
-                                input_sockets_panel
-                            })
-                            .with_child({
-                                add_input = ButtonBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_margin(Thickness::uniform(1.0))
-                                        .with_height(20.0)
-                                        .with_visibility(self.can_add_sockets)
-                                        .on_row(1)
-                                        .on_column(0),
-                                )
-                                .with_text("+Input")
-                                .build(ctx);
-                                add_input
-                            }),
-                    )
-                    .add_row(Row::stretch())
-                    .add_row(Row::auto())
-                    .add_column(Column::auto())
-                    .build(ctx),
-                )
-                .with_child({
-                    name = TextBuilder::new(
-                        WidgetBuilder::new()
-                            .with_width(150.0)
-                            .with_height(75.0)
-                            .on_column(1),
-                    )
-                    .with_vertical_text_alignment(VerticalAlignment::Center)
-                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
-                    .with_text(&self.name)
-                    .build(ctx);
-                    name
-                })
-                .with_child(
-                    StackPanelBuilder::new(
-                        WidgetBuilder::new()
-                            .with_margin(Thickness::uniform(2.0))
-                            .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_child(self.output_socket)
-                            .on_column(2),
-                    )
-                    .build(ctx),
-                ),
+                .with_margin(Thickness::uniform(1.0))
+                .with_height(20.0)
+                .with_visibility(self.can_add_sockets),
         )
-        .add_row(Row::stretch())
-        .add_column(Column::auto())
-        .add_column(Column::stretch())
-        .add_column(Column::auto())
+        .with_text("+Input")
         .build(ctx);
 
-        let grid2 = GridBuilder::new(
-            WidgetBuilder::new()
-                .with_child(
-                    self.title
-                        .map(|title| {
-                            BorderBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_height(24.0)
-                                    .with_background(Brush::Solid(Color::opaque(30, 30, 30)))
-                                    .with_child(
-                                        TextBuilder::new(
-                                            WidgetBuilder::new()
-                                                .with_vertical_alignment(VerticalAlignment::Center)
-                                                .with_margin(Thickness::uniform(2.0)),
-                                        )
-                                        .with_text(title)
-                                        .build(ctx),
-                                    ),
-                            )
-                            .with_stroke_thickness(Thickness::zero())
-                            .build(ctx)
-                        })
-                        .unwrap_or_default(),
-                )
-                .with_child(grid),
-        )
-        .add_row(Row::auto())
-        .add_row(Row::stretch())
-        .add_column(Column::stretch())
-        .build(ctx);
-
-        let background = BorderBuilder::new(
-            WidgetBuilder::new()
-                .with_foreground(Brush::Solid(BORDER_COLOR))
-                .with_background(Brush::Solid(self.normal_color))
-                .with_child(grid2),
-        )
-        .build(ctx);
+        let node = GraphNodeBuilder::new(self.widget_builder)
+            .with_title(self.title.as_ref().unwrap_or(&self.name))
+            .with_inputs(self.input_sockets)
+            .with_outputs(if self.output_socket.is_some() {
+                vec![self.output_socket]
+            } else {
+                Default::default()
+            })
+            .with_content(add_input)
+            .with_normal_brush(Brush::Solid(self.normal_color))
+            .with_selected_brush(Brush::Solid(self.selected_color))
+            .build_graph_node(ctx);
 
         let node = AbsmNode {
-            widget: self.widget_builder.with_child(background).build(),
-            background,
-            selectable: Default::default(),
+            node,
             model_handle: self.model_handle,
             name_value: self.name,
-            base: AbsmBaseNode {
-                input_sockets: self.input_sockets,
-                output_socket: self.output_socket,
-            },
             add_input,
-            input_sockets_panel,
-            normal_color: self.normal_color,
-            selected_color: self.selected_color,
-            name,
         };
 
         ctx.add_node(UiNode::new(node))
@@ -13,7 +13,7 @@ use fyrox::{
                 inspectable::InspectablePropertyEditorDefinition,
                 PropertyEditorDefinitionContainer,
             },
-            InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
+            InspectorBuilder, InspectorContext, InspectorMessage,
         },
         message::UiMessage,
         scroll_viewer::ScrollViewerBuilder,
@@ -119,8 +119,6 @@ impl ParameterPanel {
         message: &UiMessage,
         sender: &Sender<Message>,
         absm_node_handle: Handle<Node>,
-        absm_node: &mut AnimationBlendingStateMachine,
-        is_in_preview_mode: bool,
     ) {
         if message.destination() == self.inspector
             && message.direction() == MessageDirection::FromWidget
@@ -128,19 +126,11 @@ impl ParameterPanel {
             if let Some(InspectorMessage::PropertyChanged(args)) =
                 message.data::<InspectorMessage>()
             {
-                if is_in_preview_mode {
-                    Log::verify(PropertyAction::from_field_kind(&args.value).apply(
-                        &args.path(),
-                        absm_node.machine_mut().get_mut_silent().parameters_mut(),
+                sender
+                    .send(Message::DoSceneCommand(
+                        make_set_parameters_property_command((), args, absm_node_handle).unwrap(),
                     ))
-                } else {
-                    sender
-                        .send(Message::DoSceneCommand(
-                            make_set_parameters_property_command((), args, absm_node_handle)
-                                .unwrap(),
-                        ))
-                        .unwrap();
-                }
+                    .unwrap();
             }
         }
     }
@@ -0,0 +1,315 @@
+use crate::{preview::PreviewPanel, GameEngine};
+use fyrox::{
+    animation::machine::{Machine, Parameter},
+    core::pool::Handle,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    scene::{
+        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        camera::Camera,
+        graph::Graph,
+        node::Node,
+        Scene,
+    },
+};
+
+/// Preview of a state machine. The machine is copied to a separate scene together with its
+/// animation player and every animated node, so the machine could be evaluated in real time
+/// without affecting the edited scene.
+pub struct AbsmPreview {
+    pub window: Handle<UiNode>,
+    panel: PreviewPanel,
+    parameters: Handle<UiNode>,
+    parameter_controls: Vec<(Handle<UiNode>, String)>,
+    absm: Handle<Node>,
+}
+
+fn is_descendant_of(graph: &Graph, mut node: Handle<Node>, ancestor: Handle<Node>) -> bool {
+    while node.is_some() {
+        if node == ancestor {
+            return true;
+        }
+        node = graph[node].parent();
+    }
+    false
+}
+
+// Finds the deepest node that contains every given node in its sub-graph.
+fn common_ancestor(graph: &Graph, nodes: &[Handle<Node>]) -> Handle<Node> {
+    let mut candidate = nodes.first().cloned().unwrap_or_default();
+    while candidate.is_some() {
+        if nodes.iter().all(|n| is_descendant_of(graph, *n, candidate)) {
+            return candidate;
+        }
+        candidate = graph[candidate].parent();
+    }
+    graph.get_root()
+}
+
+fn make_parameter_control(value: Parameter, ctx: &mut BuildContext) -> Handle<UiNode> {
+    let widget_builder = WidgetBuilder::new()
+        .on_column(1)
+        .with_margin(Thickness::uniform(1.0));
+
+    match value {
+        Parameter::Weight(weight) => ScrollBarBuilder::new(widget_builder)
+            .with_min(0.0)
+            .with_max(1.0)
+            .with_step(0.01)
+            .with_value(weight)
+            .show_value(true)
+            .with_value_precision(2)
+            .build(ctx),
+        Parameter::Rule(rule) => CheckBoxBuilder::new(widget_builder)
+            .checked(Some(rule))
+            .build(ctx),
+        Parameter::Index(index) => NumericUpDownBuilder::new(widget_builder)
+            .with_value(index)
+            .build(ctx),
+    }
+}
+
+impl AbsmPreview {
+    pub fn new(engine: &mut GameEngine) -> Self {
+        let panel = PreviewPanel::new(engine, 300, 300);
+
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let parameters;
+        let window = WindowBuilder::new(WidgetBuilder::new())
+            .with_title(WindowTitle::text("Preview"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new().with_child(panel.root).with_child(
+                        ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
+                            .with_content({
+                                parameters =
+                                    StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+                                parameters
+                            })
+                            .build(ctx),
+                    ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(120.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .can_close(false)
+            .can_minimize(false)
+            .build(ctx);
+
+        Self {
+            window,
+            panel,
+            parameters,
+            parameter_controls: Default::default(),
+            absm: Default::default(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.absm.is_some()
+    }
+
+    /// Copies the state machine with its animation player and animated nodes to the preview
+    /// scene and enables them. Previous copy (if any) is removed.
+    pub fn instantiate(
+        &mut self,
+        absm: Handle<Node>,
+        source_scene: Handle<Scene>,
+        engine: &mut GameEngine,
+    ) {
+        self.clear(engine);
+
+        let preview_scene = self.panel.scene();
+
+        let mut source = None;
+        let mut dest = None;
+        for (handle, scene) in engine.scenes.pair_iter_mut() {
+            if handle == source_scene {
+                source = Some(scene);
+            } else if handle == preview_scene {
+                dest = Some(scene);
+            }
+        }
+
+        let (source, dest) = match (source, dest) {
+            (Some(source), Some(dest)) => (source, dest),
+            _ => return,
+        };
+
+        let absm_ref = match source
+            .graph
+            .try_get(absm)
+            .and_then(|n| n.query_component_ref::<AnimationBlendingStateMachine>())
+        {
+            Some(absm_ref) => absm_ref,
+            None => return,
+        };
+
+        let mut nodes = vec![absm, absm_ref.animation_player()];
+        if let Some(animation_player) = source
+            .graph
+            .try_get(absm_ref.animation_player())
+            .and_then(|n| n.query_component_ref::<AnimationPlayer>())
+        {
+            for animation in animation_player.animations().iter() {
+                nodes.extend(animation.tracks().iter().map(|t| t.target()));
+            }
+        }
+        nodes.retain(|n| source.graph.is_valid_handle(*n));
+
+        let root = common_ancestor(&source.graph, &nodes);
+
+        // Cameras are skipped, the preview has its own camera.
+        let (copy, mapping) = source.graph.copy_node(root, &mut dest.graph, &mut |_, n| {
+            n.cast::<Camera>().is_none()
+        });
+
+        let mut absm_copy = absm;
+        if !mapping.try_map(&mut absm_copy) {
+            dest.graph.remove_node(copy);
+            return;
+        }
+
+        let mut animation_player = Handle::NONE;
+        if let Some(absm_node) =
+            dest.graph[absm_copy].query_component_mut::<AnimationBlendingStateMachine>()
+        {
+            absm_node.set_enabled(true);
+            animation_player = absm_node.animation_player();
+        }
+
+        if let Some(animation_player) = dest
+            .graph
+            .try_get_mut(animation_player)
+            .and_then(|n| n.query_component_mut::<AnimationPlayer>())
+        {
+            for animation in animation_player.animations_mut().iter_mut() {
+                animation.set_enabled(true);
+            }
+        }
+
+        self.absm = absm_copy;
+        self.panel.set_model(copy, engine);
+
+        let ui = &mut engine.user_interface;
+        let mut rows = Vec::new();
+        if let Some(machine) = Self::machine(self.absm, &mut engine.scenes[preview_scene].graph) {
+            let ctx = &mut ui.build_ctx();
+            for definition in machine.parameters().iter() {
+                let control = make_parameter_control(definition.value, ctx);
+                rows.push(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                                )
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .with_text(&definition.name)
+                                .build(ctx),
+                            )
+                            .with_child(control),
+                    )
+                    .add_row(Row::strict(22.0))
+                    .add_column(Column::strict(120.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                );
+
+                self.parameter_controls
+                    .push((control, definition.name.clone()));
+            }
+        }
+
+        for row in rows {
+            ui.send_message(WidgetMessage::link(
+                row,
+                MessageDirection::ToWidget,
+                self.parameters,
+            ));
+        }
+    }
+
+    /// Removes the copy of the state machine from the preview scene.
+    pub fn clear(&mut self, engine: &mut GameEngine) {
+        self.panel.clear(engine);
+        self.absm = Handle::NONE;
+        self.parameter_controls.clear();
+
+        let ui = &engine.user_interface;
+        for &row in ui.node(self.parameters).children() {
+            ui.send_message(WidgetMessage::remove(row, MessageDirection::ToWidget));
+        }
+    }
+
+    fn machine(absm: Handle<Node>, graph: &mut Graph) -> Option<&mut Machine> {
+        graph
+            .try_get_mut(absm)
+            .and_then(|n| n.query_component_mut::<AnimationBlendingStateMachine>())
+            .map(|absm| absm.machine_mut().get_mut_silent())
+    }
+
+    fn machine_mut<'a>(&self, engine: &'a mut GameEngine) -> Option<&'a mut Machine> {
+        Self::machine(self.absm, &mut engine.scenes[self.panel.scene()].graph)
+    }
+
+    pub fn scene(&self) -> Handle<Scene> {
+        self.panel.scene()
+    }
+
+    /// Returns a handle of the copy of the state machine in the preview scene.
+    pub fn absm(&self) -> Handle<Node> {
+        self.absm
+    }
+
+    pub fn update(&mut self, engine: &mut GameEngine) {
+        self.panel.update(engine);
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+        self.panel.handle_message(message, engine);
+
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        let name = match self
+            .parameter_controls
+            .iter()
+            .find(|(control, _)| *control == message.destination())
+        {
+            Some((_, name)) => name,
+            None => return,
+        };
+
+        let value = if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            Parameter::Weight(*value)
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            Parameter::Rule(*value)
+        } else if let Some(NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<u32>>()
+        {
+            Parameter::Index(*value)
+        } else {
+            return;
+        };
+
+        if let Some(machine) = self.machine_mut(engine) {
+            machine.set_parameter(name, value);
+        }
+    }
+}
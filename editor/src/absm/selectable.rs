@@ -31,16 +31,19 @@ impl Selectable {
         if let Some(msg) = message.data::<WidgetMessage>() {
             match msg {
                 WidgetMessage::MouseDown { button, .. } => {
-                    if (*button == MouseButton::Left || *button == MouseButton::Right)
-                        && !self.selected
-                    {
-                        ui.send_message(SelectableMessage::select(
-                            self_handle,
-                            MessageDirection::ToWidget,
-                            true,
-                        ));
+                    if *button == MouseButton::Left || *button == MouseButton::Right {
+                        if !self.selected {
+                            ui.send_message(SelectableMessage::select(
+                                self_handle,
+                                MessageDirection::ToWidget,
+                                true,
+                            ));
+
+                            ui.capture_mouse(self_handle);
+                        }
 
-                        ui.capture_mouse(self_handle);
+                        // Prevent a parent node graph from changing selection.
+                        message.set_handled(true);
                     }
                 }
                 WidgetMessage::MouseUp { button, .. } => {
@@ -1,6 +1,5 @@
 use crate::{
     absm::{
-        canvas::{AbsmCanvasMessage, Mode},
         command::{
             AddStateCommand, DeleteStateCommand, DeleteTransitionCommand,
             SetMachineEntryStateCommand,
@@ -22,6 +21,7 @@ use fyrox::{
     gui::{
         menu::MenuItemMessage,
         message::{MessageDirection, UiMessage},
+        node_graph::NodeGraphMessage,
         popup::{Placement, PopupBuilder, PopupMessage},
         stack_panel::StackPanelBuilder,
         widget::WidgetBuilder,
@@ -143,14 +143,10 @@ impl NodeContextMenu {
         let machine = absm_node.machine();
         if let Some(MenuItemMessage::Click) = message.data() {
             if message.destination() == self.create_transition {
-                ui.send_message(AbsmCanvasMessage::switch_mode(
+                ui.send_message(NodeGraphMessage::start_link(
                     self.canvas,
                     MessageDirection::ToWidget,
-                    Mode::CreateTransition {
-                        source: self.placement_target,
-                        source_pos: ui.node(self.placement_target).center(),
-                        dest_pos: ui.node(self.canvas).screen_to_local(ui.cursor_position()),
-                    },
+                    self.placement_target,
                 ))
             } else if message.destination == self.remove {
                 let states_to_remove =
@@ -3,9 +3,10 @@ use crate::scene::commands::{ChangeSelectionCommand, CommandGroup, SceneCommand}
 use crate::scene::{EditorScene, Selection};
 use crate::{
     absm::{
-        canvas::{AbsmCanvas, AbsmCanvasBuilder, AbsmCanvasMessage},
         command::{AddTransitionCommand, MoveStateNodeCommand},
         node::{AbsmNode, AbsmNodeBuilder, AbsmNodeMessage},
+        segment::SegmentMessage,
+        selectable::SelectableMessage,
         state_graph::context::{CanvasContextMenu, NodeContextMenu, TransitionContextMenu},
         transition::{TransitionBuilder, TransitionMessage, TransitionView},
         NORMAL_BACKGROUND, NORMAL_ROOT_COLOR, SELECTED_BACKGROUND, SELECTED_ROOT_COLOR,
@@ -16,10 +17,12 @@ use fyrox::animation::machine::{State, Transition};
 use fyrox::scene::animation::absm::AnimationBlendingStateMachine;
 use fyrox::scene::node::Node;
 use fyrox::{
-    core::pool::Handle,
+    core::{algebra::Vector2, pool::Handle},
     gui::{
         border::BorderBuilder,
+        brush::Brush,
         message::{MessageDirection, UiMessage},
+        node_graph::{node::GraphNodeMessage, NodeGraph, NodeGraphBuilder, NodeGraphMessage},
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowTitle},
         BuildContext, Thickness, UiNode, UserInterface,
@@ -45,16 +48,72 @@ fn fetch_state_node_model_handle(handle: Handle<UiNode>, ui: &UserInterface) ->
         .model_handle
 }
 
+fn fetch_state_node_center(handle: Handle<UiNode>, ui: &UserInterface) -> Option<Vector2<f32>> {
+    // Desired position is used instead of actual, because it is updated immediately when a node is
+    // moved, while actual position is updated only on next layout pass.
+    ui.try_get_node(handle)
+        .map(|node| node.desired_local_position() + node.actual_local_size().scale(0.5))
+}
+
+/// Syncs ends of every transition that starts or ends at the given node (or every transition if
+/// the node is `Handle::NONE`). Transitions that share same pair of states are placed in parallel.
+fn sync_transitions_ends(canvas: Handle<UiNode>, moved_node: Handle<UiNode>, ui: &UserInterface) {
+    let transitions = ui
+        .node(canvas)
+        .children()
+        .iter()
+        .filter_map(|c| ui.node(*c).query_component::<TransitionView>())
+        .collect::<Vec<_>>();
+
+    for transition in transitions.iter().filter(|t| {
+        moved_node.is_none() || moved_node == t.segment.source || moved_node == t.segment.dest
+    }) {
+        // Find other transitions sharing the same source and dest nodes (in both directions).
+        let index = transitions
+            .iter()
+            .filter(|t| {
+                t.segment.source == transition.segment.source
+                    && t.segment.dest == transition.segment.dest
+                    || t.segment.source == transition.segment.dest
+                        && t.segment.dest == transition.segment.source
+            })
+            .position(|t| t.handle() == transition.handle())
+            .unwrap_or_default();
+
+        if let (Some(source_pos), Some(dest_pos)) = (
+            fetch_state_node_center(transition.segment.source, ui),
+            fetch_state_node_center(transition.segment.dest, ui),
+        ) {
+            let delta = dest_pos - source_pos;
+            let offset = Vector2::new(delta.y, -delta.x)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default()
+                .scale(15.0 * index as f32);
+
+            ui.send_message(SegmentMessage::source_position(
+                transition.handle(),
+                MessageDirection::ToWidget,
+                source_pos + offset,
+            ));
+
+            ui.send_message(SegmentMessage::dest_position(
+                transition.handle(),
+                MessageDirection::ToWidget,
+                dest_pos + offset,
+            ));
+        }
+    }
+}
+
 impl StateGraphViewer {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let mut node_context_menu = NodeContextMenu::new(ctx);
         let mut canvas_context_menu = CanvasContextMenu::new(ctx);
         let transition_context_menu = TransitionContextMenu::new(ctx);
 
-        let canvas = AbsmCanvasBuilder::new(
-            WidgetBuilder::new().with_context_menu(canvas_context_menu.menu),
-        )
-        .build(ctx);
+        let canvas =
+            NodeGraphBuilder::new(WidgetBuilder::new().with_context_menu(canvas_context_menu.menu))
+                .build(ctx);
 
         let window = WindowBuilder::new(WidgetBuilder::new())
             .with_title(WindowTitle::text("State Graph"))
@@ -134,12 +193,9 @@ impl StateGraphViewer {
         editor_scene: &EditorScene,
     ) {
         if message.destination() == self.canvas {
-            if let Some(msg) = message.data::<AbsmCanvasMessage>() {
+            if let Some(msg) = message.data::<NodeGraphMessage>() {
                 match msg {
-                    AbsmCanvasMessage::CommitTransition {
-                        source_node: source,
-                        dest_node: dest,
-                    } => {
+                    NodeGraphMessage::Link { source, dest } => {
                         if message.direction() == MessageDirection::FromWidget {
                             let source = fetch_state_node_model_handle(*source, ui);
                             let dest = fetch_state_node_model_handle(*dest, ui);
@@ -152,18 +208,17 @@ impl StateGraphViewer {
                                 .unwrap();
                         }
                     }
-                    AbsmCanvasMessage::CommitDrag { entries } => {
+                    NodeGraphMessage::CommitDrag(entries) => {
                         let commands = entries
                             .iter()
                             .map(|e| {
                                 let state_handle = fetch_state_node_model_handle(e.node, ui);
-                                let new_position = ui.node(e.node).actual_local_position();
 
                                 SceneCommand::new(MoveStateNodeCommand::new(
                                     absm_node_handle,
                                     state_handle,
                                     e.initial_position,
-                                    new_position,
+                                    e.new_position,
                                 ))
                             })
                             .collect::<Vec<_>>();
@@ -172,7 +227,10 @@ impl StateGraphViewer {
                             .send(Message::do_scene_command(CommandGroup::from(commands)))
                             .unwrap();
                     }
-                    AbsmCanvasMessage::SelectionChanged(selection) => {
+                    NodeGraphMessage::SyncConnections => {
+                        sync_transitions_ends(self.canvas, Handle::NONE, ui);
+                    }
+                    NodeGraphMessage::Selection(selection) => {
                         if message.direction() == MessageDirection::FromWidget {
                             let selection = Selection::Absm(AbsmSelection {
                                 absm_node_handle,
@@ -211,6 +269,40 @@ impl StateGraphViewer {
                     _ => (),
                 }
             }
+        } else if let Some(SelectableMessage::Select(true)) = message.data() {
+            // Transitions are not managed by the node graph, so their selection is passed to the
+            // graph manually. Selection made by the graph itself is marked as handled.
+            if message.direction() == MessageDirection::FromWidget
+                && !message.handled()
+                && ui.node(message.destination()).parent() == self.canvas
+            {
+                let canvas = ui
+                    .node(self.canvas)
+                    .query_component::<NodeGraph>()
+                    .expect("Must be NodeGraph!");
+
+                let new_selection = if ui.keyboard_modifiers().control {
+                    let mut selection = canvas.selection().to_vec();
+                    selection.push(message.destination());
+                    selection
+                } else {
+                    vec![message.destination()]
+                };
+
+                ui.send_message(NodeGraphMessage::selection(
+                    self.canvas,
+                    MessageDirection::ToWidget,
+                    new_selection,
+                ));
+            }
+        } else if let Some(WidgetMessage::DesiredPosition(_)) = message.data() {
+            if message.direction() == MessageDirection::ToWidget
+                && ui
+                    .node(message.destination())
+                    .has_component::<AbsmNode<State>>()
+            {
+                sync_transitions_ends(self.canvas, message.destination(), ui);
+            }
         }
 
         self.node_context_menu.handle_ui_message(
@@ -242,8 +334,8 @@ impl StateGraphViewer {
 
         let canvas = ui
             .node(self.canvas)
-            .cast::<AbsmCanvas>()
-            .expect("Must be AbsmCanvas!");
+            .query_component::<NodeGraph>()
+            .expect("Must be NodeGraph!");
 
         let mut states = canvas
             .children()
@@ -272,6 +364,7 @@ impl StateGraphViewer {
                     }) {
                         let state_view_handle = AbsmNodeBuilder::new(
                             WidgetBuilder::new()
+                                .with_min_size(Vector2::new(150.0, 50.0))
                                 .with_context_menu(self.node_context_menu.menu)
                                 .with_desired_position(state.position),
                         )
@@ -366,26 +459,26 @@ impl StateGraphViewer {
 
             send_sync_message(
                 ui,
-                AbsmNodeMessage::normal_color(
+                GraphNodeMessage::normal_brush(
                     *state,
                     MessageDirection::ToWidget,
-                    if state_model_handle == machine.entry_state() {
+                    Brush::Solid(if state_model_handle == machine.entry_state() {
                         NORMAL_ROOT_COLOR
                     } else {
                         NORMAL_BACKGROUND
-                    },
+                    }),
                 ),
             );
             send_sync_message(
                 ui,
-                AbsmNodeMessage::selected_color(
+                GraphNodeMessage::selected_brush(
                     *state,
                     MessageDirection::ToWidget,
-                    if state_model_handle == machine.entry_state() {
+                    Brush::Solid(if state_model_handle == machine.entry_state() {
                         SELECTED_ROOT_COLOR
                     } else {
                         SELECTED_BACKGROUND
-                    },
+                    }),
                 ),
             );
         }
@@ -523,21 +616,26 @@ impl StateGraphViewer {
             Default::default()
         };
 
+        for &transition in transitions.iter() {
+            send_sync_message(
+                ui,
+                SelectableMessage::select(
+                    transition,
+                    MessageDirection::ToWidget,
+                    new_selection.contains(&transition),
+                )
+                .with_handled(true),
+            );
+        }
+
         send_sync_message(
             ui,
-            AbsmCanvasMessage::selection_changed(
-                self.canvas,
-                MessageDirection::ToWidget,
-                new_selection,
-            ),
+            NodeGraphMessage::selection(self.canvas, MessageDirection::ToWidget, new_selection),
         );
 
         send_sync_message(
             ui,
-            AbsmCanvasMessage::force_sync_dependent_objects(
-                self.canvas,
-                MessageDirection::ToWidget,
-            ),
+            NodeGraphMessage::sync_connections(self.canvas, MessageDirection::ToWidget),
         );
     }
 }
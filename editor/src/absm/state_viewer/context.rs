@@ -5,19 +5,21 @@ use crate::{
     absm::{
         command::{
             blend::{
-                SetBlendAnimationByIndexInputPoseSourceCommand, SetBlendAnimationsPoseSourceCommand,
+                SetAddAnimationInputCommand, SetBlendAnimationByIndexInputPoseSourceCommand,
+                SetBlendAnimationsPoseSourceCommand, SetBlendSpacePointPoseSourceCommand,
             },
             AddPoseNodeCommand, DeletePoseNodeCommand, SetStateRootPoseCommand,
         },
-        connection::Connection,
         node::AbsmNode,
+        state_viewer::{fetch_pose_node_model_handle, fetch_socket_pose_node},
     },
     menu::create_menu_item,
     Message,
 };
 use fyrox::animation::machine::node::BasePoseNode;
 use fyrox::animation::machine::{
-    BlendAnimations, BlendAnimationsByIndex, Machine, PlayAnimation, PoseNode, State,
+    AddAnimation, BlendAnimations, BlendAnimationsByIndex, BlendSpace, Machine, PlayAnimation,
+    PoseNode, State,
 };
 use fyrox::scene::node::Node;
 use fyrox::{
@@ -25,6 +27,7 @@ use fyrox::{
     gui::{
         menu::MenuItemMessage,
         message::UiMessage,
+        node_graph::{connection::Connection, socket::Socket},
         popup::{Placement, PopupBuilder, PopupMessage},
         stack_panel::StackPanelBuilder,
         widget::WidgetBuilder,
@@ -37,6 +40,8 @@ pub struct CanvasContextMenu {
     create_play_animation: Handle<UiNode>,
     create_blend_animations: Handle<UiNode>,
     create_blend_by_index: Handle<UiNode>,
+    create_add_animation: Handle<UiNode>,
+    create_blend_space: Handle<UiNode>,
     pub menu: Handle<UiNode>,
     pub canvas: Handle<UiNode>,
     pub node_context_menu: Handle<UiNode>,
@@ -47,6 +52,8 @@ impl CanvasContextMenu {
         let create_play_animation;
         let create_blend_animations;
         let create_blend_by_index;
+        let create_add_animation;
+        let create_blend_space;
         let menu = PopupBuilder::new(
             WidgetBuilder::new()
                 .with_enabled(false) // Disabled by default.
@@ -66,6 +73,14 @@ impl CanvasContextMenu {
                     .with_child({
                         create_blend_by_index = create_menu_item("Blend By Index", vec![], ctx);
                         create_blend_by_index
+                    })
+                    .with_child({
+                        create_add_animation = create_menu_item("Add Animation", vec![], ctx);
+                        create_add_animation
+                    })
+                    .with_child({
+                        create_blend_space = create_menu_item("Blend Space", vec![], ctx);
+                        create_blend_space
                     }),
             )
             .build(ctx),
@@ -76,6 +91,8 @@ impl CanvasContextMenu {
             create_play_animation,
             create_blend_animations,
             create_blend_by_index,
+            create_add_animation,
+            create_blend_space,
             menu,
             canvas: Default::default(),
             node_context_menu: Default::default(),
@@ -100,8 +117,11 @@ impl CanvasContextMenu {
                     base: BasePoseNode {
                         position,
                         parent_state: current_state,
+                        mask: Default::default(),
                     },
                     animation: Default::default(),
+                    speed: None,
+                    sync_group: Default::default(),
                     output_pose: Default::default(),
                 }))
             } else if message.destination() == self.create_blend_animations {
@@ -109,6 +129,7 @@ impl CanvasContextMenu {
                     base: BasePoseNode {
                         position,
                         parent_state: current_state,
+                        mask: Default::default(),
                     },
                     pose_sources: Default::default(),
                     output_pose: Default::default(),
@@ -118,6 +139,7 @@ impl CanvasContextMenu {
                     base: BasePoseNode {
                         position,
                         parent_state: current_state,
+                        mask: Default::default(),
                     },
                     index_parameter: "".to_string(),
                     inputs: Default::default(),
@@ -125,6 +147,24 @@ impl CanvasContextMenu {
                     blend_time: Default::default(),
                     output_pose: Default::default(),
                 }))
+            } else if message.destination() == self.create_add_animation {
+                Some(PoseNode::AddAnimation(AddAnimation {
+                    base: BasePoseNode {
+                        position,
+                        parent_state: current_state,
+                        mask: Default::default(),
+                    },
+                    ..Default::default()
+                }))
+            } else if message.destination() == self.create_blend_space {
+                Some(PoseNode::BlendSpace(BlendSpace {
+                    base: BasePoseNode {
+                        position,
+                        parent_state: current_state,
+                        mask: Default::default(),
+                    },
+                    ..Default::default()
+                }))
             } else {
                 None
             };
@@ -234,6 +274,69 @@ impl NodeContextMenu {
     }
 }
 
+/// Removes a connection by resetting the source of the input of a pose node the connection leads to.
+pub fn remove_connection(
+    connection: Handle<UiNode>,
+    ui: &UserInterface,
+    sender: &Sender<Message>,
+    machine: &Machine,
+    absm_node_handle: Handle<Node>,
+) {
+    let connection_ref = ui.node(connection).query_component::<Connection>().unwrap();
+
+    let index = ui
+        .node(connection_ref.dest)
+        .query_component::<Socket>()
+        .unwrap()
+        .index;
+
+    let model_handle =
+        fetch_pose_node_model_handle(fetch_socket_pose_node(connection_ref.dest, ui), ui);
+    match machine.node(model_handle) {
+        PoseNode::PlayAnimation(_) => {
+            // No connections
+        }
+        PoseNode::BlendAnimations(_) => sender
+            .send(Message::do_scene_command(
+                SetBlendAnimationsPoseSourceCommand {
+                    node_handle: absm_node_handle,
+                    handle: model_handle,
+                    index,
+                    value: Default::default(),
+                },
+            ))
+            .unwrap(),
+        PoseNode::BlendAnimationsByIndex(_) => sender
+            .send(Message::do_scene_command(
+                SetBlendAnimationByIndexInputPoseSourceCommand {
+                    node_handle: absm_node_handle,
+                    handle: model_handle,
+                    index,
+                    value: Default::default(),
+                },
+            ))
+            .unwrap(),
+        PoseNode::AddAnimation(_) => sender
+            .send(Message::do_scene_command(SetAddAnimationInputCommand {
+                node_handle: absm_node_handle,
+                handle: model_handle,
+                index,
+                value: Default::default(),
+            }))
+            .unwrap(),
+        PoseNode::BlendSpace(_) => sender
+            .send(Message::do_scene_command(
+                SetBlendSpacePointPoseSourceCommand {
+                    node_handle: absm_node_handle,
+                    handle: model_handle,
+                    index,
+                    value: Default::default(),
+                },
+            ))
+            .unwrap(),
+    }
+}
+
 pub struct ConnectionContextMenu {
     remove: Handle<UiNode>,
     pub menu: Handle<UiNode>,
@@ -270,49 +373,7 @@ impl ConnectionContextMenu {
     ) {
         if let Some(MenuItemMessage::Click) = message.data() {
             if message.destination == self.remove {
-                let connection_ref = ui
-                    .node(self.placement_target)
-                    .query_component::<Connection>()
-                    .unwrap();
-
-                let dest_node_ref = ui
-                    .node(connection_ref.dest_node)
-                    .query_component::<AbsmNode<PoseNode>>()
-                    .unwrap();
-
-                let index = dest_node_ref
-                    .base
-                    .input_sockets
-                    .iter()
-                    .position(|s| *s == connection_ref.segment.dest)
-                    .unwrap();
-
-                let model_handle = dest_node_ref.model_handle;
-                match machine.node(model_handle) {
-                    PoseNode::PlayAnimation(_) => {
-                        // No connections
-                    }
-                    PoseNode::BlendAnimations(_) => sender
-                        .send(Message::do_scene_command(
-                            SetBlendAnimationsPoseSourceCommand {
-                                node_handle: absm_node_handle,
-                                handle: model_handle,
-                                index,
-                                value: Default::default(),
-                            },
-                        ))
-                        .unwrap(),
-                    PoseNode::BlendAnimationsByIndex(_) => sender
-                        .send(Message::do_scene_command(
-                            SetBlendAnimationByIndexInputPoseSourceCommand {
-                                node_handle: absm_node_handle,
-                                handle: model_handle,
-                                index,
-                                value: Default::default(),
-                            },
-                        ))
-                        .unwrap(),
-                }
+                remove_connection(self.placement_target, ui, sender, machine, absm_node_handle);
             }
         } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
             if message.destination() == self.menu {
@@ -1,15 +1,17 @@
-use crate::absm::command::blend::SetBlendAnimationsPoseSourceCommand;
+use crate::absm::command::blend::{
+    SetAddAnimationInputCommand, SetBlendAnimationsPoseSourceCommand,
+    SetBlendSpacePointPoseSourceCommand,
+};
 use crate::absm::selection::{AbsmSelection, SelectedEntity};
 use crate::scene::commands::{ChangeSelectionCommand, CommandGroup, SceneCommand};
 use crate::scene::{EditorScene, Selection};
 use crate::{
     absm::{
-        canvas::{AbsmCanvasBuilder, AbsmCanvasMessage},
         command::{blend::SetBlendAnimationByIndexInputPoseSourceCommand, MovePoseNodeCommand},
-        connection::{Connection, ConnectionBuilder},
-        node::{AbsmNode, AbsmNodeBuilder, AbsmNodeMessage},
-        socket::{Socket, SocketBuilder, SocketDirection},
-        state_viewer::context::{CanvasContextMenu, ConnectionContextMenu, NodeContextMenu},
+        node::{AbsmNode, AbsmNodeBuilder},
+        state_viewer::context::{
+            remove_connection, CanvasContextMenu, ConnectionContextMenu, NodeContextMenu,
+        },
         NORMAL_BACKGROUND, NORMAL_ROOT_COLOR, SELECTED_BACKGROUND, SELECTED_ROOT_COLOR,
     },
     send_sync_message, Message,
@@ -21,7 +23,14 @@ use fyrox::{
     core::pool::Handle,
     gui::{
         border::BorderBuilder,
+        brush::Brush,
         message::{MessageDirection, UiMessage},
+        node_graph::{
+            connection::{Connection, ConnectionBuilder},
+            node::GraphNodeMessage,
+            socket::{Socket, SocketBuilder, SocketDirection},
+            NodeGraphBuilder, NodeGraphMessage,
+        },
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, Thickness, UiNode, UserInterface,
@@ -44,12 +53,11 @@ pub struct StateViewer {
 fn create_socket(
     direction: SocketDirection,
     index: usize,
-    parent_node: Handle<PoseNode>,
     ui: &mut UserInterface,
 ) -> Handle<UiNode> {
+    // Poses have no types, so every output could be connected with any input.
     SocketBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
         .with_direction(direction)
-        .with_parent_node(parent_node)
         .with_index(index)
         .build(&mut ui.build_ctx())
 }
@@ -57,11 +65,10 @@ fn create_socket(
 fn create_sockets(
     count: usize,
     direction: SocketDirection,
-    parent_node: Handle<PoseNode>,
     ui: &mut UserInterface,
 ) -> Vec<Handle<UiNode>> {
     (0..count)
-        .map(|index| create_socket(direction, index, parent_node, ui))
+        .map(|index| create_socket(direction, index, ui))
         .collect::<Vec<_>>()
 }
 
@@ -72,14 +79,17 @@ fn fetch_pose_node_model_handle(handle: Handle<UiNode>, ui: &UserInterface) -> H
         .model_handle
 }
 
+/// Returns a handle of a pose node view the socket belongs to.
+fn fetch_socket_pose_node(socket: Handle<UiNode>, ui: &UserInterface) -> Handle<UiNode> {
+    ui.node(socket)
+        .find_by_criteria_up(ui, |n| n.has_component::<AbsmNode<PoseNode>>())
+}
+
 fn fetch_socket_pose_node_model_handle(
-    handle: Handle<UiNode>,
+    socket: Handle<UiNode>,
     ui: &UserInterface,
 ) -> Handle<PoseNode> {
-    ui.node(handle)
-        .query_component::<Socket>()
-        .unwrap()
-        .parent_node
+    fetch_pose_node_model_handle(fetch_socket_pose_node(socket, ui), ui)
 }
 
 impl StateViewer {
@@ -88,10 +98,9 @@ impl StateViewer {
         let mut canvas_context_menu = CanvasContextMenu::new(ctx);
         let connection_context_menu = ConnectionContextMenu::new(ctx);
 
-        let canvas = AbsmCanvasBuilder::new(
-            WidgetBuilder::new().with_context_menu(canvas_context_menu.menu),
-        )
-        .build(ctx);
+        let canvas =
+            NodeGraphBuilder::new(WidgetBuilder::new().with_context_menu(canvas_context_menu.menu))
+                .build(ctx);
         let window = WindowBuilder::new(WidgetBuilder::new())
             .can_close(false)
             .can_minimize(false)
@@ -192,20 +201,19 @@ impl StateViewer {
         let machine = absm_node.machine();
 
         if message.destination() == self.canvas {
-            if let Some(msg) = message.data::<AbsmCanvasMessage>() {
+            if let Some(msg) = message.data::<NodeGraphMessage>() {
                 match msg {
-                    AbsmCanvasMessage::CommitDrag { entries } => {
+                    NodeGraphMessage::CommitDrag(entries) => {
                         let commands = entries
                             .iter()
                             .map(|e| {
                                 let pose_handle = fetch_pose_node_model_handle(e.node, ui);
-                                let new_position = ui.node(e.node).actual_local_position();
 
                                 SceneCommand::new(MovePoseNodeCommand::new(
                                     absm_node_handle,
                                     pose_handle,
                                     e.initial_position,
-                                    new_position,
+                                    e.new_position,
                                 ))
                             })
                             .collect::<Vec<_>>();
@@ -214,7 +222,7 @@ impl StateViewer {
                             .send(Message::do_scene_command(CommandGroup::from(commands)))
                             .unwrap();
                     }
-                    AbsmCanvasMessage::SelectionChanged(selection) => {
+                    NodeGraphMessage::Selection(selection) => {
                         if message.direction() == MessageDirection::FromWidget {
                             let selection = Selection::Absm(AbsmSelection {
                                 absm_node_handle,
@@ -242,15 +250,16 @@ impl StateViewer {
                             }
                         }
                     }
-                    AbsmCanvasMessage::CommitConnection {
-                        source_socket,
-                        dest_socket,
-                    } => {
-                        let source_node = fetch_socket_pose_node_model_handle(*source_socket, ui);
+                    NodeGraphMessage::Disconnect(connection) => {
+                        remove_connection(*connection, ui, sender, machine, absm_node_handle);
+                    }
+                    NodeGraphMessage::Connect { source, dest } => {
+                        // Connections are always "child -> parent", so the source is an output of
+                        // a child pose node and the dest is an input of a parent pose node.
+                        let source_node = fetch_socket_pose_node_model_handle(*source, ui);
 
-                        let dest_socket_ref =
-                            ui.node(*dest_socket).query_component::<Socket>().unwrap();
-                        let dest_node = fetch_socket_pose_node_model_handle(*dest_socket, ui);
+                        let dest_socket_ref = ui.node(*dest).query_component::<Socket>().unwrap();
+                        let dest_node = fetch_socket_pose_node_model_handle(*dest, ui);
 
                         let dest_node_ref = &machine.nodes()[dest_node];
                         match dest_node_ref {
@@ -279,6 +288,28 @@ impl StateViewer {
                                     ))
                                     .unwrap();
                             }
+                            PoseNode::AddAnimation(_) => {
+                                sender
+                                    .send(Message::do_scene_command(SetAddAnimationInputCommand {
+                                        node_handle: absm_node_handle,
+                                        handle: dest_node,
+                                        index: dest_socket_ref.index,
+                                        value: source_node,
+                                    }))
+                                    .unwrap();
+                            }
+                            PoseNode::BlendSpace(_) => {
+                                sender
+                                    .send(Message::do_scene_command(
+                                        SetBlendSpacePointPoseSourceCommand {
+                                            node_handle: absm_node_handle,
+                                            handle: dest_node,
+                                            index: dest_socket_ref.index,
+                                            value: source_node,
+                                        },
+                                    ))
+                                    .unwrap();
+                            }
                         }
                     }
                     _ => (),
@@ -390,6 +421,13 @@ impl StateViewer {
                                 "Blend Animations By Index",
                                 true,
                             ),
+                            PoseNode::AddAnimation(add_animation) => {
+                                // Base, additive and reference poses.
+                                (add_animation.children().len(), "Add Animation", false)
+                            }
+                            PoseNode::BlendSpace(blend_space) => {
+                                (blend_space.points.len(), "Blend Space", true)
+                            }
                         };
 
                         let node_view = AbsmNodeBuilder::new(
@@ -403,15 +441,9 @@ impl StateViewer {
                         .with_input_sockets(create_sockets(
                             input_socket_count,
                             SocketDirection::Input,
-                            pose_definition,
-                            ui,
-                        ))
-                        .with_output_socket(create_socket(
-                            SocketDirection::Output,
-                            0,
-                            pose_definition,
                             ui,
                         ))
+                        .with_output_socket(create_socket(SocketDirection::Output, 0, ui))
                         .with_normal_color(if pose_definition == parent_state_ref.root {
                             NORMAL_ROOT_COLOR
                         } else {
@@ -472,17 +504,12 @@ impl StateViewer {
             let children = model_ref.children();
             let position = view_ref.actual_local_position();
 
-            if view_ref.base.input_sockets.len() != children.len() {
-                let input_sockets = create_sockets(
-                    children.len(),
-                    SocketDirection::Input,
-                    view_ref.model_handle,
-                    ui,
-                );
+            if view_ref.node.inputs.len() != children.len() {
+                let input_sockets = create_sockets(children.len(), SocketDirection::Input, ui);
 
                 send_sync_message(
                     ui,
-                    AbsmNodeMessage::input_sockets(view, MessageDirection::ToWidget, input_sockets),
+                    GraphNodeMessage::inputs(view, MessageDirection::ToWidget, input_sockets),
                 );
             }
 
@@ -500,26 +527,26 @@ impl StateViewer {
             if model_ref.parent_state == self.state {
                 send_sync_message(
                     ui,
-                    AbsmNodeMessage::normal_color(
+                    GraphNodeMessage::normal_brush(
                         view,
                         MessageDirection::ToWidget,
-                        if model_handle == parent_state_ref.root {
+                        Brush::Solid(if model_handle == parent_state_ref.root {
                             NORMAL_ROOT_COLOR
                         } else {
                             NORMAL_BACKGROUND
-                        },
+                        }),
                     ),
                 );
                 send_sync_message(
                     ui,
-                    AbsmNodeMessage::selected_color(
+                    GraphNodeMessage::selected_brush(
                         view,
                         MessageDirection::ToWidget,
-                        if model_handle == parent_state_ref.root {
+                        Brush::Solid(if model_handle == parent_state_ref.root {
                             SELECTED_ROOT_COLOR
                         } else {
                             SELECTED_BACKGROUND
-                        },
+                        }),
                     ),
                 );
             }
@@ -543,8 +570,7 @@ impl StateViewer {
                 .filter_map(|v| ui.node(*v).query_component::<AbsmNode<PoseNode>>())
                 .find(|v| v.model_handle == model)
                 .unwrap();
-            let dest_handle = dest_ref.handle();
-            let input_sockets = dest_ref.base.input_sockets.clone();
+            let input_sockets = dest_ref.node.inputs.clone();
 
             let model_ref = &machine.nodes()[model];
             for (i, child) in model_ref.children().into_iter().enumerate() {
@@ -561,11 +587,9 @@ impl StateViewer {
                     let connection = ConnectionBuilder::new(
                         WidgetBuilder::new().with_context_menu(self.connection_context_menu.menu),
                     )
-                    .with_source_socket(source.base.output_socket)
-                    .with_source_node(source.handle())
-                    .with_dest_socket(input_sockets[i])
-                    .with_dest_node(dest_handle)
-                    .build(self.canvas, &mut ui.build_ctx());
+                    .with_source(source.node.outputs[0])
+                    .with_dest(input_sockets[i])
+                    .build(&mut ui.build_ctx());
 
                     send_sync_message(
                         ui,
@@ -604,19 +628,12 @@ impl StateViewer {
 
         send_sync_message(
             ui,
-            AbsmCanvasMessage::selection_changed(
-                self.canvas,
-                MessageDirection::ToWidget,
-                new_selection,
-            ),
+            NodeGraphMessage::selection(self.canvas, MessageDirection::ToWidget, new_selection),
         );
 
         send_sync_message(
             ui,
-            AbsmCanvasMessage::force_sync_dependent_objects(
-                self.canvas,
-                MessageDirection::ToWidget,
-            ),
+            NodeGraphMessage::sync_connections(self.canvas, MessageDirection::ToWidget),
         );
     }
 }
@@ -7,12 +7,13 @@ use crate::{
 };
 use fyrox::animation::machine::Transition;
 use fyrox::{
-    core::{algebra::Vector2, color::Color, color::Hsv, math::Rect, pool::Handle},
+    core::{color::Color, color::Hsv, pool::Handle},
     gui::{
         brush::Brush,
         define_constructor, define_widget_deref,
-        draw::{CommandTexture, Draw, DrawingContext},
+        draw::DrawingContext,
         message::{MessageDirection, UiMessage},
+        node_graph::connection::draw_link,
         widget::{Widget, WidgetBuilder, WidgetMessage},
         BuildContext, Control, UiNode, UserInterface,
     },
@@ -65,30 +66,6 @@ impl TransitionView {
 
 define_widget_deref!(TransitionView);
 
-pub fn draw_transition(
-    drawing_context: &mut DrawingContext,
-    clip_bounds: Rect<f32>,
-    brush: Brush,
-    source_pos: Vector2<f32>,
-    dest_pos: Vector2<f32>,
-) {
-    drawing_context.push_line(source_pos, dest_pos, 4.0);
-
-    let axis = (dest_pos - source_pos).normalize();
-    let center = (dest_pos + source_pos).scale(0.5);
-    let perp = Vector2::new(axis.y, -axis.x).normalize();
-
-    let size = 18.0;
-
-    drawing_context.push_triangle_filled([
-        center + axis.scale(size),
-        center + perp.scale(size * 0.5),
-        center - perp.scale(size * 0.5),
-    ]);
-
-    drawing_context.commit(clip_bounds, brush, CommandTexture::None, None);
-}
-
 impl Control for TransitionView {
     fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
         if type_id == TypeId::of::<Self>() {
@@ -107,12 +84,12 @@ impl Control for TransitionView {
             NORMAL_COLOR
         };
 
-        draw_transition(
+        draw_link(
             drawing_context,
-            self.clip_bounds(),
-            Brush::Solid(color + Color::from(Hsv::new(180.0, 100.0, 50.0 * self.activity_factor))),
             self.segment.source_pos,
             self.segment.dest_pos,
+            self.clip_bounds(),
+            Brush::Solid(color + Color::from(Hsv::new(180.0, 100.0, 50.0 * self.activity_factor))),
         );
     }
 
@@ -149,7 +126,10 @@ impl Control for TransitionView {
 
     fn update(&mut self, dt: f32, _sender: &Sender<UiMessage>) {
         // Slowly fade.
-        self.activity_factor = (self.activity_factor - dt).max(0.0);
+        if self.activity_factor > 0.0 {
+            self.activity_factor = (self.activity_factor - dt).max(0.0);
+            self.invalidate_visual();
+        }
     }
 }
 
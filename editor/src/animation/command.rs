@@ -58,33 +58,39 @@ impl Command for AddTrackCommand {
 }
 
 #[derive(Debug)]
-pub struct ReplaceTrackCurveCommand {
+pub struct ReplaceTrackCurvesCommand {
     pub animation_player: Handle<Node>,
     pub animation: Handle<Animation>,
-    pub curve: Curve,
+    pub curves: Vec<Curve>,
 }
 
-impl ReplaceTrackCurveCommand {
+impl ReplaceTrackCurvesCommand {
     fn swap(&mut self, context: &mut SceneContext) {
-        for track in fetch_animation_player(self.animation_player, context).animations_mut()
-            [self.animation]
-            .tracks_mut()
-        {
-            for curve in track.frames_container_mut().curves_mut() {
-                if curve.id() == self.curve.id() {
-                    std::mem::swap(&mut self.curve, curve);
-                    return;
+        let animation = &mut fetch_animation_player(self.animation_player, context)
+            .animations_mut()[self.animation];
+
+        'curve_loop: for new_curve in self.curves.iter_mut() {
+            for track in animation.tracks_mut() {
+                for curve in track.frames_container_mut().curves_mut() {
+                    if curve.id() == new_curve.id() {
+                        std::mem::swap(new_curve, curve);
+                        continue 'curve_loop;
+                    }
                 }
             }
-        }
 
-        Log::err(format!("There's no such curve with id {}", self.curve.id()))
+            Log::err(format!("There's no such curve with id {}", new_curve.id()))
+        }
     }
 }
 
-impl Command for ReplaceTrackCurveCommand {
+impl Command for ReplaceTrackCurvesCommand {
     fn name(&mut self, _context: &SceneContext) -> String {
-        "Replace Track Curve".to_string()
+        if self.curves.len() == 1 {
+            "Replace Track Curve".to_string()
+        } else {
+            "Replace Track Curves".to_string()
+        }
     }
 
     fn execute(&mut self, context: &mut SceneContext) {
@@ -0,0 +1,482 @@
+//! Dope sheet shows keys of every curve of an animation on a shared time axis. It allows to
+//! select, move, remove, copy and paste keys. The widget does not modify any curves by itself,
+//! instead it reports requested changes using its messages, so the animation editor could turn
+//! them into commands.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix3, Point2, Vector2},
+        color::Color,
+        math::Rect,
+        pool::Handle,
+        uuid::Uuid,
+    },
+    fxhash::FxHashSet,
+    gui::{
+        brush::Brush,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        formatted_text::{FormattedText, FormattedTextBuilder},
+        menu::{MenuItemBuilder, MenuItemContent, MenuItemMessage},
+        message::{KeyCode, MessageDirection, MouseButton, UiMessage},
+        popup::PopupBuilder,
+        stack_panel::StackPanelBuilder,
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, UiNode, UserInterface, BRUSH_DARK, BRUSH_LIGHTER,
+    },
+};
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+};
+
+/// A row of the dope sheet, it represents a single curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DopeSheetRow {
+    pub curve: Uuid,
+    pub name: String,
+    /// Pairs of key id and key location.
+    pub keys: Vec<(Uuid, f32)>,
+}
+
+/// Unique reference to a key of a curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyRef {
+    pub curve: Uuid,
+    pub key: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DopeSheetMessage {
+    Sync(Vec<DopeSheetRow>),
+    Zoom(f32),
+    ViewPosition(f32),
+
+    // Requests, the widget emits them with FromWidget direction.
+    MoveKeys { keys: Vec<KeyRef>, offset: f32 },
+    RemoveKeys(Vec<KeyRef>),
+    AddKey { curve: Uuid, location: f32 },
+    CopyKeys(Vec<KeyRef>),
+    PasteKeys(f32),
+}
+
+impl DopeSheetMessage {
+    define_constructor!(DopeSheetMessage:Sync => fn sync(Vec<DopeSheetRow>), layout: false);
+    define_constructor!(DopeSheetMessage:Zoom => fn zoom(f32), layout: false);
+    define_constructor!(DopeSheetMessage:ViewPosition => fn view_position(f32), layout: false);
+    define_constructor!(DopeSheetMessage:MoveKeys => fn move_keys(keys: Vec<KeyRef>, offset: f32), layout: false);
+    define_constructor!(DopeSheetMessage:RemoveKeys => fn remove_keys(Vec<KeyRef>), layout: false);
+    define_constructor!(DopeSheetMessage:AddKey => fn add_key(curve: Uuid, location: f32), layout: false);
+    define_constructor!(DopeSheetMessage:CopyKeys => fn copy_keys(Vec<KeyRef>), layout: false);
+    define_constructor!(DopeSheetMessage:PasteKeys => fn paste_keys(f32), layout: false);
+}
+
+#[derive(Clone)]
+struct ContextMenu {
+    add_key: Handle<UiNode>,
+    remove: Handle<UiNode>,
+    copy: Handle<UiNode>,
+    paste: Handle<UiNode>,
+}
+
+#[derive(Clone)]
+struct Drag {
+    initial_location: f32,
+    offset: f32,
+}
+
+#[derive(Clone)]
+pub struct DopeSheet {
+    widget: Widget,
+    rows: Vec<DopeSheetRow>,
+    zoom: f32,
+    view_position: f32,
+    selection: FxHashSet<KeyRef>,
+    drag: Option<Drag>,
+    // Row and location of the last click, used by the context menu.
+    context_location: (Option<Uuid>, f32),
+    context_menu: ContextMenu,
+    text: RefCell<FormattedText>,
+    key_brush: Brush,
+    selected_key_brush: Brush,
+    row_brush: Brush,
+}
+
+define_widget_deref!(DopeSheet);
+
+const ROW_HEIGHT: f32 = 20.0;
+const KEY_HALF_SIZE: f32 = 5.0;
+
+impl DopeSheet {
+    fn view_matrix(&self) -> Matrix3<f32> {
+        Matrix3::new_nonuniform_scaling_wrt_point(
+            &Vector2::new(self.zoom, 1.0),
+            &Point2::from(self.actual_local_size().scale(0.5)),
+        ) * Matrix3::new_translation(&Vector2::new(self.view_position, 0.0))
+    }
+
+    fn local_to_view(&self, x: f32) -> f32 {
+        self.view_matrix().transform_point(&Point2::new(x, 0.0)).x
+    }
+
+    fn view_to_local(&self, x: f32) -> f32 {
+        self.view_matrix()
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&Point2::new(x, 0.0))
+            .x
+    }
+
+    fn row_at(&self, view_y: f32) -> Option<&DopeSheetRow> {
+        if view_y < 0.0 {
+            None
+        } else {
+            self.rows.get((view_y / ROW_HEIGHT) as usize)
+        }
+    }
+
+    fn pick(&self, pos: Vector2<f32>) -> Option<KeyRef> {
+        let view_pos = self.screen_to_local(pos);
+        let row = self.row_at(view_pos.y)?;
+        row.keys
+            .iter()
+            .find(|(_, location)| {
+                (self.local_to_view(*location) - view_pos.x).abs() <= KEY_HALF_SIZE
+            })
+            .map(|(key, _)| KeyRef {
+                curve: row.curve,
+                key: *key,
+            })
+    }
+
+    fn selection(&self) -> Vec<KeyRef> {
+        self.selection.iter().cloned().collect()
+    }
+
+    fn draw_key(ctx: &mut DrawingContext, center: Vector2<f32>) {
+        ctx.push_triangle_filled([
+            center - Vector2::new(KEY_HALF_SIZE, 0.0),
+            center - Vector2::new(0.0, KEY_HALF_SIZE),
+            center + Vector2::new(KEY_HALF_SIZE, 0.0),
+        ]);
+        ctx.push_triangle_filled([
+            center - Vector2::new(KEY_HALF_SIZE, 0.0),
+            center + Vector2::new(KEY_HALF_SIZE, 0.0),
+            center + Vector2::new(0.0, KEY_HALF_SIZE),
+        ]);
+    }
+}
+
+impl Control for DopeSheet {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, ctx: &mut DrawingContext) {
+        let bounds = self.bounding_rect();
+
+        // Clickable background.
+        ctx.push_rect_filled(&bounds, None);
+        ctx.commit(
+            self.clip_bounds(),
+            self.background.clone(),
+            CommandTexture::None,
+            None,
+        );
+
+        // Every other row is highlighted to make rows distinguishable.
+        for i in (0..self.rows.len()).step_by(2) {
+            ctx.push_rect_filled(
+                &Rect::new(0.0, i as f32 * ROW_HEIGHT, bounds.w(), ROW_HEIGHT),
+                None,
+            );
+        }
+        ctx.commit(
+            self.clip_bounds(),
+            self.row_brush.clone(),
+            CommandTexture::None,
+            None,
+        );
+
+        let mut text = self.text.borrow_mut();
+        for (i, row) in self.rows.iter().enumerate() {
+            text.set_text(&row.name).build();
+            ctx.draw_text(
+                self.clip_bounds(),
+                Vector2::new(2.0, i as f32 * ROW_HEIGHT + 2.0),
+                &text,
+            );
+        }
+
+        for selected in [false, true] {
+            for (i, row) in self.rows.iter().enumerate() {
+                let y = i as f32 * ROW_HEIGHT + ROW_HEIGHT * 0.5;
+                for (key, location) in row.keys.iter() {
+                    let is_selected = self.selection.contains(&KeyRef {
+                        curve: row.curve,
+                        key: *key,
+                    });
+                    if is_selected != selected {
+                        continue;
+                    }
+
+                    let mut location = *location;
+                    if let (true, Some(drag)) = (is_selected, self.drag.as_ref()) {
+                        location += drag.offset;
+                    }
+
+                    Self::draw_key(ctx, Vector2::new(self.local_to_view(location), y));
+                }
+            }
+            ctx.commit(
+                self.clip_bounds(),
+                if selected {
+                    self.selected_key_brush.clone()
+                } else {
+                    self.key_brush.clone()
+                },
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<DopeSheetMessage>() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    DopeSheetMessage::Sync(rows) => {
+                        self.rows = rows.clone();
+                        self.drag = None;
+                        // Keep selection of keys that are still exist.
+                        let rows = &self.rows;
+                        self.selection.retain(|selected| {
+                            rows.iter().any(|r| {
+                                r.curve == selected.curve
+                                    && r.keys.iter().any(|(k, _)| *k == selected.key)
+                            })
+                        });
+                    }
+                    DopeSheetMessage::Zoom(zoom) => {
+                        self.zoom = *zoom;
+                    }
+                    DopeSheetMessage::ViewPosition(position) => {
+                        self.view_position = *position;
+                    }
+                    _ => (),
+                }
+            }
+        } else if let Some(msg) = message.data::<WidgetMessage>() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::FromWidget
+            {
+                match msg {
+                    WidgetMessage::MouseDown { pos, button } => {
+                        let view_pos = self.screen_to_local(*pos);
+                        self.context_location = (
+                            self.row_at(view_pos.y).map(|r| r.curve),
+                            self.view_to_local(view_pos.x),
+                        );
+
+                        if *button == MouseButton::Left {
+                            let control = ui.keyboard_modifiers().control;
+                            if let Some(picked) = self.pick(*pos) {
+                                if control {
+                                    if !self.selection.remove(&picked) {
+                                        self.selection.insert(picked);
+                                    }
+                                } else if !self.selection.contains(&picked) {
+                                    self.selection.clear();
+                                    self.selection.insert(picked);
+                                }
+
+                                if self.selection.contains(&picked) {
+                                    ui.capture_mouse(self.handle);
+                                    self.drag = Some(Drag {
+                                        initial_location: self.context_location.1,
+                                        offset: 0.0,
+                                    });
+                                }
+                            } else if !control {
+                                self.selection.clear();
+                            }
+                        }
+                    }
+                    WidgetMessage::MouseMove { pos, .. } => {
+                        let location = self.view_to_local(self.screen_to_local(*pos).x);
+                        if let Some(drag) = self.drag.as_mut() {
+                            drag.offset = location - drag.initial_location;
+                        }
+                    }
+                    WidgetMessage::MouseUp { button, .. } => {
+                        if *button == MouseButton::Left {
+                            if let Some(drag) = self.drag.take() {
+                                ui.release_mouse_capture();
+
+                                if drag.offset != 0.0 {
+                                    ui.send_message(DopeSheetMessage::move_keys(
+                                        self.handle,
+                                        MessageDirection::FromWidget,
+                                        self.selection(),
+                                        drag.offset,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                    WidgetMessage::DoubleClick {
+                        button: MouseButton::Left,
+                    } => {
+                        if let (Some(curve), location) = self.context_location {
+                            if self.pick(ui.cursor_position()).is_none() {
+                                ui.send_message(DopeSheetMessage::add_key(
+                                    self.handle,
+                                    MessageDirection::FromWidget,
+                                    curve,
+                                    location,
+                                ));
+                            }
+                        }
+                    }
+                    WidgetMessage::KeyDown(KeyCode::Delete) => {
+                        if !self.selection.is_empty() {
+                            ui.send_message(DopeSheetMessage::remove_keys(
+                                self.handle,
+                                MessageDirection::FromWidget,
+                                self.selection(),
+                            ));
+                        }
+                    }
+                    WidgetMessage::KeyDown(KeyCode::C) => {
+                        if ui.keyboard_modifiers().control && !self.selection.is_empty() {
+                            ui.send_message(DopeSheetMessage::copy_keys(
+                                self.handle,
+                                MessageDirection::FromWidget,
+                                self.selection(),
+                            ));
+                        }
+                    }
+                    WidgetMessage::KeyDown(KeyCode::V) => {
+                        if ui.keyboard_modifiers().control {
+                            ui.send_message(DopeSheetMessage::paste_keys(
+                                self.handle,
+                                MessageDirection::FromWidget,
+                                self.context_location.1,
+                            ));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.context_menu.add_key {
+                if let (Some(curve), location) = self.context_location {
+                    ui.send_message(DopeSheetMessage::add_key(
+                        self.handle,
+                        MessageDirection::FromWidget,
+                        curve,
+                        location,
+                    ));
+                }
+            } else if message.destination() == self.context_menu.remove {
+                ui.send_message(DopeSheetMessage::remove_keys(
+                    self.handle,
+                    MessageDirection::FromWidget,
+                    self.selection(),
+                ));
+            } else if message.destination() == self.context_menu.copy {
+                ui.send_message(DopeSheetMessage::copy_keys(
+                    self.handle,
+                    MessageDirection::FromWidget,
+                    self.selection(),
+                ));
+            } else if message.destination() == self.context_menu.paste {
+                ui.send_message(DopeSheetMessage::paste_keys(
+                    self.handle,
+                    MessageDirection::FromWidget,
+                    self.context_location.1,
+                ));
+            }
+        }
+    }
+}
+
+pub struct DopeSheetBuilder {
+    widget_builder: WidgetBuilder,
+}
+
+fn make_menu_item(text: &str, ctx: &mut BuildContext) -> Handle<UiNode> {
+    MenuItemBuilder::new(WidgetBuilder::new())
+        .with_content(MenuItemContent::text(text))
+        .build(ctx)
+}
+
+impl DopeSheetBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self { widget_builder }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let add_key = make_menu_item("Add Key", ctx);
+        let remove = make_menu_item("Remove", ctx);
+        let copy = make_menu_item("Copy", ctx);
+        let paste = make_menu_item("Paste", ctx);
+        let context_menu = PopupBuilder::new(WidgetBuilder::new())
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(add_key)
+                        .with_child(remove)
+                        .with_child(copy)
+                        .with_child(paste),
+                )
+                .build(ctx),
+            )
+            .build(ctx);
+
+        let dope_sheet = DopeSheet {
+            widget: self
+                .widget_builder
+                .with_background(BRUSH_DARK)
+                .with_foreground(BRUSH_LIGHTER)
+                .with_context_menu(context_menu)
+                .with_preview_messages(true)
+                .build(),
+            rows: Default::default(),
+            zoom: 1.0,
+            view_position: 0.0,
+            selection: Default::default(),
+            drag: None,
+            context_location: (None, 0.0),
+            context_menu: ContextMenu {
+                add_key,
+                remove,
+                copy,
+                paste,
+            },
+            text: RefCell::new(
+                FormattedTextBuilder::new(ctx.default_font())
+                    .with_brush(Brush::Solid(Color::opaque(100, 100, 100)))
+                    .build(),
+            ),
+            key_brush: Brush::Solid(Color::opaque(140, 140, 140)),
+            selected_key_brush: Brush::Solid(Color::opaque(220, 220, 220)),
+            row_brush: Brush::Solid(Color::from_rgba(110, 110, 110, 30)),
+        };
+
+        ctx.add_node(UiNode::new(dope_sheet))
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     animation::{
-        command::ReplaceTrackCurveCommand,
+        command::ReplaceTrackCurvesCommand,
+        dopesheet::{DopeSheetBuilder, DopeSheetMessage, DopeSheetRow, KeyRef},
         ruler::{RulerBuilder, RulerMessage},
         selection::{AnimationSelection, SelectedEntity},
         thumb::{ThumbBuilder, ThumbMessage},
@@ -11,7 +12,13 @@ use crate::{
     send_sync_message, Message,
 };
 use fyrox::{
-    core::{algebra::Vector2, pool::Handle},
+    animation::Animation,
+    core::{
+        algebra::Vector2,
+        curve::{Curve, CurveKey, CurveKeyKind},
+        pool::Handle,
+        uuid::Uuid,
+    },
     engine::Engine,
     gui::{
         border::BorderBuilder,
@@ -28,6 +35,7 @@ use fyrox::{
 use std::sync::mpsc::Sender;
 
 mod command;
+mod dopesheet;
 mod ruler;
 pub mod selection;
 mod thumb;
@@ -42,6 +50,9 @@ pub struct AnimationEditor {
     pub window: Handle<UiNode>,
     track_list: TrackList,
     curve_editor: Handle<UiNode>,
+    dope_sheet: Handle<UiNode>,
+    // Copied keys with ids of curves they were copied from.
+    clipboard: Vec<(Uuid, CurveKey)>,
     toolbar: Toolbar,
     content: Handle<UiNode>,
     ruler: Handle<UiNode>,
@@ -74,9 +85,72 @@ fn fetch_selection(editor_selection: &Selection) -> AnimationSelection {
     }
 }
 
+fn find_curve(animation: &Animation, id: Uuid) -> Option<&Curve> {
+    animation.tracks().iter().find_map(|t| {
+        t.frames_container()
+            .curves_ref()
+            .iter()
+            .find(|c| c.id() == id)
+    })
+}
+
+fn key_index(curve: &Curve, id: Uuid) -> Option<usize> {
+    curve.keys().iter().position(|k| k.id == id)
+}
+
+// Returns a copy of a curve with the given id from the list of copies, the copy is created if
+// there's no such curve in the list yet.
+fn curve_copy<'a>(
+    curves: &'a mut Vec<Curve>,
+    animation: &Animation,
+    id: Uuid,
+) -> Option<&'a mut Curve> {
+    match curves.iter().position(|c| c.id() == id) {
+        Some(index) => curves.get_mut(index),
+        None => {
+            curves.push(find_curve(animation, id)?.clone());
+            curves.last_mut()
+        }
+    }
+}
+
+// Applies the function to a copy of every curve that is referenced by the given keys. Returns
+// modified copies of the curves.
+fn modify_curves<F>(animation: &Animation, keys: &[KeyRef], mut func: F) -> Vec<Curve>
+where
+    F: FnMut(&mut Curve, Uuid),
+{
+    let mut curves = Vec::new();
+    for key_ref in keys {
+        if let Some(curve) = curve_copy(&mut curves, animation, key_ref.curve) {
+            func(curve, key_ref.key);
+        }
+    }
+    curves
+}
+
+fn dope_sheet_rows(animation: &Animation) -> Vec<DopeSheetRow> {
+    let mut rows = Vec::new();
+    for track in animation.tracks() {
+        for (i, curve) in track.frames_container().curves_ref().iter().enumerate() {
+            rows.push(DopeSheetRow {
+                curve: curve.id(),
+                name: format!(
+                    "{} - {}",
+                    track.binding(),
+                    ["X", "Y", "Z", "W"].get(i).unwrap_or(&"_")
+                ),
+                keys: curve.keys().iter().map(|k| (k.id, k.location())).collect(),
+            });
+        }
+    }
+    rows
+}
+
 impl AnimationEditor {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let curve_editor;
+        let dope_sheet;
         let ruler;
         let thumb;
 
@@ -104,9 +178,16 @@ impl AnimationEditor {
                                             ruler
                                         })
                                         .with_child({
-                                            curve_editor = CurveEditorBuilder::new(
+                                            dope_sheet = DopeSheetBuilder::new(
                                                 WidgetBuilder::new().on_row(1),
                                             )
+                                            .build(ctx);
+                                            dope_sheet
+                                        })
+                                        .with_child({
+                                            curve_editor = CurveEditorBuilder::new(
+                                                WidgetBuilder::new().on_row(2),
+                                            )
                                             .with_show_x_values(false)
                                             .build(ctx);
                                             curve_editor
@@ -114,6 +195,7 @@ impl AnimationEditor {
                                 )
                                 .add_row(Row::strict(25.0))
                                 .add_row(Row::stretch())
+                                .add_row(Row::stretch())
                                 .add_column(Column::stretch())
                                 .build(ctx),
                             )
@@ -150,6 +232,8 @@ impl AnimationEditor {
             window,
             track_list,
             curve_editor,
+            dope_sheet,
+            clipboard: Default::default(),
             toolbar,
             content,
             ruler,
@@ -201,10 +285,10 @@ impl AnimationEditor {
                         match msg {
                             CurveEditorMessage::Sync(curve) => {
                                 sender
-                                    .send(Message::do_scene_command(ReplaceTrackCurveCommand {
+                                    .send(Message::do_scene_command(ReplaceTrackCurvesCommand {
                                         animation_player: selection.animation_player,
                                         animation: selection.animation,
-                                        curve: curve.clone(),
+                                        curves: vec![curve.clone()],
                                     }))
                                     .unwrap();
                             }
@@ -219,6 +303,11 @@ impl AnimationEditor {
                                     MessageDirection::ToWidget,
                                     position.x,
                                 ));
+                                ui.send_message(DopeSheetMessage::view_position(
+                                    self.dope_sheet,
+                                    MessageDirection::ToWidget,
+                                    position.x,
+                                ));
                             }
                             CurveEditorMessage::Zoom(zoom) => {
                                 ui.send_message(RulerMessage::zoom(
@@ -230,11 +319,35 @@ impl AnimationEditor {
                                     self.thumb,
                                     MessageDirection::ToWidget,
                                     zoom.x,
-                                ))
+                                ));
+                                ui.send_message(DopeSheetMessage::zoom(
+                                    self.dope_sheet,
+                                    MessageDirection::ToWidget,
+                                    zoom.x,
+                                ));
                             }
                             _ => (),
                         }
                     }
+                } else if let Some(msg) = message.data::<DopeSheetMessage>() {
+                    if message.destination() == self.dope_sheet
+                        && message.direction() == MessageDirection::FromWidget
+                    {
+                        if let Some(animation) =
+                            animation_player.animations().try_get(selection.animation)
+                        {
+                            let curves = self.handle_dope_sheet_message(msg, animation);
+                            if !curves.is_empty() {
+                                sender
+                                    .send(Message::do_scene_command(ReplaceTrackCurvesCommand {
+                                        animation_player: selection.animation_player,
+                                        animation: selection.animation,
+                                        curves,
+                                    }))
+                                    .unwrap();
+                            }
+                        }
+                    }
                 } else if let Some(RulerMessage::Value(value)) = message.data() {
                     if message.destination() == self.ruler
                         && message.direction() == MessageDirection::FromWidget
@@ -346,6 +459,74 @@ impl AnimationEditor {
         }
     }
 
+    // Converts a request of the dope sheet into a set of modified curves.
+    fn handle_dope_sheet_message(
+        &mut self,
+        msg: &DopeSheetMessage,
+        animation: &Animation,
+    ) -> Vec<Curve> {
+        match msg {
+            DopeSheetMessage::MoveKeys { keys, offset } => {
+                modify_curves(animation, keys, |curve, key| {
+                    if let Some(index) = key_index(curve, key) {
+                        let location = curve.keys()[index].location() + *offset;
+                        curve.move_key(index, location);
+                    }
+                })
+            }
+            DopeSheetMessage::RemoveKeys(keys) => modify_curves(animation, keys, |curve, key| {
+                if let Some(index) = key_index(curve, key) {
+                    curve.remove_key(index);
+                }
+            }),
+            DopeSheetMessage::AddKey { curve, location } => match find_curve(animation, *curve) {
+                Some(curve) => {
+                    let mut curve = curve.clone();
+                    let value = curve.value_at(*location);
+                    curve.add_key(CurveKey::new(*location, value, CurveKeyKind::Linear));
+                    vec![curve]
+                }
+                None => vec![],
+            },
+            DopeSheetMessage::CopyKeys(keys) => {
+                self.clipboard = keys
+                    .iter()
+                    .filter_map(|key_ref| {
+                        find_curve(animation, key_ref.curve).and_then(|curve| {
+                            curve
+                                .keys()
+                                .iter()
+                                .find(|k| k.id == key_ref.key)
+                                .map(|k| (key_ref.curve, k.clone()))
+                        })
+                    })
+                    .collect();
+                vec![]
+            }
+            DopeSheetMessage::PasteKeys(location) => {
+                // Keys are pasted so the earliest one will be at the given location.
+                let origin = self
+                    .clipboard
+                    .iter()
+                    .map(|(_, k)| k.location())
+                    .fold(f32::MAX, f32::min);
+
+                let mut curves = Vec::new();
+                for (curve_id, key) in self.clipboard.iter() {
+                    if let Some(curve) = curve_copy(&mut curves, animation, *curve_id) {
+                        curve.add_key(CurveKey::new(
+                            key.location() - origin + *location,
+                            key.value,
+                            key.kind.clone(),
+                        ));
+                    }
+                }
+                curves
+            }
+            _ => vec![],
+        }
+    }
+
     fn enter_preview_mode(
         &mut self,
         animation_targets: Vec<Handle<Node>>,
@@ -456,6 +637,15 @@ impl AnimationEditor {
                 self.track_list
                     .sync_to_model(animation, &scene.graph, &mut engine.user_interface);
 
+                send_sync_message(
+                    &engine.user_interface,
+                    DopeSheetMessage::sync(
+                        self.dope_sheet,
+                        MessageDirection::ToWidget,
+                        dope_sheet_rows(animation),
+                    ),
+                );
+
                 // TODO: Support multi-selection.
                 if let Some(SelectedEntity::Curve(selected_curve_id)) = selection.entities.first() {
                     if let Some(selected_curve) = animation.tracks().iter().find_map(|t| {
@@ -481,6 +671,11 @@ impl AnimationEditor {
         if !is_animation_selected || !is_animation_player_selected {
             self.track_list.clear(ui);
 
+            send_sync_message(
+                ui,
+                DopeSheetMessage::sync(self.dope_sheet, MessageDirection::ToWidget, vec![]),
+            );
+
             ui.send_message(CurveEditorMessage::sync(
                 self.curve_editor,
                 MessageDirection::ToWidget,
@@ -1,5 +1,6 @@
 use crate::gui::AssetItemMessage;
 use crate::load_image;
+use crate::vcs::FileStatus;
 use fyrox::core::color::Color;
 use fyrox::core::pool::Handle;
 use fyrox::engine::resource_manager::ResourceManager;
@@ -8,9 +9,12 @@ use fyrox::gui::draw::{CommandTexture, Draw, DrawingContext};
 use fyrox::gui::grid::{Column, GridBuilder, Row};
 use fyrox::gui::image::ImageBuilder;
 use fyrox::gui::message::{MessageDirection, UiMessage};
-use fyrox::gui::text::TextBuilder;
+use fyrox::gui::text::{TextBuilder, TextMessage};
 use fyrox::gui::widget::{Widget, WidgetBuilder, WidgetMessage};
-use fyrox::gui::{BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface};
+use fyrox::gui::{
+    BuildContext, Control, DragPayload, HorizontalAlignment, Thickness, UiNode, UserInterface,
+    VerticalAlignment,
+};
 use fyrox::utils::into_gui_texture;
 use std::any::{Any, TypeId};
 use std::ops::{Deref, DerefMut};
@@ -23,6 +27,7 @@ pub struct AssetItem {
     pub path: PathBuf,
     pub kind: AssetKind,
     preview: Handle<UiNode>,
+    vcs_badge: Handle<UiNode>,
     selected: bool,
 }
 
@@ -115,9 +120,27 @@ impl Control for AssetItem {
     }
 }
 
+impl AssetItem {
+    /// Shows a badge with the version control status of the asset.
+    pub fn set_vcs_status(&self, ui: &UserInterface, status: FileStatus) {
+        let (text, color) = status.badge().unwrap_or(("", Color::TRANSPARENT));
+        ui.send_message(TextMessage::text(
+            self.vcs_badge,
+            MessageDirection::ToWidget,
+            text.to_owned(),
+        ));
+        ui.send_message(WidgetMessage::foreground(
+            self.vcs_badge,
+            MessageDirection::ToWidget,
+            Brush::Solid(color),
+        ));
+    }
+}
+
 pub struct AssetItemBuilder {
     widget_builder: WidgetBuilder,
     path: Option<PathBuf>,
+    vcs_status: FileStatus,
 }
 
 impl AssetItemBuilder {
@@ -125,9 +148,15 @@ impl AssetItemBuilder {
         Self {
             widget_builder,
             path: None,
+            vcs_status: Default::default(),
         }
     }
 
+    pub fn with_vcs_status(mut self, status: FileStatus) -> Self {
+        self.vcs_status = status;
+        self
+    }
+
     pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
         self.path = Some(path.as_ref().to_owned());
         self
@@ -175,17 +204,30 @@ impl AssetItemBuilder {
         .with_opt_texture(texture)
         .build(ctx);
 
+        let (badge_text, badge_color) = self.vcs_status.badge().unwrap_or(("", Color::TRANSPARENT));
+        let vcs_badge = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(2.0))
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_foreground(Brush::Solid(badge_color)),
+        )
+        .with_text(badge_text)
+        .build(ctx);
+
         let item = AssetItem {
             widget: self
                 .widget_builder
                 .with_margin(Thickness::uniform(1.0))
                 .with_allow_drag(true)
+                .with_drag_payload(DragPayload::new(path.clone()))
                 .with_foreground(Brush::Solid(Color::opaque(50, 50, 50)))
                 .with_child(
                     GridBuilder::new(
                         WidgetBuilder::new()
                             .with_width(64.0)
                             .with_child(preview)
+                            .with_child(vcs_badge)
                             .with_child(
                                 TextBuilder::new(
                                     WidgetBuilder::new()
@@ -206,6 +248,7 @@ impl AssetItemBuilder {
             path,
             kind,
             preview,
+            vcs_badge,
             selected: false,
         };
         ctx.add_node(UiNode::new(item))
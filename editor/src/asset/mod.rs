@@ -8,10 +8,14 @@ use crate::{
             AssetInspector,
         },
         item::AssetItemBuilder,
+        move_dialog::MoveAssetDialog,
+        operations::duplicate_asset,
+        usages::AssetUsagesWindow,
     },
     gui::AssetItemMessage,
     preview::PreviewPanel,
     utils::window_content,
+    vcs::{FileStatus, Vcs},
     AssetItem, AssetKind, GameEngine, Message, Mode,
 };
 use fyrox::{
@@ -48,6 +52,9 @@ use std::{
 
 mod inspector;
 pub mod item;
+mod move_dialog;
+mod operations;
+mod usages;
 
 struct ContextMenu {
     menu: Handle<UiNode>,
@@ -56,6 +63,10 @@ struct ContextMenu {
     copy_file_name: Handle<UiNode>,
     show_in_explorer: Handle<UiNode>,
     delete: Handle<UiNode>,
+    revert: Handle<UiNode>,
+    move_rename: Handle<UiNode>,
+    duplicate: Handle<UiNode>,
+    find_usages: Handle<UiNode>,
     placement_target: Handle<UiNode>,
 }
 
@@ -86,10 +97,14 @@ fn put_path_to_clipboard(engine: &mut Engine, path: &OsStr) {
 impl ContextMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let delete;
+        let revert;
         let show_in_explorer;
         let open;
         let copy_path;
         let copy_file_name;
+        let move_rename;
+        let duplicate;
+        let find_usages;
         let menu = PopupBuilder::new(WidgetBuilder::new())
             .with_content(
                 StackPanelBuilder::new(
@@ -112,12 +127,36 @@ impl ContextMenu {
                                 .build(ctx);
                             copy_file_name
                         })
+                        .with_child({
+                            move_rename = MenuItemBuilder::new(WidgetBuilder::new())
+                                .with_content(MenuItemContent::text("Move/Rename..."))
+                                .build(ctx);
+                            move_rename
+                        })
+                        .with_child({
+                            duplicate = MenuItemBuilder::new(WidgetBuilder::new())
+                                .with_content(MenuItemContent::text("Duplicate"))
+                                .build(ctx);
+                            duplicate
+                        })
+                        .with_child({
+                            find_usages = MenuItemBuilder::new(WidgetBuilder::new())
+                                .with_content(MenuItemContent::text("Find Usages"))
+                                .build(ctx);
+                            find_usages
+                        })
                         .with_child({
                             delete = MenuItemBuilder::new(WidgetBuilder::new())
                                 .with_content(MenuItemContent::text("Delete"))
                                 .build(ctx);
                             delete
                         })
+                        .with_child({
+                            revert = MenuItemBuilder::new(WidgetBuilder::new())
+                                .with_content(MenuItemContent::text("Revert"))
+                                .build(ctx);
+                            revert
+                        })
                         .with_child({
                             show_in_explorer = MenuItemBuilder::new(WidgetBuilder::new())
                                 .with_content(MenuItemContent::text("Show In Explorer"))
@@ -134,13 +173,22 @@ impl ContextMenu {
             open,
             copy_path,
             delete,
+            revert,
+            move_rename,
+            duplicate,
+            find_usages,
             show_in_explorer,
             placement_target: Default::default(),
             copy_file_name,
         }
     }
 
-    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
         if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
             if message.destination() == self.menu {
                 self.placement_target = *target;
@@ -153,6 +201,10 @@ impl ContextMenu {
             {
                 if message.destination() == self.delete {
                     Log::verify(std::fs::remove_file(&item.path))
+                } else if message.destination() == self.revert {
+                    sender
+                        .send(Message::RevertAsset(item.path.clone()))
+                        .unwrap();
                 } else if message.destination() == self.show_in_explorer {
                     if let Ok(canonical_path) = item.path.canonicalize() {
                         show_in_explorer(&canonical_path)
@@ -167,6 +219,13 @@ impl ContextMenu {
                     if let Some(file_name) = item.path.clone().file_name() {
                         put_path_to_clipboard(engine, file_name)
                     }
+                } else if message.destination() == self.duplicate {
+                    match duplicate_asset(&item.path) {
+                        Ok(copy_path) => {
+                            sender.send(Message::ShowInAssetBrowser(copy_path)).unwrap()
+                        }
+                        Err(err) => Log::err(err),
+                    }
                 }
             }
         }
@@ -184,6 +243,8 @@ pub struct AssetBrowser {
     item_to_select: Option<PathBuf>,
     inspector: AssetInspector,
     context_menu: ContextMenu,
+    move_dialog: MoveAssetDialog,
+    usages_window: AssetUsagesWindow,
 }
 
 impl AssetBrowser {
@@ -284,6 +345,8 @@ impl AssetBrowser {
             .build(ctx);
 
         let context_menu = ContextMenu::new(ctx);
+        let move_dialog = MoveAssetDialog::new(ctx);
+        let usages_window = AssetUsagesWindow::new(ctx);
 
         Self {
             window,
@@ -296,6 +359,8 @@ impl AssetBrowser {
             item_to_select: None,
             inspector,
             context_menu,
+            move_dialog,
+            usages_window,
         }
     }
 
@@ -318,15 +383,34 @@ impl AssetBrowser {
         message: &UiMessage,
         engine: &mut GameEngine,
         sender: Sender<Message>,
+        vcs: &Vcs,
     ) {
         scope_profile!();
 
         self.inspector.handle_ui_message(message, engine);
         self.preview.handle_message(message, engine);
-        self.context_menu.handle_ui_message(message, engine);
+        self.context_menu
+            .handle_ui_message(message, engine, &sender);
+        self.move_dialog
+            .handle_ui_message(message, engine, &sender, vcs);
+        self.usages_window.handle_ui_message(message, &sender);
 
         let ui = &mut engine.user_interface;
 
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if let Some(path) = ui
+                .try_get_node(self.context_menu.placement_target)
+                .and_then(|n| n.cast::<AssetItem>())
+                .map(|item| item.path.clone())
+            {
+                if message.destination() == self.context_menu.move_rename {
+                    self.move_dialog.open(ui, &path);
+                } else if message.destination() == self.context_menu.find_usages {
+                    self.usages_window.open(ui, &path);
+                }
+            }
+        }
+
         if let Some(AssetItemMessage::Select(true)) = message.data::<AssetItemMessage>() {
             // Deselect other items.
             for &item in self.items.iter().filter(|i| **i != message.destination()) {
@@ -341,10 +425,15 @@ impl AssetBrowser {
                 .node(message.destination())
                 .cast::<AssetItem>()
                 .expect("Must be AssetItem");
+            let mut properties = format!("Path: {:?}", item.path);
+            let status = vcs.status(&item.path);
+            if status != FileStatus::Unmodified {
+                properties += &format!(" ({})", status.name());
+            }
             ui.send_message(TextMessage::text(
                 self.selected_properties,
                 MessageDirection::ToWidget,
-                format!("Path: {:?}", item.path),
+                properties,
             ));
 
             match item.kind {
@@ -416,6 +505,7 @@ impl AssetBrowser {
                                     WidgetBuilder::new().with_context_menu(self.context_menu.menu),
                                 )
                                 .with_path(entry_path.clone())
+                                .with_vcs_status(vcs.status(&entry_path))
                                 .build(&mut ui.build_ctx(), engine.resource_manager.clone());
 
                                 self.items.push(asset_item);
@@ -463,6 +553,15 @@ impl AssetBrowser {
         self.item_to_select = Some(path);
     }
 
+    /// Updates version control badges of the items in the current folder.
+    pub fn sync_vcs_status(&self, ui: &UserInterface, vcs: &Vcs) {
+        for &item in self.items.iter() {
+            if let Some(item) = ui.try_get_node(item).and_then(|n| n.cast::<AssetItem>()) {
+                item.set_vcs_status(ui, vcs.status(&item.path));
+            }
+        }
+    }
+
     pub fn update(&mut self, engine: &mut GameEngine) {
         self.preview.update(engine)
     }
@@ -0,0 +1,222 @@
+use crate::{asset::operations::move_asset, vcs::Vcs, GameEngine, Message};
+use fyrox::{
+    core::{color::Color, pool::Handle, replace_slashes},
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    utils::log::Log,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+
+/// A dialog that moves or renames an asset. Every reference to the asset in the project is fixed
+/// after the move.
+pub struct MoveAssetDialog {
+    pub window: Handle<UiNode>,
+    path_box: Handle<UiNode>,
+    error_text: Handle<UiNode>,
+    ok: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    src: PathBuf,
+    dest: PathBuf,
+}
+
+impl MoveAssetDialog {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let path_box;
+        let error_text;
+        let ok;
+        let cancel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(130.0))
+            .with_title(WindowTitle::text("Move/Rename Asset"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_child({
+                            path_box = TextBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_height(25.0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                            path_box
+                        })
+                        .with_child({
+                            error_text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0))
+                                    .with_foreground(Brush::Solid(Color::RED)),
+                            )
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx);
+                            error_text
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        ok = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_height(25.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("OK")
+                                        .build(ctx);
+                                        ok
+                                    })
+                                    .with_child({
+                                        cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_height(25.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Cancel")
+                                        .build(ctx);
+                                        cancel
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            path_box,
+            error_text,
+            ok,
+            cancel,
+            src: Default::default(),
+            dest: Default::default(),
+        }
+    }
+
+    pub fn open(&mut self, ui: &UserInterface, src: &Path) {
+        self.src = src.to_owned();
+        self.dest = src.to_owned();
+
+        ui.send_message(TextMessage::text(
+            self.path_box,
+            MessageDirection::ToWidget,
+            src.to_string_lossy().to_string(),
+        ));
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        self.validate(ui);
+    }
+
+    fn validate(&self, ui: &UserInterface) {
+        let error = if self.dest == self.src {
+            Default::default()
+        } else if self.dest.file_name().is_none() {
+            "Path must contain file name!".to_owned()
+        } else if self.dest.extension() != self.src.extension() {
+            "Extension of the asset must not be changed!".to_owned()
+        } else if self.dest.exists() {
+            format!("{} already exists!", self.dest.display())
+        } else {
+            Default::default()
+        };
+
+        ui.send_message(WidgetMessage::enabled(
+            self.ok,
+            MessageDirection::ToWidget,
+            error.is_empty() && self.dest != self.src,
+        ));
+        ui.send_message(TextMessage::text(
+            self.error_text,
+            MessageDirection::ToWidget,
+            error,
+        ));
+    }
+
+    fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &GameEngine,
+        sender: &Sender<Message>,
+        vcs: &Vcs,
+    ) {
+        let ui = &engine.user_interface;
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.ok {
+                match move_asset(&self.src, &self.dest, &engine.resource_manager, vcs) {
+                    Ok(fixed_files) => {
+                        Log::info(format!(
+                            "{} was moved to {}, references were fixed in {} file(s).",
+                            self.src.display(),
+                            self.dest.display(),
+                            fixed_files.len()
+                        ));
+
+                        sender
+                            .send(Message::AssetMoved {
+                                old_path: self.src.clone(),
+                                new_path: self.dest.clone(),
+                            })
+                            .unwrap();
+
+                        self.close(ui);
+                    }
+                    Err(err) => {
+                        ui.send_message(TextMessage::text(
+                            self.error_text,
+                            MessageDirection::ToWidget,
+                            err,
+                        ));
+                    }
+                }
+            } else if message.destination() == self.cancel {
+                self.close(ui);
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.path_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.dest = replace_slashes(text.trim());
+                self.validate(ui);
+            }
+        }
+    }
+}
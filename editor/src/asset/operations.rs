@@ -0,0 +1,217 @@
+//! Asset management operations that keep references to assets valid. Scenes and other assets,
+//! that are saved using [`Visitor`], reference assets by their paths, so moving or renaming an
+//! asset requires to rewrite the paths in every such file of the project.
+
+use crate::vcs::Vcs;
+use fyrox::{
+    core::{
+        append_extension, futures::executor::block_on, make_relative_path, replace_slashes,
+        visitor::Visitor,
+    },
+    engine::resource_manager::ResourceManager,
+    utils::log::Log,
+    walkdir::{DirEntry, WalkDir},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Extensions of the files that could reference other assets.
+const REFERENCING_FILE_EXTENSIONS: [&str; 2] = ["rgs", "absm"];
+
+fn path_to_string(path: &Path) -> String {
+    replace_slashes(path).to_string_lossy().to_string()
+}
+
+fn is_ignored_dir(entry: &DirEntry) -> bool {
+    // Build artifacts and hidden folders (version control, etc.) never contain assets.
+    entry.depth() > 0
+        && entry.file_type().is_dir()
+        && entry
+            .file_name()
+            .to_str()
+            .map_or(false, |name| name == "target" || name.starts_with('.'))
+}
+
+/// Returns every file in the working directory, that could contain references to other assets.
+pub fn referencing_files() -> Vec<PathBuf> {
+    WalkDir::new(".")
+        .into_iter()
+        .filter_entry(|entry| !is_ignored_dir(entry))
+        .flatten()
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry.path().extension().map_or(false, |ext| {
+                    REFERENCING_FILE_EXTENSIONS
+                        .iter()
+                        .any(|referencing| ext.eq_ignore_ascii_case(referencing))
+                })
+        })
+        .filter_map(|entry| make_relative_path(entry.path()).ok())
+        .collect()
+}
+
+/// Returns every file of the project, that references the given asset.
+pub fn find_usages(asset: &Path) -> Vec<PathBuf> {
+    let asset_path = path_to_string(asset);
+    referencing_files()
+        .into_iter()
+        .filter(|file| {
+            file.as_path() != asset
+                && block_on(Visitor::load_binary(file))
+                    .map_or(false, |visitor| visitor.contains_string(&asset_path))
+        })
+        .collect()
+}
+
+fn unique_copy_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut index = 1;
+    loop {
+        let suffix = if index == 1 {
+            "_copy".to_owned()
+        } else {
+            format!("_copy{}", index)
+        };
+        let copy_path = path.with_file_name(format!("{}{}{}", stem, suffix, extension));
+        if !copy_path.exists() {
+            return copy_path;
+        }
+        index += 1;
+    }
+}
+
+/// Copies the given asset (with its import options) next to it and returns a path to the copy.
+pub fn duplicate_asset(path: &Path) -> Result<PathBuf, String> {
+    let copy_path = unique_copy_path(path);
+
+    fs::copy(path, &copy_path).map_err(|e| {
+        format!(
+            "Unable to copy {} to {}. Reason: {}",
+            path.display(),
+            copy_path.display(),
+            e
+        )
+    })?;
+
+    let options_path = append_extension(path, "options");
+    if options_path.exists() {
+        Log::verify(fs::copy(
+            options_path,
+            append_extension(&copy_path, "options"),
+        ));
+    }
+
+    Ok(copy_path)
+}
+
+/// Returns pairs of old and new paths of every file, that will be moved when `src` is moved to
+/// `dest`. If `src` is a directory, every file inside it (at any depth) is moved.
+fn moved_files(src: &Path, dest: &Path) -> Vec<(PathBuf, PathBuf)> {
+    if src.is_dir() {
+        WalkDir::new(src)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| {
+                let relative = entry.path().strip_prefix(src).ok()?;
+                Some((entry.path().to_owned(), dest.join(relative)))
+            })
+            .collect()
+    } else {
+        vec![(src.to_owned(), dest.to_owned())]
+    }
+}
+
+/// Moves (or renames) the given asset (with its import options) and rewrites the references to it
+/// in every file of the project, loaded resources get the new path too. If the asset is a
+/// directory, references to every file inside it are rewritten. Returns a list of the files with
+/// fixed references.
+pub fn move_asset(
+    src: &Path,
+    dest: &Path,
+    resource_manager: &ResourceManager,
+    vcs: &Vcs,
+) -> Result<Vec<PathBuf>, String> {
+    if dest.exists() {
+        return Err(format!("{} already exists!", dest.display()));
+    }
+
+    if let Some(parent) = dest.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent).map_err(|e| {
+                format!(
+                    "Unable to create {} folder. Reason: {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    let moved_files = moved_files(src, dest);
+
+    fs::rename(src, dest).map_err(|e| {
+        format!(
+            "Unable to move {} to {}. Reason: {}",
+            src.display(),
+            dest.display(),
+            e
+        )
+    })?;
+
+    let options_path = append_extension(src, "options");
+    if options_path.exists() {
+        Log::verify(fs::rename(options_path, append_extension(dest, "options")));
+    }
+
+    let replacements = moved_files
+        .iter()
+        .map(|(old, new)| (path_to_string(old), path_to_string(new)))
+        .collect::<Vec<_>>();
+    let new_path = path_to_string(dest);
+
+    let mut fixed_files = Vec::new();
+    for file in referencing_files() {
+        // Files of unknown formats are skipped silently.
+        if let Ok(mut visitor) = block_on(Visitor::load_binary(&file)) {
+            let replaced = replacements
+                .iter()
+                .map(|(old, new)| visitor.replace_strings(old, new))
+                .sum::<usize>();
+            if replaced > 0 {
+                if !vcs.ensure_writable(&file) {
+                    Log::err(format!(
+                        "Unable to fix references to {} in {}, because the file is read-only.",
+                        new_path,
+                        file.display()
+                    ));
+                } else if let Err(e) = visitor.save_binary(&file) {
+                    Log::err(format!(
+                        "Unable to fix references to {} in {}. Reason: {:?}",
+                        new_path,
+                        file.display(),
+                        e
+                    ));
+                } else {
+                    fixed_files.push(file);
+                }
+            }
+        }
+    }
+
+    for (old, new) in moved_files {
+        resource_manager.set_resource_path(replace_slashes(old), replace_slashes(new));
+    }
+
+    Ok(fixed_files)
+}
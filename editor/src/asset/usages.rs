@@ -0,0 +1,137 @@
+use crate::{asset::operations::find_usages, Message};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        border::BorderBuilder,
+        decorator::DecoratorBuilder,
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+
+/// A window that lists every file of the project, that references an asset. Selected file is
+/// shown in the asset browser.
+pub struct AssetUsagesWindow {
+    pub window: Handle<UiNode>,
+    summary: Handle<UiNode>,
+    list: Handle<UiNode>,
+    usages: Vec<PathBuf>,
+}
+
+impl AssetUsagesWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let summary;
+        let list;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(300.0))
+            .with_title(WindowTitle::text("Asset Usages"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            summary = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx);
+                            summary
+                        })
+                        .with_child({
+                            list = ListViewBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            list
+                        }),
+                )
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            summary,
+            list,
+            usages: Default::default(),
+        }
+    }
+
+    /// Scans the project for the files that reference the given asset and shows them.
+    pub fn open(&mut self, ui: &mut UserInterface, asset: &Path) {
+        self.usages = find_usages(asset);
+
+        let summary = if self.usages.is_empty() {
+            format!("{} is not used anywhere.", asset.display())
+        } else {
+            format!(
+                "{} is used by {} file(s):",
+                asset.display(),
+                self.usages.len()
+            )
+        };
+        ui.send_message(TextMessage::text(
+            self.summary,
+            MessageDirection::ToWidget,
+            summary,
+        ));
+
+        let ctx = &mut ui.build_ctx();
+        let items = self
+            .usages
+            .iter()
+            .map(|usage| {
+                DecoratorBuilder::new(BorderBuilder::new(
+                    WidgetBuilder::new().with_height(22.0).with_child(
+                        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(usage.to_string_lossy())
+                            .build(ctx),
+                    ),
+                ))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+        ui.send_message(ListViewMessage::items(
+            self.list,
+            MessageDirection::ToWidget,
+            items,
+        ));
+
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, sender: &Sender<Message>) {
+        if let Some(ListViewMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(usage) = self.usages.get(*index) {
+                    sender
+                        .send(Message::ShowInAssetBrowser(usage.clone()))
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
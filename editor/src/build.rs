@@ -14,10 +14,13 @@ use fyrox::{
         BuildContext, Thickness, UiNode, UserInterface, BRUSH_DARKEST,
     },
     gui::{HorizontalAlignment, Orientation},
+    utils::log::Log,
 };
 use std::{
-    io::{BufRead, BufReader},
-    process::ChildStdout,
+    fs,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
     sync::{
         atomic::{AtomicBool, Ordering},
         mpsc::Sender,
@@ -105,27 +108,51 @@ impl BuildWindow {
         }
     }
 
-    pub fn listen(&mut self, mut stdout: ChildStdout, ui: &UserInterface) {
-        ui.send_message(WindowMessage::open_modal(
-            self.window,
-            MessageDirection::ToWidget,
-            true,
-        ));
-
+    fn read_stream<R: Read + Send + 'static>(&self, mut stream: R) {
         let log = self.log.clone();
-        self.active.store(true, Ordering::SeqCst);
         let reader_active = self.active.clone();
         let log_changed = self.changed.clone();
         std::thread::spawn(move || {
             while reader_active.load(Ordering::SeqCst) {
-                for line in BufReader::new(&mut stdout).lines().take(10).flatten() {
-                    log.lock().push_str(&line);
+                for line in BufReader::new(&mut stream).lines().take(10).flatten() {
+                    let mut log = log.lock();
+                    log.push_str(&line);
+                    log.push('\n');
                     log_changed.store(true, Ordering::SeqCst);
                 }
             }
         });
     }
 
+    /// Opens the window and starts streaming output of the given process into it. Both stdout
+    /// and stderr of the process must be piped.
+    pub fn listen(&mut self, process: &mut Child, title: &str, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text(title),
+        ));
+
+        self.active.store(true, Ordering::SeqCst);
+        if let Some(stdout) = process.stdout.take() {
+            self.read_stream(stdout);
+        }
+        // Cargo writes its progress into stderr.
+        if let Some(stderr) = process.stderr.take() {
+            self.read_stream(stderr);
+        }
+    }
+
+    /// Returns the output that was captured so far.
+    pub fn output(&self) -> String {
+        self.log.lock().clone()
+    }
+
     pub fn reset(&mut self, ui: &UserInterface) {
         self.active.store(false, Ordering::SeqCst);
         self.changed.store(false, Ordering::SeqCst);
@@ -161,9 +188,223 @@ impl BuildWindow {
     ) {
         if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.stop {
-                sender.send(Message::SwitchToEditMode).unwrap();
+                sender.send(Message::CancelBuild).unwrap();
                 self.reset(ui);
             }
         }
     }
 }
+
+/// A platform for which the game could be packaged.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TargetProfile {
+    Windows,
+    Linux,
+    Wasm,
+}
+
+impl TargetProfile {
+    pub const ALL: [TargetProfile; 3] = [
+        TargetProfile::Windows,
+        TargetProfile::Linux,
+        TargetProfile::Wasm,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TargetProfile::Windows => "Windows",
+            TargetProfile::Linux => "Linux",
+            TargetProfile::Wasm => "WebAssembly",
+        }
+    }
+
+    /// Returns a folder (relative to the working directory) where distributable files will be put.
+    pub fn dist_dir(self) -> PathBuf {
+        let folder = match self {
+            TargetProfile::Windows => "windows",
+            TargetProfile::Linux => "linux",
+            TargetProfile::Wasm => "wasm",
+        };
+        Path::new("dist").join(folder)
+    }
+
+    fn triple(self) -> &'static str {
+        match self {
+            TargetProfile::Windows => "x86_64-pc-windows-msvc",
+            TargetProfile::Linux => "x86_64-unknown-linux-gnu",
+            TargetProfile::Wasm => "wasm32-unknown-unknown",
+        }
+    }
+
+    fn build_command(self) -> Command {
+        match self {
+            TargetProfile::Windows | TargetProfile::Linux => {
+                let mut command = Command::new("cargo");
+                command
+                    .arg("build")
+                    .arg("--package")
+                    .arg("executor")
+                    .arg("--release")
+                    .arg("--target")
+                    .arg(self.triple());
+                command
+            }
+            TargetProfile::Wasm => {
+                let mut command = Command::new("wasm-pack");
+                command
+                    .current_dir("executor-wasm")
+                    .arg("build")
+                    .arg("--target")
+                    .arg("web")
+                    .arg("--release");
+                command
+            }
+        }
+    }
+
+    /// Copies build artifacts and assets of the game to the distribution folder.
+    fn collect(self, dist_dir: &Path) -> io::Result<()> {
+        if dist_dir.exists() {
+            fs::remove_dir_all(dist_dir)?;
+        }
+        fs::create_dir_all(dist_dir)?;
+
+        let release_dir = Path::new("target").join(self.triple()).join("release");
+        match self {
+            TargetProfile::Windows => {
+                fs::copy(release_dir.join("executor.exe"), dist_dir.join("game.exe"))?;
+            }
+            TargetProfile::Linux => {
+                fs::copy(release_dir.join("executor"), dist_dir.join("game"))?;
+            }
+            TargetProfile::Wasm => {
+                let executor_dir = Path::new("executor-wasm");
+                copy_dir(&executor_dir.join("pkg"), &dist_dir.join("pkg"))?;
+                for file in ["index.html", "main.js", "styles.css"] {
+                    fs::copy(executor_dir.join(file), dist_dir.join(file))?;
+                }
+            }
+        }
+
+        // There is no asset packing yet, so the assets are copied as is.
+        copy_dir(Path::new("data"), &dist_dir.join("data"))
+    }
+}
+
+fn copy_dir(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &dst_path)?;
+        } else {
+            fs::copy(&path, &dst_path)?;
+        }
+    }
+    Ok(())
+}
+
+struct PackageJob {
+    target: TargetProfile,
+    process: Child,
+}
+
+/// Builds the game in release mode for a target platform and produces a distributable folder
+/// with the executable and the assets of the game.
+#[derive(Default)]
+pub struct Packager {
+    job: Option<PackageJob>,
+}
+
+impl Packager {
+    pub fn is_active(&self) -> bool {
+        self.job.is_some()
+    }
+
+    pub fn start(
+        &mut self,
+        target: TargetProfile,
+        build_window: &mut BuildWindow,
+        ui: &UserInterface,
+    ) {
+        if self.is_active() {
+            Log::err("Another package is being built at the moment!");
+            return;
+        }
+
+        let mut command = target.build_command();
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        match command.spawn() {
+            Ok(mut process) => {
+                build_window.listen(
+                    &mut process,
+                    &format!("Packaging the Game for {}...", target.name()),
+                    ui,
+                );
+
+                self.job = Some(PackageJob { target, process });
+            }
+            Err(e) => Log::err(format!(
+                "Failed to start packaging for {}: {:?}",
+                target.name(),
+                e
+            )),
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        if let Some(mut job) = self.job.take() {
+            Log::verify(job.process.kill());
+            Log::info(format!(
+                "Packaging for {} was cancelled.",
+                job.target.name()
+            ));
+        }
+    }
+
+    pub fn update(&mut self, build_window: &mut BuildWindow, ui: &UserInterface) {
+        let job = match self.job.as_mut() {
+            Some(job) => job,
+            None => return,
+        };
+
+        build_window.update(ui);
+
+        match job.process.try_wait() {
+            Ok(Some(status)) => {
+                let target = job.target;
+                self.job = None;
+
+                if status.success() {
+                    let dist_dir = target.dist_dir();
+                    match target.collect(&dist_dir) {
+                        Ok(_) => Log::info(format!(
+                            "The game was packaged for {} successfully. Output folder: {}",
+                            target.name(),
+                            dist_dir.display()
+                        )),
+                        Err(e) => Log::err(format!(
+                            "Failed to collect package files for {}: {:?}",
+                            target.name(),
+                            e
+                        )),
+                    }
+                } else {
+                    Log::err(format!(
+                        "Failed to build the game for {}: {}\n{}",
+                        target.name(),
+                        status,
+                        build_window.output()
+                    ));
+                }
+
+                build_window.reset(ui);
+            }
+            Ok(None) => {}
+            Err(err) => Log::err(format!("Failed to wait for package process: {:?}", err)),
+        }
+    }
+}
@@ -164,7 +164,7 @@ impl CameraController {
         let camera = graph[self.camera].as_camera_mut();
 
         match *camera.projection_mut() {
-            Projection::Perspective(_) => {
+            Projection::Perspective(_) | Projection::OffAxis(_) => {
                 let look = camera.global_transform().look();
                 graph[self.pivot]
                     .local_transform_mut()
@@ -286,7 +286,7 @@ impl CameraController {
         let camera = graph[self.camera].as_camera_mut();
 
         match camera.projection_value() {
-            Projection::Perspective(_) => {
+            Projection::Perspective(_) | Projection::OffAxis(_) => {
                 let global_transform = camera.global_transform();
                 let look = global_transform.look();
                 let side = global_transform.side();
@@ -1,49 +1,17 @@
-use fyrox::core::reflect::{Reflect, ResolvePath};
+use fyrox::{
+    core::reflect::{set_field_by_path, Reflect},
+    utils::log::Log,
+};
 
 pub fn set_entity_field(
     entity: &mut dyn Reflect,
     path: &str,
     value: Box<dyn Reflect>,
 ) -> Result<Box<dyn Reflect>, Box<dyn Reflect>> {
-    let mut components = fyrox::core::reflect::path_to_components(path);
-    if let Some(fyrox::core::reflect::Component::Field(field)) = components.pop() {
-        let mut parent_path = String::new();
-        for component in components.into_iter() {
-            match component {
-                fyrox::core::reflect::Component::Field(s) => {
-                    if !parent_path.is_empty() {
-                        parent_path.push('.');
-                    }
-                    parent_path += s;
-                }
-                fyrox::core::reflect::Component::Index(s) => {
-                    parent_path.push('[');
-                    parent_path += s;
-                    parent_path.push(']');
-                }
-            }
-        }
-
-        let parent_entity = if parent_path.is_empty() {
-            entity
-        } else {
-            match entity.resolve_path_mut(&parent_path) {
-                Err(e) => {
-                    fyrox::utils::log::Log::err(format!(
-                        "There is no such parent property {}! Reason: {:?}",
-                        parent_path, e
-                    ));
-
-                    return Err(value);
-                }
-                Ok(property) => property,
-            }
-        };
-
-        parent_entity.set_field(field, value)
-    } else {
-        Err(value)
-    }
+    set_field_by_path(entity, path, value).map_err(|value| {
+        Log::err(format!("Unable to set property {}!", path));
+        value
+    })
 }
 
 #[macro_export]
@@ -1,4 +1,4 @@
-use crate::{GameEngine, Message};
+use crate::{project::ProjectWizard, GameEngine, Message};
 use fyrox::gui::text::TextMessage;
 use fyrox::{
     core::{
@@ -37,11 +37,16 @@ struct HistoryEntry {
 
 pub const HISTORY_PATH: &str = "history.bin";
 
+/// A scene that is generated with new projects.
+const DEFAULT_SCENE_PATH: &str = "data/scene.rgs";
+
 pub struct Configurator {
     pub window: Handle<UiNode>,
     work_dir_browser: Handle<UiNode>,
     select_work_dir: Handle<UiNode>,
     ok: Handle<UiNode>,
+    new_project: Handle<UiNode>,
+    project_wizard: ProjectWizard,
     sender: Sender<Message>,
     work_dir: PathBuf,
     tb_work_dir: Handle<UiNode>,
@@ -73,6 +78,7 @@ impl Configurator {
     pub fn new(sender: Sender<Message>, ctx: &mut BuildContext) -> Self {
         let select_work_dir;
         let ok;
+        let new_project;
         let tb_work_dir;
 
         let current_path = env::current_dir().unwrap();
@@ -103,14 +109,15 @@ impl Configurator {
 
         let message = "Please select the working directory of \
         your current project. In most cases it will be the root folder \
-        of your project";
+        of your project. Alternatively you can pick one of the recent \
+        projects or create a new one from a template.";
 
         let lv_history;
         let window = WindowBuilder::new(
             WidgetBuilder::new()
                 .with_width(370.0)
-                .with_height(250.0)
-                .with_min_size(Vector2::new(370.0, 250.0)),
+                .with_height(270.0)
+                .with_min_size(Vector2::new(370.0, 270.0)),
         )
         .with_title(WindowTitle::Text("Configure Editor".into()))
         .open(false)
@@ -182,7 +189,7 @@ impl Configurator {
                                 .with_margin(Thickness::uniform(5.0))
                                 .on_row(2),
                         )
-                        .with_text("Previous Configurations")
+                        .with_text("Recent Projects")
                         .with_horizontal_text_alignment(HorizontalAlignment::Center)
                         .build(ctx),
                     )
@@ -203,6 +210,17 @@ impl Configurator {
                                 .on_row(4)
                                 .with_horizontal_alignment(HorizontalAlignment::Right)
                                 .with_vertical_alignment(VerticalAlignment::Bottom)
+                                .with_child({
+                                    new_project = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_width(100.0)
+                                            .with_height(25.0)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("New Project...")
+                                    .build(ctx);
+                                    new_project
+                                })
                                 .with_child({
                                     ok = ButtonBuilder::new(
                                         WidgetBuilder::new()
@@ -235,6 +253,8 @@ impl Configurator {
             work_dir_browser: folder_browser,
             select_work_dir,
             ok,
+            new_project,
+            project_wizard: ProjectWizard::new(ctx),
             sender,
             tb_work_dir,
             work_dir: current_path,
@@ -252,9 +272,58 @@ impl Configurator {
         ));
     }
 
+    fn configure(&mut self, engine: &mut GameEngine) {
+        self.sender
+            .send(Message::Configure {
+                working_directory: self.work_dir.clone(),
+            })
+            .unwrap();
+
+        let new_entry = HistoryEntry {
+            work_dir: self.work_dir.clone(),
+        };
+        if !self.history.iter().any(|e| e == &new_entry) {
+            self.history.push(new_entry);
+
+            let widget = make_history_entry_widget(
+                &mut engine.user_interface.build_ctx(),
+                self.history.last().unwrap(),
+            );
+
+            engine
+                .user_interface
+                .send_message(ListViewMessage::add_item(
+                    self.lv_history,
+                    MessageDirection::ToWidget,
+                    widget,
+                ));
+        }
+
+        engine.user_interface.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
     pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
         scope_profile!();
 
+        if let Some(project_path) = self.project_wizard.handle_ui_message(message, engine) {
+            if let Ok(work_dir) = project_path.canonicalize() {
+                self.work_dir = work_dir;
+                self.configure(engine);
+
+                // Open default scene of the new project (if any), the working directory is
+                // already changed at this moment.
+                if self.work_dir.join(DEFAULT_SCENE_PATH).exists() {
+                    self.sender
+                        .send(Message::LoadScene(DEFAULT_SCENE_PATH.into()))
+                        .unwrap();
+                }
+            }
+            return;
+        }
+
         if let Some(WindowMessage::Close) = message.data::<WindowMessage>() {
             if message.destination() == self.window {
                 // Save history for next editor runs.
@@ -296,36 +365,9 @@ impl Configurator {
             }
         } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.ok {
-                self.sender
-                    .send(Message::Configure {
-                        working_directory: self.work_dir.clone(),
-                    })
-                    .unwrap();
-
-                let new_entry = HistoryEntry {
-                    work_dir: self.work_dir.clone(),
-                };
-                if !self.history.iter().any(|e| e == &new_entry) {
-                    self.history.push(new_entry);
-
-                    let widget = make_history_entry_widget(
-                        &mut engine.user_interface.build_ctx(),
-                        self.history.last().unwrap(),
-                    );
-
-                    engine
-                        .user_interface
-                        .send_message(ListViewMessage::add_item(
-                            self.lv_history,
-                            MessageDirection::ToWidget,
-                            widget,
-                        ));
-                }
-
-                engine.user_interface.send_message(WindowMessage::close(
-                    self.window,
-                    MessageDirection::ToWidget,
-                ));
+                self.configure(engine);
+            } else if message.destination() == self.new_project {
+                self.project_wizard.open(engine);
             } else if message.destination() == self.select_work_dir {
                 engine
                     .user_interface
@@ -0,0 +1,147 @@
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+    },
+    utils::log::Log,
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// A folder (relative to the initial working directory of the editor) where crash reports are
+/// written.
+pub const CRASH_REPORTS_DIR: &str = "crash_reports";
+
+fn open_folder(path: &Path) {
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let program = "xdg-open";
+
+    if let Err(err) = Command::new(program).arg(path).spawn() {
+        Log::err(format!(
+            "Failed to open crash report folder {}. Reason: {:?}",
+            path.display(),
+            err
+        ));
+    }
+}
+
+/// A window, that is shown on startup if the editor has crashed on previous run.
+pub struct CrashReportWindow {
+    window: Handle<UiNode>,
+    open_folder: Handle<UiNode>,
+    close: Handle<UiNode>,
+    report_path: PathBuf,
+}
+
+impl CrashReportWindow {
+    pub fn new(report_path: PathBuf, ctx: &mut BuildContext) -> Self {
+        let open_folder;
+        let close;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(160.0))
+            .can_minimize(false)
+            .can_close(false)
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new().with_margin(Thickness::uniform(4.0)),
+                            )
+                            .with_text(format!(
+                                "The editor has unexpectedly closed last time. A crash report \
+                                was saved to {}\n\nPlease attach the content of the folder to a \
+                                bug report, it will help to fix the problem.",
+                                report_path.display()
+                            ))
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .on_row(1)
+                                    .with_child({
+                                        open_folder = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(140.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Open Report Folder")
+                                        .build(ctx);
+                                        open_folder
+                                    })
+                                    .with_child({
+                                        close = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Close")
+                                        .build(ctx);
+                                        close
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(28.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .with_title(WindowTitle::text("Crash Report"))
+            .build(ctx);
+
+        Self {
+            window,
+            open_folder,
+            close,
+            report_path,
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    /// Returns `true` if the window was closed and destroyed.
+    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &UserInterface) -> bool {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.open_folder {
+                open_folder(&self.report_path);
+            } else if message.destination() == self.close {
+                ui.send_message(WindowMessage::close(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+                ui.send_message(WidgetMessage::remove(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+                return true;
+            }
+        }
+        false
+    }
+}
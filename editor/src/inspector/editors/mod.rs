@@ -18,8 +18,8 @@ use fyrox::{
     animation::{
         machine::{
             node::{play::TimeSlice, BasePoseNode},
-            BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
-            PlayAnimation, PoseWeight,
+            AddAnimation, BlendAnimations, BlendAnimationsByIndex, BlendPose, BlendSpace,
+            BlendSpacePoint, BoneMask, IndexedBlendInput, Machine, PlayAnimation, PoseWeight,
         },
         AnimationContainer,
     },
@@ -48,10 +48,11 @@ use fyrox::{
     scene::{
         base::{
             Base, LevelOfDetail, LodControlledObject, LodGroup, Mobility, Property, PropertyValue,
+            ScriptRecord,
         },
         camera::{
             ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection, Projection,
-            SkyBox,
+            SkyBox, SortingLayerView,
         },
         collider::{
             BallShape, BitMask, CapsuleShape, ColliderShape, ConeShape, ConvexPolyhedronShape,
@@ -66,25 +67,36 @@ use fyrox::{
             BaseLight,
         },
         mesh::{
+            socket::MeshSocket,
             surface::{Surface, SurfaceSharedData},
-            RenderPath,
+            BlendShape, RenderPath,
         },
         node::{Node, NodeHandle},
         particle_system::{
             emitter::{
-                base::BaseEmitter, cuboid::CuboidEmitter, cylinder::CylinderEmitter,
-                sphere::SphereEmitter, Emitter,
+                base::{BaseEmitter, EmitterBurst},
+                cuboid::CuboidEmitter,
+                cylinder::CylinderEmitter,
+                sphere::SphereEmitter,
+                Emitter,
             },
             EmitterWrapper,
         },
+        post_processing::{
+            BloomEffect, ChromaticAberrationEffect, CustomEffect, FilmGrainEffect,
+            MotionBlurEffect, PostEffect, PostEffectEntry, PostProcessStack, VignetteEffect,
+        },
         rigidbody::RigidBodyType,
         sound::{
             self,
             effect::{BaseEffect, Effect, EffectInput, ReverbEffect},
+            listener::HrtfMode,
             Biquad, DistanceModel, SoundBufferResource, SoundBufferResourceLoadError,
-            SoundBufferState, Status,
+            SoundBufferState, Status, StealPolicy,
         },
+        spline::{SplineKind, SplinePoint},
         terrain::Layer,
+        text::{TextAlignment, TextMode},
         transform::Transform,
     },
 };
@@ -138,14 +150,20 @@ pub fn make_property_editors_container(
     container.register_inheritable_vec_collection::<NodeHandle>();
 
     container.register_inheritable_vec_collection::<Surface>();
+    container.register_inheritable_vec_collection::<BlendShape>();
+    container.register_inheritable_vec_collection::<MeshSocket>();
     container.register_inheritable_vec_collection::<Layer>();
     container.register_inheritable_vec_collection::<EmitterWrapper>();
+    container.register_inheritable_vec_collection::<EmitterBurst>();
     container.register_inheritable_vec_collection::<LevelOfDetail>();
+    container.register_inheritable_vec_collection::<SplinePoint>();
     container.register_inheritable_vec_collection::<ErasedHandle>();
     container.register_inheritable_vec_collection::<Property>();
     container.register_inheritable_vec_collection::<LodControlledObject>();
     container.register_inheritable_vec_collection::<GeometrySource>();
     container.register_inheritable_vec_collection::<EffectInput>();
+    container.register_inheritable_vec_collection::<String>();
+    container.register_inheritable_vec_collection::<ScriptRecord>();
 
     container.insert(make_status_enum_editor_definition());
 
@@ -218,8 +236,13 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<RevoluteJoint>();
     container.register_inheritable_inspectable::<PrismaticJoint>();
     container.register_inheritable_inspectable::<dim2::joint::PrismaticJoint>();
+    container.register_inheritable_inspectable::<JointMotor>();
 
     container.register_inheritable_inspectable::<Base>();
+    container.register_inheritable_inspectable::<BlendShape>();
+    container.register_inheritable_inspectable::<MeshSocket>();
+    container.register_inheritable_inspectable::<SplinePoint>();
+    container.register_inheritable_inspectable::<ScriptRecord>();
     container.register_inheritable_inspectable::<BaseEffect>();
     container.register_inheritable_inspectable::<BaseLight>();
 
@@ -229,6 +252,7 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<ReverbEffect>();
     container.register_inheritable_inspectable::<Biquad>();
     container.register_inheritable_inspectable::<BaseEmitter>();
+    container.register_inheritable_inspectable::<EmitterBurst>();
     container.register_inheritable_inspectable::<SphereEmitter>();
     container.register_inheritable_inspectable::<CylinderEmitter>();
     container.register_inheritable_inspectable::<CuboidEmitter>();
@@ -240,8 +264,22 @@ pub fn make_property_editors_container(
     container.register_inheritable_option::<ColorGradingLut>();
     container.register_inheritable_option::<Biquad>();
     container.register_inheritable_option::<SkyBox>();
+    container.register_inheritable_option::<OrthographicProjection>();
 
     container.register_inheritable_inspectable::<SkyBox>();
+    container.register_inheritable_inspectable::<SortingLayerView>();
+    container.register_inheritable_vec_collection::<SortingLayerView>();
+
+    container.register_inheritable_inspectable::<PostProcessStack>();
+    container.register_inheritable_inspectable::<PostEffectEntry>();
+    container.register_inheritable_vec_collection::<PostEffectEntry>();
+    container.register_inheritable_enum::<PostEffect, _>();
+    container.register_inheritable_inspectable::<BloomEffect>();
+    container.register_inheritable_inspectable::<VignetteEffect>();
+    container.register_inheritable_inspectable::<ChromaticAberrationEffect>();
+    container.register_inheritable_inspectable::<FilmGrainEffect>();
+    container.register_inheritable_inspectable::<MotionBlurEffect>();
+    container.register_inheritable_inspectable::<CustomEffect>();
 
     container.register_inheritable_enum::<dim2::collider::ColliderShape, _>();
     container.register_inheritable_enum::<CoefficientCombineRule, _>();
@@ -258,8 +296,13 @@ pub fn make_property_editors_container(
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
+    container.register_inheritable_enum::<StealPolicy, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
+    container.register_inheritable_enum::<HrtfMode, _>();
     container.register_inheritable_enum::<RenderPath, _>();
+    container.register_inheritable_enum::<SplineKind, _>();
+    container.register_inheritable_enum::<TextMode, _>();
+    container.register_inheritable_enum::<TextAlignment, _>();
 
     container.insert(ScriptPropertyEditorDefinition {});
     container.insert(BitFieldPropertyEditorDefinition::<BitMask>::new());
@@ -288,18 +331,25 @@ pub fn make_property_editors_container(
     container.insert(InheritablePropertyEditorDefinition::<Status>::new());
 
     container.insert(InspectablePropertyEditorDefinition::<BasePoseNode>::new());
+    container.insert(InspectablePropertyEditorDefinition::<BoneMask>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<String>::new());
     container.insert(InspectablePropertyEditorDefinition::<IndexedBlendInput>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<IndexedBlendInput>::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendPose>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<BlendPose>::new());
     container.insert(EnumPropertyEditorDefinition::<PoseWeight>::new());
     container.insert(InspectablePropertyEditorDefinition::<PoseWeight>::new());
+    container.insert(EnumPropertyEditorDefinition::<PoseWeight>::new_optional());
     container.insert(InspectablePropertyEditorDefinition::<TimeSlice>::new());
     container.insert(EnumPropertyEditorDefinition::<TimeSlice>::new_optional());
     container.insert(InspectablePropertyEditorDefinition::<TimeSlice>::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendAnimationsByIndex>::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendAnimations>::new());
     container.insert(InspectablePropertyEditorDefinition::<PlayAnimation>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AddAnimation>::new());
+    container.insert(InspectablePropertyEditorDefinition::<BlendSpacePoint>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<BlendSpacePoint>::new());
+    container.insert(InspectablePropertyEditorDefinition::<BlendSpace>::new());
 
     container.insert(AnimationPropertyEditorDefinition);
 
@@ -1,4 +1,4 @@
-use crate::{asset::item::AssetItem, inspector::EditorEnvironment};
+use crate::inspector::EditorEnvironment;
 use fyrox::{
     core::{algebra::Vector2, make_relative_path, pool::Handle},
     engine::resource_manager::ResourceManager,
@@ -14,7 +14,7 @@ use fyrox::{
         },
         message::{MessageDirection, UiMessage},
         widget::{Widget, WidgetBuilder, WidgetMessage},
-        BuildContext, Control, Thickness, UiNode, UserInterface,
+        BuildContext, Control, DropFilter, Thickness, UiNode, UserInterface,
     },
     resource::texture::Texture,
     utils::into_gui_texture,
@@ -23,6 +23,7 @@ use std::{
     any::{Any, TypeId},
     fmt::{Debug, Formatter},
     ops::{Deref, DerefMut},
+    path::PathBuf,
 };
 
 #[derive(Clone)]
@@ -74,10 +75,10 @@ impl Control for TextureEditor {
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
-        if let Some(WidgetMessage::Drop(dropped)) = message.data::<WidgetMessage>() {
+        if let Some(WidgetMessage::Drop(_)) = message.data::<WidgetMessage>() {
             if message.destination() == self.image {
-                if let Some(item) = ui.node(*dropped).cast::<AssetItem>() {
-                    if let Ok(relative_path) = make_relative_path(&item.path) {
+                if let Some(path) = ui.drag_payload().and_then(|p| p.get::<PathBuf>()) {
+                    if let Ok(relative_path) = make_relative_path(path) {
                         ui.send_message(TextureEditorMessage::texture(
                             self.handle(),
                             MessageDirection::ToWidget,
@@ -134,7 +135,8 @@ impl TextureEditorBuilder {
                 image = ImageBuilder::new(
                     WidgetBuilder::new()
                         .with_margin(Thickness::uniform(1.0))
-                        .with_allow_drop(true),
+                        .with_allow_drop(true)
+                        .with_drop_filter(DropFilter::of_type::<PathBuf>()),
                 )
                 .with_opt_texture(self.texture.map(into_gui_texture))
                 .build(ctx);
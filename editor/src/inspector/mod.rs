@@ -82,6 +82,7 @@ pub struct Inspector {
     pub property_editors: Rc<PropertyEditorDefinitionContainer>,
     pub(crate) window: Handle<UiNode>,
     inspector: Handle<UiNode>,
+    scroll_viewer: Handle<UiNode>,
     // Hack. This flag tells whether the inspector should sync with model or not.
     // There is only one situation when it has to be `false` - when inspector has
     // got new context - in this case we don't need to sync with model, because
@@ -140,6 +141,7 @@ impl Inspector {
 
         let warning_text;
         let inspector;
+        let scroll_viewer;
         let window = WindowBuilder::new(WidgetBuilder::new())
             .with_title(WindowTitle::text("Inspector"))
             .with_content(
@@ -158,15 +160,17 @@ impl Inspector {
                             .build(ctx);
                             warning_text
                         })
-                        .with_child(
-                            ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
-                                .with_content({
-                                    inspector =
-                                        InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
-                                    inspector
-                                })
-                                .build(ctx),
-                        ),
+                        .with_child({
+                            scroll_viewer =
+                                ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
+                                    .with_content({
+                                        inspector =
+                                            InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
+                                        inspector
+                                    })
+                                    .build(ctx);
+                            scroll_viewer
+                        }),
                 )
                 .add_row(Row::auto())
                 .add_row(Row::stretch())
@@ -178,6 +182,7 @@ impl Inspector {
         Self {
             window,
             inspector,
+            scroll_viewer,
             property_editors,
             needs_sync: true,
             node_property_changed_handler: SceneNodePropertyChangedHandler,
@@ -396,6 +401,19 @@ impl Inspector {
         ));
     }
 
+    /// Hides content of off-screen rows of the inspector, so large objects (or objects with large
+    /// collections) do not slow down the layout and rendering of the editor.
+    pub fn post_update(&self, ui: &UserInterface) {
+        if let Some(inspector) = ui
+            .try_get_node(self.inspector)
+            .and_then(|n| n.cast::<fyrox::gui::inspector::Inspector>())
+        {
+            inspector
+                .context()
+                .virtualize_rows(ui, ui.node(self.scroll_viewer).clip_bounds());
+        }
+    }
+
     pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
         ui.send_message(WidgetMessage::enabled(
             window_content(self.window, ui),
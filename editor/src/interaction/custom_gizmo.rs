@@ -0,0 +1,297 @@
+//! Viewport gizmos for custom node and script types. Games register them using
+//! [`crate::Editor::add_gizmo`] to get proper editing tools for their components (spawn volumes,
+//! patrol paths, etc.).
+
+use crate::{
+    command::universal::set_entity_field,
+    scene::{
+        commands::{CommandGroup, SceneCommand, SetPropertyCommand},
+        EditorScene, Selection,
+    },
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{algebra::Vector2, math::ray::Ray, pool::Handle, reflect::Reflect},
+    gui::message::KeyboardModifiers,
+    scene::{debug::SceneDrawingContext, graph::Graph, node::Node},
+    utils::log::Log,
+};
+use std::sync::mpsc::Sender;
+
+/// A gizmo for nodes of some specific type (or nodes with some specific script). The editor draws
+/// it for every selected node the gizmo is applicable to, and routes mouse input of the scene
+/// preview to it before the active interaction mode.
+pub trait CustomGizmo: 'static {
+    /// Returns `true` if the gizmo is able to edit the given node. Usually it checks the type of
+    /// the node or the type of its script.
+    fn is_applicable(&self, node: &Node) -> bool;
+
+    /// Draws the gizmo for the given node using debug drawing context of the scene. It is called
+    /// every frame for every selected node the gizmo is applicable to.
+    fn draw(&self, node: Handle<Node>, graph: &Graph, drawing_context: &mut SceneDrawingContext);
+
+    /// Called when the left mouse button is pressed in the scene preview. Should return `true` if
+    /// a handle of the gizmo was hit. In this case the gizmo captures the mouse until the button is
+    /// released, and the active interaction mode does not receive the input.
+    fn on_mouse_down(&mut self, _ctx: &mut GizmoContext) -> bool {
+        false
+    }
+
+    /// Called when the mouse is moved while the gizmo captures the mouse.
+    fn on_mouse_move(&mut self, _ctx: &mut GizmoContext) {}
+
+    /// Called when the left mouse button is released while the gizmo captures the mouse. Every
+    /// property changed during the interaction is committed as a single undoable command right
+    /// after this method.
+    fn on_mouse_up(&mut self, _ctx: &mut GizmoContext) {}
+}
+
+struct PropertyChange {
+    path: String,
+    original_value: Box<dyn Reflect>,
+}
+
+/// Context of a mouse interaction with a custom gizmo.
+pub struct GizmoContext<'a> {
+    /// A node the gizmo is working with.
+    pub node: Handle<Node>,
+    pub graph: &'a mut Graph,
+    /// A camera of the scene preview.
+    pub camera: Handle<Node>,
+    /// Position of the mouse cursor relative to the scene preview.
+    pub mouse_position: Vector2<f32>,
+    /// A ray from the camera through the mouse cursor.
+    pub ray: Ray,
+    /// State of keyboard modifiers at the moment of the event.
+    pub modifiers: KeyboardModifiers,
+    changes: &'a mut Vec<PropertyChange>,
+}
+
+impl<'a> GizmoContext<'a> {
+    /// Sets a property of the node. The path is the same as the inspector uses (for example
+    /// `radius` or `points[2]`). The value is applied immediately to show the changes while
+    /// dragging, when the interaction is finished, every changed property is reverted and set
+    /// again using undoable commands. Returns `false` if there is no such property or its type
+    /// is different.
+    pub fn set_property<T: Reflect>(&mut self, path: &str, value: T) -> bool {
+        match set_entity_field(
+            self.graph[self.node].as_reflect_mut(),
+            path,
+            Box::new(value),
+        ) {
+            Ok(old_value) => {
+                // Only the value before the interaction is needed to make the command.
+                if !self.changes.iter().any(|change| change.path == path) {
+                    self.changes.push(PropertyChange {
+                        path: path.to_owned(),
+                        original_value: old_value,
+                    });
+                }
+                true
+            }
+            Err(_) => {
+                Log::err(format!(
+                    "Gizmo is unable to set {} property! No such property or incompatible types!",
+                    path
+                ));
+                false
+            }
+        }
+    }
+}
+
+struct ActiveGizmo {
+    gizmo: usize,
+    node: Handle<Node>,
+    changes: Vec<PropertyChange>,
+}
+
+/// A set of registered custom gizmos.
+pub struct CustomGizmos {
+    gizmos: Vec<Box<dyn CustomGizmo>>,
+    active: Option<ActiveGizmo>,
+    sender: Sender<Message>,
+}
+
+impl CustomGizmos {
+    pub fn new(sender: Sender<Message>) -> Self {
+        Self {
+            gizmos: Default::default(),
+            active: None,
+            sender,
+        }
+    }
+
+    pub fn add<G: CustomGizmo>(&mut self, gizmo: G) {
+        self.gizmos.push(Box::new(gizmo));
+    }
+
+    pub fn draw(&self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            let scene = &mut engine.scenes[editor_scene.scene];
+            for &node in selection.nodes() {
+                if let Some(node_ref) = scene.graph.try_get(node) {
+                    for gizmo in self.gizmos.iter() {
+                        if gizmo.is_applicable(node_ref) {
+                            gizmo.draw(node, &scene.graph, &mut scene.drawing_context);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn with_context<F>(
+        changes: &mut Vec<PropertyChange>,
+        node: Handle<Node>,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        mouse_position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        func: F,
+    ) -> bool
+    where
+        F: FnOnce(&mut GizmoContext) -> bool,
+    {
+        let modifiers = engine.user_interface.keyboard_modifiers();
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        let camera = editor_scene.camera_controller.camera;
+        let ray = graph[camera]
+            .as_camera()
+            .make_ray(mouse_position, frame_size);
+        func(&mut GizmoContext {
+            node,
+            graph,
+            camera,
+            mouse_position,
+            ray,
+            modifiers,
+            changes,
+        })
+    }
+
+    /// Returns `true` if some gizmo has captured the mouse.
+    pub fn on_mouse_down(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        mouse_position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+    ) -> bool {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            for &node in selection.nodes() {
+                for (index, gizmo) in self.gizmos.iter_mut().enumerate() {
+                    let applicable = engine.scenes[editor_scene.scene]
+                        .graph
+                        .try_get(node)
+                        .map_or(false, |node_ref| gizmo.is_applicable(node_ref));
+
+                    let mut changes = Vec::new();
+                    if applicable
+                        && Self::with_context(
+                            &mut changes,
+                            node,
+                            editor_scene,
+                            engine,
+                            mouse_position,
+                            frame_size,
+                            |ctx| gizmo.on_mouse_down(ctx),
+                        )
+                    {
+                        self.active = Some(ActiveGizmo {
+                            gizmo: index,
+                            node,
+                            changes,
+                        });
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if the mouse is captured by a gizmo.
+    pub fn on_mouse_move(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        mouse_position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+    ) -> bool {
+        if let Some(active) = self.active.as_mut() {
+            let gizmo = &mut self.gizmos[active.gizmo];
+            Self::with_context(
+                &mut active.changes,
+                active.node,
+                editor_scene,
+                engine,
+                mouse_position,
+                frame_size,
+                |ctx| {
+                    gizmo.on_mouse_move(ctx);
+                    true
+                },
+            )
+        } else {
+            false
+        }
+    }
+
+    /// Finishes the interaction with the active gizmo and commits the changes it made. Returns
+    /// `true` if the mouse was captured by a gizmo.
+    pub fn on_mouse_up(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        mouse_position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+    ) -> bool {
+        if let Some(mut active) = self.active.take() {
+            let gizmo = &mut self.gizmos[active.gizmo];
+            Self::with_context(
+                &mut active.changes,
+                active.node,
+                editor_scene,
+                engine,
+                mouse_position,
+                frame_size,
+                |ctx| {
+                    gizmo.on_mouse_up(ctx);
+                    true
+                },
+            );
+
+            // Revert the changes and do them again using commands to make them undoable.
+            let graph = &mut engine.scenes[editor_scene.scene].graph;
+            let mut commands = Vec::new();
+            for change in active.changes.into_iter().rev() {
+                if let Some(node) = graph.try_get_mut(active.node) {
+                    if let Ok(new_value) =
+                        set_entity_field(node.as_reflect_mut(), &change.path, change.original_value)
+                    {
+                        commands.push(SceneCommand::new(SetPropertyCommand::new(
+                            active.node,
+                            change.path,
+                            new_value,
+                        )));
+                    }
+                }
+            }
+            commands.reverse();
+
+            if !commands.is_empty() {
+                let command = if commands.len() == 1 {
+                    commands.pop().unwrap()
+                } else {
+                    SceneCommand::new(CommandGroup::from(commands))
+                };
+                self.sender.send(Message::DoSceneCommand(command)).unwrap();
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -10,6 +10,7 @@ use fyrox::{
 };
 use std::any::Any;
 
+pub mod custom_gizmo;
 pub mod gizmo;
 pub mod move_mode;
 pub mod navmesh;
@@ -17,6 +18,7 @@ pub mod plane;
 pub mod rotate_mode;
 pub mod scale_mode;
 pub mod select_mode;
+pub mod spline;
 pub mod terrain;
 
 pub trait BaseInteractionMode {
@@ -124,6 +126,12 @@ pub fn calculate_gizmo_distance_scaling(
                     .metric_distance(&graph[camera].global_position())
         }
         Projection::Orthographic(ortho) => 0.4 * ortho.vertical_size,
+        Projection::OffAxis(proj) => {
+            distance_scale_factor(proj.fov())
+                * graph[gizmo_origin]
+                    .global_position()
+                    .metric_distance(&graph[camera].global_position())
+        }
     };
 
     Vector3::new(s, s, s)
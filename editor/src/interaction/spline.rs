@@ -0,0 +1,187 @@
+//! Viewport tool for [`Spline`] nodes. Control points can be dragged with the mouse, Ctrl+Click
+//! inserts a new point after the selected one (or at the end of the spline) and Alt+Click removes
+//! a point.
+
+use crate::interaction::custom_gizmo::{CustomGizmo, GizmoContext};
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        color::Color,
+        math::plane::Plane,
+        pool::Handle,
+    },
+    scene::{
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        node::Node,
+        spline::{Spline, SplineKind},
+    },
+};
+
+/// Radius of control point spheres.
+const POINT_RADIUS: f32 = 0.1;
+/// Picking radius of control points relative to the distance to the camera, it keeps points
+/// clickable when the camera is far away.
+const PICK_RADIUS_SCALE: f32 = 0.02;
+
+fn transform_point(transform: &Matrix4<f32>, point: Vector3<f32>) -> Vector3<f32> {
+    transform.transform_point(&Point3::from(point)).coords
+}
+
+#[derive(Default)]
+pub struct SplineGizmo {
+    /// A spline and an index of its last picked control point.
+    selected: Option<(Handle<Node>, usize)>,
+    /// A plane in which the picked control point is moving, exists only while dragging.
+    drag_plane: Option<Plane>,
+}
+
+impl SplineGizmo {
+    fn selected_index(&self, node: Handle<Node>) -> Option<usize> {
+        match self.selected {
+            Some((selected_node, index)) if selected_node == node => Some(index),
+            _ => None,
+        }
+    }
+
+    fn pick_point(ctx: &GizmoContext, spline: &Spline) -> Option<usize> {
+        let transform = spline.global_transform();
+        let camera_position = ctx.graph[ctx.camera].global_position();
+
+        let mut closest = None;
+        let mut closest_distance = f32::MAX;
+        for (index, point) in spline.points().iter().enumerate() {
+            let position = transform_point(&transform, point.position);
+            let distance = position.metric_distance(&camera_position);
+            let radius = POINT_RADIUS.max(distance * PICK_RADIUS_SCALE);
+            if ctx.ray.is_intersect_sphere(&position, radius) && distance < closest_distance {
+                closest = Some(index);
+                closest_distance = distance;
+            }
+        }
+        closest
+    }
+}
+
+impl CustomGizmo for SplineGizmo {
+    fn is_applicable(&self, node: &Node) -> bool {
+        node.cast::<Spline>().is_some()
+    }
+
+    fn draw(&self, node: Handle<Node>, graph: &Graph, drawing_context: &mut SceneDrawingContext) {
+        let spline = match graph[node].cast::<Spline>() {
+            Some(spline) => spline,
+            None => return,
+        };
+        let transform = spline.global_transform();
+
+        let polyline = spline.polyline(16);
+        for pair in polyline.windows(2) {
+            drawing_context.add_line(Line {
+                begin: transform_point(&transform, pair[0]),
+                end: transform_point(&transform, pair[1]),
+                color: Color::ORANGE,
+            });
+        }
+
+        let selected = self.selected_index(node);
+        for (index, point) in spline.points().iter().enumerate() {
+            let position = transform_point(&transform, point.position);
+            let color = if selected == Some(index) {
+                Color::RED
+            } else {
+                Color::WHITE
+            };
+            drawing_context.draw_sphere(position, 8, 8, POINT_RADIUS, color);
+
+            if spline.kind() == SplineKind::Bezier {
+                drawing_context.add_line(Line {
+                    begin: transform_point(&transform, point.position - point.tangent),
+                    end: transform_point(&transform, point.position + point.tangent),
+                    color: Color::opaque(0, 162, 232),
+                });
+            }
+        }
+    }
+
+    fn on_mouse_down(&mut self, ctx: &mut GizmoContext) -> bool {
+        let spline = match ctx.graph[ctx.node].cast::<Spline>() {
+            Some(spline) => spline,
+            None => return false,
+        };
+        let transform = spline.global_transform();
+        let mut points = spline.points().to_vec();
+        let picked = Self::pick_point(ctx, spline);
+
+        if ctx.modifiers.alt {
+            return if let Some(index) = picked {
+                points.remove(index);
+                ctx.set_property("points", points);
+                self.selected = None;
+                true
+            } else {
+                false
+            };
+        }
+
+        if ctx.modifiers.control {
+            // Insert a new point after the selected one in the horizontal plane that goes
+            // through it, and start dragging it right away.
+            let index = self
+                .selected_index(ctx.node)
+                .filter(|index| *index < points.len())
+                .map_or(points.len(), |index| index + 1);
+            let anchor = if index > 0 {
+                transform_point(&transform, points[index - 1].position)
+            } else {
+                spline.global_position()
+            };
+            let plane = Plane::from_normal_and_point(&Vector3::y(), &anchor).unwrap_or_default();
+            let world_position = ctx.ray.plane_intersection_point(&plane).unwrap_or(anchor);
+            let local_position =
+                transform_point(&transform.try_inverse().unwrap_or_default(), world_position);
+
+            let mut point = points
+                .get(index.saturating_sub(1))
+                .cloned()
+                .unwrap_or_default();
+            point.position = local_position;
+            points.insert(index, point);
+            ctx.set_property("points", points);
+
+            self.selected = Some((ctx.node, index));
+            self.drag_plane = Some(plane);
+            return true;
+        }
+
+        if let Some(index) = picked {
+            // Drag the point in a plane that faces the camera.
+            let position = transform_point(&transform, points[index].position);
+            let look = ctx.graph[ctx.camera].look_vector();
+            self.selected = Some((ctx.node, index));
+            self.drag_plane = Plane::from_normal_and_point(&look, &position);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn on_mouse_move(&mut self, ctx: &mut GizmoContext) {
+        if let (Some(index), Some(plane)) = (self.selected_index(ctx.node), self.drag_plane) {
+            if let Some(world_position) = ctx.ray.plane_intersection_point(&plane) {
+                let inv_transform = ctx.graph[ctx.node]
+                    .global_transform()
+                    .try_inverse()
+                    .unwrap_or_default();
+                ctx.set_property(
+                    &format!("points[{}].position", index),
+                    transform_point(&inv_transform, world_position),
+                );
+            }
+        }
+    }
+
+    fn on_mouse_up(&mut self, _ctx: &mut GizmoContext) {
+        self.drag_plane = None;
+    }
+}
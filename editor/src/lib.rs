@@ -18,6 +18,7 @@ mod build;
 mod camera;
 mod command;
 mod configurator;
+mod crash;
 mod curve_editor;
 mod gui;
 mod inspector;
@@ -28,10 +29,14 @@ mod material;
 mod menu;
 mod overlay;
 mod preview;
+mod project;
 mod scene;
 mod scene_viewer;
 mod settings;
+mod shader_graph;
+mod stats;
 mod utils;
+mod vcs;
 mod world;
 
 use crate::{
@@ -39,17 +44,20 @@ use crate::{
     animation::AnimationEditor,
     asset::{item::AssetItem, item::AssetKind, AssetBrowser},
     audio::AudioPanel,
-    build::BuildWindow,
+    build::{BuildWindow, Packager, TargetProfile},
     command::{panel::CommandStackViewer, Command, CommandStack},
     configurator::Configurator,
+    crash::{CrashReportWindow, CRASH_REPORTS_DIR},
     curve_editor::CurveEditorWindow,
     inspector::{editors::handle::HandlePropertyEditorMessage, Inspector},
     interaction::{
+        custom_gizmo::CustomGizmos,
         move_mode::MoveInteractionMode,
         navmesh::{EditNavmeshMode, NavmeshPanel},
         rotate_mode::RotateInteractionMode,
         scale_mode::ScaleInteractionMode,
         select_mode::SelectInteractionMode,
+        spline::SplineGizmo,
         terrain::TerrainInteractionMode,
         InteractionMode, InteractionModeKind,
     },
@@ -69,7 +77,10 @@ use crate::{
     },
     scene_viewer::SceneViewer,
     settings::{camera::SceneCameraSettings, Settings},
-    utils::path_fixer::PathFixer,
+    shader_graph::ShaderGraphEditor,
+    stats::StatisticsWindow,
+    utils::{path_fixer::PathFixer, scene_merge::window::SceneMergeWindow},
+    vcs::Vcs,
     world::{graph::selection::GraphSelection, WorldViewer},
 };
 use fyrox::{
@@ -83,9 +94,14 @@ use fyrox::{
         visitor::Visitor,
     },
     dpi::LogicalSize,
-    engine::{resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext},
+    engine::{
+        crash::{CrashHandler, CrashHandlerConfig},
+        resource_manager::ResourceManager,
+        Engine, EngineInitParams, SerializationContext,
+    },
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
+    fxhash::FxHashMap,
     gui::{
         brush::Brush,
         dock::{DockingManagerBuilder, TileBuilder, TileContent},
@@ -94,7 +110,8 @@ use fyrox::{
         file_browser::{FileBrowserMode, FileSelectorBuilder, FileSelectorMessage, Filter},
         formatted_text::WrapMode,
         grid::{Column, GridBuilder, Row},
-        message::{KeyCode, MessageDirection, UiMessage},
+        inspector::editors::PropertyEditorDefinition,
+        message::{MessageDirection, UiMessage},
         messagebox::{MessageBoxBuilder, MessageBoxButtons, MessageBoxMessage, MessageBoxResult},
         ttf::Font,
         widget::{WidgetBuilder, WidgetMessage},
@@ -131,6 +148,11 @@ use std::{
     time::{Duration, Instant},
 };
 
+pub use crate::interaction::custom_gizmo::{CustomGizmo, GizmoContext};
+pub use crate::settings::keys::HotKey;
+pub use crate::utils::scene_merge::run_merge_driver;
+pub use crate::vcs::{FileStatus, VcsProvider};
+
 pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
 pub const MSG_SYNC_FLAG: u64 = 1;
 
@@ -141,6 +163,8 @@ pub fn send_sync_message(ui: &UserInterface, mut msg: UiMessage) {
 
 type GameEngine = fyrox::engine::Engine;
 
+type HotKeyHandler = Box<dyn FnMut(&mut GameEngine)>;
+
 pub fn load_image(data: &[u8]) -> Option<draw::SharedTexture> {
     Some(into_gui_texture(
         Texture::load_from_memory(data, CompressionOptions::NoCompression, false).ok()?,
@@ -192,7 +216,7 @@ pub fn create_terrain_layer_material() -> SharedMaterial {
     SharedMaterial::new(material)
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum BuildProfile {
     Debug,
     Release,
@@ -221,6 +245,11 @@ pub enum Message {
     OpenAbsmEditor,
     OpenMaterialEditor(SharedMaterial),
     ShowInAssetBrowser(PathBuf),
+    /// An asset was moved or renamed, references to it are already fixed in the project.
+    AssetMoved {
+        old_path: PathBuf,
+        new_path: PathBuf,
+    },
     SetWorldViewerFilter(String),
     LocateObject {
         type_id: TypeId,
@@ -238,6 +267,12 @@ pub enum Message {
     OpenSaveSceneDialog,
     OpenSaveSceneConfirmationDialog(SaveSceneConfirmationDialogAction),
     SetBuildProfile(BuildProfile),
+    /// Builds the game for the given target and puts distributable files into `dist` folder.
+    Package(TargetProfile),
+    /// Discards local changes of an asset using version control system.
+    RevertAsset(PathBuf),
+    /// Cancels active build (either packaging or building before entering play mode).
+    CancelBuild,
     SaveSelectionAsPrefab(PathBuf),
     SyncNodeHandleName {
         view: Handle<UiNode>,
@@ -472,16 +507,24 @@ pub struct Editor {
     navmesh_panel: NavmeshPanel,
     settings: Settings,
     path_fixer: PathFixer,
+    scene_merge: SceneMergeWindow,
     material_editor: MaterialEditor,
     pub inspector: Inspector,
     curve_editor: CurveEditorWindow,
+    shader_graph_editor: ShaderGraphEditor,
     audio_panel: AudioPanel,
     absm_editor: AbsmEditor,
     mode: Mode,
     build_window: BuildWindow,
     build_profile: BuildProfile,
+    packager: Packager,
+    vcs: Vcs,
     scene_settings: SceneSettingsWindow,
     animation_editor: AnimationEditor,
+    statistics_window: StatisticsWindow,
+    crash_report_window: Option<CrashReportWindow>,
+    hot_key_handlers: FxHashMap<String, HotKeyHandler>,
+    custom_gizmos: CustomGizmos,
 }
 
 impl Editor {
@@ -514,6 +557,16 @@ impl Editor {
         })
         .unwrap();
 
+        // Check if the editor has crashed on previous run before the new crash handler is installed.
+        let crash_reports_dir = std::env::current_dir()
+            .map(|dir| dir.join(CRASH_REPORTS_DIR))
+            .unwrap_or_else(|_| PathBuf::from(CRASH_REPORTS_DIR));
+        let last_crash_report = CrashHandler::take_last_report(&crash_reports_dir);
+        engine.enable_crash_handler(CrashHandlerConfig {
+            report_dir: crash_reports_dir,
+            ..Default::default()
+        });
+
         // High-DPI screen support
         let logical_size = engine
             .get_window()
@@ -576,6 +629,7 @@ impl Editor {
         let menu = Menu::new(&mut engine, message_sender.clone(), &settings);
         let light_panel = LightPanel::new(&mut engine);
         let audio_panel = AudioPanel::new(&mut engine);
+        let absm_editor = AbsmEditor::new(&mut engine, message_sender.clone());
 
         let ctx = &mut engine.user_interface.build_ctx();
         let navmesh_panel = NavmeshPanel::new(ctx, message_sender.clone());
@@ -584,7 +638,7 @@ impl Editor {
         let log = LogPanel::new(ctx, log_message_receiver);
         let inspector = Inspector::new(ctx, message_sender.clone());
         let animation_editor = AnimationEditor::new(ctx);
-        let absm_editor = AbsmEditor::new(ctx, message_sender.clone());
+        let statistics_window = StatisticsWindow::new(ctx);
 
         let root_grid = GridBuilder::new(
             WidgetBuilder::new()
@@ -731,8 +785,12 @@ impl Editor {
 
         let path_fixer = PathFixer::new(ctx);
 
+        let scene_merge = SceneMergeWindow::new(ctx);
+
         let curve_editor = CurveEditorWindow::new(ctx);
 
+        let shader_graph_editor = ShaderGraphEditor::new(ctx);
+
         let save_scene_dialog = SaveSceneConfirmationDialog::new(ctx);
 
         let build_window = BuildWindow::new(ctx);
@@ -741,8 +799,18 @@ impl Editor {
 
         let material_editor = MaterialEditor::new(&mut engine);
 
+        let crash_report_window = last_crash_report.map(|report| {
+            let window = CrashReportWindow::new(report, &mut engine.user_interface.build_ctx());
+            window.open(&engine.user_interface);
+            window
+        });
+
         let mut editor = Self {
             animation_editor,
+            statistics_window,
+            crash_report_window,
+            hot_key_handlers: Default::default(),
+            custom_gizmos: CustomGizmos::new(message_sender.clone()),
             engine,
             navmesh_panel,
             scene_viewer,
@@ -766,9 +834,11 @@ impl Editor {
             validation_message_box,
             settings,
             path_fixer,
+            scene_merge,
             material_editor,
             inspector,
             curve_editor,
+            shader_graph_editor,
             audio_panel,
             save_scene_dialog,
             mode: Mode::Edit,
@@ -779,10 +849,13 @@ impl Editor {
             absm_editor,
             build_window,
             build_profile: BuildProfile::Debug,
+            packager: Default::default(),
+            vcs: Default::default(),
             scene_settings,
         };
 
         editor.set_interaction_mode(Some(InteractionModeKind::Move));
+        editor.add_gizmo(SplineGizmo::default());
 
         if let Some(data) = startup_data {
             editor
@@ -934,6 +1007,9 @@ impl Editor {
         self.scene_viewer
             .reset_camera_projection(&self.engine.user_interface);
         self.engine.renderer.flush();
+
+        // Show version control status of the scene.
+        self.vcs.changed = true;
     }
 
     fn set_interaction_mode(&mut self, mode: Option<InteractionModeKind>) {
@@ -967,47 +1043,52 @@ impl Editor {
         let engine = &mut self.engine;
 
         if let Some(WidgetMessage::KeyDown(key)) = message.data() {
-            match key {
-                KeyCode::Y if modifiers.control => {
+            let command = match self.settings.key_bindings.find(*key, modifiers) {
+                Some(command) => command.to_string(),
+                None => return,
+            };
+
+            match command.as_str() {
+                "redo" => {
                     sender.send(Message::RedoSceneCommand).unwrap();
                 }
-                KeyCode::Z if modifiers.control => {
+                "undo" => {
                     sender.send(Message::UndoSceneCommand).unwrap();
                 }
-                KeyCode::Key1 => {
+                "select_mode" => {
                     sender
                         .send(Message::SetInteractionMode(InteractionModeKind::Select))
                         .unwrap();
                 }
-                KeyCode::Key2 => {
+                "move_mode" => {
                     sender
                         .send(Message::SetInteractionMode(InteractionModeKind::Move))
                         .unwrap();
                 }
-                KeyCode::Key3 => {
+                "rotate_mode" => {
                     sender
                         .send(Message::SetInteractionMode(InteractionModeKind::Rotate))
                         .unwrap();
                 }
-                KeyCode::Key4 => {
+                "scale_mode" => {
                     sender
                         .send(Message::SetInteractionMode(InteractionModeKind::Scale))
                         .unwrap();
                 }
-                KeyCode::Key5 => {
+                "navmesh_mode" => {
                     sender
                         .send(Message::SetInteractionMode(InteractionModeKind::Navmesh))
                         .unwrap();
                 }
-                KeyCode::Key6 => {
+                "terrain_mode" => {
                     sender
                         .send(Message::SetInteractionMode(InteractionModeKind::Terrain))
                         .unwrap();
                 }
-                KeyCode::L if modifiers.control => {
+                "load_scene" => {
                     sender.send(Message::OpenLoadSceneDialog).unwrap();
                 }
-                KeyCode::S if modifiers.control => {
+                "save_scene" => {
                     if let Some(scene) = self.scene.as_ref() {
                         if let Some(path) = scene.path.as_ref() {
                             self.message_sender
@@ -1025,7 +1106,7 @@ impl Editor {
                         }
                     }
                 }
-                KeyCode::C if modifiers.control => {
+                "copy_selection" => {
                     if let Some(editor_scene) = self.scene.as_mut() {
                         if let Selection::Graph(graph_selection) = &editor_scene.selection {
                             editor_scene.clipboard.fill_from_selection(
@@ -1036,7 +1117,7 @@ impl Editor {
                         }
                     }
                 }
-                KeyCode::V if modifiers.control => {
+                "paste" => {
                     if let Some(editor_scene) = self.scene.as_mut() {
                         if !editor_scene.clipboard.is_empty() {
                             sender
@@ -1047,13 +1128,13 @@ impl Editor {
                         }
                     }
                 }
-                KeyCode::N if modifiers.control => {
+                "new_scene" => {
                     sender.send(Message::NewScene).unwrap();
                 }
-                KeyCode::Q if modifiers.control => {
+                "close_scene" => {
                     sender.send(Message::CloseScene).unwrap();
                 }
-                KeyCode::Delete => {
+                "remove_selection" => {
                     if let Some(editor_scene) = self.scene.as_mut() {
                         if !editor_scene.selection.is_empty() {
                             if let Selection::Graph(_) = editor_scene.selection {
@@ -1067,7 +1148,11 @@ impl Editor {
                         }
                     }
                 }
-                _ => (),
+                _ => {
+                    if let Some(handler) = self.hot_key_handlers.get_mut(&command) {
+                        handler(engine);
+                    }
+                }
             }
         }
     }
@@ -1103,9 +1188,12 @@ impl Editor {
                     audio_panel: self.audio_panel.window,
                     configurator_window: self.configurator.window,
                     path_fixer: self.path_fixer.window,
+                    scene_merge: &self.scene_merge,
                     curve_editor: &self.curve_editor,
+                    shader_graph_editor: &mut self.shader_graph_editor,
                     absm_editor: &self.absm_editor,
                     command_stack_panel: self.command_stack_viewer.window,
+                    statistics_panel: self.statistics_window.window,
                     scene_settings: &self.scene_settings,
                     animation_editor: &self.animation_editor,
                 },
@@ -1115,23 +1203,36 @@ impl Editor {
 
         self.build_window
             .handle_ui_message(message, &self.message_sender, &engine.user_interface);
-        self.log.handle_ui_message(message, engine);
-        self.asset_browser
-            .handle_ui_message(message, engine, self.message_sender.clone());
+        self.log
+            .handle_ui_message(message, engine, &self.message_sender);
+        self.asset_browser.handle_ui_message(
+            message,
+            engine,
+            self.message_sender.clone(),
+            &self.vcs,
+        );
         self.command_stack_viewer.handle_ui_message(message);
+        if let Some(crash_report_window) = self.crash_report_window.as_mut() {
+            if crash_report_window.handle_ui_message(message, &engine.user_interface) {
+                self.crash_report_window = None;
+            }
+        }
         self.curve_editor.handle_ui_message(message, engine);
+        self.shader_graph_editor.handle_ui_message(message, engine);
         self.path_fixer.handle_ui_message(
             message,
             &mut engine.user_interface,
             engine.serialization_context.clone(),
             engine.resource_manager.clone(),
         );
+        self.scene_merge.handle_ui_message(message, engine);
         self.scene_viewer.handle_ui_message(
             message,
             engine,
             self.scene.as_mut(),
             self.current_interaction_mode
                 .and_then(|i| self.interaction_modes.get_mut(i as usize)),
+            &mut self.custom_gizmos,
             &self.settings,
             &self.mode,
         );
@@ -1290,6 +1391,7 @@ impl Editor {
                     let mut process = std::process::Command::new("cargo");
                     process
                         .stdout(Stdio::piped())
+                        .stderr(Stdio::piped())
                         .arg("build")
                         .arg("--package")
                         .arg("executor");
@@ -1301,7 +1403,8 @@ impl Editor {
                     match process.spawn() {
                         Ok(mut process) => {
                             self.build_window.listen(
-                                process.stdout.take().unwrap(),
+                                &mut process,
+                                "Building the Game...",
                                 &self.engine.user_interface,
                             );
 
@@ -1386,6 +1489,7 @@ impl Editor {
             self.world_viewer
                 .post_update(scene, &mut self.engine, &self.settings);
         }
+        self.inspector.post_update(&self.engine.user_interface);
     }
 
     fn handle_resize(&mut self) {
@@ -1486,7 +1590,43 @@ impl Editor {
         }
     }
 
+    fn sync_vcs_status(&mut self) {
+        let ui = &self.engine.user_interface;
+
+        self.asset_browser.sync_vcs_status(ui, &self.vcs);
+
+        let mut title = "World Viewer".to_string();
+        if let Some(path) = self.scene.as_ref().and_then(|s| s.path.as_ref()) {
+            title += &format!(" - {}", path.display());
+            let status = self.vcs.status(path);
+            if status != FileStatus::Unmodified {
+                title += &format!(" [{}]", status.name());
+            }
+        }
+        self.world_viewer.set_title(ui, title);
+    }
+
+    /// Sets a custom version control provider. By default the editor detects a provider
+    /// automatically (only Git is supported at the moment), `None` restores the detection.
+    pub fn set_vcs_provider(&mut self, provider: Option<Box<dyn VcsProvider>>) {
+        self.vcs.set_provider(provider);
+    }
+
     fn save_current_scene(&mut self, path: PathBuf) {
+        if !self.vcs.ensure_writable(&path) {
+            return;
+        }
+
+        // Fix trivial problems in the scene first, the fix could be undone as any other change.
+        if let Some(command) = self
+            .scene
+            .as_ref()
+            .and_then(|editor_scene| editor_scene.make_fix_command(&self.engine))
+        {
+            self.do_scene_command(SceneCommand::new(command));
+            self.sync_to_model();
+        }
+
         let engine = &mut self.engine;
         if let Some(editor_scene) = self.scene.as_mut() {
             match editor_scene.save(path.clone(), engine) {
@@ -1498,6 +1638,9 @@ impl Editor {
                     Log::info(message);
 
                     editor_scene.has_unsaved_changes = false;
+
+                    self.vcs.refresh();
+                    self.vcs.changed = true;
                 }
                 Err(message) => {
                     Log::err(message.clone());
@@ -1560,6 +1703,7 @@ impl Editor {
             // Set default title scene
             self.scene_viewer
                 .set_title(&engine.user_interface, "Scene Preview".to_string());
+            self.vcs.changed = true;
 
             true
         } else {
@@ -1604,6 +1748,8 @@ impl Editor {
 
         engine.renderer.flush();
 
+        self.vcs.set_working_directory(&working_directory);
+
         self.asset_browser
             .set_working_directory(engine, &working_directory);
 
@@ -1717,6 +1863,15 @@ impl Editor {
             _ => {}
         }
 
+        self.packager
+            .update(&mut self.build_window, &self.engine.user_interface);
+
+        self.vcs.update(dt);
+        if self.vcs.changed {
+            self.vcs.changed = false;
+            self.sync_vcs_status();
+        }
+
         self.log.update(&mut self.engine);
         self.material_editor.update(&mut self.engine);
         self.asset_browser.update(&mut self.engine);
@@ -1725,6 +1880,9 @@ impl Editor {
             self.animation_editor.update(scene, &self.engine);
         }
 
+        self.statistics_window
+            .update(self.scene.as_ref(), &self.engine, dt);
+
         let mut iterations = 1;
         while iterations > 0 {
             iterations -= 1;
@@ -1754,8 +1912,7 @@ impl Editor {
                 if let Some(scene) = self.scene.as_ref() {
                     self.animation_editor
                         .handle_message(&message, scene, &mut self.engine);
-                    self.absm_editor
-                        .handle_message(&message, scene, &mut self.engine);
+                    self.absm_editor.handle_message(&message);
                 }
 
                 self.scene_viewer.handle_message(&message, &mut self.engine);
@@ -1808,9 +1965,22 @@ impl Editor {
                         self.asset_browser
                             .locate_path(&self.engine.user_interface, path);
                     }
+                    Message::AssetMoved { old_path, new_path } => {
+                        if let Some(editor_scene) = self.scene.as_mut() {
+                            if editor_scene.path.as_ref() == Some(&old_path) {
+                                editor_scene.path = Some(new_path.clone());
+                                self.scene_viewer.set_title(
+                                    &self.engine.user_interface,
+                                    format!("Scene Preview - {}", new_path.display()),
+                                );
+                            }
+                        }
+                        self.vcs.changed = true;
+                        self.asset_browser
+                            .locate_path(&self.engine.user_interface, new_path);
+                    }
                     Message::SetWorldViewerFilter(filter) => {
-                        self.world_viewer
-                            .set_filter(filter, &self.engine.user_interface);
+                        self.world_viewer.set_filter(filter);
                     }
                     Message::LocateObject { type_id, handle } => self
                         .world_viewer
@@ -1846,6 +2016,30 @@ impl Editor {
                     }
                     Message::SetBuildProfile(profile) => {
                         self.build_profile = profile;
+                        self.scene_viewer
+                            .set_build_profile(&self.engine.user_interface, profile);
+                    }
+                    Message::RevertAsset(path) => {
+                        self.vcs.revert(&path);
+                        if self.scene.as_ref().and_then(|s| s.path.as_ref()) == Some(&path) {
+                            Log::warn(
+                                "Current scene was reverted on disk, reload it to see the changes.",
+                            );
+                        }
+                    }
+                    Message::Package(target) => {
+                        self.packager.start(
+                            target,
+                            &mut self.build_window,
+                            &self.engine.user_interface,
+                        );
+                    }
+                    Message::CancelBuild => {
+                        if self.packager.is_active() {
+                            self.packager.cancel();
+                        } else {
+                            self.set_editor_mode();
+                        }
                     }
                     Message::SaveSelectionAsPrefab(path) => {
                         self.try_save_selection_as_prefab(path);
@@ -1889,6 +2083,7 @@ impl Editor {
             self.absm_editor.update(editor_scene, &mut self.engine);
 
             editor_scene.draw_auxiliary_geometry(&mut self.engine, &self.settings);
+            self.custom_gizmos.draw(editor_scene, &mut self.engine);
 
             let scene = &mut self.engine.scenes[editor_scene.scene];
 
@@ -1978,6 +2173,34 @@ impl Editor {
         }
     }
 
+    /// Registers a command that could be bound to a hot key in the editor settings. The handler
+    /// will be called every time when the hot key of the command is pressed.
+    pub fn register_key_binding<F>(&mut self, id: &str, name: &str, default: HotKey, handler: F)
+    where
+        F: FnMut(&mut GameEngine) + 'static,
+    {
+        self.settings.key_bindings.register(id, name, default);
+        self.hot_key_handlers
+            .insert(id.to_string(), Box::new(handler));
+    }
+
+    /// Registers a viewport gizmo for custom node or script types. See [`CustomGizmo`] docs for
+    /// more info.
+    pub fn add_gizmo<G: CustomGizmo>(&mut self, gizmo: G) {
+        self.custom_gizmos.add(gizmo);
+    }
+
+    /// Registers a property editor for a custom type (a field of a script, for example). The
+    /// editor will be used by the inspector for every property of the type and for every item of
+    /// `Vec` properties with such items. Registering an editor for a type that already has one
+    /// replaces the built-in editor.
+    pub fn add_property_editor<D>(&mut self, definition: D)
+    where
+        D: PropertyEditorDefinition + 'static,
+    {
+        self.inspector.property_editors.insert(definition);
+    }
+
     pub fn add_game_plugin<P>(&mut self, plugin: P)
     where
         P: PluginConstructor + 'static,
@@ -1994,6 +2217,7 @@ impl Editor {
                     *control_flow = ControlFlow::Exit;
 
                     // Kill any active child process on exit.
+                    self.packager.cancel();
                     match self.mode {
                         Mode::Edit => {}
                         Mode::Build { ref mut process }
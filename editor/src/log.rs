@@ -1,4 +1,6 @@
-use crate::{gui::make_dropdown_list_option, Brush, Color, DropdownListBuilder, GameEngine};
+use crate::{
+    gui::make_dropdown_list_option, Brush, Color, DropdownListBuilder, GameEngine, Message,
+};
 use fyrox::{
     core::{pool::Handle, scope_profile},
     gui::{
@@ -8,20 +10,25 @@ use fyrox::{
         dropdown_list::DropdownListMessage,
         formatted_text::WrapMode,
         grid::{Column, GridBuilder, Row},
-        list_view::{ListView, ListViewBuilder, ListViewMessage},
+        list_view::{ListViewBuilder, ListViewMessage},
         menu::{MenuItemBuilder, MenuItemContent, MenuItemMessage},
         message::{MessageDirection, UiMessage},
         popup::{Placement, PopupBuilder, PopupMessage},
         scroll_viewer::ScrollViewerBuilder,
+        search_bar::{SearchBarBuilder, SearchBarMessage},
         stack_panel::StackPanelBuilder,
         text::{Text, TextBuilder},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowTitle},
-        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode,
+        BuildContext, Thickness, UiNode, UserInterface,
     },
     utils::log::{LogMessage, MessageKind},
 };
-use std::sync::mpsc::Receiver;
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::mpsc::{Receiver, Sender},
+};
 
 struct ContextMenu {
     menu: Handle<UiNode>,
@@ -73,6 +80,9 @@ impl ContextMenu {
     }
 }
 
+/// Maximum amount of messages stored in the panel, oldest messages are removed first.
+const MAX_STORED_MESSAGES: usize = 4096;
+
 pub struct LogPanel {
     pub window: Handle<UiNode>,
     messages: Handle<UiNode>,
@@ -80,7 +90,15 @@ pub struct LogPanel {
     receiver: Receiver<LogMessage>,
     severity: MessageKind,
     severity_list: Handle<UiNode>,
+    category_list: Handle<UiNode>,
+    search_bar: Handle<UiNode>,
     context_menu: ContextMenu,
+    stored_messages: VecDeque<LogMessage>,
+    categories: Vec<String>,
+    category: Option<String>,
+    search_text: String,
+    // Asset paths of the displayed items, in the same order as the items in the list.
+    displayed_assets: Vec<Option<PathBuf>>,
 }
 
 impl LogPanel {
@@ -88,6 +106,8 @@ impl LogPanel {
         let messages;
         let clear;
         let severity_list;
+        let category_list;
+        let search_bar;
         let window = WindowBuilder::new(WidgetBuilder::new())
             .can_minimize(false)
             .with_title(WindowTitle::Text("Message Log".to_owned()))
@@ -95,25 +115,37 @@ impl LogPanel {
                 GridBuilder::new(
                     WidgetBuilder::new()
                         .with_child(
-                            StackPanelBuilder::new(
+                            GridBuilder::new(
                                 WidgetBuilder::new()
-                                    .with_horizontal_alignment(HorizontalAlignment::Right)
                                     .on_row(0)
                                     .on_column(0)
                                     .with_child({
-                                        clear = ButtonBuilder::new(
+                                        search_bar = SearchBarBuilder::new(
                                             WidgetBuilder::new()
-                                                .with_width(120.0)
+                                                .on_column(0)
                                                 .with_margin(Thickness::uniform(1.0)),
                                         )
-                                        .with_text("Clear")
                                         .build(ctx);
-                                        clear
+                                        search_bar
+                                    })
+                                    .with_child({
+                                        category_list = DropdownListBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_items(vec![make_dropdown_list_option(
+                                            ctx,
+                                            "All Categories",
+                                        )])
+                                        .with_selected(0)
+                                        .build(ctx);
+                                        category_list
                                     })
                                     .with_child({
                                         severity_list = DropdownListBuilder::new(
                                             WidgetBuilder::new()
-                                                .with_width(120.0)
+                                                .on_column(2)
                                                 .with_margin(Thickness::uniform(1.0)),
                                         )
                                         .with_items(vec![
@@ -125,9 +157,23 @@ impl LogPanel {
                                         .with_selected(1)
                                         .build(ctx);
                                         severity_list
+                                    })
+                                    .with_child({
+                                        clear = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(3)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Clear")
+                                        .build(ctx);
+                                        clear
                                     }),
                             )
-                            .with_orientation(Orientation::Horizontal)
+                            .add_row(Row::stretch())
+                            .add_column(Column::stretch())
+                            .add_column(Column::strict(140.0))
+                            .add_column(Column::strict(120.0))
+                            .add_column(Column::strict(120.0))
                             .build(ctx),
                         )
                         .with_child({
@@ -165,33 +211,150 @@ impl LogPanel {
             receiver: message_receiver,
             severity: MessageKind::Warning,
             severity_list,
+            category_list,
+            search_bar,
             context_menu,
+            stored_messages: Default::default(),
+            categories: Default::default(),
+            category: None,
+            search_text: Default::default(),
+            displayed_assets: Default::default(),
         }
     }
 
-    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+    fn passes_filter(&self, msg: &LogMessage) -> bool {
+        msg.kind >= self.severity
+            && self.category.as_ref().map_or(true, |c| *c == msg.category)
+            && (self.search_text.is_empty()
+                || msg.content.to_lowercase().contains(&self.search_text))
+    }
+
+    fn add_item(&mut self, msg: &LogMessage, ui: &mut UserInterface) -> Handle<UiNode> {
+        let mut text = format!(
+            "[{:.2}s] [{}] {}",
+            msg.time.as_secs_f32(),
+            msg.category,
+            msg.content.trim_end_matches('\n')
+        );
+        if let Some(asset) = msg.asset.as_ref() {
+            text += &format!(" (click to show {} in the asset browser)", asset.display());
+        }
+
+        let ctx = &mut ui.build_ctx();
+        let item = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(Brush::Solid(if self.displayed_assets.len() % 2 == 0 {
+                    Color::opaque(70, 70, 70)
+                } else {
+                    Color::opaque(40, 40, 40)
+                }))
+                .with_child(
+                    TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_context_menu(self.context_menu.menu)
+                            .with_margin(Thickness::uniform(1.0))
+                            .with_foreground(Brush::Solid(match msg.kind {
+                                MessageKind::Information => Color::opaque(210, 210, 210),
+                                MessageKind::Warning => Color::ORANGE,
+                                MessageKind::Error => Color::RED,
+                            })),
+                    )
+                    .with_text(text)
+                    .with_wrap(WrapMode::Word)
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        ui.send_message(ListViewMessage::add_item(
+            self.messages,
+            MessageDirection::ToWidget,
+            item,
+        ));
+
+        self.displayed_assets.push(msg.asset.clone());
+
+        item
+    }
+
+    /// Re-creates the list of items using current filters.
+    fn rebuild(&mut self, ui: &mut UserInterface) {
+        ui.send_message(ListViewMessage::items(
+            self.messages,
+            MessageDirection::ToWidget,
+            vec![],
+        ));
+        self.displayed_assets.clear();
+
+        let messages = std::mem::take(&mut self.stored_messages);
+        let mut last = Handle::NONE;
+        for msg in messages.iter() {
+            if self.passes_filter(msg) {
+                last = self.add_item(msg, ui);
+            }
+        }
+        self.stored_messages = messages;
+
+        if last.is_some() {
+            ui.send_message(ListViewMessage::bring_item_into_view(
+                self.messages,
+                MessageDirection::ToWidget,
+                last,
+            ));
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
         scope_profile!();
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.clear {
-                engine.user_interface.send_message(ListViewMessage::items(
-                    self.messages,
-                    MessageDirection::ToWidget,
-                    vec![],
-                ));
+                self.stored_messages.clear();
+                self.rebuild(&mut engine.user_interface);
             }
         } else if let Some(DropdownListMessage::SelectionChanged(Some(idx))) =
             message.data::<DropdownListMessage>()
         {
-            if message.destination() == self.severity_list
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.severity_list {
+                    match idx {
+                        0 => self.severity = MessageKind::Information,
+                        1 => self.severity = MessageKind::Warning,
+                        2 => self.severity = MessageKind::Error,
+                        _ => (),
+                    };
+                    self.rebuild(&mut engine.user_interface);
+                } else if message.destination() == self.category_list {
+                    // First item is "All Categories".
+                    self.category = idx
+                        .checked_sub(1)
+                        .and_then(|i| self.categories.get(i).cloned());
+                    self.rebuild(&mut engine.user_interface);
+                }
+            }
+        } else if let Some(SearchBarMessage::Text(text)) = message.data::<SearchBarMessage>() {
+            if message.destination() == self.search_bar
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.search_text = text.to_lowercase();
+                self.rebuild(&mut engine.user_interface);
+            }
+        } else if let Some(ListViewMessage::SelectionChanged(Some(idx))) =
+            message.data::<ListViewMessage>()
+        {
+            if message.destination() == self.messages
                 && message.direction() == MessageDirection::FromWidget
             {
-                match idx {
-                    0 => self.severity = MessageKind::Information,
-                    1 => self.severity = MessageKind::Warning,
-                    2 => self.severity = MessageKind::Error,
-                    _ => (),
-                };
+                if let Some(Some(asset)) = self.displayed_assets.get(*idx) {
+                    sender
+                        .send(Message::ShowInAssetBrowser(asset.clone()))
+                        .unwrap();
+                }
             }
         }
 
@@ -199,69 +362,37 @@ impl LogPanel {
     }
 
     pub fn update(&mut self, engine: &mut GameEngine) {
-        let mut count = engine
-            .user_interface
-            .node(self.messages)
-            .cast::<ListView>()
-            .map(|v| v.items().len())
-            .unwrap_or_default();
+        let ui = &mut engine.user_interface;
 
         let mut item_to_bring_into_view = Handle::NONE;
 
         while let Ok(msg) = self.receiver.try_recv() {
-            if msg.kind < self.severity {
-                continue;
-            }
-
-            let text = format!("[{:.2}s] {}", msg.time.as_secs_f32(), msg.content);
-
-            let ctx = &mut engine.user_interface.build_ctx();
-            let item = BorderBuilder::new(
-                WidgetBuilder::new()
-                    .with_background(Brush::Solid(if count % 2 == 0 {
-                        Color::opaque(70, 70, 70)
-                    } else {
-                        Color::opaque(40, 40, 40)
-                    }))
-                    .with_child(
-                        TextBuilder::new(
-                            WidgetBuilder::new()
-                                .with_context_menu(self.context_menu.menu)
-                                .with_margin(Thickness::uniform(1.0))
-                                .with_foreground(Brush::Solid(match msg.kind {
-                                    MessageKind::Information => Color::opaque(210, 210, 210),
-                                    MessageKind::Warning => Color::ORANGE,
-                                    MessageKind::Error => Color::RED,
-                                })),
-                        )
-                        .with_text(text)
-                        .with_wrap(WrapMode::Word)
-                        .build(ctx),
-                    ),
-            )
-            .build(ctx);
-
-            engine
-                .user_interface
-                .send_message(ListViewMessage::add_item(
-                    self.messages,
+            if !self.categories.contains(&msg.category) {
+                self.categories.push(msg.category.clone());
+                let option = make_dropdown_list_option(&mut ui.build_ctx(), &msg.category);
+                ui.send_message(DropdownListMessage::add_item(
+                    self.category_list,
                     MessageDirection::ToWidget,
-                    item,
+                    option,
                 ));
+            }
 
-            item_to_bring_into_view = item;
+            if self.passes_filter(&msg) {
+                item_to_bring_into_view = self.add_item(&msg, ui);
+            }
 
-            count += 1;
+            if self.stored_messages.len() >= MAX_STORED_MESSAGES {
+                self.stored_messages.pop_front();
+            }
+            self.stored_messages.push_back(msg);
         }
 
         if item_to_bring_into_view.is_some() {
-            engine
-                .user_interface
-                .send_message(ListViewMessage::bring_item_into_view(
-                    self.messages,
-                    MessageDirection::ToWidget,
-                    item_to_bring_into_view,
-                ));
+            ui.send_message(ListViewMessage::bring_item_into_view(
+                self.messages,
+                MessageDirection::ToWidget,
+                item_to_bring_into_view,
+            ));
         }
     }
 }
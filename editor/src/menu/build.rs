@@ -0,0 +1,91 @@
+use crate::{
+    build::TargetProfile,
+    menu::{create_menu_item, create_root_menu_item},
+    BuildProfile, Message, Mode,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        menu::MenuItemMessage,
+        message::{MessageDirection, UiMessage},
+        widget::WidgetMessage,
+        BuildContext, UiNode, UserInterface,
+    },
+};
+use std::sync::mpsc::Sender;
+
+pub struct BuildMenu {
+    pub menu: Handle<UiNode>,
+    run_debug: Handle<UiNode>,
+    run_release: Handle<UiNode>,
+    package: Vec<(Handle<UiNode>, TargetProfile)>,
+}
+
+impl BuildMenu {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let run_debug;
+        let run_release;
+
+        let package = TargetProfile::ALL
+            .iter()
+            .map(|&target| (create_menu_item(target.name(), vec![], ctx), target))
+            .collect::<Vec<_>>();
+
+        let menu = create_root_menu_item(
+            "Build",
+            vec![
+                {
+                    run_debug = create_menu_item("Build and Run (Debug)", vec![], ctx);
+                    run_debug
+                },
+                {
+                    run_release = create_menu_item("Build and Run (Release)", vec![], ctx);
+                    run_release
+                },
+                create_menu_item(
+                    "Package",
+                    package.iter().map(|(item, _)| *item).collect(),
+                    ctx,
+                ),
+            ],
+            ctx,
+        );
+
+        Self {
+            menu,
+            run_debug,
+            run_release,
+            package,
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, sender: &Sender<Message>) {
+        if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
+            if message.destination() == self.run_debug {
+                sender
+                    .send(Message::SetBuildProfile(BuildProfile::Debug))
+                    .unwrap();
+                sender.send(Message::SwitchMode).unwrap();
+            } else if message.destination() == self.run_release {
+                sender
+                    .send(Message::SetBuildProfile(BuildProfile::Release))
+                    .unwrap();
+                sender.send(Message::SwitchMode).unwrap();
+            } else if let Some((_, target)) = self
+                .package
+                .iter()
+                .find(|(item, _)| *item == message.destination())
+            {
+                sender.send(Message::Package(*target)).unwrap();
+            }
+        }
+    }
+
+    pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
+        ui.send_message(WidgetMessage::enabled(
+            self.menu,
+            MessageDirection::ToWidget,
+            mode.is_edit(),
+        ));
+    }
+}
@@ -8,7 +8,10 @@ use crate::{
     Message, Mode,
 };
 use fyrox::{
-    core::{algebra::Matrix4, pool::Handle},
+    core::{
+        algebra::{Matrix4, Vector3},
+        pool::Handle,
+    },
     gui::{
         menu::MenuItemMessage, message::MessageDirection, message::UiMessage,
         widget::WidgetMessage, BuildContext, UiNode, UserInterface,
@@ -31,9 +34,15 @@ use fyrox::{
             ParticleSystemBuilder,
         },
         pivot::PivotBuilder,
-        sound::{listener::ListenerBuilder, SoundBuilder},
+        sound::{
+            environment::{AudioPortalBuilder, ReverbZoneBuilder},
+            listener::ListenerBuilder,
+            SoundBuilder,
+        },
+        spline::{SplineBuilder, SplinePoint},
         sprite::SpriteBuilder,
         terrain::{LayerDefinition, TerrainBuilder},
+        text::Text3DBuilder,
     },
 };
 use std::sync::mpsc::Sender;
@@ -86,10 +95,14 @@ pub struct CreateEntityMenu {
     create_spot_light: Handle<UiNode>,
     create_directional_light: Handle<UiNode>,
     create_terrain: Handle<UiNode>,
+    create_spline: Handle<UiNode>,
     create_camera: Handle<UiNode>,
     create_sprite: Handle<UiNode>,
+    create_text: Handle<UiNode>,
     create_particle_system: Handle<UiNode>,
     create_listener: Handle<UiNode>,
+    create_reverb_zone: Handle<UiNode>,
+    create_audio_portal: Handle<UiNode>,
     create_sound_source: Handle<UiNode>,
     physics_menu: PhysicsMenu,
     physics2d_menu: Physics2dMenu,
@@ -109,12 +122,16 @@ impl CreateEntityMenu {
         let create_directional_light;
         let create_camera;
         let create_sprite;
+        let create_text;
         let create_decal;
         let create_particle_system;
         let create_terrain;
+        let create_spline;
         let create_pivot;
         let create_sound_source;
         let create_listener;
+        let create_reverb_zone;
+        let create_audio_portal;
         let physics_menu = PhysicsMenu::new(ctx);
         let physics2d_menu = Physics2dMenu::new(ctx);
         let dim2_menu = Dim2Menu::new(ctx);
@@ -162,6 +179,14 @@ impl CreateEntityMenu {
                         create_listener = create_menu_item("Listener", vec![], ctx);
                         create_listener
                     },
+                    {
+                        create_reverb_zone = create_menu_item("Reverb Zone", vec![], ctx);
+                        create_reverb_zone
+                    },
+                    {
+                        create_audio_portal = create_menu_item("Audio Portal", vec![], ctx);
+                        create_audio_portal
+                    },
                 ],
                 ctx,
             ),
@@ -196,6 +221,10 @@ impl CreateEntityMenu {
                 create_sprite = create_menu_item("Sprite (3D)", vec![], ctx);
                 create_sprite
             },
+            {
+                create_text = create_menu_item("Text (3D)", vec![], ctx);
+                create_text
+            },
             {
                 create_particle_system = create_menu_item("Particle System", vec![], ctx);
                 create_particle_system
@@ -204,6 +233,10 @@ impl CreateEntityMenu {
                 create_terrain = create_menu_item("Terrain", vec![], ctx);
                 create_terrain
             },
+            {
+                create_spline = create_menu_item("Spline", vec![], ctx);
+                create_spline
+            },
             {
                 create_decal = create_menu_item("Decal", vec![], ctx);
                 create_decal
@@ -222,11 +255,15 @@ impl CreateEntityMenu {
                 create_directional_light,
                 create_camera,
                 create_sprite,
+                create_text,
                 create_particle_system,
                 create_pivot,
                 create_terrain,
+                create_spline,
                 create_sound_source,
                 create_listener,
+                create_reverb_zone,
+                create_audio_portal,
                 create_decal,
                 physics_menu,
                 physics2d_menu,
@@ -326,6 +363,12 @@ impl CreateEntityMenu {
                         Some(
                             SpriteBuilder::new(BaseBuilder::new().with_name("Sprite")).build_node(),
                         )
+                    } else if message.destination() == self.create_text {
+                        Some(
+                            Text3DBuilder::new(BaseBuilder::new().with_name("Text"))
+                                .with_text("Text")
+                                .build_node(),
+                        )
                     } else if message.destination() == self.create_sound_source {
                         Some(SoundBuilder::new(BaseBuilder::new().with_name("Sound")).build_node())
                     } else if message.destination() == self.create_particle_system {
@@ -352,6 +395,16 @@ impl CreateEntityMenu {
                                 .with_height_map_resolution(4.0)
                                 .build_node(),
                         )
+                    } else if message.destination() == self.create_spline {
+                        Some(
+                            SplineBuilder::new(BaseBuilder::new().with_name("Spline"))
+                                .with_points(vec![
+                                    SplinePoint::new(Vector3::new(0.0, 0.0, 0.0)),
+                                    SplinePoint::new(Vector3::new(2.0, 0.0, 2.0)),
+                                    SplinePoint::new(Vector3::new(4.0, 0.0, 0.0)),
+                                ])
+                                .build_node(),
+                        )
                     } else if message.destination() == self.create_decal {
                         Some(DecalBuilder::new(BaseBuilder::new().with_name("Decal")).build_node())
                     } else if message.destination() == self.create_listener {
@@ -359,6 +412,16 @@ impl CreateEntityMenu {
                             ListenerBuilder::new(BaseBuilder::new().with_name("Listener"))
                                 .build_node(),
                         )
+                    } else if message.destination() == self.create_reverb_zone {
+                        Some(
+                            ReverbZoneBuilder::new(BaseBuilder::new().with_name("ReverbZone"))
+                                .build_node(),
+                        )
+                    } else if message.destination() == self.create_audio_portal {
+                        Some(
+                            AudioPortalBuilder::new(BaseBuilder::new().with_name("AudioPortal"))
+                                .build_node(),
+                        )
                     } else {
                         None
                     }
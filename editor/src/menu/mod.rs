@@ -1,12 +1,14 @@
 use crate::{
     animation::AnimationEditor,
     menu::{
-        create::CreateEntityRootMenu, edit::EditMenu, file::FileMenu, utils::UtilsMenu,
-        view::ViewMenu,
+        build::BuildMenu, create::CreateEntityRootMenu, edit::EditMenu, file::FileMenu,
+        utils::UtilsMenu, view::ViewMenu,
     },
     scene::EditorScene,
     send_sync_message,
     settings::Settings,
+    shader_graph::ShaderGraphEditor,
+    utils::scene_merge::window::SceneMergeWindow,
     AbsmEditor, CurveEditorWindow, GameEngine, Message, Mode, SceneSettingsWindow,
 };
 use fyrox::{
@@ -21,6 +23,7 @@ use fyrox::{
 use std::sync::mpsc::Sender;
 
 pub mod animation;
+pub mod build;
 pub mod create;
 pub mod dim2;
 pub mod edit;
@@ -38,6 +41,7 @@ pub struct Menu {
     view_menu: ViewMenu,
     message_sender: Sender<Message>,
     utils_menu: UtilsMenu,
+    build_menu: BuildMenu,
 }
 
 pub struct Panels<'b> {
@@ -46,12 +50,15 @@ pub struct Panels<'b> {
     pub navmesh_panel: Handle<UiNode>,
     pub audio_panel: Handle<UiNode>,
     pub command_stack_panel: Handle<UiNode>,
+    pub statistics_panel: Handle<UiNode>,
     pub inspector_window: Handle<UiNode>,
     pub world_outliner_window: Handle<UiNode>,
     pub asset_window: Handle<UiNode>,
     pub configurator_window: Handle<UiNode>,
     pub path_fixer: Handle<UiNode>,
+    pub scene_merge: &'b SceneMergeWindow,
     pub curve_editor: &'b CurveEditorWindow,
+    pub shader_graph_editor: &'b mut ShaderGraphEditor,
     pub absm_editor: &'b AbsmEditor,
     pub scene_settings: &'b SceneSettingsWindow,
     pub animation_editor: &'b AnimationEditor,
@@ -110,6 +117,7 @@ impl Menu {
         let edit_menu = EditMenu::new(ctx);
         let view_menu = ViewMenu::new(ctx);
         let utils_menu = UtilsMenu::new(ctx);
+        let build_menu = BuildMenu::new(ctx);
 
         let menu = MenuBuilder::new(WidgetBuilder::new().on_row(0))
             .with_items(vec![
@@ -118,6 +126,7 @@ impl Menu {
                 create_entity_menu.menu,
                 view_menu.menu,
                 utils_menu.menu,
+                build_menu.menu,
             ])
             .build(ctx);
 
@@ -129,6 +138,7 @@ impl Menu {
             file_menu,
             view_menu,
             utils_menu,
+            build_menu,
         }
     }
 
@@ -175,7 +185,7 @@ impl Menu {
         }
 
         self.utils_menu
-            .handle_ui_message(message, &ctx.panels, &ctx.engine.user_interface);
+            .handle_ui_message(message, &mut ctx.panels, &mut ctx.engine.user_interface);
         self.file_menu.handle_ui_message(
             message,
             &self.message_sender,
@@ -185,12 +195,15 @@ impl Menu {
             &ctx.panels,
         );
         self.view_menu
-            .handle_ui_message(message, &ctx.engine.user_interface, &ctx.panels);
+            .handle_ui_message(message, ctx.engine, ctx.settings, &ctx.panels);
+        self.build_menu
+            .handle_ui_message(message, &self.message_sender);
     }
 
     pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
         self.create_entity_menu.on_mode_changed(ui, mode);
         self.edit_menu.on_mode_changed(ui, mode);
         self.file_menu.on_mode_changed(ui, mode);
+        self.build_menu.on_mode_changed(ui, mode);
     }
 }
@@ -12,7 +12,9 @@ use fyrox::{
 pub struct UtilsMenu {
     pub menu: Handle<UiNode>,
     open_path_fixer: Handle<UiNode>,
+    open_scene_merge: Handle<UiNode>,
     open_curve_editor: Handle<UiNode>,
+    open_shader_graph_editor: Handle<UiNode>,
     absm_editor: Handle<UiNode>,
     animation_editor: Handle<UiNode>,
 }
@@ -20,7 +22,9 @@ pub struct UtilsMenu {
 impl UtilsMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let open_path_fixer;
+        let open_scene_merge;
         let open_curve_editor;
+        let open_shader_graph_editor;
         let absm_editor;
         let animation_editor;
         let menu = create_root_menu_item(
@@ -30,10 +34,18 @@ impl UtilsMenu {
                     open_path_fixer = create_menu_item("Path Fixer", vec![], ctx);
                     open_path_fixer
                 },
+                {
+                    open_scene_merge = create_menu_item("Scene Merge", vec![], ctx);
+                    open_scene_merge
+                },
                 {
                     open_curve_editor = create_menu_item("Curve Editor", vec![], ctx);
                     open_curve_editor
                 },
+                {
+                    open_shader_graph_editor = create_menu_item("Shader Graph Editor", vec![], ctx);
+                    open_shader_graph_editor
+                },
                 {
                     absm_editor = create_menu_item("ABSM Editor", vec![], ctx);
                     absm_editor
@@ -49,13 +61,20 @@ impl UtilsMenu {
         Self {
             menu,
             open_path_fixer,
+            open_scene_merge,
             open_curve_editor,
+            open_shader_graph_editor,
             absm_editor,
             animation_editor,
         }
     }
 
-    pub fn handle_ui_message(&mut self, message: &UiMessage, panels: &Panels, ui: &UserInterface) {
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        panels: &mut Panels,
+        ui: &mut UserInterface,
+    ) {
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
             if message.destination() == self.open_path_fixer {
                 ui.send_message(WindowMessage::open_modal(
@@ -63,8 +82,12 @@ impl UtilsMenu {
                     MessageDirection::ToWidget,
                     true,
                 ));
+            } else if message.destination() == self.open_scene_merge {
+                panels.scene_merge.open(ui);
             } else if message.destination() == self.open_curve_editor {
                 panels.curve_editor.open(ui);
+            } else if message.destination() == self.open_shader_graph_editor {
+                panels.shader_graph_editor.open(ui);
             } else if message.destination() == self.absm_editor {
                 panels.absm_editor.open(ui);
             } else if message.destination() == self.animation_editor {
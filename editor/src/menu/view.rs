@@ -1,4 +1,8 @@
-use crate::menu::{create_menu_item, create_root_menu_item, Panels};
+use crate::{
+    menu::{create_menu_item, create_root_menu_item, Panels},
+    settings::Settings,
+    GameEngine,
+};
 use fyrox::{
     core::pool::Handle,
     gui::{
@@ -7,6 +11,8 @@ use fyrox::{
         window::WindowMessage,
         BuildContext, UiNode, UserInterface,
     },
+    renderer::DebugViewMode,
+    utils::log::Log,
 };
 
 pub struct ViewMenu {
@@ -19,6 +25,8 @@ pub struct ViewMenu {
     nav_mesh: Handle<UiNode>,
     audio: Handle<UiNode>,
     command_stack: Handle<UiNode>,
+    statistics: Handle<UiNode>,
+    debug_view_modes: Vec<(Handle<UiNode>, DebugViewMode)>,
 }
 
 fn switch_window_state(window: Handle<UiNode>, ui: &UserInterface, center: bool) {
@@ -40,6 +48,20 @@ impl ViewMenu {
         let nav_mesh;
         let audio;
         let command_stack;
+        let statistics;
+        let debug_view_modes = [
+            ("Lit", DebugViewMode::None),
+            ("Wireframe", DebugViewMode::Wireframe),
+            ("Albedo", DebugViewMode::Albedo),
+            ("Normals", DebugViewMode::Normals),
+            ("Roughness/Metallic", DebugViewMode::RoughnessMetallic),
+            ("Overdraw", DebugViewMode::Overdraw),
+            ("Light Count", DebugViewMode::LightCount),
+            ("Shadow Cascades", DebugViewMode::ShadowCascades),
+        ]
+        .iter()
+        .map(|(name, mode)| (create_menu_item(name, vec![], ctx), *mode))
+        .collect::<Vec<_>>();
         let menu = create_root_menu_item(
             "View",
             vec![
@@ -75,6 +97,15 @@ impl ViewMenu {
                     command_stack = create_menu_item("Command Stack Panel", vec![], ctx);
                     command_stack
                 },
+                {
+                    statistics = create_menu_item("Statistics Panel", vec![], ctx);
+                    statistics
+                },
+                create_menu_item(
+                    "Debug View",
+                    debug_view_modes.iter().map(|(item, _)| *item).collect(),
+                    ctx,
+                ),
             ],
             ctx,
         );
@@ -89,10 +120,19 @@ impl ViewMenu {
             nav_mesh,
             audio,
             command_stack,
+            statistics,
+            debug_view_modes,
         }
     }
 
-    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &UserInterface, panels: &Panels) {
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        settings: &mut Settings,
+        panels: &Panels,
+    ) {
+        let ui = &engine.user_interface;
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
             if message.destination() == self.asset_browser {
                 switch_window_state(panels.asset_window, ui, false);
@@ -110,6 +150,19 @@ impl ViewMenu {
                 switch_window_state(panels.audio_panel, ui, false);
             } else if message.destination() == self.command_stack {
                 switch_window_state(panels.command_stack_panel, ui, false);
+            } else if message.destination() == self.statistics {
+                switch_window_state(panels.statistics_panel, ui, true);
+            } else if let Some((_, mode)) = self
+                .debug_view_modes
+                .iter()
+                .find(|(item, _)| message.destination() == *item)
+            {
+                settings.graphics.quality.debug_view_mode = *mode;
+                Log::verify(
+                    engine
+                        .renderer
+                        .set_quality_settings(&settings.graphics.quality),
+                );
             }
         }
     }
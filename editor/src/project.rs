@@ -0,0 +1,347 @@
+//! Project manager. Allows to generate new game projects from templates.
+
+use crate::{gui::make_dropdown_list_option, GameEngine};
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle, scope_profile},
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        file_browser::{FileSelectorBuilder, FileSelectorMessage, Filter},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, VerticalAlignment,
+    },
+    utils::log::Log,
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// Project templates in the same order as in the style selector.
+const TEMPLATES: [(&str, &str); 3] = [("3D", "3d"), ("2D", "2d"), ("Minimal", "minimal")];
+
+/// A window that generates a new game project (a cargo workspace with game, editor and executor
+/// crates and the data folder) from one of the templates.
+pub struct ProjectWizard {
+    pub window: Handle<UiNode>,
+    name_box: Handle<UiNode>,
+    location_box: Handle<UiNode>,
+    select_location: Handle<UiNode>,
+    location_selector: Handle<UiNode>,
+    template_selector: Handle<UiNode>,
+    error_text: Handle<UiNode>,
+    create: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    name: String,
+    location: PathBuf,
+    template: usize,
+}
+
+fn make_text_mark(ctx: &mut BuildContext, text: &str, row: usize) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_margin(Thickness::uniform(1.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl ProjectWizard {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let name = "my_game".to_owned();
+        let location = env::current_dir().unwrap_or_default();
+
+        let location_selector = FileSelectorBuilder::new(
+            WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(400.0))
+                .open(false)
+                .with_title(WindowTitle::text("Select Project Location")),
+        )
+        .with_filter(Filter::new(|p: &Path| p.is_dir()))
+        .with_path(&location)
+        .build(ctx);
+
+        let name_box;
+        let location_box;
+        let select_location;
+        let template_selector;
+        let error_text;
+        let create;
+        let cancel;
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(400.0)
+                .with_height(200.0)
+                .with_min_size(Vector2::new(400.0, 200.0)),
+        )
+        .with_title(WindowTitle::text("New Project"))
+        .open(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_margin(Thickness::uniform(1.0))
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(make_text_mark(ctx, "Name", 0))
+                                .with_child({
+                                    name_box = TextBoxBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_row(0)
+                                            .on_column(1)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text(&name)
+                                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                                    .build(ctx);
+                                    name_box
+                                })
+                                .with_child(make_text_mark(ctx, "Location", 1))
+                                .with_child({
+                                    location_box = TextBoxBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_row(1)
+                                            .on_column(1)
+                                            .with_margin(Thickness::uniform(1.0))
+                                            .with_enabled(false),
+                                    )
+                                    .with_text(location.to_string_lossy())
+                                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                                    .build(ctx);
+                                    location_box
+                                })
+                                .with_child({
+                                    select_location = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_row(1)
+                                            .on_column(2)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("...")
+                                    .build(ctx);
+                                    select_location
+                                })
+                                .with_child(make_text_mark(ctx, "Template", 2))
+                                .with_child({
+                                    template_selector = DropdownListBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_row(2)
+                                            .on_column(1)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_items(
+                                        TEMPLATES
+                                            .iter()
+                                            .map(|(name, _)| make_dropdown_list_option(ctx, name))
+                                            .collect(),
+                                    )
+                                    .with_selected(0)
+                                    .build(ctx);
+                                    template_selector
+                                }),
+                        )
+                        .add_row(Row::strict(25.0))
+                        .add_row(Row::strict(25.0))
+                        .add_row(Row::strict(25.0))
+                        .add_column(Column::strict(80.0))
+                        .add_column(Column::stretch())
+                        .add_column(Column::strict(25.0))
+                        .build(ctx),
+                    )
+                    .with_child({
+                        error_text = TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(1)
+                                .with_margin(Thickness::uniform(1.0))
+                                .with_foreground(Brush::Solid(Color::RED)),
+                        )
+                        .with_wrap(WrapMode::Word)
+                        .build(ctx);
+                        error_text
+                    })
+                    .with_child(
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(2)
+                                .with_horizontal_alignment(HorizontalAlignment::Right)
+                                .with_vertical_alignment(VerticalAlignment::Bottom)
+                                .with_child({
+                                    create = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_width(80.0)
+                                            .with_height(25.0)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Create")
+                                    .build(ctx);
+                                    create
+                                })
+                                .with_child({
+                                    cancel = ButtonBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_width(80.0)
+                                            .with_height(25.0)
+                                            .with_margin(Thickness::uniform(1.0)),
+                                    )
+                                    .with_text("Cancel")
+                                    .build(ctx);
+                                    cancel
+                                }),
+                        )
+                        .with_orientation(Orientation::Horizontal)
+                        .build(ctx),
+                    ),
+            )
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .add_row(Row::auto())
+            .add_column(Column::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            window,
+            name_box,
+            location_box,
+            select_location,
+            location_selector,
+            template_selector,
+            error_text,
+            create,
+            cancel,
+            name,
+            location,
+            template: 0,
+        }
+    }
+
+    pub fn open(&self, engine: &GameEngine) {
+        engine
+            .user_interface
+            .send_message(WindowMessage::open_modal(
+                self.window,
+                MessageDirection::ToWidget,
+                true,
+            ));
+
+        self.validate(engine);
+    }
+
+    fn project_path(&self) -> PathBuf {
+        self.location.join(&self.name)
+    }
+
+    fn validate(&self, engine: &GameEngine) {
+        let error = match fyrox_template::check_name(&self.name) {
+            Ok(_) if self.project_path().exists() => {
+                format!("{} already exists!", self.project_path().display())
+            }
+            Ok(_) => Default::default(),
+            Err(err) => err,
+        };
+
+        let ui = &engine.user_interface;
+        ui.send_message(WidgetMessage::enabled(
+            self.create,
+            MessageDirection::ToWidget,
+            error.is_empty(),
+        ));
+        ui.send_message(TextMessage::text(
+            self.error_text,
+            MessageDirection::ToWidget,
+            error,
+        ));
+    }
+
+    fn close(&self, engine: &GameEngine) {
+        engine.user_interface.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    /// Handles messages of the wizard, returns a path to the root folder of a newly created project
+    /// (if any).
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+    ) -> Option<PathBuf> {
+        scope_profile!();
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.create {
+                let path = self.project_path();
+                let (_, style) = TEMPLATES[self.template];
+                match fyrox_template::init_project(&path, &self.name, style) {
+                    Ok(_) => {
+                        Log::info(format!(
+                            "Project {} was generated successfully! Use `cargo run --package editor \
+                            --release` in its folder to run the editor with your game attached.",
+                            path.display()
+                        ));
+
+                        self.close(engine);
+
+                        return Some(path);
+                    }
+                    Err(err) => {
+                        engine.user_interface.send_message(TextMessage::text(
+                            self.error_text,
+                            MessageDirection::ToWidget,
+                            format!("Unable to create the project. Reason: {}", err),
+                        ));
+                    }
+                }
+            } else if message.destination() == self.cancel {
+                self.close(engine);
+            } else if message.destination() == self.select_location {
+                engine
+                    .user_interface
+                    .send_message(WindowMessage::open_modal(
+                        self.location_selector,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.name_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.name = text.clone();
+                self.validate(engine);
+            }
+        } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.template_selector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.template = index;
+            }
+        } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
+            if message.destination() == self.location_selector {
+                if let Ok(location) = path.canonicalize() {
+                    self.location = location;
+                    engine.user_interface.send_message(TextMessage::text(
+                        self.location_box,
+                        MessageDirection::ToWidget,
+                        self.location.to_string_lossy().to_string(),
+                    ));
+                    self.validate(engine);
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -8,6 +8,7 @@ use fyrox::{
         base::Base,
         graph::{Graph, SubGraph},
         node::Node,
+        validation::{Diagnostic, DiagnosticFixUndo},
     },
 };
 
@@ -403,3 +404,35 @@ impl Command for ReplaceNodeCommand {
         self.swap(context);
     }
 }
+
+/// Fixes a problem found by scene validation, see [`Diagnostic::fix`] for more info.
+#[derive(Debug)]
+pub struct FixDiagnosticCommand {
+    diagnostic: Diagnostic,
+    undo: Option<DiagnosticFixUndo>,
+}
+
+impl FixDiagnosticCommand {
+    pub fn new(diagnostic: Diagnostic) -> Self {
+        Self {
+            diagnostic,
+            undo: None,
+        }
+    }
+}
+
+impl Command for FixDiagnosticCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Fix Scene Problem".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.undo = self.diagnostic.fix_with_undo(context.scene);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        if let Some(undo) = self.undo.take() {
+            undo.undo(context.scene);
+        }
+    }
+}
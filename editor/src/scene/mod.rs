@@ -7,7 +7,10 @@ use crate::{
         data_model::{Navmesh, NavmeshContainer, NavmeshTriangle, NavmeshVertex},
         selection::NavmeshSelection,
     },
-    scene::clipboard::Clipboard,
+    scene::{
+        clipboard::Clipboard,
+        commands::{graph::FixDiagnosticCommand, CommandGroup, SceneCommand},
+    },
     settings::debugging::DebuggingSettings,
     world::graph::selection::GraphSelection,
     GameEngine, Settings,
@@ -35,10 +38,16 @@ use fyrox::{
         node::Node,
         particle_system::ParticleSystem,
         pivot::PivotBuilder,
+        validation::DiagnosticSeverity,
         Scene,
     },
+    utils::log::Log,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::PathBuf,
 };
-use std::{collections::HashMap, fmt::Write, path::PathBuf};
 
 pub mod clipboard;
 pub mod property;
@@ -59,6 +68,9 @@ pub struct EditorScene {
     pub camera_controller: CameraController,
     pub navmeshes: NavmeshContainer,
     pub preview_camera: Handle<Node>,
+    /// Original visibility of the nodes, that were hidden by isolate mode of the world viewer.
+    /// `None` if isolate mode is off.
+    pub isolated_visibility: Option<HashMap<Handle<Node>, bool>>,
 }
 
 pub fn is_scene_needs_to_be_saved(editor_scene: Option<&EditorScene>) -> bool {
@@ -137,6 +149,7 @@ impl EditorScene {
             clipboard: Default::default(),
             has_unsaved_changes: false,
             preview_camera: Default::default(),
+            isolated_visibility: None,
         }
     }
 
@@ -144,7 +157,17 @@ impl EditorScene {
         let scene = &mut engine.scenes[self.scene];
 
         let editor_root = self.editor_objects_root;
-        let (mut pure_scene, _) = scene.clone(&mut |node, _| node != editor_root);
+        let (mut pure_scene, old_new_map) = scene.clone(&mut |node, _| node != editor_root);
+
+        // Nodes hidden by isolate mode must be saved with their original visibility.
+        if let Some(isolated_visibility) = self.isolated_visibility.as_ref() {
+            for (&handle, &visibility) in isolated_visibility.iter() {
+                let mut handle = handle;
+                if old_new_map.try_map(&mut handle) {
+                    pure_scene.graph[handle].set_visibility(visibility);
+                }
+            }
+        }
 
         // Disable all animations and state machines back.
         set_animation_enabled(&mut pure_scene, true);
@@ -153,8 +176,8 @@ impl EditorScene {
         // so before saving scene, we have to reset state of such nodes.
         for node in pure_scene.graph.linear_iter_mut() {
             if let Some(particle_system) = node.cast_mut::<ParticleSystem>() {
-                // Particle system must not save generated vertices.
-                particle_system.clear_particles();
+                // Particle system must not save generated particles and state of emitters.
+                particle_system.reset();
             }
         }
 
@@ -194,16 +217,54 @@ impl EditorScene {
         pure_scene
     }
 
+    /// Validates the scene and makes a command, that fixes trivial problems in it. Editor objects
+    /// are ignored. Returns `None` if there's nothing to fix.
+    pub fn make_fix_command(&self, engine: &GameEngine) -> Option<CommandGroup> {
+        let graph = &engine.scenes[self.scene].graph;
+        let editor_objects = graph
+            .traverse_handle_iter(self.editor_objects_root)
+            .collect::<HashSet<_>>();
+
+        let commands = engine.scenes[self.scene]
+            .validate()
+            .into_iter()
+            .filter(|d| d.can_fix() && !editor_objects.contains(&d.node))
+            .map(|d| {
+                Log::warn(format!("{} - fixed.", d));
+                SceneCommand::new(FixDiagnosticCommand::new(d))
+            })
+            .collect::<Vec<_>>();
+
+        if commands.is_empty() {
+            None
+        } else {
+            Some(CommandGroup::from(commands))
+        }
+    }
+
     pub fn save(&mut self, path: PathBuf, engine: &mut GameEngine) -> Result<String, String> {
-        // Validate first.
-        let valid = true;
+        let mut pure_scene = self.make_purified_scene(engine);
+
+        // Validate first. Trivial problems should be fixed in the scene already (see
+        // `make_fix_command`), but if something is left, it is fixed in the saved copy of the
+        // scene, the rest is reported.
+        let mut valid = true;
         let mut reason = "Scene is not saved, because validation failed:\n".to_owned();
 
+        for diagnostic in pure_scene.validate() {
+            if diagnostic.fix(&mut pure_scene) {
+                Log::warn(format!("{} - fixed in the saved scene.", diagnostic));
+            } else if diagnostic.severity == DiagnosticSeverity::Error {
+                valid = false;
+                writeln!(&mut reason, "{}", diagnostic).unwrap();
+            } else {
+                Log::warn(diagnostic.to_string());
+            }
+        }
+
         if valid {
             self.path = Some(path.clone());
 
-            let mut pure_scene = self.make_purified_scene(engine);
-
             let mut visitor = Visitor::new();
             pure_scene.save("Scene", &mut visitor).unwrap();
             if let Err(e) = visitor.save_binary(&path) {
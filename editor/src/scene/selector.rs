@@ -9,12 +9,11 @@ use fyrox::{
         grid::{Column, GridBuilder, Row},
         message::{MessageDirection, OsEvent, UiMessage},
         scroll_viewer::ScrollViewerBuilder,
+        search_bar::{SearchBarBuilder, SearchBarMessage},
         stack_panel::StackPanelBuilder,
-        text::{TextBuilder, TextMessage},
-        text_box::{TextBoxBuilder, TextCommitMode},
-        tree::{Tree, TreeBuilder, TreeRootBuilder, TreeRootMessage},
-        utils,
-        widget::{Widget, WidgetBuilder, WidgetMessage},
+        text::TextBuilder,
+        tree::{TreeBuilder, TreeRootBuilder, TreeRootMessage},
+        widget::{Widget, WidgetBuilder},
         window::{Window, WindowBuilder, WindowMessage},
         BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Orientation, Thickness,
         UiNode, UserInterface,
@@ -66,6 +65,7 @@ impl HierarchyNode {
             handle: self.handle,
         })))
         .with_items(self.children.iter().map(|c| c.make_view(ctx)).collect())
+        .with_search_text(&self.name)
         .with_content(
             TextBuilder::new(WidgetBuilder::new())
                 .with_text(make_node_name(&self.name, self.handle.into()))
@@ -96,37 +96,12 @@ struct TreeData {
 pub struct NodeSelector {
     widget: Widget,
     tree_root: Handle<UiNode>,
-    filter_text: Handle<UiNode>,
-    clear_filter: Handle<UiNode>,
+    search_bar: Handle<UiNode>,
     selected: Vec<Handle<Node>>,
 }
 
 define_widget_deref!(NodeSelector);
 
-fn apply_filter_recursive(node: Handle<UiNode>, filter: &str, ui: &UserInterface) -> bool {
-    let node_ref = ui.node(node);
-
-    let mut is_any_match = false;
-    for &child in node_ref.children() {
-        is_any_match |= apply_filter_recursive(child, filter, ui)
-    }
-
-    if let Some(data) = node_ref
-        .query_component::<Tree>()
-        .and_then(|n| n.user_data_ref::<TreeData>())
-    {
-        is_any_match |= data.name.to_lowercase().contains(filter);
-
-        ui.send_message(WidgetMessage::visibility(
-            node,
-            MessageDirection::ToWidget,
-            is_any_match,
-        ));
-    }
-
-    is_any_match
-}
-
 impl Control for NodeSelector {
     fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
         if type_id == TypeId::of::<Self>() {
@@ -160,11 +135,15 @@ impl Control for NodeSelector {
                     }
                 }
             }
-        } else if let Some(TextMessage::Text(filter_text)) = message.data() {
-            if message.destination() == self.filter_text
+        } else if let Some(SearchBarMessage::Text(filter_text)) = message.data() {
+            if message.destination() == self.search_bar
                 && message.direction() == MessageDirection::FromWidget
             {
-                apply_filter_recursive(self.tree_root, &filter_text.to_lowercase(), ui);
+                ui.send_message(TreeRootMessage::filter(
+                    self.tree_root,
+                    MessageDirection::ToWidget,
+                    filter_text.clone(),
+                ));
             }
         } else if let Some(TreeRootMessage::Selected(selection)) = message.data() {
             if message.destination() == self.tree_root
@@ -179,14 +158,6 @@ impl Control for NodeSelector {
                         .collect(),
                 ));
             }
-        } else if let Some(ButtonMessage::Click) = message.data() {
-            if message.destination() == self.clear_filter {
-                ui.send_message(TextMessage::text(
-                    self.filter_text,
-                    MessageDirection::ToWidget,
-                    Default::default(),
-                ));
-            }
         }
     }
 }
@@ -218,43 +189,20 @@ impl NodeSelectorBuilder {
         let tree_root = TreeRootBuilder::new(WidgetBuilder::new())
             .with_items(items)
             .build(ctx);
-        let filter_text;
-        let clear_filter;
+        let search_bar;
 
         let content = GridBuilder::new(
             WidgetBuilder::new()
-                .with_child(
-                    GridBuilder::new(
+                .with_child({
+                    search_bar = SearchBarBuilder::new(
                         WidgetBuilder::new()
-                            .with_child({
-                                filter_text = TextBoxBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_column(0)
-                                        .on_row(0)
-                                        .with_margin(Thickness::uniform(1.0)),
-                                )
-                                .with_text_commit_mode(TextCommitMode::Immediate)
-                                .build(ctx);
-                                filter_text
-                            })
-                            .with_child({
-                                clear_filter = ButtonBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_width(20.0)
-                                        .on_column(1)
-                                        .on_row(0)
-                                        .with_margin(Thickness::uniform(1.0)),
-                                )
-                                .with_content(utils::make_cross(ctx, 10.0, 2.0))
-                                .build(ctx);
-                                clear_filter
-                            }),
+                            .on_column(0)
+                            .on_row(0)
+                            .with_margin(Thickness::uniform(1.0)),
                     )
-                    .add_row(Row::strict(22.0))
-                    .add_column(Column::stretch())
-                    .add_column(Column::strict(22.0))
-                    .build(ctx),
-                )
+                    .build(ctx);
+                    search_bar
+                })
                 .with_child(
                     BorderBuilder::new(
                         WidgetBuilder::new()
@@ -280,9 +228,7 @@ impl NodeSelectorBuilder {
         let selector = NodeSelector {
             widget: self.widget_builder.with_child(content).build(),
             tree_root,
-            filter_text,
-            clear_filter,
-
+            search_bar,
             selected: Default::default(),
         };
 
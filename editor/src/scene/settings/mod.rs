@@ -9,7 +9,9 @@ use fyrox::{
     gui::{
         inspector::{
             editors::{
-                inspectable::InspectablePropertyEditorDefinition, PropertyEditorDefinitionContainer,
+                collection::VecCollectionPropertyEditorDefinition,
+                inspectable::InspectablePropertyEditorDefinition,
+                PropertyEditorDefinitionContainer,
             },
             InspectorBuilder, InspectorContext, InspectorMessage,
         },
@@ -22,7 +24,7 @@ use fyrox::{
     scene::{
         dim2,
         graph::{
-            physics::{IntegrationParameters, PhysicsWorld},
+            physics::{IntegrationParameters, LayerCollisionMatrix, PhysicsWorld},
             Graph,
         },
     },
@@ -59,6 +61,8 @@ impl SceneSettingsWindow {
         container.insert(InspectablePropertyEditorDefinition::<Graph>::new());
         container.insert(InspectablePropertyEditorDefinition::<IntegrationParameters>::new());
         container.insert(InspectablePropertyEditorDefinition::<PhysicsWorld>::new());
+        container.insert(InspectablePropertyEditorDefinition::<LayerCollisionMatrix>::new());
+        container.insert(VecCollectionPropertyEditorDefinition::<u32>::new());
         container.insert(InspectablePropertyEditorDefinition::<
             dim2::physics::PhysicsWorld,
         >::new());
@@ -1,9 +1,10 @@
 use crate::{
     camera::PickingOptions, gui::make_dropdown_list_option,
-    gui::make_dropdown_list_option_with_height, load_image, utils::enable_widget, AddModelCommand,
-    AssetItem, AssetKind, BuildProfile, ChangeSelectionCommand, CommandGroup, DropdownListBuilder,
-    EditorScene, GameEngine, GraphSelection, InteractionMode, InteractionModeKind, Message, Mode,
-    SceneCommand, Selection, SetMeshTextureCommand, Settings,
+    gui::make_dropdown_list_option_with_height, interaction::custom_gizmo::CustomGizmos,
+    load_image, utils::enable_widget, AddModelCommand, AssetItem, AssetKind, BuildProfile,
+    ChangeSelectionCommand, CommandGroup, DropdownListBuilder, EditorScene, GameEngine,
+    GraphSelection, InteractionMode, InteractionModeKind, Message, Mode, SceneCommand, Selection,
+    SetMeshTextureCommand, Settings,
 };
 use fyrox::{
     core::{
@@ -17,7 +18,7 @@ use fyrox::{
     fxhash::FxHashSet,
     gui::{
         border::BorderBuilder,
-        brush::{Brush, GradientPoint},
+        brush::{Brush, BrushCoordinates, GradientPoint},
         button::{Button, ButtonBuilder, ButtonContent, ButtonMessage},
         canvas::CanvasBuilder,
         decorator::{DecoratorBuilder, DecoratorMessage},
@@ -102,6 +103,7 @@ fn make_interaction_mode_button(
                         color: COLOR_DARKEST,
                     },
                 ],
+                coordinates: BrushCoordinates::Relative,
             }))
             .with_stroke_thickness(Thickness::uniform(1.0)),
         )
@@ -455,6 +457,7 @@ impl SceneViewer {
         engine: &mut Engine,
         editor_scene: Option<&mut EditorScene>,
         interaction_mode: Option<&mut Box<dyn InteractionMode>>,
+        custom_gizmos: &mut CustomGizmos,
         settings: &Settings,
         mode: &Mode,
     ) {
@@ -536,6 +539,7 @@ impl SceneViewer {
                         pos,
                         editor_scene,
                         interaction_mode,
+                        custom_gizmos,
                         engine,
                         settings,
                     ),
@@ -544,6 +548,7 @@ impl SceneViewer {
                         pos,
                         editor_scene,
                         interaction_mode,
+                        custom_gizmos,
                         engine,
                         settings,
                     ),
@@ -552,9 +557,14 @@ impl SceneViewer {
                             .camera_controller
                             .on_mouse_wheel(amount, &mut engine.scenes[editor_scene.scene].graph);
                     }
-                    WidgetMessage::MouseMove { pos, .. } => {
-                        self.on_mouse_move(pos, editor_scene, interaction_mode, engine, settings)
-                    }
+                    WidgetMessage::MouseMove { pos, .. } => self.on_mouse_move(
+                        pos,
+                        editor_scene,
+                        interaction_mode,
+                        custom_gizmos,
+                        engine,
+                        settings,
+                    ),
                     WidgetMessage::KeyUp(key) => {
                         if self.on_key_up(key, editor_scene, interaction_mode, engine) {
                             message.set_handled(true);
@@ -661,7 +671,7 @@ impl SceneViewer {
                                         .cast::<Camera>()
                                     {
                                         let normal = match camera.projection() {
-                                            Projection::Perspective(_) => {
+                                            Projection::Perspective(_) | Projection::OffAxis(_) => {
                                                 Vector3::new(0.0, 1.0, 0.0)
                                             }
                                             Projection::Orthographic(_) => {
@@ -721,6 +731,17 @@ impl SceneViewer {
         }
     }
 
+    pub fn set_build_profile(&self, ui: &UserInterface, profile: BuildProfile) {
+        ui.send_message(DropdownListMessage::selection(
+            self.build_profile,
+            MessageDirection::ToWidget,
+            Some(match profile {
+                BuildProfile::Debug => 0,
+                BuildProfile::Release => 1,
+            }),
+        ));
+    }
+
     pub fn set_render_target(&self, ui: &UserInterface, render_target: Option<Texture>) {
         ui.send_message(ImageMessage::texture(
             self.frame,
@@ -797,6 +818,7 @@ impl SceneViewer {
         pos: Vector2<f32>,
         editor_scene: &mut EditorScene,
         active_interaction_mode: Option<&mut Box<dyn InteractionMode>>,
+        custom_gizmos: &mut CustomGizmos,
         engine: &mut Engine,
         settings: &Settings,
     ) {
@@ -809,7 +831,9 @@ impl SceneViewer {
             .on_mouse_move(mouse_offset, &settings.camera);
         let rel_pos = pos - screen_bounds.position;
 
-        if let Some(interaction_mode) = active_interaction_mode {
+        if custom_gizmos.on_mouse_move(editor_scene, engine, rel_pos, screen_bounds.size) {
+            // The mouse is captured by a custom gizmo.
+        } else if let Some(interaction_mode) = active_interaction_mode {
             interaction_mode.on_mouse_move(
                 mouse_offset,
                 rel_pos,
@@ -830,6 +854,7 @@ impl SceneViewer {
         pos: Vector2<f32>,
         editor_scene: &mut EditorScene,
         active_interaction_mode: Option<&mut Box<dyn InteractionMode>>,
+        custom_gizmos: &mut CustomGizmos,
         engine: &mut Engine,
         settings: &Settings,
     ) {
@@ -839,8 +864,10 @@ impl SceneViewer {
 
         if button == MouseButton::Left {
             self.click_mouse_pos = None;
-            if let Some(current_im) = active_interaction_mode {
-                let rel_pos = pos - screen_bounds.position;
+            let rel_pos = pos - screen_bounds.position;
+            if custom_gizmos.on_mouse_up(editor_scene, engine, rel_pos, screen_bounds.size) {
+                // The interaction with a custom gizmo is finished.
+            } else if let Some(current_im) = active_interaction_mode {
                 current_im.on_left_mouse_button_up(
                     editor_scene,
                     engine,
@@ -860,6 +887,7 @@ impl SceneViewer {
         pos: Vector2<f32>,
         editor_scene: &mut EditorScene,
         active_interaction_mode: Option<&mut Box<dyn InteractionMode>>,
+        custom_gizmos: &mut CustomGizmos,
         engine: &mut Engine,
         settings: &Settings,
     ) {
@@ -868,9 +896,10 @@ impl SceneViewer {
         let screen_bounds = self.frame_bounds(&engine.user_interface);
 
         if button == MouseButton::Left {
-            if let Some(current_im) = active_interaction_mode {
-                let rel_pos = pos - screen_bounds.position;
-
+            let rel_pos = pos - screen_bounds.position;
+            if custom_gizmos.on_mouse_down(editor_scene, engine, rel_pos, screen_bounds.size) {
+                // A custom gizmo has captured the mouse.
+            } else if let Some(current_im) = active_interaction_mode {
                 self.click_mouse_pos = Some(rel_pos);
 
                 current_im.on_left_mouse_button_down(
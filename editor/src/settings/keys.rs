@@ -0,0 +1,593 @@
+//! Key bindings of the editor commands. Every bindable command is described by a definition with
+//! its default hot key, only hot keys that differ from the defaults are stored in the settings.
+
+use crate::GameEngine;
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{KeyCode, KeyboardModifiers, MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment, BRUSH_FOREGROUND,
+    },
+    utils::log::Log,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// A key with a set of modifiers, that must be pressed to activate a command.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct HotKey {
+    /// Key of the hot key, `None` means that the hot key is not set.
+    pub code: Option<KeyCode>,
+    pub control: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl HotKey {
+    pub const NONE: Self = Self {
+        code: None,
+        control: false,
+        shift: false,
+        alt: false,
+    };
+
+    pub fn from_key_code(code: KeyCode) -> Self {
+        Self {
+            code: Some(code),
+            ..Self::NONE
+        }
+    }
+
+    pub fn ctrl(code: KeyCode) -> Self {
+        Self {
+            code: Some(code),
+            control: true,
+            ..Self::NONE
+        }
+    }
+
+    pub fn new(code: KeyCode, modifiers: KeyboardModifiers) -> Self {
+        Self {
+            code: Some(code),
+            control: modifiers.control,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.code.is_some()
+    }
+
+    pub fn matches(&self, code: KeyCode, modifiers: KeyboardModifiers) -> bool {
+        self.code == Some(code)
+            && self.control == modifiers.control
+            && self.shift == modifiers.shift
+            && self.alt == modifiers.alt
+    }
+}
+
+impl Display for HotKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            Some(code) => {
+                if self.control {
+                    write!(f, "Ctrl+")?;
+                }
+                if self.shift {
+                    write!(f, "Shift+")?;
+                }
+                if self.alt {
+                    write!(f, "Alt+")?;
+                }
+                write!(f, "{}", code.as_ref())
+            }
+            None => write!(f, "None"),
+        }
+    }
+}
+
+impl FromStr for HotKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hot_key = HotKey::NONE;
+
+        if s == "None" {
+            return Ok(hot_key);
+        }
+
+        for part in s.split('+') {
+            match part {
+                "Ctrl" => hot_key.control = true,
+                "Shift" => hot_key.shift = true,
+                "Alt" => hot_key.alt = true,
+                _ => {
+                    if hot_key.code.is_some() {
+                        return Err(format!("Hot key {} has more than one key!", s));
+                    }
+                    hot_key.code = Some(
+                        KeyCode::from_str(part).map_err(|_| format!("Unknown key {}!", part))?,
+                    );
+                }
+            }
+        }
+
+        if hot_key.code.is_none() {
+            Err(format!("Hot key {} has no key!", s))
+        } else {
+            Ok(hot_key)
+        }
+    }
+}
+
+// Hot keys are stored as strings, so the settings file could be edited by hand.
+impl Serialize for HotKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for HotKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        HotKey::from_str(&string).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Description of a command that could be bound to a hot key.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyBindingDefinition {
+    /// Unique id of the command. It is used to store the hot key in the settings.
+    pub id: String,
+    /// Human-readable name of the command.
+    pub name: String,
+    pub default: HotKey,
+}
+
+fn built_in_definitions() -> Vec<KeyBindingDefinition> {
+    [
+        ("undo", "Undo", HotKey::ctrl(KeyCode::Z)),
+        ("redo", "Redo", HotKey::ctrl(KeyCode::Y)),
+        (
+            "select_mode",
+            "Select Mode",
+            HotKey::from_key_code(KeyCode::Key1),
+        ),
+        (
+            "move_mode",
+            "Move Mode",
+            HotKey::from_key_code(KeyCode::Key2),
+        ),
+        (
+            "rotate_mode",
+            "Rotate Mode",
+            HotKey::from_key_code(KeyCode::Key3),
+        ),
+        (
+            "scale_mode",
+            "Scale Mode",
+            HotKey::from_key_code(KeyCode::Key4),
+        ),
+        (
+            "navmesh_mode",
+            "Navmesh Mode",
+            HotKey::from_key_code(KeyCode::Key5),
+        ),
+        (
+            "terrain_mode",
+            "Terrain Mode",
+            HotKey::from_key_code(KeyCode::Key6),
+        ),
+        ("new_scene", "New Scene", HotKey::ctrl(KeyCode::N)),
+        ("load_scene", "Load Scene", HotKey::ctrl(KeyCode::L)),
+        ("save_scene", "Save Scene", HotKey::ctrl(KeyCode::S)),
+        ("close_scene", "Close Scene", HotKey::ctrl(KeyCode::Q)),
+        ("copy_selection", "Copy Selection", HotKey::ctrl(KeyCode::C)),
+        ("paste", "Paste", HotKey::ctrl(KeyCode::V)),
+        (
+            "remove_selection",
+            "Remove Selection",
+            HotKey::from_key_code(KeyCode::Delete),
+        ),
+    ]
+    .into_iter()
+    .map(|(id, name, default)| KeyBindingDefinition {
+        id: id.to_string(),
+        name: name.to_string(),
+        default,
+    })
+    .collect()
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct KeyBindings {
+    #[serde(skip, default = "built_in_definitions")]
+    definitions: Vec<KeyBindingDefinition>,
+    // Hot keys that differ from the defaults.
+    overrides: BTreeMap<String, HotKey>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            definitions: built_in_definitions(),
+            overrides: Default::default(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Registers a new bindable command. Does nothing if there is a command with the same id
+    /// already.
+    pub fn register<I, N>(&mut self, id: I, name: N, default: HotKey)
+    where
+        I: Into<String>,
+        N: Into<String>,
+    {
+        let id = id.into();
+        if self.definitions.iter().all(|d| d.id != id) {
+            self.definitions.push(KeyBindingDefinition {
+                id,
+                name: name.into(),
+                default,
+            });
+        }
+    }
+
+    /// Restores default hot keys of every command.
+    pub fn reset(&mut self) {
+        self.overrides.clear();
+    }
+
+    pub fn definitions(&self) -> &[KeyBindingDefinition] {
+        &self.definitions
+    }
+
+    /// Returns current hot key of a command.
+    pub fn hot_key(&self, id: &str) -> HotKey {
+        if let Some(hot_key) = self.overrides.get(id) {
+            *hot_key
+        } else {
+            self.definitions
+                .iter()
+                .find(|d| d.id == id)
+                .map(|d| d.default)
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn set_hot_key(&mut self, id: &str, hot_key: HotKey) {
+        let default = self
+            .definitions
+            .iter()
+            .find(|d| d.id == id)
+            .map(|d| d.default);
+        if default == Some(hot_key) {
+            self.overrides.remove(id);
+        } else {
+            self.overrides.insert(id.to_string(), hot_key);
+        }
+    }
+
+    /// Returns ids of every command that is bound to the same hot key as the given command.
+    pub fn conflicts(&self, id: &str) -> Vec<&str> {
+        let hot_key = self.hot_key(id);
+        if !hot_key.is_set() {
+            return vec![];
+        }
+        self.definitions
+            .iter()
+            .filter(|d| d.id != id && self.hot_key(&d.id) == hot_key)
+            .map(|d| d.id.as_str())
+            .collect()
+    }
+
+    /// Returns id of a command that is bound to the given key and modifiers.
+    pub fn find(&self, code: KeyCode, modifiers: KeyboardModifiers) -> Option<&str> {
+        self.definitions
+            .iter()
+            .find(|d| self.hot_key(&d.id).matches(code, modifiers))
+            .map(|d| d.id.as_str())
+    }
+}
+
+struct KeyBindingView {
+    id: String,
+    button: Handle<UiNode>,
+    text: Handle<UiNode>,
+}
+
+/// A window that allows to rebind editor commands.
+pub struct KeyBindingsWindow {
+    pub window: Handle<UiNode>,
+    list: Handle<UiNode>,
+    reset: Handle<UiNode>,
+    views: Vec<KeyBindingView>,
+    // Id of a command that waits for a new hot key.
+    listening: Option<String>,
+}
+
+fn conflict_brush(is_conflicting: bool) -> Brush {
+    if is_conflicting {
+        Brush::Solid(Color::RED)
+    } else {
+        BRUSH_FOREGROUND
+    }
+}
+
+impl KeyBindingsWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let list;
+        let reset;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(500.0))
+            .open(false)
+            .with_title(WindowTitle::text("Key Bindings"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            ScrollViewerBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(2.0))
+                                    .on_row(0),
+                            )
+                            .with_content({
+                                list = StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+                                list
+                            })
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        reset = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Default")
+                                        .build(ctx);
+                                        reset
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            list,
+            reset,
+            views: Default::default(),
+            listening: None,
+        }
+    }
+
+    pub fn open(&mut self, ui: &mut UserInterface, key_bindings: &KeyBindings) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        self.rebuild(ui, key_bindings);
+    }
+
+    fn rebuild(&mut self, ui: &mut UserInterface, key_bindings: &KeyBindings) {
+        for view in self.views.drain(..) {
+            ui.send_message(WidgetMessage::remove(
+                ui.node(view.button).parent(),
+                MessageDirection::ToWidget,
+            ));
+        }
+        self.listening = None;
+
+        let ctx = &mut ui.build_ctx();
+        let mut rows = Vec::new();
+        for definition in key_bindings.definitions() {
+            let text;
+            let button = ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(1)
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_content({
+                text = TextBuilder::new(WidgetBuilder::new().with_foreground(conflict_brush(
+                    !key_bindings.conflicts(&definition.id).is_empty(),
+                )))
+                .with_text(key_bindings.hot_key(&definition.id).to_string())
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx);
+                text
+            })
+            .build(ctx);
+
+            rows.push(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new().on_column(0))
+                                .with_text(&definition.name)
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                        )
+                        .with_child(button),
+                )
+                .add_row(Row::strict(24.0))
+                .add_column(Column::stretch())
+                .add_column(Column::strict(150.0))
+                .build(ctx),
+            );
+
+            self.views.push(KeyBindingView {
+                id: definition.id.clone(),
+                button,
+                text,
+            });
+        }
+
+        for row in rows {
+            ui.send_message(WidgetMessage::link(
+                row,
+                MessageDirection::ToWidget,
+                self.list,
+            ));
+        }
+    }
+
+    fn sync_to_model(&self, ui: &UserInterface, key_bindings: &KeyBindings) {
+        for view in self.views.iter() {
+            ui.send_message(TextMessage::text(
+                view.text,
+                MessageDirection::ToWidget,
+                key_bindings.hot_key(&view.id).to_string(),
+            ));
+            ui.send_message(WidgetMessage::foreground(
+                view.text,
+                MessageDirection::ToWidget,
+                conflict_brush(!key_bindings.conflicts(&view.id).is_empty()),
+            ));
+        }
+    }
+
+    pub fn handle_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        key_bindings: &mut KeyBindings,
+    ) {
+        let ui = &mut engine.user_interface;
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.reset {
+                key_bindings.reset();
+                self.sync_to_model(ui, key_bindings);
+            } else if let Some(view) = self
+                .views
+                .iter()
+                .find(|v| v.button == message.destination())
+            {
+                self.sync_to_model(ui, key_bindings);
+                ui.send_message(TextMessage::text(
+                    view.text,
+                    MessageDirection::ToWidget,
+                    "Press a key...".to_string(),
+                ));
+                self.listening = Some(view.id.clone());
+            }
+        } else if let Some(WidgetMessage::KeyDown(code)) = message.data() {
+            if let Some(id) = self.listening.clone() {
+                // Modifiers alone can't be hot keys.
+                if matches!(
+                    code,
+                    KeyCode::LControl
+                        | KeyCode::RControl
+                        | KeyCode::LShift
+                        | KeyCode::RShift
+                        | KeyCode::LAlt
+                        | KeyCode::RAlt
+                ) {
+                    return;
+                }
+
+                // Prevent the editor from executing a command bound to the key.
+                message.set_handled(true);
+                self.listening = None;
+
+                match code {
+                    KeyCode::Escape => {
+                        self.sync_to_model(ui, key_bindings);
+                        return;
+                    }
+                    KeyCode::Backspace => key_bindings.set_hot_key(&id, HotKey::NONE),
+                    _ => key_bindings.set_hot_key(&id, HotKey::new(*code, ui.keyboard_modifiers())),
+                }
+
+                let conflicts = key_bindings.conflicts(&id);
+                if !conflicts.is_empty() {
+                    Log::warn(format!(
+                        "Hot key {} of {} command is also used by {:?}!",
+                        key_bindings.hot_key(&id),
+                        id,
+                        conflicts
+                    ));
+                }
+
+                self.sync_to_model(ui, key_bindings);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::settings::keys::{HotKey, KeyBindings};
+    use fyrox::gui::message::{KeyCode, KeyboardModifiers};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_hot_key_string_round_trip() {
+        let hot_key = HotKey {
+            code: Some(KeyCode::S),
+            control: true,
+            shift: true,
+            alt: false,
+        };
+        assert_eq!(hot_key.to_string(), "Ctrl+Shift+S");
+        assert_eq!(HotKey::from_str("Ctrl+Shift+S"), Ok(hot_key));
+        assert_eq!(HotKey::from_str("None"), Ok(HotKey::NONE));
+        assert!(HotKey::from_str("Ctrl+").is_err());
+        assert!(HotKey::from_str("A+B").is_err());
+    }
+
+    #[test]
+    fn test_key_bindings_conflicts() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.register("custom", "Custom", HotKey::ctrl(KeyCode::K));
+        assert!(key_bindings.conflicts("custom").is_empty());
+
+        key_bindings.set_hot_key("custom", HotKey::ctrl(KeyCode::Z));
+        assert_eq!(key_bindings.conflicts("custom"), vec!["undo"]);
+        assert_eq!(key_bindings.conflicts("undo"), vec!["custom"]);
+
+        let modifiers = KeyboardModifiers {
+            control: true,
+            ..Default::default()
+        };
+        assert_eq!(key_bindings.find(KeyCode::K, modifiers), None);
+
+        // Setting the default value removes the override.
+        key_bindings.set_hot_key("custom", HotKey::ctrl(KeyCode::K));
+        assert_eq!(key_bindings, {
+            let mut default = KeyBindings::default();
+            default.register("custom", "Custom", HotKey::ctrl(KeyCode::K));
+            default
+        });
+        assert_eq!(key_bindings.find(KeyCode::K, modifiers), Some("custom"));
+    }
+}
@@ -2,9 +2,15 @@ use crate::{
     inspector::editors::make_property_editors_container,
     settings::navmesh::NavmeshSettings,
     settings::{
-        camera::CameraSettings, debugging::DebuggingSettings, graphics::GraphicsSettings,
-        model::ModelSettings, move_mode::MoveInteractionModeSettings, recent::RecentFiles,
-        rotate_mode::RotateInteractionModeSettings, selection::SelectionSettings,
+        camera::CameraSettings,
+        debugging::DebuggingSettings,
+        graphics::GraphicsSettings,
+        keys::{KeyBindings, KeyBindingsWindow},
+        model::ModelSettings,
+        move_mode::MoveInteractionModeSettings,
+        recent::RecentFiles,
+        rotate_mode::RotateInteractionModeSettings,
+        selection::SelectionSettings,
     },
     GameEngine, Message, MSG_SYNC_FLAG,
 };
@@ -38,6 +44,7 @@ use std::{fs::File, path::PathBuf, rc::Rc, sync::mpsc::Sender};
 pub mod camera;
 pub mod debugging;
 pub mod graphics;
+pub mod keys;
 pub mod model;
 pub mod move_mode;
 pub mod navmesh;
@@ -49,7 +56,9 @@ pub struct SettingsWindow {
     window: Handle<UiNode>,
     ok: Handle<UiNode>,
     default: Handle<UiNode>,
+    key_bindings: Handle<UiNode>,
     inspector: Handle<UiNode>,
+    key_bindings_window: KeyBindingsWindow,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Clone, Default, Debug, Reflect)]
@@ -64,6 +73,9 @@ pub struct Settings {
     pub navmesh: NavmeshSettings,
     #[reflect(hidden)]
     pub recent: RecentFiles,
+    #[serde(default)]
+    #[reflect(hidden)]
+    pub key_bindings: KeyBindings,
 }
 
 #[derive(Debug)]
@@ -145,6 +157,7 @@ impl SettingsWindow {
     pub fn new(engine: &mut GameEngine) -> Self {
         let ok;
         let default;
+        let key_bindings;
 
         let ctx = &mut engine.user_interface.build_ctx();
 
@@ -170,6 +183,16 @@ impl SettingsWindow {
                                 WidgetBuilder::new()
                                     .on_row(1)
                                     .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        key_bindings = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Key Bindings...")
+                                        .build(ctx);
+                                        key_bindings
+                                    })
                                     .with_child({
                                         default = ButtonBuilder::new(
                                             WidgetBuilder::new()
@@ -202,11 +225,15 @@ impl SettingsWindow {
             )
             .build(ctx);
 
+        let key_bindings_window = KeyBindingsWindow::new(ctx);
+
         Self {
             window,
             ok,
             default,
+            key_bindings,
             inspector,
+            key_bindings_window,
         }
     }
 
@@ -247,6 +274,9 @@ impl SettingsWindow {
 
         let old_settings = settings.clone();
 
+        self.key_bindings_window
+            .handle_message(message, engine, &mut settings.key_bindings);
+
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.ok {
                 engine.user_interface.send_message(WindowMessage::close(
@@ -254,8 +284,17 @@ impl SettingsWindow {
                     MessageDirection::ToWidget,
                 ));
             } else if message.destination() == self.default {
-                *settings = Default::default();
+                // Keep commands registered by tools.
+                let mut key_bindings = std::mem::take(&mut settings.key_bindings);
+                key_bindings.reset();
+                *settings = Settings {
+                    key_bindings,
+                    ..Default::default()
+                };
                 self.sync_to_model(&mut engine.user_interface, settings, sender);
+            } else if message.destination() == self.key_bindings {
+                self.key_bindings_window
+                    .open(&mut engine.user_interface, &settings.key_bindings);
             }
         } else if let Some(InspectorMessage::PropertyChanged(property_changed)) = message.data() {
             if message.destination() == self.inspector {
@@ -0,0 +1,692 @@
+use crate::{
+    menu::{create_menu_item, create_root_menu_item},
+    utils::create_file_selector,
+};
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    engine::Engine,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        file_browser::{FileBrowserMode, FileSelectorMessage},
+        grid::{Column, GridBuilder, Row},
+        menu::{MenuBuilder, MenuItemMessage},
+        message::{MessageDirection, UiMessage},
+        node_graph::{
+            connection::ConnectionBuilder,
+            node::{GraphNode, GraphNodeBuilder},
+            socket::{Socket, SocketBuilder, SocketDirection},
+            NodeGraph, NodeGraphBuilder, NodeGraphMessage,
+        },
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    material::{
+        shader::SamplerFallback,
+        shader_graph::{
+            BinaryOperator, ShaderGraph, ShaderGraphConnection, ShaderGraphInput, ShaderGraphNode,
+            ShaderGraphNodeKind, ShaderGraphValue, UnaryOperator,
+        },
+    },
+    utils::log::Log,
+};
+use std::path::PathBuf;
+
+struct FileMenu {
+    new: Handle<UiNode>,
+    load: Handle<UiNode>,
+    save: Handle<UiNode>,
+    export: Handle<UiNode>,
+}
+
+struct EditMenu {
+    delete: Handle<UiNode>,
+}
+
+struct Menu {
+    file: FileMenu,
+    edit: EditMenu,
+    // Every item of "Add" menu with a template of a node that will be added.
+    add: Vec<(Handle<UiNode>, ShaderGraphNodeKind)>,
+}
+
+enum NodeFieldKind {
+    Name,
+    Value,
+}
+
+// Maps a widget of a node with a field of respective node of the model.
+struct NodeField {
+    widget: Handle<UiNode>,
+    node: usize,
+    kind: NodeFieldKind,
+}
+
+pub struct ShaderGraphEditor {
+    window: Handle<UiNode>,
+    node_graph: Handle<UiNode>,
+    status: Handle<UiNode>,
+    menu: Menu,
+    load_file_selector: Handle<UiNode>,
+    save_file_selector: Handle<UiNode>,
+    export_file_selector: Handle<UiNode>,
+    graph: ShaderGraph,
+    path: PathBuf,
+    // View nodes, their indices match indices of the nodes in the model.
+    nodes: Vec<Handle<UiNode>>,
+    connections: Vec<(Handle<UiNode>, ShaderGraphConnection)>,
+    fields: Vec<NodeField>,
+    selection: Vec<Handle<UiNode>>,
+}
+
+fn templates(group: &str) -> Vec<ShaderGraphNodeKind> {
+    match group {
+        "Values" => vec![
+            ShaderGraphNodeKind::Constant(ShaderGraphValue::Float(0.0)),
+            ShaderGraphNodeKind::Property {
+                name: "value".to_owned(),
+                value: ShaderGraphValue::Float(0.0),
+            },
+            ShaderGraphNodeKind::Property {
+                name: "color".to_owned(),
+                value: ShaderGraphValue::Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                },
+            },
+            ShaderGraphNodeKind::TextureSample {
+                name: "texture".to_owned(),
+                fallback: SamplerFallback::White,
+            },
+        ],
+        "Inputs" => [
+            ShaderGraphInput::TexCoord,
+            ShaderGraphInput::SecondTexCoord,
+            ShaderGraphInput::WorldPosition,
+            ShaderGraphInput::WorldNormal,
+            ShaderGraphInput::ViewDirection,
+        ]
+        .iter()
+        .map(|i| ShaderGraphNodeKind::Input(*i))
+        .collect(),
+        "Math" => [
+            BinaryOperator::Add,
+            BinaryOperator::Subtract,
+            BinaryOperator::Multiply,
+            BinaryOperator::Divide,
+            BinaryOperator::Min,
+            BinaryOperator::Max,
+            BinaryOperator::Power,
+            BinaryOperator::Dot,
+        ]
+        .iter()
+        .map(|op| ShaderGraphNodeKind::Binary(*op))
+        .chain(
+            [
+                UnaryOperator::Negate,
+                UnaryOperator::OneMinus,
+                UnaryOperator::Abs,
+                UnaryOperator::Sin,
+                UnaryOperator::Cos,
+                UnaryOperator::Fract,
+                UnaryOperator::Floor,
+                UnaryOperator::Sqrt,
+                UnaryOperator::Saturate,
+                UnaryOperator::Normalize,
+                UnaryOperator::Length,
+            ]
+            .iter()
+            .map(|op| ShaderGraphNodeKind::Unary(*op)),
+        )
+        .chain(std::iter::once(ShaderGraphNodeKind::Lerp))
+        .collect(),
+        "Vector" => vec![ShaderGraphNodeKind::Split, ShaderGraphNodeKind::Combine],
+        "Output" => vec![ShaderGraphNodeKind::PbrOutput { alpha_cutoff: 0.0 }],
+        _ => vec![],
+    }
+}
+
+fn default_graph() -> ShaderGraph {
+    let mut graph = ShaderGraph::new("ShaderGraph");
+    graph.add_node_at(
+        ShaderGraphNodeKind::PbrOutput { alpha_cutoff: 0.0 },
+        Vector2::new(400.0, 100.0),
+    );
+    graph
+}
+
+impl ShaderGraphEditor {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let load_file_selector = create_file_selector(ctx, "shadergraph", FileBrowserMode::Open);
+        let save_file_selector = create_file_selector(
+            ctx,
+            "shadergraph",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("unnamed.shadergraph"),
+            },
+        );
+        let export_file_selector = create_file_selector(
+            ctx,
+            "shader",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("unnamed.shader"),
+            },
+        );
+
+        let new = create_menu_item("New", vec![], ctx);
+        let load = create_menu_item("Load...", vec![], ctx);
+        let save = create_menu_item("Save", vec![], ctx);
+        let export = create_menu_item("Export Shader...", vec![], ctx);
+        let delete = create_menu_item("Delete Selected", vec![], ctx);
+
+        let mut add = Vec::new();
+        let mut groups = Vec::new();
+        for group in ["Values", "Inputs", "Math", "Vector", "Output"] {
+            let mut items = Vec::new();
+            for template in templates(group) {
+                let item = create_menu_item(&template.name(), vec![], ctx);
+                items.push(item);
+                add.push((item, template));
+            }
+            groups.push(create_menu_item(group, items, ctx));
+        }
+
+        let menu = MenuBuilder::new(WidgetBuilder::new())
+            .with_items(vec![
+                create_root_menu_item("File", vec![new, load, save, export], ctx),
+                create_root_menu_item("Edit", vec![delete], ctx),
+                create_root_menu_item("Add", groups, ctx),
+            ])
+            .build(ctx);
+
+        let node_graph = NodeGraphBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let status = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(800.0).with_height(600.0))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(menu)
+                        .with_child(
+                            BorderBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_background(Brush::Solid(Color::opaque(20, 20, 20)))
+                                    .with_child(node_graph),
+                            )
+                            .build(ctx),
+                        )
+                        .with_child(status),
+                )
+                .add_row(Row::strict(25.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(22.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .with_title(WindowTitle::text("Shader Graph Editor"))
+            .build(ctx);
+
+        Self {
+            window,
+            node_graph,
+            status,
+            menu: Menu {
+                file: FileMenu {
+                    new,
+                    load,
+                    save,
+                    export,
+                },
+                edit: EditMenu { delete },
+                add,
+            },
+            load_file_selector,
+            save_file_selector,
+            export_file_selector,
+            graph: default_graph(),
+            path: Default::default(),
+            nodes: Default::default(),
+            connections: Default::default(),
+            fields: Default::default(),
+            selection: Default::default(),
+        }
+    }
+
+    pub fn open(&mut self, ui: &mut UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        self.sync_to_model(ui);
+    }
+
+    fn make_view_node(
+        &mut self,
+        index: usize,
+        node: &ShaderGraphNode,
+        ctx: &mut BuildContext,
+    ) -> Handle<UiNode> {
+        let inputs = node
+            .kind
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(i, definition)| {
+                SocketBuilder::new(WidgetBuilder::new())
+                    .with_direction(SocketDirection::Input)
+                    .with_index(i)
+                    .with_name(definition.name)
+                    .build(ctx)
+            })
+            .collect();
+
+        let outputs = node
+            .kind
+            .outputs()
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                SocketBuilder::new(WidgetBuilder::new())
+                    .with_direction(SocketDirection::Output)
+                    .with_index(i)
+                    .with_name(name)
+                    .build(ctx)
+            })
+            .collect();
+
+        let mut content = Vec::new();
+        match &node.kind {
+            ShaderGraphNodeKind::Property { name, .. }
+            | ShaderGraphNodeKind::TextureSample { name, .. } => {
+                let widget = TextBoxBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text(name)
+                .build(ctx);
+                content.push(widget);
+                self.fields.push(NodeField {
+                    widget,
+                    node: index,
+                    kind: NodeFieldKind::Name,
+                });
+            }
+            _ => (),
+        }
+        match &node.kind {
+            ShaderGraphNodeKind::Constant(ShaderGraphValue::Float(value))
+            | ShaderGraphNodeKind::Property {
+                value: ShaderGraphValue::Float(value),
+                ..
+            } => {
+                let widget = NumericUpDownBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_value(*value)
+                .build(ctx);
+                content.push(widget);
+                self.fields.push(NodeField {
+                    widget,
+                    node: index,
+                    kind: NodeFieldKind::Value,
+                });
+            }
+            _ => (),
+        }
+
+        let content = if content.is_empty() {
+            Handle::NONE
+        } else {
+            for (row, widget) in content.iter().enumerate() {
+                if let Some(widget) = ctx.try_get_node_mut(*widget) {
+                    widget.set_row(row);
+                }
+            }
+            let rows = content.iter().map(|_| Row::auto()).collect();
+            GridBuilder::new(WidgetBuilder::new().with_children(content))
+                .add_rows(rows)
+                .add_column(Column::stretch())
+                .build(ctx)
+        };
+
+        GraphNodeBuilder::new(
+            WidgetBuilder::new()
+                .with_desired_position(node.position)
+                .with_min_size(Vector2::new(120.0, 0.0)),
+        )
+        .with_title(node.kind.name())
+        .with_inputs(inputs)
+        .with_outputs(outputs)
+        .with_content(content)
+        .build(ctx)
+    }
+
+    fn clear(&mut self, ui: &UserInterface) {
+        for node in self
+            .nodes
+            .drain(..)
+            .chain(self.connections.drain(..).map(|(c, _)| c))
+        {
+            ui.send_message(WidgetMessage::remove(node, MessageDirection::ToWidget));
+        }
+        self.fields.clear();
+        self.selection.clear();
+    }
+
+    /// Recreates every view node and connection from the model.
+    fn sync_to_model(&mut self, ui: &mut UserInterface) {
+        self.clear(ui);
+
+        let graph = std::mem::take(&mut self.graph);
+
+        for (index, node) in graph.nodes.iter().enumerate() {
+            let view_node = self.make_view_node(index, node, &mut ui.build_ctx());
+            ui.send_message(WidgetMessage::link(
+                view_node,
+                MessageDirection::ToWidget,
+                self.node_graph,
+            ));
+            self.nodes.push(view_node);
+        }
+
+        for connection in graph.connections.iter() {
+            let source = self.socket(ui, connection.source, connection.output, false);
+            let dest = self.socket(ui, connection.dest, connection.input, true);
+            let view_connection = ConnectionBuilder::new(WidgetBuilder::new())
+                .with_source(source)
+                .with_dest(dest)
+                .build(&mut ui.build_ctx());
+            ui.send_message(WidgetMessage::link(
+                view_connection,
+                MessageDirection::ToWidget,
+                self.node_graph,
+            ));
+            self.connections.push((view_connection, *connection));
+        }
+
+        self.graph = graph;
+
+        ui.send_message(NodeGraphMessage::sync_connections(
+            self.node_graph,
+            MessageDirection::ToWidget,
+        ));
+
+        self.sync_status(ui);
+    }
+
+    fn sync_status(&self, ui: &UserInterface) {
+        let status = match self.graph.compile() {
+            Ok(_) => "Shader graph is valid.".to_owned(),
+            Err(e) => e.to_string(),
+        };
+
+        ui.send_message(TextMessage::text(
+            self.status,
+            MessageDirection::ToWidget,
+            status,
+        ));
+    }
+
+    fn socket(
+        &self,
+        ui: &UserInterface,
+        node: usize,
+        socket: usize,
+        input: bool,
+    ) -> Handle<UiNode> {
+        self.nodes
+            .get(node)
+            .and_then(|n| ui.node(*n).query_component::<GraphNode>())
+            .and_then(|n| {
+                if input {
+                    n.inputs.get(socket)
+                } else {
+                    n.outputs.get(socket)
+                }
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Returns index of a node and index of a socket in the node by a socket widget.
+    fn socket_location(
+        &self,
+        ui: &UserInterface,
+        socket: Handle<UiNode>,
+    ) -> Option<(usize, usize)> {
+        let socket_index = ui.node(socket).query_component::<Socket>()?.index;
+        self.nodes
+            .iter()
+            .position(|n| {
+                ui.node(*n)
+                    .query_component::<GraphNode>()
+                    .map_or(false, |n| n.sockets().any(|s| s == socket))
+            })
+            .map(|node| (node, socket_index))
+    }
+
+    fn save(&self) {
+        match self.graph.to_ron_string() {
+            Ok(source) => {
+                if let Err(e) = std::fs::write(&self.path, source) {
+                    Log::err(format!(
+                        "Unable to save shader graph to {}. Reason: {:?}",
+                        self.path.display(),
+                        e
+                    ))
+                }
+            }
+            Err(e) => Log::err(format!("Unable to save shader graph. Reason: {}", e)),
+        }
+    }
+
+    fn load(&mut self, path: &PathBuf, ui: &mut UserInterface) {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|s| ShaderGraph::from_ron_str(&s).map_err(|e| e.to_string()));
+
+        match result {
+            Ok(graph) => {
+                self.graph = graph;
+                self.path = path.clone();
+                self.sync_to_model(ui);
+            }
+            Err(e) => Log::err(format!(
+                "Unable to load shader graph from {}. Reason: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    fn export(&self, path: &PathBuf) {
+        match self.graph.compile() {
+            Ok(source) => {
+                if let Err(e) = std::fs::write(path, source) {
+                    Log::err(format!(
+                        "Unable to export shader to {}. Reason: {:?}",
+                        path.display(),
+                        e
+                    ))
+                } else {
+                    Log::info(format!("Shader was exported to {}", path.display()))
+                }
+            }
+            Err(e) => Log::err(format!("Unable to compile shader graph. Reason: {}", e)),
+        }
+    }
+
+    fn open_file_selector(&self, file_selector: Handle<UiNode>, ui: &UserInterface) {
+        ui.send_message(FileSelectorMessage::root(
+            file_selector,
+            MessageDirection::ToWidget,
+            Some(std::env::current_dir().unwrap()),
+        ));
+
+        ui.send_message(WindowMessage::open_modal(
+            file_selector,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut Engine) {
+        let ui = &mut engine.user_interface;
+
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.menu.file.new {
+                self.graph = default_graph();
+                self.path = Default::default();
+                self.sync_to_model(ui);
+            } else if message.destination() == self.menu.file.load {
+                self.open_file_selector(self.load_file_selector, ui);
+            } else if message.destination() == self.menu.file.save {
+                if self.path == PathBuf::default() {
+                    self.open_file_selector(self.save_file_selector, ui);
+                } else {
+                    self.save();
+                }
+            } else if message.destination() == self.menu.file.export {
+                self.open_file_selector(self.export_file_selector, ui);
+            } else if message.destination() == self.menu.edit.delete {
+                let mut indices = self
+                    .selection
+                    .iter()
+                    .filter_map(|s| self.nodes.iter().position(|n| n == s))
+                    .collect::<Vec<_>>();
+                // Remove from the end to keep indices of remaining nodes valid.
+                indices.sort_unstable();
+                for index in indices.into_iter().rev() {
+                    self.graph.remove_node(index);
+                }
+                self.sync_to_model(ui);
+            } else if let Some((_, template)) = self
+                .menu
+                .add
+                .iter()
+                .find(|(item, _)| *item == message.destination())
+            {
+                let position = ui
+                    .node(self.node_graph)
+                    .query_component::<NodeGraph>()
+                    .map(|g| g.point_to_local_space(g.screen_position() + Vector2::new(50.0, 50.0)))
+                    .unwrap_or_default();
+                self.graph.add_node_at(template.clone(), position);
+                self.sync_to_model(ui);
+            }
+        } else if let Some(msg) = message.data::<NodeGraphMessage>() {
+            if message.destination() == self.node_graph
+                && message.direction() == MessageDirection::FromWidget
+            {
+                match msg {
+                    NodeGraphMessage::Connect { source, dest } => {
+                        if let (Some((source, output)), Some((dest, input))) = (
+                            self.socket_location(ui, *source),
+                            self.socket_location(ui, *dest),
+                        ) {
+                            if let Err(e) = self.graph.connect(source, output, dest, input) {
+                                Log::warn(format!("Unable to connect nodes. Reason: {}", e));
+                            }
+                            self.sync_to_model(ui);
+                        }
+                    }
+                    NodeGraphMessage::Disconnect(connection) => {
+                        if let Some((_, connection)) =
+                            self.connections.iter().find(|(c, _)| c == connection)
+                        {
+                            self.graph.disconnect(connection.dest, connection.input);
+                            self.sync_to_model(ui);
+                        }
+                    }
+                    NodeGraphMessage::CommitDrag(entries) => {
+                        for entry in entries {
+                            if let Some(index) = self.nodes.iter().position(|n| *n == entry.node) {
+                                self.graph.nodes[index].position = entry.new_position;
+                            }
+                        }
+                    }
+                    NodeGraphMessage::Selection(selection) => {
+                        self.selection = selection.clone();
+                    }
+                    _ => (),
+                }
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                if let Some(field) = self
+                    .fields
+                    .iter()
+                    .find(|f| f.widget == message.destination())
+                {
+                    if let (NodeFieldKind::Name, Some(node)) =
+                        (&field.kind, self.graph.nodes.get_mut(field.node))
+                    {
+                        match &mut node.kind {
+                            ShaderGraphNodeKind::Property { name, .. }
+                            | ShaderGraphNodeKind::TextureSample { name, .. } => {
+                                *name = text.clone();
+                            }
+                            _ => (),
+                        }
+                        self.sync_status(ui);
+                    }
+                }
+            }
+        } else if let Some(NumericUpDownMessage::Value(new_value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if let Some(field) = self
+                    .fields
+                    .iter()
+                    .find(|f| f.widget == message.destination())
+                {
+                    if let (NodeFieldKind::Value, Some(node)) =
+                        (&field.kind, self.graph.nodes.get_mut(field.node))
+                    {
+                        match &mut node.kind {
+                            ShaderGraphNodeKind::Constant(ShaderGraphValue::Float(value))
+                            | ShaderGraphNodeKind::Property {
+                                value: ShaderGraphValue::Float(value),
+                                ..
+                            } => {
+                                *value = *new_value;
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+            }
+        } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
+            if message.destination() == self.load_file_selector {
+                self.load(path, ui);
+            } else if message.destination() == self.save_file_selector {
+                self.path = path.clone();
+                self.save();
+            } else if message.destination() == self.export_file_selector {
+                self.export(path);
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{scene::EditorScene, GameEngine};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        message::MessageDirection,
+        scroll_viewer::ScrollViewerBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode,
+    },
+    scene::{
+        animation::AnimationPlayer, dim2, light::BaseLight, node::Node, rigidbody::RigidBody, Scene,
+    },
+};
+use std::fmt::{Display, Formatter};
+
+/// How often (in seconds) the statistics is refreshed.
+const UPDATE_INTERVAL: f32 = 0.25;
+
+#[derive(Default)]
+struct SceneCounts {
+    nodes: usize,
+    lights: usize,
+    rigid_bodies: usize,
+    active_animations: usize,
+}
+
+impl SceneCounts {
+    fn from_scene(scene: &Scene, editor_objects_root: Handle<Node>) -> Self {
+        let mut counts = Self::default();
+
+        let graph = &scene.graph;
+        let mut stack = vec![graph.get_root()];
+        while let Some(handle) = stack.pop() {
+            // Editor objects are not part of the scene.
+            if handle == editor_objects_root {
+                continue;
+            }
+
+            let node = &graph[handle];
+
+            counts.nodes += 1;
+            if node.query_component_ref::<BaseLight>().is_some() {
+                counts.lights += 1;
+            }
+            if node.cast::<RigidBody>().is_some()
+                || node.cast::<dim2::rigidbody::RigidBody>().is_some()
+            {
+                counts.rigid_bodies += 1;
+            }
+            if let Some(animation_player) = node.query_component_ref::<AnimationPlayer>() {
+                counts.active_animations += animation_player
+                    .animations()
+                    .iter()
+                    .filter(|a| a.is_enabled())
+                    .count();
+            }
+
+            stack.extend_from_slice(node.children());
+        }
+
+        counts
+    }
+}
+
+impl Display for SceneCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Nodes: {}\n\
+            Lights: {}\n\
+            Rigid Bodies: {}\n\
+            Active Animations: {}",
+            self.nodes, self.lights, self.rigid_bodies, self.active_animations
+        )
+    }
+}
+
+/// A window that shows renderer statistics, counters of the current scene and times of its
+/// update steps.
+pub struct StatisticsWindow {
+    pub window: Handle<UiNode>,
+    text: Handle<UiNode>,
+    time_until_update: f32,
+}
+
+impl StatisticsWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(500.0))
+            .with_title(WindowTitle::text("Statistics"))
+            .open(false)
+            .with_content(
+                ScrollViewerBuilder::new(WidgetBuilder::new())
+                    .with_content({
+                        text = TextBuilder::new(
+                            WidgetBuilder::new().with_margin(Thickness::uniform(2.0)),
+                        )
+                        .build(ctx);
+                        text
+                    })
+                    .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            text,
+            time_until_update: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, editor_scene: Option<&EditorScene>, engine: &GameEngine, dt: f32) {
+        let ui = &engine.user_interface;
+
+        // There is no need to gather statistics for a closed window.
+        if !ui.node(self.window).visibility() {
+            return;
+        }
+
+        self.time_until_update -= dt;
+        if self.time_until_update > 0.0 {
+            return;
+        }
+        self.time_until_update = UPDATE_INTERVAL;
+
+        let mut text = format!("Renderer\n{}", engine.renderer.get_statistics());
+
+        if let Some(editor_scene) = editor_scene {
+            let scene = &engine.scenes[editor_scene.scene];
+
+            text += &format!(
+                "\nScene\n{}\n\nUpdate Times\n{}",
+                SceneCounts::from_scene(scene, editor_scene.editor_objects_root),
+                scene.performance_statistics
+            );
+        }
+
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+}
@@ -12,6 +12,7 @@ use fyrox::{
 };
 
 pub mod path_fixer;
+pub mod scene_merge;
 
 pub fn is_slice_equal_permutation<T: PartialEq>(a: &[T], b: &[T]) -> bool {
     if a.is_empty() && !b.is_empty() {
@@ -74,18 +75,6 @@ pub fn fetch_node_center(handle: Handle<UiNode>, ctx: &BuildContext) -> Vector2<
         .unwrap_or_default()
 }
 
-pub fn fetch_node_screen_center(handle: Handle<UiNode>, ctx: &BuildContext) -> Vector2<f32> {
-    ctx.try_get_node(handle)
-        .map(|node| node.screen_bounds().center())
-        .unwrap_or_default()
-}
-
-pub fn fetch_node_screen_center_ui(handle: Handle<UiNode>, ui: &UserInterface) -> Vector2<f32> {
-    ui.try_get_node(handle)
-        .map(|node| node.screen_bounds().center())
-        .unwrap_or_default()
-}
-
 fn load_texture(data: &[u8]) -> Texture {
     Texture::load_from_memory(data, CompressionOptions::NoCompression, false)
         .ok()
@@ -0,0 +1,573 @@
+//! Structural comparison and three-way merge of scenes. Nodes are matched by their instance ids,
+//! so the merge does not depend on the order of the nodes in the files and it works fine when
+//! several people edit the same scene.
+//!
+//! The merge works on per-node granularity: a node changed (added, removed, modified) only on one
+//! side is taken from that side, a node changed differently on both sides is a conflict, which has
+//! to be resolved by picking one of the versions. Only scene graph is merged, the rest of the scene
+//! (navmeshes, sound and physics settings, etc.) is always taken from "our" side. Handles to other
+//! nodes (skinning bones, joint bodies, etc.) are remapped to the nodes of the merged scene, handles
+//! to the nodes that do not exist in the merged scene are set to `NONE`.
+
+use fyrox::{
+    core::pool::Handle,
+    core::{futures::executor::block_on, uuid::Uuid, visitor::prelude::*},
+    engine::{resource_manager::ResourceManager, SerializationContext},
+    scene::{
+        base::InstanceId, graph::map::NodeHandleMap, mesh::Mesh, node::Node, Scene, SceneLoader,
+    },
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::{Display, Formatter},
+    path::Path,
+    sync::Arc,
+};
+
+pub mod window;
+
+/// Serialized fields that describe structure of the graph. The hierarchy is compared using
+/// instance ids of the parents instead, because raw handles differ from file to file.
+const STRUCTURAL_FIELDS: [&str; 3] = ["Parent", "Children", "InstanceId"];
+
+/// Flattened state of a scene node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeState {
+    pub name: String,
+    /// Instance id of the parent node, `None` for direct children of the root.
+    pub parent: Option<Uuid>,
+    /// Serialized properties of the node (see [`Visitor::flatten`]).
+    pub properties: BTreeMap<String, String>,
+}
+
+impl NodeState {
+    /// Returns a list of properties that differ in the given states. Reparenting is reported as
+    /// change of `Parent` property.
+    pub fn changed_properties(&self, other: &NodeState) -> Vec<String> {
+        let mut changed = Vec::new();
+        if self.parent != other.parent {
+            changed.push("Parent".to_owned());
+        }
+        let names = self
+            .properties
+            .keys()
+            .chain(other.properties.keys())
+            .collect::<BTreeSet<_>>();
+        for name in names {
+            if self.properties.get(name) != other.properties.get(name) {
+                changed.push(name.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// Flattened state of every node of a scene (except the root) keyed by instance id.
+#[derive(Default, Clone, Debug)]
+pub struct SceneSnapshot {
+    pub nodes: HashMap<Uuid, NodeState>,
+}
+
+impl SceneSnapshot {
+    pub fn new(scene: &mut Scene) -> Self {
+        let graph = &mut scene.graph;
+        let root = graph.get_root();
+        let instance_ids = graph
+            .pair_iter()
+            .map(|(handle, node)| (handle, node.instance_id().0))
+            .collect::<HashMap<_, _>>();
+
+        let mut nodes = HashMap::new();
+        for (handle, node) in graph.pair_iter_mut() {
+            if handle == root {
+                continue;
+            }
+
+            let parent = if node.parent() == root {
+                None
+            } else {
+                instance_ids.get(&node.parent()).cloned()
+            };
+
+            let mut visitor = Visitor::new();
+            let properties = match node.visit("Node", &mut visitor) {
+                Ok(_) => visitor
+                    .flatten()
+                    .into_iter()
+                    .filter_map(|(path, value)| {
+                        let path = path.strip_prefix("Node/").unwrap_or(&path);
+                        if path
+                            .split('/')
+                            .any(|segment| STRUCTURAL_FIELDS.contains(&segment))
+                        {
+                            None
+                        } else {
+                            Some((path.to_owned(), value))
+                        }
+                    })
+                    .collect(),
+                Err(_) => Default::default(),
+            };
+
+            nodes.insert(
+                node.instance_id().0,
+                NodeState {
+                    name: node.name_owned(),
+                    parent,
+                    properties,
+                },
+            );
+        }
+
+        Self { nodes }
+    }
+
+    fn name_of(&self, id: &Uuid) -> Option<&str> {
+        self.nodes.get(id).map(|state| state.name.as_str())
+    }
+}
+
+/// Change of a node relative to some previous state of a scene.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeChange {
+    Added,
+    Removed,
+    /// A list of changed properties.
+    Modified(Vec<String>),
+}
+
+impl NodeChange {
+    fn between(from: Option<&NodeState>, to: Option<&NodeState>) -> Option<NodeChange> {
+        match (from, to) {
+            (None, None) => None,
+            (None, Some(_)) => Some(NodeChange::Added),
+            (Some(_), None) => Some(NodeChange::Removed),
+            (Some(from), Some(to)) => {
+                let changed = from.changed_properties(to);
+                if changed.is_empty() {
+                    None
+                } else {
+                    Some(NodeChange::Modified(changed))
+                }
+            }
+        }
+    }
+}
+
+impl Display for NodeChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeChange::Added => write!(f, "added"),
+            NodeChange::Removed => write!(f, "removed"),
+            NodeChange::Modified(properties) => {
+                write!(f, "modified ({})", properties.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeDiff {
+    pub id: Uuid,
+    pub name: String,
+    pub change: NodeChange,
+}
+
+impl Display for NodeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] - {}", self.name, self.id, self.change)
+    }
+}
+
+fn all_ids<'a>(snapshots: &[&'a SceneSnapshot]) -> Vec<&'a Uuid> {
+    let mut ids = snapshots
+        .iter()
+        .flat_map(|snapshot| snapshot.nodes.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+/// Returns every node that was added, removed or modified in `to` relative to `from`.
+pub fn diff(from: &SceneSnapshot, to: &SceneSnapshot) -> Vec<NodeDiff> {
+    all_ids(&[from, to])
+        .into_iter()
+        .filter_map(|id| {
+            NodeChange::between(from.nodes.get(id), to.nodes.get(id)).map(|change| NodeDiff {
+                id: *id,
+                name: to
+                    .name_of(id)
+                    .or_else(|| from.name_of(id))
+                    .unwrap_or_default()
+                    .to_owned(),
+                change,
+            })
+        })
+        .collect()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MergeSide {
+    Ours,
+    Theirs,
+}
+
+/// A node that was changed differently on both sides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeConflict {
+    pub id: Uuid,
+    pub name: String,
+    pub ours: NodeChange,
+    pub theirs: NodeChange,
+    pub resolution: Option<MergeSide>,
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} [{}] - ours: {}; theirs: {}",
+            self.name, self.id, self.ours, self.theirs
+        )
+    }
+}
+
+/// Result of a three-way comparison of scenes.
+#[derive(Default, Clone, Debug)]
+pub struct SceneMerge {
+    /// Changes made only on "their" side, they're applied to "our" scene as is.
+    pub theirs_changes: Vec<NodeDiff>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl SceneMerge {
+    pub fn new(base: &SceneSnapshot, ours: &SceneSnapshot, theirs: &SceneSnapshot) -> Self {
+        let mut merge = Self::default();
+
+        for id in all_ids(&[base, ours, theirs]) {
+            let base_state = base.nodes.get(id);
+            let ours_state = ours.nodes.get(id);
+            let theirs_state = theirs.nodes.get(id);
+
+            let name = ours
+                .name_of(id)
+                .or_else(|| theirs.name_of(id))
+                .or_else(|| base.name_of(id))
+                .unwrap_or_default()
+                .to_owned();
+
+            match (
+                NodeChange::between(base_state, ours_state),
+                NodeChange::between(base_state, theirs_state),
+            ) {
+                (_, None) => (),
+                (None, Some(change)) => merge.theirs_changes.push(NodeDiff {
+                    id: *id,
+                    name,
+                    change,
+                }),
+                (Some(ours_change), Some(theirs_change)) => {
+                    // Same change on both sides is not a conflict.
+                    if ours_state != theirs_state {
+                        merge.conflicts.push(MergeConflict {
+                            id: *id,
+                            name,
+                            ours: ours_change,
+                            theirs: theirs_change,
+                            resolution: None,
+                        })
+                    }
+                }
+            }
+        }
+
+        merge
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.conflicts.iter().all(|c| c.resolution.is_some())
+    }
+
+    /// Applies changes taken from "their" side to "our" scene. Unresolved conflicts are resolved
+    /// in favor of "our" side.
+    pub fn apply(&self, ours: &mut Scene, theirs: &Scene) {
+        let taken = self
+            .theirs_changes
+            .iter()
+            .map(|change| change.id)
+            .chain(
+                self.conflicts
+                    .iter()
+                    .filter(|c| c.resolution == Some(MergeSide::Theirs))
+                    .map(|c| c.id),
+            )
+            .collect::<Vec<_>>();
+
+        let graph = &mut ours.graph;
+        let mut parents = Vec::new();
+        let mut removed = HashSet::new();
+        // Our nodes, that were replaced with copies of their nodes.
+        let mut replaced = Vec::new();
+        let mut copies = HashSet::new();
+
+        for id in taken {
+            let ours_handle = graph.find_by_instance_id(InstanceId(id));
+            let theirs_handle = theirs.graph.find_by_instance_id(InstanceId(id));
+
+            if theirs_handle.is_some() {
+                let mut copy = theirs.graph.copy_single_node(theirs_handle);
+                // Single node copy drops bones, they will be remapped below.
+                if let (Some(copy), Some(original)) = (
+                    copy.cast_mut::<Mesh>(),
+                    theirs.graph[theirs_handle].cast::<Mesh>(),
+                ) {
+                    for (surface, original) in
+                        copy.surfaces_mut().iter_mut().zip(original.surfaces())
+                    {
+                        surface.bones = original.bones.clone();
+                    }
+                }
+                let handle = graph.add_node(copy);
+                copies.insert(handle);
+
+                if ours_handle.is_some() {
+                    replaced.push((ours_handle, handle));
+                    // Children are merged separately, keep ours until then.
+                    for child in graph[ours_handle].children().to_vec() {
+                        graph.link_nodes(child, handle);
+                    }
+                    graph.remove_node(ours_handle);
+                }
+
+                let theirs_parent = theirs.graph[theirs_handle].parent();
+                if theirs_parent != theirs.graph.get_root() {
+                    parents.push((handle, theirs.graph[theirs_parent].instance_id()));
+                }
+            } else if ours_handle.is_some() {
+                removed.insert(ours_handle);
+            }
+        }
+
+        for (handle, parent_id) in parents {
+            let parent = graph.find_by_instance_id(parent_id);
+            if parent.is_some() && parent != handle {
+                graph.link_nodes(handle, parent);
+            }
+        }
+
+        for handle in removed.iter() {
+            // The node could be removed already together with its removed ancestor.
+            if graph.is_valid_handle(*handle) {
+                // Nodes that were not removed must survive removal of their parent.
+                let parent = graph[*handle].parent();
+                for child in graph[*handle].children().to_vec() {
+                    if !removed.contains(&child) {
+                        graph.link_nodes(child, parent);
+                    }
+                }
+                graph.remove_node(*handle);
+            }
+        }
+
+        // Copies have handles to nodes of their scene and our nodes could have handles to replaced
+        // or removed nodes, all of them must point to the nodes of the merged scene.
+        let merged = graph
+            .pair_iter()
+            .map(|(handle, node)| (node.instance_id(), handle))
+            .collect::<HashMap<_, _>>();
+        let mut theirs_to_merged = NodeHandleMap::default();
+        for (handle, node) in theirs.graph.pair_iter() {
+            theirs_to_merged.insert(
+                handle,
+                merged.get(&node.instance_id()).cloned().unwrap_or_default(),
+            );
+        }
+        let mut ours_to_merged = NodeHandleMap::default();
+        for &handle in merged.values() {
+            ours_to_merged.insert(handle, handle);
+        }
+        for (old, new) in replaced {
+            ours_to_merged.insert(old, new);
+        }
+        for handle in removed {
+            ours_to_merged.insert(handle, Handle::<Node>::NONE);
+        }
+
+        for handle in merged.values() {
+            if copies.contains(handle) {
+                theirs_to_merged.remap_handles(&mut graph[*handle]);
+            } else {
+                ours_to_merged.remap_handles(&mut graph[*handle]);
+            }
+        }
+    }
+}
+
+pub fn load_scene(
+    path: &Path,
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+) -> Result<Scene, String> {
+    let loader = block_on(SceneLoader::from_file(path, serialization_context))
+        .map_err(|e| format!("Unable to load {} scene. Reason: {}", path.display(), e))?;
+    Ok(block_on(loader.finish(resource_manager)))
+}
+
+pub fn save_scene(scene: &mut Scene, path: &Path) -> Result<(), String> {
+    let mut visitor = Visitor::new();
+    scene
+        .save("Scene", &mut visitor)
+        .and_then(|_| visitor.save_binary(path))
+        .map_err(|e| format!("Unable to save {} scene. Reason: {}", path.display(), e))
+}
+
+/// Merges scenes the way git merge drivers do: `ours` scene is replaced with the merge result if
+/// there are no conflicts. Returns an exit code for the driver: `0` - the scenes were merged, `1` -
+/// there are conflicts (they're printed to stderr) and the files were left untouched, `2` - an
+/// error occurred.
+///
+/// Register the driver in git config:
+///
+/// ```text
+/// [merge "fyrox-scene"]
+///     name = Fyrox scene merge driver
+///     driver = fyroxed merge %O %A %B
+/// ```
+///
+/// and assign it to the scenes in `.gitattributes`: `*.rgs merge=fyrox-scene`.
+pub fn run_merge_driver(base: &Path, ours: &Path, theirs: &Path) -> i32 {
+    let serialization_context = Arc::new(SerializationContext::new());
+    let resource_manager = ResourceManager::new(serialization_context.clone());
+
+    let mut scenes = Vec::new();
+    for path in [base, ours, theirs] {
+        match load_scene(
+            path,
+            serialization_context.clone(),
+            resource_manager.clone(),
+        ) {
+            Ok(scene) => scenes.push(scene),
+            Err(e) => {
+                eprintln!("{}", e);
+                return 2;
+            }
+        }
+    }
+
+    let mut theirs_scene = scenes.pop().unwrap();
+    let mut ours_scene = scenes.pop().unwrap();
+    let mut base_scene = scenes.pop().unwrap();
+
+    let merge = SceneMerge::new(
+        &SceneSnapshot::new(&mut base_scene),
+        &SceneSnapshot::new(&mut ours_scene),
+        &SceneSnapshot::new(&mut theirs_scene),
+    );
+
+    if !merge.conflicts.is_empty() {
+        eprintln!(
+            "Unable to merge {}, {} node(s) are changed on both sides:",
+            ours.display(),
+            merge.conflicts.len()
+        );
+        for conflict in merge.conflicts.iter() {
+            eprintln!("\t{}", conflict);
+        }
+        eprintln!("Use Utils -> Scene Merge in the editor to resolve the conflicts.");
+        return 1;
+    }
+
+    merge.apply(&mut ours_scene, &theirs_scene);
+
+    match save_scene(&mut ours_scene, ours) {
+        Ok(_) => {
+            println!(
+                "{} was merged, {} change(s) were taken from the other side.",
+                ours.display(),
+                merge.theirs_changes.len()
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::scene_merge::{MergeSide, NodeChange, NodeState, SceneMerge, SceneSnapshot};
+    use fyrox::core::uuid::Uuid;
+
+    fn state(name: &str, position: &str) -> NodeState {
+        NodeState {
+            name: name.to_owned(),
+            parent: None,
+            properties: [("Transform/Position".to_owned(), position.to_owned())]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn snapshot(nodes: &[(Uuid, NodeState)]) -> SceneSnapshot {
+        SceneSnapshot {
+            nodes: nodes.iter().cloned().collect(),
+        }
+    }
+
+    #[test]
+    fn test_three_way_merge() {
+        let unchanged = Uuid::new_v4();
+        let moved_by_them = Uuid::new_v4();
+        let conflicting = Uuid::new_v4();
+        let added_by_them = Uuid::new_v4();
+        let removed_by_them = Uuid::new_v4();
+
+        let base = snapshot(&[
+            (unchanged, state("A", "0")),
+            (moved_by_them, state("B", "0")),
+            (conflicting, state("C", "0")),
+            (removed_by_them, state("D", "0")),
+        ]);
+        let ours = snapshot(&[
+            (unchanged, state("A", "0")),
+            (moved_by_them, state("B", "0")),
+            (conflicting, state("C", "1")),
+            (removed_by_them, state("D", "0")),
+        ]);
+        let theirs = snapshot(&[
+            (unchanged, state("A", "0")),
+            (moved_by_them, state("B", "2")),
+            (conflicting, state("C", "2")),
+            (added_by_them, state("E", "0")),
+        ]);
+
+        let mut merge = SceneMerge::new(&base, &ours, &theirs);
+
+        let mut theirs_changes = merge
+            .theirs_changes
+            .iter()
+            .map(|c| (c.id, c.change.clone()))
+            .collect::<Vec<_>>();
+        theirs_changes.sort_by_key(|(id, _)| *id);
+        let mut expected = vec![
+            (
+                moved_by_them,
+                NodeChange::Modified(vec!["Transform/Position".to_owned()]),
+            ),
+            (added_by_them, NodeChange::Added),
+            (removed_by_them, NodeChange::Removed),
+        ];
+        expected.sort_by_key(|(id, _)| *id);
+        assert_eq!(theirs_changes, expected);
+
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].id, conflicting);
+        assert!(!merge.is_resolved());
+        merge.conflicts[0].resolution = Some(MergeSide::Theirs);
+        assert!(merge.is_resolved());
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{
+    utils::scene_merge::{
+        diff, load_scene, save_scene, MergeSide, NodeDiff, SceneMerge, SceneSnapshot,
+    },
+    GameEngine,
+};
+use fyrox::{
+    core::{pool::Handle, replace_slashes},
+    gui::{
+        border::BorderBuilder,
+        button::{ButtonBuilder, ButtonMessage},
+        decorator::DecoratorBuilder,
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    scene::Scene,
+    utils::log::Log,
+};
+use std::path::PathBuf;
+
+/// A window that compares three versions of a scene (common base, ours and theirs), shows every
+/// change and allows to resolve conflicting changes before writing the merged scene.
+pub struct SceneMergeWindow {
+    pub window: Handle<UiNode>,
+    base_path: Handle<UiNode>,
+    ours_path: Handle<UiNode>,
+    theirs_path: Handle<UiNode>,
+    output_path: Handle<UiNode>,
+    compare: Handle<UiNode>,
+    summary: Handle<UiNode>,
+    changes: Handle<UiNode>,
+    use_ours: Handle<UiNode>,
+    use_theirs: Handle<UiNode>,
+    merge_button: Handle<UiNode>,
+    close: Handle<UiNode>,
+    base: PathBuf,
+    ours: PathBuf,
+    theirs: PathBuf,
+    output: PathBuf,
+    merge: SceneMerge,
+    ours_changes: Vec<NodeDiff>,
+    scenes: Option<(Scene, Scene)>,
+    selection: Option<usize>,
+}
+
+fn make_path_field(
+    label: &str,
+    row: usize,
+    ctx: &mut BuildContext,
+) -> (Handle<UiNode>, Handle<UiNode>) {
+    let text_box;
+    let grid = GridBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_child(
+                TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                    .with_text(label)
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx),
+            )
+            .with_child({
+                text_box = TextBoxBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(1)
+                        .with_height(25.0)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx);
+                text_box
+            }),
+    )
+    .add_row(Row::auto())
+    .add_column(Column::strict(60.0))
+    .add_column(Column::stretch())
+    .build(ctx);
+    (grid, text_box)
+}
+
+fn make_button(text: &str, ctx: &mut BuildContext) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_width(90.0)
+            .with_height(25.0)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl SceneMergeWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let (base_field, base_path) = make_path_field("Base", 0, ctx);
+        let (ours_field, ours_path) = make_path_field("Ours", 1, ctx);
+        let (theirs_field, theirs_path) = make_path_field("Theirs", 2, ctx);
+        let (output_field, output_path) = make_path_field("Output", 3, ctx);
+
+        let compare;
+        let summary;
+        let changes;
+        let use_ours;
+        let use_theirs;
+        let merge_button;
+        let close;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(600.0).with_height(500.0))
+            .with_title(WindowTitle::text("Scene Merge"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_child(base_field)
+                        .with_child(ours_field)
+                        .with_child(theirs_field)
+                        .with_child(output_field)
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(4)
+                                    .with_child({
+                                        compare = make_button("Compare", ctx);
+                                        compare
+                                    })
+                                    .with_child({
+                                        summary = TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .with_wrap(WrapMode::Word)
+                                        .build(ctx);
+                                        summary
+                                    }),
+                            )
+                            .add_row(Row::auto())
+                            .add_column(Column::auto())
+                            .add_column(Column::stretch())
+                            .build(ctx),
+                        )
+                        .with_child({
+                            changes = ListViewBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(5)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            changes
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(6)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        use_ours = make_button("Use Ours", ctx);
+                                        use_ours
+                                    })
+                                    .with_child({
+                                        use_theirs = make_button("Use Theirs", ctx);
+                                        use_theirs
+                                    })
+                                    .with_child({
+                                        merge_button = make_button("Merge", ctx);
+                                        merge_button
+                                    })
+                                    .with_child({
+                                        close = make_button("Close", ctx);
+                                        close
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::auto())
+                .add_row(Row::auto())
+                .add_row(Row::auto())
+                .add_row(Row::auto())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            base_path,
+            ours_path,
+            theirs_path,
+            output_path,
+            compare,
+            summary,
+            changes,
+            use_ours,
+            use_theirs,
+            merge_button,
+            close,
+            base: Default::default(),
+            ours: Default::default(),
+            theirs: Default::default(),
+            output: Default::default(),
+            merge: Default::default(),
+            ours_changes: Default::default(),
+            scenes: None,
+            selection: None,
+        }
+    }
+
+    pub fn open(&self, ui: &mut UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        self.sync_to_model(ui);
+    }
+
+    fn set_summary(&self, ui: &UserInterface, text: String) {
+        ui.send_message(TextMessage::text(
+            self.summary,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn compare_scenes(&mut self, engine: &mut GameEngine) {
+        self.scenes = None;
+        self.merge = Default::default();
+        self.ours_changes.clear();
+        self.selection = None;
+
+        let mut scenes = Vec::new();
+        for path in [&self.base, &self.ours, &self.theirs] {
+            match load_scene(
+                path,
+                engine.serialization_context.clone(),
+                engine.resource_manager.clone(),
+            ) {
+                Ok(scene) => scenes.push(scene),
+                Err(e) => {
+                    self.set_summary(&engine.user_interface, e);
+                    self.sync_to_model(&mut engine.user_interface);
+                    return;
+                }
+            }
+        }
+
+        let mut theirs_scene = scenes.pop().unwrap();
+        let mut ours_scene = scenes.pop().unwrap();
+        let mut base_scene = scenes.pop().unwrap();
+
+        let base_snapshot = SceneSnapshot::new(&mut base_scene);
+        let ours_snapshot = SceneSnapshot::new(&mut ours_scene);
+        self.merge = SceneMerge::new(
+            &base_snapshot,
+            &ours_snapshot,
+            &SceneSnapshot::new(&mut theirs_scene),
+        );
+        self.ours_changes = diff(&base_snapshot, &ours_snapshot);
+        self.scenes = Some((ours_scene, theirs_scene));
+
+        self.set_summary(
+            &engine.user_interface,
+            format!(
+                "{} conflict(s), {} change(s) on our side, {} change(s) on their side.",
+                self.merge.conflicts.len(),
+                self.ours_changes.len(),
+                self.merge.theirs_changes.len()
+            ),
+        );
+        self.sync_to_model(&mut engine.user_interface);
+    }
+
+    fn write_merged_scene(&mut self, ui: &mut UserInterface) {
+        if let Some((mut ours_scene, theirs_scene)) = self.scenes.take() {
+            self.merge.apply(&mut ours_scene, &theirs_scene);
+            let summary = match save_scene(&mut ours_scene, &self.output) {
+                Ok(_) => {
+                    let message = format!("Merged scene was saved to {}.", self.output.display());
+                    Log::info(message.clone());
+                    message
+                }
+                Err(e) => e,
+            };
+            self.merge = Default::default();
+            self.ours_changes.clear();
+            self.selection = None;
+            self.set_summary(ui, summary);
+            self.sync_to_model(ui);
+        }
+    }
+
+    fn resolve(&mut self, ui: &mut UserInterface, side: MergeSide) {
+        if let Some(conflict) = self
+            .selection
+            .and_then(|index| self.merge.conflicts.get_mut(index))
+        {
+            conflict.resolution = Some(side);
+            self.sync_to_model(ui);
+        }
+    }
+
+    fn sync_to_model(&self, ui: &mut UserInterface) {
+        let conflicts = self.merge.conflicts.iter().map(|conflict| {
+            let state = match conflict.resolution {
+                None => "Conflict",
+                Some(MergeSide::Ours) => "Conflict, use ours",
+                Some(MergeSide::Theirs) => "Conflict, use theirs",
+            };
+            format!("[{}] {}", state, conflict)
+        });
+        let theirs_changes = self
+            .merge
+            .theirs_changes
+            .iter()
+            .map(|change| format!("[Theirs] {}", change));
+        let ours_changes = self
+            .ours_changes
+            .iter()
+            .map(|change| format!("[Ours] {}", change));
+
+        let ctx = &mut ui.build_ctx();
+        let items = conflicts
+            .chain(theirs_changes)
+            .chain(ours_changes)
+            .map(|text| {
+                DecoratorBuilder::new(BorderBuilder::new(
+                    WidgetBuilder::new().with_child(
+                        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_wrap(WrapMode::Word)
+                            .with_text(text)
+                            .build(ctx),
+                    ),
+                ))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+        ui.send_message(ListViewMessage::items(
+            self.changes,
+            MessageDirection::ToWidget,
+            items,
+        ));
+        ui.send_message(ListViewMessage::selection(
+            self.changes,
+            MessageDirection::ToWidget,
+            self.selection,
+        ));
+
+        let conflict_selected = self
+            .selection
+            .map_or(false, |index| index < self.merge.conflicts.len());
+        for button in [self.use_ours, self.use_theirs] {
+            ui.send_message(WidgetMessage::enabled(
+                button,
+                MessageDirection::ToWidget,
+                conflict_selected,
+            ));
+        }
+        ui.send_message(WidgetMessage::enabled(
+            self.merge_button,
+            MessageDirection::ToWidget,
+            self.scenes.is_some() && self.merge.is_resolved(),
+        ));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.compare {
+                self.compare_scenes(engine);
+            } else if message.destination() == self.use_ours {
+                self.resolve(&mut engine.user_interface, MergeSide::Ours);
+            } else if message.destination() == self.use_theirs {
+                self.resolve(&mut engine.user_interface, MergeSide::Theirs);
+            } else if message.destination() == self.merge_button {
+                self.write_merged_scene(&mut engine.user_interface);
+            } else if message.destination() == self.close {
+                engine.user_interface.send_message(WindowMessage::close(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                let path = replace_slashes(text.trim());
+                if message.destination() == self.base_path {
+                    self.base = path;
+                } else if message.destination() == self.ours_path {
+                    // Ours is replaced with the merge result by default, as git does.
+                    if self.output.as_os_str().is_empty() || self.output == self.ours {
+                        engine.user_interface.send_message(TextMessage::text(
+                            self.output_path,
+                            MessageDirection::ToWidget,
+                            path.to_string_lossy().to_string(),
+                        ));
+                    }
+                    self.ours = path;
+                } else if message.destination() == self.theirs_path {
+                    self.theirs = path;
+                } else if message.destination() == self.output_path {
+                    self.output = path;
+                }
+            }
+        } else if let Some(ListViewMessage::SelectionChanged(selection)) = message.data() {
+            if message.destination() == self.changes
+                && message.direction() == MessageDirection::FromWidget
+                && self.selection != *selection
+            {
+                self.selection = *selection;
+                let conflict_selected =
+                    selection.map_or(false, |index| index < self.merge.conflicts.len());
+                for button in [self.use_ours, self.use_theirs] {
+                    engine.user_interface.send_message(WidgetMessage::enabled(
+                        button,
+                        MessageDirection::ToWidget,
+                        conflict_selected,
+                    ));
+                }
+            }
+        }
+    }
+}
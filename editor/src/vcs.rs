@@ -0,0 +1,382 @@
+//! Version control integration. The editor asks a [`VcsProvider`] for statuses of files in the
+//! working directory and shows them as badges in the asset browser and in the world viewer.
+//! Statuses are fetched on a background thread, so slow version control systems do not stall
+//! the editor.
+
+use fyrox::{core::color::Color, fxhash::FxHashMap, utils::log::Log};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+/// How often (in seconds) statuses of files are refreshed.
+const REFRESH_INTERVAL: f32 = 5.0;
+
+/// Status of a file in a version control system.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FileStatus {
+    /// File is not changed since the last commit (or it is not under version control at all).
+    Unmodified,
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+impl Default for FileStatus {
+    fn default() -> Self {
+        Self::Unmodified
+    }
+}
+
+impl FileStatus {
+    /// Returns a short text and a color of a badge that represents the status.
+    pub fn badge(self) -> Option<(&'static str, Color)> {
+        match self {
+            FileStatus::Unmodified => None,
+            FileStatus::Modified => Some(("M", Color::opaque(230, 160, 40))),
+            FileStatus::Added => Some(("A", Color::opaque(80, 200, 80))),
+            FileStatus::Deleted => Some(("D", Color::opaque(200, 60, 60))),
+            FileStatus::Renamed => Some(("R", Color::opaque(80, 160, 230))),
+            FileStatus::Untracked => Some(("?", Color::opaque(150, 150, 150))),
+            FileStatus::Conflicted => Some(("!", Color::RED)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FileStatus::Unmodified => "Unmodified",
+            FileStatus::Modified => "Modified",
+            FileStatus::Added => "Added",
+            FileStatus::Deleted => "Deleted",
+            FileStatus::Renamed => "Renamed",
+            FileStatus::Untracked => "Untracked",
+            FileStatus::Conflicted => "Conflicted",
+        }
+    }
+}
+
+type StatusesResult = Result<FxHashMap<PathBuf, FileStatus>, String>;
+
+/// An interface to a version control system. Statuses are requested from a background thread,
+/// that's why the provider must be thread-safe.
+pub trait VcsProvider: Send + Sync {
+    /// Name of the version control system.
+    fn name(&self) -> &str;
+
+    /// Returns statuses of all changed files in the given directory. Keys are canonical paths of
+    /// the files, unmodified files can be omitted.
+    fn statuses(&self, working_directory: &Path) -> StatusesResult;
+
+    /// Discards all local changes of the file.
+    fn revert(&self, path: &Path) -> Result<(), String>;
+
+    /// Makes a read-only file writable. Systems with file locking (like Perforce) should check out
+    /// the file here.
+    fn check_out(&self, path: &Path) -> Result<(), String> {
+        Err(format!(
+            "{} does not support checking out files, {} is read-only.",
+            self.name(),
+            path.display()
+        ))
+    }
+}
+
+/// Git provider, uses the `git` command line tool.
+pub struct GitProvider;
+
+fn run_git(current_dir: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .current_dir(current_dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Unable to run git. Reason: {:?}", e))?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    }
+}
+
+fn parse_status_code(index: u8, work_tree: u8) -> FileStatus {
+    match (index, work_tree) {
+        (b'?', b'?') => FileStatus::Untracked,
+        (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => FileStatus::Conflicted,
+        (b'R', _) | (b'C', _) => FileStatus::Renamed,
+        (b'A', _) => FileStatus::Added,
+        (b'D', _) | (_, b'D') => FileStatus::Deleted,
+        (b'M', _) | (_, b'M') | (b'T', _) | (_, b'T') => FileStatus::Modified,
+        _ => FileStatus::Unmodified,
+    }
+}
+
+/// Parses output of `git status --porcelain -z`. Returned paths are relative to the root of the
+/// repository.
+fn parse_porcelain_status(output: &[u8]) -> Vec<(PathBuf, FileStatus)> {
+    let mut statuses = Vec::new();
+
+    let mut entries = output.split(|b| *b == 0).filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+
+        let status = parse_status_code(entry[0], entry[1]);
+        let path = PathBuf::from(String::from_utf8_lossy(&entry[3..]).to_string());
+
+        // Renamed and copied files are followed by their original path.
+        if let FileStatus::Renamed = status {
+            entries.next();
+        }
+
+        statuses.push((path, status));
+    }
+
+    statuses
+}
+
+impl VcsProvider for GitProvider {
+    fn name(&self) -> &str {
+        "Git"
+    }
+
+    fn statuses(&self, working_directory: &Path) -> StatusesResult {
+        let root = run_git(working_directory, &["rev-parse", "--show-toplevel"])?;
+        let root = PathBuf::from(String::from_utf8_lossy(&root).trim())
+            .canonicalize()
+            .map_err(|e| format!("{:?}", e))?;
+
+        let output = run_git(
+            working_directory,
+            &["status", "--porcelain", "-z", "--untracked-files=all"],
+        )?;
+
+        Ok(parse_porcelain_status(&output)
+            .into_iter()
+            .map(|(path, status)| (root.join(path), status))
+            .collect())
+    }
+
+    fn revert(&self, path: &Path) -> Result<(), String> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| format!("{} is not a file!", path.display()))?
+            .to_string_lossy()
+            .to_string();
+
+        run_git(
+            parent_dir(path),
+            &["checkout", "HEAD", "--", file_name.as_str()],
+        )
+        .map(|_| ())
+    }
+}
+
+/// Detects a version control system that is used in the given directory.
+pub fn detect_provider(working_directory: &Path) -> Option<Arc<dyn VcsProvider>> {
+    if run_git(working_directory, &["rev-parse", "--is-inside-work-tree"]).is_ok() {
+        Some(Arc::new(GitProvider))
+    } else {
+        None
+    }
+}
+
+/// Keeps statuses of files in the working directory up to date.
+#[derive(Default)]
+pub struct Vcs {
+    provider: Option<Arc<dyn VcsProvider>>,
+    working_directory: PathBuf,
+    statuses: FxHashMap<PathBuf, FileStatus>,
+    time_until_refresh: f32,
+    refresh_requested: bool,
+    pending: Option<Receiver<StatusesResult>>,
+    custom_provider: bool,
+    /// Becomes `true` every time when statuses were changed, it is up to the user to reset it.
+    pub changed: bool,
+}
+
+impl Vcs {
+    /// Sets a custom provider, it will be used instead of automatically detected one. `None`
+    /// restores automatic detection.
+    pub fn set_provider(&mut self, provider: Option<Box<dyn VcsProvider>>) {
+        self.custom_provider = provider.is_some();
+        self.provider = provider.map(Arc::from);
+        if !self.custom_provider {
+            self.provider = detect_provider(&self.working_directory);
+        }
+        // Statuses of the previous provider are not needed anymore.
+        self.pending = None;
+        self.refresh();
+    }
+
+    pub fn set_working_directory(&mut self, working_directory: &Path) {
+        self.working_directory = working_directory.to_owned();
+        if !self.custom_provider {
+            self.provider = detect_provider(working_directory);
+        }
+        if let Some(provider) = self.provider.as_ref() {
+            Log::info(format!(
+                "{} repository was detected, file statuses will be shown in the asset browser.",
+                provider.name()
+            ));
+        }
+        self.pending = None;
+        self.refresh();
+    }
+
+    /// Requests statuses of the files. The request is processed on a background thread, new
+    /// statuses will be applied in one of the next [`Self::update`] calls.
+    pub fn refresh(&mut self) {
+        self.refresh_requested = true;
+        self.poll();
+    }
+
+    fn apply(&mut self, result: StatusesResult) {
+        let statuses = result.unwrap_or_else(|err| {
+            Log::err(format!("Unable to fetch file statuses. Reason: {}", err));
+            Default::default()
+        });
+
+        if statuses != self.statuses {
+            self.statuses = statuses;
+            self.changed = true;
+        }
+    }
+
+    fn poll(&mut self) {
+        if let Some(receiver) = self.pending.as_ref() {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.pending = None;
+                    self.apply(result);
+                }
+                // Wait for the current request, a new one (if any) will be made after it.
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        if !self.refresh_requested {
+            return;
+        }
+
+        self.refresh_requested = false;
+        self.time_until_refresh = REFRESH_INTERVAL;
+
+        match self.provider.clone() {
+            Some(provider) => {
+                let (sender, receiver) = mpsc::channel();
+                let working_directory = self.working_directory.clone();
+                thread::spawn(move || {
+                    // The receiver could be dropped already, it is fine to ignore the error.
+                    let _ = sender.send(provider.statuses(&working_directory));
+                });
+                self.pending = Some(receiver);
+            }
+            None => self.apply(Ok(Default::default())),
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time_until_refresh -= dt;
+        if self.time_until_refresh <= 0.0 {
+            self.refresh_requested = true;
+        }
+        self.poll();
+    }
+
+    pub fn status<P: AsRef<Path>>(&self, path: P) -> FileStatus {
+        path.as_ref()
+            .canonicalize()
+            .ok()
+            .and_then(|path| self.statuses.get(&path).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn revert(&mut self, path: &Path) {
+        if let Some(provider) = self.provider.as_ref() {
+            match provider.revert(path) {
+                Ok(_) => {
+                    Log::info(format!("{} was reverted.", path.display()));
+                    self.refresh();
+                }
+                Err(err) => Log::err(format!(
+                    "Unable to revert {}. Reason: {}",
+                    path.display(),
+                    err
+                )),
+            }
+        } else {
+            Log::err("There is no version control system in the working directory!");
+        }
+    }
+
+    /// Checks whether the file can be written, tries to check it out if it is read-only. Returns
+    /// `false` if the file is still read-only.
+    pub fn ensure_writable(&self, path: &Path) -> bool {
+        let is_read_only = fs::metadata(path)
+            .map(|m| m.permissions().readonly())
+            .unwrap_or_default();
+        if !is_read_only {
+            return true;
+        }
+
+        Log::warn(format!(
+            "{} is read-only, trying to check it out.",
+            path.display()
+        ));
+
+        match self.provider.as_ref() {
+            Some(provider) => match provider.check_out(path) {
+                Ok(_) => true,
+                Err(err) => {
+                    Log::err(err);
+                    false
+                }
+            },
+            None => {
+                Log::err(format!("{} is read-only!", path.display()));
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vcs::{parse_porcelain_status, FileStatus};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_porcelain_status() {
+        let output = b" M data/scene.rgs\0A  data/new.png\0?? data/tmp.ogg\0\
+                       R  data/b.fbx\0data/a.fbx\0UU data/c.rgs\0";
+
+        assert_eq!(
+            parse_porcelain_status(output),
+            vec![
+                (PathBuf::from("data/scene.rgs"), FileStatus::Modified),
+                (PathBuf::from("data/new.png"), FileStatus::Added),
+                (PathBuf::from("data/tmp.ogg"), FileStatus::Untracked),
+                (PathBuf::from("data/b.fbx"), FileStatus::Renamed),
+                (PathBuf::from("data/c.rgs"), FileStatus::Conflicted),
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use fyrox::scene::node::Node;
+
+/// Broad category of scene nodes, that is used by type filter of the world viewer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeCategory {
+    Lights,
+    Cameras,
+    Meshes,
+    Sprites,
+    Particles,
+    Sounds,
+    Physics,
+}
+
+impl NodeCategory {
+    pub const ALL: [NodeCategory; 7] = [
+        NodeCategory::Lights,
+        NodeCategory::Cameras,
+        NodeCategory::Meshes,
+        NodeCategory::Sprites,
+        NodeCategory::Particles,
+        NodeCategory::Sounds,
+        NodeCategory::Physics,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeCategory::Lights => "Lights",
+            NodeCategory::Cameras => "Cameras",
+            NodeCategory::Meshes => "Meshes",
+            NodeCategory::Sprites => "Sprites",
+            NodeCategory::Particles => "Particles",
+            NodeCategory::Sounds => "Sounds",
+            NodeCategory::Physics => "Physics",
+        }
+    }
+
+    pub fn contains(self, node: &Node) -> bool {
+        match self {
+            NodeCategory::Lights => {
+                node.is_point_light() || node.is_spot_light() || node.is_directional_light()
+            }
+            NodeCategory::Cameras => node.is_camera(),
+            NodeCategory::Meshes => node.is_mesh() || node.is_terrain(),
+            NodeCategory::Sprites => node.is_sprite() || node.is_rectangle(),
+            NodeCategory::Particles => node.is_particle_system(),
+            NodeCategory::Sounds => node.is_sound() || node.is_listener(),
+            NodeCategory::Physics => {
+                node.is_rigid_body()
+                    || node.is_rigid_body2d()
+                    || node.is_collider()
+                    || node.is_collider2d()
+                    || node.is_joint()
+                    || node.is_joint2d()
+            }
+        }
+    }
+}
+
+/// Combined filter of the world viewer: a node passes the filter if its name contains the search
+/// string (case-insensitive) and it belongs to any of the selected categories. Empty category
+/// list means that every category is allowed.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct WorldViewerFilter {
+    /// Lowercase search string.
+    pub text: String,
+    pub categories: Vec<NodeCategory>,
+}
+
+impl WorldViewerFilter {
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty() || !self.categories.is_empty()
+    }
+
+    pub fn matches(&self, node: &Node) -> bool {
+        (self.categories.is_empty() || self.categories.iter().any(|c| c.contains(node)))
+            && (self.text.is_empty() || node.name().to_lowercase().contains(&self.text))
+    }
+}
@@ -8,14 +8,14 @@ use crate::{
     send_sync_message,
     utils::window_content,
     world::{
+        filter::{NodeCategory, WorldViewerFilter},
         graph::{
             item::{SceneItem, SceneItemBuilder, SceneItemMessage},
             menu::ItemContextMenu,
             selection::GraphSelection,
         },
-        search::SearchBar,
     },
-    GameEngine, Message, Mode, Settings,
+    GameEngine, Message, Mode, Settings, MSG_SYNC_FLAG,
 };
 use fyrox::{
     core::{
@@ -27,11 +27,12 @@ use fyrox::{
     gui::{
         brush::Brush,
         button::{ButtonBuilder, ButtonMessage},
-        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        check_box::{CheckBox, CheckBoxBuilder, CheckBoxMessage},
         decorator::{Decorator, DecoratorMessage},
         grid::{Column, GridBuilder, Row},
         message::{MessageDirection, UiMessage},
         scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
+        search_bar::{SearchBarBuilder, SearchBarMessage},
         stack_panel::StackPanelBuilder,
         text::TextBuilder,
         tree::{
@@ -39,17 +40,23 @@ use fyrox::{
             TreeRootMessage,
         },
         widget::{WidgetBuilder, WidgetMessage},
-        window::{WindowBuilder, WindowTitle},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        wrap_panel::WrapPanelBuilder,
         BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
         VerticalAlignment,
     },
     scene::{graph::Graph, node::Node, Scene},
     utils::log::Log,
 };
-use std::{any::TypeId, cmp::Ordering, collections::HashMap, sync::mpsc::Sender};
+use std::{
+    any::TypeId,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    sync::mpsc::Sender,
+};
 
+pub mod filter;
 pub mod graph;
-pub mod search;
 
 pub struct WorldViewer {
     pub window: Handle<UiNode>,
@@ -57,8 +64,7 @@ pub struct WorldViewer {
     graph_folder: Handle<UiNode>,
     sender: Sender<Message>,
     track_selection: Handle<UiNode>,
-    search_bar: SearchBar,
-    filter: String,
+    search_bar: Handle<UiNode>,
     stack: Vec<(Handle<UiNode>, Handle<Node>)>,
     /// Hack. Due to delayed execution of UI code we can't sync immediately after we
     /// did sync_to_model, instead we defer selection syncing to post_update() - at
@@ -72,6 +78,17 @@ pub struct WorldViewer {
     scroll_view: Handle<UiNode>,
     item_context_menu: ItemContextMenu,
     node_to_view_map: HashMap<Handle<Node>, Handle<UiNode>>,
+    type_filter_chips: Vec<(Handle<UiNode>, NodeCategory)>,
+    isolate_selection: Handle<UiNode>,
+    filter: WorldViewerFilter,
+    /// Nodes that pass the filter, in tree order.
+    filter_matches: Vec<Handle<Node>>,
+    /// Same hack as `sync_selection` - visibility of the items is synced with the filter in
+    /// post_update(), when every item is created.
+    sync_filter: bool,
+    /// Set when the filter is changed, matching items are revealed (expanded and scrolled to) only
+    /// on next filter sync after that, so the tree won't jump on every sync.
+    filter_changed: bool,
 }
 
 fn make_graph_node_item(
@@ -79,6 +96,7 @@ fn make_graph_node_item(
     handle: Handle<Node>,
     ctx: &mut BuildContext,
     context_menu: Handle<UiNode>,
+    expanded: bool,
 ) -> Handle<UiNode> {
     let icon = if node.is_point_light() || node.is_directional_light() || node.is_spot_light() {
         load_image(include_bytes!("../../resources/embed/light.png"))
@@ -94,16 +112,21 @@ fn make_graph_node_item(
         load_image(include_bytes!("../../resources/embed/cube.png"))
     };
 
-    SceneItemBuilder::new(TreeBuilder::new(
-        WidgetBuilder::new()
-            .with_margin(Thickness {
-                left: 1.0,
-                top: 1.0,
-                right: 0.0,
-                bottom: 0.0,
-            })
-            .with_context_menu(context_menu),
-    ))
+    SceneItemBuilder::new(
+        TreeBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness {
+                    left: 1.0,
+                    top: 1.0,
+                    right: 0.0,
+                    bottom: 0.0,
+                })
+                .with_context_menu(context_menu),
+        )
+        .with_expanded(expanded)
+        // Children items will be created by the world viewer on first expansion.
+        .with_lazy_population(!expanded && !node.children().is_empty()),
+    )
     .with_text_brush(if node.resource().is_some() {
         Brush::Solid(Color::opaque(160, 160, 200))
     } else {
@@ -150,6 +173,18 @@ fn colorize(handle: Handle<UiNode>, ui: &UserInterface, index: &mut usize) {
     }
 }
 
+fn make_check_box(ctx: &mut BuildContext, text: &str, checked: bool) -> Handle<UiNode> {
+    CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text(text)
+                .build(ctx),
+        )
+        .checked(Some(checked))
+        .build(ctx)
+}
+
 fn make_folder(ctx: &mut BuildContext, name: &str) -> Handle<UiNode> {
     TreeBuilder::new(WidgetBuilder::new())
         .with_content(
@@ -174,8 +209,13 @@ impl WorldViewer {
         let locate_selection;
         let scroll_view;
         let track_selection;
-        let search_bar = SearchBar::new(ctx);
+        let isolate_selection;
+        let search_bar = SearchBarBuilder::new(WidgetBuilder::new().on_row(1)).build(ctx);
         let graph_folder = make_folder(ctx, "Scene Graph");
+        let type_filter_chips = NodeCategory::ALL
+            .iter()
+            .map(|&category| (make_check_box(ctx, category.name(), false), category))
+            .collect::<Vec<_>>();
         let window = WindowBuilder::new(WidgetBuilder::new())
             .can_minimize(false)
             .with_title(WindowTitle::text("World Viewer"))
@@ -224,31 +264,36 @@ impl WorldViewer {
                                         locate_selection
                                     })
                                     .with_child({
-                                        track_selection = CheckBoxBuilder::new(
-                                            WidgetBuilder::new()
-                                                .with_margin(Thickness::uniform(1.0)),
-                                        )
-                                        .with_content(
-                                            TextBuilder::new(WidgetBuilder::new())
-                                                .with_vertical_text_alignment(
-                                                    VerticalAlignment::Center,
-                                                )
-                                                .with_text("Track Selection")
-                                                .build(ctx),
-                                        )
-                                        .checked(Some(settings.selection.track_selection))
-                                        .build(ctx);
+                                        track_selection = make_check_box(
+                                            ctx,
+                                            "Track Selection",
+                                            settings.selection.track_selection,
+                                        );
                                         track_selection
+                                    })
+                                    .with_child({
+                                        isolate_selection =
+                                            make_check_box(ctx, "Isolate Selection", false);
+                                        isolate_selection
                                     }),
                             )
                             .with_orientation(Orientation::Horizontal)
                             .build(ctx),
                         )
-                        .with_child(search_bar.container)
+                        .with_child(search_bar)
                         .with_child(
-                            TextBuilder::new(
+                            WrapPanelBuilder::new(
                                 WidgetBuilder::new()
                                     .on_row(2)
+                                    .with_children(type_filter_chips.iter().map(|(chip, _)| *chip)),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        )
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
                                     .on_column(0)
                                     .with_opacity(Some(0.4)),
                             )
@@ -258,7 +303,7 @@ impl WorldViewer {
                             .build(ctx),
                         )
                         .with_child(
-                            ScrollViewerBuilder::new(WidgetBuilder::new().on_row(2))
+                            ScrollViewerBuilder::new(WidgetBuilder::new().on_row(3))
                                 .with_content({
                                     node_path = StackPanelBuilder::new(WidgetBuilder::new())
                                         .with_orientation(Orientation::Horizontal)
@@ -268,7 +313,7 @@ impl WorldViewer {
                                 .build(ctx),
                         )
                         .with_child({
-                            scroll_view = ScrollViewerBuilder::new(WidgetBuilder::new().on_row(3))
+                            scroll_view = ScrollViewerBuilder::new(WidgetBuilder::new().on_row(4))
                                 .with_content({
                                     tree_root = TreeRootBuilder::new(WidgetBuilder::new())
                                         .with_items(vec![graph_folder])
@@ -282,6 +327,7 @@ impl WorldViewer {
                 .add_column(Column::stretch())
                 .add_row(Row::strict(24.0))
                 .add_row(Row::strict(24.0))
+                .add_row(Row::auto())
                 .add_row(Row::strict(24.0))
                 .add_row(Row::stretch())
                 .build(ctx),
@@ -307,7 +353,12 @@ impl WorldViewer {
             scroll_view,
             item_context_menu,
             node_to_view_map: Default::default(),
+            type_filter_chips,
+            isolate_selection,
             filter: Default::default(),
+            filter_matches: Default::default(),
+            sync_filter: false,
+            filter_changed: false,
         }
     }
 
@@ -320,6 +371,22 @@ impl WorldViewer {
 
         self.sync_graph(ui, editor_scene, graph);
 
+        let is_isolated = editor_scene.isolated_visibility.is_some();
+        if ui
+            .node(self.isolate_selection)
+            .cast::<CheckBox>()
+            .map_or(false, |check_box| check_box.checked != Some(is_isolated))
+        {
+            send_sync_message(
+                ui,
+                CheckBoxMessage::checked(
+                    self.isolate_selection,
+                    MessageDirection::ToWidget,
+                    Some(is_isolated),
+                ),
+            );
+        }
+
         self.validate(editor_scene, engine);
     }
 
@@ -378,6 +445,42 @@ impl WorldViewer {
     }
 
     fn sync_graph(&mut self, ui: &mut UserInterface, editor_scene: &EditorScene, graph: &Graph) {
+        self.filter_matches.clear();
+        if self.filter.is_active() {
+            let mut stack = vec![graph.get_root()];
+            while let Some(handle) = stack.pop() {
+                let node = &graph[handle];
+                if self.filter.matches(node) {
+                    self.filter_matches.push(handle);
+                }
+                // Reversed to keep tree order of the matches.
+                for &child in node.children().iter().rev() {
+                    if child != editor_scene.editor_objects_root {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        // Items must be re-synced after the filter was reset too, to make every item visible back.
+        if self.filter.is_active() || self.filter_changed {
+            self.sync_filter = true;
+        }
+
+        // Selected nodes and nodes that pass the filter must always have their views, so we
+        // must populate every collapsed ancestor of them.
+        let mut selection_ancestors = HashSet::new();
+        let selected = if let Selection::Graph(selection) = &editor_scene.selection {
+            selection.nodes()
+        } else {
+            &[]
+        };
+        for &node in selected.iter().chain(self.filter_matches.iter()) {
+            let mut ancestor = graph.try_get(node).map(|n| n.parent()).unwrap_or_default();
+            while ancestor.is_some() && selection_ancestors.insert(ancestor) {
+                ancestor = graph[ancestor].parent();
+            }
+        }
+
         // Sync tree structure with graph structure.
         self.stack.clear();
         self.stack.push((self.graph_folder, graph.get_root()));
@@ -409,6 +512,26 @@ impl WorldViewer {
                     .filter(|i| ui.node(*i).cast::<SceneItem<Node>>().is_some())
                     .collect::<Vec<_>>();
 
+                // Content of collapsed items is synced only when they're expanded and children
+                // of never expanded items are created lazily on first expansion, this saves a lot
+                // of time on large graphs.
+                if !item.tree.is_expanded && !selection_ancestors.contains(&node_handle) {
+                    if items.is_empty() {
+                        let is_lazy = child_count > 0;
+                        if item.tree.is_lazy != is_lazy {
+                            send_sync_message(
+                                ui,
+                                TreeMessage::set_lazy(
+                                    tree_handle,
+                                    MessageDirection::ToWidget,
+                                    is_lazy,
+                                ),
+                            );
+                        }
+                    }
+                    continue;
+                }
+
                 match child_count.cmp(&items.len()) {
                     Ordering::Less => {
                         for &item in items.iter() {
@@ -455,6 +578,7 @@ impl WorldViewer {
                                     child_handle,
                                     &mut ui.build_ctx(),
                                     self.item_context_menu.menu,
+                                    false,
                                 );
                                 send_sync_message(
                                     ui,
@@ -477,6 +601,7 @@ impl WorldViewer {
                         node_handle,
                         &mut ui.build_ctx(),
                         self.item_context_menu.menu,
+                        true,
                     );
                     send_sync_message(
                         ui,
@@ -512,7 +637,9 @@ impl WorldViewer {
                         );
                     }
 
-                    stack.extend_from_slice(&item.tree.items);
+                    if item.tree.is_expanded {
+                        stack.extend_from_slice(&item.tree.items);
+                    }
                 }
             } else if let Some(root) = ui_node.cast::<TreeRoot>() {
                 stack.extend_from_slice(root.items())
@@ -533,58 +660,203 @@ impl WorldViewer {
         colorize(self.tree_root, ui, &mut index);
     }
 
-    fn apply_filter(&self, ui: &UserInterface) {
-        fn apply_filter_recursive(node: Handle<UiNode>, filter: &str, ui: &UserInterface) -> bool {
-            let node_ref = ui.node(node);
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter.text = filter.to_lowercase();
+        self.on_filter_changed();
+    }
+
+    fn on_filter_changed(&mut self) {
+        self.filter_changed = true;
+        // Views of the matching nodes could be not created yet.
+        self.sender.send(Message::ForceSync).unwrap();
+    }
+
+    fn apply_filter(&self, editor_scene: &EditorScene, engine: &GameEngine) {
+        let graph = &engine.scenes[editor_scene.scene].graph;
+        let ui = &engine.user_interface;
+
+        let is_active = self.filter.is_active();
+        let matches = self.filter_matches.iter().cloned().collect::<HashSet<_>>();
 
-            let mut is_any_match = false;
-            for &child in node_ref.children() {
-                is_any_match |= apply_filter_recursive(child, filter, ui)
+        // Ancestors of the matching nodes must stay visible, otherwise the matches will be hidden
+        // too.
+        let mut visible = HashSet::new();
+        for &handle in self.filter_matches.iter() {
+            let mut node = handle;
+            while node.is_some() && visible.insert(node) {
+                node = graph.try_get(node).map(|n| n.parent()).unwrap_or_default();
             }
+        }
+
+        for (node, &view) in self.node_to_view_map.iter() {
+            if let Some(item) = ui
+                .try_get_node(view)
+                .and_then(|n| n.cast::<SceneItem<Node>>())
+            {
+                let is_visible = !is_active || visible.contains(node);
+                if item.visibility() != is_visible {
+                    ui.send_message(WidgetMessage::visibility(
+                        view,
+                        MessageDirection::ToWidget,
+                        is_visible,
+                    ));
+                }
 
-            let name = node_ref.cast::<SceneItem<Node>>().map(|i| i.name());
+                let is_highlighted = !self.filter.text.is_empty() && matches.contains(node);
+                if ui.node(item.tree.highlight).visibility() != is_highlighted {
+                    ui.send_message(WidgetMessage::visibility(
+                        item.tree.highlight,
+                        MessageDirection::ToWidget,
+                        is_highlighted,
+                    ));
+                }
+            }
+        }
+    }
 
-            if let Some(name) = name {
-                is_any_match |= name.to_lowercase().contains(filter);
+    fn reveal_matches(&self, editor_scene: &EditorScene, engine: &GameEngine) {
+        let graph = &engine.scenes[editor_scene.scene].graph;
+        let ui = &engine.user_interface;
 
-                ui.send_message(WidgetMessage::visibility(
-                    node,
+        for &handle in self.filter_matches.iter() {
+            let parent = graph
+                .try_get(handle)
+                .map(|n| n.parent())
+                .unwrap_or_default();
+            if let Some(&parent_view) = self.node_to_view_map.get(&parent) {
+                ui.send_message(TreeMessage::expand(
+                    parent_view,
                     MessageDirection::ToWidget,
-                    is_any_match,
+                    true,
+                    TreeExpansionStrategy::RecursiveAncestors,
                 ));
             }
-
-            is_any_match
         }
 
-        apply_filter_recursive(self.tree_root, &self.filter.to_lowercase(), ui);
+        if let Some(first_match) = self
+            .filter_matches
+            .first()
+            .and_then(|first| self.node_to_view_map.get(first))
+        {
+            ui.send_message(ScrollViewerMessage::bring_into_view(
+                self.scroll_view,
+                MessageDirection::ToWidget,
+                *first_match,
+            ));
+        }
     }
 
-    pub fn set_filter(&mut self, filter: String, ui: &UserInterface) {
-        self.filter = filter;
-        self.apply_filter(ui)
+    fn set_isolation(
+        &self,
+        enabled: bool,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+    ) {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+
+        if enabled {
+            if editor_scene.isolated_visibility.is_some() {
+                return;
+            }
+
+            let mut isolated = HashSet::new();
+            let mut ancestors = HashSet::new();
+            if let Selection::Graph(selection) = &editor_scene.selection {
+                for &selected in selection.nodes() {
+                    if let Some(node) = graph.try_get(selected) {
+                        isolated.insert(selected);
+                        let mut ancestor = node.parent();
+                        while ancestor.is_some() && ancestors.insert(ancestor) {
+                            ancestor = graph[ancestor].parent();
+                        }
+                    }
+                }
+            }
+
+            if isolated.is_empty() {
+                Log::warn("Select some scene nodes to isolate them!");
+                engine.user_interface.send_message(CheckBoxMessage::checked(
+                    self.isolate_selection,
+                    MessageDirection::ToWidget,
+                    Some(false),
+                ));
+                return;
+            }
+
+            // Visibility is hierarchical, so it is enough to hide topmost nodes that are neither
+            // isolated nor ancestors of isolated nodes. Ancestors are kept visible, otherwise
+            // isolated nodes will be hidden as well.
+            let mut original_visibility = HashMap::new();
+            let mut stack = vec![graph.get_root()];
+            while let Some(handle) = stack.pop() {
+                for &child in graph[handle].children() {
+                    if child == editor_scene.editor_objects_root || isolated.contains(&child) {
+                        continue;
+                    }
+                    if ancestors.contains(&child) {
+                        stack.push(child);
+                    } else {
+                        original_visibility.insert(child, graph[child].visibility());
+                    }
+                }
+            }
+
+            for &handle in original_visibility.keys() {
+                graph[handle].set_visibility(false);
+            }
+
+            editor_scene.isolated_visibility = Some(original_visibility);
+        } else if let Some(original_visibility) = editor_scene.isolated_visibility.take() {
+            for (handle, visibility) in original_visibility {
+                if let Some(node) = graph.try_get_mut(handle) {
+                    // Keep the nodes that were made visible explicitly while isolate mode was on.
+                    if !node.visibility() {
+                        node.set_visibility(visibility);
+                    }
+                }
+            }
+        }
     }
 
     pub fn handle_ui_message(
         &mut self,
         message: &UiMessage,
         editor_scene: &mut EditorScene,
-        engine: &GameEngine,
+        engine: &mut GameEngine,
         settings: &mut Settings,
     ) {
         scope_profile!();
 
         self.item_context_menu
             .handle_ui_message(message, editor_scene, engine, &self.sender);
-        self.search_bar
-            .handle_ui_message(message, &engine.user_interface, &self.sender);
-
-        if let Some(TreeRootMessage::Selected(selection)) = message.data::<TreeRootMessage>() {
+        if let Some(SearchBarMessage::Text(filter)) = message.data::<SearchBarMessage>() {
+            if message.destination() == self.search_bar
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.sender
+                    .send(Message::SetWorldViewerFilter(filter.clone()))
+                    .unwrap();
+            }
+        } else if let Some(TreeRootMessage::Selected(selection)) = message.data::<TreeRootMessage>()
+        {
             if message.destination() == self.tree_root
                 && message.direction() == MessageDirection::FromWidget
             {
                 self.handle_selection(selection, editor_scene, engine);
             }
+        } else if let Some(&TreeMessage::Expand { expand: true, .. }) =
+            message.data::<TreeMessage>()
+        {
+            if engine
+                .user_interface
+                .try_get_node(message.destination())
+                .and_then(|n| n.cast::<SceneItem<Node>>())
+                .is_some()
+            {
+                // Content of collapsed items is not synced, so it must be synced on expansion.
+                // This also creates children of lazy items.
+                self.sender.send(Message::ForceSync).unwrap();
+            }
         } else if let Some(&WidgetMessage::Drop(node)) = message.data::<WidgetMessage>() {
             self.handle_drop(engine, editor_scene, message.destination(), node);
         } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
@@ -632,6 +904,27 @@ impl WorldViewer {
                 if *value {
                     self.locate_selection(&editor_scene.selection, engine);
                 }
+            } else if message.destination() == self.isolate_selection
+                && message.direction() == MessageDirection::FromWidget
+                && !message.has_flags(MSG_SYNC_FLAG)
+            {
+                self.set_isolation(*value, editor_scene, engine);
+            } else if let Some(&(_, category)) = self
+                .type_filter_chips
+                .iter()
+                .find(|(chip, _)| *chip == message.destination())
+            {
+                if message.direction() == MessageDirection::FromWidget {
+                    let mut categories = self.filter.categories.clone();
+                    categories.retain(|c| *c != category);
+                    if *value {
+                        categories.push(category);
+                    }
+                    if categories != self.filter.categories {
+                        self.filter.categories = categories;
+                        self.on_filter_changed();
+                    }
+                }
             }
         }
     }
@@ -779,6 +1072,25 @@ impl WorldViewer {
 
             self.sync_selection = false;
         }
+
+        if self.sync_filter {
+            self.apply_filter(editor_scene, engine);
+            if self.filter_changed && self.filter.is_active() {
+                self.reveal_matches(editor_scene, engine);
+            }
+
+            self.sync_filter = false;
+            self.filter_changed = false;
+        }
+
+        // Content of off-screen items is hidden, so it is not measured, arranged and drawn.
+        let ui = &engine.user_interface;
+        if let Some(tree_root) = ui
+            .try_get_node(self.tree_root)
+            .and_then(|n| n.cast::<TreeRoot>())
+        {
+            tree_root.virtualize_items(ui, ui.node(self.scroll_view).clip_bounds());
+        }
     }
 
     pub fn clear(&mut self, ui: &UserInterface) {
@@ -791,6 +1103,14 @@ impl WorldViewer {
         ));
     }
 
+    pub fn set_title(&self, ui: &UserInterface, title: String) {
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::Text(title),
+        ));
+    }
+
     pub fn on_configure(&self, ui: &UserInterface, settings: &Settings) {
         ui.send_message(CheckBoxMessage::checked(
             self.track_selection,
@@ -394,6 +394,7 @@ fn main() {
                             ProgressStage::UvGeneration => "Generating UVs",
                             ProgressStage::GeometryCaching => "Caching Geometry",
                             ProgressStage::CalculatingLight => "Calculating Light",
+                            ProgressStage::BakingProbes => "Baking Light Probes",
                         };
 
                         let message = if load_context.generate_lightmap {
@@ -168,6 +168,7 @@ impl Plugin for Game {
                 max_len: 9999.0,
                 groups: Default::default(),
                 sort_results: true,
+                layer_mask: u32::MAX,
             },
             &mut buffer,
         );
@@ -58,6 +58,7 @@ impl Plugin for Game {
                 max_len: ray_direction.norm(),
                 groups: Default::default(),
                 sort_results: true,
+                layer_mask: u32::MAX,
             },
             &mut buffer,
         );
@@ -183,7 +183,7 @@ fn impl_reflect_struct(ty_args: &args::TypeArgs, field_args: &args::Fields) -> T
         ]
     };
 
-    let set_field_body = self::struct_set_field_body(ty_args);
+    let set_field_body = self::set_field_body(ty_args);
     self::gen_impl(
         ty_args,
         field_body,
@@ -195,7 +195,7 @@ fn impl_reflect_struct(ty_args: &args::TypeArgs, field_args: &args::Fields) -> T
     )
 }
 
-fn struct_set_field_body(ty_args: &args::TypeArgs) -> Option<TokenStream2> {
+fn set_field_body(ty_args: &args::TypeArgs) -> Option<TokenStream2> {
     let props = prop::props(ty_args)
         .filter(|p| p.field.setter.is_some())
         .collect::<Vec<_>>();
@@ -206,9 +206,10 @@ fn struct_set_field_body(ty_args: &args::TypeArgs) -> Option<TokenStream2> {
 
     let prop_values = props.iter().map(|p| &p.value);
 
+    let is_enum = matches!(ty_args.data, ast::Data::Enum(_));
     let set_fields = props.iter().map(|p| {
         let setter = p.field.setter.as_ref().unwrap();
-        quote! {{
+        let set = quote! {
             match value.take() {
                 Ok(value) => {
                     let prev = self.#setter(value);
@@ -218,7 +219,20 @@ fn struct_set_field_body(ty_args: &args::TypeArgs) -> Option<TokenStream2> {
                     Err(current)
                 }
             }
-        }}
+        };
+
+        if is_enum {
+            // Fields of enum variants exist only when the variant is active.
+            quote! {{
+                if self.field(name).is_some() {
+                    #set
+                } else {
+                    Err(value)
+                }
+            }}
+        } else {
+            quote! {{ #set }}
+        }
     });
 
     Some(quote! {
@@ -388,7 +402,7 @@ fn impl_reflect_enum(ty_args: &args::TypeArgs, variant_args: &[args::VariantArgs
             field_mut_body,
             fields_body,
             fields_mut_body,
-            None,
+            self::set_field_body(ty_args),
             fields_metadata_body,
         )
     }
@@ -145,10 +145,10 @@ pub struct FieldArgs {
 
     /// `#[reflect(setter = "<method name>")]
     ///
-    /// **STRUCT-ONLY (for now)**
-    ///
-    /// Setter method name used in `Reflect::set_field`.
-    /// Expected signature: `fn(&mut self, value: T)`
+    /// Setter method name used in `Reflect::set_field` (and in `set_field_by_path`), it allows
+    /// to validate new values or to do side effects on assignment.
+    /// Expected signature: `fn(&mut self, value: T) -> T`, it must return the previous value. For
+    /// enums, the setter is called only if the variant of the field is active.
     #[darling(default)]
     pub setter: Option<Path>,
 
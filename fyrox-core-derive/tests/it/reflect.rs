@@ -227,6 +227,80 @@ fn reflect_custom_setter() {
     assert!(wrapper.is_dirty);
 }
 
+#[test]
+fn reflect_setter_by_path() {
+    #[derive(Reflect)]
+    pub struct Inner {
+        #[reflect(setter = "set_value")]
+        value: u32,
+        is_dirty: bool,
+    }
+
+    impl Inner {
+        pub fn set_value(&mut self, value: u32) -> u32 {
+            self.is_dirty = true;
+            std::mem::replace(&mut self.value, value.min(100))
+        }
+    }
+
+    #[derive(Reflect)]
+    pub struct Outer {
+        inner: Inner,
+        items: Vec<u32>,
+    }
+
+    let mut outer = Outer {
+        inner: Inner {
+            value: 1,
+            is_dirty: false,
+        },
+        items: vec![1, 2, 3],
+    };
+
+    let prev = set_field_by_path(&mut outer, "inner.value", Box::new(200u32)).unwrap();
+    assert_eq!(prev.downcast_ref::<u32>(), Some(&1));
+    assert_eq!(outer.inner.value, 100);
+    assert!(outer.inner.is_dirty);
+
+    assert!(set_field_by_path(&mut outer, "items[1]", Box::new(5u32)).is_ok());
+    assert_eq!(outer.items, vec![1, 5, 3]);
+
+    assert!(set_field_by_path(&mut outer, "inner.value", Box::new(1.0f32)).is_err());
+    assert!(set_field_by_path(&mut outer, "inner.foo", Box::new(1u32)).is_err());
+}
+
+#[test]
+fn reflect_enum_custom_setter() {
+    #[derive(Reflect)]
+    pub enum Shape {
+        Circle {
+            #[reflect(setter = "set_radius")]
+            radius: f32,
+        },
+        Point,
+    }
+
+    impl Shape {
+        pub fn set_radius(&mut self, value: f32) -> f32 {
+            match self {
+                Shape::Circle { radius } => std::mem::replace(radius, value.max(0.0)),
+                Shape::Point => unreachable!(),
+            }
+        }
+    }
+
+    let mut circle = Shape::Circle { radius: 1.0 };
+    assert!(circle
+        .set_field(Shape::CIRCLE_RADIUS, Box::new(-1.0f32))
+        .is_ok());
+    assert!(matches!(circle, Shape::Circle { radius } if radius == 0.0));
+
+    let mut point = Shape::Point;
+    assert!(point
+        .set_field(Shape::CIRCLE_RADIUS, Box::new(1.0f32))
+        .is_err());
+}
+
 #[test]
 fn reflect_fields_list_of_struct() {
     #[derive(Reflect)]
@@ -0,0 +1,56 @@
+//! Command line tool to inspect files in visitor format (scenes, saved games, etc.)
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run --example visitor_tool -- schema <file>
+//! cargo run --example visitor_tool -- sizes <file> [max depth]
+//! cargo run --example visitor_tool -- to-text <binary file> <text file>
+//! cargo run --example visitor_tool -- to-binary <text file> <binary file>
+//! ```
+
+use fyrox_core::visitor::{
+    text::{binary_to_text, text_to_binary},
+    VisitError, Visitor,
+};
+use std::{env, fs};
+
+const USAGE: &str = "usage: visitor_tool schema <file>
+       visitor_tool sizes <file> [max depth]
+       visitor_tool to-text <binary file> <text file>
+       visitor_tool to-binary <text file> <binary file>";
+
+fn run(args: &[String]) -> Result<(), VisitError> {
+    match args {
+        [command, input] if command == "schema" => {
+            let visitor = Visitor::load_from_memory(fs::read(input)?)?;
+            print!("{}", visitor.schema()?);
+        }
+        [command, input, rest @ ..] if command == "sizes" && rest.len() <= 1 => {
+            let max_depth = match rest.first() {
+                Some(depth) => depth
+                    .parse()
+                    .map_err(|_| VisitError::User(format!("invalid depth {}", depth)))?,
+                None => 3,
+            };
+            let visitor = Visitor::load_from_memory(fs::read(input)?)?;
+            print!("{}", visitor.size_report(max_depth)?);
+        }
+        [command, input, output] if command == "to-text" => {
+            fs::write(output, binary_to_text(fs::read(input)?)?)?;
+        }
+        [command, input, output] if command == "to-binary" => {
+            fs::write(output, text_to_binary(&fs::read_to_string(input)?)?)?;
+        }
+        _ => return Err(VisitError::User(USAGE.to_owned())),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -15,6 +15,14 @@ impl GradientPoint {
     pub fn new(location: f32, color: Color) -> Self {
         Self { location, color }
     }
+
+    pub fn location(&self) -> f32 {
+        self.location
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
 }
 
 impl Default for GradientPoint {
@@ -122,6 +130,16 @@ impl ColorGradient {
     pub fn clear(&mut self) {
         self.points.clear()
     }
+
+    /// Returns points of the gradient sorted by their locations.
+    pub fn points(&self) -> &[GradientPoint] {
+        &self.points
+    }
+
+    /// Removes a point at the given index and returns it. Panics if the index is out of bounds.
+    pub fn remove_point(&mut self, index: usize) -> GradientPoint {
+        self.points.remove(index)
+    }
 }
 
 #[derive(Default)]
@@ -212,6 +212,15 @@ impl Curve {
         }
     }
 
+    #[inline]
+    pub fn remove_key(&mut self, key_id: usize) -> Option<CurveKey> {
+        if key_id < self.keys.len() {
+            Some(self.keys.remove(key_id))
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn max_location(&self) -> f32 {
         self.keys.last().map(|k| k.location).unwrap_or_default()
@@ -302,4 +311,17 @@ mod test {
         // Check interpolation.
         assert_eq!(curve.value_at(0.5), 0.5);
     }
+
+    #[test]
+    fn test_curve_key_removal() {
+        let mut curve = Curve::from(vec![
+            CurveKey::new(0.0, 0.0, CurveKeyKind::Linear),
+            CurveKey::new(1.0, 1.0, CurveKeyKind::Linear),
+        ]);
+
+        assert!(curve.remove_key(2).is_none());
+        assert_eq!(curve.remove_key(0).map(|k| k.location), Some(0.0));
+        assert_eq!(curve.keys.len(), 1);
+        assert_eq!(curve.value_at(0.0), 1.0);
+    }
 }
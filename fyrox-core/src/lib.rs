@@ -32,11 +32,13 @@ pub mod color_gradient;
 pub mod curve;
 pub mod io;
 pub mod math;
+pub mod memory;
 pub mod numeric_range;
 pub mod octree;
 pub mod pool;
 pub mod profiler;
 pub mod quadtree;
+pub mod random;
 pub mod rectpack;
 pub mod reflect;
 pub mod sparse;
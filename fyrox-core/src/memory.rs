@@ -0,0 +1,631 @@
+//! Memory utilities for transient per-frame data.
+//!
+//! [`FrameArena`] is a bump allocator for short-living allocations, every allocation is just a
+//! pointer increment and the whole arena is reset at once. The engine owns one arena per thread
+//! (see [`with_frame_arena`]) and resets it at the beginning of every frame, so renderer, UI layout,
+//! animation evaluation, etc. could use it for temporary arrays instead of the heap.
+//!
+//! [`TrackingAllocator`] is a global allocator, that counts heap allocations of every [`Subsystem`].
+//! It is opt-in, install it in your executable to see the counters in engine statistics:
+//!
+//! ```no_run
+//! use fyrox_core::memory::TrackingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// Maximum alignment of values, that could be stored in a [`FrameArena`].
+pub const MAX_FRAME_ARENA_ALIGN: usize = 16;
+
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+struct Chunk {
+    ptr: NonNull<u8>,
+    size: usize,
+}
+
+impl Chunk {
+    fn new(size: usize) -> Self {
+        let layout = Layout::from_size_align(size, MAX_FRAME_ARENA_ALIGN).unwrap();
+        // SAFETY: Size of a chunk is never zero.
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        match NonNull::new(ptr) {
+            Some(ptr) => Self { ptr, size },
+            None => std::alloc::handle_alloc_error(layout),
+        }
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        // SAFETY: The pointer was allocated with the same layout in `Chunk::new`.
+        unsafe {
+            std::alloc::dealloc(
+                self.ptr.as_ptr(),
+                Layout::from_size_align_unchecked(self.size, MAX_FRAME_ARENA_ALIGN),
+            )
+        }
+    }
+}
+
+/// Bump allocator for transient allocations. Allocation takes a few instructions, there is no
+/// way to free a single allocation - every allocation lives until [`FrameArena::reset`], which
+/// requires mutable access and thus ensures that there are no references to the memory of the
+/// arena.
+///
+/// Only [`Copy`] types could be stored in the arena, because it never runs destructors.
+///
+/// ```
+/// use fyrox_core::memory::FrameArena;
+///
+/// let mut arena = FrameArena::new();
+/// let mut stack = arena.vec();
+/// stack.push(1u32);
+/// stack.extend_from_slice(&[2, 3]);
+/// assert_eq!(stack.pop(), Some(3));
+/// let squares = arena.alloc_slice_copy(&stack);
+/// squares.iter_mut().for_each(|n| *n *= *n);
+/// assert_eq!(squares, &[1, 4]);
+/// arena.reset();
+/// ```
+pub struct FrameArena {
+    chunks: RefCell<Vec<Chunk>>,
+    // Offset of the free space in the last chunk.
+    offset: Cell<usize>,
+    allocated: Cell<usize>,
+}
+
+impl Debug for FrameArena {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FrameArena: {} of {} bytes allocated",
+            self.allocated(),
+            self.capacity()
+        )
+    }
+}
+
+impl Default for FrameArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameArena {
+    /// Creates new empty arena, it does not allocate until the first allocation.
+    pub fn new() -> Self {
+        Self {
+            chunks: Default::default(),
+            offset: Cell::new(0),
+            allocated: Cell::new(0),
+        }
+    }
+
+    /// Creates new arena that could hold at least the given amount of bytes without additional
+    /// heap allocations.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let arena = Self::new();
+        if capacity > 0 {
+            arena.chunks.borrow_mut().push(Chunk::new(capacity));
+        }
+        arena
+    }
+
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        assert!(
+            layout.align() <= MAX_FRAME_ARENA_ALIGN,
+            "Alignment {} is not supported by frame arena!",
+            layout.align()
+        );
+
+        let mut chunks = self.chunks.borrow_mut();
+        let mut offset = (self.offset.get() + layout.align() - 1) & !(layout.align() - 1);
+        let fits = chunks
+            .last()
+            .map_or(false, |chunk| offset + layout.size() <= chunk.size);
+        if !fits {
+            let size = chunks
+                .last()
+                .map_or(DEFAULT_CHUNK_SIZE, |chunk| chunk.size * 2)
+                .max(layout.size());
+            chunks.push(Chunk::new(size));
+            offset = 0;
+        }
+
+        self.offset.set(offset + layout.size());
+        self.allocated.set(self.allocated.get() + layout.size());
+
+        let chunk = chunks.last().unwrap();
+        // SAFETY: The offset is within the chunk, the memory is not used by other allocations.
+        unsafe { NonNull::new_unchecked(chunk.ptr.as_ptr().add(offset)) }
+    }
+
+    fn alloc_array<T>(&self, len: usize) -> NonNull<T> {
+        if mem::size_of::<T>() == 0 || len == 0 {
+            NonNull::dangling()
+        } else {
+            self.alloc_layout(Layout::array::<T>(len).unwrap()).cast()
+        }
+    }
+
+    /// Moves the value to the arena and returns a reference to it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_array::<T>(1);
+        // SAFETY: The memory is allocated for the value and it is not used by anything else.
+        unsafe {
+            ptr::write(ptr.as_ptr(), value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copies the slice to the arena and returns a reference to the copy.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, src: &[T]) -> &mut [T] {
+        let ptr = self.alloc_array::<T>(src.len());
+        // SAFETY: The memory is allocated for `src.len()` elements and it is not used by anything else.
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), ptr.as_ptr(), src.len());
+            slice::from_raw_parts_mut(ptr.as_ptr(), src.len())
+        }
+    }
+
+    /// Allocates a slice of the given length filled with the value.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_fill<T: Copy>(&self, len: usize, value: T) -> &mut [T] {
+        let ptr = self.alloc_array::<T>(len);
+        // SAFETY: The memory is allocated for `len` elements and it is not used by anything else.
+        unsafe {
+            for i in 0..len {
+                ptr::write(ptr.as_ptr().add(i), value);
+            }
+            slice::from_raw_parts_mut(ptr.as_ptr(), len)
+        }
+    }
+
+    /// Creates new empty growable array in the arena.
+    pub fn vec<T: Copy>(&self) -> ArenaVec<'_, T> {
+        self.vec_with_capacity(0)
+    }
+
+    /// Creates new empty growable array in the arena, that could hold the given amount of elements
+    /// without reallocation.
+    pub fn vec_with_capacity<T: Copy>(&self, capacity: usize) -> ArenaVec<'_, T> {
+        ArenaVec {
+            arena: self,
+            ptr: self.alloc_array(capacity),
+            len: 0,
+            capacity: if mem::size_of::<T>() == 0 {
+                usize::MAX
+            } else {
+                capacity
+            },
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns amount of bytes allocated since the last reset (excluding alignment padding).
+    pub fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+
+    /// Returns total size of memory blocks owned by the arena.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(|chunk| chunk.size).sum()
+    }
+
+    /// Frees every allocation of the arena at once. If the arena had to allocate more than one memory
+    /// block since the last reset, the blocks are replaced with a single one of total size, so the
+    /// next frame with the same allocations won't touch the heap at all.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if chunks.len() > 1 {
+            let size = chunks.iter().map(|chunk| chunk.size).sum();
+            chunks.clear();
+            chunks.push(Chunk::new(size));
+        }
+        self.offset.set(0);
+        self.allocated.set(0);
+    }
+}
+
+/// Growable array, that stores its elements in a [`FrameArena`]. Growing the array does not free
+/// previous memory block until arena reset, so it is better to reserve enough capacity if it is known.
+pub struct ArenaVec<'a, T: Copy> {
+    arena: &'a FrameArena,
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    phantom: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T: Copy + Debug> Debug for ArenaVec<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<'a, T: Copy> ArenaVec<'a, T> {
+    /// Makes sure, that the array could hold at least `additional` more elements without
+    /// reallocation.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len + additional;
+        if required > self.capacity {
+            let capacity = required.max(self.capacity * 2).max(4);
+            let ptr = self.arena.alloc_array::<T>(capacity);
+            // SAFETY: Both memory blocks are valid for `len` elements and do not overlap.
+            unsafe { ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len) };
+            self.ptr = ptr;
+            self.capacity = capacity;
+        }
+    }
+
+    /// Appends an element to the end of the array.
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        // SAFETY: The capacity is enough to hold one more element.
+        unsafe { ptr::write(self.ptr.as_ptr().add(self.len), value) };
+        self.len += 1;
+    }
+
+    /// Removes the last element of the array and returns it, `None` if the array is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            // SAFETY: The element was initialized by `push` or `extend_from_slice`.
+            Some(unsafe { ptr::read(self.ptr.as_ptr().add(self.len)) })
+        }
+    }
+
+    /// Appends every element of the slice to the end of the array.
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.reserve(other.len());
+        // SAFETY: The capacity is enough to hold every element of the slice.
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.ptr.as_ptr().add(self.len), other.len())
+        };
+        self.len += other.len();
+    }
+
+    /// Removes every element of the array, the capacity is kept.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns amount of elements the array could hold without reallocation.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<'a, T: Copy> Extend<T> for ArenaVec<'a, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, T: Copy> Deref for ArenaVec<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: First `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<'a, T: Copy> DerefMut for ArenaVec<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: First `len` elements are initialized.
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+thread_local! {
+    static FRAME_ARENA: RefCell<FrameArena> = RefCell::new(FrameArena::new());
+}
+
+/// Calls the given closure with the frame arena of the current thread. Allocations can't outlive
+/// the closure, nested calls are allowed.
+///
+/// ```
+/// use fyrox_core::memory::with_frame_arena;
+///
+/// let sum = with_frame_arena(|arena| {
+///     let mut numbers = arena.vec();
+///     numbers.extend(0..10u32);
+///     numbers.iter().sum::<u32>()
+/// });
+/// assert_eq!(sum, 45);
+/// ```
+pub fn with_frame_arena<F, R>(func: F) -> R
+where
+    F: FnOnce(&FrameArena) -> R,
+{
+    FRAME_ARENA.with(|arena| func(&arena.borrow()))
+}
+
+/// Frees every allocation of the frame arena of the current thread and returns the amount of bytes
+/// that was allocated since the previous reset. The engine calls it at the beginning of every
+/// frame.
+///
+/// # Panics
+///
+/// Panics if it is called inside of [`with_frame_arena`].
+pub fn reset_frame_arena() -> usize {
+    FRAME_ARENA.with(|arena| {
+        let mut arena = arena.borrow_mut();
+        let allocated = arena.allocated();
+        arena.reset();
+        allocated
+    })
+}
+
+/// Engine subsystem, that heap allocations are attributed to. See [`AllocationScope`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Subsystem {
+    /// Everything outside of the other subsystems.
+    Other = 0,
+    /// Scene graph update, except physics, animation and sound.
+    Scenes = 1,
+    /// Physics simulation.
+    Physics = 2,
+    /// Animation players and animation blending state machines.
+    Animation = 3,
+    /// Sound update.
+    Sound = 4,
+    /// Plugins update.
+    Plugins = 5,
+    /// Scripts update.
+    Scripts = 6,
+    /// User interface update (layout, message processing) and drawing.
+    Ui = 7,
+    /// Renderer.
+    Renderer = 8,
+}
+
+impl Subsystem {
+    /// Amount of subsystems.
+    pub const COUNT: usize = 9;
+
+    /// Every subsystem in the order of their ids.
+    pub const ALL: [Subsystem; Self::COUNT] = [
+        Subsystem::Other,
+        Subsystem::Scenes,
+        Subsystem::Physics,
+        Subsystem::Animation,
+        Subsystem::Sound,
+        Subsystem::Plugins,
+        Subsystem::Scripts,
+        Subsystem::Ui,
+        Subsystem::Renderer,
+    ];
+}
+
+impl Default for Subsystem {
+    fn default() -> Self {
+        Self::Other
+    }
+}
+
+thread_local! {
+    static CURRENT_SUBSYSTEM: Cell<u8> = Cell::new(Subsystem::Other as u8);
+}
+
+/// A guard, that attributes every heap allocation of the current thread to the given subsystem
+/// until it is dropped. Scopes could be nested, the previous subsystem is restored on drop.
+///
+/// ```
+/// use fyrox_core::memory::{AllocationScope, Subsystem};
+///
+/// fn layout() {
+///     let _scope = AllocationScope::new(Subsystem::Ui);
+///     // Allocations here are counted as UI allocations.
+/// }
+/// ```
+#[must_use = "the scope ends when the guard is dropped"]
+pub struct AllocationScope {
+    previous: u8,
+}
+
+impl AllocationScope {
+    /// Makes the subsystem current for the thread.
+    pub fn new(subsystem: Subsystem) -> Self {
+        Self {
+            previous: CURRENT_SUBSYSTEM.with(|current| current.replace(subsystem as u8)),
+        }
+    }
+}
+
+impl Drop for AllocationScope {
+    fn drop(&mut self) {
+        CURRENT_SUBSYSTEM.with(|current| current.set(self.previous));
+    }
+}
+
+struct Counter {
+    count: AtomicUsize,
+    bytes: AtomicUsize,
+}
+
+impl Counter {
+    const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+        }
+    }
+}
+
+static COUNTERS: [Counter; Subsystem::COUNT] = [
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+];
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+fn record_allocation(size: usize) {
+    if !TRACKING.load(Ordering::Relaxed) {
+        TRACKING.store(true, Ordering::Relaxed);
+    }
+    // Thread-local storage could be already destroyed when a thread is shutting down.
+    let subsystem = CURRENT_SUBSYSTEM
+        .try_with(|current| current.get())
+        .unwrap_or(Subsystem::Other as u8);
+    let counter = &COUNTERS[subsystem as usize];
+    counter.count.fetch_add(1, Ordering::Relaxed);
+    counter.bytes.fetch_add(size, Ordering::Relaxed);
+}
+
+/// Global allocator, that counts heap allocations (including reallocations) of every
+/// [`Subsystem`] and then passes them to the system allocator. See [module docs](self) for more
+/// info.
+pub struct TrackingAllocator;
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Counters of heap allocations of every subsystem, see [`take_allocation_counters`].
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct AllocationCounters {
+    /// `false` if [`TrackingAllocator`] is not installed, every counter is zero in this case.
+    pub tracking: bool,
+    /// Amount of allocations of every subsystem, indexed by subsystem id.
+    pub counts: [usize; Subsystem::COUNT],
+    /// Amount of allocated bytes of every subsystem, indexed by subsystem id.
+    pub bytes: [usize; Subsystem::COUNT],
+}
+
+impl AllocationCounters {
+    /// Returns amount of allocations of the subsystem.
+    pub fn count(&self, subsystem: Subsystem) -> usize {
+        self.counts[subsystem as usize]
+    }
+
+    /// Returns amount of allocated bytes of the subsystem.
+    pub fn bytes(&self, subsystem: Subsystem) -> usize {
+        self.bytes[subsystem as usize]
+    }
+
+    /// Returns total amount of allocations.
+    pub fn total_count(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns total amount of allocated bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.bytes.iter().sum()
+    }
+}
+
+/// Returns allocation counters (of every thread) since the previous call and resets them. The engine
+/// calls it at the beginning of every frame, so the counters of the last frame are available in
+/// engine statistics.
+pub fn take_allocation_counters() -> AllocationCounters {
+    let mut counters = AllocationCounters {
+        tracking: TRACKING.load(Ordering::Relaxed),
+        ..Default::default()
+    };
+    for (i, counter) in COUNTERS.iter().enumerate() {
+        counters.counts[i] = counter.count.swap(0, Ordering::Relaxed);
+        counters.bytes[i] = counter.bytes.swap(0, Ordering::Relaxed);
+    }
+    counters
+}
+
+#[cfg(test)]
+mod test {
+    use crate::memory::{AllocationScope, FrameArena, Subsystem, CURRENT_SUBSYSTEM};
+
+    #[test]
+    fn test_frame_arena() {
+        let mut arena = FrameArena::with_capacity(64);
+
+        let value = arena.alloc(123u64);
+        assert_eq!(*value, 123);
+        assert_eq!(value as *mut u64 as usize % 8, 0);
+
+        // Exceeds the first chunk.
+        let big = arena.alloc_slice_fill(100, 1u32);
+        assert_eq!(big.len(), 100);
+        assert!(big.iter().all(|n| *n == 1));
+
+        let mut vec = arena.vec();
+        for i in 0..1000u16 {
+            vec.push(i);
+        }
+        assert_eq!(vec.len(), 1000);
+        assert_eq!(vec[999], 999);
+        assert_eq!(vec.pop(), Some(999));
+        assert!(arena.allocated() > 2000);
+
+        let capacity = arena.capacity();
+        arena.reset();
+        assert_eq!(arena.allocated(), 0);
+        assert_eq!(arena.capacity(), capacity);
+        assert_eq!(arena.chunks.borrow().len(), 1);
+
+        let empty = arena.alloc_slice_copy::<u8>(&[]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_allocation_scope() {
+        let current = || CURRENT_SUBSYSTEM.with(|current| current.get());
+        {
+            let _ui = AllocationScope::new(Subsystem::Ui);
+            assert_eq!(current(), Subsystem::Ui as u8);
+            {
+                let _renderer = AllocationScope::new(Subsystem::Renderer);
+                assert_eq!(current(), Subsystem::Renderer as u8);
+            }
+            assert_eq!(current(), Subsystem::Ui as u8);
+        }
+        assert_eq!(current(), Subsystem::Other as u8);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::num_traits::Num;
+use crate::{num_traits::Num, random::with_rng};
 use rand::{distributions::uniform::SampleUniform, Rng};
 use std::ops::Range;
 
@@ -38,7 +38,7 @@ impl<T: Num + PartialOrd + SampleUniform + Copy> RangeExt<T> for Range<T> {
     fn random(&self) -> T {
         let start = min(self.start, self.end);
         let end = max(self.start, self.end);
-        rand::thread_rng().gen_range(Range { start, end })
+        with_rng(|rng| rng.gen_range(Range { start, end }))
     }
 
     #[inline]
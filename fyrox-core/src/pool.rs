@@ -30,6 +30,7 @@ use crate::{
 use arrayvec::ArrayVec;
 use std::any::Any;
 use std::{
+    cmp::Ordering,
     fmt::{Debug, Display, Formatter},
     future::Future,
     hash::{Hash, Hasher},
@@ -377,6 +378,21 @@ impl<T> Hash for Handle<T> {
     }
 }
 
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    /// Handles are ordered by their index first and then by their generation.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index
+            .cmp(&other.index)
+            .then_with(|| self.generation.cmp(&other.generation))
+    }
+}
+
 impl<T> Handle<T> {
     pub const NONE: Handle<T> = Handle {
         index: 0,
@@ -1633,6 +1649,16 @@ mod test {
         pool.free(baz);
     }
 
+    #[test]
+    fn handle_ordering() {
+        let a = Handle::<()>::new(1, 2);
+        let b = Handle::<()>::new(2, 1);
+        let c = Handle::<()>::new(2, 3);
+        assert!(a < b);
+        assert!(b < c);
+        assert_eq!(a.cmp(&a), std::cmp::Ordering::Equal);
+    }
+
     #[test]
     fn handle_of() {
         #[allow(dead_code)]
@@ -0,0 +1,102 @@
+//! A source of random numbers used by the engine (particle systems, SSAO kernels, etc.). By
+//! default it is `rand::thread_rng()`, but it could be replaced with a seeded generator for the
+//! current thread to get deterministic results, which is useful in tests.
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    static SEEDED_RNG: RefCell<Option<StdRng>> = RefCell::new(None);
+}
+
+/// Sets a seed for random numbers of the current thread. `None` restores default behaviour
+/// (non-deterministic thread-local generator).
+pub fn set_seed(seed: Option<u64>) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = seed.map(StdRng::seed_from_u64));
+}
+
+/// Returns `true` if random numbers of the current thread are seeded.
+pub fn is_seeded() -> bool {
+    SEEDED_RNG.with(|rng| rng.borrow().is_some())
+}
+
+/// Calls the given closure with current random number generator of the thread.
+///
+/// # Panics
+///
+/// Panics if called from inside of the closure.
+pub fn with_rng<R, F>(func: F) -> R
+where
+    F: FnOnce(&mut dyn RngCore) -> R,
+{
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(rng) => func(rng),
+        None => func(&mut rand::thread_rng()),
+    })
+}
+
+/// Makes the given generator current for the thread while the closure runs, so every random
+/// number requested inside of the closure (via [`with_rng`]) will be taken from it. It allows
+/// objects to have their own deterministic sequences of random numbers.
+pub fn with_rng_override<R, F>(rng: &mut StdRng, func: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = SEEDED_RNG.with(|current| current.replace(Some(rng.clone())));
+    let result = func();
+    if let Some(state) = SEEDED_RNG.with(|current| current.replace(prev)) {
+        *rng = state;
+    }
+    result
+}
+
+/// Creates a new generator, that is seeded by the current generator of the thread. It is useful
+/// when lots of random numbers are needed at once.
+pub fn new_rng() -> StdRng {
+    with_rng(|rng| StdRng::from_rng(rng).expect("Unable to seed random number generator!"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{is_seeded, new_rng, set_seed, with_rng, with_rng_override};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_seeded_rng() {
+        let sample = || -> Vec<u32> {
+            let mut values = (0..4)
+                .map(|_| with_rng(|rng| rng.gen_range(0..1000)))
+                .collect::<Vec<_>>();
+            values.push(new_rng().gen());
+            values
+        };
+
+        set_seed(Some(42));
+        let a = sample();
+        set_seed(Some(42));
+        let b = sample();
+        set_seed(None);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_rng_override() {
+        let sample = |rng: &mut StdRng| {
+            with_rng_override(rng, || {
+                (0..4)
+                    .map(|_| with_rng(|rng| rng.gen_range(0..1000)))
+                    .collect::<Vec<u32>>()
+            })
+        };
+
+        let mut a = StdRng::seed_from_u64(1);
+        let mut b = StdRng::seed_from_u64(1);
+        assert_eq!(sample(&mut a), sample(&mut b));
+        // State of the generator must be preserved between calls.
+        let first = sample(&mut a);
+        assert_eq!(first, sample(&mut b));
+        assert_ne!(first, sample(&mut a));
+        assert!(!is_seeded());
+    }
+}
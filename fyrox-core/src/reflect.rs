@@ -366,6 +366,57 @@ pub fn path_to_components(path: &str) -> Vec<Component> {
     components
 }
 
+/// Assigns a new value to a property at given path. Unlike assignment through
+/// [`ResolvePath::resolve_path_mut`], the value is passed to [`Reflect::set_field`] of the owner
+/// of the property, so setters specified with `#[reflect(setter = ..)]` are called. Items of
+/// collections (paths like `items[3]`) have no setters and are replaced as is.
+///
+/// Returns previous value of the property on success, or the given value back if there is no such
+/// property or the value has wrong type.
+pub fn set_field_by_path(
+    entity: &mut dyn Reflect,
+    path: &str,
+    value: Box<dyn Reflect>,
+) -> Result<Box<dyn Reflect>, Box<dyn Reflect>> {
+    let mut components = path_to_components(path);
+    match components.pop() {
+        Some(Component::Field(field)) => {
+            let mut parent_path = String::new();
+            for component in components {
+                match component {
+                    Component::Field(s) => {
+                        if !parent_path.is_empty() {
+                            parent_path.push('.');
+                        }
+                        parent_path += s;
+                    }
+                    Component::Index(s) => {
+                        parent_path.push('[');
+                        parent_path += s;
+                        parent_path.push(']');
+                    }
+                }
+            }
+
+            let parent = if parent_path.is_empty() {
+                entity
+            } else {
+                match entity.resolve_path_mut(&parent_path) {
+                    Ok(parent) => parent,
+                    Err(_) => return Err(value),
+                }
+            };
+
+            parent.set_field(field, value)
+        }
+        Some(Component::Index(_)) => match entity.resolve_path_mut(path) {
+            Ok(item) => item.set(value),
+            Err(_) => Err(value),
+        },
+        None => Err(value),
+    }
+}
+
 /// Helper methods over [`Reflect`] types
 pub trait GetField {
     fn get_field<T: 'static>(&self, name: &str) -> Option<&T>;
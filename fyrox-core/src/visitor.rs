@@ -10,6 +10,8 @@
 
 pub use fyrox_core_derive::Visit;
 
+pub mod text;
+
 pub mod prelude {
     //! Types to use `#[derive(Visit)]`
     pub use super::{Visit, VisitError, VisitResult, Visitor};
@@ -531,6 +533,7 @@ pub enum VisitError {
     UnexpectedRcNullIndex,
     PoisonedMutex,
     FileLoadError(FileLoadError),
+    InvalidTextFormat { line: usize, reason: String },
 }
 
 impl Display for VisitError {
@@ -553,6 +556,9 @@ impl Display for VisitError {
             Self::UnexpectedRcNullIndex => write!(f, "unexpected rc null index"),
             Self::PoisonedMutex => write!(f, "attempt to lock poisoned mutex"),
             Self::FileLoadError(e) => write!(f, "file load error: {:?}", e),
+            Self::InvalidTextFormat { line, reason } => {
+                write!(f, "invalid text format at line {}: {}", line, reason)
+            }
         }
     }
 }
@@ -906,7 +912,7 @@ impl Field {
                     for n in &mut f {
                         *n = file.read_f32::<LittleEndian>()?;
                     }
-                    Matrix4::from_row_slice(&f)
+                    Matrix4::from_column_slice(&f)
                 }),
                 14 => FieldKind::Data({
                     let len = file.read_u32::<LittleEndian>()? as usize;
@@ -920,7 +926,7 @@ impl Field {
                     for n in &mut f {
                         *n = file.read_f32::<LittleEndian>()?;
                     }
-                    Matrix3::from_row_slice(&f)
+                    Matrix3::from_column_slice(&f)
                 }),
                 17 => FieldKind::Vector2F32({
                     let x = file.read_f32::<LittleEndian>()?;
@@ -957,11 +963,11 @@ impl Field {
                     }
                 }
                 22 => FieldKind::Matrix2({
-                    let mut f = [0.0f32; 3];
+                    let mut f = [0.0f32; 4];
                     for n in &mut f {
                         *n = file.read_f32::<LittleEndian>()?;
                     }
-                    Matrix2::from_row_slice(&f)
+                    Matrix2::from_column_slice(&f)
                 }),
                 23 => FieldKind::Vector2F64(read_vec_n(file)?),
                 24 => FieldKind::Vector3F64(read_vec_n(file)?),
@@ -1025,6 +1031,36 @@ impl Node {
             children: Vec::new(),
         }
     }
+
+    /// Returns raw bytes of a string value stored in the node. Strings and paths are stored as
+    /// a region with `Length` and `Data` fields.
+    fn string_value(&self) -> Option<&[u8]> {
+        match self.fields.as_slice() {
+            [Field {
+                name: length_name,
+                kind: FieldKind::U32(length),
+            }, Field {
+                name: data_name,
+                kind: FieldKind::Data(data),
+            }] if length_name == "Length"
+                && data_name == "Data"
+                && *length as usize == data.len() =>
+            {
+                Some(data)
+            }
+            _ => None,
+        }
+    }
+
+    fn set_string_value(&mut self, value: &str) {
+        for field in self.fields.iter_mut() {
+            match field.kind {
+                FieldKind::U32(ref mut length) => *length = value.len() as u32,
+                FieldKind::Data(ref mut data) => *data = value.as_bytes().to_vec(),
+                _ => (),
+            }
+        }
+    }
 }
 
 impl Default for Node {
@@ -1063,6 +1099,19 @@ impl<'a> Drop for RegionGuard<'a> {
     }
 }
 
+/// Size of a region of a visitor in binary format, see [`Visitor::region_sizes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionSize {
+    /// Names of the region and all its ancestors (except the root) separated by `/`.
+    pub path: String,
+    /// Nesting level of the region, the root region has zero depth.
+    pub depth: usize,
+    /// Size of the fields of the region in bytes.
+    pub fields_size: usize,
+    /// Total size of the region (including all its descendants) in bytes.
+    pub total_size: usize,
+}
+
 pub struct Visitor {
     nodes: Pool<Node>,
     rc_map: FxHashMap<u64, Rc<dyn Any>>,
@@ -1190,12 +1239,250 @@ impl Visitor {
         }
     }
 
+    /// Replaces every string value (both strings and paths), that is equal to `old`, with `new` and
+    /// returns the amount of replaced values. It allows to fix references to moved or renamed
+    /// files in serialized data without knowing its actual structure.
+    pub fn replace_strings(&mut self, old: &str, new: &str) -> usize {
+        let mut count = 0;
+        for node in self.nodes.iter_mut() {
+            if node.string_value() == Some(old.as_bytes()) {
+                node.set_string_value(new);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns `true` if there is at least one string value (both strings and paths) that is equal
+    /// to the given one.
+    pub fn contains_string(&self, value: &str) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| node.string_value() == Some(value.as_bytes()))
+    }
+
+    fn flatten_node(&self, node_handle: Handle<Node>, path: &str, out: &mut Vec<(String, String)>) {
+        let node = self.nodes.borrow(node_handle);
+        if let Some(string) = node.string_value() {
+            out.push((path.to_owned(), String::from_utf8_lossy(string).to_string()));
+        } else {
+            for field in node.fields.iter() {
+                out.push((
+                    format!("{}/{}", path, field.name),
+                    field.kind.as_string().trim_end_matches(", ").to_owned(),
+                ));
+            }
+        }
+
+        for child_handle in node.children.iter() {
+            let child = self.nodes.borrow(*child_handle);
+            let child_path = if path.is_empty() {
+                child.name.clone()
+            } else {
+                format!("{}/{}", path, child.name)
+            };
+            self.flatten_node(*child_handle, &child_path, out);
+        }
+    }
+
+    /// Returns every value stored in the visitor as a pair of its path (names of the regions and
+    /// the field separated by `/`) and its textual representation. Strings and paths are returned
+    /// as a single value with the path of their region. The output is deterministic and could be
+    /// used to compare two pieces of serialized data without knowing their actual structure.
+    pub fn flatten(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        self.flatten_node(self.root, "", &mut out);
+        out
+    }
+
     pub fn save_text(&self) -> String {
         let mut out_string = String::new();
         self.print_node(self.root, 0, &mut out_string);
         out_string
     }
 
+    fn schema_body(&self, node_handle: Handle<Node>, nesting: usize) -> Result<String, VisitError> {
+        fn is_item(name: &str) -> bool {
+            name.strip_prefix("Item")
+                .map_or(false, |index| index.parse::<usize>().is_ok())
+        }
+
+        let offset = "    ".repeat(nesting);
+        let node = self.nodes.borrow(node_handle);
+        let mut out = String::new();
+        for field in node.fields.iter() {
+            let (type_name, _) = text::field_type_and_size(field)?;
+            out += &format!("{}{}: {}\n", offset, field.name, type_name);
+        }
+
+        let children = node
+            .children
+            .iter()
+            .map(|child| {
+                let name = self.nodes.borrow(*child).name.as_str();
+                self.schema_body(*child, nesting + 1)
+                    .map(|body| (name, body))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Collection items with the same structure are printed only once.
+        let mut i = 0;
+        while i < children.len() {
+            let (name, body) = &children[i];
+            let mut end = i + 1;
+            if is_item(name) {
+                while end < children.len() && is_item(children[end].0) && children[end].1 == *body {
+                    end += 1;
+                }
+            }
+            if end - i > 1 {
+                out += &format!(
+                    "{}{}..{} [{} items]\n",
+                    offset,
+                    name,
+                    children[end - 1].0,
+                    end - i
+                );
+            } else {
+                out += &format!("{}{}\n", offset, name);
+            }
+            out += body;
+            i = end;
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the structure of the data stored in the visitor: the tree of the regions with names
+    /// and types of their fields, but without actual values. Consecutive collection items with the
+    /// same structure are printed once.
+    pub fn schema(&self) -> Result<String, VisitError> {
+        let root = self.nodes.borrow(self.root);
+        Ok(format!(
+            "{}\n{}",
+            root.name,
+            self.schema_body(self.root, 1)?
+        ))
+    }
+
+    fn collect_region_sizes(
+        &self,
+        node_handle: Handle<Node>,
+        path: &str,
+        depth: usize,
+        out: &mut Vec<RegionSize>,
+    ) -> Result<usize, VisitError> {
+        let node = self.nodes.borrow(node_handle);
+        let mut fields_size = 0;
+        for field in node.fields.iter() {
+            fields_size += text::field_type_and_size(field)?.1;
+        }
+
+        let index = out.len();
+        out.push(RegionSize {
+            path: path.to_owned(),
+            depth,
+            fields_size,
+            total_size: 0,
+        });
+
+        // Name, field count and children count.
+        let mut total_size = 4 + node.name.len() + 4 + 4 + fields_size;
+        for child_handle in node.children.iter() {
+            let child = self.nodes.borrow(*child_handle);
+            let child_path = if path.is_empty() {
+                child.name.clone()
+            } else {
+                format!("{}/{}", path, child.name)
+            };
+            total_size += self.collect_region_sizes(*child_handle, &child_path, depth + 1, out)?;
+        }
+
+        out[index].total_size = total_size;
+        Ok(total_size)
+    }
+
+    /// Returns sizes of every region in binary format in depth-first order, the first one is the
+    /// root region and its size is the size of entire data (except the format header).
+    pub fn region_sizes(&self) -> Result<Vec<RegionSize>, VisitError> {
+        let mut out = Vec::new();
+        self.collect_region_sizes(self.root, "", 0, &mut out)?;
+        Ok(out)
+    }
+
+    /// Returns human-readable report of the sizes of the regions up to the given depth. Regions of
+    /// each level are sorted by their size, regions smaller than 1% of total size are folded into
+    /// a single line. It helps to find out what takes most of the space in a file.
+    pub fn size_report(&self, max_depth: usize) -> Result<String, VisitError> {
+        fn format_size(size: usize) -> String {
+            if size >= 1024 * 1024 {
+                format!("{:.2} MiB", size as f64 / (1024.0 * 1024.0))
+            } else if size >= 1024 {
+                format!("{:.2} KiB", size as f64 / 1024.0)
+            } else {
+                format!("{} B", size)
+            }
+        }
+
+        fn print(
+            sizes: &[RegionSize],
+            index: usize,
+            total: usize,
+            max_depth: usize,
+            out: &mut String,
+        ) -> usize {
+            let region = &sizes[index];
+            let offset = "    ".repeat(region.depth);
+            let name = region.path.rsplit('/').next().unwrap_or_default();
+            *out += &format!(
+                "{}{} - {} ({:.1}%)\n",
+                offset,
+                if region.depth == 0 { "__ROOT__" } else { name },
+                format_size(region.total_size),
+                region.total_size as f64 * 100.0 / total.max(1) as f64
+            );
+
+            // Children follow the region until the first region with the same or lower depth.
+            let mut children = Vec::new();
+            let mut next = index + 1;
+            while next < sizes.len() && sizes[next].depth > region.depth {
+                if sizes[next].depth == region.depth + 1 {
+                    children.push(next);
+                }
+                next += 1;
+            }
+
+            if region.depth < max_depth {
+                children.sort_by(|a, b| sizes[*b].total_size.cmp(&sizes[*a].total_size));
+                let mut folded_count = 0;
+                let mut folded_size = 0;
+                for child in children {
+                    if sizes[child].total_size * 100 >= total {
+                        print(sizes, child, total, max_depth, out);
+                    } else {
+                        folded_count += 1;
+                        folded_size += sizes[child].total_size;
+                    }
+                }
+                if folded_count > 0 {
+                    *out += &format!(
+                        "{}    ... {} more - {}\n",
+                        offset,
+                        folded_count,
+                        format_size(folded_size)
+                    );
+                }
+            }
+
+            next
+        }
+
+        let sizes = self.region_sizes()?;
+        let mut out = String::new();
+        print(&sizes, 0, sizes[0].total_size, max_depth, &mut out);
+        Ok(out)
+    }
+
     pub fn save_binary_to_memory<W: Write>(&self, mut writer: W) -> VisitResult {
         writer.write_all(Self::MAGIC.as_bytes())?;
         let mut stack = vec![self.root];
@@ -1809,7 +2096,12 @@ impl<T: Visit> Visit for Range<T> {
 #[cfg(test)]
 mod test {
     use crate::visitor::{Data, Visit, VisitResult, Visitor};
-    use std::{fs::File, io::Write, path::Path, rc::Rc};
+    use std::{
+        fs::File,
+        io::Write,
+        path::{Path, PathBuf},
+        rc::Rc,
+    };
 
     #[derive(Visit, Default)]
     pub struct Model {
@@ -1912,4 +2204,146 @@ mod test {
             objects.visit("Objects", &mut visitor).unwrap();
         }
     }
+
+    #[test]
+    fn visitor_replace_strings_test() {
+        #[derive(Visit, Default)]
+        struct Asset {
+            name: String,
+            path: PathBuf,
+            other_path: PathBuf,
+        }
+
+        let mut asset = Asset {
+            name: "data/foo.png".to_owned(),
+            path: PathBuf::from("data/foo.png"),
+            other_path: PathBuf::from("data/bar.png"),
+        };
+        let mut visitor = Visitor::new();
+        asset.visit("Asset", &mut visitor).unwrap();
+
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        assert!(visitor.contains_string("data/foo.png"));
+        assert!(!visitor.contains_string("data/baz.png"));
+        assert_eq!(
+            visitor.replace_strings("data/foo.png", "textures/foo.png"),
+            2
+        );
+        assert!(!visitor.contains_string("data/foo.png"));
+
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        let mut asset = Asset::default();
+        asset.visit("Asset", &mut visitor).unwrap();
+        assert_eq!(asset.name, "textures/foo.png");
+        assert_eq!(asset.path, PathBuf::from("textures/foo.png"));
+        assert_eq!(asset.other_path, PathBuf::from("data/bar.png"));
+    }
+
+    #[test]
+    fn visitor_schema_and_sizes_test() {
+        #[derive(Visit, Default)]
+        struct Item {
+            count: u32,
+        }
+
+        let mut items = vec![Item { count: 1 }, Item { count: 2 }, Item { count: 3 }];
+        let mut visitor = Visitor::new();
+        items.visit("Items", &mut visitor).unwrap();
+
+        assert_eq!(
+            visitor.schema().unwrap(),
+            "__ROOT__\n    Items\n        Length: u32\n        Item0..Item2 [3 items]\n            ItemData\n                Count: u32\n"
+        );
+
+        let sizes = visitor.region_sizes().unwrap();
+        assert_eq!(sizes[1].path, "Items");
+        assert_eq!(sizes[2].path, "Items/Item0");
+        assert_eq!(sizes[2].depth, 2);
+        assert_eq!(
+            sizes[0].total_size + 4,
+            visitor.save_binary_to_vec().unwrap().len()
+        );
+        assert_eq!(
+            sizes[1].total_size,
+            sizes[1].fields_size
+                + 4
+                + "Items".len()
+                + 8
+                + sizes
+                    .iter()
+                    .filter(|s| s.depth == 2)
+                    .map(|s| s.total_size)
+                    .sum::<usize>()
+        );
+        assert!(visitor.size_report(1).unwrap().starts_with("__ROOT__"));
+    }
+
+    #[test]
+    fn visitor_flatten_test() {
+        #[derive(Visit, Default)]
+        struct Item {
+            name: String,
+            count: u32,
+        }
+
+        let mut item = Item {
+            name: "Sword".to_owned(),
+            count: 3,
+        };
+        let mut visitor = Visitor::new();
+        item.visit("Item", &mut visitor).unwrap();
+
+        assert_eq!(
+            visitor.flatten(),
+            // Fields of a region come before its child regions (strings are stored as regions).
+            vec![
+                ("Item/Count".to_owned(), "<u32 = 3>".to_owned()),
+                ("Item/Name".to_owned(), "Sword".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn visitor_matrix_binary_test() {
+        use crate::algebra::{Matrix2, Matrix3, Matrix4};
+
+        // Matrices are written in column-major order (the writer is unchanged, so files saved
+        // by previous versions have the same layout), they must not be transposed on load and
+        // the field after a `Matrix2` must be read correctly.
+        let mut m2 = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+        let mut m3 = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        let mut m4 = Matrix4::new(
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+        );
+        let mut after = 123u32;
+
+        let mut visitor = Visitor::new();
+        m2.visit("M2", &mut visitor).unwrap();
+        m3.visit("M3", &mut visitor).unwrap();
+        m4.visit("M4", &mut visitor).unwrap();
+        after.visit("After", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        // First element of `M2` field is the first column: 1.0, 3.0.
+        let m2_data = [1.0f32, 3.0, 2.0, 4.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert!(data.windows(m2_data.len()).any(|w| w == m2_data.as_slice()));
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded_m2 = Matrix2::default();
+        let mut loaded_m3 = Matrix3::default();
+        let mut loaded_m4 = Matrix4::default();
+        let mut loaded_after = 0u32;
+        loaded_m2.visit("M2", &mut visitor).unwrap();
+        loaded_m3.visit("M3", &mut visitor).unwrap();
+        loaded_m4.visit("M4", &mut visitor).unwrap();
+        loaded_after.visit("After", &mut visitor).unwrap();
+
+        assert_eq!(loaded_m2, m2);
+        assert_eq!(loaded_m3, m3);
+        assert_eq!(loaded_m4, m4);
+        assert_eq!(loaded_after, after);
+    }
 }
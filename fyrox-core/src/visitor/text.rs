@@ -0,0 +1,548 @@
+//! Lossless text format of the visitor. Unlike [`Visitor::save_text`], which is a debug dump, the
+//! text format could be converted back to binary, so it could be used to inspect, diff and manually
+//! fix serialized data (scenes, saved games, etc.).
+//!
+//! Every region is written as its quoted name followed by a block, every field is written as its
+//! quoted name, type and value(s):
+//!
+//! ```text
+//! RG3D_TEXT
+//! "__ROOT__" {
+//!     "Item" {
+//!         "Count": u32 = 3
+//!         "Position": vec3f32 = 1 2.5 -3
+//!         "Name" {
+//!             "Length": u32 = 5
+//!             "Data": data = "Sword"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Binary data that is not a valid UTF-8 string is written in base64 with `b64:` prefix. Empty lines
+//! and lines starting with `//` are ignored.
+
+use crate::{
+    pool::{Handle, Pool},
+    visitor::{Field, Node, VisitError, Visitor},
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    io::{Cursor, Read},
+    str::FromStr,
+};
+use uuid::Uuid;
+
+const TEXT_MAGIC: &str = "RG3D_TEXT";
+
+#[derive(Copy, Clone)]
+enum Scalar {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn read(self, reader: &mut dyn Read) -> Result<String, VisitError> {
+        Ok(match self {
+            Scalar::Bool => (reader.read_u8()? != 0).to_string(),
+            Scalar::U8 => reader.read_u8()?.to_string(),
+            Scalar::I8 => reader.read_i8()?.to_string(),
+            Scalar::U16 => reader.read_u16::<LittleEndian>()?.to_string(),
+            Scalar::I16 => reader.read_i16::<LittleEndian>()?.to_string(),
+            Scalar::U32 => reader.read_u32::<LittleEndian>()?.to_string(),
+            Scalar::I32 => reader.read_i32::<LittleEndian>()?.to_string(),
+            Scalar::U64 => reader.read_u64::<LittleEndian>()?.to_string(),
+            Scalar::I64 => reader.read_i64::<LittleEndian>()?.to_string(),
+            // Display implementation of floats gives the shortest representation that is parsed
+            // back to the same value.
+            Scalar::F32 => reader.read_f32::<LittleEndian>()?.to_string(),
+            Scalar::F64 => reader.read_f64::<LittleEndian>()?.to_string(),
+        })
+    }
+
+    fn write(self, value: &str, out: &mut Vec<u8>) -> Result<(), String> {
+        fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value {}", value))
+        }
+
+        match self {
+            Scalar::Bool => out.write_u8(u8::from(parse::<bool>(value)?)),
+            Scalar::U8 => out.write_u8(parse(value)?),
+            Scalar::I8 => out.write_i8(parse(value)?),
+            Scalar::U16 => out.write_u16::<LittleEndian>(parse(value)?),
+            Scalar::I16 => out.write_i16::<LittleEndian>(parse(value)?),
+            Scalar::U32 => out.write_u32::<LittleEndian>(parse(value)?),
+            Scalar::I32 => out.write_i32::<LittleEndian>(parse(value)?),
+            Scalar::U64 => out.write_u64::<LittleEndian>(parse(value)?),
+            Scalar::I64 => out.write_i64::<LittleEndian>(parse(value)?),
+            Scalar::F32 => out.write_f32::<LittleEndian>(parse(value)?),
+            Scalar::F64 => out.write_f64::<LittleEndian>(parse(value)?),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+enum Layout {
+    Scalars(Scalar, usize),
+    Bytes,
+    Uuid,
+    PodArray,
+}
+
+/// Returns type name and binary layout of a field with given type id. Ids must match the ones used
+/// in [`Field::save`] and [`Field::load`].
+fn layout(id: u8) -> Option<(&'static str, Layout)> {
+    Some(match id {
+        1 => ("u8", Layout::Scalars(Scalar::U8, 1)),
+        2 => ("i8", Layout::Scalars(Scalar::I8, 1)),
+        3 => ("u16", Layout::Scalars(Scalar::U16, 1)),
+        4 => ("i16", Layout::Scalars(Scalar::I16, 1)),
+        5 => ("u32", Layout::Scalars(Scalar::U32, 1)),
+        6 => ("i32", Layout::Scalars(Scalar::I32, 1)),
+        7 => ("u64", Layout::Scalars(Scalar::U64, 1)),
+        8 => ("i64", Layout::Scalars(Scalar::I64, 1)),
+        9 => ("f32", Layout::Scalars(Scalar::F32, 1)),
+        10 => ("f64", Layout::Scalars(Scalar::F64, 1)),
+        11 => ("vec3f32", Layout::Scalars(Scalar::F32, 3)),
+        12 => ("quat", Layout::Scalars(Scalar::F32, 4)),
+        13 => ("mat4", Layout::Scalars(Scalar::F32, 16)),
+        14 => ("data", Layout::Bytes),
+        15 => ("bool", Layout::Scalars(Scalar::Bool, 1)),
+        16 => ("mat3", Layout::Scalars(Scalar::F32, 9)),
+        17 => ("vec2f32", Layout::Scalars(Scalar::F32, 2)),
+        18 => ("vec4f32", Layout::Scalars(Scalar::F32, 4)),
+        19 => ("uuid", Layout::Uuid),
+        20 => ("complex", Layout::Scalars(Scalar::F32, 2)),
+        21 => ("podarray", Layout::PodArray),
+        22 => ("mat2", Layout::Scalars(Scalar::F32, 4)),
+        23 => ("vec2f64", Layout::Scalars(Scalar::F64, 2)),
+        24 => ("vec3f64", Layout::Scalars(Scalar::F64, 3)),
+        25 => ("vec4f64", Layout::Scalars(Scalar::F64, 4)),
+        26 => ("vec2i8", Layout::Scalars(Scalar::I8, 2)),
+        27 => ("vec3i8", Layout::Scalars(Scalar::I8, 3)),
+        28 => ("vec4i8", Layout::Scalars(Scalar::I8, 4)),
+        29 => ("vec2u8", Layout::Scalars(Scalar::U8, 2)),
+        30 => ("vec3u8", Layout::Scalars(Scalar::U8, 3)),
+        31 => ("vec4u8", Layout::Scalars(Scalar::U8, 4)),
+        32 => ("vec2i16", Layout::Scalars(Scalar::I16, 2)),
+        33 => ("vec3i16", Layout::Scalars(Scalar::I16, 3)),
+        34 => ("vec4i16", Layout::Scalars(Scalar::I16, 4)),
+        35 => ("vec2u16", Layout::Scalars(Scalar::U16, 2)),
+        36 => ("vec3u16", Layout::Scalars(Scalar::U16, 3)),
+        37 => ("vec4u16", Layout::Scalars(Scalar::U16, 4)),
+        38 => ("vec2i32", Layout::Scalars(Scalar::I32, 2)),
+        39 => ("vec3i32", Layout::Scalars(Scalar::I32, 3)),
+        40 => ("vec4i32", Layout::Scalars(Scalar::I32, 4)),
+        41 => ("vec2u32", Layout::Scalars(Scalar::U32, 2)),
+        42 => ("vec3u32", Layout::Scalars(Scalar::U32, 3)),
+        43 => ("vec4u32", Layout::Scalars(Scalar::U32, 4)),
+        44 => ("vec2i64", Layout::Scalars(Scalar::I64, 2)),
+        45 => ("vec3i64", Layout::Scalars(Scalar::I64, 3)),
+        46 => ("vec4i64", Layout::Scalars(Scalar::I64, 4)),
+        47 => ("vec2u64", Layout::Scalars(Scalar::U64, 2)),
+        48 => ("vec3u64", Layout::Scalars(Scalar::U64, 3)),
+        49 => ("vec4u64", Layout::Scalars(Scalar::U64, 4)),
+        _ => return None,
+    })
+}
+
+/// Returns binary representation of a field value (without the name of the field) - type id
+/// followed by the value itself.
+fn field_bytes(field: &Field) -> Result<Vec<u8>, VisitError> {
+    let mut bytes = Vec::new();
+    Field::save(field, &mut bytes)?;
+    bytes.drain(..4 + field.name.len());
+    Ok(bytes)
+}
+
+/// Returns type name of a field and its size in binary format.
+pub(super) fn field_type_and_size(field: &Field) -> Result<(&'static str, usize), VisitError> {
+    let bytes = field_bytes(field)?;
+    let (type_name, _) = layout(bytes[0]).ok_or(VisitError::UnknownFieldType(bytes[0]))?;
+    Ok((type_name, 4 + field.name.len() + bytes.len()))
+}
+
+fn write_field(field: &Field, out: &mut String) -> Result<(), VisitError> {
+    let bytes = field_bytes(field)?;
+    let mut reader = Cursor::new(&bytes[1..]);
+    let (type_name, layout) = layout(bytes[0]).ok_or(VisitError::UnknownFieldType(bytes[0]))?;
+
+    *out += &format!("{:?}: {} =", field.name, type_name);
+    match layout {
+        Layout::Scalars(scalar, count) => {
+            for _ in 0..count {
+                out.push(' ');
+                *out += &scalar.read(&mut reader)?;
+            }
+        }
+        Layout::Bytes => {
+            let len = reader.read_u32::<LittleEndian>()? as usize;
+            let data = &bytes[5..5 + len];
+            match std::str::from_utf8(data) {
+                Ok(string) => *out += &format!(" {:?}", string),
+                Err(_) => *out += &format!(" b64:{}", base64::encode(data)),
+            }
+        }
+        Layout::Uuid => {
+            let mut uuid = uuid::Bytes::default();
+            reader.read_exact(&mut uuid)?;
+            *out += &format!(" {}", Uuid::from_bytes(uuid));
+        }
+        Layout::PodArray => {
+            let type_id = reader.read_u8()?;
+            let element_size = reader.read_u32::<LittleEndian>()?;
+            let len = reader.read_u64::<LittleEndian>()? as usize;
+            let data = &bytes[14..14 + len];
+            *out += &format!(" {} {} b64:{}", type_id, element_size, base64::encode(data));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a string in double quotes (with Rust escape sequences) at the beginning of the given
+/// string, returns the parsed string and the rest of the input.
+fn parse_quoted(input: &str) -> Result<(String, &str), String> {
+    let mut chars = input.char_indices();
+    if !matches!(chars.next(), Some((_, '"'))) {
+        return Err("expected a string in double quotes".to_owned());
+    }
+
+    let mut string = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((string, &input[i + 1..])),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some('0') => string.push('\0'),
+                Some('\\') => string.push('\\'),
+                Some('"') => string.push('"'),
+                Some('\'') => string.push('\''),
+                Some('u') => {
+                    let mut code = String::new();
+                    if !matches!(chars.next(), Some((_, '{'))) {
+                        return Err("invalid unicode escape".to_owned());
+                    }
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                        code.push(c);
+                    }
+                    match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                        Some(c) => string.push(c),
+                        None => return Err(format!("invalid unicode escape {}", code)),
+                    }
+                }
+                _ => return Err("invalid escape sequence".to_owned()),
+            },
+            _ => string.push(c),
+        }
+    }
+
+    Err("unterminated string".to_owned())
+}
+
+fn parse_bytes(value: &str) -> Result<Vec<u8>, String> {
+    if let Some(encoded) = value.strip_prefix("b64:") {
+        base64::decode(encoded).map_err(|e| e.to_string())
+    } else {
+        let (string, rest) = parse_quoted(value)?;
+        if rest.trim().is_empty() {
+            Ok(string.into_bytes())
+        } else {
+            Err(format!("unexpected {}", rest))
+        }
+    }
+}
+
+fn parse_field(name: &str, type_name: &str, value: &str) -> Result<Field, String> {
+    let (id, layout) = (1..=u8::MAX)
+        .find_map(|id| match layout(id) {
+            Some((other_type_name, layout)) if other_type_name == type_name => Some((id, layout)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("unknown field type {}", type_name))?;
+
+    let mut bytes = Vec::new();
+    bytes
+        .write_u32::<LittleEndian>(name.len() as u32)
+        .map_err(|e| e.to_string())?;
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.push(id);
+
+    match layout {
+        Layout::Scalars(scalar, count) => {
+            let values = value.split_whitespace().collect::<Vec<_>>();
+            if values.len() != count {
+                return Err(format!(
+                    "{} expects {} value(s), got {}",
+                    type_name,
+                    count,
+                    values.len()
+                ));
+            }
+            for value in values {
+                scalar.write(value, &mut bytes)?;
+            }
+        }
+        Layout::Bytes => {
+            let data = parse_bytes(value)?;
+            bytes
+                .write_u32::<LittleEndian>(data.len() as u32)
+                .map_err(|e| e.to_string())?;
+            bytes.extend_from_slice(&data);
+        }
+        Layout::Uuid => {
+            let uuid = Uuid::parse_str(value).map_err(|e| e.to_string())?;
+            bytes.extend_from_slice(uuid.as_bytes());
+        }
+        Layout::PodArray => {
+            let values = value.split_whitespace().collect::<Vec<_>>();
+            if values.len() != 3 {
+                return Err("podarray expects type id, element size and data".to_owned());
+            }
+            Scalar::U8.write(values[0], &mut bytes)?;
+            Scalar::U32.write(values[1], &mut bytes)?;
+            let data = parse_bytes(values[2])?;
+            bytes
+                .write_u64::<LittleEndian>(data.len() as u64)
+                .map_err(|e| e.to_string())?;
+            bytes.extend_from_slice(&data);
+        }
+    }
+
+    Field::load(&mut Cursor::new(bytes)).map_err(|e| e.to_string())
+}
+
+impl Visitor {
+    fn write_node_text(
+        &self,
+        node_handle: Handle<Node>,
+        nesting: usize,
+        out: &mut String,
+    ) -> Result<(), VisitError> {
+        let indent = "    ".repeat(nesting);
+        let node = self.nodes.borrow(node_handle);
+        *out += &format!("{}{:?} {{\n", indent, node.name);
+        for field in node.fields.iter() {
+            *out += &indent;
+            *out += "    ";
+            write_field(field, out)?;
+            out.push('\n');
+        }
+        for child_handle in node.children.iter() {
+            self.write_node_text(*child_handle, nesting + 1, out)?;
+        }
+        *out += &format!("{}}}\n", indent);
+        Ok(())
+    }
+
+    /// Writes the entire content of the visitor in the lossless text format, see [module
+    /// docs](self) for more info. Use [`Visitor::load_text_format`] to read it back.
+    pub fn save_text_format(&self) -> Result<String, VisitError> {
+        let mut out = format!("{}\n", TEXT_MAGIC);
+        self.write_node_text(self.root, 0, &mut out)?;
+        Ok(out)
+    }
+
+    /// Creates a visitor for reading from a string in the text format, produced by
+    /// [`Visitor::save_text_format`] (or written manually).
+    pub fn load_text_format(text: &str) -> Result<Self, VisitError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(n, line)| (n + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with("//"));
+
+        match lines.next() {
+            Some((_, line)) if line == TEXT_MAGIC => (),
+            _ => return Err(VisitError::NotSupportedFormat),
+        }
+
+        let mut visitor = Self {
+            nodes: Pool::new(),
+            rc_map: Default::default(),
+            arc_map: Default::default(),
+            reading: true,
+            current_node: Handle::NONE,
+            root: Handle::NONE,
+            environment: None,
+        };
+
+        let mut current = Handle::NONE;
+        let mut last_line = 0;
+        for (line_number, line) in lines {
+            last_line = line_number;
+            let error = move |reason: String| VisitError::InvalidTextFormat {
+                line: line_number,
+                reason,
+            };
+
+            if line == "}" {
+                if current.is_none() {
+                    return Err(error("unexpected }".to_owned()));
+                }
+                current = visitor.nodes.borrow(current).parent;
+                continue;
+            }
+
+            let (name, rest) = parse_quoted(line).map_err(error)?;
+            let rest = rest.trim_start();
+            if rest == "{" {
+                if current.is_none() && visitor.root.is_some() {
+                    return Err(error("there must be only one root region".to_owned()));
+                }
+                let handle = visitor.nodes.spawn(Node::new(&name, current));
+                if current.is_some() {
+                    visitor.nodes.borrow_mut(current).children.push(handle);
+                } else {
+                    visitor.root = handle;
+                }
+                current = handle;
+            } else if let Some(rest) = rest.strip_prefix(':') {
+                if current.is_none() {
+                    return Err(error("field must be inside of a region".to_owned()));
+                }
+                let (type_name, value) = rest
+                    .split_once('=')
+                    .ok_or_else(|| error("expected =".to_owned()))?;
+                let field = parse_field(&name, type_name.trim(), value.trim()).map_err(error)?;
+                visitor.nodes.borrow_mut(current).fields.push(field);
+            } else {
+                return Err(error("expected { or :".to_owned()));
+            }
+        }
+
+        if current.is_some() || visitor.root.is_none() {
+            return Err(VisitError::InvalidTextFormat {
+                line: last_line,
+                reason: "unexpected end of input".to_owned(),
+            });
+        }
+
+        visitor.current_node = visitor.root;
+        Ok(visitor)
+    }
+}
+
+/// Converts data in binary format (the content of a scene or a saved game file, for example) to the
+/// text format.
+pub fn binary_to_text(data: Vec<u8>) -> Result<String, VisitError> {
+    Visitor::load_from_memory(data)?.save_text_format()
+}
+
+/// Converts data in the text format back to binary format.
+pub fn text_to_binary(text: &str) -> Result<Vec<u8>, VisitError> {
+    Visitor::load_text_format(text)?.save_binary_to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        visitor::{
+            text::{binary_to_text, text_to_binary},
+            Data, PodVecView, Visit, VisitError, VisitResult, Visitor,
+        },
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_text_format_round_trip() {
+        let mut visitor = Visitor::new();
+        {
+            let mut region = visitor.enter_region("Some \"Region\"").unwrap();
+            true.visit("Bool", &mut region).unwrap();
+            (-5i8).visit("I8", &mut region).unwrap();
+            let mut max_u64 = u64::MAX;
+            max_u64.visit("U64", &mut region).unwrap();
+            0.1f32.visit("F32", &mut region).unwrap();
+            let mut pi = std::f64::consts::PI;
+            pi.visit("F64", &mut region).unwrap();
+            Vector3::new(1.0f32, -2.5, 3.0)
+                .visit("Vec3", &mut region)
+                .unwrap();
+            Vector2::new(1u16, 2).visit("Vec2", &mut region).unwrap();
+            UnitQuaternion::<f32>::identity()
+                .visit("Rotation", &mut region)
+                .unwrap();
+            Matrix4::new_translation(&Vector3::new(1.0f32, 2.0, 3.0))
+                .visit("Matrix", &mut region)
+                .unwrap();
+            Uuid::new_v4().visit("Id", &mut region).unwrap();
+            "Multi\nline \"string\" ✓"
+                .to_owned()
+                .visit("String", &mut region)
+                .unwrap();
+            let mut bytes = vec![0u8, 159, 146, 150];
+            Data { vec: &mut bytes }
+                .visit("Bytes", &mut region)
+                .unwrap();
+            let mut pod = vec![1u32, 2, 3];
+            PodVecView::from_pod_vec(&mut pod)
+                .visit("Pod", &mut region)
+                .unwrap();
+        }
+
+        let binary = visitor.save_binary_to_vec().unwrap();
+        let text = binary_to_text(binary.clone()).unwrap();
+        assert_eq!(text_to_binary(&text).unwrap(), binary);
+        assert_eq!(
+            Visitor::load_text_format(&text)
+                .unwrap()
+                .save_text_format()
+                .unwrap(),
+            text
+        );
+    }
+
+    #[test]
+    fn test_text_format_parse() {
+        let text = r#"
+            RG3D_TEXT
+            // Comments are allowed.
+            "__ROOT__" {
+                "Item" {
+                    "Count": u32 = 3
+                    "Name" {
+                        "Length": u32 = 5
+                        "Data": data = "Sword"
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Visit, Default)]
+        struct Item {
+            count: u32,
+            name: String,
+        }
+
+        let mut visitor = Visitor::load_text_format(text).unwrap();
+        let mut item = Item::default();
+        item.visit("Item", &mut visitor).unwrap();
+        assert_eq!(item.count, 3);
+        assert_eq!(item.name, "Sword");
+
+        match Visitor::load_text_format("RG3D_TEXT\n\"__ROOT__\" {\n\"Count\": u32 = foo\n}") {
+            Err(VisitError::InvalidTextFormat { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("must fail"),
+        }
+        assert!(Visitor::load_text_format("RG3D_TEXT\n\"__ROOT__\" {\n").is_err());
+        assert!(Visitor::load_text_format("\"__ROOT__\" {\n}").is_err());
+    }
+}
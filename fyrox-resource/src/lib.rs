@@ -407,6 +407,17 @@ impl<T: ResourceData, E: ResourceLoadError> ResourceState<T, E> {
         }
     }
 
+    /// Changes a path to the resource source. It does not reload the resource, it is useful to
+    /// keep the resource in sync with its source file that was moved or renamed.
+    #[inline]
+    pub fn set_path(&mut self, new_path: PathBuf) {
+        match self {
+            Self::Pending { path, .. } => *path = new_path,
+            Self::LoadError { path, .. } => *path = new_path,
+            Self::Ok(details) => details.set_path(new_path),
+        }
+    }
+
     /// Changes ResourceState::Pending state to ResourceState::Ok(data) with given `data`.
     /// Additionally it wakes all futures.
     #[inline]
@@ -13,9 +13,11 @@
 use crate::{
     effects::{Effect, EffectRenderTrait},
     listener::Listener,
+    math,
     pool::Ticket,
-    renderer::{render_source_default, Renderer},
+    renderer::{hrtf::HrtfRenderer, render_source_default, Renderer},
     source::{SoundSource, Status},
+    voice::VoiceManager,
 };
 use fyrox_core::{
     pool::{Handle, Pool},
@@ -108,6 +110,30 @@ pub struct State {
     effects: Pool<Effect>,
     distance_model: DistanceModel,
     paused: bool,
+    // Previous HRTF renderer, it is used to crossfade between renderers when the renderer was changed.
+    fade_from: Option<HrtfRenderer>,
+    // Whether HRTF was used to render the last portion of samples.
+    hrtf_active: bool,
+    voice_manager: VoiceManager,
+}
+
+/// Renders the given sources using either HRTF or the default renderer.
+fn render_sources(
+    sources: &mut [&mut SoundSource],
+    hrtf_renderer: Option<&mut HrtfRenderer>,
+    listener: &Listener,
+    distance_model: DistanceModel,
+    buf: &mut [(f32, f32)],
+) {
+    match hrtf_renderer {
+        Some(hrtf_renderer) => hrtf_renderer.render_sources(sources, listener, distance_model, buf),
+        None => {
+            for source in sources.iter_mut() {
+                // Simple rendering path. Much faster (4-5 times) than HRTF path.
+                render_source_default(source, listener, distance_model, buf);
+            }
+        }
+    }
 }
 
 impl State {
@@ -175,9 +201,14 @@ impl State {
         self.render_duration
     }
 
-    /// Sets new renderer.
+    /// Sets new renderer. Switching between renderers is crossfaded during the next portion of
+    /// samples.
     pub fn set_renderer(&mut self, renderer: Renderer) -> Renderer {
-        std::mem::replace(&mut self.renderer, renderer)
+        let prev = std::mem::replace(&mut self.renderer, renderer);
+        if let Renderer::HrtfRenderer(ref hrtf_renderer) = prev {
+            self.fade_from = Some(hrtf_renderer.clone());
+        }
+        prev
     }
 
     /// Returns shared reference to current renderer.
@@ -241,6 +272,17 @@ impl State {
         self.sources.try_borrow_mut(handle)
     }
 
+    /// Returns shared reference to voice manager of the context.
+    pub fn voice_manager(&self) -> &VoiceManager {
+        &self.voice_manager
+    }
+
+    /// Returns mutable reference to voice manager of the context. It could be used to limit
+    /// amount of simultaneously playing sounds.
+    pub fn voice_manager_mut(&mut self) -> &mut VoiceManager {
+        &mut self.voice_manager
+    }
+
     /// Returns shared reference to listener. Engine has only one listener.
     pub fn listener(&self) -> &Listener {
         &self.listener
@@ -261,38 +303,118 @@ impl State {
         self.effects.borrow_mut(handle)
     }
 
-    pub(crate) fn render(&mut self, master_gain: f32, buf: &mut [(f32, f32)]) {
+    pub(crate) fn render(
+        &mut self,
+        master_gain: f32,
+        hrtf_allowed: bool,
+        hrtf_parallel_workers: Option<usize>,
+        buf: &mut [(f32, f32)],
+    ) {
         let last_time = fyrox_core::instant::Instant::now();
 
+        if let (Renderer::HrtfRenderer(hrtf_renderer), Some(count)) =
+            (&mut self.renderer, hrtf_parallel_workers)
+        {
+            if hrtf_renderer.parallel_workers() != count {
+                hrtf_renderer.set_parallel_workers(count);
+            }
+        }
+
         if !self.paused {
             self.sources.retain(|source| {
                 let done = source.is_play_once() && source.status() == Status::Stopped;
                 !done
             });
 
-            for source in self
+            self.voice_manager
+                .update(&mut self.sources, &self.listener, self.distance_model);
+
+            let mut sources = self
                 .sources
                 .iter_mut()
                 .filter(|s| s.status() == Status::Playing)
-            {
+                .collect::<Vec<_>>();
+
+            for source in sources.iter_mut() {
                 source.render(buf.len());
 
-                match self.renderer {
-                    Renderer::Default => {
-                        // Simple rendering path. Much faster (4-5 times) than HRTF path.
-                        render_source_default(source, &self.listener, self.distance_model, buf);
-                    }
-                    Renderer::HrtfRenderer(ref mut hrtf_renderer) => {
-                        hrtf_renderer.render_source(
-                            source,
-                            &self.listener,
-                            self.distance_model,
-                            buf,
-                        );
+                if source.is_virtual {
+                    // Virtual voices must advance their playback position, but they must not be
+                    // heard (effects are reading samples of sources too).
+                    for sample in source.frame_samples.iter_mut() {
+                        *sample = (0.0, 0.0);
                     }
                 }
             }
 
+            sources.retain(|s| !s.is_virtual);
+
+            let was_hrtf = self.hrtf_active;
+            let use_hrtf = hrtf_allowed && matches!(self.renderer, Renderer::HrtfRenderer(_));
+            let mut fade_from = self.fade_from.take();
+            let crossfade = was_hrtf != use_hrtf || (was_hrtf && fade_from.is_some());
+
+            let renderer = match self.renderer {
+                Renderer::Default => None,
+                Renderer::HrtfRenderer(ref mut hrtf_renderer) => Some(hrtf_renderer),
+            };
+
+            if crossfade {
+                let (previous, current) = if use_hrtf {
+                    (fade_from.as_mut(), renderer)
+                } else {
+                    (fade_from.as_mut().or(renderer), None)
+                };
+
+                // Render samples with both renderers and blend them to prevent clicks. Renderers
+                // modify rendering history of sources, so the current renderer must start from
+                // the same history as the previous one.
+                let history = sources
+                    .iter()
+                    .map(|s| s.render_history())
+                    .collect::<Vec<_>>();
+                let mut previous_buf = vec![(0.0, 0.0); buf.len()];
+                render_sources(
+                    &mut sources,
+                    if was_hrtf { previous } else { None },
+                    &self.listener,
+                    self.distance_model,
+                    &mut previous_buf,
+                );
+                for (source, history) in sources.iter_mut().zip(history) {
+                    source.set_render_history(history);
+                }
+                let mut current_buf = vec![(0.0, 0.0); buf.len()];
+                render_sources(
+                    &mut sources,
+                    current,
+                    &self.listener,
+                    self.distance_model,
+                    &mut current_buf,
+                );
+
+                let step = 1.0 / buf.len() as f32;
+                for (i, ((out_left, out_right), (prev, cur))) in buf
+                    .iter_mut()
+                    .zip(previous_buf.iter().zip(current_buf.iter()))
+                    .enumerate()
+                {
+                    let t = i as f32 * step;
+                    *out_left += math::lerpf(prev.0, cur.0, t);
+                    *out_right += math::lerpf(prev.1, cur.1, t);
+                }
+            } else {
+                render_sources(
+                    &mut sources,
+                    if use_hrtf { renderer } else { None },
+                    &self.listener,
+                    self.distance_model,
+                    buf,
+                );
+            }
+
+            self.hrtf_active = use_hrtf;
+
             for effect in self.effects.iter_mut() {
                 effect.render(&self.sources, &self.listener, self.distance_model, buf);
             }
@@ -334,6 +456,9 @@ impl SoundContext {
                 effects: Pool::new(),
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
+                fade_from: None,
+                hrtf_active: false,
+                voice_manager: Default::default(),
             }))),
         }
     }
@@ -389,6 +514,7 @@ impl Visit for State {
         self.renderer.visit("Renderer", &mut region)?;
         self.paused.visit("Paused", &mut region)?;
         self.distance_model.visit("DistanceModel", &mut region)?;
+        let _ = self.voice_manager.visit("VoiceManager", &mut region);
 
         Ok(())
     }
@@ -9,10 +9,22 @@ use fyrox_core::visitor::{Visit, VisitResult, Visitor};
 use std::sync::{Arc, Mutex};
 
 /// Internal state of sound engine.
-#[derive(Default)]
 pub struct SoundEngine {
     contexts: Vec<SoundContext>,
     master_gain: f32,
+    hrtf_allowed: bool,
+    hrtf_parallel_workers: Option<usize>,
+}
+
+impl Default for SoundEngine {
+    fn default() -> Self {
+        Self {
+            contexts: Default::default(),
+            master_gain: 1.0,
+            hrtf_allowed: true,
+            hrtf_parallel_workers: None,
+        }
+    }
 }
 
 impl SoundEngine {
@@ -23,6 +35,8 @@ impl SoundEngine {
         let engine = Arc::new(Mutex::new(Self {
             contexts: Default::default(),
             master_gain: 1.0,
+            hrtf_allowed: true,
+            hrtf_parallel_workers: None,
         }));
 
         // Run the default output device. Internally it creates separate thread, so we have
@@ -46,6 +60,8 @@ impl SoundEngine {
         Arc::new(Mutex::new(Self {
             contexts: Default::default(),
             master_gain: 1.0,
+            hrtf_allowed: true,
+            hrtf_parallel_workers: None,
         }))
     }
 
@@ -89,6 +105,30 @@ impl SoundEngine {
         self.master_gain
     }
 
+    /// Allows or forbids HRTF rendering. When forbidden, contexts with HRTF renderer will use the
+    /// default distance/pan model instead, which is much cheaper and useful for low-end devices.
+    /// Switching is crossfaded, so it can be done at runtime.
+    pub fn set_hrtf_allowed(&mut self, allowed: bool) {
+        self.hrtf_allowed = allowed;
+    }
+
+    /// Returns `true` if HRTF rendering is allowed, `false` - otherwise.
+    pub fn is_hrtf_allowed(&self) -> bool {
+        self.hrtf_allowed
+    }
+
+    /// Sets amount of worker threads for HRTF renderers of every context, see
+    /// [`crate::renderer::hrtf::HrtfRenderer::set_parallel_workers`]. `None` keeps the amount that
+    /// was set on each renderer.
+    pub fn set_hrtf_parallel_workers(&mut self, count: Option<usize>) {
+        self.hrtf_parallel_workers = count;
+    }
+
+    /// Returns amount of worker threads for HRTF renderers of every context.
+    pub fn hrtf_parallel_workers(&self) -> Option<usize> {
+        self.hrtf_parallel_workers
+    }
+
     /// Returns the length of buf to be passed to [`Self::render()`].
     pub fn render_buffer_len() -> usize {
         SoundContext::SAMPLES_PER_CHANNEL
@@ -109,8 +149,12 @@ impl SoundEngine {
 
     fn render_inner(&mut self, buf: &mut [(f32, f32)]) {
         let master_gain = self.master_gain;
+        let hrtf_allowed = self.hrtf_allowed;
+        let hrtf_parallel_workers = self.hrtf_parallel_workers;
         for context in self.contexts.iter_mut() {
-            context.state().render(master_gain, buf);
+            context
+                .state()
+                .render(master_gain, hrtf_allowed, hrtf_parallel_workers, buf);
         }
     }
 }
@@ -124,6 +168,14 @@ impl Visit for SoundEngine {
         let mut region = visitor.enter_region(name)?;
 
         self.master_gain.visit("MasterGain", &mut region)?;
+        let _ = self.hrtf_allowed.visit("HrtfAllowed", &mut region);
+        let mut hrtf_parallel_workers = self.hrtf_parallel_workers.map(|count| count as u32);
+        if hrtf_parallel_workers
+            .visit("HrtfParallelWorkers", &mut region)
+            .is_ok()
+        {
+            self.hrtf_parallel_workers = hrtf_parallel_workers.map(|count| count as usize);
+        }
         self.contexts.visit("Contexts", &mut region)?;
 
         Ok(())
@@ -0,0 +1,550 @@
+//! Interactive music module.
+//!
+//! # Overview
+//!
+//! Interactive (adaptive) music reacts to the state of a game. A music asset ([`MusicAsset`]) consists
+//! of sections ([`MusicSection`]) - parts of a soundtrack for different situations (exploration, combat,
+//! etc.). Every section consists of stems ([`Stem`]) - layers of the same length that are played in sync
+//! (drums, bass, strings, etc.).
+//!
+//! # Intensity
+//!
+//! Music player ([`MusicPlayer`]) has intensity parameter in `[0; 1]` range, every stem defines a range of
+//! intensity in which it fades in. This way a section could start from a single layer and add more layers
+//! as intensity grows.
+//!
+//! # Transitions
+//!
+//! Transitions between sections are quantized to beats or bars of currently playing section, so the new
+//! section starts in sync with the music. Transition rules ([`TransitionRule`]) define quantization and
+//! crossfade duration for specific pairs of sections.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fyrox_sound::{
+//!     buffer::SoundBufferResource,
+//!     context::SoundContext,
+//!     music::{MusicAsset, MusicPlayer, MusicSection, Quantization, Stem, TransitionRule},
+//! };
+//!
+//! fn make_player(drums: SoundBufferResource, strings: SoundBufferResource) -> MusicPlayer {
+//!     let asset = MusicAsset::new()
+//!         .with_section(
+//!             MusicSection::new("explore", 100.0, 4)
+//!                 .with_stem(Stem::new("strings", strings))
+//!                 .with_stem(Stem::new("drums", drums).with_intensity_range(0.5..1.0)),
+//!         )
+//!         .with_transition(TransitionRule::new(None, "explore", Quantization::Bar, 2.0));
+//!
+//!     let mut player = MusicPlayer::new(asset);
+//!     player.transition_to("explore");
+//!     player
+//! }
+//!
+//! fn update(player: &mut MusicPlayer, context: &SoundContext, dt: f32) {
+//!     player.set_intensity(0.7);
+//!     player.update(&mut context.state(), dt);
+//! }
+//! ```
+
+use crate::{
+    buffer::SoundBufferResource,
+    context::State,
+    source::{SoundSource, SoundSourceBuilder, Status},
+};
+use fyrox_core::pool::Handle;
+use std::ops::Range;
+
+/// A layer of a music section.
+#[derive(Debug, Clone)]
+pub struct Stem {
+    name: String,
+    buffer: SoundBufferResource,
+    intensity_range: Range<f32>,
+    gain: f32,
+}
+
+impl Stem {
+    /// Creates new stem that is always audible.
+    pub fn new<N: AsRef<str>>(name: N, buffer: SoundBufferResource) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            buffer,
+            intensity_range: 0.0..0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Sets a range of intensity in which the stem fades in. The stem is silent below the start
+    /// of the range and fully audible above the end of the range.
+    pub fn with_intensity_range(mut self, range: Range<f32>) -> Self {
+        self.intensity_range = range;
+        self
+    }
+
+    /// Sets gain of the stem.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Returns name of the stem.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns gain of the stem at given intensity.
+    pub fn gain_at(&self, intensity: f32) -> f32 {
+        let Range { start, end } = self.intensity_range;
+        let k = if end > start {
+            ((intensity - start) / (end - start)).clamp(0.0, 1.0)
+        } else if intensity >= start {
+            1.0
+        } else {
+            0.0
+        };
+        k * self.gain
+    }
+}
+
+/// A part of a soundtrack that consists of stems played in sync.
+#[derive(Debug, Clone)]
+pub struct MusicSection {
+    name: String,
+    stems: Vec<Stem>,
+    tempo: f32,
+    beats_per_bar: u32,
+    looping: bool,
+}
+
+impl MusicSection {
+    /// Creates new looping section with given tempo (in beats per minute) and amount of beats in
+    /// a bar.
+    pub fn new<N: AsRef<str>>(name: N, tempo: f32, beats_per_bar: u32) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            stems: Default::default(),
+            tempo,
+            beats_per_bar: beats_per_bar.max(1),
+            looping: true,
+        }
+    }
+
+    /// Adds new stem to the section.
+    pub fn with_stem(mut self, stem: Stem) -> Self {
+        self.stems.push(stem);
+        self
+    }
+
+    /// Sets whether the section should be looped or not.
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Returns name of the section.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns stems of the section.
+    pub fn stems(&self) -> &[Stem] {
+        &self.stems
+    }
+
+    /// Returns tempo of the section in beats per minute.
+    pub fn tempo(&self) -> f32 {
+        self.tempo
+    }
+
+    /// Returns amount of beats in a bar.
+    pub fn beats_per_bar(&self) -> u32 {
+        self.beats_per_bar
+    }
+
+    /// Returns closest time (in seconds) that is greater or equal than given time and lies on a
+    /// boundary defined by quantization.
+    pub fn next_boundary(&self, time: f32, quantization: Quantization) -> f32 {
+        let beat_duration = 60.0 / self.tempo.max(f32::EPSILON);
+        let unit = match quantization {
+            Quantization::Immediate => return time,
+            Quantization::Beat => beat_duration,
+            Quantization::Bar => beat_duration * self.beats_per_bar as f32,
+        };
+        (time / unit).ceil() * unit
+    }
+}
+
+/// Defines when a transition could happen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Quantization {
+    /// Transition happens on next update.
+    Immediate,
+    /// Transition happens on next beat of current section.
+    Beat,
+    /// Transition happens on next bar of current section.
+    Bar,
+}
+
+impl Default for Quantization {
+    fn default() -> Self {
+        Self::Bar
+    }
+}
+
+/// Defines how a transition between two sections is performed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionRule {
+    from: Option<String>,
+    to: String,
+    quantization: Quantization,
+    crossfade: f32,
+}
+
+impl TransitionRule {
+    /// Creates new rule for a transition to `to` section. `from` could be `None`, in this case the rule
+    /// is used for transitions from any section. Crossfade duration is in seconds.
+    pub fn new<N: AsRef<str>>(
+        from: Option<&str>,
+        to: N,
+        quantization: Quantization,
+        crossfade: f32,
+    ) -> Self {
+        Self {
+            from: from.map(|from| from.to_owned()),
+            to: to.as_ref().to_owned(),
+            quantization,
+            crossfade: crossfade.max(0.0),
+        }
+    }
+
+    fn matches(&self, from: Option<&str>, to: &str) -> bool {
+        self.to == to
+            && match (self.from.as_deref(), from) {
+                (None, _) => true,
+                (Some(rule_from), Some(from)) => rule_from == from,
+                (Some(_), None) => false,
+            }
+    }
+}
+
+/// A set of sections and transition rules between them.
+#[derive(Debug, Clone)]
+pub struct MusicAsset {
+    sections: Vec<MusicSection>,
+    transitions: Vec<TransitionRule>,
+    default_quantization: Quantization,
+    default_crossfade: f32,
+}
+
+impl Default for MusicAsset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MusicAsset {
+    /// Creates new empty music asset. Transitions without rules are quantized to bars and
+    /// have one second crossfade.
+    pub fn new() -> Self {
+        Self {
+            sections: Default::default(),
+            transitions: Default::default(),
+            default_quantization: Quantization::Bar,
+            default_crossfade: 1.0,
+        }
+    }
+
+    /// Adds new section.
+    pub fn with_section(mut self, section: MusicSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Adds new transition rule. Rules with explicit source section take precedence over the
+    /// rules for any section.
+    pub fn with_transition(mut self, rule: TransitionRule) -> Self {
+        self.transitions.push(rule);
+        self
+    }
+
+    /// Sets quantization and crossfade duration (in seconds) for transitions without rules.
+    pub fn with_default_transition(mut self, quantization: Quantization, crossfade: f32) -> Self {
+        self.default_quantization = quantization;
+        self.default_crossfade = crossfade.max(0.0);
+        self
+    }
+
+    /// Returns sections of the asset.
+    pub fn sections(&self) -> &[MusicSection] {
+        &self.sections
+    }
+
+    /// Tries to find a section by its name.
+    pub fn find_section(&self, name: &str) -> Option<usize> {
+        self.sections.iter().position(|s| s.name == name)
+    }
+
+    /// Returns quantization and crossfade duration of a transition between given sections.
+    pub fn transition(&self, from: Option<&str>, to: &str) -> (Quantization, f32) {
+        self.transitions
+            .iter()
+            .filter(|rule| rule.matches(from, to))
+            // Explicit rules first.
+            .min_by_key(|rule| rule.from.is_none())
+            .map(|rule| (rule.quantization, rule.crossfade))
+            .unwrap_or((self.default_quantization, self.default_crossfade))
+    }
+}
+
+#[derive(Debug)]
+struct ActiveSection {
+    section: usize,
+    sources: Vec<Handle<SoundSource>>,
+    fade: f32,
+    // Fade speed in units per second, negative values fade the section out.
+    fade_speed: f32,
+    prev_time: f32,
+}
+
+#[derive(Debug)]
+struct PendingTransition {
+    section: usize,
+    quantization: Quantization,
+    crossfade: f32,
+    // Playback time of current section at which the transition will happen.
+    time: Option<f32>,
+}
+
+/// Plays a music asset, see module docs for more info.
+#[derive(Debug)]
+pub struct MusicPlayer {
+    asset: MusicAsset,
+    intensity: f32,
+    target_intensity: f32,
+    intensity_speed: f32,
+    gain: f32,
+    // The last one is current section, the rest are fading out.
+    active: Vec<ActiveSection>,
+    pending: Option<PendingTransition>,
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl MusicPlayer {
+    /// Creates new music player for given asset. Nothing is played until first transition.
+    pub fn new(asset: MusicAsset) -> Self {
+        Self {
+            asset,
+            intensity: 0.0,
+            target_intensity: 0.0,
+            intensity_speed: 0.5,
+            gain: 1.0,
+            active: Default::default(),
+            pending: None,
+        }
+    }
+
+    /// Returns current music asset.
+    pub fn asset(&self) -> &MusicAsset {
+        &self.asset
+    }
+
+    /// Sets new music asset. Current music must be stopped first (see [`Self::stop`]).
+    pub fn set_asset(&mut self, asset: MusicAsset) {
+        self.pending = None;
+        self.asset = asset;
+    }
+
+    /// Sets desired intensity in `[0; 1]` range. Actual intensity changes smoothly, see
+    /// [`Self::set_intensity_speed`].
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.target_intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// Returns current intensity.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets maximum change of intensity per second.
+    pub fn set_intensity_speed(&mut self, speed: f32) {
+        self.intensity_speed = speed.max(0.0);
+    }
+
+    /// Sets gain of the music.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    /// Returns gain of the music.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Returns name of the current section (if any).
+    pub fn current_section(&self) -> Option<&str> {
+        self.active
+            .last()
+            .map(|active| self.asset.sections[active.section].name())
+    }
+
+    /// Requests a transition to a section with given name. The transition will happen on next
+    /// beat or bar of current section (depending on transition rule). Returns `false` if there is
+    /// no such section.
+    pub fn transition_to(&mut self, name: &str) -> bool {
+        let section = match self.asset.find_section(name) {
+            Some(section) => section,
+            None => return false,
+        };
+
+        if self.pending.is_none() && self.active.last().map(|a| a.section) == Some(section) {
+            return true;
+        }
+
+        let (quantization, crossfade) = self.asset.transition(self.current_section(), name);
+        self.pending = Some(PendingTransition {
+            section,
+            quantization,
+            crossfade,
+            time: None,
+        });
+        true
+    }
+
+    /// Stops the music immediately.
+    pub fn stop(&mut self, state: &mut State) {
+        self.pending = None;
+        for active in self.active.drain(..) {
+            for source in active.sources {
+                if state.is_valid_handle(source) {
+                    state.remove_source(source);
+                }
+            }
+        }
+    }
+
+    fn playback_time(state: &State, active: &ActiveSection) -> Option<(f32, bool)> {
+        let source = active
+            .sources
+            .iter()
+            .find(|source| state.is_valid_handle(**source))?;
+        let source = state.source(*source);
+        Some((
+            source.playback_time().as_secs_f32(),
+            source.status() == Status::Stopped,
+        ))
+    }
+
+    fn start_section(&mut self, state: &mut State, pending: &PendingTransition) {
+        let section = &self.asset.sections[pending.section];
+
+        let (fade, fade_speed) = if pending.crossfade > 0.0 {
+            (0.0, 1.0 / pending.crossfade)
+        } else {
+            (1.0, 0.0)
+        };
+
+        for active in self.active.iter_mut() {
+            if pending.crossfade > 0.0 {
+                active.fade_speed = -1.0 / pending.crossfade;
+            } else {
+                active.fade = 0.0;
+                active.fade_speed = -1.0;
+            }
+        }
+
+        let sources = section
+            .stems
+            .iter()
+            .map(|stem| {
+                match SoundSourceBuilder::new()
+                    .with_buffer(stem.buffer.clone())
+                    .with_looping(section.looping)
+                    .with_spatial_blend_factor(0.0)
+                    .with_gain(stem.gain_at(self.intensity) * fade * self.gain)
+                    .with_status(Status::Playing)
+                    .build()
+                {
+                    Ok(source) => state.add_source(source),
+                    Err(_) => Handle::NONE,
+                }
+            })
+            .collect();
+
+        self.active.push(ActiveSection {
+            section: pending.section,
+            sources,
+            fade,
+            fade_speed,
+            prev_time: 0.0,
+        });
+    }
+
+    /// Updates the music player, it must be called every frame.
+    pub fn update(&mut self, state: &mut State, dt: f32) {
+        let max_delta = self.intensity_speed * dt;
+        self.intensity += (self.target_intensity - self.intensity).clamp(-max_delta, max_delta);
+
+        if let Some(mut pending) = self.pending.take() {
+            let ready = match self.active.last() {
+                Some(current) => match Self::playback_time(state, current) {
+                    Some((time, stopped)) => {
+                        let boundary = *pending.time.get_or_insert_with(|| {
+                            self.asset.sections[current.section]
+                                .next_boundary(time, pending.quantization)
+                        });
+                        // Looping section could wrap before reaching the boundary.
+                        time >= boundary || time < current.prev_time || stopped
+                    }
+                    None => true,
+                },
+                None => true,
+            };
+
+            if ready {
+                self.start_section(state, &pending);
+            } else {
+                self.pending = Some(pending);
+            }
+        }
+
+        if let Some(current) = self.active.last_mut() {
+            if let Some((time, _)) = Self::playback_time(state, current) {
+                current.prev_time = time;
+            }
+        }
+
+        let mut i = 0;
+        while i < self.active.len() {
+            let active = &mut self.active[i];
+            active.fade = (active.fade + active.fade_speed * dt).clamp(0.0, 1.0);
+
+            if active.fade_speed < 0.0 && active.fade <= 0.0 {
+                for source in self.active.remove(i).sources {
+                    if state.is_valid_handle(source) {
+                        state.remove_source(source);
+                    }
+                }
+                continue;
+            }
+
+            for (stem, source) in self.asset.sections[active.section]
+                .stems
+                .iter()
+                .zip(active.sources.iter())
+            {
+                if let Some(source) = state.try_get_source_mut(*source) {
+                    source.set_gain(stem.gain_at(self.intensity) * active.fade * self.gain);
+                }
+            }
+
+            i += 1;
+        }
+    }
+}
@@ -50,6 +50,18 @@
 //! [here](http://csoundjournal.com/issue9/newHRTFOpcodes.html)
 //!
 //! Clicks can be reproduced by using clean sine wave of 440 Hz on some source moving around listener.
+//!
+//! # Parallel rendering
+//!
+//! Sources are independent of each other, so they can be rendered on multiple threads. Use
+//! [`HrtfRenderer::set_parallel_workers`] to split sources between worker threads, this is useful
+//! when there are lots of spatial sounds playing at the same time.
+//!
+//! # Fallback
+//!
+//! HRTF could be too heavy for low-end devices, use [`crate::engine::SoundEngine::set_hrtf_allowed`]
+//! to render all sounds using the default distance/pan model instead. Switching between renderers
+//! at runtime is crossfaded to prevent clicks.
 
 use crate::{
     context::{self, DistanceModel, SoundContext},
@@ -62,7 +74,17 @@ use fyrox_core::{
     visitor::{Visit, VisitResult, Visitor},
 };
 use hrtf::HrirSphere;
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
+
+/// Scratch state of a worker thread of parallel HRTF rendering.
+#[derive(Clone, Debug)]
+struct HrtfWorker {
+    processor: hrtf::HrtfProcessor,
+    buffer: Vec<(f32, f32)>,
+}
 
 /// See module docs.
 #[derive(Clone, Debug, Default, Reflect)]
@@ -70,6 +92,9 @@ pub struct HrtfRenderer {
     hrir_path: PathBuf,
     #[reflect(hidden)]
     processor: Option<hrtf::HrtfProcessor>,
+    parallel_workers: usize,
+    #[reflect(hidden)]
+    workers: Vec<HrtfWorker>,
 }
 
 impl Visit for HrtfRenderer {
@@ -77,6 +102,10 @@ impl Visit for HrtfRenderer {
         let mut region = visitor.enter_region(name)?;
 
         self.hrir_path.visit("ResourcePath", &mut region)?;
+        let mut parallel_workers = self.parallel_workers as u32;
+        if parallel_workers.visit("ParallelWorkers", &mut region).is_ok() {
+            self.parallel_workers = parallel_workers as usize;
+        }
 
         drop(region);
 
@@ -86,12 +115,51 @@ impl Visit for HrtfRenderer {
                 SoundContext::HRTF_INTERPOLATION_STEPS,
                 SoundContext::HRTF_BLOCK_LEN,
             ));
+            self.workers.clear();
         }
 
         Ok(())
     }
 }
 
+fn render_source_hrtf(
+    processor: &mut hrtf::HrtfProcessor,
+    source: &mut SoundSource,
+    listener: &Listener,
+    distance_model: DistanceModel,
+    out_buf: &mut [(f32, f32)],
+) {
+    // Render as 2D first with k = (1.0 - spatial_blend).
+    render_source_2d_only(source, out_buf);
+
+    // Then add HRTF part with k = spatial_blend
+    let new_distance_gain =
+        source.spatial_blend() * source.calculate_distance_gain(listener, distance_model);
+    let new_sampling_vector = source.calculate_sampling_vector(listener);
+
+    processor.process_samples(hrtf::HrtfContext {
+        source: &source.frame_samples,
+        output: out_buf,
+        new_sample_vector: hrtf::Vec3::new(
+            new_sampling_vector.x,
+            new_sampling_vector.y,
+            new_sampling_vector.z,
+        ),
+        prev_sample_vector: hrtf::Vec3::new(
+            source.prev_sampling_vector.x,
+            source.prev_sampling_vector.y,
+            source.prev_sampling_vector.z,
+        ),
+        prev_left_samples: &mut source.prev_left_samples,
+        prev_right_samples: &mut source.prev_right_samples,
+        prev_distance_gain: source.prev_distance_gain.unwrap_or(new_distance_gain),
+        new_distance_gain,
+    });
+
+    source.prev_sampling_vector = new_sampling_vector;
+    source.prev_distance_gain = Some(new_distance_gain);
+}
+
 impl HrtfRenderer {
     /// Creates new HRTF renderer using specified HRTF sphere. See module docs for more info.
     pub fn new(hrir_sphere: hrtf::HrirSphere) -> Self {
@@ -102,47 +170,95 @@ impl HrtfRenderer {
                 SoundContext::HRTF_INTERPOLATION_STEPS,
                 SoundContext::HRTF_BLOCK_LEN,
             )),
+            parallel_workers: 0,
+            workers: Default::default(),
+        }
+    }
+
+    /// Returns a path to the HRIR sphere that is used by the renderer.
+    pub fn hrir_path(&self) -> &Path {
+        &self.hrir_path
+    }
+
+    /// Sets amount of threads that will be used to render sound sources. Each thread has its own
+    /// copy of HRTF processor, so each worker adds memory overhead of one HRIR sphere. 0 or 1 means
+    /// that all sources will be rendered on the sound thread. Parallel rendering is not available
+    /// on WebAssembly, the setting is ignored there.
+    pub fn set_parallel_workers(&mut self, count: usize) {
+        self.parallel_workers = count;
+        self.workers.truncate(count);
+    }
+
+    /// Returns amount of threads that are used to render sound sources.
+    pub fn parallel_workers(&self) -> usize {
+        self.parallel_workers
+    }
+
+    pub(crate) fn render_sources(
+        &mut self,
+        sources: &mut [&mut SoundSource],
+        listener: &Listener,
+        distance_model: DistanceModel,
+        out_buf: &mut [(f32, f32)],
+    ) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if self.parallel_workers > 1 && sources.len() > 1 {
+                self.render_sources_parallel(sources, listener, distance_model, out_buf);
+                return;
+            }
+        }
+
+        let processor = self.processor.as_mut().unwrap();
+        for source in sources.iter_mut() {
+            render_source_hrtf(processor, source, listener, distance_model, out_buf);
         }
     }
 
-    pub(crate) fn render_source(
+    #[cfg(not(target_arch = "wasm32"))]
+    fn render_sources_parallel(
         &mut self,
-        source: &mut SoundSource,
+        sources: &mut [&mut SoundSource],
         listener: &Listener,
         distance_model: DistanceModel,
         out_buf: &mut [(f32, f32)],
     ) {
-        // Render as 2D first with k = (1.0 - spatial_blend).
-        render_source_2d_only(source, out_buf);
-
-        // Then add HRTF part with k = spatial_blend
-        let new_distance_gain =
-            source.spatial_blend() * source.calculate_distance_gain(listener, distance_model);
-        let new_sampling_vector = source.calculate_sampling_vector(listener);
-
-        self.processor
-            .as_mut()
-            .unwrap()
-            .process_samples(hrtf::HrtfContext {
-                source: &source.frame_samples,
-                output: out_buf,
-                new_sample_vector: hrtf::Vec3::new(
-                    new_sampling_vector.x,
-                    new_sampling_vector.y,
-                    new_sampling_vector.z,
-                ),
-                prev_sample_vector: hrtf::Vec3::new(
-                    source.prev_sampling_vector.x,
-                    source.prev_sampling_vector.y,
-                    source.prev_sampling_vector.z,
-                ),
-                prev_left_samples: &mut source.prev_left_samples,
-                prev_right_samples: &mut source.prev_right_samples,
-                prev_distance_gain: source.prev_distance_gain.unwrap_or(new_distance_gain),
-                new_distance_gain,
+        use rayon::prelude::*;
+
+        let processor = self.processor.as_ref().unwrap();
+        while self.workers.len() < self.parallel_workers {
+            self.workers.push(HrtfWorker {
+                processor: processor.clone(),
+                buffer: Default::default(),
             });
+        }
 
-        source.prev_sampling_vector = new_sampling_vector;
-        source.prev_distance_gain = Some(new_distance_gain);
+        let chunk_size = (sources.len() + self.parallel_workers - 1) / self.parallel_workers;
+        let chunk_count = (sources.len() + chunk_size - 1) / chunk_size;
+        let buffer_len = out_buf.len();
+
+        sources
+            .par_chunks_mut(chunk_size)
+            .zip(self.workers.par_iter_mut())
+            .for_each(|(chunk, worker)| {
+                worker.buffer.clear();
+                worker.buffer.resize(buffer_len, (0.0, 0.0));
+                for source in chunk.iter_mut() {
+                    render_source_hrtf(
+                        &mut worker.processor,
+                        source,
+                        listener,
+                        distance_model,
+                        &mut worker.buffer,
+                    );
+                }
+            });
+
+        for worker in self.workers.iter().take(chunk_count) {
+            for ((out_left, out_right), (left, right)) in out_buf.iter_mut().zip(&worker.buffer) {
+                *out_left += *left;
+                *out_right += *right;
+            }
+        }
     }
 }
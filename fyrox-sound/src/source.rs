@@ -34,6 +34,7 @@ use crate::{
     context::DistanceModel,
     error::SoundError,
     listener::Listener,
+    voice::StealPolicy,
 };
 use fyrox_core::{
     algebra::Vector3,
@@ -134,6 +135,24 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    #[visit(optional)]
+    priority: u32,
+    #[visit(optional)]
+    bus: String,
+    #[visit(optional)]
+    max_instances: Option<u32>,
+    #[visit(optional)]
+    steal_policy: StealPolicy,
+    // Virtual voices are not mixed, but their playback position is still advancing. See
+    // `voice` module docs for more info.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) is_virtual: bool,
+    // A number that is assigned by the voice manager when the source starts playing, zero
+    // if the source is not playing.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) voice_age: u64,
 }
 
 impl Default for SoundSource {
@@ -163,11 +182,49 @@ impl Default for SoundSource {
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            priority: 0,
+            bus: Default::default(),
+            max_instances: None,
+            steal_policy: Default::default(),
+            is_virtual: false,
+            voice_age: 0,
         }
     }
 }
 
+/// State of a source that renderers carry between portions of samples (gains, samples for
+/// overlap-save convolution, etc.).
+#[derive(Debug, Clone)]
+pub(crate) struct RenderHistory {
+    last_left_gain: Option<f32>,
+    last_right_gain: Option<f32>,
+    prev_left_samples: Vec<f32>,
+    prev_right_samples: Vec<f32>,
+    prev_sampling_vector: Vector3<f32>,
+    prev_distance_gain: Option<f32>,
+}
+
 impl SoundSource {
+    pub(crate) fn render_history(&self) -> RenderHistory {
+        RenderHistory {
+            last_left_gain: self.last_left_gain,
+            last_right_gain: self.last_right_gain,
+            prev_left_samples: self.prev_left_samples.clone(),
+            prev_right_samples: self.prev_right_samples.clone(),
+            prev_sampling_vector: self.prev_sampling_vector,
+            prev_distance_gain: self.prev_distance_gain,
+        }
+    }
+
+    pub(crate) fn set_render_history(&mut self, history: RenderHistory) {
+        self.last_left_gain = history.last_left_gain;
+        self.last_right_gain = history.last_right_gain;
+        self.prev_left_samples = history.prev_left_samples;
+        self.prev_right_samples = history.prev_right_samples;
+        self.prev_sampling_vector = history.prev_sampling_vector;
+        self.prev_distance_gain = history.prev_distance_gain;
+    }
+
     /// Sets new name of the sound source.
     pub fn set_name<N: AsRef<str>>(&mut self, name: N) {
         self.name = name.as_ref().to_owned();
@@ -326,9 +383,65 @@ impl SoundSource {
         self.pitch
     }
 
+    /// Sets priority of the source. Voices with lower priority are virtualized first when
+    /// [`StealPolicy::LowestPriority`] is used.
+    pub fn set_priority(&mut self, priority: u32) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns priority of the source.
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    /// Sets name of a bus the source belongs to. Buses could have their own voice limits,
+    /// see [`crate::voice::VoiceManager::set_bus_limit`]. Empty name is a default bus.
+    pub fn set_bus<N: AsRef<str>>(&mut self, bus: N) -> &mut Self {
+        self.bus = bus.as_ref().to_owned();
+        self
+    }
+
+    /// Returns name of a bus the source belongs to.
+    pub fn bus(&self) -> &str {
+        &self.bus
+    }
+
+    /// Sets maximum amount of simultaneously playing instances of the sound (sources that uses the
+    /// same buffer). Excessive instances are virtualized according to [`Self::set_steal_policy`].
+    /// `None` means no limit.
+    pub fn set_max_instances(&mut self, max_instances: Option<u32>) -> &mut Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    /// Returns maximum amount of simultaneously playing instances of the sound.
+    pub fn max_instances(&self) -> Option<u32> {
+        self.max_instances
+    }
+
+    /// Sets a policy that is used to select instances that will be virtualized when the amount of
+    /// instances exceeds [`Self::max_instances`].
+    pub fn set_steal_policy(&mut self, policy: StealPolicy) -> &mut Self {
+        self.steal_policy = policy;
+        self
+    }
+
+    /// Returns steal policy of the source.
+    pub fn steal_policy(&self) -> StealPolicy {
+        self.steal_policy
+    }
+
+    /// Returns `true` if the source is playing, but it is not audible because of voice limits.
+    /// See [`crate::voice`] module docs for more info.
+    pub fn is_virtual(&self) -> bool {
+        self.is_virtual
+    }
+
     /// Stops sound source. Automatically rewinds streaming buffers.
     pub fn stop(&mut self) -> Result<(), SoundError> {
         self.status = Status::Stopped;
+        self.voice_age = 0;
 
         self.buf_read_pos = 0.0;
         self.playback_pos = 0.0;
@@ -703,6 +816,10 @@ pub struct SoundSourceBuilder {
     max_distance: f32,
     rolloff_factor: f32,
     spatial_blend: f32,
+    priority: u32,
+    bus: String,
+    max_instances: Option<u32>,
+    steal_policy: StealPolicy,
 }
 
 impl Default for SoundSourceBuilder {
@@ -729,6 +846,10 @@ impl SoundSourceBuilder {
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
+            priority: 0,
+            bus: Default::default(),
+            max_instances: None,
+            steal_policy: Default::default(),
         }
     }
 
@@ -822,6 +943,30 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See [`SoundSource::set_priority`]
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// See [`SoundSource::set_bus`]
+    pub fn with_bus<N: AsRef<str>>(mut self, bus: N) -> Self {
+        self.bus = bus.as_ref().to_owned();
+        self
+    }
+
+    /// See [`SoundSource::set_max_instances`]
+    pub fn with_max_instances(mut self, max_instances: Option<u32>) -> Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    /// See [`SoundSource::set_steal_policy`]
+    pub fn with_steal_policy(mut self, policy: StealPolicy) -> Self {
+        self.steal_policy = policy;
+        self
+    }
+
     /// Creates new instance of generic sound source. May fail if buffer is invalid.
     pub fn build(self) -> Result<SoundSource, SoundError> {
         let mut source = SoundSource {
@@ -841,6 +986,10 @@ impl SoundSourceBuilder {
             spatial_blend: self.spatial_blend,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
+            priority: self.priority,
+            bus: self.bus,
+            max_instances: self.max_instances,
+            steal_policy: self.steal_policy,
             ..Default::default()
         };
 
@@ -0,0 +1,434 @@
+//! Voice management module.
+//!
+//! # Overview
+//!
+//! Every playing sound source is a voice, and every voice has its cost - it must be decoded, resampled,
+//! spatialized and mixed. When lots of sounds are played at once (for example a hundred of bullets
+//! hitting a wall at the same time) the mixer could be easily overloaded. Voice manager limits
+//! amount of voices that are actually rendered:
+//!
+//! - Global limit - maximum amount of voices in a context.
+//! - Bus limits - maximum amount of voices for sources on a bus (see [`SoundSource::set_bus`]).
+//! - Instance limit - maximum amount of simultaneously playing instances of the same sound (sources
+//!   with the same buffer), see [`SoundSource::set_max_instances`].
+//!
+//! When a limit is exceeded, some voices are "stolen" according to a steal policy ([`StealPolicy`]).
+//! Stolen voices are not stopped, they become virtual - a virtual voice is not audible, but its playback
+//! position is still advancing. Once there is a free slot, the voice becomes real again and continues
+//! from the position where it should be at this moment.
+
+use crate::{
+    context::DistanceModel,
+    listener::Listener,
+    source::{SoundSource, Status},
+};
+use fyrox_core::{
+    pool::{Handle, Pool},
+    reflect::prelude::*,
+    visitor::prelude::*,
+};
+use std::{cmp::Ordering, collections::HashMap};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines which voices will be virtualized first when a voice limit is exceeded.
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Reflect, Visit, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum StealPolicy {
+    /// Voices that started playing earlier are stolen first.
+    Oldest = 0,
+    /// Voices with the lowest gain (including distance attenuation) are stolen first.
+    Quietest = 1,
+    /// Voices with the lowest priority are stolen first, voices with the same priority are stolen
+    /// from the oldest one.
+    LowestPriority = 2,
+}
+
+impl Default for StealPolicy {
+    fn default() -> Self {
+        Self::Oldest
+    }
+}
+
+struct Voice {
+    handle: Handle<SoundSource>,
+    priority: u32,
+    age: u64,
+    loudness: f32,
+    is_virtual: bool,
+}
+
+// Returns `Ordering::Less` if `a` should be kept over `b`.
+fn compare(policy: StealPolicy, a: &Voice, b: &Voice) -> Ordering {
+    let newest_first = b.age.cmp(&a.age);
+    match policy {
+        StealPolicy::Oldest => newest_first,
+        StealPolicy::Quietest => b
+            .loudness
+            .partial_cmp(&a.loudness)
+            .unwrap_or(Ordering::Equal)
+            .then(newest_first),
+        StealPolicy::LowestPriority => b.priority.cmp(&a.priority).then(newest_first),
+    }
+}
+
+/// Voice manager, see module docs for more info.
+#[derive(Clone, Debug, Default)]
+pub struct VoiceManager {
+    max_voices: Option<usize>,
+    bus_limits: HashMap<String, usize>,
+    steal_policy: StealPolicy,
+    // Monotonic counter which is used to find out the age of voices.
+    counter: u64,
+    real_voices: usize,
+    virtual_voices: usize,
+}
+
+impl Visit for VoiceManager {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        // Limits are stored as `u32`, because `usize` is platform-dependent.
+        let mut max_voices = self.max_voices.map(|max| max as u32);
+        if max_voices.visit("MaxVoices", &mut region).is_ok() {
+            self.max_voices = max_voices.map(|max| max as usize);
+        }
+
+        let mut bus_limits = self
+            .bus_limits
+            .iter()
+            .map(|(bus, limit)| (bus.clone(), *limit as u32))
+            .collect::<HashMap<_, _>>();
+        if bus_limits.visit("BusLimits", &mut region).is_ok() {
+            self.bus_limits = bus_limits
+                .into_iter()
+                .map(|(bus, limit)| (bus, limit as usize))
+                .collect();
+        }
+
+        let _ = self.steal_policy.visit("StealPolicy", &mut region);
+
+        Ok(())
+    }
+}
+
+impl VoiceManager {
+    /// Sets maximum amount of voices that could be played at once. `None` means no limit.
+    pub fn set_max_voices(&mut self, max_voices: Option<usize>) {
+        self.max_voices = max_voices;
+    }
+
+    /// Returns maximum amount of voices that could be played at once.
+    pub fn max_voices(&self) -> Option<usize> {
+        self.max_voices
+    }
+
+    /// Sets maximum amount of voices for a bus with the given name. `None` removes the limit.
+    pub fn set_bus_limit(&mut self, bus: &str, limit: Option<usize>) {
+        match limit {
+            Some(limit) => {
+                self.bus_limits.insert(bus.to_owned(), limit);
+            }
+            None => {
+                self.bus_limits.remove(bus);
+            }
+        }
+    }
+
+    /// Returns maximum amount of voices for a bus with the given name.
+    pub fn bus_limit(&self, bus: &str) -> Option<usize> {
+        self.bus_limits.get(bus).cloned()
+    }
+
+    /// Sets steal policy which is used when global or bus limit is exceeded.
+    pub fn set_steal_policy(&mut self, policy: StealPolicy) {
+        self.steal_policy = policy;
+    }
+
+    /// Returns steal policy which is used when global or bus limit is exceeded.
+    pub fn steal_policy(&self) -> StealPolicy {
+        self.steal_policy
+    }
+
+    /// Returns amount of audible voices at the last update.
+    pub fn real_voice_count(&self) -> usize {
+        self.real_voices
+    }
+
+    /// Returns amount of virtual voices at the last update.
+    pub fn virtual_voice_count(&self) -> usize {
+        self.virtual_voices
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        sources: &mut Pool<SoundSource>,
+        listener: &Listener,
+        distance_model: DistanceModel,
+    ) {
+        let mut voices = Vec::new();
+        // Indices of voices grouped by buffer.
+        let mut instances = HashMap::<usize, Vec<usize>>::new();
+
+        for (handle, source) in sources.pair_iter_mut() {
+            if source.status() != Status::Playing {
+                source.voice_age = 0;
+                source.is_virtual = false;
+                continue;
+            }
+
+            if source.voice_age == 0 {
+                self.counter += 1;
+                source.voice_age = self.counter;
+            }
+
+            let distance_gain = source.calculate_distance_gain(listener, distance_model);
+            let spatial_blend = source.spatial_blend();
+
+            if let (Some(buffer), Some(_)) = (source.buffer(), source.max_instances()) {
+                instances
+                    .entry(buffer.key())
+                    .or_default()
+                    .push(voices.len());
+            }
+
+            voices.push(Voice {
+                handle,
+                priority: source.priority(),
+                age: source.voice_age,
+                loudness: source.gain() * ((1.0 - spatial_blend) + distance_gain * spatial_blend),
+                is_virtual: false,
+            });
+        }
+
+        // Per-sound limits first, the limit and the policy are taken from the newest instance.
+        for indices in instances.values_mut() {
+            indices.sort_by(|a, b| compare(StealPolicy::Oldest, &voices[*a], &voices[*b]));
+            let newest = sources.borrow(voices[indices[0]].handle);
+            let limit = newest.max_instances().unwrap_or(u32::MAX) as usize;
+            let policy = newest.steal_policy();
+            indices.sort_by(|a, b| compare(policy, &voices[*a], &voices[*b]));
+            for index in indices.iter().skip(limit) {
+                voices[*index].is_virtual = true;
+            }
+        }
+
+        // Then global and bus limits.
+        let mut order = (0..voices.len())
+            .filter(|i| !voices[*i].is_virtual)
+            .collect::<Vec<_>>();
+        let policy = self.steal_policy;
+        order.sort_by(|a, b| compare(policy, &voices[*a], &voices[*b]));
+        let mut bus_voices = HashMap::<&str, usize>::new();
+        let mut total = 0;
+        for index in order {
+            let bus = sources.borrow(voices[index].handle).bus();
+            let count = bus_voices.entry(bus).or_default();
+            if self.max_voices.map_or(false, |max| total >= max)
+                || self.bus_limits.get(bus).map_or(false, |max| *count >= *max)
+            {
+                voices[index].is_virtual = true;
+            } else {
+                total += 1;
+                *count += 1;
+            }
+        }
+
+        self.real_voices = total;
+        self.virtual_voices = voices.len() - total;
+
+        for voice in voices {
+            let source = sources.borrow_mut(voice.handle);
+            if source.is_virtual && !voice.is_virtual {
+                // Reset gains, so the voice won't fade in from the values that it had before
+                // it was virtualized.
+                source.last_left_gain = None;
+                source.last_right_gain = None;
+                source.prev_distance_gain = None;
+            }
+            source.is_virtual = voice.is_virtual;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{DataSource, SoundBufferResource},
+        context::DistanceModel,
+        listener::Listener,
+        source::{SoundSource, SoundSourceBuilder, Status},
+        voice::{StealPolicy, VoiceManager},
+    };
+    use fyrox_core::{
+        pool::{Handle, Pool},
+        visitor::prelude::*,
+    };
+
+    fn make_buffer() -> SoundBufferResource {
+        SoundBufferResource::new_generic(DataSource::Raw {
+            sample_rate: 44100,
+            channel_count: 1,
+            samples: vec![0.0; 64],
+        })
+        .unwrap()
+    }
+
+    fn spawn(sources: &mut Pool<SoundSource>, builder: SoundSourceBuilder) -> Handle<SoundSource> {
+        sources.spawn(
+            builder
+                .with_status(Status::Playing)
+                // Disable spatialization, so gain of a voice is not affected by distance.
+                .with_spatial_blend_factor(0.0)
+                .build()
+                .unwrap(),
+        )
+    }
+
+    fn update(manager: &mut VoiceManager, sources: &mut Pool<SoundSource>) {
+        manager.update(sources, &Listener::default(), DistanceModel::None);
+    }
+
+    fn virtual_voices(sources: &Pool<SoundSource>) -> Vec<bool> {
+        sources.iter().map(|s| s.is_virtual).collect()
+    }
+
+    #[test]
+    fn test_steal_oldest() {
+        let mut sources = Pool::new();
+        for _ in 0..3 {
+            spawn(&mut sources, SoundSourceBuilder::new());
+        }
+
+        let mut manager = VoiceManager::default();
+        manager.set_max_voices(Some(2));
+        update(&mut manager, &mut sources);
+
+        assert_eq!(virtual_voices(&sources), [true, false, false]);
+        assert_eq!(manager.real_voice_count(), 2);
+        assert_eq!(manager.virtual_voice_count(), 1);
+    }
+
+    #[test]
+    fn test_steal_quietest() {
+        let mut sources = Pool::new();
+        for gain in [0.5, 0.1, 1.0] {
+            spawn(&mut sources, SoundSourceBuilder::new().with_gain(gain));
+        }
+
+        let mut manager = VoiceManager::default();
+        manager.set_max_voices(Some(2));
+        manager.set_steal_policy(StealPolicy::Quietest);
+        update(&mut manager, &mut sources);
+
+        assert_eq!(virtual_voices(&sources), [false, true, false]);
+    }
+
+    #[test]
+    fn test_steal_lowest_priority() {
+        let mut sources = Pool::new();
+        for priority in [5, 3, 1] {
+            spawn(
+                &mut sources,
+                SoundSourceBuilder::new().with_priority(priority),
+            );
+        }
+
+        let mut manager = VoiceManager::default();
+        manager.set_max_voices(Some(2));
+        manager.set_steal_policy(StealPolicy::LowestPriority);
+        update(&mut manager, &mut sources);
+
+        assert_eq!(virtual_voices(&sources), [false, false, true]);
+    }
+
+    #[test]
+    fn test_bus_limit() {
+        let mut sources = Pool::new();
+        spawn(&mut sources, SoundSourceBuilder::new().with_bus("sfx"));
+        spawn(&mut sources, SoundSourceBuilder::new().with_bus("music"));
+        spawn(&mut sources, SoundSourceBuilder::new().with_bus("sfx"));
+
+        let mut manager = VoiceManager::default();
+        manager.set_bus_limit("sfx", Some(1));
+        update(&mut manager, &mut sources);
+
+        assert_eq!(virtual_voices(&sources), [true, false, false]);
+
+        manager.set_bus_limit("sfx", None);
+        assert_eq!(manager.bus_limit("sfx"), None);
+        update(&mut manager, &mut sources);
+
+        assert_eq!(virtual_voices(&sources), [false, false, false]);
+    }
+
+    #[test]
+    fn test_instance_limit() {
+        let buffer = make_buffer();
+        let mut sources = Pool::new();
+        for _ in 0..3 {
+            spawn(
+                &mut sources,
+                SoundSourceBuilder::new()
+                    .with_buffer(buffer.clone())
+                    .with_max_instances(Some(2)),
+            );
+        }
+        // Instances of other sounds are not affected.
+        spawn(
+            &mut sources,
+            SoundSourceBuilder::new()
+                .with_buffer(make_buffer())
+                .with_max_instances(Some(2)),
+        );
+
+        let mut manager = VoiceManager::default();
+        update(&mut manager, &mut sources);
+
+        assert_eq!(virtual_voices(&sources), [true, false, false, false]);
+    }
+
+    #[test]
+    fn test_devirtualization() {
+        let mut sources = Pool::new();
+        let older = spawn(&mut sources, SoundSourceBuilder::new());
+        let newer = spawn(&mut sources, SoundSourceBuilder::new());
+
+        let mut manager = VoiceManager::default();
+        manager.set_max_voices(Some(1));
+        update(&mut manager, &mut sources);
+        assert!(sources[older].is_virtual);
+        assert!(!sources[newer].is_virtual);
+
+        sources[older].last_left_gain = Some(1.0);
+        sources[newer].stop().unwrap();
+        update(&mut manager, &mut sources);
+
+        assert!(!sources[older].is_virtual);
+        // Gains are reset, so the voice won't fade in from stale values.
+        assert_eq!(sources[older].last_left_gain, None);
+        assert_eq!(manager.real_voice_count(), 1);
+        assert_eq!(manager.virtual_voice_count(), 0);
+    }
+
+    #[test]
+    fn test_visit() {
+        let mut manager = VoiceManager::default();
+        manager.set_max_voices(Some(32));
+        manager.set_bus_limit("sfx", Some(8));
+        manager.set_steal_policy(StealPolicy::Quietest);
+
+        let mut visitor = Visitor::new();
+        manager.visit("VoiceManager", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = VoiceManager::default();
+        loaded.visit("VoiceManager", &mut visitor).unwrap();
+
+        assert_eq!(loaded.max_voices(), Some(32));
+        assert_eq!(loaded.bus_limit("sfx"), Some(8));
+        assert_eq!(loaded.steal_policy(), StealPolicy::Quietest);
+    }
+}
@@ -0,0 +1,346 @@
+//! Breadcrumb is a horizontal list of clickable path segments (for example `Assets > Models > Props`).
+//! When there is not enough space for every segment, leading segments are collapsed into "..."
+//! button, that shows collapsed segments in a popup. The last segment is always visible.
+
+use crate::{
+    border::BorderBuilder,
+    brush::Brush,
+    button::{ButtonBuilder, ButtonMessage},
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    decorator::DecoratorBuilder,
+    define_constructor,
+    message::{MessageDirection, UiMessage},
+    popup::{Placement, PopupBuilder, PopupMessage},
+    stack_panel::StackPanelBuilder,
+    text::TextBuilder,
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface, VerticalAlignment,
+    BRUSH_LIGHT, BRUSH_LIGHTER,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::Cell,
+    ops::{Deref, DerefMut},
+    sync::mpsc::Sender,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreadcrumbMessage {
+    /// Sets new path segments.
+    Segments(Vec<String>),
+    /// Initiated when user clicks on a segment (either visible or collapsed) with given index.
+    Click(usize),
+}
+
+impl BreadcrumbMessage {
+    define_constructor!(BreadcrumbMessage:Segments => fn segments(Vec<String>), layout: false);
+    define_constructor!(BreadcrumbMessage:Click => fn click(usize), layout: false);
+}
+
+#[derive(Clone)]
+pub struct Breadcrumb {
+    pub widget: Widget,
+    pub segments: Vec<String>,
+    pub buttons: Vec<Handle<UiNode>>,
+    /// Separator after every segment except the last one.
+    pub separators: Vec<Handle<UiNode>>,
+    pub overflow: Handle<UiNode>,
+    pub popup: Handle<UiNode>,
+    pub popup_buttons: Vec<Handle<UiNode>>,
+    /// Amount of leading segments that do not fit and collapsed into "..." button.
+    collapsed: Cell<usize>,
+}
+
+crate::define_widget_deref!(Breadcrumb);
+
+fn make_segment_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(WidgetBuilder::new())
+        .with_back(
+            DecoratorBuilder::new(
+                BorderBuilder::new(WidgetBuilder::new())
+                    .with_stroke_thickness(Thickness::uniform(0.0)),
+            )
+            .with_normal_brush(Brush::Solid(Color::TRANSPARENT))
+            .with_hover_brush(BRUSH_LIGHT)
+            .with_pressed_brush(BRUSH_LIGHTER)
+            .build(ctx),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left_right(4.0)))
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text(text)
+                .build(ctx),
+        )
+        .build(ctx)
+}
+
+fn make_separator(ctx: &mut BuildContext) -> Handle<UiNode> {
+    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left_right(2.0)))
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .with_text(">")
+        .build(ctx)
+}
+
+fn make_segments(
+    ctx: &mut BuildContext,
+    segments: &[String],
+) -> (Vec<Handle<UiNode>>, Vec<Handle<UiNode>>) {
+    let buttons = segments
+        .iter()
+        .map(|s| make_segment_button(ctx, s))
+        .collect::<Vec<_>>();
+    let separators = (1..segments.len())
+        .map(|_| make_separator(ctx))
+        .collect::<Vec<_>>();
+    (buttons, separators)
+}
+
+/// Returns amount of leading segments that must be collapsed, so the rest of segments fits into
+/// given width. The last segment is never collapsed.
+fn collapsed_count(segments: &[f32], separators: &[f32], overflow: f32, width: f32) -> usize {
+    let total = segments.iter().sum::<f32>() + separators.iter().sum::<f32>();
+    if total <= width || segments.len() < 2 {
+        return 0;
+    }
+    for collapsed in 1..segments.len() {
+        let visible = overflow
+            + segments[collapsed..].iter().sum::<f32>()
+            + separators[(collapsed - 1)..].iter().sum::<f32>();
+        if visible <= width {
+            return collapsed;
+        }
+    }
+    segments.len() - 1
+}
+
+impl Breadcrumb {
+    /// Returns amount of leading segments that are collapsed into "..." button.
+    pub fn collapsed(&self) -> usize {
+        self.collapsed.get()
+    }
+
+    fn widths(&self, ui: &UserInterface, handles: &[Handle<UiNode>]) -> Vec<f32> {
+        handles
+            .iter()
+            .map(|h| ui.node(*h).desired_size().x)
+            .collect()
+    }
+}
+
+impl Control for Breadcrumb {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn on_remove(&self, sender: &Sender<UiMessage>) {
+        // Popup is not a child of the breadcrumb, so it must be removed manually.
+        sender
+            .send(WidgetMessage::remove(
+                self.popup,
+                MessageDirection::ToWidget,
+            ))
+            .unwrap();
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve_slice(&mut self.buttons);
+        node_map.resolve_slice(&mut self.separators);
+        node_map.resolve(&mut self.overflow);
+        node_map.resolve(&mut self.popup);
+        node_map.resolve_slice(&mut self.popup_buttons);
+    }
+
+    fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
+        let constraint = Vector2::new(f32::INFINITY, available_size.y);
+        let mut size = Vector2::<f32>::default();
+        for &child in self.buttons.iter().chain(self.separators.iter()) {
+            ui.measure_node(child, constraint);
+            let desired = ui.node(child).desired_size();
+            size.x += desired.x;
+            size.y = size.y.max(desired.y);
+        }
+        ui.measure_node(self.overflow, constraint);
+        size.x = size.x.min(available_size.x);
+        size
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        let overflow_width = ui.node(self.overflow).desired_size().x;
+        let collapsed = collapsed_count(
+            &self.widths(ui, &self.buttons),
+            &self.widths(ui, &self.separators),
+            overflow_width,
+            final_size.x,
+        );
+        self.collapsed.set(collapsed);
+
+        let mut x = 0.0;
+        let mut place = |handle: Handle<UiNode>, visible: bool| {
+            if visible {
+                let width = ui.node(handle).desired_size().x;
+                ui.arrange_node(handle, &Rect::new(x, 0.0, width, final_size.y));
+                x += width;
+            } else {
+                // Collapsed items are arranged with zero size, which makes them invisible.
+                ui.arrange_node(handle, &Rect::default());
+            }
+        };
+
+        place(self.overflow, collapsed > 0);
+        for (i, &button) in self.buttons.iter().enumerate() {
+            if i > 0 {
+                place(self.separators[i - 1], i >= collapsed);
+            }
+            place(button, i >= collapsed);
+        }
+
+        final_size
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if let Some(index) = self
+                .buttons
+                .iter()
+                .position(|b| *b == message.destination())
+            {
+                ui.send_message(BreadcrumbMessage::click(
+                    self.handle,
+                    MessageDirection::FromWidget,
+                    index,
+                ));
+            } else if message.destination() == self.overflow {
+                // Previous content of the popup (with its buttons) is removed by the popup itself.
+                let ctx = &mut ui.build_ctx();
+                self.popup_buttons = self.segments[..self.collapsed.get()]
+                    .iter()
+                    .map(|s| make_segment_button(ctx, s))
+                    .collect();
+                let panel = StackPanelBuilder::new(
+                    WidgetBuilder::new().with_children(self.popup_buttons.iter().cloned()),
+                )
+                .build(ctx);
+                ui.send_message(PopupMessage::content(
+                    self.popup,
+                    MessageDirection::ToWidget,
+                    panel,
+                ));
+                ui.send_message(PopupMessage::placement(
+                    self.popup,
+                    MessageDirection::ToWidget,
+                    Placement::LeftBottom(self.overflow),
+                ));
+                ui.send_message(PopupMessage::open(self.popup, MessageDirection::ToWidget));
+            }
+        } else if let Some(BreadcrumbMessage::Segments(segments)) = message.data() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+                && &self.segments != segments
+            {
+                for &child in self.buttons.iter().chain(self.separators.iter()) {
+                    ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+                }
+                let (buttons, separators) = make_segments(&mut ui.build_ctx(), segments);
+                for &child in buttons.iter().chain(separators.iter()) {
+                    ui.send_message(WidgetMessage::link(
+                        child,
+                        MessageDirection::ToWidget,
+                        self.handle,
+                    ));
+                }
+                self.buttons = buttons;
+                self.separators = separators;
+                self.segments = segments.clone();
+                self.invalidate_layout();
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        // Popup is not a child of the breadcrumb, so clicks on collapsed segments won't be routed
+        // to it.
+        if let Some(ButtonMessage::Click) = message.data() {
+            if let Some(index) = self
+                .popup_buttons
+                .iter()
+                .position(|b| *b == message.destination())
+            {
+                ui.send_message(PopupMessage::close(self.popup, MessageDirection::ToWidget));
+                ui.send_message(BreadcrumbMessage::click(
+                    self.handle,
+                    MessageDirection::FromWidget,
+                    index,
+                ));
+            }
+        }
+    }
+}
+
+pub struct BreadcrumbBuilder {
+    widget_builder: WidgetBuilder,
+    segments: Vec<String>,
+}
+
+impl BreadcrumbBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            segments: Default::default(),
+        }
+    }
+
+    pub fn with_segments(mut self, segments: Vec<String>) -> Self {
+        self.segments = segments;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let (buttons, separators) = make_segments(ctx, &self.segments);
+        let overflow = make_segment_button(ctx, "...");
+        let popup = PopupBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let breadcrumb = Breadcrumb {
+            widget: self
+                .widget_builder
+                .with_child(overflow)
+                .with_children(buttons.iter().chain(separators.iter()).cloned())
+                .with_preview_messages(true)
+                .build(),
+            segments: self.segments,
+            buttons,
+            separators,
+            overflow,
+            popup,
+            popup_buttons: Default::default(),
+            collapsed: Cell::new(0),
+        };
+
+        ctx.add_node(UiNode::new(breadcrumb))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::breadcrumb::collapsed_count;
+
+    #[test]
+    fn test_collapsed_count() {
+        let segments = [50.0, 50.0, 50.0, 50.0];
+        let separators = [10.0, 10.0, 10.0];
+
+        // Everything fits.
+        assert_eq!(collapsed_count(&segments, &separators, 20.0, 230.0), 0);
+        // "..." + sep + 3 segments + 2 separators = 20 + 10 + 150 + 20 = 200.
+        assert_eq!(collapsed_count(&segments, &separators, 20.0, 200.0), 1);
+        assert_eq!(collapsed_count(&segments, &separators, 20.0, 140.0), 2);
+        // The last segment is always visible.
+        assert_eq!(collapsed_count(&segments, &separators, 20.0, 10.0), 3);
+        assert_eq!(collapsed_count(&[50.0], &[], 20.0, 10.0), 0);
+    }
+}
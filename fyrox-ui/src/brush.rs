@@ -1,5 +1,17 @@
-use crate::core::algebra::Vector2;
+//! Brushes define how geometry of widgets is filled: with a solid color, a gradient or a texture.
+//!
+//! Gradient and texture brushes use [`BrushCoordinates`] to define what their points are relative
+//! to. With [`BrushCoordinates::Relative`] (default) `(0, 0)` is the top-left corner and `(1, 1)`
+//! is the bottom-right corner of bounds of the drawn geometry, so the same brush can be used for
+//! widgets of any size. With [`BrushCoordinates::Absolute`] points are in screen-space pixels,
+//! which allows multiple widgets to share single continuous gradient or texture.
+//!
+//! All brushes are rendered by the UI shader directly, no intermediate images are created.
+
+use crate::core::algebra::{Matrix3, Vector2};
 use crate::core::color::Color;
+use crate::core::color_gradient::ColorGradient;
+use crate::draw::SharedTexture;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct GradientPoint {
@@ -7,6 +19,28 @@ pub struct GradientPoint {
     pub color: Color,
 }
 
+impl GradientPoint {
+    pub fn new(stop: f32, color: Color) -> Self {
+        Self { stop, color }
+    }
+}
+
+/// Defines how coordinates of gradient and texture brushes are interpreted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BrushCoordinates {
+    /// Coordinates are relative to bounds of drawn geometry: `(0, 0)` is the top-left corner,
+    /// `(1, 1)` is the bottom-right corner.
+    Relative,
+    /// Coordinates are in screen-space pixels.
+    Absolute,
+}
+
+impl Default for BrushCoordinates {
+    fn default() -> Self {
+        Self::Relative
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Brush {
     Solid(Color),
@@ -14,9 +48,216 @@ pub enum Brush {
         from: Vector2<f32>,
         to: Vector2<f32>,
         stops: Vec<GradientPoint>,
+        coordinates: BrushCoordinates,
     },
     RadialGradient {
         center: Vector2<f32>,
+        /// Distance from the center at which the last stop is reached.
+        radius: f32,
         stops: Vec<GradientPoint>,
+        coordinates: BrushCoordinates,
     },
+    /// Gradient that sweeps around its center, stops are placed along the full circle starting
+    /// from `angle` (in radians, clockwise, `0` points to the right).
+    ConicGradient {
+        center: Vector2<f32>,
+        angle: f32,
+        stops: Vec<GradientPoint>,
+        coordinates: BrushCoordinates,
+    },
+    /// Fills geometry with a texture. `transform` maps brush coordinates to texture coordinates,
+    /// if `tiling` is set the texture is repeated, otherwise its edge pixels are stretched.
+    Texture {
+        texture: SharedTexture,
+        transform: Matrix3<f32>,
+        tiling: bool,
+        coordinates: BrushCoordinates,
+    },
+}
+
+/// Maximum amount of gradient stops supported by the UI renderer, extra stops are ignored.
+pub const MAX_GRADIENT_STOPS: usize = 16;
+
+impl Brush {
+    /// Creates linear gradient between two points in relative coordinates.
+    pub fn linear_gradient(
+        from: Vector2<f32>,
+        to: Vector2<f32>,
+        stops: Vec<GradientPoint>,
+    ) -> Self {
+        Brush::LinearGradient {
+            from,
+            to,
+            stops,
+            coordinates: BrushCoordinates::Relative,
+        }
+    }
+
+    /// Creates linear gradient that goes through the center of drawn geometry at the given angle
+    /// (in radians, clockwise, `0` means left-to-right). The gradient line is chosen so that
+    /// the first and the last stops are exactly at the corners of the bounds.
+    pub fn linear_gradient_with_angle(angle: f32, stops: Vec<GradientPoint>) -> Self {
+        let direction = Vector2::new(angle.cos(), angle.sin());
+        let half_length = 0.5 * (direction.x.abs() + direction.y.abs());
+        let center = Vector2::new(0.5, 0.5);
+        Self::linear_gradient(
+            center - direction * half_length,
+            center + direction * half_length,
+            stops,
+        )
+    }
+
+    /// Creates radial gradient in relative coordinates.
+    pub fn radial_gradient(center: Vector2<f32>, radius: f32, stops: Vec<GradientPoint>) -> Self {
+        Brush::RadialGradient {
+            center,
+            radius,
+            stops,
+            coordinates: BrushCoordinates::Relative,
+        }
+    }
+
+    /// Creates conic gradient in relative coordinates.
+    pub fn conic_gradient(center: Vector2<f32>, angle: f32, stops: Vec<GradientPoint>) -> Self {
+        Brush::ConicGradient {
+            center,
+            angle,
+            stops,
+            coordinates: BrushCoordinates::Relative,
+        }
+    }
+
+    /// Creates texture brush that stretches the texture over bounds of drawn geometry.
+    pub fn texture(texture: SharedTexture) -> Self {
+        Brush::Texture {
+            texture,
+            transform: Matrix3::identity(),
+            tiling: false,
+            coordinates: BrushCoordinates::Relative,
+        }
+    }
+
+    /// Creates texture brush that repeats the texture every `tile_size` pixels.
+    pub fn tiled_texture(texture: SharedTexture, tile_size: Vector2<f32>) -> Self {
+        Brush::Texture {
+            texture,
+            transform: Matrix3::new_nonuniform_scaling(&Vector2::new(
+                1.0 / tile_size.x,
+                1.0 / tile_size.y,
+            )),
+            tiling: true,
+            coordinates: BrushCoordinates::Absolute,
+        }
+    }
+
+    /// Sets coordinate mode of a gradient or a texture brush, does nothing for solid brushes.
+    pub fn with_coordinates(mut self, mode: BrushCoordinates) -> Self {
+        match self {
+            Brush::Solid(_) => (),
+            Brush::LinearGradient {
+                ref mut coordinates,
+                ..
+            }
+            | Brush::RadialGradient {
+                ref mut coordinates,
+                ..
+            }
+            | Brush::ConicGradient {
+                ref mut coordinates,
+                ..
+            }
+            | Brush::Texture {
+                ref mut coordinates,
+                ..
+            } => *coordinates = mode,
+        }
+        self
+    }
+
+    /// Returns gradient stops of the brush, empty slice for solid and texture brushes.
+    pub fn stops(&self) -> &[GradientPoint] {
+        match self {
+            Brush::Solid(_) | Brush::Texture { .. } => &[],
+            Brush::LinearGradient { stops, .. }
+            | Brush::RadialGradient { stops, .. }
+            | Brush::ConicGradient { stops, .. } => stops,
+        }
+    }
+
+    /// Returns coordinate mode of the brush, solid brushes are always relative.
+    pub fn coordinates(&self) -> BrushCoordinates {
+        match *self {
+            Brush::Solid(_) => BrushCoordinates::Relative,
+            Brush::LinearGradient { coordinates, .. }
+            | Brush::RadialGradient { coordinates, .. }
+            | Brush::ConicGradient { coordinates, .. }
+            | Brush::Texture { coordinates, .. } => coordinates,
+        }
+    }
+
+    /// Creates linear gradient brush from a color gradient (for example the one made in
+    /// [`crate::color::ColorGradientEditor`]). Only first [`MAX_GRADIENT_STOPS`] points of the
+    /// gradient are used.
+    pub fn from_color_gradient(
+        from: Vector2<f32>,
+        to: Vector2<f32>,
+        gradient: &ColorGradient,
+    ) -> Self {
+        Self::linear_gradient(
+            from,
+            to,
+            gradient
+                .points()
+                .iter()
+                .take(MAX_GRADIENT_STOPS)
+                .map(|point| GradientPoint::new(point.location(), point.color()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        brush::{Brush, BrushCoordinates, GradientPoint},
+        core::{algebra::Vector2, color::Color},
+    };
+
+    #[test]
+    fn test_linear_gradient_with_angle() {
+        let stops = vec![
+            GradientPoint::new(0.0, Color::BLACK),
+            GradientPoint::new(1.0, Color::WHITE),
+        ];
+
+        match Brush::linear_gradient_with_angle(0.0, stops.clone()) {
+            Brush::LinearGradient { from, to, .. } => {
+                assert!((from - Vector2::new(0.0, 0.5)).norm() < 1.0e-5);
+                assert!((to - Vector2::new(1.0, 0.5)).norm() < 1.0e-5);
+            }
+            _ => unreachable!(),
+        }
+
+        // Diagonal gradient must go from corner to corner (in projection).
+        match Brush::linear_gradient_with_angle(std::f32::consts::FRAC_PI_4, stops) {
+            Brush::LinearGradient { from, to, .. } => {
+                assert!((from - Vector2::new(0.0, 0.0)).norm() < 1.0e-5);
+                assert!((to - Vector2::new(1.0, 1.0)).norm() < 1.0e-5);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_with_coordinates() {
+        let brush = Brush::radial_gradient(Vector2::new(0.5, 0.5), 0.5, vec![])
+            .with_coordinates(BrushCoordinates::Absolute);
+        assert_eq!(brush.coordinates(), BrushCoordinates::Absolute);
+        assert_eq!(
+            Brush::Solid(Color::WHITE)
+                .with_coordinates(BrushCoordinates::Absolute)
+                .coordinates(),
+            BrushCoordinates::Relative
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     border::BorderBuilder,
-    brush::{Brush, GradientPoint},
+    brush::{Brush, BrushCoordinates, GradientPoint},
     core::{algebra::Vector2, pool::Handle},
     decorator::DecoratorBuilder,
     define_constructor,
@@ -198,6 +198,7 @@ impl ButtonBuilder {
                                     color: COLOR_DARKEST,
                                 },
                             ],
+                            coordinates: BrushCoordinates::Relative,
                         })
                         .with_child(content),
                 )
@@ -0,0 +1,775 @@
+//! Chart is a widget that plots one or more data series as lines, bars or scattered points. Axes
+//! are scaled automatically to fit the data (unless fixed ranges are specified), ticks are placed
+//! on "nice" values (1, 2 or 5 times a power of ten) and labeled.
+//!
+//! Every series can work as a ring buffer (see [`Series::with_capacity`]), which makes the chart
+//! suitable for live telemetry - just send [`ChartMessage::AppendPoints`] every frame and old
+//! points will be discarded automatically.
+//!
+//! Hovering the mouse over a data point shows a tooltip with the name of the series and the
+//! coordinates of the point.
+
+use crate::{
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext},
+    formatted_text::{FormattedText, FormattedTextBuilder},
+    message::{MessageDirection, UiMessage},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, UiNode, UserInterface, BRUSH_DARKER, BRUSH_LIGHTEST, BRUSH_TEXT,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+
+/// Default maximum amount of ticks per axis.
+const DEFAULT_MAX_TICKS: usize = 8;
+/// Distance between labels and the plot area.
+const LABEL_PADDING: f32 = 4.0;
+/// Maximum distance (in pixels) from the cursor to a data point at which the point is considered
+/// hovered.
+const HOVER_RADIUS: f32 = 8.0;
+/// Portion of the distance between adjacent bars that is occupied by a group of bars.
+const BAR_GROUP_WIDTH: f32 = 0.8;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SeriesKind {
+    /// Points are connected with straight lines.
+    Line,
+    /// Every point is a vertical bar that starts at zero. Bars of different series that share the
+    /// same x coordinate are grouped side by side.
+    Bar,
+    /// Every point is drawn as a circle.
+    Scatter,
+}
+
+impl Default for SeriesKind {
+    fn default() -> Self {
+        Self::Line
+    }
+}
+
+/// A named set of points drawn in the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub kind: SeriesKind,
+    pub brush: Brush,
+    /// Thickness of lines or radius of points of a scatter series. Not used by bars.
+    pub thickness: f32,
+    points: VecDeque<Vector2<f32>>,
+    capacity: Option<usize>,
+}
+
+impl Series {
+    pub fn new<S: AsRef<str>>(name: S, kind: SeriesKind, brush: Brush) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            kind,
+            brush,
+            thickness: 2.0,
+            points: Default::default(),
+            capacity: None,
+        }
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_points<I: IntoIterator<Item = Vector2<f32>>>(mut self, points: I) -> Self {
+        self.extend(points);
+        self
+    }
+
+    /// Limits the amount of points of the series. When the limit is reached, every new point
+    /// replaces the oldest one.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.set_capacity(Some(capacity));
+        self
+    }
+
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub fn push(&mut self, point: Vector2<f32>) {
+        self.points.push_back(point);
+        self.trim();
+    }
+
+    pub fn extend<I: IntoIterator<Item = Vector2<f32>>>(&mut self, points: I) {
+        self.points.extend(points);
+        self.trim();
+    }
+
+    pub fn points(&self) -> &VecDeque<Vector2<f32>> {
+        &self.points
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Returns min and max corners of the bounding rectangle of the points, `None` if the series
+    /// is empty.
+    pub fn bounds(&self) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        let mut points = self.points.iter();
+        let first = *points.next()?;
+        Some(points.fold((first, first), |(min, max), p| (min.inf(p), max.sup(p))))
+    }
+
+    fn trim(&mut self) {
+        if let Some(capacity) = self.capacity {
+            while self.points.len() > capacity {
+                self.points.pop_front();
+            }
+        }
+    }
+}
+
+/// Picks a step between ticks that is 1, 2 or 5 times a power of ten, so there will be no more
+/// than `max_ticks` intervals in given range.
+fn nice_step(range: f32, max_ticks: usize) -> f32 {
+    let rough = range / max_ticks.max(1) as f32;
+    if !rough.is_finite() || rough <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10.0f32.powf(rough.log10().floor());
+    // Tolerance protects from rounding errors of `powf`, for example 0.05 / 0.01 could be
+    // slightly larger than 5.
+    let residual = rough / magnitude - 1.0e-4;
+    let nice = if residual <= 1.0 {
+        1.0
+    } else if residual <= 2.0 {
+        2.0
+    } else if residual <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Returns multiples of `step` that lie in `[min; max]` range.
+fn ticks(min: f32, max: f32, step: f32) -> Vec<f32> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step + 1.0e-3).floor() as i64;
+    (first..=last).map(|i| i as f32 * step).collect()
+}
+
+/// Returns amount of digits after the decimal point that is enough to distinguish ticks.
+fn precision(step: f32) -> usize {
+    (-step.log10().floor()).max(0.0) as usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartMessage {
+    /// Replaces all series of the chart.
+    ///
+    /// Direction: **To UI**.
+    Series(Vec<Series>),
+    /// Adds new series on top of others.
+    ///
+    /// Direction: **To UI**.
+    AddSeries(Series),
+    /// Removes a series at given index.
+    ///
+    /// Direction: **To UI**.
+    RemoveSeries(usize),
+    /// Appends points to a series at given index. If the series has limited capacity, oldest
+    /// points will be removed.
+    ///
+    /// Direction: **To UI**.
+    AppendPoints {
+        series: usize,
+        points: Vec<Vector2<f32>>,
+    },
+    /// Removes every point of a series at given index.
+    ///
+    /// Direction: **To UI**.
+    ClearPoints(usize),
+    /// Sets fixed range of the x axis, `None` - the axis fits the data.
+    ///
+    /// Direction: **To UI**.
+    XRange(Option<(f32, f32)>),
+    /// Sets fixed range of the y axis, `None` - the axis fits the data.
+    ///
+    /// Direction: **To UI**.
+    YRange(Option<(f32, f32)>),
+}
+
+impl ChartMessage {
+    define_constructor!(ChartMessage:Series => fn series(Vec<Series>), layout: false);
+    define_constructor!(ChartMessage:AddSeries => fn add_series(Series), layout: false);
+    define_constructor!(ChartMessage:RemoveSeries => fn remove_series(usize), layout: false);
+    define_constructor!(ChartMessage:AppendPoints => fn append_points(series: usize, points: Vec<Vector2<f32>>), layout: false);
+    define_constructor!(ChartMessage:ClearPoints => fn clear_points(usize), layout: false);
+    define_constructor!(ChartMessage:XRange => fn x_range(Option<(f32, f32)>), layout: false);
+    define_constructor!(ChartMessage:YRange => fn y_range(Option<(f32, f32)>), layout: false);
+}
+
+/// Mapping between data space and local space of the widget.
+struct ChartLayout {
+    /// Area that is occupied by the data (without labels) in local coordinates.
+    plot: Rect<f32>,
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+    step: Vector2<f32>,
+}
+
+impl ChartLayout {
+    fn data_to_local(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let size = self.max - self.min;
+        Vector2::new(
+            self.plot.x() + (point.x - self.min.x) / size.x * self.plot.w(),
+            self.plot.y() + self.plot.h() - (point.y - self.min.y) / size.y * self.plot.h(),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct Chart {
+    pub widget: Widget,
+    pub series: Vec<Series>,
+    pub x_range: Option<(f32, f32)>,
+    pub y_range: Option<(f32, f32)>,
+    pub max_ticks: usize,
+    pub grid_brush: Brush,
+    pub axis_brush: Brush,
+    /// Series and point indices of a point under cursor.
+    hovered: Option<(usize, usize)>,
+    text: RefCell<FormattedText>,
+}
+
+crate::define_widget_deref!(Chart);
+
+impl Chart {
+    pub fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    /// Returns series and point indices of a data point under cursor.
+    pub fn hovered(&self) -> Option<(usize, usize)> {
+        self.hovered
+    }
+
+    /// Distance between adjacent bars in data units, it is the smallest distance between x
+    /// coordinates of points of bar series.
+    fn bar_spacing(&self) -> Option<f32> {
+        let mut spacing: Option<f32> = None;
+        for series in self.series.iter().filter(|s| s.kind == SeriesKind::Bar) {
+            for (a, b) in series.points.iter().zip(series.points.iter().skip(1)) {
+                let distance = (b.x - a.x).abs();
+                if distance > f32::EPSILON {
+                    spacing = Some(spacing.map_or(distance, |s| s.min(distance)));
+                }
+            }
+            if spacing.is_none() && !series.points.is_empty() {
+                spacing = Some(1.0);
+            }
+        }
+        spacing
+    }
+
+    /// Returns min and max corners of the visible area in data space.
+    fn visible_range(&self) -> (Vector2<f32>, Vector2<f32>, Vector2<f32>) {
+        let mut min = Vector2::repeat(f32::MAX);
+        let mut max = Vector2::repeat(-f32::MAX);
+        for series in self.series.iter() {
+            if let Some((series_min, series_max)) = series.bounds() {
+                min = min.inf(&series_min);
+                max = max.sup(&series_max);
+                if series.kind == SeriesKind::Bar {
+                    // Bars start at zero.
+                    min.y = min.y.min(0.0);
+                    max.y = max.y.max(0.0);
+                }
+            }
+        }
+        if min.x > max.x {
+            min = Vector2::new(0.0, 0.0);
+            max = Vector2::new(1.0, 1.0);
+        }
+        if let Some(spacing) = self.bar_spacing() {
+            min.x -= spacing * 0.5;
+            max.x += spacing * 0.5;
+        }
+
+        let mut step = Vector2::default();
+        for axis in 0..2 {
+            let fixed = if axis == 0 {
+                self.x_range
+            } else {
+                self.y_range
+            };
+            if let Some((fixed_min, fixed_max)) = fixed {
+                min[axis] = fixed_min;
+                max[axis] = fixed_max;
+            }
+            if max[axis] - min[axis] <= f32::EPSILON {
+                min[axis] -= 0.5;
+                max[axis] += 0.5;
+            }
+            step[axis] = nice_step(max[axis] - min[axis], self.max_ticks);
+            if fixed.is_none() {
+                // Snap automatic ranges to ticks, so the data is surrounded by labeled lines.
+                min[axis] = (min[axis] / step[axis]).floor() * step[axis];
+                max[axis] = (max[axis] / step[axis]).ceil() * step[axis];
+            }
+        }
+
+        (min, max, step)
+    }
+
+    fn layout(&self) -> ChartLayout {
+        let (min, max, step) = self.visible_range();
+        let bounds = self.widget.bounding_rect();
+
+        let mut text = self.text.borrow_mut();
+        let y_precision = precision(step.y);
+        let mut label_size = Vector2::<f32>::default();
+        for value in ticks(min.y, max.y, step.y) {
+            label_size =
+                label_size.sup(&text.set_text(format!("{:.*}", y_precision, value)).build());
+        }
+
+        let left = label_size.x + LABEL_PADDING;
+        let bottom = label_size.y + LABEL_PADDING;
+        // Leave some space at the top and right for the last labels.
+        let top = label_size.y * 0.5;
+        let right = LABEL_PADDING * 4.0;
+
+        ChartLayout {
+            plot: Rect::new(
+                bounds.x() + left,
+                bounds.y() + top,
+                (bounds.w() - left - right).max(0.0),
+                (bounds.h() - top - bottom).max(0.0),
+            ),
+            min,
+            max,
+            step,
+        }
+    }
+
+    /// Returns series and point indices of a point closest to given point in local coordinates.
+    fn pick(&self, layout: &ChartLayout, local_pos: Vector2<f32>) -> Option<(usize, usize)> {
+        let mut closest = None;
+        let mut closest_distance = HOVER_RADIUS;
+        for (series_index, series) in self.series.iter().enumerate() {
+            for (point_index, point) in series.points.iter().enumerate() {
+                let distance = (layout.data_to_local(*point) - local_pos).norm();
+                if distance <= closest_distance {
+                    closest = Some((series_index, point_index));
+                    closest_distance = distance;
+                }
+            }
+        }
+        closest
+    }
+
+    fn draw_series(
+        &self,
+        drawing_context: &mut DrawingContext,
+        layout: &ChartLayout,
+        clip_bounds: Rect<f32>,
+    ) {
+        let bar_series_count = self
+            .series
+            .iter()
+            .filter(|s| s.kind == SeriesKind::Bar)
+            .count();
+        let bar_width =
+            self.bar_spacing().unwrap_or(1.0) * BAR_GROUP_WIDTH / bar_series_count.max(1) as f32;
+        let mut bar_index = 0;
+
+        for series in self.series.iter() {
+            match series.kind {
+                SeriesKind::Line => {
+                    for (a, b) in series.points.iter().zip(series.points.iter().skip(1)) {
+                        drawing_context.push_line(
+                            layout.data_to_local(*a),
+                            layout.data_to_local(*b),
+                            series.thickness,
+                        );
+                    }
+                }
+                SeriesKind::Bar => {
+                    let offset = bar_width * (bar_index as f32 - bar_series_count as f32 * 0.5);
+                    for point in series.points.iter() {
+                        let a = layout.data_to_local(Vector2::new(point.x + offset, 0.0));
+                        let b = layout
+                            .data_to_local(Vector2::new(point.x + offset + bar_width, point.y));
+                        let min = a.inf(&b);
+                        let max = a.sup(&b);
+                        drawing_context.push_rect_filled(
+                            &Rect::new(min.x, min.y, max.x - min.x, max.y - min.y),
+                            None,
+                        );
+                    }
+                    bar_index += 1;
+                }
+                SeriesKind::Scatter => {
+                    for point in series.points.iter() {
+                        drawing_context.push_circle(
+                            layout.data_to_local(*point),
+                            series.thickness,
+                            12,
+                            Color::WHITE,
+                        );
+                    }
+                }
+            }
+            drawing_context.commit(
+                clip_bounds,
+                series.brush.clone(),
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn draw_legend(&self, drawing_context: &mut DrawingContext, layout: &ChartLayout) {
+        let mut text = self.text.borrow_mut();
+        let mut position = Vector2::new(layout.plot.x() + LABEL_PADDING * 2.0, layout.plot.y());
+        for series in self.series.iter().filter(|s| !s.name.is_empty()) {
+            let size = text.set_text(&series.name).build();
+            drawing_context.push_rect_filled(
+                &Rect::new(
+                    position.x,
+                    position.y + size.y * 0.25,
+                    size.y * 0.5,
+                    size.y * 0.5,
+                ),
+                None,
+            );
+            drawing_context.commit(
+                self.clip_bounds(),
+                series.brush.clone(),
+                CommandTexture::None,
+                None,
+            );
+            drawing_context.draw_text(
+                self.clip_bounds(),
+                Vector2::new(position.x + size.y * 0.5 + LABEL_PADDING, position.y),
+                &text,
+            );
+            position.y += size.y;
+        }
+    }
+
+    fn draw_tooltip(&self, drawing_context: &mut DrawingContext, layout: &ChartLayout) {
+        let (series, point) = match self
+            .hovered
+            .and_then(|(s, p)| Some((self.series.get(s)?, *self.series.get(s)?.points.get(p)?)))
+        {
+            Some(hovered) => hovered,
+            None => return,
+        };
+
+        let local = layout.data_to_local(point);
+        drawing_context.push_circle(local, HOVER_RADIUS * 0.5, 12, Color::WHITE);
+        drawing_context.commit(self.clip_bounds(), BRUSH_TEXT, CommandTexture::None, None);
+
+        let mut text = self.text.borrow_mut();
+        let x_precision = precision(layout.step.x) + 1;
+        let y_precision = precision(layout.step.y) + 1;
+        let size = text
+            .set_text(format!(
+                "{}: ({:.*}, {:.*})",
+                series.name, x_precision, point.x, y_precision, point.y
+            ))
+            .build();
+
+        // Keep the tooltip inside of the widget.
+        let bounds = self.widget.bounding_rect();
+        let mut position = local + Vector2::new(HOVER_RADIUS, -HOVER_RADIUS - size.y);
+        position.x = position
+            .x
+            .min(bounds.x() + bounds.w() - size.x - LABEL_PADDING)
+            .max(bounds.x() + LABEL_PADDING);
+        position.y = position.y.max(bounds.y() + LABEL_PADDING);
+
+        let background = Rect::new(position.x, position.y, size.x, size.y)
+            .inflate(LABEL_PADDING, LABEL_PADDING * 0.5);
+        drawing_context.push_rect_filled(&background, None);
+        drawing_context.commit(self.clip_bounds(), BRUSH_DARKER, CommandTexture::None, None);
+        drawing_context.push_rect(&background, 1.0);
+        drawing_context.commit(
+            self.clip_bounds(),
+            series.brush.clone(),
+            CommandTexture::None,
+            None,
+        );
+        drawing_context.draw_text(self.clip_bounds(), position, &text);
+    }
+}
+
+impl Control for Chart {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.widget.bounding_rect();
+        drawing_context.push_rect_filled(&bounds, None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::None,
+            None,
+        );
+
+        let layout = self.layout();
+        let plot = layout.plot;
+        if plot.w() <= 0.0 || plot.h() <= 0.0 {
+            return;
+        }
+        let x_ticks = ticks(layout.min.x, layout.max.x, layout.step.x);
+        let y_ticks = ticks(layout.min.y, layout.max.y, layout.step.y);
+
+        // Grid.
+        for &x in x_ticks.iter() {
+            let local_x = layout.data_to_local(Vector2::new(x, 0.0)).x;
+            drawing_context.push_line(
+                Vector2::new(local_x, plot.y()),
+                Vector2::new(local_x, plot.y() + plot.h()),
+                1.0,
+            );
+        }
+        for &y in y_ticks.iter() {
+            let local_y = layout.data_to_local(Vector2::new(0.0, y)).y;
+            drawing_context.push_line(
+                Vector2::new(plot.x(), local_y),
+                Vector2::new(plot.x() + plot.w(), local_y),
+                1.0,
+            );
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.grid_brush.clone(),
+            CommandTexture::None,
+            None,
+        );
+
+        // Axes.
+        drawing_context.push_line(plot.left_top_corner(), plot.left_bottom_corner(), 1.0);
+        drawing_context.push_line(plot.left_bottom_corner(), plot.right_bottom_corner(), 1.0);
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.axis_brush.clone(),
+            CommandTexture::None,
+            None,
+        );
+
+        // Data is clipped by the plot area, so the points outside of fixed ranges won't overlap
+        // the labels.
+        let plot_clip_bounds = self
+            .clip_bounds()
+            .clip_by(plot.transform(self.widget.visual_transform()));
+        self.draw_series(drawing_context, &layout, plot_clip_bounds);
+
+        // Labels.
+        {
+            let mut text = self.text.borrow_mut();
+            let x_precision = precision(layout.step.x);
+            for &x in x_ticks.iter() {
+                let size = text.set_text(format!("{:.*}", x_precision, x)).build();
+                let local = layout.data_to_local(Vector2::new(x, layout.min.y));
+                drawing_context.draw_text(
+                    self.clip_bounds(),
+                    Vector2::new(local.x - size.x * 0.5, local.y + LABEL_PADDING),
+                    &text,
+                );
+            }
+            let y_precision = precision(layout.step.y);
+            for &y in y_ticks.iter() {
+                let size = text.set_text(format!("{:.*}", y_precision, y)).build();
+                let local = layout.data_to_local(Vector2::new(layout.min.x, y));
+                drawing_context.draw_text(
+                    self.clip_bounds(),
+                    Vector2::new(local.x - size.x - LABEL_PADDING, local.y - size.y * 0.5),
+                    &text,
+                );
+            }
+        }
+
+        self.draw_legend(drawing_context, &layout);
+        self.draw_tooltip(drawing_context, &layout);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<ChartMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    ChartMessage::Series(series) => {
+                        self.series = series.clone();
+                        self.hovered = None;
+                    }
+                    ChartMessage::AddSeries(series) => {
+                        self.series.push(series.clone());
+                    }
+                    &ChartMessage::RemoveSeries(index) => {
+                        if index < self.series.len() {
+                            self.series.remove(index);
+                            self.hovered = None;
+                        }
+                    }
+                    ChartMessage::AppendPoints { series, points } => {
+                        if let Some(series) = self.series.get_mut(*series) {
+                            series.extend(points.iter().cloned());
+                        }
+                    }
+                    &ChartMessage::ClearPoints(index) => {
+                        if let Some(series) = self.series.get_mut(index) {
+                            series.clear();
+                        }
+                    }
+                    &ChartMessage::XRange(range) => {
+                        self.x_range = range;
+                    }
+                    &ChartMessage::YRange(range) => {
+                        self.y_range = range;
+                    }
+                }
+            }
+        } else if let Some(msg) = message.data::<WidgetMessage>() {
+            if message.destination() == self.handle() {
+                match msg {
+                    WidgetMessage::MouseMove { pos, .. } => {
+                        let local_pos = *pos - self.screen_position();
+                        let layout = self.layout();
+                        self.hovered = self.pick(&layout, local_pos);
+                    }
+                    WidgetMessage::MouseLeave => {
+                        self.hovered = None;
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+pub struct ChartBuilder {
+    widget_builder: WidgetBuilder,
+    series: Vec<Series>,
+    x_range: Option<(f32, f32)>,
+    y_range: Option<(f32, f32)>,
+    max_ticks: usize,
+}
+
+impl ChartBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            series: Default::default(),
+            x_range: None,
+            y_range: None,
+            max_ticks: DEFAULT_MAX_TICKS,
+        }
+    }
+
+    pub fn with_series(mut self, series: Vec<Series>) -> Self {
+        self.series = series;
+        self
+    }
+
+    pub fn with_x_range(mut self, min: f32, max: f32) -> Self {
+        self.x_range = Some((min, max));
+        self
+    }
+
+    pub fn with_y_range(mut self, min: f32, max: f32) -> Self {
+        self.y_range = Some((min, max));
+        self
+    }
+
+    pub fn with_max_ticks(mut self, max_ticks: usize) -> Self {
+        self.max_ticks = max_ticks;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let chart = Chart {
+            widget: self.widget_builder.build(),
+            series: self.series,
+            x_range: self.x_range,
+            y_range: self.y_range,
+            max_ticks: self.max_ticks,
+            grid_brush: Brush::Solid(Color::from_rgba(110, 110, 110, 50)),
+            axis_brush: BRUSH_LIGHTEST,
+            hovered: None,
+            text: RefCell::new(
+                FormattedTextBuilder::new(ctx.default_font())
+                    .with_brush(BRUSH_TEXT)
+                    .build(),
+            ),
+        };
+
+        ctx.add_node(UiNode::new(chart))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        brush::Brush,
+        chart::{nice_step, precision, ticks, Series, SeriesKind},
+        core::{algebra::Vector2, color::Color},
+    };
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(nice_step(10.0, 10), 1.0);
+        assert_eq!(nice_step(10.0, 4), 5.0);
+        assert_eq!(nice_step(100.0, 8), 20.0);
+        assert!((nice_step(0.3, 6) - 0.05).abs() < 1.0e-6);
+
+        assert_eq!(ticks(-1.0, 3.5, 1.0), vec![-1.0, 0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(ticks(0.5, 0.9, 1.0), Vec::<f32>::new());
+
+        assert_eq!(precision(10.0), 0);
+        assert_eq!(precision(1.0), 0);
+        assert_eq!(precision(0.05), 2);
+    }
+
+    #[test]
+    fn test_series_ring_buffer() {
+        let mut series = Series::new("Frame time", SeriesKind::Line, Brush::Solid(Color::WHITE))
+            .with_capacity(3)
+            .with_points((0..2).map(|i| Vector2::new(i as f32, 0.0)));
+        assert_eq!(series.points().len(), 2);
+
+        series.extend((2..5).map(|i| Vector2::new(i as f32, i as f32)));
+        assert_eq!(series.points().len(), 3);
+        assert_eq!(series.points()[0], Vector2::new(2.0, 2.0));
+        assert_eq!(
+            series.bounds(),
+            Some((Vector2::new(2.0, 2.0), Vector2::new(4.0, 4.0)))
+        );
+
+        series.set_capacity(Some(1));
+        assert_eq!(series.points().len(), 1);
+        assert_eq!(series.points()[0], Vector2::new(4.0, 4.0));
+    }
+}
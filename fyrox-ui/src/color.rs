@@ -1,11 +1,15 @@
 use crate::{
     border::BorderBuilder,
     brush::Brush,
+    button::{ButtonBuilder, ButtonMessage},
     core::{
         algebra::Vector2,
         color::{Color, Hsv},
+        color_gradient::{ColorGradient, GradientPoint},
+        futures::executor::block_on,
         math::Rect,
         pool::Handle,
+        visitor::prelude::*,
     },
     define_constructor,
     draw::{CommandTexture, Draw, DrawingContext},
@@ -13,14 +17,17 @@ use crate::{
     message::{MessageDirection, MouseButton, UiMessage},
     numeric::{NumericUpDownBuilder, NumericUpDownMessage},
     popup::{Placement, PopupBuilder, PopupMessage},
-    text::TextBuilder,
+    text::{TextBuilder, TextMessage},
+    text_box::{TextBoxBuilder, TextCommitMode},
     widget::{Widget, WidgetBuilder, WidgetMessage},
-    BuildContext, Control, NodeHandleMapping, Orientation, Thickness, UiNode, UserInterface,
-    VerticalAlignment,
+    wrap_panel::WrapPanelBuilder,
+    BuildContext, Control, NodeHandleMapping, Orientation, RestrictionEntry, Thickness, UiNode,
+    UserInterface, VerticalAlignment,
 };
 use std::{
     any::{Any, TypeId},
     ops::{Deref, DerefMut},
+    path::Path,
     sync::mpsc::Sender,
 };
 
@@ -81,11 +88,18 @@ pub enum ColorPickerMessage {
     ///
     /// Direction: **To Widget**.
     Hsv(Hsv),
+
+    /// Sets palette of the picker. The picker sends this message back when the user adds or
+    /// removes a swatch, listen to it to persist the palette (see [`ColorPalette::save`]).
+    ///
+    /// Direction: **To/From Widget**.
+    Palette(ColorPalette),
 }
 
 impl ColorPickerMessage {
     define_constructor!(ColorPickerMessage:Color => fn color(Color), layout: false);
     define_constructor!(ColorPickerMessage:Hsv => fn hsv(Hsv), layout: false);
+    define_constructor!(ColorPickerMessage:Palette => fn palette(ColorPalette), layout: false);
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -719,6 +733,63 @@ impl SaturationBrightnessFieldBuilder {
     }
 }
 
+/// A named set of colors (swatches) of a [`ColorPicker`]. Palettes can be saved to a file and
+/// loaded back, which allows to keep frequently used colors across sessions.
+#[derive(Debug, Clone, PartialEq, Default, Visit)]
+pub struct ColorPalette {
+    pub name: String,
+    pub colors: Vec<Color>,
+}
+
+impl ColorPalette {
+    /// Saves the palette to a file at the given path.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.clone().visit("ColorPalette", &mut visitor)?;
+        visitor.save_binary(path)
+    }
+
+    /// Loads a palette from a file at the given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
+        let mut visitor = block_on(Visitor::load_binary(path))?;
+        let mut palette = Self::default();
+        palette.visit("ColorPalette", &mut visitor)?;
+        Ok(palette)
+    }
+}
+
+/// Formats a color as `#RRGGBB`, or `#RRGGBBAA` if the color is not fully opaque.
+pub fn color_to_hex(color: Color) -> String {
+    if color.a == 255 {
+        format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+    } else {
+        format!(
+            "#{:02X}{:02X}{:02X}{:02X}",
+            color.r, color.g, color.b, color.a
+        )
+    }
+}
+
+/// Parses a color in `#RRGGBB` or `#RRGGBBAA` form, leading `#` is optional.
+pub fn color_from_hex(text: &str) -> Option<Color> {
+    let text = text.trim();
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    if !digits.is_ascii() || (digits.len() != 6 && digits.len() != 8) {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).ok();
+    Some(Color::from_rgba(
+        component(0)?,
+        component(1)?,
+        component(2)?,
+        if digits.len() == 8 {
+            component(3)?
+        } else {
+            255
+        },
+    ))
+}
+
 #[derive(Clone)]
 pub struct ColorPicker {
     pub widget: Widget,
@@ -732,7 +803,15 @@ pub struct ColorPicker {
     pub hue: Handle<UiNode>,
     pub saturation: Handle<UiNode>,
     pub brightness: Handle<UiNode>,
+    pub hex: Handle<UiNode>,
     pub color_mark: Handle<UiNode>,
+    pub eyedropper: Handle<UiNode>,
+    pub palette_panel: Handle<UiNode>,
+    pub add_swatch: Handle<UiNode>,
+    pub swatches: Vec<Handle<UiNode>>,
+    pub palette: ColorPalette,
+    /// `true` if the picker waits for a click to pick a color from the screen.
+    pub picking: bool,
     pub color: Color,
     pub hsv: Hsv,
 }
@@ -744,6 +823,18 @@ fn mark_handled(message: UiMessage) -> UiMessage {
     message
 }
 
+fn make_swatch(ctx: &mut BuildContext, color: Color) -> Handle<UiNode> {
+    BorderBuilder::new(
+        WidgetBuilder::new()
+            .with_width(16.0)
+            .with_height(16.0)
+            .with_margin(Thickness::uniform(1.0))
+            .with_background(Brush::Solid(color)),
+    )
+    .with_stroke_thickness(Thickness::uniform(1.0))
+    .build(ctx)
+}
+
 impl ColorPicker {
     fn sync_fields(&self, ui: &mut UserInterface, color: Color, hsv: Hsv) {
         ui.send_message(mark_handled(NumericUpDownMessage::value(
@@ -788,12 +879,58 @@ impl ColorPicker {
             color.a as f32,
         )));
 
+        ui.send_message(mark_handled(TextMessage::text(
+            self.hex,
+            MessageDirection::ToWidget,
+            color_to_hex(color),
+        )));
+
         ui.send_message(mark_handled(WidgetMessage::background(
             self.color_mark,
             MessageDirection::ToWidget,
             Brush::Solid(color),
         )));
     }
+
+    fn rebuild_swatches(&mut self, ui: &mut UserInterface) {
+        for swatch in self.swatches.drain(..) {
+            ui.send_message(WidgetMessage::remove(swatch, MessageDirection::ToWidget));
+        }
+
+        let ctx = &mut ui.build_ctx();
+        self.swatches = self
+            .palette
+            .colors
+            .iter()
+            .map(|color| make_swatch(ctx, *color))
+            .collect();
+
+        for &swatch in self.swatches.iter() {
+            ui.send_message(WidgetMessage::link(
+                swatch,
+                MessageDirection::ToWidget,
+                self.palette_panel,
+            ));
+        }
+    }
+
+    /// Applies a palette change made by the user and notifies the outside world about it.
+    fn on_palette_edited(&mut self, ui: &mut UserInterface) {
+        self.rebuild_swatches(ui);
+        ui.send_message(ColorPickerMessage::palette(
+            self.handle,
+            MessageDirection::FromWidget,
+            self.palette.clone(),
+        ));
+    }
+
+    fn stop_picking(&mut self, ui: &mut UserInterface) {
+        self.picking = false;
+        ui.remove_picking_restriction(self.handle);
+        if ui.captured_node() == self.handle {
+            ui.release_mouse_capture();
+        }
+    }
 }
 
 impl Control for ColorPicker {
@@ -816,13 +953,84 @@ impl Control for ColorPicker {
         node_map.resolve(&mut self.hue);
         node_map.resolve(&mut self.saturation);
         node_map.resolve(&mut self.brightness);
+        node_map.resolve(&mut self.hex);
         node_map.resolve(&mut self.color_mark);
+        node_map.resolve(&mut self.eyedropper);
+        node_map.resolve(&mut self.palette_panel);
+        node_map.resolve(&mut self.add_swatch);
+        node_map.resolve_slice(&mut self.swatches);
     }
 
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
-        if let Some(&HueBarMessage::Hue(hue)) = message.data::<HueBarMessage>() {
+        if let Some(&WidgetMessage::MouseDown { pos, button }) = message.data::<WidgetMessage>() {
+            if self.picking && message.destination() == self.handle {
+                // The picker captures the mouse while the eyedropper is active, so any click
+                // anywhere in the application ends up here.
+                if button == MouseButton::Left {
+                    ui.request_screen_color(self.handle, pos);
+                }
+                self.stop_picking(ui);
+                message.set_handled(true);
+            } else if let Some(index) = self
+                .swatches
+                .iter()
+                .position(|swatch| *swatch == message.destination())
+            {
+                match button {
+                    MouseButton::Left => {
+                        ui.send_message(ColorPickerMessage::color(
+                            self.handle,
+                            MessageDirection::ToWidget,
+                            self.palette.colors[index],
+                        ));
+                    }
+                    MouseButton::Right => {
+                        self.palette.colors.remove(index);
+                        self.on_palette_edited(ui);
+                    }
+                    _ => (),
+                }
+                message.set_handled(true);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.add_swatch {
+                self.palette.colors.push(self.color);
+                self.on_palette_edited(ui);
+            } else if message.destination() == self.eyedropper
+                && !self.picking
+                && ui.capture_mouse(self.handle)
+            {
+                self.picking = true;
+                // Restrict picking to the picker, so popups containing it (for example the
+                // one of a color field) will stay open while the user picks a color.
+                ui.push_picking_restriction(RestrictionEntry {
+                    handle: self.handle,
+                    stop: true,
+                });
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data::<TextMessage>() {
+            if message.destination() == self.hex
+                && message.direction() == MessageDirection::FromWidget
+                && !message.handled()
+            {
+                if let Some(color) = color_from_hex(text) {
+                    ui.send_message(ColorPickerMessage::color(
+                        self.handle,
+                        MessageDirection::ToWidget,
+                        color,
+                    ));
+                } else {
+                    // Revert invalid input.
+                    ui.send_message(mark_handled(TextMessage::text(
+                        self.hex,
+                        MessageDirection::ToWidget,
+                        color_to_hex(self.color),
+                    )));
+                }
+            }
+        } else if let Some(&HueBarMessage::Hue(hue)) = message.data::<HueBarMessage>() {
             if message.destination() == self.hue_bar
                 && message.direction() == MessageDirection::FromWidget
             {
@@ -948,6 +1156,14 @@ impl Control for ColorPicker {
 
                             self.sync_fields(ui, self.color, hsv);
 
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    ColorPickerMessage::Palette(ref palette) => {
+                        if self.palette != *palette {
+                            self.palette = palette.clone();
+                            self.rebuild_swatches(ui);
+
                             ui.send_message(message.reverse());
                         }
                     }
@@ -960,6 +1176,7 @@ impl Control for ColorPicker {
 pub struct ColorPickerBuilder {
     widget_builder: WidgetBuilder,
     color: Color,
+    palette: ColorPalette,
 }
 
 fn make_text_mark(ctx: &mut BuildContext, text: &str, row: usize, column: usize) -> Handle<UiNode> {
@@ -999,6 +1216,7 @@ impl ColorPickerBuilder {
         Self {
             widget_builder,
             color: Color::WHITE,
+            palette: Default::default(),
         }
     }
 
@@ -1007,6 +1225,11 @@ impl ColorPickerBuilder {
         self
     }
 
+    pub fn with_palette(mut self, palette: ColorPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
         let hue_bar;
         let alpha_bar;
@@ -1019,11 +1242,15 @@ impl ColorPickerBuilder {
         let brightness;
         let color_mark;
         let alpha;
+        let hex;
+        let eyedropper;
+        let palette_panel;
+        let add_swatch;
         let hsv = Hsv::from(self.color);
 
         let numerics_grid = GridBuilder::new(
             WidgetBuilder::new()
-                .on_row(1)
+                .on_row(2)
                 .with_child(make_text_mark(ctx, "R", 0, 0))
                 .with_child({
                     red = make_input_field(ctx, self.color.r as f32, 255.0, 0, 1);
@@ -1071,65 +1298,159 @@ impl ColorPickerBuilder {
         .add_row(Row::stretch())
         .build(ctx);
 
+        let main_grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child({
+                    saturation_brightness_field = SaturationBrightnessFieldBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .on_column(0),
+                    )
+                    .build(ctx);
+                    saturation_brightness_field
+                })
+                .with_child({
+                    hue_bar = HueBarBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .on_column(1),
+                    )
+                    .build(ctx);
+                    hue_bar
+                })
+                .with_child({
+                    alpha_bar = AlphaBarBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .on_column(2),
+                    )
+                    .with_alpha(self.color.a as f32)
+                    .build(ctx);
+                    alpha_bar
+                })
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .on_column(3)
+                            .with_child(
+                                GridBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(0)
+                                        .with_child({
+                                            color_mark = BorderBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_column(0)
+                                                    .with_margin(Thickness::uniform(1.0)),
+                                            )
+                                            .build(ctx);
+                                            color_mark
+                                        })
+                                        .with_child({
+                                            eyedropper = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_column(1)
+                                                    .with_margin(Thickness::uniform(1.0)),
+                                            )
+                                            .with_text("P")
+                                            .build(ctx);
+                                            eyedropper
+                                        }),
+                                )
+                                .add_column(Column::stretch())
+                                .add_column(Column::strict(25.0))
+                                .add_row(Row::stretch())
+                                .build(ctx),
+                            )
+                            .with_child(
+                                GridBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(1)
+                                        .with_child(make_text_mark(ctx, "#", 0, 0))
+                                        .with_child({
+                                            hex = TextBoxBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_column(1)
+                                                    .with_margin(Thickness::uniform(1.0)),
+                                            )
+                                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                                            .with_text_commit_mode(
+                                                TextCommitMode::LostFocusPlusEnter,
+                                            )
+                                            .with_text(color_to_hex(self.color))
+                                            .build(ctx);
+                                            hex
+                                        }),
+                                )
+                                .add_column(Column::strict(10.0))
+                                .add_column(Column::stretch())
+                                .add_row(Row::stretch())
+                                .build(ctx),
+                            )
+                            .with_child(numerics_grid),
+                    )
+                    .add_row(Row::strict(25.0))
+                    .add_row(Row::strict(25.0))
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .add_column(Column::stretch())
+        .add_column(Column::strict(20.0))
+        .add_column(Column::strict(20.0))
+        .add_column(Column::strict(100.0))
+        .add_row(Row::auto())
+        .build(ctx);
+
+        let swatches = self
+            .palette
+            .colors
+            .iter()
+            .map(|color| make_swatch(ctx, *color))
+            .collect::<Vec<_>>();
+
+        let palette_grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_child({
+                    palette_panel = WrapPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .on_column(0)
+                            .with_children(swatches.iter().cloned()),
+                    )
+                    .with_orientation(Orientation::Horizontal)
+                    .build(ctx);
+                    palette_panel
+                })
+                .with_child({
+                    add_swatch = ButtonBuilder::new(
+                        WidgetBuilder::new()
+                            .on_column(1)
+                            .with_height(20.0)
+                            .with_vertical_alignment(VerticalAlignment::Top)
+                            .with_margin(Thickness::uniform(1.0)),
+                    )
+                    .with_text("+")
+                    .build(ctx);
+                    add_swatch
+                }),
+        )
+        .add_column(Column::stretch())
+        .add_column(Column::strict(22.0))
+        .add_row(Row::auto())
+        .build(ctx);
+
         let widget = self
             .widget_builder
             .with_child(
                 GridBuilder::new(
                     WidgetBuilder::new()
-                        .with_child({
-                            saturation_brightness_field = SaturationBrightnessFieldBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(1.0))
-                                    .on_column(0),
-                            )
-                            .build(ctx);
-                            saturation_brightness_field
-                        })
-                        .with_child({
-                            hue_bar = HueBarBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(1.0))
-                                    .on_column(1),
-                            )
-                            .build(ctx);
-                            hue_bar
-                        })
-                        .with_child({
-                            alpha_bar = AlphaBarBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(1.0))
-                                    .on_column(2),
-                            )
-                            .with_alpha(self.color.a as f32)
-                            .build(ctx);
-                            alpha_bar
-                        })
-                        .with_child(
-                            GridBuilder::new(
-                                WidgetBuilder::new()
-                                    .on_column(3)
-                                    .with_child({
-                                        color_mark = BorderBuilder::new(
-                                            WidgetBuilder::new()
-                                                .on_row(0)
-                                                .with_margin(Thickness::uniform(1.0)),
-                                        )
-                                        .build(ctx);
-                                        color_mark
-                                    })
-                                    .with_child(numerics_grid),
-                            )
-                            .add_row(Row::strict(25.0))
-                            .add_row(Row::stretch())
-                            .add_column(Column::stretch())
-                            .build(ctx),
-                        ),
+                        .with_child(main_grid)
+                        .with_child(palette_grid),
                 )
-                .add_column(Column::stretch())
-                .add_column(Column::strict(20.0))
-                .add_column(Column::strict(20.0))
-                .add_column(Column::strict(100.0))
+                .add_row(Row::stretch())
                 .add_row(Row::auto())
+                .add_column(Column::stretch())
                 .build(ctx),
             )
             .build();
@@ -1144,8 +1465,15 @@ impl ColorPickerBuilder {
             hue,
             saturation,
             brightness,
+            hex,
             color: self.color,
             color_mark,
+            eyedropper,
+            palette_panel,
+            add_swatch,
+            swatches,
+            palette: self.palette,
+            picking: false,
             hsv,
             alpha_bar,
             alpha,
@@ -1259,6 +1587,7 @@ impl Control for ColorField {
 pub struct ColorFieldBuilder {
     widget_builder: WidgetBuilder,
     color: Color,
+    palette: ColorPalette,
 }
 
 impl ColorFieldBuilder {
@@ -1266,6 +1595,7 @@ impl ColorFieldBuilder {
         Self {
             widget_builder,
             color: Color::WHITE,
+            palette: Default::default(),
         }
     }
 
@@ -1274,12 +1604,19 @@ impl ColorFieldBuilder {
         self
     }
 
+    /// Sets palette of the color picker of the field.
+    pub fn with_palette(mut self, palette: ColorPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
         let picker;
         let popup = PopupBuilder::new(WidgetBuilder::new())
             .with_content({
                 picker = ColorPickerBuilder::new(WidgetBuilder::new())
                     .with_color(self.color)
+                    .with_palette(self.palette)
                     .build(ctx);
                 picker
             })
@@ -1294,3 +1631,352 @@ impl ColorFieldBuilder {
         ctx.add_node(UiNode::new(field))
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorGradientEditorMessage {
+    /// Sets new gradient. The editor sends this message back when the user edits the gradient.
+    ///
+    /// Direction: **To/From Widget**.
+    Value(ColorGradient),
+}
+
+impl ColorGradientEditorMessage {
+    define_constructor!(ColorGradientEditorMessage:Value => fn value(ColorGradient), layout: false);
+}
+
+/// Size of a marker of a gradient point.
+const MARKER_SIZE: f32 = 8.0;
+
+/// Height of the color field of the selected point.
+const GRADIENT_FIELD_HEIGHT: f32 = 20.0;
+
+/// An editor of multi-stop color gradients. Gradient points are shown as markers below the
+/// gradient bar:
+///
+/// - click on the bar adds a new point,
+/// - left click on a marker selects the point, the marker then can be dragged to change location
+/// of the point, color of the selected point can be changed using the color field below,
+/// - right click on a marker removes the point (the last point cannot be removed).
+///
+/// Resulting gradient can be used for particle systems or converted to a brush using
+/// [`Brush::from_color_gradient`].
+#[derive(Clone)]
+pub struct ColorGradientEditor {
+    pub widget: Widget,
+    pub gradient: ColorGradient,
+    pub selected: Option<usize>,
+    pub dragging: bool,
+    pub color_field: Handle<UiNode>,
+}
+
+crate::define_widget_deref!(ColorGradientEditor);
+
+impl ColorGradientEditor {
+    fn bar_bounds(&self) -> Rect<f32> {
+        let size = self.actual_local_size();
+        Rect::new(
+            0.0,
+            0.0,
+            size.x,
+            (size.y - GRADIENT_FIELD_HEIGHT - MARKER_SIZE).max(0.0),
+        )
+    }
+
+    fn marker_bounds(&self, location: f32) -> Rect<f32> {
+        let bar = self.bar_bounds();
+        Rect::new(
+            bar.x() + bar.w() * location - MARKER_SIZE * 0.5,
+            bar.y() + bar.h(),
+            MARKER_SIZE,
+            MARKER_SIZE,
+        )
+    }
+
+    fn location_at(&self, mouse_pos: Vector2<f32>) -> f32 {
+        let relative_pos = mouse_pos - self.screen_position();
+        (relative_pos.x / self.bar_bounds().w().max(1.0)).clamp(0.0, 1.0)
+    }
+
+    fn marker_at(&self, mouse_pos: Vector2<f32>) -> Option<usize> {
+        let relative_pos = mouse_pos - self.screen_position();
+        self.gradient
+            .points()
+            .iter()
+            .position(|point| self.marker_bounds(point.location()).contains(relative_pos))
+    }
+
+    /// Replaces a point at the given index and returns new index of the point, points are
+    /// always kept sorted by their locations.
+    fn set_point(&mut self, index: usize, point: GradientPoint) -> usize {
+        self.gradient.remove_point(index);
+        self.gradient.add_point(point.clone());
+        self.gradient
+            .points()
+            .iter()
+            .position(|p| *p == point)
+            .unwrap_or_default()
+    }
+
+    fn select(&mut self, ui: &UserInterface, index: Option<usize>) {
+        self.selected = index;
+        if let Some(point) = index.and_then(|i| self.gradient.points().get(i)) {
+            ui.send_message(mark_handled(ColorFieldMessage::color(
+                self.color_field,
+                MessageDirection::ToWidget,
+                point.color(),
+            )));
+        }
+    }
+
+    fn notify_changed(&self, ui: &UserInterface) {
+        ui.send_message(ColorGradientEditorMessage::value(
+            self.handle,
+            MessageDirection::FromWidget,
+            self.gradient.clone(),
+        ));
+    }
+}
+
+impl Control for ColorGradientEditor {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve(&mut self.color_field);
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bar = self.bar_bounds();
+
+        let mut stops = vec![(0.0, self.gradient.get_color(0.0))];
+        stops.extend(
+            self.gradient
+                .points()
+                .iter()
+                .map(|point| (point.location(), point.color())),
+        );
+        stops.push((1.0, self.gradient.get_color(1.0)));
+
+        for pair in stops.windows(2) {
+            let (prev_k, prev_color) = pair[0];
+            let (curr_k, curr_color) = pair[1];
+            push_gradient_rect(
+                drawing_context,
+                &bar,
+                Orientation::Horizontal,
+                prev_k,
+                prev_color,
+                curr_k,
+                curr_color,
+            );
+        }
+        for point in self.gradient.points() {
+            drawing_context
+                .push_rect_multicolor(&self.marker_bounds(point.location()), [point.color(); 4]);
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::WHITE),
+            CommandTexture::None,
+            None,
+        );
+
+        for (i, point) in self.gradient.points().iter().enumerate() {
+            if self.selected != Some(i) {
+                drawing_context.push_rect(&self.marker_bounds(point.location()), 1.0);
+            }
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::BLACK),
+            CommandTexture::None,
+            None,
+        );
+
+        if let Some(point) = self.selected.and_then(|i| self.gradient.points().get(i)) {
+            drawing_context.push_rect(&self.marker_bounds(point.location()), 1.0);
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(Color::WHITE),
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<WidgetMessage>() {
+            match *msg {
+                WidgetMessage::MouseDown { pos, button } => {
+                    let relative_pos = pos - self.screen_position();
+                    let mut area = self.bar_bounds();
+                    area.size.y += MARKER_SIZE;
+                    if message.handled() || !area.contains(relative_pos) {
+                        return;
+                    }
+
+                    if let Some(index) = self.marker_at(pos) {
+                        match button {
+                            MouseButton::Left => {
+                                self.select(ui, Some(index));
+                                self.dragging = true;
+                                ui.capture_mouse(self.handle);
+                            }
+                            MouseButton::Right if self.gradient.points().len() > 1 => {
+                                self.gradient.remove_point(index);
+                                self.selected = None;
+                                self.notify_changed(ui);
+                            }
+                            _ => (),
+                        }
+                    } else if button == MouseButton::Left {
+                        let location = self.location_at(pos);
+                        let point = GradientPoint::new(location, self.gradient.get_color(location));
+                        self.gradient.add_point(point.clone());
+                        let index = self.gradient.points().iter().position(|p| *p == point);
+                        self.select(ui, index);
+                        self.notify_changed(ui);
+                    }
+
+                    message.set_handled(true);
+                }
+                WidgetMessage::MouseMove { pos, .. } => {
+                    if self.dragging {
+                        if let Some(index) = self.selected {
+                            let color = self.gradient.points()[index].color();
+                            let location = self.location_at(pos);
+                            self.selected =
+                                Some(self.set_point(index, GradientPoint::new(location, color)));
+                        }
+                    }
+                }
+                WidgetMessage::MouseUp { .. } => {
+                    if self.dragging {
+                        self.dragging = false;
+                        ui.release_mouse_capture();
+                        self.notify_changed(ui);
+                        message.set_handled(true);
+                    }
+                }
+                _ => (),
+            }
+        } else if let Some(&ColorFieldMessage::Color(color)) = message.data::<ColorFieldMessage>() {
+            if message.destination() == self.color_field
+                && message.direction() == MessageDirection::FromWidget
+                && !message.handled()
+            {
+                if let Some(index) = self.selected {
+                    let location = self.gradient.points()[index].location();
+                    self.selected =
+                        Some(self.set_point(index, GradientPoint::new(location, color)));
+                    self.notify_changed(ui);
+                }
+            }
+        } else if let Some(ColorGradientEditorMessage::Value(gradient)) =
+            message.data::<ColorGradientEditorMessage>()
+        {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+                && &self.gradient != gradient
+            {
+                self.gradient = gradient.clone();
+                self.selected = None;
+                self.dragging = false;
+                ui.send_message(message.reverse());
+            }
+        }
+    }
+}
+
+pub struct ColorGradientEditorBuilder {
+    widget_builder: WidgetBuilder,
+    gradient: ColorGradient,
+}
+
+impl ColorGradientEditorBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            gradient: Default::default(),
+        }
+    }
+
+    pub fn with_gradient(mut self, gradient: ColorGradient) -> Self {
+        self.gradient = gradient;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let color_field;
+        let grid = GridBuilder::new(WidgetBuilder::new().with_child({
+            color_field = ColorFieldBuilder::new(
+                WidgetBuilder::new()
+                    .on_row(1)
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_color(
+                self.gradient
+                    .points()
+                    .first()
+                    .map(|point| point.color())
+                    .unwrap_or(Color::WHITE),
+            )
+            .build(ctx);
+            color_field
+        }))
+        .add_row(Row::stretch())
+        .add_row(Row::strict(GRADIENT_FIELD_HEIGHT))
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let editor = ColorGradientEditor {
+            widget: self
+                .widget_builder
+                .with_min_size(Vector2::new(
+                    0.0,
+                    GRADIENT_FIELD_HEIGHT + MARKER_SIZE + 10.0,
+                ))
+                .with_child(grid)
+                .build(),
+            selected: if self.gradient.points().is_empty() {
+                None
+            } else {
+                Some(0)
+            },
+            gradient: self.gradient,
+            dragging: false,
+            color_field,
+        };
+        ctx.add_node(UiNode::new(editor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        color::{color_from_hex, color_to_hex},
+        core::color::Color,
+    };
+
+    #[test]
+    fn test_hex_color() {
+        for color in [
+            Color::opaque(255, 128, 0),
+            Color::from_rgba(1, 2, 3, 4),
+            Color::TRANSPARENT,
+        ] {
+            assert_eq!(color_from_hex(&color_to_hex(color)), Some(color));
+        }
+        assert_eq!(color_to_hex(Color::opaque(255, 0, 16)), "#FF0010");
+        assert_eq!(color_from_hex("00ff00"), Some(Color::opaque(0, 255, 0)));
+        assert_eq!(color_from_hex("#12345"), None);
+        assert_eq!(color_from_hex("#GG0000"), None);
+    }
+}
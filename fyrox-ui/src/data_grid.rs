@@ -0,0 +1,1130 @@
+//! Data grid is a spreadsheet-like widget that shows a table of string values. It supports column
+//! headers with click-to-sort, column resizing (drag the right edge of a header), row or cell
+//! selection, in-place editing of cells (double click on a cell of an editable column) and custom
+//! cell templates.
+//!
+//! Only visible rows have widgets (row virtualization), so the grid can show thousands of rows
+//! without any noticeable overhead. Row indices in messages always refer to the index of a row in
+//! the data that was passed to the grid, not to its position on screen, so they're stable when
+//! the grid is sorted.
+
+use crate::{
+    brush::Brush,
+    core::{
+        algebra::{Point2, Vector2},
+        color::Color,
+        math::Rect,
+        pool::Handle,
+    },
+    define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext},
+    message::{CursorIcon, KeyCode, MessageDirection, MouseButton, UiMessage},
+    scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+    text::{TextBuilder, TextMessage},
+    text_box::{TextBoxBuilder, TextCommitMode},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, NodeHandleMapping, Orientation, Thickness, UiNode, UserInterface,
+    VerticalAlignment, BRUSH_DARK, BRUSH_DARKER, BRUSH_LIGHT, BRUSH_PRIMARY, BRUSH_TEXT,
+};
+use std::{
+    any::{Any, TypeId},
+    cmp::Ordering,
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::mpsc::Sender,
+};
+
+/// Width of the vertical scroll bar.
+const SCROLL_BAR_WIDTH: f32 = 16.0;
+/// Distance (in pixels) from the right edge of a header at which the column can be resized.
+const RESIZE_GRIP: f32 = 4.0;
+/// Amount of rows scrolled per mouse wheel step.
+const WHEEL_ROWS: f32 = 3.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SelectionMode {
+    /// Whole row is selected.
+    Row,
+    /// Single cell is selected.
+    Cell,
+}
+
+impl Default for SelectionMode {
+    fn default() -> Self {
+        Self::Row
+    }
+}
+
+/// Selected row and column. In [`SelectionMode::Row`] column is the column that was clicked, it
+/// does not affect highlighting.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DataGridSelection {
+    pub row: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataGridMessage {
+    /// Replaces every row of the grid. Every row should have the same amount of cells as there
+    /// are columns, missing cells are shown empty.
+    Rows(Vec<Vec<String>>),
+    /// Sorts the grid by given column (when sent to widget) or notifies that sorting was changed
+    /// by clicking on a header (when sent from widget). `None` restores original order of rows.
+    Sort(Option<(usize, SortDirection)>),
+    /// Sets new width of a column (when sent to widget) or notifies that a column was resized by
+    /// user (when sent from widget).
+    ColumnWidth { column: usize, width: f32 },
+    /// Sets new selection (when sent to widget) or notifies that selection has changed (when sent
+    /// from widget).
+    Selection(Option<DataGridSelection>),
+    /// Sets new value of a cell (when sent to widget) or notifies that a cell was edited by user
+    /// (when sent from widget).
+    CellEdited {
+        row: usize,
+        column: usize,
+        value: String,
+    },
+    /// Scrolls the grid so the given row becomes visible.
+    BringRowIntoView(usize),
+    /// Re-creates widgets of visible cells. The grid sends it to itself when its size changes,
+    /// there is no need to send it manually unless cell templates depend on some external state.
+    Refresh,
+}
+
+impl DataGridMessage {
+    define_constructor!(DataGridMessage:Rows => fn rows(Vec<Vec<String>>), layout: false);
+    define_constructor!(DataGridMessage:Sort => fn sort(Option<(usize, SortDirection)>), layout: false);
+    define_constructor!(DataGridMessage:ColumnWidth => fn column_width(column: usize, width: f32), layout: false);
+    define_constructor!(DataGridMessage:Selection => fn selection(Option<DataGridSelection>), layout: false);
+    define_constructor!(DataGridMessage:CellEdited => fn cell_edited(row: usize, column: usize, value: String), layout: false);
+    define_constructor!(DataGridMessage:BringRowIntoView => fn bring_row_into_view(usize), layout: false);
+    define_constructor!(DataGridMessage:Refresh => fn refresh(), layout: false);
+}
+
+/// Creates a widget for a cell with given value.
+pub type CellTemplate = dyn Fn(&mut BuildContext, &str) -> Handle<UiNode>;
+
+#[derive(Clone)]
+pub struct DataGridColumn {
+    pub header: String,
+    pub width: f32,
+    pub min_width: f32,
+    /// Whether the grid can be sorted by this column by clicking on its header.
+    pub sortable: bool,
+    /// Whether cells of this column can be edited by user.
+    pub editable: bool,
+    /// Custom template for cells of this column. If not set, the value is shown as text.
+    pub template: Option<Rc<CellTemplate>>,
+}
+
+impl Debug for DataGridColumn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataGridColumn")
+            .field("header", &self.header)
+            .field("width", &self.width)
+            .field("min_width", &self.min_width)
+            .field("sortable", &self.sortable)
+            .field("editable", &self.editable)
+            .finish()
+    }
+}
+
+impl DataGridColumn {
+    pub fn new<S: AsRef<str>>(header: S, width: f32) -> Self {
+        Self {
+            header: header.as_ref().to_owned(),
+            width,
+            min_width: 20.0,
+            sortable: true,
+            editable: false,
+            template: None,
+        }
+    }
+
+    pub fn with_min_width(mut self, min_width: f32) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    pub fn with_sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
+    pub fn with_editable(mut self, editable: bool) -> Self {
+        self.editable = editable;
+        self
+    }
+
+    pub fn with_template<F>(mut self, template: F) -> Self
+    where
+        F: Fn(&mut BuildContext, &str) -> Handle<UiNode> + 'static,
+    {
+        self.template = Some(Rc::new(template));
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RealizedRow {
+    row: usize,
+    cells: Vec<Handle<UiNode>>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CellEditor {
+    text_box: Handle<UiNode>,
+    row: usize,
+    column: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ColumnResize {
+    column: usize,
+    start_x: f32,
+    start_width: f32,
+}
+
+/// Compares two cells. Numbers are compared by their values, everything else - as strings.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+/// Returns indices of rows in the order defined by the sort. Sorting is stable.
+fn sorted_order(rows: &[Vec<String>], sort: Option<(usize, SortDirection)>) -> Vec<usize> {
+    let mut order = (0..rows.len()).collect::<Vec<_>>();
+    if let Some((column, direction)) = sort {
+        let cell = |row: usize| rows[row].get(column).map(|c| c.as_str()).unwrap_or("");
+        order.sort_by(|&a, &b| {
+            let ordering = compare_cells(cell(a), cell(b));
+            match direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+    }
+    order
+}
+
+#[derive(Clone)]
+pub struct DataGrid {
+    pub widget: Widget,
+    pub columns: Vec<DataGridColumn>,
+    pub rows: Vec<Vec<String>>,
+    pub sort: Option<(usize, SortDirection)>,
+    pub selection_mode: SelectionMode,
+    pub selection: Option<DataGridSelection>,
+    pub header_height: f32,
+    pub row_height: f32,
+    pub headers: Vec<Handle<UiNode>>,
+    pub scroll_bar: Handle<UiNode>,
+    // Indices of rows in the order they're shown.
+    order: Vec<usize>,
+    first_visible: usize,
+    visible_capacity: usize,
+    realized: Vec<RealizedRow>,
+    editor: Option<CellEditor>,
+    resize: Option<ColumnResize>,
+    resize_hover: bool,
+}
+
+impl Debug for DataGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DataGrid")
+    }
+}
+
+crate::define_widget_deref!(DataGrid);
+
+impl DataGrid {
+    /// Returns indices of rows in the order they're shown on screen.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Returns index of the first visible row in the order of rows on screen.
+    pub fn first_visible(&self) -> usize {
+        self.first_visible
+    }
+
+    fn content_width(&self, total_width: f32) -> f32 {
+        (total_width - SCROLL_BAR_WIDTH).max(0.0)
+    }
+
+    fn column_x(&self, column: usize) -> f32 {
+        self.columns.iter().take(column).map(|c| c.width).sum()
+    }
+
+    fn max_first_visible(&self) -> usize {
+        self.order
+            .len()
+            .saturating_sub(self.visible_capacity.max(1))
+    }
+
+    fn screen_to_local(&self, position: Vector2<f32>) -> Vector2<f32> {
+        self.visual_transform
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&Point2::from(position))
+            .coords
+    }
+
+    fn column_at(&self, x: f32) -> Option<usize> {
+        let mut column_x = 0.0;
+        for (i, column) in self.columns.iter().enumerate() {
+            if x >= column_x && x < column_x + column.width {
+                return Some(i);
+            }
+            column_x += column.width;
+        }
+        None
+    }
+
+    /// Returns a column which right edge is close to the given point of the header.
+    fn resize_grip_at(&self, position: Vector2<f32>) -> Option<usize> {
+        if position.y < 0.0 || position.y > self.header_height {
+            return None;
+        }
+        let mut column_x = 0.0;
+        for (i, column) in self.columns.iter().enumerate() {
+            column_x += column.width;
+            if (position.x - column_x).abs() <= RESIZE_GRIP {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Returns selection for a point in local coordinates if it is inside the body of the grid.
+    fn cell_at(&self, position: Vector2<f32>) -> Option<DataGridSelection> {
+        if position.y < self.header_height
+            || position.x > self.content_width(self.actual_local_size().x)
+        {
+            return None;
+        }
+        let slot = ((position.y - self.header_height) / self.row_height) as usize;
+        let row = *self.order.get(self.first_visible + slot)?;
+        let column = self.column_at(position.x)?;
+        Some(DataGridSelection { row, column })
+    }
+
+    fn cell_rect(&self, slot: usize, column: usize) -> Rect<f32> {
+        Rect::new(
+            self.column_x(column),
+            self.header_height + slot as f32 * self.row_height,
+            self.columns[column].width,
+            self.row_height,
+        )
+    }
+
+    fn slot_of(&self, row: usize) -> Option<usize> {
+        self.realized.iter().position(|r| r.row == row)
+    }
+
+    fn build_cell(&self, ctx: &mut BuildContext, row: usize, column: usize) -> Handle<UiNode> {
+        let value = self.rows[row]
+            .get(column)
+            .map(|c| c.as_str())
+            .unwrap_or_default();
+        if let Some(template) = self.columns[column].template.as_ref() {
+            template(ctx, value)
+        } else {
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(4.0)))
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text(value)
+                .build(ctx)
+        }
+    }
+
+    /// Creates widgets for visible rows. Rows that are still visible keep their widgets.
+    fn realize(&mut self, ui: &mut UserInterface) {
+        let count = self
+            .visible_capacity
+            .min(self.order.len().saturating_sub(self.first_visible));
+
+        let mut old = std::mem::take(&mut self.realized);
+        let mut realized = Vec::with_capacity(count);
+        for &row in &self.order[self.first_visible..self.first_visible + count] {
+            if let Some(position) = old.iter().position(|r| r.row == row) {
+                realized.push(old.remove(position));
+            } else {
+                let cells = (0..self.columns.len())
+                    .map(|column| self.build_cell(&mut ui.build_ctx(), row, column))
+                    .collect::<Vec<_>>();
+                for &cell in cells.iter() {
+                    ui.send_message(WidgetMessage::link(
+                        cell,
+                        MessageDirection::ToWidget,
+                        self.handle,
+                    ));
+                }
+                realized.push(RealizedRow { row, cells });
+            }
+        }
+
+        for row in old {
+            for cell in row.cells {
+                ui.send_message(WidgetMessage::remove(cell, MessageDirection::ToWidget));
+            }
+        }
+
+        self.realized = realized;
+        self.invalidate_layout();
+    }
+
+    fn clear_realized(&mut self, ui: &UserInterface) {
+        for row in self.realized.drain(..) {
+            for cell in row.cells {
+                ui.send_message(WidgetMessage::remove(cell, MessageDirection::ToWidget));
+            }
+        }
+    }
+
+    fn sync_scroll_bar(&self, ui: &UserInterface) {
+        ui.send_message(ScrollBarMessage::max_value(
+            self.scroll_bar,
+            MessageDirection::ToWidget,
+            self.max_first_visible() as f32,
+        ));
+        ui.send_message(ScrollBarMessage::value(
+            self.scroll_bar,
+            MessageDirection::ToWidget,
+            self.first_visible as f32,
+        ));
+    }
+
+    fn set_first_visible(&mut self, first_visible: usize, ui: &mut UserInterface) {
+        let first_visible = first_visible.min(self.max_first_visible());
+        if first_visible != self.first_visible {
+            self.close_editor(ui);
+            self.first_visible = first_visible;
+            self.realize(ui);
+            self.sync_scroll_bar(ui);
+        }
+    }
+
+    fn bring_row_into_view(&mut self, row: usize, ui: &mut UserInterface) {
+        if let Some(position) = self.order.iter().position(|&r| r == row) {
+            let capacity = self.visible_capacity.max(1);
+            if position < self.first_visible {
+                self.set_first_visible(position, ui);
+            } else if position >= self.first_visible + capacity {
+                self.set_first_visible(position + 1 - capacity, ui);
+            }
+        }
+    }
+
+    fn apply_sort(&mut self, sort: Option<(usize, SortDirection)>, ui: &mut UserInterface) {
+        self.sort = sort.filter(|(column, _)| *column < self.columns.len());
+        self.order = sorted_order(&self.rows, self.sort);
+        self.close_editor(ui);
+        self.realize(ui);
+    }
+
+    fn set_selection(&mut self, selection: Option<DataGridSelection>, ui: &mut UserInterface) {
+        if self.selection != selection {
+            self.selection = selection;
+            if let Some(selection) = selection {
+                self.bring_row_into_view(selection.row, ui);
+            }
+            ui.send_message(DataGridMessage::selection(
+                self.handle,
+                MessageDirection::FromWidget,
+                selection,
+            ));
+        }
+    }
+
+    /// Moves selection by given amount of rows (in the order on screen) and columns.
+    fn move_selection(&mut self, rows: isize, columns: isize, ui: &mut UserInterface) {
+        if self.order.is_empty() || self.columns.is_empty() {
+            return;
+        }
+        let (position, column) = match self.selection {
+            Some(selection) => (
+                self.order
+                    .iter()
+                    .position(|&r| r == selection.row)
+                    .unwrap_or_default(),
+                selection.column,
+            ),
+            None => (0, 0),
+        };
+        let position = (position as isize + rows).clamp(0, self.order.len() as isize - 1) as usize;
+        let column = (column as isize + columns).clamp(0, self.columns.len() as isize - 1) as usize;
+        self.set_selection(
+            Some(DataGridSelection {
+                row: self.order[position],
+                column,
+            }),
+            ui,
+        );
+    }
+
+    fn open_editor(&mut self, row: usize, column: usize, ui: &mut UserInterface) {
+        self.close_editor(ui);
+
+        let value = self.rows[row].get(column).cloned().unwrap_or_default();
+        let text_box = TextBoxBuilder::new(WidgetBuilder::new())
+            .with_text_commit_mode(TextCommitMode::LostFocusPlusEnter)
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            .with_text(value)
+            .build(&mut ui.build_ctx());
+        ui.send_message(WidgetMessage::link(
+            text_box,
+            MessageDirection::ToWidget,
+            self.handle,
+        ));
+        ui.send_message(WidgetMessage::focus(text_box, MessageDirection::ToWidget));
+
+        self.editor = Some(CellEditor {
+            text_box,
+            row,
+            column,
+        });
+        self.invalidate_layout();
+    }
+
+    fn close_editor(&mut self, ui: &UserInterface) {
+        if let Some(editor) = self.editor.take() {
+            ui.send_message(WidgetMessage::remove(
+                editor.text_box,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    fn set_cell(
+        &mut self,
+        row: usize,
+        column: usize,
+        value: String,
+        ui: &mut UserInterface,
+    ) -> bool {
+        if column >= self.columns.len() {
+            return false;
+        }
+        if let Some(cells) = self.rows.get_mut(row) {
+            if cells.len() <= column {
+                cells.resize(column + 1, String::new());
+            }
+            if cells[column] != value {
+                cells[column] = value;
+
+                // Re-create widgets of the row with new value.
+                if let Some(slot) = self.slot_of(row) {
+                    let row = self.realized.remove(slot);
+                    for cell in row.cells {
+                        ui.send_message(WidgetMessage::remove(cell, MessageDirection::ToWidget));
+                    }
+                    self.realize(ui);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn handle_mouse_down(&mut self, pos: Vector2<f32>, ui: &mut UserInterface) -> bool {
+        let local = self.screen_to_local(pos);
+        if let Some(column) = self.resize_grip_at(local) {
+            self.resize = Some(ColumnResize {
+                column,
+                start_x: local.x,
+                start_width: self.columns[column].width,
+            });
+            ui.capture_mouse(self.handle);
+            true
+        } else if local.y < self.header_height {
+            if let Some(column) = self.column_at(local.x) {
+                if self.columns[column].sortable {
+                    let sort = match self.sort {
+                        Some((sorted, SortDirection::Ascending)) if sorted == column => {
+                            Some((column, SortDirection::Descending))
+                        }
+                        _ => Some((column, SortDirection::Ascending)),
+                    };
+                    self.apply_sort(sort, ui);
+                    ui.send_message(DataGridMessage::sort(
+                        self.handle,
+                        MessageDirection::FromWidget,
+                        self.sort,
+                    ));
+                }
+            }
+            true
+        } else if let Some(selection) = self.cell_at(local) {
+            self.set_selection(Some(selection), ui);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Control for DataGrid {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve(&mut self.scroll_bar);
+        node_map.resolve_slice(&mut self.headers);
+        for row in self.realized.iter_mut() {
+            node_map.resolve_slice(&mut row.cells);
+        }
+        if let Some(editor) = self.editor.as_mut() {
+            node_map.resolve(&mut editor.text_box);
+        }
+    }
+
+    fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
+        for (&header, column) in self.headers.iter().zip(&self.columns) {
+            ui.measure_node(header, Vector2::new(column.width, self.header_height));
+        }
+        for row in self.realized.iter() {
+            for (&cell, column) in row.cells.iter().zip(&self.columns) {
+                ui.measure_node(cell, Vector2::new(column.width, self.row_height));
+            }
+        }
+        if let Some(editor) = self.editor.as_ref() {
+            ui.measure_node(
+                editor.text_box,
+                Vector2::new(self.columns[editor.column].width, self.row_height),
+            );
+        }
+        ui.measure_node(
+            self.scroll_bar,
+            Vector2::new(SCROLL_BAR_WIDTH, available_size.y),
+        );
+
+        let width = self.columns.iter().map(|c| c.width).sum::<f32>() + SCROLL_BAR_WIDTH;
+        let height = self.header_height + self.rows.len() as f32 * self.row_height;
+        Vector2::new(width.min(available_size.x), height.min(available_size.y))
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        let mut x = 0.0;
+        for (&header, column) in self.headers.iter().zip(&self.columns) {
+            ui.arrange_node(header, &Rect::new(x, 0.0, column.width, self.header_height));
+            x += column.width;
+        }
+
+        for (slot, row) in self.realized.iter().enumerate() {
+            for (column, &cell) in row.cells.iter().enumerate() {
+                ui.arrange_node(cell, &self.cell_rect(slot, column));
+            }
+        }
+
+        if let Some(editor) = self.editor.as_ref() {
+            let rect = self
+                .slot_of(editor.row)
+                .map(|slot| self.cell_rect(slot, editor.column))
+                .unwrap_or_default();
+            ui.arrange_node(editor.text_box, &rect);
+        }
+
+        ui.arrange_node(
+            self.scroll_bar,
+            &Rect::new(
+                self.content_width(final_size.x),
+                self.header_height,
+                SCROLL_BAR_WIDTH,
+                (final_size.y - self.header_height).max(0.0),
+            ),
+        );
+
+        final_size
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.widget.bounding_rect();
+        let content_width = self.content_width(bounds.w());
+
+        // Background also makes the whole grid pickable.
+        drawing_context.push_rect_filled(&bounds, None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::None,
+            None,
+        );
+
+        drawing_context.push_rect_filled(
+            &Rect::new(0.0, 0.0, content_width, self.header_height),
+            None,
+        );
+        drawing_context.commit(self.clip_bounds(), BRUSH_DARK, CommandTexture::None, None);
+
+        // Stripes on odd rows make wide tables easier to read.
+        for slot in 0..self.realized.len() {
+            if (self.first_visible + slot) % 2 == 1 {
+                drawing_context.push_rect_filled(
+                    &Rect::new(
+                        0.0,
+                        self.header_height + slot as f32 * self.row_height,
+                        content_width,
+                        self.row_height,
+                    ),
+                    None,
+                );
+            }
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::from_rgba(255, 255, 255, 8)),
+            CommandTexture::None,
+            None,
+        );
+
+        if let Some(selection) = self.selection {
+            if let Some(slot) = self.slot_of(selection.row) {
+                let rect = match self.selection_mode {
+                    SelectionMode::Row => Rect::new(
+                        0.0,
+                        self.header_height + slot as f32 * self.row_height,
+                        content_width,
+                        self.row_height,
+                    ),
+                    SelectionMode::Cell => self.cell_rect(slot, selection.column),
+                };
+                drawing_context.push_rect_filled(&rect, None);
+                drawing_context.commit(
+                    self.clip_bounds(),
+                    BRUSH_PRIMARY,
+                    CommandTexture::None,
+                    None,
+                );
+            }
+        }
+
+        // Column separators.
+        let mut x = 0.0;
+        for column in self.columns.iter() {
+            x += column.width;
+            drawing_context.push_line(Vector2::new(x, 0.0), Vector2::new(x, bounds.h()), 1.0);
+        }
+        drawing_context.push_line(
+            Vector2::new(0.0, self.header_height),
+            Vector2::new(content_width, self.header_height),
+            1.0,
+        );
+        drawing_context.commit(
+            self.clip_bounds(),
+            self.widget.foreground(),
+            CommandTexture::None,
+            None,
+        );
+
+        // Sort direction indicator at the right side of the header of sorted column.
+        if let Some((column, direction)) = self.sort {
+            let size = self.header_height * 0.25;
+            let center = Vector2::new(
+                self.column_x(column) + self.columns[column].width - size * 2.0,
+                self.header_height * 0.5,
+            );
+            let (tip, base) = match direction {
+                SortDirection::Ascending => (-size * 0.5, size * 0.5),
+                SortDirection::Descending => (size * 0.5, -size * 0.5),
+            };
+            drawing_context.push_triangle_filled([
+                Vector2::new(center.x - size, center.y + base),
+                Vector2::new(center.x + size, center.y + base),
+                Vector2::new(center.x, center.y + tip),
+            ]);
+            drawing_context.commit(self.clip_bounds(), BRUSH_TEXT, CommandTexture::None, None);
+        }
+    }
+
+    fn update(&mut self, _dt: f32, sender: &Sender<UiMessage>) {
+        let body_height = self.actual_local_size().y - self.header_height;
+        let capacity = (body_height / self.row_height).ceil().max(0.0) as usize;
+        if capacity != self.visible_capacity {
+            self.visible_capacity = capacity;
+            let _ = sender.send(DataGridMessage::refresh(
+                self.handle,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<WidgetMessage>() {
+            match *msg {
+                WidgetMessage::MouseDown {
+                    pos,
+                    button: MouseButton::Left,
+                } => {
+                    if !message.handled() && self.handle_mouse_down(pos, ui) {
+                        message.set_handled(true);
+                    }
+                }
+                WidgetMessage::MouseMove { pos, .. } => {
+                    let local = self.screen_to_local(pos);
+                    if let Some(resize) = self.resize {
+                        let column = &mut self.columns[resize.column];
+                        column.width =
+                            (resize.start_width + local.x - resize.start_x).max(column.min_width);
+                        self.invalidate_layout();
+                    } else {
+                        let hover = self.resize_grip_at(local).is_some();
+                        if hover != self.resize_hover {
+                            self.resize_hover = hover;
+                            ui.send_message(WidgetMessage::cursor(
+                                self.handle,
+                                MessageDirection::ToWidget,
+                                if hover {
+                                    Some(CursorIcon::ColResize)
+                                } else {
+                                    None
+                                },
+                            ));
+                        }
+                    }
+                }
+                WidgetMessage::MouseUp {
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    if let Some(resize) = self.resize.take() {
+                        ui.release_mouse_capture();
+                        ui.send_message(DataGridMessage::column_width(
+                            self.handle,
+                            MessageDirection::FromWidget,
+                            resize.column,
+                            self.columns[resize.column].width,
+                        ));
+                        message.set_handled(true);
+                    }
+                }
+                WidgetMessage::DoubleClick {
+                    button: MouseButton::Left,
+                } => {
+                    if let Some(cell) = self.cell_at(self.screen_to_local(ui.cursor_position())) {
+                        if self.columns[cell.column].editable {
+                            self.open_editor(cell.row, cell.column, ui);
+                            message.set_handled(true);
+                        }
+                    }
+                }
+                WidgetMessage::MouseWheel { amount, .. } => {
+                    if !message.handled() {
+                        let first_visible =
+                            (self.first_visible as f32 - amount * WHEEL_ROWS).max(0.0) as usize;
+                        self.set_first_visible(first_visible, ui);
+                        message.set_handled(true);
+                    }
+                }
+                WidgetMessage::KeyDown(code) => {
+                    if let Some(editor) = self.editor {
+                        if code == KeyCode::Escape && message.destination() == editor.text_box {
+                            self.close_editor(ui);
+                            message.set_handled(true);
+                        }
+                    } else if !message.handled() {
+                        let columns = if self.selection_mode == SelectionMode::Cell {
+                            1
+                        } else {
+                            0
+                        };
+                        let handled = match code {
+                            KeyCode::Up => {
+                                self.move_selection(-1, 0, ui);
+                                true
+                            }
+                            KeyCode::Down => {
+                                self.move_selection(1, 0, ui);
+                                true
+                            }
+                            KeyCode::PageUp => {
+                                self.move_selection(-(self.visible_capacity as isize), 0, ui);
+                                true
+                            }
+                            KeyCode::PageDown => {
+                                self.move_selection(self.visible_capacity as isize, 0, ui);
+                                true
+                            }
+                            KeyCode::Left if columns != 0 => {
+                                self.move_selection(0, -columns, ui);
+                                true
+                            }
+                            KeyCode::Right if columns != 0 => {
+                                self.move_selection(0, columns, ui);
+                                true
+                            }
+                            KeyCode::F2 | KeyCode::Return => {
+                                if let Some(selection) = self.selection {
+                                    if self.columns[selection.column].editable {
+                                        self.open_editor(selection.row, selection.column, ui);
+                                    }
+                                }
+                                true
+                            }
+                            _ => false,
+                        };
+                        if handled {
+                            message.set_handled(true);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.scroll_bar
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.set_first_visible(value.round().max(0.0) as usize, ui);
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if let Some(editor) = self.editor {
+                if message.destination() == editor.text_box
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    self.close_editor(ui);
+                    if self.set_cell(editor.row, editor.column, text.clone(), ui) {
+                        ui.send_message(DataGridMessage::cell_edited(
+                            self.handle,
+                            MessageDirection::FromWidget,
+                            editor.row,
+                            editor.column,
+                            text.clone(),
+                        ));
+                    }
+                }
+            }
+        } else if let Some(msg) = message.data::<DataGridMessage>() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    DataGridMessage::Rows(rows) => {
+                        self.close_editor(ui);
+                        self.clear_realized(ui);
+                        self.rows = rows.clone();
+                        self.order = sorted_order(&self.rows, self.sort);
+                        self.first_visible = self.first_visible.min(self.max_first_visible());
+                        if let Some(selection) = self.selection {
+                            if selection.row >= self.rows.len() {
+                                self.set_selection(None, ui);
+                            }
+                        }
+                        self.realize(ui);
+                        self.sync_scroll_bar(ui);
+                    }
+                    &DataGridMessage::Sort(sort) => {
+                        if self.sort != sort {
+                            self.apply_sort(sort, ui);
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    &DataGridMessage::ColumnWidth { column, width } => {
+                        if let Some(column_ref) = self.columns.get_mut(column) {
+                            let width = width.max(column_ref.min_width);
+                            if column_ref.width != width {
+                                column_ref.width = width;
+                                self.invalidate_layout();
+                                ui.send_message(DataGridMessage::column_width(
+                                    self.handle,
+                                    MessageDirection::FromWidget,
+                                    column,
+                                    width,
+                                ));
+                            }
+                        }
+                    }
+                    &DataGridMessage::Selection(selection) => {
+                        let selection = selection
+                            .filter(|s| s.row < self.rows.len() && s.column < self.columns.len());
+                        self.set_selection(selection, ui);
+                    }
+                    DataGridMessage::CellEdited { row, column, value } => {
+                        if self.set_cell(*row, *column, value.clone(), ui) {
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    &DataGridMessage::BringRowIntoView(row) => {
+                        self.bring_row_into_view(row, ui);
+                    }
+                    DataGridMessage::Refresh => {
+                        self.first_visible = self.first_visible.min(self.max_first_visible());
+                        self.clear_realized(ui);
+                        self.realize(ui);
+                        self.sync_scroll_bar(ui);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct DataGridBuilder {
+    widget_builder: WidgetBuilder,
+    columns: Vec<DataGridColumn>,
+    rows: Vec<Vec<String>>,
+    sort: Option<(usize, SortDirection)>,
+    selection_mode: SelectionMode,
+    header_height: f32,
+    row_height: f32,
+}
+
+impl DataGridBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            columns: Default::default(),
+            rows: Default::default(),
+            sort: None,
+            selection_mode: Default::default(),
+            header_height: 24.0,
+            row_height: 22.0,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<DataGridColumn>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_rows(mut self, rows: Vec<Vec<String>>) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    pub fn with_sort(mut self, sort: Option<(usize, SortDirection)>) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn with_selection_mode(mut self, selection_mode: SelectionMode) -> Self {
+        self.selection_mode = selection_mode;
+        self
+    }
+
+    pub fn with_header_height(mut self, height: f32) -> Self {
+        self.header_height = height;
+        self
+    }
+
+    pub fn with_row_height(mut self, height: f32) -> Self {
+        self.row_height = height.max(1.0);
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        if self.widget_builder.background.is_none() {
+            self.widget_builder.background = Some(BRUSH_DARKER);
+        }
+        if self.widget_builder.foreground.is_none() {
+            self.widget_builder.foreground = Some(BRUSH_LIGHT);
+        }
+
+        let headers = self
+            .columns
+            .iter()
+            .map(|column| {
+                TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(4.0)))
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .with_text(&column.header)
+                    .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let scroll_bar = ScrollBarBuilder::new(WidgetBuilder::new())
+            .with_orientation(Orientation::Vertical)
+            .with_min(0.0)
+            .with_max(0.0)
+            .with_step(1.0)
+            .build(ctx);
+
+        let sort = self.sort.filter(|(column, _)| *column < self.columns.len());
+        let order = sorted_order(&self.rows, sort);
+
+        let data_grid = DataGrid {
+            widget: self
+                .widget_builder
+                .with_children(headers.iter().cloned())
+                .with_child(scroll_bar)
+                .build(),
+            columns: self.columns,
+            rows: self.rows,
+            sort,
+            selection_mode: self.selection_mode,
+            selection: None,
+            header_height: self.header_height,
+            row_height: self.row_height,
+            headers,
+            scroll_bar,
+            order,
+            first_visible: 0,
+            // Rows are created on first update, when the size of the grid is known.
+            visible_capacity: 0,
+            realized: Default::default(),
+            editor: None,
+            resize: None,
+            resize_hover: false,
+        };
+
+        ctx.add_node(UiNode::new(data_grid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data_grid::{compare_cells, sorted_order, SortDirection};
+    use std::cmp::Ordering;
+
+    fn rows(data: &[[&str; 2]]) -> Vec<Vec<String>> {
+        data.iter()
+            .map(|row| row.iter().map(|c| c.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_cells() {
+        assert_eq!(compare_cells("2", "10"), Ordering::Less);
+        assert_eq!(compare_cells("2.5", " 2.5"), Ordering::Equal);
+        assert_eq!(compare_cells("b", "a"), Ordering::Greater);
+        assert_eq!(compare_cells("10", "a"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_sorted_order() {
+        let rows = rows(&[["Bob", "30"], ["Alice", "120"], ["Carl", "30"]]);
+
+        assert_eq!(sorted_order(&rows, None), vec![0, 1, 2]);
+        assert_eq!(
+            sorted_order(&rows, Some((0, SortDirection::Ascending))),
+            vec![1, 0, 2]
+        );
+        // Sorting is stable - rows with equal values keep their order.
+        assert_eq!(
+            sorted_order(&rows, Some((1, SortDirection::Ascending))),
+            vec![0, 2, 1]
+        );
+        assert_eq!(
+            sorted_order(&rows, Some((1, SortDirection::Descending))),
+            vec![1, 0, 2]
+        );
+        // Missing column is treated as empty cells.
+        assert_eq!(
+            sorted_order(&rows, Some((5, SortDirection::Ascending))),
+            vec![0, 1, 2]
+        );
+    }
+}
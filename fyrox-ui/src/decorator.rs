@@ -1,6 +1,6 @@
 use crate::{
     border::{Border, BorderBuilder},
-    brush::{Brush, GradientPoint},
+    brush::{Brush, BrushCoordinates, GradientPoint},
     core::{algebra::Vector2, color::Color, pool::Handle},
     define_constructor,
     draw::DrawingContext,
@@ -294,6 +294,7 @@ impl DecoratorBuilder {
                         color: COLOR_DARKEST,
                     },
                 ],
+                coordinates: BrushCoordinates::Relative,
             });
         }
 
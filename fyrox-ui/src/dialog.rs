@@ -0,0 +1,525 @@
+//! Modal dialogs with callback-style results.
+//!
+//! Any widget hierarchy with a [`crate::window::Window`] at its root could be shown as a dialog
+//! by implementing [`Dialog`] trait for its builder. [`UserInterface::show_dialog`] opens the
+//! window modally and [`DialogHandle::on_result`] registers a callback that will be called
+//! exactly once: either with a value sent via [`DialogMessage::Close`] or with `None` if the
+//! window was closed in any other way (for example by its close button). The window is
+//! removed from the UI after that.
+//!
+//! ```rust,no_run
+//! # use fyrox_ui::{dialog::ConfirmDialog, UserInterface};
+//! # fn f(ui: &mut UserInterface) {
+//! ui.show_dialog(ConfirmDialog::new("Delete", "Are you sure?"))
+//!     .on_result(|_ui, result| {
+//!         if result == Some(true) {
+//!             println!("Confirmed!");
+//!         }
+//!     });
+//! # }
+//! ```
+//!
+//! There are few standard dialogs: [`ConfirmDialog`], [`InputDialog`] and [`PickDialog`].
+
+use crate::{
+    border::BorderBuilder,
+    brush::Brush,
+    button::{ButtonBuilder, ButtonMessage},
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    decorator::DecoratorBuilder,
+    define_constructor,
+    draw::DrawingContext,
+    formatted_text::WrapMode,
+    grid::{Column, GridBuilder, Row},
+    list_view::{ListView, ListViewBuilder},
+    message::{MessageDirection, OsEvent, UiMessage},
+    stack_panel::StackPanelBuilder,
+    text::TextBuilder,
+    text_box::{TextBox, TextBoxBuilder},
+    widget::{Widget, WidgetBuilder},
+    window::{Window, WindowBuilder, WindowTitle},
+    BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Orientation, Thickness, UiNode,
+    UserInterface, BRUSH_DARK, BRUSH_DARKEST,
+};
+use std::{
+    any::{Any, TypeId},
+    fmt::{Debug, Formatter},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::mpsc::Sender,
+};
+
+/// Type-erased result of a dialog. `None` means that the dialog was cancelled.
+#[derive(Clone, Default)]
+pub struct DialogResult(pub Option<Rc<dyn Any>>);
+
+impl DialogResult {
+    pub fn new<T: Any>(value: T) -> Self {
+        Self(Some(Rc::new(value)))
+    }
+
+    pub fn cancelled() -> Self {
+        Self(None)
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.as_ref().and_then(|v| v.downcast_ref::<T>())
+    }
+}
+
+impl PartialEq for DialogResult {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Debug for DialogResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DialogResult")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogMessage {
+    /// Closes a dialog window (destination) with the given result. Result callback of the dialog
+    /// (if any) will be called and the window will be removed.
+    ///
+    /// Direction: **To UI**.
+    Close(DialogResult),
+}
+
+impl DialogMessage {
+    define_constructor!(DialogMessage:Close => fn close(DialogResult), layout: false);
+}
+
+/// A dialog that could be shown via [`UserInterface::show_dialog`].
+pub trait Dialog {
+    /// Type of the dialog result, it must match type of the value sent in [`DialogMessage::Close`].
+    type Output: Clone + 'static;
+
+    /// Builds dialog widgets and returns a handle to its window. The window should be closed.
+    fn build(self, ctx: &mut BuildContext) -> Handle<UiNode>;
+}
+
+pub(crate) type DialogCallback = Box<dyn FnOnce(&mut UserInterface, DialogResult)>;
+
+/// A handle to an opened dialog, it allows you to specify a callback for the dialog result.
+#[must_use = "dialog result will be lost if no callback is specified"]
+pub struct DialogHandle<'a, T> {
+    pub(crate) ui: &'a mut UserInterface,
+    pub(crate) window: Handle<UiNode>,
+    pub(crate) phantom: PhantomData<T>,
+}
+
+impl<'a, T: Clone + 'static> DialogHandle<'a, T> {
+    /// Returns a handle of the dialog window.
+    pub fn window(&self) -> Handle<UiNode> {
+        self.window
+    }
+
+    /// Sets a callback that will be called when the dialog is closed. The result is `None` if
+    /// the dialog was cancelled. Returns a handle of the dialog window.
+    pub fn on_result<F>(self, callback: F) -> Handle<UiNode>
+    where
+        F: FnOnce(&mut UserInterface, Option<T>) + 'static,
+    {
+        self.ui.dialog_callbacks.insert(
+            self.window,
+            Box::new(move |ui, result| callback(ui, result.get::<T>().cloned())),
+        );
+        self.window
+    }
+}
+
+#[derive(Clone)]
+enum StandardDialogKind {
+    Confirm,
+    Input { text_box: Handle<UiNode> },
+    Pick { list: Handle<UiNode> },
+}
+
+/// A window that is used by standard dialogs. Use [`ConfirmDialog`], [`InputDialog`] or
+/// [`PickDialog`] to create it.
+#[derive(Clone)]
+pub struct StandardDialog {
+    pub window: Window,
+    kind: StandardDialogKind,
+    ok: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+}
+
+impl Deref for StandardDialog {
+    type Target = Widget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.window
+    }
+}
+
+impl DerefMut for StandardDialog {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.window
+    }
+}
+
+impl Control for StandardDialog {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        self.window.query_component(type_id).or_else(|| {
+            if type_id == TypeId::of::<Self>() {
+                Some(self)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        self.window.resolve(node_map);
+        node_map.resolve(&mut self.ok);
+        node_map.resolve(&mut self.cancel);
+        match self.kind {
+            StandardDialogKind::Confirm => (),
+            StandardDialogKind::Input { ref mut text_box } => node_map.resolve(text_box),
+            StandardDialogKind::Pick { ref mut list } => node_map.resolve(list),
+        }
+    }
+
+    fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
+        self.window.measure_override(ui, available_size)
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        self.window.arrange_override(ui, final_size)
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        self.window.draw(drawing_context)
+    }
+
+    fn update(&mut self, dt: f32, sender: &Sender<UiMessage>) {
+        self.window.update(dt, sender);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.window.handle_routed_message(ui, message);
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            let result = if message.destination() == self.ok {
+                match self.kind {
+                    StandardDialogKind::Confirm => Some(DialogResult::new(true)),
+                    StandardDialogKind::Input { text_box } => ui
+                        .node(text_box)
+                        .query_component::<TextBox>()
+                        .map(|text_box| DialogResult::new(text_box.text())),
+                    StandardDialogKind::Pick { list } => Some(
+                        ui.node(list)
+                            .query_component::<ListView>()
+                            .and_then(|list| list.selected())
+                            .map(DialogResult::new)
+                            .unwrap_or_default(),
+                    ),
+                }
+            } else if message.destination() == self.cancel {
+                Some(match self.kind {
+                    StandardDialogKind::Confirm => DialogResult::new(false),
+                    _ => DialogResult::cancelled(),
+                })
+            } else {
+                None
+            };
+
+            if let Some(result) = result {
+                ui.send_message(DialogMessage::close(
+                    self.handle(),
+                    MessageDirection::ToWidget,
+                    result,
+                ));
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        self.window.preview_message(ui, message);
+    }
+
+    fn handle_os_event(
+        &mut self,
+        self_handle: Handle<UiNode>,
+        ui: &mut UserInterface,
+        event: &OsEvent,
+    ) {
+        self.window.handle_os_event(self_handle, ui, event);
+    }
+}
+
+fn build_standard_dialog(
+    title: &str,
+    text: &str,
+    body: Option<Handle<UiNode>>,
+    kind: StandardDialogKind,
+    ok_text: &str,
+    cancel_text: &str,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    let ok;
+    let cancel;
+    let mut content_builder = WidgetBuilder::new()
+        .with_child(
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+                .with_text(text)
+                .with_wrap(WrapMode::Word)
+                .build(ctx),
+        )
+        .with_child(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                    .on_row(2)
+                    .with_child({
+                        ok = ButtonBuilder::new(
+                            WidgetBuilder::new()
+                                .with_width(80.0)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_text(ok_text)
+                        .build(ctx);
+                        ok
+                    })
+                    .with_child({
+                        cancel = ButtonBuilder::new(
+                            WidgetBuilder::new()
+                                .with_width(80.0)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_text(cancel_text)
+                        .build(ctx);
+                        cancel
+                    }),
+            )
+            .with_orientation(Orientation::Horizontal)
+            .build(ctx),
+        );
+
+    if let Some(body) = body {
+        ctx[body].set_row(1);
+        content_builder = content_builder.with_child(body);
+    }
+
+    let content = GridBuilder::new(content_builder)
+        .add_row(Row::auto())
+        .add_row(Row::stretch())
+        .add_row(Row::strict(25.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+
+    let dialog = StandardDialog {
+        window: WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_min_size(Vector2::new(200.0, 100.0))
+                .with_width(300.0)
+                .with_handle_os_events(true),
+        )
+        .with_title(WindowTitle::text(title))
+        .with_content(content)
+        .can_minimize(false)
+        .open(false)
+        .build_window(ctx),
+        kind,
+        ok,
+        cancel,
+    };
+
+    ctx.add_node(UiNode::new(dialog))
+}
+
+/// A dialog with a text and "Yes"/"No" buttons. Its result is `true` if user clicked "Yes".
+pub struct ConfirmDialog {
+    pub title: String,
+    pub text: String,
+}
+
+impl ConfirmDialog {
+    pub fn new<T: AsRef<str>, S: AsRef<str>>(title: T, text: S) -> Self {
+        Self {
+            title: title.as_ref().to_owned(),
+            text: text.as_ref().to_owned(),
+        }
+    }
+}
+
+impl Dialog for ConfirmDialog {
+    type Output = bool;
+
+    fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        build_standard_dialog(
+            &self.title,
+            &self.text,
+            None,
+            StandardDialogKind::Confirm,
+            "Yes",
+            "No",
+            ctx,
+        )
+    }
+}
+
+/// A dialog with a text box. Its result is the entered string.
+pub struct InputDialog {
+    pub title: String,
+    pub text: String,
+    pub value: String,
+}
+
+impl InputDialog {
+    pub fn new<T: AsRef<str>, S: AsRef<str>>(title: T, text: S) -> Self {
+        Self {
+            title: title.as_ref().to_owned(),
+            text: text.as_ref().to_owned(),
+            value: Default::default(),
+        }
+    }
+
+    /// Sets initial value of the text box.
+    pub fn with_value<S: AsRef<str>>(mut self, value: S) -> Self {
+        self.value = value.as_ref().to_owned();
+        self
+    }
+}
+
+impl Dialog for InputDialog {
+    type Output = String;
+
+    fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let text_box = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_height(22.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text(self.value)
+        .build(ctx);
+
+        build_standard_dialog(
+            &self.title,
+            &self.text,
+            Some(text_box),
+            StandardDialogKind::Input { text_box },
+            "OK",
+            "Cancel",
+            ctx,
+        )
+    }
+}
+
+/// A dialog with a list of items. Its result is the index of selected item.
+pub struct PickDialog {
+    pub title: String,
+    pub text: String,
+    pub items: Vec<String>,
+}
+
+impl PickDialog {
+    pub fn new<T: AsRef<str>, S: AsRef<str>>(title: T, text: S, items: Vec<String>) -> Self {
+        Self {
+            title: title.as_ref().to_owned(),
+            text: text.as_ref().to_owned(),
+            items,
+        }
+    }
+}
+
+impl Dialog for PickDialog {
+    type Output = usize;
+
+    fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let items = self
+            .items
+            .iter()
+            .map(|item| {
+                DecoratorBuilder::new(BorderBuilder::new(
+                    WidgetBuilder::new().with_height(22.0).with_child(
+                        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+                            .with_text(item)
+                            .build(ctx),
+                    ),
+                ))
+                .with_selected_brush(BRUSH_DARKEST)
+                .with_hover_brush(BRUSH_DARK)
+                .with_normal_brush(Brush::Solid(Color::TRANSPARENT))
+                .build(ctx)
+            })
+            .collect();
+
+        let list = ListViewBuilder::new(
+            WidgetBuilder::new()
+                .with_height(150.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_items(items)
+        .build(ctx);
+
+        build_standard_dialog(
+            &self.title,
+            &self.text,
+            Some(list),
+            StandardDialogKind::Pick { list },
+            "OK",
+            "Cancel",
+            ctx,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, pool::Handle},
+        dialog::{ConfirmDialog, DialogMessage, DialogResult, InputDialog},
+        message::MessageDirection,
+        window::WindowMessage,
+        UserInterface,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_dialog_result() {
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+
+        let result = Rc::new(RefCell::new(None));
+        let result_clone = result.clone();
+        let window = ui
+            .show_dialog(ConfirmDialog::new("Title", "Text"))
+            .on_result(move |_, r| *result_clone.borrow_mut() = Some(r));
+
+        ui.send_message(DialogMessage::close(
+            window,
+            MessageDirection::ToWidget,
+            DialogResult::new(true),
+        ));
+        while ui.poll_message().is_some() {}
+
+        assert_eq!(*result.borrow(), Some(Some(true)));
+        assert_eq!(ui.try_get_node(window).map(|_| ()), None);
+    }
+
+    #[test]
+    fn test_dialog_cancelled_by_window_close() {
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+
+        let result = Rc::new(RefCell::new(None));
+        let result_clone = result.clone();
+        let window = ui
+            .show_dialog(InputDialog::new("Title", "Text"))
+            .on_result(move |_, r| *result_clone.borrow_mut() = Some(r));
+        assert_ne!(window, Handle::NONE);
+
+        ui.send_message(WindowMessage::close(window, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+
+        assert_eq!(*result.borrow(), Some(None));
+    }
+}
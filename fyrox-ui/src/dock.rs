@@ -4,17 +4,23 @@
 //!
 //! Docking manager can hold any types of UI elements, but dragging works only
 //! for windows.
+//!
+//! Arrangement of windows can be saved and restored using [`DockingManager::layout`] and
+//! [`DockingManagerMessage::apply_layout`]. Windows are identified by their names in a layout,
+//! so every window that should be restored must have a unique name.
 
 use crate::{
     border::BorderBuilder,
     brush::Brush,
-    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    button::{ButtonBuilder, ButtonMessage},
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle, visitor::prelude::*},
     define_constructor,
     grid::{Column, GridBuilder, Row},
     message::{CursorIcon, MessageDirection, UiMessage},
+    stack_panel::StackPanelBuilder,
     widget::{Widget, WidgetBuilder, WidgetMessage},
     window::{Window, WindowMessage},
-    BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface,
+    BuildContext, Control, NodeHandleMapping, Orientation, Thickness, UiNode, UserInterface,
 };
 use std::{
     any::{Any, TypeId},
@@ -66,6 +72,72 @@ impl TileContent {
     }
 }
 
+/// Side of a docking manager at which auto-hide windows are collapsed to tabs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Visit)]
+pub enum DockSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Default for DockSide {
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
+/// Serializable description of a tile, see [`Layout`].
+#[derive(Debug, Clone, PartialEq, Visit)]
+pub enum TileLayout {
+    Empty,
+    /// A window with the given name.
+    Window {
+        name: String,
+    },
+    /// Split tile, `tiles` are indices of sub-tiles in [`Layout::tiles`].
+    VerticalTiles {
+        splitter: f32,
+        tiles: [u32; 2],
+    },
+    /// Split tile, `tiles` are indices of sub-tiles in [`Layout::tiles`].
+    HorizontalTiles {
+        splitter: f32,
+        tiles: [u32; 2],
+    },
+}
+
+impl Default for TileLayout {
+    fn default() -> Self {
+        Self::Empty
+    }
+}
+
+/// Position and size of a floating (undocked) window.
+#[derive(Debug, Clone, PartialEq, Default, Visit)]
+pub struct FloatingWindowLayout {
+    pub name: String,
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+}
+
+/// A window that is collapsed to a tab at a side of a docking manager.
+#[derive(Debug, Clone, PartialEq, Default, Visit)]
+pub struct AutoHideWindowLayout {
+    pub name: String,
+    pub side: DockSide,
+}
+
+/// Serializable arrangement of windows of a docking manager. Use [`DockingManager::layout`]
+/// to get current layout and [`DockingManagerMessage::apply_layout`] to restore it.
+#[derive(Debug, Clone, PartialEq, Default, Visit)]
+pub struct Layout {
+    /// Flattened tree of tiles, first tile (if any) is the root tile.
+    pub tiles: Vec<TileLayout>,
+    pub floating_windows: Vec<FloatingWindowLayout>,
+    pub auto_hide_windows: Vec<AutoHideWindowLayout>,
+}
+
 #[derive(Clone)]
 pub struct Tile {
     pub widget: Widget,
@@ -346,6 +418,9 @@ impl Control for Tile {
                 &WidgetMessage::MouseUp { .. } => {
                     if !message.handled() && message.destination() == self.splitter {
                         message.set_handled(true);
+                        if self.dragging_splitter {
+                            self.notify_layout_changed(ui);
+                        }
                         self.dragging_splitter = false;
                         ui.release_mouse_capture();
                     }
@@ -505,6 +580,8 @@ impl Control for Tile {
                                 .borrow_mut()
                                 .push(message.destination());
                         }
+
+                        self.notify_layout_changed(ui);
                     }
                 }
             }
@@ -515,7 +592,7 @@ impl Control for Tile {
     // from docking manager and handle_routed_message won't receive any messages from window.
     fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
         if let Some(msg) = message.data::<WindowMessage>() {
-            if let Some((_, docking_manager)) =
+            if let Some((docking_manager_handle, docking_manager)) =
                 ui.try_borrow_by_type_up::<DockingManager>(self.parent())
             {
                 // Make sure we are dragging one of floating windows of parent docking manager.
@@ -660,6 +737,11 @@ impl Control for Tile {
                                     // Rest cannot accept windows.
                                     _ => (),
                                 }
+
+                                ui.send_message(DockingManagerMessage::layout_changed(
+                                    docking_manager_handle,
+                                    MessageDirection::FromWidget,
+                                ));
                             }
                         }
                         _ => (),
@@ -677,6 +759,18 @@ pub enum SplitDirection {
 }
 
 impl Tile {
+    /// Notifies parent docking manager that the layout was changed by user.
+    fn notify_layout_changed(&self, ui: &UserInterface) {
+        if let Some((docking_manager, _)) =
+            ui.try_borrow_by_type_up::<DockingManager>(self.parent())
+        {
+            ui.send_message(DockingManagerMessage::layout_changed(
+                docking_manager,
+                MessageDirection::FromWidget,
+            ));
+        }
+    }
+
     pub fn anchors(&self) -> [Handle<UiNode>; 5] {
         [
             self.left_anchor,
@@ -744,10 +838,49 @@ impl Tile {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockingManagerMessage {
+    /// Replaces current arrangement of windows with the given one. Windows that are not
+    /// mentioned in the layout become floating.
+    Layout(Layout),
+    /// Collapses a window to a tab at the given side of docking manager. The window is shown
+    /// over other windows when its tab is clicked.
+    Unpin {
+        window: Handle<UiNode>,
+        side: DockSide,
+    },
+    /// Docks an auto-hide window back at the side it was collapsed to.
+    Pin(Handle<UiNode>),
+    /// Emitted by docking manager (with [`MessageDirection::FromWidget`]) when user changes
+    /// the layout: docks, undocks, pins or unpins a window or moves a splitter.
+    LayoutChanged,
+}
+
+impl DockingManagerMessage {
+    define_constructor!(DockingManagerMessage:Layout => fn apply_layout(Layout), layout: false);
+    define_constructor!(DockingManagerMessage:Unpin => fn unpin(window: Handle<UiNode>,
+        side: DockSide), layout: false);
+    define_constructor!(DockingManagerMessage:Pin => fn pin(Handle<UiNode>), layout: false);
+    define_constructor!(DockingManagerMessage:LayoutChanged => fn layout_changed(), layout: false);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoHideWindow {
+    pub window: Handle<UiNode>,
+    pub side: DockSide,
+    /// A button at the side of docking manager that toggles visibility of the window.
+    pub tab: Handle<UiNode>,
+}
+
 #[derive(Clone)]
 pub struct DockingManager {
     pub widget: Widget,
     pub floating_windows: RefCell<Vec<Handle<UiNode>>>,
+    /// Panels with tabs of auto-hide windows, indexed by [`DockSide`].
+    pub auto_hide_bars: [Handle<UiNode>; 4],
+    pub auto_hide_windows: Vec<AutoHideWindow>,
+    /// Auto-hide window that is currently shown.
+    pub active_auto_hide_window: Handle<UiNode>,
 }
 
 crate::define_widget_deref!(DockingManager);
@@ -763,10 +896,128 @@ impl Control for DockingManager {
 
     fn resolve(&mut self, node_map: &NodeHandleMapping) {
         node_map.resolve_slice(&mut self.floating_windows.borrow_mut());
+        node_map.resolve_slice(&mut self.auto_hide_bars);
+        for entry in self.auto_hide_windows.iter_mut() {
+            node_map.resolve(&mut entry.window);
+            node_map.resolve(&mut entry.tab);
+        }
+        node_map.resolve(&mut self.active_auto_hide_window);
+    }
+
+    fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
+        let mut size: Vector2<f32> = Vector2::default();
+
+        for &child in self.children() {
+            ui.measure_node(child, available_size);
+            let desired_size = ui.node(child).desired_size();
+            size.x = size.x.max(desired_size.x);
+            size.y = size.y.max(desired_size.y);
+        }
+
+        size
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        let bar_size = |side: DockSide| ui.node(self.auto_hide_bars[side as usize]).desired_size();
+
+        let left = bar_size(DockSide::Left).x;
+        let right = bar_size(DockSide::Right).x;
+        let top = bar_size(DockSide::Top).y;
+        let bottom = bar_size(DockSide::Bottom).y;
+
+        // Tiles take everything that is left after the bars.
+        let content = Rect::new(
+            left,
+            top,
+            (final_size.x - left - right).max(0.0),
+            (final_size.y - top - bottom).max(0.0),
+        );
+
+        for &child in self.children() {
+            let bounds = if child == self.auto_hide_bars[DockSide::Left as usize] {
+                Rect::new(0.0, top, left, content.h())
+            } else if child == self.auto_hide_bars[DockSide::Right as usize] {
+                Rect::new(final_size.x - right, top, right, content.h())
+            } else if child == self.auto_hide_bars[DockSide::Top as usize] {
+                Rect::new(0.0, 0.0, final_size.x, top)
+            } else if child == self.auto_hide_bars[DockSide::Bottom as usize] {
+                Rect::new(0.0, final_size.y - bottom, final_size.x, bottom)
+            } else if let Some(entry) = self.auto_hide_windows.iter().find(|e| e.window == child) {
+                // Auto-hide windows slide out of their bars over the content.
+                let desired_size = ui.node(child).desired_size();
+                let w = desired_size.x.min(content.w());
+                let h = desired_size.y.min(content.h());
+                match entry.side {
+                    DockSide::Left => Rect::new(content.x(), content.y(), w, content.h()),
+                    DockSide::Right => {
+                        Rect::new(content.x() + content.w() - w, content.y(), w, content.h())
+                    }
+                    DockSide::Top => Rect::new(content.x(), content.y(), content.w(), h),
+                    DockSide::Bottom => {
+                        Rect::new(content.x(), content.y() + content.h() - h, content.w(), h)
+                    }
+                }
+            } else {
+                content
+            };
+
+            ui.arrange_node(child, &bounds);
+        }
+
+        final_size
     }
 
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<DockingManagerMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    DockingManagerMessage::Layout(layout) => {
+                        self.apply_layout(ui, layout);
+                    }
+                    &DockingManagerMessage::Unpin { window, side } => {
+                        self.unpin(ui, window, side);
+                    }
+                    &DockingManagerMessage::Pin(window) => {
+                        self.pin(ui, window);
+                    }
+                    DockingManagerMessage::LayoutChanged => {}
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if let Some(window) = self
+                .auto_hide_windows
+                .iter()
+                .find(|e| e.tab == message.destination())
+                .map(|e| e.window)
+            {
+                let show = self.active_auto_hide_window != window;
+                self.hide_active_auto_hide_window(ui);
+                if show {
+                    self.active_auto_hide_window = window;
+                    ui.send_message(WidgetMessage::visibility(
+                        window,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+                }
+            }
+        } else if let Some(WidgetMessage::MouseDown { .. }) = message.data::<WidgetMessage>() {
+            // Clicking anywhere outside of shown auto-hide window hides it.
+            if self.active_auto_hide_window.is_some()
+                && message.destination() != self.active_auto_hide_window
+                && !ui.is_node_child_of(message.destination(), self.active_auto_hide_window)
+                && !self
+                    .auto_hide_bars
+                    .iter()
+                    .any(|&bar| ui.is_node_child_of(message.destination(), bar))
+            {
+                self.hide_active_auto_hide_window(ui);
+            }
+        }
     }
 
     fn preview_message(&self, _ui: &UserInterface, message: &mut UiMessage) {
@@ -783,6 +1034,429 @@ impl Control for DockingManager {
     }
 }
 
+impl DockingManager {
+    /// Returns current arrangement of windows.
+    pub fn layout(&self, ui: &UserInterface) -> Layout {
+        fn save_tile(
+            ui: &UserInterface,
+            handle: Handle<UiNode>,
+            tiles: &mut Vec<TileLayout>,
+        ) -> u32 {
+            let index = tiles.len();
+            tiles.push(TileLayout::Empty);
+            if let Some(tile) = ui.node(handle).cast::<Tile>() {
+                tiles[index] = match tile.content {
+                    TileContent::Empty => TileLayout::Empty,
+                    TileContent::Window(window) => TileLayout::Window {
+                        name: ui.node(window).name().to_owned(),
+                    },
+                    TileContent::VerticalTiles {
+                        splitter,
+                        tiles: sub_tiles,
+                    } => TileLayout::VerticalTiles {
+                        splitter,
+                        tiles: [
+                            save_tile(ui, sub_tiles[0], tiles),
+                            save_tile(ui, sub_tiles[1], tiles),
+                        ],
+                    },
+                    TileContent::HorizontalTiles {
+                        splitter,
+                        tiles: sub_tiles,
+                    } => TileLayout::HorizontalTiles {
+                        splitter,
+                        tiles: [
+                            save_tile(ui, sub_tiles[0], tiles),
+                            save_tile(ui, sub_tiles[1], tiles),
+                        ],
+                    },
+                };
+            }
+            index as u32
+        }
+
+        let mut layout = Layout::default();
+
+        if let Some(root_tile) = self.root_tile(ui) {
+            save_tile(ui, root_tile, &mut layout.tiles);
+        }
+
+        for &window in self.floating_windows.borrow().iter() {
+            if let Some(window) = ui.try_get_node(window) {
+                layout.floating_windows.push(FloatingWindowLayout {
+                    name: window.name().to_owned(),
+                    position: window.actual_local_position(),
+                    size: window.actual_local_size(),
+                });
+            }
+        }
+
+        for entry in self.auto_hide_windows.iter() {
+            layout.auto_hide_windows.push(AutoHideWindowLayout {
+                name: ui.node(entry.window).name().to_owned(),
+                side: entry.side,
+            });
+        }
+
+        layout
+    }
+
+    fn root_tile(&self, ui: &UserInterface) -> Option<Handle<UiNode>> {
+        self.children()
+            .iter()
+            .cloned()
+            .find(|&c| ui.node(c).cast::<Tile>().is_some())
+    }
+
+    fn hide_active_auto_hide_window(&mut self, ui: &UserInterface) {
+        if self.active_auto_hide_window.is_some() {
+            ui.send_message(WidgetMessage::visibility(
+                self.active_auto_hide_window,
+                MessageDirection::ToWidget,
+                false,
+            ));
+            self.active_auto_hide_window = Handle::NONE;
+        }
+    }
+
+    // Docking manager is taken out of the pool while it handles its messages, so every
+    // relinking that involves the manager itself must be done here directly.
+    fn detach(&mut self, ui: &mut UserInterface, node: Handle<UiNode>) {
+        if ui.node(node).parent() == self.handle {
+            self.widget.remove_child(node);
+            ui.nodes[node].set_parent(Handle::NONE);
+            self.invalidate_layout();
+        }
+        ui.unlink_node(node);
+    }
+
+    fn attach(&mut self, ui: &mut UserInterface, node: Handle<UiNode>) {
+        self.detach(ui, node);
+        ui.unlink_node_internal(node);
+        ui.nodes[node].set_parent(self.handle);
+        self.widget.add_child(node, false);
+        self.invalidate_layout();
+    }
+
+    // The window must be attached to the manager by the caller.
+    fn add_auto_hide_window(
+        &mut self,
+        ui: &mut UserInterface,
+        window: Handle<UiNode>,
+        side: DockSide,
+    ) {
+        // Keep the size along the side of the docking manager, the other one is defined by it.
+        let size = ui.node(window).actual_local_size();
+        match side {
+            DockSide::Left | DockSide::Right => {
+                ui.send_message(WidgetMessage::width(
+                    window,
+                    MessageDirection::ToWidget,
+                    if size.x > 0.0 { size.x } else { 300.0 },
+                ));
+                ui.send_message(WidgetMessage::height(
+                    window,
+                    MessageDirection::ToWidget,
+                    f32::NAN,
+                ));
+            }
+            DockSide::Top | DockSide::Bottom => {
+                ui.send_message(WidgetMessage::width(
+                    window,
+                    MessageDirection::ToWidget,
+                    f32::NAN,
+                ));
+                ui.send_message(WidgetMessage::height(
+                    window,
+                    MessageDirection::ToWidget,
+                    if size.y > 0.0 { size.y } else { 300.0 },
+                ));
+            }
+        }
+        ui.send_message(WidgetMessage::visibility(
+            window,
+            MessageDirection::ToWidget,
+            false,
+        ));
+        ui.send_message(WindowMessage::can_resize(
+            window,
+            MessageDirection::ToWidget,
+            false,
+        ));
+
+        let name = ui.node(window).name().to_owned();
+        let tab = ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+            .with_text(if name.is_empty() {
+                "Window"
+            } else {
+                name.as_str()
+            })
+            .build(&mut ui.build_ctx());
+        ui.link_nodes_internal(tab, self.auto_hide_bars[side as usize], false);
+
+        self.auto_hide_windows
+            .push(AutoHideWindow { window, side, tab });
+    }
+
+    fn remove_auto_hide_window(
+        &mut self,
+        ui: &mut UserInterface,
+        window: Handle<UiNode>,
+    ) -> Option<AutoHideWindow> {
+        let index = self
+            .auto_hide_windows
+            .iter()
+            .position(|e| e.window == window)?;
+        let entry = self.auto_hide_windows.remove(index);
+        if self.active_auto_hide_window == window {
+            self.active_auto_hide_window = Handle::NONE;
+        }
+        ui.send_message(WidgetMessage::remove(entry.tab, MessageDirection::ToWidget));
+        self.detach(ui, window);
+        ui.send_message(WidgetMessage::visibility(
+            window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        Some(entry)
+    }
+
+    fn unpin(&mut self, ui: &mut UserInterface, window: Handle<UiNode>, side: DockSide) {
+        if self.auto_hide_windows.iter().any(|e| e.window == window) {
+            return;
+        }
+
+        let parent = ui.node(window).parent();
+        if ui.node(parent).cast::<Tile>().is_some() {
+            // Same as undocking by dragging, empty tiles will be collapsed.
+            ui.send_message(TileMessage::content(
+                parent,
+                MessageDirection::ToWidget,
+                TileContent::Empty,
+            ));
+            ui.send_message(WidgetMessage::unlink(window, MessageDirection::ToWidget));
+            ui.send_message(WidgetMessage::link(
+                window,
+                MessageDirection::ToWidget,
+                self.handle,
+            ));
+        } else {
+            self.floating_windows.borrow_mut().retain(|&w| w != window);
+            self.attach(ui, window);
+        }
+
+        self.add_auto_hide_window(ui, window, side);
+
+        ui.send_message(DockingManagerMessage::layout_changed(
+            self.handle,
+            MessageDirection::FromWidget,
+        ));
+    }
+
+    fn pin(&mut self, ui: &mut UserInterface, window: Handle<UiNode>) {
+        let entry = match self.remove_auto_hide_window(ui, window) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        // Find a tile closest to the side the window was collapsed to.
+        let mut tile = self.root_tile(ui).unwrap_or_default();
+        while let Some(tile_ref) = ui.try_get_node(tile).and_then(|n| n.cast::<Tile>()) {
+            match tile_ref.content {
+                TileContent::VerticalTiles { tiles, .. } => {
+                    tile = tiles[(entry.side == DockSide::Bottom) as usize];
+                }
+                TileContent::HorizontalTiles { tiles, .. } => {
+                    tile = tiles[(entry.side == DockSide::Right) as usize];
+                }
+                TileContent::Empty => {
+                    ui.send_message(TileMessage::content(
+                        tile,
+                        MessageDirection::ToWidget,
+                        TileContent::Window(window),
+                    ));
+                    break;
+                }
+                TileContent::Window(_) => {
+                    ui.send_message(TileMessage::split(
+                        tile,
+                        MessageDirection::ToWidget,
+                        window,
+                        match entry.side {
+                            DockSide::Left | DockSide::Right => SplitDirection::Horizontal,
+                            DockSide::Top | DockSide::Bottom => SplitDirection::Vertical,
+                        },
+                        matches!(entry.side, DockSide::Left | DockSide::Top),
+                    ));
+                    break;
+                }
+            }
+        }
+
+        // There is no tiles, so the window can only float.
+        if tile.is_none() {
+            self.floating_windows.borrow_mut().push(window);
+            ui.send_message(WindowMessage::can_resize(
+                window,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        }
+
+        ui.send_message(DockingManagerMessage::layout_changed(
+            self.handle,
+            MessageDirection::FromWidget,
+        ));
+    }
+
+    fn apply_layout(&mut self, ui: &mut UserInterface, layout: &Layout) {
+        fn collect_windows(
+            ui: &UserInterface,
+            tile: Handle<UiNode>,
+            windows: &mut Vec<Handle<UiNode>>,
+        ) {
+            if let Some(tile) = ui.node(tile).cast::<Tile>() {
+                match tile.content {
+                    TileContent::Empty => {}
+                    TileContent::Window(window) => windows.push(window),
+                    TileContent::VerticalTiles { tiles, .. }
+                    | TileContent::HorizontalTiles { tiles, .. } => {
+                        for &tile in tiles.iter() {
+                            collect_windows(ui, tile, windows);
+                        }
+                    }
+                }
+            }
+        }
+
+        fn find_window(
+            ui: &UserInterface,
+            windows: &mut Vec<Handle<UiNode>>,
+            name: &str,
+        ) -> Option<Handle<UiNode>> {
+            let index = windows.iter().position(|&w| ui.node(w).name() == name)?;
+            Some(windows.remove(index))
+        }
+
+        fn build_tile(
+            ui: &mut UserInterface,
+            layout: &Layout,
+            index: u32,
+            windows: &mut Vec<Handle<UiNode>>,
+            depth: usize,
+        ) -> Handle<UiNode> {
+            // Depth check protects from malformed layouts with cycles.
+            let content = match layout.tiles.get(index as usize) {
+                Some(tile) if depth < layout.tiles.len() => match tile {
+                    TileLayout::Empty => TileContent::Empty,
+                    TileLayout::Window { name } => match find_window(ui, windows, name) {
+                        Some(window) => {
+                            ui.send_message(WidgetMessage::visibility(
+                                window,
+                                MessageDirection::ToWidget,
+                                true,
+                            ));
+                            TileContent::Window(window)
+                        }
+                        None => TileContent::Empty,
+                    },
+                    &TileLayout::VerticalTiles { splitter, tiles } => TileContent::VerticalTiles {
+                        splitter,
+                        tiles: [
+                            build_tile(ui, layout, tiles[0], windows, depth + 1),
+                            build_tile(ui, layout, tiles[1], windows, depth + 1),
+                        ],
+                    },
+                    &TileLayout::HorizontalTiles { splitter, tiles } => {
+                        TileContent::HorizontalTiles {
+                            splitter,
+                            tiles: [
+                                build_tile(ui, layout, tiles[0], windows, depth + 1),
+                                build_tile(ui, layout, tiles[1], windows, depth + 1),
+                            ],
+                        }
+                    }
+                },
+                _ => TileContent::Empty,
+            };
+
+            TileBuilder::new(WidgetBuilder::new())
+                .with_content(content)
+                .build(&mut ui.build_ctx())
+        }
+
+        // Gather every window of the manager and detach auto-hide windows first, so they could be
+        // moved anywhere.
+        let old_root_tile = self.root_tile(ui);
+        let mut windows = Vec::new();
+        if let Some(old_root_tile) = old_root_tile {
+            collect_windows(ui, old_root_tile, &mut windows);
+        }
+        windows.extend(self.floating_windows.borrow_mut().drain(..));
+        while let Some(entry) = self.auto_hide_windows.first().cloned() {
+            self.remove_auto_hide_window(ui, entry.window);
+            windows.push(entry.window);
+        }
+
+        if !layout.tiles.is_empty() {
+            let new_root_tile = build_tile(ui, layout, 0, &mut windows, 0);
+            self.attach(ui, new_root_tile);
+        }
+
+        if let Some(old_root_tile) = old_root_tile {
+            ui.send_message(WidgetMessage::remove(
+                old_root_tile,
+                MessageDirection::ToWidget,
+            ));
+        }
+
+        for floating_window in layout.floating_windows.iter() {
+            if let Some(window) = find_window(ui, &mut windows, &floating_window.name) {
+                self.detach(ui, window);
+                ui.send_message(WidgetMessage::desired_position(
+                    window,
+                    MessageDirection::ToWidget,
+                    floating_window.position,
+                ));
+                ui.send_message(WidgetMessage::width(
+                    window,
+                    MessageDirection::ToWidget,
+                    floating_window.size.x,
+                ));
+                ui.send_message(WidgetMessage::height(
+                    window,
+                    MessageDirection::ToWidget,
+                    floating_window.size.y,
+                ));
+                ui.send_message(WindowMessage::can_resize(
+                    window,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+                self.floating_windows.borrow_mut().push(window);
+            }
+        }
+
+        for auto_hide_window in layout.auto_hide_windows.iter() {
+            if let Some(window) = find_window(ui, &mut windows, &auto_hide_window.name) {
+                self.attach(ui, window);
+                self.add_auto_hide_window(ui, window, auto_hide_window.side);
+            }
+        }
+
+        // Windows that are missing in the layout become floating.
+        for window in windows {
+            self.detach(ui, window);
+            ui.send_message(WindowMessage::can_resize(
+                window,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            self.floating_windows.borrow_mut().push(window);
+        }
+    }
+}
+
 pub struct DockingManagerBuilder {
     widget_builder: WidgetBuilder,
     floating_windows: Vec<Handle<UiNode>>,
@@ -802,9 +1476,28 @@ impl DockingManagerBuilder {
     }
 
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let mut make_bar = |orientation| {
+            StackPanelBuilder::new(WidgetBuilder::new())
+                .with_orientation(orientation)
+                .build(ctx)
+        };
+        let auto_hide_bars = [
+            make_bar(Orientation::Vertical),
+            make_bar(Orientation::Vertical),
+            make_bar(Orientation::Horizontal),
+            make_bar(Orientation::Horizontal),
+        ];
+
         let docking_manager = DockingManager {
-            widget: self.widget_builder.with_preview_messages(true).build(),
+            widget: self
+                .widget_builder
+                .with_preview_messages(true)
+                .with_children(auto_hide_bars.iter().cloned())
+                .build(),
             floating_windows: RefCell::new(self.floating_windows),
+            auto_hide_bars,
+            auto_hide_windows: Default::default(),
+            active_auto_hide_window: Default::default(),
         };
 
         ctx.add_node(UiNode::new(docking_manager))
@@ -940,3 +1633,123 @@ impl TileBuilder {
         ctx.add_node(UiNode::new(tile))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, pool::Handle},
+        dock::{
+            AutoHideWindowLayout, DockSide, DockingManager, DockingManagerBuilder,
+            DockingManagerMessage, FloatingWindowLayout, Layout, TileBuilder, TileContent,
+            TileLayout,
+        },
+        message::MessageDirection,
+        widget::WidgetBuilder,
+        window::WindowBuilder,
+        UiNode, UserInterface,
+    };
+
+    fn layout(ui: &UserInterface, docking_manager: Handle<UiNode>) -> Layout {
+        ui.node(docking_manager)
+            .cast::<DockingManager>()
+            .unwrap()
+            .layout(ui)
+    }
+
+    #[test]
+    fn test_layout_save_restore() {
+        let mut ui = UserInterface::new(Vector2::new(1000.0, 1000.0));
+
+        let ctx = &mut ui.build_ctx();
+        let a = WindowBuilder::new(WidgetBuilder::new().with_name("A")).build(ctx);
+        let b = WindowBuilder::new(WidgetBuilder::new().with_name("B")).build(ctx);
+        let c = WindowBuilder::new(WidgetBuilder::new().with_name("C")).build(ctx);
+        let tile_a = TileBuilder::new(WidgetBuilder::new())
+            .with_content(TileContent::Window(a))
+            .build(ctx);
+        let tile_b = TileBuilder::new(WidgetBuilder::new())
+            .with_content(TileContent::Window(b))
+            .build(ctx);
+        let root_tile = TileBuilder::new(WidgetBuilder::new())
+            .with_content(TileContent::HorizontalTiles {
+                splitter: 0.3,
+                tiles: [tile_a, tile_b],
+            })
+            .build(ctx);
+        let docking_manager =
+            DockingManagerBuilder::new(WidgetBuilder::new().with_child(root_tile))
+                .with_floating_windows(vec![c])
+                .build(ctx);
+
+        let saved = layout(&ui, docking_manager);
+        assert_eq!(
+            saved.tiles,
+            vec![
+                TileLayout::HorizontalTiles {
+                    splitter: 0.3,
+                    tiles: [1, 2]
+                },
+                TileLayout::Window {
+                    name: "A".to_owned()
+                },
+                TileLayout::Window {
+                    name: "B".to_owned()
+                },
+            ]
+        );
+        assert_eq!(saved.floating_windows.len(), 1);
+        assert_eq!(saved.floating_windows[0].name, "C");
+
+        let new_layout = Layout {
+            tiles: vec![
+                TileLayout::VerticalTiles {
+                    splitter: 0.6,
+                    tiles: [1, 2],
+                },
+                TileLayout::Window {
+                    name: "C".to_owned(),
+                },
+                TileLayout::Window {
+                    name: "A".to_owned(),
+                },
+            ],
+            floating_windows: vec![FloatingWindowLayout {
+                name: "B".to_owned(),
+                position: Vector2::new(10.0, 20.0),
+                size: Vector2::new(100.0, 200.0),
+            }],
+            auto_hide_windows: vec![],
+        };
+        ui.send_message(DockingManagerMessage::apply_layout(
+            docking_manager,
+            MessageDirection::ToWidget,
+            new_layout.clone(),
+        ));
+        while ui.poll_message().is_some() {}
+
+        let restored = layout(&ui, docking_manager);
+        assert_eq!(restored.tiles, new_layout.tiles);
+        assert_eq!(restored.floating_windows.len(), 1);
+        assert_eq!(restored.floating_windows[0].name, "B");
+        assert!(restored.auto_hide_windows.is_empty());
+
+        ui.send_message(DockingManagerMessage::unpin(
+            docking_manager,
+            MessageDirection::ToWidget,
+            b,
+            DockSide::Right,
+        ));
+        while ui.poll_message().is_some() {}
+
+        let unpinned = layout(&ui, docking_manager);
+        assert!(unpinned.floating_windows.is_empty());
+        assert_eq!(
+            unpinned.auto_hide_windows,
+            vec![AutoHideWindowLayout {
+                name: "B".to_owned(),
+                side: DockSide::Right
+            }]
+        );
+        assert_eq!(ui.node(b).parent(), docking_manager);
+    }
+}
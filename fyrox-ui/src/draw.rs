@@ -1,16 +1,22 @@
 use crate::{
     brush::Brush,
     core::{
-        algebra::Vector2,
+        algebra::{Vector2, Vector3},
         color::Color,
-        math::{self, Rect, TriangleDefinition},
+        math::{self, triangulator::triangulate, Rect, TriangleDefinition},
     },
+    drawing_canvas::Path,
     formatted_text::FormattedText,
     ttf::SharedFont,
     Thickness,
 };
 use fyrox_core::algebra::{Matrix3, Point2};
-use std::{any::Any, ops::Range, sync::Arc};
+use std::{
+    any::Any,
+    fmt::{Debug, Formatter},
+    ops::Range,
+    sync::Arc,
+};
 
 #[derive(Clone)]
 #[repr(C)]
@@ -58,12 +64,26 @@ pub enum CommandTexture {
     Font(SharedFont),
 }
 
+impl PartialEq for CommandTexture {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CommandTexture::None, CommandTexture::None) => true,
+            (CommandTexture::Texture(a), CommandTexture::Texture(b)) => a == b,
+            (CommandTexture::Font(a), CommandTexture::Font(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
 /// A set of triangles that will be used for clipping.
 #[derive(Clone)]
 pub struct ClippingGeometry {
     pub vertex_buffer: Vec<Vertex>,
     pub triangle_buffer: Vec<TriangleDefinition>,
     pub transform_stack: TransformStack,
+    /// Amount of shapes in the geometry. Triangles of a single shape must not overlap, a pixel
+    /// is drawn only if it is covered by all shapes.
+    pub shape_count: u32,
 }
 
 impl Draw for ClippingGeometry {
@@ -90,6 +110,32 @@ impl Draw for ClippingGeometry {
 }
 
 impl ClippingGeometry {
+    /// Creates empty geometry of a single shape, vertices will be transformed by given matrix.
+    pub fn new(transform: Matrix3<f32>) -> Self {
+        let mut transform_stack = TransformStack::default();
+        transform_stack.push(transform);
+        Self {
+            vertex_buffer: Default::default(),
+            triangle_buffer: Default::default(),
+            transform_stack,
+            shape_count: 1,
+        }
+    }
+
+    /// Appends triangles of other geometry, so resulting geometry clips by intersection of both.
+    pub fn append(&mut self, other: &ClippingGeometry) {
+        let offset = self.vertex_buffer.len() as u32;
+        self.vertex_buffer
+            .extend(other.vertex_buffer.iter().cloned());
+        self.triangle_buffer.extend(
+            other
+                .triangle_buffer
+                .iter()
+                .map(|t| TriangleDefinition([t[0] + offset, t[1] + offset, t[2] + offset])),
+        );
+        self.shape_count += other.shape_count;
+    }
+
     pub fn is_contains_point(&self, pos: Vector2<f32>) -> bool {
         for triangle in self.triangle_buffer.iter() {
             if let Some((va, vb, vc)) = self.triangle_points(triangle) {
@@ -113,6 +159,60 @@ impl ClippingGeometry {
     }
 }
 
+/// Shape that clips a widget and its children, see [`crate::widget::Widget::clip_shape`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipShape {
+    /// Bounds of the widget with rounded corners.
+    RoundedRect { corner_radius: f32 },
+    /// Arbitrary closed path in local coordinates of the widget, every sub-path is treated as a
+    /// separate polygon.
+    Path(Path),
+}
+
+impl ClipShape {
+    /// Builds screen-space geometry of the shape for a widget with given local bounds and
+    /// visual transform.
+    pub fn make_geometry(&self, bounds: &Rect<f32>, transform: Matrix3<f32>) -> ClippingGeometry {
+        let mut geometry = ClippingGeometry::new(transform);
+
+        let rounded_rect;
+        let path = match self {
+            ClipShape::RoundedRect { corner_radius } => {
+                rounded_rect = Path::rounded_rect(*bounds, *corner_radius);
+                &rounded_rect
+            }
+            ClipShape::Path(path) => path,
+        };
+
+        let mut triangles = Vec::new();
+        for mut polyline in path.flatten() {
+            // Adjacent segments of a path may produce duplicated points, they must be removed to
+            // keep the polygon valid for triangulation.
+            polyline
+                .points
+                .dedup_by(|a, b| (*a - *b).norm_squared() <= f32::EPSILON);
+            if polyline.points.len() < 3 {
+                continue;
+            }
+            let vertices = polyline
+                .points
+                .iter()
+                .map(|p| Vector3::new(p.x, p.y, 0.0))
+                .collect::<Vec<_>>();
+            triangulate(&vertices, &mut triangles);
+            for triangle in triangles.iter() {
+                geometry.push_triangle_filled([
+                    polyline.points[triangle[0]],
+                    polyline.points[triangle[1]],
+                    polyline.points[triangle[2]],
+                ]);
+            }
+        }
+
+        geometry
+    }
+}
+
 #[derive(Clone)]
 pub struct Command {
     /// Clipping bounds, should be used for scissor-test. Screen-space.
@@ -128,6 +228,95 @@ pub struct Command {
     pub clipping_geometry: Option<ClippingGeometry>,
 }
 
+/// Geometry emitted by a widget, it is used to draw the widget until its visual is invalidated
+/// (see [`crate::widget::Widget::invalidate_visual`]). Vertices are stored in screen-space, so
+/// the cache is valid only while visual transform of the widget stays the same. Opacity and
+/// clipping geometry of the parents are taken from the drawing context when the cache is drawn.
+#[derive(Clone, Default)]
+pub struct GeometryCache {
+    vertices: Vec<Vertex>,
+    triangles: Vec<TriangleDefinition>,
+    commands: Vec<CachedCommand>,
+}
+
+impl Debug for GeometryCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeometryCache")
+            .field("vertices", &self.vertices.len())
+            .field("triangles", &self.triangles.len())
+            .field("commands", &self.commands.len())
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+struct CachedCommand {
+    clip_bounds: Rect<f32>,
+    bounds: Rect<f32>,
+    brush: Brush,
+    texture: CommandTexture,
+    triangles: Range<usize>,
+    /// Clipping geometry that was passed explicitly to [`DrawingContext::commit`].
+    clipping_geometry: Option<ClippingGeometry>,
+}
+
+/// Position in the buffers of a drawing context, see [`DrawingContext::mark`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DrawingMark {
+    vertex: usize,
+    triangle: usize,
+    command: usize,
+}
+
+/// Statistics of a drawing context, it is reset by [`DrawingContext::clear`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawingStatistics {
+    /// Amount of widgets drawn using their cached geometry.
+    pub geometry_cache_hits: usize,
+    /// Amount of widgets that had to emit their geometry again.
+    pub geometry_cache_misses: usize,
+    /// Amount of committed commands.
+    pub commands: usize,
+    /// Amount of batches (draw calls) after merging commands with identical state.
+    pub batches: usize,
+}
+
+impl DrawingStatistics {
+    /// Returns a fraction of widgets drawn using cached geometry, `[0; 1]` range.
+    pub fn geometry_cache_hit_rate(&self) -> f32 {
+        let total = self.geometry_cache_hits + self.geometry_cache_misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.geometry_cache_hits as f32 / total as f32
+        }
+    }
+}
+
+fn is_rect_inside(inner: &Rect<f32>, outer: &Rect<f32>) -> bool {
+    let inner_max = inner.right_bottom_corner();
+    let outer_max = outer.right_bottom_corner();
+    inner.position.x >= outer.position.x
+        && inner.position.y >= outer.position.y
+        && inner_max.x <= outer_max.x
+        && inner_max.y <= outer_max.y
+}
+
+/// Checks whether the command could be drawn in the same draw call with the batch. Bounds of
+/// the geometry are used by non-solid brushes, so only solid brushes could be merged. Scissor
+/// test of the batch must not cut the command, and the command itself must not rely on its own
+/// scissor test.
+fn can_merge(batch: &Command, command: &Command) -> bool {
+    batch.triangles.end == command.triangles.start
+        && matches!((&batch.brush, &command.brush), (Brush::Solid(a), Brush::Solid(b)) if a == b)
+        && batch.texture == command.texture
+        && batch.opacity == command.opacity
+        && batch.clipping_geometry.is_none()
+        && command.clipping_geometry.is_none()
+        && is_rect_inside(&command.bounds, &batch.clip_bounds)
+        && is_rect_inside(&command.bounds, &command.clip_bounds)
+}
+
 pub trait Draw {
     fn push_vertex(&mut self, pos: Vector2<f32>, tex_coord: Vector2<f32>) {
         self.push_vertex_raw(Vertex::new(pos, tex_coord))
@@ -380,8 +569,15 @@ pub struct DrawingContext {
     vertex_buffer: Vec<Vertex>,
     triangle_buffer: Vec<TriangleDefinition>,
     command_buffer: Vec<Command>,
+    /// Commands with identical state merged together, see [`DrawingContext::get_batches`].
+    batch_buffer: Vec<Command>,
+    /// Whether a command in the command buffer had explicit clipping geometry.
+    explicit_clipping: Vec<bool>,
+    geometry_cache_hits: usize,
+    geometry_cache_misses: usize,
     pub transform_stack: TransformStack,
     opacity_stack: Vec<f32>,
+    clipping_stack: Vec<ClippingGeometry>,
     triangles_to_commit: usize,
 }
 
@@ -429,8 +625,13 @@ impl DrawingContext {
             vertex_buffer: Vec::new(),
             triangle_buffer: Vec::new(),
             command_buffer: Vec::new(),
+            batch_buffer: Vec::new(),
+            explicit_clipping: Vec::new(),
+            geometry_cache_hits: 0,
+            geometry_cache_misses: 0,
             triangles_to_commit: 0,
             opacity_stack: vec![1.0],
+            clipping_stack: Default::default(),
             transform_stack: Default::default(),
         }
     }
@@ -440,8 +641,13 @@ impl DrawingContext {
         self.vertex_buffer.clear();
         self.triangle_buffer.clear();
         self.command_buffer.clear();
+        self.batch_buffer.clear();
+        self.explicit_clipping.clear();
+        self.geometry_cache_hits = 0;
+        self.geometry_cache_misses = 0;
         self.opacity_stack.clear();
         self.opacity_stack.push(1.0);
+        self.clipping_stack.clear();
         self.triangles_to_commit = 0;
     }
 
@@ -460,6 +666,125 @@ impl DrawingContext {
         &self.command_buffer
     }
 
+    /// Returns committed commands, where consecutive commands with identical state (brush, texture,
+    /// opacity, clipping) are merged together. Renderers should use batches instead of commands to
+    /// reduce the amount of draw calls, commands are still used for precise hit testing.
+    #[inline]
+    pub fn get_batches(&self) -> &[Command] {
+        &self.batch_buffer
+    }
+
+    pub fn statistics(&self) -> DrawingStatistics {
+        DrawingStatistics {
+            geometry_cache_hits: self.geometry_cache_hits,
+            geometry_cache_misses: self.geometry_cache_misses,
+            commands: self.command_buffer.len(),
+            batches: self.batch_buffer.len(),
+        }
+    }
+
+    /// Returns current position in the buffers, it should be used with [`Self::capture_geometry`].
+    pub fn mark(&self) -> DrawingMark {
+        DrawingMark {
+            vertex: self.vertex_buffer.len(),
+            triangle: self.triangle_buffer.len(),
+            command: self.command_buffer.len(),
+        }
+    }
+
+    /// Copies geometry that was committed since the given mark. Returns `None` if there are
+    /// uncommitted triangles, such geometry cannot be cached.
+    pub fn capture_geometry(&mut self, mark: DrawingMark) -> Option<GeometryCache> {
+        self.geometry_cache_misses += 1;
+
+        if self.triangles_to_commit > 0 {
+            return None;
+        }
+
+        Some(GeometryCache {
+            vertices: self.vertex_buffer[mark.vertex..].to_vec(),
+            triangles: self.triangle_buffer[mark.triangle..]
+                .iter()
+                .map(|triangle| {
+                    let offset = mark.vertex as u32;
+                    TriangleDefinition([
+                        triangle[0] - offset,
+                        triangle[1] - offset,
+                        triangle[2] - offset,
+                    ])
+                })
+                .collect(),
+            commands: self.command_buffer[mark.command..]
+                .iter()
+                .zip(&self.explicit_clipping[mark.command..])
+                .map(|(command, explicit)| CachedCommand {
+                    clip_bounds: command.clip_bounds,
+                    bounds: command.bounds,
+                    brush: command.brush.clone(),
+                    texture: command.texture.clone(),
+                    triangles: (command.triangles.start - mark.triangle)
+                        ..(command.triangles.end - mark.triangle),
+                    clipping_geometry: if *explicit {
+                        command.clipping_geometry.clone()
+                    } else {
+                        None
+                    },
+                })
+                .collect(),
+        })
+    }
+
+    /// Draws cached geometry using current opacity and clipping geometry.
+    pub fn draw_cached_geometry(&mut self, cache: &GeometryCache) {
+        self.geometry_cache_hits += 1;
+
+        let vertex_offset = self.vertex_buffer.len() as u32;
+        let triangle_offset = self.triangle_buffer.len();
+
+        self.vertex_buffer.extend_from_slice(&cache.vertices);
+        self.triangle_buffer
+            .extend(cache.triangles.iter().map(|triangle| {
+                TriangleDefinition([
+                    triangle[0] + vertex_offset,
+                    triangle[1] + vertex_offset,
+                    triangle[2] + vertex_offset,
+                ])
+            }));
+
+        let opacity = *self.opacity_stack.last().unwrap();
+        for command in cache.commands.iter() {
+            let explicit = command.clipping_geometry.is_some();
+            self.push_command(
+                Command {
+                    clip_bounds: command.clip_bounds,
+                    bounds: command.bounds,
+                    brush: command.brush.clone(),
+                    texture: command.texture.clone(),
+                    triangles: (command.triangles.start + triangle_offset)
+                        ..(command.triangles.end + triangle_offset),
+                    opacity,
+                    clipping_geometry: command
+                        .clipping_geometry
+                        .clone()
+                        .or_else(|| self.clipping_stack.last().cloned()),
+                },
+                explicit,
+            );
+        }
+    }
+
+    fn push_command(&mut self, command: Command, explicit_clipping: bool) {
+        match self.batch_buffer.last_mut() {
+            Some(batch) if can_merge(batch, &command) => {
+                batch.triangles.end = command.triangles.end;
+                batch.bounds.extend_to_contain(command.bounds);
+            }
+            _ => self.batch_buffer.push(command.clone()),
+        }
+        self.command_buffer.push(command);
+        self.explicit_clipping.push(explicit_clipping);
+    }
+
     pub fn push_opacity(&mut self, opacity: f32) {
         self.opacity_stack.push(opacity);
     }
@@ -468,6 +793,21 @@ impl DrawingContext {
         self.opacity_stack.pop().unwrap();
     }
 
+    /// Pushes clipping geometry, every command committed without explicit clipping geometry
+    /// will be clipped by intersection of all geometries in the stack.
+    pub fn push_clipping_geometry(&mut self, mut geometry: ClippingGeometry) {
+        if let Some(top) = self.clipping_stack.last() {
+            let mut combined = top.clone();
+            combined.append(&geometry);
+            geometry = combined;
+        }
+        self.clipping_stack.push(geometry);
+    }
+
+    pub fn pop_clipping_geometry(&mut self) {
+        self.clipping_stack.pop().unwrap();
+    }
+
     pub fn triangle_points(
         &self,
         triangle: &TriangleDefinition,
@@ -522,15 +862,20 @@ impl DrawingContext {
             let bounds = self.bounds_of(triangles.clone());
 
             let opacity = *self.opacity_stack.last().unwrap();
-            self.command_buffer.push(Command {
-                clip_bounds,
-                bounds,
-                brush,
-                texture,
-                triangles,
-                opacity,
-                clipping_geometry,
-            });
+            let explicit_clipping = clipping_geometry.is_some();
+            self.push_command(
+                Command {
+                    clip_bounds,
+                    bounds,
+                    brush,
+                    texture,
+                    triangles,
+                    opacity,
+                    clipping_geometry: clipping_geometry
+                        .or_else(|| self.clipping_stack.last().cloned()),
+                },
+                explicit_clipping,
+            );
             self.triangles_to_commit = 0;
         }
     }
@@ -543,9 +888,55 @@ impl DrawingContext {
     ) {
         let font = formatted_text.get_font();
 
-        // Draw shadow, if any.
-        if formatted_text.shadow {
-            for element in formatted_text.get_glyphs() {
+        // Glyphs of fallback fonts are stored in atlases of these fonts, so glyphs have to be
+        // drawn in a separate command per font.
+        let mut fonts = vec![(font.clone(), font.0.lock().is_color())];
+        fonts.extend(font.0.lock().fallbacks().iter().map(|fallback| {
+            let is_color = fallback.0.try_lock().map_or(false, |f| f.is_color());
+            (fallback.clone(), is_color)
+        }));
+
+        for (font_index, (font, is_color)) in fonts.into_iter().enumerate() {
+            let glyphs = || {
+                formatted_text
+                    .get_glyphs()
+                    .iter()
+                    .filter(move |glyph| glyph.font_index() as usize == font_index)
+            };
+
+            if glyphs().next().is_none() {
+                continue;
+            }
+
+            // Draw shadow, if any. Color glyphs do not have shadows.
+            if formatted_text.shadow && !is_color {
+                for element in glyphs() {
+                    let bounds = element.get_bounds();
+
+                    let final_bounds = Rect::new(
+                        position.x + bounds.x(),
+                        position.y + bounds.y(),
+                        bounds.w(),
+                        bounds.h(),
+                    )
+                    .inflate(
+                        formatted_text.shadow_dilation,
+                        formatted_text.shadow_dilation,
+                    )
+                    .translate(formatted_text.shadow_offset);
+
+                    self.push_rect_filled(&final_bounds, Some(element.get_tex_coords()));
+                }
+
+                self.commit(
+                    clip_bounds,
+                    formatted_text.shadow_brush.clone(),
+                    CommandTexture::Font(font.clone()),
+                    None,
+                )
+            }
+
+            for element in glyphs() {
                 let bounds = element.get_bounds();
 
                 let final_bounds = Rect::new(
@@ -553,42 +944,123 @@ impl DrawingContext {
                     position.y + bounds.y(),
                     bounds.w(),
                     bounds.h(),
-                )
-                .inflate(
-                    formatted_text.shadow_dilation,
-                    formatted_text.shadow_dilation,
-                )
-                .translate(formatted_text.shadow_offset);
+                );
 
                 self.push_rect_filled(&final_bounds, Some(element.get_tex_coords()));
             }
 
             self.commit(
                 clip_bounds,
-                formatted_text.shadow_brush.clone(),
-                CommandTexture::Font(font.clone()),
+                // Color glyphs must keep their own colors.
+                if is_color {
+                    Brush::Solid(Color::WHITE)
+                } else {
+                    formatted_text.brush()
+                },
+                CommandTexture::Font(font),
                 None,
             )
         }
+    }
+}
 
-        for element in formatted_text.get_glyphs() {
-            let bounds = element.get_bounds();
+#[cfg(test)]
+mod test {
+    use crate::{
+        brush::Brush,
+        core::{color::Color, math::Rect},
+        draw::{CommandTexture, Draw, DrawingContext},
+    };
+
+    fn push_rect(ctx: &mut DrawingContext, rect: Rect<f32>, clip_bounds: Rect<f32>, color: Color) {
+        ctx.push_rect_filled(&rect, None);
+        ctx.commit(clip_bounds, Brush::Solid(color), CommandTexture::None, None);
+    }
 
-            let final_bounds = Rect::new(
-                position.x + bounds.x(),
-                position.y + bounds.y(),
-                bounds.w(),
-                bounds.h(),
-            );
+    #[test]
+    fn test_command_batching() {
+        let screen = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let mut ctx = DrawingContext::new();
+        push_rect(
+            &mut ctx,
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+            screen,
+            Color::RED,
+        );
+        push_rect(
+            &mut ctx,
+            Rect::new(10.0, 0.0, 10.0, 10.0),
+            screen,
+            Color::RED,
+        );
+        // Own scissor test of the command does not cut its geometry.
+        push_rect(
+            &mut ctx,
+            Rect::new(20.0, 0.0, 10.0, 10.0),
+            Rect::new(20.0, 0.0, 10.0, 10.0),
+            Color::RED,
+        );
+        // Different brush.
+        push_rect(
+            &mut ctx,
+            Rect::new(30.0, 0.0, 10.0, 10.0),
+            screen,
+            Color::GREEN,
+        );
+        // Geometry relies on the scissor test.
+        push_rect(
+            &mut ctx,
+            Rect::new(40.0, 0.0, 10.0, 10.0),
+            Rect::new(40.0, 0.0, 5.0, 5.0),
+            Color::GREEN,
+        );
 
-            self.push_rect_filled(&final_bounds, Some(element.get_tex_coords()));
-        }
+        let statistics = ctx.statistics();
+        assert_eq!(statistics.commands, 5);
+        assert_eq!(statistics.batches, 3);
+        let batches = ctx.get_batches();
+        assert_eq!(batches[0].triangles, 0..6);
+        assert_eq!(batches[1].triangles, 6..8);
+        assert_eq!(batches[2].triangles, 8..10);
+    }
+
+    #[test]
+    fn test_geometry_cache() {
+        let screen = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let mut ctx = DrawingContext::new();
+        push_rect(
+            &mut ctx,
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+            screen,
+            Color::RED,
+        );
 
-        self.commit(
-            clip_bounds,
-            formatted_text.brush(),
-            CommandTexture::Font(font),
-            None,
-        )
+        let mark = ctx.mark();
+        push_rect(
+            &mut ctx,
+            Rect::new(10.0, 0.0, 10.0, 10.0),
+            screen,
+            Color::GREEN,
+        );
+        let cache = ctx.capture_geometry(mark).unwrap();
+        let vertices = ctx.get_vertices()[mark.vertex..]
+            .iter()
+            .map(|v| v.pos)
+            .collect::<Vec<_>>();
+
+        ctx.clear();
+        ctx.push_opacity(0.5);
+        ctx.draw_cached_geometry(&cache);
+
+        assert_eq!(
+            ctx.get_vertices().iter().map(|v| v.pos).collect::<Vec<_>>(),
+            vertices
+        );
+        assert_eq!(ctx.get_triangles()[0].0, [0, 1, 2]);
+        let command = &ctx.get_commands()[0];
+        assert_eq!(command.triangles, 0..2);
+        assert_eq!(command.opacity, 0.5);
+        assert_eq!(command.brush, Brush::Solid(Color::GREEN));
+        assert_eq!(ctx.statistics().geometry_cache_hits, 1);
     }
 }
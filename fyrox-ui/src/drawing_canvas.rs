@@ -0,0 +1,709 @@
+//! Drawing canvas is a widget with retained-mode vector graphics API. It keeps a list of
+//! [`Shape`]s (paths with optional fill and stroke) and draws them in order, so custom charts,
+//! minimaps or graph views can be built without implementing [`Control::draw`] manually.
+//!
+//! Shapes are hit-testable: see [`DrawingCanvas::hit_test`], also the canvas sends
+//! [`DrawingCanvasMessage::ShapeClicked`] when user clicks on a shape.
+
+use crate::{
+    brush::Brush,
+    core::{
+        algebra::{Vector2, Vector3},
+        math::{self, triangulator::triangulate, Rect},
+        pool::Handle,
+    },
+    define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext},
+    message::{MessageDirection, UiMessage},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, UiNode, UserInterface,
+};
+use std::{
+    any::{Any, TypeId},
+    f32::consts::PI,
+    ops::{Deref, DerefMut},
+};
+
+/// Amount of line segments that is used to approximate bezier curves.
+const CURVE_SEGMENTS: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    /// Starts new sub-path at given point.
+    MoveTo(Vector2<f32>),
+    /// Straight line from current point to given point.
+    LineTo(Vector2<f32>),
+    /// Circular arc, angles are in radians. If current point does not match the start of the arc,
+    /// a straight line will connect them.
+    Arc {
+        center: Vector2<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// Quadratic bezier curve from current point.
+    QuadraticTo {
+        control: Vector2<f32>,
+        end: Vector2<f32>,
+    },
+    /// Cubic bezier curve from current point.
+    CubicTo {
+        control1: Vector2<f32>,
+        control2: Vector2<f32>,
+        end: Vector2<f32>,
+    },
+    /// Closes current sub-path with a straight line to its first point.
+    Close,
+}
+
+/// A polyline that approximates a sub-path.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Polyline {
+    pub points: Vec<Vector2<f32>>,
+    pub closed: bool,
+}
+
+/// A sequence of segments that forms one or more sub-paths.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Path {
+    pub segments: Vec<PathSegment>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(mut self, point: Vector2<f32>) -> Self {
+        self.segments.push(PathSegment::MoveTo(point));
+        self
+    }
+
+    pub fn line_to(mut self, point: Vector2<f32>) -> Self {
+        self.segments.push(PathSegment::LineTo(point));
+        self
+    }
+
+    pub fn arc(
+        mut self,
+        center: Vector2<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    ) -> Self {
+        self.segments.push(PathSegment::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        });
+        self
+    }
+
+    pub fn quadratic_to(mut self, control: Vector2<f32>, end: Vector2<f32>) -> Self {
+        self.segments
+            .push(PathSegment::QuadraticTo { control, end });
+        self
+    }
+
+    pub fn cubic_to(
+        mut self,
+        control1: Vector2<f32>,
+        control2: Vector2<f32>,
+        end: Vector2<f32>,
+    ) -> Self {
+        self.segments.push(PathSegment::CubicTo {
+            control1,
+            control2,
+            end,
+        });
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    /// Creates closed rectangular path.
+    pub fn rect(rect: Rect<f32>) -> Self {
+        Self::new()
+            .move_to(rect.left_top_corner())
+            .line_to(rect.right_top_corner())
+            .line_to(rect.right_bottom_corner())
+            .line_to(rect.left_bottom_corner())
+            .close()
+    }
+
+    /// Creates closed rectangular path with rounded corners, radius is clamped to fit the
+    /// rectangle.
+    pub fn rounded_rect(rect: Rect<f32>, corner_radius: f32) -> Self {
+        let r = corner_radius.min(rect.w() * 0.5).min(rect.h() * 0.5);
+        if r <= f32::EPSILON {
+            return Self::rect(rect);
+        }
+        let (left, top) = (rect.x() + r, rect.y() + r);
+        let (right, bottom) = (rect.x() + rect.w() - r, rect.y() + rect.h() - r);
+        Self::new()
+            .arc(Vector2::new(left, top), r, PI, 1.5 * PI)
+            .arc(Vector2::new(right, top), r, 1.5 * PI, 2.0 * PI)
+            .arc(Vector2::new(right, bottom), r, 0.0, 0.5 * PI)
+            .arc(Vector2::new(left, bottom), r, 0.5 * PI, PI)
+            .close()
+    }
+
+    /// Creates closed circular path.
+    pub fn circle(center: Vector2<f32>, radius: f32) -> Self {
+        Self::new().arc(center, radius, 0.0, 2.0 * PI).close()
+    }
+
+    /// Creates open path that goes through given points.
+    pub fn polyline(points: &[Vector2<f32>]) -> Self {
+        let mut path = Self::new();
+        for (i, point) in points.iter().enumerate() {
+            path = if i == 0 {
+                path.move_to(*point)
+            } else {
+                path.line_to(*point)
+            };
+        }
+        path
+    }
+
+    /// Approximates the path with a set of polylines (one per sub-path).
+    pub fn flatten(&self) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut current = Polyline::default();
+
+        fn flush(current: &mut Polyline, polylines: &mut Vec<Polyline>) {
+            if current.points.len() > 1 {
+                polylines.push(std::mem::take(current));
+            } else {
+                current.points.clear();
+                current.closed = false;
+            }
+        }
+
+        for segment in self.segments.iter() {
+            match *segment {
+                PathSegment::MoveTo(point) => {
+                    flush(&mut current, &mut polylines);
+                    current.points.push(point);
+                }
+                PathSegment::LineTo(point) => current.points.push(point),
+                PathSegment::Arc {
+                    center,
+                    radius,
+                    start_angle,
+                    end_angle,
+                } => {
+                    let sweep = end_angle - start_angle;
+                    let segments = ((sweep.abs() / (PI / 16.0)).ceil() as usize).max(4);
+                    for i in 0..=segments {
+                        let angle = start_angle + sweep * (i as f32 / segments as f32);
+                        current
+                            .points
+                            .push(center + Vector2::new(angle.cos(), angle.sin()).scale(radius));
+                    }
+                }
+                PathSegment::QuadraticTo { control, end } => {
+                    let start = current.points.last().cloned().unwrap_or(control);
+                    for i in 1..=CURVE_SEGMENTS {
+                        let t = i as f32 / CURVE_SEGMENTS as f32;
+                        let k = 1.0 - t;
+                        current.points.push(
+                            start.scale(k * k) + control.scale(2.0 * k * t) + end.scale(t * t),
+                        );
+                    }
+                }
+                PathSegment::CubicTo {
+                    control1,
+                    control2,
+                    end,
+                } => {
+                    let start = current.points.last().cloned().unwrap_or(control1);
+                    for i in 1..=CURVE_SEGMENTS {
+                        let t = i as f32 / CURVE_SEGMENTS as f32;
+                        let k = 1.0 - t;
+                        current.points.push(
+                            start.scale(k * k * k)
+                                + control1.scale(3.0 * k * k * t)
+                                + control2.scale(3.0 * k * t * t)
+                                + end.scale(t * t * t),
+                        );
+                    }
+                }
+                PathSegment::Close => {
+                    current.closed = true;
+                    let first = current.points.first().cloned();
+                    // Closing segment is implicit, so drop duplicated end point (full circles,
+                    // for example) to keep the polygon valid for triangulation.
+                    if current.points.len() > 2
+                        && first
+                            .zip(current.points.last().cloned())
+                            .map_or(false, |(f, l)| (f - l).norm_squared() <= f32::EPSILON)
+                    {
+                        current.points.pop();
+                    }
+                    flush(&mut current, &mut polylines);
+                    // Next segments continue from the first point of the closed sub-path.
+                    if let Some(first) = first {
+                        current.points.push(first);
+                    }
+                }
+            }
+        }
+
+        flush(&mut current, &mut polylines);
+
+        polylines
+    }
+}
+
+/// Dash pattern of a stroke.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Dash {
+    pub length: f32,
+    pub gap: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub brush: Brush,
+    pub thickness: f32,
+    pub dash: Option<Dash>,
+}
+
+impl Stroke {
+    pub fn new(brush: Brush, thickness: f32) -> Self {
+        Self {
+            brush,
+            thickness,
+            dash: None,
+        }
+    }
+
+    pub fn with_dash(mut self, length: f32, gap: f32) -> Self {
+        self.dash = Some(Dash { length, gap });
+        self
+    }
+}
+
+/// A path with optional fill and stroke.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shape {
+    pub path: Path,
+    pub fill: Option<Brush>,
+    pub stroke: Option<Stroke>,
+    /// Allows to exclude the shape from hit testing.
+    pub hit_test_visible: bool,
+}
+
+impl Shape {
+    pub fn new(path: Path) -> Self {
+        Self {
+            path,
+            fill: None,
+            stroke: None,
+            hit_test_visible: true,
+        }
+    }
+
+    pub fn with_fill(mut self, brush: Brush) -> Self {
+        self.fill = Some(brush);
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = Some(stroke);
+        self
+    }
+
+    pub fn with_hit_test_visibility(mut self, visible: bool) -> Self {
+        self.hit_test_visible = visible;
+        self
+    }
+
+    /// Checks whether given point (in canvas coordinates) is inside of the filled area of the
+    /// shape or close enough to its stroke.
+    pub fn contains_point(&self, point: Vector2<f32>) -> bool {
+        let polylines = self.path.flatten();
+
+        if self.fill.is_some() {
+            // Even-odd rule, every sub-path is considered closed when filled.
+            let mut inside = false;
+            for polyline in polylines.iter() {
+                let points = &polyline.points;
+                let mut j = points.len() - 1;
+                for i in 0..points.len() {
+                    let (a, b) = (points[i], points[j]);
+                    if (a.y > point.y) != (b.y > point.y)
+                        && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+                    {
+                        inside = !inside;
+                    }
+                    j = i;
+                }
+            }
+            if inside {
+                return true;
+            }
+        }
+
+        if let Some(stroke) = self.stroke.as_ref() {
+            let max_distance = stroke.thickness * 0.5;
+            for polyline in polylines.iter() {
+                for (a, b) in polyline_segments(polyline) {
+                    if distance_to_segment(point, a, b) <= max_distance {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    fn bounds(&self) -> Option<Rect<f32>> {
+        let mut bounds: Option<Rect<f32>> = None;
+        for polyline in self.path.flatten() {
+            for point in polyline.points {
+                match bounds.as_mut() {
+                    Some(bounds) => bounds.push(point),
+                    None => bounds = Some(Rect::new(point.x, point.y, 0.0, 0.0)),
+                }
+            }
+        }
+        let inflation = self.stroke.as_ref().map_or(0.0, |s| s.thickness * 0.5);
+        bounds.map(|b| b.inflate(inflation, inflation))
+    }
+}
+
+fn polyline_segments(
+    polyline: &Polyline,
+) -> impl Iterator<Item = (Vector2<f32>, Vector2<f32>)> + '_ {
+    let closing = if polyline.closed {
+        polyline
+            .points
+            .last()
+            .cloned()
+            .zip(polyline.points.first().cloned())
+    } else {
+        None
+    };
+    polyline
+        .points
+        .windows(2)
+        .map(|w| (w[0], w[1]))
+        .chain(closing)
+}
+
+fn distance_to_segment(point: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let len_sqr = ab.norm_squared();
+    let t = if len_sqr > f32::EPSILON {
+        ((point - a).dot(&ab) / len_sqr).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (a + ab.scale(t) - point).norm()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawingCanvasMessage {
+    /// Replaces all shapes of the canvas.
+    ///
+    /// Direction: **To UI**.
+    Shapes(Vec<Shape>),
+    /// Adds new shape on top of others.
+    ///
+    /// Direction: **To UI**.
+    AddShape(Shape),
+    /// Removes a shape at given index.
+    ///
+    /// Direction: **To UI**.
+    RemoveShape(usize),
+    /// Sent when user clicks on a shape, contains index of topmost shape under cursor.
+    ///
+    /// Direction: **From UI**.
+    ShapeClicked(usize),
+}
+
+impl DrawingCanvasMessage {
+    define_constructor!(DrawingCanvasMessage:Shapes => fn shapes(Vec<Shape>), layout: false);
+    define_constructor!(DrawingCanvasMessage:AddShape => fn add_shape(Shape), layout: false);
+    define_constructor!(DrawingCanvasMessage:RemoveShape => fn remove_shape(usize), layout: false);
+    define_constructor!(DrawingCanvasMessage:ShapeClicked => fn shape_clicked(usize), layout: false);
+}
+
+#[derive(Clone)]
+pub struct DrawingCanvas {
+    pub widget: Widget,
+    pub shapes: Vec<Shape>,
+}
+
+crate::define_widget_deref!(DrawingCanvas);
+
+impl DrawingCanvas {
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    /// Returns index of topmost shape at given point in canvas (local) coordinates.
+    pub fn hit_test(&self, point: Vector2<f32>) -> Option<usize> {
+        self.shapes
+            .iter()
+            .rposition(|shape| shape.hit_test_visible && shape.contains_point(point))
+    }
+}
+
+impl Control for DrawingCanvas {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn measure_override(&self, _ui: &UserInterface, _available_size: Vector2<f32>) -> Vector2<f32> {
+        let mut size = Vector2::default();
+        for bounds in self.shapes.iter().filter_map(|s| s.bounds()) {
+            size = size.sup(&bounds.right_bottom_corner());
+        }
+        size
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let origin = self.widget.bounding_rect().position;
+        let mut triangles = Vec::new();
+
+        for shape in self.shapes.iter() {
+            let polylines = shape.path.flatten();
+
+            if let Some(fill) = shape.fill.as_ref() {
+                for polyline in polylines.iter() {
+                    let vertices = polyline
+                        .points
+                        .iter()
+                        .map(|p| Vector3::new(p.x, p.y, 0.0))
+                        .collect::<Vec<_>>();
+                    if vertices.len() < 3 {
+                        continue;
+                    }
+                    triangulate(&vertices, &mut triangles);
+                    for triangle in triangles.iter() {
+                        drawing_context.push_triangle_filled([
+                            origin + polyline.points[triangle[0]],
+                            origin + polyline.points[triangle[1]],
+                            origin + polyline.points[triangle[2]],
+                        ]);
+                    }
+                }
+                drawing_context.commit(
+                    self.clip_bounds(),
+                    fill.clone(),
+                    CommandTexture::None,
+                    None,
+                );
+            }
+
+            if let Some(stroke) = shape.stroke.as_ref() {
+                for polyline in polylines.iter() {
+                    match stroke.dash {
+                        Some(dash) if dash.length > 0.0 => {
+                            push_dashed(drawing_context, polyline, origin, stroke.thickness, dash)
+                        }
+                        _ => {
+                            for (a, b) in polyline_segments(polyline) {
+                                drawing_context.push_line(origin + a, origin + b, stroke.thickness);
+                            }
+                        }
+                    }
+                }
+                drawing_context.commit(
+                    self.clip_bounds(),
+                    stroke.brush.clone(),
+                    CommandTexture::None,
+                    None,
+                );
+            }
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<DrawingCanvasMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    DrawingCanvasMessage::Shapes(shapes) => {
+                        self.shapes = shapes.clone();
+                        self.invalidate_layout();
+                    }
+                    DrawingCanvasMessage::AddShape(shape) => {
+                        self.shapes.push(shape.clone());
+                        self.invalidate_layout();
+                    }
+                    &DrawingCanvasMessage::RemoveShape(index) => {
+                        if index < self.shapes.len() {
+                            self.shapes.remove(index);
+                            self.invalidate_layout();
+                        }
+                    }
+                    DrawingCanvasMessage::ShapeClicked(_) => (),
+                }
+            }
+        } else if let Some(WidgetMessage::MouseDown { pos, .. }) = message.data() {
+            if message.destination() == self.handle() {
+                let local_pos = *pos - self.screen_position();
+                if let Some(index) = self.hit_test(local_pos) {
+                    ui.send_message(DrawingCanvasMessage::shape_clicked(
+                        self.handle(),
+                        MessageDirection::FromWidget,
+                        index,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn push_dashed(
+    drawing_context: &mut DrawingContext,
+    polyline: &Polyline,
+    origin: Vector2<f32>,
+    thickness: f32,
+    dash: Dash,
+) {
+    let period = dash.length + dash.gap.max(0.0);
+    // Distance along the polyline, it is not reset on corners so the pattern stays continuous.
+    let mut distance = 0.0f32;
+    for (a, b) in polyline_segments(polyline) {
+        let length = (b - a).norm();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let dir = (b - a).scale(1.0 / length);
+        let mut t = 0.0;
+        while t < length {
+            let phase = math::wrapf(distance + t, 0.0, period);
+            if phase < dash.length {
+                let end = (t + dash.length - phase).min(length);
+                drawing_context.push_line(
+                    origin + a + dir.scale(t),
+                    origin + a + dir.scale(end),
+                    thickness,
+                );
+                t = end;
+            } else {
+                t += period - phase;
+            }
+        }
+        distance += length;
+    }
+}
+
+pub struct DrawingCanvasBuilder {
+    widget_builder: WidgetBuilder,
+    shapes: Vec<Shape>,
+}
+
+impl DrawingCanvasBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            shapes: Default::default(),
+        }
+    }
+
+    pub fn with_shapes(mut self, shapes: Vec<Shape>) -> Self {
+        self.shapes = shapes;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let canvas = DrawingCanvas {
+            widget: self.widget_builder.build(),
+            shapes: self.shapes,
+        };
+
+        ctx.add_node(UiNode::new(canvas))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        brush::Brush,
+        core::{
+            algebra::{Matrix3, Vector2},
+            color::Color,
+            math::Rect,
+        },
+        draw::ClipShape,
+        drawing_canvas::{Path, Shape, Stroke},
+    };
+
+    #[test]
+    fn test_flatten() {
+        let polylines = Path::rect(Rect::new(0.0, 0.0, 10.0, 10.0)).flatten();
+        assert_eq!(polylines.len(), 1);
+        assert!(polylines[0].closed);
+        assert_eq!(polylines[0].points.len(), 4);
+
+        let polylines = Path::new()
+            .move_to(Vector2::new(0.0, 0.0))
+            .line_to(Vector2::new(1.0, 0.0))
+            .move_to(Vector2::new(0.0, 1.0))
+            .line_to(Vector2::new(1.0, 1.0))
+            .flatten();
+        assert_eq!(polylines.len(), 2);
+        assert!(!polylines[0].closed);
+    }
+
+    #[test]
+    fn test_shape_hit_test() {
+        let filled = Shape::new(Path::rect(Rect::new(0.0, 0.0, 10.0, 10.0)))
+            .with_fill(Brush::Solid(Color::WHITE));
+        assert!(filled.contains_point(Vector2::new(5.0, 5.0)));
+        assert!(!filled.contains_point(Vector2::new(15.0, 5.0)));
+
+        let stroked = Shape::new(Path::polyline(&[
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+        ]))
+        .with_stroke(Stroke::new(Brush::Solid(Color::WHITE), 2.0));
+        assert!(stroked.contains_point(Vector2::new(5.0, 0.9)));
+        assert!(!stroked.contains_point(Vector2::new(5.0, 1.5)));
+
+        let circle = Shape::new(Path::circle(Vector2::new(0.0, 0.0), 5.0))
+            .with_fill(Brush::Solid(Color::WHITE));
+        assert!(circle.contains_point(Vector2::new(1.0, 1.0)));
+        assert!(!circle.contains_point(Vector2::new(4.5, 4.5)));
+    }
+
+    #[test]
+    fn test_clip_shape() {
+        let bounds = Rect::new(0.0, 0.0, 20.0, 20.0);
+
+        let rounded = ClipShape::RoundedRect { corner_radius: 5.0 }
+            .make_geometry(&bounds, Matrix3::new_translation(&Vector2::new(10.0, 10.0)));
+        assert_eq!(rounded.shape_count, 1);
+        assert!(rounded.is_contains_point(Vector2::new(20.0, 20.0)));
+        assert!(rounded.is_contains_point(Vector2::new(20.0, 10.5)));
+        // Cut-off corner.
+        assert!(!rounded.is_contains_point(Vector2::new(10.5, 10.5)));
+        assert!(!rounded.is_contains_point(Vector2::new(5.0, 5.0)));
+
+        let mut circle = ClipShape::Path(Path::circle(Vector2::new(10.0, 10.0), 10.0))
+            .make_geometry(&bounds, Matrix3::identity());
+        assert!(circle.is_contains_point(Vector2::new(10.0, 10.0)));
+        assert!(!circle.is_contains_point(Vector2::new(1.0, 1.0)));
+
+        circle.append(&rounded);
+        assert_eq!(circle.shape_count, 2);
+    }
+}
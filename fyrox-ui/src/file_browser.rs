@@ -2,12 +2,19 @@
 //!
 //! File selector is dialog window with file browser, it somewhat similar to standard
 //! OS file selector.
+//!
+//! Content of expanded directories is scanned on a separate thread, so huge directories
+//! do not block the user interface. The tree is refreshed automatically when file system
+//! changes.
 
 use crate::{
+    border::BorderBuilder,
     button::{ButtonBuilder, ButtonMessage},
     core::{algebra::Vector2, pool::Handle},
+    decorator::DecoratorBuilder,
     define_constructor,
     draw::DrawingContext,
+    dropdown_list::{DropdownListBuilder, DropdownListMessage},
     grid::{Column, GridBuilder, Row},
     message::{MessageDirection, OsEvent, UiMessage},
     scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
@@ -15,7 +22,7 @@ use crate::{
     text::TextBuilder,
     text_box::{TextBoxBuilder, TextCommitMode},
     tree::{Tree, TreeBuilder, TreeMessage, TreeRoot, TreeRootBuilder, TreeRootMessage},
-    widget::{Widget, WidgetBuilder},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
     window::{Window, WindowBuilder, WindowMessage, WindowTitle},
     BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Orientation, Thickness, UiNode,
     UserInterface, VerticalAlignment,
@@ -27,17 +34,20 @@ use std::{
     cell,
     cmp::Ordering,
     fmt::{Debug, Formatter},
-    fs::DirEntry,
     ops::{Deref, DerefMut},
     path::{Component, Path, PathBuf, Prefix},
     rc::Rc,
     sync::mpsc::{Receiver, Sender},
     sync::{mpsc, Arc, Mutex},
     thread,
+    time::SystemTime,
 };
 
 use crate::text::TextMessage;
-use notify::Watcher;
+use notify::{
+    event::{ModifyKind, RenameMode},
+    Watcher,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use sysinfo::{DiskExt, RefreshKind, SystemExt};
 
@@ -66,6 +76,14 @@ pub enum FileBrowserMessage {
     Add(PathBuf),
     Remove(PathBuf),
     Rescan,
+    Sort(FileBrowserSortMode),
+    /// Sent when content of a directory was scanned on a background thread. The browser
+    /// sends it to itself and then responds with the same message in
+    /// [`MessageDirection::FromWidget`] once tree items are created.
+    ScanCompleted {
+        path: PathBuf,
+        entries: Vec<FileEntry>,
+    },
 }
 
 impl FileBrowserMessage {
@@ -75,6 +93,67 @@ impl FileBrowserMessage {
     define_constructor!(FileBrowserMessage:Add => fn add(PathBuf), layout: false);
     define_constructor!(FileBrowserMessage:Remove => fn remove(PathBuf), layout: false);
     define_constructor!(FileBrowserMessage:Rescan => fn rescan(), layout: false);
+    define_constructor!(FileBrowserMessage:Sort => fn sort(FileBrowserSortMode), layout: false);
+    define_constructor!(FileBrowserMessage:ScanCompleted => fn scan_completed(path: PathBuf,
+        entries: Vec<FileEntry>), layout: false);
+}
+
+/// Order of items in a file browser, directories always go before files.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FileBrowserSortMode {
+    /// Alphabetical order, case-insensitive.
+    Name,
+    /// Most recently modified first.
+    Date,
+    /// Largest first.
+    Size,
+}
+
+impl Default for FileBrowserSortMode {
+    fn default() -> Self {
+        Self::Name
+    }
+}
+
+impl FileBrowserSortMode {
+    pub const ALL: [FileBrowserSortMode; 3] = [
+        FileBrowserSortMode::Name,
+        FileBrowserSortMode::Date,
+        FileBrowserSortMode::Size,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FileBrowserSortMode::Name => "Name",
+            FileBrowserSortMode::Date => "Date",
+            FileBrowserSortMode::Size => "Size",
+        }
+    }
+}
+
+/// File system entry gathered by a directory scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// `true` if the entry is a non-empty directory.
+    pub has_children: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileEntry {
+    pub fn new(path: PathBuf) -> Self {
+        let metadata = std::fs::metadata(&path).ok();
+        let is_dir = metadata.as_ref().map_or(false, |m| m.is_dir());
+        Self {
+            has_children: is_dir && has_children(&path),
+            size: metadata.as_ref().map_or(0, |m| m.len()),
+            modified: metadata.and_then(|m| m.modified().ok()),
+            is_dir,
+            path,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -120,6 +199,11 @@ pub struct FileBrowser {
     pub fs_receiver: Rc<Receiver<notify::Event>>,
     #[allow(clippy::type_complexity)]
     pub watcher: Rc<cell::Cell<Option<(notify::RecommendedWatcher, thread::JoinHandle<()>)>>>,
+    pub sort_mode: FileBrowserSortMode,
+    pub sort_selector: Handle<UiNode>,
+    pub breadcrumbs: Handle<UiNode>,
+    pub scan_sender: Sender<(PathBuf, Vec<FileEntry>)>,
+    pub scan_receiver: Rc<Receiver<(PathBuf, Vec<FileEntry>)>>,
 }
 
 crate::define_widget_deref!(FileBrowser);
@@ -131,6 +215,7 @@ impl FileBrowser {
             self.root.as_ref(),
             &self.path,
             self.filter.clone(),
+            self.sort_mode,
             &mut ui.build_ctx(),
         );
 
@@ -162,6 +247,22 @@ impl FileBrowser {
                 String::new(),
             ));
         }
+
+        self.rebuild_breadcrumbs(ui);
+    }
+
+    fn rebuild_breadcrumbs(&self, ui: &mut UserInterface) {
+        for &crumb in ui.node(self.breadcrumbs).children() {
+            ui.send_message(WidgetMessage::remove(crumb, MessageDirection::ToWidget));
+        }
+
+        for crumb in build_breadcrumbs(&self.path, &mut ui.build_ctx()) {
+            ui.send_message(WidgetMessage::link(
+                crumb,
+                MessageDirection::ToWidget,
+                self.breadcrumbs,
+            ));
+        }
     }
 }
 
@@ -178,6 +279,8 @@ impl Control for FileBrowser {
         node_map.resolve(&mut self.tree_root);
         node_map.resolve(&mut self.path_text);
         node_map.resolve(&mut self.scroll_viewer);
+        node_map.resolve(&mut self.sort_selector);
+        node_map.resolve(&mut self.breadcrumbs);
     }
 
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
@@ -198,6 +301,7 @@ impl Control for FileBrowser {
                                     self.root.as_ref(),
                                     &existing_path,
                                     self.filter.clone(),
+                                    self.sort_mode,
                                     &mut ui.build_ctx(),
                                 );
 
@@ -220,6 +324,8 @@ impl Control for FileBrowser {
                                 path.to_string_lossy().to_string(),
                             ));
 
+                            self.rebuild_breadcrumbs(ui);
+
                             // Path can be invalid, so we shouldn't do anything in such case.
                             if item.is_some() {
                                 // Select item of new path.
@@ -319,13 +425,89 @@ impl Control for FileBrowser {
                             ))
                         }
                     }
-                    FileBrowserMessage::Rescan => (),
+                    FileBrowserMessage::Rescan => {
+                        self.rebuild_from_root(ui);
+                    }
+                    &FileBrowserMessage::Sort(sort_mode) => {
+                        if message.direction() == MessageDirection::ToWidget
+                            && self.sort_mode != sort_mode
+                        {
+                            self.sort_mode = sort_mode;
+                            ui.send_message(DropdownListMessage::selection(
+                                self.sort_selector,
+                                MessageDirection::ToWidget,
+                                FileBrowserSortMode::ALL
+                                    .iter()
+                                    .position(|&m| m == sort_mode),
+                            ));
+                            self.rebuild_from_root(ui);
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    FileBrowserMessage::ScanCompleted { path, entries } => {
+                        if message.direction() == MessageDirection::ToWidget {
+                            let item = find_tree(self.tree_root, path, ui);
+                            // Directory could be collapsed or removed while it was scanned.
+                            let is_expanded = ui
+                                .try_get_node(item)
+                                .and_then(|n| n.cast::<Tree>())
+                                .map_or(false, |tree| tree.is_expanded);
+                            if is_expanded {
+                                let items = entries
+                                    .iter()
+                                    .map(|entry| {
+                                        build_tree_item(
+                                            &entry.path,
+                                            path,
+                                            entry.has_children,
+                                            &mut ui.build_ctx(),
+                                        )
+                                    })
+                                    .collect();
+                                ui.send_message(TreeMessage::set_items(
+                                    item,
+                                    MessageDirection::ToWidget,
+                                    items,
+                                ));
+                            }
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if ui.node(message.destination()).parent() == self.breadcrumbs {
+                if let Some(path) = ui
+                    .node(message.destination())
+                    .user_data_ref::<PathBuf>()
+                    .cloned()
+                {
+                    ui.send_message(FileBrowserMessage::path(
+                        self.handle,
+                        MessageDirection::ToWidget,
+                        path,
+                    ));
+                }
+            }
+        } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+            message.data::<DropdownListMessage>()
+        {
+            if message.destination() == self.sort_selector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(&sort_mode) = FileBrowserSortMode::ALL.get(index) {
+                    ui.send_message(FileBrowserMessage::sort(
+                        self.handle,
+                        MessageDirection::ToWidget,
+                        sort_mode,
+                    ));
                 }
             }
         } else if let Some(TextMessage::Text(txt)) = message.data::<TextMessage>() {
             if message.direction() == MessageDirection::FromWidget {
                 if message.destination() == self.path_text {
                     self.path = txt.into();
+                    self.rebuild_breadcrumbs(ui);
                 } else if message.destination() == self.file_name {
                     self.file_name_value = txt.into();
                     ui.send_message(FileBrowserMessage::path(
@@ -341,27 +523,19 @@ impl Control for FileBrowser {
             }
         } else if let Some(TreeMessage::Expand { expand, .. }) = message.data::<TreeMessage>() {
             if *expand {
-                // Look into internals of directory and build tree items.
+                // Look into internals of directory on a separate thread, tree items will be
+                // built when the scan is completed.
                 let parent_path = ui
                     .node(message.destination())
                     .user_data_ref::<PathBuf>()
                     .unwrap()
                     .clone();
-                if let Ok(dir_iter) = std::fs::read_dir(&parent_path) {
-                    let mut entries: Vec<_> = dir_iter.flatten().collect();
-                    entries.sort_unstable_by(sort_dir_entries);
-                    for entry in entries {
-                        let path = entry.path();
-                        let build = if let Some(filter) = self.filter.as_mut() {
-                            filter.0.borrow_mut().deref_mut().lock().unwrap()(&path)
-                        } else {
-                            true
-                        };
-                        if build {
-                            build_tree(message.destination(), false, &path, &parent_path, ui);
-                        }
-                    }
-                }
+                scan_directory_async(
+                    parent_path,
+                    self.filter.clone(),
+                    self.sort_mode,
+                    self.scan_sender.clone(),
+                );
             } else {
                 // Nuke everything in collapsed item. This also will free some resources
                 // and will speed up layout pass.
@@ -406,6 +580,8 @@ impl Control for FileBrowser {
                             path.to_string_lossy().to_string(),
                         ));
 
+                        self.rebuild_breadcrumbs(ui);
+
                         // Do response.
                         ui.send_message(FileBrowserMessage::path(
                             self.handle,
@@ -419,6 +595,15 @@ impl Control for FileBrowser {
     }
 
     fn update(&mut self, _dt: f32, sender: &Sender<UiMessage>) {
+        while let Ok((path, entries)) = self.scan_receiver.try_recv() {
+            let _ = sender.send(FileBrowserMessage::scan_completed(
+                self.handle,
+                MessageDirection::ToWidget,
+                path,
+                entries,
+            ));
+        }
+
         if let Ok(event) = self.fs_receiver.try_recv() {
             if event.need_rescan() {
                 let _ = sender.send(FileBrowserMessage::rescan(
@@ -426,16 +611,30 @@ impl Control for FileBrowser {
                     MessageDirection::ToWidget,
                 ));
             } else {
-                for path in event.paths.iter() {
-                    match event.kind {
-                        notify::EventKind::Remove(_) => {
+                for (i, path) in event.paths.iter().enumerate() {
+                    // Renamed entries are removed from old location and added to new one.
+                    let (removed, added) = match event.kind {
+                        notify::EventKind::Remove(_) => (true, false),
+                        notify::EventKind::Create(_) => (false, true),
+                        notify::EventKind::Modify(ModifyKind::Name(rename_mode)) => {
+                            match rename_mode {
+                                RenameMode::From => (true, false),
+                                RenameMode::To => (false, true),
+                                RenameMode::Both => (i == 0, i == 1),
+                                _ => (false, false),
+                            }
+                        }
+                        _ => (false, false),
+                    };
+                    match (removed, added) {
+                        (true, _) => {
                             let _ = sender.send(FileBrowserMessage::remove(
                                 self.handle,
                                 MessageDirection::ToWidget,
                                 path.clone(),
                             ));
                         }
-                        notify::EventKind::Create(_) => {
+                        (_, true) => {
                             let _ = sender.send(FileBrowserMessage::add(
                                 self.handle,
                                 MessageDirection::ToWidget,
@@ -473,21 +672,84 @@ fn ignore_nonexistent_sub_dirs(path: &Path) -> PathBuf {
     existing_path
 }
 
-fn sort_dir_entries(a: &DirEntry, b: &DirEntry) -> Ordering {
-    let a_is_dir = a.path().is_dir();
-    let b_is_dir = b.path().is_dir();
+fn has_children(path: &Path) -> bool {
+    path.read_dir().map_or(false, |mut f| f.next().is_some())
+}
 
-    if a_is_dir && !b_is_dir {
-        Ordering::Less
-    } else if !a_is_dir && b_is_dir {
-        Ordering::Greater
-    } else {
-        a.file_name()
-            .to_ascii_lowercase()
-            .cmp(&b.file_name().to_ascii_lowercase())
+fn compare_entries(a: &FileEntry, b: &FileEntry, sort_mode: FileBrowserSortMode) -> Ordering {
+    b.is_dir
+        .cmp(&a.is_dir)
+        .then_with(|| match sort_mode {
+            FileBrowserSortMode::Name => Ordering::Equal,
+            FileBrowserSortMode::Date => b.modified.cmp(&a.modified),
+            FileBrowserSortMode::Size => b.size.cmp(&a.size),
+        })
+        .then_with(|| {
+            let a_name = a.path.file_name().map(|n| n.to_ascii_lowercase());
+            let b_name = b.path.file_name().map(|n| n.to_ascii_lowercase());
+            a_name.cmp(&b_name)
+        })
+}
+
+/// Reads filtered and sorted content of a directory.
+fn scan_directory(
+    path: &Path,
+    filter: &mut Option<Filter>,
+    sort_mode: FileBrowserSortMode,
+) -> Vec<FileEntry> {
+    let mut entries = match std::fs::read_dir(path) {
+        Ok(dir_iter) => dir_iter
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| !filtered_out(filter, path))
+            .map(FileEntry::new)
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    entries.sort_unstable_by(|a, b| compare_entries(a, b, sort_mode));
+    entries
+}
+
+fn scan_directory_async(
+    path: PathBuf,
+    mut filter: Option<Filter>,
+    sort_mode: FileBrowserSortMode,
+    sender: Sender<(PathBuf, Vec<FileEntry>)>,
+) {
+    let scan = move || {
+        let entries = scan_directory(&path, &mut filter, sort_mode);
+        let _ = sender.send((path, entries));
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        thread::spawn(scan);
+    }
+
+    // There is no threads on WebAssembly.
+    #[cfg(target_arch = "wasm32")]
+    {
+        scan();
     }
 }
 
+/// Builds a button for every component of the path, each button stores path to its component.
+fn build_breadcrumbs(path: &Path, ctx: &mut BuildContext) -> Vec<Handle<UiNode>> {
+    let mut crumb_path = PathBuf::new();
+    path.components()
+        .map(|component| {
+            crumb_path.push(component);
+            ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .with_user_data(Rc::new(crumb_path.clone()))
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_text(&component.as_os_str().to_string_lossy())
+            .build(ctx)
+        })
+        .collect()
+}
+
 fn make_fs_watcher_event_path_relative_to_tree_root(
     root: &Option<PathBuf>,
     path: &Path,
@@ -539,15 +801,12 @@ fn find_tree<P: AsRef<Path>>(node: Handle<UiNode>, path: &P, ui: &UserInterface)
 fn build_tree_item<P: AsRef<Path>>(
     path: P,
     parent_path: P,
+    has_children: bool,
     ctx: &mut BuildContext,
 ) -> Handle<UiNode> {
-    let is_dir_empty = path
-        .as_ref()
-        .read_dir()
-        .map_or(true, |mut f| f.next().is_none());
     TreeBuilder::new(WidgetBuilder::new().with_user_data(Rc::new(path.as_ref().to_owned())))
         .with_expanded(false)
-        .with_always_show_expander(!is_dir_empty)
+        .with_always_show_expander(has_children)
         .with_content(
             TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(4.0)))
                 .with_text(
@@ -569,7 +828,8 @@ fn build_tree<P: AsRef<Path>>(
     parent_path: P,
     ui: &mut UserInterface,
 ) -> Handle<UiNode> {
-    let subtree = build_tree_item(path, parent_path, &mut ui.build_ctx());
+    let has_children = has_children(path.as_ref());
+    let subtree = build_tree_item(path, parent_path, has_children, &mut ui.build_ctx());
     insert_subtree_in_parent(ui, parent, is_parent_root, subtree);
     subtree
 }
@@ -605,6 +865,7 @@ fn build_all(
     root: Option<&PathBuf>,
     final_path: &Path,
     mut filter: Option<Filter>,
+    sort_mode: FileBrowserSortMode,
     ctx: &mut BuildContext,
 ) -> BuildResult {
     let mut dest_path = PathBuf::new();
@@ -639,7 +900,7 @@ fn build_all(
         } else {
             root.as_path()
         };
-        let item = build_tree_item(path, Path::new(""), ctx);
+        let item = build_tree_item(path, Path::new(""), has_children(path), ctx);
         root_items.push(item);
         item
     } else {
@@ -653,7 +914,7 @@ fn build_all(
                 .iter()
                 .map(|i| i.mount_point().to_string_lossy())
             {
-                let item = build_tree_item(disk.as_ref(), "", ctx);
+                let item = build_tree_item(disk.as_ref(), "", true, ctx);
 
                 let disk_letter = disk.chars().next().unwrap() as u8;
 
@@ -685,32 +946,22 @@ fn build_all(
         let next = dest_path_components.get(i + 1).map(|p| full_path.join(p));
 
         let mut new_parent = parent;
-        if let Ok(dir_iter) = std::fs::read_dir(&full_path) {
-            let mut entries: Vec<_> = dir_iter.flatten().collect();
-            entries.sort_unstable_by(sort_dir_entries);
-            for entry in entries {
-                let path = entry.path();
-                #[allow(clippy::blocks_in_if_conditions)]
-                if filter.as_mut().map_or(true, |f| {
-                    f.0.borrow_mut().deref_mut().lock().unwrap()(&path)
-                }) {
-                    let item = build_tree_item(&path, &full_path, ctx);
-                    if parent.is_some() {
-                        Tree::add_item(parent, item, ctx);
-                    } else {
-                        root_items.push(item);
-                    }
-                    if let Some(next) = next.as_ref() {
-                        if *next == path {
-                            new_parent = item;
-                        }
-                    }
-
-                    if path == dest_path {
-                        path_item = item;
-                    }
+        for entry in scan_directory(&full_path, &mut filter, sort_mode) {
+            let item = build_tree_item(&entry.path, &full_path, entry.has_children, ctx);
+            if parent.is_some() {
+                Tree::add_item(parent, item, ctx);
+            } else {
+                root_items.push(item);
+            }
+            if let Some(next) = next.as_ref() {
+                if *next == entry.path {
+                    new_parent = item;
                 }
             }
+
+            if entry.path == dest_path {
+                path_item = item;
+            }
         }
         parent = new_parent;
     }
@@ -727,6 +978,7 @@ pub struct FileBrowserBuilder {
     filter: Option<Filter>,
     root: Option<PathBuf>,
     mode: FileBrowserMode,
+    sort_mode: FileBrowserSortMode,
 }
 
 impl FileBrowserBuilder {
@@ -737,6 +989,7 @@ impl FileBrowserBuilder {
             filter: None,
             root: None,
             mode: FileBrowserMode::Open,
+            sort_mode: Default::default(),
         }
     }
 
@@ -755,6 +1008,11 @@ impl FileBrowserBuilder {
         self
     }
 
+    pub fn with_sort_mode(mut self, sort_mode: FileBrowserSortMode) -> Self {
+        self.sort_mode = sort_mode;
+        self
+    }
+
     /// Sets desired path which will be used to build file system tree.
     ///
     /// # Notes
@@ -786,16 +1044,19 @@ impl FileBrowserBuilder {
             self.root.as_ref(),
             self.path.as_path(),
             self.filter.clone(),
+            self.sort_mode,
             ctx,
         );
 
         let path_text;
         let tree_root;
+        let breadcrumbs;
+        let sort_selector;
         let scroll_viewer = ScrollViewerBuilder::new(
             WidgetBuilder::new()
                 .on_row(match self.mode {
-                    FileBrowserMode::Open => 1,
-                    FileBrowserMode::Save { .. } => 2,
+                    FileBrowserMode::Open => 2,
+                    FileBrowserMode::Save { .. } => 3,
                 })
                 .on_column(0),
         )
@@ -844,15 +1105,63 @@ impl FileBrowserBuilder {
                     .add_column(Column::stretch())
                     .build(ctx),
                 )
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .on_column(0)
+                            .with_child({
+                                breadcrumbs = StackPanelBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_column(0)
+                                        .with_children(build_breadcrumbs(&self.path, ctx)),
+                                )
+                                .with_orientation(Orientation::Horizontal)
+                                .build(ctx);
+                                breadcrumbs
+                            })
+                            .with_child({
+                                sort_selector = DropdownListBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_column(1)
+                                        .with_margin(Thickness::uniform(1.0)),
+                                )
+                                .with_items(
+                                    FileBrowserSortMode::ALL
+                                        .iter()
+                                        .map(|sort_mode| make_sort_option(sort_mode.name(), ctx))
+                                        .collect(),
+                                )
+                                .with_selected(
+                                    FileBrowserSortMode::ALL
+                                        .iter()
+                                        .position(|&m| m == self.sort_mode)
+                                        .unwrap_or_default(),
+                                )
+                                .with_close_on_selection(true)
+                                .build(ctx);
+                                sort_selector
+                            }),
+                    )
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(80.0))
+                    .build(ctx),
+                )
                 .with_child(scroll_viewer),
         )
         .add_column(Column::stretch())
         .add_rows(match self.mode {
             FileBrowserMode::Open => {
-                vec![Row::strict(24.0), Row::stretch()]
+                vec![Row::strict(24.0), Row::strict(24.0), Row::stretch()]
             }
             FileBrowserMode::Save { .. } => {
-                vec![Row::strict(24.0), Row::strict(24.0), Row::stretch()]
+                vec![
+                    Row::strict(24.0),
+                    Row::strict(24.0),
+                    Row::strict(24.0),
+                    Row::stretch(),
+                ]
             }
         })
         .build(ctx);
@@ -864,7 +1173,7 @@ impl FileBrowserBuilder {
                 let file_name;
                 let name_grid = GridBuilder::new(
                     WidgetBuilder::new()
-                        .on_row(1)
+                        .on_row(2)
                         .on_column(0)
                         .with_child(
                             TextBuilder::new(
@@ -908,6 +1217,7 @@ impl FileBrowserBuilder {
             _ => self.path.clone(),
         };
         let (fs_sender, fs_receiver) = mpsc::channel();
+        let (scan_sender, scan_receiver) = mpsc::channel();
         let browser = FileBrowser {
             fs_receiver: Rc::new(fs_receiver),
             widget,
@@ -931,6 +1241,11 @@ impl FileBrowserBuilder {
             root: self.root,
             file_name,
             watcher: Rc::new(cell::Cell::new(None)),
+            sort_mode: self.sort_mode,
+            sort_selector,
+            breadcrumbs,
+            scan_sender,
+            scan_receiver: Rc::new(scan_receiver),
         };
         let watcher = browser.watcher.clone();
         let filebrowser_node = UiNode::new(browser);
@@ -940,6 +1255,19 @@ impl FileBrowserBuilder {
     }
 }
 
+fn make_sort_option(name: &str, ctx: &mut BuildContext) -> Handle<UiNode> {
+    DecoratorBuilder::new(BorderBuilder::new(
+        WidgetBuilder::new().with_child(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .with_text(name)
+                .build(ctx),
+        ),
+    ))
+    .build(ctx)
+}
+
 fn setup_filebrowser_fs_watcher(
     fs_sender: mpsc::Sender<notify::Event>,
     the_path: PathBuf,
@@ -1219,13 +1547,17 @@ impl FileSelectorBuilder {
 mod test {
     use crate::{
         core::pool::Handle,
-        file_browser::{build_tree, find_tree},
+        file_browser::{build_tree, compare_entries, find_tree, FileBrowserSortMode, FileEntry},
         tree::TreeRootBuilder,
         widget::WidgetBuilder,
         UserInterface,
     };
     use fyrox_core::algebra::Vector2;
-    use std::{path::PathBuf, rc::Rc};
+    use std::{
+        path::PathBuf,
+        rc::Rc,
+        time::{Duration, SystemTime},
+    };
 
     #[test]
     fn test_find_tree() {
@@ -1247,4 +1579,38 @@ mod test {
         // https://github.com/rust-lang/rust/issues/31374
         assert_eq!(find_tree(root, &"test/path1", &ui), Handle::NONE);
     }
+
+    #[test]
+    fn test_sort_entries() {
+        let entry = |name: &str, is_dir: bool, size: u64, age: u64| FileEntry {
+            path: PathBuf::from(name),
+            is_dir,
+            has_children: false,
+            size,
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - age)),
+        };
+
+        let mut entries = vec![
+            entry("b.txt", false, 10, 5),
+            entry("A.txt", false, 1, 1),
+            entry("dir", true, 0, 10),
+            entry("c.txt", false, 100, 3),
+        ];
+
+        let names = |entries: &[FileEntry]| {
+            entries
+                .iter()
+                .map(|e| e.path.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        entries.sort_by(|a, b| compare_entries(a, b, FileBrowserSortMode::Name));
+        assert_eq!(names(&entries), ["dir", "A.txt", "b.txt", "c.txt"]);
+
+        entries.sort_by(|a, b| compare_entries(a, b, FileBrowserSortMode::Date));
+        assert_eq!(names(&entries), ["dir", "A.txt", "c.txt", "b.txt"]);
+
+        entries.sort_by(|a, b| compare_entries(a, b, FileBrowserSortMode::Size));
+        assert_eq!(names(&entries), ["dir", "c.txt", "b.txt", "A.txt"]);
+    }
 }
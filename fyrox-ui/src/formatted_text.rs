@@ -10,6 +10,7 @@ use std::ops::Range;
 pub struct TextGlyph {
     bounds: Rect<f32>,
     tex_coords: [Vector2<f32>; 4],
+    font_index: u32,
 }
 
 impl TextGlyph {
@@ -20,6 +21,12 @@ impl TextGlyph {
     pub fn get_tex_coords(&self) -> &[Vector2<f32>; 4] {
         &self.tex_coords
     }
+
+    /// Index of a font in the fallback chain of the text's font, whose atlas contains the glyph.
+    /// `0` is the font of the text itself.
+    pub fn font_index(&self) -> u32 {
+        self.font_index
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -76,13 +83,19 @@ pub enum WrapMode {
 pub struct Character {
     pub char_code: u32,
     pub glyph_index: u32,
+    /// Index of a font in the fallback chain that has the glyph, `0` is the main font.
+    pub font_index: u32,
 }
 
 impl Character {
+    /// Resolves a glyph for the character, fallback fonts of the font are used if the font
+    /// itself does not have the glyph.
     pub fn from_char_with_font(char_code: u32, font: &Font) -> Self {
+        let (font_index, glyph_index) = font.find_glyph(char_code).unwrap_or_default();
         Self {
             char_code,
-            glyph_index: font.glyph_index(char_code).unwrap_or_default() as u32,
+            glyph_index: glyph_index as u32,
+            font_index: font_index as u32,
         }
     }
 
@@ -190,7 +203,7 @@ impl FormattedText {
         let mut width = 0.0;
         let font = self.font.0.lock();
         for index in range {
-            width += font.glyph_advance(self.text[index].char_code);
+            width += font.character_advance(&self.text[index]);
         }
         width
     }
@@ -297,10 +310,7 @@ impl FormattedText {
         let mut word: Option<Word> = None;
         self.lines.clear();
         for (i, character) in text.iter().enumerate() {
-            let advance = match font.glyphs().get(character.glyph_index as usize) {
-                Some(glyph) => glyph.advance,
-                None => font.height(),
-            };
+            let advance = font.character_advance(character);
             let is_new_line =
                 character.char_code == u32::from(b'\n') || character.char_code == u32::from(b'\r');
             let new_width = current_line.width + advance;
@@ -392,11 +402,7 @@ impl FormattedText {
         // Commit rest of text.
         if current_line.begin != current_line.end {
             for character in text.iter().skip(current_line.end) {
-                let advance = match font.glyphs().get(character.glyph_index as usize) {
-                    Some(glyph) => glyph.advance,
-                    None => font.height(),
-                };
-                current_line.width += advance;
+                current_line.width += font.character_advance(character);
             }
             current_line.end = self.text.len();
             self.lines.push(current_line);
@@ -458,24 +464,31 @@ impl FormattedText {
             cursor.x = line.x_offset;
 
             for &character in text.iter().take(line.end).skip(line.begin) {
-                match font.glyphs().get(character.glyph_index as usize) {
-                    Some(glyph) => {
-                        // Insert glyph
-                        let rect = Rect::new(
-                            cursor.x + glyph.left.floor(),
-                            cursor.y + font.ascender().floor()
-                                - glyph.top.floor()
-                                - glyph.bitmap_height as f32,
-                            glyph.bitmap_width as f32,
-                            glyph.bitmap_height as f32,
-                        );
-                        let text_glyph = TextGlyph {
+                let ascender = font.ascender();
+                let glyph = font.map_character_glyph(&character, |glyph| {
+                    let rect = Rect::new(
+                        cursor.x + glyph.left.floor(),
+                        cursor.y + ascender.floor()
+                            - glyph.top.floor()
+                            - glyph.bitmap_height as f32,
+                        glyph.bitmap_width as f32,
+                        glyph.bitmap_height as f32,
+                    );
+                    (
+                        TextGlyph {
                             bounds: rect,
                             tex_coords: glyph.tex_coords,
-                        };
+                            font_index: character.font_index,
+                        },
+                        glyph.advance,
+                    )
+                });
+                match glyph {
+                    Some((text_glyph, advance)) => {
+                        // Insert glyph
                         self.glyphs.push(text_glyph);
 
-                        cursor.x += glyph.advance;
+                        cursor.x += advance;
                     }
                     None => {
                         // Insert invalid symbol
@@ -488,6 +501,7 @@ impl FormattedText {
                         self.glyphs.push(TextGlyph {
                             bounds: rect,
                             tex_coords: [Vector2::default(); 4],
+                            font_index: 0,
                         });
                         cursor.x += rect.w();
                     }
@@ -630,3 +644,47 @@ impl FormattedTextBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        formatted_text::FormattedTextBuilder,
+        ttf::{ColorGlyphImage, Font, FontBuilder, SharedFont},
+    };
+
+    #[test]
+    fn test_font_fallback() {
+        let emoji = SharedFont::new(Font::from_color_glyphs(
+            16.0,
+            vec![ColorGlyphImage {
+                unicode: 0x1F600,
+                width: 14,
+                height: 14,
+                pixels: vec![255; 14 * 14 * 4],
+            }],
+        ));
+        let font = SharedFont::new(
+            FontBuilder::new()
+                .with_fallback(emoji)
+                .build_builtin()
+                .unwrap(),
+        );
+
+        let mut text = FormattedTextBuilder::new(font)
+            .with_text("a\u{1F600}".to_owned())
+            .build();
+        let font_indices = text
+            .get_raw_text()
+            .iter()
+            .map(|c| c.font_index)
+            .collect::<Vec<_>>();
+        assert_eq!(font_indices, vec![0, 1]);
+
+        text.build();
+        let glyphs = text.get_glyphs();
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[1].font_index(), 1);
+        assert_eq!(glyphs[1].get_bounds().w(), 14.0);
+        assert_eq!(text.get_range_width(1..2), 14.0);
+    }
+}
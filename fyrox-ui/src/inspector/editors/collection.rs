@@ -2,49 +2,211 @@ use crate::{
     button::{ButtonBuilder, ButtonMessage},
     core::pool::Handle,
     define_constructor,
+    expander::ExpanderMessage,
+    formatted_text::WrapMode,
+    grid::{Column, GridBuilder, Row},
     inspector::{
         editors::{
             PropertyEditorBuildContext, PropertyEditorDefinition,
             PropertyEditorDefinitionContainer, PropertyEditorInstance,
             PropertyEditorMessageContext, PropertyEditorTranslationContext,
         },
-        make_expander_container, CollectionChanged, FieldKind, Inspector, InspectorBuilder,
-        InspectorContext, InspectorEnvironment, InspectorError, InspectorMessage, ObjectValue,
-        PropertyChanged,
+        make_expander_container, make_expander_container_with_state, CollectionChanged, FieldKind,
+        Inspector, InspectorBuilder, InspectorContext, InspectorEnvironment, InspectorError,
+        InspectorMessage, ObjectValue, PropertyChanged,
     },
     message::{MessageDirection, UiMessage},
     stack_panel::StackPanelBuilder,
+    text::{TextBuilder, TextMessage},
     widget::{Widget, WidgetBuilder, WidgetMessage},
     BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface,
     VerticalAlignment,
 };
-use fyrox_core::reflect::Reflect;
+use fyrox_core::reflect::{FieldInfo, Reflect};
 use std::{
     any::{Any, TypeId},
     fmt::Debug,
     marker::PhantomData,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
     rc::Rc,
 };
 
+/// Collections with more items than this have their items collapsed, editors of such items are
+/// created only when an item is expanded for the first time.
+pub const EXPANDED_ITEMS_LIMIT: usize = 16;
+
+/// Maximum amount of items that is shown at once, larger collections are split in pages, so the
+/// amount of widgets does not depend on the size of a collection.
+pub const ITEMS_PER_PAGE: usize = 50;
+
+fn page_count(len: usize) -> usize {
+    ((len + ITEMS_PER_PAGE - 1) / ITEMS_PER_PAGE).max(1)
+}
+
+fn page_range(page: usize, len: usize) -> Range<usize> {
+    let start = (page * ITEMS_PER_PAGE).min(len);
+    start..(start + ITEMS_PER_PAGE).min(len)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Item {
+    /// Inspector of the item, it is [`Handle::NONE`] until the item is expanded.
     inspector: Handle<UiNode>,
     remove: Handle<UiNode>,
+    expander: Handle<UiNode>,
+    /// A panel that hosts the inspector.
+    content: Handle<UiNode>,
 }
 
 pub trait CollectionItem: Clone + Reflect + Debug + Default + 'static {}
 
 impl<T: Clone + Reflect + Debug + Default + 'static> CollectionItem for T {}
 
-#[derive(Debug)]
+/// Everything that is needed to create editors of collection items.
+#[derive(Clone)]
+struct ItemContext {
+    environment: Option<Rc<dyn InspectorEnvironment>>,
+    definition_container: Rc<PropertyEditorDefinitionContainer>,
+    /// A property editor that is registered for the type of items. If there is no such editor,
+    /// every item is shown as a nested inspector with the fields of the item.
+    item_definition: Option<Rc<dyn PropertyEditorDefinition>>,
+    sync_flag: u64,
+    layer_index: usize,
+}
+
+fn make_item_info<T: CollectionItem>(value: &T) -> FieldInfo<'_> {
+    FieldInfo {
+        owner_type_id: TypeId::of::<Vec<T>>(),
+        name: "",
+        display_name: "",
+        description: "",
+        type_name: std::any::type_name::<T>(),
+        value,
+        read_only: false,
+        min_value: None,
+        max_value: None,
+        step: None,
+        precision: None,
+    }
+}
+
+impl ItemContext {
+    /// Creates an editor of an item, returns a widget that should be added to the item view and
+    /// the editor itself.
+    fn create_inspector<T: CollectionItem>(
+        &self,
+        value: &T,
+        ctx: &mut BuildContext,
+    ) -> (Handle<UiNode>, Handle<UiNode>) {
+        if let Some(definition) = self.item_definition.as_ref() {
+            return match definition.create_instance(PropertyEditorBuildContext {
+                build_context: ctx,
+                property_info: &make_item_info(value),
+                environment: self.environment.clone(),
+                definition_container: self.definition_container.clone(),
+                sync_flag: self.sync_flag,
+                layer_index: self.layer_index + 1,
+            }) {
+                Ok(PropertyEditorInstance::Simple { editor }) => (editor, editor),
+                Ok(PropertyEditorInstance::Custom { container, editor }) => (container, editor),
+                Err(e) => (
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_wrap(WrapMode::Word)
+                        .with_text(format!(
+                            "Unable to create property editor instance: Reason {:?}",
+                            e
+                        ))
+                        .build(ctx),
+                    Handle::NONE,
+                ),
+            };
+        }
+
+        let inspector_context = InspectorContext::from_object(
+            value,
+            ctx,
+            self.definition_container.clone(),
+            self.environment.clone(),
+            self.sync_flag,
+            self.layer_index + 1,
+        );
+
+        let inspector = InspectorBuilder::new(WidgetBuilder::new())
+            .with_context(inspector_context)
+            .build(ctx);
+
+        (inspector, inspector)
+    }
+
+    /// Creates views of the items at given page. Items of large collections are collapsed and
+    /// their inspectors are not created.
+    fn create_items<T: CollectionItem>(
+        &self,
+        values: &[T],
+        page: usize,
+        ctx: &mut BuildContext,
+    ) -> Vec<Item> {
+        let expanded = values.len() <= EXPANDED_ITEMS_LIMIT;
+
+        page_range(page, values.len())
+            .map(|index| {
+                let (view, inspector) = if expanded {
+                    self.create_inspector(&values[index], ctx)
+                } else {
+                    (Handle::NONE, Handle::NONE)
+                };
+
+                let content =
+                    StackPanelBuilder::new(WidgetBuilder::new().with_child(view)).build(ctx);
+
+                let remove = ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_vertical_alignment(VerticalAlignment::Center)
+                        .with_horizontal_alignment(HorizontalAlignment::Right)
+                        .on_column(1)
+                        .with_width(16.0)
+                        .with_height(16.0),
+                )
+                .with_text("-")
+                .build(ctx);
+
+                let expander = make_expander_container_with_state(
+                    self.layer_index,
+                    &format!("Item {}", index),
+                    &format!("Item {} of the collection", index),
+                    remove,
+                    content,
+                    expanded,
+                    ctx,
+                );
+
+                Item {
+                    inspector,
+                    remove,
+                    expander,
+                    content,
+                }
+            })
+            .collect()
+    }
+}
+
 pub struct CollectionEditor<T: CollectionItem> {
     pub widget: Widget,
     pub add: Handle<UiNode>,
+    /// Items of the current page.
     pub items: Vec<Item>,
     pub panel: Handle<UiNode>,
     pub layer_index: usize,
-    pub phantom: PhantomData<T>,
+    /// Actual values of the collection, they are used to create editors of items on demand.
+    pub values: Vec<T>,
+    pub page: usize,
+    pub pager: Handle<UiNode>,
+    pub prev_page: Handle<UiNode>,
+    pub next_page: Handle<UiNode>,
+    pub page_text: Handle<UiNode>,
+    item_context: ItemContext,
 }
 
 impl<T: CollectionItem> Clone for CollectionEditor<T> {
@@ -55,7 +217,13 @@ impl<T: CollectionItem> Clone for CollectionEditor<T> {
             items: self.items.clone(),
             panel: self.panel,
             layer_index: self.layer_index,
-            phantom: PhantomData,
+            values: self.values.clone(),
+            page: self.page,
+            pager: self.pager,
+            prev_page: self.prev_page,
+            next_page: self.next_page,
+            page_text: self.page_text,
+            item_context: self.item_context.clone(),
         }
     }
 }
@@ -74,13 +242,79 @@ impl<T: CollectionItem> DerefMut for CollectionEditor<T> {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum CollectionEditorMessage {
-    Items(Vec<Item>),
+    /// Sets actual values of the collection, the value must be `Vec<T>`. Views of items are
+    /// re-created only if the amount of items has changed.
+    Values(ObjectValue),
+    /// Shows items at given page.
+    Page(usize),
 }
 
 impl CollectionEditorMessage {
-    define_constructor!(CollectionEditorMessage:Items => fn items(Vec<Item>), layout: false);
+    define_constructor!(CollectionEditorMessage:Values => fn values(ObjectValue), layout: false);
+    define_constructor!(CollectionEditorMessage:Page => fn page(usize), layout: false);
+}
+
+impl<T: CollectionItem> CollectionEditor<T> {
+    /// Returns index of the first item at the current page.
+    fn first_index(&self) -> usize {
+        page_range(self.page, self.values.len()).start
+    }
+
+    fn rebuild_items(&mut self, ui: &mut UserInterface) {
+        for old_item in ui.node(self.panel).children() {
+            ui.send_message(WidgetMessage::remove(*old_item, MessageDirection::ToWidget));
+        }
+
+        self.items = self
+            .item_context
+            .create_items(&self.values, self.page, &mut ui.build_ctx());
+
+        for item in self.items.iter() {
+            ui.send_message(WidgetMessage::link(
+                item.expander,
+                MessageDirection::ToWidget,
+                self.panel,
+            ));
+        }
+
+        self.sync_pager(ui);
+    }
+
+    fn sync_pager(&self, ui: &UserInterface) {
+        let page_count = page_count(self.values.len());
+        ui.send_message(WidgetMessage::visibility(
+            self.pager,
+            MessageDirection::ToWidget,
+            page_count > 1,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.prev_page,
+            MessageDirection::ToWidget,
+            self.page > 0,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.next_page,
+            MessageDirection::ToWidget,
+            self.page + 1 < page_count,
+        ));
+        ui.send_message(TextMessage::text(
+            self.page_text,
+            MessageDirection::ToWidget,
+            make_page_text(self.page, self.values.len()),
+        ));
+    }
+}
+
+fn make_page_text(page: usize, len: usize) -> String {
+    let range = page_range(page, len);
+    format!(
+        "Items {}..{} of {}",
+        range.start,
+        range.end.saturating_sub(1),
+        len
+    )
 }
 
 impl<T: CollectionItem> Control for CollectionEditor<T> {
@@ -95,16 +329,44 @@ impl<T: CollectionItem> Control for CollectionEditor<T> {
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
+        if let Some(definition) = self.item_context.item_definition.as_ref() {
+            // Items are edited by a registered property editor, its messages are translated the
+            // same way as the inspector does it for properties.
+            if message.flags != self.item_context.sync_flag {
+                if let Some(index) = self
+                    .items
+                    .iter()
+                    .position(|i| i.inspector.is_some() && i.inspector == message.destination())
+                {
+                    if let Some(property) =
+                        definition.translate_message(PropertyEditorTranslationContext {
+                            environment: self.item_context.environment.clone(),
+                            name: "",
+                            owner_type_id: TypeId::of::<Vec<T>>(),
+                            message,
+                            definition_container: self.item_context.definition_container.clone(),
+                        })
+                    {
+                        ui.send_message(CollectionChanged::item_changed(
+                            self.handle,
+                            MessageDirection::FromWidget,
+                            self.first_index() + index,
+                            property,
+                        ));
+                        return;
+                    }
+                }
+            }
+        }
+
         if let Some(InspectorMessage::PropertyChanged(p)) = message.data::<InspectorMessage>() {
-            if let Some(index) = self
-                .items
-                .iter()
-                .position(|i| i.inspector == message.destination())
-            {
+            if let Some(index) = self.items.iter().position(|i| {
+                self.item_context.item_definition.is_none() && i.inspector == message.destination()
+            }) {
                 ui.send_message(CollectionChanged::item_changed(
                     self.handle,
                     MessageDirection::FromWidget,
-                    index,
+                    self.first_index() + index,
                     p.clone(),
                 ))
             }
@@ -117,31 +379,57 @@ impl<T: CollectionItem> Control for CollectionEditor<T> {
                 ui.send_message(CollectionChanged::remove(
                     self.handle,
                     MessageDirection::FromWidget,
-                    index,
+                    self.first_index() + index,
+                ));
+            } else if message.destination() == self.prev_page {
+                ui.send_message(CollectionEditorMessage::page(
+                    self.handle,
+                    MessageDirection::ToWidget,
+                    self.page.saturating_sub(1),
+                ));
+            } else if message.destination() == self.next_page {
+                ui.send_message(CollectionEditorMessage::page(
+                    self.handle,
+                    MessageDirection::ToWidget,
+                    self.page + 1,
                 ));
             }
+        } else if let Some(&ExpanderMessage::Expand(true)) = message.data::<ExpanderMessage>() {
+            // Create inspector of an item on first expansion.
+            if let Some(index) = self.items.iter().position(|i| {
+                i.expander == message.destination() && ui.node(i.content).children().is_empty()
+            }) {
+                if let Some(value) = self.values.get(self.first_index() + index) {
+                    let (view, inspector) = self
+                        .item_context
+                        .create_inspector(value, &mut ui.build_ctx());
+                    ui.send_message(WidgetMessage::link(
+                        view,
+                        MessageDirection::ToWidget,
+                        self.items[index].content,
+                    ));
+                    self.items[index].inspector = inspector;
+                }
+            }
         } else if let Some(msg) = message.data::<CollectionEditorMessage>() {
             if message.destination == self.handle {
                 match msg {
-                    CollectionEditorMessage::Items(items) => {
-                        let views = create_item_views(items, &mut ui.build_ctx(), self.layer_index);
-
-                        for old_item in ui.node(self.panel).children() {
-                            ui.send_message(WidgetMessage::remove(
-                                *old_item,
-                                MessageDirection::ToWidget,
-                            ));
+                    CollectionEditorMessage::Values(values) => {
+                        if let Some(values) = values.cast_value::<Vec<T>>() {
+                            let len_changed = values.len() != self.values.len();
+                            self.values = values.clone();
+                            if len_changed {
+                                self.page = self.page.min(page_count(self.values.len()) - 1);
+                                self.rebuild_items(ui);
+                            }
                         }
-
-                        for view in views {
-                            ui.send_message(WidgetMessage::link(
-                                view,
-                                MessageDirection::ToWidget,
-                                self.panel,
-                            ));
+                    }
+                    &CollectionEditorMessage::Page(page) => {
+                        let page = page.min(page_count(self.values.len()) - 1);
+                        if page != self.page {
+                            self.page = page;
+                            self.rebuild_items(ui);
                         }
-
-                        self.items = items.clone();
                     }
                 }
             }
@@ -176,69 +464,16 @@ where
     layer_index: usize,
 }
 
-fn create_item_views(
-    items: &[Item],
-    ctx: &mut BuildContext,
-    layer_index: usize,
-) -> Vec<Handle<UiNode>> {
-    items
-        .iter()
-        .enumerate()
-        .map(|(n, item)| {
-            make_expander_container(
-                layer_index,
-                &format!("Item {}", n),
-                &format!("Item {} of the collection", n),
-                item.remove,
-                item.inspector,
-                ctx,
-            )
-        })
-        .collect::<Vec<_>>()
-}
-
-fn create_items<'a, T, I>(
-    iter: I,
-    environment: Option<Rc<dyn InspectorEnvironment>>,
-    definition_container: Rc<PropertyEditorDefinitionContainer>,
-    ctx: &mut BuildContext,
-    sync_flag: u64,
-    layer_index: usize,
-) -> Vec<Item>
-where
-    T: CollectionItem,
-    I: IntoIterator<Item = &'a T>,
-{
-    iter.into_iter()
-        .map(|entry| {
-            let inspector_context = InspectorContext::from_object(
-                entry,
-                ctx,
-                definition_container.clone(),
-                environment.clone(),
-                sync_flag,
-                layer_index,
-            );
-
-            let inspector = InspectorBuilder::new(WidgetBuilder::new())
-                .with_context(inspector_context)
-                .build(ctx);
-
-            let remove = ButtonBuilder::new(
-                WidgetBuilder::new()
-                    .with_margin(Thickness::uniform(1.0))
-                    .with_vertical_alignment(VerticalAlignment::Center)
-                    .with_horizontal_alignment(HorizontalAlignment::Right)
-                    .on_column(1)
-                    .with_width(16.0)
-                    .with_height(16.0),
-            )
-            .with_text("-")
-            .build(ctx);
-
-            Item { inspector, remove }
-        })
-        .collect::<Vec<_>>()
+fn make_page_button(ctx: &mut BuildContext, text: &str, column: usize) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .on_column(column)
+            .with_width(20.0)
+            .with_height(16.0),
+    )
+    .with_text(text)
+    .build(ctx)
 }
 
 impl<'a, T, I> CollectionEditorBuilder<'a, T, I>
@@ -289,40 +524,83 @@ where
         let definition_container = self
             .definition_container
             .unwrap_or_else(|| Rc::new(PropertyEditorDefinitionContainer::new()));
+        let item_definition = definition_container
+            .definitions()
+            .get(&TypeId::of::<T>())
+            .cloned();
+        let item_context = ItemContext {
+            environment: self.environment,
+            item_definition,
+            definition_container,
+            sync_flag,
+            layer_index: self.layer_index,
+        };
 
-        let environment = self.environment;
-        let items = self
+        let values = self
             .collection
-            .map(|collection| {
-                create_items(
-                    collection,
-                    environment,
-                    definition_container,
-                    ctx,
-                    sync_flag,
-                    self.layer_index + 1,
-                )
-            })
+            .map(|collection| collection.into_iter().cloned().collect::<Vec<_>>())
             .unwrap_or_default();
 
-        let panel = StackPanelBuilder::new(WidgetBuilder::new().with_children(create_item_views(
-            &items,
-            ctx,
-            self.layer_index,
-        )))
+        let items = item_context.create_items(&values, 0, ctx);
+
+        let panel = StackPanelBuilder::new(
+            WidgetBuilder::new().with_children(items.iter().map(|i| i.expander)),
+        )
+        .build(ctx);
+
+        let prev_page;
+        let next_page;
+        let page_text;
+        let pager = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(page_count(values.len()) > 1)
+                .with_child({
+                    prev_page = make_page_button(ctx, "<", 0);
+                    prev_page
+                })
+                .with_child({
+                    page_text = TextBuilder::new(WidgetBuilder::new().on_column(1))
+                        .with_text(make_page_text(0, values.len()))
+                        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .build(ctx);
+                    page_text
+                })
+                .with_child({
+                    next_page = make_page_button(ctx, ">", 2);
+                    next_page
+                }),
+        )
+        .add_row(Row::auto())
+        .add_column(Column::auto())
+        .add_column(Column::stretch())
+        .add_column(Column::auto())
         .build(ctx);
 
+        ctx[prev_page].set_enabled(false);
+
         let ce = CollectionEditor::<T> {
             widget: self
                 .widget_builder
                 .with_preview_messages(true)
-                .with_child(panel)
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new().with_child(pager).with_child(panel),
+                    )
+                    .build(ctx),
+                )
                 .build(),
             add: self.add,
             items,
             panel,
             layer_index: self.layer_index,
-            phantom: PhantomData,
+            values,
+            page: 0,
+            pager,
+            prev_page,
+            next_page,
+            page_text,
+            item_context,
         };
 
         ctx.add_node(UiNode::new(ce))
@@ -411,13 +689,11 @@ where
         ctx: PropertyEditorMessageContext,
     ) -> Result<Option<UiMessage>, InspectorError> {
         let PropertyEditorMessageContext {
-            sync_flag,
             instance,
             ui,
             property_info,
-            definition_container,
             layer_index,
-            environment,
+            ..
         } = ctx;
 
         let instance_ref = if let Some(instance) = ui.node(instance).cast::<CollectionEditor<T>>() {
@@ -430,45 +706,62 @@ where
 
         let value = property_info.cast_value::<Vec<T>>()?;
 
-        if value.len() != instance_ref.items.len() {
-            // Re-create items.
-            let items = create_items(
-                value.iter(),
-                environment,
-                definition_container,
-                &mut ui.build_ctx(),
-                sync_flag,
-                layer_index + 1,
-            );
-
-            Ok(Some(CollectionEditorMessage::items(
-                instance,
-                MessageDirection::ToWidget,
-                items,
-            )))
-        } else {
+        if value.len() == instance_ref.values.len() {
+            // Sync only inspectors that were created, the rest will be created from actual
+            // values on expansion.
+            let first_index = instance_ref.first_index();
+            let item_context = instance_ref.item_context.clone();
             let mut error_group = Vec::new();
-
-            // Just sync inspector of every item.
-            for (item, obj) in instance_ref.items.clone().iter().zip(value.iter()) {
-                let layer_index = ctx.layer_index;
-                let ctx = ui
-                    .node(item.inspector)
-                    .cast::<Inspector>()
-                    .expect("Must be Inspector!")
-                    .context()
-                    .clone();
-                if let Err(e) = ctx.sync(obj, ui, layer_index + 1) {
-                    error_group.extend(e.into_iter())
+            for (index, item) in instance_ref.items.clone().iter().enumerate() {
+                let obj = match value.get(first_index + index) {
+                    Some(obj) if item.inspector.is_some() => obj,
+                    _ => continue,
+                };
+
+                if let Some(definition) = item_context.item_definition.as_ref() {
+                    match definition.create_message(PropertyEditorMessageContext {
+                        sync_flag: item_context.sync_flag,
+                        instance: item.inspector,
+                        ui: &mut *ui,
+                        property_info: &make_item_info(obj),
+                        definition_container: item_context.definition_container.clone(),
+                        layer_index: layer_index + 1,
+                        environment: item_context.environment.clone(),
+                    }) {
+                        Ok(Some(mut message)) => {
+                            message.flags = item_context.sync_flag;
+                            ui.send_message(message);
+                        }
+                        Ok(None) => (),
+                        Err(e) => error_group.push(e),
+                    }
+                } else {
+                    let ctx = ui
+                        .node(item.inspector)
+                        .cast::<Inspector>()
+                        .expect("Must be Inspector!")
+                        .context()
+                        .clone();
+                    if let Err(e) = ctx.sync(obj, ui, layer_index + 1) {
+                        error_group.extend(e)
+                    }
                 }
             }
 
-            if error_group.is_empty() {
-                Ok(None)
-            } else {
-                Err(InspectorError::Group(error_group))
+            if !error_group.is_empty() {
+                return Err(InspectorError::Group(error_group));
             }
         }
+
+        // A single message updates all values, views of items are re-created only if the amount
+        // of items has changed.
+        Ok(Some(CollectionEditorMessage::values(
+            instance,
+            MessageDirection::ToWidget,
+            ObjectValue {
+                value: Box::new(value.clone()),
+            },
+        )))
     }
 
     fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
@@ -484,3 +777,61 @@ where
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::inspector::{
+        editors::collection::{make_page_text, page_count, page_range, ITEMS_PER_PAGE},
+        CollectionChanged, FieldKind, PropertyChanged,
+    };
+    use std::any::TypeId;
+
+    #[test]
+    fn test_pages() {
+        assert_eq!(page_count(0), 1);
+        assert_eq!(page_count(ITEMS_PER_PAGE), 1);
+        assert_eq!(page_count(ITEMS_PER_PAGE + 1), 2);
+        assert_eq!(page_count(2000), 40);
+
+        assert_eq!(page_range(0, 10), 0..10);
+        assert_eq!(
+            page_range(1, ITEMS_PER_PAGE + 5),
+            ITEMS_PER_PAGE..ITEMS_PER_PAGE + 5
+        );
+        // Out-of-bounds pages are empty.
+        assert_eq!(page_range(3, 10), 10..10);
+
+        assert_eq!(make_page_text(1, 120), "Items 50..99 of 120");
+    }
+
+    #[test]
+    fn test_item_path() {
+        let item_changed = |property| PropertyChanged {
+            name: "items".to_string(),
+            owner_type_id: TypeId::of::<()>(),
+            value: FieldKind::Collection(Box::new(CollectionChanged::ItemChanged {
+                index: 3,
+                property,
+            })),
+        };
+
+        // An item that is edited by a custom property editor as a whole.
+        let whole = item_changed(PropertyChanged {
+            name: "".to_string(),
+            owner_type_id: TypeId::of::<()>(),
+            value: FieldKind::object(1u32),
+        });
+        assert_eq!(whole.path(), "items[3]");
+
+        let field = item_changed(PropertyChanged {
+            name: "".to_string(),
+            owner_type_id: TypeId::of::<()>(),
+            value: FieldKind::Inspectable(Box::new(PropertyChanged {
+                name: "damage".to_string(),
+                owner_type_id: TypeId::of::<()>(),
+                value: FieldKind::object(1u32),
+            })),
+        });
+        assert_eq!(field.path(), "items[3].damage");
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    color::{ColorGradientEditorBuilder, ColorGradientEditorMessage},
+    core::{algebra::Vector2, color_gradient::ColorGradient},
+    inspector::{
+        editors::{
+            PropertyEditorBuildContext, PropertyEditorDefinition, PropertyEditorInstance,
+            PropertyEditorMessageContext, PropertyEditorTranslationContext,
+        },
+        FieldKind, InspectorError, PropertyChanged,
+    },
+    message::{MessageDirection, UiMessage},
+    widget::WidgetBuilder,
+    Thickness,
+};
+use std::any::TypeId;
+
+#[derive(Debug)]
+pub struct ColorGradientPropertyEditorDefinition;
+
+impl PropertyEditorDefinition for ColorGradientPropertyEditorDefinition {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<ColorGradient>()
+    }
+
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
+        let value = ctx.property_info.cast_value::<ColorGradient>()?;
+        Ok(PropertyEditorInstance::Simple {
+            editor: ColorGradientEditorBuilder::new(
+                WidgetBuilder::new()
+                    .with_min_size(Vector2::new(0.0, 48.0))
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_gradient(value.clone())
+            .build(ctx.build_context),
+        })
+    }
+
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        let value = ctx.property_info.cast_value::<ColorGradient>()?;
+        Ok(Some(ColorGradientEditorMessage::value(
+            ctx.instance,
+            MessageDirection::ToWidget,
+            value.clone(),
+        )))
+    }
+
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        if ctx.message.direction() == MessageDirection::FromWidget {
+            if let Some(ColorGradientEditorMessage::Value(value)) =
+                ctx.message.data::<ColorGradientEditorMessage>()
+            {
+                return Some(PropertyChanged {
+                    name: ctx.name.to_string(),
+                    owner_type_id: ctx.owner_type_id,
+                    value: FieldKind::object(value.clone()),
+                });
+            }
+        }
+        None
+    }
+}
@@ -68,20 +68,23 @@ where
             ctx.layer_index + 1,
         );
 
-        let editor;
-        let container = make_expander_container(
-            ctx.layer_index,
-            ctx.property_info.display_name,
-            ctx.property_info.description,
-            Handle::NONE,
-            {
-                editor = InspectorBuilder::new(WidgetBuilder::new())
-                    .with_context(inspector_context)
-                    .build(ctx.build_context);
-                editor
-            },
-            ctx.build_context,
-        );
+        let editor = InspectorBuilder::new(WidgetBuilder::new())
+            .with_context(inspector_context)
+            .build(ctx.build_context);
+
+        // Nameless values (collection items, for example) are already shown in some container.
+        let container = if ctx.property_info.display_name.is_empty() {
+            editor
+        } else {
+            make_expander_container(
+                ctx.layer_index,
+                ctx.property_info.display_name,
+                ctx.property_info.description,
+                Handle::NONE,
+                editor,
+                ctx.build_context,
+            )
+        };
 
         Ok(PropertyEditorInstance::Custom { container, editor })
     }
@@ -2,6 +2,7 @@ use crate::{
     core::{
         algebra::{UnitQuaternion, Vector2, Vector3, Vector4},
         color::Color,
+        color_gradient::ColorGradient,
         math::{Rect, SmoothAngle},
         pool::Handle,
         reflect::{FieldInfo, FieldValue, Reflect},
@@ -12,10 +13,12 @@ use crate::{
             bool::BoolPropertyEditorDefinition,
             collection::{CollectionItem, VecCollectionPropertyEditorDefinition},
             color::ColorPropertyEditorDefinition,
+            color_gradient::ColorGradientPropertyEditorDefinition,
             enumeration::{EnumPropertyEditorDefinition, InspectableEnum},
             inherit::InheritablePropertyEditorDefinition,
             inspectable::InspectablePropertyEditorDefinition,
             numeric::NumericPropertyEditorDefinition,
+            path::PathPropertyEditorDefinition,
             quat::QuatPropertyEditorDefinition,
             range::RangePropertyEditorDefinition,
             rect::RectPropertyEditorDefinition,
@@ -36,6 +39,7 @@ use std::{
     cell::{Ref, RefCell},
     fmt::Debug,
     ops::Range,
+    path::PathBuf,
     rc::Rc,
     str::FromStr,
 };
@@ -46,10 +50,12 @@ pub mod bit;
 pub mod bool;
 pub mod collection;
 pub mod color;
+pub mod color_gradient;
 pub mod enumeration;
 pub mod inherit;
 pub mod inspectable;
 pub mod numeric;
+pub mod path;
 pub mod quat;
 pub mod range;
 pub mod rect;
@@ -147,6 +153,10 @@ impl PropertyEditorDefinitionContainer {
         container.insert(StringPropertyEditorDefinition);
         container.insert(InheritablePropertyEditorDefinition::<String>::new());
 
+        // PathBuf + InheritableVariable<PathBuf>
+        container.insert(PathPropertyEditorDefinition);
+        container.insert(InheritablePropertyEditorDefinition::<PathBuf>::new());
+
         // NumericType + InheritableVariable<NumericType>
         reg_property_editor! { container, NumericPropertyEditorDefinition: default, f64, f32, i64, u64, i32, u32, i16, u16, i8, u8, usize, isize };
         reg_property_editor! { container, InheritablePropertyEditorDefinition: new, f64, f32, i64, u64, i32, u32, i16, u16, i8, u8, usize, isize };
@@ -203,6 +213,12 @@ impl PropertyEditorDefinitionContainer {
         container.insert(ColorPropertyEditorDefinition);
         container.insert(InheritablePropertyEditorDefinition::<Color>::new());
 
+        // ColorGradient + InheritableVariable<ColorGradient> + Option<ColorGradient> + InheritableVariable<Option<ColorGradient>>
+        container.insert(ColorGradientPropertyEditorDefinition);
+        container.insert(InheritablePropertyEditorDefinition::<ColorGradient>::new());
+        container.insert(EnumPropertyEditorDefinition::<ColorGradient>::new_optional());
+        container.insert(InheritablePropertyEditorDefinition::<Option<ColorGradient>>::new());
+
         // [NumericType; 1..N]
         reg_array_property_editor! { container, f64, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16 };
         reg_array_property_editor! { container, f32, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16 };
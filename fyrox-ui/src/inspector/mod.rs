@@ -1,7 +1,7 @@
 use crate::{
     border::BorderBuilder,
     check_box::CheckBoxBuilder,
-    core::{algebra::Vector2, pool::Handle, reflect::CastError},
+    core::{algebra::Vector2, math::Rect, pool::Handle, reflect::CastError},
     define_constructor,
     expander::ExpanderBuilder,
     formatted_text::WrapMode,
@@ -17,7 +17,7 @@ use crate::{
     widget::{Widget, WidgetBuilder, WidgetMessage},
     BuildContext, Control, Thickness, UiNode, UserInterface, VerticalAlignment,
 };
-use fyrox_core::reflect::{Reflect, ResolvePath};
+use fyrox_core::reflect::{set_field_by_path, Reflect, ResolvePath};
 use std::{
     any::{Any, TypeId},
     fmt::{Debug, Formatter},
@@ -112,17 +112,9 @@ impl PropertyAction {
         target: &mut dyn Reflect,
     ) -> Result<Option<Box<dyn Reflect>>, Self> {
         match self {
-            PropertyAction::Modify { value } => {
-                if let Ok(field) = target.resolve_path_mut(path) {
-                    if let Err(value) = field.set(value) {
-                        Err(Self::Modify { value })
-                    } else {
-                        Ok(None)
-                    }
-                } else {
-                    Err(Self::Modify { value })
-                }
-            }
+            PropertyAction::Modify { value } => set_field_by_path(target, path, value)
+                .map(|_| None)
+                .map_err(|value| Self::Modify { value }),
             PropertyAction::AddItem { value } => {
                 if let Ok(field) = target.resolve_path_mut(path) {
                     if let Some(list) = field.as_list_mut() {
@@ -254,11 +246,20 @@ impl PropertyChanged {
                     index,
                 } = **collection_changed
                 {
-                    path += format!("[{}].{}", index, property.path()).as_ref();
+                    path += format!("[{}]", index).as_ref();
+                    // Items that are edited by a custom property editor as a whole have no name.
+                    let item_path = property.path();
+                    if !item_path.is_empty() {
+                        path += format!(".{}", item_path).as_ref();
+                    }
                 }
             }
             FieldKind::Inspectable(ref inspectable) => {
-                path += format!(".{}", inspectable.path()).as_ref();
+                if path.is_empty() {
+                    path = inspectable.path();
+                } else {
+                    path += format!(".{}", inspectable.path()).as_ref();
+                }
             }
             FieldKind::Object(_) | FieldKind::Inheritable { .. } => {}
         }
@@ -400,6 +401,7 @@ fn make_expander_check_box(
     layer_index: usize,
     property_name: &str,
     property_description: &str,
+    expanded: bool,
     ctx: &mut BuildContext,
 ) -> Handle<UiNode> {
     CheckBoxBuilder::new(
@@ -427,7 +429,7 @@ fn make_expander_check_box(
         .with_text(property_name)
         .build(ctx),
     )
-    .checked(Some(true))
+    .checked(Some(expanded))
     .with_check_mark(make_arrow(ctx, ArrowDirection::Bottom, 8.0))
     .with_uncheck_mark(make_arrow(ctx, ArrowDirection::Right, 8.0))
     .build(ctx)
@@ -440,16 +442,39 @@ pub fn make_expander_container(
     header: Handle<UiNode>,
     content: Handle<UiNode>,
     ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    make_expander_container_with_state(
+        layer_index,
+        property_name,
+        description,
+        header,
+        content,
+        true,
+        ctx,
+    )
+}
+
+/// Same as [`make_expander_container`], but allows to create collapsed containers. It is useful
+/// for editors that create their content lazily on first expansion.
+pub fn make_expander_container_with_state(
+    layer_index: usize,
+    property_name: &str,
+    description: &str,
+    header: Handle<UiNode>,
+    content: Handle<UiNode>,
+    expanded: bool,
+    ctx: &mut BuildContext,
 ) -> Handle<UiNode> {
     ExpanderBuilder::new(WidgetBuilder::new())
         .with_checkbox(make_expander_check_box(
             layer_index,
             property_name,
             description,
+            expanded,
             ctx,
         ))
         .with_expander_column(Column::strict(NAME_COLUMN_WIDTH))
-        .with_expanded(true)
+        .with_expanded(expanded)
         .with_header(header)
         .with_content(content)
         .build(ctx)
@@ -487,6 +512,28 @@ fn make_simple_property_container(
         .build(ctx)
 }
 
+/// Wraps a property container in a row, the row keeps its height when its content is hidden by
+/// [`InspectorContext::virtualize_rows`].
+fn make_row(container: Handle<UiNode>, ctx: &mut BuildContext) -> Handle<UiNode> {
+    StackPanelBuilder::new(WidgetBuilder::new().with_child(container)).build(ctx)
+}
+
+// Looks for nested inspectors in visible descendants of the node and virtualizes their rows.
+fn virtualize_nested(node: Handle<UiNode>, viewport: Rect<f32>, ui: &UserInterface) {
+    let node_ref = ui.node(node);
+    if !node_ref.visibility() {
+        return;
+    }
+
+    if let Some(inspector) = node_ref.cast::<Inspector>() {
+        inspector.context.virtualize_rows(ui, viewport);
+    } else {
+        for &child in node_ref.children() {
+            virtualize_nested(child, viewport, ui);
+        }
+    }
+}
+
 impl InspectorContext {
     pub fn from_object(
         object: &dyn Reflect,
@@ -579,8 +626,13 @@ impl InspectorContext {
             })
             .collect::<Vec<_>>();
 
+        let rows = editors
+            .into_iter()
+            .map(|container| make_row(container, ctx))
+            .collect::<Vec<_>>();
+
         let stack_panel =
-            StackPanelBuilder::new(WidgetBuilder::new().with_children(editors)).build(ctx);
+            StackPanelBuilder::new(WidgetBuilder::new().with_children(rows)).build(ctx);
 
         Self {
             stack_panel,
@@ -636,6 +688,51 @@ impl InspectorContext {
         }
     }
 
+    /// Hides content of every row which is out of the given visible area (in screen coordinates)
+    /// and shows content of the rest, rows of nested inspectors (for example inspectors of
+    /// collection items) are virtualized too. Hidden rows keep their height, so the layout of the
+    /// inspector does not change, but their content is not measured, arranged and drawn. Call it
+    /// with clip bounds of a scroll viewer that contains the inspector when they could change.
+    pub fn virtualize_rows(&self, ui: &UserInterface, viewport: Rect<f32>) {
+        for &row in ui.node(self.stack_panel).children() {
+            let row_ref = ui.node(row);
+            let content = match row_ref.children().first() {
+                Some(&content) => content,
+                None => continue,
+            };
+            let is_virtualized = !ui.node(content).visibility();
+
+            if viewport.intersects(row_ref.screen_bounds()) {
+                if is_virtualized {
+                    ui.send_message(WidgetMessage::height(
+                        row,
+                        MessageDirection::ToWidget,
+                        f32::NAN,
+                    ));
+                    ui.send_message(WidgetMessage::visibility(
+                        content,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+                } else {
+                    virtualize_nested(content, viewport, ui);
+                }
+            } else if !is_virtualized && row_ref.actual_local_size().y > 0.0 {
+                // Rows that weren't arranged yet are left as is, their height is unknown.
+                ui.send_message(WidgetMessage::height(
+                    row,
+                    MessageDirection::ToWidget,
+                    row_ref.actual_local_size().y,
+                ));
+                ui.send_message(WidgetMessage::visibility(
+                    content,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+            }
+        }
+    }
+
     pub fn property_editors(&self) -> impl Iterator<Item = &ContextEntry> + '_ {
         self.entries.iter()
     }
@@ -745,3 +842,65 @@ impl InspectorBuilder {
         ctx.add_node(UiNode::new(canvas))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        canvas::CanvasBuilder,
+        core::{algebra::Vector2, math::Rect},
+        inspector::{make_row, InspectorBuilder, InspectorContext},
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn test_row_virtualization() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let ctx = &mut ui.build_ctx();
+        let contents = (0..100)
+            .map(|_| CanvasBuilder::new(WidgetBuilder::new().with_height(20.0)).build(ctx))
+            .collect::<Vec<_>>();
+        let rows = contents
+            .iter()
+            .map(|&content| make_row(content, ctx))
+            .collect::<Vec<_>>();
+        let context = InspectorContext {
+            stack_panel: StackPanelBuilder::new(WidgetBuilder::new().with_children(rows))
+                .build(ctx),
+            ..Default::default()
+        };
+        let inspector = InspectorBuilder::new(WidgetBuilder::new().with_width(screen_size.x))
+            .with_context(context.clone())
+            .build(ctx);
+        ui.update(screen_size, 0.0);
+        let height = ui.node(inspector).actual_local_size().y;
+        assert_eq!(height, 100.0 * 20.0);
+
+        let viewport = Rect::new(0.0, 0.0, screen_size.x, 10.0 * 20.0);
+        context.virtualize_rows(&ui, viewport);
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+
+        let is_virtualized = |ui: &UserInterface, content| !ui.node(content).visibility();
+        assert_eq!(
+            contents.iter().filter(|c| is_virtualized(&ui, **c)).count(),
+            90
+        );
+        assert!(!is_virtualized(&ui, contents[9]));
+        assert!(is_virtualized(&ui, contents[10]));
+        // Virtualized rows must keep their height.
+        assert_eq!(ui.node(inspector).actual_local_size().y, height);
+
+        // Rows are restored when they're back in the visible area.
+        let viewport = Rect::new(0.0, 90.0 * 20.0, screen_size.x, 10.0 * 20.0);
+        context.virtualize_rows(&ui, viewport);
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        assert!(is_virtualized(&ui, contents[0]));
+        assert!(!is_virtualized(&ui, contents[95]));
+        assert_eq!(ui.node(inspector).actual_local_size().y, height);
+    }
+}
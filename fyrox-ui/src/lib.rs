@@ -14,15 +14,20 @@ pub use fyrox_core as core;
 
 pub mod bit;
 pub mod border;
+pub mod breadcrumb;
 pub mod brush;
 pub mod button;
 pub mod canvas;
+pub mod chart;
 pub mod check_box;
 pub mod color;
 pub mod curve;
+pub mod data_grid;
 pub mod decorator;
+pub mod dialog;
 pub mod dock;
 pub mod draw;
+pub mod drawing_canvas;
 pub mod dropdown_list;
 pub mod expander;
 pub mod file_browser;
@@ -31,10 +36,14 @@ pub mod grid;
 pub mod image;
 pub mod inspector;
 pub mod list_view;
+pub mod localization;
 pub mod menu;
 pub mod message;
 pub mod messagebox;
+pub mod nine_patch;
+pub mod node_graph;
 pub mod numeric;
+pub mod overlay;
 pub mod popup;
 pub mod progress_bar;
 pub mod range;
@@ -42,10 +51,12 @@ pub mod rect;
 pub mod scroll_bar;
 pub mod scroll_panel;
 pub mod scroll_viewer;
+pub mod search_bar;
 pub mod stack_panel;
 pub mod tab_control;
 pub mod text;
 pub mod text_box;
+pub mod toolbar;
 pub mod tree;
 pub mod ttf;
 pub mod utils;
@@ -62,17 +73,24 @@ use crate::{
         algebra::Vector2,
         color::Color,
         math::Rect,
+        memory::FrameArena,
         pool::{Handle, Pool},
         scope_profile,
     },
+    dialog::{Dialog, DialogCallback, DialogHandle, DialogMessage, DialogResult},
     draw::{CommandTexture, Draw, DrawingContext},
+    localization::Localization,
     message::{
         ButtonState, CursorIcon, KeyboardModifiers, MessageDirection, MouseButton, OsEvent,
         UiMessage,
     },
+    overlay::{OverlayLayer, ToastEntry, TOAST_FADE_TIME, TOAST_SPACING},
     popup::{Placement, PopupMessage},
+    text::Text,
+    text_box::TextBox,
     ttf::{Font, FontBuilder, SharedFont},
     widget::{Widget, WidgetBuilder, WidgetMessage},
+    window::WindowMessage,
 };
 use copypasta::ClipboardContext;
 use fxhash::{FxHashMap, FxHashSet};
@@ -81,7 +99,7 @@ use std::collections::hash_map::Entry;
 use std::rc::Rc;
 use std::{
     any::{Any, TypeId},
-    cell::Cell,
+    cell::{Cell, RefCell},
     collections::VecDeque,
     fmt::Debug,
     ops::{Deref, DerefMut, Index, IndexMut},
@@ -410,11 +428,87 @@ pub trait Control: BaseControl + Deref<Target = Widget> + DerefMut {
     }
 }
 
+/// Typed data attached to a drag operation. Dragged widget provides its payload (see
+/// [`WidgetBuilder::with_drag_payload`]) and drop targets can inspect it to decide whether
+/// they want to accept it (see [`DropFilter`]) and to handle the drop itself, instead of
+/// downcasting user data of dragged widget.
+#[derive(Clone)]
+pub struct DragPayload(pub Rc<dyn Any>);
+
+impl DragPayload {
+    pub fn new<T: Any>(value: T) -> Self {
+        Self(Rc::new(value))
+    }
+
+    /// Tries to get payload data of specified type.
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+
+    /// Checks whether the payload has specified type.
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+}
+
+impl PartialEq for DragPayload {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Debug for DragPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DragPayload")
+    }
+}
+
+/// Per-target validation hook for drag'n'drop. It is called when user drags something over the
+/// target, if it returns `false` the cursor changes to [`CursorIcon::NoDrop`] and the drop will
+/// be rejected. Targets without a filter accept everything.
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct DropFilter(pub Rc<dyn Fn(Option<&DragPayload>) -> bool>);
+
+impl DropFilter {
+    pub fn new<F: Fn(Option<&DragPayload>) -> bool + 'static>(filter: F) -> Self {
+        Self(Rc::new(filter))
+    }
+
+    /// Creates a filter that accepts only payloads of specified type.
+    pub fn of_type<T: Any>() -> Self {
+        Self::new(|payload| payload.map_or(false, |p| p.is::<T>()))
+    }
+
+    pub fn accepts(&self, payload: Option<&DragPayload>) -> bool {
+        (self.0)(payload)
+    }
+}
+
+impl PartialEq for DropFilter {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Debug for DropFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DropFilter")
+    }
+}
+
 pub struct DragContext {
     pub is_dragging: bool,
     pub drag_node: Handle<UiNode>,
     pub click_pos: Vector2<f32>,
     pub drag_preview: Handle<UiNode>,
+    /// Payload of current drag operation. It stays available until next drag operation starts,
+    /// so drop handlers can read it.
+    pub payload: Option<DragPayload>,
+    /// A widget that will receive the drop if user releases mouse button now.
+    pub drop_target: Handle<UiNode>,
+    /// `true` if current drop target accepts the payload.
+    pub is_drop_accepted: bool,
 }
 
 impl Default for DragContext {
@@ -424,6 +518,9 @@ impl Default for DragContext {
             drag_node: Default::default(),
             click_pos: Vector2::new(0.0, 0.0),
             drag_preview: Default::default(),
+            payload: None,
+            drop_target: Default::default(),
+            is_drop_accepted: false,
         }
     }
 }
@@ -501,6 +598,11 @@ impl<'a> BuildContext<'a> {
         self.ui.default_font.clone()
     }
 
+    /// Returns localization service of the user interface.
+    pub fn localization(&self) -> &Localization {
+        &self.ui.localization
+    }
+
     pub fn add_node(&mut self, node: UiNode) -> Handle<UiNode> {
         self.ui.add_node(node)
     }
@@ -509,6 +611,10 @@ impl<'a> BuildContext<'a> {
         self.ui.link_nodes_internal(child, parent, false)
     }
 
+    pub fn layer(&self, layer: OverlayLayer) -> Handle<UiNode> {
+        self.ui.layer(layer)
+    }
+
     pub fn copy(&mut self, node: Handle<UiNode>) -> Handle<UiNode> {
         self.ui.copy_node(node)
     }
@@ -518,7 +624,9 @@ impl<'a> BuildContext<'a> {
     }
 
     pub fn try_get_node_mut(&mut self, node: Handle<UiNode>) -> Option<&mut UiNode> {
-        self.ui.nodes.try_borrow_mut(node)
+        let node = self.ui.nodes.try_borrow_mut(node)?;
+        node.invalidate_visual();
+        Some(node)
     }
 }
 
@@ -532,7 +640,9 @@ impl<'a> Index<Handle<UiNode>> for BuildContext<'a> {
 
 impl<'a> IndexMut<Handle<UiNode>> for BuildContext<'a> {
     fn index_mut(&mut self, index: Handle<UiNode>) -> &mut Self::Output {
-        &mut self.ui.nodes[index]
+        let node = &mut self.ui.nodes[index];
+        node.invalidate_visual();
+        node
     }
 }
 
@@ -579,6 +689,32 @@ impl TooltipEntry {
     }
 }
 
+/// A request to read a color of a pixel of the final frame, see
+/// [`UserInterface::request_screen_color`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScreenColorRequest {
+    /// A widget that will receive [`color::ColorPickerMessage::Color`] with the picked color.
+    pub requester: Handle<UiNode>,
+    /// Position of the pixel in screen coordinates.
+    pub position: Vector2<f32>,
+}
+
+/// Layout statistics of the last [`UserInterface::update`], see
+/// [`UserInterface::layout_statistics`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LayoutStatistics {
+    /// Amount of [`UserInterface::measure_node`] calls.
+    pub measure_calls: usize,
+    /// Amount of widgets that were actually measured, the rest of the calls used cached results.
+    pub measured: usize,
+    /// Amount of [`UserInterface::arrange_node`] calls.
+    pub arrange_calls: usize,
+    /// Amount of widgets that were actually arranged, the rest of the calls used cached results.
+    pub arranged: usize,
+    /// Amount of widgets, that had their visual transform and clip bounds updated.
+    pub transform_updates: usize,
+}
+
 #[derive(Debug)]
 pub enum LayoutEvent {
     MeasurementInvalidated(Handle<UiNode>),
@@ -598,6 +734,10 @@ pub struct UserInterface {
     drawing_context: DrawingContext,
     visual_debug: bool,
     root_canvas: Handle<UiNode>,
+    /// Canvases of overlay layers, always the last children of the root canvas.
+    layers: [Handle<UiNode>; 4],
+    toasts: Vec<ToastEntry>,
+    screen_color_requests: Vec<ScreenColorRequest>,
     picked_node: Handle<UiNode>,
     prev_picked_node: Handle<UiNode>,
     captured_node: Handle<UiNode>,
@@ -617,10 +757,21 @@ pub struct UserInterface {
     clipboard: Option<ClipboardContext>,
     layout_events_receiver: Receiver<LayoutEvent>,
     layout_events_sender: Sender<LayoutEvent>,
-    need_update_global_transform: bool,
+    measure_dirty: Vec<Handle<UiNode>>,
+    arrange_dirty: Vec<Handle<UiNode>>,
+    // Transient storage of the layout pass. The user interface could be used without the engine,
+    // which resets the per-thread frame arena, so it has its own arena that is reset every update.
+    layout_arena: FrameArena,
+    // Arranged widgets, their subtrees need to update visual transform and clip bounds.
+    visual_dirty: RefCell<Vec<Handle<UiNode>>>,
+    layout_statistics: Cell<LayoutStatistics>,
+    last_layout_statistics: LayoutStatistics,
     pub default_font: SharedFont,
     double_click_entries: FxHashMap<MouseButton, DoubleClickEntry>,
     pub double_click_time_slice: f32,
+    dialog_callbacks: FxHashMap<Handle<UiNode>, DialogCallback>,
+    localization: Localization,
+    localization_revision: u64,
 }
 
 fn is_on_screen(node: &UiNode, nodes: &Pool<UiNode>) -> bool {
@@ -670,7 +821,28 @@ fn draw_node(
 
     drawing_context.transform_stack.push(node.visual_transform);
 
-    node.draw(drawing_context);
+    // Clipping shape affects the node itself and all its children.
+    let clipped = if let Some(clip_shape) = node.clip_shape.as_ref() {
+        drawing_context.push_clipping_geometry(
+            clip_shape.make_geometry(&node.bounding_rect(), node.visual_transform),
+        );
+        true
+    } else {
+        false
+    };
+
+    // Geometry is emitted again only if the node was changed since the last frame.
+    let is_cached = if let Some(cache) = node.geometry_cache.borrow().as_ref() {
+        drawing_context.draw_cached_geometry(cache);
+        true
+    } else {
+        false
+    };
+    if !is_cached {
+        let mark = drawing_context.mark();
+        node.draw(drawing_context);
+        *node.geometry_cache.borrow_mut() = drawing_context.capture_geometry(mark);
+    }
 
     let end_index = drawing_context.get_commands().len();
     for i in start_index..end_index {
@@ -685,6 +857,10 @@ fn draw_node(
         }
     }
 
+    if clipped {
+        drawing_context.pop_clipping_geometry();
+    }
+
     drawing_context.transform_stack.pop();
 
     if pushed {
@@ -719,6 +895,9 @@ impl UserInterface {
             visual_debug: false,
             captured_node: Handle::NONE,
             root_canvas: Handle::NONE,
+            layers: Default::default(),
+            toasts: Default::default(),
+            screen_color_requests: Default::default(),
             nodes: Pool::new(),
             cursor_position: Vector2::new(0.0, 0.0),
             drawing_context: DrawingContext::new(),
@@ -737,20 +916,168 @@ impl UserInterface {
             clipboard: ClipboardContext::new().ok(),
             layout_events_receiver,
             layout_events_sender,
-            need_update_global_transform: Default::default(),
+            measure_dirty: Default::default(),
+            arrange_dirty: Default::default(),
+            layout_arena: Default::default(),
+            visual_dirty: Default::default(),
+            layout_statistics: Default::default(),
+            last_layout_statistics: Default::default(),
             default_font,
             double_click_entries: Default::default(),
             double_click_time_slice: 0.5, // 500 ms is standard in most operating systems.
+            dialog_callbacks: Default::default(),
+            localization: Default::default(),
+            localization_revision: 0,
         };
         ui.root_canvas = ui.add_node(UiNode::new(Canvas::new(WidgetBuilder::new().build())));
+        for i in 0..ui.layers.len() {
+            let layer = ui.add_node(UiNode::new(Canvas::new(
+                WidgetBuilder::new().with_clip_to_bounds(false).build(),
+            )));
+            // Content is inserted before existing layers, so move the layer to the end.
+            let root = &mut ui.nodes[ui.root_canvas];
+            root.remove_child(layer);
+            root.add_child(layer, false);
+            ui.layers[i] = layer;
+        }
         ui.keyboard_focus_node = ui.root_canvas;
         ui
     }
 
+    /// Returns a handle of a canvas of given overlay layer.
+    pub fn layer(&self, layer: OverlayLayer) -> Handle<UiNode> {
+        match layer {
+            OverlayLayer::Content => self.root_canvas,
+            OverlayLayer::Popups => self.layers[0],
+            OverlayLayer::Tooltips => self.layers[1],
+            OverlayLayer::Notifications => self.layers[2],
+            OverlayLayer::DragPreview => self.layers[3],
+        }
+    }
+
+    /// Puts a widget to the notifications layer and removes it after `duration` seconds.
+    pub fn add_toast(&mut self, toast: Handle<UiNode>, duration: f32) {
+        self.link_nodes_internal(toast, self.layer(OverlayLayer::Notifications), false);
+        self.toasts.push(ToastEntry {
+            handle: toast,
+            time: duration,
+        });
+    }
+
+    /// Asks the renderer to read a color of a pixel at the given screen position after the next
+    /// frame is rendered. The color will be sent to the `requester` as
+    /// [`color::ColorPickerMessage::Color`] message. Requests are processed by the engine, custom
+    /// renderers must use [`Self::take_screen_color_requests`] to handle them.
+    pub fn request_screen_color(&mut self, requester: Handle<UiNode>, position: Vector2<f32>) {
+        self.screen_color_requests.push(ScreenColorRequest {
+            requester,
+            position,
+        });
+    }
+
+    /// Returns all pending screen color requests and clears the queue.
+    pub fn take_screen_color_requests(&mut self) -> Vec<ScreenColorRequest> {
+        std::mem::take(&mut self.screen_color_requests)
+    }
+
+    /// Returns localization service of the user interface. The service is shared, so it could be
+    /// cloned and passed anywhere where translations are needed.
+    pub fn localization(&self) -> &Localization {
+        &self.localization
+    }
+
+    /// Replaces localization service of the user interface. Every widget bound to a message will
+    /// be re-translated on next update.
+    pub fn set_localization(&mut self, localization: Localization) {
+        self.localization = localization;
+        self.localization_revision = u64::MAX;
+    }
+
+    /// Translates a message with the given key using current language, it is a shortcut for
+    /// `ui.localization().tr(key)`.
+    pub fn tr(&self, key: &str) -> String {
+        self.localization.tr(key)
+    }
+
+    // Re-translates every text bound to a message, if the language or translations has changed.
+    fn update_localization(&mut self) {
+        let revision = self.localization.revision();
+        if self.localization_revision == revision {
+            return;
+        }
+        self.localization_revision = revision;
+
+        for node in self.nodes.iter_mut() {
+            if let Some(text) = node.cast_mut::<Text>() {
+                text.relocalize(&self.localization);
+                text.invalidate_visual();
+            }
+        }
+    }
+
+    fn update_toasts(&mut self, dt: f32) {
+        for entry in self.toasts.iter_mut() {
+            entry.time -= dt;
+        }
+
+        let nodes = &mut self.nodes;
+        let sender = &self.sender;
+        self.toasts.retain(|entry| {
+            if !nodes.is_valid_handle(entry.handle) {
+                false
+            } else if entry.time <= 0.0 {
+                sender
+                    .send(WidgetMessage::remove(
+                        entry.handle,
+                        MessageDirection::ToWidget,
+                    ))
+                    .unwrap();
+                false
+            } else {
+                let opacity = (entry.time / TOAST_FADE_TIME).min(1.0);
+                nodes[entry.handle].set_opacity(Some(opacity));
+                true
+            }
+        });
+    }
+
+    /// Stacks toasts at the bottom right corner of the screen, newest toast is at the bottom.
+    /// Must be called after measurement, so desired sizes of toasts are known.
+    fn arrange_toasts(&mut self) {
+        let mut bottom = self.screen_size.y - TOAST_SPACING;
+        for entry in self.toasts.iter().rev() {
+            let node = &mut self.nodes[entry.handle];
+            let size = node.desired_size();
+            let position =
+                Vector2::new(self.screen_size.x - TOAST_SPACING - size.x, bottom - size.y);
+            bottom -= size.y + TOAST_SPACING;
+
+            if node.desired_local_position() != position {
+                node.set_desired_local_position(position);
+                // Arrangement must be updated in the same frame.
+                let mut handle = entry.handle;
+                while let Some(node) = self.nodes.try_borrow(handle) {
+                    node.arrange_valid.set(false);
+                    handle = node.parent();
+                }
+            }
+        }
+    }
+
     pub fn keyboard_modifiers(&self) -> KeyboardModifiers {
         self.keyboard_modifiers
     }
 
+    /// Returns screen position for IME candidate window if a widget that accepts text input is
+    /// in focus, `None` - otherwise. It could be used to enable or disable IME for the window.
+    pub fn ime_position(&self) -> Option<Vector2<f32>> {
+        self.nodes
+            .try_borrow(self.keyboard_focus_node)
+            .and_then(|node| node.cast::<TextBox>())
+            .filter(|text_box| text_box.has_focus && text_box.editable)
+            .map(|text_box| text_box.ime_candidate_position())
+    }
+
     pub fn build_ctx(&mut self) -> BuildContext<'_> {
         BuildContext { ui: self }
     }
@@ -817,11 +1144,66 @@ impl UserInterface {
         }
     }
 
+    fn depth(&self, mut handle: Handle<UiNode>) -> usize {
+        let mut depth = 0;
+        while let Some(node) = self.nodes.try_borrow(handle) {
+            handle = node.parent();
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Updates visual transforms and clip bounds of subtrees of the widgets, that were arranged since
+    /// the last update.
     fn update_visual_transform(&mut self) {
         scope_profile!();
 
+        let dirty = std::mem::take(self.visual_dirty.get_mut())
+            .into_iter()
+            .collect::<FxHashSet<_>>();
+        if dirty.is_empty() {
+            return;
+        }
+
+        // Take only top-most widgets, their subtrees include the rest.
+        let roots = if dirty.contains(&self.root_canvas) {
+            vec![self.root_canvas]
+        } else {
+            dirty
+                .iter()
+                .filter(|handle| {
+                    let mut parent = self.nodes.try_borrow(**handle).map(|n| n.parent());
+                    while let Some(parent_handle) = parent {
+                        if dirty.contains(&parent_handle) {
+                            return false;
+                        }
+                        parent = self.nodes.try_borrow(parent_handle).map(|n| n.parent());
+                    }
+                    // Removed widgets are ignored.
+                    self.nodes.is_valid_handle(**handle)
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let mut statistics = self.layout_statistics.get();
+        for root in roots {
+            statistics.transform_updates += self.update_subtree_visual_transform(root);
+
+            let parent = self.nodes[root].parent();
+            let parent_bounds = match self.nodes.try_borrow(parent) {
+                Some(parent) => parent.clip_bounds(),
+                None => Rect::new(0.0, 0.0, self.screen_size.x, self.screen_size.y),
+            };
+            self.calculate_clip_bounds(root, parent_bounds);
+        }
+        self.layout_statistics.set(statistics);
+    }
+
+    fn update_subtree_visual_transform(&mut self, root: Handle<UiNode>) -> usize {
+        let mut count = 0;
         self.stack.clear();
-        self.stack.push(self.root_canvas);
+        self.stack.push(root);
         while let Some(node_handle) = self.stack.pop() {
             let (widget, parent) = self
                 .nodes
@@ -844,8 +1226,11 @@ impl UserInterface {
                 };
 
                 widget.visual_transform = visual_transform;
+                widget.invalidate_visual();
+                count += 1;
             }
         }
+        count
     }
 
     pub fn screen_size(&self) -> Vector2<f32> {
@@ -853,31 +1238,19 @@ impl UserInterface {
     }
 
     fn handle_layout_events(&mut self) {
-        fn invalidate_recursive_up(
-            nodes: &Pool<UiNode>,
-            node: Handle<UiNode>,
-            callback: fn(&UiNode),
-        ) {
-            if let Some(node_ref) = nodes.try_borrow(node) {
-                (callback)(node_ref);
-                if node_ref.parent().is_some() {
-                    invalidate_recursive_up(nodes, node_ref.parent(), callback);
-                }
-            }
-        }
-
         while let Ok(layout_event) = self.layout_events_receiver.try_recv() {
             match layout_event {
                 LayoutEvent::MeasurementInvalidated(node) => {
-                    invalidate_recursive_up(&self.nodes, node, |node_ref| {
-                        node_ref.measure_valid.set(false)
-                    });
+                    if let Some(node_ref) = self.nodes.try_borrow(node) {
+                        node_ref.measure_valid.set(false);
+                        self.measure_dirty.push(node);
+                    }
                 }
                 LayoutEvent::ArrangementInvalidated(node) => {
-                    invalidate_recursive_up(&self.nodes, node, |node_ref| {
-                        node_ref.arrange_valid.set(false)
-                    });
-                    self.need_update_global_transform = true;
+                    if let Some(node_ref) = self.nodes.try_borrow(node) {
+                        node_ref.arrange_valid.set(false);
+                        self.arrange_dirty.push(node);
+                    }
                 }
                 LayoutEvent::VisibilityChanged(node) => {
                     self.update_global_visibility(node);
@@ -886,6 +1259,146 @@ impl UserInterface {
         }
     }
 
+    fn invalidate_parent_layout(&mut self, node: Handle<UiNode>) {
+        if let Some(parent) = self.nodes.try_borrow(node).map(|n| n.parent()) {
+            if let Some(parent_ref) = self.nodes.try_borrow(parent) {
+                parent_ref.measure_valid.set(false);
+                parent_ref.arrange_valid.set(false);
+                self.measure_dirty.push(parent);
+                self.arrange_dirty.push(parent);
+            }
+        }
+    }
+
+    /// Re-measures invalidated widgets with their previous constraints. If desired size of a widget
+    /// is not changed, its parent does not need to be measured again, but it still must be
+    /// re-arranged, because the parent decides where the widget is placed (for example, canvas uses
+    /// desired position of the widget). Otherwise, the invalidation is propagated to the parent.
+    /// This way only the affected subtrees are laid out again instead of whole chains up to the
+    /// root.
+    fn measure_dirty_nodes(&mut self, arena: &FrameArena) {
+        let mut dirty = arena.vec_with_capacity(self.measure_dirty.len());
+        for node in std::mem::take(&mut self.measure_dirty) {
+            dirty.push((self.depth(node), node));
+        }
+        // Deepest widgets first, so every widget is measured at most once.
+        dirty.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        let mut level = arena.vec();
+        // Parents of the widgets of the current level, they form the next level together with
+        // the dirty widgets of the same depth.
+        let mut parents = arena.vec();
+        let mut next = 0;
+        let mut depth = dirty.first().map(|(depth, _)| *depth);
+        while let Some(current_depth) = depth {
+            level.clear();
+            level.extend_from_slice(&parents);
+            parents.clear();
+            while let Some((node_depth, node)) = dirty.get(next) {
+                if *node_depth != current_depth {
+                    break;
+                }
+                level.push(*node);
+                next += 1;
+            }
+
+            for &node in level.iter() {
+                let (measure_valid, prev_measure, desired_size, parent) =
+                    match self.nodes.try_borrow(node) {
+                        Some(node_ref) => (
+                            node_ref.is_measure_valid(),
+                            node_ref.prev_measure.get(),
+                            node_ref.desired_size(),
+                            node_ref.parent(),
+                        ),
+                        None => continue,
+                    };
+
+                // Already measured or the root, which is measured in the main layout pass.
+                if measure_valid || parent.is_none() {
+                    continue;
+                }
+
+                let parent_measure_valid = self.nodes[parent].is_measure_valid();
+                // NaN means that the widget was never measured.
+                if !parent_measure_valid || prev_measure.x.is_nan() {
+                    // Parent will measure the widget anyway.
+                } else {
+                    self.measure_node(node, prev_measure);
+                    if self.nodes[node].desired_size() == desired_size {
+                        self.nodes[node].arrange_valid.set(false);
+                        self.nodes[parent].arrange_valid.set(false);
+                        self.arrange_dirty.push(parent);
+                        continue;
+                    }
+                }
+
+                self.invalidate_parent_layout(node);
+                parents.push(parent);
+            }
+
+            depth = if parents.is_empty() {
+                dirty.get(next).map(|(depth, _)| *depth)
+            } else {
+                Some(current_depth - 1)
+            };
+        }
+
+        // Parents, that were pushed by invalidation, are already processed.
+        self.measure_dirty.clear();
+    }
+
+    /// Arranges invalidated widgets with their previous rectangles if their ancestors are not going
+    /// to be arranged, otherwise the invalidation is propagated up to the root.
+    fn arrange_dirty_nodes(&mut self, arena: &FrameArena) {
+        let mut dirty = arena.vec_with_capacity(self.arrange_dirty.len());
+        for node in std::mem::take(&mut self.arrange_dirty) {
+            dirty.push((self.depth(node), node));
+        }
+        // Top-most widgets first, arranging a widget arranges its descendants as well.
+        dirty.sort_by_key(|(depth, _)| *depth);
+
+        for &(_, node) in dirty.iter() {
+            let (arrange_valid, prev_arrange, mut parent) = match self.nodes.try_borrow(node) {
+                Some(node_ref) => (
+                    node_ref.is_arrange_valid(),
+                    node_ref.prev_arrange.get(),
+                    node_ref.parent(),
+                ),
+                None => continue,
+            };
+
+            if arrange_valid || parent.is_none() {
+                continue;
+            }
+
+            let mut ancestors_valid = !prev_arrange.position.x.is_nan();
+            while let Some(parent_ref) = self.nodes.try_borrow(parent) {
+                if !parent_ref.is_arrange_valid() {
+                    ancestors_valid = false;
+                }
+                parent = parent_ref.parent();
+            }
+
+            if ancestors_valid {
+                self.arrange_node(node, &prev_arrange);
+            } else {
+                // Make sure that the main layout pass will reach the widget.
+                let mut handle = node;
+                while let Some(node_ref) = self.nodes.try_borrow(handle) {
+                    node_ref.arrange_valid.set(false);
+                    handle = node_ref.parent();
+                }
+            }
+        }
+    }
+
+    /// Returns layout statistics of the last update. It could be used to find widgets, that cause
+    /// excessive layout updates.
+    pub fn layout_statistics(&self) -> LayoutStatistics {
+        self.last_layout_statistics
+    }
+
     pub fn update(&mut self, screen_size: Vector2<f32>, dt: f32) {
         scope_profile!();
 
@@ -895,25 +1408,29 @@ impl UserInterface {
             entry.timer -= dt;
         }
 
+        self.update_localization();
+
+        self.layout_statistics.set(Default::default());
+
         self.handle_layout_events();
 
+        self.update_toasts(dt);
+
+        let mut layout_arena = std::mem::take(&mut self.layout_arena);
+        self.measure_dirty_nodes(&layout_arena);
         self.measure_node(self.root_canvas, screen_size);
-        let arrangement_changed = self.arrange_node(
+        self.arrange_toasts();
+        self.arrange_dirty_nodes(&layout_arena);
+        layout_arena.reset();
+        self.layout_arena = layout_arena;
+        self.arrange_node(
             self.root_canvas,
             &Rect::new(0.0, 0.0, screen_size.x, screen_size.y),
         );
 
-        if self.need_update_global_transform {
-            self.update_visual_transform();
-            self.need_update_global_transform = false;
-        }
+        self.update_visual_transform();
 
-        if arrangement_changed {
-            self.calculate_clip_bounds(
-                self.root_canvas,
-                Rect::new(0.0, 0.0, self.screen_size.x, self.screen_size.y),
-            );
-        }
+        self.last_layout_statistics = self.layout_statistics.get();
 
         let sender = self.sender.clone();
         for node in self.nodes.iter_mut() {
@@ -1014,10 +1531,18 @@ impl UserInterface {
 
         let node = self.node(handle);
 
+        let mut statistics = self.layout_statistics.get();
+        statistics.arrange_calls += 1;
+
         if node.is_arrange_valid() && node.prev_arrange.get() == *final_rect {
+            self.layout_statistics.set(statistics);
             return false;
         }
 
+        statistics.arranged += 1;
+        self.layout_statistics.set(statistics);
+        self.visual_dirty.borrow_mut().push(handle);
+
         if node.visibility() {
             node.prev_arrange.set(*final_rect);
 
@@ -1086,10 +1611,19 @@ impl UserInterface {
 
         let node = self.node(handle);
 
+        let mut statistics = self.layout_statistics.get();
+        statistics.measure_calls += 1;
+
         if node.is_measure_valid() && node.prev_measure.get() == available_size {
+            self.layout_statistics.set(statistics);
             return false;
         }
 
+        statistics.measured += 1;
+        self.layout_statistics.set(statistics);
+        // Measurement may change content of the widget (text formatting for example).
+        node.invalidate_visual();
+
         if node.visibility() {
             node.prev_measure.set(available_size);
 
@@ -1156,15 +1690,10 @@ impl UserInterface {
             clipped = !widget.clip_bounds().contains(pt);
 
             if !clipped {
-                for command_index in widget.command_indices.borrow().iter() {
-                    if let Some(command) = self.drawing_context.get_commands().get(*command_index) {
-                        if let Some(geometry) = command.clipping_geometry.as_ref() {
-                            if geometry.is_contains_point(pt) {
-                                clipped = false;
-                                break;
-                            }
-                        }
-                    }
+                if let Some(clip_shape) = widget.clip_shape.as_ref() {
+                    clipped = !clip_shape
+                        .make_geometry(&widget.bounding_rect(), widget.visual_transform)
+                        .is_contains_point(pt);
                 }
             }
 
@@ -1462,9 +1991,27 @@ impl UserInterface {
     fn make_topmost(&mut self, node: Handle<UiNode>) {
         let parent = self.node(node).parent();
         if parent.is_some() {
-            let parent = &mut self.nodes[parent];
-            parent.remove_child(node);
-            parent.add_child(node, false);
+            self.nodes[parent].remove_child(node);
+            self.add_child_internal(parent, node, false);
+        }
+    }
+
+    /// Adds a child to a parent, overlay layers are kept on top of any content of the root canvas.
+    fn add_child_internal(
+        &mut self,
+        parent_handle: Handle<UiNode>,
+        child_handle: Handle<UiNode>,
+        in_front: bool,
+    ) {
+        let layers = self.layers;
+        let parent = &mut self.nodes[parent_handle];
+        if parent_handle == self.root_canvas && !in_front && !layers.contains(&child_handle) {
+            match parent.children().iter().position(|c| layers.contains(c)) {
+                Some(index) => parent.insert_child(index, child_handle),
+                None => parent.add_child(child_handle, false),
+            }
+        } else {
+            parent.add_child(child_handle, in_front);
         }
     }
 
@@ -1494,6 +2041,8 @@ impl UserInterface {
         while let Some(handle) = self.bubble_queue.pop_front() {
             let (ticket, mut node) = self.nodes.take_reserve(handle);
             node.handle_routed_message(self, message);
+            // Any message could change visual of the node.
+            node.invalidate_visual();
             self.nodes.put_back(ticket, node);
         }
     }
@@ -1671,6 +2220,26 @@ impl UserInterface {
                         }
                         _ => {}
                     }
+                } else if let Some(DialogMessage::Close(result)) = message.data() {
+                    if message.direction() == MessageDirection::ToWidget {
+                        let window = message.destination();
+                        if !self.close_dialog(window, result.clone()) {
+                            // Dialog without a callback.
+                            self.send_message(WindowMessage::close(
+                                window,
+                                MessageDirection::ToWidget,
+                            ));
+                            self.send_message(WidgetMessage::remove(
+                                window,
+                                MessageDirection::ToWidget,
+                            ));
+                        }
+                    }
+                } else if let Some(WindowMessage::Close) = message.data() {
+                    // Closing dialog window in any other way means that the dialog was cancelled.
+                    if message.direction() == MessageDirection::ToWidget {
+                        self.close_dialog(message.destination(), DialogResult::cancelled());
+                    }
                 }
 
                 Some(message)
@@ -1692,7 +2261,11 @@ impl UserInterface {
             MessageDirection::ToWidget,
             true,
         ));
-        self.send_message(WidgetMessage::topmost(*tooltip, MessageDirection::ToWidget));
+        self.send_message(WidgetMessage::link(
+            *tooltip,
+            MessageDirection::ToWidget,
+            self.layer(OverlayLayer::Tooltips),
+        ));
         self.send_message(WidgetMessage::desired_position(
             *tooltip,
             MessageDirection::ToWidget,
@@ -1913,23 +2486,30 @@ impl UserInterface {
                                 self.drag_context.is_dragging = false;
                                 self.cursor_icon = CursorIcon::Default;
 
-                                // Try to find node with drop allowed in hierarchy starting from picked node.
-                                self.stack.clear();
-                                self.stack.push(self.picked_node);
-                                while let Some(handle) = self.stack.pop() {
-                                    let node = &self.nodes[handle];
-                                    if node.is_drop_allowed() {
-                                        self.send_message(WidgetMessage::drop(
-                                            handle,
-                                            MessageDirection::FromWidget,
-                                            self.drag_context.drag_node,
-                                        ));
-                                        self.stack.clear();
-                                        break;
-                                    } else if node.parent().is_some() {
-                                        self.stack.push(node.parent());
-                                    }
+                                self.update_drop_target();
+
+                                let drop_target = self.drag_context.drop_target;
+                                if drop_target.is_some() && self.drag_context.is_drop_accepted {
+                                    self.send_message(WidgetMessage::drop(
+                                        drop_target,
+                                        MessageDirection::FromWidget,
+                                        self.drag_context.drag_node,
+                                    ));
+                                    self.send_message(WidgetMessage::drop_accepted(
+                                        self.drag_context.drag_node,
+                                        MessageDirection::FromWidget,
+                                        drop_target,
+                                    ));
+                                } else {
+                                    self.send_message(WidgetMessage::drop_rejected(
+                                        self.drag_context.drag_node,
+                                        MessageDirection::FromWidget,
+                                        drop_target,
+                                    ));
                                 }
+
+                                self.drag_context.drop_target = Handle::NONE;
+                                self.drag_context.is_drop_accepted = false;
                             }
                             self.drag_context.drag_node = Handle::NONE;
                             if self.nodes.is_valid_handle(self.drag_context.drag_preview) {
@@ -1955,6 +2535,11 @@ impl UserInterface {
                     self.drag_context.drag_preview =
                         self.copy_node_with_limit(self.drag_context.drag_node, Some(30));
                     self.nodes[self.drag_context.drag_preview].set_opacity(Some(0.5));
+                    self.link_nodes_internal(
+                        self.drag_context.drag_preview,
+                        self.layer(OverlayLayer::DragPreview),
+                        false,
+                    );
 
                     // Make preview nodes invisible for hit test.
                     let mut stack = vec![self.drag_context.drag_preview];
@@ -1965,6 +2550,8 @@ impl UserInterface {
                     }
 
                     self.drag_context.is_dragging = true;
+                    self.drag_context.payload =
+                        self.nodes[self.drag_context.drag_node].drag_payload.clone();
 
                     self.send_message(WidgetMessage::drag_started(
                         self.picked_node,
@@ -1975,6 +2562,17 @@ impl UserInterface {
                     self.cursor_icon = CursorIcon::Crosshair;
                 }
 
+                if self.drag_context.is_dragging {
+                    self.update_drop_target();
+                    self.cursor_icon = if self.drag_context.is_drop_accepted
+                        || self.drag_context.drop_target.is_none()
+                    {
+                        CursorIcon::Crosshair
+                    } else {
+                        CursorIcon::NoDrop
+                    };
+                }
+
                 if self.drag_context.is_dragging
                     && self.nodes.is_valid_handle(self.drag_context.drag_preview)
                 {
@@ -2071,6 +2669,17 @@ impl UserInterface {
                 // TODO: Is message needed for focused node?
                 self.keyboard_modifiers = modifiers;
             }
+            OsEvent::Ime(ime) => {
+                if self.keyboard_focus_node.is_some() {
+                    self.send_message(WidgetMessage::ime(
+                        self.keyboard_focus_node,
+                        MessageDirection::FromWidget,
+                        ime.clone(),
+                    ));
+
+                    event_processed = true;
+                }
+            }
         }
 
         self.prev_picked_node = self.picked_node;
@@ -2083,6 +2692,7 @@ impl UserInterface {
                     let (ticket, mut node) = self.nodes.take_reserve(handle);
 
                     node.handle_os_event(handle, self, event);
+                    node.invalidate_visual();
 
                     self.nodes.put_back(ticket, node);
                 }
@@ -2190,10 +2800,96 @@ impl UserInterface {
         self.preview_set.remove(&node);
     }
 
+    /// Builds the dialog and opens its window modally. Use [`DialogHandle::on_result`] to get
+    /// the result of the dialog, see [`dialog`] module docs for more info.
+    pub fn show_dialog<D: Dialog>(&mut self, dialog: D) -> DialogHandle<'_, D::Output> {
+        let window = dialog.build(&mut self.build_ctx());
+        self.send_message(WindowMessage::open_modal(
+            window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        DialogHandle {
+            ui: self,
+            window,
+            phantom: Default::default(),
+        }
+    }
+
+    fn close_dialog(&mut self, window: Handle<UiNode>, result: DialogResult) -> bool {
+        if let Some(callback) = self.dialog_callbacks.remove(&window) {
+            self.send_message(WindowMessage::close(window, MessageDirection::ToWidget));
+            self.send_message(WidgetMessage::remove(window, MessageDirection::ToWidget));
+            callback(self, result);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn drag_context(&self) -> &DragContext {
         &self.drag_context
     }
 
+    /// Returns payload of current (or last finished) drag operation.
+    pub fn drag_payload(&self) -> Option<&DragPayload> {
+        self.drag_context.payload.as_ref()
+    }
+
+    /// Replaces payload of current drag operation. Could be used in [`WidgetMessage::DragStarted`]
+    /// handler to provide a payload that depends on the state at the moment of drag start.
+    pub fn set_drag_payload(&mut self, payload: Option<DragPayload>) {
+        self.drag_context.payload = payload;
+    }
+
+    /// Replaces preview widget (a "ghost" that follows the cursor) of current drag operation.
+    /// By default, the preview is a semi-transparent copy of dragged widget. Could be used in
+    /// [`WidgetMessage::DragStarted`] handler, the widget will be removed when drag ends.
+    pub fn set_drag_preview(&mut self, preview: Handle<UiNode>) {
+        if !self.drag_context.is_dragging || !self.nodes.is_valid_handle(preview) {
+            return;
+        }
+
+        if self.nodes.is_valid_handle(self.drag_context.drag_preview) {
+            self.remove_node(self.drag_context.drag_preview);
+        }
+
+        // Make preview nodes invisible for hit test.
+        let mut stack = vec![preview];
+        while let Some(handle) = stack.pop() {
+            let preview_node = &mut self.nodes[handle];
+            preview_node.hit_test_visibility = false;
+            stack.extend_from_slice(preview_node.children());
+        }
+
+        self.drag_context.drag_preview = preview;
+
+        self.send_message(WidgetMessage::desired_position(
+            preview,
+            MessageDirection::ToWidget,
+            self.cursor_position,
+        ));
+    }
+
+    fn update_drop_target(&mut self) {
+        self.drag_context.drop_target = Handle::NONE;
+        self.drag_context.is_drop_accepted = false;
+
+        // Try to find node with drop allowed in hierarchy starting from picked node.
+        let mut handle = self.picked_node;
+        while let Some(node) = self.nodes.try_borrow(handle) {
+            if node.is_drop_allowed() {
+                self.drag_context.drop_target = handle;
+                self.drag_context.is_drop_accepted = node
+                    .drop_filter
+                    .as_ref()
+                    .map_or(true, |f| f.accepts(self.drag_context.payload.as_ref()));
+                break;
+            }
+            handle = node.parent();
+        }
+    }
+
     /// Links specified child with specified parent.
     #[inline]
     fn link_nodes_internal(
@@ -2205,7 +2901,7 @@ impl UserInterface {
         assert_ne!(child_handle, parent_handle);
         self.unlink_node_internal(child_handle);
         self.nodes[child_handle].set_parent(parent_handle);
-        self.nodes[parent_handle].add_child(child_handle, in_front);
+        self.add_child_internal(parent_handle, child_handle, in_front);
     }
 
     /// Unlinks specified node from its parent, so node will become root.
@@ -2518,8 +3214,13 @@ fn transform_size(transform_space_bounds: Vector2<f32>, matrix: &Matrix3<f32>) -
 mod test {
     use crate::{
         border::BorderBuilder,
-        core::algebra::{Rotation2, UnitComplex, Vector2},
-        message::MessageDirection,
+        brush::Brush,
+        core::{
+            algebra::{Rotation2, UnitComplex, Vector2},
+            color::Color,
+        },
+        message::{ImeEvent, MessageDirection},
+        overlay::{OverlayLayer, ToastBuilder},
         text::TextMessage,
         text_box::TextBoxBuilder,
         transform_size,
@@ -2610,5 +3311,148 @@ mod test {
         );
 
         assert!(ui.poll_message().is_none());
+
+        // Composition string must not be inserted until it is committed.
+        let preedit = ImeEvent::Preedit {
+            text: "ni".to_string(),
+            cursor: Some((2, 2)),
+        };
+        ui.process_os_event(&OsEvent::Ime(preedit.clone()));
+        // The message is handled by the text box, so compare its contents only.
+        let message = ui.poll_message().unwrap();
+        assert_eq!(message.destination(), text_box);
+        assert_eq!(message.direction(), MessageDirection::FromWidget);
+        assert_eq!(message.data(), Some(&WidgetMessage::Ime(preedit)));
+        assert!(ui.poll_message().is_none());
+        assert!(ui.ime_position().is_some());
+
+        let commit = ImeEvent::Commit("你".to_string());
+        ui.process_os_event(&OsEvent::Ime(commit.clone()));
+        let message = ui.poll_message().unwrap();
+        assert_eq!(message.destination(), text_box);
+        assert_eq!(message.direction(), MessageDirection::FromWidget);
+        assert_eq!(message.data(), Some(&WidgetMessage::Ime(commit)));
+        assert_eq!(
+            ui.poll_message(),
+            Some(TextMessage::text(
+                text_box,
+                MessageDirection::ToWidget,
+                "A你".to_string()
+            ))
+        );
+
+        assert!(ui.poll_message().is_none());
+    }
+
+    #[test]
+    fn test_overlay_layers() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let content = BorderBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx());
+        ui.send_message(WidgetMessage::topmost(content, MessageDirection::ToWidget));
+        while ui.poll_message().is_some() {}
+
+        // Layers must stay on top of any content.
+        let children = ui.node(ui.root()).children().to_vec();
+        assert_eq!(children.len(), 5);
+        assert_eq!(children[0], content);
+        for (child, layer) in children[1..].iter().zip(OverlayLayer::OVERLAYS.iter()) {
+            assert_eq!(*child, ui.layer(*layer));
+        }
+
+        let toast = ToastBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(20.0))
+            .with_duration(1.0)
+            .build(&mut ui.build_ctx());
+        assert_eq!(
+            ui.node(toast).parent(),
+            ui.layer(OverlayLayer::Notifications)
+        );
+        ui.update(screen_size, 0.0);
+        assert_eq!(
+            ui.node(toast).actual_local_position(),
+            Vector2::new(890.0, 970.0)
+        );
+
+        // Toast must be removed when its time is out.
+        ui.update(screen_size, 2.0);
+        while ui.poll_message().is_some() {}
+        assert!(!ui.nodes().is_valid_handle(toast));
+    }
+
+    #[test]
+    fn test_geometry_cache() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let border = BorderBuilder::new(WidgetBuilder::new().with_width(50.0).with_height(50.0))
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        while ui.poll_message().is_some() {}
+
+        ui.draw();
+        let statistics = ui.get_drawing_context().statistics();
+        assert!(statistics.geometry_cache_misses > 0);
+
+        // Nothing has changed, every widget must be drawn from cache.
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        let statistics = ui.get_drawing_context().statistics();
+        assert_eq!(statistics.geometry_cache_misses, 0);
+        assert_eq!(statistics.geometry_cache_hit_rate(), 1.0);
+
+        // Only the changed widget and its ancestors should be drawn again.
+        ui.send_message(WidgetMessage::background(
+            border,
+            MessageDirection::ToWidget,
+            Brush::Solid(Color::RED),
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        ui.draw();
+        let statistics = ui.get_drawing_context().statistics();
+        assert_eq!(statistics.geometry_cache_misses, 2);
+        assert!(statistics.geometry_cache_hits > 0);
+    }
+
+    #[test]
+    fn test_partial_layout() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let child = BorderBuilder::new(WidgetBuilder::new().with_width(50.0).with_height(50.0))
+            .build(&mut ui.build_ctx());
+        let parent =
+            BorderBuilder::new(WidgetBuilder::new().with_child(child)).build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        assert!(ui.layout_statistics().measured > 0);
+
+        // Nothing has changed, layout must be fully cached.
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.layout_statistics().measured, 0);
+        assert_eq!(ui.layout_statistics().arranged, 0);
+
+        // Desired size of the child stays the same, so only the child is measured again, but the
+        // parent still must place it.
+        ui.node(child).invalidate_layout();
+        ui.update(screen_size, 0.0);
+        assert_eq!(ui.layout_statistics().measured, 1);
+        assert_eq!(ui.layout_statistics().arranged, 2);
+        assert!(ui.node(parent).is_measure_valid());
+
+        // Size change must be propagated to the parent.
+        ui.send_message(WidgetMessage::width(
+            child,
+            MessageDirection::ToWidget,
+            100.0,
+        ));
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+        // Border has 1px stroke by default.
+        assert_eq!(ui.node(parent).desired_size(), Vector2::new(102.0, 52.0));
+        assert_eq!(
+            ui.node(child).actual_local_size(),
+            Vector2::new(100.0, 50.0)
+        );
     }
 }
@@ -8,6 +8,7 @@ use crate::{
     message::{MessageDirection, UiMessage},
     scroll_viewer::{ScrollViewer, ScrollViewerBuilder, ScrollViewerMessage},
     stack_panel::StackPanelBuilder,
+    utils::collect_text,
     widget::{Widget, WidgetBuilder, WidgetMessage},
     BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface, BRUSH_DARK,
     BRUSH_LIGHT,
@@ -24,6 +25,10 @@ pub enum ListViewMessage {
     AddItem(Handle<UiNode>),
     RemoveItem(Handle<UiNode>),
     BringItemIntoView(Handle<UiNode>),
+    /// Hides every item which text does not contain given filter string (case-insensitive).
+    /// Empty string resets the filter. Text of an item is collected from every
+    /// [`crate::text::Text`] widget in it.
+    Filter(String),
 }
 
 impl ListViewMessage {
@@ -32,6 +37,7 @@ impl ListViewMessage {
     define_constructor!(ListViewMessage:AddItem => fn add_item(Handle<UiNode>), layout: false);
     define_constructor!(ListViewMessage:RemoveItem => fn remove_item(Handle<UiNode>), layout: false);
     define_constructor!(ListViewMessage:BringItemIntoView => fn bring_item_into_view(Handle<UiNode>), layout: false);
+    define_constructor!(ListViewMessage:Filter => fn filter(String), layout: false);
 }
 
 #[derive(Clone)]
@@ -271,6 +277,18 @@ impl Control for ListView {
                             ));
                         }
                     }
+                    ListViewMessage::Filter(filter) => {
+                        let filter = filter.to_lowercase();
+                        for (&item, &container) in self.items.iter().zip(&self.item_containers) {
+                            let is_match = filter.is_empty()
+                                || collect_text(ui, item).to_lowercase().contains(&filter);
+                            ui.send_message(WidgetMessage::visibility(
+                                container,
+                                MessageDirection::ToWidget,
+                                is_match,
+                            ));
+                        }
+                    }
                 }
             }
         }
@@ -0,0 +1,1121 @@
+//! Localization support. Translations are stored in [`LocalizationBundle`]s, one bundle per
+//! language, that could be loaded from [Fluent](https://projectfluent.org) (`.ftl`) or gettext
+//! (`.po`) sources. [`Localization`] is a shared service that holds the bundles and current
+//! language, it is available via [`crate::UserInterface::localization`].
+//!
+//! # Fluent support
+//!
+//! Only a subset of Fluent syntax is supported: messages, terms, attributes (`key.attribute`),
+//! multiline values, variables (`{ $name }`), message and term references, string and number
+//! literals and selectors (`{ $count -> [one] ... *[other] ... }`) with plural categories. Functions
+//! (`NUMBER()`, `DATETIME()`) and parametrized terms are not supported.
+//!
+//! ```text
+//! -game-name = Fyrox Tanks
+//! menu-start = Start { -game-name }
+//! apples = { $count ->
+//!     [0] No apples
+//!     [one] One apple
+//!    *[other] { $count } apples
+//! }
+//! ```
+//!
+//! # Gettext support
+//!
+//! Every `msgid` becomes a key of a message, translations (`msgstr`) are parsed as Fluent patterns,
+//! so they can use variables. Plural forms (`msgstr[N]`) are mapped to plural categories of the
+//! language and selected by `$count` variable.
+
+use crate::core::parking_lot::Mutex;
+use fxhash::FxHashMap;
+use std::{
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+/// Maximum depth of message references, it prevents infinite recursion on cyclic references.
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+/// A value of a localization argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalizationValue {
+    /// A string.
+    String(String),
+    /// A number, it is used to select plural forms.
+    Number(f64),
+}
+
+impl Display for LocalizationValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalizationValue::String(v) => write!(f, "{}", v),
+            LocalizationValue::Number(v) => {
+                if v.fract() == 0.0 && v.abs() < 1.0e15 {
+                    write!(f, "{}", *v as i64)
+                } else {
+                    write!(f, "{}", v)
+                }
+            }
+        }
+    }
+}
+
+impl From<&str> for LocalizationValue {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_owned())
+    }
+}
+
+impl From<String> for LocalizationValue {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+macro_rules! impl_number_value {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for LocalizationValue {
+                fn from(v: $ty) -> Self {
+                    Self::Number(v as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_number_value!(i8, u8, i16, u16, i32, u32, i64, u64, isize, usize, f32, f64);
+
+/// A set of named arguments of a message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocalizationArgs {
+    args: Vec<(String, LocalizationValue)>,
+}
+
+impl LocalizationArgs {
+    /// Creates new empty set of arguments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds new argument (or replaces existing one with the same name).
+    pub fn with<N: AsRef<str>, V: Into<LocalizationValue>>(mut self, name: N, value: V) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Adds new argument (or replaces existing one with the same name).
+    pub fn set<N: AsRef<str>, V: Into<LocalizationValue>>(&mut self, name: N, value: V) {
+        let name = name.as_ref();
+        let value = value.into();
+        if let Some((_, existing)) = self.args.iter_mut().find(|(n, _)| n == name) {
+            *existing = value;
+        } else {
+            self.args.push((name.to_owned(), value));
+        }
+    }
+
+    /// Returns a value of an argument with given name.
+    pub fn get(&self, name: &str) -> Option<&LocalizationValue> {
+        self.args.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+}
+
+/// A key of a message together with its arguments. It is used to bind widgets to messages, so
+/// they could be re-translated when the language changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizedText {
+    /// A key of a message.
+    pub key: String,
+    /// Arguments of the message.
+    pub args: LocalizationArgs,
+}
+
+impl LocalizedText {
+    /// Creates new localized text without arguments.
+    pub fn new<K: AsRef<str>>(key: K) -> Self {
+        Self {
+            key: key.as_ref().to_owned(),
+            args: Default::default(),
+        }
+    }
+
+    /// Sets arguments of the message.
+    pub fn with_args(mut self, args: LocalizationArgs) -> Self {
+        self.args = args;
+        self
+    }
+}
+
+/// An error that may occur during parsing of translation sources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizationError {
+    /// Line number (starting from 1) at which the error has occurred.
+    pub line: usize,
+    /// Error description.
+    pub message: String,
+}
+
+impl Display for LocalizationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Syntax error at line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PatternElement {
+    Text(String),
+    Placeable(Expression),
+}
+
+type Pattern = Vec<PatternElement>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum VariantKey {
+    Identifier(String),
+    Number(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Variant {
+    key: VariantKey,
+    pattern: Pattern,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Variable(String),
+    Message(String),
+    Term(String),
+    String(String),
+    Number(f64),
+    Select {
+        selector: Box<Expression>,
+        variants: Vec<Variant>,
+        default: usize,
+    },
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+impl PatternParser {
+    fn new(source: &str, line: usize) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            position: 0,
+            line,
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, LocalizationError> {
+        Err(LocalizationError {
+            line: self.line,
+            message: message.to_owned(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), LocalizationError> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            self.error(&format!("'{}' expected", expected))
+        }
+    }
+
+    fn identifier(&mut self, allow_attribute: bool) -> String {
+        let mut identifier = String::new();
+        while let Some(c) = self.peek() {
+            if is_identifier_char(c) || (allow_attribute && c == '.') {
+                identifier.push(c);
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+        identifier
+    }
+
+    fn pattern(&mut self, in_variant: bool) -> Result<Pattern, LocalizationError> {
+        let mut pattern = Pattern::new();
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            if in_variant && (c == '\n' || c == '}') {
+                break;
+            }
+
+            self.position += 1;
+
+            if c == '{' {
+                if !text.is_empty() {
+                    pattern.push(PatternElement::Text(std::mem::take(&mut text)));
+                }
+                pattern.push(PatternElement::Placeable(self.placeable()?));
+            } else {
+                text.push(c);
+            }
+        }
+
+        if in_variant {
+            let trimmed = text.trim_end().len();
+            text.truncate(trimmed);
+        }
+        if !text.is_empty() {
+            pattern.push(PatternElement::Text(text));
+        }
+
+        Ok(pattern)
+    }
+
+    fn placeable(&mut self) -> Result<Expression, LocalizationError> {
+        self.skip_whitespace();
+        let expression = self.inline_expression()?;
+        self.skip_whitespace();
+
+        if self.peek() == Some('-') && self.peek_at(1) == Some('>') {
+            self.position += 2;
+
+            let mut variants = Vec::new();
+            let mut default = None;
+            loop {
+                self.skip_whitespace();
+                match self.peek() {
+                    Some('}') => {
+                        self.position += 1;
+                        break;
+                    }
+                    Some('*') => {
+                        self.position += 1;
+                        if default.is_some() {
+                            return self.error("only one default variant is allowed");
+                        }
+                        default = Some(variants.len());
+                    }
+                    None => return self.error("unterminated selector"),
+                    _ => (),
+                }
+
+                self.expect('[')?;
+                let mut key = String::new();
+                while let Some(c) = self.peek() {
+                    self.position += 1;
+                    if c == ']' {
+                        break;
+                    }
+                    key.push(c);
+                }
+                let key = key.trim();
+                let key = match key.parse::<f64>() {
+                    Ok(number) => VariantKey::Number(number),
+                    Err(_) => VariantKey::Identifier(key.to_owned()),
+                };
+
+                while self.peek() == Some(' ') || self.peek() == Some('\t') {
+                    self.position += 1;
+                }
+
+                let pattern = self.pattern(true)?;
+                variants.push(Variant { key, pattern });
+            }
+
+            match default {
+                Some(default) => Ok(Expression::Select {
+                    selector: Box::new(expression),
+                    variants,
+                    default,
+                }),
+                None => self.error("selector must have a default variant"),
+            }
+        } else {
+            self.expect('}')?;
+            Ok(expression)
+        }
+    }
+
+    fn inline_expression(&mut self) -> Result<Expression, LocalizationError> {
+        match self.peek() {
+            Some('$') => {
+                self.position += 1;
+                Ok(Expression::Variable(self.identifier(false)))
+            }
+            Some('"') => {
+                self.position += 1;
+                let mut string = String::new();
+                loop {
+                    match self.peek() {
+                        Some('"') => {
+                            self.position += 1;
+                            break;
+                        }
+                        Some('\\') => {
+                            self.position += 1;
+                            if let Some(c) = self.peek() {
+                                string.push(c);
+                                self.position += 1;
+                            }
+                        }
+                        Some(c) => {
+                            string.push(c);
+                            self.position += 1;
+                        }
+                        None => return self.error("unterminated string literal"),
+                    }
+                }
+                Ok(Expression::String(string))
+            }
+            Some('-') if self.peek_at(1).map_or(false, |c| c.is_alphabetic()) => {
+                self.position += 1;
+                Ok(Expression::Term(self.identifier(false)))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' => {
+                let mut number = String::new();
+                number.push(c);
+                self.position += 1;
+                while let Some(c) = self.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        number.push(c);
+                        self.position += 1;
+                    } else {
+                        break;
+                    }
+                }
+                match number.parse() {
+                    Ok(number) => Ok(Expression::Number(number)),
+                    Err(_) => self.error("invalid number literal"),
+                }
+            }
+            Some(c) if c.is_alphabetic() => {
+                let identifier = self.identifier(true);
+                if self.peek() == Some('(') {
+                    self.error("functions are not supported")
+                } else {
+                    Ok(Expression::Message(identifier))
+                }
+            }
+            _ => self.error("expression expected"),
+        }
+    }
+}
+
+fn parse_pattern(source: &str, line: usize) -> Result<Pattern, LocalizationError> {
+    PatternParser::new(source, line).pattern(false)
+}
+
+fn primary_language(language: &str) -> String {
+    language
+        .split(|c| c == '-' || c == '_')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Returns plural category (`zero`, `one`, `two`, `few`, `many` or `other`) of a number for the
+/// given language. Rules for most common languages are supported, other languages use English
+/// rules.
+pub fn plural_category(language: &str, number: f64) -> &'static str {
+    let is_integer = number.fract() == 0.0;
+    let i = number.abs().trunc() as u64;
+    match primary_language(language).as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" => "other",
+        "fr" | "pt" => {
+            if i == 0 || i == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" => {
+            if !is_integer {
+                "other"
+            } else if i % 10 == 1 && i % 100 != 11 {
+                "one"
+            } else if (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "pl" => {
+            if !is_integer {
+                "other"
+            } else if i == 1 {
+                "one"
+            } else if (2..=4).contains(&(i % 10)) && !(12..=14).contains(&(i % 100)) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "cs" | "sk" => {
+            if !is_integer {
+                "many"
+            } else if i == 1 {
+                "one"
+            } else if (2..=4).contains(&i) {
+                "few"
+            } else {
+                "other"
+            }
+        }
+        _ => {
+            if is_integer && i == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+}
+
+/// Returns plural categories of the language in the order of gettext plural forms.
+fn gettext_plural_categories(language: &str) -> &'static [&'static str] {
+    match primary_language(language).as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" => &["other"],
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" | "pl" => &["one", "few", "many"],
+        "cs" | "sk" => &["one", "few", "other"],
+        _ => &["one", "other"],
+    }
+}
+
+fn unescape_po_string(line: &str, line_number: usize) -> Result<String, LocalizationError> {
+    let line = line.trim();
+    if line.len() < 2 || !line.starts_with('"') || !line.ends_with('"') {
+        return Err(LocalizationError {
+            line: line_number,
+            message: "quoted string expected".to_owned(),
+        });
+    }
+
+    let mut string = String::new();
+    let mut chars = line[1..line.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some(c) => string.push(c),
+                None => (),
+            }
+        } else {
+            string.push(c);
+        }
+    }
+    Ok(string)
+}
+
+#[derive(Default)]
+struct PoEntry {
+    id: String,
+    translations: Vec<String>,
+    plural: bool,
+    line: usize,
+}
+
+/// A set of translated messages of a single language.
+#[derive(Debug, Clone, Default)]
+pub struct LocalizationBundle {
+    language: String,
+    messages: FxHashMap<String, Pattern>,
+    terms: FxHashMap<String, Pattern>,
+}
+
+impl LocalizationBundle {
+    /// Creates new empty bundle for the given language (for example `en-US`).
+    pub fn new<L: AsRef<str>>(language: L) -> Self {
+        Self {
+            language: language.as_ref().to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates new bundle from Fluent source. See module docs for supported syntax.
+    pub fn from_fluent<L: AsRef<str>>(
+        language: L,
+        source: &str,
+    ) -> Result<Self, LocalizationError> {
+        let mut bundle = Self::new(language);
+        bundle.add_fluent(source)?;
+        Ok(bundle)
+    }
+
+    /// Creates new bundle from gettext (`.po`) source.
+    pub fn from_gettext<L: AsRef<str>>(
+        language: L,
+        source: &str,
+    ) -> Result<Self, LocalizationError> {
+        let mut bundle = Self::new(language);
+        bundle.add_gettext(source)?;
+        Ok(bundle)
+    }
+
+    /// Returns language of the bundle.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Returns `true` if the bundle contains a message with the given key.
+    pub fn has_message(&self, key: &str) -> bool {
+        self.messages.contains_key(key)
+    }
+
+    /// Returns an iterator over keys of every message in the bundle.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(|k| k.as_str())
+    }
+
+    /// Adds every message from the other bundle, existing messages with the same keys are
+    /// replaced.
+    pub fn merge(&mut self, other: LocalizationBundle) {
+        self.messages.extend(other.messages);
+        self.terms.extend(other.terms);
+    }
+
+    /// Parses Fluent source and adds every message from it to the bundle.
+    pub fn add_fluent(&mut self, source: &str) -> Result<(), LocalizationError> {
+        // Message id, id of the attribute owner, value and line number.
+        let mut current: Option<(String, String, usize)> = None;
+        let mut owner = String::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Closing brace of a selector and variants are allowed to be at the line start.
+            if line.starts_with(|c: char| c.is_whitespace() || c == '}' || c == '[' || c == '*') {
+                let trimmed = line.trim();
+
+                if trimmed.starts_with('.') && trimmed.contains('=') && !owner.is_empty() {
+                    self.flush_fluent_entry(current.take())?;
+                    let (attribute, value) = trimmed[1..].split_once('=').unwrap();
+                    current = Some((
+                        format!("{}.{}", owner, attribute.trim()),
+                        value.trim().to_owned(),
+                        line_number,
+                    ));
+                } else if let Some((_, value, _)) = current.as_mut() {
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(trimmed);
+                } else {
+                    return Err(LocalizationError {
+                        line: line_number,
+                        message: "unexpected indented line".to_owned(),
+                    });
+                }
+            } else if line.starts_with('#') {
+                self.flush_fluent_entry(current.take())?;
+                owner.clear();
+            } else {
+                self.flush_fluent_entry(current.take())?;
+
+                let (id, value) = line.split_once('=').ok_or_else(|| LocalizationError {
+                    line: line_number,
+                    message: "'=' expected".to_owned(),
+                })?;
+                let id = id.trim();
+                let identifier = id.strip_prefix('-').unwrap_or(id);
+                if identifier.is_empty()
+                    || !identifier.starts_with(|c: char| c.is_alphabetic())
+                    || !identifier.chars().all(is_identifier_char)
+                {
+                    return Err(LocalizationError {
+                        line: line_number,
+                        message: format!("invalid identifier {}", id),
+                    });
+                }
+
+                owner = id.to_owned();
+                current = Some((id.to_owned(), value.trim().to_owned(), line_number));
+            }
+        }
+
+        self.flush_fluent_entry(current)
+    }
+
+    fn flush_fluent_entry(
+        &mut self,
+        entry: Option<(String, String, usize)>,
+    ) -> Result<(), LocalizationError> {
+        if let Some((id, value, line)) = entry {
+            let pattern = parse_pattern(&value, line)?;
+            if let Some(term) = id.strip_prefix('-') {
+                self.terms.insert(term.to_owned(), pattern);
+            } else {
+                self.messages.insert(id, pattern);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses gettext (`.po`) source and adds every translated message from it to the bundle.
+    pub fn add_gettext(&mut self, source: &str) -> Result<(), LocalizationError> {
+        let mut entry = PoEntry::default();
+        // Index of a string that is continued by lines that start from a quote, `None` means msgid.
+        let mut last: Option<Option<usize>> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('"') {
+                let string = unescape_po_string(line, line_number)?;
+                match last {
+                    Some(None) => entry.id.push_str(&string),
+                    Some(Some(index)) => entry.translations[index].push_str(&string),
+                    None => {
+                        return Err(LocalizationError {
+                            line: line_number,
+                            message: "unexpected string".to_owned(),
+                        })
+                    }
+                }
+                continue;
+            }
+
+            let (keyword, rest) = line.split_at(line.find(' ').unwrap_or(line.len()));
+            match keyword {
+                "msgctxt" => {
+                    self.flush_po_entry(std::mem::take(&mut entry))?;
+                    last = None;
+                }
+                "msgid" => {
+                    if !entry.id.is_empty() || !entry.translations.is_empty() {
+                        self.flush_po_entry(std::mem::take(&mut entry))?;
+                    }
+                    entry.id = unescape_po_string(rest, line_number)?;
+                    entry.line = line_number;
+                    last = Some(None);
+                }
+                "msgid_plural" => {
+                    entry.plural = true;
+                    last = None;
+                }
+                _ if keyword.starts_with("msgstr") => {
+                    entry
+                        .translations
+                        .push(unescape_po_string(rest, line_number)?);
+                    last = Some(Some(entry.translations.len() - 1));
+                }
+                _ => {
+                    return Err(LocalizationError {
+                        line: line_number,
+                        message: format!("unknown keyword {}", keyword),
+                    })
+                }
+            }
+        }
+
+        self.flush_po_entry(entry)
+    }
+
+    fn flush_po_entry(&mut self, entry: PoEntry) -> Result<(), LocalizationError> {
+        // Empty id is the header, empty translations mean that the message is not translated.
+        if entry.id.is_empty() || entry.translations.iter().all(|t| t.is_empty()) {
+            return Ok(());
+        }
+
+        let pattern = if entry.plural {
+            let categories = gettext_plural_categories(&self.language);
+            let variants = entry
+                .translations
+                .iter()
+                .zip(categories.iter())
+                .map(|(translation, category)| {
+                    Ok(Variant {
+                        key: VariantKey::Identifier((*category).to_owned()),
+                        pattern: parse_pattern(translation, entry.line)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, LocalizationError>>()?;
+            vec![PatternElement::Placeable(Expression::Select {
+                selector: Box::new(Expression::Variable("count".to_owned())),
+                default: variants.len() - 1,
+                variants,
+            })]
+        } else {
+            parse_pattern(&entry.translations[0], entry.line)?
+        };
+
+        self.messages.insert(entry.id, pattern);
+
+        Ok(())
+    }
+
+    /// Formats a message with given key and arguments. Returns `None` if there is no such message.
+    pub fn format(&self, key: &str, args: &LocalizationArgs) -> Option<String> {
+        let pattern = self.messages.get(key)?;
+        let mut output = String::new();
+        self.format_pattern(pattern, args, &mut output, 0);
+        Some(output)
+    }
+
+    fn format_pattern(
+        &self,
+        pattern: &[PatternElement],
+        args: &LocalizationArgs,
+        output: &mut String,
+        depth: usize,
+    ) {
+        for element in pattern {
+            match element {
+                PatternElement::Text(text) => output.push_str(text),
+                PatternElement::Placeable(expression) => {
+                    self.format_expression(expression, args, output, depth)
+                }
+            }
+        }
+    }
+
+    fn format_expression(
+        &self,
+        expression: &Expression,
+        args: &LocalizationArgs,
+        output: &mut String,
+        depth: usize,
+    ) {
+        match expression {
+            Expression::Variable(name) => match args.get(name) {
+                Some(value) => output.push_str(&value.to_string()),
+                None => {
+                    output.push_str("{$");
+                    output.push_str(name);
+                    output.push('}');
+                }
+            },
+            Expression::Message(id) | Expression::Term(id) => {
+                let patterns = if let Expression::Term(_) = expression {
+                    &self.terms
+                } else {
+                    &self.messages
+                };
+                match patterns.get(id) {
+                    Some(pattern) if depth < MAX_REFERENCE_DEPTH => {
+                        self.format_pattern(pattern, args, output, depth + 1)
+                    }
+                    _ => {
+                        output.push('{');
+                        output.push_str(id);
+                        output.push('}');
+                    }
+                }
+            }
+            Expression::String(string) => output.push_str(string),
+            Expression::Number(number) => {
+                output.push_str(&LocalizationValue::Number(*number).to_string())
+            }
+            Expression::Select {
+                selector,
+                variants,
+                default,
+            } => {
+                let value = match selector.as_ref() {
+                    Expression::Variable(name) => args.get(name).cloned(),
+                    Expression::Number(number) => Some(LocalizationValue::Number(*number)),
+                    other => {
+                        let mut string = String::new();
+                        self.format_expression(other, args, &mut string, depth + 1);
+                        Some(LocalizationValue::String(string))
+                    }
+                };
+
+                let index = match value {
+                    Some(LocalizationValue::Number(number)) => variants
+                        .iter()
+                        .position(|v| v.key == VariantKey::Number(number))
+                        .or_else(|| {
+                            let category = plural_category(&self.language, number);
+                            variants
+                                .iter()
+                                .position(|v| v.key == VariantKey::Identifier(category.to_owned()))
+                        }),
+                    Some(LocalizationValue::String(string)) => variants
+                        .iter()
+                        .position(|v| v.key == VariantKey::Identifier(string.clone())),
+                    None => None,
+                }
+                .unwrap_or(*default);
+
+                self.format_pattern(&variants[index].pattern, args, output, depth);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LocalizationState {
+    bundles: Vec<LocalizationBundle>,
+    language: String,
+    fallback_language: String,
+    revision: u64,
+}
+
+impl LocalizationState {
+    fn find_bundle(&self, language: &str) -> Option<&LocalizationBundle> {
+        self.bundles
+            .iter()
+            .find(|b| b.language == language)
+            .or_else(|| {
+                let primary = primary_language(language);
+                self.bundles
+                    .iter()
+                    .find(|b| primary_language(&b.language) == primary)
+            })
+    }
+}
+
+/// Localization service, it holds translation bundles and current language. The service is
+/// shared - every clone of it refers to the same data, so it could be passed to any place where
+/// translations are needed (scripts, plugins, widgets). See module docs for more info.
+#[derive(Debug, Clone, Default)]
+pub struct Localization {
+    state: Arc<Mutex<LocalizationState>>,
+}
+
+impl Localization {
+    /// Creates new localization service without any bundles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds new bundle. If there is a bundle for the same language, the bundles are merged.
+    pub fn add_bundle(&self, bundle: LocalizationBundle) {
+        let mut state = self.state.lock();
+        if let Some(existing) = state
+            .bundles
+            .iter_mut()
+            .find(|b| b.language == bundle.language)
+        {
+            existing.merge(bundle);
+        } else {
+            state.bundles.push(bundle);
+        }
+        state.revision += 1;
+    }
+
+    /// Removes every bundle of the given language.
+    pub fn remove_language(&self, language: &str) {
+        let mut state = self.state.lock();
+        state.bundles.retain(|b| b.language != language);
+        state.revision += 1;
+    }
+
+    /// Returns a list of languages of every bundle.
+    pub fn languages(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .bundles
+            .iter()
+            .map(|b| b.language.clone())
+            .collect()
+    }
+
+    /// Sets current language. Every widget bound to a message will be re-translated on next
+    /// update of the user interface.
+    pub fn set_language<L: AsRef<str>>(&self, language: L) {
+        let mut state = self.state.lock();
+        if state.language != language.as_ref() {
+            state.language = language.as_ref().to_owned();
+            state.revision += 1;
+        }
+    }
+
+    /// Returns current language.
+    pub fn language(&self) -> String {
+        self.state.lock().language.clone()
+    }
+
+    /// Sets a language, that will be used for messages that are missing in current language.
+    pub fn set_fallback_language<L: AsRef<str>>(&self, language: L) {
+        let mut state = self.state.lock();
+        state.fallback_language = language.as_ref().to_owned();
+        state.revision += 1;
+    }
+
+    /// Returns fallback language.
+    pub fn fallback_language(&self) -> String {
+        self.state.lock().fallback_language.clone()
+    }
+
+    /// Returns a number, that is changed every time when translations or current language are
+    /// changed.
+    pub fn revision(&self) -> u64 {
+        self.state.lock().revision
+    }
+
+    /// Returns `true` if there is a message with the given key in current or fallback language.
+    pub fn has(&self, key: &str) -> bool {
+        let state = self.state.lock();
+        [&state.language, &state.fallback_language]
+            .iter()
+            .filter_map(|language| state.find_bundle(language))
+            .any(|bundle| bundle.has_message(key))
+    }
+
+    /// Translates a message with given key. If there is no such message in current and fallback
+    /// languages, the key itself is returned.
+    pub fn tr(&self, key: &str) -> String {
+        self.tr_args(key, &LocalizationArgs::default())
+    }
+
+    /// Translates a message with given key and arguments. If there is no such message in current
+    /// and fallback languages, the key itself is returned.
+    pub fn tr_args(&self, key: &str, args: &LocalizationArgs) -> String {
+        let state = self.state.lock();
+        [&state.language, &state.fallback_language]
+            .iter()
+            .filter_map(|language| state.find_bundle(language))
+            .find_map(|bundle| bundle.format(key, args))
+            .unwrap_or_else(|| key.to_owned())
+    }
+
+    /// Translates localized text.
+    pub fn localize(&self, text: &LocalizedText) -> String {
+        self.tr_args(&text.key, &text.args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::localization::{
+        plural_category, Localization, LocalizationArgs, LocalizationBundle,
+    };
+
+    const EN: &str = r#"
+# Main menu
+-game-name = Fyrox Tanks
+menu-start = Start { -game-name }
+    .tooltip = Starts new game
+greeting = Hello, { $name }!
+multiline = First line
+    second line
+apples = { $count ->
+    [0] No apples
+    [one] One apple
+   *[other] { $count } apples
+}
+"#;
+
+    #[test]
+    fn test_fluent() {
+        let bundle = LocalizationBundle::from_fluent("en-US", EN).unwrap();
+        let no_args = LocalizationArgs::new();
+
+        assert_eq!(
+            bundle.format("menu-start", &no_args).unwrap(),
+            "Start Fyrox Tanks"
+        );
+        assert_eq!(
+            bundle.format("menu-start.tooltip", &no_args).unwrap(),
+            "Starts new game"
+        );
+        assert_eq!(
+            bundle
+                .format("greeting", &LocalizationArgs::new().with("name", "Bob"))
+                .unwrap(),
+            "Hello, Bob!"
+        );
+        assert_eq!(
+            bundle.format("multiline", &no_args).unwrap(),
+            "First line\nsecond line"
+        );
+        for (count, expected) in [(0, "No apples"), (1, "One apple"), (5, "5 apples")] {
+            assert_eq!(
+                bundle
+                    .format("apples", &LocalizationArgs::new().with("count", count))
+                    .unwrap(),
+                expected
+            );
+        }
+        assert!(bundle.format("missing", &no_args).is_none());
+        assert!(LocalizationBundle::from_fluent("en", "key = { $a -> [one] x }").is_err());
+    }
+
+    #[test]
+    fn test_gettext() {
+        let source = r#"
+msgid ""
+msgstr "Plural-Forms: nplurals=3;\n"
+
+msgid "menu-start"
+msgstr "Начать"
+
+msgid "apples"
+msgid_plural "apples"
+msgstr[0] "{ $count } яблоко"
+msgstr[1] "{ $count } яблока"
+msgstr[2] "{ $count } "
+"яблок"
+"#;
+        let bundle = LocalizationBundle::from_gettext("ru", source).unwrap();
+        assert_eq!(
+            bundle.format("menu-start", &Default::default()).unwrap(),
+            "Начать"
+        );
+        for (count, expected) in [(1, "1 яблоко"), (3, "3 яблока"), (11, "11 яблок")]
+        {
+            assert_eq!(
+                bundle
+                    .format("apples", &LocalizationArgs::new().with("count", count))
+                    .unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_localization_fallback() {
+        let localization = Localization::new();
+        localization.add_bundle(LocalizationBundle::from_fluent("en", EN).unwrap());
+        localization.add_bundle(
+            LocalizationBundle::from_fluent("de", "greeting = Hallo, { $name }!").unwrap(),
+        );
+        localization.set_fallback_language("en");
+
+        let revision = localization.revision();
+        localization.set_language("de-DE");
+        assert_ne!(localization.revision(), revision);
+
+        let args = LocalizationArgs::new().with("name", "Anna");
+        assert_eq!(localization.tr_args("greeting", &args), "Hallo, Anna!");
+        assert_eq!(localization.tr("menu-start"), "Start Fyrox Tanks");
+        assert_eq!(localization.tr("missing-key"), "missing-key");
+
+        assert_eq!(plural_category("fr", 0.0), "one");
+        assert_eq!(plural_category("pl", 22.0), "few");
+        assert_eq!(plural_category("ja", 1.0), "other");
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
     UiNode,
 };
 use std::{any::Any, cell::Cell, fmt::Debug, rc::Rc};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 #[macro_export]
 macro_rules! define_constructor {
@@ -269,6 +270,26 @@ pub enum MouseButton {
     Other(u16),
 }
 
+/// Input method editor event. IME is used to enter characters that can't be typed directly
+/// on a keyboard (for example CJK characters): the user composes a "pre-edit" string first, and
+/// then commits it as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImeEvent {
+    /// IME was enabled for the window.
+    Enabled,
+    /// Composition string was changed. Empty text means that composition was cancelled.
+    Preedit {
+        /// Current composition string.
+        text: String,
+        /// Byte-wise range of the cursor inside the composition string, if any.
+        cursor: Option<(usize, usize)>,
+    },
+    /// Composition was finished and the given string must be inserted.
+    Commit(String),
+    /// IME was disabled for the window, any active composition must be discarded.
+    Disabled,
+}
+
 pub enum OsEvent {
     MouseInput {
         button: MouseButton,
@@ -284,6 +305,7 @@ pub enum OsEvent {
     Character(char),
     KeyboardModifiers(KeyboardModifiers),
     MouseWheel(f32, f32),
+    Ime(ImeEvent),
 }
 
 #[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Default)]
@@ -300,7 +322,9 @@ impl KeyboardModifiers {
     }
 }
 
-#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy)]
+#[derive(
+    Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, AsRefStr, EnumString, EnumVariantNames,
+)]
 #[repr(u32)]
 pub enum KeyCode {
     Key1,
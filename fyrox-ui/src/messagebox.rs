@@ -1,3 +1,7 @@
+//! Message box is a modal window with a text and a set of buttons. Enter key "clicks" the default
+//! button and Esc key "clicks" the cancel button. [`InputBox`] is a modal window that asks user
+//! to enter a value of some type (see [`TextInputBox`] and [`NumericInputBox`]).
+
 use crate::{
     button::{ButtonBuilder, ButtonMessage},
     core::{algebra::Vector2, pool::Handle},
@@ -5,17 +9,21 @@ use crate::{
     draw::DrawingContext,
     formatted_text::WrapMode,
     grid::{Column, GridBuilder, Row},
-    message::{MessageDirection, OsEvent, UiMessage},
+    message::{ButtonState, KeyCode, MessageDirection, OsEvent, UiMessage},
     stack_panel::StackPanelBuilder,
     text::{TextBuilder, TextMessage},
-    widget::{Widget, WidgetBuilder},
+    text_box::{TextBox, TextBoxBuilder},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
     window::{Window, WindowBuilder, WindowMessage, WindowTitle},
     BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Orientation, RestrictionEntry,
     Thickness, UiNode, UserInterface,
 };
 use std::{
     any::{Any, TypeId},
+    fmt::Debug,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
+    str::FromStr,
     sync::mpsc::Sender,
 };
 
@@ -41,17 +49,96 @@ pub enum MessageBoxResult {
     Cancel,
 }
 
+impl MessageBoxResult {
+    fn default_text(self) -> &'static str {
+        match self {
+            MessageBoxResult::Ok => "OK",
+            MessageBoxResult::No => "No",
+            MessageBoxResult::Yes => "Yes",
+            MessageBoxResult::Cancel => "Cancel",
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialOrd, PartialEq, Ord, Eq, Hash, Debug)]
 pub enum MessageBoxButtons {
     Ok,
+    OkCancel,
     YesNo,
     YesNoCancel,
 }
 
+impl MessageBoxButtons {
+    /// Returns results of the buttons in the set, in the order of appearance.
+    pub fn results(self) -> &'static [MessageBoxResult] {
+        match self {
+            MessageBoxButtons::Ok => &[MessageBoxResult::Ok],
+            MessageBoxButtons::OkCancel => &[MessageBoxResult::Ok, MessageBoxResult::Cancel],
+            MessageBoxButtons::YesNo => &[MessageBoxResult::Yes, MessageBoxResult::No],
+            MessageBoxButtons::YesNoCancel => &[
+                MessageBoxResult::Yes,
+                MessageBoxResult::No,
+                MessageBoxResult::Cancel,
+            ],
+        }
+    }
+
+    /// Result of the button that is "clicked" by Enter key by default.
+    pub fn default_result(self) -> MessageBoxResult {
+        self.results()[0]
+    }
+
+    /// Result of the button that is "clicked" by Esc key by default.
+    pub fn cancel_result(self) -> MessageBoxResult {
+        match self {
+            MessageBoxButtons::Ok => MessageBoxResult::Ok,
+            MessageBoxButtons::YesNo => MessageBoxResult::No,
+            MessageBoxButtons::OkCancel | MessageBoxButtons::YesNoCancel => {
+                MessageBoxResult::Cancel
+            }
+        }
+    }
+}
+
+enum DialogKey {
+    Accept,
+    Cancel,
+}
+
+/// Translates key press into a dialog action. Keys are handled only by top-most modal window, so
+/// a message box opened over another message box won't close both of them.
+fn dialog_key(
+    self_handle: Handle<UiNode>,
+    ui: &UserInterface,
+    event: &OsEvent,
+) -> Option<DialogKey> {
+    if ui.top_picking_restriction().map(|r| r.handle) != Some(self_handle) {
+        return None;
+    }
+
+    if let OsEvent::KeyboardInput {
+        button,
+        state: ButtonState::Pressed,
+    } = event
+    {
+        match button {
+            KeyCode::Return | KeyCode::NumpadEnter => Some(DialogKey::Accept),
+            KeyCode::Escape => Some(DialogKey::Cancel),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
 #[derive(Clone)]
 pub struct MessageBox {
     pub window: Window,
     pub buttons: MessageBoxButtons,
+    /// Result that is used when Enter key is pressed.
+    pub default_result: MessageBoxResult,
+    /// Result that is used when Esc key is pressed.
+    pub cancel_result: MessageBoxResult,
     pub ok_yes: Handle<UiNode>,
     pub no: Handle<UiNode>,
     pub cancel: Handle<UiNode>,
@@ -115,9 +202,10 @@ impl Control for MessageBox {
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.ok_yes {
                 let result = match self.buttons {
-                    MessageBoxButtons::Ok => MessageBoxResult::Ok,
-                    MessageBoxButtons::YesNo => MessageBoxResult::Yes,
-                    MessageBoxButtons::YesNoCancel => MessageBoxResult::Yes,
+                    MessageBoxButtons::Ok | MessageBoxButtons::OkCancel => MessageBoxResult::Ok,
+                    MessageBoxButtons::YesNo | MessageBoxButtons::YesNoCancel => {
+                        MessageBoxResult::Yes
+                    }
                 };
                 ui.send_message(MessageBoxMessage::close(
                     self.handle,
@@ -184,6 +272,17 @@ impl Control for MessageBox {
         event: &OsEvent,
     ) {
         self.window.handle_os_event(self_handle, ui, event);
+
+        let result = match dialog_key(self_handle, ui, event) {
+            Some(DialogKey::Accept) => self.default_result,
+            Some(DialogKey::Cancel) => self.cancel_result,
+            None => return,
+        };
+        ui.send_message(MessageBoxMessage::close(
+            self_handle,
+            MessageDirection::ToWidget,
+            result,
+        ));
     }
 }
 
@@ -191,6 +290,9 @@ pub struct MessageBoxBuilder<'b> {
     window_builder: WindowBuilder,
     buttons: MessageBoxButtons,
     text: &'b str,
+    button_texts: Vec<(MessageBoxResult, &'b str)>,
+    default_result: Option<MessageBoxResult>,
+    cancel_result: Option<MessageBoxResult>,
 }
 
 impl<'b> MessageBoxBuilder<'b> {
@@ -199,6 +301,9 @@ impl<'b> MessageBoxBuilder<'b> {
             window_builder,
             buttons: MessageBoxButtons::Ok,
             text: "",
+            button_texts: Default::default(),
+            default_result: None,
+            cancel_result: None,
         }
     }
 
@@ -212,137 +317,77 @@ impl<'b> MessageBoxBuilder<'b> {
         self
     }
 
+    /// Sets custom text of a button with the given result, for example "Save" instead of "Yes".
+    pub fn with_button_text(mut self, result: MessageBoxResult, text: &'b str) -> Self {
+        self.button_texts.push((result, text));
+        self
+    }
+
+    /// Sets result that will be used when Enter key is pressed. By default it is the result of
+    /// the first button.
+    pub fn with_default_result(mut self, result: MessageBoxResult) -> Self {
+        self.default_result = Some(result);
+        self
+    }
+
+    /// Sets result that will be used when Esc key is pressed. By default it is `Cancel` (if
+    /// there is such button), `No` for [`MessageBoxButtons::YesNo`] and `Ok` for
+    /// [`MessageBoxButtons::Ok`].
+    pub fn with_cancel_result(mut self, result: MessageBoxResult) -> Self {
+        self.cancel_result = Some(result);
+        self
+    }
+
     pub fn build(mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
-        let ok_yes;
+        let mut ok_yes = Default::default();
         let mut no = Default::default();
         let mut cancel = Default::default();
-        let text;
-        let content = match self.buttons {
-            MessageBoxButtons::Ok => GridBuilder::new(
-                WidgetBuilder::new()
-                    .with_child({
-                        text = TextBuilder::new(
-                            WidgetBuilder::new().with_margin(Thickness::uniform(4.0)),
-                        )
-                        .with_text(self.text)
-                        .with_wrap(WrapMode::Word)
-                        .build(ctx);
-                        text
-                    })
-                    .with_child({
-                        ok_yes = ButtonBuilder::new(
-                            WidgetBuilder::new()
-                                .with_margin(Thickness::uniform(1.0))
-                                .with_width(80.0)
-                                .on_row(1)
-                                .with_horizontal_alignment(HorizontalAlignment::Center),
-                        )
-                        .with_text("OK")
-                        .build(ctx);
-                        ok_yes
-                    }),
-            )
-            .add_row(Row::stretch())
-            .add_row(Row::strict(25.0))
-            .add_column(Column::stretch())
-            .build(ctx),
-            MessageBoxButtons::YesNo => GridBuilder::new(
-                WidgetBuilder::new()
-                    .with_child({
-                        text = TextBuilder::new(WidgetBuilder::new())
-                            .with_text(self.text)
-                            .with_wrap(WrapMode::Word)
-                            .build(ctx);
-                        text
-                    })
-                    .with_child(
-                        StackPanelBuilder::new(
-                            WidgetBuilder::new()
-                                .with_horizontal_alignment(HorizontalAlignment::Right)
-                                .on_row(1)
-                                .with_child({
-                                    ok_yes = ButtonBuilder::new(
-                                        WidgetBuilder::new()
-                                            .with_width(80.0)
-                                            .with_margin(Thickness::uniform(1.0)),
-                                    )
-                                    .with_text("Yes")
-                                    .build(ctx);
-                                    ok_yes
-                                })
-                                .with_child({
-                                    no = ButtonBuilder::new(
-                                        WidgetBuilder::new()
-                                            .with_width(80.0)
-                                            .with_margin(Thickness::uniform(1.0)),
-                                    )
-                                    .with_text("No")
-                                    .build(ctx);
-                                    no
-                                }),
-                        )
-                        .with_orientation(Orientation::Horizontal)
-                        .build(ctx),
-                    ),
-            )
-            .add_row(Row::stretch())
-            .add_row(Row::strict(25.0))
-            .add_column(Column::stretch())
-            .build(ctx),
-            MessageBoxButtons::YesNoCancel => GridBuilder::new(
+
+        let text = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_text(self.text)
+            .with_wrap(WrapMode::Word)
+            .build(ctx);
+
+        let mut buttons_panel = WidgetBuilder::new().on_row(1).with_horizontal_alignment(
+            if self.buttons == MessageBoxButtons::Ok {
+                HorizontalAlignment::Center
+            } else {
+                HorizontalAlignment::Right
+            },
+        );
+        for &result in self.buttons.results() {
+            let button_text = self
+                .button_texts
+                .iter()
+                .rev()
+                .find_map(|(r, text)| if *r == result { Some(*text) } else { None })
+                .unwrap_or_else(|| result.default_text());
+            let button = ButtonBuilder::new(
                 WidgetBuilder::new()
-                    .with_child({
-                        text = TextBuilder::new(WidgetBuilder::new())
-                            .with_text(self.text)
-                            .with_wrap(WrapMode::Word)
-                            .build(ctx);
-                        text
-                    })
-                    .with_child(
-                        StackPanelBuilder::new(
-                            WidgetBuilder::new()
-                                .with_horizontal_alignment(HorizontalAlignment::Right)
-                                .on_row(1)
-                                .with_child({
-                                    ok_yes = ButtonBuilder::new(
-                                        WidgetBuilder::new()
-                                            .with_width(80.0)
-                                            .with_margin(Thickness::uniform(1.0)),
-                                    )
-                                    .with_text("Yes")
-                                    .build(ctx);
-                                    ok_yes
-                                })
-                                .with_child({
-                                    no = ButtonBuilder::new(
-                                        WidgetBuilder::new()
-                                            .with_width(80.0)
-                                            .with_margin(Thickness::uniform(1.0)),
-                                    )
-                                    .with_text("No")
-                                    .build(ctx);
-                                    no
-                                })
-                                .with_child({
-                                    cancel = ButtonBuilder::new(
-                                        WidgetBuilder::new()
-                                            .with_width(80.0)
-                                            .with_margin(Thickness::uniform(1.0)),
-                                    )
-                                    .with_text("Cancel")
-                                    .build(ctx);
-                                    cancel
-                                }),
-                        )
-                        .with_orientation(Orientation::Horizontal)
-                        .build(ctx),
-                    ),
+                    .with_width(80.0)
+                    .with_margin(Thickness::uniform(1.0)),
             )
-            .add_row(Row::stretch())
-            .add_row(Row::strict(25.0))
-            .add_column(Column::stretch())
-            .build(ctx),
-        };
+            .with_text(button_text)
+            .build(ctx);
+            match result {
+                MessageBoxResult::Ok | MessageBoxResult::Yes => ok_yes = button,
+                MessageBoxResult::No => no = button,
+                MessageBoxResult::Cancel => cancel = button,
+            }
+            buttons_panel = buttons_panel.with_child(button);
+        }
+
+        let content = GridBuilder::new(
+            WidgetBuilder::new().with_child(text).with_child(
+                StackPanelBuilder::new(buttons_panel)
+                    .with_orientation(Orientation::Horizontal)
+                    .build(ctx),
+            ),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::strict(25.0))
+        .add_column(Column::stretch())
+        .build(ctx);
 
         if self.window_builder.widget_builder.min_size.is_none() {
             self.window_builder.widget_builder.min_size = Some(Vector2::new(200.0, 100.0));
@@ -354,6 +399,12 @@ impl<'b> MessageBoxBuilder<'b> {
 
         let message_box = MessageBox {
             buttons: self.buttons,
+            default_result: self
+                .default_result
+                .unwrap_or_else(|| self.buttons.default_result()),
+            cancel_result: self
+                .cancel_result
+                .unwrap_or_else(|| self.buttons.cancel_result()),
             window: self.window_builder.with_content(content).build_window(ctx),
             ok_yes,
             no,
@@ -372,3 +423,400 @@ impl<'b> MessageBoxBuilder<'b> {
         handle
     }
 }
+
+/// A value that could be entered in [`InputBox`]. It is implemented for every type that could be
+/// parsed from a string and converted back, for example `String`, `f32` or `i32`.
+pub trait InputValue: FromStr + ToString + Debug + Clone + PartialEq + 'static {}
+
+impl<T> InputValue for T where T: FromStr + ToString + Debug + Clone + PartialEq + 'static {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputBoxMessage<T: InputValue> {
+    /// Opens an input box modally. `None` values leave current title, text or value unchanged.
+    Open {
+        title: Option<String>,
+        text: Option<String>,
+        value: Option<T>,
+    },
+    /// Closes an input box. The value is `None` if user cancelled the input.
+    Close(Option<T>),
+}
+
+impl<T: InputValue> InputBoxMessage<T> {
+    define_constructor!(InputBoxMessage:Open => fn open(title: Option<String>, text: Option<String>, value: Option<T>), layout: false);
+    define_constructor!(InputBoxMessage:Close => fn close(Option<T>), layout: false);
+}
+
+/// Modal window with a text, a text box and OK/Cancel buttons. OK button is enabled only when the
+/// entered text could be parsed as a value of type `T`.
+#[derive(Clone)]
+pub struct InputBox<T: InputValue> {
+    pub window: Window,
+    pub text: Handle<UiNode>,
+    pub text_box: Handle<UiNode>,
+    pub ok: Handle<UiNode>,
+    pub cancel: Handle<UiNode>,
+    phantom: PhantomData<T>,
+}
+
+/// Input box for strings.
+pub type TextInputBox = InputBox<String>;
+
+/// Input box for numbers.
+pub type NumericInputBox = InputBox<f32>;
+
+impl<T: InputValue> Deref for InputBox<T> {
+    type Target = Widget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.window
+    }
+}
+
+impl<T: InputValue> DerefMut for InputBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.window
+    }
+}
+
+impl<T: InputValue> InputBox<T> {
+    /// Returns current value of the input box or `None` if entered text is not a valid value.
+    pub fn value(&self, ui: &UserInterface) -> Option<T> {
+        ui.node(self.text_box)
+            .query_component::<TextBox>()
+            .and_then(|text_box| text_box.text().parse::<T>().ok())
+    }
+
+    fn accept(&self, ui: &UserInterface) {
+        if let Some(value) = self.value(ui) {
+            ui.send_message(InputBoxMessage::close(
+                self.handle(),
+                MessageDirection::ToWidget,
+                Some(value),
+            ));
+        }
+    }
+}
+
+impl<T: InputValue> Control for InputBox<T> {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        self.window.query_component(type_id).or_else(|| {
+            if type_id == TypeId::of::<Self>() {
+                Some(self)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        self.window.resolve(node_map);
+        node_map.resolve(&mut self.text);
+        node_map.resolve(&mut self.text_box);
+        node_map.resolve(&mut self.ok);
+        node_map.resolve(&mut self.cancel);
+    }
+
+    fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
+        self.window.measure_override(ui, available_size)
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        self.window.arrange_override(ui, final_size)
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        self.window.draw(drawing_context)
+    }
+
+    fn update(&mut self, dt: f32, sender: &Sender<UiMessage>) {
+        self.window.update(dt, sender);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.window.handle_routed_message(ui, message);
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.ok {
+                self.accept(ui);
+            } else if message.destination() == self.cancel {
+                ui.send_message(InputBoxMessage::<T>::close(
+                    self.handle(),
+                    MessageDirection::ToWidget,
+                    None,
+                ));
+            }
+        } else if let Some(TextMessage::Text(_)) = message.data::<TextMessage>() {
+            if message.destination() == self.text_box {
+                ui.send_message(WidgetMessage::enabled(
+                    self.ok,
+                    MessageDirection::ToWidget,
+                    self.value(ui).is_some(),
+                ));
+            }
+        } else if let Some(msg) = message.data::<InputBoxMessage<T>>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    InputBoxMessage::Open { title, text, value } => {
+                        if let Some(title) = title {
+                            ui.send_message(WindowMessage::title(
+                                self.handle(),
+                                MessageDirection::ToWidget,
+                                WindowTitle::Text(title.clone()),
+                            ));
+                        }
+
+                        if let Some(text) = text {
+                            ui.send_message(TextMessage::text(
+                                self.text,
+                                MessageDirection::ToWidget,
+                                text.clone(),
+                            ));
+                        }
+
+                        if let Some(value) = value {
+                            ui.send_message(TextMessage::text(
+                                self.text_box,
+                                MessageDirection::ToWidget,
+                                value.to_string(),
+                            ));
+                        }
+
+                        ui.send_message(WindowMessage::open_modal(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                            true,
+                        ));
+                        ui.send_message(WidgetMessage::focus(
+                            self.text_box,
+                            MessageDirection::ToWidget,
+                        ));
+                    }
+                    InputBoxMessage::Close(_) => {
+                        ui.send_message(WindowMessage::close(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        self.window.preview_message(ui, message);
+    }
+
+    fn handle_os_event(
+        &mut self,
+        self_handle: Handle<UiNode>,
+        ui: &mut UserInterface,
+        event: &OsEvent,
+    ) {
+        self.window.handle_os_event(self_handle, ui, event);
+
+        match dialog_key(self_handle, ui, event) {
+            Some(DialogKey::Accept) => self.accept(ui),
+            Some(DialogKey::Cancel) => ui.send_message(InputBoxMessage::<T>::close(
+                self_handle,
+                MessageDirection::ToWidget,
+                None,
+            )),
+            None => (),
+        }
+    }
+}
+
+pub struct InputBoxBuilder<T: InputValue> {
+    window_builder: WindowBuilder,
+    text: String,
+    value: Option<T>,
+    ok_text: String,
+    cancel_text: String,
+}
+
+impl<T: InputValue> InputBoxBuilder<T> {
+    pub fn new(window_builder: WindowBuilder) -> Self {
+        Self {
+            window_builder,
+            text: Default::default(),
+            value: None,
+            ok_text: "OK".to_owned(),
+            cancel_text: "Cancel".to_owned(),
+        }
+    }
+
+    pub fn with_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.text = text.as_ref().to_owned();
+        self
+    }
+
+    /// Sets initial value of the input box.
+    pub fn with_value(mut self, value: T) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    pub fn with_ok_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.ok_text = text.as_ref().to_owned();
+        self
+    }
+
+    pub fn with_cancel_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.cancel_text = text.as_ref().to_owned();
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let text;
+        let text_box;
+        let ok;
+        let cancel;
+        let is_valid = self.value.is_some() || "".parse::<T>().is_ok();
+        let content = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child({
+                    text =
+                        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+                            .with_text(&self.text)
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx);
+                    text
+                })
+                .with_child({
+                    text_box = TextBoxBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .with_height(22.0)
+                            .with_margin(Thickness::uniform(2.0)),
+                    )
+                    .with_text(
+                        self.value
+                            .as_ref()
+                            .map(|v| v.to_string())
+                            .unwrap_or_default(),
+                    )
+                    .build(ctx);
+                    text_box
+                })
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_horizontal_alignment(HorizontalAlignment::Right)
+                            .on_row(3)
+                            .with_child({
+                                ok = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_enabled(is_valid)
+                                        .with_width(80.0)
+                                        .with_margin(Thickness::uniform(1.0)),
+                                )
+                                .with_text(&self.ok_text)
+                                .build(ctx);
+                                ok
+                            })
+                            .with_child({
+                                cancel = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_width(80.0)
+                                        .with_margin(Thickness::uniform(1.0)),
+                                )
+                                .with_text(&self.cancel_text)
+                                .build(ctx);
+                                cancel
+                            }),
+                    )
+                    .with_orientation(Orientation::Horizontal)
+                    .build(ctx),
+                ),
+        )
+        .add_row(Row::auto())
+        .add_row(Row::auto())
+        .add_row(Row::stretch())
+        .add_row(Row::strict(25.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        if self.window_builder.widget_builder.min_size.is_none() {
+            self.window_builder.widget_builder.min_size = Some(Vector2::new(200.0, 100.0));
+        }
+
+        self.window_builder.widget_builder.handle_os_events = true;
+
+        let is_open = self.window_builder.open;
+
+        let input_box = InputBox::<T> {
+            window: self.window_builder.with_content(content).build_window(ctx),
+            text,
+            text_box,
+            ok,
+            cancel,
+            phantom: PhantomData,
+        };
+
+        let handle = ctx.add_node(UiNode::new(input_box));
+
+        if is_open {
+            // We must restrict picking because input box is modal.
+            ctx.ui
+                .push_picking_restriction(RestrictionEntry { handle, stop: true });
+        }
+
+        handle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        message::{ButtonState, KeyCode, MessageDirection, OsEvent},
+        messagebox::{InputBoxBuilder, InputBoxMessage, NumericInputBox},
+        text::TextMessage,
+        widget::WidgetBuilder,
+        window::WindowBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn test_numeric_input_box() {
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+
+        let input_box = InputBoxBuilder::<f32>::new(WindowBuilder::new(WidgetBuilder::new()))
+            .build(&mut ui.build_ctx());
+        let text_box = ui
+            .node(input_box)
+            .query_component::<NumericInputBox>()
+            .unwrap()
+            .text_box;
+
+        ui.send_message(InputBoxMessage::<f32>::open(
+            input_box,
+            MessageDirection::ToWidget,
+            None,
+            None,
+            None,
+        ));
+        ui.send_message(TextMessage::text(
+            text_box,
+            MessageDirection::ToWidget,
+            "1.5".to_owned(),
+        ));
+        while ui.poll_message().is_some() {}
+
+        ui.process_os_event(&OsEvent::KeyboardInput {
+            button: KeyCode::Return,
+            state: ButtonState::Pressed,
+        });
+
+        let mut result = None;
+        while let Some(message) = ui.poll_message() {
+            if let Some(InputBoxMessage::Close(value)) = message.data::<InputBoxMessage<f32>>() {
+                result = Some(*value);
+            }
+        }
+        assert_eq!(result, Some(Some(1.5)));
+    }
+}
@@ -0,0 +1,461 @@
+//! Nine-patch image widget draws a texture split into nine regions: corners are drawn at fixed
+//! size, edges are stretched (or tiled) along one axis and the center is stretched (or tiled)
+//! along both axes. It is used to make scalable panels and buttons with decorated borders that
+//! do not distort when resized.
+
+use crate::{
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext, SharedTexture},
+    message::{MessageDirection, UiMessage},
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, Thickness, UiNode, UserInterface,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+/// Maximum amount of tiles per axis of a region, tiles are stretched instead if there are more,
+/// this prevents generating tons of geometry for tiny tiles.
+const MAX_TILES: f32 = 256.0;
+
+/// Defines how a texture is sliced into nine regions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SliceMargins {
+    /// Margins are defined in pixels of the texture, fixed regions are drawn pixel-perfect. Size
+    /// of the texture must be provided to convert margins to texture coordinates.
+    Pixels {
+        margins: Thickness,
+        texture_size: Vector2<f32>,
+    },
+    /// Margins are defined in normalized texture coordinates, `border` defines the size of fixed
+    /// regions on screen.
+    Uv {
+        margins: Thickness,
+        border: Thickness,
+    },
+}
+
+impl Default for SliceMargins {
+    fn default() -> Self {
+        Self::Uv {
+            margins: Thickness::zero(),
+            border: Thickness::zero(),
+        }
+    }
+}
+
+impl SliceMargins {
+    /// Returns margins in normalized texture coordinates.
+    pub fn uv(&self) -> Thickness {
+        match *self {
+            SliceMargins::Pixels {
+                margins,
+                texture_size,
+            } => {
+                let width = texture_size.x.max(1.0);
+                let height = texture_size.y.max(1.0);
+                Thickness {
+                    left: margins.left / width,
+                    top: margins.top / height,
+                    right: margins.right / width,
+                    bottom: margins.bottom / height,
+                }
+            }
+            SliceMargins::Uv { margins, .. } => margins,
+        }
+    }
+
+    /// Returns size of fixed regions on screen.
+    pub fn border(&self) -> Thickness {
+        match *self {
+            SliceMargins::Pixels { margins, .. } => margins,
+            SliceMargins::Uv { border, .. } => border,
+        }
+    }
+
+    /// Returns amount of screen units per one unit of texture coordinates, it is used to calculate
+    /// size of tiles.
+    fn texel_scale(&self) -> Vector2<f32> {
+        fn ratio(border_a: f32, uv_a: f32, border_b: f32, uv_b: f32) -> f32 {
+            if uv_a > 0.0 {
+                border_a / uv_a
+            } else if uv_b > 0.0 {
+                border_b / uv_b
+            } else {
+                0.0
+            }
+        }
+
+        match *self {
+            SliceMargins::Pixels { texture_size, .. } => texture_size,
+            SliceMargins::Uv { margins, border } => Vector2::new(
+                ratio(border.left, margins.left, border.right, margins.right),
+                ratio(border.top, margins.top, border.bottom, margins.bottom),
+            ),
+        }
+    }
+}
+
+/// Defines how stretchable regions of a nine-patch are filled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TileMode {
+    /// Region of the texture is stretched to fill the region of the widget.
+    Stretch,
+    /// Region of the texture is repeated at its original size, last tile is cut.
+    Tile,
+}
+
+impl Default for TileMode {
+    fn default() -> Self {
+        Self::Stretch
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NinePatchMessage {
+    Texture(Option<SharedTexture>),
+    SliceMargins(SliceMargins),
+    EdgeMode(TileMode),
+    CenterMode(TileMode),
+}
+
+impl NinePatchMessage {
+    define_constructor!(NinePatchMessage:Texture => fn texture(Option<SharedTexture>), layout: false);
+    define_constructor!(NinePatchMessage:SliceMargins => fn slice_margins(SliceMargins), layout: false);
+    define_constructor!(NinePatchMessage:EdgeMode => fn edge_mode(TileMode), layout: false);
+    define_constructor!(NinePatchMessage:CenterMode => fn center_mode(TileMode), layout: false);
+}
+
+#[derive(Clone)]
+pub struct NinePatch {
+    pub widget: Widget,
+    pub texture: Option<SharedTexture>,
+    pub slice_margins: SliceMargins,
+    pub edge_mode: TileMode,
+    pub center_mode: TileMode,
+}
+
+crate::define_widget_deref!(NinePatch);
+
+/// Splits `length` into tiles of `tile` size, returns offset, size and fraction of the texture
+/// region of every tile. Zero tile size means that the region is stretched.
+fn tile_segments(length: f32, tile: f32) -> Vec<(f32, f32, f32)> {
+    if tile <= f32::EPSILON || length / tile > MAX_TILES {
+        return vec![(0.0, length, 1.0)];
+    }
+
+    let mut segments = Vec::new();
+    let mut offset = 0.0;
+    while length - offset > f32::EPSILON {
+        let size = tile.min(length - offset);
+        segments.push((offset, size, size / tile));
+        offset += size;
+    }
+    segments
+}
+
+/// Fills `rect` with `uv_rect` region of a texture, the region is repeated along the axes that
+/// have non-zero tile size.
+fn push_tiled<D: Draw>(
+    drawing_context: &mut D,
+    rect: Rect<f32>,
+    uv_rect: Rect<f32>,
+    tile_size: Vector2<f32>,
+) {
+    let columns = tile_segments(rect.w(), tile_size.x);
+    let rows = tile_segments(rect.h(), tile_size.y);
+
+    for &(y, h, v_fraction) in rows.iter() {
+        for &(x, w, u_fraction) in columns.iter() {
+            let u0 = uv_rect.x();
+            let v0 = uv_rect.y();
+            let u1 = u0 + uv_rect.w() * u_fraction;
+            let v1 = v0 + uv_rect.h() * v_fraction;
+            drawing_context.push_rect_filled(
+                &Rect::new(rect.x() + x, rect.y() + y, w, h),
+                Some(&[
+                    Vector2::new(u0, v0),
+                    Vector2::new(u1, v0),
+                    Vector2::new(u1, v1),
+                    Vector2::new(u0, v1),
+                ]),
+            );
+        }
+    }
+}
+
+fn push_nine_patch<D: Draw>(
+    drawing_context: &mut D,
+    bounds: &Rect<f32>,
+    slice_margins: &SliceMargins,
+    edge_mode: TileMode,
+    center_mode: TileMode,
+) {
+    let uv = slice_margins.uv();
+    let mut border = slice_margins.border();
+
+    // Shrink fixed regions proportionally if the widget is too small to fit them.
+    let horizontal = border.left + border.right;
+    if horizontal > bounds.w() && horizontal > 0.0 {
+        let k = bounds.w() / horizontal;
+        border.left *= k;
+        border.right *= k;
+    }
+    let vertical = border.top + border.bottom;
+    if vertical > bounds.h() && vertical > 0.0 {
+        let k = bounds.h() / vertical;
+        border.top *= k;
+        border.bottom *= k;
+    }
+
+    let xs = [
+        bounds.x(),
+        bounds.x() + border.left,
+        bounds.x() + bounds.w() - border.right,
+        bounds.x() + bounds.w(),
+    ];
+    let ys = [
+        bounds.y(),
+        bounds.y() + border.top,
+        bounds.y() + bounds.h() - border.bottom,
+        bounds.y() + bounds.h(),
+    ];
+    let us = [0.0, uv.left, 1.0 - uv.right, 1.0];
+    let vs = [0.0, uv.top, 1.0 - uv.bottom, 1.0];
+
+    let texel_scale = slice_margins.texel_scale();
+    let tile_size = Vector2::new(
+        (us[2] - us[1]) * texel_scale.x,
+        (vs[2] - vs[1]) * texel_scale.y,
+    );
+
+    for row in 0..3 {
+        for column in 0..3 {
+            let rect = Rect::new(
+                xs[column],
+                ys[row],
+                xs[column + 1] - xs[column],
+                ys[row + 1] - ys[row],
+            );
+            if rect.w() <= 0.0 || rect.h() <= 0.0 {
+                continue;
+            }
+
+            let mode = if row == 1 && column == 1 {
+                center_mode
+            } else {
+                edge_mode
+            };
+
+            // Corners are never tiled, edges are tiled only along their length.
+            let tile = if mode == TileMode::Tile {
+                Vector2::new(
+                    if column == 1 { tile_size.x } else { 0.0 },
+                    if row == 1 { tile_size.y } else { 0.0 },
+                )
+            } else {
+                Vector2::default()
+            };
+
+            push_tiled(
+                drawing_context,
+                rect,
+                Rect::new(
+                    us[column],
+                    vs[row],
+                    us[column + 1] - us[column],
+                    vs[row + 1] - vs[row],
+                ),
+                tile,
+            );
+        }
+    }
+}
+
+impl Control for NinePatch {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.widget.bounding_rect();
+        push_nine_patch(
+            drawing_context,
+            &bounds,
+            &self.slice_margins,
+            self.edge_mode,
+            self.center_mode,
+        );
+        let texture = self
+            .texture
+            .as_ref()
+            .map_or(CommandTexture::None, |t| CommandTexture::Texture(t.clone()));
+        drawing_context.commit(self.clip_bounds(), self.widget.background(), texture, None);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<NinePatchMessage>() {
+            if message.destination() == self.handle {
+                match msg {
+                    NinePatchMessage::Texture(texture) => {
+                        self.texture = texture.clone();
+                    }
+                    &NinePatchMessage::SliceMargins(slice_margins) => {
+                        self.slice_margins = slice_margins;
+                    }
+                    &NinePatchMessage::EdgeMode(edge_mode) => {
+                        self.edge_mode = edge_mode;
+                    }
+                    &NinePatchMessage::CenterMode(center_mode) => {
+                        self.center_mode = center_mode;
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct NinePatchBuilder {
+    widget_builder: WidgetBuilder,
+    texture: Option<SharedTexture>,
+    slice_margins: SliceMargins,
+    edge_mode: TileMode,
+    center_mode: TileMode,
+}
+
+impl NinePatchBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            texture: None,
+            slice_margins: Default::default(),
+            edge_mode: Default::default(),
+            center_mode: Default::default(),
+        }
+    }
+
+    pub fn with_texture(mut self, texture: SharedTexture) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+
+    pub fn with_opt_texture(mut self, texture: Option<SharedTexture>) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    pub fn with_slice_margins(mut self, slice_margins: SliceMargins) -> Self {
+        self.slice_margins = slice_margins;
+        self
+    }
+
+    pub fn with_edge_mode(mut self, edge_mode: TileMode) -> Self {
+        self.edge_mode = edge_mode;
+        self
+    }
+
+    pub fn with_center_mode(mut self, center_mode: TileMode) -> Self {
+        self.center_mode = center_mode;
+        self
+    }
+
+    pub fn build_node(mut self) -> UiNode {
+        if self.widget_builder.background.is_none() {
+            self.widget_builder.background = Some(Brush::Solid(Color::WHITE))
+        }
+
+        let nine_patch = NinePatch {
+            widget: self.widget_builder.build(),
+            texture: self.texture,
+            slice_margins: self.slice_margins,
+            edge_mode: self.edge_mode,
+            center_mode: self.center_mode,
+        };
+        UiNode::new(nine_patch)
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        ctx.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        draw::DrawingContext,
+        nine_patch::{push_nine_patch, SliceMargins, TileMode},
+        Thickness,
+    };
+
+    fn pixel_margins() -> SliceMargins {
+        SliceMargins::Pixels {
+            margins: Thickness::uniform(4.0),
+            texture_size: Vector2::new(16.0, 16.0),
+        }
+    }
+
+    #[test]
+    fn test_slice_margins_uv() {
+        assert_eq!(pixel_margins().uv(), Thickness::uniform(0.25));
+        assert_eq!(pixel_margins().border(), Thickness::uniform(4.0));
+    }
+
+    #[test]
+    fn test_stretched_nine_patch() {
+        let mut ctx = DrawingContext::new();
+        push_nine_patch(
+            &mut ctx,
+            &Rect::new(0.0, 0.0, 40.0, 40.0),
+            &pixel_margins(),
+            TileMode::Stretch,
+            TileMode::Stretch,
+        );
+        // Nine quads.
+        assert_eq!(ctx.get_vertices().len(), 9 * 4);
+        // Left-top corner is drawn at fixed size.
+        let vertices = ctx.get_vertices();
+        assert_eq!(vertices[2].pos, Vector2::new(4.0, 4.0));
+        assert_eq!(vertices[2].tex_coord, Vector2::new(0.25, 0.25));
+    }
+
+    #[test]
+    fn test_tiled_nine_patch() {
+        let mut ctx = DrawingContext::new();
+        push_nine_patch(
+            &mut ctx,
+            &Rect::new(0.0, 0.0, 40.0, 36.0),
+            &pixel_margins(),
+            TileMode::Tile,
+            TileMode::Stretch,
+        );
+        // Four corners, 32 px horizontal edges are filled with four 8 px tiles, 28 px vertical
+        // edges are filled with three full tiles and a half tile, one stretched center.
+        assert_eq!(ctx.get_vertices().len(), (4 + 2 * 4 + 2 * 4 + 1) * 4);
+    }
+
+    #[test]
+    fn test_small_nine_patch() {
+        let mut ctx = DrawingContext::new();
+        push_nine_patch(
+            &mut ctx,
+            &Rect::new(0.0, 0.0, 4.0, 4.0),
+            &pixel_margins(),
+            TileMode::Stretch,
+            TileMode::Stretch,
+        );
+        // Only corners are left, each one is shrunk to fit the widget.
+        let vertices = ctx.get_vertices();
+        assert_eq!(vertices.len(), 4 * 4);
+        assert_eq!(vertices[2].pos, Vector2::new(2.0, 2.0));
+    }
+}
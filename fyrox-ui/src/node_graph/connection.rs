@@ -0,0 +1,171 @@
+use crate::{
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext},
+    message::{MessageDirection, UiMessage},
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, UiNode, UserInterface,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionMessage {
+    /// Sets new position of the source end (in local coordinates of node graph).
+    ///
+    /// Direction: **To UI**.
+    SourcePosition(Vector2<f32>),
+    /// Sets new position of the destination end (in local coordinates of node graph).
+    ///
+    /// Direction: **To UI**.
+    DestPosition(Vector2<f32>),
+}
+
+impl ConnectionMessage {
+    define_constructor!(ConnectionMessage:SourcePosition => fn source_position(Vector2<f32>), layout: false);
+    define_constructor!(ConnectionMessage:DestPosition => fn dest_position(Vector2<f32>), layout: false);
+}
+
+/// A curved line between output socket (`source`) and input socket (`dest`). Connections must be
+/// children of a [`super::NodeGraph`], which keeps their ends in sync with the sockets.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub widget: Widget,
+    pub source: Handle<UiNode>,
+    pub source_pos: Vector2<f32>,
+    pub dest: Handle<UiNode>,
+    pub dest_pos: Vector2<f32>,
+}
+
+crate::define_widget_deref!(Connection);
+
+/// Draws a connection curve between two points, it is also used to draw a connection that is
+/// being created.
+pub fn draw_connection(
+    drawing_context: &mut DrawingContext,
+    source: Vector2<f32>,
+    dest: Vector2<f32>,
+    clip_bounds: Rect<f32>,
+    brush: Brush,
+) {
+    let k = 75.0;
+    drawing_context.push_bezier(
+        source,
+        source + Vector2::new(k, 0.0),
+        dest - Vector2::new(k, 0.0),
+        dest,
+        20,
+        4.0,
+    );
+    drawing_context.commit(clip_bounds, brush, CommandTexture::None, None);
+}
+
+/// Draws a straight arrow between two points with an arrowhead in the middle. It is used to draw
+/// links between nodes, see [`super::NodeGraphMessage::StartLink`].
+pub fn draw_link(
+    drawing_context: &mut DrawingContext,
+    source: Vector2<f32>,
+    dest: Vector2<f32>,
+    clip_bounds: Rect<f32>,
+    brush: Brush,
+) {
+    drawing_context.push_line(source, dest, 4.0);
+
+    let axis = (dest - source).normalize();
+    let center = (dest + source).scale(0.5);
+    let perp = Vector2::new(axis.y, -axis.x).normalize();
+
+    let size = 18.0;
+
+    drawing_context.push_triangle_filled([
+        center + axis.scale(size),
+        center + perp.scale(size * 0.5),
+        center - perp.scale(size * 0.5),
+    ]);
+
+    drawing_context.commit(clip_bounds, brush, CommandTexture::None, None);
+}
+
+impl Control for Connection {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        draw_connection(
+            drawing_context,
+            self.source_pos,
+            self.dest_pos,
+            self.clip_bounds(),
+            self.foreground(),
+        );
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<ConnectionMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    ConnectionMessage::SourcePosition(pos) => self.source_pos = *pos,
+                    ConnectionMessage::DestPosition(pos) => self.dest_pos = *pos,
+                }
+            }
+        }
+    }
+}
+
+pub struct ConnectionBuilder {
+    widget_builder: WidgetBuilder,
+    source: Handle<UiNode>,
+    dest: Handle<UiNode>,
+}
+
+impl ConnectionBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            source: Default::default(),
+            dest: Default::default(),
+        }
+    }
+
+    /// Sets output socket of the connection.
+    pub fn with_source(mut self, source: Handle<UiNode>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Sets input socket of the connection.
+    pub fn with_dest(mut self, dest: Handle<UiNode>) -> Self {
+        self.dest = dest;
+        self
+    }
+
+    /// Builds the connection, ends of the connection will be synced by the node graph on next
+    /// [`super::NodeGraphMessage::SyncConnections`] message or when any of the nodes will move.
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let connection = Connection {
+            widget: self
+                .widget_builder
+                .with_foreground(Brush::Solid(Color::opaque(80, 80, 80)))
+                .with_clip_to_bounds(false)
+                .build(),
+            source: self.source,
+            source_pos: Default::default(),
+            dest: self.dest,
+            dest_pos: Default::default(),
+        };
+
+        ctx.add_node(UiNode::new(connection))
+    }
+}
@@ -0,0 +1,778 @@
+//! Node graph is a generic editor of graphs that consist of nodes with typed input and output
+//! sockets connected by curves. It supports panning (middle mouse button), zooming (mouse wheel),
+//! dragging of nodes, rubber-band selection and creation of connections by dragging sockets. Nodes
+//! could also be linked directly (without sockets), see [`NodeGraphMessage::StartLink`].
+//!
+//! The widget does not own any model - it only reports user intentions via [`NodeGraphMessage`]
+//! (`Connect`, `Disconnect`, `Link`, `CommitDrag`, `Selection`) and it is up to the user to modify a model
+//! and add/remove [`node::GraphNode`] and [`connection::Connection`] widgets as children of the
+//! graph accordingly.
+//!
+//! ```rust,no_run
+//! # use fyrox_ui::{
+//! #     core::pool::Handle,
+//! #     node_graph::{
+//! #         node::GraphNodeBuilder,
+//! #         socket::{SocketBuilder, SocketDirection},
+//! #         NodeGraphBuilder,
+//! #     },
+//! #     widget::WidgetBuilder,
+//! #     BuildContext, UiNode,
+//! # };
+//! fn make_graph(ctx: &mut BuildContext) -> Handle<UiNode> {
+//!     let output = SocketBuilder::new(WidgetBuilder::new())
+//!         .with_direction(SocketDirection::Output)
+//!         .with_data_type("f32")
+//!         .with_name("Value")
+//!         .build(ctx);
+//!
+//!     let node = GraphNodeBuilder::new(WidgetBuilder::new())
+//!         .with_title("Constant")
+//!         .with_outputs(vec![output])
+//!         .build(ctx);
+//!
+//!     NodeGraphBuilder::new(WidgetBuilder::new().with_child(node)).build(ctx)
+//! }
+//! ```
+
+use crate::{
+    brush::Brush,
+    core::{
+        algebra::{Matrix3, Point2, Vector2},
+        color::Color,
+        math::{round_to_step, Rect},
+        pool::Handle,
+    },
+    define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext},
+    message::{MessageDirection, MouseButton, UiMessage},
+    node_graph::{
+        connection::{draw_connection, draw_link, Connection, ConnectionMessage},
+        node::{GraphNode, GraphNodeMessage},
+        socket::{Socket, SocketDirection, SocketMessage},
+    },
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, UiNode, UserInterface,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+pub mod connection;
+pub mod node;
+pub mod socket;
+
+/// Initial and current position of a dragged node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMove {
+    pub node: Handle<UiNode>,
+    pub initial_position: Vector2<f32>,
+    pub new_position: Vector2<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeGraphMessage {
+    /// Sets new selection (when sent to widget) or notifies that selection was changed (when sent
+    /// from widget). Selection is a list of [`GraphNode`] handles, it could also contain handles
+    /// of other children of the graph, the graph does not change their state in this case.
+    ///
+    /// Direction: **To/From UI**.
+    Selection(Vec<Handle<UiNode>>),
+    /// A request to connect an output socket (`source`) with an input socket (`dest`). Sockets
+    /// are guaranteed to be compatible and belong to different nodes.
+    ///
+    /// Direction: **From UI**.
+    Connect {
+        source: Handle<UiNode>,
+        dest: Handle<UiNode>,
+    },
+    /// A request to remove a connection.
+    ///
+    /// Direction: **From UI**.
+    Disconnect(Handle<UiNode>),
+    /// Starts linking of the given node with some other node, the graph draws an arrow from the
+    /// node to the cursor until user clicks on a node (link is created) or on an empty space
+    /// (linking is cancelled).
+    ///
+    /// Direction: **To UI**.
+    StartLink(Handle<UiNode>),
+    /// A request to link two nodes (`dest` could be the same node as `source`), it is sent when
+    /// user finishes linking started by [`NodeGraphMessage::StartLink`].
+    ///
+    /// Direction: **From UI**.
+    Link {
+        source: Handle<UiNode>,
+        dest: Handle<UiNode>,
+    },
+    /// Sent when user has finished dragging of nodes. Nodes are already moved at this moment,
+    /// the message could be used to sync a model (and to make undo/redo).
+    ///
+    /// Direction: **From UI**.
+    CommitDrag(Vec<NodeMove>),
+    /// Forces the graph to sync ends of every connection with positions of their sockets. Should
+    /// be used after new connections were added.
+    ///
+    /// Direction: **To UI**.
+    SyncConnections,
+    /// Sets new view position.
+    ///
+    /// Direction: **To UI**.
+    ViewPosition(Vector2<f32>),
+    /// Sets new zoom.
+    ///
+    /// Direction: **To UI**.
+    Zoom(f32),
+}
+
+impl NodeGraphMessage {
+    define_constructor!(NodeGraphMessage:Selection => fn selection(Vec<Handle<UiNode>>), layout: false);
+    define_constructor!(NodeGraphMessage:Connect => fn connect(source: Handle<UiNode>, dest: Handle<UiNode>), layout: false);
+    define_constructor!(NodeGraphMessage:Disconnect => fn disconnect(Handle<UiNode>), layout: false);
+    define_constructor!(NodeGraphMessage:StartLink => fn start_link(Handle<UiNode>), layout: false);
+    define_constructor!(NodeGraphMessage:Link => fn link(source: Handle<UiNode>, dest: Handle<UiNode>), layout: false);
+    define_constructor!(NodeGraphMessage:CommitDrag => fn commit_drag(Vec<NodeMove>), layout: false);
+    define_constructor!(NodeGraphMessage:SyncConnections => fn sync_connections(), layout: true);
+    define_constructor!(NodeGraphMessage:ViewPosition => fn view_position(Vector2<f32>), layout: false);
+    define_constructor!(NodeGraphMessage:Zoom => fn zoom(f32), layout: false);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    Drag {
+        initial_cursor_position: Vector2<f32>,
+        entries: Vec<NodeMove>,
+    },
+    RubberBand {
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+    },
+    CreateConnection {
+        source: Handle<UiNode>,
+        source_pos: Vector2<f32>,
+        dest_pos: Vector2<f32>,
+    },
+    CreateLink {
+        source: Handle<UiNode>,
+        source_pos: Vector2<f32>,
+        dest_pos: Vector2<f32>,
+    },
+}
+
+#[derive(Clone)]
+pub struct NodeGraph {
+    pub widget: Widget,
+    selection: Vec<Handle<UiNode>>,
+    view_position: Vector2<f32>,
+    zoom: f32,
+    initial_view_position: Vector2<f32>,
+    click_position: Vector2<f32>,
+    is_dragging_view: bool,
+    mode: Mode,
+    // A handle to a node that was under the cursor at the moment of release of left mouse button.
+    lmb_released_node: Cell<Handle<UiNode>>,
+}
+
+crate::define_widget_deref!(NodeGraph);
+
+impl NodeGraph {
+    pub fn selection(&self) -> &[Handle<UiNode>] {
+        &self.selection
+    }
+
+    pub fn view_position(&self) -> Vector2<f32> {
+        self.view_position
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn point_to_local_space(&self, point: Vector2<f32>) -> Vector2<f32> {
+        self.visual_transform()
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&Point2::from(point))
+            .coords
+    }
+
+    fn update_transform(&self, ui: &UserInterface) {
+        let transform =
+            Matrix3::new_translation(&-self.view_position) * Matrix3::new_scaling(self.zoom);
+
+        ui.send_message(WidgetMessage::layout_transform(
+            self.handle(),
+            MessageDirection::ToWidget,
+            transform,
+        ));
+    }
+
+    fn graph_nodes<'a>(&'a self, ui: &'a UserInterface) -> impl Iterator<Item = &'a GraphNode> {
+        self.children()
+            .iter()
+            .filter_map(|c| ui.node(*c).query_component::<GraphNode>())
+    }
+
+    fn make_drag_entries(&self, ui: &UserInterface) -> Vec<NodeMove> {
+        self.selection
+            .iter()
+            .filter(|n| ui.node(**n).has_component::<GraphNode>())
+            .map(|n| {
+                let position = ui.node(*n).actual_local_position();
+                NodeMove {
+                    node: *n,
+                    initial_position: position,
+                    new_position: position,
+                }
+            })
+            .collect()
+    }
+
+    fn set_selection(&mut self, new_selection: &[Handle<UiNode>], ui: &UserInterface) {
+        if self.selection != new_selection {
+            for node in self.graph_nodes(ui) {
+                ui.send_message(GraphNodeMessage::select(
+                    node.handle(),
+                    MessageDirection::ToWidget,
+                    new_selection.contains(&node.handle()),
+                ));
+            }
+
+            self.selection = new_selection.to_vec();
+
+            ui.send_message(NodeGraphMessage::selection(
+                self.handle(),
+                MessageDirection::FromWidget,
+                self.selection.clone(),
+            ));
+        }
+    }
+
+    fn fetch_component_up<T: 'static>(
+        &self,
+        node_handle: Handle<UiNode>,
+        ui: &UserInterface,
+    ) -> Handle<UiNode> {
+        match ui.try_get_node(node_handle) {
+            Some(node) if node.has_component::<T>() => node_handle,
+            Some(_) if node_handle != self.handle() => ui
+                .node(node_handle)
+                .find_by_criteria_up(ui, |n| n.has_component::<T>()),
+            _ => Handle::NONE,
+        }
+    }
+
+    /// Returns position of socket's pin in local coordinates of the graph.
+    fn socket_position(&self, socket: Handle<UiNode>, ui: &UserInterface) -> Vector2<f32> {
+        ui.try_get_node(socket)
+            .and_then(|s| s.query_component::<Socket>())
+            .and_then(|s| ui.try_get_node(s.pin))
+            .map(|pin| self.screen_to_local(pin.screen_bounds().center()))
+            .unwrap_or_default()
+    }
+
+    fn sync_connections(&self, ui: &UserInterface) {
+        for connection in self
+            .children()
+            .iter()
+            .filter_map(|c| ui.node(*c).query_component::<Connection>())
+        {
+            ui.send_message(ConnectionMessage::source_position(
+                connection.handle(),
+                MessageDirection::ToWidget,
+                self.socket_position(connection.source, ui),
+            ));
+            ui.send_message(ConnectionMessage::dest_position(
+                connection.handle(),
+                MessageDirection::ToWidget,
+                self.socket_position(connection.dest, ui),
+            ));
+        }
+    }
+
+    /// Moves ends of connections of the node that is about to be moved to a new position.
+    /// Layout of the node is not updated yet, so socket positions are offset manually.
+    fn sync_moved_node_connections(
+        &self,
+        moved_node: Handle<UiNode>,
+        new_position: Vector2<f32>,
+        ui: &UserInterface,
+    ) {
+        let node_ref = ui.node(moved_node);
+        let graph_node = match node_ref.query_component::<GraphNode>() {
+            Some(graph_node) => graph_node,
+            None => return,
+        };
+        let offset = new_position - node_ref.actual_local_position();
+
+        for connection in self
+            .children()
+            .iter()
+            .filter_map(|c| ui.node(*c).query_component::<Connection>())
+        {
+            for socket in graph_node.sockets() {
+                if connection.source == socket {
+                    ui.send_message(ConnectionMessage::source_position(
+                        connection.handle(),
+                        MessageDirection::ToWidget,
+                        self.socket_position(socket, ui) + offset,
+                    ));
+                }
+                if connection.dest == socket {
+                    ui.send_message(ConnectionMessage::dest_position(
+                        connection.handle(),
+                        MessageDirection::ToWidget,
+                        self.socket_position(socket, ui) + offset,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn try_commit_connection(&self, source: Handle<UiNode>, ui: &UserInterface) {
+        let dest = self.fetch_component_up::<Socket>(self.lmb_released_node.get(), ui);
+
+        let (source_ref, dest_ref) = match (
+            ui.try_get_node(source)
+                .and_then(|n| n.query_component::<Socket>()),
+            ui.try_get_node(dest)
+                .and_then(|n| n.query_component::<Socket>()),
+        ) {
+            (Some(source_ref), Some(dest_ref)) => (source_ref, dest_ref),
+            _ => return,
+        };
+
+        // Do not allow to create connections between sockets of the same node.
+        let source_node = self.fetch_component_up::<GraphNode>(source, ui);
+        let dest_node = self.fetch_component_up::<GraphNode>(dest, ui);
+
+        if source_node != dest_node && source_ref.is_compatible(dest_ref) {
+            // Always report connections in "output -> input" order.
+            let (output, input) = match source_ref.direction {
+                SocketDirection::Output => (source, dest),
+                SocketDirection::Input => (dest, source),
+            };
+
+            ui.send_message(NodeGraphMessage::connect(
+                self.handle(),
+                MessageDirection::FromWidget,
+                output,
+                input,
+            ));
+        }
+    }
+
+    fn commit_rubber_band(&mut self, start: Vector2<f32>, end: Vector2<f32>, ui: &UserInterface) {
+        let min = start.inf(&end);
+        let max = start.sup(&end);
+        let band = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+
+        let mut new_selection = if ui.keyboard_modifiers().control {
+            self.selection.clone()
+        } else {
+            Vec::new()
+        };
+
+        for node in self.graph_nodes(ui) {
+            let position = node.actual_local_position();
+            let size = node.actual_local_size();
+            if band.intersects(Rect::new(position.x, position.y, size.x, size.y))
+                && !new_selection.contains(&node.handle())
+            {
+                new_selection.push(node.handle());
+            }
+        }
+
+        self.set_selection(&new_selection, ui);
+    }
+}
+
+impl Control for NodeGraph {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, ctx: &mut DrawingContext) {
+        let size = 9999.0;
+
+        let local_bounds = self
+            .widget
+            .bounding_rect()
+            .inflate(size, size)
+            .translate(Vector2::new(size * 0.5, size * 0.5));
+        ctx.push_rect_filled(&local_bounds, None);
+        ctx.commit(
+            self.clip_bounds(),
+            self.widget.background(),
+            CommandTexture::None,
+            None,
+        );
+
+        let step_size = 50.0;
+
+        let mut left_top = local_bounds.left_top_corner();
+        left_top.x = round_to_step(left_top.x, step_size);
+        left_top.y = round_to_step(left_top.y, step_size);
+
+        let mut right_bottom = local_bounds.right_bottom_corner();
+        right_bottom.x = round_to_step(right_bottom.x, step_size);
+        right_bottom.y = round_to_step(right_bottom.y, step_size);
+
+        let w = (right_bottom.x - left_top.x).abs();
+        let h = (right_bottom.y - left_top.y).abs();
+
+        let nw = ((w / step_size).ceil()) as usize;
+        let nh = ((h / step_size).ceil()) as usize;
+
+        for ny in 0..=nh {
+            let y = left_top.y + ny as f32 / nh as f32 * h;
+            ctx.push_line(
+                Vector2::new(left_top.x - step_size, y),
+                Vector2::new(right_bottom.x + step_size, y),
+                1.0 / self.zoom,
+            );
+        }
+
+        for nx in 0..=nw {
+            let x = left_top.x + nx as f32 / nw as f32 * w;
+            ctx.push_line(
+                Vector2::new(x, left_top.y + step_size),
+                Vector2::new(x, right_bottom.y - step_size),
+                1.0 / self.zoom,
+            );
+        }
+
+        ctx.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::opaque(60, 60, 60)),
+            CommandTexture::None,
+            None,
+        );
+
+        match self.mode {
+            Mode::CreateConnection {
+                source_pos,
+                dest_pos,
+                ..
+            } => {
+                draw_connection(
+                    ctx,
+                    source_pos,
+                    dest_pos,
+                    self.clip_bounds(),
+                    Brush::Solid(Color::WHITE),
+                );
+            }
+            Mode::CreateLink {
+                source_pos,
+                dest_pos,
+                ..
+            } => {
+                draw_link(
+                    ctx,
+                    source_pos,
+                    dest_pos,
+                    self.clip_bounds(),
+                    Brush::Solid(Color::WHITE),
+                );
+            }
+            Mode::RubberBand { start, end } => {
+                let min = start.inf(&end);
+                let max = start.sup(&end);
+                let band = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+                ctx.push_rect_filled(&band, None);
+                ctx.commit(
+                    self.clip_bounds(),
+                    Brush::Solid(Color::from_rgba(80, 118, 178, 50)),
+                    CommandTexture::None,
+                    None,
+                );
+                ctx.push_rect(&band, 1.0 / self.zoom);
+                ctx.commit(
+                    self.clip_bounds(),
+                    Brush::Solid(Color::opaque(80, 118, 178)),
+                    CommandTexture::None,
+                    None,
+                );
+            }
+            _ => (),
+        }
+    }
+
+    fn measure_override(&self, ui: &UserInterface, _available_size: Vector2<f32>) -> Vector2<f32> {
+        let size_for_child = Vector2::new(f32::INFINITY, f32::INFINITY);
+
+        for child_handle in self.widget.children() {
+            ui.measure_node(*child_handle, size_for_child);
+        }
+
+        Vector2::default()
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        for &child_handle in self.widget.children() {
+            let child = ui.node(child_handle);
+            ui.arrange_node(
+                child_handle,
+                &Rect::new(
+                    child.desired_local_position().x,
+                    child.desired_local_position().y,
+                    child.desired_size().x,
+                    child.desired_size().y,
+                ),
+            );
+        }
+
+        final_size
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(WidgetMessage::MouseDown { pos, button }) = message.data() {
+            if *button == MouseButton::Middle {
+                self.is_dragging_view = true;
+                self.click_position = *pos;
+                self.initial_view_position = self.view_position;
+
+                ui.capture_mouse(self.handle());
+            } else if *button == MouseButton::Right && !message.handled() {
+                // Select a node under the cursor, so its context menu will act on it.
+                let node = self.fetch_component_up::<GraphNode>(message.destination(), ui);
+
+                if node.is_some() && !self.selection.contains(&node) {
+                    self.set_selection(&[node], ui);
+                }
+            } else if *button == MouseButton::Left && !message.handled() {
+                let node = self.fetch_component_up::<GraphNode>(message.destination(), ui);
+
+                if let Mode::CreateLink { source, .. } = self.mode {
+                    if node.is_some() {
+                        ui.send_message(NodeGraphMessage::link(
+                            self.handle(),
+                            MessageDirection::FromWidget,
+                            source,
+                            node,
+                        ));
+                    }
+
+                    self.mode = Mode::Normal;
+                } else if self.mode == Mode::Normal {
+                    if node.is_some() {
+                        if !self.selection.contains(&node) {
+                            let new_selection = if ui.keyboard_modifiers().control {
+                                let mut selection = self.selection.clone();
+                                selection.push(node);
+                                selection
+                            } else {
+                                vec![node]
+                            };
+                            self.set_selection(&new_selection, ui);
+                        }
+
+                        self.mode = Mode::Drag {
+                            initial_cursor_position: self.point_to_local_space(*pos),
+                            entries: self.make_drag_entries(ui),
+                        };
+                    } else {
+                        let start = self.point_to_local_space(*pos);
+                        self.mode = Mode::RubberBand { start, end: start };
+                    }
+
+                    ui.capture_mouse(self.handle());
+                }
+            }
+        } else if let Some(WidgetMessage::MouseUp { button, .. }) = message.data() {
+            if *button == MouseButton::Middle {
+                self.is_dragging_view = false;
+
+                ui.release_mouse_capture();
+            } else if *button == MouseButton::Left {
+                match std::mem::replace(&mut self.mode, Mode::Normal) {
+                    Mode::Drag { entries, .. } => {
+                        if entries.iter().any(|e| e.initial_position != e.new_position) {
+                            ui.send_message(NodeGraphMessage::commit_drag(
+                                self.handle(),
+                                MessageDirection::FromWidget,
+                                entries,
+                            ));
+                        }
+                        ui.release_mouse_capture();
+                    }
+                    Mode::RubberBand { start, end } => {
+                        self.commit_rubber_band(start, end, ui);
+                        ui.release_mouse_capture();
+                    }
+                    Mode::CreateConnection { source, .. } => {
+                        self.try_commit_connection(source, ui);
+                    }
+                    mode @ Mode::CreateLink { .. } => {
+                        // Linking is finished by a click, not by a release of the button.
+                        self.mode = mode;
+                    }
+                    Mode::Normal => (),
+                }
+            }
+        } else if let Some(WidgetMessage::MouseMove { pos, .. }) = message.data() {
+            if self.is_dragging_view {
+                self.view_position = self.initial_view_position + (*pos - self.click_position);
+                self.update_transform(ui);
+            }
+
+            let local_cursor_position = self.point_to_local_space(*pos);
+            let connection_end = self.screen_to_local(*pos);
+
+            match self.mode {
+                Mode::Drag {
+                    initial_cursor_position,
+                    ref mut entries,
+                } => {
+                    for entry in entries.iter_mut() {
+                        entry.new_position = entry.initial_position
+                            + (local_cursor_position - initial_cursor_position);
+
+                        ui.send_message(WidgetMessage::desired_position(
+                            entry.node,
+                            MessageDirection::ToWidget,
+                            entry.new_position,
+                        ));
+                    }
+                }
+                Mode::RubberBand { ref mut end, .. } => {
+                    *end = local_cursor_position;
+                }
+                Mode::CreateConnection {
+                    ref mut dest_pos, ..
+                }
+                | Mode::CreateLink {
+                    ref mut dest_pos, ..
+                } => {
+                    *dest_pos = connection_end;
+                }
+                Mode::Normal => (),
+            }
+        } else if let Some(WidgetMessage::MouseWheel { amount, pos }) = message.data() {
+            let cursor_pos = (*pos - self.screen_position()).scale(self.zoom);
+
+            self.zoom = (self.zoom + 0.1 * amount).clamp(0.2, 2.0);
+
+            let new_cursor_pos = (*pos - self.screen_position()).scale(self.zoom);
+
+            self.view_position -= (new_cursor_pos - cursor_pos).scale(self.zoom);
+
+            self.update_transform(ui);
+        } else if let Some(WidgetMessage::DesiredPosition(position)) = message.data() {
+            if message.direction() == MessageDirection::ToWidget
+                && self.children().contains(&message.destination())
+            {
+                self.sync_moved_node_connections(message.destination(), *position, ui);
+            }
+        } else if let Some(msg) = message.data::<SocketMessage>() {
+            if message.direction() == MessageDirection::FromWidget {
+                let socket = message.destination();
+                match msg {
+                    SocketMessage::StartDragging => {
+                        let source_pos = self.socket_position(socket, ui);
+                        self.mode = Mode::CreateConnection {
+                            source: socket,
+                            source_pos,
+                            dest_pos: source_pos,
+                        };
+                    }
+                    SocketMessage::Disconnect => {
+                        for connection in self
+                            .children()
+                            .iter()
+                            .filter_map(|c| ui.node(*c).query_component::<Connection>())
+                            .filter(|c| c.source == socket || c.dest == socket)
+                        {
+                            ui.send_message(NodeGraphMessage::disconnect(
+                                self.handle(),
+                                MessageDirection::FromWidget,
+                                connection.handle(),
+                            ));
+                        }
+                    }
+                }
+            }
+        } else if let Some(msg) = message.data::<NodeGraphMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    NodeGraphMessage::Selection(selection) => {
+                        self.set_selection(selection, ui);
+                    }
+                    NodeGraphMessage::SyncConnections => {
+                        self.sync_connections(ui);
+                    }
+                    &NodeGraphMessage::ViewPosition(position) => {
+                        self.view_position = position;
+                        self.update_transform(ui);
+                    }
+                    &NodeGraphMessage::Zoom(zoom) => {
+                        self.zoom = zoom.clamp(0.2, 2.0);
+                        self.update_transform(ui);
+                    }
+                    &NodeGraphMessage::StartLink(source) => {
+                        if let Some(source_ref) = ui.try_get_node(source) {
+                            self.mode = Mode::CreateLink {
+                                source,
+                                source_pos: source_ref.center(),
+                                dest_pos: self.screen_to_local(ui.cursor_position()),
+                            };
+                        }
+                    }
+                    NodeGraphMessage::Connect { .. }
+                    | NodeGraphMessage::Disconnect(_)
+                    | NodeGraphMessage::Link { .. }
+                    | NodeGraphMessage::CommitDrag(_) => (),
+                }
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        if let Some(WidgetMessage::MouseUp { button, pos }) = message.data() {
+            if *button == MouseButton::Left {
+                self.lmb_released_node.set(ui.hit_test_unrestricted(*pos));
+            }
+        }
+    }
+}
+
+pub struct NodeGraphBuilder {
+    widget_builder: WidgetBuilder,
+}
+
+impl NodeGraphBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self { widget_builder }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let graph = NodeGraph {
+            widget: self
+                .widget_builder
+                .with_preview_messages(true)
+                .with_clip_to_bounds(false)
+                .build(),
+            selection: Default::default(),
+            view_position: Default::default(),
+            zoom: 1.0,
+            initial_view_position: Default::default(),
+            click_position: Default::default(),
+            is_dragging_view: false,
+            mode: Mode::Normal,
+            lmb_released_node: Default::default(),
+        };
+
+        ctx.add_node(UiNode::new(graph))
+    }
+}
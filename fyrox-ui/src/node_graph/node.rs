@@ -0,0 +1,329 @@
+use crate::{
+    border::BorderBuilder,
+    brush::Brush,
+    core::{color::Color, pool::Handle},
+    define_constructor,
+    grid::{Column, GridBuilder, Row},
+    message::{MessageDirection, UiMessage},
+    stack_panel::StackPanelBuilder,
+    text::{TextBuilder, TextMessage},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface, VerticalAlignment,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+const NORMAL_BACKGROUND: Color = Color::opaque(60, 60, 60);
+const SELECTED_BACKGROUND: Color = Color::opaque(80, 80, 80);
+const BORDER_COLOR: Color = Color::opaque(70, 70, 70);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphNodeMessage {
+    /// Selects or deselects the node. Selection is managed by [`super::NodeGraph`], use
+    /// [`super::NodeGraphMessage::Selection`] to change it.
+    ///
+    /// Direction: **To/From UI**.
+    Select(bool),
+    /// Sets new title of the node.
+    ///
+    /// Direction: **To UI**.
+    Title(String),
+    /// Replaces input sockets of the node, old sockets are removed.
+    ///
+    /// Direction: **To UI**.
+    Inputs(Vec<Handle<UiNode>>),
+    /// Sets new background brush of the node when it is not selected.
+    ///
+    /// Direction: **To UI**.
+    NormalBrush(Brush),
+    /// Sets new background brush of the node when it is selected.
+    ///
+    /// Direction: **To UI**.
+    SelectedBrush(Brush),
+}
+
+impl GraphNodeMessage {
+    define_constructor!(GraphNodeMessage:Select => fn select(bool), layout: false);
+    define_constructor!(GraphNodeMessage:Title => fn title(String), layout: false);
+    define_constructor!(GraphNodeMessage:Inputs => fn inputs(Vec<Handle<UiNode>>), layout: false);
+    define_constructor!(GraphNodeMessage:NormalBrush => fn normal_brush(Brush), layout: false);
+    define_constructor!(GraphNodeMessage:SelectedBrush => fn selected_brush(Brush), layout: false);
+}
+
+/// A draggable node of a [`super::NodeGraph`] with a title, a set of input sockets on the left
+/// side, a set of output sockets on the right side and optional content between them.
+#[derive(Clone)]
+pub struct GraphNode {
+    pub widget: Widget,
+    pub inputs: Vec<Handle<UiNode>>,
+    pub outputs: Vec<Handle<UiNode>>,
+    pub selected: bool,
+    background: Handle<UiNode>,
+    title: Handle<UiNode>,
+    inputs_panel: Handle<UiNode>,
+    normal_brush: Brush,
+    selected_brush: Brush,
+}
+
+crate::define_widget_deref!(GraphNode);
+
+impl GraphNode {
+    /// Returns an iterator over all sockets of the node (inputs first).
+    pub fn sockets(&self) -> impl Iterator<Item = Handle<UiNode>> + '_ {
+        self.inputs.iter().chain(self.outputs.iter()).cloned()
+    }
+
+    /// Returns a handle of the border that forms the body of the node.
+    pub fn background(&self) -> Handle<UiNode> {
+        self.background
+    }
+
+    fn update_background(&self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::background(
+            self.background,
+            MessageDirection::ToWidget,
+            if self.selected {
+                self.selected_brush.clone()
+            } else {
+                self.normal_brush.clone()
+            },
+        ));
+    }
+}
+
+impl Control for GraphNode {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve(&mut self.background);
+        node_map.resolve(&mut self.title);
+        node_map.resolve(&mut self.inputs_panel);
+        node_map.resolve_slice(&mut self.inputs);
+        node_map.resolve_slice(&mut self.outputs);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<GraphNodeMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    &GraphNodeMessage::Select(selected) => {
+                        if self.selected != selected {
+                            self.selected = selected;
+                            self.update_background(ui);
+
+                            if selected {
+                                ui.send_message(WidgetMessage::topmost(
+                                    self.handle(),
+                                    MessageDirection::ToWidget,
+                                ));
+                            }
+
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    GraphNodeMessage::Title(title) => {
+                        ui.send_message(TextMessage::text(
+                            self.title,
+                            MessageDirection::ToWidget,
+                            title.clone(),
+                        ));
+                    }
+                    GraphNodeMessage::Inputs(inputs) => {
+                        if &self.inputs != inputs {
+                            for &input in self.inputs.iter() {
+                                ui.send_message(WidgetMessage::remove(
+                                    input,
+                                    MessageDirection::ToWidget,
+                                ));
+                            }
+
+                            for &input in inputs.iter() {
+                                ui.send_message(WidgetMessage::link(
+                                    input,
+                                    MessageDirection::ToWidget,
+                                    self.inputs_panel,
+                                ));
+                            }
+
+                            self.inputs = inputs.clone();
+                        }
+                    }
+                    GraphNodeMessage::NormalBrush(brush) => {
+                        if &self.normal_brush != brush {
+                            self.normal_brush = brush.clone();
+                            self.update_background(ui);
+                        }
+                    }
+                    GraphNodeMessage::SelectedBrush(brush) => {
+                        if &self.selected_brush != brush {
+                            self.selected_brush = brush.clone();
+                            self.update_background(ui);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct GraphNodeBuilder {
+    widget_builder: WidgetBuilder,
+    title: String,
+    inputs: Vec<Handle<UiNode>>,
+    outputs: Vec<Handle<UiNode>>,
+    content: Handle<UiNode>,
+    normal_brush: Brush,
+    selected_brush: Brush,
+}
+
+impl GraphNodeBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            title: Default::default(),
+            inputs: Default::default(),
+            outputs: Default::default(),
+            content: Default::default(),
+            normal_brush: Brush::Solid(NORMAL_BACKGROUND),
+            selected_brush: Brush::Solid(SELECTED_BACKGROUND),
+        }
+    }
+
+    pub fn with_title<S: AsRef<str>>(mut self, title: S) -> Self {
+        self.title = title.as_ref().to_owned();
+        self
+    }
+
+    /// Sets input sockets of the node, see [`super::socket::SocketBuilder`].
+    pub fn with_inputs(mut self, inputs: Vec<Handle<UiNode>>) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    /// Sets output sockets of the node, see [`super::socket::SocketBuilder`].
+    pub fn with_outputs(mut self, outputs: Vec<Handle<UiNode>>) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    /// Sets an arbitrary widget that will be placed between inputs and outputs, it could be
+    /// used to show a preview or editors of node properties.
+    pub fn with_content(mut self, content: Handle<UiNode>) -> Self {
+        self.content = content;
+        self
+    }
+
+    pub fn with_normal_brush(mut self, brush: Brush) -> Self {
+        self.normal_brush = brush;
+        self
+    }
+
+    pub fn with_selected_brush(mut self, brush: Brush) -> Self {
+        self.selected_brush = brush;
+        self
+    }
+
+    /// Builds the node without adding it to the user interface, it could be used to embed the
+    /// node into another widget (which must forward [`GraphNode`] queries of `query_component`
+    /// to it to make the node visible to [`super::NodeGraph`]).
+    pub fn build_graph_node(self, ctx: &mut BuildContext) -> GraphNode {
+        let title = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text(self.title)
+        .build(ctx);
+
+        if let Some(content) = ctx.try_get_node_mut(self.content) {
+            content.set_row(0).set_column(1);
+        }
+
+        let inputs_panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_column(0)
+                .with_margin(Thickness::uniform(2.0))
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_children(self.inputs.iter().cloned()),
+        )
+        .build(ctx);
+
+        let body = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_child(inputs_panel)
+                .with_child(self.content)
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .on_column(2)
+                            .with_margin(Thickness::uniform(2.0))
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_children(self.outputs.iter().cloned()),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .add_row(Row::stretch())
+        .add_column(Column::auto())
+        .add_column(Column::stretch())
+        .add_column(Column::auto())
+        .build(ctx);
+
+        let background = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_foreground(Brush::Solid(BORDER_COLOR))
+                .with_background(self.normal_brush.clone())
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(
+                                BorderBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_height(22.0)
+                                        .with_background(Brush::Solid(Color::opaque(30, 30, 30)))
+                                        .with_child(title),
+                                )
+                                .with_stroke_thickness(Thickness::zero())
+                                .build(ctx),
+                            )
+                            .with_child(body),
+                    )
+                    .add_row(Row::auto())
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        GraphNode {
+            widget: self.widget_builder.with_child(background).build(),
+            inputs: self.inputs,
+            outputs: self.outputs,
+            selected: false,
+            background,
+            title,
+            inputs_panel,
+            normal_brush: self.normal_brush,
+            selected_brush: self.selected_brush,
+        }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let node = self.build_graph_node(ctx);
+        ctx.add_node(UiNode::new(node))
+    }
+}
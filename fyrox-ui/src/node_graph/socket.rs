@@ -0,0 +1,271 @@
+use crate::{
+    brush::Brush,
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    define_constructor,
+    grid::{Column, GridBuilder, Row},
+    message::{MessageDirection, MouseButton, UiMessage},
+    text::TextBuilder,
+    vector_image::{Primitive, VectorImageBuilder},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, Thickness, UiNode, UserInterface, VerticalAlignment,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+const RADIUS: f32 = 6.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketMessage {
+    /// Occurs when user clicks on socket and starts dragging it.
+    ///
+    /// Direction: **From UI**.
+    StartDragging,
+    /// Occurs when user clicks on socket with Alt key pressed, it is a request to remove every
+    /// connection of the socket.
+    ///
+    /// Direction: **From UI**.
+    Disconnect,
+}
+
+impl SocketMessage {
+    define_constructor!(SocketMessage:StartDragging => fn start_dragging(), layout: false);
+    define_constructor!(SocketMessage:Disconnect => fn disconnect(), layout: false);
+}
+
+#[derive(Copy, Clone, PartialEq, Hash, Debug, Eq)]
+pub enum SocketDirection {
+    Input,
+    Output,
+}
+
+#[derive(Clone, Debug)]
+pub struct Socket {
+    pub widget: Widget,
+    pub direction: SocketDirection,
+    /// Name of the type of data that flows through the socket. Empty string means that socket
+    /// accepts data of any type.
+    pub data_type: String,
+    /// Index of the socket in its node, it could be used to map the socket to the model.
+    pub index: usize,
+    pub pin: Handle<UiNode>,
+    click_position: Option<Vector2<f32>>,
+    normal_brush: Brush,
+}
+
+crate::define_widget_deref!(Socket);
+
+impl Socket {
+    /// Checks whether the socket can be connected with the other one. Sockets must have different
+    /// directions and same data types (unless one of them accepts any type).
+    pub fn is_compatible(&self, other: &Socket) -> bool {
+        is_compatible(
+            self.direction,
+            &self.data_type,
+            other.direction,
+            &other.data_type,
+        )
+    }
+}
+
+pub(crate) fn is_compatible(
+    direction: SocketDirection,
+    data_type: &str,
+    other_direction: SocketDirection,
+    other_data_type: &str,
+) -> bool {
+    direction != other_direction
+        && (data_type.is_empty() || other_data_type.is_empty() || data_type == other_data_type)
+}
+
+impl Control for Socket {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<WidgetMessage>() {
+            match msg {
+                WidgetMessage::MouseDown { button, pos } => {
+                    if *button == MouseButton::Left && message.destination() == self.pin {
+                        if ui.keyboard_modifiers().alt {
+                            ui.send_message(SocketMessage::disconnect(
+                                self.handle(),
+                                MessageDirection::FromWidget,
+                            ));
+                        } else {
+                            self.click_position = Some(*pos);
+
+                            ui.capture_mouse(self.handle());
+                        }
+
+                        message.set_handled(true);
+                    }
+                }
+                WidgetMessage::MouseUp { button, .. } => {
+                    if *button == MouseButton::Left {
+                        self.click_position = None;
+
+                        ui.release_mouse_capture();
+
+                        message.set_handled(true);
+                    }
+                }
+                WidgetMessage::MouseMove { pos, .. } => {
+                    if let Some(click_position) = self.click_position {
+                        if click_position.metric_distance(pos) >= 5.0 {
+                            ui.send_message(SocketMessage::start_dragging(
+                                self.handle(),
+                                MessageDirection::FromWidget,
+                            ));
+
+                            self.click_position = None;
+                        }
+                    }
+                }
+                WidgetMessage::MouseLeave => {
+                    ui.send_message(WidgetMessage::foreground(
+                        self.pin,
+                        MessageDirection::ToWidget,
+                        self.normal_brush.clone(),
+                    ));
+                }
+                WidgetMessage::MouseEnter => {
+                    ui.send_message(WidgetMessage::foreground(
+                        self.pin,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(Color::opaque(220, 220, 220)),
+                    ));
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+pub struct SocketBuilder {
+    widget_builder: WidgetBuilder,
+    direction: SocketDirection,
+    data_type: String,
+    index: usize,
+    name: String,
+    pin_brush: Brush,
+}
+
+impl SocketBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            direction: SocketDirection::Input,
+            data_type: Default::default(),
+            index: 0,
+            name: Default::default(),
+            pin_brush: Brush::Solid(Color::opaque(120, 120, 120)),
+        }
+    }
+
+    pub fn with_direction(mut self, direction: SocketDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_data_type<S: AsRef<str>>(mut self, data_type: S) -> Self {
+        self.data_type = data_type.as_ref().to_owned();
+        self
+    }
+
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn with_name<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.name = name.as_ref().to_owned();
+        self
+    }
+
+    pub fn with_pin_brush(mut self, brush: Brush) -> Self {
+        self.pin_brush = brush;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        // Pins of input sockets are on the left side of a node, outputs - on the right side.
+        let (pin_column, name_column) = match self.direction {
+            SocketDirection::Input => (0, 1),
+            SocketDirection::Output => (1, 0),
+        };
+
+        let pin = VectorImageBuilder::new(
+            WidgetBuilder::new()
+                .on_column(pin_column)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_foreground(self.pin_brush.clone()),
+        )
+        .with_primitives(vec![Primitive::Circle {
+            center: Vector2::new(RADIUS, RADIUS),
+            radius: RADIUS,
+            segments: 16,
+        }])
+        .build(ctx);
+
+        let name = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_column(name_column)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text(self.name)
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+
+        let grid = GridBuilder::new(WidgetBuilder::new().with_child(pin).with_child(name))
+            .add_row(Row::auto())
+            .add_column(if pin_column == 0 {
+                Column::auto()
+            } else {
+                Column::stretch()
+            })
+            .add_column(if pin_column == 1 {
+                Column::auto()
+            } else {
+                Column::stretch()
+            })
+            .build(ctx);
+
+        let socket = Socket {
+            widget: self.widget_builder.with_child(grid).build(),
+            direction: self.direction,
+            data_type: self.data_type,
+            index: self.index,
+            pin,
+            click_position: None,
+            normal_brush: self.pin_brush,
+        };
+
+        ctx.add_node(UiNode::new(socket))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::node_graph::socket::{is_compatible, SocketDirection};
+
+    #[test]
+    fn test_socket_compatibility() {
+        use SocketDirection::*;
+
+        assert!(is_compatible(Output, "f32", Input, "f32"));
+        assert!(is_compatible(Output, "", Input, "f32"));
+        assert!(is_compatible(Output, "Vector3", Input, ""));
+        assert!(!is_compatible(Output, "f32", Input, "Vector3"));
+        assert!(!is_compatible(Input, "f32", Input, "f32"));
+        assert!(!is_compatible(Output, "", Output, ""));
+    }
+}
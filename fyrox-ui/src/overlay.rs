@@ -0,0 +1,98 @@
+//! Overlay layers of the user interface and toast notifications.
+//!
+//! Every user interface has a fixed set of layers (see [`OverlayLayer`]), each layer is drawn
+//! and picked on top of all previous ones, regardless of the order in which widgets were created
+//! or of [`crate::widget::WidgetMessage::topmost`] requests. Popups, tooltips and drag previews
+//! are placed in their layers automatically, use [`crate::UserInterface::layer`] to put custom
+//! widgets in a layer.
+
+use crate::{
+    border::BorderBuilder, core::pool::Handle, text::TextBuilder, widget::WidgetBuilder,
+    BuildContext, Thickness, UiNode, BRUSH_DARKER, BRUSH_LIGHTER,
+};
+
+/// Duration of fade out animation of a toast in seconds.
+pub(crate) const TOAST_FADE_TIME: f32 = 0.3;
+
+/// Distance between toasts and from toasts to the edges of the screen.
+pub(crate) const TOAST_SPACING: f32 = 10.0;
+
+/// Overlay layers in drawing order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OverlayLayer {
+    /// Regular widgets, this is the root canvas of the user interface.
+    Content,
+    /// Popups, menus, drop-down lists, context menus.
+    Popups,
+    /// Tooltips.
+    Tooltips,
+    /// Toast notifications, see [`crate::UserInterface::add_toast`].
+    Notifications,
+    /// Preview of a dragged widget.
+    DragPreview,
+}
+
+impl OverlayLayer {
+    /// Layers that are drawn on top of the content.
+    pub const OVERLAYS: [OverlayLayer; 4] = [
+        OverlayLayer::Popups,
+        OverlayLayer::Tooltips,
+        OverlayLayer::Notifications,
+        OverlayLayer::DragPreview,
+    ];
+}
+
+pub(crate) struct ToastEntry {
+    pub handle: Handle<UiNode>,
+    /// Time remaining until the toast is removed (in seconds).
+    pub time: f32,
+}
+
+/// Builds a toast notification with text and adds it to the notifications layer. The toast will
+/// be removed automatically when its duration is elapsed.
+pub struct ToastBuilder {
+    widget_builder: WidgetBuilder,
+    text: String,
+    duration: f32,
+}
+
+impl ToastBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            text: Default::default(),
+            duration: 3.0,
+        }
+    }
+
+    pub fn with_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.text = text.as_ref().to_owned();
+        self
+    }
+
+    /// Sets time in seconds after which the toast will be removed.
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let toast = BorderBuilder::new(
+            self.widget_builder
+                .with_background(BRUSH_DARKER)
+                .with_foreground(BRUSH_LIGHTER)
+                .with_hit_test_visibility(false)
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(6.0)))
+                        .with_text(self.text)
+                        .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        ctx.ui.add_toast(toast, self.duration);
+
+        toast
+    }
+}
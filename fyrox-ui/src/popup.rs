@@ -2,7 +2,9 @@ use crate::{
     border::BorderBuilder,
     core::{algebra::Vector2, math::Rect, pool::Handle},
     define_constructor,
+    draw::{CommandTexture, Draw, DrawingContext},
     message::{ButtonState, MessageDirection, OsEvent, UiMessage},
+    overlay::OverlayLayer,
     widget::{Widget, WidgetBuilder, WidgetMessage},
     BuildContext, Control, NodeHandleMapping, RestrictionEntry, Thickness, UiNode, UserInterface,
     BRUSH_DARKER, BRUSH_LIGHTER,
@@ -55,6 +57,22 @@ pub enum Placement {
     /// widget that is directly behind the cursor.
     Cursor(Handle<UiNode>),
 
+    /// A popup should be placed outside of given widget at the given edge of its screen bounds.
+    /// If the widget handle is `NONE`, the popup will be placed relative to the cursor position.
+    Edge {
+        /// A widget to anchor the popup to.
+        target: Handle<UiNode>,
+
+        /// An edge of the target widget, next to which the popup will be placed.
+        edge: PlacementEdge,
+
+        /// Alignment of the popup along the edge.
+        alignment: PlacementAlignment,
+
+        /// Distance between the edge and the popup.
+        offset: f32,
+    },
+
     /// A popup should be placed at given screen-space position.
     Position {
         /// Screen-space position.
@@ -66,6 +84,48 @@ pub enum Placement {
     },
 }
 
+/// An edge of a widget the popup is anchored to, see [`Placement::Edge`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PlacementEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl PlacementEdge {
+    pub fn opposite(self) -> Self {
+        match self {
+            PlacementEdge::Top => PlacementEdge::Bottom,
+            PlacementEdge::Bottom => PlacementEdge::Top,
+            PlacementEdge::Left => PlacementEdge::Right,
+            PlacementEdge::Right => PlacementEdge::Left,
+        }
+    }
+}
+
+/// Alignment of a popup along an edge of a widget, see [`Placement::Edge`]. `Start` means that
+/// left (or top) sides of the popup and the widget are aligned.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PlacementAlignment {
+    Start,
+    Center,
+    End,
+}
+
+impl PlacementAlignment {
+    fn opposite(self) -> Self {
+        match self {
+            PlacementAlignment::Start => PlacementAlignment::End,
+            PlacementAlignment::Center => PlacementAlignment::Center,
+            PlacementAlignment::End => PlacementAlignment::Start,
+        }
+    }
+}
+
+/// Size of the arrow that points to the anchor of a popup.
+const ARROW_SIZE: f32 = 6.0;
+
 #[derive(Clone)]
 pub struct Popup {
     pub widget: Widget,
@@ -75,23 +135,95 @@ pub struct Popup {
     pub content: Handle<UiNode>,
     pub body: Handle<UiNode>,
     pub smart_placement: bool,
+    /// Whether the popup should have an arrow that points to its anchor (target widget or
+    /// position).
+    pub arrow: bool,
+    /// Screen-space position at which the popup was opened.
+    anchor_position: Vector2<f32>,
+    /// Edge of the anchor at which the popup was actually placed (after flipping).
+    placed_edge: PlacementEdge,
+    /// Local position of the arrow along the edge of the popup.
+    arrow_offset: f32,
 }
 
 crate::define_widget_deref!(Popup);
 
-fn adjust_placement_position(
-    node_screen_bounds: Rect<f32>,
-    screen_size: Vector2<f32>,
+fn aligned(start: f32, length: f32, size: f32, alignment: PlacementAlignment) -> f32 {
+    match alignment {
+        PlacementAlignment::Start => start,
+        PlacementAlignment::Center => start + (length - size) * 0.5,
+        PlacementAlignment::End => start + length - size,
+    }
+}
+
+/// Calculates screen-space position of a popup of given size, placed outside of the anchor
+/// rectangle at given edge.
+fn edge_position(
+    anchor: Rect<f32>,
+    edge: PlacementEdge,
+    alignment: PlacementAlignment,
+    offset: f32,
+    size: Vector2<f32>,
 ) -> Vector2<f32> {
-    let mut new_position = node_screen_bounds.position;
-    let right_bottom = node_screen_bounds.right_bottom_corner();
-    if right_bottom.x > screen_size.x {
-        new_position.x -= right_bottom.x - screen_size.x;
+    match edge {
+        PlacementEdge::Top => Vector2::new(
+            aligned(anchor.x(), anchor.w(), size.x, alignment),
+            anchor.y() - offset - size.y,
+        ),
+        PlacementEdge::Bottom => Vector2::new(
+            aligned(anchor.x(), anchor.w(), size.x, alignment),
+            anchor.y() + anchor.h() + offset,
+        ),
+        PlacementEdge::Left => Vector2::new(
+            anchor.x() - offset - size.x,
+            aligned(anchor.y(), anchor.h(), size.y, alignment),
+        ),
+        PlacementEdge::Right => Vector2::new(
+            anchor.x() + anchor.w() + offset,
+            aligned(anchor.y(), anchor.h(), size.y, alignment),
+        ),
     }
-    if right_bottom.y > screen_size.y {
-        new_position.y -= right_bottom.y - screen_size.y;
+}
+
+/// Calculates screen-space position of a popup of given size, placed outside of the anchor
+/// rectangle at given edge. The popup is flipped to the opposite edge (and its alignment is
+/// flipped as well) if it does not fit into the screen, and then shifted into view if it still
+/// overflows the screen. Returns the position and the edge at which the popup was placed.
+fn smart_position(
+    anchor: Rect<f32>,
+    edge: PlacementEdge,
+    alignment: PlacementAlignment,
+    offset: f32,
+    size: Vector2<f32>,
+    screen_size: Vector2<f32>,
+) -> (Vector2<f32>, PlacementEdge) {
+    let fits_x = |x: f32| x >= 0.0 && x + size.x <= screen_size.x;
+    let fits_y = |y: f32| y >= 0.0 && y + size.y <= screen_size.y;
+    let vertical = matches!(edge, PlacementEdge::Top | PlacementEdge::Bottom);
+    let fits_main = |p: Vector2<f32>| if vertical { fits_y(p.y) } else { fits_x(p.x) };
+    let fits_cross = |p: Vector2<f32>| if vertical { fits_x(p.x) } else { fits_y(p.y) };
+
+    let mut edge = edge;
+    let mut position = edge_position(anchor, edge, alignment, offset, size);
+    if !fits_main(position) {
+        let flipped = edge_position(anchor, edge.opposite(), alignment, offset, size);
+        if fits_main(flipped) {
+            edge = edge.opposite();
+            position = flipped;
+        }
     }
-    new_position
+    if !fits_cross(position) {
+        let flipped = edge_position(anchor, edge, alignment.opposite(), offset, size);
+        if fits_cross(flipped) {
+            position = flipped;
+        }
+    }
+
+    // Shift into view, left top corner has priority if the popup is larger than the screen.
+    position.x = position.x.min(screen_size.x - size.x).max(0.0);
+    position.y = position.y.min(screen_size.y - size.y).max(0.0);
+
+    (position, edge)
 }
 
 impl Popup {
@@ -128,6 +260,51 @@ impl Popup {
             .map(|n| n.screen_position() + n.actual_global_size())
             .unwrap_or_else(|| ui.screen_size - self.widget.actual_global_size())
     }
+
+    /// Returns screen-space rectangle the popup is anchored to, edge of the rectangle, alignment
+    /// and offset. Placements that do not refer to an edge of a widget are anchored to a point
+    /// and open to the right bottom of it.
+    fn anchor(&self, ui: &UserInterface) -> (Rect<f32>, PlacementEdge, PlacementAlignment, f32) {
+        let target_bounds =
+            |target: Handle<UiNode>| ui.try_get_node(target).map(|n| n.screen_bounds());
+        let point = Rect::new(self.anchor_position.x, self.anchor_position.y, 0.0, 0.0);
+        match self.placement {
+            Placement::Edge {
+                target,
+                edge,
+                alignment,
+                offset,
+            } => (
+                target_bounds(target).unwrap_or(point),
+                edge,
+                alignment,
+                offset,
+            ),
+            Placement::LeftBottom(target) => match target_bounds(target) {
+                Some(bounds) => (
+                    bounds,
+                    PlacementEdge::Bottom,
+                    PlacementAlignment::Start,
+                    0.0,
+                ),
+                None => (point, PlacementEdge::Bottom, PlacementAlignment::Start, 0.0),
+            },
+            Placement::RightTop(target) => match target_bounds(target) {
+                Some(bounds) => (bounds, PlacementEdge::Right, PlacementAlignment::Start, 0.0),
+                None => (point, PlacementEdge::Bottom, PlacementAlignment::Start, 0.0),
+            },
+            _ => (point, PlacementEdge::Bottom, PlacementAlignment::Start, 0.0),
+        }
+    }
+
+    fn arrow_margin(edge: PlacementEdge) -> Thickness {
+        match edge {
+            PlacementEdge::Top => Thickness::bottom(ARROW_SIZE),
+            PlacementEdge::Bottom => Thickness::top(ARROW_SIZE),
+            PlacementEdge::Left => Thickness::right(ARROW_SIZE),
+            PlacementEdge::Right => Thickness::left(ARROW_SIZE),
+        }
+    }
 }
 
 impl Control for Popup {
@@ -144,6 +321,40 @@ impl Control for Popup {
         node_map.resolve(&mut self.body);
     }
 
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        if !self.arrow {
+            return;
+        }
+
+        // Arrow is drawn in the margin of the body, at the side that faces the anchor.
+        let size = self.actual_local_size();
+        let offset = self.arrow_offset;
+        let vertices = match self.placed_edge {
+            PlacementEdge::Bottom => [
+                Vector2::new(offset - ARROW_SIZE, ARROW_SIZE),
+                Vector2::new(offset + ARROW_SIZE, ARROW_SIZE),
+                Vector2::new(offset, 0.0),
+            ],
+            PlacementEdge::Top => [
+                Vector2::new(offset - ARROW_SIZE, size.y - ARROW_SIZE),
+                Vector2::new(offset + ARROW_SIZE, size.y - ARROW_SIZE),
+                Vector2::new(offset, size.y),
+            ],
+            PlacementEdge::Right => [
+                Vector2::new(ARROW_SIZE, offset - ARROW_SIZE),
+                Vector2::new(ARROW_SIZE, offset + ARROW_SIZE),
+                Vector2::new(0.0, offset),
+            ],
+            PlacementEdge::Left => [
+                Vector2::new(size.x - ARROW_SIZE, offset - ARROW_SIZE),
+                Vector2::new(size.x - ARROW_SIZE, offset + ARROW_SIZE),
+                Vector2::new(size.x, offset),
+            ],
+        };
+        drawing_context.push_triangle_filled(vertices);
+        drawing_context.commit(self.clip_bounds(), BRUSH_DARKER, CommandTexture::None, None);
+    }
+
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.widget.handle_routed_message(ui, message);
 
@@ -178,6 +389,21 @@ impl Control for Popup {
                                 }
                                 Placement::Cursor(_) => ui.cursor_position(),
                                 Placement::Position { position, .. } => position,
+                                Placement::Edge { .. } => ui.cursor_position(),
+                            };
+                            self.anchor_position = position;
+
+                            let position = if let Placement::Edge { .. } = self.placement {
+                                let (anchor, edge, alignment, offset) = self.anchor(ui);
+                                edge_position(
+                                    anchor,
+                                    edge,
+                                    alignment,
+                                    offset,
+                                    self.actual_global_size(),
+                                )
+                            } else {
+                                position
                             };
 
                             ui.send_message(WidgetMessage::desired_position(
@@ -227,8 +453,47 @@ impl Control for Popup {
                         self.invalidate_layout();
                     }
                     PopupMessage::AdjustPosition => {
-                        let new_position =
-                            adjust_placement_position(self.screen_bounds(), ui.screen_size());
+                        let (anchor, edge, alignment, offset) = self.anchor(ui);
+                        let (new_position, placed_edge) = smart_position(
+                            anchor,
+                            edge,
+                            alignment,
+                            offset,
+                            self.actual_global_size(),
+                            ui.screen_size(),
+                        );
+
+                        self.placed_edge = placed_edge;
+                        if self.arrow {
+                            let size = self.actual_global_size();
+                            let center = anchor.center();
+                            // Keep the arrow within the popup, away from its corners.
+                            self.arrow_offset = match placed_edge {
+                                PlacementEdge::Top | PlacementEdge::Bottom => (center.x
+                                    - new_position.x)
+                                    .min(size.x - ARROW_SIZE * 2.0)
+                                    .max(ARROW_SIZE * 2.0),
+                                PlacementEdge::Left | PlacementEdge::Right => (center.y
+                                    - new_position.y)
+                                    .min(size.y - ARROW_SIZE * 2.0)
+                                    .max(ARROW_SIZE * 2.0),
+                            };
+
+                            let margin = Self::arrow_margin(placed_edge);
+                            if ui.node(self.body).margin() != margin {
+                                ui.send_message(WidgetMessage::margin(
+                                    self.body,
+                                    MessageDirection::ToWidget,
+                                    margin,
+                                ));
+                                // Size of the popup might change, so the position must be
+                                // calculated again.
+                                ui.send_message(PopupMessage::adjust_position(
+                                    self.handle,
+                                    MessageDirection::ToWidget,
+                                ));
+                            }
+                        }
 
                         if new_position != self.screen_position() {
                             ui.send_message(WidgetMessage::desired_position(
@@ -274,6 +539,7 @@ pub struct PopupBuilder {
     stays_open: bool,
     content: Handle<UiNode>,
     smart_placement: bool,
+    arrow: bool,
 }
 
 impl PopupBuilder {
@@ -284,6 +550,7 @@ impl PopupBuilder {
             stays_open: false,
             content: Default::default(),
             smart_placement: true,
+            arrow: false,
         }
     }
 
@@ -297,6 +564,13 @@ impl PopupBuilder {
         self
     }
 
+    /// Adds an arrow that points from the popup to its anchor. Arrow is placed only when smart
+    /// placement is enabled.
+    pub fn with_arrow(mut self, arrow: bool) -> Self {
+        self.arrow = arrow;
+        self
+    }
+
     pub fn stays_open(mut self, value: bool) -> Self {
         self.stays_open = value;
         self
@@ -310,6 +584,11 @@ impl PopupBuilder {
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
         let body = BorderBuilder::new(
             WidgetBuilder::new()
+                .with_margin(if self.arrow {
+                    Popup::arrow_margin(PlacementEdge::Bottom)
+                } else {
+                    Thickness::zero()
+                })
                 .with_background(BRUSH_DARKER)
                 .with_foreground(BRUSH_LIGHTER)
                 .with_child(self.content),
@@ -329,9 +608,72 @@ impl PopupBuilder {
             is_open: false,
             content: self.content,
             smart_placement: self.smart_placement,
+            arrow: self.arrow,
+            anchor_position: Default::default(),
+            placed_edge: PlacementEdge::Bottom,
+            arrow_offset: 0.0,
             body,
         };
 
-        ctx.add_node(UiNode::new(popup))
+        let popup = ctx.add_node(UiNode::new(popup));
+        let layer = ctx.layer(OverlayLayer::Popups);
+        ctx.link(popup, layer);
+        popup
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        popup::{smart_position, PlacementAlignment, PlacementEdge},
+    };
+
+    #[test]
+    fn test_smart_position() {
+        let screen = Vector2::new(100.0, 100.0);
+        let size = Vector2::new(40.0, 30.0);
+
+        // Fits as is.
+        let anchor = Rect::new(10.0, 10.0, 20.0, 10.0);
+        assert_eq!(
+            smart_position(
+                anchor,
+                PlacementEdge::Bottom,
+                PlacementAlignment::Start,
+                2.0,
+                size,
+                screen
+            ),
+            (Vector2::new(10.0, 22.0), PlacementEdge::Bottom)
+        );
+
+        // Flipped to the top edge and to the end alignment near right bottom corner.
+        let anchor = Rect::new(70.0, 80.0, 20.0, 10.0);
+        assert_eq!(
+            smart_position(
+                anchor,
+                PlacementEdge::Bottom,
+                PlacementAlignment::Start,
+                0.0,
+                size,
+                screen
+            ),
+            (Vector2::new(50.0, 50.0), PlacementEdge::Top)
+        );
+
+        // Does not fit at any side - shifted into view.
+        let anchor = Rect::new(90.0, 0.0, 0.0, 100.0);
+        assert_eq!(
+            smart_position(
+                anchor,
+                PlacementEdge::Right,
+                PlacementAlignment::Start,
+                0.0,
+                Vector2::new(95.0, 30.0),
+                screen
+            ),
+            (Vector2::new(5.0, 0.0), PlacementEdge::Right)
+        );
     }
 }
@@ -1,6 +1,6 @@
 use crate::{
     border::BorderBuilder,
-    brush::{Brush, GradientPoint},
+    brush::{Brush, BrushCoordinates, GradientPoint},
     button::{ButtonBuilder, ButtonMessage},
     canvas::CanvasBuilder,
     core::{algebra::Vector2, color::Color, pool::Handle},
@@ -453,6 +453,7 @@ impl ScrollBarBuilder {
                             color: COLOR_DARKEST,
                         },
                     ],
+                    coordinates: BrushCoordinates::Relative,
                 }))
                 .with_stroke_thickness(Thickness::uniform(1.0)),
             )
@@ -0,0 +1,158 @@
+//! Search bar is a text box with a "clear" button, it is meant to be used as a source of filter
+//! strings for [`crate::tree::TreeRoot`] or [`crate::list_view::ListView`] (see
+//! [`crate::tree::TreeRootMessage::Filter`] and [`crate::list_view::ListViewMessage::Filter`]).
+
+use crate::{
+    button::{ButtonBuilder, ButtonMessage},
+    core::pool::Handle,
+    define_constructor,
+    grid::{Column, GridBuilder, Row},
+    message::{MessageDirection, UiMessage},
+    text::TextMessage,
+    text_box::{TextBoxBuilder, TextCommitMode},
+    utils::make_cross,
+    widget::{Widget, WidgetBuilder},
+    BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface, VerticalAlignment,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchBarMessage {
+    /// Sets new search text (when sent to widget) or notifies that search text was changed (when
+    /// sent from widget).
+    Text(String),
+}
+
+impl SearchBarMessage {
+    define_constructor!(SearchBarMessage:Text => fn text(String), layout: false);
+}
+
+#[derive(Clone)]
+pub struct SearchBar {
+    pub widget: Widget,
+    pub text_box: Handle<UiNode>,
+    pub clear: Handle<UiNode>,
+    pub text: String,
+}
+
+crate::define_widget_deref!(SearchBar);
+
+impl Control for SearchBar {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve(&mut self.text_box);
+        node_map.resolve(&mut self.clear);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.text_box
+                && message.direction() == MessageDirection::FromWidget
+                && &self.text != text
+            {
+                self.text = text.clone();
+                ui.send_message(SearchBarMessage::text(
+                    self.handle,
+                    MessageDirection::FromWidget,
+                    self.text.clone(),
+                ));
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.clear {
+                ui.send_message(TextMessage::text(
+                    self.text_box,
+                    MessageDirection::ToWidget,
+                    Default::default(),
+                ));
+            }
+        } else if let Some(SearchBarMessage::Text(text)) = message.data() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+                && &self.text != text
+            {
+                // Text box will send the change back to us and we'll notify subscribers.
+                ui.send_message(TextMessage::text(
+                    self.text_box,
+                    MessageDirection::ToWidget,
+                    text.clone(),
+                ));
+            }
+        }
+    }
+}
+
+impl SearchBar {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+pub struct SearchBarBuilder {
+    widget_builder: WidgetBuilder,
+    text: String,
+}
+
+impl SearchBarBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            text: Default::default(),
+        }
+    }
+
+    pub fn with_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.text = text.as_ref().to_owned();
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let text_box;
+        let clear;
+        let grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child({
+                    text_box = TextBoxBuilder::new(WidgetBuilder::new().on_column(0))
+                        .with_text_commit_mode(TextCommitMode::Immediate)
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .with_text(self.text.clone())
+                        .build(ctx);
+                    text_box
+                })
+                .with_child({
+                    clear = ButtonBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::left(1.0))
+                            .on_column(1),
+                    )
+                    .with_content(make_cross(ctx, 8.0, 2.0))
+                    .build(ctx);
+                    clear
+                }),
+        )
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .add_column(Column::strict(20.0))
+        .build(ctx);
+
+        let search_bar = SearchBar {
+            widget: self.widget_builder.with_child(grid).build(),
+            text_box,
+            clear,
+            text: self.text,
+        };
+
+        ctx.add_node(UiNode::new(search_bar))
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     define_constructor,
     draw::DrawingContext,
     formatted_text::{FormattedText, FormattedTextBuilder, WrapMode},
+    localization::{Localization, LocalizedText},
     message::{MessageDirection, UiMessage},
     ttf::SharedFont,
     widget::{Widget, WidgetBuilder},
@@ -26,6 +27,9 @@ pub enum TextMessage {
     ShadowDilation(f32),
     ShadowBrush(Brush),
     ShadowOffset(Vector2<f32>),
+    /// Binds the text to a message of [`Localization`], the text will be re-translated every
+    /// time when current language changes. `None` removes the binding, current text is kept.
+    Localized(Option<LocalizedText>),
 }
 
 impl TextMessage {
@@ -38,12 +42,14 @@ impl TextMessage {
     define_constructor!(TextMessage:ShadowDilation => fn shadow_dilation(f32), layout: false);
     define_constructor!(TextMessage:ShadowBrush => fn shadow_brush(Brush), layout: false);
     define_constructor!(TextMessage:ShadowOffset => fn shadow_offset(Vector2<f32>), layout: false);
+    define_constructor!(TextMessage:Localized => fn localized(Option<LocalizedText>), layout: false);
 }
 
 #[derive(Clone)]
 pub struct Text {
     pub widget: Widget,
     pub formatted_text: RefCell<FormattedText>,
+    pub localized_text: Option<LocalizedText>,
 }
 
 crate::define_widget_deref!(Text);
@@ -142,6 +148,13 @@ impl Control for Text {
                             self.invalidate_layout();
                         }
                     }
+                    TextMessage::Localized(localized_text) => {
+                        drop(text_ref);
+                        if &self.localized_text != localized_text {
+                            self.localized_text = localized_text.clone();
+                            self.relocalize(ui.localization());
+                        }
+                    }
                 }
             }
         }
@@ -168,11 +181,29 @@ impl Text {
     pub fn horizontal_alignment(&self) -> HorizontalAlignment {
         self.formatted_text.borrow().horizontal_alignment()
     }
+
+    /// Returns a message the text is bound to.
+    pub fn localized_text(&self) -> Option<&LocalizedText> {
+        self.localized_text.as_ref()
+    }
+
+    pub(crate) fn relocalize(&mut self, localization: &Localization) {
+        if let Some(localized_text) = self.localized_text.as_ref() {
+            let text = localization.localize(localized_text);
+            let mut text_ref = self.formatted_text.borrow_mut();
+            if text_ref.text() != text {
+                text_ref.set_text(text);
+                drop(text_ref);
+                self.invalidate_layout();
+            }
+        }
+    }
 }
 
 pub struct TextBuilder {
     widget_builder: WidgetBuilder,
     text: Option<String>,
+    localized_text: Option<LocalizedText>,
     font: Option<SharedFont>,
     vertical_text_alignment: VerticalAlignment,
     horizontal_text_alignment: HorizontalAlignment,
@@ -188,6 +219,7 @@ impl TextBuilder {
         Self {
             widget_builder,
             text: None,
+            localized_text: None,
             font: None,
             vertical_text_alignment: VerticalAlignment::Top,
             horizontal_text_alignment: HorizontalAlignment::Left,
@@ -204,6 +236,17 @@ impl TextBuilder {
         self
     }
 
+    /// Binds the text to a message with the given key, see [`TextMessage::Localized`] for more info.
+    pub fn with_localization_key<K: AsRef<str>>(self, key: K) -> Self {
+        self.with_localized_text(LocalizedText::new(key))
+    }
+
+    /// Binds the text to a message, see [`TextMessage::Localized`] for more info.
+    pub fn with_localized_text(mut self, localized_text: LocalizedText) -> Self {
+        self.localized_text = Some(localized_text);
+        self
+    }
+
     pub fn with_font(mut self, font: SharedFont) -> Self {
         self.font = Some(font);
         self
@@ -265,11 +308,16 @@ impl TextBuilder {
             self.widget_builder.foreground = Some(Brush::Solid(Color::opaque(220, 220, 220)));
         }
 
+        let text = match self.localized_text.as_ref() {
+            Some(localized_text) => ui.localization().localize(localized_text),
+            None => self.text.unwrap_or_default(),
+        };
+
         let text = Text {
             widget: self.widget_builder.build(),
             formatted_text: RefCell::new(
                 FormattedTextBuilder::new(font)
-                    .with_text(self.text.unwrap_or_default())
+                    .with_text(text)
                     .with_vertical_alignment(self.vertical_text_alignment)
                     .with_horizontal_alignment(self.horizontal_text_alignment)
                     .with_wrap(self.wrap)
@@ -279,6 +327,7 @@ impl TextBuilder {
                     .with_shadow_offset(self.shadow_offset)
                     .build(),
             ),
+            localized_text: self.localized_text,
         };
         ui.add_node(UiNode::new(text))
     }
@@ -9,7 +9,7 @@ use crate::{
     define_constructor,
     draw::{CommandTexture, Draw, DrawingContext},
     formatted_text::{FormattedText, FormattedTextBuilder, WrapMode},
-    message::{CursorIcon, KeyCode, MessageDirection, MouseButton, UiMessage},
+    message::{CursorIcon, ImeEvent, KeyCode, MessageDirection, MouseButton, UiMessage},
     text::TextMessage,
     ttf::SharedFont,
     widget::{Widget, WidgetBuilder, WidgetMessage},
@@ -138,6 +138,11 @@ pub struct TextBox {
     pub editable: bool,
     pub view_position: Vector2<f32>,
     pub skip_chars: Vec<u32>,
+    /// Current IME composition (pre-edit) string. It is drawn at the caret position, but it is
+    /// not a part of the text until it is committed.
+    pub composition: String,
+    /// Position of IME cursor (in characters) inside the composition string.
+    pub composition_cursor: Option<usize>,
 }
 
 impl Debug for TextBox {
@@ -405,6 +410,44 @@ impl TextBox {
         ));
     }
 
+    fn commit_composition(&mut self, text: &str, ui: &UserInterface) {
+        self.composition.clear();
+        self.composition_cursor = None;
+
+        let text = if let Some(filter) = self.filter.as_ref() {
+            let filter = &mut *filter.borrow_mut();
+            text.chars()
+                .filter(|c| !c.is_control() && filter(*c))
+                .collect::<String>()
+        } else {
+            text.chars().filter(|c| !c.is_control()).collect::<String>()
+        };
+
+        if !text.is_empty() {
+            if let Some(range) = self.selection_range {
+                self.remove_range(ui, range);
+                self.selection_range = None;
+            }
+            self.insert_str(&text, ui);
+        }
+    }
+
+    fn reset_composition(&mut self) {
+        self.composition.clear();
+        self.composition_cursor = None;
+    }
+
+    /// Returns position (in screen coordinates) right below the caret. It should be used to
+    /// place IME candidate window, so it won't overlap the text that is being typed.
+    pub fn ime_candidate_position(&self) -> Vector2<f32> {
+        let height = self.formatted_text.borrow().get_font().0.lock().height();
+        let position =
+            self.point_to_view_pos(self.caret_local_position()) + Vector2::new(0.0, height);
+        self.visual_transform
+            .transform_point(&Point2::from(position))
+            .coords
+    }
+
     pub fn get_text_len(&self) -> usize {
         self.formatted_text.borrow_mut().get_raw_text().len()
     }
@@ -423,14 +466,9 @@ impl TextBox {
                 if offset >= self.caret_position.offset {
                     break;
                 }
-                if let Some(glyph) = text
+                caret_pos.x += text
                     .get(char_index)
-                    .and_then(|c| font.glyphs().get(c.glyph_index as usize))
-                {
-                    caret_pos.x += glyph.advance;
-                } else {
-                    caret_pos.x += font.height();
-                }
+                    .map_or(font.height(), |c| font.character_advance(c));
             }
         }
 
@@ -565,18 +603,19 @@ impl TextBox {
                 // Check each character in line.
                 for (offset, index) in (line.begin..line.end).enumerate() {
                     let character = self.formatted_text.borrow().get_raw_text()[index];
-                    let (width, height, advance) =
-                        if let Some(glyph) = font.glyphs().get(character.glyph_index as usize) {
+                    let (width, height, advance) = font
+                        .map_character_glyph(&character, |glyph| {
                             (
                                 glyph.bitmap_width as f32,
                                 glyph.bitmap_height as f32,
                                 glyph.advance,
                             )
-                        } else {
+                        })
+                        .unwrap_or_else(|| {
                             // Stub
                             let h = font.height();
                             (h, h, h)
-                        };
+                        });
                     let char_screen_bounds = Rect::new(x, line_screen_bounds.y(), width, height);
                     if char_screen_bounds.contains(point_to_check) {
                         let char_bounds_center_x =
@@ -791,8 +830,49 @@ impl Control for TextBox {
             &self.formatted_text.borrow(),
         );
 
+        let mut caret_pos = self.point_to_view_pos(self.caret_local_position());
+
+        if !self.composition.is_empty() {
+            let font = self.formatted_text.borrow().get_font();
+            let height = font.0.lock().height();
+            let mut composition = FormattedTextBuilder::new(font)
+                .with_text(self.composition.clone())
+                .with_brush(self.widget.foreground())
+                .with_constraint(Vector2::new(f32::INFINITY, height))
+                .build();
+            composition.build();
+            let len = composition.get_raw_text().len();
+            let width = composition.get_range_width(0..len);
+
+            // Composition string is drawn on top of the text, so hide the text behind it.
+            drawing_context
+                .push_rect_filled(&Rect::new(caret_pos.x, caret_pos.y, width, height), None);
+            drawing_context.commit(
+                self.clip_bounds(),
+                self.widget.background(),
+                CommandTexture::None,
+                None,
+            );
+
+            drawing_context.draw_text(self.clip_bounds(), caret_pos, &composition);
+
+            // Underline marks the text as not committed yet.
+            drawing_context.push_rect_filled(
+                &Rect::new(caret_pos.x, caret_pos.y + height - 1.0, width, 1.0),
+                None,
+            );
+            drawing_context.commit(
+                self.clip_bounds(),
+                self.widget.foreground(),
+                CommandTexture::None,
+                None,
+            );
+
+            caret_pos.x +=
+                composition.get_range_width(0..self.composition_cursor.unwrap_or(len).min(len));
+        }
+
         if self.caret_visible {
-            let caret_pos = self.point_to_view_pos(self.caret_local_position());
             let caret_bounds = Rect::new(
                 caret_pos.x,
                 caret_pos.y,
@@ -815,9 +895,11 @@ impl Control for TextBox {
             if self.blink_timer >= self.blink_interval {
                 self.blink_timer = 0.0;
                 self.caret_visible = !self.caret_visible;
+                self.invalidate_visual();
             }
-        } else {
+        } else if self.caret_visible {
             self.caret_visible = false;
+            self.invalidate_visual();
         }
     }
 
@@ -848,6 +930,26 @@ impl Control for TextBox {
                             }
                         }
                     }
+                    WidgetMessage::Ime(ime) if self.editable => {
+                        match ime {
+                            ImeEvent::Preedit { text, cursor } => {
+                                self.composition = text.clone();
+                                // IME reports cursor position in bytes, but we need characters.
+                                self.composition_cursor = cursor.and_then(|(_, end)| {
+                                    text.get(..end).map(|prefix| prefix.chars().count())
+                                });
+                                self.reset_blink();
+                            }
+                            ImeEvent::Commit(text) => {
+                                self.commit_composition(text, ui);
+                            }
+                            ImeEvent::Enabled | ImeEvent::Disabled => {
+                                self.reset_composition();
+                            }
+                        }
+
+                        message.set_handled(true);
+                    }
                     WidgetMessage::KeyDown(code) => {
                         match code {
                             KeyCode::Up => {
@@ -1072,6 +1174,7 @@ impl Control for TextBox {
                         if message.direction() == MessageDirection::FromWidget {
                             self.selection_range = None;
                             self.has_focus = false;
+                            self.reset_composition();
 
                             if self.commit_mode == TextCommitMode::LostFocus
                                 || self.commit_mode == TextCommitMode::LostFocusPlusEnter
@@ -1214,6 +1317,9 @@ impl Control for TextBox {
                                 ui.send_message(message.reverse());
                             }
                         }
+                        TextMessage::Localized(_) => {
+                            // Text box contains user input, it is never localized.
+                        }
                     }
                 }
             } else if let Some(msg) = message.data::<TextBoxMessage>() {
@@ -1436,6 +1542,8 @@ impl TextBoxBuilder {
             editable: self.editable,
             view_position: Default::default(),
             skip_chars: self.skip_chars,
+            composition: Default::default(),
+            composition_cursor: None,
         };
 
         ctx.add_node(UiNode::new(text_box))
@@ -0,0 +1,468 @@
+//! Toolbar is a horizontal strip of items (buttons, toggle buttons, separators or any other
+//! widgets). Items that do not fit into the toolbar are hidden and can be accessed via the
+//! overflow button at the right side of the toolbar, which shows them in a drop-down popup.
+//!
+//! The module also contains [`ToggleButton`] - a button that stays pressed until it is clicked
+//! again, and [`make_separator`] to visually split items into groups.
+
+use crate::{
+    border::BorderBuilder,
+    button::{ButtonBuilder, ButtonMessage},
+    core::{algebra::Vector2, math::Rect, pool::Handle},
+    decorator::{DecoratorBuilder, DecoratorMessage},
+    define_constructor,
+    message::{MessageDirection, UiMessage},
+    popup::{Placement, PopupBuilder, PopupMessage},
+    stack_panel::StackPanelBuilder,
+    utils::{make_arrow, ArrowDirection},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface, BRUSH_LIGHT,
+    BRUSH_LIGHTER,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::Cell,
+    ops::{Deref, DerefMut},
+    sync::mpsc::Sender,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolbarMessage {
+    /// Replaces every item of the toolbar. Previous items are removed.
+    Items(Vec<Handle<UiNode>>),
+    AddItem(Handle<UiNode>),
+    /// Removes given item from the toolbar and deletes it.
+    RemoveItem(Handle<UiNode>),
+}
+
+impl ToolbarMessage {
+    define_constructor!(ToolbarMessage:Items => fn items(Vec<Handle<UiNode>>), layout: false);
+    define_constructor!(ToolbarMessage:AddItem => fn add_item(Handle<UiNode>), layout: false);
+    define_constructor!(ToolbarMessage:RemoveItem => fn remove_item(Handle<UiNode>), layout: false);
+}
+
+#[derive(Clone)]
+pub struct Toolbar {
+    pub widget: Widget,
+    pub items: Vec<Handle<UiNode>>,
+    pub overflow: Handle<UiNode>,
+    pub popup: Handle<UiNode>,
+    /// A panel in the popup, that holds overflowing items while the popup is open.
+    pub overflow_panel: Handle<UiNode>,
+    /// Index of the first item that does not fit into the toolbar.
+    overflow_index: Cell<usize>,
+}
+
+crate::define_widget_deref!(Toolbar);
+
+/// Returns index of the first item that does not fit into given width. If some items do not fit,
+/// the space for the overflow button is reserved.
+fn overflow_index(items: &[f32], overflow: f32, width: f32) -> usize {
+    if items.iter().sum::<f32>() <= width {
+        return items.len();
+    }
+    let mut x = overflow;
+    for (i, item) in items.iter().enumerate() {
+        x += item;
+        if x > width {
+            return i;
+        }
+    }
+    items.len()
+}
+
+impl Toolbar {
+    /// Returns index of the first item that is hidden in the overflow popup. It is equal to the
+    /// amount of items if every item fits into the toolbar.
+    pub fn overflow_index(&self) -> usize {
+        self.overflow_index.get()
+    }
+
+    fn is_in_toolbar(&self, ui: &UserInterface, item: Handle<UiNode>) -> bool {
+        ui.node(item).parent() == self.handle
+    }
+
+    fn return_items(&self, ui: &UserInterface) {
+        for &item in self.items.iter() {
+            if ui.node(item).parent() == self.overflow_panel {
+                ui.send_message(WidgetMessage::link(
+                    item,
+                    MessageDirection::ToWidget,
+                    self.handle,
+                ));
+            }
+        }
+    }
+}
+
+impl Control for Toolbar {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn on_remove(&self, sender: &Sender<UiMessage>) {
+        // Popup is not a child of the toolbar, so it must be removed manually.
+        sender
+            .send(WidgetMessage::remove(
+                self.popup,
+                MessageDirection::ToWidget,
+            ))
+            .unwrap();
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve_slice(&mut self.items);
+        node_map.resolve(&mut self.overflow);
+        node_map.resolve(&mut self.popup);
+        node_map.resolve(&mut self.overflow_panel);
+    }
+
+    fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
+        let constraint = Vector2::new(f32::INFINITY, available_size.y);
+        let mut size = Vector2::<f32>::default();
+        for &item in self.items.iter() {
+            // Items in the overflow popup are measured by the popup.
+            if self.is_in_toolbar(ui, item) {
+                ui.measure_node(item, constraint);
+            }
+            let desired = ui.node(item).desired_size();
+            size.x += desired.x;
+            size.y = size.y.max(desired.y);
+        }
+        ui.measure_node(self.overflow, constraint);
+        size.x = size.x.min(available_size.x);
+        size
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        let widths = self
+            .items
+            .iter()
+            .map(|i| ui.node(*i).desired_size().x)
+            .collect::<Vec<_>>();
+        let overflow_width = ui.node(self.overflow).desired_size().x;
+        let overflow_index = overflow_index(&widths, overflow_width, final_size.x);
+        self.overflow_index.set(overflow_index);
+
+        let mut x = 0.0;
+        for (i, (&item, width)) in self.items.iter().zip(widths).enumerate() {
+            if !self.is_in_toolbar(ui, item) {
+                continue;
+            }
+            if i < overflow_index {
+                ui.arrange_node(item, &Rect::new(x, 0.0, width, final_size.y));
+                x += width;
+            } else {
+                // Overflowing items are arranged with zero size, which makes them invisible.
+                ui.arrange_node(item, &Rect::default());
+            }
+        }
+
+        let overflow_bounds = if overflow_index < self.items.len() {
+            Rect::new(
+                final_size.x - overflow_width,
+                0.0,
+                overflow_width,
+                final_size.y,
+            )
+        } else {
+            Rect::default()
+        };
+        ui.arrange_node(self.overflow, &overflow_bounds);
+
+        final_size
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.overflow {
+                for &item in self.items[self.overflow_index.get().min(self.items.len())..].iter() {
+                    ui.send_message(WidgetMessage::link(
+                        item,
+                        MessageDirection::ToWidget,
+                        self.overflow_panel,
+                    ));
+                }
+                ui.send_message(PopupMessage::placement(
+                    self.popup,
+                    MessageDirection::ToWidget,
+                    Placement::LeftBottom(self.overflow),
+                ));
+                ui.send_message(PopupMessage::open(self.popup, MessageDirection::ToWidget));
+            }
+        } else if let Some(msg) = message.data::<ToolbarMessage>() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    ToolbarMessage::Items(items) => {
+                        for &item in self.items.iter() {
+                            ui.send_message(WidgetMessage::remove(
+                                item,
+                                MessageDirection::ToWidget,
+                            ));
+                        }
+                        for &item in items.iter() {
+                            ui.send_message(WidgetMessage::link(
+                                item,
+                                MessageDirection::ToWidget,
+                                self.handle,
+                            ));
+                        }
+                        self.items = items.clone();
+                        self.invalidate_layout();
+                    }
+                    &ToolbarMessage::AddItem(item) => {
+                        ui.send_message(WidgetMessage::link(
+                            item,
+                            MessageDirection::ToWidget,
+                            self.handle,
+                        ));
+                        self.items.push(item);
+                        self.invalidate_layout();
+                    }
+                    &ToolbarMessage::RemoveItem(item) => {
+                        if let Some(position) = self.items.iter().position(|i| *i == item) {
+                            self.items.remove(position);
+                            ui.send_message(WidgetMessage::remove(
+                                item,
+                                MessageDirection::ToWidget,
+                            ));
+                            self.invalidate_layout();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn preview_message(&self, ui: &UserInterface, message: &mut UiMessage) {
+        // Popup is not a child of the toolbar, so messages from it won't be routed to the toolbar.
+        if let Some(PopupMessage::Close) = message.data() {
+            if message.destination() == self.popup {
+                self.return_items(ui);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if ui
+                .node(self.overflow_panel)
+                .has_descendant(message.destination(), ui)
+            {
+                ui.send_message(PopupMessage::close(self.popup, MessageDirection::ToWidget));
+            }
+        }
+    }
+}
+
+pub struct ToolbarBuilder {
+    widget_builder: WidgetBuilder,
+    items: Vec<Handle<UiNode>>,
+}
+
+impl ToolbarBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            items: Default::default(),
+        }
+    }
+
+    pub fn with_items(mut self, items: Vec<Handle<UiNode>>) -> Self {
+        self.items = items;
+        self
+    }
+
+    pub fn with_item(mut self, item: Handle<UiNode>) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let overflow = ButtonBuilder::new(WidgetBuilder::new().with_width(16.0))
+            .with_content(make_arrow(ctx, ArrowDirection::Bottom, 8.0))
+            .build(ctx);
+        let overflow_panel = StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+        let popup = PopupBuilder::new(WidgetBuilder::new())
+            .with_content(overflow_panel)
+            .build(ctx);
+
+        let toolbar = Toolbar {
+            widget: self
+                .widget_builder
+                .with_children(self.items.iter().cloned())
+                .with_child(overflow)
+                .with_preview_messages(true)
+                .build(),
+            items: self.items,
+            overflow,
+            popup,
+            overflow_panel,
+            overflow_index: Cell::new(0),
+        };
+
+        ctx.add_node(UiNode::new(toolbar))
+    }
+}
+
+/// Creates a vertical line that splits toolbar items into groups.
+pub fn make_separator(ctx: &mut BuildContext) -> Handle<UiNode> {
+    BorderBuilder::new(
+        WidgetBuilder::new()
+            .with_width(1.0)
+            .with_margin(Thickness::left_right(3.0))
+            .with_background(BRUSH_LIGHT),
+    )
+    .with_stroke_thickness(Thickness::uniform(0.0))
+    .build(ctx)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToggleButtonMessage {
+    /// Sets new state of the button (when sent to widget) or notifies that the state was changed
+    /// (when sent from widget).
+    Toggled(bool),
+}
+
+impl ToggleButtonMessage {
+    define_constructor!(ToggleButtonMessage:Toggled => fn toggled(bool), layout: false);
+}
+
+/// A button that switches its state on every click. Toggled button is highlighted.
+#[derive(Clone)]
+pub struct ToggleButton {
+    pub widget: Widget,
+    pub decorator: Handle<UiNode>,
+    pub content: Handle<UiNode>,
+    pub is_toggled: bool,
+}
+
+crate::define_widget_deref!(ToggleButton);
+
+impl ToggleButton {
+    pub fn is_toggled(&self) -> bool {
+        self.is_toggled
+    }
+}
+
+impl Control for ToggleButton {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve(&mut self.decorator);
+        node_map.resolve(&mut self.content);
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<WidgetMessage>() {
+            if message.destination() == self.handle()
+                || self.has_descendant(message.destination(), ui)
+            {
+                match msg {
+                    WidgetMessage::MouseDown { .. } => {
+                        ui.capture_mouse(message.destination());
+                        message.set_handled(true);
+                    }
+                    WidgetMessage::MouseUp { .. } => {
+                        ui.release_mouse_capture();
+                        ui.send_message(ToggleButtonMessage::toggled(
+                            self.handle,
+                            MessageDirection::ToWidget,
+                            !self.is_toggled,
+                        ));
+                        message.set_handled(true);
+                    }
+                    _ => (),
+                }
+            }
+        } else if let Some(&ToggleButtonMessage::Toggled(toggled)) = message.data() {
+            if message.destination() == self.handle
+                && message.direction() == MessageDirection::ToWidget
+                && self.is_toggled != toggled
+            {
+                self.is_toggled = toggled;
+                ui.send_message(DecoratorMessage::select(
+                    self.decorator,
+                    MessageDirection::ToWidget,
+                    toggled,
+                ));
+                ui.send_message(message.reverse());
+            }
+        }
+    }
+}
+
+pub struct ToggleButtonBuilder {
+    widget_builder: WidgetBuilder,
+    content: Handle<UiNode>,
+    is_toggled: bool,
+}
+
+impl ToggleButtonBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            content: Default::default(),
+            is_toggled: false,
+        }
+    }
+
+    pub fn with_content(mut self, content: Handle<UiNode>) -> Self {
+        self.content = content;
+        self
+    }
+
+    pub fn with_toggled(mut self, toggled: bool) -> Self {
+        self.is_toggled = toggled;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let decorator = DecoratorBuilder::new(
+            BorderBuilder::new(WidgetBuilder::new().with_child(self.content))
+                .with_stroke_thickness(Thickness::uniform(1.0)),
+        )
+        .with_normal_brush(BRUSH_LIGHT)
+        .with_hover_brush(BRUSH_LIGHTER)
+        .with_selected(self.is_toggled)
+        .build(ctx);
+
+        let toggle_button = ToggleButton {
+            widget: self.widget_builder.with_child(decorator).build(),
+            decorator,
+            content: self.content,
+            is_toggled: self.is_toggled,
+        };
+
+        ctx.add_node(UiNode::new(toggle_button))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::toolbar::overflow_index;
+
+    #[test]
+    fn test_overflow_index() {
+        let items = [30.0, 30.0, 10.0, 30.0];
+
+        assert_eq!(overflow_index(&items, 16.0, 100.0), 4);
+        // Space for the overflow button is reserved: 16 + 30 + 30 + 10 = 86.
+        assert_eq!(overflow_index(&items, 16.0, 90.0), 3);
+        assert_eq!(overflow_index(&items, 16.0, 50.0), 1);
+        assert_eq!(overflow_index(&items, 16.0, 10.0), 0);
+        assert_eq!(overflow_index(&[], 16.0, 0.0), 0);
+    }
+}
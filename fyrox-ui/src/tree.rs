@@ -6,18 +6,42 @@
 //!
 //! `Ctrl+Click` - enables multi-selection.
 //! `Alt+Click` - prevents selection allowing you to use drag'n'drop.
+//!
+//! ## Lazy population
+//!
+//! Trees with large amount of items could be populated on demand: build a tree with
+//! [`TreeBuilder::with_lazy_population`] and it will show its expander even if it has no items,
+//! on first expansion it will send [`TreeMessage::PopulateChildren`] (with
+//! [`MessageDirection::FromWidget`]) so you can create its items (via [`TreeMessage::SetItems`]
+//! or [`TreeMessage::AddItem`]) only when they're needed.
+//!
+//! ## Virtualization
+//!
+//! Every item has a header row of fixed height ([`TREE_ITEM_HEIGHT`]), so content of items that
+//! are out of the visible area could be hidden without changing the layout of the tree. Call
+//! [`TreeRoot::virtualize_items`] with the visible area (for example clip bounds of a scroll viewer
+//! that contains the tree) when it could change, and content of off-screen items won't be measured,
+//! arranged and drawn. Only expanded items are visited, so the cost of the call depends on the
+//! amount of expanded items.
+//!
+//! ## Filtering
+//!
+//! [`TreeRootMessage::Filter`] hides every tree that does not match the filter string and does not
+//! have matching descendants, matching items are highlighted. By default, the text of an item is
+//! collected from every [`crate::text::Text`] widget in its content, use
+//! [`TreeBuilder::with_search_text`] to override it.
 
 use crate::{
     border::BorderBuilder,
     brush::Brush,
     check_box::{CheckBoxBuilder, CheckBoxMessage},
-    core::{algebra::Vector2, color::Color, pool::Handle},
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
     decorator::{DecoratorBuilder, DecoratorMessage},
     define_constructor,
     grid::{Column, GridBuilder, Row},
     message::{MessageDirection, UiMessage},
     stack_panel::StackPanelBuilder,
-    utils::{make_arrow, ArrowDirection},
+    utils::{collect_text, make_arrow, ArrowDirection},
     widget::{Widget, WidgetBuilder, WidgetMessage},
     BuildContext, Control, MouseButton, NodeHandleMapping, Thickness, UiNode, UserInterface,
     VerticalAlignment, BRUSH_DARK, BRUSH_DARKEST,
@@ -27,6 +51,9 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+/// Height of the header row of every tree item.
+pub const TREE_ITEM_HEIGHT: f32 = 24.0;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SelectionState(pub(crate) bool);
 
@@ -50,6 +77,13 @@ pub enum TreeMessage {
     RemoveItem(Handle<UiNode>),
     SetExpanderShown(bool),
     SetItems(Vec<Handle<UiNode>>),
+    /// Marks tree as lazy (or not), see [`TreeBuilder::with_lazy_population`].
+    SetLazy(bool),
+    /// Sent by a lazy tree when it was expanded first time and its items should be created.
+    PopulateChildren,
+    /// Hides (or shows) content of the tree item, the item keeps its height. See
+    /// [`TreeRoot::virtualize_items`].
+    Virtualize(bool),
     // Private, do not use. For internal needs only. Use TreeRootMessage::Selected.
     Select(SelectionState),
 }
@@ -60,6 +94,9 @@ impl TreeMessage {
     define_constructor!(TreeMessage:RemoveItem => fn remove_item(Handle<UiNode>), layout: false);
     define_constructor!(TreeMessage:SetExpanderShown => fn set_expander_shown(bool), layout: false);
     define_constructor!(TreeMessage:SetItems => fn set_items(Vec<Handle<UiNode >>), layout: false);
+    define_constructor!(TreeMessage:SetLazy => fn set_lazy(bool), layout: false);
+    define_constructor!(TreeMessage:PopulateChildren => fn populate_children(), layout: false);
+    define_constructor!(TreeMessage:Virtualize => fn virtualize(bool), layout: false);
     define_constructor!(TreeMessage:Select => fn select(SelectionState), layout: false);
 }
 
@@ -71,6 +108,9 @@ pub enum TreeRootMessage {
     Selected(Vec<Handle<UiNode>>),
     ExpandAll,
     CollapseAll,
+    /// Hides every item that does not match given filter string (case-insensitive) and does not
+    /// have matching descendants. Empty string resets the filter.
+    Filter(String),
 }
 
 impl TreeRootMessage {
@@ -80,6 +120,7 @@ impl TreeRootMessage {
     define_constructor!(TreeRootMessage:Selected => fn select(Vec<Handle<UiNode >>), layout: false);
     define_constructor!(TreeRootMessage:ExpandAll => fn expand_all(), layout: false);
     define_constructor!(TreeRootMessage:CollapseAll => fn collapse_all(), layout: false);
+    define_constructor!(TreeRootMessage:Filter => fn filter(String), layout: false);
 }
 
 #[derive(Debug, Clone)]
@@ -93,6 +134,12 @@ pub struct Tree {
     pub items: Vec<Handle<UiNode>>,
     pub is_selected: bool,
     pub always_show_expander: bool,
+    pub highlight: Handle<UiNode>,
+    pub search_text: Option<String>,
+    /// `true` if the tree is waiting for its items to be populated on first expansion.
+    pub is_lazy: bool,
+    /// `true` if content of the tree item is hidden, because the item is out of the visible area.
+    pub is_virtualized: bool,
 }
 
 crate::define_widget_deref!(Tree);
@@ -111,12 +158,14 @@ impl Control for Tree {
         node_map.resolve(&mut self.expander);
         node_map.resolve(&mut self.panel);
         node_map.resolve(&mut self.background);
+        node_map.resolve(&mut self.highlight);
     }
 
     fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
         let size = self.widget.arrange_override(ui, final_size);
 
-        let expander_visibility = !self.items.is_empty() || self.always_show_expander;
+        let expander_visibility =
+            !self.items.is_empty() || self.always_show_expander || self.is_lazy;
         ui.send_message(WidgetMessage::visibility(
             self.expander,
             MessageDirection::ToWidget,
@@ -200,6 +249,14 @@ impl Control for Tree {
                     } => {
                         self.is_expanded = expand;
 
+                        if expand && self.is_lazy {
+                            self.is_lazy = false;
+                            ui.send_message(TreeMessage::populate_children(
+                                self.handle(),
+                                MessageDirection::FromWidget,
+                            ));
+                        }
+
                         ui.send_message(WidgetMessage::visibility(
                             self.panel,
                             MessageDirection::ToWidget,
@@ -245,6 +302,25 @@ impl Control for Tree {
                             }
                         }
                     }
+                    &TreeMessage::SetLazy(lazy) => {
+                        if self.is_lazy != lazy {
+                            self.is_lazy = lazy;
+                            self.invalidate_arrange();
+                        }
+                    }
+                    TreeMessage::PopulateChildren => {
+                        // Notification for the tree owner.
+                    }
+                    &TreeMessage::Virtualize(virtualized) => {
+                        if self.is_virtualized != virtualized {
+                            self.is_virtualized = virtualized;
+                            ui.send_message(WidgetMessage::visibility(
+                                self.background,
+                                MessageDirection::ToWidget,
+                                !virtualized,
+                            ));
+                        }
+                    }
                     &TreeMessage::SetExpanderShown(show) => {
                         self.always_show_expander = show;
                         self.invalidate_arrange();
@@ -309,6 +385,15 @@ impl Tree {
             ctx.link(item, panel);
         }
     }
+
+    /// Returns text that is used to match the tree against filter string. It is either explicitly
+    /// specified search text, or text of every [`crate::text::Text`] widget in the content.
+    pub fn search_text(&self, ui: &UserInterface) -> String {
+        match self.search_text {
+            Some(ref search_text) => search_text.clone(),
+            None => collect_text(ui, self.content),
+        }
+    }
 }
 
 pub struct TreeBuilder {
@@ -318,6 +403,8 @@ pub struct TreeBuilder {
     is_expanded: bool,
     always_show_expander: bool,
     back: Option<Handle<UiNode>>,
+    search_text: Option<String>,
+    is_lazy: bool,
 }
 
 impl TreeBuilder {
@@ -329,6 +416,8 @@ impl TreeBuilder {
             is_expanded: true,
             always_show_expander: false,
             back: None,
+            search_text: None,
+            is_lazy: false,
         }
     }
 
@@ -357,9 +446,23 @@ impl TreeBuilder {
         self
     }
 
+    /// Sets text that will be used to match the tree against filter string. See
+    /// [`TreeRootMessage::Filter`].
+    pub fn with_search_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.search_text = Some(text.as_ref().to_owned());
+        self
+    }
+
+    /// Defers creation of items of the tree until it is expanded first time, see module docs for
+    /// more info.
+    pub fn with_lazy_population(mut self, lazy: bool) -> Self {
+        self.is_lazy = lazy;
+        self
+    }
+
     pub fn build_tree(self, ctx: &mut BuildContext) -> Tree {
         let expander = build_expander(
-            self.always_show_expander,
+            self.always_show_expander || self.is_lazy,
             !self.items.is_empty(),
             self.is_expanded,
             ctx,
@@ -401,6 +504,16 @@ impl TreeBuilder {
             .build(ctx)
         });
 
+        let highlight = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_foreground(Brush::Solid(Color::opaque(200, 160, 40)))
+                .with_background(Brush::Solid(Color::TRANSPARENT)),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        ctx.link(highlight, item_background);
         ctx.link(internals, item_background);
 
         let panel;
@@ -421,7 +534,7 @@ impl TreeBuilder {
                 }),
         )
         .add_column(Column::auto())
-        .add_row(Row::strict(24.0))
+        .add_row(Row::strict(TREE_ITEM_HEIGHT))
         .add_row(Row::stretch())
         .build(ctx);
 
@@ -440,6 +553,10 @@ impl TreeBuilder {
             items: self.items,
             is_selected: false,
             always_show_expander: self.always_show_expander,
+            highlight,
+            search_text: self.search_text,
+            is_lazy: self.is_lazy,
+            is_virtualized: false,
         }
     }
 
@@ -581,6 +698,12 @@ impl Control for TreeRoot {
                     TreeRootMessage::ExpandAll => {
                         self.expand_all(ui, true);
                     }
+                    TreeRootMessage::Filter(filter) => {
+                        let filter = filter.to_lowercase();
+                        for &item in self.items.iter() {
+                            apply_filter_recursive(item, &filter, ui);
+                        }
+                    }
                 }
             }
         }
@@ -592,6 +715,14 @@ impl TreeRoot {
         &self.items
     }
 
+    /// Hides content of every expanded item which header is out of the given visible area (in screen
+    /// coordinates) and shows content of the rest. See module docs for more info.
+    pub fn virtualize_items(&self, ui: &UserInterface, viewport: Rect<f32>) {
+        for &item in self.items.iter() {
+            virtualize_recursive(item, Some(viewport), ui);
+        }
+    }
+
     fn expand_all(&self, ui: &UserInterface, expand: bool) {
         for &item in self.items.iter() {
             ui.send_message(TreeMessage::expand(
@@ -604,6 +735,71 @@ impl TreeRoot {
     }
 }
 
+// `None` viewport means that the tree is out of the visible area with all its descendants.
+fn virtualize_recursive(tree: Handle<UiNode>, viewport: Option<Rect<f32>>, ui: &UserInterface) {
+    let tree_ref = match ui
+        .try_get_node(tree)
+        .and_then(|n| n.query_component::<Tree>())
+    {
+        Some(tree_ref) if tree_ref.visibility() => tree_ref,
+        // Hidden (filtered out) items do not take any space.
+        _ => return,
+    };
+
+    let bounds = tree_ref.screen_bounds();
+    let viewport = viewport.filter(|viewport| viewport.intersects(bounds));
+    let header = Rect::new(
+        bounds.x(),
+        bounds.y(),
+        bounds.w(),
+        TREE_ITEM_HEIGHT.min(bounds.h()),
+    );
+    let virtualized = !viewport.map_or(false, |viewport| viewport.intersects(header));
+    if tree_ref.is_virtualized != virtualized {
+        ui.send_message(TreeMessage::virtualize(
+            tree,
+            MessageDirection::ToWidget,
+            virtualized,
+        ));
+    }
+
+    if tree_ref.is_expanded {
+        for &item in tree_ref.items.iter() {
+            virtualize_recursive(item, viewport, ui);
+        }
+    }
+}
+
+fn apply_filter_recursive(tree: Handle<UiNode>, filter: &str, ui: &UserInterface) -> bool {
+    let tree_ref = match ui
+        .try_get_node(tree)
+        .and_then(|n| n.query_component::<Tree>())
+    {
+        Some(tree_ref) => tree_ref,
+        None => return false,
+    };
+
+    let is_match = filter.is_empty() || tree_ref.search_text(ui).to_lowercase().contains(filter);
+
+    let mut is_any_match = is_match;
+    for &item in tree_ref.items.iter() {
+        is_any_match |= apply_filter_recursive(item, filter, ui);
+    }
+
+    ui.send_message(WidgetMessage::visibility(
+        tree,
+        MessageDirection::ToWidget,
+        is_any_match,
+    ));
+    ui.send_message(WidgetMessage::visibility(
+        tree_ref.highlight,
+        MessageDirection::ToWidget,
+        is_match && !filter.is_empty(),
+    ));
+
+    is_any_match
+}
+
 pub struct TreeRootBuilder {
     widget_builder: WidgetBuilder,
     items: Vec<Handle<UiNode>>,
@@ -637,3 +833,44 @@ impl TreeRootBuilder {
         ctx.add_node(UiNode::new(tree))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        tree::{Tree, TreeBuilder, TreeRoot, TreeRootBuilder, TREE_ITEM_HEIGHT},
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn test_virtualization() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+
+        let items = (0..100)
+            .map(|_| TreeBuilder::new(WidgetBuilder::new()).build(&mut ui.build_ctx()))
+            .collect::<Vec<_>>();
+        let root = TreeRootBuilder::new(WidgetBuilder::new())
+            .with_items(items.clone())
+            .build(&mut ui.build_ctx());
+        ui.update(screen_size, 0.0);
+        let height = ui.node(root).actual_local_size().y;
+        assert_eq!(height, 100.0 * TREE_ITEM_HEIGHT);
+
+        let viewport = Rect::new(0.0, 0.0, screen_size.x, 10.0 * TREE_ITEM_HEIGHT);
+        ui.node(root)
+            .cast::<TreeRoot>()
+            .unwrap()
+            .virtualize_items(&ui, viewport);
+        while ui.poll_message().is_some() {}
+        ui.update(screen_size, 0.0);
+
+        let is_virtualized = |item| ui.node(item).cast::<Tree>().unwrap().is_virtualized;
+        assert_eq!(items.iter().filter(|i| is_virtualized(**i)).count(), 90);
+        assert!(!is_virtualized(items[9]));
+        assert!(is_virtualized(items[10]));
+        // Virtualized items must keep their height.
+        assert_eq!(ui.node(root).actual_local_size().y, height);
+    }
+}
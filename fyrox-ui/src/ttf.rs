@@ -1,6 +1,14 @@
+//! TrueType fonts and glyph atlases.
+//!
+//! A font can have a prioritized list of fallback fonts (see [`Font::set_fallbacks`]), which
+//! are consulted per character when the font itself does not have a glyph for it. This allows
+//! to render mixed-language text with a single font and to show emoji using a font with color
+//! glyphs (see [`Font::from_color_glyphs`]) instead of "tofu" boxes.
+
 use crate::{
     core::{algebra::Vector2, io, parking_lot::Mutex, rectpack::RectPacker},
     draw::SharedTexture,
+    formatted_text::Character,
 };
 use fxhash::FxHashMap;
 use std::{
@@ -22,6 +30,17 @@ pub struct FontGlyph {
     pub pixels: Vec<u8>,
 }
 
+/// An image of a color glyph, see [`Font::from_color_glyphs`].
+#[derive(Debug, Clone)]
+pub struct ColorGlyphImage {
+    /// Character code of the glyph.
+    pub unicode: u32,
+    pub width: usize,
+    pub height: usize,
+    /// Pixels of the glyph in RGBA8 format, row by row starting from the top.
+    pub pixels: Vec<u8>,
+}
+
 pub struct Font {
     height: f32,
     glyphs: Vec<FontGlyph>,
@@ -30,6 +49,8 @@ pub struct Font {
     char_map: FxHashMap<u32, usize>,
     atlas: Vec<u8>,
     atlas_size: usize,
+    is_color: bool,
+    fallbacks: Vec<SharedFont>,
     pub texture: Option<SharedTexture>,
 }
 
@@ -159,6 +180,8 @@ impl Font {
             char_map: FxHashMap::default(),
             atlas: Vec::new(),
             atlas_size: 0,
+            is_color: false,
+            fallbacks: Vec::new(),
             texture: None,
         };
 
@@ -189,6 +212,47 @@ impl Font {
         Ok(font)
     }
 
+    /// Creates a font with color glyphs from a set of images, for example from an emoji sprite
+    /// sheet. Such fonts are meant to be used as fallbacks, so they provide glyphs missing in
+    /// regular fonts. TrueType color glyph tables are not supported by the rasterizer, so color
+    /// glyphs have to be provided as images. Glyphs are placed on the baseline and their
+    /// advance is equal to their width.
+    pub fn from_color_glyphs<I>(height: f32, glyphs: I) -> Self
+    where
+        I: IntoIterator<Item = ColorGlyphImage>,
+    {
+        let mut font = Font {
+            height,
+            glyphs: Vec::new(),
+            ascender: height,
+            descender: 0.0,
+            char_map: FxHashMap::default(),
+            atlas: Vec::new(),
+            atlas_size: 0,
+            is_color: true,
+            fallbacks: Vec::new(),
+            texture: None,
+        };
+
+        for image in glyphs {
+            debug_assert_eq!(image.pixels.len(), image.width * image.height * 4);
+            font.char_map.insert(image.unicode, font.glyphs.len());
+            font.glyphs.push(FontGlyph {
+                top: 0.0,
+                left: 0.0,
+                advance: image.width as f32,
+                tex_coords: Default::default(),
+                bitmap_width: image.width,
+                bitmap_height: image.height,
+                pixels: image.pixels,
+            });
+        }
+
+        font.pack();
+
+        font
+    }
+
     pub async fn from_file<P: AsRef<Path>>(
         path: P,
         height: f32,
@@ -214,6 +278,79 @@ impl Font {
         self.char_map.get(&unicode).cloned()
     }
 
+    /// Returns index of a font in the fallback chain that has a glyph for the given character
+    /// together with index of the glyph. Index `0` is this font, `1..` are the fallbacks.
+    pub fn find_glyph(&self, unicode: u32) -> Option<(usize, usize)> {
+        if let Some(index) = self.glyph_index(unicode) {
+            return Some((0, index));
+        }
+        self.fallbacks.iter().enumerate().find_map(|(i, fallback)| {
+            // A font cannot be locked here if it is in its own fallback chain.
+            let fallback = fallback.0.try_lock()?;
+            fallback.glyph_index(unicode).map(|index| (i + 1, index))
+        })
+    }
+
+    /// Calls `func` with a glyph of the character, the glyph is taken from a fallback font if
+    /// the character was resolved to one. Returns `None` if there is no such glyph.
+    pub fn map_character_glyph<F, R>(&self, character: &Character, func: F) -> Option<R>
+    where
+        F: FnOnce(&FontGlyph) -> R,
+    {
+        let glyph_index = character.glyph_index as usize;
+        match character.font_index as usize {
+            0 => self.glyphs.get(glyph_index).map(func),
+            i => {
+                let fallback = self.fallbacks.get(i - 1)?.0.try_lock()?;
+                fallback.glyphs.get(glyph_index).map(func)
+            }
+        }
+    }
+
+    /// Returns advance of a character, the character is expected to be resolved using this font
+    /// (see [`Character::from_char_with_font`]).
+    #[inline]
+    pub fn character_advance(&self, character: &Character) -> f32 {
+        self.map_character_glyph(character, |glyph| glyph.advance)
+            .unwrap_or(self.height)
+    }
+
+    /// Returns a font of the fallback chain by its index, `0` is this font itself, so `None`
+    /// is returned for it.
+    #[inline]
+    pub fn fallback(&self, font_index: usize) -> Option<&SharedFont> {
+        font_index
+            .checked_sub(1)
+            .and_then(|index| self.fallbacks.get(index))
+    }
+
+    /// Returns prioritized list of fonts that are used when the font does not have a glyph for
+    /// a character.
+    #[inline]
+    pub fn fallbacks(&self) -> &[SharedFont] {
+        &self.fallbacks
+    }
+
+    /// Sets prioritized list of fallback fonts. Fallbacks of the fallback fonts are not used.
+    /// Keep in mind that characters of existing texts are resolved when the text is set, so
+    /// the text has to be set again to take new fallbacks into account.
+    #[inline]
+    pub fn set_fallbacks(&mut self, fallbacks: Vec<SharedFont>) {
+        self.fallbacks = fallbacks;
+    }
+
+    /// Adds a font to the end of the fallback list.
+    #[inline]
+    pub fn add_fallback(&mut self, fallback: SharedFont) {
+        self.fallbacks.push(fallback);
+    }
+
+    /// Returns `true` if the font has color glyphs, atlas of such font is in RGBA8 format.
+    #[inline]
+    pub fn is_color(&self) -> bool {
+        self.is_color
+    }
+
     #[inline]
     pub fn glyphs(&self) -> &[FontGlyph] {
         &self.glyphs
@@ -261,7 +398,8 @@ impl Font {
     fn pack(&mut self) {
         let border = 2;
         self.atlas_size = self.compute_atlas_size(border);
-        self.atlas = vec![0; (self.atlas_size * self.atlas_size) as usize];
+        let bytes_per_pixel = if self.is_color { 4 } else { 1 };
+        self.atlas = vec![0; self.atlas_size * self.atlas_size * bytes_per_pixel];
         let k = 1.0 / self.atlas_size as f32;
         let mut rect_packer = RectPacker::new(self.atlas_size, self.atlas_size);
         for glyph in self.glyphs.iter_mut() {
@@ -289,8 +427,10 @@ impl Font {
                 // Copy glyph pixels to atlas pixels
                 for (src_row, row) in (by..row_end).enumerate() {
                     for (src_col, col) in (bx..col_end).enumerate() {
-                        self.atlas[row * self.atlas_size + col] =
-                            glyph.pixels[src_row * bw + src_col];
+                        let dest = (row * self.atlas_size + col) * bytes_per_pixel;
+                        let src = (src_row * bw + src_col) * bytes_per_pixel;
+                        self.atlas[dest..dest + bytes_per_pixel]
+                            .copy_from_slice(&glyph.pixels[src..src + bytes_per_pixel]);
                     }
                 }
             } else {
@@ -304,6 +444,7 @@ impl Font {
 pub struct FontBuilder<'a> {
     height: Option<f32>,
     char_set: Option<Cow<'a, [Range<u32>]>>,
+    fallbacks: Vec<SharedFont>,
 }
 impl<'a> FontBuilder<'a> {
     const DEFAULT_HEIGHT: f32 = 16.0;
@@ -313,6 +454,7 @@ impl<'a> FontBuilder<'a> {
         Self {
             height: None,
             char_set: None,
+            fallbacks: Vec::new(),
         }
     }
 
@@ -330,14 +472,26 @@ impl<'a> FontBuilder<'a> {
         self
     }
 
+    /// Adds a font to the end of the fallback list of the produced font, see
+    /// [`Font::set_fallbacks`].
+    #[inline]
+    pub fn with_fallback(mut self, fallback: SharedFont) -> Self {
+        self.fallbacks.push(fallback);
+        self
+    }
+
     /// Creates a new font from the data at the specified path.
     pub async fn build_from_file(self, path: impl AsRef<Path>) -> Result<Font, &'static str> {
-        Font::from_file(path, self.height(), self.char_set()).await
+        let mut font = Font::from_file(path, self.height(), self.char_set()).await?;
+        font.set_fallbacks(self.fallbacks);
+        Ok(font)
     }
 
     /// Creates a new font from bytes in memory.
     pub fn build_from_memory(self, data: impl Deref<Target = [u8]>) -> Result<Font, &'static str> {
-        Font::from_memory(data, self.height(), self.char_set())
+        let mut font = Font::from_memory(data, self.height(), self.char_set())?;
+        font.set_fallbacks(self.fallbacks);
+        Ok(font)
     }
 
     /// Creates a new font using the built-in font face.
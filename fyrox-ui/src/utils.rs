@@ -2,10 +2,11 @@ use crate::{
     border::BorderBuilder,
     core::{algebra::Vector2, pool::Handle},
     formatted_text::WrapMode,
-    text::TextBuilder,
+    text::{Text, TextBuilder},
     vector_image::{Primitive, VectorImageBuilder},
     widget::WidgetBuilder,
-    Brush, BuildContext, HorizontalAlignment, Thickness, UiNode, VerticalAlignment, BRUSH_BRIGHT,
+    Brush, BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    BRUSH_BRIGHT,
 };
 use fyrox_core::color::Color;
 use std::rc::Rc;
@@ -104,3 +105,22 @@ pub fn make_simple_tooltip(ctx: &mut BuildContext, text: &str) -> Rc<Handle<UiNo
         .build(ctx),
     )
 }
+
+/// Collects text of every [`Text`] widget in the given sub-tree (including the root) and joins
+/// them with spaces. Useful to get a searchable representation of an arbitrary item content.
+pub fn collect_text(ui: &UserInterface, root: Handle<UiNode>) -> String {
+    let mut result = String::new();
+    let mut stack = vec![root];
+    while let Some(handle) = stack.pop() {
+        if let Some(node) = ui.try_get_node(handle) {
+            if let Some(text) = node.cast::<Text>() {
+                if !result.is_empty() {
+                    result.push(' ');
+                }
+                result += &text.text();
+            }
+            stack.extend(node.children().iter().rev());
+        }
+    }
+    result
+}
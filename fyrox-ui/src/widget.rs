@@ -2,9 +2,10 @@ use crate::{
     brush::Brush,
     core::{algebra::Vector2, math::Rect, pool::Handle},
     define_constructor,
-    message::{CursorIcon, KeyCode, MessageDirection, UiMessage},
-    HorizontalAlignment, LayoutEvent, MouseButton, MouseState, Thickness, UiNode, UserInterface,
-    VerticalAlignment, BRUSH_FOREGROUND, BRUSH_PRIMARY,
+    draw::{ClipShape, GeometryCache},
+    message::{CursorIcon, ImeEvent, KeyCode, MessageDirection, UiMessage},
+    DragPayload, DropFilter, HorizontalAlignment, LayoutEvent, MouseButton, MouseState, Thickness,
+    UiNode, UserInterface, VerticalAlignment, BRUSH_FOREGROUND, BRUSH_PRIMARY,
 };
 use fyrox_core::algebra::{Matrix3, Point2};
 use std::{
@@ -73,6 +74,11 @@ pub enum WidgetMessage {
     /// Direction: **From/To UI**.
     Text(char),
 
+    /// Initiated when widget is in focus and user composes text using input method editor (IME).
+    ///
+    /// Direction: **From UI**.
+    Ime(ImeEvent),
+
     /// Initiated when widget is in focus and user presses a button on a keyboard.
     ///
     /// Direction: **From UI**.
@@ -105,11 +111,26 @@ pub enum WidgetMessage {
     /// Direction: **From UI**.
     DragOver(Handle<UiNode>),
 
-    /// Initiated when user drops a widget onto some other widget.
+    /// Initiated when user drops a widget onto some other widget. It is sent only if the target
+    /// accepted the drop (see [`crate::DropFilter`]). Payload of the drag operation is available
+    /// via [`UserInterface::drag_payload`].
     ///
     /// Direction: **From UI**.
     Drop(Handle<UiNode>),
 
+    /// Initiated when a drag operation was finished and a drop target (its handle is stored in the
+    /// message) accepted the dragged widget. Sent to the dragged widget.
+    ///
+    /// Direction: **From UI**.
+    DropAccepted(Handle<UiNode>),
+
+    /// Initiated when a drag operation was finished, but there was no drop target or the target
+    /// rejected the dragged widget (its handle is stored in the message, could be `NONE`). Sent to
+    /// the dragged widget.
+    ///
+    /// Direction: **From UI**.
+    DropRejected(Handle<UiNode>),
+
     /// A request to make widget topmost. Widget can be made topmost only in the same hierarchy
     /// level only!
     ///
@@ -280,6 +301,11 @@ pub enum WidgetMessage {
 
     /// A request to set new context menu for a widget. Old context menu will be removed.
     ContextMenu(Handle<UiNode>),
+
+    /// A request to set new clipping shape for a widget.
+    ///
+    /// Direction: **From/To UI**
+    ClipShape(Option<ClipShape>),
 }
 
 impl WidgetMessage {
@@ -312,6 +338,7 @@ impl WidgetMessage {
     define_constructor!(WidgetMessage:LayoutTransform => fn layout_transform(Matrix3<f32>), layout: false);
     define_constructor!(WidgetMessage:RenderTransform => fn render_transform(Matrix3<f32>), layout: false);
     define_constructor!(WidgetMessage:ContextMenu => fn context_menu(Handle<UiNode>), layout: false);
+    define_constructor!(WidgetMessage:ClipShape => fn clip_shape(Option<ClipShape>), layout: false);
     define_constructor!(WidgetMessage:Focus => fn focus(), layout: false);
     define_constructor!(WidgetMessage:Unfocus => fn unfocus(), layout: false);
 
@@ -323,11 +350,14 @@ impl WidgetMessage {
     define_constructor!(WidgetMessage:MouseLeave => fn mouse_leave(), layout: false);
     define_constructor!(WidgetMessage:MouseEnter => fn mouse_enter(), layout: false);
     define_constructor!(WidgetMessage:Text => fn text(char), layout: false);
+    define_constructor!(WidgetMessage:Ime => fn ime(ImeEvent), layout: false);
     define_constructor!(WidgetMessage:KeyDown => fn key_down(KeyCode), layout: false);
     define_constructor!(WidgetMessage:KeyUp => fn key_up(KeyCode), layout: false);
     define_constructor!(WidgetMessage:DragStarted => fn drag_started(Handle<UiNode>), layout: false);
     define_constructor!(WidgetMessage:DragOver => fn drag_over(Handle<UiNode>), layout: false);
     define_constructor!(WidgetMessage:Drop => fn drop(Handle<UiNode>), layout: false);
+    define_constructor!(WidgetMessage:DropAccepted => fn drop_accepted(Handle<UiNode>), layout: false);
+    define_constructor!(WidgetMessage:DropRejected => fn drop_rejected(Handle<UiNode>), layout: false);
     define_constructor!(WidgetMessage:DoubleClick => fn double_click(button: MouseButton), layout: false);
 }
 
@@ -364,11 +394,15 @@ pub struct Widget {
     pub parent: Handle<UiNode>,
     /// Indices of commands in command buffer emitted by the node.
     pub command_indices: RefCell<Vec<usize>>,
+    /// Geometry emitted by the node on last draw, it is reused until the node is invalidated.
+    pub geometry_cache: RefCell<Option<GeometryCache>>,
     pub is_mouse_directly_over: bool,
     pub hit_test_visibility: bool,
     pub z_index: usize,
     pub allow_drag: bool,
     pub allow_drop: bool,
+    pub drag_payload: Option<DragPayload>,
+    pub drop_filter: Option<DropFilter>,
     pub user_data: Option<Rc<dyn Any>>,
     pub draw_on_top: bool,
     pub enabled: bool,
@@ -378,6 +412,9 @@ pub struct Widget {
     pub tooltip_time: f32,
     pub context_menu: Handle<UiNode>,
     pub clip_to_bounds: bool,
+    /// Optional shape that clips the widget and its children in addition to bounds, it is also
+    /// respected by hit testing.
+    pub clip_shape: Option<ClipShape>,
     pub layout_transform: Matrix3<f32>,
     pub render_transform: Matrix3<f32>,
     pub visual_transform: Matrix3<f32>,
@@ -484,6 +521,26 @@ impl Widget {
         self.allow_drop
     }
 
+    #[inline]
+    pub fn set_drag_payload(&mut self, payload: Option<DragPayload>) {
+        self.drag_payload = payload;
+    }
+
+    #[inline]
+    pub fn drag_payload(&self) -> Option<&DragPayload> {
+        self.drag_payload.as_ref()
+    }
+
+    #[inline]
+    pub fn set_drop_filter(&mut self, filter: Option<DropFilter>) {
+        self.drop_filter = filter;
+    }
+
+    #[inline]
+    pub fn drop_filter(&self) -> Option<&DropFilter> {
+        self.drop_filter.as_ref()
+    }
+
     #[inline]
     pub fn screen_to_local(&self, point: Vector2<f32>) -> Vector2<f32> {
         self.visual_transform
@@ -517,6 +574,14 @@ impl Widget {
         }
     }
 
+    /// Drops cached geometry of the widget, so it will be drawn again on next frame. Widgets that
+    /// change their visual in [`crate::Control::update`] or by any other means than messages
+    /// must call this method, otherwise the changes won't be visible.
+    #[inline]
+    pub fn invalidate_visual(&self) {
+        self.geometry_cache.borrow_mut().take();
+    }
+
     #[inline]
     pub fn is_hit_test_visible(&self) -> bool {
         self.hit_test_visibility
@@ -623,6 +688,12 @@ impl Widget {
         }
     }
 
+    #[inline]
+    pub(crate) fn insert_child(&mut self, index: usize, child: Handle<UiNode>) {
+        self.invalidate_layout();
+        self.children.insert(index, child)
+    }
+
     #[inline(always)]
     pub fn children(&self) -> &[Handle<UiNode>] {
         &self.children
@@ -751,6 +822,7 @@ impl Widget {
             if let Some(msg) = msg.data::<WidgetMessage>() {
                 match msg {
                     &WidgetMessage::Opacity(opacity) => self.opacity = opacity,
+                    WidgetMessage::ClipShape(clip_shape) => self.clip_shape = clip_shape.clone(),
                     WidgetMessage::Background(background) => self.background = background.clone(),
                     WidgetMessage::Foreground(foreground) => self.foreground = foreground.clone(),
                     WidgetMessage::Name(name) => self.name = name.clone(),
@@ -833,7 +905,11 @@ impl Widget {
                         }
                     }
                     WidgetMessage::RenderTransform(transform) => {
-                        self.render_transform = *transform;
+                        if &self.render_transform != transform {
+                            self.render_transform = *transform;
+                            // Visual transform is updated for arranged widgets only.
+                            self.invalidate_arrange();
+                        }
                     }
                     _ => (),
                 }
@@ -1101,6 +1177,8 @@ pub struct WidgetBuilder {
     pub z_index: usize,
     pub allow_drag: bool,
     pub allow_drop: bool,
+    pub drag_payload: Option<DragPayload>,
+    pub drop_filter: Option<DropFilter>,
     pub user_data: Option<Rc<dyn Any>>,
     pub draw_on_top: bool,
     pub enabled: bool,
@@ -1114,6 +1192,7 @@ pub struct WidgetBuilder {
     pub layout_transform: Matrix3<f32>,
     pub render_transform: Matrix3<f32>,
     pub clip_to_bounds: bool,
+    pub clip_shape: Option<ClipShape>,
 }
 
 impl Default for WidgetBuilder {
@@ -1144,6 +1223,8 @@ impl WidgetBuilder {
             z_index: 0,
             allow_drag: false,
             allow_drop: false,
+            drag_payload: None,
+            drop_filter: None,
             user_data: None,
             draw_on_top: false,
             enabled: true,
@@ -1157,6 +1238,7 @@ impl WidgetBuilder {
             layout_transform: Matrix3::identity(),
             render_transform: Matrix3::identity(),
             clip_to_bounds: true,
+            clip_shape: None,
         }
     }
 
@@ -1185,6 +1267,11 @@ impl WidgetBuilder {
         self
     }
 
+    pub fn with_clip_shape(mut self, clip_shape: ClipShape) -> Self {
+        self.clip_shape = Some(clip_shape);
+        self
+    }
+
     pub fn with_enabled(mut self, enabled: bool) -> Self {
         self.enabled = enabled;
         self
@@ -1301,6 +1388,18 @@ impl WidgetBuilder {
         self
     }
 
+    /// Sets payload that will be attached to a drag operation started on the widget.
+    pub fn with_drag_payload(mut self, payload: DragPayload) -> Self {
+        self.drag_payload = Some(payload);
+        self
+    }
+
+    /// Sets a filter that decides whether the widget accepts dropped payloads.
+    pub fn with_drop_filter(mut self, filter: DropFilter) -> Self {
+        self.drop_filter = Some(filter);
+        self
+    }
+
     pub fn with_user_data(mut self, user_data: Rc<dyn Any>) -> Self {
         self.user_data = Some(user_data);
         self
@@ -1369,15 +1468,19 @@ impl WidgetBuilder {
             children: self.children,
             parent: Handle::NONE,
             command_indices: Default::default(),
+            geometry_cache: Default::default(),
             is_mouse_directly_over: false,
             measure_valid: Cell::new(false),
             arrange_valid: Cell::new(false),
             hit_test_visibility: self.is_hit_test_visible,
-            prev_measure: Default::default(),
-            prev_arrange: Default::default(),
+            // NaN means that the widget was never measured or arranged.
+            prev_measure: Cell::new(Vector2::repeat(f32::NAN)),
+            prev_arrange: Cell::new(Rect::new(f32::NAN, f32::NAN, f32::NAN, f32::NAN)),
             z_index: self.z_index,
             allow_drag: self.allow_drag,
             allow_drop: self.allow_drop,
+            drag_payload: self.drag_payload,
+            drop_filter: self.drop_filter,
             user_data: self.user_data.clone(),
             draw_on_top: self.draw_on_top,
             enabled: self.enabled,
@@ -1394,6 +1497,7 @@ impl WidgetBuilder {
             render_transform: self.render_transform,
             visual_transform: Matrix3::identity(),
             clip_to_bounds: self.clip_to_bounds,
+            clip_shape: self.clip_shape,
         }
     }
 }
@@ -1,12 +1,12 @@
 use crate::{
     border::BorderBuilder,
-    brush::{Brush, GradientPoint},
+    brush::{Brush, BrushCoordinates, GradientPoint},
     button::{ButtonBuilder, ButtonMessage},
-    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle},
+    core::{algebra::Vector2, color::Color, math::Rect, pool::Handle, visitor::prelude::*},
     decorator::DecoratorBuilder,
     define_constructor,
     grid::{Column, GridBuilder, Row},
-    message::{CursorIcon, MessageDirection, UiMessage},
+    message::{CursorIcon, MessageDirection, MouseButton, UiMessage},
     text::{Text, TextBuilder, TextMessage},
     vector_image::{Primitive, VectorImageBuilder},
     widget::{Widget, WidgetBuilder, WidgetMessage},
@@ -40,6 +40,14 @@ pub enum WindowMessage {
     /// Whether or not window can be minimized by _ mark. false hides _ mark.
     CanMinimize(bool),
 
+    /// Maximizes a window to the size of the UI root (when `true`) or restores its previous position
+    /// and size (when `false`). Maximized window can not be moved or resized.
+    Maximize(bool),
+
+    /// Whether or not window can be maximized by a button in the header (or by double click on the
+    /// header). false hides the button.
+    CanMaximize(bool),
+
     /// Whether or not window can be closed by X mark. false hides X mark.
     CanClose(bool),
 
@@ -62,6 +70,15 @@ pub enum WindowMessage {
     /// It is used to prevent moving window outside of main application window bounds, to still
     /// be able to drag it.  
     SafeBorderSize(Option<Vector2<f32>>),
+
+    /// Whether or not window should be snapped to an edge of the UI root when dragged to it: left
+    /// and right edges tile the window to the corresponding half of the root, top edge maximizes
+    /// the window (if it can be maximized).
+    SnapToEdges(bool),
+
+    /// Sets new position, size and state of a window (when sent to widget). Could be used to
+    /// restore window placement saved by [`Window::geometry`].
+    Geometry(WindowGeometry),
 }
 
 impl WindowMessage {
@@ -70,6 +87,8 @@ impl WindowMessage {
     define_constructor!(WindowMessage:Close => fn close(), layout: false);
     define_constructor!(WindowMessage:Minimize => fn minimize(bool), layout: false);
     define_constructor!(WindowMessage:CanMinimize => fn can_minimize(bool), layout: false);
+    define_constructor!(WindowMessage:Maximize => fn maximize(bool), layout: false);
+    define_constructor!(WindowMessage:CanMaximize => fn can_maximize(bool), layout: false);
     define_constructor!(WindowMessage:CanClose => fn can_close(bool), layout: false);
     define_constructor!(WindowMessage:CanResize => fn can_resize(bool), layout: false);
     define_constructor!(WindowMessage:MoveStart => fn move_start(), layout: false);
@@ -77,6 +96,19 @@ impl WindowMessage {
     define_constructor!(WindowMessage:MoveEnd => fn move_end(), layout: false);
     define_constructor!(WindowMessage:Title => fn title(WindowTitle), layout: false);
     define_constructor!(WindowMessage:SafeBorderSize => fn safe_border_size(Option<Vector2<f32>>), layout: false);
+    define_constructor!(WindowMessage:SnapToEdges => fn snap_to_edges(bool), layout: false);
+    define_constructor!(WindowMessage:Geometry => fn geometry(WindowGeometry), layout: false);
+}
+
+/// Serializable placement of a window. Position is in the local space of the UI root. For
+/// maximized window, position and size are the ones that will be restored when the window is
+/// un-maximized.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Visit)]
+pub struct WindowGeometry {
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub maximized: bool,
+    pub minimized: bool,
 }
 
 /// Represents a widget looking as window in Windows - with title, minimize and close buttons.
@@ -93,8 +125,14 @@ pub struct Window {
     pub can_minimize: bool,
     pub can_close: bool,
     pub can_resize: bool,
+    pub maximized: bool,
+    pub can_maximize: bool,
+    pub snap_to_edges: bool,
+    /// Position and size of the window before it was maximized or snapped to an edge.
+    pub restore_geometry: Option<(Vector2<f32>, Vector2<f32>)>,
     pub header: Handle<UiNode>,
     pub minimize_button: Handle<UiNode>,
+    pub maximize_button: Handle<UiNode>,
     pub close_button: Handle<UiNode>,
     pub drag_delta: Vector2<f32>,
     pub content: Handle<UiNode>,
@@ -106,6 +144,8 @@ pub struct Window {
 
 const GRIP_SIZE: f32 = 6.0;
 const CORNER_GRIP_SIZE: f32 = GRIP_SIZE * 2.0;
+/// Distance from an edge of the UI root at which a dragged window is snapped to the edge.
+const SNAP_DISTANCE: f32 = 8.0;
 
 #[derive(Copy, Clone, Debug)]
 pub enum GripKind {
@@ -152,6 +192,7 @@ impl Control for Window {
     fn resolve(&mut self, node_map: &NodeHandleMapping) {
         node_map.resolve(&mut self.header);
         node_map.resolve(&mut self.minimize_button);
+        node_map.resolve(&mut self.maximize_button);
         node_map.resolve(&mut self.close_button);
         node_map.resolve(&mut self.title);
         node_map.resolve(&mut self.title_grid);
@@ -210,8 +251,9 @@ impl Control for Window {
         self.widget.handle_routed_message(ui, message);
 
         if let Some(msg) = message.data::<WidgetMessage>() {
-            // Grip interaction have higher priority than other actions.
-            if self.can_resize {
+            // Grip interaction have higher priority than other actions. Maximized window can't be
+            // resized.
+            if self.can_resize && !self.maximized {
                 match msg {
                     &WidgetMessage::MouseDown { pos, .. } => {
                         ui.send_message(WidgetMessage::topmost(
@@ -265,33 +307,36 @@ impl Control for Window {
                                     GripKind::LeftBottomCorner => (-1.0, 0.0, 1.0, -1.0),
                                 };
 
-                                let new_pos = self.initial_position
-                                    + Vector2::new(delta.x * dx, delta.y * dy);
+                                // Grips must not overlap, so there's a lower limit for the size.
                                 let new_size =
                                     self.initial_size + Vector2::new(delta.x * dw, delta.y * dh);
-
-                                if new_size.x > self.min_width()
-                                    && new_size.x < self.max_width()
-                                    && new_size.y > self.min_height()
-                                    && new_size.y < self.max_height()
-                                {
-                                    ui.send_message(WidgetMessage::desired_position(
-                                        self.handle(),
-                                        MessageDirection::ToWidget,
-                                        ui.screen_to_root_canvas_space(new_pos),
-                                    ));
-                                    ui.send_message(WidgetMessage::width(
-                                        self.handle(),
-                                        MessageDirection::ToWidget,
-                                        new_size.x,
-                                    ));
-                                    ui.send_message(WidgetMessage::height(
-                                        self.handle(),
-                                        MessageDirection::ToWidget,
-                                        new_size.y,
-                                    ));
+                                let new_size = Vector2::new(
+                                    new_size
+                                        .x
+                                        .max(self.min_width().max(CORNER_GRIP_SIZE * 2.0))
+                                        .min(self.max_width()),
+                                    new_size
+                                        .y
+                                        .max(self.min_height().max(CORNER_GRIP_SIZE * 2.0))
+                                        .min(self.max_height()),
+                                );
+
+                                // Left and top grips move the window, opposite edge must stay in
+                                // place when the size is clamped.
+                                let mut new_pos = self.initial_position;
+                                if dx != 0.0 {
+                                    new_pos.x += self.initial_size.x - new_size.x;
+                                }
+                                if dy != 0.0 {
+                                    new_pos.y += self.initial_size.y - new_size.y;
                                 }
 
+                                self.set_geometry(
+                                    ui,
+                                    ui.screen_to_root_canvas_space(new_pos),
+                                    new_size,
+                                );
+
                                 break;
                             }
                         }
@@ -337,6 +382,18 @@ impl Control for Window {
                         }
                         message.set_handled(true);
                     }
+                    WidgetMessage::DoubleClick {
+                        button: MouseButton::Left,
+                    } => {
+                        if self.can_maximize {
+                            ui.send_message(WindowMessage::maximize(
+                                self.handle(),
+                                MessageDirection::ToWidget,
+                                !self.maximized,
+                            ));
+                            message.set_handled(true);
+                        }
+                    }
                     _ => (),
                 }
             }
@@ -352,6 +409,12 @@ impl Control for Window {
                     MessageDirection::ToWidget,
                     !self.minimized,
                 ));
+            } else if message.destination() == self.maximize_button {
+                ui.send_message(WindowMessage::maximize(
+                    self.handle(),
+                    MessageDirection::ToWidget,
+                    !self.maximized,
+                ));
             } else if message.destination() == self.close_button {
                 ui.send_message(WindowMessage::close(
                     self.handle(),
@@ -439,6 +502,28 @@ impl Control for Window {
                             }
                         }
                     }
+                    &WindowMessage::Maximize(maximized) => {
+                        if self.maximized != maximized {
+                            if maximized {
+                                self.maximize(ui);
+                            } else {
+                                self.restore(ui);
+                            }
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    &WindowMessage::CanMaximize(value) => {
+                        if self.can_maximize != value {
+                            self.can_maximize = value;
+                            if self.maximize_button.is_some() {
+                                ui.send_message(WidgetMessage::visibility(
+                                    self.maximize_button,
+                                    MessageDirection::ToWidget,
+                                    value,
+                                ));
+                            }
+                        }
+                    }
                     &WindowMessage::CanClose(value) => {
                         if self.can_close != value {
                             self.can_close = value;
@@ -482,12 +567,26 @@ impl Control for Window {
                         }
                     }
                     WindowMessage::MoveStart => {
-                        if !self.is_dragging {
+                        if !self.is_dragging && !self.maximized {
                             ui.capture_mouse(self.header);
                             let initial_position = self.screen_position();
                             self.initial_position = initial_position;
                             self.is_dragging = true;
 
+                            // Window that was snapped to an edge gets its previous size back.
+                            if let Some((_, size)) = self.restore_geometry.take() {
+                                ui.send_message(WidgetMessage::width(
+                                    self.handle(),
+                                    MessageDirection::ToWidget,
+                                    size.x,
+                                ));
+                                ui.send_message(WidgetMessage::height(
+                                    self.handle(),
+                                    MessageDirection::ToWidget,
+                                    size.y,
+                                ));
+                            }
+
                             ui.send_message(message.reverse());
                         }
                     }
@@ -496,6 +595,10 @@ impl Control for Window {
                             ui.release_mouse_capture();
                             self.is_dragging = false;
 
+                            if self.snap_to_edges {
+                                self.snap(ui);
+                            }
+
                             ui.send_message(message.reverse());
                         }
                     }
@@ -546,6 +649,26 @@ impl Control for Window {
                             ui.send_message(message.reverse());
                         }
                     }
+                    &WindowMessage::SnapToEdges(value) => {
+                        if self.snap_to_edges != value {
+                            self.snap_to_edges = value;
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    &WindowMessage::Geometry(geometry) => {
+                        self.maximized = false;
+                        self.restore_geometry = None;
+                        self.set_geometry(ui, geometry.position, geometry.size);
+                        if geometry.maximized {
+                            self.restore_geometry = Some((geometry.position, geometry.size));
+                            self.maximize(ui);
+                        }
+                        ui.send_message(WindowMessage::minimize(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                            geometry.minimized,
+                        ));
+                    }
                 }
             }
         }
@@ -581,6 +704,96 @@ impl Window {
     pub fn content(&self) -> Handle<UiNode> {
         self.content
     }
+
+    pub fn is_maximized(&self) -> bool {
+        self.maximized
+    }
+
+    /// Returns current placement of the window, it could be saved and then restored using
+    /// [`WindowMessage::Geometry`].
+    pub fn geometry(&self) -> WindowGeometry {
+        let (position, size) = match self.restore_geometry {
+            Some(restore_geometry) if self.maximized => restore_geometry,
+            _ => (self.desired_local_position(), self.actual_local_size()),
+        };
+        WindowGeometry {
+            position,
+            size,
+            maximized: self.maximized,
+            minimized: self.minimized,
+        }
+    }
+
+    fn set_geometry(&self, ui: &UserInterface, position: Vector2<f32>, size: Vector2<f32>) {
+        ui.send_message(WidgetMessage::desired_position(
+            self.handle(),
+            MessageDirection::ToWidget,
+            position,
+        ));
+        ui.send_message(WidgetMessage::width(
+            self.handle(),
+            MessageDirection::ToWidget,
+            size.x,
+        ));
+        ui.send_message(WidgetMessage::height(
+            self.handle(),
+            MessageDirection::ToWidget,
+            size.y,
+        ));
+    }
+
+    fn save_geometry(&mut self) {
+        if self.restore_geometry.is_none() {
+            self.restore_geometry = Some((self.desired_local_position(), self.actual_local_size()));
+        }
+    }
+
+    fn maximize(&mut self, ui: &UserInterface) {
+        self.save_geometry();
+        self.maximized = true;
+        self.set_geometry(
+            ui,
+            Vector2::default(),
+            ui.node(ui.root()).actual_local_size(),
+        );
+    }
+
+    fn restore(&mut self, ui: &UserInterface) {
+        self.maximized = false;
+        if let Some((position, size)) = self.restore_geometry.take() {
+            self.set_geometry(ui, position, size);
+        }
+    }
+
+    /// Snaps the window to an edge of the UI root if the cursor is close to it.
+    fn snap(&mut self, ui: &UserInterface) {
+        let root_size = ui.node(ui.root()).actual_local_size();
+        let cursor = ui.screen_to_root_canvas_space(ui.cursor_position());
+        let half_size = Vector2::new(root_size.x * 0.5, root_size.y);
+
+        if cursor.y <= SNAP_DISTANCE && self.can_maximize {
+            ui.send_message(WindowMessage::maximize(
+                self.handle(),
+                MessageDirection::ToWidget,
+                true,
+            ));
+        } else if cursor.x <= SNAP_DISTANCE && self.can_resize {
+            self.save_geometry_before_drag(ui);
+            self.set_geometry(ui, Vector2::default(), half_size);
+        } else if cursor.x >= root_size.x - SNAP_DISTANCE && self.can_resize {
+            self.save_geometry_before_drag(ui);
+            self.set_geometry(ui, Vector2::new(half_size.x, 0.0), half_size);
+        }
+    }
+
+    // Position of the window has already changed by dragging, so the position before the drag is
+    // used as restore position.
+    fn save_geometry_before_drag(&mut self, ui: &UserInterface) {
+        self.restore_geometry = Some((
+            ui.screen_to_root_canvas_space(self.initial_position),
+            self.actual_local_size(),
+        ));
+    }
 }
 
 pub struct WindowBuilder {
@@ -592,6 +805,9 @@ pub struct WindowBuilder {
     pub open: bool,
     pub close_button: Option<Handle<UiNode>>,
     pub minimize_button: Option<Handle<UiNode>>,
+    pub maximize_button: Option<Handle<UiNode>>,
+    pub can_maximize: bool,
+    pub snap_to_edges: bool,
     // Warning: Any dependant builders must take this into account!
     pub modal: bool,
     pub can_resize: bool,
@@ -637,6 +853,7 @@ fn make_text_title(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
 enum HeaderButton {
     Close,
     Minimize,
+    Maximize,
 }
 
 fn make_mark(ctx: &mut BuildContext, button: HeaderButton) -> Handle<UiNode> {
@@ -644,11 +861,11 @@ fn make_mark(ctx: &mut BuildContext, button: HeaderButton) -> Handle<UiNode> {
         WidgetBuilder::new()
             .with_horizontal_alignment(HorizontalAlignment::Center)
             .with_vertical_alignment(match button {
-                HeaderButton::Close => VerticalAlignment::Center,
+                HeaderButton::Close | HeaderButton::Maximize => VerticalAlignment::Center,
                 HeaderButton::Minimize => VerticalAlignment::Bottom,
             })
             .with_margin(match button {
-                HeaderButton::Close => Thickness::uniform(0.0),
+                HeaderButton::Close | HeaderButton::Maximize => Thickness::uniform(0.0),
                 HeaderButton::Minimize => Thickness::bottom(3.0),
             })
             .with_foreground(BRUSH_BRIGHT),
@@ -675,6 +892,12 @@ fn make_mark(ctx: &mut BuildContext, button: HeaderButton) -> Handle<UiNode> {
                 thickness: 3.0,
             }]
         }
+        HeaderButton::Maximize => {
+            vec![Primitive::Rectangle {
+                rect: Rect::new(0.0, 0.0, 12.0, 12.0),
+                thickness: 2.0,
+            }]
+        }
     })
     .build(ctx)
 }
@@ -706,6 +929,9 @@ impl WindowBuilder {
             open: true,
             close_button: None,
             minimize_button: None,
+            maximize_button: None,
+            can_maximize: false,
+            snap_to_edges: false,
             modal: false,
             can_resize: true,
             safe_border_size: Some(Vector2::new(25.0, 20.0)),
@@ -732,6 +958,21 @@ impl WindowBuilder {
         self
     }
 
+    pub fn with_maximize_button(mut self, button: Handle<UiNode>) -> Self {
+        self.maximize_button = Some(button);
+        self
+    }
+
+    pub fn can_maximize(mut self, can_maximize: bool) -> Self {
+        self.can_maximize = can_maximize;
+        self
+    }
+
+    pub fn with_snap_to_edges(mut self, snap_to_edges: bool) -> Self {
+        self.snap_to_edges = snap_to_edges;
+        self
+    }
+
     pub fn can_close(mut self, can_close: bool) -> Self {
         self.can_close = can_close;
         self
@@ -764,6 +1005,7 @@ impl WindowBuilder {
 
     pub fn build_window(self, ctx: &mut BuildContext) -> Window {
         let minimize_button;
+        let maximize_button;
         let close_button;
 
         let title;
@@ -789,6 +1031,7 @@ impl WindowBuilder {
                             color: COLOR_DARKEST,
                         },
                     ],
+                    coordinates: BrushCoordinates::Relative,
                 })
                 .with_child({
                     title_grid = GridBuilder::new(
@@ -814,6 +1057,17 @@ impl WindowBuilder {
                                     .set_column(1);
                                 minimize_button
                             })
+                            .with_child({
+                                maximize_button = self.maximize_button.unwrap_or_else(|| {
+                                    make_header_button(ctx, HeaderButton::Maximize)
+                                });
+                                ctx[maximize_button]
+                                    .set_visibility(self.can_maximize)
+                                    .set_width(30.0)
+                                    .set_row(0)
+                                    .set_column(2);
+                                maximize_button
+                            })
                             .with_child({
                                 close_button = self.close_button.unwrap_or_else(|| {
                                     make_header_button(ctx, HeaderButton::Close)
@@ -822,13 +1076,14 @@ impl WindowBuilder {
                                     .set_width(30.0)
                                     .set_visibility(self.can_close)
                                     .set_row(0)
-                                    .set_column(2);
+                                    .set_column(3);
                                 close_button
                             }),
                     )
                     .add_column(Column::stretch())
                     .add_column(Column::auto())
                     .add_column(Column::auto())
+                    .add_column(Column::auto())
                     .add_row(Row::stretch())
                     .build(ctx);
                     title_grid
@@ -872,8 +1127,13 @@ impl WindowBuilder {
             can_minimize: self.can_minimize,
             can_close: self.can_close,
             can_resize: self.can_resize,
+            maximized: false,
+            can_maximize: self.can_maximize,
+            snap_to_edges: self.snap_to_edges,
+            restore_geometry: None,
             header,
             minimize_button,
+            maximize_button,
             close_button,
             drag_delta: Default::default(),
             content: self.content,
@@ -0,0 +1,266 @@
+//! Animation baking allows you to "bake" skeletal animations into a texture, that could be sampled
+//! in a vertex shader. It is used for GPU crowd rendering, when thousands of animated characters
+//! should be drawn without updating their skeletons on CPU. See [`bake_animations`] docs for more
+//! info.
+
+use crate::{
+    animation::Animation,
+    core::{algebra::Matrix4, pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    engine::resource_manager::ResourceManager,
+    resource::texture::{
+        Texture, TextureKind, TextureMagnificationFilter, TextureMinificationFilter,
+        TexturePixelKind,
+    },
+    scene::{graph::Graph, mesh::Mesh, node::Node, transform::Transform},
+};
+
+/// Amount of RGBA32F texels used to store a single bone matrix. Only the first three rows of an
+/// affine matrix are stored, the last one is always `(0, 0, 0, 1)`.
+pub const TEXELS_PER_BONE: usize = 3;
+
+/// A range of frames of a single animation in the baked animation texture.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BakedClip {
+    /// Name of the source animation.
+    pub name: String,
+    /// Index of the first frame (row) of the clip in the texture.
+    pub first_frame: u32,
+    /// Total amount of frames of the clip.
+    pub frame_count: u32,
+    /// Amount of frames per second.
+    pub frame_rate: f32,
+    /// Whether the clip is looped or not.
+    pub looped: bool,
+}
+
+impl BakedClip {
+    /// Returns length of the clip in seconds.
+    pub fn length(&self) -> f32 {
+        if self.frame_rate > 0.0 {
+            self.frame_count.saturating_sub(1) as f32 / self.frame_rate
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A set of animations baked into a single texture. Each row of the texture is a single frame,
+/// each frame contains [`TEXELS_PER_BONE`] texels per bone, so the texture has
+/// `bone_count * TEXELS_PER_BONE` width and total amount of frames of every clip as height.
+/// Bone matrices are stored in the local space of the mesh, so the baked animations could be
+/// played on any amount of instances with their own world transform.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct BakedAnimations {
+    texture: Option<Texture>,
+    clips: Vec<BakedClip>,
+    bone_count: u32,
+}
+
+impl BakedAnimations {
+    /// Returns the texture with bone matrices.
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_ref()
+    }
+
+    /// Returns a list of baked clips. Clip indices are used to select an animation of a crowd
+    /// instance.
+    pub fn clips(&self) -> &[BakedClip] {
+        &self.clips
+    }
+
+    /// Tries to find a clip index by the name of its source animation.
+    pub fn find_clip(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|c| c.name == name)
+    }
+
+    /// Returns amount of bones stored in each frame.
+    pub fn bone_count(&self) -> u32 {
+        self.bone_count
+    }
+
+    pub(crate) fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        let mut state = resource_manager.state();
+        let texture_container = &mut state.containers_mut().textures;
+        texture_container.try_restore_optional_resource(&mut self.texture);
+    }
+}
+
+/// Bakes the given animations into a texture. Every animation is sampled with the given frame
+/// rate, for each frame the pose of the animation is applied to the graph and the matrices of the
+/// bones of the surface with `surface_index` of the `mesh` are written to the texture.
+///
+/// Local transforms of animated nodes are restored after baking, the animations are not modified.
+/// Returns `None` if the node is not a mesh or the surface has no bones.
+pub fn bake_animations(
+    graph: &mut Graph,
+    mesh: Handle<Node>,
+    surface_index: usize,
+    animations: &[&Animation],
+    frame_rate: f32,
+) -> Option<BakedAnimations> {
+    let bones = graph
+        .try_get(mesh)
+        .and_then(|n| n.cast::<Mesh>())
+        .and_then(|m| m.surfaces().get(surface_index))
+        .map(|s| s.bones().to_vec())?;
+
+    if bones.is_empty() || frame_rate <= 0.0 {
+        return None;
+    }
+
+    // Remember local transforms of every animated node to restore them after baking.
+    let mut saved_transforms: Vec<(Handle<Node>, Transform)> = Vec::new();
+    for animation in animations {
+        for track in animation.tracks() {
+            let target = track.target();
+            if !saved_transforms.iter().any(|(h, _)| *h == target) {
+                if let Some(node) = graph.try_get(target) {
+                    saved_transforms.push((target, node.local_transform().clone()));
+                }
+            }
+        }
+    }
+
+    let mut clips = Vec::with_capacity(animations.len());
+    let mut data: Vec<[f32; 4]> = Vec::new();
+    let mut total_frames = 0u32;
+
+    for animation in animations {
+        let mut animation = (*animation).clone();
+        let time_slice = animation.time_slice();
+        let frame_count = (animation.length() * frame_rate).ceil() as u32 + 1;
+
+        for frame in 0..frame_count {
+            let time = (time_slice.start + frame as f32 / frame_rate).min(time_slice.end);
+            animation.set_time_position(time);
+            animation.update_pose();
+            animation.pose().apply(graph);
+            graph.update_hierarchical_data();
+
+            let inv_mesh_transform = graph[mesh]
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity);
+
+            for &bone in bones.iter() {
+                let matrix = graph.try_get(bone).map_or_else(Matrix4::identity, |bone| {
+                    inv_mesh_transform * bone.global_transform() * bone.inv_bind_pose_transform()
+                });
+
+                for row in 0..TEXELS_PER_BONE {
+                    data.push([
+                        matrix[(row, 0)],
+                        matrix[(row, 1)],
+                        matrix[(row, 2)],
+                        matrix[(row, 3)],
+                    ]);
+                }
+            }
+        }
+
+        clips.push(BakedClip {
+            name: animation.name().to_owned(),
+            first_frame: total_frames,
+            frame_count,
+            frame_rate,
+            looped: animation.is_loop(),
+        });
+
+        total_frames += frame_count;
+    }
+
+    for (handle, transform) in saved_transforms {
+        graph[handle].set_local_transform(transform);
+    }
+    graph.update_hierarchical_data();
+
+    let texture = Texture::from_bytes(
+        TextureKind::Rectangle {
+            width: (bones.len() * TEXELS_PER_BONE) as u32,
+            height: total_frames,
+        },
+        TexturePixelKind::RGBA32F,
+        crate::utils::array_as_u8_slice(&data).to_vec(),
+        true,
+    )?;
+
+    {
+        let mut texture_data = texture.data_ref();
+        texture_data.set_minification_filter(TextureMinificationFilter::Nearest);
+        texture_data.set_magnification_filter(TextureMagnificationFilter::Nearest);
+    }
+
+    Some(BakedAnimations {
+        texture: Some(texture),
+        clips,
+        bone_count: bones.len() as u32,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{baking::bake_animations, track::Track, Animation},
+        core::{
+            algebra::{Matrix4, Vector3},
+            curve::{CurveKey, CurveKeyKind},
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+                MeshBuilder,
+            },
+            pivot::PivotBuilder,
+        },
+    };
+
+    #[test]
+    fn test_bake_animations() {
+        let mut graph = Graph::new();
+        let bone = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .with_bones(vec![bone])
+            .build()])
+            .build(&mut graph);
+
+        let mut track = Track::new_position();
+        track.set_target(bone);
+        let curves = track.frames_container_mut().curves_mut();
+        curves[0].add_key(CurveKey::new(0.0, 0.0, CurveKeyKind::Linear));
+        curves[0].add_key(CurveKey::new(1.0, 1.0, CurveKeyKind::Linear));
+        let mut animation = Animation::default();
+        animation.set_name("Walk");
+        animation.set_loop(false);
+        animation.add_track(track);
+        animation.fit_length_to_content();
+
+        let baked = bake_animations(&mut graph, mesh, 0, &[&animation], 2.0).unwrap();
+
+        assert_eq!(baked.bone_count(), 1);
+        assert_eq!(baked.find_clip("Walk"), Some(0));
+        let clip = &baked.clips()[0];
+        assert_eq!(clip.first_frame, 0);
+        assert_eq!(clip.frame_count, 3);
+        assert_eq!(clip.length(), 1.0);
+
+        // Translation of the bone is stored in the last column of the first row of each frame.
+        let texture = baked.texture().unwrap().data_ref();
+        let translation_x = |frame: usize| {
+            let offset = (frame * 12 + 3) * 4;
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&texture.data()[offset..offset + 4]);
+            f32::from_ne_bytes(bytes)
+        };
+        assert_eq!(translation_x(0), 0.0);
+        assert_eq!(translation_x(1), 0.5);
+        assert_eq!(translation_x(2), 1.0);
+
+        // The pose of the graph must be restored.
+        assert_eq!(graph[bone].global_position(), Vector3::default());
+    }
+}
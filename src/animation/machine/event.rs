@@ -18,6 +18,9 @@ pub enum Event {
 
     /// Occurs when active transition was changed.
     ActiveTransitionChanged(Handle<Transition>),
+
+    /// Occurs when a transition has started.
+    TransitionStart(Handle<Transition>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
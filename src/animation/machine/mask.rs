@@ -0,0 +1,176 @@
+//! Bone masks of machine nodes. See [`BoneMask`] docs for more info.
+
+use crate::{
+    animation::{AnimationContainer, AnimationPose},
+    core::{pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    scene::{graph::NodePool, node::Node},
+};
+use fxhash::FxHashSet;
+use std::cell::RefCell;
+
+#[derive(Default, Debug, Clone)]
+struct ResolvedMask {
+    valid: bool,
+    bones: Vec<String>,
+    handles: FxHashSet<Handle<Node>>,
+}
+
+/// A set of bones (referenced by their names), that restricts the pose of a machine node: only
+/// listed bones are animated by the node, the rest of the tracks do not contribute to the blended
+/// pose. It allows to restrict, for example, a reload animation to arm bones without authoring a
+/// separate animation clip. An empty mask passes every bone.
+///
+/// Bone names are resolved using the targets of the tracks of the animations of an animation
+/// player, so two instances of the same character do not interfere with each other.
+#[derive(Default, Debug, Visit, Clone, Reflect)]
+pub struct BoneMask {
+    bones: Vec<String>,
+    invert: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    resolved: RefCell<ResolvedMask>,
+}
+
+impl PartialEq for BoneMask {
+    fn eq(&self, other: &Self) -> bool {
+        self.bones == other.bones && self.invert == other.invert
+    }
+}
+
+impl BoneMask {
+    /// Creates new mask, that passes only the given bones.
+    pub fn new<I, S>(bones: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            bones: bones.into_iter().map(Into::into).collect(),
+            invert: false,
+            resolved: Default::default(),
+        }
+    }
+
+    /// Sets new set of bones of the mask.
+    pub fn set_bones<I, S>(&mut self, bones: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.bones = bones.into_iter().map(Into::into).collect();
+    }
+
+    /// Returns names of the bones of the mask.
+    pub fn bones(&self) -> &[String] {
+        &self.bones
+    }
+
+    /// Inverts the mask: listed bones will be excluded from the pose, the rest will pass.
+    pub fn set_inverted(&mut self, invert: bool) {
+        self.invert = invert;
+    }
+
+    /// Returns `true` if the mask is inverted.
+    pub fn is_inverted(&self) -> bool {
+        self.invert
+    }
+
+    /// Returns `true` if the mask has no bones and passes every bone.
+    pub fn is_empty(&self) -> bool {
+        self.bones.is_empty()
+    }
+
+    /// Returns `true` if the given node passes the mask. Always `true` for empty masks, otherwise
+    /// the mask must be resolved first (it is done by animation blending state machine node).
+    pub fn passes(&self, node: Handle<Node>) -> bool {
+        self.is_empty() || self.resolved.borrow().handles.contains(&node) != self.invert
+    }
+
+    /// Finds handles of the bones of the mask among the targets of the tracks of the given
+    /// animations. It does nothing if the mask was already resolved for the same set of bones.
+    pub fn resolve(&self, animations: &AnimationContainer, nodes: &NodePool) {
+        let mut resolved = self.resolved.borrow_mut();
+        if resolved.valid && resolved.bones == self.bones {
+            return;
+        }
+
+        resolved.handles.clear();
+        for animation in animations.iter() {
+            for track in animation.tracks() {
+                if let Some(node) = nodes.try_borrow(track.target()) {
+                    if self.bones.iter().any(|bone| bone == node.name()) {
+                        resolved.handles.insert(track.target());
+                    }
+                }
+            }
+        }
+        resolved.bones = self.bones.clone();
+        resolved.valid = true;
+    }
+
+    /// Removes local poses of the nodes, that do not pass the mask, from the given pose.
+    pub fn apply(&self, pose: &mut AnimationPose) {
+        if !self.is_empty() {
+            pose.retain_nodes(|node| self.passes(node));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            machine::BoneMask, track::Track, Animation, AnimationContainer, AnimationPose,
+        },
+        core::curve::{CurveKey, CurveKeyKind},
+        scene::{base::BaseBuilder, graph::NodePool, pivot::PivotBuilder},
+    };
+
+    #[test]
+    fn test_bone_mask() {
+        let mut nodes = NodePool::new();
+        let arm = nodes.spawn(PivotBuilder::new(BaseBuilder::new().with_name("Arm")).build_node());
+        let leg = nodes.spawn(PivotBuilder::new(BaseBuilder::new().with_name("Leg")).build_node());
+
+        let mut animation = Animation::default();
+        for bone in [arm, leg] {
+            let mut track = Track::new_position();
+            track.set_target(bone);
+            track.frames_container_mut().curves_mut()[0].add_key(CurveKey::new(
+                0.0,
+                1.0,
+                CurveKeyKind::Constant,
+            ));
+            animation.add_track(track);
+        }
+        animation.fit_length_to_content();
+        let mut animations = AnimationContainer::new();
+        animations.add(animation);
+        animations.update_animations(&mut nodes, false, 0.0);
+
+        let mut mask = BoneMask::new(["Arm"]);
+        mask.resolve(&animations, &nodes);
+        assert!(mask.passes(arm));
+        assert!(!mask.passes(leg));
+
+        let mut pose = AnimationPose::default();
+        animations
+            .iter()
+            .next()
+            .unwrap()
+            .pose()
+            .clone_into(&mut pose);
+        mask.apply(&mut pose);
+        assert!(pose.local_pose(arm).is_some());
+        assert!(pose.local_pose(leg).is_none());
+
+        mask.set_inverted(true);
+        assert!(!mask.passes(arm));
+        assert!(mask.passes(leg));
+
+        mask.set_bones(["Leg"]);
+        mask.resolve(&animations, &nodes);
+        assert!(mask.passes(arm));
+        assert!(!mask.passes(leg));
+    }
+}
@@ -81,31 +81,50 @@
 //!
 //! ```
 //!
+//! # Debugging
+//!
+//! Machine provides a set of methods to inspect its runtime state: [`Machine::active_state`],
+//! [`Machine::active_transition_progress`], [`Machine::node_weight`] (weights of pose nodes in the
+//! final pose of last frame) and a queue of events (see [`Event`]) which could be fetched using
+//! [`Machine::pop_event`]. It is useful both for gameplay logic and for debugging tools.
+//!
 //! You can use multiple machines to animation single model - for example one machine can be for
 //! locomotion and other is for combat. This means that locomotion machine will take control over
 //! lower body and combat machine will control upper body.
 
 use crate::{
-    animation::{machine::event::LimitedEventQueue, AnimationContainer, AnimationPose},
+    animation::{machine::event::LimitedEventQueue, Animation, AnimationContainer, AnimationPose},
     core::{
+        memory::with_frame_arena,
         pool::{Handle, Pool},
         reflect::prelude::*,
         visitor::{Visit, VisitResult, Visitor},
     },
+    scene::graph::NodePool,
     utils::log::{Log, MessageKind},
 };
 pub use event::Event;
+use fxhash::FxHashMap;
+pub use mask::BoneMask;
+pub use modifier::{BasePoseModifier, PoseModifier, PoseModifierContext};
 pub use node::{
+    additive::AddAnimation,
     blend::{BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
+    blendspace::{BlendSpace, BlendSpacePoint},
     play::PlayAnimation,
     EvaluatePose, PoseNode,
 };
-pub use parameter::{Parameter, ParameterContainer, PoseWeight};
+pub use parameter::{
+    Parameter, ParameterChangedEvent, ParameterContainer, ParameterError, ParameterHandle,
+    ParameterValue, PoseWeight,
+};
 pub use state::State;
 pub use transition::Transition;
 
 pub mod container;
 pub mod event;
+pub mod mask;
+pub mod modifier;
 pub mod node;
 pub mod parameter;
 pub mod state;
@@ -143,6 +162,9 @@ pub struct Machine {
     #[visit(skip)]
     #[reflect(hidden)]
     debug: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    node_weights: FxHashMap<Handle<PoseNode>, f32>,
 }
 
 impl Machine {
@@ -159,6 +181,7 @@ impl Machine {
             parameters: Default::default(),
             events: LimitedEventQueue::new(2048),
             debug: false,
+            node_weights: Default::default(),
         }
     }
 
@@ -169,18 +192,36 @@ impl Machine {
 
     #[inline]
     pub fn set_parameter(&mut self, id: &str, new_value: Parameter) -> &mut Self {
-        match self.parameters.get_mut(id) {
-            Some(parameter) => {
-                *parameter = new_value;
-            }
-            None => {
-                self.parameters.add(id, new_value);
-            }
-        }
-
+        self.parameters.set(id, new_value);
         self
     }
 
+    /// Resolves a typed handle of a parameter with the given name. See
+    /// [`ParameterContainer::resolve`] for more info.
+    #[inline]
+    pub fn parameter_handle<T: ParameterValue>(
+        &self,
+        name: &str,
+    ) -> Result<ParameterHandle<T>, ParameterError> {
+        self.parameters.resolve(name)
+    }
+
+    /// Sets new value of a parameter using its typed handle. Returns `false` if the handle is invalid.
+    #[inline]
+    pub fn set_parameter_by_handle<T: ParameterValue>(
+        &mut self,
+        handle: ParameterHandle<T>,
+        value: T,
+    ) -> bool {
+        self.parameters.set_by_handle(handle, value)
+    }
+
+    /// Returns a value of a parameter using its typed handle.
+    #[inline]
+    pub fn parameter_by_handle<T: ParameterValue>(&self, handle: ParameterHandle<T>) -> Option<T> {
+        self.parameters.get_by_handle(handle)
+    }
+
     #[inline]
     pub fn parameters(&self) -> &ParameterContainer {
         &self.parameters
@@ -275,6 +316,48 @@ impl Machine {
         self.active_transition
     }
 
+    /// Returns active transition and its normalized progress in `[0; 1]` range (if any).
+    #[inline]
+    pub fn active_transition_progress(&self) -> Option<(Handle<Transition>, f32)> {
+        self.transitions
+            .try_borrow(self.active_transition)
+            .map(|t| (self.active_transition, t.blend_factor()))
+    }
+
+    /// Returns `true` if the given state is active. A state is considered active if it is
+    /// current active state or it is a destination state of active transition.
+    #[inline]
+    pub fn is_state_active(&self, state: Handle<State>) -> bool {
+        state.is_some()
+            && (self.active_state == state
+                || self
+                    .transitions
+                    .try_borrow(self.active_transition)
+                    .map_or(false, |t| t.dest() == state))
+    }
+
+    /// Tries to find a state by its name.
+    #[inline]
+    pub fn find_state_by_name(&self, name: &str) -> Option<Handle<State>> {
+        self.states
+            .pair_iter()
+            .find(|(_, state)| state.name() == name)
+            .map(|(handle, _)| handle)
+    }
+
+    /// Returns a weight of the given pose node in the final pose of last evaluation. Zero means
+    /// that the node was not used at all.
+    #[inline]
+    pub fn node_weight(&self, node: Handle<PoseNode>) -> f32 {
+        self.node_weights.get(&node).cloned().unwrap_or_default()
+    }
+
+    /// Returns weights of every pose node that was used in the final pose of last evaluation.
+    #[inline]
+    pub fn node_weights(&self) -> &FxHashMap<Handle<PoseNode>, f32> {
+        &self.node_weights
+    }
+
     #[inline]
     pub fn transition(&self, handle: Handle<Transition>) -> &Transition {
         &self.transitions[handle]
@@ -315,12 +398,95 @@ impl Machine {
         &mut self.states
     }
 
+    fn collect_node_weights(
+        node_weights: &mut FxHashMap<Handle<PoseNode>, f32>,
+        nodes: &Pool<PoseNode>,
+        parameters: &ParameterContainer,
+        roots: &[(Handle<PoseNode>, f32)],
+    ) {
+        with_frame_arena(|arena| {
+            let mut stack = arena.vec_with_capacity(roots.len());
+            stack.extend_from_slice(roots);
+            while let Some((handle, weight)) = stack.pop() {
+                if let Some(node) = nodes.try_borrow(handle) {
+                    *node_weights.entry(handle).or_default() += weight;
+
+                    for (child, child_weight) in node.child_weights(parameters) {
+                        stack.push((child, weight * child_weight));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Resolves bone masks of every node, see [`BoneMask::resolve`] for more info.
+    pub(crate) fn resolve_masks(&self, animations: &AnimationContainer, nodes: &NodePool) {
+        for node in self.nodes.iter().filter(|n| !n.mask.is_empty()) {
+            node.mask.resolve(animations, nodes);
+        }
+    }
+
+    /// Applies playback parameters of [`PlayAnimation`] nodes (such as speed) to their animations
+    /// and synchronizes animations of sync groups.
+    pub(crate) fn update_animation_playback(&self, animations: &mut AnimationContainer) {
+        // Leader of each sync group is the node with the highest weight (from the last update).
+        let mut leaders = FxHashMap::<&str, (f32, Handle<Animation>)>::default();
+
+        for (handle, node) in self.nodes.pair_iter() {
+            if let PoseNode::PlayAnimation(play_animation) = node {
+                if let (Some(speed), Some(animation)) = (
+                    play_animation.speed.as_ref(),
+                    animations.try_get_mut(play_animation.animation),
+                ) {
+                    animation.set_speed(speed.evaluate(&self.parameters));
+                }
+
+                if !play_animation.sync_group.is_empty() {
+                    let weight = self.node_weight(handle);
+                    let leader = leaders
+                        .entry(play_animation.sync_group.as_str())
+                        .or_insert((weight, play_animation.animation));
+                    if weight > leader.0 {
+                        *leader = (weight, play_animation.animation);
+                    }
+                }
+            }
+        }
+
+        for (group, (_, leader)) in leaders {
+            let phase = match animations.try_get(leader) {
+                Some(animation) => animation.normalized_time_position(),
+                None => continue,
+            };
+
+            for node in self.nodes.iter() {
+                if let PoseNode::PlayAnimation(play_animation) = node {
+                    if play_animation.sync_group == group && play_animation.animation != leader {
+                        if let Some(animation) = animations.try_get_mut(play_animation.animation) {
+                            animation.set_normalized_time_position(phase);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the final pose of the last evaluation.
+    pub fn pose(&self) -> &AnimationPose {
+        &self.final_pose
+    }
+
+    pub(crate) fn pose_mut(&mut self) -> &mut AnimationPose {
+        &mut self.final_pose
+    }
+
     pub(crate) fn evaluate_pose(
         &mut self,
         animations: &AnimationContainer,
         dt: f32,
     ) -> &AnimationPose {
         self.final_pose.reset();
+        self.node_weights.clear();
 
         if self.active_state.is_some() || self.active_transition.is_some() {
             // Gather actual poses for each state.
@@ -355,20 +521,10 @@ impl Machine {
                                 );
                             }
 
-                            self.events.push(Event::StateEnter(transition.source()));
-                            if self.debug {
-                                Log::writeln(
-                                    MessageKind::Information,
-                                    format!(
-                                        "Entering state: {}",
-                                        self.states[transition.source()].name()
-                                    ),
-                                );
-                            }
-
                             self.active_state = Handle::NONE;
 
                             self.active_transition = handle;
+                            self.events.push(Event::TransitionStart(handle));
                             self.events
                                 .push(Event::ActiveTransitionChanged(self.active_transition));
 
@@ -382,6 +538,22 @@ impl Machine {
             if self.active_transition.is_some() {
                 let transition = &mut self.transitions[self.active_transition];
 
+                Self::collect_node_weights(
+                    &mut self.node_weights,
+                    &self.nodes,
+                    &self.parameters,
+                    &[
+                        (
+                            self.states[transition.source()].root,
+                            1.0 - transition.blend_factor(),
+                        ),
+                        (
+                            self.states[transition.dest()].root,
+                            transition.blend_factor(),
+                        ),
+                    ],
+                );
+
                 // Blend between source and dest states.
                 if let Some(source_pose) = self.states[transition.source()].pose(&self.nodes) {
                     self.final_pose
@@ -402,10 +574,15 @@ impl Machine {
                         .push(Event::ActiveTransitionChanged(self.active_transition));
 
                     self.active_state = transition.dest();
+                    self.events.push(Event::StateEnter(self.active_state));
                     self.events
                         .push(Event::ActiveStateChanged(self.active_state));
 
                     if self.debug {
+                        Log::writeln(
+                            MessageKind::Information,
+                            format!("Entering state: {}", self.states[self.active_state].name()),
+                        );
                         Log::writeln(
                             MessageKind::Information,
                             format!(
@@ -418,6 +595,13 @@ impl Machine {
             } else {
                 // We must have active state all the time when we do not have any active transition.
                 // Just get pose from active state.
+                Self::collect_node_weights(
+                    &mut self.node_weights,
+                    &self.nodes,
+                    &self.parameters,
+                    &[(self.states[self.active_state].root, 1.0)],
+                );
+
                 if let Some(active_state_pose) = self.states[self.active_state].pose(&self.nodes) {
                     active_state_pose.clone_into(&mut self.final_pose);
                 }
@@ -427,3 +611,46 @@ impl Machine {
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::animation::{
+        machine::{BlendAnimations, BlendPose, Machine, PlayAnimation, PoseNode, State},
+        Animation, AnimationContainer,
+    };
+
+    #[test]
+    fn test_sync_groups() {
+        let mut animations = AnimationContainer::new();
+        let mut add_animation = |length: f32| {
+            let mut animation = Animation::default();
+            animation.set_time_slice(0.0..length);
+            animations.add(animation)
+        };
+        let walk = add_animation(1.0);
+        let run = add_animation(2.0);
+
+        let mut machine = Machine::new();
+        let mut add_play_node = |animation| {
+            let mut node = PlayAnimation::new(animation);
+            node.sync_group = "Locomotion".to_string();
+            machine.add_node(PoseNode::PlayAnimation(node))
+        };
+        let walk_node = add_play_node(walk);
+        let run_node = add_play_node(run);
+        let blend = machine.add_node(PoseNode::BlendAnimations(BlendAnimations::new(vec![
+            BlendPose::with_constant_weight(0.8, walk_node),
+            BlendPose::with_constant_weight(0.2, run_node),
+        ])));
+        let state = machine.add_state(State::new("Locomotion", blend));
+        machine.set_entry_state(state);
+        machine.evaluate_pose(&animations, 0.0);
+
+        animations.get_mut(walk).set_time_position(0.25);
+        machine.update_animation_playback(&mut animations);
+
+        // Run animation follows the phase of the dominant walk animation.
+        assert_eq!(animations.get(run).time_position(), 0.5);
+        assert_eq!(animations.get(walk).time_position(), 0.25);
+    }
+}
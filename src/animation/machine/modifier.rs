@@ -0,0 +1,96 @@
+//! Pose modifiers. See [`PoseModifier`] docs for more info.
+
+use crate::{
+    animation::{AnimationPose, LocalPose},
+    core::pool::Handle,
+    scene::{graph::NodePool, node::Node},
+};
+use std::fmt::Debug;
+
+/// A context of a pose modifier.
+pub struct PoseModifierContext<'a> {
+    /// Time step of the update.
+    pub dt: f32,
+    /// Scene nodes. Keep in mind, that global transforms of the nodes are from the previous frame.
+    pub nodes: &'a NodePool,
+}
+
+impl<'a> PoseModifierContext<'a> {
+    /// Searches for a bone with the given name among the nodes of the pose.
+    pub fn find_bone(&self, pose: &AnimationPose, name: &str) -> Handle<Node> {
+        pose.find_node_by_name(name, self.nodes)
+    }
+
+    /// Returns a local pose of a bone with the given name, creates an empty one if the bone is not
+    /// animated. Returns `None` if there's no such bone among the nodes of the pose.
+    pub fn bone_pose<'b>(
+        &self,
+        pose: &'b mut AnimationPose,
+        name: &str,
+    ) -> Option<&'b mut LocalPose> {
+        let handle = self.find_bone(pose, name);
+        if handle.is_some() {
+            Some(pose.local_pose_or_insert(handle))
+        } else {
+            None
+        }
+    }
+}
+
+/// Base pose modifier trait is used to automatically implement some trait to reduce amount of
+/// boilerplate code.
+pub trait BasePoseModifier: Send + Debug + 'static {
+    /// Creates exact copy of the modifier.
+    fn clone_box(&self) -> Box<dyn PoseModifier>;
+}
+
+impl<T> BasePoseModifier for T
+where
+    T: Clone + PoseModifier,
+{
+    fn clone_box(&self) -> Box<dyn PoseModifier> {
+        Box::new(self.clone())
+    }
+}
+
+/// Pose modifier allows gameplay code to modify the final pose of an animation blending state
+/// machine after its evaluation and before it is applied to the scene graph. It is useful for
+/// procedural animation, such as head tracking, procedural recoil, spine bending toward aim
+/// direction, etc. Use [`crate::scene::animation::absm::AnimationBlendingStateMachine::add_pose_modifier`]
+/// to add a modifier. Pose modifiers are runtime-only, they're not serialized.
+///
+/// ```rust
+/// use fyrox::{
+///     animation::{
+///         machine::{PoseModifier, PoseModifierContext},
+///         AnimationPose,
+///     },
+///     core::algebra::{UnitQuaternion, Vector3},
+/// };
+///
+/// #[derive(Clone, Debug)]
+/// struct HeadTilt {
+///     angle: f32,
+///     weight: f32,
+/// }
+///
+/// impl PoseModifier for HeadTilt {
+///     fn modify_pose(&mut self, pose: &mut AnimationPose, context: &PoseModifierContext) {
+///         if let Some(head) = context.bone_pose(pose, "Head") {
+///             let tilt = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), self.angle);
+///             let rotation = head.rotation().unwrap_or_else(UnitQuaternion::identity) * tilt;
+///             head.blend_rotation(rotation, self.weight);
+///         }
+///     }
+/// }
+/// ```
+pub trait PoseModifier: BasePoseModifier {
+    /// Modifies the given pose.
+    fn modify_pose(&mut self, pose: &mut AnimationPose, context: &PoseModifierContext);
+}
+
+impl Clone for Box<dyn PoseModifier> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    animation::{
+        machine::{
+            node::{BasePoseNode, EvaluatePose},
+            ParameterContainer, PoseNode, PoseWeight,
+        },
+        AnimationContainer, AnimationPose,
+    },
+    core::{
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+};
+use std::{
+    cell::{Ref, RefCell},
+    ops::{Deref, DerefMut},
+};
+
+/// Machine node that layers an additive pose on top of a base pose. The additive pose is a
+/// difference between a clip and a reference pose (usually the first frame of the clip), it is
+/// multiplied by the weight and applied to the base pose. It allows to add breathing, recoil or
+/// lean offsets over base locomotion without extra states.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct AddAnimation {
+    pub base: BasePoseNode,
+    /// A source of the pose on top of which the difference is applied.
+    #[reflect(hidden)]
+    pub base_pose: Handle<PoseNode>,
+    /// A source of the clip pose.
+    #[reflect(hidden)]
+    pub additive_pose: Handle<PoseNode>,
+    /// A source of the reference pose, that is subtracted from the clip pose. If not set, the clip
+    /// pose is treated as a difference itself.
+    #[reflect(hidden)]
+    pub reference_pose: Handle<PoseNode>,
+    /// Weight of the difference.
+    pub weight: PoseWeight,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub reference_output_pose: RefCell<AnimationPose>,
+}
+
+impl Deref for AddAnimation {
+    type Target = BasePoseNode;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for AddAnimation {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl AddAnimation {
+    /// Creates new node, that applies difference between `additive_pose` and `reference_pose` on
+    /// top of `base_pose`.
+    pub fn new(
+        base_pose: Handle<PoseNode>,
+        additive_pose: Handle<PoseNode>,
+        reference_pose: Handle<PoseNode>,
+        weight: PoseWeight,
+    ) -> Self {
+        Self {
+            base: Default::default(),
+            base_pose,
+            additive_pose,
+            reference_pose,
+            weight,
+            output_pose: Default::default(),
+            reference_output_pose: Default::default(),
+        }
+    }
+
+    pub fn children(&self) -> Vec<Handle<PoseNode>> {
+        vec![self.base_pose, self.additive_pose, self.reference_pose]
+    }
+
+    pub fn child_weights(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode>, f32)> {
+        let weight = self.weight.evaluate(params);
+        vec![
+            (self.base_pose, 1.0),
+            (self.additive_pose, weight),
+            (self.reference_pose, weight),
+        ]
+    }
+}
+
+impl EvaluatePose for AddAnimation {
+    fn eval_pose(
+        &self,
+        nodes: &Pool<PoseNode>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer,
+        dt: f32,
+    ) -> Ref<AnimationPose> {
+        self.output_pose.borrow_mut().reset();
+
+        if let Some(base_pose) = nodes.try_borrow(self.base_pose) {
+            base_pose
+                .eval_pose(nodes, params, animations, dt)
+                .clone_into(&mut self.output_pose.borrow_mut());
+        }
+
+        // Reference pose is copied, because it could share pose sources with the clip pose.
+        let has_reference = match nodes.try_borrow(self.reference_pose) {
+            Some(reference_pose) => {
+                reference_pose
+                    .eval_pose(nodes, params, animations, dt)
+                    .clone_into(&mut self.reference_output_pose.borrow_mut());
+                true
+            }
+            _ => false,
+        };
+
+        // Difference between a pose and itself is zero.
+        if let Some(additive_pose) = nodes
+            .try_borrow(self.additive_pose)
+            .filter(|_| self.additive_pose != self.reference_pose)
+        {
+            let clip = additive_pose.eval_pose(nodes, params, animations, dt);
+            let reference = self.reference_output_pose.borrow();
+            self.output_pose.borrow_mut().add_difference(
+                &clip,
+                if has_reference {
+                    Some(&*reference)
+                } else {
+                    None
+                },
+                self.weight.evaluate(params),
+            );
+        }
+
+        self.base.mask.apply(&mut self.output_pose.borrow_mut());
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose> {
+        self.output_pose.borrow()
+    }
+}
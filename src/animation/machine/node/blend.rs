@@ -97,6 +97,14 @@ impl BlendAnimations {
     pub fn children(&self) -> Vec<Handle<PoseNode>> {
         self.pose_sources.iter().map(|s| s.pose_source).collect()
     }
+
+    /// Returns children nodes with their current blend weights.
+    pub fn child_weights(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode>, f32)> {
+        self.pose_sources
+            .iter()
+            .map(|s| (s.pose_source, s.weight.evaluate(params)))
+            .collect()
+    }
 }
 
 impl EvaluatePose for BlendAnimations {
@@ -109,16 +117,7 @@ impl EvaluatePose for BlendAnimations {
     ) -> Ref<AnimationPose> {
         self.output_pose.borrow_mut().reset();
         for blend_pose in self.pose_sources.iter() {
-            let weight = match blend_pose.weight {
-                PoseWeight::Constant(value) => value,
-                PoseWeight::Parameter(ref param_id) => {
-                    if let Some(Parameter::Weight(weight)) = params.get(param_id) {
-                        *weight
-                    } else {
-                        0.0
-                    }
-                }
-            };
+            let weight = blend_pose.weight.evaluate(params);
 
             if let Some(pose_source) = nodes
                 .try_borrow(blend_pose.pose_source)
@@ -129,6 +128,7 @@ impl EvaluatePose for BlendAnimations {
                     .blend_with(&pose_source, weight);
             }
         }
+        self.base.mask.apply(&mut self.output_pose.borrow_mut());
         self.output_pose.borrow()
     }
 
@@ -185,6 +185,29 @@ impl BlendAnimationsByIndex {
     pub fn children(&self) -> Vec<Handle<PoseNode>> {
         self.inputs.iter().map(|s| s.pose_source).collect()
     }
+
+    /// Returns children nodes that were used in the last evaluation with their blend weights.
+    pub fn child_weights(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode>, f32)> {
+        let mut weights = Vec::new();
+        if let Some(&Parameter::Index(current_index)) = params.get(&self.index_parameter) {
+            if let Some(current_input) = self.inputs.get(current_index as usize) {
+                match self
+                    .prev_index
+                    .get()
+                    .filter(|prev_index| *prev_index != current_index)
+                    .and_then(|prev_index| self.inputs.get(prev_index as usize))
+                {
+                    Some(prev_input) if current_input.blend_time > 0.0 => {
+                        let interpolator = self.blend_time.get() / current_input.blend_time;
+                        weights.push((prev_input.pose_source, 1.0 - interpolator));
+                        weights.push((current_input.pose_source, interpolator));
+                    }
+                    _ => weights.push((current_input.pose_source, 1.0)),
+                }
+            }
+        }
+        weights
+    }
 }
 
 impl EvaluatePose for BlendAnimationsByIndex {
@@ -245,6 +268,7 @@ impl EvaluatePose for BlendAnimationsByIndex {
             }
         }
 
+        self.base.mask.apply(&mut self.output_pose.borrow_mut());
         self.output_pose.borrow()
     }
 
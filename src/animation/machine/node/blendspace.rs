@@ -0,0 +1,269 @@
+use crate::{
+    animation::{
+        machine::{node::BasePoseNode, EvaluatePose, Parameter, ParameterContainer, PoseNode},
+        AnimationContainer, AnimationPose,
+    },
+    core::{
+        algebra::Vector2,
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+};
+use std::{
+    cell::{Ref, RefCell},
+    ops::{Deref, DerefMut},
+};
+
+/// A sample point of a blend space. It places a pose source at the given position in the
+/// parameter space of a blend space.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct BlendSpacePoint {
+    /// Position of the point in the parameter space.
+    pub position: Vector2<f32>,
+    /// A source of the pose of the point.
+    #[reflect(hidden)]
+    pub pose_source: Handle<PoseNode>,
+}
+
+impl BlendSpacePoint {
+    /// Creates new sample point at the given position with the given pose source.
+    pub fn new(position: Vector2<f32>, pose_source: Handle<PoseNode>) -> Self {
+        Self {
+            position,
+            pose_source,
+        }
+    }
+}
+
+/// Machine node that blends poses of its sample points, that are scattered over 2D parameter
+/// space, using two weight parameters as coordinates of a sample in the space. It is useful for
+/// directional locomotion, for example: walk animations for every direction could be placed
+/// around a central idle animation and the node will produce smooth transitions between them
+/// while the character changes its velocity.
+///
+/// Weights of the points are calculated using gradient band interpolation: a pose of a point is
+/// taken as is if the sample matches the position of the point, and the points, that are closer to
+/// the sample, contribute more to the blended pose.
+#[derive(Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct BlendSpace {
+    pub base: BasePoseNode,
+    /// Sample points of the space.
+    pub points: Vec<BlendSpacePoint>,
+    /// Name of a weight parameter, that is used as X coordinate of a sample.
+    pub x_parameter: String,
+    /// Name of a weight parameter, that is used as Y coordinate of a sample.
+    pub y_parameter: String,
+    /// Name of X axis, used only by the editor.
+    pub x_axis_name: String,
+    /// Name of Y axis, used only by the editor.
+    pub y_axis_name: String,
+    /// Minimal values of the parameter space. Samples are clamped to the bounds of the space.
+    pub min_values: Vector2<f32>,
+    /// Maximal values of the parameter space. Samples are clamped to the bounds of the space.
+    pub max_values: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose>,
+}
+
+impl Default for BlendSpace {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            points: Default::default(),
+            x_parameter: Default::default(),
+            y_parameter: Default::default(),
+            x_axis_name: "X".to_string(),
+            y_axis_name: "Y".to_string(),
+            min_values: Vector2::new(-1.0, -1.0),
+            max_values: Vector2::new(1.0, 1.0),
+            output_pose: Default::default(),
+        }
+    }
+}
+
+impl Deref for BlendSpace {
+    type Target = BasePoseNode;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for BlendSpace {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl BlendSpace {
+    /// Creates new blend space with the given sample points, that uses the given weight parameters
+    /// as coordinates of a sample.
+    pub fn new(x_parameter: String, y_parameter: String, points: Vec<BlendSpacePoint>) -> Self {
+        Self {
+            x_parameter,
+            y_parameter,
+            points,
+            ..Default::default()
+        }
+    }
+
+    pub fn children(&self) -> Vec<Handle<PoseNode>> {
+        self.points.iter().map(|p| p.pose_source).collect()
+    }
+
+    /// Returns children nodes with their current blend weights.
+    pub fn child_weights(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode>, f32)> {
+        self.fetch_weights(self.sample(params))
+            .into_iter()
+            .map(|(index, weight)| (self.points[index].pose_source, weight))
+            .collect()
+    }
+
+    /// Returns current sample in the parameter space. The sample is clamped to the bounds of the
+    /// space, missing parameters are treated as zero.
+    pub fn sample(&self, params: &ParameterContainer) -> Vector2<f32> {
+        let fetch = |name: &str| {
+            if let Some(Parameter::Weight(weight)) = params.get(name) {
+                *weight
+            } else {
+                0.0
+            }
+        };
+
+        self.clamp_sample(Vector2::new(
+            fetch(&self.x_parameter),
+            fetch(&self.y_parameter),
+        ))
+    }
+
+    /// Clamps the given sample to the bounds of the space.
+    pub fn clamp_sample(&self, sample: Vector2<f32>) -> Vector2<f32> {
+        Vector2::new(
+            sample
+                .x
+                .max(self.min_values.x.min(self.max_values.x))
+                .min(self.max_values.x.max(self.min_values.x)),
+            sample
+                .y
+                .max(self.min_values.y.min(self.max_values.y))
+                .min(self.max_values.y.max(self.min_values.y)),
+        )
+    }
+
+    /// Calculates weights of the points for the given sample. Returns indices of the points, that
+    /// contribute to the pose, with their normalized weights.
+    pub fn fetch_weights(&self, sample: Vector2<f32>) -> Vec<(usize, f32)> {
+        let mut weights = Vec::new();
+        let mut total_weight = 0.0;
+
+        for (i, point) in self.points.iter().enumerate() {
+            let mut weight = 1.0f32;
+
+            for (j, other) in self.points.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+
+                let edge = other.position - point.position;
+                let sqr_length = edge.norm_squared();
+                if sqr_length <= f32::EPSILON {
+                    // Coincident points do not form a gradient band.
+                    continue;
+                }
+
+                let k = 1.0 - (sample - point.position).dot(&edge) / sqr_length;
+
+                weight = weight.min(k.clamp(0.0, 1.0));
+            }
+
+            if weight > 0.0 {
+                total_weight += weight;
+                weights.push((i, weight));
+            }
+        }
+
+        if total_weight > 0.0 {
+            for (_, weight) in weights.iter_mut() {
+                *weight /= total_weight;
+            }
+        }
+
+        weights
+    }
+}
+
+impl EvaluatePose for BlendSpace {
+    fn eval_pose(
+        &self,
+        nodes: &Pool<PoseNode>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer,
+        dt: f32,
+    ) -> Ref<AnimationPose> {
+        self.output_pose.borrow_mut().reset();
+
+        for (index, weight) in self.fetch_weights(self.sample(params)) {
+            if let Some(pose_source) = nodes
+                .try_borrow(self.points[index].pose_source)
+                .map(|pose_source| pose_source.eval_pose(nodes, params, animations, dt))
+            {
+                self.output_pose
+                    .borrow_mut()
+                    .blend_with(&pose_source, weight);
+            }
+        }
+
+        self.base.mask.apply(&mut self.output_pose.borrow_mut());
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose> {
+        self.output_pose.borrow()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::machine::node::blendspace::{BlendSpace, BlendSpacePoint},
+        core::{algebra::Vector2, pool::Handle},
+    };
+
+    #[test]
+    fn test_blend_space_weights() {
+        let blend_space = BlendSpace::new(
+            "X".to_string(),
+            "Y".to_string(),
+            vec![
+                BlendSpacePoint::new(Vector2::new(-1.0, 0.0), Handle::NONE),
+                BlendSpacePoint::new(Vector2::new(1.0, 0.0), Handle::NONE),
+                BlendSpacePoint::new(Vector2::new(0.0, 1.0), Handle::NONE),
+            ],
+        );
+
+        // Exact match takes the pose of the point as is.
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(1.0, 0.0)),
+            vec![(1, 1.0)]
+        );
+
+        // The sample is equally distant from the bands of every point.
+        let weights = blend_space.fetch_weights(Vector2::new(0.0, 0.0));
+        assert_eq!(weights.len(), 3);
+        for (_, weight) in weights {
+            assert!((weight - 1.0 / 3.0).abs() <= 0.0001);
+        }
+
+        // Weights are always normalized.
+        let weights = blend_space.fetch_weights(Vector2::new(0.3, 0.4));
+        let total = weights.iter().map(|(_, w)| *w).sum::<f32>();
+        assert!((total - 1.0).abs() <= 0.0001);
+
+        assert_eq!(
+            blend_space.clamp_sample(Vector2::new(5.0, -5.0)),
+            Vector2::new(1.0, -1.0)
+        );
+    }
+}
@@ -2,8 +2,12 @@ use crate::animation::machine::State;
 use crate::{
     animation::{
         machine::{
-            node::{blend::BlendAnimations, play::PlayAnimation},
-            BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer,
+            node::{
+                additive::AddAnimation, blend::BlendAnimations, blendspace::BlendSpace,
+                play::PlayAnimation,
+            },
+            BlendAnimationsByIndex, BlendPose, BlendSpacePoint, BoneMask, IndexedBlendInput,
+            ParameterContainer, PoseWeight,
         },
         Animation, AnimationContainer, AnimationPose,
     },
@@ -19,7 +23,9 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+pub mod additive;
 pub mod blend;
+pub mod blendspace;
 pub mod play;
 
 #[derive(Debug, Visit, Clone, Default, Reflect, PartialEq)]
@@ -27,6 +33,9 @@ pub struct BasePoseNode {
     pub position: Vector2<f32>,
     #[reflect(hidden)]
     pub parent_state: Handle<State>,
+    /// Restricts the pose of the node to a set of bones, see [`BoneMask`] docs for more info.
+    #[visit(optional)] // Backward compatibility
+    pub mask: BoneMask,
 }
 
 /// Specialized node that provides animation pose. See documentation for each variant.
@@ -40,6 +49,12 @@ pub enum PoseNode {
 
     /// See docs for `BlendAnimationsByIndex`.
     BlendAnimationsByIndex(BlendAnimationsByIndex),
+
+    /// See docs for `AddAnimation`.
+    AddAnimation(AddAnimation),
+
+    /// See docs for `BlendSpace`.
+    BlendSpace(BlendSpace),
 }
 
 impl Default for PoseNode {
@@ -67,6 +82,31 @@ impl PoseNode {
         Self::BlendAnimationsByIndex(BlendAnimationsByIndex::new(index_parameter, inputs))
     }
 
+    /// Creates new node that applies a difference between `additive_pose` and `reference_pose`
+    /// on top of `base_pose`.
+    pub fn make_add_animation(
+        base_pose: Handle<PoseNode>,
+        additive_pose: Handle<PoseNode>,
+        reference_pose: Handle<PoseNode>,
+        weight: PoseWeight,
+    ) -> Self {
+        Self::AddAnimation(AddAnimation::new(
+            base_pose,
+            additive_pose,
+            reference_pose,
+            weight,
+        ))
+    }
+
+    /// Creates new node that blends poses of the given sample points of 2D parameter space.
+    pub fn make_blend_space(
+        x_parameter: String,
+        y_parameter: String,
+        points: Vec<BlendSpacePoint>,
+    ) -> Self {
+        Self::BlendSpace(BlendSpace::new(x_parameter, y_parameter, points))
+    }
+
     pub fn children(&self) -> Vec<Handle<PoseNode>> {
         match self {
             Self::PlayAnimation(_) => {
@@ -75,6 +115,19 @@ impl PoseNode {
             }
             Self::BlendAnimations(definition) => definition.children(),
             Self::BlendAnimationsByIndex(definition) => definition.children(),
+            Self::AddAnimation(definition) => definition.children(),
+            Self::BlendSpace(definition) => definition.children(),
+        }
+    }
+
+    /// Returns children nodes with the weights they're contributing to the pose of this node.
+    pub fn child_weights(&self, params: &ParameterContainer) -> Vec<(Handle<PoseNode>, f32)> {
+        match self {
+            Self::PlayAnimation(_) => vec![],
+            Self::BlendAnimations(definition) => definition.child_weights(params),
+            Self::BlendAnimationsByIndex(definition) => definition.child_weights(params),
+            Self::AddAnimation(definition) => definition.child_weights(params),
+            Self::BlendSpace(definition) => definition.child_weights(params),
         }
     }
 }
@@ -85,6 +138,8 @@ macro_rules! static_dispatch {
             PoseNode::PlayAnimation(v) => v.$func($($args),*),
             PoseNode::BlendAnimations(v) => v.$func($($args),*),
             PoseNode::BlendAnimationsByIndex(v) => v.$func($($args),*),
+            PoseNode::AddAnimation(v) => v.$func($($args),*),
+            PoseNode::BlendSpace(v) => v.$func($($args),*),
         }
     };
 }
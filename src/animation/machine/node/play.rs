@@ -2,7 +2,7 @@ use crate::{
     animation::{
         machine::{
             node::{BasePoseNode, EvaluatePose},
-            ParameterContainer, PoseNode,
+            ParameterContainer, PoseNode, PoseWeight,
         },
         Animation, AnimationContainer, AnimationPose,
     },
@@ -23,6 +23,17 @@ use std::{
 pub struct PlayAnimation {
     pub base: BasePoseNode,
     pub animation: Handle<Animation>,
+    /// Optional playback speed of the animation, it could be bound to a weight parameter of the
+    /// machine. Negative speed plays the animation in reverse. `None` - the speed of the animation
+    /// is left as is.
+    #[visit(optional)] // Backward compatibility
+    pub speed: Option<PoseWeight>,
+    /// Name of a synchronization group of the node. Animations of the nodes of the same group are
+    /// phase-aligned: they follow normalized time position of the animation of the node with the
+    /// highest weight in the group. It keeps blended cyclic animations (walk and run, for example)
+    /// in step and eliminates foot sliding. Empty name - no synchronization.
+    #[visit(optional)] // Backward compatibility
+    pub sync_group: String,
     #[visit(skip)]
     #[reflect(hidden)]
     pub output_pose: RefCell<AnimationPose>,
@@ -51,6 +62,8 @@ impl PlayAnimation {
         Self {
             base: Default::default(),
             animation,
+            speed: None,
+            sync_group: Default::default(),
             output_pose: Default::default(),
         }
     }
@@ -69,6 +82,7 @@ impl EvaluatePose for PlayAnimation {
                 .pose()
                 .clone_into(&mut self.output_pose.borrow_mut());
         }
+        self.base.mask.apply(&mut self.output_pose.borrow_mut());
         self.output_pose.borrow()
     }
 
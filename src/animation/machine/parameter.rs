@@ -1,8 +1,13 @@
-use crate::core::{reflect::prelude::*, visitor::prelude::*};
+use crate::core::{reflect::prelude::*, visitor::prelude::*, VecExtensions};
 use fxhash::FxHashMap;
 use fyrox_core::parking_lot::Mutex;
-use std::cell::Cell;
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    sync::mpsc::Sender,
+};
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 /// Machine parameter.  Machine uses various parameters for specific actions. For example
@@ -26,6 +31,140 @@ impl Default for Parameter {
     }
 }
 
+/// A type that could be stored in a [`Parameter`]. It is used by typed parameter API, see
+/// [`ParameterContainer::resolve`] for more info.
+pub trait ParameterValue: Copy {
+    /// Name of respective [`Parameter`] variant.
+    const KIND: &'static str;
+
+    /// Tries to extract a value from the given parameter. Returns `None` if the type of the
+    /// parameter does not match.
+    fn from_parameter(parameter: &Parameter) -> Option<Self>;
+
+    /// Wraps the value in a parameter.
+    fn into_parameter(self) -> Parameter;
+}
+
+impl ParameterValue for f32 {
+    const KIND: &'static str = "Weight";
+
+    fn from_parameter(parameter: &Parameter) -> Option<Self> {
+        if let Parameter::Weight(value) = parameter {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    fn into_parameter(self) -> Parameter {
+        Parameter::Weight(self)
+    }
+}
+
+impl ParameterValue for bool {
+    const KIND: &'static str = "Rule";
+
+    fn from_parameter(parameter: &Parameter) -> Option<Self> {
+        if let Parameter::Rule(value) = parameter {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    fn into_parameter(self) -> Parameter {
+        Parameter::Rule(self)
+    }
+}
+
+impl ParameterValue for u32 {
+    const KIND: &'static str = "Index";
+
+    fn from_parameter(parameter: &Parameter) -> Option<Self> {
+        if let Parameter::Index(value) = parameter {
+            Some(*value)
+        } else {
+            None
+        }
+    }
+
+    fn into_parameter(self) -> Parameter {
+        Parameter::Index(self)
+    }
+}
+
+/// A typed handle of a parameter in a [`ParameterContainer`]. It allows to access parameters
+/// without string lookups. Handles are invalidated when parameters are removed or reordered,
+/// in this case access methods will return `None`/`false` if the type of the parameter at
+/// the position does not match anymore.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ParameterHandle<T> {
+    index: usize,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Clone for ParameterHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ParameterHandle<T> {}
+
+impl<T> ParameterHandle<T> {
+    /// Returns index of the parameter in the container.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// An error that may occur during parameter handle resolution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    /// There is no parameter with the given name.
+    NotFound(String),
+    /// A parameter with the given name exists, but has different type.
+    TypeMismatch {
+        /// Name of the parameter.
+        name: String,
+        /// Requested type of the parameter.
+        expected: &'static str,
+        /// Actual type of the parameter.
+        actual: String,
+    },
+}
+
+impl Display for ParameterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterError::NotFound(name) => write!(f, "There is no parameter {}!", name),
+            ParameterError::TypeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Parameter {} has type {}, but {} was requested!",
+                name, actual, expected
+            ),
+        }
+    }
+}
+
+/// An event that is sent to subscribers of a [`ParameterContainer`] when a parameter value has
+/// changed. See [`ParameterContainer::subscribe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterChangedEvent {
+    /// Name of the parameter.
+    pub name: String,
+    /// Index of the parameter.
+    pub index: usize,
+    /// Previous value of the parameter.
+    pub old_value: Parameter,
+    /// New value of the parameter.
+    pub new_value: Parameter,
+}
+
 /// Specific animation pose weight.
 #[derive(Debug, Visit, Clone, PartialEq, Reflect, EnumVariantNames, EnumString, AsRefStr)]
 pub enum PoseWeight {
@@ -43,6 +182,23 @@ impl Default for PoseWeight {
     }
 }
 
+impl PoseWeight {
+    /// Calculates actual value of the weight. Parametrized weight is zero if there is no such
+    /// parameter or it is not a Weight parameter.
+    pub fn evaluate(&self, params: &ParameterContainer) -> f32 {
+        match self {
+            PoseWeight::Constant(value) => *value,
+            PoseWeight::Parameter(param_id) => {
+                if let Some(Parameter::Weight(weight)) = params.get(param_id) {
+                    *weight
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
 #[derive(Reflect, Visit, Default, Debug, Clone, PartialEq)]
 pub struct ParameterDefinition {
     pub name: String,
@@ -90,6 +246,10 @@ pub struct ParameterContainer {
     #[reflect(hidden)]
     #[visit(skip)]
     lookup: Mutex<FxHashMap<String, usize>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    subscribers: Vec<Sender<ParameterChangedEvent>>,
 }
 
 impl PartialEq for ParameterContainer {
@@ -103,6 +263,8 @@ impl Clone for ParameterContainer {
         Self {
             parameters: self.parameters.clone(),
             lookup: Mutex::new(self.lookup.lock().clone()),
+            // Subscribers are interested in changes of this particular container only.
+            subscribers: Default::default(),
         }
     }
 }
@@ -121,6 +283,11 @@ impl ParameterContainer {
         }
     }
 
+    /// Returns an iterator over every parameter definition in the container.
+    pub fn iter(&self) -> impl Iterator<Item = &ParameterDefinition> {
+        self.parameters.parameters.iter()
+    }
+
     pub fn add(&mut self, name: &str, value: Parameter) {
         self.parameters.push(ParameterDefinition {
             name: name.to_string(),
@@ -143,4 +310,108 @@ impl ParameterContainer {
             .get(name)
             .and_then(|i| self.parameters.parameters.get_mut(*i).map(|d| &mut d.value))
     }
+
+    /// Sets new value of a parameter with the given name and notifies subscribers. Adds new
+    /// parameter if there is no parameter with the given name.
+    pub fn set(&mut self, name: &str, value: Parameter) {
+        self.update_index();
+        let index = self.lookup.lock().get(name).cloned();
+        match index {
+            Some(index) => self.set_at(index, value),
+            None => self.add(name, value),
+        }
+    }
+
+    fn set_at(&mut self, index: usize, value: Parameter) {
+        let definition = &mut self.parameters.parameters[index];
+        if definition.value != value {
+            let old_value = std::mem::replace(&mut definition.value, value);
+            if !self.subscribers.is_empty() {
+                let event = ParameterChangedEvent {
+                    name: definition.name.clone(),
+                    index,
+                    old_value,
+                    new_value: value,
+                };
+                self.subscribers
+                    .retain_mut_ext(|sender| sender.send(event.clone()).is_ok());
+            }
+        }
+    }
+
+    /// Resolves a typed handle of a parameter with the given name. The handle could then be used
+    /// to access the parameter without string lookups. Type of the parameter is validated at
+    /// resolution time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fyrox::animation::machine::{Parameter, ParameterContainer};
+    ///
+    /// let mut parameters = ParameterContainer::default();
+    /// parameters.add("Speed", Parameter::Weight(0.0));
+    ///
+    /// let speed = parameters.resolve::<f32>("Speed").unwrap();
+    /// parameters.set_by_handle(speed, 2.0);
+    /// assert_eq!(parameters.get_by_handle(speed), Some(2.0));
+    ///
+    /// // Type mismatch is detected at resolution time.
+    /// assert!(parameters.resolve::<bool>("Speed").is_err());
+    /// ```
+    pub fn resolve<T: ParameterValue>(
+        &self,
+        name: &str,
+    ) -> Result<ParameterHandle<T>, ParameterError> {
+        self.update_index();
+        let index = self
+            .lookup
+            .lock()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ParameterError::NotFound(name.to_owned()))?;
+        let value = &self.parameters.parameters[index].value;
+        if T::from_parameter(value).is_some() {
+            Ok(ParameterHandle {
+                index,
+                phantom: PhantomData,
+            })
+        } else {
+            Err(ParameterError::TypeMismatch {
+                name: name.to_owned(),
+                expected: T::KIND,
+                actual: value.as_ref().to_owned(),
+            })
+        }
+    }
+
+    /// Returns a value of a parameter using its typed handle. Returns `None` if the handle is
+    /// invalid.
+    pub fn get_by_handle<T: ParameterValue>(&self, handle: ParameterHandle<T>) -> Option<T> {
+        self.parameters
+            .parameters
+            .get(handle.index)
+            .and_then(|d| T::from_parameter(&d.value))
+    }
+
+    /// Sets new value of a parameter using its typed handle and notifies subscribers. Returns
+    /// `false` if the handle is invalid.
+    pub fn set_by_handle<T: ParameterValue>(
+        &mut self,
+        handle: ParameterHandle<T>,
+        value: T,
+    ) -> bool {
+        if self.get_by_handle(handle).is_some() {
+            self.set_at(handle.index, value.into_parameter());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds new subscriber, that will receive [`ParameterChangedEvent`] every time when a parameter
+    /// changes its value via [`Self::set`] or [`Self::set_by_handle`]. Subscribers with closed
+    /// channels are removed automatically.
+    pub fn subscribe(&mut self, sender: Sender<ParameterChangedEvent>) {
+        self.subscribers.push(sender);
+    }
 }
@@ -1,9 +1,15 @@
 use crate::{
-    animation::{track::Track, value::BoundValueCollection},
+    animation::{
+        track::Track,
+        value::{BoundValueCollection, TrackValue, ValueBinding},
+    },
     core::{
+        algebra::{UnitQuaternion, Vector3},
+        curve::Curve,
         math::wrapf,
         pool::{Handle, Pool, Ticket},
         reflect::prelude::*,
+        uuid::Uuid,
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
@@ -21,6 +27,7 @@ use std::{
     ops::{Index, IndexMut, Range},
 };
 
+pub mod baking;
 pub mod container;
 pub mod machine;
 pub mod spritesheet;
@@ -88,6 +95,15 @@ pub struct Animation {
     speed: f32,
     looped: bool,
     enabled: bool,
+    /// Optional playback rate curve, see [`Animation::set_speed_curve`] for more info.
+    #[visit(optional)]
+    speed_curve: Option<Curve>,
+    #[visit(optional)]
+    ping_pong: bool,
+    // Current direction of ping-pong playback.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    ping_pong_reversed: bool,
     /// An external resource from which the animation was created.
     #[visit(optional)]
     pub(crate) resource: Option<Model>,
@@ -119,6 +135,14 @@ impl Default for LocalPose {
 }
 
 impl LocalPose {
+    /// Creates new local pose of the given node without any values.
+    pub fn new(node: Handle<Node>) -> Self {
+        Self {
+            node,
+            values: Default::default(),
+        }
+    }
+
     fn weighted_clone(&self, weight: f32) -> Self {
         Self {
             node: self.node,
@@ -133,6 +157,79 @@ impl LocalPose {
     pub fn values(&self) -> &BoundValueCollection {
         &self.values
     }
+
+    /// Returns a reference to the values of the pose.
+    pub fn values_mut(&mut self) -> &mut BoundValueCollection {
+        &mut self.values
+    }
+
+    /// Returns a handle of the node the pose is for.
+    pub fn node(&self) -> Handle<Node> {
+        self.node
+    }
+
+    /// Returns local position of the node (if any).
+    pub fn position(&self) -> Option<Vector3<f32>> {
+        match self.values.value(&ValueBinding::Position) {
+            Some(TrackValue::Vector3(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns local rotation of the node (if any).
+    pub fn rotation(&self) -> Option<UnitQuaternion<f32>> {
+        match self.values.value(&ValueBinding::Rotation) {
+            Some(TrackValue::UnitQuaternion(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns local scale of the node (if any).
+    pub fn scale(&self) -> Option<Vector3<f32>> {
+        match self.values.value(&ValueBinding::Scale) {
+            Some(TrackValue::Vector3(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Sets new local position of the node.
+    pub fn set_position(&mut self, position: Vector3<f32>) {
+        self.values
+            .set_value(ValueBinding::Position, TrackValue::Vector3(position));
+    }
+
+    /// Sets new local rotation of the node.
+    pub fn set_rotation(&mut self, rotation: UnitQuaternion<f32>) {
+        self.values
+            .set_value(ValueBinding::Rotation, TrackValue::UnitQuaternion(rotation));
+    }
+
+    /// Sets new local scale of the node.
+    pub fn set_scale(&mut self, scale: Vector3<f32>) {
+        self.values
+            .set_value(ValueBinding::Scale, TrackValue::Vector3(scale));
+    }
+
+    /// Blends current local position with the given one using the weight in `[0; 1]` range. If the
+    /// pose has no position, the given one is set as is.
+    pub fn blend_position(&mut self, position: Vector3<f32>, weight: f32) {
+        let current = self.position().unwrap_or(position);
+        self.set_position(current.lerp(&position, weight));
+    }
+
+    /// Blends current local rotation with the given one using the weight in `[0; 1]` range. If the
+    /// pose has no rotation, the given one is set as is.
+    pub fn blend_rotation(&mut self, rotation: UnitQuaternion<f32>, weight: f32) {
+        let current = self.rotation().unwrap_or(rotation);
+        self.set_rotation(current.nlerp(&rotation, weight));
+    }
+
+    /// Blends current local scale with the given one using the weight in `[0; 1]` range. If the
+    /// pose has no scale, the given one is set as is.
+    pub fn blend_scale(&mut self, scale: Vector3<f32>, weight: f32) {
+        let current = self.scale().unwrap_or(scale);
+        self.set_scale(current.lerp(&scale, weight));
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -160,6 +257,71 @@ impl AnimationPose {
         }
     }
 
+    /// Applies a difference between `clip` and `reference` poses (additive pose), multiplied by the
+    /// given weight, on top of the pose. Only nodes, that are present in both the pose and the
+    /// clip, are affected. `None` reference means that the clip is already a difference.
+    pub fn add_difference(
+        &mut self,
+        clip: &AnimationPose,
+        reference: Option<&AnimationPose>,
+        weight: f32,
+    ) {
+        for (handle, local_pose) in self.local_poses.iter_mut() {
+            if let Some(clip_pose) = clip.local_poses.get(handle) {
+                let reference_values = reference
+                    .and_then(|r| r.local_poses.get(handle))
+                    .map(|p| &p.values);
+                local_pose
+                    .values
+                    .add_difference(&clip_pose.values, reference_values, weight);
+            }
+        }
+    }
+
+    /// Returns a local pose of the given node (if any).
+    pub fn local_pose(&self, handle: Handle<Node>) -> Option<&LocalPose> {
+        self.local_poses.get(&handle)
+    }
+
+    /// Returns a local pose of the given node (if any).
+    pub fn local_pose_mut(&mut self, handle: Handle<Node>) -> Option<&mut LocalPose> {
+        self.local_poses.get_mut(&handle)
+    }
+
+    /// Returns a local pose of the given node, creates an empty one if there's no such pose. It
+    /// allows to control nodes, that are not animated.
+    pub fn local_pose_or_insert(&mut self, handle: Handle<Node>) -> &mut LocalPose {
+        self.local_poses
+            .entry(handle)
+            .or_insert_with(|| LocalPose::new(handle))
+    }
+
+    /// Searches for a node with the given name among the nodes of the pose.
+    pub fn find_node_by_name(&self, name: &str, nodes: &NodePool) -> Handle<Node> {
+        self.local_poses
+            .keys()
+            .find(|handle| {
+                nodes
+                    .try_borrow(**handle)
+                    .map_or(false, |node| node.name() == name)
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over every local pose.
+    pub fn local_poses(&self) -> impl Iterator<Item = &LocalPose> {
+        self.local_poses.values()
+    }
+
+    /// Keeps local poses of the nodes, that satisfy the given predicate, and removes the rest.
+    pub fn retain_nodes<F>(&mut self, mut filter: F)
+    where
+        F: FnMut(Handle<Node>) -> bool,
+    {
+        self.local_poses.retain(|node, _| filter(*node))
+    }
+
     fn add_local_pose(&mut self, local_pose: LocalPose) {
         self.local_poses.insert(local_pose.node, local_pose);
     }
@@ -213,6 +375,9 @@ impl Clone for Animation {
             time_position: self.time_position,
             looped: self.looped,
             enabled: self.enabled,
+            speed_curve: self.speed_curve.clone(),
+            ping_pong: self.ping_pong,
+            ping_pong_reversed: self.ping_pong_reversed,
             resource: self.resource.clone(),
             pose: Default::default(),
             signals: self.signals.clone(),
@@ -260,7 +425,7 @@ impl Animation {
     }
 
     pub fn set_time_position(&mut self, time: f32) -> &mut Self {
-        if self.looped {
+        if self.looped && !self.ping_pong {
             self.time_position = wrapf(time, self.time_slice.start, self.time_slice.end);
         } else {
             self.time_position = time.clamp(self.time_slice.start, self.time_slice.end);
@@ -296,13 +461,14 @@ impl Animation {
     pub(crate) fn tick(&mut self, dt: f32) {
         self.update_pose();
 
+        let speed = self.effective_speed();
         let current_time_position = self.time_position();
-        let new_time_position = current_time_position + dt * self.speed();
+        let mut new_time_position = current_time_position + dt * speed;
 
         for signal in self.signals.iter_mut().filter(|s| s.enabled) {
-            if self.speed >= 0.0
+            if speed >= 0.0
                 && (current_time_position < signal.time && new_time_position >= signal.time)
-                || self.speed < 0.0
+                || speed < 0.0
                     && (current_time_position > signal.time && new_time_position <= signal.time)
             {
                 // TODO: Make this configurable.
@@ -314,9 +480,49 @@ impl Animation {
             }
         }
 
+        if self.looped && self.ping_pong && self.length() > 0.0 {
+            // Reflect the position from the bounds and flip the direction.
+            if new_time_position > self.time_slice.end {
+                new_time_position = self.time_slice.end - (new_time_position - self.time_slice.end);
+                self.ping_pong_reversed = !self.ping_pong_reversed;
+            } else if new_time_position < self.time_slice.start {
+                new_time_position =
+                    self.time_slice.start + (self.time_slice.start - new_time_position);
+                self.ping_pong_reversed = !self.ping_pong_reversed;
+            }
+        }
+
         self.set_time_position(new_time_position);
     }
 
+    /// Returns current playback position normalized to `[0; 1]` range of the time slice.
+    pub fn normalized_time_position(&self) -> f32 {
+        let length = self.length();
+        if length > 0.0 {
+            (self.time_position - self.time_slice.start) / length
+        } else {
+            0.0
+        }
+    }
+
+    /// Sets playback position using normalized (`[0; 1]`) time of the time slice.
+    pub fn set_normalized_time_position(&mut self, position: f32) -> &mut Self {
+        self.set_time_position(self.time_slice.start + position * self.length())
+    }
+
+    /// Returns actual playback rate of the animation at current time position. It is a product of
+    /// the speed, a value of the speed curve (if any) and the direction of ping-pong playback.
+    pub fn effective_speed(&self) -> f32 {
+        let mut speed = self.speed;
+        if let Some(curve) = self.speed_curve.as_ref().filter(|curve| !curve.is_empty()) {
+            speed *= curve.value_at(self.normalized_time_position());
+        }
+        if self.ping_pong_reversed {
+            speed = -speed;
+        }
+        speed
+    }
+
     pub fn pop_event(&mut self) -> Option<AnimationEvent> {
         self.events.pop_front()
     }
@@ -338,8 +544,41 @@ impl Animation {
         self.looped
     }
 
+    /// Returns `true` if non-looped animation has reached the end of its time slice (or the
+    /// beginning, if the animation is played in reverse).
     pub fn has_ended(&self) -> bool {
-        !self.looped && (self.time_position - self.time_slice.end).abs() <= f32::EPSILON
+        let end = if self.effective_speed() < 0.0 {
+            self.time_slice.start
+        } else {
+            self.time_slice.end
+        };
+        !self.looped && (self.time_position - end).abs() <= f32::EPSILON
+    }
+
+    /// Enables or disables ping-pong mode of looped animation: the playback direction flips each
+    /// time the animation reaches either end of its time slice. Does nothing for non-looped
+    /// animations.
+    pub fn set_ping_pong(&mut self, ping_pong: bool) -> &mut Self {
+        self.ping_pong = ping_pong;
+        self.ping_pong_reversed = false;
+        self
+    }
+
+    pub fn is_ping_pong(&self) -> bool {
+        self.ping_pong
+    }
+
+    /// Sets a curve, that defines playback rate multiplier over normalized (`[0; 1]`) time of the
+    /// animation. It allows to stretch parts of the animation without re-authoring, for example, to
+    /// speed up the wind-up of an attack and slow down its recovery. Negative values play the
+    /// animation in reverse. `None` (or an empty curve) means constant rate.
+    pub fn set_speed_curve(&mut self, curve: Option<Curve>) -> &mut Self {
+        self.speed_curve = curve;
+        self
+    }
+
+    pub fn speed_curve(&self) -> Option<&Curve> {
+        self.speed_curve.as_ref()
     }
 
     pub fn set_enabled(&mut self, enabled: bool) -> &mut Self {
@@ -351,6 +590,7 @@ impl Animation {
         self.enabled
     }
 
+    /// Sets playback speed of the animation. Negative speed plays the animation in reverse.
     pub fn set_speed(&mut self, speed: f32) -> &mut Self {
         self.speed = speed;
         self
@@ -420,6 +660,29 @@ impl Animation {
         }
     }
 
+    /// Enables or disables a track with the given id. Returns `false` if there's no such track.
+    pub fn set_track_enabled(&mut self, id: Uuid, enabled: bool) -> bool {
+        match self.tracks.iter_mut().find(|track| track.id() == id) {
+            Some(track) => {
+                track.enable(enabled);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Enables or disables tracks of the nodes with the given names. Disabled tracks do not
+    /// contribute to the pose of the animation.
+    pub fn set_named_tracks_enabled(&mut self, names: &[&str], enabled: bool, graph: &Graph) {
+        for track in self.tracks.iter_mut() {
+            if let Some(node) = graph.try_get(track.target()) {
+                if names.contains(&node.name()) {
+                    track.enable(enabled);
+                }
+            }
+        }
+    }
+
     pub fn tracks_of(&self, handle: Handle<Node>) -> impl Iterator<Item = &NodeTrack> {
         self.tracks
             .iter()
@@ -481,6 +744,9 @@ impl Default for Animation {
             time_position: 0.0,
             enabled: true,
             looped: true,
+            speed_curve: None,
+            ping_pong: false,
+            ping_pong_reversed: false,
             resource: Default::default(),
             pose: Default::default(),
             signals: Default::default(),
@@ -646,3 +912,73 @@ impl IndexMut<Handle<Animation>> for AnimationContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{Animation, AnimationPose},
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            curve::{Curve, CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+    };
+
+    #[test]
+    fn test_local_pose_transform() {
+        let mut pose = AnimationPose::default();
+        let node = Handle::new(1, 1);
+        assert!(pose.local_pose(node).is_none());
+
+        let local_pose = pose.local_pose_or_insert(node);
+        assert_eq!(local_pose.node(), node);
+        assert_eq!(local_pose.position(), None);
+
+        local_pose.blend_position(Vector3::new(2.0, 0.0, 0.0), 0.5);
+        assert_eq!(local_pose.position(), Some(Vector3::new(2.0, 0.0, 0.0)));
+
+        local_pose.blend_position(Vector3::new(4.0, 0.0, 0.0), 0.5);
+        assert_eq!(local_pose.position(), Some(Vector3::new(3.0, 0.0, 0.0)));
+
+        local_pose.set_rotation(UnitQuaternion::identity());
+        local_pose.set_scale(Vector3::new(1.0, 1.0, 1.0));
+        assert_eq!(local_pose.rotation(), Some(UnitQuaternion::identity()));
+        assert_eq!(local_pose.values().values.len(), 3);
+
+        assert!(pose.local_pose_mut(node).is_some());
+        assert_eq!(pose.local_poses().count(), 1);
+    }
+
+    #[test]
+    fn test_reverse_and_ping_pong_playback() {
+        let mut animation = Animation::default();
+        animation.set_time_slice(0.0..1.0);
+        animation
+            .set_loop(false)
+            .set_speed(-1.0)
+            .set_time_position(1.0);
+        animation.tick(0.25);
+        assert_eq!(animation.time_position(), 0.75);
+        animation.tick(1.0);
+        assert_eq!(animation.time_position(), 0.0);
+        assert!(animation.has_ended());
+
+        animation
+            .set_loop(true)
+            .set_ping_pong(true)
+            .set_speed(1.0)
+            .set_time_position(0.75);
+        animation.tick(0.5);
+        assert_eq!(animation.time_position(), 0.75);
+        assert_eq!(animation.effective_speed(), -1.0);
+
+        let mut curve = Curve::default();
+        curve.add_key(CurveKey::new(0.0, 2.0, CurveKeyKind::Constant));
+        animation
+            .set_ping_pong(false)
+            .set_speed_curve(Some(curve))
+            .set_time_position(0.0);
+        animation.tick(0.25);
+        assert_eq!(animation.time_position(), 0.5);
+    }
+}
@@ -102,6 +102,14 @@ where
         }
     }
 
+    pub fn new_blend_shape<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            frames: TrackFramesContainer::new(TrackValueKind::Real),
+            binding: ValueBinding::BlendShape(name.as_ref().to_owned()),
+            ..Default::default()
+        }
+    }
+
     pub fn set_binding(&mut self, binding: ValueBinding) {
         self.binding = binding;
     }
@@ -5,7 +5,7 @@ use crate::{
         reflect::{prelude::*, ResolvePath},
         visitor::prelude::*,
     },
-    scene::node::Node,
+    scene::{mesh::Mesh, node::Node},
     utils::log::Log,
 };
 use std::fmt::{Debug, Display, Formatter};
@@ -41,7 +41,10 @@ impl TrackValue {
 
     pub fn blend_with(&mut self, other: &Self, weight: f32) {
         match (self, other) {
+            (Self::Real(a), Self::Real(b)) => *a += *b * weight,
+            (Self::Vector2(a), Self::Vector2(b)) => *a += b.scale(weight),
             (Self::Vector3(a), Self::Vector3(b)) => *a += b.scale(weight),
+            (Self::Vector4(a), Self::Vector4(b)) => *a += b.scale(weight),
             (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => *a = a.nlerp(b, weight),
             _ => (),
         }
@@ -77,6 +80,8 @@ pub enum ValueBinding {
     Scale,
     Rotation,
     Property(String),
+    /// Weight of a named blend shape (morph target) of a mesh, must be used with real values.
+    BlendShape(String),
 }
 
 impl Display for ValueBinding {
@@ -86,6 +91,7 @@ impl Display for ValueBinding {
             ValueBinding::Scale => write!(f, "Scale"),
             ValueBinding::Rotation => write!(f, "Rotation"),
             ValueBinding::Property(name) => write!(f, "{}", name),
+            ValueBinding::BlendShape(name) => write!(f, "Blend Shape: {}", name),
         }
     }
 }
@@ -109,6 +115,59 @@ impl BoundValue {
         self.value.blend_with(&other.value, weight);
     }
 
+    /// Adds a difference between `clip` and `reference` values, multiplied by the given weight, to
+    /// the value. Rotations are combined by multiplication and scales are combined component-wise,
+    /// the rest values are summed. `None` reference means identity (zero, identity rotation or unit
+    /// scale).
+    pub fn add_difference(&mut self, clip: &Self, reference: Option<&Self>, weight: f32) {
+        let reference = reference.map(|r| &r.value);
+        match (&self.binding, &mut self.value, &clip.value) {
+            (_, TrackValue::UnitQuaternion(a), TrackValue::UnitQuaternion(c)) => {
+                let delta = match reference {
+                    Some(TrackValue::UnitQuaternion(r)) => r.inverse() * c,
+                    _ => *c,
+                };
+                *a *= UnitQuaternion::identity().nlerp(&delta, weight);
+            }
+            (ValueBinding::Scale, TrackValue::Vector3(a), TrackValue::Vector3(c)) => {
+                let ratio = match reference {
+                    Some(TrackValue::Vector3(r)) => c.component_div(r),
+                    _ => *c,
+                };
+                *a = a.component_mul(&Vector3::repeat(1.0).lerp(&ratio, weight));
+            }
+            (_, TrackValue::Real(a), TrackValue::Real(c)) => {
+                let r = match reference {
+                    Some(TrackValue::Real(r)) => *r,
+                    _ => 0.0,
+                };
+                *a += (*c - r) * weight;
+            }
+            (_, TrackValue::Vector2(a), TrackValue::Vector2(c)) => {
+                let d = match reference {
+                    Some(TrackValue::Vector2(r)) => c - r,
+                    _ => *c,
+                };
+                *a += d.scale(weight);
+            }
+            (_, TrackValue::Vector3(a), TrackValue::Vector3(c)) => {
+                let d = match reference {
+                    Some(TrackValue::Vector3(r)) => c - r,
+                    _ => *c,
+                };
+                *a += d.scale(weight);
+            }
+            (_, TrackValue::Vector4(a), TrackValue::Vector4(c)) => {
+                let d = match reference {
+                    Some(TrackValue::Vector4(r)) => c - r,
+                    _ => *c,
+                };
+                *a += d.scale(weight);
+            }
+            _ => (),
+        }
+    }
+
     pub fn interpolate(&self, other: &Self, t: f32) -> Option<Self> {
         assert_eq!(self.binding, other.binding);
         self.value.interpolate(&other.value, t).map(|value| Self {
@@ -144,6 +203,28 @@ impl BoundValueCollection {
                 value.blend_with(other_value, weight);
             }
         }
+
+        // Missing blend shape channel means zero weight, so blend shapes animated only by the
+        // other pose still must be blended in.
+        for other_value in other.values.iter() {
+            if matches!(other_value.binding, ValueBinding::BlendShape(_))
+                && !self.values.iter().any(|v| v.binding == other_value.binding)
+            {
+                self.values.push(other_value.weighted_clone(weight));
+            }
+        }
+    }
+
+    /// Adds a difference between matching values of `clip` and `reference` collections to the values
+    /// of the collection. See [`BoundValue::add_difference`] for more info.
+    pub fn add_difference(&mut self, clip: &Self, reference: Option<&Self>, weight: f32) {
+        for value in self.values.iter_mut() {
+            if let Some(clip_value) = clip.values.iter().find(|v| v.binding == value.binding) {
+                let reference_value =
+                    reference.and_then(|r| r.values.iter().find(|v| v.binding == value.binding));
+                value.add_difference(clip_value, reference_value, weight);
+            }
+        }
     }
 
     pub fn interpolate(&self, other: &Self, t: f32) -> Self {
@@ -157,6 +238,36 @@ impl BoundValueCollection {
         Self { values: new_values }
     }
 
+    /// Returns a value with the given binding (if any).
+    pub fn value(&self, binding: &ValueBinding) -> Option<&TrackValue> {
+        self.values
+            .iter()
+            .find(|v| &v.binding == binding)
+            .map(|v| &v.value)
+    }
+
+    /// Sets a value with the given binding, adds new value if there's no such binding.
+    pub fn set_value(&mut self, binding: ValueBinding, value: TrackValue) {
+        match self.values.iter_mut().find(|v| v.binding == binding) {
+            Some(bound_value) => bound_value.value = value,
+            None => self.values.push(BoundValue { binding, value }),
+        }
+    }
+
+    /// Returns a value of a named blend shape channel (if any).
+    pub fn blend_shape_weight(&self, name: &str) -> Option<f32> {
+        self.values
+            .iter()
+            .find_map(|v| match (&v.binding, &v.value) {
+                (ValueBinding::BlendShape(binding_name), TrackValue::Real(weight))
+                    if binding_name == name =>
+                {
+                    Some(*weight)
+                }
+                _ => None,
+            })
+    }
+
     pub fn apply(&self, node_ref: &mut Node) {
         for bound_value in self.values.iter() {
             match bound_value.binding {
@@ -183,6 +294,23 @@ impl BoundValueCollection {
                         Log::err("Unable to apply rotation, because underlying type is not UnitQuaternion!")
                     }
                 }
+                ValueBinding::BlendShape(ref name) => {
+                    if let TrackValue::Real(weight) = bound_value.value {
+                        match node_ref.cast_mut::<Mesh>() {
+                            Some(mesh) => {
+                                if !mesh.set_blend_shape_weight(name, weight) {
+                                    Log::err(format!("There is no blend shape {}!", name))
+                                }
+                            }
+                            None => Log::err(format!(
+                                "Unable to apply blend shape {} weight, because node is not a mesh!",
+                                name
+                            )),
+                        }
+                    } else {
+                        Log::err("Unable to apply blend shape weight, because underlying type is not Real!")
+                    }
+                }
                 ValueBinding::Property(ref property_name) => {
                     match node_ref.as_reflect_mut().resolve_path_mut(property_name) {
                         Ok(property) => {
@@ -205,3 +333,62 @@ impl BoundValueCollection {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::value::{BoundValue, BoundValueCollection, TrackValue, ValueBinding},
+        core::algebra::Vector3,
+    };
+
+    fn blend_shape(name: &str, weight: f32) -> BoundValue {
+        BoundValue {
+            binding: ValueBinding::BlendShape(name.to_owned()),
+            value: TrackValue::Real(weight),
+        }
+    }
+
+    #[test]
+    fn test_blend_shape_blending() {
+        let a = BoundValueCollection {
+            values: vec![blend_shape("Smile", 1.0)],
+        };
+        let b = BoundValueCollection {
+            values: vec![blend_shape("Smile", 0.5), blend_shape("Blink", 1.0)],
+        };
+
+        let mut result = a.weighted_clone(0.5);
+        result.blend_with(&b, 0.5);
+
+        assert_eq!(result.blend_shape_weight("Smile"), Some(0.75));
+        assert_eq!(result.blend_shape_weight("Blink"), Some(0.5));
+        assert_eq!(result.blend_shape_weight("Frown"), None);
+    }
+
+    #[test]
+    fn test_add_difference() {
+        let position = |x: f32| BoundValue {
+            binding: ValueBinding::Position,
+            value: TrackValue::Vector3(Vector3::new(x, 0.0, 0.0)),
+        };
+        let scale = |x: f32| BoundValue {
+            binding: ValueBinding::Scale,
+            value: TrackValue::Vector3(Vector3::new(x, 1.0, 1.0)),
+        };
+
+        let mut base = BoundValueCollection {
+            values: vec![position(1.0), scale(2.0)],
+        };
+        let clip = BoundValueCollection {
+            values: vec![position(3.0), scale(3.0)],
+        };
+        let reference = BoundValueCollection {
+            values: vec![position(2.0), scale(1.5)],
+        };
+
+        base.add_difference(&clip, Some(&reference), 0.5);
+
+        assert_eq!(base.values[0], position(1.5));
+        assert_eq!(base.values[1], scale(3.0));
+    }
+}
@@ -0,0 +1,779 @@
+//! Developer console. The console is a registry of commands and console variables (cvars), that
+//! could be invoked by typing text lines. It is useful for debugging, cheats and modding.
+//!
+//! # Commands and cvars
+//!
+//! Commands are closures, that take a set of arguments ([`CommandArgs`]) and the console itself
+//! (to print output, read or write cvars, etc.). Cvars are typed values (see [`CVarValue`]), typing
+//! a name of a cvar prints its value, typing a name with an argument sets new value (the argument
+//! is parsed according to the type of the cvar).
+//!
+//! ```rust
+//! use fyrox::console::Console;
+//!
+//! let console = Console::new();
+//! console.register_cvar("god_mode", false, "Makes the player invulnerable.");
+//! console.register_command("add", "Prints a sum of two numbers.", "add <a> <b>", |args, console| {
+//!     let sum = args.parse::<f32>(0)? + args.parse::<f32>(1)?;
+//!     console.print(sum.to_string());
+//!     Ok(())
+//! });
+//!
+//! console.execute("god_mode true; add 2 3").unwrap();
+//! assert_eq!(console.cvar_bool("god_mode"), Some(true));
+//! ```
+//!
+//! Multiple commands could be put in a single line, separated by `;`. Arguments with spaces
+//! should be quoted: `echo "Hello, world!"`.
+//!
+//! # Startup scripts
+//!
+//! A set of commands could be executed from a text file (one command per line, lines starting
+//! with `#` are comments) using [`Console::execute_file`] or `exec <path>` command. It is a good
+//! way to store user settings or to run setup commands on startup.
+//!
+//! # User interface
+//!
+//! The console is not bound to any user interface, use [`view::ConsoleView`] to add a drop-down
+//! console overlay to your game.
+
+pub mod view;
+
+use crate::core::parking_lot::Mutex;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Maximum amount of lines in the output of the console, oldest lines are removed first.
+pub const MAX_OUTPUT_LINES: usize = 1024;
+
+/// Maximum amount of entries in the command history.
+pub const MAX_HISTORY_LENGTH: usize = 128;
+
+/// A value of a console variable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CVarValue {
+    /// A boolean value, accepts `true`/`false`, `1`/`0`, `on`/`off`.
+    Bool(bool),
+    /// An integer value.
+    Integer(i64),
+    /// A floating-point value.
+    Float(f64),
+    /// A string value.
+    String(String),
+}
+
+impl CVarValue {
+    /// Returns a name of the type of the value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            CVarValue::Bool(_) => "bool",
+            CVarValue::Integer(_) => "integer",
+            CVarValue::Float(_) => "float",
+            CVarValue::String(_) => "string",
+        }
+    }
+
+    /// Parses a string as a value of the same type as `self`.
+    pub fn parse_same_type(&self, string: &str) -> Result<CVarValue, String> {
+        let error = || format!("{} is not a valid {} value", string, self.type_name());
+        match self {
+            CVarValue::Bool(_) => match string.to_lowercase().as_str() {
+                "true" | "1" | "on" | "yes" => Ok(CVarValue::Bool(true)),
+                "false" | "0" | "off" | "no" => Ok(CVarValue::Bool(false)),
+                _ => Err(error()),
+            },
+            CVarValue::Integer(_) => string.parse().map(CVarValue::Integer).map_err(|_| error()),
+            CVarValue::Float(_) => string.parse().map(CVarValue::Float).map_err(|_| error()),
+            CVarValue::String(_) => Ok(CVarValue::String(string.to_owned())),
+        }
+    }
+}
+
+impl Display for CVarValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CVarValue::Bool(v) => write!(f, "{}", v),
+            CVarValue::Integer(v) => write!(f, "{}", v),
+            CVarValue::Float(v) => write!(f, "{}", v),
+            CVarValue::String(v) => write!(f, "\"{}\"", v),
+        }
+    }
+}
+
+impl From<bool> for CVarValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i32> for CVarValue {
+    fn from(v: i32) -> Self {
+        Self::Integer(v as i64)
+    }
+}
+
+impl From<i64> for CVarValue {
+    fn from(v: i64) -> Self {
+        Self::Integer(v)
+    }
+}
+
+impl From<f32> for CVarValue {
+    fn from(v: f32) -> Self {
+        Self::Float(v as f64)
+    }
+}
+
+impl From<f64> for CVarValue {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<&str> for CVarValue {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_owned())
+    }
+}
+
+impl From<String> for CVarValue {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+/// Arguments of a command (without the name of the command).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandArgs {
+    args: Vec<String>,
+}
+
+impl CommandArgs {
+    /// Returns amount of arguments.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Returns `true` if there are no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Returns an argument at the given index.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(|a| a.as_str())
+    }
+
+    /// Parses an argument at the given index, returns human-readable error if there is no such
+    /// argument or it has invalid format.
+    pub fn parse<T: FromStr>(&self, index: usize) -> Result<T, String> {
+        let arg = self
+            .get(index)
+            .ok_or_else(|| format!("Argument {} is missing", index + 1))?;
+        arg.parse().map_err(|_| {
+            format!(
+                "Argument {} ({}) must be {}",
+                index + 1,
+                arg,
+                std::any::type_name::<T>()
+            )
+        })
+    }
+
+    /// Returns an iterator over every argument.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.args.iter().map(|a| a.as_str())
+    }
+}
+
+/// A handler of a command. It must return human-readable error if the command has failed.
+pub type CommandHandler = Arc<dyn Fn(&CommandArgs, &Console) -> Result<(), String> + Send + Sync>;
+
+struct Command {
+    description: String,
+    usage: String,
+    handler: CommandHandler,
+}
+
+struct CVar {
+    value: CVarValue,
+    default: CVarValue,
+    description: String,
+}
+
+/// Kind of a line in the console output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLineKind {
+    /// A command entered by user.
+    Input,
+    /// An output of a command.
+    Output,
+    /// An error.
+    Error,
+}
+
+/// A line in the console output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleLine {
+    /// Kind of the line.
+    pub kind: ConsoleLineKind,
+    /// Text of the line.
+    pub text: String,
+}
+
+#[derive(Default)]
+struct ConsoleState {
+    commands: BTreeMap<String, Command>,
+    cvars: BTreeMap<String, CVar>,
+    history: Vec<String>,
+    output: Vec<ConsoleLine>,
+    revision: u64,
+}
+
+impl ConsoleState {
+    fn push_line(&mut self, kind: ConsoleLineKind, text: String) {
+        if self.output.len() >= MAX_OUTPUT_LINES {
+            self.output.remove(0);
+        }
+        self.output.push(ConsoleLine { kind, text });
+        self.revision += 1;
+    }
+}
+
+/// Splits a line into a set of commands (separated by `;`), every command is split into a set of
+/// tokens (separated by whitespace, quoted tokens may contain whitespaces and `;`).
+fn tokenize(line: &str) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut has_token = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_token = true;
+            }
+            _ if quoted => token.push(c),
+            ';' => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut token));
+                    has_token = false;
+                }
+                if !tokens.is_empty() {
+                    commands.push(std::mem::take(&mut tokens));
+                }
+            }
+            _ if c.is_whitespace() => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut token));
+                    has_token = false;
+                }
+            }
+            _ => {
+                token.push(c);
+                has_token = true;
+            }
+        }
+    }
+
+    if has_token {
+        tokens.push(token);
+    }
+    if !tokens.is_empty() {
+        commands.push(tokens);
+    }
+
+    commands
+}
+
+/// Developer console. The console is shared - every clone of it refers to the same commands,
+/// cvars and output. See module docs for more info.
+#[derive(Clone)]
+pub struct Console {
+    state: Arc<Mutex<ConsoleState>>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    /// Creates new console with a set of built-in commands (`help`, `echo`, `clear`, `exec`,
+    /// `cvars`, `reset`, `history`).
+    pub fn new() -> Self {
+        let console = Self {
+            state: Default::default(),
+        };
+        console.register_builtin_commands();
+        console
+    }
+
+    fn register_builtin_commands(&self) {
+        self.register_command(
+            "help",
+            "Prints a list of commands or description of a command or cvar.",
+            "help [name]",
+            |args, console| {
+                let state = console.state.lock();
+                let mut lines = Vec::new();
+                if let Some(name) = args.get(0) {
+                    if let Some(command) = state.commands.get(name) {
+                        lines.push(format!("{} - {}", command.usage, command.description));
+                    } else if let Some(cvar) = state.cvars.get(name) {
+                        lines.push(format!(
+                            "{} = {} (default {}) - {}",
+                            name, cvar.value, cvar.default, cvar.description
+                        ));
+                    } else {
+                        return Err(format!("Unknown command or cvar {}", name));
+                    }
+                } else {
+                    for (name, command) in state.commands.iter() {
+                        lines.push(format!("{} - {}", name, command.description));
+                    }
+                }
+                drop(state);
+                for line in lines {
+                    console.print(line);
+                }
+                Ok(())
+            },
+        );
+        self.register_command(
+            "echo",
+            "Prints its arguments.",
+            "echo <text>",
+            |args, console| {
+                console.print(args.iter().collect::<Vec<_>>().join(" "));
+                Ok(())
+            },
+        );
+        self.register_command("clear", "Clears console output.", "clear", |_, console| {
+            console.clear_output();
+            Ok(())
+        });
+        self.register_command(
+            "exec",
+            "Executes commands from a file.",
+            "exec <path>",
+            |args, console| {
+                let path = args.parse::<String>(0)?;
+                console.execute_file(path).map(|_| ())
+            },
+        );
+        self.register_command(
+            "cvars",
+            "Prints every cvar with its value.",
+            "cvars",
+            |_, console| {
+                let lines = console
+                    .state
+                    .lock()
+                    .cvars
+                    .iter()
+                    .map(|(name, cvar)| format!("{} = {}", name, cvar.value))
+                    .collect::<Vec<_>>();
+                for line in lines {
+                    console.print(line);
+                }
+                Ok(())
+            },
+        );
+        self.register_command(
+            "reset",
+            "Resets a cvar to its default value.",
+            "reset <cvar>",
+            |args, console| {
+                let name = args.parse::<String>(0)?;
+                let default = console
+                    .state
+                    .lock()
+                    .cvars
+                    .get(&name)
+                    .map(|cvar| cvar.default.clone())
+                    .ok_or_else(|| format!("Unknown cvar {}", name))?;
+                console.set_cvar(&name, default)
+            },
+        );
+        self.register_command(
+            "history",
+            "Prints command history.",
+            "history",
+            |_, console| {
+                for line in console.history() {
+                    console.print(line);
+                }
+                Ok(())
+            },
+        );
+    }
+
+    /// Registers new command (or replaces existing one with the same name). `usage` is a short
+    /// description of arguments (for example `spawn <monster> [count]`), it is printed by `help`.
+    pub fn register_command<F>(&self, name: &str, description: &str, usage: &str, handler: F)
+    where
+        F: Fn(&CommandArgs, &Console) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.state.lock().commands.insert(
+            name.to_owned(),
+            Command {
+                description: description.to_owned(),
+                usage: usage.to_owned(),
+                handler: Arc::new(handler),
+            },
+        );
+    }
+
+    /// Removes a command, returns `true` if the command existed.
+    pub fn unregister_command(&self, name: &str) -> bool {
+        self.state.lock().commands.remove(name).is_some()
+    }
+
+    /// Returns `true` if there is a command with the given name.
+    pub fn has_command(&self, name: &str) -> bool {
+        self.state.lock().commands.contains_key(name)
+    }
+
+    /// Registers new cvar with the given default value. If the cvar already exists and has the
+    /// same type, its current value is kept (it could be set by a startup script before the cvar
+    /// was registered by a plugin), otherwise the value is replaced.
+    pub fn register_cvar<V: Into<CVarValue>>(&self, name: &str, value: V, description: &str) {
+        let value = value.into();
+        let mut state = self.state.lock();
+        match state.cvars.get_mut(name) {
+            Some(cvar) if cvar.value.type_name() == value.type_name() => {
+                cvar.default = value;
+                cvar.description = description.to_owned();
+            }
+            _ => {
+                state.cvars.insert(
+                    name.to_owned(),
+                    CVar {
+                        value: value.clone(),
+                        default: value,
+                        description: description.to_owned(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Removes a cvar, returns `true` if the cvar existed.
+    pub fn unregister_cvar(&self, name: &str) -> bool {
+        self.state.lock().cvars.remove(name).is_some()
+    }
+
+    /// Returns a value of a cvar.
+    pub fn cvar(&self, name: &str) -> Option<CVarValue> {
+        self.state.lock().cvars.get(name).map(|c| c.value.clone())
+    }
+
+    /// Returns a value of a boolean cvar.
+    pub fn cvar_bool(&self, name: &str) -> Option<bool> {
+        match self.cvar(name)? {
+            CVarValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns a value of an integer cvar.
+    pub fn cvar_integer(&self, name: &str) -> Option<i64> {
+        match self.cvar(name)? {
+            CVarValue::Integer(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns a value of a numeric (integer or float) cvar.
+    pub fn cvar_float(&self, name: &str) -> Option<f64> {
+        match self.cvar(name)? {
+            CVarValue::Float(v) => Some(v),
+            CVarValue::Integer(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns a value of a string cvar.
+    pub fn cvar_string(&self, name: &str) -> Option<String> {
+        match self.cvar(name)? {
+            CVarValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Sets new value of a cvar. The value must have the same type as the cvar.
+    pub fn set_cvar<V: Into<CVarValue>>(&self, name: &str, value: V) -> Result<(), String> {
+        let value = value.into();
+        let mut state = self.state.lock();
+        let cvar = state
+            .cvars
+            .get_mut(name)
+            .ok_or_else(|| format!("Unknown cvar {}", name))?;
+        if cvar.value.type_name() != value.type_name() {
+            return Err(format!(
+                "Cvar {} has {} type, got {}",
+                name,
+                cvar.value.type_name(),
+                value.type_name()
+            ));
+        }
+        cvar.value = value;
+        state.revision += 1;
+        Ok(())
+    }
+
+    /// Prints a line into the console output.
+    pub fn print<S: Into<String>>(&self, text: S) {
+        self.state
+            .lock()
+            .push_line(ConsoleLineKind::Output, text.into());
+    }
+
+    /// Prints an error line into the console output.
+    pub fn print_error<S: Into<String>>(&self, text: S) {
+        self.state
+            .lock()
+            .push_line(ConsoleLineKind::Error, text.into());
+    }
+
+    /// Returns a copy of the console output.
+    pub fn output(&self) -> Vec<ConsoleLine> {
+        self.state.lock().output.clone()
+    }
+
+    /// Clears the console output.
+    pub fn clear_output(&self) {
+        let mut state = self.state.lock();
+        state.output.clear();
+        state.revision += 1;
+    }
+
+    /// Returns a copy of the command history, oldest commands go first.
+    pub fn history(&self) -> Vec<String> {
+        self.state.lock().history.clone()
+    }
+
+    /// Returns a number, that is changed every time when the output or cvars are changed.
+    pub fn revision(&self) -> u64 {
+        self.state.lock().revision
+    }
+
+    /// Executes a line entered by a user. The line is added to the history and the output. Errors
+    /// are printed to the output and returned.
+    pub fn execute(&self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        {
+            let mut state = self.state.lock();
+            if state.history.last().map(|l| l.as_str()) != Some(line) {
+                if state.history.len() >= MAX_HISTORY_LENGTH {
+                    state.history.remove(0);
+                }
+                state.history.push(line.to_owned());
+            }
+            state.push_line(ConsoleLineKind::Input, format!("> {}", line));
+        }
+
+        let result = self.execute_silent(line);
+        if let Err(error) = result.as_ref() {
+            self.print_error(error.clone());
+        }
+        result
+    }
+
+    /// Executes a line without adding it to the history and the output. Output of commands is
+    /// still printed. Execution stops at the first failed command.
+    pub fn execute_silent(&self, line: &str) -> Result<(), String> {
+        for mut tokens in tokenize(line) {
+            let name = tokens.remove(0);
+            let args = CommandArgs { args: tokens };
+
+            // The handler is called with unlocked state, so it can use the console freely.
+            let handler = self
+                .state
+                .lock()
+                .commands
+                .get(&name)
+                .map(|c| c.handler.clone());
+
+            if let Some(handler) = handler {
+                handler(&args, self)?;
+            } else if let Some(value) = self.cvar(&name) {
+                match args.get(0) {
+                    Some(arg) => self.set_cvar(&name, value.parse_same_type(arg)?)?,
+                    None => self.print(format!("{} = {}", name, value)),
+                }
+            } else {
+                return Err(format!("Unknown command or cvar {}", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes every line of a script, empty lines and lines starting with `#` are ignored.
+    /// Errors are printed into the output, returns amount of failed lines.
+    pub fn execute_script(&self, source: &str) -> usize {
+        let mut errors = 0;
+        for line in source.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(error) = self.execute_silent(line) {
+                self.print_error(error);
+                errors += 1;
+            }
+        }
+        errors
+    }
+
+    /// Executes a script from a file, see [`Self::execute_script`] for more info. Returns amount
+    /// of failed lines.
+    pub fn execute_file<P: AsRef<Path>>(&self, path: P) -> Result<usize, String> {
+        let source = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Unable to read {}: {}", path.as_ref().display(), e))?;
+        Ok(self.execute_script(&source))
+    }
+
+    /// Returns sorted names of every command and cvar, that starts with the given prefix.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let state = self.state.lock();
+        let mut names = state
+            .commands
+            .keys()
+            .chain(state.cvars.keys())
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Completes the name of the last command in the line. If there is a single candidate, its
+    /// full name is inserted (followed by a space), otherwise the line is extended to the longest
+    /// common prefix of all candidates. Returns new line and the list of candidates.
+    pub fn autocomplete(&self, line: &str) -> (String, Vec<String>) {
+        let start = line.rfind(';').map(|i| i + 1).unwrap_or(0);
+        let (head, command) = line.split_at(start);
+        let prefix = command.trim_start();
+
+        // Only names of commands are completed, not arguments.
+        if prefix.contains(char::is_whitespace) {
+            return (line.to_owned(), Vec::new());
+        }
+
+        let candidates = self.complete(prefix);
+        let completed = match candidates.as_slice() {
+            [] => prefix.to_owned(),
+            [single] => format!("{} ", single),
+            [first, rest @ ..] => {
+                let mut common = first.clone();
+                for candidate in rest {
+                    while !candidate.starts_with(common.as_str()) {
+                        common.pop();
+                    }
+                }
+                common
+            }
+        };
+
+        let whitespace = &command[..command.len() - prefix.len()];
+        (format!("{}{}{}", head, whitespace, completed), candidates)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::console::{tokenize, CVarValue, Console, ConsoleLineKind};
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#"echo "a; b" c; set  x 1;;"#),
+            vec![
+                vec!["echo".to_string(), "a; b".to_string(), "c".to_string()],
+                vec!["set".to_string(), "x".to_string(), "1".to_string()],
+            ]
+        );
+        assert_eq!(
+            tokenize(r#"echo """#),
+            vec![vec!["echo".to_string(), "".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_commands_and_cvars() {
+        let console = Console::new();
+        console.register_cvar("speed", 1.5, "Player speed.");
+        console.register_cvar("name", "Player", "Player name.");
+        console.register_command(
+            "add",
+            "Adds two numbers.",
+            "add <a> <b>",
+            |args, console| {
+                let sum = args.parse::<i32>(0)? + args.parse::<i32>(1)?;
+                console.print(sum.to_string());
+                Ok(())
+            },
+        );
+
+        console
+            .execute("add 2 3; speed 2.5; name \"John Doe\"")
+            .unwrap();
+        assert_eq!(console.cvar_float("speed"), Some(2.5));
+        assert_eq!(
+            console.cvar("name"),
+            Some(CVarValue::String("John Doe".to_string()))
+        );
+        assert_eq!(console.output().last().unwrap().text, "5");
+
+        assert!(console.execute("add 2 x").is_err());
+        assert!(console.execute("speed fast").is_err());
+        assert!(console.execute("unknown").is_err());
+        assert_eq!(
+            console.output().last().unwrap().kind,
+            ConsoleLineKind::Error
+        );
+
+        console.execute("reset speed").unwrap();
+        assert_eq!(console.cvar_float("speed"), Some(1.5));
+
+        // Re-registration keeps current value of the same type.
+        console.execute("name Bob").unwrap();
+        console.register_cvar("name", "Player", "Player name.");
+        assert_eq!(console.cvar_string("name"), Some("Bob".to_string()));
+
+        assert_eq!(console.history().len(), 6);
+    }
+
+    #[test]
+    fn test_autocomplete_and_scripts() {
+        let console = Console::new();
+        console.register_cvar("r_shadows", true, "");
+        console.register_cvar("r_ssao", true, "");
+
+        assert_eq!(console.autocomplete("r_").0, "r_s");
+        assert_eq!(console.autocomplete("r_sh").0, "r_shadows ");
+        assert_eq!(console.autocomplete("echo 1; r_ss").0, "echo 1; r_ssao ");
+        assert_eq!(console.autocomplete("echo r_").0, "echo r_");
+
+        let errors = console.execute_script("# Settings\nr_shadows off\n\nr_ssao maybe\n");
+        assert_eq!(errors, 1);
+        assert_eq!(console.cvar_bool("r_shadows"), Some(false));
+        assert_eq!(console.cvar_bool("r_ssao"), Some(true));
+        assert!(console.history().is_empty());
+    }
+}
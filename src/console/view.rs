@@ -0,0 +1,281 @@
+//! Drop-down overlay for the developer console. See [`ConsoleView`] docs for more info.
+
+use crate::{
+    console::{Console, ConsoleLineKind},
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{KeyCode, MessageDirection, UiMessage},
+        overlay::OverlayLayer,
+        scroll_bar::ScrollBarMessage,
+        scroll_viewer::{ScrollViewer, ScrollViewerBuilder},
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBox, TextBoxBuilder, TextCommitMode},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// A drop-down console overlay at the top of the screen. The overlay is hidden by default and
+/// toggled by a key (`` ` `` by default). It shows output of the console and has an input line,
+/// that supports command history (`Up`/`Down` keys) and autocompletion (`Tab` key).
+///
+/// The view must be fed with every UI message (see [`Self::handle_ui_message`]) and updated every
+/// frame (see [`Self::update`]):
+///
+/// ```rust,no_run
+/// use fyrox::{console::view::ConsoleView, engine::Engine};
+///
+/// fn create(engine: &mut Engine) -> ConsoleView {
+///     let console = engine.console.clone();
+///     ConsoleView::new(console, &mut engine.user_interface.build_ctx())
+/// }
+///
+/// fn update(view: &mut ConsoleView, engine: &mut Engine) {
+///     while let Some(message) = engine.user_interface.poll_message() {
+///         view.handle_ui_message(&message, &mut engine.user_interface);
+///     }
+///     view.update(&mut engine.user_interface);
+/// }
+/// ```
+pub struct ConsoleView {
+    console: Console,
+    root: Handle<UiNode>,
+    scroll_viewer: Handle<UiNode>,
+    output: Handle<UiNode>,
+    input: Handle<UiNode>,
+    toggle_key: KeyCode,
+    visible: bool,
+    history_index: Option<usize>,
+    revision: Option<u64>,
+    scroll_to_end: bool,
+}
+
+impl ConsoleView {
+    /// Height of the overlay in pixels.
+    pub const HEIGHT: f32 = 300.0;
+
+    /// Creates new console view and adds it to the notifications layer of the user interface.
+    pub fn new(console: Console, ctx: &mut BuildContext) -> Self {
+        let output = TextBuilder::new(WidgetBuilder::new())
+            .with_wrap(WrapMode::Word)
+            .build(ctx);
+        let scroll_viewer = ScrollViewerBuilder::new(WidgetBuilder::new().on_row(0))
+            .with_content(output)
+            .build(ctx);
+        let input = TextBoxBuilder::new(WidgetBuilder::new().on_row(1))
+            .with_text_commit_mode(TextCommitMode::LostFocusPlusEnter)
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            // Typed toggle key must not appear in the input line.
+            .with_filter(Rc::new(RefCell::new(|c: char| c != '`' && c != '~')))
+            .build(ctx);
+        let grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_child(scroll_viewer)
+                .with_child(input),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::strict(24.0))
+        .add_column(Column::stretch())
+        .build(ctx);
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_height(Self::HEIGHT)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::from_rgba(20, 20, 20, 220)))
+                .with_child(grid),
+        )
+        .build(ctx);
+        let layer = ctx.layer(OverlayLayer::Notifications);
+        ctx.link(root, layer);
+
+        Self {
+            console,
+            root,
+            scroll_viewer,
+            output,
+            input,
+            toggle_key: KeyCode::Grave,
+            visible: false,
+            history_index: None,
+            revision: None,
+            scroll_to_end: false,
+        }
+    }
+
+    /// Sets a key, that toggles visibility of the console.
+    pub fn set_toggle_key(&mut self, key: KeyCode) {
+        self.toggle_key = key;
+    }
+
+    /// Returns a key, that toggles visibility of the console.
+    pub fn toggle_key(&self) -> KeyCode {
+        self.toggle_key
+    }
+
+    /// Returns a handle of the root widget of the overlay.
+    pub fn root(&self) -> Handle<UiNode> {
+        self.root
+    }
+
+    /// Returns the console shown by the view.
+    pub fn console(&self) -> &Console {
+        &self.console
+    }
+
+    /// Returns `true` if the console is shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the console. Shown console takes keyboard focus.
+    pub fn set_visible(&mut self, visible: bool, ui: &UserInterface) {
+        self.visible = visible;
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+        if visible {
+            ui.send_message(WidgetMessage::topmost(
+                self.root,
+                MessageDirection::ToWidget,
+            ));
+            ui.send_message(WidgetMessage::focus(self.input, MessageDirection::ToWidget));
+            self.scroll_to_end = true;
+        } else {
+            ui.send_message(WidgetMessage::unfocus(
+                self.input,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    fn set_input(&self, text: String, ui: &UserInterface) {
+        ui.send_message(TextMessage::text(
+            self.input,
+            MessageDirection::ToWidget,
+            text,
+        ));
+        // Move caret to the end of the new text.
+        ui.send_message(WidgetMessage::key_down(
+            self.input,
+            MessageDirection::ToWidget,
+            KeyCode::End,
+        ));
+    }
+
+    fn input_text(&self, ui: &UserInterface) -> String {
+        ui.node(self.input)
+            .cast::<TextBox>()
+            .map(|text_box| text_box.text())
+            .unwrap_or_default()
+    }
+
+    /// Handles UI messages: toggles the console, executes entered commands, navigates through the
+    /// history and autocompletes input.
+    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &mut UserInterface) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(&WidgetMessage::KeyDown(key)) = message.data() {
+            if key == self.toggle_key {
+                self.set_visible(!self.visible, ui);
+                return;
+            }
+
+            if !self.visible || message.destination() != self.input {
+                return;
+            }
+
+            match key {
+                KeyCode::Return | KeyCode::NumpadEnter => {
+                    let line = self.input_text(ui);
+                    // Errors are printed to the output by the console.
+                    let _ = self.console.execute(&line);
+                    self.history_index = None;
+                    self.set_input(String::new(), ui);
+                    self.scroll_to_end = true;
+                }
+                KeyCode::Up | KeyCode::Down => {
+                    let history = self.console.history();
+                    if history.is_empty() {
+                        return;
+                    }
+                    self.history_index = match (key, self.history_index) {
+                        (KeyCode::Up, None) => Some(history.len() - 1),
+                        (KeyCode::Up, Some(index)) => Some(index.saturating_sub(1)),
+                        (_, Some(index)) if index + 1 < history.len() => Some(index + 1),
+                        _ => None,
+                    };
+                    let text = self
+                        .history_index
+                        .map(|index| history[index].clone())
+                        .unwrap_or_default();
+                    self.set_input(text, ui);
+                }
+                KeyCode::Tab => {
+                    let (completed, candidates) = self.console.autocomplete(&self.input_text(ui));
+                    if candidates.len() > 1 {
+                        self.console.print(candidates.join("  "));
+                    }
+                    self.set_input(completed, ui);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Refreshes the output and adjusts size of the overlay to the screen. Must be called every
+    /// frame.
+    pub fn update(&mut self, ui: &mut UserInterface) {
+        let width = ui.screen_size().x;
+        if ui.node(self.root).width() != width {
+            ui.send_message(WidgetMessage::width(
+                self.root,
+                MessageDirection::ToWidget,
+                width,
+            ));
+        }
+
+        // Scroll bar bounds are updated on layout, so scrolling is done one frame later than
+        // the output has changed.
+        if self.scroll_to_end {
+            self.scroll_to_end = false;
+            if let Some(scroll_viewer) = ui.node(self.scroll_viewer).cast::<ScrollViewer>() {
+                ui.send_message(ScrollBarMessage::value(
+                    scroll_viewer.v_scroll_bar,
+                    MessageDirection::ToWidget,
+                    f32::MAX,
+                ));
+            }
+        }
+
+        let revision = self.console.revision();
+        if self.revision != Some(revision) {
+            self.revision = Some(revision);
+            let text = self
+                .console
+                .output()
+                .into_iter()
+                .map(|line| match line.kind {
+                    ConsoleLineKind::Error => format!("Error: {}", line.text),
+                    _ => line.text,
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.send_message(TextMessage::text(
+                self.output,
+                MessageDirection::ToWidget,
+                text,
+            ));
+            self.scroll_to_end = true;
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Crash handler. It installs a panic hook, that writes a crash report to disk, so bug reports of
+//! users contain actionable data.
+//!
+//! A report is a folder (`crash_reports/crash-<unix time>` by default) with `report.txt` file,
+//! that contains the panic message and its location, engine and renderer info, a summary of every
+//! scene and last messages of the log. If scene snapshots are enabled (see
+//! [`CrashHandlerConfig::snapshot_interval`]), the folder also contains last snapshot of every
+//! scene (`scene-<index>.rgs`), that could be opened in the editor.
+//!
+//! The panic hook cannot access the engine, so the handler collects the data every frame (scene
+//! summaries and renderer info) or periodically (snapshots) when [`crate::engine::Engine::update`]
+//! is called. The crash handler is disabled by default, use
+//! [`crate::engine::Engine::enable_crash_handler`] to enable it.
+//!
+//! The previous panic hook is still called after the report is written, so the panic message is
+//! printed to stderr as usual. The crash handler is not available on WebAssembly.
+
+use crate::{
+    core::{parking_lot::Mutex, visitor::Visitor},
+    renderer::Renderer,
+    scene::SceneContainer,
+    utils::log::{Log, LogSinkId, MemorySink},
+};
+use std::{
+    fmt::Write,
+    panic::PanicInfo,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Name of a file in the report folder, that holds path of the last crash report. It is used to
+/// find out if the application has crashed on previous run (see [`CrashHandler::take_last_report`]).
+pub const LAST_REPORT_FILE_NAME: &str = "last_crash.txt";
+
+/// Name of the main file of a report.
+pub const REPORT_FILE_NAME: &str = "report.txt";
+
+/// Crash handler settings.
+#[derive(Clone, Debug)]
+pub struct CrashHandlerConfig {
+    /// A folder, where reports will be written.
+    pub report_dir: PathBuf,
+    /// Amount of last log messages, that will be written to a report.
+    pub log_tail_len: usize,
+    /// Interval (in seconds) between scene snapshots. Snapshot is a serialized copy of a scene,
+    /// it is a heavy operation for large scenes, so the snapshots are disabled by default (`None`).
+    pub snapshot_interval: Option<f32>,
+}
+
+impl Default for CrashHandlerConfig {
+    fn default() -> Self {
+        Self {
+            report_dir: PathBuf::from("crash_reports"),
+            log_tail_len: 256,
+            snapshot_interval: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct CrashContext {
+    engine_info: String,
+    scenes_summary: String,
+    snapshots: Vec<Vec<u8>>,
+}
+
+/// See module docs.
+pub struct CrashHandler {
+    config: CrashHandlerConfig,
+    context: Arc<Mutex<CrashContext>>,
+    enabled: Arc<AtomicBool>,
+    log_sink: LogSinkId,
+    snapshot_timer: f32,
+    renderer_info: Option<String>,
+}
+
+fn panic_message(info: &PanicInfo) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic payload".to_string()
+    }
+}
+
+fn write_report(
+    config: &CrashHandlerConfig,
+    context: &Mutex<CrashContext>,
+    log: &MemorySink,
+    info: &PanicInfo,
+) -> std::io::Result<PathBuf> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let dir = config.report_dir.join(format!("crash-{}", time));
+    std::fs::create_dir_all(&dir)?;
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Fyrox crash report");
+    let _ = writeln!(report, "Engine version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {} (unix)", time);
+    let _ = writeln!(
+        report,
+        "Platform: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(
+        report,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+    let _ = writeln!(report, "Panic: {}", panic_message(info));
+    if let Some(location) = info.location() {
+        let _ = writeln!(
+            report,
+            "Location: {}:{}:{}",
+            location.file(),
+            location.line(),
+            location.column()
+        );
+    }
+
+    // The panic could happen while the context is locked (for example in the middle of a
+    // snapshot), do not deadlock in this case.
+    match context.try_lock() {
+        Some(mut context) => {
+            let _ = write!(report, "\n== Engine ==\n{}", context.engine_info);
+            let _ = write!(report, "\n== Scenes ==\n{}", context.scenes_summary);
+
+            for (index, snapshot) in context.snapshots.drain(..).enumerate() {
+                std::fs::write(dir.join(format!("scene-{}.rgs", index)), snapshot)?;
+            }
+        }
+        None => {
+            let _ = writeln!(report, "\nEngine state is unavailable.");
+        }
+    }
+
+    let messages = log.messages();
+    let _ = writeln!(report, "\n== Log (last {} messages) ==", messages.len());
+    for message in messages {
+        let _ = writeln!(report, "{}", message.format());
+    }
+
+    std::fs::write(dir.join(REPORT_FILE_NAME), report)?;
+    std::fs::write(
+        config.report_dir.join(LAST_REPORT_FILE_NAME),
+        dir.to_string_lossy().as_bytes(),
+    )?;
+
+    Ok(dir)
+}
+
+impl CrashHandler {
+    /// Installs panic hook, that writes crash reports using the given config. The hook is active
+    /// while the handler is alive.
+    pub fn install(config: CrashHandlerConfig) -> Self {
+        let log = MemorySink::new(config.log_tail_len);
+        let log_sink = Log::add_sink(Box::new(log.clone()));
+        let context = Arc::new(Mutex::new(CrashContext::default()));
+        let enabled = Arc::new(AtomicBool::new(true));
+
+        let previous_hook = std::panic::take_hook();
+        let hook_config = config.clone();
+        let hook_context = context.clone();
+        let hook_enabled = enabled.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            if hook_enabled.load(Ordering::SeqCst) {
+                // Log is not used here, because the panic may happen while it is locked.
+                match write_report(&hook_config, &hook_context, &log, info) {
+                    Ok(path) => eprintln!("Crash report was written to {}", path.display()),
+                    Err(err) => eprintln!("Unable to write crash report. Reason: {}", err),
+                }
+            }
+            previous_hook(info);
+        }));
+
+        Self {
+            config,
+            context,
+            enabled,
+            log_sink,
+            snapshot_timer: 0.0,
+            renderer_info: None,
+        }
+    }
+
+    /// Returns current config of the handler.
+    pub fn config(&self) -> &CrashHandlerConfig {
+        &self.config
+    }
+
+    /// Collects engine data for a possible crash report. It is called automatically by the engine
+    /// every frame.
+    pub fn update(&mut self, renderer: &mut Renderer, scenes: &mut SceneContainer, dt: f32) {
+        let renderer_info = self.renderer_info.get_or_insert_with(|| {
+            use glow::HasContext;
+            let gl = &renderer.pipeline_state().gl;
+            unsafe {
+                format!(
+                    "GL vendor: {}\nGL renderer: {}\nGL version: {}\n",
+                    gl.get_parameter_string(glow::VENDOR),
+                    gl.get_parameter_string(glow::RENDERER),
+                    gl.get_parameter_string(glow::VERSION)
+                )
+            }
+        });
+
+        let mut engine_info = renderer_info.clone();
+        let _ = write!(engine_info, "{}", renderer.get_statistics());
+
+        let mut scenes_summary = String::new();
+        for (handle, scene) in scenes.pair_iter() {
+            let graph = &scene.graph;
+            let scripted = graph
+                .pair_iter()
+                .filter(|(_, node)| node.script_instances().next().is_some())
+                .count();
+            let _ = writeln!(
+                scenes_summary,
+                "Scene {}: enabled: {}, nodes: {}, scripted nodes: {}",
+                handle,
+                scene.enabled,
+                graph.node_count(),
+                scripted
+            );
+            for &child in graph[graph.get_root()].children() {
+                let _ = writeln!(scenes_summary, "  - {}", graph[child].name());
+            }
+        }
+
+        let mut snapshots = None;
+        if let Some(interval) = self.config.snapshot_interval {
+            self.snapshot_timer -= dt;
+            if self.snapshot_timer <= 0.0 {
+                self.snapshot_timer = interval;
+                snapshots = Some(Self::capture_snapshots(scenes));
+            }
+        }
+
+        let mut context = self.context.lock();
+        context.engine_info = engine_info;
+        context.scenes_summary = scenes_summary;
+        if let Some(snapshots) = snapshots {
+            context.snapshots = snapshots;
+        }
+    }
+
+    fn capture_snapshots(scenes: &mut SceneContainer) -> Vec<Vec<u8>> {
+        let mut snapshots = Vec::new();
+        for scene in scenes.iter_mut() {
+            let mut visitor = Visitor::new();
+            match scene
+                .save("Scene", &mut visitor)
+                .and_then(|_| visitor.save_binary_to_vec())
+            {
+                Ok(data) => snapshots.push(data),
+                Err(err) => Log::err(format!(
+                    "Unable to capture scene snapshot for crash report. Reason: {:?}",
+                    err
+                )),
+            }
+        }
+        snapshots
+    }
+
+    /// Returns path of the report, that was written on previous run of the application (if any).
+    /// The information is removed, so the next call will return `None`. Could be used to show a
+    /// message to a user after a crash.
+    pub fn take_last_report<P: AsRef<Path>>(report_dir: P) -> Option<PathBuf> {
+        let marker = report_dir.as_ref().join(LAST_REPORT_FILE_NAME);
+        let path = PathBuf::from(std::fs::read_to_string(&marker).ok()?.trim());
+        let _ = std::fs::remove_file(marker);
+        if path.exists() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for CrashHandler {
+    fn drop(&mut self) {
+        // The hook cannot be safely removed (other hooks could be installed after it), so it is
+        // just disabled.
+        self.enabled.store(false, Ordering::SeqCst);
+        Log::remove_sink(self.log_sink);
+    }
+}
@@ -0,0 +1,250 @@
+//! Deterministic simulation mode. It is a foundation for lockstep multiplayer and replay systems:
+//! two engines, that receive the same input in the same frames, produce the same state.
+//!
+//! When the mode is enabled (see [`crate::engine::Engine::set_deterministic_mode`]) the engine:
+//!
+//! - seeds random numbers of the main thread (see [`crate::core::random`]), so particle systems
+//! and scripts, that use [`crate::core::random::with_rng`], produce the same values;
+//! - forces fixed time step for every update, a different time step passed to
+//! [`crate::engine::Engine::update`] is replaced with the fixed one;
+//! - calculates a hash of the state of every scene after each update and stores it in
+//! [`FrameHashHistory`], so the hashes could be exchanged between peers (or stored in a replay)
+//! and a divergence could be detected as soon as possible.
+//!
+//! Scripts are always updated in a stable order - scenes in the order of their handles, nodes in
+//! the order of their handles and scripts of a node in the order of their appearance; plugins in
+//! the order of their registration. Physics is deterministic on the same platform if bodies are
+//! created in the same order.
+//!
+//! Keep in mind that random numbers of other threads are not seeded, and that game code must not
+//! use wall clock time or other non-deterministic data to affect the simulation.
+
+use crate::scene::{dim2, rigidbody::RigidBody, Scene, SceneContainer};
+use std::{collections::VecDeque, hash::Hasher};
+
+/// Parameters of deterministic simulation mode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeterministicSettings {
+    /// Seed of random numbers.
+    pub seed: u64,
+    /// Time step (in seconds) of every update.
+    pub fixed_dt: f32,
+}
+
+impl Default for DeterministicSettings {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            fixed_dt: 1.0 / 60.0,
+        }
+    }
+}
+
+/// 64-bit FNV-1a hasher. Unlike the hashers of the standard library, it produces the same values
+/// on every platform and every run, so the hashes could be compared between different machines.
+#[derive(Clone, Debug)]
+pub struct StateHasher {
+    state: u64,
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self {
+            state: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+}
+
+impl StateHasher {
+    /// Writes bits of the given float to the hasher. Negative and positive zeros are considered
+    /// equal.
+    pub fn write_f32(&mut self, value: f32) {
+        let value = if value == 0.0 { 0.0 } else { value };
+        self.write_u32(value.to_bits());
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    // Integers are written in little-endian order, so the hash does not depend on platform.
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+}
+
+/// Writes the state of the given scene to the hasher: handles and global transforms of every node
+/// and velocities of every rigid body.
+pub fn hash_scene(scene: &Scene, hasher: &mut StateHasher) {
+    for (handle, node) in scene.graph.pair_iter() {
+        hasher.write_u32(handle.index());
+        hasher.write_u32(handle.generation());
+
+        for &value in node.global_transform().iter() {
+            hasher.write_f32(value);
+        }
+
+        if let Some(body) = node.cast::<RigidBody>() {
+            for &value in body.lin_vel().iter().chain(body.ang_vel().iter()) {
+                hasher.write_f32(value);
+            }
+        } else if let Some(body) = node.cast::<dim2::rigidbody::RigidBody>() {
+            for &value in body.lin_vel().iter() {
+                hasher.write_f32(value);
+            }
+            hasher.write_f32(body.ang_vel());
+        }
+    }
+}
+
+/// Calculates a hash of the state of every enabled scene. See [`hash_scene`] for more info.
+pub fn hash_scenes(scenes: &SceneContainer) -> u64 {
+    let mut hasher = StateHasher::default();
+    for (handle, scene) in scenes.pair_iter().filter(|(_, s)| s.enabled) {
+        hasher.write_u32(handle.index());
+        hash_scene(scene, &mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Describes a divergence of two simulations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// A frame, when the divergence was detected.
+    pub frame: u64,
+    /// Local hash of the frame.
+    pub local: u64,
+    /// Remote hash of the frame.
+    pub remote: u64,
+}
+
+/// A fixed-size history of state hashes of the last frames.
+#[derive(Clone, Debug)]
+pub struct FrameHashHistory {
+    hashes: VecDeque<(u64, u64)>,
+    capacity: usize,
+}
+
+impl Default for FrameHashHistory {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl FrameHashHistory {
+    /// Creates new history, that keeps hashes of the given amount of last frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            hashes: Default::default(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Adds a hash of the given frame.
+    pub fn push(&mut self, frame: u64, hash: u64) {
+        if self.hashes.len() == self.capacity {
+            self.hashes.pop_front();
+        }
+        self.hashes.push_back((frame, hash));
+    }
+
+    /// Returns a hash of the given frame, if it is still in the history.
+    pub fn get(&self, frame: u64) -> Option<u64> {
+        self.hashes
+            .iter()
+            .rev()
+            .find(|(f, _)| *f == frame)
+            .map(|(_, hash)| *hash)
+    }
+
+    /// Returns the last frame and its hash.
+    pub fn last(&self) -> Option<(u64, u64)> {
+        self.hashes.back().copied()
+    }
+
+    /// Compares a hash of a frame received from other peer (or from a replay) with the local one.
+    /// Frames, that are not in the history (too old or not simulated yet), are not compared.
+    pub fn check(&self, frame: u64, remote: u64) -> Result<(), Divergence> {
+        match self.get(frame) {
+            Some(local) if local != remote => Err(Divergence {
+                frame,
+                local,
+                remote,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes every hash from the history.
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        engine::determinism::{hash_scene, Divergence, FrameHashHistory, StateHasher},
+        scene::{base::BaseBuilder, pivot::PivotBuilder, transform::TransformBuilder, Scene},
+    };
+    use std::hash::Hasher;
+
+    fn scene_hash(x: f32) -> u64 {
+        let mut scene = Scene::new();
+        PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(x, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+        scene.graph.update_hierarchical_data();
+
+        let mut hasher = StateHasher::default();
+        hash_scene(&scene, &mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_state_hash_and_divergence() {
+        let a = scene_hash(1.0);
+        assert_eq!(a, scene_hash(1.0));
+        let b = scene_hash(2.0);
+        assert_ne!(a, b);
+
+        let mut history = FrameHashHistory::new(2);
+        history.push(1, a);
+        history.push(2, a);
+        history.push(3, b);
+        assert_eq!(history.get(1), None);
+        assert_eq!(history.check(2, a), Ok(()));
+        assert_eq!(
+            history.check(3, a),
+            Err(Divergence {
+                frame: 3,
+                local: b,
+                remote: a
+            })
+        );
+    }
+}
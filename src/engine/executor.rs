@@ -1,4 +1,12 @@
 //! Executor is a small wrapper that manages plugins and scripts for your game.
+//!
+//! # Frame pacing
+//!
+//! Executor renders frames according to [`FramePacingSettings`] (stored in [`Engine::settings`]).
+//! When there's a frame rate limit (the FPS cap when vertical synchronization is disabled, or the
+//! background frame rate when the window is unfocused or minimized), the executor sleeps until the
+//! next frame instead of busy-waiting. Game logic is still updated with the fixed time step, so
+//! throttling does not affect the simulation speed.
 
 use crate::{
     core::instant::Instant,
@@ -7,6 +15,7 @@ use crate::{
     event_loop::{ControlFlow, EventLoop},
     plugin::PluginConstructor,
     scene::loader::AsyncSceneLoader,
+    settings::FramePacingSettings,
     utils::{
         log::{Log, MessageKind},
         translate_event,
@@ -17,6 +26,7 @@ use clap::Parser;
 use std::{
     ops::{Deref, DerefMut},
     sync::Arc,
+    time::Duration,
 };
 
 #[derive(Parser, Debug)]
@@ -131,6 +141,9 @@ impl Executor {
         let mut previous = Instant::now();
         let fixed_time_step = 1.0 / self.desired_update_rate;
         let mut lag = 0.0;
+        let mut next_frame = Instant::now();
+        let mut focused = true;
+        let mut minimized = false;
 
         event_loop.run(move |event, _, control_flow| {
             if let Some(loader) = self.loader.as_ref() {
@@ -165,6 +178,8 @@ impl Executor {
                 engine.handle_os_event_by_scripts(&event, scene_handle, fixed_time_step);
             }
 
+            let input_blocked = engine.is_input_blocked_by_replay(&event);
+
             match event {
                 Event::MainEventsCleared => {
                     let elapsed = previous.elapsed();
@@ -176,7 +191,36 @@ impl Executor {
                         lag -= fixed_time_step;
                     }
 
-                    engine.get_window().request_redraw();
+                    let frame_interval = engine
+                        .settings
+                        .get::<FramePacingSettings>()
+                        .frame_interval(engine.is_vsync_enabled(), !focused || minimized);
+
+                    let next_control_flow = match frame_interval {
+                        Some(frame_interval) => {
+                            let now = Instant::now();
+                            if now >= next_frame {
+                                engine.get_window().request_redraw();
+
+                                // Keep steady pace, but do not try to catch up if we're too late.
+                                let interval = Duration::from_secs_f32(frame_interval);
+                                next_frame = if next_frame + interval < now {
+                                    now + interval
+                                } else {
+                                    next_frame + interval
+                                };
+                            }
+                            ControlFlow::WaitUntil(next_frame)
+                        }
+                        None => {
+                            engine.get_window().request_redraw();
+                            ControlFlow::Poll
+                        }
+                    };
+
+                    if *control_flow != ControlFlow::Exit {
+                        *control_flow = next_control_flow;
+                    }
                 }
                 Event::RedrawRequested(_) => {
                     engine.render().unwrap();
@@ -184,7 +228,9 @@ impl Executor {
                 Event::WindowEvent { event, .. } => {
                     match event {
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                        WindowEvent::Focused(state) => focused = state,
                         WindowEvent::Resized(size) => {
+                            minimized = size.width == 0 || size.height == 0;
                             if let Err(e) = engine.set_frame_size(size.into()) {
                                 Log::writeln(
                                     MessageKind::Error,
@@ -195,11 +241,13 @@ impl Executor {
                         _ => (),
                     }
 
-                    if let Some(os_event) = translate_event(&event) {
-                        engine.user_interface.process_os_event(&os_event);
+                    if !input_blocked {
+                        if let Some(os_event) = translate_event(&event) {
+                            engine.user_interface.process_os_event(&os_event);
+                        }
                     }
                 }
-                _ => *control_flow = ControlFlow::Poll,
+                _ => (),
             }
         })
     }
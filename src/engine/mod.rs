@@ -3,21 +3,40 @@
 
 #![warn(missing_docs)]
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crash;
+pub mod determinism;
 pub mod error;
 pub mod executor;
+pub mod replay;
 pub mod resource_manager;
+pub mod statistics;
 
 use crate::engine::resource_manager::ResourceWaitContext;
 use crate::{
     asset::ResourceState,
-    core::{algebra::Vector2, futures::executor::block_on, instant, pool::Handle},
+    console::Console,
+    core::{
+        algebra::Vector2,
+        futures::executor::block_on,
+        instant,
+        memory::{self, AllocationScope, Subsystem},
+        pool::Handle,
+    },
+    dpi::PhysicalPosition,
     engine::{
+        determinism::{DeterministicSettings, Divergence, FrameHashHistory},
         error::EngineError,
+        replay::{Replay, ReplayEvent, ReplayPlayer, ReplayRecorder},
         resource_manager::{container::event::ResourceEvent, ResourceManager},
+        statistics::EngineStatistics,
     },
     event::Event,
     event_loop::{ControlFlow, EventLoop},
-    gui::UserInterface,
+    gui::{
+        color::ColorPickerMessage, localization::Localization, message::MessageDirection,
+        UserInterface,
+    },
     plugin::{
         Plugin, PluginConstructor, PluginContext, PluginRegistrationContext, SoundEngineHelper,
     },
@@ -28,12 +47,16 @@ use crate::{
         Scene, SceneContainer,
     },
     script::{constructor::ScriptConstructorContainer, Script, ScriptContext, ScriptDeinitContext},
-    utils::log::Log,
+    settings::{
+        AudioSettings, FramePacingSettings, GraphicsSettings, Settings, SettingsEvent,
+        SettingsSection,
+    },
+    utils::{log::Log, translate_event},
     window::{Window, WindowBuilder},
 };
 use fxhash::FxHashSet;
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{BTreeSet, HashSet, VecDeque},
     sync::{
         mpsc::{channel, Receiver},
         Arc, Mutex,
@@ -77,6 +100,19 @@ pub struct Engine {
     pub renderer: Renderer,
     /// User interface allows you to build interface of any kind.
     pub user_interface: UserInterface,
+    /// Developer console, plugins and scripts could register their commands and cvars in it. The
+    /// console does not have any visual representation by default, use
+    /// [`crate::console::view::ConsoleView`] to show it.
+    pub console: Console,
+    /// Settings of the engine and the game. Graphics and audio sections of the engine are applied
+    /// automatically when changed, see [`crate::settings`] docs for more info.
+    pub settings: Settings,
+    settings_receiver: Receiver<SettingsEvent>,
+    vsync: bool,
+    // Last IME candidate window position passed to the window, `None` means that IME is disabled.
+    ime_position: Option<Vector2<f32>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    crash_handler: Option<crash::CrashHandler>,
     /// Current resource manager. Resource manager can be cloned (it does clone only ref) to be able to
     /// use resource manager from any thread, this is useful to load resources from multiple
     /// threads to decrease loading times of your game by utilizing all available power of
@@ -84,11 +120,30 @@ pub struct Engine {
     pub resource_manager: ResourceManager,
     /// All available scenes in the engine.
     pub scenes: SceneContainer,
-    /// The time user interface took for internal needs. TODO: This is not the right place
-    /// for such statistics, probably it is best to make separate structure to hold all
-    /// such data.
+    /// The time user interface took for internal needs. See also [`Self::statistics`], it
+    /// contains this and many other timings.
     pub ui_time: Duration,
 
+    // Statistics of the last complete frame.
+    statistics: EngineStatistics,
+
+    // Statistics of the frame, that is being processed.
+    frame_statistics: EngineStatistics,
+
+    deterministic_mode: Option<DeterministicSettings>,
+
+    // Amount of updates since deterministic mode was enabled.
+    simulation_frame: u64,
+
+    frame_hashes: FrameHashHistory,
+
+    replay_recorder: Option<ReplayRecorder>,
+
+    replay_player: Option<ReplayPlayer>,
+
+    // Input actions of the current frame of a replay, that is being played back.
+    replay_actions: Vec<(String, f32)>,
+
     model_events_receiver: Receiver<ResourceEvent<Model>>,
 
     // Sound context control all sound sources in the engine. It is wrapped into Arc<Mutex<>>
@@ -117,7 +172,12 @@ pub struct Engine {
 #[derive(Default)]
 struct ScriptProcessor {
     wait_list: Vec<ResourceWaitContext>,
-    scripted_scenes: FxHashSet<Handle<Scene>>,
+    // Ordered set to update scenes in a stable order, it is required for deterministic simulation.
+    scripted_scenes: BTreeSet<Handle<Scene>>,
+    // Scenes, which scripts were disabled because the scene itself was disabled.
+    disabled_scenes: FxHashSet<Handle<Scene>>,
+    // A counter that is used to spread updates of scripts with update interval across frames.
+    update_phase_counter: u32,
 }
 
 impl ScriptProcessor {
@@ -156,6 +216,9 @@ impl ScriptProcessor {
         scenes: &mut SceneContainer,
         plugins: &mut Vec<Box<dyn Plugin>>,
         resource_manager: &ResourceManager,
+        localization: &Localization,
+        console: &Console,
+        settings: &Settings,
         dt: f32,
         elapsed_time: f32,
     ) {
@@ -168,25 +231,62 @@ impl ScriptProcessor {
 
         self.scripted_scenes
             .retain(|handle| scenes.is_valid_handle(*handle));
+        self.disabled_scenes
+            .retain(|handle| scenes.is_valid_handle(*handle));
+
+        let mut update_phase_counter = self.update_phase_counter;
 
         'scene_loop: for &scene_handle in self.scripted_scenes.iter() {
             let scene = &mut scenes[scene_handle];
 
             // Disabled scenes should not update their scripts.
             if !scene.enabled {
+                if self.disabled_scenes.insert(scene_handle) {
+                    let mut context = ScriptContext {
+                        dt,
+                        elapsed_time,
+                        plugins,
+                        handle: Default::default(),
+                        scene,
+                        resource_manager,
+                        localization,
+                        console,
+                        settings,
+                    };
+
+                    for node_index in 0..context.scene.graph.capacity() {
+                        context.handle = context.scene.graph.handle_from_index(node_index);
+
+                        process_node(&mut context, &mut |script, _, context| {
+                            if script.active {
+                                script.active = false;
+                                script.on_disable(context);
+                            }
+                        });
+                    }
+                }
+
                 continue 'scene_loop;
             }
 
+            // Scripts of a scene that was enabled back will be activated on update.
+            self.disabled_scenes.remove(&scene_handle);
+
             // Fill in initial handles to nodes to update.
             let mut update_queue = VecDeque::new();
             for (handle, node) in scene.graph.pair_iter() {
-                if let Some(script) = node.script.as_ref() {
-                    if script.initialized && script.started {
-                        update_queue.push_back(handle);
-                    }
+                if node
+                    .script_instances()
+                    .any(|script| script.initialized && script.started)
+                {
+                    update_queue.push_back(handle);
                 }
             }
 
+            // A node could have multiple scripts, every node must be updated only once per frame.
+            // Scripts that were added to an already updated node will be updated on next frame.
+            let mut updated = FxHashSet::default();
+
             // We'll gather all scripts queued for destruction and destroy them all at once at the
             // end of the frame.
             let mut destruction_queue = VecDeque::new();
@@ -201,6 +301,9 @@ impl ScriptProcessor {
                     handle: Default::default(),
                     scene,
                     resource_manager,
+                    localization,
+                    console,
+                    settings,
                 };
 
                 'init_loop: for init_loop_iteration in 0..max_iterations {
@@ -213,15 +316,17 @@ impl ScriptProcessor {
                             ScriptMessage::InitializeScript { handle } => {
                                 context.handle = handle;
 
-                                process_node(&mut context, &mut |script, context| {
+                                process_node(&mut context, &mut |script, _, context| {
                                     if !script.initialized {
                                         script.on_init(context);
                                         script.initialized = true;
                                     }
+                                });
 
-                                    // `on_start` must be called even if the script was initialized.
+                                // `on_start` must be called even if the script was initialized.
+                                if !start_queue.contains(&handle) {
                                     start_queue.push_back(handle);
-                                });
+                                }
                             }
                             ScriptMessage::DestroyScript { handle, script } => {
                                 // Destruction is delayed to the end of the frame.
@@ -240,14 +345,21 @@ impl ScriptProcessor {
                         while let Some(node) = start_queue.pop_front() {
                             context.handle = node;
 
-                            process_node(&mut context, &mut |script, context| {
-                                if !script.started {
+                            let mut any_started = false;
+                            process_node(&mut context, &mut |script, _, context| {
+                                if script.initialized && !script.started {
                                     script.on_start(context);
                                     script.started = true;
+                                    any_started = true;
 
-                                    update_queue.push_back(node);
+                                    script.update_timer = script.update_interval()
+                                        * next_update_phase(&mut update_phase_counter);
                                 }
                             });
+
+                            if any_started && !update_queue.contains(&node) {
+                                update_queue.push_back(node);
+                            }
                         }
                     }
 
@@ -264,10 +376,29 @@ impl ScriptProcessor {
                     break 'update_loop;
                 } else {
                     while let Some(handle) = update_queue.pop_front() {
+                        if !updated.insert(handle) {
+                            continue;
+                        }
+
                         context.handle = handle;
 
-                        process_node(&mut context, &mut |script, context| {
-                            script.on_update(context);
+                        process_node(&mut context, &mut |script, enabled, context| {
+                            if !script.initialized || !script.started {
+                                return;
+                            }
+
+                            if script.active != enabled {
+                                script.active = enabled;
+                                if enabled {
+                                    script.on_enable(context);
+                                } else {
+                                    script.on_disable(context);
+                                }
+                            }
+
+                            if enabled {
+                                update_script(script, context);
+                            }
                         });
                     }
                 }
@@ -297,6 +428,8 @@ impl ScriptProcessor {
             }
         }
 
+        self.update_phase_counter = update_phase_counter;
+
         // Process scripts from destroyed scenes.
         for (handle, mut detached_scene) in scenes.destruction_list.drain(..) {
             if self.scripted_scenes.contains(&handle) {
@@ -312,12 +445,14 @@ impl ScriptProcessor {
                 for node_index in 0..context.scene.graph.capacity() {
                     context.node_handle = context.scene.graph.handle_from_index(node_index);
 
-                    if let Some(mut script) = context
+                    let records = context
                         .scene
                         .graph
                         .try_get_mut(context.node_handle)
-                        .and_then(|node| node.script.take())
-                    {
+                        .map(|node| std::mem::take(node.scripts_inner()))
+                        .unwrap_or_default();
+
+                    for mut script in records.into_iter().filter_map(|r| r.script) {
                         // A script could not be initialized in case if we added a scene, and then immediately
                         // removed it. Calling `on_deinit` in this case would be a violation of API contract.
                         if script.initialized {
@@ -419,38 +554,93 @@ pub struct EngineInitParams<'a> {
 
 fn process_node<T>(context: &mut ScriptContext, func: &mut T)
 where
-    T: FnMut(&mut Script, &mut ScriptContext),
+    T: FnMut(&mut Script, bool, &mut ScriptContext),
 {
-    // Take a script from node. We're temporarily taking ownership over script
-    // instance.
-    let mut script = match context.scene.graph.try_get_mut(context.handle) {
-        Some(node) => {
-            if let Some(script) = node.script.take() {
-                script
-            } else {
-                // No script.
+    // Scripts are processed in the order of their appearance in the node, scripts that were added
+    // while processing the node will be processed too.
+    let mut index = 0;
+    loop {
+        // Take a script from node. We're temporarily taking ownership over script
+        // instance, other scripts of the node are still accessible.
+        let (mut script, enabled) = match context.scene.graph.try_get_mut(context.handle) {
+            Some(node) => {
+                // Scripts of disabled nodes are disabled too.
+                let node_enabled = node.is_globally_enabled();
+                match node.scripts_inner().get_mut(index) {
+                    Some(record) => {
+                        if let Some(script) = record.script.take() {
+                            (script, record.enabled && node_enabled)
+                        } else {
+                            // No script.
+                            index += 1;
+                            continue;
+                        }
+                    }
+                    // No more scripts.
+                    None => return,
+                }
+            }
+            None => {
+                // Invalid handle.
                 return;
             }
+        };
+
+        func(&mut script, enabled, context);
+
+        // Put the script back to the node. We must do a checked borrow, because it is possible
+        // that the node is already destroyed by script logic.
+        if let Some(record) = context
+            .scene
+            .graph
+            .try_get_mut(context.handle)
+            .and_then(|node| node.scripts_inner().get_mut(index))
+        {
+            if record.script.is_none() {
+                record.script = Some(script);
+            }
         }
-        None => {
-            // Invalid handle.
-            return;
-        }
-    };
 
-    func(&mut script, context);
+        index += 1;
+    }
+}
 
-    // Put the script back to the node. We must do a checked borrow, because it is possible
-    // that the node is already destroyed by script logic.
-    if let Some(node) = context.scene.graph.try_get_mut(context.handle) {
-        node.script = Some(script);
+// Updates the script taking its update interval into account.
+fn update_script(script: &mut Script, context: &mut ScriptContext) {
+    let interval = script.update_interval();
+    if interval > 0.0 {
+        let dt = context.dt;
+        script.time_since_update += dt;
+        if script.update_timer <= 0.0 {
+            context.dt = script.time_since_update;
+            script.on_update(context);
+            context.dt = dt;
+
+            script.time_since_update = 0.0;
+            script.update_timer = (script.update_timer + interval).max(0.0);
+        }
+        script.update_timer -= dt;
+    } else {
+        script.on_update(context);
     }
 }
 
+// Returns a value in [0; 1) range. Consecutive values are evenly distributed in the range, so
+// scripts with the same update interval will be updated on different frames.
+fn next_update_phase(counter: &mut u32) -> f32 {
+    // Fractional part of the golden ratio gives low-discrepancy sequence.
+    let phase = (*counter as f64 * 0.618_033_988_749_895).fract() as f32;
+    *counter = counter.wrapping_add(1);
+    phase
+}
+
 pub(crate) fn process_scripts<T>(
     scene: &mut Scene,
     plugins: &mut [Box<dyn Plugin>],
     resource_manager: &ResourceManager,
+    localization: &Localization,
+    console: &Console,
+    settings: &Settings,
     dt: f32,
     elapsed_time: f32,
     mut func: T,
@@ -464,12 +654,19 @@ pub(crate) fn process_scripts<T>(
         handle: Default::default(),
         scene,
         resource_manager,
+        localization,
+        console,
+        settings,
     };
 
     for node_index in 0..context.scene.graph.capacity() {
         context.handle = context.scene.graph.handle_from_index(node_index);
 
-        process_node(&mut context, &mut func);
+        process_node(&mut context, &mut |script, enabled, context| {
+            if enabled {
+                func(script, context)
+            }
+        });
     }
 }
 
@@ -601,6 +798,12 @@ impl Engine {
             .event_broadcaster
             .add(rx);
 
+        let settings = Settings::new();
+        settings.register::<GraphicsSettings>();
+        settings.register::<AudioSettings>();
+        settings.modify(|pacing: &mut FramePacingSettings| pacing.vsync = vsync);
+        let settings_receiver = settings.subscribe();
+
         Ok(Self {
             model_events_receiver: tx,
             resource_manager,
@@ -609,6 +812,14 @@ impl Engine {
             sound_engine,
             user_interface: UserInterface::new(Vector2::new(client_size.x, client_size.y)),
             ui_time: Default::default(),
+            statistics: Default::default(),
+            frame_statistics: Default::default(),
+            deterministic_mode: None,
+            simulation_frame: 0,
+            frame_hashes: Default::default(),
+            replay_recorder: None,
+            replay_player: None,
+            replay_actions: Default::default(),
             #[cfg(not(target_arch = "wasm32"))]
             context,
             #[cfg(target_arch = "wasm32")]
@@ -619,6 +830,13 @@ impl Engine {
             plugins_enabled: false,
             plugin_constructors: Default::default(),
             elapsed_time: 0.0,
+            console: Console::new(),
+            settings,
+            settings_receiver,
+            vsync,
+            ime_position: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            crash_handler: None,
         })
     }
 
@@ -680,13 +898,32 @@ impl Engine {
     /// but the plugins attach may use it, that's why you need to provide it. If you don't use plugins, then
     /// put `&mut 0.0` here.
     pub fn pre_update(&mut self, dt: f32, control_flow: &mut ControlFlow, lag: &mut f32) {
+        let dt = self.enforce_fixed_dt(dt);
+        self.play_replay_events(dt, control_flow, lag);
         let inner_size = self.get_window().inner_size();
         let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
+        // Previous frame is complete at this point (including rendering).
+        self.frame_statistics.allocations = memory::take_allocation_counters();
+        self.frame_statistics.frame_arena = memory::reset_frame_arena();
+        self.statistics = self.frame_statistics.clone();
+        self.frame_statistics.frame += 1;
+        self.frame_statistics.dt = dt;
+
         self.resource_manager.state().update(dt);
         self.renderer.update_caches(dt);
         self.handle_model_events();
+        self.apply_settings();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(crash_handler) = self.crash_handler.as_mut() {
+                crash_handler.update(&mut self.renderer, &mut self.scenes, dt);
+            }
+        }
 
+        let time = instant::Instant::now();
+        let scope = AllocationScope::new(Subsystem::Scenes);
         for scene in self.scenes.iter_mut().filter(|s| s.enabled) {
             let frame_size = scene.render_target.as_ref().map_or(window_size, |rt| {
                 if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
@@ -698,9 +935,42 @@ impl Engine {
 
             scene.update(frame_size, dt);
         }
+        drop(scope);
+        self.frame_statistics.timings.scenes = instant::Instant::now() - time;
 
+        let time = instant::Instant::now();
+        let scope = AllocationScope::new(Subsystem::Plugins);
         self.update_plugins(dt, control_flow, lag);
+        drop(scope);
+        self.frame_statistics.timings.plugins = instant::Instant::now() - time;
+
+        let time = instant::Instant::now();
+        let scope = AllocationScope::new(Subsystem::Scripts);
         self.handle_scripts(dt);
+        drop(scope);
+        self.frame_statistics.timings.scripts = instant::Instant::now() - time;
+
+        if self.deterministic_mode.is_some() {
+            self.simulation_frame += 1;
+            let hash = determinism::hash_scenes(&self.scenes);
+            self.frame_hashes.push(self.simulation_frame, hash);
+
+            if let Some(recorder) = self.replay_recorder.as_mut() {
+                recorder.end_frame(self.simulation_frame, hash);
+            }
+
+            if let Some(player) = self.replay_player.as_mut() {
+                let recorded = player.frame(self.simulation_frame).map(|f| f.hash);
+                let finished = player.is_finished();
+                if let Some(recorded) = recorded {
+                    let _ = self.check_frame_hash(self.simulation_frame, recorded);
+                }
+                if finished {
+                    Log::info("Replay playback has finished.");
+                    self.replay_player = None;
+                }
+            }
+        }
     }
 
     /// Performs post update for the engine.
@@ -708,13 +978,312 @@ impl Engine {
     /// Normally, this is called from `Engine::update()`.
     /// You should only call this manually if you don't use that method.
     pub fn post_update(&mut self, dt: f32) {
+        let dt = self.enforce_fixed_dt(dt);
         let inner_size = self.get_window().inner_size();
         let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
         let time = instant::Instant::now();
+        let scope = AllocationScope::new(Subsystem::Ui);
         self.user_interface.update(window_size, dt);
+        drop(scope);
         self.ui_time = instant::Instant::now() - time;
         self.elapsed_time += dt;
+
+        self.update_ime();
+
+        self.frame_statistics.timings.ui = self.ui_time;
+        self.frame_statistics.collect_scenes(&self.scenes);
+        self.frame_statistics.collect_counters(
+            &self.user_interface,
+            &self.resource_manager,
+            &self.renderer,
+        );
+    }
+
+    // Allows IME only when a text field is focused (otherwise IME would "eat" keyboard input) and
+    // keeps candidate window near the caret.
+    fn update_ime(&mut self) {
+        let ime_position = self.user_interface.ime_position();
+        if ime_position != self.ime_position {
+            let window = self.get_window();
+            if ime_position.is_some() != self.ime_position.is_some() {
+                window.set_ime_allowed(ime_position.is_some());
+            }
+            if let Some(position) = ime_position {
+                window.set_ime_position(PhysicalPosition::new(position.x, position.y));
+            }
+            self.ime_position = ime_position;
+        }
+    }
+
+    /// Enables or disables deterministic simulation mode. Enabling the mode seeds random numbers of
+    /// the current thread, resets the simulation frame counter and clears the history of frame
+    /// hashes. See [`determinism`] module docs for more info.
+    pub fn set_deterministic_mode(&mut self, settings: Option<DeterministicSettings>) {
+        crate::core::random::set_seed(settings.map(|s| s.seed));
+        self.deterministic_mode = settings;
+        self.simulation_frame = 0;
+        self.frame_hashes.clear();
+    }
+
+    /// Returns current settings of deterministic simulation mode, `None` if the mode is disabled.
+    pub fn deterministic_mode(&self) -> Option<&DeterministicSettings> {
+        self.deterministic_mode.as_ref()
+    }
+
+    /// Returns amount of updates since deterministic mode was enabled. It is an index of the last
+    /// frame in [`Self::frame_hashes`].
+    pub fn simulation_frame(&self) -> u64 {
+        self.simulation_frame
+    }
+
+    /// Returns the history of state hashes of the last frames, it is filled only in deterministic
+    /// mode.
+    pub fn frame_hashes(&self) -> &FrameHashHistory {
+        &self.frame_hashes
+    }
+
+    /// Calculates a hash of the current state of every enabled scene. It could be used to compare
+    /// states of two simulations.
+    pub fn frame_state_hash(&self) -> u64 {
+        determinism::hash_scenes(&self.scenes)
+    }
+
+    /// Compares a hash of a frame received from other peer (or from a replay) with the local one,
+    /// a divergence is also written to the log.
+    pub fn check_frame_hash(&self, frame: u64, remote: u64) -> Result<(), Divergence> {
+        self.frame_hashes
+            .check(frame, remote)
+            .map_err(|divergence| {
+                Log::err(format!(
+                    "Simulation diverged at frame {}! Local hash: {:x}, remote hash: {:x}",
+                    divergence.frame, divergence.local, divergence.remote
+                ));
+                divergence
+            })
+    }
+
+    /// Starts recording of a replay. It enables deterministic mode with the given settings, every
+    /// input event received by [`Self::handle_os_event_by_plugins`] and every action passed to
+    /// [`Self::record_replay_action`] is recorded frame-by-frame. Replay playback is stopped, if
+    /// any. See [`replay`] module docs for more info.
+    pub fn start_replay_recording(&mut self, settings: DeterministicSettings) {
+        self.replay_player = None;
+        self.set_deterministic_mode(Some(settings));
+        self.replay_recorder = Some(ReplayRecorder::new(settings));
+    }
+
+    /// Stops recording of a replay and returns the recorded replay, `None` if there was no
+    /// recording. Deterministic mode stays enabled.
+    pub fn stop_replay_recording(&mut self) -> Option<Replay> {
+        self.replay_recorder
+            .take()
+            .map(|recorder| recorder.finish())
+    }
+
+    /// Returns `true` if a replay is being recorded.
+    pub fn is_recording_replay(&self) -> bool {
+        self.replay_recorder.is_some()
+    }
+
+    /// Records a custom input action of a game, the action will be available via
+    /// [`Self::replay_actions`] in the same frame when the replay is played back. Does nothing if
+    /// there's no recording.
+    pub fn record_replay_action<S: Into<String>>(&mut self, name: S, value: f32) {
+        if let Some(recorder) = self.replay_recorder.as_mut() {
+            recorder.record_action(name, value);
+        }
+    }
+
+    /// Starts playback of the given replay. It enables deterministic mode with the settings of the
+    /// replay, recorded input events are passed to plugins, scripts and the user interface at the
+    /// beginning of their frames, real input events are ignored until the playback is finished.
+    /// Recorded state hashes are compared with local ones, a divergence is written to the log.
+    /// Replay recording is stopped, if any.
+    ///
+    /// # Important notes
+    ///
+    /// A replay must be played back from the same initial state as it was recorded, usually it
+    /// means that the playback should be started right after the same scenes were loaded.
+    pub fn start_replay_playback(&mut self, replay: Replay) {
+        self.replay_recorder = None;
+        self.set_deterministic_mode(Some(replay.settings()));
+        self.replay_player = Some(ReplayPlayer::new(replay));
+    }
+
+    /// Stops playback of a replay. Deterministic mode stays enabled.
+    pub fn stop_replay_playback(&mut self) {
+        self.replay_player = None;
+        self.replay_actions.clear();
+    }
+
+    /// Returns `true` if a replay is being played back.
+    pub fn is_playing_replay(&self) -> bool {
+        self.replay_player.is_some()
+    }
+
+    /// Returns custom input actions (name and value) of the current frame of a replay, that is
+    /// being played back.
+    pub fn replay_actions(&self) -> &[(String, f32)] {
+        &self.replay_actions
+    }
+
+    /// Returns `true` if the given event is a real input event, that must be ignored because a
+    /// replay is being played back. It is intended to be used by the game runner and the editor,
+    /// that pass OS events to the user interface directly.
+    pub fn is_input_blocked_by_replay(&self, event: &Event<()>) -> bool {
+        self.replay_player.is_some()
+            && matches!(event, Event::WindowEvent { event, .. }
+                if ReplayEvent::from_window_event(event).is_some())
+    }
+
+    fn play_replay_events(&mut self, dt: f32, control_flow: &mut ControlFlow, lag: &mut f32) {
+        self.replay_actions.clear();
+
+        let frame = self.simulation_frame + 1;
+        let events = match self
+            .replay_player
+            .as_mut()
+            .and_then(|player| player.frame(frame))
+        {
+            Some(frame) => frame.events.clone(),
+            None => return,
+        };
+
+        let window_id = self.get_window().id();
+        for event in events {
+            if let Some(window_event) = event.to_window_event() {
+                if let Some(os_event) = translate_event(&window_event) {
+                    self.user_interface.process_os_event(&os_event);
+                }
+
+                let event = Event::WindowEvent {
+                    window_id,
+                    event: window_event,
+                };
+
+                self.pass_os_event_to_plugins(&event, dt, control_flow, lag);
+
+                let scenes = self.scenes.pair_iter().map(|(s, _)| s).collect::<Vec<_>>();
+                for scene in scenes {
+                    self.pass_os_event_to_scripts(&event, scene, dt);
+                }
+            } else if let ReplayEvent::Action { name, value } = event {
+                self.replay_actions.push((name, value));
+            }
+        }
+    }
+
+    fn enforce_fixed_dt(&self, dt: f32) -> f32 {
+        self.deterministic_mode
+            .map_or(dt, |settings| settings.fixed_dt)
+    }
+
+    /// Returns statistics of the last complete frame (update and rendering), it contains time spent
+    /// by every engine system, amount of objects in pools and resource memory. See
+    /// [`EngineStatistics`] docs for more info.
+    pub fn statistics(&self) -> &EngineStatistics {
+        &self.statistics
+    }
+
+    /// Installs a panic hook, that writes a crash report when the application panics. See
+    /// [`crash`] module docs for more info.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn enable_crash_handler(&mut self, config: crash::CrashHandlerConfig) {
+        // Drop previous handler first, so its hook is disabled.
+        self.crash_handler = None;
+        self.crash_handler = Some(crash::CrashHandler::install(config));
+    }
+
+    /// Disables crash reports.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn disable_crash_handler(&mut self) {
+        self.crash_handler = None;
+    }
+
+    /// Returns a reference to the crash handler, if it is enabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn crash_handler(&self) -> Option<&crash::CrashHandler> {
+        self.crash_handler.as_ref()
+    }
+
+    /// Returns `true` if vertical synchronization is enabled, `false` - otherwise.
+    pub fn is_vsync_enabled(&self) -> bool {
+        self.vsync
+    }
+
+    /// Tries to change swap interval of the graphics context without re-creating it. Returns `false`
+    /// if the context does not allow it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_swap_interval(&self, vsync: bool) -> bool {
+        use glutin::platform::ContextTraitExt;
+        use std::ffi::c_void;
+
+        let interval = i32::from(vsync);
+
+        // SAFETY: The context is current and the functions are resolved by the context itself, so
+        // they match the API it was created with.
+        unsafe {
+            if let Some(display) = self.context.get_egl_display() {
+                let swap_interval = self.context.get_proc_address("eglSwapInterval");
+                if !swap_interval.is_null() {
+                    let swap_interval: extern "system" fn(*const c_void, i32) -> u32 =
+                        std::mem::transmute(swap_interval);
+                    return swap_interval(display, interval) != 0;
+                }
+            } else if cfg!(windows) {
+                let swap_interval = self.context.get_proc_address("wglSwapIntervalEXT");
+                if !swap_interval.is_null() {
+                    let swap_interval: extern "system" fn(i32) -> i32 =
+                        std::mem::transmute(swap_interval);
+                    return swap_interval(interval) != 0;
+                }
+            }
+        }
+
+        false
+    }
+
+    // Browsers synchronize frames with the display by themselves.
+    #[cfg(target_arch = "wasm32")]
+    fn set_swap_interval(&self, _vsync: bool) -> bool {
+        false
+    }
+
+    fn apply_settings(&mut self) {
+        while let Ok(SettingsEvent::Changed(section)) = self.settings_receiver.try_recv() {
+            if section == GraphicsSettings::section_name() {
+                let quality = self.settings.get::<GraphicsSettings>().quality;
+                if quality != self.renderer.get_quality_settings() {
+                    if let Err(e) = self.renderer.set_quality_settings(&quality) {
+                        Log::err(format!(
+                            "Unable to apply graphics settings. Reason: {:?}",
+                            e
+                        ));
+                    }
+                }
+            } else if section == AudioSettings::section_name() {
+                let audio = self.settings.get::<AudioSettings>();
+                let mut sound_engine = self.sound_engine.lock().unwrap();
+                sound_engine.set_master_gain(audio.master_gain);
+                sound_engine.set_hrtf_allowed(audio.hrtf);
+                sound_engine.set_hrtf_parallel_workers(
+                    audio.hrtf_parallel_workers.map(|count| count as usize),
+                );
+            } else if section == FramePacingSettings::section_name() {
+                let vsync = self.settings.get::<FramePacingSettings>().vsync;
+                if vsync != self.vsync {
+                    if self.set_swap_interval(vsync) {
+                        self.vsync = vsync;
+                    } else {
+                        Log::warn(
+                            "Vertical synchronization can't be changed while the engine is \
+                            running, the change will be applied on next start.",
+                        );
+                    }
+                }
+            }
+        }
     }
 
     /// Returns true if the scene is registered for script processing.
@@ -736,6 +1305,9 @@ impl Engine {
             &mut self.scenes,
             &mut self.plugins,
             &self.resource_manager,
+            self.user_interface.localization(),
+            &self.console,
+            &self.settings,
             dt,
             self.elapsed_time,
         );
@@ -751,6 +1323,9 @@ impl Engine {
                 lag,
                 user_interface: &mut self.user_interface,
                 serialization_context: &self.serialization_context,
+                console: &self.console,
+                settings: &self.settings,
+                statistics: &self.statistics,
                 window: get_window!(self),
                 sound_engine: SoundEngineHelper {
                     engine: &self.sound_engine,
@@ -770,6 +1345,9 @@ impl Engine {
                     lag,
                     user_interface: &mut self.user_interface,
                     serialization_context: &self.serialization_context,
+                    console: &self.console,
+                    settings: &self.settings,
+                    statistics: &self.statistics,
                     window: get_window!(self),
                     sound_engine: SoundEngineHelper {
                         engine: &self.sound_engine,
@@ -790,6 +1368,26 @@ impl Engine {
         dt: f32,
         control_flow: &mut ControlFlow,
         lag: &mut f32,
+    ) {
+        if self.is_input_blocked_by_replay(event) {
+            return;
+        }
+
+        if let (Some(recorder), Event::WindowEvent { event, .. }) =
+            (self.replay_recorder.as_mut(), event)
+        {
+            recorder.record_window_event(event);
+        }
+
+        self.pass_os_event_to_plugins(event, dt, control_flow, lag);
+    }
+
+    fn pass_os_event_to_plugins(
+        &mut self,
+        event: &Event<()>,
+        dt: f32,
+        control_flow: &mut ControlFlow,
+        lag: &mut f32,
     ) {
         if self.plugins_enabled {
             for plugin in self.plugins.iter_mut() {
@@ -803,6 +1401,9 @@ impl Engine {
                         lag,
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
+                        console: &self.console,
+                        settings: &self.settings,
+                        statistics: &self.statistics,
                         window: get_window!(self),
                         sound_engine: SoundEngineHelper {
                             engine: &self.sound_engine,
@@ -827,12 +1428,21 @@ impl Engine {
         scene: Handle<Scene>,
         dt: f32,
     ) {
+        if !self.is_input_blocked_by_replay(event) {
+            self.pass_os_event_to_scripts(event, scene, dt);
+        }
+    }
+
+    fn pass_os_event_to_scripts(&mut self, event: &Event<()>, scene: Handle<Scene>, dt: f32) {
         let scene = &mut self.scenes[scene];
         if scene.enabled {
             process_scripts(
                 scene,
                 &mut self.plugins,
                 &self.resource_manager,
+                self.user_interface.localization(),
+                &self.console,
+                &self.settings,
                 dt,
                 self.elapsed_time,
                 |script, context| {
@@ -875,21 +1485,45 @@ impl Engine {
     /// see anything.
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        let time = instant::Instant::now();
+
+        let scope = AllocationScope::new(Subsystem::Ui);
         self.user_interface.draw();
+        drop(scope);
+
+        let _scope = AllocationScope::new(Subsystem::Renderer);
+
+        let color_requests = self.user_interface.take_screen_color_requests();
+        let positions = color_requests
+            .iter()
+            .map(|request| request.position)
+            .collect::<Vec<_>>();
 
         #[cfg(not(target_arch = "wasm32"))]
-        {
-            self.renderer.render_and_swap_buffers(
-                &self.scenes,
-                self.user_interface.get_drawing_context(),
-                &self.context,
-            )
-        }
+        let colors = self.renderer.render_and_swap_buffers(
+            &self.scenes,
+            self.user_interface.get_drawing_context(),
+            &self.context,
+            &positions,
+        )?;
         #[cfg(target_arch = "wasm32")]
-        {
-            self.renderer
-                .render_and_swap_buffers(&self.scenes, &self.user_interface.get_drawing_context())
+        let colors = self.renderer.render_and_swap_buffers(
+            &self.scenes,
+            &self.user_interface.get_drawing_context(),
+            &positions,
+        )?;
+
+        for (request, color) in color_requests.iter().zip(colors) {
+            self.user_interface.send_message(ColorPickerMessage::color(
+                request.requester,
+                MessageDirection::ToWidget,
+                color,
+            ));
         }
+
+        self.frame_statistics.timings.render = instant::Instant::now() - time;
+
+        Ok(())
     }
 
     /// Sets master gain of the sound engine. Can be used to control overall gain of all sound
@@ -921,6 +1555,9 @@ impl Engine {
                             lag: &mut 0.0,
                             user_interface: &mut self.user_interface,
                             serialization_context: &self.serialization_context,
+                            console: &self.console,
+                            settings: &self.settings,
+                            statistics: &self.statistics,
                             window: get_window!(self),
                             sound_engine: SoundEngineHelper {
                                 engine: &self.sound_engine,
@@ -941,6 +1578,9 @@ impl Engine {
                         lag: &mut 0.0,
                         user_interface: &mut self.user_interface,
                         serialization_context: &self.serialization_context,
+                        console: &self.console,
+                        settings: &self.settings,
+                        statistics: &self.statistics,
                         window: get_window!(self),
                         sound_engine: SoundEngineHelper {
                             engine: &self.sound_engine,
@@ -958,6 +1598,8 @@ impl Engine {
     {
         constructor.register(PluginRegistrationContext {
             serialization_context: &self.serialization_context,
+            console: &self.console,
+            settings: &self.settings,
         });
 
         self.plugin_constructors.push(Box::new(constructor));
@@ -1128,6 +1770,9 @@ mod test {
                 &mut scene_container,
                 &mut Default::default(),
                 &resource_manager,
+                &Default::default(),
+                &Default::default(),
+                &Default::default(),
                 0.0,
                 0.0,
             );
@@ -1179,4 +1824,173 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_multiple_scripts() {
+        let resource_manager = ResourceManager::new(Default::default());
+        let mut scene = Scene::new();
+
+        let (tx, rx) = mpsc::channel();
+
+        let node_handle = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_script(Script::new(MySubScript { sender: tx.clone() }))
+                .with_script(Script::new(MySubScript { sender: tx })),
+        )
+        .build(&mut scene.graph);
+
+        let mut scene_container = SceneContainer::new(Default::default());
+
+        let scene_handle = scene_container.add(scene);
+
+        let mut script_processor = ScriptProcessor::default();
+
+        script_processor.register_scripted_scene(
+            scene_handle,
+            &mut scene_container,
+            &resource_manager,
+        );
+
+        for iteration in 0..3 {
+            script_processor.handle_scripts(
+                &mut scene_container,
+                &mut Default::default(),
+                &resource_manager,
+                &Default::default(),
+                &Default::default(),
+                &Default::default(),
+                0.0,
+                0.0,
+            );
+
+            match iteration {
+                0 => {
+                    assert_eq!(rx.try_recv(), Ok(Event::Initialized(node_handle)));
+                    assert_eq!(rx.try_recv(), Ok(Event::Initialized(node_handle)));
+                    assert_eq!(rx.try_recv(), Ok(Event::Started(node_handle)));
+                    assert_eq!(rx.try_recv(), Ok(Event::Started(node_handle)));
+                    assert_eq!(rx.try_recv(), Ok(Event::Updated(node_handle)));
+                    assert_eq!(rx.try_recv(), Ok(Event::Updated(node_handle)));
+                    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+                    // Disabled scripts must not be updated.
+                    let node = &mut scene_container[scene_handle].graph[node_handle];
+                    assert!(node.set_script_enabled(1, false));
+                }
+                1 => {
+                    assert_eq!(rx.try_recv(), Ok(Event::Updated(node_handle)));
+                    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+                    scene_container[scene_handle].remove_node(node_handle);
+                }
+                2 => {
+                    assert_eq!(rx.try_recv(), Ok(Event::Destroyed(node_handle)));
+                    assert_eq!(rx.try_recv(), Ok(Event::Destroyed(node_handle)));
+                    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[derive(PartialEq, Eq, Clone, Debug)]
+    enum StateEvent {
+        Enabled,
+        Disabled,
+        Updated,
+    }
+
+    #[derive(Debug, Clone, Reflect, Visit)]
+    struct IntervalScript {
+        #[reflect(hidden)]
+        #[visit(skip)]
+        sender: Sender<StateEvent>,
+    }
+
+    impl_component_provider!(IntervalScript);
+
+    impl ScriptTrait for IntervalScript {
+        fn on_update(&mut self, _ctx: &mut ScriptContext) {
+            self.sender.send(StateEvent::Updated).unwrap();
+        }
+
+        fn on_enable(&mut self, _ctx: &mut ScriptContext) {
+            self.sender.send(StateEvent::Enabled).unwrap();
+        }
+
+        fn on_disable(&mut self, _ctx: &mut ScriptContext) {
+            self.sender.send(StateEvent::Disabled).unwrap();
+        }
+
+        fn update_interval(&self) -> f32 {
+            0.1
+        }
+
+        fn id(&self) -> Uuid {
+            Uuid::new_v4()
+        }
+    }
+
+    #[test]
+    fn test_enable_disable_and_update_interval() {
+        let resource_manager = ResourceManager::new(Default::default());
+        let mut scene = Scene::new();
+
+        let (tx, rx) = mpsc::channel();
+
+        let node_handle = PivotBuilder::new(
+            BaseBuilder::new().with_script(Script::new(IntervalScript { sender: tx })),
+        )
+        .build(&mut scene.graph);
+
+        let mut scene_container = SceneContainer::new(Default::default());
+
+        let scene_handle = scene_container.add(scene);
+
+        let mut script_processor = ScriptProcessor::default();
+
+        script_processor.register_scripted_scene(
+            scene_handle,
+            &mut scene_container,
+            &resource_manager,
+        );
+
+        let mut tick = |scene_container: &mut SceneContainer| {
+            script_processor.handle_scripts(
+                scene_container,
+                &mut Default::default(),
+                &resource_manager,
+                &Default::default(),
+                &Default::default(),
+                &Default::default(),
+                0.05,
+                0.0,
+            );
+        };
+
+        // The script is updated every second frame.
+        tick(&mut scene_container);
+        assert_eq!(rx.try_recv(), Ok(StateEvent::Enabled));
+        assert_eq!(rx.try_recv(), Ok(StateEvent::Updated));
+        tick(&mut scene_container);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        tick(&mut scene_container);
+        assert_eq!(rx.try_recv(), Ok(StateEvent::Updated));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        scene_container[scene_handle].graph[node_handle].set_script_enabled(0, false);
+        tick(&mut scene_container);
+        assert_eq!(rx.try_recv(), Ok(StateEvent::Disabled));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        scene_container[scene_handle].graph[node_handle].set_script_enabled(0, true);
+        tick(&mut scene_container);
+        assert_eq!(rx.try_recv(), Ok(StateEvent::Enabled));
+
+        scene_container[scene_handle].enabled = false;
+        tick(&mut scene_container);
+        tick(&mut scene_container);
+        assert_eq!(rx.try_recv(), Ok(StateEvent::Disabled));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
 }
@@ -0,0 +1,465 @@
+//! Replay recording and playback. A replay contains input events (OS events and custom input
+//! actions) of every frame of a session, seed of random numbers and time step of the simulation,
+//! so the session could be reproduced later. It is useful for bug reproduction, demos and
+//! automated gameplay testing.
+//!
+//! Replays rely on [deterministic simulation mode](super::determinism): recording and playback
+//! enable it with the settings stored in the replay. Every recorded frame also contains a state
+//! hash, so a playback is able to detect a divergence of the simulation (for example, when game
+//! code uses non-deterministic data).
+//!
+//! Use [`crate::engine::Engine::start_replay_recording`] and
+//! [`crate::engine::Engine::stop_replay_recording`] to record a replay, and
+//! [`crate::engine::Engine::start_replay_playback`] to play it back. While a replay is being
+//! played back, real input events are ignored by the engine.
+
+use crate::{
+    dpi::PhysicalPosition,
+    engine::determinism::DeterministicSettings,
+    event::{
+        DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+        TouchPhase, VirtualKeyCode, WindowEvent,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+/// Current version of replay format.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// An error that may occur during replay loading or saving.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Serialization or deserialization error.
+    Ron(String),
+    /// A replay has unsupported version.
+    UnsupportedVersion(u32),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(v) => write!(f, "An i/o error has occurred {:?}", v),
+            ReplayError::Ron(v) => write!(f, "Invalid replay: {}", v),
+            ReplayError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported replay version {}", v)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// State of a button or a key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ButtonState {
+    /// The button is pressed.
+    Pressed,
+    /// The button is released.
+    Released,
+}
+
+impl From<ElementState> for ButtonState {
+    fn from(state: ElementState) -> Self {
+        match state {
+            ElementState::Pressed => Self::Pressed,
+            ElementState::Released => Self::Released,
+        }
+    }
+}
+
+impl From<ButtonState> for ElementState {
+    fn from(state: ButtonState) -> Self {
+        match state {
+            ButtonState::Pressed => Self::Pressed,
+            ButtonState::Released => Self::Released,
+        }
+    }
+}
+
+/// A recorded input event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    /// A key was pressed or released.
+    KeyboardInput {
+        /// Platform-dependent scan code of the key.
+        scancode: u32,
+        /// Virtual code of the key.
+        key: Option<VirtualKeyCode>,
+        /// New state of the key.
+        state: ButtonState,
+    },
+    /// A character was received.
+    ReceivedCharacter(char),
+    /// State of modifier keys has changed, the value contains bits of [`ModifiersState`].
+    ModifiersChanged(u32),
+    /// Cursor was moved to the given position (in physical pixels).
+    CursorMoved {
+        /// Horizontal position.
+        x: f64,
+        /// Vertical position.
+        y: f64,
+    },
+    /// A mouse button was pressed or released.
+    MouseInput {
+        /// The button.
+        button: MouseButton,
+        /// New state of the button.
+        state: ButtonState,
+    },
+    /// Mouse wheel was scrolled.
+    MouseWheel {
+        /// Horizontal scroll.
+        x: f64,
+        /// Vertical scroll.
+        y: f64,
+        /// `true` if the delta is in pixels, `false` - in lines.
+        pixels: bool,
+    },
+    /// A custom input action of a game (see [`crate::engine::Engine::record_replay_action`]).
+    Action {
+        /// Name of the action.
+        name: String,
+        /// Value of the action (1.0 for pressed buttons, axis value for sticks, etc.).
+        value: f32,
+    },
+}
+
+impl ReplayEvent {
+    /// Creates a replay event from the given window event. Returns `None` for events, that are not
+    /// input events (window resizing, focus changes, etc.), such events are not recorded.
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => Some(Self::KeyboardInput {
+                scancode: input.scancode,
+                key: input.virtual_keycode,
+                state: input.state.into(),
+            }),
+            WindowEvent::ReceivedCharacter(c) => Some(Self::ReceivedCharacter(*c)),
+            WindowEvent::ModifiersChanged(modifiers) => {
+                Some(Self::ModifiersChanged(modifiers.bits()))
+            }
+            WindowEvent::CursorMoved { position, .. } => Some(Self::CursorMoved {
+                x: position.x,
+                y: position.y,
+            }),
+            WindowEvent::MouseInput { button, state, .. } => Some(Self::MouseInput {
+                button: *button,
+                state: (*state).into(),
+            }),
+            WindowEvent::MouseWheel { delta, .. } => Some(match delta {
+                MouseScrollDelta::LineDelta(x, y) => Self::MouseWheel {
+                    x: *x as f64,
+                    y: *y as f64,
+                    pixels: false,
+                },
+                MouseScrollDelta::PixelDelta(position) => Self::MouseWheel {
+                    x: position.x,
+                    y: position.y,
+                    pixels: true,
+                },
+            }),
+            _ => None,
+        }
+    }
+
+    /// Converts the event back to a window event. Returns `None` for custom input actions.
+    #[allow(deprecated)] // Modifiers of some events are deprecated, but still must be specified.
+    pub fn to_window_event(&self) -> Option<WindowEvent<'static>> {
+        // SAFETY: Dummy device id is only compared with other ids, it is never passed to the
+        // platform.
+        let device_id = unsafe { DeviceId::dummy() };
+        let modifiers = ModifiersState::empty();
+
+        match self {
+            ReplayEvent::KeyboardInput {
+                scancode,
+                key,
+                state,
+            } => Some(WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode: *scancode,
+                    state: (*state).into(),
+                    virtual_keycode: *key,
+                    modifiers,
+                },
+                is_synthetic: false,
+            }),
+            ReplayEvent::ReceivedCharacter(c) => Some(WindowEvent::ReceivedCharacter(*c)),
+            ReplayEvent::ModifiersChanged(bits) => Some(WindowEvent::ModifiersChanged(
+                ModifiersState::from_bits_truncate(*bits),
+            )),
+            ReplayEvent::CursorMoved { x, y } => Some(WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(*x, *y),
+                modifiers,
+            }),
+            ReplayEvent::MouseInput { button, state } => Some(WindowEvent::MouseInput {
+                device_id,
+                state: (*state).into(),
+                button: *button,
+                modifiers,
+            }),
+            ReplayEvent::MouseWheel { x, y, pixels } => Some(WindowEvent::MouseWheel {
+                device_id,
+                delta: if *pixels {
+                    MouseScrollDelta::PixelDelta(PhysicalPosition::new(*x, *y))
+                } else {
+                    MouseScrollDelta::LineDelta(*x as f32, *y as f32)
+                },
+                phase: TouchPhase::Moved,
+                modifiers,
+            }),
+            ReplayEvent::Action { .. } => None,
+        }
+    }
+}
+
+/// Input events of a single frame and a state hash after the frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Index of the frame (see [`crate::engine::Engine::simulation_frame`]).
+    pub frame: u64,
+    /// Input events, that were received before the update of the frame.
+    pub events: Vec<ReplayEvent>,
+    /// State hash of every scene after the update of the frame.
+    pub hash: u64,
+}
+
+/// A recorded session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// Version of the replay format.
+    pub version: u32,
+    /// Seed of random numbers.
+    pub seed: u64,
+    /// Time step of every update.
+    pub fixed_dt: f32,
+    /// Recorded frames in ascending order.
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    /// Creates an empty replay with the given settings of deterministic mode.
+    pub fn new(settings: DeterministicSettings) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed: settings.seed,
+            fixed_dt: settings.fixed_dt,
+            frames: Default::default(),
+        }
+    }
+
+    /// Returns settings of deterministic mode, that were used to record the replay.
+    pub fn settings(&self) -> DeterministicSettings {
+        DeterministicSettings {
+            seed: self.seed,
+            fixed_dt: self.fixed_dt,
+        }
+    }
+
+    /// Returns duration of the replay in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |f| f.frame as f32) * self.fixed_dt
+    }
+
+    /// Serializes the replay to a string.
+    pub fn save_to_string(&self) -> Result<String, ReplayError> {
+        ron::to_string(self).map_err(|e| ReplayError::Ron(e.to_string()))
+    }
+
+    /// Deserializes a replay from the given string.
+    pub fn load_from_string(source: &str) -> Result<Self, ReplayError> {
+        let replay = ron::from_str::<Self>(source).map_err(|e| ReplayError::Ron(e.to_string()))?;
+        if replay.version > REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(replay.version));
+        }
+        Ok(replay)
+    }
+
+    /// Saves the replay to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        std::fs::write(path, self.save_to_string()?)?;
+        Ok(())
+    }
+
+    /// Loads a replay from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Self::load_from_string(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Records input events frame-by-frame.
+#[derive(Debug)]
+pub struct ReplayRecorder {
+    replay: Replay,
+    events: Vec<ReplayEvent>,
+}
+
+impl ReplayRecorder {
+    /// Creates new recorder with the given settings of deterministic mode.
+    pub fn new(settings: DeterministicSettings) -> Self {
+        Self {
+            replay: Replay::new(settings),
+            events: Default::default(),
+        }
+    }
+
+    /// Records the given window event, if it is an input event.
+    pub fn record_window_event(&mut self, event: &WindowEvent) {
+        if let Some(event) = ReplayEvent::from_window_event(event) {
+            self.events.push(event);
+        }
+    }
+
+    /// Records a custom input action.
+    pub fn record_action<S: Into<String>>(&mut self, name: S, value: f32) {
+        self.events.push(ReplayEvent::Action {
+            name: name.into(),
+            value,
+        });
+    }
+
+    /// Finishes the given frame: every event recorded since the previous frame is assigned to it.
+    pub fn end_frame(&mut self, frame: u64, hash: u64) {
+        self.replay.frames.push(ReplayFrame {
+            frame,
+            events: std::mem::take(&mut self.events),
+            hash,
+        });
+    }
+
+    /// Returns the replay recorded so far.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Finishes recording and returns the replay. Events of the unfinished frame are discarded.
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// Plays a replay back frame-by-frame.
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    replay: Replay,
+    position: usize,
+    last_requested: u64,
+}
+
+impl ReplayPlayer {
+    /// Creates new player of the given replay.
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            position: 0,
+            last_requested: 0,
+        }
+    }
+
+    /// Returns recorded frame with the given index. Frames must be requested in ascending order,
+    /// the player skips every frame before the requested one.
+    pub fn frame(&mut self, frame: u64) -> Option<&ReplayFrame> {
+        self.last_requested = self.last_requested.max(frame);
+
+        while self
+            .replay
+            .frames
+            .get(self.position)
+            .map_or(false, |f| f.frame < frame)
+        {
+            self.position += 1;
+        }
+
+        self.replay
+            .frames
+            .get(self.position)
+            .filter(|f| f.frame == frame)
+    }
+
+    /// Returns `true` if every frame of the replay was requested.
+    pub fn is_finished(&self) -> bool {
+        self.replay
+            .frames
+            .last()
+            .map_or(true, |f| f.frame <= self.last_requested)
+    }
+
+    /// Returns the replay being played.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dpi::PhysicalPosition,
+        engine::{
+            determinism::DeterministicSettings,
+            replay::{Replay, ReplayEvent, ReplayPlayer, ReplayRecorder},
+        },
+        event::{DeviceId, ElementState, MouseButton, WindowEvent},
+    };
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_replay_record_and_play() {
+        let device_id = unsafe { DeviceId::dummy() };
+
+        let mut recorder = ReplayRecorder::new(DeterministicSettings::default());
+        recorder.record_window_event(&WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(10.0, 20.0),
+            modifiers: Default::default(),
+        });
+        recorder.record_window_event(&WindowEvent::Focused(true));
+        recorder.end_frame(1, 123);
+        recorder.record_window_event(&WindowEvent::MouseInput {
+            device_id,
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        });
+        recorder.record_action("Jump", 1.0);
+        recorder.end_frame(2, 456);
+        let replay = recorder.finish();
+
+        let loaded = Replay::load_from_string(&replay.save_to_string().unwrap()).unwrap();
+        assert_eq!(replay, loaded);
+        assert_eq!(loaded.settings(), DeterministicSettings::default());
+
+        let mut player = ReplayPlayer::new(loaded);
+        let frame = player.frame(1).unwrap();
+        assert_eq!(
+            frame.events,
+            vec![ReplayEvent::CursorMoved { x: 10.0, y: 20.0 }]
+        );
+        assert_eq!(frame.hash, 123);
+        assert!(!player.is_finished());
+        let frame = player.frame(2).unwrap();
+        assert_eq!(frame.events.len(), 2);
+        assert!(matches!(
+            frame.events[0].to_window_event(),
+            Some(WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            })
+        ));
+        assert!(frame.events[1].to_window_event().is_none());
+        assert!(player.frame(3).is_none());
+        assert!(player.is_finished());
+    }
+}
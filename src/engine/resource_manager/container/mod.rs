@@ -15,7 +15,12 @@ use crate::{
     },
     utils::log::Log,
 };
-use std::{future::Future, ops::Deref, path::Path, sync::Arc};
+use std::{
+    future::Future,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 pub mod entry;
 pub mod event;
@@ -92,6 +97,17 @@ where
         None
     }
 
+    /// Changes a path of a resource with the given path, without reloading it. Returns `false` if
+    /// there is no such resource.
+    pub fn set_resource_path<P: AsRef<Path>>(&self, path: P, new_path: PathBuf) -> bool {
+        if let Some(resource) = self.find(path) {
+            resource.state().set_path(new_path);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Tracks life time of resource and removes unused resources after some time of idling.
     pub fn update(&mut self, dt: f32) {
         self.resources.retain_mut_ext(|resource| {
@@ -25,17 +25,17 @@ impl ResourceLoader<CurveResource, CurveImportOptions> for CurveLoader {
 
             match CurveResourceState::from_file(&path).await {
                 Ok(curve_state) => {
-                    Log::info(format!("Curve {:?} is loaded!", path));
+                    Log::asset_info(&path, format!("Curve {:?} is loaded!", path));
 
                     curve.state().commit_ok(curve_state);
 
                     event_broadcaster.broadcast_loaded_or_reloaded(curve, reload);
                 }
                 Err(error) => {
-                    Log::err(format!(
-                        "Unable to load curve from {:?}! Reason {:?}",
-                        path, error
-                    ));
+                    Log::asset_err(
+                        &path,
+                        format!("Unable to load curve from {:?}! Reason {:?}", path, error),
+                    );
 
                     curve.state().commit_error(path, error);
                 }
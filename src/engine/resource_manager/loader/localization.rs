@@ -0,0 +1,55 @@
+//! Localization loader.
+
+use crate::{
+    engine::resource_manager::{
+        container::event::ResourceEventBroadcaster,
+        loader::{BoxedLoaderFuture, ResourceLoader},
+        options::try_get_import_settings,
+    },
+    resource::localization::{
+        LocalizationImportOptions, LocalizationResource, LocalizationResourceState,
+    },
+    utils::log::Log,
+};
+
+/// Default implementation for localization loading.
+pub struct LocalizationLoader;
+
+impl ResourceLoader<LocalizationResource, LocalizationImportOptions> for LocalizationLoader {
+    fn load(
+        &self,
+        localization: LocalizationResource,
+        default_import_options: LocalizationImportOptions,
+        event_broadcaster: ResourceEventBroadcaster<LocalizationResource>,
+        reload: bool,
+    ) -> BoxedLoaderFuture {
+        Box::pin(async move {
+            let path = localization.state().path().to_path_buf();
+
+            let import_options = try_get_import_settings(&path)
+                .await
+                .unwrap_or(default_import_options);
+
+            match LocalizationResourceState::from_file(&path, import_options.language).await {
+                Ok(localization_state) => {
+                    Log::asset_info(&path, format!("Localization {:?} is loaded!", path));
+
+                    localization.state().commit_ok(localization_state);
+
+                    event_broadcaster.broadcast_loaded_or_reloaded(localization, reload);
+                }
+                Err(error) => {
+                    Log::asset_err(
+                        &path,
+                        format!(
+                            "Unable to load localization from {:?}! Reason {:?}",
+                            path, error
+                        ),
+                    );
+
+                    localization.state().commit_error(path, error);
+                }
+            }
+        })
+    }
+}
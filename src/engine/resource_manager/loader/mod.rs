@@ -6,6 +6,7 @@ use crate::engine::resource_manager::{
 use std::{future::Future, pin::Pin};
 
 pub mod curve;
+pub mod localization;
 pub mod model;
 pub mod shader;
 pub mod sound;
@@ -43,17 +43,17 @@ impl ResourceLoader<Model, ModelImportOptions> for ModelLoader {
             match ModelData::load(&path, node_constructors, resource_manager, import_options).await
             {
                 Ok(raw_model) => {
-                    Log::info(format!("Model {:?} is loaded!", path));
+                    Log::asset_info(&path, format!("Model {:?} is loaded!", path));
 
                     model.state().commit_ok(raw_model);
 
                     event_broadcaster.broadcast_loaded_or_reloaded(model, reload);
                 }
                 Err(error) => {
-                    Log::err(format!(
-                        "Unable to load model from {:?}! Reason {:?}",
-                        path, error
-                    ));
+                    Log::asset_err(
+                        &path,
+                        format!("Unable to load model from {:?}! Reason {:?}", path, error),
+                    );
 
                     model.state().commit_error(path, error);
                 }
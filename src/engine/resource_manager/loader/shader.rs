@@ -25,17 +25,17 @@ impl ResourceLoader<Shader, ShaderImportOptions> for ShaderLoader {
 
             match ShaderState::from_file(&path).await {
                 Ok(shader_state) => {
-                    Log::info(format!("Shader {:?} is loaded!", path));
+                    Log::asset_info(&path, format!("Shader {:?} is loaded!", path));
 
                     shader.state().commit_ok(shader_state);
 
                     event_broadcaster.broadcast_loaded_or_reloaded(shader, reload);
                 }
                 Err(error) => {
-                    Log::err(format!(
-                        "Unable to load model from {:?}! Reason {:?}",
-                        path, error
-                    ));
+                    Log::asset_err(
+                        &path,
+                        format!("Unable to load model from {:?}! Reason {:?}", path, error),
+                    );
 
                     shader.state().commit_error(path, error);
                 }
@@ -54,7 +54,7 @@ impl ResourceLoader<SoundBufferResource, SoundBufferImportOptions> for SoundBuff
 
                             event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
 
-                            Log::info(format!("Sound buffer {:?} is loaded!", path));
+                            Log::asset_info(&path, format!("Sound buffer {:?} is loaded!", path));
                         }
                         Err(_) => {
                             resource.state().commit_error(
@@ -62,12 +62,18 @@ impl ResourceLoader<SoundBufferResource, SoundBufferImportOptions> for SoundBuff
                                 SoundBufferResourceLoadError::UnsupportedFormat,
                             );
 
-                            Log::err(format!("Unable to load sound buffer from {:?}!", path));
+                            Log::asset_err(
+                                &path,
+                                format!("Unable to load sound buffer from {:?}!", path),
+                            );
                         }
                     }
                 }
                 Err(e) => {
-                    Log::err(format!("Invalid data source for sound buffer: {:?}", e));
+                    Log::asset_err(
+                        &path,
+                        format!("Invalid data source for sound buffer: {:?}", e),
+                    );
 
                     resource
                         .state()
@@ -35,15 +35,15 @@ impl ResourceLoader<Texture, TextureImportOptions> for TextureLoader {
             match TextureData::load_from_file(&path, import_options.compression, gen_mip_maps).await
             {
                 Ok(mut raw_texture) => {
-                    Log::info(format!(
-                        "Texture {:?} is loaded in {:?}!",
-                        path,
-                        time.elapsed()
-                    ));
+                    Log::asset_info(
+                        &path,
+                        format!("Texture {:?} is loaded in {:?}!", path, time.elapsed()),
+                    );
 
                     raw_texture.set_magnification_filter(import_options.magnification_filter);
                     raw_texture.set_minification_filter(import_options.minification_filter);
                     raw_texture.set_anisotropy_level(import_options.anisotropy);
+                    raw_texture.set_lod_bias(import_options.lod_bias);
                     raw_texture.set_s_wrap_mode(import_options.s_wrap_mode);
                     raw_texture.set_t_wrap_mode(import_options.t_wrap_mode);
 
@@ -52,10 +52,10 @@ impl ResourceLoader<Texture, TextureImportOptions> for TextureLoader {
                     event_broadcaster.broadcast_loaded_or_reloaded(texture, reload);
                 }
                 Err(error) => {
-                    Log::err(format!(
-                        "Unable to load texture {:?}! Reason {:?}",
-                        &path, &error
-                    ));
+                    Log::asset_err(
+                        &path,
+                        format!("Unable to load texture {:?}! Reason {:?}", &path, &error),
+                    );
 
                     texture.state().commit_error(path, error);
                 }
@@ -12,6 +12,7 @@ use crate::{
             container::{Container, ResourceContainer},
             loader::{
                 curve::CurveLoader,
+                localization::LocalizationLoader,
                 model::ModelLoader,
                 shader::ShaderLoader,
                 sound::{SoundBufferImportOptions, SoundBufferLoader},
@@ -25,6 +26,7 @@ use crate::{
     material::shader::{Shader, ShaderImportOptions},
     resource::{
         curve::{CurveImportOptions, CurveResource},
+        localization::{LocalizationImportOptions, LocalizationResource},
         model::{Model, ModelImportOptions},
         texture::{Texture, TextureError, TextureImportOptions, TextureState},
     },
@@ -35,7 +37,7 @@ use std::{
     fmt::{Debug, Display, Formatter},
     future::Future,
     ops::Deref,
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
@@ -60,6 +62,9 @@ pub struct ContainersStorage {
 
     /// Container for curve resources.
     pub curves: ResourceContainer<CurveResource, CurveImportOptions>,
+
+    /// Container for localization resources.
+    pub localizations: ResourceContainer<LocalizationResource, LocalizationImportOptions>,
 }
 
 impl ContainersStorage {
@@ -103,6 +108,14 @@ impl ContainersStorage {
         self.curves.set_loader(loader);
     }
 
+    /// Sets a custom localization loader.
+    pub fn set_localization_loader<L>(&mut self, loader: L)
+    where
+        L: 'static + ResourceLoader<LocalizationResource, LocalizationImportOptions>,
+    {
+        self.localizations.set_loader(loader);
+    }
+
     /// Wait until all resources are loaded (or failed to load).
     pub fn get_wait_context(&self) -> ResourceWaitContext {
         ResourceWaitContext {
@@ -111,6 +124,7 @@ impl ContainersStorage {
             shaders: self.shaders.resources(),
             textures: self.textures.resources(),
             sound_buffers: self.sound_buffers.resources(),
+            localizations: self.localizations.resources(),
         }
     }
 }
@@ -123,6 +137,7 @@ pub struct ResourceWaitContext {
     shaders: Vec<Shader>,
     textures: Vec<Texture>,
     sound_buffers: Vec<SoundBufferResource>,
+    localizations: Vec<LocalizationResource>,
 }
 
 impl ResourceWaitContext {
@@ -154,6 +169,7 @@ impl ResourceWaitContext {
             && check_container(&self.shaders)
             && check_container(&self.textures)
             && check_container(&self.sound_buffers)
+            && check_container(&self.localizations)
     }
 }
 /// See module docs.
@@ -219,7 +235,8 @@ impl ResourceManager {
             ),
             sound_buffers: ResourceContainer::new(task_pool.clone(), Box::new(SoundBufferLoader)),
             shaders: ResourceContainer::new(task_pool.clone(), Box::new(ShaderLoader)),
-            curves: ResourceContainer::new(task_pool, Box::new(CurveLoader)),
+            curves: ResourceContainer::new(task_pool.clone(), Box::new(CurveLoader)),
+            localizations: ResourceContainer::new(task_pool, Box::new(LocalizationLoader)),
         });
 
         resource_manager
@@ -349,6 +366,18 @@ impl ResourceManager {
         self.state().containers_mut().curves.request(path)
     }
 
+    /// Tries to load a new localization resource (`.ftl` or `.po` file) from given path or get
+    /// instance of existing, if any. This method is asynchronous, it immediately returns a resource
+    /// which can be shared across multiple places, the loading may fail, but it is internal state
+    /// of the resource.
+    ///
+    /// # Async/.await
+    ///
+    /// Each localization resource implements Future trait and can be used in async contexts.
+    pub fn request_localization<P: AsRef<Path>>(&self, path: P) -> LocalizationResource {
+        self.state().containers_mut().localizations.request(path)
+    }
+
     /// Reloads every loaded texture. This method is asynchronous, internally it uses thread pool
     /// to run reload on separate thread per texture.
     pub async fn reload_textures(&self) {
@@ -377,6 +406,17 @@ impl ResourceManager {
         join_all(resources).await;
     }
 
+    /// Reloads every loaded localization resource. This method is asynchronous, internally it uses
+    /// thread pool to run reload on separate thread per resource.
+    pub async fn reload_localizations(&self) {
+        let resources = self
+            .state()
+            .containers_mut()
+            .localizations
+            .reload_resources();
+        join_all(resources).await;
+    }
+
     /// Reloads every loaded sound buffer. This method is asynchronous, internally it uses thread pool
     /// to run reload on separate thread per sound buffer.
     pub async fn reload_sound_buffers(&self) {
@@ -388,6 +428,24 @@ impl ResourceManager {
         join_all(resources).await;
     }
 
+    /// Changes a path of a loaded resource of any kind, without reloading it. It is useful to keep
+    /// loaded resources (and everything that uses them) in sync with moved or renamed files, so
+    /// they will be saved with new paths. Returns `false` if there is no resource with the given
+    /// path.
+    pub fn set_resource_path<P: AsRef<Path>>(&self, path: P, new_path: PathBuf) -> bool {
+        let path = path.as_ref();
+        let state = self.state();
+        let containers = state.containers();
+        containers.textures.set_resource_path(path, new_path.clone())
+            | containers.models.set_resource_path(path, new_path.clone())
+            | containers
+                .sound_buffers
+                .set_resource_path(path, new_path.clone())
+            | containers.shaders.set_resource_path(path, new_path.clone())
+            | containers.curves.set_resource_path(path, new_path.clone())
+            | containers.localizations.set_resource_path(path, new_path)
+    }
+
     /// Reloads all loaded resources. Normally it should never be called, because it is **very** heavy
     /// method! This method is asynchronous, it uses all available CPU power to reload resources as
     /// fast as possible.
@@ -398,6 +456,7 @@ impl ResourceManager {
             self.reload_sound_buffers(),
             self.reload_shaders(),
             self.reload_curve_resources(),
+            self.reload_localizations(),
         );
     }
 }
@@ -440,6 +499,7 @@ impl ResourceManagerState {
             + containers.models.count_pending_resources()
             + containers.shaders.count_pending_resources()
             + containers.curves.count_pending_resources()
+            + containers.localizations.count_pending_resources()
     }
 
     /// Returns total amount of loaded resources.
@@ -450,6 +510,7 @@ impl ResourceManagerState {
             + containers.models.count_loaded_resources()
             + containers.shaders.count_loaded_resources()
             + containers.curves.count_loaded_resources()
+            + containers.localizations.count_loaded_resources()
     }
 
     /// Returns total amount of registered resources.
@@ -460,6 +521,7 @@ impl ResourceManagerState {
             + containers.models.len()
             + containers.shaders.len()
             + containers.curves.len()
+            + containers.localizations.len()
     }
 
     /// Returns percentage of loading progress. This method is useful to show progress on
@@ -483,6 +545,7 @@ impl ResourceManagerState {
         containers.textures.destroy_unused();
         containers.shaders.destroy_unused();
         containers.curves.destroy_unused();
+        containers.localizations.destroy_unused();
     }
 
     /// Update resource containers and do hot-reloading.
@@ -499,6 +562,7 @@ impl ResourceManagerState {
         containers.sound_buffers.update(dt);
         containers.shaders.update(dt);
         containers.curves.update(dt);
+        containers.localizations.update(dt);
 
         if let Some(watcher) = self.watcher.as_ref() {
             if let Some(evt) = watcher.try_get_event() {
@@ -512,6 +576,7 @@ impl ResourceManagerState {
                                 &mut containers.sound_buffers as &mut dyn Container,
                                 &mut containers.shaders as &mut dyn Container,
                                 &mut containers.curves as &mut dyn Container,
+                                &mut containers.localizations as &mut dyn Container,
                             ] {
                                 if container.try_reload_resource_from_path(&relative_path) {
                                     Log::info(format!(
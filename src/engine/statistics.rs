@@ -0,0 +1,223 @@
+//! Engine statistics. See [`EngineStatistics`] docs for more info.
+
+use crate::{
+    asset::ResourceState,
+    core::memory::{AllocationCounters, Subsystem},
+    engine::resource_manager::ResourceManager,
+    gui::UserInterface,
+    renderer::{MemoryStatistics, Renderer},
+    scene::SceneContainer,
+};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// Time spent by every engine system in a frame.
+#[derive(Clone, Default, Debug)]
+pub struct FrameTimings {
+    /// Total time of scene updates, it includes physics and animation time.
+    pub scenes: Duration,
+    /// Time of 3D and 2D physics simulation of every scene.
+    pub physics: Duration,
+    /// Time of animation players and animation blending state machines update of every scene.
+    pub animation: Duration,
+    /// Time of sound update of every scene.
+    pub sound: Duration,
+    /// Time of plugins update (including UI messages handling by plugins).
+    pub plugins: Duration,
+    /// Time of scripts update.
+    pub scripts: Duration,
+    /// Time of user interface update (layout, message processing, etc.).
+    pub ui: Duration,
+    /// Time of rendering, it includes submission of draw commands and swapping of buffers.
+    pub render: Duration,
+}
+
+impl FrameTimings {
+    /// Returns total time of the frame. Physics, animation and sound are parts of scene updates, so
+    /// they're not summed.
+    pub fn total(&self) -> Duration {
+        self.scenes + self.plugins + self.scripts + self.ui + self.render
+    }
+}
+
+/// Counters of objects stored in pools.
+#[derive(Clone, Default, Debug)]
+pub struct PoolStatistics {
+    /// Amount of scenes.
+    pub scenes: usize,
+    /// Amount of alive scene nodes in every scene.
+    pub scene_nodes: usize,
+    /// Total capacity of node pools of every scene (alive nodes + free slots).
+    pub scene_nodes_capacity: usize,
+    /// Amount of alive widgets of the user interface.
+    pub ui_nodes: usize,
+    /// Capacity of widget pool of the user interface (alive widgets + free slots).
+    pub ui_nodes_capacity: usize,
+}
+
+/// Counters of resources of the resource manager.
+#[derive(Clone, Default, Debug)]
+pub struct ResourceStatistics {
+    /// Amount of textures.
+    pub textures: usize,
+    /// Amount of models.
+    pub models: usize,
+    /// Amount of sound buffers.
+    pub sound_buffers: usize,
+    /// Amount of shaders.
+    pub shaders: usize,
+    /// Amount of curves.
+    pub curves: usize,
+    /// Amount of localizations.
+    pub localizations: usize,
+    /// Amount of resources, that are still loading.
+    pub pending: usize,
+    /// Amount of memory (in bytes) occupied by pixels of loaded textures in RAM.
+    pub texture_memory: usize,
+}
+
+/// A snapshot of engine statistics, that describes the last complete frame: time spent in every
+/// engine system, amount of objects in pools and resource memory. It could be used to show a
+/// custom performance HUD or in automated performance regression tests. Use
+/// [`crate::engine::Engine::statistics`] or [`crate::plugin::PluginContext::statistics`] to
+/// get it.
+#[derive(Clone, Default, Debug)]
+pub struct EngineStatistics {
+    /// Index of the frame the statistics was collected for.
+    pub frame: u64,
+    /// Time step of the frame (in seconds).
+    pub dt: f32,
+    /// Time spent by every engine system.
+    pub timings: FrameTimings,
+    /// Pool counters.
+    pub pools: PoolStatistics,
+    /// Resource counters.
+    pub resources: ResourceStatistics,
+    /// Estimated GPU memory usage of the renderer.
+    pub gpu_memory: MemoryStatistics,
+    /// Heap allocations of every engine subsystem, available only if
+    /// [`crate::core::memory::TrackingAllocator`] is installed as global allocator.
+    pub allocations: AllocationCounters,
+    /// Amount of bytes allocated in the frame arena of the main thread, see
+    /// [`crate::core::memory::with_frame_arena`].
+    pub frame_arena: usize,
+}
+
+impl EngineStatistics {
+    pub(crate) fn collect_scenes(&mut self, scenes: &SceneContainer) {
+        self.timings.physics = Duration::default();
+        self.timings.animation = Duration::default();
+        self.timings.sound = Duration::default();
+        self.pools.scenes = 0;
+        self.pools.scene_nodes = 0;
+        self.pools.scene_nodes_capacity = 0;
+
+        for scene in scenes.iter() {
+            let graph = &scene.performance_statistics.graph;
+            self.timings.physics += graph.physics.total() + graph.physics2d.total();
+            self.timings.animation += graph.animation_time;
+            self.timings.sound += graph.sound_update_time;
+
+            self.pools.scenes += 1;
+            self.pools.scene_nodes += scene.graph.node_count() as usize;
+            self.pools.scene_nodes_capacity += scene.graph.capacity() as usize;
+        }
+    }
+
+    pub(crate) fn collect_counters(
+        &mut self,
+        ui: &UserInterface,
+        resource_manager: &ResourceManager,
+        renderer: &Renderer,
+    ) {
+        self.pools.ui_nodes = ui.nodes().alive_count() as usize;
+        self.pools.ui_nodes_capacity = ui.nodes().get_capacity() as usize;
+
+        let state = resource_manager.state();
+        let containers = state.containers();
+        self.resources = ResourceStatistics {
+            textures: containers.textures.len(),
+            models: containers.models.len(),
+            sound_buffers: containers.sound_buffers.len(),
+            shaders: containers.shaders.len(),
+            curves: containers.curves.len(),
+            localizations: containers.localizations.len(),
+            pending: state.count_pending_resources(),
+            texture_memory: containers
+                .textures
+                .iter()
+                .map(|texture| match *texture.state() {
+                    ResourceState::Ok(ref data) => data.data().len(),
+                    _ => 0,
+                })
+                .sum(),
+        };
+
+        self.gpu_memory = renderer.get_statistics().memory;
+    }
+}
+
+impl Display for EngineStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Frame: {} (dt: {:.2} ms)\n\
+            Total: {:?}\n\
+            \tScenes: {:?}\n\
+            \t\tPhysics: {:?}\n\
+            \t\tAnimation: {:?}\n\
+            \t\tSound: {:?}\n\
+            \tPlugins: {:?}\n\
+            \tScripts: {:?}\n\
+            \tUI: {:?}\n\
+            \tRender: {:?}\n\
+            Scenes: {}, Nodes: {}/{}, Widgets: {}/{}\n\
+            Textures: {} ({:.2} Mb), Models: {}, Sound Buffers: {}, Shaders: {}, Pending: {}",
+            self.frame,
+            self.dt * 1000.0,
+            self.timings.total(),
+            self.timings.scenes,
+            self.timings.physics,
+            self.timings.animation,
+            self.timings.sound,
+            self.timings.plugins,
+            self.timings.scripts,
+            self.timings.ui,
+            self.timings.render,
+            self.pools.scenes,
+            self.pools.scene_nodes,
+            self.pools.scene_nodes_capacity,
+            self.pools.ui_nodes,
+            self.pools.ui_nodes_capacity,
+            self.resources.textures,
+            self.resources.texture_memory as f32 / (1024.0 * 1024.0),
+            self.resources.models,
+            self.resources.sound_buffers,
+            self.resources.shaders,
+            self.resources.pending,
+        )?;
+
+        write!(f, "\nFrame Arena: {} bytes", self.frame_arena)?;
+        if self.allocations.tracking {
+            write!(
+                f,
+                "\nAllocations: {} ({:.2} Kb)",
+                self.allocations.total_count(),
+                self.allocations.total_bytes() as f32 / 1024.0
+            )?;
+            for subsystem in Subsystem::ALL {
+                write!(
+                    f,
+                    "\n\t{:?}: {} ({:.2} Kb)",
+                    subsystem,
+                    self.allocations.count(subsystem),
+                    self.allocations.bytes(subsystem) as f32 / 1024.0
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
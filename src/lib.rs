@@ -10,6 +10,7 @@
 #![allow(clippy::approx_constant)]
 
 pub mod animation;
+pub mod console;
 pub mod engine;
 pub mod material;
 pub mod plugin;
@@ -17,7 +18,9 @@ pub mod renderer;
 pub mod resource;
 pub mod scene;
 pub mod script;
+pub mod settings;
 pub mod utils;
+pub mod video;
 
 pub use crate::core::rand;
 pub use fxhash;
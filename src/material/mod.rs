@@ -25,6 +25,7 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 pub mod shader;
+pub mod shader_graph;
 
 /// A value of a property that will be used for rendering with a shader.
 ///
@@ -0,0 +1,1380 @@
+//! Shader graph is a visual way of making shaders. Instead of writing shader source code by hand,
+//! you combine nodes (texture samples, math operations, properties, etc.) and connect them to
+//! a PBR output node. The graph then could be compiled to the engine's shader asset format.
+//!
+//! For more info see [`ShaderGraph`] struct docs.
+
+use crate::{
+    core::algebra::{Vector2, Vector3, Vector4},
+    material::shader::{SamplerFallback, Shader, ShaderError},
+};
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Write};
+
+/// Type of a value that flows between the nodes of a shader graph.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// Real number.
+    Float,
+    /// Two-dimensional vector.
+    Vector2,
+    /// Three-dimensional vector.
+    Vector3,
+    /// Four-dimensional vector.
+    Vector4,
+}
+
+impl ValueType {
+    /// Returns name of the type in GLSL.
+    pub fn glsl_name(self) -> &'static str {
+        match self {
+            ValueType::Float => "float",
+            ValueType::Vector2 => "vec2",
+            ValueType::Vector3 => "vec3",
+            ValueType::Vector4 => "vec4",
+        }
+    }
+
+    /// Returns amount of components in the type.
+    pub fn components(self) -> usize {
+        match self {
+            ValueType::Float => 1,
+            ValueType::Vector2 => 2,
+            ValueType::Vector3 => 3,
+            ValueType::Vector4 => 4,
+        }
+    }
+
+    fn widest(self, other: ValueType) -> ValueType {
+        if self.components() >= other.components() {
+            self
+        } else {
+            other
+        }
+    }
+
+    /// Converts an expression of the type to the other type. Scalars are broadcast to every
+    /// component, vectors are truncated or extended with zeros (`w` is extended with one).
+    fn convert(self, expr: &str, to: ValueType) -> String {
+        if self == to {
+            expr.to_owned()
+        } else if self == ValueType::Float {
+            format!("{}({})", to.glsl_name(), expr)
+        } else if to == ValueType::Float {
+            format!("({}).x", expr)
+        } else if to.components() < self.components() {
+            format!("({}).{}", expr, &"xyzw"[..to.components()])
+        } else {
+            let mut result = format!("{}({}", to.glsl_name(), expr);
+            for i in self.components()..to.components() {
+                result += if i == 3 { ", 1.0" } else { ", 0.0" };
+            }
+            result += ")";
+            result
+        }
+    }
+}
+
+/// A value of a constant or a property of a shader graph.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ShaderGraphValue {
+    /// Real number.
+    Float(f32),
+    /// Two-dimensional vector.
+    Vector2(Vector2<f32>),
+    /// Three-dimensional vector.
+    Vector3(Vector3<f32>),
+    /// Four-dimensional vector.
+    Vector4(Vector4<f32>),
+    /// An sRGB color, it is passed to a shader as four-dimensional vector. Colors of properties
+    /// are converted to linear color space by the engine, colors of constants are used as is.
+    Color {
+        /// Red.
+        r: u8,
+        /// Green.
+        g: u8,
+        /// Blue.
+        b: u8,
+        /// Alpha.
+        a: u8,
+    },
+}
+
+impl Default for ShaderGraphValue {
+    fn default() -> Self {
+        Self::Float(0.0)
+    }
+}
+
+impl ShaderGraphValue {
+    /// Returns type of the value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            ShaderGraphValue::Float(_) => ValueType::Float,
+            ShaderGraphValue::Vector2(_) => ValueType::Vector2,
+            ShaderGraphValue::Vector3(_) => ValueType::Vector3,
+            ShaderGraphValue::Vector4(_) | ShaderGraphValue::Color { .. } => ValueType::Vector4,
+        }
+    }
+
+    fn glsl_literal(&self) -> String {
+        match self {
+            ShaderGraphValue::Float(v) => format!("{:?}", v),
+            ShaderGraphValue::Vector2(v) => format!("vec2({:?}, {:?})", v.x, v.y),
+            ShaderGraphValue::Vector3(v) => format!("vec3({:?}, {:?}, {:?})", v.x, v.y, v.z),
+            ShaderGraphValue::Vector4(v) => {
+                format!("vec4({:?}, {:?}, {:?}, {:?})", v.x, v.y, v.z, v.w)
+            }
+            ShaderGraphValue::Color { r, g, b, a } => format!(
+                "vec4({:?}, {:?}, {:?}, {:?})",
+                *r as f32 / 255.0,
+                *g as f32 / 255.0,
+                *b as f32 / 255.0,
+                *a as f32 / 255.0
+            ),
+        }
+    }
+
+    fn ron_property_kind(&self) -> String {
+        match self {
+            ShaderGraphValue::Float(v) => format!("Float({:?})", v),
+            ShaderGraphValue::Vector2(v) => format!("Vector2([{:?}, {:?}])", v.x, v.y),
+            ShaderGraphValue::Vector3(v) => {
+                format!("Vector3([{:?}, {:?}, {:?}])", v.x, v.y, v.z)
+            }
+            ShaderGraphValue::Vector4(v) => {
+                format!("Vector4([{:?}, {:?}, {:?}, {:?}])", v.x, v.y, v.z, v.w)
+            }
+            ShaderGraphValue::Color { r, g, b, a } => {
+                format!("Color(r: {}, g: {}, b: {}, a: {})", r, g, b, a)
+            }
+        }
+    }
+}
+
+/// Built-in per-fragment values.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderGraphInput {
+    /// First texture coordinates of a mesh (`vec2`).
+    TexCoord,
+    /// Second texture coordinates of a mesh, usually used for lightmaps (`vec2`).
+    SecondTexCoord,
+    /// Position of a fragment in world space (`vec3`).
+    WorldPosition,
+    /// Normal of a fragment in world space (`vec3`).
+    WorldNormal,
+    /// Normalized direction from a fragment to the camera in world space (`vec3`).
+    ViewDirection,
+}
+
+/// Math operation with two arguments.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BinaryOperator {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Subtract,
+    /// `a * b` (component-wise)
+    Multiply,
+    /// `a / b` (component-wise)
+    Divide,
+    /// `min(a, b)`
+    Min,
+    /// `max(a, b)`
+    Max,
+    /// `pow(a, b)`
+    Power,
+    /// `dot(a, b)`, the result is always a scalar.
+    Dot,
+}
+
+/// Math operation with one argument.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnaryOperator {
+    /// `-a`
+    Negate,
+    /// `1 - a`
+    OneMinus,
+    /// `abs(a)`
+    Abs,
+    /// `sin(a)`
+    Sin,
+    /// `cos(a)`
+    Cos,
+    /// `fract(a)`
+    Fract,
+    /// `floor(a)`
+    Floor,
+    /// `sqrt(a)`
+    Sqrt,
+    /// `clamp(a, 0, 1)`
+    Saturate,
+    /// `normalize(a)`
+    Normalize,
+    /// `length(a)`, the result is always a scalar.
+    Length,
+}
+
+/// Kind of a shader graph node, it defines inputs, outputs and produced code of a node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ShaderGraphNodeKind {
+    /// A constant value. Has one output.
+    Constant(ShaderGraphValue),
+    /// A value that is exposed as a material property. Has one output.
+    Property {
+        /// Name of the property, it must be a valid identifier.
+        name: String,
+        /// Default value of the property.
+        value: ShaderGraphValue,
+    },
+    /// Samples a texture that is exposed as a material property. Takes texture coordinates and
+    /// has `RGBA`, `RGB`, `R`, `G`, `B`, `A` outputs.
+    TextureSample {
+        /// Name of the property, it must be a valid identifier.
+        name: String,
+        /// A value that will be used when there is no texture.
+        fallback: SamplerFallback,
+    },
+    /// Built-in per-fragment value. Has one output.
+    Input(ShaderGraphInput),
+    /// Math operation with two arguments (`A` and `B`). Has one output.
+    Binary(BinaryOperator),
+    /// Math operation with one argument. Has one output.
+    Unary(UnaryOperator),
+    /// Linear interpolation between `A` and `B` by `T`. Has one output.
+    Lerp,
+    /// Splits a vector to `X`, `Y`, `Z`, `W` components.
+    Split,
+    /// Combines `X`, `Y`, `Z`, `W` scalars into `XYZW`, `XYZ` and `XY` vectors.
+    Combine,
+    /// Final node of every graph that defines parameters of physically-based material. There must
+    /// be exactly one output node in a graph.
+    PbrOutput {
+        /// Fragments with albedo alpha less than the value will be discarded. Zero disables
+        /// alpha test.
+        alpha_cutoff: f32,
+    },
+}
+
+impl Default for ShaderGraphNodeKind {
+    fn default() -> Self {
+        Self::Constant(Default::default())
+    }
+}
+
+/// Description of an input socket of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SocketDefinition {
+    /// Name of the socket.
+    pub name: &'static str,
+    /// Type of the socket. `None` means that the socket accepts values of any type.
+    pub value_type: Option<ValueType>,
+    /// GLSL expression that will be used when the socket is not connected.
+    pub default: &'static str,
+}
+
+const fn socket(
+    name: &'static str,
+    value_type: Option<ValueType>,
+    default: &'static str,
+) -> SocketDefinition {
+    SocketDefinition {
+        name,
+        value_type,
+        default,
+    }
+}
+
+impl ShaderGraphNodeKind {
+    /// Returns human-readable name of the node kind.
+    pub fn name(&self) -> String {
+        match self {
+            ShaderGraphNodeKind::Constant(value) => format!("{:?} Constant", value.value_type()),
+            ShaderGraphNodeKind::Property { name, .. } => format!("Property {}", name),
+            ShaderGraphNodeKind::TextureSample { name, .. } => format!("Texture {}", name),
+            ShaderGraphNodeKind::Input(input) => format!("{:?}", input),
+            ShaderGraphNodeKind::Binary(op) => format!("{:?}", op),
+            ShaderGraphNodeKind::Unary(op) => format!("{:?}", op),
+            ShaderGraphNodeKind::Lerp => "Lerp".to_owned(),
+            ShaderGraphNodeKind::Split => "Split".to_owned(),
+            ShaderGraphNodeKind::Combine => "Combine".to_owned(),
+            ShaderGraphNodeKind::PbrOutput { .. } => "PBR Output".to_owned(),
+        }
+    }
+
+    /// Returns a list of input sockets of the node.
+    pub fn inputs(&self) -> Vec<SocketDefinition> {
+        match self {
+            ShaderGraphNodeKind::Constant(_)
+            | ShaderGraphNodeKind::Property { .. }
+            | ShaderGraphNodeKind::Input(_) => vec![],
+            ShaderGraphNodeKind::TextureSample { .. } => {
+                vec![socket("UV", Some(ValueType::Vector2), "texCoord")]
+            }
+            ShaderGraphNodeKind::Binary(op) => {
+                let default = match op {
+                    BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Power => {
+                        "1.0"
+                    }
+                    _ => "0.0",
+                };
+                vec![socket("A", None, default), socket("B", None, default)]
+            }
+            ShaderGraphNodeKind::Unary(_) => vec![socket("A", None, "0.0")],
+            ShaderGraphNodeKind::Lerp => vec![
+                socket("A", None, "0.0"),
+                socket("B", None, "1.0"),
+                socket("T", None, "0.5"),
+            ],
+            ShaderGraphNodeKind::Split => {
+                vec![socket("Value", Some(ValueType::Vector4), "vec4(0.0)")]
+            }
+            ShaderGraphNodeKind::Combine => vec![
+                socket("X", Some(ValueType::Float), "0.0"),
+                socket("Y", Some(ValueType::Float), "0.0"),
+                socket("Z", Some(ValueType::Float), "0.0"),
+                socket("W", Some(ValueType::Float), "1.0"),
+            ],
+            ShaderGraphNodeKind::PbrOutput { .. } => vec![
+                socket("Albedo", Some(ValueType::Vector4), "vec4(1.0)"),
+                socket("Normal", Some(ValueType::Vector3), "vec3(0.0, 0.0, 1.0)"),
+                socket("Metallic", Some(ValueType::Float), "0.0"),
+                socket("Roughness", Some(ValueType::Float), "1.0"),
+                socket("AO", Some(ValueType::Float), "1.0"),
+                socket("Emission", Some(ValueType::Vector3), "vec3(0.0)"),
+            ],
+        }
+    }
+
+    /// Returns a list of names of output sockets of the node.
+    pub fn outputs(&self) -> Vec<&'static str> {
+        match self {
+            ShaderGraphNodeKind::TextureSample { .. } => vec!["RGBA", "RGB", "R", "G", "B", "A"],
+            ShaderGraphNodeKind::Split => vec!["X", "Y", "Z", "W"],
+            ShaderGraphNodeKind::Combine => vec!["XYZW", "XYZ", "XY"],
+            ShaderGraphNodeKind::PbrOutput { .. } => vec![],
+            _ => vec!["Value"],
+        }
+    }
+}
+
+/// A node of a shader graph.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ShaderGraphNode {
+    /// Kind of the node.
+    pub kind: ShaderGraphNodeKind,
+    /// Position of the node in the editor, it does not affect the produced shader.
+    pub position: Vector2<f32>,
+}
+
+/// A connection between an output socket of a `source` node and an input socket of a `dest` node.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShaderGraphConnection {
+    /// Index of the source node.
+    pub source: usize,
+    /// Index of the output socket of the source node.
+    pub output: usize,
+    /// Index of the destination node.
+    pub dest: usize,
+    /// Index of the input socket of the destination node.
+    pub input: usize,
+}
+
+/// A set of possible errors that can occur during shader graph editing or compilation.
+#[derive(Debug)]
+pub enum ShaderGraphError {
+    /// There is no output node in the graph.
+    NoOutput,
+    /// There is more than one output node in the graph.
+    MultipleOutputs,
+    /// The graph has a cycle.
+    Cycle,
+    /// There is no node with the given index.
+    InvalidNode(usize),
+    /// There is no socket with the given index in the node.
+    InvalidSocket {
+        /// Index of the node.
+        node: usize,
+        /// Index of the socket.
+        socket: usize,
+    },
+    /// Property name is not a valid identifier or it is reserved.
+    InvalidName(String),
+    /// There are two properties with the same name, but different types.
+    PropertyConflict(String),
+    /// The graph cannot be serialized.
+    Serialization(ron::Error),
+    /// The graph cannot be deserialized.
+    Deserialization(ron::error::SpannedError),
+    /// Produced shader cannot be loaded.
+    Shader(ShaderError),
+}
+
+impl Display for ShaderGraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderGraphError::NoOutput => write!(f, "There is no output node in the graph."),
+            ShaderGraphError::MultipleOutputs => {
+                write!(f, "There is more than one output node in the graph.")
+            }
+            ShaderGraphError::Cycle => write!(f, "The graph has a cycle."),
+            ShaderGraphError::InvalidNode(node) => write!(f, "There is no node {}.", node),
+            ShaderGraphError::InvalidSocket { node, socket } => {
+                write!(f, "There is no socket {} in node {}.", socket, node)
+            }
+            ShaderGraphError::InvalidName(name) => {
+                write!(f, "{} is not a valid property name.", name)
+            }
+            ShaderGraphError::PropertyConflict(name) => write!(
+                f,
+                "There are multiple properties {} with different types.",
+                name
+            ),
+            ShaderGraphError::Serialization(v) => {
+                write!(f, "A serialization error has occurred {:?}", v)
+            }
+            ShaderGraphError::Deserialization(v) => {
+                write!(f, "A deserialization error has occurred {:?}", v)
+            }
+            ShaderGraphError::Shader(v) => write!(f, "Produced shader is invalid: {}", v),
+        }
+    }
+}
+
+impl From<ShaderError> for ShaderGraphError {
+    fn from(e: ShaderError) -> Self {
+        Self::Shader(e)
+    }
+}
+
+impl From<ron::Error> for ShaderGraphError {
+    fn from(e: ron::Error) -> Self {
+        Self::Serialization(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for ShaderGraphError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::Deserialization(e)
+    }
+}
+
+/// Shader graph is a set of nodes connected with each other, the graph is compiled to a shader
+/// with deferred (`GBuffer`) and shadow render passes, so the resulting shader could be used
+/// in the same way as the standard shader.
+///
+/// # Example
+///
+/// ```no_run
+/// # use fyrox::material::{
+/// #     shader::SamplerFallback,
+/// #     shader_graph::{BinaryOperator, ShaderGraph, ShaderGraphNodeKind, ShaderGraphValue},
+/// # };
+/// let mut graph = ShaderGraph::new("MyShader");
+///
+/// let texture = graph.add_node(ShaderGraphNodeKind::TextureSample {
+///     name: "diffuseTexture".to_owned(),
+///     fallback: SamplerFallback::White,
+/// });
+/// let tint = graph.add_node(ShaderGraphNodeKind::Property {
+///     name: "tint".to_owned(),
+///     value: ShaderGraphValue::Color { r: 255, g: 0, b: 0, a: 255 },
+/// });
+/// let multiply = graph.add_node(ShaderGraphNodeKind::Binary(BinaryOperator::Multiply));
+/// let output = graph.add_node(ShaderGraphNodeKind::PbrOutput { alpha_cutoff: 0.0 });
+///
+/// graph.connect(texture, 0, multiply, 0).unwrap();
+/// graph.connect(tint, 0, multiply, 1).unwrap();
+/// graph.connect(multiply, 0, output, 0).unwrap();
+///
+/// let shader = graph.compile_shader().unwrap();
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ShaderGraph {
+    /// Name of the shader.
+    pub name: String,
+    /// A set of nodes of the graph.
+    pub nodes: Vec<ShaderGraphNode>,
+    /// A set of connections between nodes.
+    pub connections: Vec<ShaderGraphConnection>,
+}
+
+/// Identifiers that are used by the generated shader (including the template), properties with
+/// such names would clash with them.
+const RESERVED_NAMES: &[&str] = &[
+    // Vertex attributes, varyings, outputs and uniforms of the template.
+    "vertexPosition",
+    "vertexTexCoord",
+    "vertexNormal",
+    "vertexTangent",
+    "vertexSecondTexCoord",
+    "boneWeights",
+    "boneIndices",
+    "position",
+    "normal",
+    "texCoord",
+    "tangent",
+    "binormal",
+    "secondTexCoord",
+    "outColor",
+    "outNormal",
+    "outAmbient",
+    "outMaterial",
+    "outDecalMask",
+    "layerIndex",
+    // Local variables.
+    "tangentSpace",
+    "albedo",
+    "localPosition",
+    "localNormal",
+    "localTangent",
+    "worldPosition",
+    "vertex",
+    "depth",
+    "nm",
+    "m",
+    "i",
+    // Built-in functions.
+    "main",
+    "abs",
+    "clamp",
+    "cos",
+    "cross",
+    "dot",
+    "floor",
+    "fract",
+    "length",
+    "max",
+    "min",
+    "mix",
+    "normalize",
+    "pow",
+    "sin",
+    "sqrt",
+    "texture",
+    // Types and keywords.
+    "bool",
+    "int",
+    "uint",
+    "float",
+    "vec2",
+    "vec3",
+    "vec4",
+    "mat3",
+    "mat4",
+    "sampler2D",
+    "in",
+    "out",
+    "uniform",
+    "layout",
+    "location",
+    "if",
+    "else",
+    "for",
+    "discard",
+    "return",
+    "void",
+    "true",
+    "false",
+    "const",
+];
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_correctly = chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_');
+    // Names like `n0`, `n1`, etc. are used for generated node variables.
+    let is_node_variable = name.starts_with('n') && name[1..].chars().all(|c| c.is_ascii_digit());
+    starts_correctly
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("fyrox_")
+        && !name.starts_with("gl_")
+        && !is_node_variable
+        && !RESERVED_NAMES.contains(&name)
+}
+
+#[derive(PartialEq)]
+enum PropertyEntry {
+    Value(ShaderGraphValue),
+    Sampler(SamplerFallback),
+}
+
+#[derive(Default)]
+struct CompilationContext {
+    properties: Vec<(String, PropertyEntry)>,
+    body: String,
+    // Expressions and types of outputs of every compiled node.
+    outputs: FxHashMap<usize, Vec<(String, ValueType)>>,
+}
+
+impl CompilationContext {
+    fn add_property(&mut self, name: &str, entry: PropertyEntry) -> Result<(), ShaderGraphError> {
+        if !is_valid_name(name) {
+            return Err(ShaderGraphError::InvalidName(name.to_owned()));
+        }
+
+        if let Some((_, existing)) = self.properties.iter().find(|(n, _)| n == name) {
+            let compatible = match (existing, &entry) {
+                (PropertyEntry::Value(a), PropertyEntry::Value(b)) => {
+                    a.value_type() == b.value_type()
+                }
+                (PropertyEntry::Sampler(_), PropertyEntry::Sampler(_)) => true,
+                _ => false,
+            };
+            if compatible {
+                Ok(())
+            } else {
+                Err(ShaderGraphError::PropertyConflict(name.to_owned()))
+            }
+        } else {
+            self.properties.push((name.to_owned(), entry));
+            Ok(())
+        }
+    }
+}
+
+impl ShaderGraph {
+    /// Creates new empty graph.
+    pub fn new<S: AsRef<str>>(name: S) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            nodes: Default::default(),
+            connections: Default::default(),
+        }
+    }
+
+    /// Adds new node to the graph and returns its index.
+    pub fn add_node(&mut self, kind: ShaderGraphNodeKind) -> usize {
+        self.add_node_at(kind, Default::default())
+    }
+
+    /// Adds new node at the given position (in the editor) to the graph and returns its index.
+    pub fn add_node_at(&mut self, kind: ShaderGraphNodeKind, position: Vector2<f32>) -> usize {
+        self.nodes.push(ShaderGraphNode { kind, position });
+        self.nodes.len() - 1
+    }
+
+    /// Removes a node and every its connection. Indices of the nodes after the removed one are
+    /// shifted by one.
+    pub fn remove_node(&mut self, index: usize) -> ShaderGraphNode {
+        let node = self.nodes.remove(index);
+        self.connections
+            .retain(|c| c.source != index && c.dest != index);
+        for connection in self.connections.iter_mut() {
+            if connection.source > index {
+                connection.source -= 1;
+            }
+            if connection.dest > index {
+                connection.dest -= 1;
+            }
+        }
+        node
+    }
+
+    /// Connects an output socket of the `source` node with an input socket of the `dest` node.
+    /// Existing connection of the input socket will be replaced. Returns the replaced connection
+    /// if any.
+    pub fn connect(
+        &mut self,
+        source: usize,
+        output: usize,
+        dest: usize,
+        input: usize,
+    ) -> Result<Option<ShaderGraphConnection>, ShaderGraphError> {
+        let source_node = self
+            .nodes
+            .get(source)
+            .ok_or(ShaderGraphError::InvalidNode(source))?;
+        if output >= source_node.kind.outputs().len() {
+            return Err(ShaderGraphError::InvalidSocket {
+                node: source,
+                socket: output,
+            });
+        }
+        let dest_node = self
+            .nodes
+            .get(dest)
+            .ok_or(ShaderGraphError::InvalidNode(dest))?;
+        if input >= dest_node.kind.inputs().len() {
+            return Err(ShaderGraphError::InvalidSocket {
+                node: dest,
+                socket: input,
+            });
+        }
+        if source == dest || self.depends_on(source, dest) {
+            return Err(ShaderGraphError::Cycle);
+        }
+
+        let previous = self.disconnect(dest, input);
+        self.connections.push(ShaderGraphConnection {
+            source,
+            output,
+            dest,
+            input,
+        });
+        Ok(previous)
+    }
+
+    /// Removes a connection of the input socket of the node.
+    pub fn disconnect(&mut self, dest: usize, input: usize) -> Option<ShaderGraphConnection> {
+        let position = self
+            .connections
+            .iter()
+            .position(|c| c.dest == dest && c.input == input)?;
+        Some(self.connections.remove(position))
+    }
+
+    /// Returns a connection of the input socket of the node.
+    pub fn input_connection(&self, dest: usize, input: usize) -> Option<&ShaderGraphConnection> {
+        self.connections
+            .iter()
+            .find(|c| c.dest == dest && c.input == input)
+    }
+
+    /// Checks whether the `node` uses an output of the `other` node (directly or indirectly).
+    pub fn depends_on(&self, node: usize, other: usize) -> bool {
+        let mut stack = vec![node];
+        let mut visited = vec![false; self.nodes.len()];
+        while let Some(current) = stack.pop() {
+            if current == other {
+                return true;
+            }
+            if visited.get(current).cloned().unwrap_or(true) {
+                continue;
+            }
+            visited[current] = true;
+            stack.extend(
+                self.connections
+                    .iter()
+                    .filter(|c| c.dest == current)
+                    .map(|c| c.source),
+            );
+        }
+        false
+    }
+
+    /// Serializes the graph to a string, it could be saved to a file to edit the graph later.
+    pub fn to_ron_string(&self) -> Result<String, ShaderGraphError> {
+        Ok(ron::ser::to_string_pretty(self, Default::default())?)
+    }
+
+    /// Deserializes the graph from a string, produced by [`Self::to_ron_string`].
+    pub fn from_ron_str(str: &str) -> Result<Self, ShaderGraphError> {
+        Ok(ron::de::from_str(str)?)
+    }
+
+    fn compile_node(
+        &self,
+        index: usize,
+        ctx: &mut CompilationContext,
+        visiting: &mut Vec<usize>,
+    ) -> Result<(), ShaderGraphError> {
+        if ctx.outputs.contains_key(&index) {
+            return Ok(());
+        }
+        if visiting.contains(&index) {
+            return Err(ShaderGraphError::Cycle);
+        }
+        visiting.push(index);
+
+        let node = self
+            .nodes
+            .get(index)
+            .ok_or(ShaderGraphError::InvalidNode(index))?;
+
+        // Compile dependencies first and collect expressions of inputs.
+        let mut inputs = Vec::new();
+        for (input_index, definition) in node.kind.inputs().into_iter().enumerate() {
+            let (expr, value_type) =
+                if let Some(connection) = self.input_connection(index, input_index) {
+                    self.compile_node(connection.source, ctx, visiting)?;
+                    ctx.outputs[&connection.source]
+                        .get(connection.output)
+                        .cloned()
+                        .ok_or(ShaderGraphError::InvalidSocket {
+                            node: connection.source,
+                            socket: connection.output,
+                        })?
+                } else {
+                    (
+                        definition.default.to_owned(),
+                        definition.value_type.unwrap_or(ValueType::Float),
+                    )
+                };
+            inputs.push(match definition.value_type {
+                Some(socket_type) => (value_type.convert(&expr, socket_type), socket_type),
+                None => (expr, value_type),
+            });
+        }
+
+        let var = format!("n{}", index);
+        let declare = |ctx: &mut CompilationContext, value_type: ValueType, expr: String| {
+            writeln!(
+                ctx.body,
+                "    {} {} = {};",
+                value_type.glsl_name(),
+                var,
+                expr
+            )
+            .unwrap();
+        };
+
+        let outputs = match &node.kind {
+            ShaderGraphNodeKind::Constant(value) => {
+                declare(ctx, value.value_type(), value.glsl_literal());
+                vec![(var.clone(), value.value_type())]
+            }
+            ShaderGraphNodeKind::Property { name, value } => {
+                ctx.add_property(name, PropertyEntry::Value(value.clone()))?;
+                vec![(name.clone(), value.value_type())]
+            }
+            ShaderGraphNodeKind::TextureSample { name, fallback } => {
+                ctx.add_property(name, PropertyEntry::Sampler(*fallback))?;
+                declare(
+                    ctx,
+                    ValueType::Vector4,
+                    format!("texture({}, {})", name, inputs[0].0),
+                );
+                vec![
+                    (var.clone(), ValueType::Vector4),
+                    (format!("{}.rgb", var), ValueType::Vector3),
+                    (format!("{}.r", var), ValueType::Float),
+                    (format!("{}.g", var), ValueType::Float),
+                    (format!("{}.b", var), ValueType::Float),
+                    (format!("{}.a", var), ValueType::Float),
+                ]
+            }
+            ShaderGraphNodeKind::Input(input) => {
+                let (expr, value_type) = match input {
+                    ShaderGraphInput::TexCoord => ("texCoord", ValueType::Vector2),
+                    ShaderGraphInput::SecondTexCoord => ("secondTexCoord", ValueType::Vector2),
+                    ShaderGraphInput::WorldPosition => ("position", ValueType::Vector3),
+                    ShaderGraphInput::WorldNormal => ("normal", ValueType::Vector3),
+                    ShaderGraphInput::ViewDirection => (
+                        "normalize(fyrox_cameraPosition - position)",
+                        ValueType::Vector3,
+                    ),
+                };
+                vec![(expr.to_owned(), value_type)]
+            }
+            ShaderGraphNodeKind::Binary(op) => {
+                let value_type = inputs[0].1.widest(inputs[1].1);
+                let a = inputs[0].1.convert(&inputs[0].0, value_type);
+                let b = inputs[1].1.convert(&inputs[1].0, value_type);
+                let (expr, result_type) = match op {
+                    BinaryOperator::Add => (format!("{} + {}", a, b), value_type),
+                    BinaryOperator::Subtract => (format!("{} - {}", a, b), value_type),
+                    BinaryOperator::Multiply => (format!("{} * {}", a, b), value_type),
+                    BinaryOperator::Divide => (format!("{} / {}", a, b), value_type),
+                    BinaryOperator::Min => (format!("min({}, {})", a, b), value_type),
+                    BinaryOperator::Max => (format!("max({}, {})", a, b), value_type),
+                    BinaryOperator::Power => (format!("pow({}, {})", a, b), value_type),
+                    BinaryOperator::Dot => (format!("dot({}, {})", a, b), ValueType::Float),
+                };
+                declare(ctx, result_type, expr);
+                vec![(var.clone(), result_type)]
+            }
+            ShaderGraphNodeKind::Unary(op) => {
+                let (a, value_type) = &inputs[0];
+                let value_type = *value_type;
+                let (expr, result_type) = match op {
+                    UnaryOperator::Negate => (format!("-({})", a), value_type),
+                    UnaryOperator::OneMinus => (
+                        format!("{} - ({})", ValueType::Float.convert("1.0", value_type), a),
+                        value_type,
+                    ),
+                    UnaryOperator::Abs => (format!("abs({})", a), value_type),
+                    UnaryOperator::Sin => (format!("sin({})", a), value_type),
+                    UnaryOperator::Cos => (format!("cos({})", a), value_type),
+                    UnaryOperator::Fract => (format!("fract({})", a), value_type),
+                    UnaryOperator::Floor => (format!("floor({})", a), value_type),
+                    UnaryOperator::Sqrt => (format!("sqrt({})", a), value_type),
+                    UnaryOperator::Saturate => (format!("clamp({}, 0.0, 1.0)", a), value_type),
+                    UnaryOperator::Normalize => (format!("normalize({})", a), value_type),
+                    UnaryOperator::Length => (format!("length({})", a), ValueType::Float),
+                };
+                declare(ctx, result_type, expr);
+                vec![(var.clone(), result_type)]
+            }
+            ShaderGraphNodeKind::Lerp => {
+                let value_type = inputs[0].1.widest(inputs[1].1);
+                let a = inputs[0].1.convert(&inputs[0].0, value_type);
+                let b = inputs[1].1.convert(&inputs[1].0, value_type);
+                // `mix` accepts either scalar or vector of the same type as an interpolation factor.
+                let t = if inputs[2].1 == ValueType::Float {
+                    inputs[2].0.clone()
+                } else {
+                    inputs[2].1.convert(&inputs[2].0, value_type)
+                };
+                declare(ctx, value_type, format!("mix({}, {}, {})", a, b, t));
+                vec![(var.clone(), value_type)]
+            }
+            ShaderGraphNodeKind::Split => {
+                declare(ctx, ValueType::Vector4, inputs[0].0.clone());
+                ["x", "y", "z", "w"]
+                    .iter()
+                    .map(|c| (format!("{}.{}", var, c), ValueType::Float))
+                    .collect()
+            }
+            ShaderGraphNodeKind::Combine => {
+                declare(
+                    ctx,
+                    ValueType::Vector4,
+                    format!(
+                        "vec4({}, {}, {}, {})",
+                        inputs[0].0, inputs[1].0, inputs[2].0, inputs[3].0
+                    ),
+                );
+                vec![
+                    (var.clone(), ValueType::Vector4),
+                    (format!("{}.xyz", var), ValueType::Vector3),
+                    (format!("{}.xy", var), ValueType::Vector2),
+                ]
+            }
+            ShaderGraphNodeKind::PbrOutput { alpha_cutoff } => {
+                let body = &mut ctx.body;
+                writeln!(body, "    vec4 albedo = {};", inputs[0].0).unwrap();
+                if *alpha_cutoff > 0.0 {
+                    writeln!(body, "    if (albedo.a < {:?}) discard;", alpha_cutoff).unwrap();
+                }
+                writeln!(body, "    outColor = vec4(albedo.rgb, 1.0);").unwrap();
+                writeln!(
+                    body,
+                    "    outNormal = vec4(normalize(tangentSpace * normalize({})) * 0.5 + 0.5, 1.0);",
+                    inputs[1].0
+                )
+                .unwrap();
+                writeln!(
+                    body,
+                    "    outMaterial = vec4({}, {}, {}, 1.0);",
+                    inputs[2].0, inputs[3].0, inputs[4].0
+                )
+                .unwrap();
+                writeln!(body, "    outAmbient = vec4({}, 1.0);", inputs[5].0).unwrap();
+                writeln!(body, "    outDecalMask = layerIndex;").unwrap();
+                vec![]
+            }
+        };
+
+        visiting.pop();
+        ctx.outputs.insert(index, outputs);
+
+        Ok(())
+    }
+
+    /// Compiles the graph to a source code of a shader, the source could be saved to a `.shader`
+    /// file or loaded using [`Shader::from_str`].
+    pub fn compile(&self) -> Result<String, ShaderGraphError> {
+        let mut outputs = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| matches!(n.kind, ShaderGraphNodeKind::PbrOutput { .. }));
+        let output = outputs.next().ok_or(ShaderGraphError::NoOutput)?.0;
+        if outputs.next().is_some() {
+            return Err(ShaderGraphError::MultipleOutputs);
+        }
+
+        let mut ctx = CompilationContext::default();
+        self.compile_node(output, &mut ctx, &mut Vec::new())?;
+
+        let mut properties = String::new();
+        let mut uniforms = String::new();
+        for (name, entry) in ctx.properties.iter() {
+            match entry {
+                PropertyEntry::Value(value) => {
+                    writeln!(
+                        properties,
+                        "        (name: \"{}\", kind: {}),",
+                        name,
+                        value.ron_property_kind()
+                    )
+                    .unwrap();
+                    writeln!(
+                        uniforms,
+                        "uniform {} {};",
+                        value.value_type().glsl_name(),
+                        name
+                    )
+                    .unwrap();
+                }
+                PropertyEntry::Sampler(fallback) => {
+                    writeln!(
+                        properties,
+                        "        (name: \"{}\", kind: Sampler(default: None, fallback: {:?})),",
+                        name, fallback
+                    )
+                    .unwrap();
+                    writeln!(uniforms, "uniform sampler2D {};", name).unwrap();
+                }
+            }
+        }
+
+        Ok(SHADER_TEMPLATE
+            .replace("$NAME$", &format!("{:?}", self.name))
+            .replace("$PROPERTIES$", &properties)
+            .replace("$UNIFORMS$", &uniforms)
+            .replace("$BODY$", &ctx.body))
+    }
+
+    /// Compiles the graph and creates new shader from it.
+    pub fn compile_shader(&self) -> Result<Shader, ShaderGraphError> {
+        let source = self.compile()?;
+        Ok(Shader::from_str(&source, &self.name)?)
+    }
+}
+
+const SHADER_TEMPLATE: &str = r##"(
+    name: $NAME$,
+
+    properties: [
+$PROPERTIES$        (name: "layerIndex", kind: UInt(0)),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(red: true, green: true, blue: true, alpha: true),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(fail: Keep, zfail: Keep, zpass: Keep, write_mask: 0xFFFF_FFFF),
+            ),
+            vertex_shader: r#"
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 1) in vec2 vertexTexCoord;
+layout(location = 2) in vec3 vertexNormal;
+layout(location = 3) in vec4 vertexTangent;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+layout(location = 6) in vec2 vertexSecondTexCoord;
+
+uniform mat4 fyrox_worldMatrix;
+uniform mat4 fyrox_worldViewProjection;
+uniform mat4 fyrox_boneMatrices[60];
+uniform bool fyrox_useSkeletalAnimation;
+
+out vec3 position;
+out vec3 normal;
+out vec2 texCoord;
+out vec3 tangent;
+out vec3 binormal;
+out vec2 secondTexCoord;
+
+void main()
+{
+    vec4 localPosition = vec4(0);
+    vec3 localNormal = vec3(0);
+    vec3 localTangent = vec3(0);
+
+    if (fyrox_useSkeletalAnimation)
+    {
+        vec4 vertex = vec4(vertexPosition, 1.0);
+
+        for (int i = 0; i < 4; ++i)
+        {
+            mat4 m = fyrox_boneMatrices[int(boneIndices[i])];
+            localPosition += m * vertex * boneWeights[i];
+            localNormal += mat3(m) * vertexNormal * boneWeights[i];
+            localTangent += mat3(m) * vertexTangent.xyz * boneWeights[i];
+        }
+    }
+    else
+    {
+        localPosition = vec4(vertexPosition, 1.0);
+        localNormal = vertexNormal;
+        localTangent = vertexTangent.xyz;
+    }
+
+    mat3 nm = mat3(fyrox_worldMatrix);
+    normal = normalize(nm * localNormal);
+    tangent = normalize(nm * localTangent);
+    binormal = normalize(vertexTangent.w * cross(tangent, normal));
+    texCoord = vertexTexCoord;
+    position = vec3(fyrox_worldMatrix * localPosition);
+    secondTexCoord = vertexSecondTexCoord;
+
+    gl_Position = fyrox_worldViewProjection * localPosition;
+}
+"#,
+            fragment_shader: r#"
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec4 outNormal;
+layout(location = 2) out vec4 outAmbient;
+layout(location = 3) out vec4 outMaterial;
+layout(location = 4) out uint outDecalMask;
+
+uniform uint layerIndex;
+uniform vec3 fyrox_cameraPosition;
+
+$UNIFORMS$
+in vec3 position;
+in vec3 normal;
+in vec2 texCoord;
+in vec3 tangent;
+in vec3 binormal;
+in vec2 secondTexCoord;
+
+void main()
+{
+    mat3 tangentSpace = mat3(tangent, binormal, normal);
+
+$BODY$}
+"#,
+        ),
+        (
+            name: "DirectionalShadow",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(red: false, green: false, blue: false, alpha: false),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(fail: Keep, zfail: Keep, zpass: Keep, write_mask: 0xFFFF_FFFF),
+            ),
+            vertex_shader: r#"
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+
+uniform mat4 fyrox_worldViewProjection;
+uniform bool fyrox_useSkeletalAnimation;
+uniform mat4 fyrox_boneMatrices[60];
+
+void main()
+{
+    vec4 localPosition = vec4(0);
+    if (fyrox_useSkeletalAnimation)
+    {
+        vec4 vertex = vec4(vertexPosition, 1.0);
+        for (int i = 0; i < 4; ++i)
+        {
+            localPosition += fyrox_boneMatrices[int(boneIndices[i])] * vertex * boneWeights[i];
+        }
+    }
+    else
+    {
+        localPosition = vec4(vertexPosition, 1.0);
+    }
+    gl_Position = fyrox_worldViewProjection * localPosition;
+}
+"#,
+            fragment_shader: r#"
+void main()
+{
+}
+"#,
+        ),
+        (
+            name: "SpotShadow",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(red: false, green: false, blue: false, alpha: false),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(fail: Keep, zfail: Keep, zpass: Keep, write_mask: 0xFFFF_FFFF),
+            ),
+            vertex_shader: r#"
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+
+uniform mat4 fyrox_worldViewProjection;
+uniform bool fyrox_useSkeletalAnimation;
+uniform mat4 fyrox_boneMatrices[60];
+
+void main()
+{
+    vec4 localPosition = vec4(0);
+    if (fyrox_useSkeletalAnimation)
+    {
+        vec4 vertex = vec4(vertexPosition, 1.0);
+        for (int i = 0; i < 4; ++i)
+        {
+            localPosition += fyrox_boneMatrices[int(boneIndices[i])] * vertex * boneWeights[i];
+        }
+    }
+    else
+    {
+        localPosition = vec4(vertexPosition, 1.0);
+    }
+    gl_Position = fyrox_worldViewProjection * localPosition;
+}
+"#,
+            fragment_shader: r#"
+void main()
+{
+}
+"#,
+        ),
+        (
+            name: "PointShadow",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(red: true, green: true, blue: true, alpha: true),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(fail: Keep, zfail: Keep, zpass: Keep, write_mask: 0xFFFF_FFFF),
+            ),
+            vertex_shader: r#"
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+
+uniform mat4 fyrox_worldMatrix;
+uniform mat4 fyrox_worldViewProjection;
+uniform bool fyrox_useSkeletalAnimation;
+uniform mat4 fyrox_boneMatrices[60];
+
+out vec3 worldPosition;
+
+void main()
+{
+    vec4 localPosition = vec4(0);
+    if (fyrox_useSkeletalAnimation)
+    {
+        vec4 vertex = vec4(vertexPosition, 1.0);
+        for (int i = 0; i < 4; ++i)
+        {
+            localPosition += fyrox_boneMatrices[int(boneIndices[i])] * vertex * boneWeights[i];
+        }
+    }
+    else
+    {
+        localPosition = vec4(vertexPosition, 1.0);
+    }
+    gl_Position = fyrox_worldViewProjection * localPosition;
+    worldPosition = (fyrox_worldMatrix * localPosition).xyz;
+}
+"#,
+            fragment_shader: r#"
+uniform vec3 fyrox_lightPosition;
+
+in vec3 worldPosition;
+
+layout(location = 0) out float depth;
+
+void main()
+{
+    depth = length(fyrox_lightPosition - worldPosition);
+}
+"#,
+        ),
+    ],
+)
+"##;
+
+#[cfg(test)]
+mod test {
+    use crate::material::{
+        shader::{PropertyKind, SamplerFallback},
+        shader_graph::{
+            BinaryOperator, ShaderGraph, ShaderGraphError, ShaderGraphNodeKind, ShaderGraphValue,
+            UnaryOperator, ValueType,
+        },
+    };
+
+    #[test]
+    fn test_template_identifiers_are_reserved() {
+        // Every identifier in GLSL sources of the template, except swizzles and placeholders.
+        for source in super::SHADER_TEMPLATE.split("r#\"").skip(1) {
+            let source = &source[..source.find("\"#").unwrap()];
+            for word in source.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_')) {
+                if word.is_empty() || word.starts_with(|c: char| c.is_ascii_digit()) {
+                    continue;
+                }
+                if source.contains(&format!(".{}", word)) || word.chars().all(|c| c.is_uppercase())
+                {
+                    continue;
+                }
+                assert!(!super::is_valid_name(word), "{} must be reserved", word);
+            }
+        }
+    }
+
+    #[test]
+    fn test_value_conversion() {
+        assert_eq!(ValueType::Float.convert("a", ValueType::Vector3), "vec3(a)");
+        assert_eq!(ValueType::Vector4.convert("a", ValueType::Float), "(a).x");
+        assert_eq!(
+            ValueType::Vector4.convert("a", ValueType::Vector2),
+            "(a).xy"
+        );
+        assert_eq!(
+            ValueType::Vector2.convert("a", ValueType::Vector4),
+            "vec4(a, 0.0, 1.0)"
+        );
+    }
+
+    #[test]
+    fn test_shader_graph_compilation() {
+        let mut graph = ShaderGraph::new("Test");
+        let texture = graph.add_node(ShaderGraphNodeKind::TextureSample {
+            name: "diffuseTexture".to_owned(),
+            fallback: SamplerFallback::White,
+        });
+        let tint = graph.add_node(ShaderGraphNodeKind::Property {
+            name: "tint".to_owned(),
+            value: ShaderGraphValue::Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+        });
+        let multiply = graph.add_node(ShaderGraphNodeKind::Binary(BinaryOperator::Multiply));
+        let one_minus = graph.add_node(ShaderGraphNodeKind::Unary(UnaryOperator::OneMinus));
+        let output = graph.add_node(ShaderGraphNodeKind::PbrOutput { alpha_cutoff: 0.5 });
+
+        graph.connect(texture, 0, multiply, 0).unwrap();
+        graph.connect(tint, 0, multiply, 1).unwrap();
+        graph.connect(multiply, 0, output, 0).unwrap();
+        graph.connect(texture, 2, one_minus, 0).unwrap();
+        graph.connect(one_minus, 0, output, 3).unwrap();
+
+        assert!(matches!(
+            graph.connect(output, 0, texture, 0),
+            Err(ShaderGraphError::InvalidSocket { .. })
+        ));
+        assert!(matches!(
+            graph.connect(multiply, 0, multiply, 0),
+            Err(ShaderGraphError::Cycle)
+        ));
+
+        let shader = graph.compile_shader().unwrap();
+        let data = shader.data_ref();
+        let definition = &data.definition;
+        assert_eq!(definition.name, "Test");
+        assert_eq!(definition.passes.len(), 4);
+        assert_eq!(definition.properties.len(), 3);
+        assert_eq!(definition.properties[0].name, "diffuseTexture");
+        assert_eq!(
+            definition.properties[1].kind,
+            PropertyKind::Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255
+            }
+        );
+        let fragment_shader = &definition.passes[0].fragment_shader;
+        assert!(fragment_shader.contains("vec4 n2 = n0 * tint;"));
+        assert!(fragment_shader.contains("if (albedo.a < 0.5) discard;"));
+
+        let restored = ShaderGraph::from_ron_str(&graph.to_ron_string().unwrap()).unwrap();
+        assert_eq!(restored, graph);
+
+        graph.remove_node(output);
+        assert!(matches!(graph.compile(), Err(ShaderGraphError::NoOutput)));
+    }
+}
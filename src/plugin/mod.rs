@@ -3,13 +3,17 @@
 #![warn(missing_docs)]
 
 use crate::{
+    console::Console,
     core::pool::Handle,
-    engine::{resource_manager::ResourceManager, SerializationContext},
+    engine::{
+        resource_manager::ResourceManager, statistics::EngineStatistics, SerializationContext,
+    },
     event::Event,
     event_loop::ControlFlow,
     gui::{message::UiMessage, UserInterface},
     renderer::Renderer,
     scene::{Scene, SceneContainer},
+    settings::Settings,
     window::Window,
 };
 use fyrox_sound::engine::SoundEngine;
@@ -48,6 +52,14 @@ pub struct PluginRegistrationContext<'a> {
     /// A reference to serialization context of the engine. See [`SerializationContext`] for more
     /// info.
     pub serialization_context: &'a Arc<SerializationContext>,
+
+    /// A reference to the developer console of the engine, use it to register commands and cvars
+    /// of the plugin.
+    pub console: &'a Console,
+
+    /// A reference to the settings of the engine, use it to register settings sections of the
+    /// plugin.
+    pub settings: &'a Settings,
 }
 
 /// A small wrapper that provides limited access to inner sound engine.
@@ -105,6 +117,17 @@ pub struct PluginContext<'a, 'b> {
 
     /// Sound engine allows you to change global sound parameters, such as master gain, etc.
     pub sound_engine: SoundEngineHelper<'a>,
+
+    /// A reference to the developer console of the engine. See [`Console`] docs for more info.
+    pub console: &'a Console,
+
+    /// A reference to the settings of the engine and the game. See [`Settings`] docs for more
+    /// info.
+    pub settings: &'a Settings,
+
+    /// Statistics of the last complete frame of the engine, it could be used to show custom
+    /// performance HUD. See [`EngineStatistics`] docs for more info.
+    pub statistics: &'a EngineStatistics,
 }
 
 /// Base plugin automatically implements type casting for plugins.
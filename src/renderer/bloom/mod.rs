@@ -25,6 +25,7 @@ struct Shader {
     program: GpuProgram,
     world_view_projection_matrix: UniformLocation,
     hdr_sampler: UniformLocation,
+    threshold: UniformLocation,
 }
 
 impl Shader {
@@ -38,6 +39,7 @@ impl Shader {
             world_view_projection_matrix: program
                 .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
             hdr_sampler: program.uniform_location(state, &ImmutableString::new("hdrSampler"))?,
+            threshold: program.uniform_location(state, &ImmutableString::new("threshold"))?,
             program,
         })
     }
@@ -104,6 +106,7 @@ impl BloomRenderer {
         state: &mut PipelineState,
         quad: &GeometryBuffer,
         hdr_scene_frame: Rc<RefCell<GpuTexture>>,
+        threshold: f32,
     ) -> RenderPassStatistics {
         scope_profile!();
 
@@ -132,7 +135,8 @@ impl BloomRenderer {
                         &shader.world_view_projection_matrix,
                         &(make_viewport_matrix(viewport)),
                     )
-                    .set_texture(&shader.hdr_sampler, &hdr_scene_frame);
+                    .set_texture(&shader.hdr_sampler, &hdr_scene_frame)
+                    .set_f32(&shader.threshold, threshold);
             },
         );
 
@@ -58,6 +58,19 @@ impl GeometryCache {
         }
     }
 
+    /// Returns amount of geometry buffers in the cache.
+    pub fn alive_count(&self) -> usize {
+        self.buffer.filled()
+    }
+
+    /// Returns estimated amount of video memory (in bytes) occupied by the geometry buffers.
+    pub fn memory_usage(&self) -> usize {
+        self.buffer
+            .iter()
+            .map(|entry| entry.value.byte_size())
+            .sum()
+    }
+
     pub fn update(&mut self, dt: f32) {
         scope_profile!();
 
@@ -5,19 +5,153 @@ use crate::{
     renderer::{
         cache::CacheEntry,
         framework::{
-            gpu_texture::{Coordinate, GpuTexture, PixelKind},
+            gpu_texture::{image_2d_size_bytes, Coordinate, GpuTexture, GpuTextureKind, PixelKind},
             state::PipelineState,
         },
     },
-    resource::texture::{Texture, TextureState},
+    resource::texture::{Texture, TextureData, TextureState},
     utils::log::{Log, MessageKind},
 };
 use fxhash::FxHashMap;
 use std::{cell::RefCell, collections::hash_map::Entry, ops::Deref, rc::Rc};
 
+// Residency information of a texture, that was uploaded from a texture resource. Render targets
+// and other textures that are put in the cache directly are not managed.
+#[derive(Default, Debug)]
+struct Residency {
+    // Index of a frame at which the texture was used last time.
+    last_used: u64,
+    // Amount of top mip levels that are not uploaded to GPU.
+    mip_skip: u32,
+    // Amount of top mip levels that should not be uploaded to GPU, the texture will be re-uploaded
+    // when it is used next time.
+    desired_mip_skip: u32,
+    // Pinned textures (render targets) are never evicted or streamed.
+    pinned: bool,
+}
+
+/// Texture residency statistics.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ResidencyStatistics {
+    /// Amount of textures that were evicted from GPU memory due to the memory budget.
+    pub evicted: usize,
+    /// Amount of textures that have some of their top mip levels dropped due to the memory budget.
+    pub streamed: usize,
+}
+
 #[derive(Default)]
 pub struct TextureCache {
     pub(crate) map: FxHashMap<usize, CacheEntry<Rc<RefCell<GpuTexture>>>>,
+    residency: FxHashMap<usize, Residency>,
+    frame: u64,
+    evicted: usize,
+}
+
+// Textures smaller than this size (in pixels on any axis) are never streamed.
+const MIN_STREAMED_SIZE: usize = 64;
+
+fn create_gpu_texture(
+    state: &mut PipelineState,
+    texture: &TextureData,
+    mip_skip: u32,
+) -> Result<GpuTexture, FrameworkError> {
+    let pixel_kind = PixelKind::from(texture.pixel_kind());
+    let mut kind: GpuTextureKind = texture.kind().into();
+    let mut mip_count = texture.mip_count() as usize;
+    let mut data = texture.data();
+
+    // Skip top mip levels of 2D textures. Mip levels are stored one after another, starting
+    // from the largest one.
+    if let GpuTextureKind::Rectangle {
+        mut width,
+        mut height,
+    } = kind
+    {
+        let skip = (mip_skip as usize).min(mip_count.saturating_sub(1));
+        let mut offset = 0;
+        for _ in 0..skip {
+            offset += image_2d_size_bytes(pixel_kind, width, height);
+            width = (width / 2).max(1);
+            height = (height / 2).max(1);
+        }
+        if offset < data.len() {
+            data = &data[offset..];
+            kind = GpuTextureKind::Rectangle { width, height };
+            mip_count -= skip;
+        }
+    }
+
+    let mut gpu_texture = GpuTexture::new(
+        state,
+        kind,
+        pixel_kind,
+        texture.minification_filter().into(),
+        texture.magnification_filter().into(),
+        mip_count,
+        Some(data),
+    )?;
+
+    if needs_mip_generation(texture) {
+        gpu_texture.bind_mut(state, 0).generate_mip_chain();
+    }
+
+    apply_sampler_state(state, &mut gpu_texture, texture);
+
+    Ok(gpu_texture)
+}
+
+// Only textures with a single mip level are allowed to have their mips generated at runtime,
+// otherwise mip levels from the texture data are used.
+fn needs_mip_generation(texture: &TextureData) -> bool {
+    texture.is_mip_map_generation_enabled() && texture.mip_count() == 1
+}
+
+// Syncs sampling parameters of the GPU texture with the parameters of the texture resource, only
+// changed parameters are passed to GPU.
+fn apply_sampler_state(state: &mut PipelineState, tex: &mut GpuTexture, texture: &TextureData) {
+    let new_mag_filter = texture.magnification_filter().into();
+    if tex.magnification_filter() != new_mag_filter {
+        tex.bind_mut(state, 0)
+            .set_magnification_filter(new_mag_filter);
+    }
+
+    let new_min_filter = texture.minification_filter().into();
+    if tex.minification_filter() != new_min_filter {
+        tex.bind_mut(state, 0)
+            .set_minification_filter(new_min_filter);
+    }
+
+    if tex.anisotropy().ne(&texture.anisotropy_level()) {
+        tex.bind_mut(state, 0)
+            .set_anisotropy(texture.anisotropy_level());
+    }
+
+    if tex.lod_bias().ne(&texture.lod_bias()) {
+        tex.bind_mut(state, 0).set_lod_bias(texture.lod_bias());
+    }
+
+    let new_s_wrap_mode = texture.s_wrap_mode().into();
+    if tex.s_wrap_mode() != new_s_wrap_mode {
+        tex.bind_mut(state, 0)
+            .set_wrap(Coordinate::S, new_s_wrap_mode);
+    }
+
+    let new_t_wrap_mode = texture.t_wrap_mode().into();
+    if tex.t_wrap_mode() != new_t_wrap_mode {
+        tex.bind_mut(state, 0)
+            .set_wrap(Coordinate::T, new_t_wrap_mode);
+    }
+}
+
+fn can_be_streamed(texture: &GpuTexture) -> bool {
+    match texture.kind() {
+        GpuTextureKind::Rectangle { width, height } => {
+            texture.mip_count() > 1
+                && !texture.has_generated_mips()
+                && width.min(height) / 2 >= MIN_STREAMED_SIZE
+        }
+        _ => false,
+    }
 }
 
 impl TextureCache {
@@ -32,15 +166,10 @@ impl TextureCache {
         let texture = texture.state();
 
         if let TextureState::Ok(texture) = texture.deref() {
-            let gpu_texture = GpuTexture::new(
-                state,
-                texture.kind().into(),
-                PixelKind::from(texture.pixel_kind()),
-                texture.minification_filter().into(),
-                texture.magnification_filter().into(),
-                texture.mip_count() as usize,
-                Some(texture.data()),
-            )?;
+            let residency = self.residency.entry(key).or_default();
+            residency.last_used = self.frame;
+            residency.pinned = texture.is_render_target();
+            let gpu_texture = create_gpu_texture(state, texture, residency.mip_skip)?;
 
             match self.map.entry(key) {
                 Entry::Occupied(mut e) => {
@@ -63,6 +192,95 @@ impl TextureCache {
         }
     }
 
+    /// Returns amount of textures in the cache.
+    pub fn alive_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns estimated amount of video memory (in bytes) occupied by cached textures.
+    pub fn memory_usage(&self) -> usize {
+        self.map
+            .values()
+            .map(|entry| entry.value.borrow().byte_size())
+            .sum()
+    }
+
+    /// Returns residency statistics of the cache.
+    pub fn residency_statistics(&self) -> ResidencyStatistics {
+        ResidencyStatistics {
+            evicted: self.evicted,
+            streamed: self.residency.values().filter(|r| r.mip_skip > 0).count(),
+        }
+    }
+
+    /// Tries to fit textures into the given memory budget (in bytes). Least recently used textures
+    /// are processed first: streamable textures (2D textures with mip maps) get their top mip
+    /// level dropped, other textures, that weren't used on the current frame, are evicted from GPU
+    /// memory. Evicted textures will be uploaded back when they're used again. If there is enough
+    /// free memory, most recently used textures get their mip levels back.
+    pub fn enforce_budget(&mut self, budget: usize) {
+        scope_profile!();
+
+        let mut usage = self.memory_usage();
+
+        let mut managed = self
+            .residency
+            .iter()
+            .filter(|(_, residency)| !residency.pinned)
+            .filter_map(|(key, residency)| {
+                self.map
+                    .get(key)
+                    .map(|entry| (*key, residency.last_used, entry.value.borrow().byte_size()))
+            })
+            .collect::<Vec<_>>();
+
+        if usage > budget {
+            // Least recently used first.
+            managed.sort_by_key(|(_, last_used, _)| *last_used);
+
+            for (key, last_used, size) in managed {
+                if usage <= budget {
+                    break;
+                }
+
+                let residency = self.residency.get_mut(&key).unwrap();
+                let entry = &self.map[&key];
+                let texture = entry.value.borrow();
+
+                if residency.desired_mip_skip > residency.mip_skip {
+                    // The texture will be re-uploaded with less mip levels once it is used.
+                    usage -= size * 3 / 4;
+                } else if can_be_streamed(&texture) {
+                    // Dropping top mip level frees ~75% of memory.
+                    residency.desired_mip_skip += 1;
+                    usage -= size * 3 / 4;
+                } else if last_used != self.frame {
+                    drop(texture);
+                    self.map.remove(&key);
+                    self.residency.remove(&key);
+                    self.evicted += 1;
+                    usage -= size;
+                }
+            }
+        } else {
+            // Most recently used first.
+            managed.sort_by_key(|(_, last_used, _)| std::cmp::Reverse(*last_used));
+
+            for (key, _, size) in managed {
+                let residency = self.residency.get_mut(&key).unwrap();
+                if residency.desired_mip_skip > 0 {
+                    // Restoring top mip level takes ~4x more memory.
+                    let grown_usage = usage + size * 3;
+                    if grown_usage > budget {
+                        break;
+                    }
+                    residency.desired_mip_skip -= 1;
+                    usage = grown_usage;
+                }
+            }
+        }
+    }
+
     pub fn get(
         &mut self,
         state: &mut PipelineState,
@@ -75,6 +293,10 @@ impl TextureCache {
         let texture = texture.state();
 
         if let TextureState::Ok(texture) = texture.deref() {
+            let residency = self.residency.entry(key).or_default();
+            residency.last_used = self.frame;
+            residency.pinned = texture.is_render_target();
+
             let entry = match self.map.entry(key) {
                 Entry::Occupied(e) => {
                     let entry = e.into_mut();
@@ -86,7 +308,24 @@ impl TextureCache {
 
                     // Data might change from last frame, so we have to check it and upload new if so.
                     let data_hash = texture.data_hash();
-                    if entry.value_hash != data_hash {
+                    let generated_mips = entry.value.borrow().has_generated_mips();
+                    if residency.mip_skip != residency.desired_mip_skip
+                        || (residency.mip_skip > 0 && entry.value_hash != data_hash)
+                        || (generated_mips && !needs_mip_generation(texture))
+                    {
+                        // Re-upload the texture with desired amount of mip levels.
+                        match create_gpu_texture(state, texture, residency.desired_mip_skip) {
+                            Ok(gpu_texture) => {
+                                *entry.value.borrow_mut() = gpu_texture;
+                                entry.value_hash = data_hash;
+                                residency.mip_skip = residency.desired_mip_skip;
+                            }
+                            Err(e) => Log::err(format!(
+                                "Unable to re-upload texture data to GPU. Reason: {:?}",
+                                e
+                            )),
+                        }
+                    } else if entry.value_hash != data_hash {
                         let mut tex = entry.borrow_mut();
                         if let Err(e) = tex.bind_mut(state, 0).set_data(
                             texture.kind().into(),
@@ -102,57 +341,25 @@ impl TextureCache {
                                 ),
                             )
                         } else {
+                            if needs_mip_generation(texture) {
+                                tex.bind_mut(state, 0).generate_mip_chain();
+                            }
                             drop(tex);
                             // TODO: Is this correct to overwrite hash only if we've succeeded?
                             entry.value_hash = data_hash;
                         }
+                    } else if !generated_mips && needs_mip_generation(texture) {
+                        // Mip-map generation was enabled at runtime.
+                        entry.borrow_mut().bind_mut(state, 0).generate_mip_chain();
                     }
 
-                    let mut tex = entry.borrow_mut();
-
-                    let new_mag_filter = texture.magnification_filter().into();
-                    if tex.magnification_filter() != new_mag_filter {
-                        tex.bind_mut(state, 0)
-                            .set_magnification_filter(new_mag_filter);
-                    }
-
-                    let new_min_filter = texture.minification_filter().into();
-                    if tex.minification_filter() != new_min_filter {
-                        tex.bind_mut(state, 0)
-                            .set_minification_filter(new_min_filter);
-                    }
-
-                    if tex.anisotropy().ne(&texture.anisotropy_level()) {
-                        tex.bind_mut(state, 0)
-                            .set_anisotropy(texture.anisotropy_level());
-                    }
-
-                    let new_s_wrap_mode = texture.s_wrap_mode().into();
-                    if tex.s_wrap_mode() != new_s_wrap_mode {
-                        tex.bind_mut(state, 0)
-                            .set_wrap(Coordinate::S, new_s_wrap_mode);
-                    }
-
-                    let new_t_wrap_mode = texture.t_wrap_mode().into();
-                    if tex.t_wrap_mode() != new_t_wrap_mode {
-                        tex.bind_mut(state, 0)
-                            .set_wrap(Coordinate::T, new_t_wrap_mode);
-                    }
-
-                    std::mem::drop(tex);
+                    apply_sampler_state(state, &mut entry.borrow_mut(), texture);
 
                     entry
                 }
                 Entry::Vacant(e) => {
-                    let gpu_texture = match GpuTexture::new(
-                        state,
-                        texture.kind().into(),
-                        PixelKind::from(texture.pixel_kind()),
-                        texture.minification_filter().into(),
-                        texture.magnification_filter().into(),
-                        texture.mip_count() as usize,
-                        Some(texture.data()),
-                    ) {
+                    residency.mip_skip = residency.desired_mip_skip;
+                    let gpu_texture = match create_gpu_texture(state, texture, residency.mip_skip) {
                         Ok(texture) => texture,
                         Err(e) => {
                             Log::writeln(
@@ -185,13 +392,20 @@ impl TextureCache {
         }
 
         self.map.retain(|_, v| v.time_to_live > 0.0);
+
+        let map = &self.map;
+        self.residency.retain(|key, _| map.contains_key(key));
+
+        self.frame += 1;
     }
 
     pub fn clear(&mut self) {
         self.map.clear();
+        self.residency.clear();
     }
 
     pub fn unload(&mut self, texture: Texture) {
         self.map.remove(&texture.key());
+        self.residency.remove(&texture.key());
     }
 }
@@ -0,0 +1,528 @@
+//! Debug visualization of a frame, see [`DebugViewMode`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector3, color::Color, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        batch::BatchStorage,
+        blit_pixels,
+        cache::geometry::GeometryCache,
+        flat_shader::FlatShader,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, BlendParameters, DrawParameters, FrameBuffer,
+            },
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{BlendFactor, BlendFunc, PipelineState, PolygonFillMode},
+        },
+        make_viewport_matrix,
+        shadow::csm::cascade_split_distances,
+        AssociatedSceneData, DebugViewMode, QualitySettings, RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        Scene,
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+// Amount of draws or lights, that is shown as the "hottest" color of heat maps.
+const HEAT_MAP_MAX_COUNT: f32 = 8.0;
+
+struct GeometryShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    use_skeletal_animation: UniformLocation,
+    bone_matrices: UniformLocation,
+    color: UniformLocation,
+}
+
+impl GeometryShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/debug_view_geometry_fs.glsl");
+        let vertex_source = include_str!("shaders/debug_view_geometry_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "DebugViewGeometryShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            use_skeletal_animation: program
+                .uniform_location(state, &ImmutableString::new("useSkeletalAnimation"))?,
+            bone_matrices: program
+                .uniform_location(state, &ImmutableString::new("boneMatrices"))?,
+            color: program.uniform_location(state, &ImmutableString::new("color"))?,
+            program,
+        })
+    }
+}
+
+struct LightCountShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    depth_texture: UniformLocation,
+    inv_view_proj: UniformLocation,
+    light_position: UniformLocation,
+    light_radius: UniformLocation,
+    light_direction: UniformLocation,
+    half_cone_angle_cos: UniformLocation,
+}
+
+impl LightCountShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/debug_view_light_count_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "DebugViewLightCountShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            inv_view_proj: program.uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            light_position: program
+                .uniform_location(state, &ImmutableString::new("lightPosition"))?,
+            light_radius: program.uniform_location(state, &ImmutableString::new("lightRadius"))?,
+            light_direction: program
+                .uniform_location(state, &ImmutableString::new("lightDirection"))?,
+            half_cone_angle_cos: program
+                .uniform_location(state, &ImmutableString::new("halfConeAngleCos"))?,
+            program,
+        })
+    }
+}
+
+struct CompositeShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    depth_texture: UniformLocation,
+    color_texture: UniformLocation,
+    normal_texture: UniformLocation,
+    material_texture: UniformLocation,
+    count_texture: UniformLocation,
+    frame_texture: UniformLocation,
+    mode: UniformLocation,
+    base_count: UniformLocation,
+    max_count: UniformLocation,
+    inv_view_proj: UniformLocation,
+    view_matrix: UniformLocation,
+    cascades_enabled: UniformLocation,
+    cascade_distances: UniformLocation,
+}
+
+impl CompositeShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/debug_view_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "DebugViewShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            color_texture: program
+                .uniform_location(state, &ImmutableString::new("colorTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_texture: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            count_texture: program
+                .uniform_location(state, &ImmutableString::new("countTexture"))?,
+            frame_texture: program
+                .uniform_location(state, &ImmutableString::new("frameTexture"))?,
+            mode: program.uniform_location(state, &ImmutableString::new("mode"))?,
+            base_count: program.uniform_location(state, &ImmutableString::new("baseCount"))?,
+            max_count: program.uniform_location(state, &ImmutableString::new("maxCount"))?,
+            inv_view_proj: program.uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            view_matrix: program.uniform_location(state, &ImmutableString::new("viewMatrix"))?,
+            cascades_enabled: program
+                .uniform_location(state, &ImmutableString::new("cascadesEnabled"))?,
+            cascade_distances: program
+                .uniform_location(state, &ImmutableString::new("cascadeDistances"))?,
+            program,
+        })
+    }
+}
+
+pub(crate) struct DebugViewRenderContext<'a, 'b> {
+    pub state: &'a mut PipelineState,
+    pub mode: DebugViewMode,
+    pub scene: &'b Scene,
+    pub camera: &'b Camera,
+    pub geom_cache: &'a mut GeometryCache,
+    pub batch_storage: &'a BatchStorage,
+    pub scene_data: &'a mut AssociatedSceneData,
+    pub viewport: Rect<i32>,
+    pub quad: &'a GeometryBuffer,
+    pub flat_shader: &'a FlatShader,
+    pub settings: &'a QualitySettings,
+}
+
+/// Renders debug visualization into low dynamic range frame of a camera.
+pub(crate) struct DebugViewRenderer {
+    geometry_shader: GeometryShader,
+    light_count_shader: LightCountShader,
+    composite_shader: CompositeShader,
+    // Accumulates overdraw and light count, re-created when frame size changes.
+    count_framebuffer: Option<CountFramebuffer>,
+}
+
+struct CountFramebuffer {
+    framebuffer: FrameBuffer,
+    width: usize,
+    height: usize,
+}
+
+impl CountFramebuffer {
+    fn new(state: &mut PipelineState, width: usize, height: usize) -> Result<Self, FrameworkError> {
+        let mut count_texture = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            PixelKind::RGBA16F,
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            None,
+        )?;
+        count_texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+        Ok(Self {
+            framebuffer: FrameBuffer::new(
+                state,
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(count_texture)),
+                }],
+            )?,
+            width,
+            height,
+        })
+    }
+}
+
+fn additive_draw_params() -> DrawParameters {
+    DrawParameters {
+        cull_face: None,
+        color_write: Default::default(),
+        depth_write: false,
+        stencil_test: None,
+        depth_test: false,
+        blend: Some(BlendParameters {
+            func: BlendFunc::new(BlendFactor::One, BlendFactor::One),
+            ..Default::default()
+        }),
+        stencil_op: Default::default(),
+    }
+}
+
+impl DebugViewRenderer {
+    pub(crate) fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            geometry_shader: GeometryShader::new(state)?,
+            light_count_shader: LightCountShader::new(state)?,
+            composite_shader: CompositeShader::new(state)?,
+            count_framebuffer: None,
+        })
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: DebugViewRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let DebugViewRenderContext {
+            state,
+            mode,
+            scene,
+            camera,
+            geom_cache,
+            batch_storage,
+            scene_data,
+            viewport,
+            quad,
+            flat_shader,
+            settings,
+        } = ctx;
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let view_projection = camera.view_projection_matrix();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let width = scene_data.gbuffer.width as usize;
+        let height = scene_data.gbuffer.height as usize;
+
+        if mode == DebugViewMode::Wireframe {
+            scene_data.ldr_scene_framebuffer.clear(
+                state,
+                viewport,
+                Some(Color::opaque(20, 20, 20)),
+                None,
+                None,
+            );
+
+            state.set_polygon_fill_mode(PolygonFillMode::Line);
+            statistics += draw_batches(
+                state,
+                &mut scene_data.ldr_scene_framebuffer,
+                &self.geometry_shader,
+                camera,
+                geom_cache,
+                batch_storage,
+                viewport,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                Color::opaque(220, 220, 220),
+            );
+            state.set_polygon_fill_mode(PolygonFillMode::Fill);
+
+            return Ok(statistics);
+        }
+
+        if self
+            .count_framebuffer
+            .as_ref()
+            .map_or(true, |c| c.width != width || c.height != height)
+        {
+            self.count_framebuffer = Some(CountFramebuffer::new(state, width, height)?);
+        }
+        let count_framebuffer = &mut self.count_framebuffer.as_mut().unwrap().framebuffer;
+
+        let mut base_count = 0.0;
+        match mode {
+            DebugViewMode::Overdraw => {
+                count_framebuffer.clear(
+                    state,
+                    viewport,
+                    Some(Color::from_rgba(0, 0, 0, 0)),
+                    None,
+                    None,
+                );
+                statistics += draw_batches(
+                    state,
+                    count_framebuffer,
+                    &self.geometry_shader,
+                    camera,
+                    geom_cache,
+                    batch_storage,
+                    viewport,
+                    &additive_draw_params(),
+                    Color::WHITE,
+                );
+            }
+            DebugViewMode::LightCount => {
+                count_framebuffer.clear(
+                    state,
+                    viewport,
+                    Some(Color::from_rgba(0, 0, 0, 0)),
+                    None,
+                    None,
+                );
+
+                let depth_texture = scene_data.gbuffer.depth();
+                let shader = &self.light_count_shader;
+
+                for light in scene.graph.linear_iter() {
+                    if !light.global_visibility() {
+                        continue;
+                    }
+
+                    // Directional lights affect every pixel.
+                    if light.cast::<DirectionalLight>().is_some() {
+                        base_count += 1.0;
+                        continue;
+                    }
+
+                    let (radius, half_cone_angle_cos) =
+                        if let Some(spot) = light.cast::<SpotLight>() {
+                            (spot.distance(), (spot.full_cone_angle() * 0.5).cos())
+                        } else if let Some(point) = light.cast::<PointLight>() {
+                            (point.radius(), -1.0)
+                        } else {
+                            continue;
+                        };
+
+                    let scale = light.local_transform().scale();
+                    let radius = radius * scale.x.max(scale.y).max(scale.z);
+                    let light_position = light.global_position();
+                    let light_direction = light
+                        .up_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::z);
+
+                    statistics += count_framebuffer.draw(
+                        quad,
+                        state,
+                        viewport,
+                        &shader.program,
+                        &additive_draw_params(),
+                        |mut program_binding| {
+                            program_binding
+                                .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                                .set_texture(&shader.depth_texture, &depth_texture)
+                                .set_matrix4(&shader.inv_view_proj, &inv_view_projection)
+                                .set_vector3(&shader.light_position, &light_position)
+                                .set_f32(&shader.light_radius, radius)
+                                .set_vector3(&shader.light_direction, &light_direction)
+                                .set_f32(&shader.half_cone_angle_cos, half_cone_angle_cos);
+                        },
+                    );
+                }
+            }
+            _ => (),
+        }
+
+        let (cascades_enabled, cascade_distances) = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<DirectionalLight>())
+            .find(|light| {
+                light.global_visibility()
+                    && light.base_light_ref().is_cast_shadows()
+                    && settings.csm_settings.enabled
+            })
+            .map_or((false, [0.0; 3]), |light| {
+                let distances = cascade_split_distances(light, camera);
+                (true, [distances[1], distances[2], distances[3]])
+            });
+
+        let mode_index = match mode {
+            DebugViewMode::Albedo => 0,
+            DebugViewMode::Normals => 1,
+            DebugViewMode::RoughnessMetallic => 2,
+            DebugViewMode::Overdraw => 3,
+            DebugViewMode::LightCount => 4,
+            _ => 5,
+        };
+
+        let count_texture = count_framebuffer.color_attachments()[0].texture.clone();
+        let frame_texture = scene_data.ldr_scene_frame_texture();
+        let depth_texture = scene_data.gbuffer.depth();
+        let color_texture = scene_data.gbuffer.diffuse_texture();
+        let normal_texture = scene_data.gbuffer.normal_texture();
+        let material_texture = scene_data.gbuffer.material_texture();
+        let view_matrix = camera.view_matrix();
+        let shader = &self.composite_shader;
+
+        // Shadow cascades mode tints the frame, so it cannot be drawn directly into the frame.
+        statistics += scene_data.ldr_temp_framebuffer.draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&shader.depth_texture, &depth_texture)
+                    .set_texture(&shader.color_texture, &color_texture)
+                    .set_texture(&shader.normal_texture, &normal_texture)
+                    .set_texture(&shader.material_texture, &material_texture)
+                    .set_texture(&shader.count_texture, &count_texture)
+                    .set_texture(&shader.frame_texture, &frame_texture)
+                    .set_i32(&shader.mode, mode_index)
+                    .set_f32(&shader.base_count, base_count)
+                    .set_f32(&shader.max_count, HEAT_MAP_MAX_COUNT)
+                    .set_matrix4(&shader.inv_view_proj, &inv_view_projection)
+                    .set_matrix4(&shader.view_matrix, &view_matrix)
+                    .set_bool(&shader.cascades_enabled, cascades_enabled)
+                    .set_f32_slice(&shader.cascade_distances, &cascade_distances);
+            },
+        );
+
+        let temp_frame_texture = scene_data.ldr_temp_frame_texture();
+        statistics += blit_pixels(
+            state,
+            &mut scene_data.ldr_scene_framebuffer,
+            temp_frame_texture,
+            flat_shader,
+            viewport,
+            quad,
+        );
+
+        Ok(statistics)
+    }
+}
+
+fn draw_batches(
+    state: &mut PipelineState,
+    framebuffer: &mut FrameBuffer,
+    shader: &GeometryShader,
+    camera: &Camera,
+    geom_cache: &mut GeometryCache,
+    batch_storage: &BatchStorage,
+    viewport: Rect<i32>,
+    draw_params: &DrawParameters,
+    color: Color,
+) -> RenderPassStatistics {
+    let mut statistics = RenderPassStatistics::default();
+
+    let view_projection = camera.view_projection_matrix();
+
+    for batch in batch_storage.batches.iter() {
+        let geometry = geom_cache.get(state, &batch.data);
+
+        for instance in batch.instances.iter() {
+            if !camera.visibility_cache.is_visible(instance.owner) {
+                continue;
+            }
+
+            statistics += framebuffer.draw(
+                geometry,
+                state,
+                viewport,
+                &shader.program,
+                draw_params,
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(
+                            &shader.wvp_matrix,
+                            &(view_projection * instance.world_transform),
+                        )
+                        .set_bool(&shader.use_skeletal_animation, batch.is_skinned)
+                        .set_matrix4_array(&shader.bone_matrices, &instance.bone_matrices)
+                        .set_linear_color(&shader.color, &color);
+                },
+            );
+        }
+    }
+
+    statistics
+}
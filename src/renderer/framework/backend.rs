@@ -0,0 +1,531 @@
+//! Render backend abstraction. This is the first step towards support of multiple graphics APIs
+//! (wgpu, Metal, Vulkan, etc.). The renderer talks to a backend through the [`RenderBackend`]
+//! trait when it binds frame buffers, GPU programs and geometry buffers, sets pipeline state,
+//! clears render targets and issues draw calls. OpenGL backend is implemented by
+//! [`PipelineState`], [`MockBackend`] is a headless backend that records commands, it is used in
+//! render tests.
+//!
+//! Creation of GPU resources (textures, buffers, programs) and uploading of their data (including
+//! uniforms) is still OpenGL-specific.
+
+use crate::{
+    core::{color::Color, math::Rect},
+    renderer::framework::{
+        framebuffer::{AttachmentKind, DrawParameters},
+        geometry_buffer::ElementKind,
+        gpu_texture::PixelElementKind,
+        state::{ColorMask, PipelineState},
+    },
+};
+use glow::HasContext;
+
+/// A set of operations that every render backend must support.
+pub trait RenderBackend {
+    /// Backend-specific frame buffer identifier.
+    type FrameBuffer: Copy + PartialEq;
+
+    /// Backend-specific GPU program identifier.
+    type Program: Copy + PartialEq;
+
+    /// Backend-specific geometry buffer identifier.
+    type Geometry: Copy + PartialEq;
+
+    /// Binds a frame buffer, `None` means the back buffer.
+    fn bind_framebuffer(&mut self, framebuffer: Option<Self::FrameBuffer>);
+
+    /// Binds a GPU program for the next draw calls.
+    fn bind_program(&mut self, program: Self::Program);
+
+    /// Binds a geometry buffer for the next draw calls.
+    fn bind_geometry(&mut self, geometry: Self::Geometry);
+
+    /// Draws `index_count` indices of the bound geometry buffer starting from `start_index`.
+    fn draw_elements(&mut self, kind: ElementKind, start_index: usize, index_count: usize);
+
+    /// Draws `instance_count` instances of first `index_count` indices of the bound geometry
+    /// buffer.
+    fn draw_elements_instanced(
+        &mut self,
+        kind: ElementKind,
+        index_count: usize,
+        instance_count: usize,
+    );
+
+    /// Sets viewport of the current frame buffer.
+    fn set_viewport(&mut self, viewport: Rect<i32>);
+
+    /// Applies pipeline state for the next draw calls.
+    fn apply_draw_parameters(&mut self, params: &DrawParameters);
+
+    /// Clears the back buffer using the given values. `None` values are not cleared.
+    fn clear_back_buffer(&mut self, color: Option<Color>, depth: Option<f32>, stencil: Option<i32>);
+
+    /// Clears depth (and stencil if the attachment has it) attachment of the current frame buffer.
+    fn clear_depth_stencil(
+        &mut self,
+        kind: AttachmentKind,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    );
+
+    /// Clears color attachment at the given index of the current frame buffer.
+    fn clear_color_attachment(&mut self, index: u32, element_kind: PixelElementKind, color: Color);
+}
+
+/// Clears the given frame buffer using the backend. `depth_attachment` and `color_attachments`
+/// describe attachments of the frame buffer, they're used to select appropriate clear routines.
+#[allow(clippy::too_many_arguments)]
+pub fn clear_framebuffer<B, I>(
+    backend: &mut B,
+    framebuffer: Option<B::FrameBuffer>,
+    depth_attachment: Option<AttachmentKind>,
+    color_attachments: I,
+    viewport: Rect<i32>,
+    color: Option<Color>,
+    depth: Option<f32>,
+    stencil: Option<i32>,
+) where
+    B: RenderBackend,
+    I: IntoIterator<Item = PixelElementKind>,
+{
+    backend.set_viewport(viewport);
+    backend.bind_framebuffer(framebuffer);
+
+    // Special route for default buffer.
+    if framebuffer.is_none() {
+        backend.clear_back_buffer(color, depth, stencil);
+    }
+
+    // Custom routes for specific frame buffer attachments.
+    if let Some(kind) = depth_attachment {
+        backend.clear_depth_stencil(kind, depth, stencil);
+    }
+
+    if let Some(color) = color {
+        for (i, element_kind) in color_attachments.into_iter().enumerate() {
+            backend.clear_color_attachment(i as u32, element_kind, color);
+        }
+    }
+}
+
+/// Prepares the backend for a draw call: binds the frame buffer, sets the viewport and the
+/// pipeline state.
+pub fn prepare_draw<B: RenderBackend>(
+    backend: &mut B,
+    framebuffer: Option<B::FrameBuffer>,
+    viewport: Rect<i32>,
+    params: &DrawParameters,
+) {
+    backend.bind_framebuffer(framebuffer);
+    backend.set_viewport(viewport);
+    backend.apply_draw_parameters(params);
+}
+
+/// OpenGL geometry buffer, element buffer binding is stored in the vertex array object.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct GlGeometry {
+    pub vertex_array: glow::VertexArray,
+    pub element_buffer: glow::Buffer,
+}
+
+fn gl_mode(kind: ElementKind) -> u32 {
+    match kind {
+        ElementKind::Triangle => glow::TRIANGLES,
+        ElementKind::Line => glow::LINES,
+    }
+}
+
+impl RenderBackend for PipelineState {
+    type FrameBuffer = glow::Framebuffer;
+    type Program = glow::Program;
+    type Geometry = GlGeometry;
+
+    fn bind_framebuffer(&mut self, framebuffer: Option<Self::FrameBuffer>) {
+        self.set_framebuffer(framebuffer);
+    }
+
+    fn bind_program(&mut self, program: Self::Program) {
+        self.set_program(Some(program));
+    }
+
+    fn bind_geometry(&mut self, geometry: Self::Geometry) {
+        self.set_vertex_array_object(Some(geometry.vertex_array));
+
+        // Element buffer object binding is stored inside vertex array object, so
+        // it does not modifies state.
+        unsafe {
+            self.gl
+                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(geometry.element_buffer));
+        }
+    }
+
+    fn draw_elements(&mut self, kind: ElementKind, start_index: usize, index_count: usize) {
+        if index_count > 0 {
+            unsafe {
+                self.gl.draw_elements(
+                    gl_mode(kind),
+                    index_count as i32,
+                    glow::UNSIGNED_INT,
+                    (start_index * std::mem::size_of::<u32>()) as i32,
+                );
+            }
+        }
+    }
+
+    fn draw_elements_instanced(
+        &mut self,
+        kind: ElementKind,
+        index_count: usize,
+        instance_count: usize,
+    ) {
+        if index_count > 0 {
+            unsafe {
+                self.gl.draw_elements_instanced(
+                    gl_mode(kind),
+                    index_count as i32,
+                    glow::UNSIGNED_INT,
+                    0,
+                    instance_count as i32,
+                )
+            }
+        }
+    }
+
+    fn set_viewport(&mut self, viewport: Rect<i32>) {
+        PipelineState::set_viewport(self, viewport);
+    }
+
+    fn apply_draw_parameters(&mut self, params: &DrawParameters) {
+        PipelineState::apply_draw_parameters(self, params);
+    }
+
+    fn clear_back_buffer(
+        &mut self,
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        let mut mask = 0;
+
+        if let Some(color) = color {
+            self.set_color_write(ColorMask::default());
+            self.set_clear_color(color);
+            mask |= glow::COLOR_BUFFER_BIT;
+        }
+        if let Some(depth) = depth {
+            self.set_depth_write(true);
+            self.set_clear_depth(depth);
+            mask |= glow::DEPTH_BUFFER_BIT;
+        }
+        if let Some(stencil) = stencil {
+            self.set_stencil_mask(0xFFFF_FFFF);
+            self.set_clear_stencil(stencil);
+            mask |= glow::STENCIL_BUFFER_BIT;
+        }
+
+        unsafe {
+            self.gl.clear(mask);
+        }
+    }
+
+    fn clear_depth_stencil(
+        &mut self,
+        kind: AttachmentKind,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        self.set_depth_write(true);
+        self.set_stencil_mask(0xFFFF_FFFF);
+
+        unsafe {
+            match kind {
+                AttachmentKind::Color => unreachable!("depth cannot be color!"),
+                AttachmentKind::DepthStencil => match (depth, stencil) {
+                    (Some(depth), Some(stencil)) => {
+                        self.gl
+                            .clear_buffer_depth_stencil(glow::DEPTH_STENCIL, 0, depth, stencil);
+                    }
+                    (Some(depth), None) => {
+                        let values = [depth];
+                        self.gl.clear_buffer_f32_slice(glow::DEPTH, 0, &values);
+                    }
+                    (None, Some(stencil)) => {
+                        let values = [stencil];
+                        self.gl.clear_buffer_i32_slice(glow::STENCIL, 0, &values);
+                    }
+                    (None, None) => {
+                        // Nothing to do
+                    }
+                },
+                AttachmentKind::Depth => {
+                    if let Some(depth) = depth {
+                        let values = [depth];
+                        self.gl.clear_buffer_f32_slice(glow::DEPTH, 0, &values);
+                    }
+                }
+            }
+        }
+    }
+
+    fn clear_color_attachment(&mut self, index: u32, element_kind: PixelElementKind, color: Color) {
+        self.set_color_write(ColorMask::default());
+
+        unsafe {
+            match element_kind {
+                PixelElementKind::Float | PixelElementKind::NormalizedUnsignedInteger => {
+                    let fvalues = color.as_frgba();
+                    self.gl
+                        .clear_buffer_f32_slice(glow::COLOR, index, &fvalues.data.0[0])
+                }
+                PixelElementKind::Integer => {
+                    let values = [
+                        color.r as i32,
+                        color.g as i32,
+                        color.b as i32,
+                        color.a as i32,
+                    ];
+                    self.gl.clear_buffer_i32_slice(glow::COLOR, index, &values);
+                }
+                PixelElementKind::UnsignedInteger => {
+                    let values = [
+                        color.r as u32,
+                        color.g as u32,
+                        color.b as u32,
+                        color.a as u32,
+                    ];
+                    self.gl.clear_buffer_u32_slice(glow::COLOR, index, &values);
+                }
+            }
+        }
+    }
+}
+
+/// A command recorded by [`MockBackend`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MockCommand {
+    BindFrameBuffer(Option<u32>),
+    BindProgram(u32),
+    BindGeometry(u32),
+    DrawElements {
+        kind: ElementKind,
+        start_index: usize,
+        index_count: usize,
+    },
+    DrawElementsInstanced {
+        kind: ElementKind,
+        index_count: usize,
+        instance_count: usize,
+    },
+    SetViewport(Rect<i32>),
+    ApplyDrawParameters(DrawParameters),
+    ClearBackBuffer {
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    },
+    ClearDepthStencil {
+        kind: AttachmentKind,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    },
+    ClearColorAttachment {
+        index: u32,
+        element_kind: PixelElementKind,
+        color: Color,
+    },
+}
+
+/// Headless backend, that does not render anything, but records every command instead. It is
+/// used to test render passes without a graphics context.
+#[derive(Default, Debug)]
+pub struct MockBackend {
+    /// A list of recorded commands.
+    pub commands: Vec<MockCommand>,
+}
+
+impl MockBackend {
+    /// Takes recorded commands out of the backend.
+    pub fn take_commands(&mut self) -> Vec<MockCommand> {
+        std::mem::take(&mut self.commands)
+    }
+}
+
+impl RenderBackend for MockBackend {
+    type FrameBuffer = u32;
+    type Program = u32;
+    type Geometry = u32;
+
+    fn bind_framebuffer(&mut self, framebuffer: Option<Self::FrameBuffer>) {
+        self.commands
+            .push(MockCommand::BindFrameBuffer(framebuffer));
+    }
+
+    fn bind_program(&mut self, program: Self::Program) {
+        self.commands.push(MockCommand::BindProgram(program));
+    }
+
+    fn bind_geometry(&mut self, geometry: Self::Geometry) {
+        self.commands.push(MockCommand::BindGeometry(geometry));
+    }
+
+    fn draw_elements(&mut self, kind: ElementKind, start_index: usize, index_count: usize) {
+        self.commands.push(MockCommand::DrawElements {
+            kind,
+            start_index,
+            index_count,
+        });
+    }
+
+    fn draw_elements_instanced(
+        &mut self,
+        kind: ElementKind,
+        index_count: usize,
+        instance_count: usize,
+    ) {
+        self.commands.push(MockCommand::DrawElementsInstanced {
+            kind,
+            index_count,
+            instance_count,
+        });
+    }
+
+    fn set_viewport(&mut self, viewport: Rect<i32>) {
+        self.commands.push(MockCommand::SetViewport(viewport));
+    }
+
+    fn apply_draw_parameters(&mut self, params: &DrawParameters) {
+        self.commands
+            .push(MockCommand::ApplyDrawParameters(params.clone()));
+    }
+
+    fn clear_back_buffer(
+        &mut self,
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        self.commands.push(MockCommand::ClearBackBuffer {
+            color,
+            depth,
+            stencil,
+        });
+    }
+
+    fn clear_depth_stencil(
+        &mut self,
+        kind: AttachmentKind,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        self.commands.push(MockCommand::ClearDepthStencil {
+            kind,
+            depth,
+            stencil,
+        });
+    }
+
+    fn clear_color_attachment(&mut self, index: u32, element_kind: PixelElementKind, color: Color) {
+        self.commands.push(MockCommand::ClearColorAttachment {
+            index,
+            element_kind,
+            color,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{color::Color, math::Rect},
+        renderer::framework::{
+            backend::{clear_framebuffer, prepare_draw, MockBackend, MockCommand, RenderBackend},
+            framebuffer::{AttachmentKind, DrawParameters},
+            geometry_buffer::ElementKind,
+            gpu_texture::PixelElementKind,
+        },
+    };
+
+    #[test]
+    fn test_clear_back_buffer() {
+        let mut backend = MockBackend::default();
+        let viewport = Rect::new(0, 0, 100, 100);
+
+        clear_framebuffer(
+            &mut backend,
+            None,
+            None,
+            std::iter::empty(),
+            viewport,
+            Some(Color::BLACK),
+            Some(1.0),
+            None,
+        );
+
+        assert_eq!(
+            backend.take_commands(),
+            vec![
+                MockCommand::SetViewport(viewport),
+                MockCommand::BindFrameBuffer(None),
+                MockCommand::ClearBackBuffer {
+                    color: Some(Color::BLACK),
+                    depth: Some(1.0),
+                    stencil: None
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clear_render_target_and_draw() {
+        let mut backend = MockBackend::default();
+        let viewport = Rect::new(0, 0, 100, 100);
+
+        clear_framebuffer(
+            &mut backend,
+            Some(1),
+            Some(AttachmentKind::DepthStencil),
+            [PixelElementKind::Float, PixelElementKind::UnsignedInteger],
+            viewport,
+            Some(Color::WHITE),
+            Some(1.0),
+            Some(0),
+        );
+        prepare_draw(&mut backend, Some(1), viewport, &DrawParameters::default());
+        backend.bind_program(2);
+        backend.bind_geometry(3);
+        backend.draw_elements(ElementKind::Triangle, 0, 6);
+
+        assert_eq!(
+            backend.take_commands(),
+            vec![
+                MockCommand::SetViewport(viewport),
+                MockCommand::BindFrameBuffer(Some(1)),
+                MockCommand::ClearDepthStencil {
+                    kind: AttachmentKind::DepthStencil,
+                    depth: Some(1.0),
+                    stencil: Some(0)
+                },
+                MockCommand::ClearColorAttachment {
+                    index: 0,
+                    element_kind: PixelElementKind::Float,
+                    color: Color::WHITE
+                },
+                MockCommand::ClearColorAttachment {
+                    index: 1,
+                    element_kind: PixelElementKind::UnsignedInteger,
+                    color: Color::WHITE
+                },
+                MockCommand::BindFrameBuffer(Some(1)),
+                MockCommand::SetViewport(viewport),
+                MockCommand::ApplyDrawParameters(DrawParameters::default()),
+                MockCommand::BindProgram(2),
+                MockCommand::BindGeometry(3),
+                MockCommand::DrawElements {
+                    kind: ElementKind::Triangle,
+                    start_index: 0,
+                    index_count: 6
+                },
+            ]
+        );
+    }
+}
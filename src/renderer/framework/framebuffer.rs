@@ -2,10 +2,11 @@ use crate::renderer::framework::state::BlendEquation;
 use crate::{
     core::{color::Color, math::Rect, scope_profile, visitor::prelude::*},
     renderer::framework::{
+        backend::{clear_framebuffer, prepare_draw},
         error::FrameworkError,
         geometry_buffer::{DrawCallStatistics, GeometryBuffer},
         gpu_program::{GpuProgram, GpuProgramBinding},
-        gpu_texture::{CubeMapFace, GpuTexture, GpuTextureKind, PixelElementKind},
+        gpu_texture::{CubeMapFace, GpuTexture, GpuTextureKind},
         state::{BlendFunc, ColorMask, PipelineState, StencilFunc, StencilOp},
     },
 };
@@ -230,109 +231,18 @@ impl FrameBuffer {
     ) {
         scope_profile!();
 
-        state.set_viewport(viewport);
-        state.set_framebuffer(self.id());
-
-        unsafe {
-            // Special route for default buffer.
-            if self.fbo == Default::default() {
-                let mut mask = 0;
-
-                if let Some(color) = color {
-                    state.set_color_write(ColorMask::default());
-                    state.set_clear_color(color);
-                    mask |= glow::COLOR_BUFFER_BIT;
-                }
-                if let Some(depth) = depth {
-                    state.set_depth_write(true);
-                    state.set_clear_depth(depth);
-                    mask |= glow::DEPTH_BUFFER_BIT;
-                }
-                if let Some(stencil) = stencil {
-                    state.set_stencil_mask(0xFFFF_FFFF);
-                    state.set_clear_stencil(stencil);
-                    mask |= glow::STENCIL_BUFFER_BIT;
-                }
-
-                state.gl.clear(mask);
-            }
-
-            // Custom routes for specific frame buffer attachments.
-            if let Some(depth_stencil) = self.depth_attachment.as_ref() {
-                state.set_depth_write(true);
-                state.set_stencil_mask(0xFFFF_FFFF);
-
-                match depth_stencil.kind {
-                    AttachmentKind::Color => unreachable!("depth cannot be color!"),
-                    AttachmentKind::DepthStencil => match (depth, stencil) {
-                        (Some(depth), Some(stencil)) => {
-                            state.gl.clear_buffer_depth_stencil(
-                                glow::DEPTH_STENCIL,
-                                0,
-                                depth,
-                                stencil,
-                            );
-                        }
-                        (Some(depth), None) => {
-                            let values = [depth];
-                            state.gl.clear_buffer_f32_slice(glow::DEPTH, 0, &values);
-                        }
-                        (None, Some(stencil)) => {
-                            let values = [stencil];
-                            state.gl.clear_buffer_i32_slice(glow::STENCIL, 0, &values);
-                        }
-                        (None, None) => {
-                            // Nothing to do
-                        }
-                    },
-                    AttachmentKind::Depth => {
-                        if let Some(depth) = depth {
-                            let values = [depth];
-                            state.gl.clear_buffer_f32_slice(glow::DEPTH, 0, &values);
-                        }
-                    }
-                }
-            }
-
-            if let Some(color) = color {
-                state.set_color_write(ColorMask::default());
-
-                for (i, attachment) in self.color_attachments.iter().enumerate() {
-                    match attachment.texture.borrow().pixel_kind().element_kind() {
-                        PixelElementKind::Float | PixelElementKind::NormalizedUnsignedInteger => {
-                            let fvalues = color.as_frgba();
-                            state.gl.clear_buffer_f32_slice(
-                                glow::COLOR,
-                                i as u32,
-                                &fvalues.data.0[0],
-                            )
-                        }
-                        PixelElementKind::Integer => {
-                            let values = [
-                                color.r as i32,
-                                color.g as i32,
-                                color.b as i32,
-                                color.a as i32,
-                            ];
-                            state
-                                .gl
-                                .clear_buffer_i32_slice(glow::COLOR, i as u32, &values);
-                        }
-                        PixelElementKind::UnsignedInteger => {
-                            let values = [
-                                color.r as u32,
-                                color.g as u32,
-                                color.b as u32,
-                                color.a as u32,
-                            ];
-                            state
-                                .gl
-                                .clear_buffer_u32_slice(glow::COLOR, i as u32, &values);
-                        }
-                    }
-                }
-            }
-        }
+        clear_framebuffer(
+            state,
+            self.id(),
+            self.depth_attachment.as_ref().map(|a| a.kind),
+            self.color_attachments
+                .iter()
+                .map(|a| a.texture.borrow().pixel_kind().element_kind()),
+            viewport,
+            color,
+            depth,
+            stencil,
+        );
     }
 
     pub fn draw<F: FnOnce(GpuProgramBinding<'_, '_>)>(
@@ -394,9 +304,7 @@ fn pre_draw<F: FnOnce(GpuProgramBinding<'_, '_>)>(
 ) {
     scope_profile!();
 
-    state.set_framebuffer(fbo);
-    state.set_viewport(viewport);
-    state.apply_draw_parameters(params);
+    prepare_draw(state, fbo, viewport, params);
 
     let program_binding = program.bind(state);
     apply_uniforms(program_binding);
@@ -1,7 +1,11 @@
 use crate::scene::mesh::surface::SurfaceData;
 use crate::{
     core::{math::TriangleDefinition, scope_profile},
-    renderer::framework::{error::FrameworkError, state::PipelineState},
+    renderer::framework::{
+        backend::{GlGeometry, RenderBackend},
+        error::FrameworkError,
+        state::PipelineState,
+    },
     scene::mesh::buffer::{VertexAttributeDataType, VertexBuffer},
     utils::array_as_u8_slice,
 };
@@ -32,6 +36,7 @@ pub struct GeometryBuffer {
     buffers: Vec<NativeBuffer>,
     element_buffer_object: glow::Buffer,
     element_count: Cell<usize>,
+    element_buffer_size: Cell<usize>,
     element_kind: ElementKind,
     // Force compiler to not implement Send and Sync, because OpenGL is not thread-safe.
     thread_mark: PhantomData<*const u8>,
@@ -202,13 +207,14 @@ impl<'a> GeometryBufferBinding<'a> {
     unsafe fn set_elements(&self, data: &[u8]) {
         scope_profile!();
 
+        self.buffer.element_buffer_size.set(data.len());
         self.state
             .gl
             .buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, data, glow::DYNAMIC_DRAW);
     }
 
     pub fn draw_part(
-        &self,
+        &mut self,
         offset: usize,
         count: usize,
     ) -> Result<DrawCallStatistics, FrameworkError> {
@@ -227,63 +233,38 @@ impl<'a> GeometryBufferBinding<'a> {
             let start_index = offset * index_per_element;
             let index_count = count * index_per_element;
 
-            unsafe {
-                self.draw_internal(start_index, index_count);
-            }
+            self.draw_internal(start_index, index_count);
 
             Ok(DrawCallStatistics { triangles: count })
         }
     }
 
-    fn mode(&self) -> u32 {
-        match self.buffer.element_kind {
-            ElementKind::Triangle => glow::TRIANGLES,
-            ElementKind::Line => glow::LINES,
-        }
-    }
-
-    pub fn draw(&self) -> DrawCallStatistics {
+    pub fn draw(&mut self) -> DrawCallStatistics {
         scope_profile!();
 
         let start_index = 0;
         let index_per_element = self.buffer.element_kind.index_per_element();
         let index_count = self.buffer.element_count.get() * index_per_element;
 
-        unsafe { self.draw_internal(start_index, index_count) }
+        self.draw_internal(start_index, index_count);
 
         DrawCallStatistics {
             triangles: self.buffer.element_count.get(),
         }
     }
 
-    unsafe fn draw_internal(&self, start_index: usize, index_count: usize) {
+    fn draw_internal(&mut self, start_index: usize, index_count: usize) {
         scope_profile!();
 
-        if index_count > 0 {
-            let indices = (start_index * size_of::<u32>()) as i32;
-            self.state.gl.draw_elements(
-                self.mode(),
-                index_count as i32,
-                glow::UNSIGNED_INT,
-                indices,
-            );
-        }
+        self.state
+            .draw_elements(self.buffer.element_kind, start_index, index_count);
     }
 
-    pub fn draw_instances(&self, count: usize) -> DrawCallStatistics {
+    pub fn draw_instances(&mut self, count: usize) -> DrawCallStatistics {
         let index_per_element = self.buffer.element_kind.index_per_element();
         let index_count = self.buffer.element_count.get() * index_per_element;
-        if index_count > 0 {
-            unsafe {
-                self.state.gl.draw_elements_instanced(
-                    self.mode(),
-                    index_count as i32,
-                    glow::UNSIGNED_INT,
-                    0,
-                    count as i32,
-                )
-            }
-        }
+        self.state
+            .draw_elements_instanced(self.buffer.element_kind, index_count, count);
         DrawCallStatistics {
             triangles: self.buffer.element_count.get() * count,
         }
@@ -335,18 +316,18 @@ impl GeometryBuffer {
         buffer.size_bytes = size;
     }
 
+    /// Returns amount of video memory (in bytes) occupied by the vertex and element buffers.
+    pub fn byte_size(&self) -> usize {
+        self.buffers.iter().map(|b| b.size_bytes).sum::<usize>() + self.element_buffer_size.get()
+    }
+
     pub fn bind<'a>(&'a self, state: &'a mut PipelineState) -> GeometryBufferBinding<'a> {
         scope_profile!();
 
-        state.set_vertex_array_object(Some(self.vertex_array_object));
-
-        // Element buffer object binding is stored inside vertex array object, so
-        // it does not modifies state.
-        unsafe {
-            state
-                .gl
-                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.element_buffer_object));
-        }
+        state.bind_geometry(GlGeometry {
+            vertex_array: self.vertex_array_object,
+            element_buffer: self.element_buffer_object,
+        });
 
         GeometryBufferBinding {
             state,
@@ -525,6 +506,7 @@ impl GeometryBufferBuilder {
             buffers,
             element_buffer_object: ebo,
             element_count: Cell::new(0),
+            element_buffer_size: Cell::new(0),
             element_kind: self.element_kind,
             thread_mark: PhantomData,
         })
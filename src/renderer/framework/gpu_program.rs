@@ -5,7 +5,10 @@ use crate::{
         algebra::{Matrix3, Matrix4, Vector2, Vector3, Vector4},
         color::Color,
     },
-    renderer::framework::{error::FrameworkError, gpu_texture::GpuTexture, state::PipelineState},
+    renderer::framework::{
+        backend::RenderBackend, error::FrameworkError, gpu_texture::GpuTexture,
+        state::PipelineState,
+    },
     utils::log::{Log, MessageKind},
 };
 use fxhash::FxHashMap;
@@ -525,7 +528,7 @@ impl GpuProgram {
     }
 
     pub fn bind<'a, 'b>(&'b self, state: &'a mut PipelineState) -> GpuProgramBinding<'a, 'b> {
-        state.set_program(Some(self.id));
+        state.bind_program(self.id);
         GpuProgramBinding {
             state,
             active_sampler: 0,
@@ -65,6 +65,27 @@ impl GpuTextureKind {
             Self::Volume { .. } => glow::TEXTURE_3D,
         }
     }
+
+    /// Returns amount of mip levels in a full mip chain of a texture of this kind, down to
+    /// 1x1 level.
+    pub fn full_mip_count(&self) -> usize {
+        let max_size = match *self {
+            Self::Line { length } => length,
+            Self::Rectangle { width, height } | Self::Cube { width, height } => width.max(height),
+            Self::Volume {
+                width,
+                height,
+                depth,
+            } => width.max(height).max(depth),
+        };
+        let mut count = 1;
+        let mut size = max_size;
+        while size > 1 {
+            size /= 2;
+            count += 1;
+        }
+        count
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -133,6 +154,7 @@ impl From<TexturePixelKind> for PixelKind {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PixelElementKind {
     Float,
     NormalizedUnsignedInteger,
@@ -255,7 +277,11 @@ pub struct GpuTexture {
     t_wrap_mode: WrapMode,
     r_wrap_mode: WrapMode,
     anisotropy: f32,
+    lod_bias: f32,
     pixel_kind: PixelKind,
+    byte_size: usize,
+    mip_count: usize,
+    mips_generated: bool,
     // Force compiler to not implement Send and Sync, because OpenGL is not thread-safe.
     thread_mark: PhantomData<*const u8>,
 }
@@ -300,7 +326,7 @@ fn image_3d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize, depth
     }
 }
 
-fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -> usize {
+pub(crate) fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -> usize {
     let pixel_count = width * height;
     match pixel_kind {
         PixelKind::RGBA32F => 16 * pixel_count,
@@ -503,7 +529,7 @@ impl<'a> TextureBinding<'a> {
                 .gl
                 .get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT);
             self.state.gl.tex_parameter_f32(
-                glow::TEXTURE_2D,
+                self.texture.kind.gl_texture_target(),
                 glow::TEXTURE_MAX_ANISOTROPY_EXT,
                 anisotropy.max(1.0).min(max),
             );
@@ -575,6 +601,83 @@ impl<'a> TextureBinding<'a> {
         self
     }
 
+    /// Sets bias that is added to the level of detail computed by the sampler. Positive values make
+    /// the texture blurrier, negative - sharper. The bias is ignored on WebGL.
+    pub fn set_lod_bias(self, #[allow(unused_variables)] lod_bias: f32) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            self.state.gl.tex_parameter_f32(
+                self.texture.kind.gl_texture_target(),
+                glow::TEXTURE_LOD_BIAS,
+                lod_bias,
+            );
+        }
+        self.texture.lod_bias = lod_bias;
+        self
+    }
+
+    /// Generates every mip level of the texture from its first level.
+    pub fn generate_mipmap(self) -> Self {
+        unsafe {
+            self.state
+                .gl
+                .generate_mipmap(self.texture.kind.gl_texture_target());
+        }
+        self
+    }
+
+    /// Generates full mip chain (down to 1x1 level) of the texture from its first level, discarding
+    /// any previously uploaded mip levels. Compressed textures are left untouched, because mip levels
+    /// of such textures cannot be generated at runtime.
+    pub fn generate_mip_chain(self) -> Self {
+        if self.texture.pixel_kind.is_compressed() {
+            return self;
+        }
+
+        let kind = self.texture.kind;
+        let pixel_kind = self.texture.pixel_kind;
+        let mip_count = kind.full_mip_count();
+
+        let mut byte_size = 0;
+        for mip in 0..mip_count {
+            let level_size = |size: usize| (size >> mip).max(1);
+            byte_size += match kind {
+                GpuTextureKind::Line { length } => {
+                    image_1d_size_bytes(pixel_kind, level_size(length))
+                }
+                GpuTextureKind::Rectangle { width, height } => {
+                    image_2d_size_bytes(pixel_kind, level_size(width), level_size(height))
+                }
+                GpuTextureKind::Cube { width, height } => {
+                    6 * image_2d_size_bytes(pixel_kind, level_size(width), level_size(height))
+                }
+                GpuTextureKind::Volume {
+                    width,
+                    height,
+                    depth,
+                } => image_3d_size_bytes(
+                    pixel_kind,
+                    level_size(width),
+                    level_size(height),
+                    level_size(depth),
+                ),
+            };
+        }
+
+        unsafe {
+            let target = kind.gl_texture_target();
+            self.state
+                .gl
+                .tex_parameter_i32(target, glow::TEXTURE_MAX_LEVEL, mip_count as i32 - 1);
+            self.state.gl.generate_mipmap(target);
+        }
+
+        self.texture.mip_count = mip_count;
+        self.texture.byte_size = byte_size;
+        self.texture.mips_generated = true;
+        self
+    }
+
     pub fn set_data(
         self,
         kind: GpuTextureKind,
@@ -645,6 +748,9 @@ impl<'a> TextureBinding<'a> {
 
         self.texture.kind = kind;
         self.texture.pixel_kind = pixel_kind;
+        self.texture.byte_size = desired_byte_count;
+        self.texture.mip_count = mip_count;
+        self.texture.mips_generated = false;
 
         let target = kind.gl_texture_target();
 
@@ -998,7 +1104,11 @@ impl GpuTexture {
                 t_wrap_mode: WrapMode::Repeat,
                 r_wrap_mode: WrapMode::Repeat,
                 anisotropy: 1.0,
+                lod_bias: 0.0,
                 pixel_kind,
+                byte_size: 0,
+                mip_count: 0,
+                mips_generated: false,
                 thread_mark: PhantomData,
             };
 
@@ -1081,9 +1191,30 @@ impl GpuTexture {
         self.anisotropy
     }
 
+    /// Returns level of detail bias of the texture.
+    pub fn lod_bias(&self) -> f32 {
+        self.lod_bias
+    }
+
     pub fn pixel_kind(&self) -> PixelKind {
         self.pixel_kind
     }
+
+    /// Returns amount of video memory (in bytes) occupied by the texture, including all its mips.
+    pub fn byte_size(&self) -> usize {
+        self.byte_size
+    }
+
+    /// Returns amount of mip levels of the texture.
+    pub fn mip_count(&self) -> usize {
+        self.mip_count
+    }
+
+    /// Returns `true` if mip levels of the texture were generated at runtime, instead of being
+    /// uploaded from texture data.
+    pub fn has_generated_mips(&self) -> bool {
+        self.mips_generated
+    }
 }
 
 impl Drop for GpuTexture {
@@ -1,5 +1,6 @@
 #![allow(missing_docs)] // TODO
 
+pub mod backend;
 pub mod error;
 pub mod framebuffer;
 pub mod geometry_buffer;
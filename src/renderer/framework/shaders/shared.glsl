@@ -275,6 +275,149 @@ float S_SpotShadowFactor(
     }
 }
 
+// Maps local coordinates of a region of shadow atlas to atlas coordinates. The region is packed as
+// (x, y, size, half texel size) in normalized atlas coordinates. Local coordinates are clamped to
+// the region to prevent bleeding of neighbour shadow maps.
+vec2 S_ShadowAtlasCoords(vec4 region, vec2 localCoords)
+{
+    return region.xy + clamp(localCoords, vec2(region.w), vec2(1.0 - region.w)) * region.z;
+}
+
+// Same as S_SpotShadowFactor, but the shadow map is stored in a region of shadow atlas.
+float S_SpotShadowFactorAtlas(
+    bool shadowsEnabled,
+    bool softShadows,
+    float shadowBias,
+    vec3 fragmentPosition,
+    mat4 lightViewProjMatrix,
+    float shadowMapInvSize,
+    vec4 atlasRegion,
+    in sampler2D shadowAtlas)
+{
+    if (shadowsEnabled)
+    {
+        vec3 lightSpacePosition = S_Project(fragmentPosition, lightViewProjMatrix);
+
+        float biasedLightSpaceFragmentDepth = lightSpacePosition.z - shadowBias;
+
+        if (softShadows)
+        {
+            float accumulator = 0.0;
+
+            for (float y = -0.5; y <= 0.5; y += 0.5)
+            {
+                for (float x = -0.5; x <= 0.5; x += 0.5)
+                {
+                    vec2 fetchTexCoord = S_ShadowAtlasCoords(atlasRegion, lightSpacePosition.xy + vec2(x, y) * shadowMapInvSize);
+                    if (biasedLightSpaceFragmentDepth > texture(shadowAtlas, fetchTexCoord).r)
+                    {
+                        accumulator += 1.0;
+                    }
+                }
+            }
+
+            return clamp(1.0 - accumulator / 9.0, 0.0, 1.0);
+        }
+        else
+        {
+            vec2 fetchTexCoord = S_ShadowAtlasCoords(atlasRegion, lightSpacePosition.xy);
+            return biasedLightSpaceFragmentDepth > texture(shadowAtlas, fetchTexCoord).r ? 0.0 : 1.0;
+        }
+    } else {
+        return 1.0; // No shadow
+    }
+}
+
+// Returns shadow atlas coordinates for the given direction from a point light. Every cube map face
+// is stored in its own region of the atlas. Order and orientation of the faces must match point
+// shadow map renderer.
+vec2 S_PointShadowAtlasCoords(vec3 direction, vec4 faceRegions[6])
+{
+    const vec3 looks[6] = vec3[6] (
+    vec3(1.0, 0.0, 0.0), vec3(-1.0, 0.0, 0.0),
+    vec3(0.0, 1.0, 0.0), vec3(0.0, -1.0, 0.0),
+    vec3(0.0, 0.0, 1.0), vec3(0.0, 0.0, -1.0)
+    );
+
+    const vec3 ups[6] = vec3[6] (
+    vec3(0.0, -1.0, 0.0), vec3(0.0, -1.0, 0.0),
+    vec3(0.0, 0.0, 1.0), vec3(0.0, 0.0, -1.0),
+    vec3(0.0, -1.0, 0.0), vec3(0.0, -1.0, 0.0)
+    );
+
+    vec3 absDirection = abs(direction);
+    int face;
+    if (absDirection.x >= absDirection.y && absDirection.x >= absDirection.z) {
+        face = direction.x > 0.0 ? 0 : 1;
+    } else if (absDirection.y >= absDirection.z) {
+        face = direction.y > 0.0 ? 2 : 3;
+    } else {
+        face = direction.z > 0.0 ? 4 : 5;
+    }
+
+    // Same basis as the view matrix of the face (right-handed look-at).
+    vec3 look = looks[face];
+    vec3 zAxis = -look;
+    vec3 xAxis = normalize(cross(ups[face], zAxis));
+    vec3 yAxis = cross(zAxis, xAxis);
+
+    vec2 ndc = vec2(dot(direction, xAxis), dot(direction, yAxis)) / dot(direction, look);
+
+    return S_ShadowAtlasCoords(faceRegions[face], ndc * 0.5 + 0.5);
+}
+
+// Same as S_PointShadow, but the shadow map is stored in six regions of shadow atlas.
+float S_PointShadowAtlas(
+    bool shadowsEnabled,
+    bool softShadows,
+    float fragmentDistance,
+    float shadowBias,
+    vec3 toLight,
+    vec4 faceRegions[6],
+    in sampler2D shadowAtlas)
+{
+    if (shadowsEnabled)
+    {
+        float biasedFragmentDistance = fragmentDistance - shadowBias;
+
+        if (softShadows)
+        {
+            const int samples = 20;
+
+            const vec3 directions[samples] = vec3[samples] (
+            vec3(1, 1, 1), vec3(1, -1, 1), vec3(-1, -1, 1), vec3(-1, 1, 1),
+            vec3(1, 1, -1), vec3(1, -1, -1), vec3(-1, -1, -1), vec3(-1, 1, -1),
+            vec3(1, 1, 0), vec3(1, -1, 0), vec3(-1, -1, 0), vec3(-1, 1, 0),
+            vec3(1, 0, 1), vec3(-1, 0, 1), vec3(1, 0, -1), vec3(-1, 0, -1),
+            vec3(0, 1, 1), vec3(0, -1, 1), vec3(0, -1, -1), vec3(0, 1, -1)
+            );
+
+            const float diskRadius = 0.0025;
+
+            float accumulator = 0.0;
+
+            for (int i = 0; i < samples; ++i)
+            {
+                vec3 fetchDirection = -toLight + directions[i] * diskRadius;
+                float shadowDistanceToLight = texture(shadowAtlas, S_PointShadowAtlasCoords(fetchDirection, faceRegions)).r;
+                if (biasedFragmentDistance > shadowDistanceToLight)
+                {
+                    accumulator += 1.0;
+                }
+            }
+
+            return clamp(1.0 - accumulator / float(samples), 0.0, 1.0);
+        }
+        else
+        {
+            float shadowDistanceToLight = texture(shadowAtlas, S_PointShadowAtlasCoords(-toLight, faceRegions)).r;
+            return biasedFragmentDistance > shadowDistanceToLight ? 0.0 : 1.0;
+        }
+    } else {
+        return 1.0; // No shadow
+    }
+}
+
 float Internal_FetchHeight(in sampler2D heightTexture, vec2 texCoords) {
     return texture(heightTexture, texCoords).r;
 }
@@ -72,6 +72,26 @@ impl Default for CompareFunc {
     }
 }
 
+/// Defines how polygons are rasterized.
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash, Debug)]
+#[repr(u32)]
+pub enum PolygonFillMode {
+    /// Only vertices of polygons are drawn.
+    Point = glow::POINT,
+
+    /// Only edges of polygons are drawn.
+    Line = glow::LINE,
+
+    /// Polygons are filled.
+    Fill = glow::FILL,
+}
+
+impl Default for PolygonFillMode {
+    fn default() -> Self {
+        Self::Fill
+    }
+}
+
 #[derive(Copy, Clone, Hash, PartialOrd, PartialEq, Eq, Ord, Deserialize, Visit, Debug)]
 #[repr(u32)]
 pub enum BlendFactor {
@@ -177,6 +197,8 @@ pub struct PipelineState {
     depth_write: bool,
     depth_func: CompareFunc,
 
+    polygon_fill_mode: PolygonFillMode,
+
     color_write: ColorMask,
     stencil_test: bool,
     cull_face: CullFace,
@@ -338,6 +360,7 @@ impl PipelineState {
             depth_test: false,
             depth_write: true,
             depth_func: Default::default(),
+            polygon_fill_mode: Default::default(),
             color_write: Default::default(),
             stencil_test: false,
             cull_face: CullFace::Back,
@@ -361,6 +384,55 @@ impl PipelineState {
         }
     }
 
+    /// Reads a color of a single pixel of the back buffer. Coordinates are in OpenGL
+    /// convention (origin at the bottom-left corner).
+    pub fn read_backbuffer_pixel(&mut self, x: i32, y: i32) -> Color {
+        self.set_framebuffer(None);
+
+        let mut pixel = [0u8; 4];
+        unsafe {
+            self.gl.read_pixels(
+                x,
+                y,
+                1,
+                1,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixel),
+            );
+        }
+
+        Color::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+    }
+
+    /// Reads RGBA8 pixels of the given rectangle of a framebuffer (`None` - back buffer).
+    /// Coordinates are in OpenGL convention (origin at the bottom-left corner), rows are
+    /// ordered from bottom to top.
+    pub fn read_pixels(
+        &mut self,
+        framebuffer: Option<glow::Framebuffer>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> Vec<u8> {
+        self.set_framebuffer(framebuffer);
+
+        let mut pixels = vec![0u8; width.max(0) as usize * height.max(0) as usize * 4];
+        unsafe {
+            self.gl.read_pixels(
+                x,
+                y,
+                width,
+                height,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixels),
+            );
+        }
+        pixels
+    }
+
     pub fn set_framebuffer(&mut self, framebuffer: Option<glow::Framebuffer>) {
         if self.framebuffer != framebuffer {
             self.framebuffer = framebuffer;
@@ -559,6 +631,20 @@ impl PipelineState {
         }
     }
 
+    /// Sets polygon rasterization mode for both front and back faces. The mode is not supported
+    /// on WebGL, where polygons are always filled.
+    pub fn set_polygon_fill_mode(&mut self, polygon_fill_mode: PolygonFillMode) {
+        if self.polygon_fill_mode != polygon_fill_mode {
+            self.polygon_fill_mode = polygon_fill_mode;
+
+            #[cfg(not(target_arch = "wasm32"))]
+            unsafe {
+                self.gl
+                    .polygon_mode(glow::FRONT_AND_BACK, polygon_fill_mode as u32);
+            }
+        }
+    }
+
     pub fn set_program(&mut self, program: Option<glow::Program>) {
         if self.program != program {
             self.program = program;
@@ -0,0 +1,287 @@
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector4},
+        math::Rect,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{CullFace, DrawParameters, FrameBuffer},
+            geometry_buffer::{
+                AttributeDefinition, AttributeKind, BufferBuilder, ElementKind, GeometryBuffer,
+                GeometryBufferBuilder, GeometryBufferKind,
+            },
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::PipelineState,
+        },
+        RenderPassStatistics, TextureCache,
+    },
+    scene::{camera::Camera, crowd::Crowd, graph::Graph, mesh::surface::SurfaceSharedData},
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, rc::Rc};
+
+/// Amount of frames after which unused geometry buffer will be destroyed.
+const GEOMETRY_LIFETIME_FRAMES: u32 = 600;
+
+pub struct CrowdShader {
+    pub view_projection: UniformLocation,
+    pub animation_texture: UniformLocation,
+    pub diffuse_texture: UniformLocation,
+    pub normal_texture: UniformLocation,
+    pub program: GpuProgram,
+}
+
+impl CrowdShader {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/crowd_fs.glsl");
+        let vertex_source = include_str!("../shaders/crowd_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "CrowdShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            view_projection: program
+                .uniform_location(state, &ImmutableString::new("viewProjection"))?,
+            animation_texture: program
+                .uniform_location(state, &ImmutableString::new("animationTexture"))?,
+            diffuse_texture: program
+                .uniform_location(state, &ImmutableString::new("diffuseTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            program,
+        })
+    }
+}
+
+#[repr(C)]
+pub(crate) struct InstanceData {
+    pub world_matrix: Matrix4<f32>,
+    /// - `x` - first frame of the clip
+    /// - `y` - frame count of the clip
+    /// - `z` - frame rate of the clip
+    /// - `w` - animation time of the instance
+    pub animation: Vector4<f32>,
+    pub looped: f32,
+}
+
+struct CrowdGeometry {
+    geometry: GeometryBuffer,
+    unused_frames: u32,
+}
+
+fn create_crowd_geometry(state: &mut PipelineState, data: &SurfaceSharedData) -> GeometryBuffer {
+    let data = data.lock();
+
+    let geometry_buffer = GeometryBufferBuilder::new(ElementKind::Triangle)
+        .with_buffer_builder(BufferBuilder::from_vertex_buffer(
+            &data.vertex_buffer,
+            GeometryBufferKind::StaticDraw,
+        ))
+        // Buffer for instance data.
+        .with_buffer_builder(
+            BufferBuilder::new::<InstanceData>(GeometryBufferKind::DynamicDraw, None)
+                // World Matrix
+                .with_attribute(AttributeDefinition {
+                    location: 7,
+                    kind: AttributeKind::Float4,
+                    normalized: false,
+                    divisor: 1,
+                })
+                .with_attribute(AttributeDefinition {
+                    location: 8,
+                    kind: AttributeKind::Float4,
+                    normalized: false,
+                    divisor: 1,
+                })
+                .with_attribute(AttributeDefinition {
+                    location: 9,
+                    kind: AttributeKind::Float4,
+                    normalized: false,
+                    divisor: 1,
+                })
+                .with_attribute(AttributeDefinition {
+                    location: 10,
+                    kind: AttributeKind::Float4,
+                    normalized: false,
+                    divisor: 1,
+                })
+                // Animation
+                .with_attribute(AttributeDefinition {
+                    location: 11,
+                    kind: AttributeKind::Float4,
+                    normalized: false,
+                    divisor: 1,
+                })
+                // Looped
+                .with_attribute(AttributeDefinition {
+                    location: 12,
+                    kind: AttributeKind::Float,
+                    normalized: false,
+                    divisor: 1,
+                }),
+        )
+        .build(state)
+        .unwrap();
+
+    geometry_buffer
+        .bind(state)
+        .set_triangles(data.geometry_buffer.triangles_ref());
+
+    geometry_buffer
+}
+
+/// Renders crowds into the G-Buffer. Every crowd is drawn using a single instanced draw call,
+/// skinning is done in the vertex shader using bone matrices from the baked animation texture.
+pub struct CrowdRenderer {
+    shader: CrowdShader,
+    geometry: FxHashMap<u64, CrowdGeometry>,
+    instances: Vec<InstanceData>,
+}
+
+pub(crate) struct CrowdRenderContext<'a, 'b> {
+    pub state: &'a mut PipelineState,
+    pub framebuffer: &'a mut FrameBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'b Camera,
+    pub graph: &'b Graph,
+    pub texture_cache: &'a mut TextureCache,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+    pub normal_dummy: Rc<RefCell<GpuTexture>>,
+}
+
+impl CrowdRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: CrowdShader::new(state)?,
+            geometry: Default::default(),
+            instances: Default::default(),
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn render(&mut self, args: CrowdRenderContext) -> RenderPassStatistics {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let CrowdRenderContext {
+            state,
+            framebuffer,
+            viewport,
+            camera,
+            graph,
+            texture_cache,
+            white_dummy,
+            normal_dummy,
+        } = args;
+
+        for entry in self.geometry.values_mut() {
+            entry.unused_frames += 1;
+        }
+
+        let view_projection = camera.view_projection_matrix();
+
+        for (handle, node) in graph.pair_iter() {
+            let crowd = if let Some(crowd) = node.cast::<Crowd>() {
+                crowd
+            } else {
+                continue;
+            };
+
+            if crowd.instances().is_empty() || !camera.visibility_cache.is_visible(handle) {
+                continue;
+            }
+
+            let animations = crowd.animations();
+            let animation_texture = if let Some(texture) = animations
+                .texture()
+                .and_then(|t| texture_cache.get(state, t))
+            {
+                texture
+            } else {
+                continue;
+            };
+
+            let (diffuse_texture, normal_texture) = {
+                let material = crowd.surface().material().lock();
+                let mut fetch = |name: &str| {
+                    material
+                        .property_ref(&ImmutableString::new(name))
+                        .and_then(|p| p.as_sampler())
+                        .and_then(|t| texture_cache.get(state, &t))
+                };
+                (
+                    fetch("diffuseTexture").unwrap_or_else(|| white_dummy.clone()),
+                    fetch("normalTexture").unwrap_or_else(|| normal_dummy.clone()),
+                )
+            };
+
+            self.instances.clear();
+            let crowd_transform = crowd.global_transform();
+            for instance in crowd.instances() {
+                if let Some(clip) = animations.clips().get(instance.clip as usize) {
+                    self.instances.push(InstanceData {
+                        world_matrix: crowd_transform * instance.local_transform(),
+                        animation: Vector4::new(
+                            clip.first_frame as f32,
+                            clip.frame_count as f32,
+                            clip.frame_rate,
+                            crowd.time() * instance.speed + instance.time_offset,
+                        ),
+                        looped: if clip.looped { 1.0 } else { 0.0 },
+                    });
+                }
+            }
+
+            if self.instances.is_empty() {
+                continue;
+            }
+
+            let data = crowd.surface().data();
+            let entry = self
+                .geometry
+                .entry(data.key())
+                .or_insert_with(|| CrowdGeometry {
+                    geometry: create_crowd_geometry(state, &data),
+                    unused_frames: 0,
+                });
+            entry.unused_frames = 0;
+            entry
+                .geometry
+                .set_buffer_data(state, 1, self.instances.as_slice());
+
+            let shader = &self.shader;
+            statistics += framebuffer.draw_instances(
+                self.instances.len(),
+                &entry.geometry,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: Some(CullFace::Back),
+                    color_write: Default::default(),
+                    depth_write: true,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: None,
+                    stencil_op: Default::default(),
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.view_projection, &view_projection)
+                        .set_texture(&shader.animation_texture, &animation_texture)
+                        .set_texture(&shader.diffuse_texture, &diffuse_texture)
+                        .set_texture(&shader.normal_texture, &normal_texture);
+                },
+            );
+        }
+
+        self.geometry
+            .retain(|_, entry| entry.unused_frames < GEOMETRY_LIFETIME_FRAMES);
+
+        statistics
+    }
+}
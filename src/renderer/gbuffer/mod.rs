@@ -34,13 +34,17 @@ use crate::{
             },
             state::{BlendFactor, BlendFunc, PipelineState},
         },
-        gbuffer::decal::DecalShader,
+        gbuffer::{
+            crowd::{CrowdRenderContext, CrowdRenderer},
+            decal::DecalShader,
+        },
         GeometryCache, MaterialContext, RenderPassStatistics, TextureCache,
     },
     scene::{camera::Camera, graph::Graph, mesh::surface::SurfaceData, mesh::RenderPath},
 };
 use std::{cell::RefCell, rc::Rc};
 
+mod crowd;
 mod decal;
 
 pub struct GBuffer {
@@ -50,6 +54,7 @@ pub struct GBuffer {
     pub height: i32,
     cube: GeometryBuffer,
     decal_shader: DecalShader,
+    crowd_renderer: CrowdRenderer,
     render_pass_name: ImmutableString,
 }
 
@@ -215,6 +220,7 @@ impl GBuffer {
             width: width as i32,
             height: height as i32,
             decal_shader: DecalShader::new(state)?,
+            crowd_renderer: CrowdRenderer::new(state)?,
             cube: GeometryBuffer::from_surface_data(
                 &SurfaceData::make_cube(Matrix4::identity()),
                 GeometryBufferKind::StaticDraw,
@@ -338,6 +344,17 @@ impl GBuffer {
             }
         }
 
+        statistics += self.crowd_renderer.render(CrowdRenderContext {
+            state,
+            framebuffer: &mut self.framebuffer,
+            viewport,
+            camera,
+            graph,
+            texture_cache,
+            white_dummy: white_dummy.clone(),
+            normal_dummy: normal_dummy.clone(),
+        });
+
         let inv_view_proj = initial_view_projection.try_inverse().unwrap_or_default();
         let depth = self.depth();
         let decal_mask = self.decal_mask_texture();
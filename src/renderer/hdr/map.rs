@@ -11,6 +11,7 @@ pub struct MapShader {
     pub hdr_sampler: UniformLocation,
     pub lum_sampler: UniformLocation,
     pub bloom_sampler: UniformLocation,
+    pub bloom_intensity: UniformLocation,
     pub color_map_sampler: UniformLocation,
     pub use_color_grading: UniformLocation,
     pub key_value: UniformLocation,
@@ -35,6 +36,8 @@ impl MapShader {
             lum_sampler: program.uniform_location(state, &ImmutableString::new("lumSampler"))?,
             bloom_sampler: program
                 .uniform_location(state, &ImmutableString::new("bloomSampler"))?,
+            bloom_intensity: program
+                .uniform_location(state, &ImmutableString::new("bloomIntensity"))?,
             color_map_sampler: program
                 .uniform_location(state, &ImmutableString::new("colorMapSampler"))?,
             use_color_grading: program
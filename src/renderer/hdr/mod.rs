@@ -251,6 +251,7 @@ impl HighDynamicRangeRenderer {
         state: &mut PipelineState,
         hdr_scene_frame: Rc<RefCell<GpuTexture>>,
         bloom_texture: Rc<RefCell<GpuTexture>>,
+        bloom_intensity: f32,
         ldr_framebuffer: &mut FrameBuffer,
         viewport: Rect<i32>,
         quad: &GeometryBuffer,
@@ -286,6 +287,7 @@ impl HighDynamicRangeRenderer {
                     .set_matrix4(&shader.wvp_matrix, &frame_matrix)
                     .set_texture(&shader.lum_sampler, &avg_lum)
                     .set_texture(&shader.bloom_sampler, &bloom_texture)
+                    .set_f32(&shader.bloom_intensity, bloom_intensity)
                     .set_texture(&shader.hdr_sampler, &hdr_scene_frame)
                     .set_bool(
                         &shader.use_color_grading,
@@ -320,6 +322,7 @@ impl HighDynamicRangeRenderer {
         state: &mut PipelineState,
         hdr_scene_frame: Rc<RefCell<GpuTexture>>,
         bloom_texture: Rc<RefCell<GpuTexture>>,
+        bloom_intensity: f32,
         ldr_framebuffer: &mut FrameBuffer,
         viewport: Rect<i32>,
         quad: &GeometryBuffer,
@@ -337,6 +340,7 @@ impl HighDynamicRangeRenderer {
             state,
             hdr_scene_frame,
             bloom_texture,
+            bloom_intensity,
             ldr_framebuffer,
             viewport,
             quad,
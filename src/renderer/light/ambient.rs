@@ -1,8 +1,11 @@
 use crate::core::sstorage::ImmutableString;
-use crate::renderer::framework::{
-    error::FrameworkError,
-    gpu_program::{GpuProgram, UniformLocation},
-    state::PipelineState,
+use crate::renderer::{
+    framework::{
+        error::FrameworkError,
+        gpu_program::{GpuProgram, UniformLocation},
+        state::PipelineState,
+    },
+    reflection_probe::MAX_REFLECTION_PROBES,
 };
 
 pub struct AmbientLightShader {
@@ -12,6 +15,24 @@ pub struct AmbientLightShader {
     pub ambient_color: UniformLocation,
     pub ao_sampler: UniformLocation,
     pub ambient_texture: UniformLocation,
+    pub depth_texture: UniformLocation,
+    pub normal_texture: UniformLocation,
+    pub inv_view_proj_matrix: UniformLocation,
+    pub probe_texture: UniformLocation,
+    pub probe_volume_count: UniformLocation,
+    pub probe_inv_world: UniformLocation,
+    pub probe_volume_info: UniformLocation,
+    pub material_texture: UniformLocation,
+    pub camera_position: UniformLocation,
+    pub reflection_probe_count: UniformLocation,
+    pub reflection_probe_textures: Vec<UniformLocation>,
+    pub reflection_probe_inv_world: UniformLocation,
+    pub reflection_probe_position: UniformLocation,
+    pub reflection_probe_size: UniformLocation,
+    pub reflection_probe_params: UniformLocation,
+    pub environment_map_enabled: UniformLocation,
+    pub environment_map: UniformLocation,
+    pub environment_map_max_lod: UniformLocation,
 }
 
 impl AmbientLightShader {
@@ -30,6 +51,48 @@ impl AmbientLightShader {
             ao_sampler: program.uniform_location(state, &ImmutableString::new("aoSampler"))?,
             ambient_texture: program
                 .uniform_location(state, &ImmutableString::new("ambientTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            probe_texture: program
+                .uniform_location(state, &ImmutableString::new("probeTexture"))?,
+            probe_volume_count: program
+                .uniform_location(state, &ImmutableString::new("probeVolumeCount"))?,
+            probe_inv_world: program
+                .uniform_location(state, &ImmutableString::new("probeInvWorld"))?,
+            probe_volume_info: program
+                .uniform_location(state, &ImmutableString::new("probeVolumeInfo"))?,
+            material_texture: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            reflection_probe_count: program
+                .uniform_location(state, &ImmutableString::new("reflectionProbeCount"))?,
+            reflection_probe_textures: (0..MAX_REFLECTION_PROBES)
+                .map(|i| {
+                    program.uniform_location(
+                        state,
+                        &ImmutableString::new(format!("reflectionProbeTextures[{}]", i)),
+                    )
+                })
+                .collect::<Result<Vec<_>, FrameworkError>>()?,
+            reflection_probe_inv_world: program
+                .uniform_location(state, &ImmutableString::new("reflectionProbeInvWorld"))?,
+            reflection_probe_position: program
+                .uniform_location(state, &ImmutableString::new("reflectionProbePosition"))?,
+            reflection_probe_size: program
+                .uniform_location(state, &ImmutableString::new("reflectionProbeSize"))?,
+            reflection_probe_params: program
+                .uniform_location(state, &ImmutableString::new("reflectionProbeParams"))?,
+            environment_map_enabled: program
+                .uniform_location(state, &ImmutableString::new("environmentMapEnabled"))?,
+            environment_map: program
+                .uniform_location(state, &ImmutableString::new("environmentMap"))?,
+            environment_map_max_lod: program
+                .uniform_location(state, &ImmutableString::new("environmentMapMaxLod"))?,
             program,
         })
     }
@@ -6,19 +6,21 @@ use crate::scene::light::point::PointLight;
 use crate::scene::light::spot::SpotLight;
 use crate::{
     core::{
-        algebra::{Matrix4, Point3, Vector3},
+        algebra::{Matrix4, Point3, Vector3, Vector4},
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        memory::with_frame_arena,
+        pool::Handle,
         scope_profile,
     },
     renderer::{
-        batch::BatchStorage,
+        batch::{BatchStorage, SurfaceInstanceFlags},
         cache::shader::ShaderCache,
         flat_shader::FlatShader,
         framework::{
             error::FrameworkError,
             framebuffer::{CullFace, DrawParameters, FrameBuffer},
-            gpu_texture::GpuTexture,
+            gpu_texture::{GpuTexture, GpuTextureKind},
             state::{
                 BlendFactor, BlendFunc, ColorMask, CompareFunc, PipelineState, StencilAction,
                 StencilFunc, StencilOp,
@@ -27,10 +29,13 @@ use crate::{
         gbuffer::GBuffer,
         light::{
             ambient::AmbientLightShader, directional::DirectionalLightShader,
-            point::PointLightShader, spot::SpotLightShader,
+            point::PointLightShader, probe::LightProbeStorage, spot::SpotLightShader,
         },
         light_volume::LightVolumeRenderer,
+        procedural_sky_shader::ProceduralSkyShader,
+        reflection_probe::{ActiveReflectionProbes, ReflectionProbeRenderer},
         shadow::{
+            atlas::{atlas_size, ShadowAtlas, ShadowMapAllocation},
             csm::CsmRenderer,
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
@@ -40,19 +45,24 @@ use crate::{
         GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
+        base::InstanceId,
         camera::Camera,
         mesh::{
             buffer::{TriangleBuffer, VertexBuffer},
             surface::SurfaceData,
             vertex::SimpleVertex,
         },
+        node::Node,
         Scene,
     },
+    utils::log::{Log, MessageKind},
 };
+use fxhash::{FxHashMap, FxHasher};
 use fyrox_core::algebra::Vector2;
 use std::{
     cell::RefCell,
     fmt::{Display, Formatter},
+    hash::Hasher,
     ops::AddAssign,
     rc::Rc,
 };
@@ -60,6 +70,7 @@ use std::{
 pub mod ambient;
 pub mod directional;
 pub mod point;
+pub mod probe;
 pub mod spot;
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -69,6 +80,7 @@ pub struct LightingStatistics {
     pub csm_rendered: usize,
     pub spot_lights_rendered: usize,
     pub spot_shadow_maps_rendered: usize,
+    pub shadow_maps_cached: usize,
     pub directional_lights_rendered: usize,
 }
 
@@ -78,6 +90,7 @@ impl AddAssign for LightingStatistics {
         self.point_shadow_maps_rendered += rhs.point_shadow_maps_rendered;
         self.spot_lights_rendered += rhs.spot_lights_rendered;
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
+        self.shadow_maps_cached += rhs.shadow_maps_cached;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
         self.csm_rendered += rhs.csm_rendered;
     }
@@ -93,13 +106,15 @@ impl Display for LightingStatistics {
             \tDirectional Lights: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
-            \tSpot Shadow Maps: {}\n",
+            \tSpot Shadow Maps: {}\n\
+            \tCached Shadow Maps: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
-            self.csm_rendered
+            self.csm_rendered,
+            self.shadow_maps_cached
         )
     }
 }
@@ -110,13 +125,17 @@ pub struct DeferredLightRenderer {
     point_light_shader: PointLightShader,
     directional_light_shader: DirectionalLightShader,
     ambient_light_shader: AmbientLightShader,
+    light_probes: LightProbeStorage,
+    active_reflection_probes: ActiveReflectionProbes,
     quad: GeometryBuffer,
     sphere: GeometryBuffer,
     skybox: GeometryBuffer,
     flat_shader: FlatShader,
     skybox_shader: SkyboxShader,
+    procedural_sky_shader: ProceduralSkyShader,
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
+    shadow_atlas: ShadowAtlas,
     csm_renderer: CsmRenderer,
     light_volume: LightVolumeRenderer,
 }
@@ -124,6 +143,7 @@ pub struct DeferredLightRenderer {
 pub(crate) struct DeferredRendererContext<'a> {
     pub state: &'a mut PipelineState,
     pub scene: &'a Scene,
+    pub scene_handle: Handle<Scene>,
     pub camera: &'a Camera,
     pub gbuffer: &'a mut GBuffer,
     pub ambient_color: Color,
@@ -136,6 +156,8 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
+    pub environment_dummy: Rc<RefCell<GpuTexture>>,
+    pub reflection_probes: &'a ReflectionProbeRenderer,
 }
 
 impl DeferredLightRenderer {
@@ -189,6 +211,8 @@ impl DeferredLightRenderer {
             point_light_shader: PointLightShader::new(state)?,
             directional_light_shader: DirectionalLightShader::new(state)?,
             ambient_light_shader: AmbientLightShader::new(state)?,
+            light_probes: Default::default(),
+            active_reflection_probes: Default::default(),
             quad: GeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 GeometryBufferKind::StaticDraw,
@@ -223,14 +247,13 @@ impl DeferredLightRenderer {
             ),
             flat_shader: FlatShader::new(state)?,
             skybox_shader: SkyboxShader::new(state)?,
-            spot_shadow_map_renderer: SpotShadowMapRenderer::new(
+            procedural_sky_shader: ProceduralSkyShader::new(state)?,
+            spot_shadow_map_renderer: Default::default(),
+            point_shadow_map_renderer: Default::default(),
+            shadow_atlas: ShadowAtlas::new(
                 state,
-                settings.spot_shadow_map_size,
+                settings.shadow_atlas_size,
                 quality_defaults.spot_shadow_map_precision,
-            )?,
-            point_shadow_map_renderer: PointShadowMapRenderer::new(
-                state,
-                settings.point_shadow_map_size,
                 quality_defaults.point_shadow_map_precision,
             )?,
             light_volume: LightVolumeRenderer::new(state)?,
@@ -247,21 +270,14 @@ impl DeferredLightRenderer {
         state: &mut PipelineState,
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
-        if settings.spot_shadow_map_size != self.spot_shadow_map_renderer.base_size()
-            || settings.spot_shadow_map_precision != self.spot_shadow_map_renderer.precision()
+        if atlas_size(settings.shadow_atlas_size) != self.shadow_atlas.size()
+            || settings.spot_shadow_map_precision != self.shadow_atlas.depth_precision()
+            || settings.point_shadow_map_precision != self.shadow_atlas.distance_precision()
         {
-            self.spot_shadow_map_renderer = SpotShadowMapRenderer::new(
+            self.shadow_atlas = ShadowAtlas::new(
                 state,
-                settings.spot_shadow_map_size,
+                settings.shadow_atlas_size,
                 settings.spot_shadow_map_precision,
-            )?;
-        }
-        if settings.point_shadow_map_size != self.point_shadow_map_renderer.base_size()
-            || settings.point_shadow_map_precision != self.point_shadow_map_renderer.precision()
-        {
-            self.point_shadow_map_renderer = PointShadowMapRenderer::new(
-                state,
-                settings.point_shadow_map_size,
                 settings.point_shadow_map_precision,
             )?;
         }
@@ -304,6 +320,7 @@ impl DeferredLightRenderer {
         let DeferredRendererContext {
             state,
             scene,
+            scene_handle,
             camera,
             gbuffer,
             shader_cache,
@@ -316,6 +333,8 @@ impl DeferredLightRenderer {
             batch_storage,
             frame_buffer,
             black_dummy,
+            environment_dummy,
+            reflection_probes,
         } = args;
 
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
@@ -350,12 +369,47 @@ impl DeferredLightRenderer {
             );
         }
 
-        // Render skybox (if any).
-        if let Some(skybox) = camera.skybox_ref() {
-            let size = camera.projection().z_far() / 2.0f32.sqrt();
-            let scale = Matrix4::new_scaling(size);
-            let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
+        // Render procedural sky or skybox (if any).
+        let sky_size = camera.projection().z_far() / 2.0f32.sqrt();
+        let sky_wvp = view_projection
+            * Matrix4::new_translation(&camera.global_position())
+            * Matrix4::new_scaling(sky_size);
+        let sky_draw_params = DrawParameters {
+            cull_face: None,
+            color_write: Default::default(),
+            depth_write: false,
+            stencil_test: None,
+            depth_test: false,
+            blend: None,
+            stencil_op: Default::default(),
+        };
 
+        if let Some(sky) = camera.procedural_sky_ref() {
+            let shader = &self.procedural_sky_shader;
+            pass_stats += frame_buffer
+                .draw_part(
+                    &self.skybox,
+                    state,
+                    viewport,
+                    &shader.program,
+                    sky_draw_params,
+                    0,
+                    12,
+                    |mut program_binding| {
+                        program_binding
+                            .set_matrix4(&shader.wvp_matrix, &sky_wvp)
+                            .set_vector3(&shader.sun_direction, &sky.sun_direction)
+                            .set_f32(&shader.sun_intensity, sky.sun_intensity)
+                            .set_f32(&shader.sun_angular_radius, sky.sun_angular_radius)
+                            .set_vector3(&shader.rayleigh_scattering, &sky.rayleigh_scattering)
+                            .set_f32(&shader.mie_scattering, sky.mie_scattering)
+                            .set_f32(&shader.mie_anisotropy, sky.mie_anisotropy)
+                            .set_f32(&shader.rayleigh_scale_height, sky.rayleigh_scale_height)
+                            .set_f32(&shader.mie_scale_height, sky.mie_scale_height);
+                    },
+                )
+                .unwrap();
+        } else if let Some(skybox) = camera.skybox_ref() {
             if let Some(gpu_texture) = skybox
                 .cubemap_ref()
                 .and_then(|cube_map| textures.get(state, cube_map))
@@ -367,21 +421,13 @@ impl DeferredLightRenderer {
                         state,
                         viewport,
                         &shader.program,
-                        DrawParameters {
-                            cull_face: None,
-                            color_write: Default::default(),
-                            depth_write: false,
-                            stencil_test: None,
-                            depth_test: false,
-                            blend: None,
-                            stencil_op: Default::default(),
-                        },
+                        sky_draw_params,
                         0,
                         12,
                         |mut program_binding| {
                             program_binding
                                 .set_texture(&shader.cubemap_texture, &gpu_texture)
-                                .set_matrix4(&shader.wvp_matrix, &(view_projection * wvp));
+                                .set_matrix4(&shader.wvp_matrix, &sky_wvp);
                         },
                     )
                     .unwrap();
@@ -396,6 +442,34 @@ impl DeferredLightRenderer {
         let gbuffer_ambient_map = gbuffer.ambient_texture();
         let ao_map = self.ssao_renderer.ao_map();
 
+        if let Err(e) = self.light_probes.update(state, scene, camera) {
+            Log::writeln(
+                MessageKind::Error,
+                format!("Failed to upload light probes. Reason: {:?}", e),
+            );
+        }
+        let light_probes = &self.light_probes;
+        let probe_texture = light_probes
+            .texture()
+            .cloned()
+            .unwrap_or_else(|| black_dummy.clone());
+
+        reflection_probes.select(
+            scene_handle,
+            scene,
+            camera,
+            &mut self.active_reflection_probes,
+        );
+        let active_reflection_probes = &self.active_reflection_probes;
+
+        let environment_map = camera
+            .environment_ref()
+            .and_then(|environment| textures.get(state, environment))
+            .filter(|texture| matches!(texture.borrow().kind(), GpuTextureKind::Cube { .. }));
+        let environment_map_max_lod = environment_map.as_ref().map_or(0.0, |texture| {
+            texture.borrow().mip_count().saturating_sub(1) as f32
+        });
+
         frame_buffer.draw(
             &self.quad,
             state,
@@ -432,10 +506,93 @@ impl DeferredLightRenderer {
                     .set_texture(
                         &self.ambient_light_shader.ambient_texture,
                         &gbuffer_ambient_map,
+                    )
+                    .set_texture(&self.ambient_light_shader.depth_texture, &gbuffer_depth_map)
+                    .set_texture(
+                        &self.ambient_light_shader.normal_texture,
+                        &gbuffer_normal_map,
+                    )
+                    .set_matrix4(
+                        &self.ambient_light_shader.inv_view_proj_matrix,
+                        &inv_view_projection,
+                    )
+                    .set_texture(&self.ambient_light_shader.probe_texture, &probe_texture)
+                    .set_i32(
+                        &self.ambient_light_shader.probe_volume_count,
+                        light_probes.volume_count() as i32,
+                    )
+                    .set_matrix4_array(
+                        &self.ambient_light_shader.probe_inv_world,
+                        light_probes.inv_world_matrices(),
+                    )
+                    .set_i32_slice(
+                        &self.ambient_light_shader.probe_volume_info,
+                        light_probes.volume_info(),
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.material_texture,
+                        &gbuffer_material_map,
+                    )
+                    .set_vector3(
+                        &self.ambient_light_shader.camera_position,
+                        &camera_global_position,
+                    )
+                    .set_i32(
+                        &self.ambient_light_shader.reflection_probe_count,
+                        active_reflection_probes.len() as i32,
+                    )
+                    .set_matrix4_array(
+                        &self.ambient_light_shader.reflection_probe_inv_world,
+                        &active_reflection_probes.inv_world_matrices,
+                    )
+                    .set_vector3_slice(
+                        &self.ambient_light_shader.reflection_probe_position,
+                        &active_reflection_probes.positions,
+                    )
+                    .set_vector3_slice(
+                        &self.ambient_light_shader.reflection_probe_size,
+                        &active_reflection_probes.sizes,
+                    )
+                    .set_vector3_slice(
+                        &self.ambient_light_shader.reflection_probe_params,
+                        &active_reflection_probes.params,
+                    )
+                    .set_bool(
+                        &self.ambient_light_shader.environment_map_enabled,
+                        environment_map.is_some(),
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.environment_map,
+                        environment_map.as_ref().unwrap_or(&environment_dummy),
+                    )
+                    .set_f32(
+                        &self.ambient_light_shader.environment_map_max_lod,
+                        environment_map_max_lod,
                     );
+
+                // Every sampler must be bound to a cube map, even if it is not used.
+                for (i, location) in self
+                    .ambient_light_shader
+                    .reflection_probe_textures
+                    .iter()
+                    .enumerate()
+                {
+                    program_binding.set_texture(
+                        location,
+                        active_reflection_probes
+                            .cube_maps
+                            .get(i)
+                            .unwrap_or(&environment_dummy),
+                    );
+                }
             },
         );
 
+        // Allocate shadow maps in the atlas before rendering any light.
+        let shadow_allocations =
+            self.allocate_shadow_maps(scene, camera, &frustum, settings, batch_storage);
+        let shadow_atlas_size = self.shadow_atlas.size();
+
         for (light_handle, light) in scene.graph.pair_iter() {
             if !light.global_visibility() {
                 continue;
@@ -443,38 +600,14 @@ impl DeferredLightRenderer {
 
             let distance_to_camera = (light.global_position() - camera.global_position()).norm();
 
-            let (raw_radius, shadows_distance, shadows_enabled) = if let Some(spot_light) =
-                light.cast::<SpotLight>()
-            {
-                (
-                    spot_light.distance(),
-                    settings.spot_shadows_distance,
-                    spot_light.base_light_ref().is_cast_shadows()
-                        && distance_to_camera <= settings.spot_shadows_distance
-                        && settings.spot_shadows_enabled,
-                )
-            } else if let Some(point_light) = light.cast::<PointLight>() {
-                (
-                    point_light.radius(),
-                    settings.point_shadows_distance,
-                    point_light.base_light_ref().is_cast_shadows()
-                        && distance_to_camera <= settings.point_shadows_distance
-                        && settings.point_shadows_enabled,
-                )
-            } else if let Some(directional) = light.cast::<DirectionalLight>() {
-                (
-                    f32::MAX,
-                    0.0,
-                    directional.base_light_ref().is_cast_shadows() && settings.csm_settings.enabled,
-                )
-            } else {
-                continue;
-            };
+            let (raw_radius, mut shadows_enabled) =
+                match light_shadow_settings(light, distance_to_camera, settings) {
+                    Some(light_settings) => light_settings,
+                    None => continue,
+                };
 
             let light_position = light.global_position();
-            let scl = light.local_transform().scale();
-            let light_radius_scale = scl.x.max(scl.y).max(scl.z);
-            let light_radius = light_radius_scale * raw_radius;
+            let light_radius = scaled_light_radius(light, raw_radius);
             let light_r_inflate = 1.05 * light_radius;
             let light_radius_vec = Vector3::new(light_r_inflate, light_r_inflate, light_r_inflate);
             let emit_direction = light
@@ -486,16 +619,7 @@ impl DeferredLightRenderer {
                 continue;
             }
 
-            let b1 = shadows_distance * 0.2;
-            let b2 = shadows_distance * 0.4;
-            let cascade_index =
-                if distance_to_camera < b1 || frustum.is_contains_point(camera.global_position()) {
-                    0
-                } else if distance_to_camera > b1 && distance_to_camera < b2 {
-                    1
-                } else {
-                    2
-                };
+            let shadow_allocation = shadow_allocations.get(&light.instance_id());
 
             let mut light_view_projection = Matrix4::identity();
 
@@ -519,38 +643,52 @@ impl DeferredLightRenderer {
 
                     light_view_projection = light_projection_matrix * light_view_matrix;
 
-                    pass_stats += self.spot_shadow_map_renderer.render(
-                        state,
-                        &light_view_projection,
-                        batch_storage,
-                        geometry_cache,
-                        cascade_index,
-                        shader_cache,
-                        textures,
-                        normal_dummy.clone(),
-                        white_dummy.clone(),
-                        black_dummy.clone(),
-                    );
-
-                    light_stats.spot_shadow_maps_rendered += 1;
-                } else if light.cast::<PointLight>().is_some() {
-                    pass_stats +=
-                        self.point_shadow_map_renderer
-                            .render(PointShadowMapRenderContext {
+                    match shadow_allocation {
+                        Some(allocation) if allocation.needs_render => {
+                            pass_stats += self.spot_shadow_map_renderer.render(
                                 state,
-                                light_pos: light_position,
-                                light_radius,
-                                geom_cache: geometry_cache,
-                                cascade: cascade_index,
+                                self.shadow_atlas.framebuffer_mut(),
+                                allocation.regions[0],
+                                &light_view_projection,
                                 batch_storage,
+                                geometry_cache,
                                 shader_cache,
-                                texture_cache: textures,
-                                normal_dummy: normal_dummy.clone(),
-                                white_dummy: white_dummy.clone(),
-                                black_dummy: black_dummy.clone(),
-                            });
+                                textures,
+                                normal_dummy.clone(),
+                                white_dummy.clone(),
+                                black_dummy.clone(),
+                            );
 
-                    light_stats.point_shadow_maps_rendered += 1;
+                            light_stats.spot_shadow_maps_rendered += 1;
+                        }
+                        Some(_) => light_stats.shadow_maps_cached += 1,
+                        None => shadows_enabled = false,
+                    }
+                } else if light.cast::<PointLight>().is_some() {
+                    match shadow_allocation {
+                        Some(allocation) if allocation.needs_render => {
+                            pass_stats += self.point_shadow_map_renderer.render(
+                                PointShadowMapRenderContext {
+                                    state,
+                                    light_pos: light_position,
+                                    light_radius,
+                                    geom_cache: geometry_cache,
+                                    framebuffer: self.shadow_atlas.framebuffer_mut(),
+                                    regions: &allocation.regions,
+                                    batch_storage,
+                                    shader_cache,
+                                    texture_cache: textures,
+                                    normal_dummy: normal_dummy.clone(),
+                                    white_dummy: white_dummy.clone(),
+                                    black_dummy: black_dummy.clone(),
+                                },
+                            );
+
+                            light_stats.point_shadow_maps_rendered += 1;
+                        }
+                        Some(_) => light_stats.shadow_maps_cached += 1,
+                        None => shadows_enabled = false,
+                    }
                 } else if let Some(directional) = light.cast::<DirectionalLight>() {
                     pass_stats += self.csm_renderer.render(CsmRenderContext {
                         frame_size: Vector2::new(gbuffer.width as f32, gbuffer.height as f32),
@@ -659,6 +797,19 @@ impl DeferredLightRenderer {
             pass_stats += if let Some(spot_light) = light.cast::<SpotLight>() {
                 let shader = &self.spot_light_shader;
 
+                let atlas_region = shadow_allocation
+                    .map(|allocation| allocation.regions[0])
+                    .filter(|_| shadows_enabled);
+                let (shadow_map_inv_size, shadow_atlas_region) = atlas_region
+                    .map(|region| {
+                        (
+                            1.0 / region.size as f32,
+                            region.normalized(shadow_atlas_size),
+                        )
+                    })
+                    .unwrap_or((0.0, Vector4::zeros()));
+                let shadow_atlas_texture = self.shadow_atlas.depth_texture();
+
                 let (cookie_enabled, cookie_texture) =
                     if let Some(texture) = spot_light.cookie_texture_ref() {
                         if let Some(cookie) = textures.get(state, texture) {
@@ -700,20 +851,14 @@ impl DeferredLightRenderer {
                                 (spot_light.full_cone_angle() * 0.5).cos(),
                             )
                             .set_matrix4(&shader.wvp_matrix, &frame_matrix)
-                            .set_f32(
-                                &shader.shadow_map_inv_size,
-                                1.0 / (self.spot_shadow_map_renderer.cascade_size(cascade_index)
-                                    as f32),
-                            )
+                            .set_f32(&shader.shadow_map_inv_size, shadow_map_inv_size)
+                            .set_vector4(&shader.shadow_atlas_region, &shadow_atlas_region)
                             .set_vector3(&shader.camera_position, &camera_global_position)
                             .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
                             .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
                             .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
                             .set_texture(&shader.material_sampler, &gbuffer_material_map)
-                            .set_texture(
-                                &shader.spot_shadow_texture,
-                                &self.spot_shadow_map_renderer.cascade_texture(cascade_index),
-                            )
+                            .set_texture(&shader.spot_shadow_texture, &shadow_atlas_texture)
                             .set_texture(&shader.cookie_texture, &cookie_texture)
                             .set_bool(&shader.cookie_enabled, cookie_enabled)
                             .set_f32(&shader.shadow_bias, spot_light.shadow_bias())
@@ -726,6 +871,14 @@ impl DeferredLightRenderer {
             } else if let Some(point_light) = light.cast::<PointLight>() {
                 let shader = &self.point_light_shader;
 
+                let mut shadow_atlas_faces = [Vector4::zeros(); 6];
+                if let Some(allocation) = shadow_allocation.filter(|_| shadows_enabled) {
+                    for (face, region) in shadow_atlas_faces.iter_mut().zip(&allocation.regions) {
+                        *face = region.normalized(shadow_atlas_size);
+                    }
+                }
+                let shadow_atlas_texture = self.shadow_atlas.distance_texture();
+
                 light_stats.point_lights_rendered += 1;
 
                 frame_buffer.draw(
@@ -756,12 +909,8 @@ impl DeferredLightRenderer {
                             .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
                             .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
                             .set_texture(&shader.material_sampler, &gbuffer_material_map)
-                            .set_texture(
-                                &shader.point_shadow_texture,
-                                &self
-                                    .point_shadow_map_renderer
-                                    .cascade_texture(cascade_index),
-                            );
+                            .set_texture(&shader.point_shadow_texture, &shadow_atlas_texture)
+                            .set_vector4_slice(&shader.shadow_atlas_faces, &shadow_atlas_faces);
                     },
                 )
             } else if let Some(directional) = light.cast::<DirectionalLight>() {
@@ -860,4 +1009,170 @@ impl DeferredLightRenderer {
 
         (pass_stats, light_stats)
     }
+
+    // Allocates shadow maps of visible point and spot lights in the shadow atlas. Lights are
+    // sorted by their on-screen importance, so the most important lights get the largest shadow
+    // maps. Requests are transient, so they're stored in the frame arena.
+    fn allocate_shadow_maps(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        frustum: &Frustum,
+        settings: &QualitySettings,
+        batch_storage: &BatchStorage,
+    ) -> FxHashMap<InstanceId, ShadowMapAllocation> {
+        #[derive(Copy, Clone)]
+        struct Request {
+            light: InstanceId,
+            count: usize,
+            size: usize,
+            importance: f32,
+            content_hash: u64,
+        }
+
+        with_frame_arena(|arena| {
+            let mut requests = arena.vec();
+            for light in scene.graph.linear_iter() {
+                if !light.global_visibility() {
+                    continue;
+                }
+
+                let (count, max_size) = if light.cast::<SpotLight>().is_some() {
+                    (1, settings.spot_shadow_map_size)
+                } else if light.cast::<PointLight>().is_some() {
+                    (6, settings.point_shadow_map_size)
+                } else {
+                    continue;
+                };
+
+                let distance_to_camera =
+                    (light.global_position() - camera.global_position()).norm();
+
+                if let Some((raw_radius, true)) =
+                    light_shadow_settings(light, distance_to_camera, settings)
+                {
+                    let light_radius = scaled_light_radius(light, raw_radius);
+
+                    if !frustum.is_intersects_sphere(light.global_position(), light_radius) {
+                        continue;
+                    }
+
+                    // Rough estimation of on-screen size of the light.
+                    let importance = (light_radius / distance_to_camera.max(f32::EPSILON)).min(1.0);
+
+                    requests.push(Request {
+                        light: light.instance_id(),
+                        count,
+                        size: (max_size as f32 * importance) as usize,
+                        importance,
+                        content_hash: shadow_content_hash(light, light_radius, batch_storage),
+                    });
+                }
+            }
+
+            requests.sort_by(|a, b| {
+                b.importance
+                    .partial_cmp(&a.importance)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+            let layout = self.shadow_atlas.layout_mut();
+            layout.begin_frame();
+            requests
+                .iter()
+                .filter_map(|request| {
+                    layout
+                        .request(
+                            request.light,
+                            request.count,
+                            request.size,
+                            request.content_hash,
+                        )
+                        .map(|allocation| (request.light, allocation))
+                })
+                .collect()
+        })
+    }
+}
+
+// Returns light radius (before scaling) and whether the light should cast shadows or not.
+fn light_shadow_settings(
+    light: &Node,
+    distance_to_camera: f32,
+    settings: &QualitySettings,
+) -> Option<(f32, bool)> {
+    if let Some(spot_light) = light.cast::<SpotLight>() {
+        Some((
+            spot_light.distance(),
+            spot_light.base_light_ref().is_cast_shadows()
+                && distance_to_camera <= settings.spot_shadows_distance
+                && settings.spot_shadows_enabled,
+        ))
+    } else if let Some(point_light) = light.cast::<PointLight>() {
+        Some((
+            point_light.radius(),
+            point_light.base_light_ref().is_cast_shadows()
+                && distance_to_camera <= settings.point_shadows_distance
+                && settings.point_shadows_enabled,
+        ))
+    } else {
+        light.cast::<DirectionalLight>().map(|directional| {
+            (
+                f32::MAX,
+                directional.base_light_ref().is_cast_shadows() && settings.csm_settings.enabled,
+            )
+        })
+    }
+}
+
+fn scaled_light_radius(light: &Node, raw_radius: f32) -> f32 {
+    let scl = light.local_transform().scale();
+    let light_radius_scale = scl.x.max(scl.y).max(scl.z);
+    light_radius_scale * raw_radius
+}
+
+// Computes a hash of everything that affects a shadow map of a light: light transform, its
+// parameters and shadow casters (including their geometry) within the light radius. Cached shadow map is re-rendered only
+// if the hash has changed.
+fn shadow_content_hash(light: &Node, light_radius: f32, batch_storage: &BatchStorage) -> u64 {
+    fn hash_matrix(hasher: &mut FxHasher, matrix: &Matrix4<f32>) {
+        for value in matrix.iter() {
+            hasher.write_u32(value.to_bits());
+        }
+    }
+
+    let mut hasher = FxHasher::default();
+
+    hash_matrix(&mut hasher, &light.global_transform());
+    hasher.write_u32(light_radius.to_bits());
+    if let Some(spot_light) = light.cast::<SpotLight>() {
+        hasher.write_u32(spot_light.full_cone_angle().to_bits());
+    }
+
+    let light_position = light.global_position();
+    for batch in batch_storage.batches.iter() {
+        // Surface data could be modified in-place (procedural or dynamic meshes), so its content
+        // must be taken into account as well.
+        let mut content_hash = None;
+        for instance in batch.instances.iter() {
+            if instance
+                .flags
+                .contains(SurfaceInstanceFlags::IS_VISIBLE | SurfaceInstanceFlags::CAST_SHADOWS)
+                && instance
+                    .world_aabb
+                    .is_intersects_sphere(light_position, light_radius)
+            {
+                hasher.write_u64(batch.data.key());
+                hasher.write_u64(
+                    *content_hash.get_or_insert_with(|| batch.data.lock().content_hash()),
+                );
+                hash_matrix(&mut hasher, &instance.world_transform);
+                for bone_matrix in instance.bone_matrices.iter() {
+                    hash_matrix(&mut hasher, bone_matrix);
+                }
+            }
+        }
+    }
+
+    hasher.finish()
 }
@@ -13,6 +13,7 @@ pub struct PointLightShader {
     pub normal_sampler: UniformLocation,
     pub material_sampler: UniformLocation,
     pub point_shadow_texture: UniformLocation,
+    pub shadow_atlas_faces: UniformLocation,
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
     pub light_position: UniformLocation,
@@ -43,6 +44,8 @@ impl PointLightShader {
                 .uniform_location(state, &ImmutableString::new("materialTexture"))?,
             point_shadow_texture: program
                 .uniform_location(state, &ImmutableString::new("pointShadowTexture"))?,
+            shadow_atlas_faces: program
+                .uniform_location(state, &ImmutableString::new("shadowAtlasFaces"))?,
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
@@ -0,0 +1,141 @@
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3, Vector4},
+        scope_profile,
+    },
+    renderer::framework::{
+        error::FrameworkError,
+        gpu_texture::{
+            GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
+        },
+        state::PipelineState,
+    },
+    scene::{
+        camera::Camera,
+        light::probe::{LightProbeVolume, SphericalHarmonics, SH_COEFFICIENT_COUNT},
+        Scene,
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Maximum amount of light probe volumes that could be used at the same time. Keep in sync with
+/// the ambient light shader.
+pub const MAX_PROBE_VOLUMES: usize = 4;
+
+/// Baked light probes of the volumes closest to the camera, packed for the ambient light shader.
+/// Each row of the texture is a single probe, each texel of a row is an SH coefficient
+/// pre-multiplied by its diffuse convolution factor.
+#[derive(Default)]
+pub struct LightProbeStorage {
+    texture: Option<Rc<RefCell<GpuTexture>>>,
+    data: Vec<Vector4<f32>>,
+    inv_world_matrices: Vec<Matrix4<f32>>,
+    volume_info: Vec<i32>,
+}
+
+impl LightProbeStorage {
+    pub fn update(
+        &mut self,
+        state: &mut PipelineState,
+        scene: &Scene,
+        camera: &Camera,
+    ) -> Result<(), FrameworkError> {
+        scope_profile!();
+
+        self.data.clear();
+        self.inv_world_matrices.clear();
+        self.volume_info.clear();
+
+        let camera_position = camera.global_position();
+        let mut volumes = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<LightProbeVolume>())
+            .filter(|volume| volume.global_visibility() && volume.is_baked())
+            .map(|volume| {
+                let distance = volume
+                    .world_bounding_box()
+                    .center()
+                    .metric_distance(&camera_position);
+                (distance, volume)
+            })
+            .collect::<Vec<_>>();
+        volumes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let factors = SphericalHarmonics::diffuse_convolution_factors();
+
+        for (_, volume) in volumes.into_iter().take(MAX_PROBE_VOLUMES) {
+            let resolution = volume.resolution();
+            self.volume_info.extend_from_slice(&[
+                resolution.x as i32,
+                resolution.y as i32,
+                resolution.z as i32,
+                (self.data.len() / SH_COEFFICIENT_COUNT) as i32,
+            ]);
+            self.inv_world_matrices.push(
+                volume
+                    .global_transform()
+                    .try_inverse()
+                    .unwrap_or_else(Matrix4::identity),
+            );
+            for probe in volume.probes() {
+                for (coefficient, factor) in probe.coefficients.iter().zip(factors.iter()) {
+                    let c: Vector3<f32> = coefficient.scale(*factor);
+                    self.data.push(Vector4::new(c.x, c.y, c.z, 0.0));
+                }
+            }
+        }
+
+        let rows = self.data.len() / SH_COEFFICIENT_COUNT;
+        if rows == 0 {
+            return Ok(());
+        }
+
+        let kind = GpuTextureKind::Rectangle {
+            width: SH_COEFFICIENT_COUNT,
+            height: rows,
+        };
+        let bytes = crate::utils::array_as_u8_slice(&self.data);
+
+        match self.texture {
+            Some(ref texture) => {
+                texture.borrow_mut().bind_mut(state, 0).set_data(
+                    kind,
+                    PixelKind::RGBA32F,
+                    1,
+                    Some(bytes),
+                )?;
+            }
+            None => {
+                self.texture = Some(Rc::new(RefCell::new(GpuTexture::new(
+                    state,
+                    kind,
+                    PixelKind::RGBA32F,
+                    MinificationFilter::Nearest,
+                    MagnificationFilter::Nearest,
+                    1,
+                    Some(bytes),
+                )?)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns amount of volumes, that should be used by the shader.
+    pub fn volume_count(&self) -> usize {
+        self.inv_world_matrices.len()
+    }
+
+    pub fn texture(&self) -> Option<&Rc<RefCell<GpuTexture>>> {
+        self.texture.as_ref()
+    }
+
+    pub fn inv_world_matrices(&self) -> &[Matrix4<f32>] {
+        &self.inv_world_matrices
+    }
+
+    pub fn volume_info(&self) -> &[i32] {
+        &self.volume_info
+    }
+}
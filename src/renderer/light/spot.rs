@@ -19,6 +19,7 @@ pub struct SpotLightShader {
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
     pub shadow_map_inv_size: UniformLocation,
+    pub shadow_atlas_region: UniformLocation,
     pub light_position: UniformLocation,
     pub light_radius: UniformLocation,
     pub light_color: UniformLocation,
@@ -61,6 +62,8 @@ impl SpotLightShader {
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
             shadow_map_inv_size: program
                 .uniform_location(state, &ImmutableString::new("shadowMapInvSize"))?,
+            shadow_atlas_region: program
+                .uniform_location(state, &ImmutableString::new("shadowAtlasRegion"))?,
             light_position: program.uniform_location(state, &ImmutableString::new("lightPos"))?,
             light_radius: program.uniform_location(state, &ImmutableString::new("lightRadius"))?,
             light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
@@ -20,6 +20,7 @@ pub mod renderer2d;
 pub mod ui_renderer;
 
 mod bloom;
+mod debug_view;
 mod flat_shader;
 mod forward_renderer;
 mod fxaa;
@@ -28,10 +29,15 @@ mod hdr;
 mod light;
 mod light_volume;
 mod particle_system_renderer;
+mod post_processing;
+mod procedural_sky_shader;
+mod reflection_probe;
 mod shadow;
 mod skybox_shader;
 mod sprite_renderer;
 mod ssao;
+mod text_renderer;
+mod velocity;
 
 use crate::{
     core::{
@@ -54,6 +60,7 @@ use crate::{
         bloom::BloomRenderer,
         cache::{geometry::GeometryCache, shader::ShaderCache, texture::TextureCache, CacheEntry},
         debug_renderer::DebugRenderer,
+        debug_view::{DebugViewRenderContext, DebugViewRenderer},
         flat_shader::FlatShader,
         forward_renderer::{ForwardRenderContext, ForwardRenderer},
         framework::{
@@ -72,15 +79,22 @@ use crate::{
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext, LightingStatistics},
         particle_system_renderer::{ParticleSystemRenderContext, ParticleSystemRenderer},
+        post_processing::{PostProcessingContext, PostProcessingRenderer},
+        reflection_probe::{make_capture_camera, ReflectionProbeRenderer},
         renderer2d::Renderer2d,
         sprite_renderer::{SpriteRenderContext, SpriteRenderer},
+        text_renderer::{TextRenderContext, TextRenderer},
         ui_renderer::{UiRenderContext, UiRenderer},
+        velocity::{VelocityRenderContext, VelocityRenderer},
     },
     resource::texture::{Texture, TextureKind},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    scene::{
+        camera::Camera, light::reflection_probe::ReflectionProbe, mesh::surface::SurfaceData,
+        node::Node, Scene, SceneContainer,
+    },
     utils::log::{Log, MessageKind},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use glow::HasContext;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -102,6 +116,8 @@ pub struct Statistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many resources are uploaded to GPU and how much memory they're using.
+    pub memory: MemoryStatistics,
     /// Real time consumed to render frame. Time given in **seconds**.
     pub pure_frame_time: f32,
     /// Total time renderer took to process single frame, usually includes
@@ -124,13 +140,58 @@ impl Display for Statistics {
             Capped Frame Time: {:.2} ms\n\
             {}\n\
             {}\n\
+            {}\n\
             {}\n",
             self.frames_per_second,
             self.pure_frame_time * 1000.0,
             self.capped_frame_time * 1000.0,
             self.geometry,
             self.lighting,
-            self.pipeline
+            self.pipeline,
+            self.memory
+        )
+    }
+}
+
+/// Estimated GPU memory usage of resources cached by the renderer.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MemoryStatistics {
+    /// Amount of textures uploaded to GPU.
+    pub texture_count: usize,
+    /// Estimated amount of memory (in bytes) occupied by the textures. It does not include
+    /// render targets of the renderer.
+    pub texture_memory: usize,
+    /// Amount of geometry buffers uploaded to GPU.
+    pub geometry_buffer_count: usize,
+    /// Estimated amount of memory (in bytes) occupied by the geometry buffers.
+    pub geometry_memory: usize,
+    /// Video memory budget (in bytes), if any. See [`Renderer::set_memory_budget`].
+    pub budget: Option<usize>,
+    /// Total amount of textures evicted from GPU memory to fit into the budget.
+    pub evicted_textures: usize,
+    /// Amount of textures, that are uploaded with reduced amount of mip levels to fit into the
+    /// budget.
+    pub streamed_textures: usize,
+}
+
+impl Display for MemoryStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Textures: {} ({:.2} Mb)\n\
+            Geometry Buffers: {} ({:.2} Mb)\n\
+            Budget: {}\n\
+            Evicted Textures: {}\n\
+            Streamed Textures: {}",
+            self.texture_count,
+            self.texture_memory as f32 / (1024.0 * 1024.0),
+            self.geometry_buffer_count,
+            self.geometry_memory as f32 / (1024.0 * 1024.0),
+            self.budget
+                .map(|b| format!("{:.2} Mb", b as f32 / (1024.0 * 1024.0)))
+                .unwrap_or_else(|| "Unlimited".to_string()),
+            self.evicted_textures,
+            self.streamed_textures
         )
     }
 }
@@ -201,6 +262,59 @@ pub enum ShadowMapPrecision {
     Full,
 }
 
+/// Debug visualization mode of the renderer. Every mode except [`DebugViewMode::None`] and
+/// [`DebugViewMode::ShadowCascades`] replaces final frame of every camera, post effects are not
+/// applied in this case.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Debug,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum DebugViewMode {
+    /// Normal rendering.
+    None,
+    /// Edges of every mesh, including hidden ones.
+    Wireframe,
+    /// Unlit diffuse color from G-Buffer.
+    Albedo,
+    /// World-space normals from G-Buffer, mapped to `[0; 1]` range.
+    Normals,
+    /// Metallic (red channel) and roughness (green channel) from G-Buffer.
+    RoughnessMetallic,
+    /// Heat map of how many times each pixel was drawn, from blue (once) to red (8 times or more).
+    Overdraw,
+    /// Heat map of how many lights affect each pixel, from blue (one light) to red (8 lights or
+    /// more).
+    LightCount,
+    /// Normal rendering with pixels tinted by shadow cascade of the first directional light, that
+    /// casts shadows: red - first cascade, green - second, blue - third.
+    ShadowCascades,
+}
+
+impl Default for DebugViewMode {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl DebugViewMode {
+    /// Returns `true` if the mode replaces final frame.
+    pub fn replaces_frame(self) -> bool {
+        !matches!(self, Self::None | Self::ShadowCascades)
+    }
+}
+
 /// Cascaded-shadow maps settings.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect, Eq)]
 pub struct CsmSettings {
@@ -234,7 +348,7 @@ impl Default for CsmSettings {
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct QualitySettings {
     /// Point shadows
-    /// Size of cube map face of shadow map texture in pixels.
+    /// Maximum size of cube map face of shadow map in the shadow atlas in pixels.
     pub point_shadow_map_size: usize,
     /// Use or not percentage close filtering (smoothing) for point shadows.
     pub point_soft_shadows: bool,
@@ -247,7 +361,7 @@ pub struct QualitySettings {
     pub point_shadow_map_precision: ShadowMapPrecision,
 
     /// Spot shadows
-    /// Size of square shadow map texture in pixels
+    /// Maximum size of square shadow map in the shadow atlas in pixels.
     pub spot_shadow_map_size: usize,
     /// Use or not percentage close filtering (smoothing) for spot shadows.
     pub spot_soft_shadows: bool,
@@ -259,6 +373,14 @@ pub struct QualitySettings {
     /// quality and performance.
     pub spot_shadow_map_precision: ShadowMapPrecision,
 
+    /// Size of the shadow atlas texture in pixels. The atlas contains shadow maps of every point
+    /// and spot light, size of each shadow map depends on on-screen importance of its light. The
+    /// size will be rounded down to the nearest power of two. It should be at least four times
+    /// bigger than [`Self::point_shadow_map_size`], otherwise six faces of a point light shadow
+    /// map won't fit in the atlas at full size.
+    #[serde(default = "default_shadow_atlas_size")]
+    pub shadow_atlas_size: usize,
+
     /// Cascaded-shadow maps settings.
     pub csm_settings: CsmSettings,
 
@@ -280,6 +402,20 @@ pub struct QualitySettings {
 
     /// Whether to use bloom effect.
     pub use_bloom: bool,
+
+    /// Whether to render velocity buffer for every camera, even if it is not needed for motion
+    /// blur. Enable it, if your custom render passes use
+    /// [`SceneRenderPassContext::velocity_texture`].
+    #[serde(default)]
+    pub use_velocity_buffer: bool,
+
+    /// Debug visualization mode, see [`DebugViewMode`] docs for more info.
+    #[serde(default)]
+    pub debug_view_mode: DebugViewMode,
+}
+
+fn default_shadow_atlas_size() -> usize {
+    QualitySettings::default().shadow_atlas_size
 }
 
 impl Default for QualitySettings {
@@ -316,6 +452,12 @@ impl QualitySettings {
 
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
+            use_velocity_buffer: false,
+
+            debug_view_mode: DebugViewMode::None,
+
+            shadow_atlas_size: 8192,
+
             csm_settings: Default::default(),
         }
     }
@@ -347,6 +489,12 @@ impl QualitySettings {
 
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
+            use_velocity_buffer: false,
+
+            debug_view_mode: DebugViewMode::None,
+
+            shadow_atlas_size: 4096,
+
             csm_settings: CsmSettings {
                 enabled: true,
                 size: 2048,
@@ -383,6 +531,12 @@ impl QualitySettings {
 
             use_parallax_mapping: false,
 
+            use_velocity_buffer: false,
+
+            debug_view_mode: DebugViewMode::None,
+
+            shadow_atlas_size: 2048,
+
             csm_settings: CsmSettings {
                 enabled: true,
                 size: 512,
@@ -419,6 +573,12 @@ impl QualitySettings {
 
             use_parallax_mapping: false,
 
+            use_velocity_buffer: false,
+
+            debug_view_mode: DebugViewMode::None,
+
+            shadow_atlas_size: 128,
+
             csm_settings: CsmSettings {
                 enabled: true,
                 size: 512,
@@ -471,6 +631,7 @@ impl Default for Statistics {
             pipeline: Default::default(),
             lighting: Default::default(),
             geometry: Default::default(),
+            memory: Default::default(),
             pure_frame_time: 0.0,
             capped_frame_time: 0.0,
             frames_per_second: 0,
@@ -495,6 +656,10 @@ pub struct AssociatedSceneData {
     /// Additional frame buffer for post processing.
     pub ldr_temp_framebuffer: FrameBuffer,
 
+    /// Screen-space motion of every pixel since the previous frame. It has its own depth buffer,
+    /// because G-Buffer depth must stay intact.
+    pub velocity_framebuffer: FrameBuffer,
+
     /// HDR renderer has be created per scene, because it contains
     /// scene luminance.
     pub hdr_renderer: HighDynamicRangeRenderer,
@@ -596,6 +761,47 @@ impl AssociatedSceneData {
             }],
         )?;
 
+        let mut velocity_depth_stencil = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            PixelKind::D24S8,
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            None,
+        )?;
+        velocity_depth_stencil
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+        let mut velocity_texture = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            // Only first two channels are used.
+            PixelKind::RGBA16F,
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            None,
+        )?;
+        velocity_texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+        let velocity_framebuffer = FrameBuffer::new(
+            state,
+            Some(Attachment {
+                kind: AttachmentKind::DepthStencil,
+                texture: Rc::new(RefCell::new(velocity_depth_stencil)),
+            }),
+            vec![Attachment {
+                kind: AttachmentKind::Color,
+                texture: Rc::new(RefCell::new(velocity_texture)),
+            }],
+        )?;
+
         Ok(Self {
             gbuffer: GBuffer::new(state, width, height)?,
             hdr_renderer: HighDynamicRangeRenderer::new(state)?,
@@ -603,6 +809,7 @@ impl AssociatedSceneData {
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
+            velocity_framebuffer,
         })
     }
 
@@ -644,6 +851,14 @@ impl AssociatedSceneData {
             .texture
             .clone()
     }
+
+    /// Returns velocity buffer texture. See [`SceneRenderPassContext::velocity_texture`] docs for
+    /// more info.
+    pub fn velocity_texture(&self) -> Rc<RefCell<GpuTexture>> {
+        self.velocity_framebuffer.color_attachments()[0]
+            .texture
+            .clone()
+    }
 }
 
 pub(crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
@@ -668,6 +883,7 @@ pub struct Renderer {
     deferred_light_renderer: DeferredLightRenderer,
     flat_shader: FlatShader,
     sprite_renderer: SpriteRenderer,
+    text_renderer: TextRenderer,
     particle_system_renderer: ParticleSystemRenderer,
     /// Dummy white one pixel texture which will be used as stub when rendering
     /// something without texture specified.
@@ -690,15 +906,23 @@ pub struct Renderer {
     pub debug_renderer: DebugRenderer,
     /// A set of associated data for each scene that was rendered.
     pub scene_data_map: FxHashMap<Handle<Scene>, AssociatedSceneData>,
+    // TextureId -> AssociatedSceneData mapping. This mapping is used for cameras that render
+    // into textures.
+    camera_data_map: FxHashMap<usize, AssociatedSceneData>,
     backbuffer_clear_color: Color,
     /// Texture cache with GPU textures.
     pub texture_cache: TextureCache,
     shader_cache: ShaderCache,
     geometry_cache: GeometryCache,
+    memory_budget: Option<usize>,
     batch_storage: BatchStorage,
     forward_renderer: ForwardRenderer,
     fxaa_renderer: FxaaRenderer,
+    post_processing_renderer: PostProcessingRenderer,
     renderer2d: Renderer2d,
+    reflection_probe_renderer: ReflectionProbeRenderer,
+    velocity_renderer: VelocityRenderer,
+    debug_view_renderer: DebugViewRenderer,
     texture_event_receiver: Receiver<ResourceEvent<Texture>>,
     shader_event_receiver: Receiver<ResourceEvent<Shader>>,
     // TextureId -> FrameBuffer mapping. This mapping is used for temporal frame buffers
@@ -831,6 +1055,16 @@ pub struct SceneRenderPassContext<'a, 'b> {
     /// have an ability to write to this texture.
     pub ambient_texture: Rc<RefCell<GpuTexture>>,
 
+    /// A texture with screen-space motion of every pixel since the previous frame, in texture
+    /// coordinates (`xy` channels). To find where a pixel was on the previous frame, subtract its
+    /// velocity from its texture coordinates. The texture has the same size as the G-Buffer.
+    ///
+    /// # Important notes
+    ///
+    /// Velocity buffer is rendered only for cameras with enabled motion blur effect or if
+    /// [`QualitySettings::use_velocity_buffer`] is set, otherwise this field is `None`.
+    pub velocity_texture: Option<Rc<RefCell<GpuTexture>>>,
+
     /// User interface renderer.
     pub ui_renderer: &'a mut UiRenderer,
 }
@@ -1072,6 +1306,7 @@ impl Renderer {
             deferred_light_renderer: DeferredLightRenderer::new(&mut state, frame_size, &settings)?,
             flat_shader: FlatShader::new(&mut state)?,
             sprite_renderer: SpriteRenderer::new(&mut state)?,
+            text_renderer: TextRenderer::new(&mut state)?,
             white_dummy: Rc::new(RefCell::new(GpuTexture::new(
                 &mut state,
                 GpuTextureKind::Rectangle {
@@ -1149,15 +1384,21 @@ impl Renderer {
             quality_settings: settings,
             debug_renderer: DebugRenderer::new(&mut state)?,
             scene_data_map: Default::default(),
+            camera_data_map: Default::default(),
             backbuffer_clear_color: Color::BLACK,
             texture_cache: Default::default(),
             geometry_cache: Default::default(),
+            memory_budget: None,
             batch_storage: Default::default(),
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&mut state)?,
+            post_processing_renderer: PostProcessingRenderer::new(&mut state)?,
             statistics: Statistics::default(),
             renderer2d: Renderer2d::new(&mut state)?,
+            reflection_probe_renderer: Default::default(),
+            velocity_renderer: VelocityRenderer::new(&mut state)?,
+            debug_view_renderer: DebugViewRenderer::new(&mut state)?,
             shader_event_receiver,
             texture_event_receiver,
             state,
@@ -1197,6 +1438,34 @@ impl Renderer {
         self.statistics
     }
 
+    /// Reads pixels of the last rendered frame of the given scene (tone mapped and gamma corrected).
+    /// Returns width, height and pixels in RGBA8 format, rows are ordered from top to bottom.
+    /// Returns `None` if the scene wasn't rendered yet. It is a slow operation, that stalls the
+    /// pipeline, use it only for tests or screenshots.
+    pub fn read_scene_frame(&mut self, scene: Handle<Scene>) -> Option<(usize, usize, Vec<u8>)> {
+        let data = self.scene_data_map.get(&scene)?;
+        let width = data.gbuffer.width.max(0) as usize;
+        let height = data.gbuffer.height.max(0) as usize;
+
+        let pixels = self.state.read_pixels(
+            data.ldr_scene_framebuffer.id(),
+            0,
+            0,
+            width as i32,
+            height as i32,
+        );
+
+        // OpenGL has origin at the bottom-left corner.
+        let flipped = pixels
+            .chunks_exact(width * 4)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Some((width, height, flipped))
+    }
+
     /// Unloads texture from GPU memory.
     pub fn unload_texture(&mut self, texture: Texture) {
         self.texture_cache.unload(texture)
@@ -1336,6 +1605,31 @@ impl Renderer {
         Ok(())
     }
 
+    fn memory_statistics(&self) -> MemoryStatistics {
+        let residency = self.texture_cache.residency_statistics();
+        MemoryStatistics {
+            texture_count: self.texture_cache.alive_count(),
+            texture_memory: self.texture_cache.memory_usage(),
+            geometry_buffer_count: self.geometry_cache.alive_count(),
+            geometry_memory: self.geometry_cache.memory_usage(),
+            budget: self.memory_budget,
+            evicted_textures: residency.evicted,
+            streamed_textures: residency.streamed,
+        }
+    }
+
+    /// Sets video memory budget (in bytes) for textures and geometry buffers. When the budget is
+    /// exceeded, least recently used textures are streamed at lower resolution or evicted from GPU
+    /// memory. Render targets are never evicted. `None` means unlimited budget (default).
+    pub fn set_memory_budget(&mut self, budget: Option<usize>) {
+        self.memory_budget = budget;
+    }
+
+    /// Returns current video memory budget (in bytes).
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
     fn update_texture_cache(&mut self, dt: f32) {
         // Maximum amount of textures uploaded to GPU per frame. This defines throughput **only** for
         // requests from resource manager. This is needed to prevent huge lag when there are tons of
@@ -1362,6 +1656,11 @@ impl Renderer {
             }
         }
 
+        if let Some(budget) = self.memory_budget {
+            self.texture_cache
+                .enforce_budget(budget.saturating_sub(self.geometry_cache.memory_usage()));
+        }
+
         self.texture_cache.update(dt);
     }
 
@@ -1388,6 +1687,426 @@ impl Renderer {
         self.renderer2d.update_caches(dt);
     }
 
+    /// Renders the scene from the camera into the high dynamic range frame buffer of the scene
+    /// data, without any post effects.
+    fn render_hdr_frame(
+        &mut self,
+        scene: &Scene,
+        scene_handle: Handle<Scene>,
+        camera: &Camera,
+        scene_associated_data: &mut AssociatedSceneData,
+        frame_size: Vector2<f32>,
+        viewport: Rect<i32>,
+    ) -> Result<(), FrameworkError> {
+        let state = &mut self.state;
+        let graph = &scene.graph;
+
+        self.statistics += scene_associated_data.gbuffer.fill(GBufferRenderContext {
+            state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            batch_storage: &self.batch_storage,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            environment_dummy: self.environment_dummy.clone(),
+            use_parallax_mapping: self.quality_settings.use_parallax_mapping,
+            normal_dummy: self.normal_dummy.clone(),
+            white_dummy: self.white_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+            graph,
+        });
+
+        scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
+
+        scene_associated_data.hdr_scene_framebuffer.clear(
+            state,
+            viewport,
+            Some(self.backbuffer_clear_color),
+            None, // Keep depth, we've just copied valid data in it.
+            Some(0),
+        );
+
+        let (pass_stats, light_stats) =
+            self.deferred_light_renderer
+                .render(DeferredRendererContext {
+                    state,
+                    scene,
+                    camera,
+                    scene_handle,
+                    gbuffer: &mut scene_associated_data.gbuffer,
+                    white_dummy: self.white_dummy.clone(),
+                    ambient_color: scene.ambient_lighting_color,
+                    reflection_probes: &self.reflection_probe_renderer,
+                    environment_dummy: self.environment_dummy.clone(),
+                    settings: &self.quality_settings,
+                    textures: &mut self.texture_cache,
+                    geometry_cache: &mut self.geometry_cache,
+                    batch_storage: &self.batch_storage,
+                    frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                    shader_cache: &mut self.shader_cache,
+                    normal_dummy: self.normal_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                });
+
+        self.statistics.lighting += light_stats;
+        self.statistics.geometry += pass_stats;
+
+        let depth = scene_associated_data.gbuffer.depth();
+
+        self.statistics += self
+            .particle_system_renderer
+            .render(ParticleSystemRenderContext {
+                state,
+                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                graph,
+                camera,
+                white_dummy: self.white_dummy.clone(),
+                depth,
+                frame_width: frame_size.x,
+                frame_height: frame_size.y,
+                viewport,
+                texture_cache: &mut self.texture_cache,
+            });
+
+        self.statistics += self.sprite_renderer.render(SpriteRenderContext {
+            state,
+            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+            graph,
+            camera,
+            white_dummy: self.white_dummy.clone(),
+            viewport,
+            textures: &mut self.texture_cache,
+        });
+
+        self.statistics += self.text_renderer.render(TextRenderContext {
+            state,
+            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+            graph,
+            camera,
+            viewport,
+            textures: &mut self.texture_cache,
+        });
+
+        self.statistics += self.renderer2d.render(
+            state,
+            camera,
+            &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+            graph,
+            &mut self.texture_cache,
+            self.white_dummy.clone(),
+            scene.ambient_lighting_color,
+        )?;
+
+        self.statistics += self.forward_renderer.render(ForwardRenderContext {
+            state,
+            camera,
+            geom_cache: &mut self.geometry_cache,
+            texture_cache: &mut self.texture_cache,
+            shader_cache: &mut self.shader_cache,
+            batch_storage: &self.batch_storage,
+            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+            viewport,
+            quality_settings: &self.quality_settings,
+            white_dummy: self.white_dummy.clone(),
+            normal_dummy: self.normal_dummy.clone(),
+            black_dummy: self.black_dummy.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Captures every reflection probe of the scene, that must be captured on this frame.
+    fn capture_reflection_probes(
+        &mut self,
+        scene: &Scene,
+        scene_handle: Handle<Scene>,
+    ) -> Result<(), FrameworkError> {
+        scope_profile!();
+
+        // Take the probes out of the renderer, so the captures won't see any reflections.
+        let mut probes = std::mem::take(&mut self.reflection_probe_renderer);
+
+        probes.begin_scene(scene_handle);
+
+        // Captures use sky of the first enabled camera.
+        let sky_source = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .find(|camera| camera.is_enabled());
+
+        let mut result = Ok(());
+        for (handle, node) in scene.graph.pair_iter() {
+            if let Some(probe) = node.cast::<ReflectionProbe>() {
+                if probes.prepare_capture(scene_handle, handle, probe) {
+                    if let Err(e) = self.capture_reflection_probe(
+                        &mut probes,
+                        scene,
+                        scene_handle,
+                        handle,
+                        probe,
+                        sky_source,
+                    ) {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        probes.end_scene(scene_handle);
+
+        self.reflection_probe_renderer = probes;
+
+        result
+    }
+
+    fn capture_reflection_probe(
+        &mut self,
+        probes: &mut ReflectionProbeRenderer,
+        scene: &Scene,
+        scene_handle: Handle<Scene>,
+        handle: Handle<Node>,
+        probe: &ReflectionProbe,
+        sky_source: Option<&Camera>,
+    ) -> Result<(), FrameworkError> {
+        let resolution = probe.resolution() as usize;
+        let frame_size = Vector2::new(resolution as f32, resolution as f32);
+        let viewport = Rect::new(0, 0, resolution as i32, resolution as i32);
+
+        let (capture_data, captured_probe) =
+            probes.capture_target(&mut self.state, scene_handle, handle, resolution)?;
+
+        for face_index in 0..6 {
+            let (face, camera) = make_capture_camera(probe, face_index, sky_source);
+
+            self.render_hdr_frame(
+                scene,
+                scene_handle,
+                &camera,
+                capture_data,
+                frame_size,
+                viewport,
+            )?;
+
+            let framebuffer = captured_probe.framebuffer_mut();
+            framebuffer.set_cubemap_face(&mut self.state, 0, face);
+            self.statistics.geometry += blit_pixels(
+                &mut self.state,
+                framebuffer,
+                capture_data.hdr_scene_frame_texture(),
+                &self.flat_shader,
+                viewport,
+                &self.quad,
+            );
+        }
+
+        captured_probe
+            .cube_map()
+            .borrow_mut()
+            .bind_mut(&mut self.state, 0)
+            .generate_mipmap();
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_camera(
+        &mut self,
+        scene: &Scene,
+        scene_handle: Handle<Scene>,
+        camera_handle: Handle<Node>,
+        camera: &Camera,
+        scene_associated_data: &mut AssociatedSceneData,
+        frame_size: Vector2<f32>,
+        viewport: Rect<i32>,
+        dt: f32,
+    ) -> Result<(), FrameworkError> {
+        self.render_hdr_frame(
+            scene,
+            scene_handle,
+            camera,
+            scene_associated_data,
+            frame_size,
+            viewport,
+        )?;
+
+        let velocity_texture = if self.quality_settings.use_velocity_buffer
+            || camera.post_processing().motion_blur().is_some()
+        {
+            self.statistics.geometry += self.velocity_renderer.render(VelocityRenderContext {
+                state: &mut self.state,
+                scene_handle,
+                camera_handle,
+                camera,
+                geom_cache: &mut self.geometry_cache,
+                batch_storage: &self.batch_storage,
+                framebuffer: &mut scene_associated_data.velocity_framebuffer,
+                viewport,
+                quad: &self.quad,
+            });
+            Some(scene_associated_data.velocity_texture())
+        } else {
+            None
+        };
+
+        let state = &mut self.state;
+
+        for render_pass in self.scene_render_passes.iter() {
+            self.statistics += render_pass
+                .borrow_mut()
+                .on_hdr_render(SceneRenderPassContext {
+                    pipeline_state: state,
+                    texture_cache: &mut self.texture_cache,
+                    geometry_cache: &mut self.geometry_cache,
+                    quality_settings: &self.quality_settings,
+                    batch_storage: &self.batch_storage,
+                    viewport,
+                    scene,
+                    camera,
+                    scene_handle,
+                    white_dummy: self.white_dummy.clone(),
+                    normal_dummy: self.normal_dummy.clone(),
+                    metallic_dummy: self.metallic_dummy.clone(),
+                    environment_dummy: self.environment_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                    depth_texture: scene_associated_data.gbuffer.depth(),
+                    normal_texture: scene_associated_data.gbuffer.normal_texture(),
+                    ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
+                    velocity_texture: velocity_texture.clone(),
+                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                    ui_renderer: &mut self.ui_renderer,
+                })?;
+        }
+
+        let debug_view_mode = self.quality_settings.debug_view_mode;
+
+        if !debug_view_mode.replaces_frame() {
+            let quad = &self.quad;
+
+            let bloom = camera.post_processing().bloom();
+
+            // Prepare glow map.
+            if let Some(bloom) = bloom {
+                self.statistics.geometry += scene_associated_data.bloom_renderer.render(
+                    state,
+                    quad,
+                    scene_associated_data.hdr_scene_frame_texture(),
+                    bloom.threshold,
+                );
+            }
+
+            // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
+            self.statistics.geometry += scene_associated_data.hdr_renderer.render(
+                state,
+                scene_associated_data.hdr_scene_frame_texture(),
+                scene_associated_data.bloom_renderer.result(),
+                bloom.map_or(0.0, |bloom| bloom.intensity),
+                &mut scene_associated_data.ldr_scene_framebuffer,
+                viewport,
+                quad,
+                dt,
+                camera.exposure(),
+                camera.color_grading_lut_ref(),
+                camera.color_grading_enabled(),
+                &mut self.texture_cache,
+            );
+
+            // Apply FXAA if needed.
+            if self.quality_settings.fxaa {
+                self.statistics.geometry += self.fxaa_renderer.render(
+                    state,
+                    viewport,
+                    scene_associated_data.ldr_scene_frame_texture(),
+                    &mut scene_associated_data.ldr_temp_framebuffer,
+                );
+
+                let quad = &self.quad;
+                let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
+                self.statistics.geometry += blit_pixels(
+                    state,
+                    &mut scene_associated_data.ldr_scene_framebuffer,
+                    temp_frame_texture,
+                    &self.flat_shader,
+                    viewport,
+                    quad,
+                );
+            }
+
+            // Apply user-configurable effects of the camera.
+            self.statistics.geometry +=
+                self.post_processing_renderer.render(PostProcessingContext {
+                    state,
+                    stack: camera.post_processing(),
+                    scene_data: scene_associated_data,
+                    viewport,
+                    quad: &self.quad,
+                    flat_shader: &self.flat_shader,
+                    texture_cache: &mut self.texture_cache,
+                    shader_cache: &mut self.shader_cache,
+                    white_dummy: self.white_dummy.clone(),
+                    normal_dummy: self.normal_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                });
+        }
+
+        if debug_view_mode != DebugViewMode::None {
+            self.statistics.geometry +=
+                self.debug_view_renderer.render(DebugViewRenderContext {
+                    state,
+                    mode: debug_view_mode,
+                    scene,
+                    camera,
+                    geom_cache: &mut self.geometry_cache,
+                    batch_storage: &self.batch_storage,
+                    scene_data: scene_associated_data,
+                    viewport,
+                    quad: &self.quad,
+                    flat_shader: &self.flat_shader,
+                    settings: &self.quality_settings,
+                })?;
+        }
+
+        // Render debug geometry in the LDR frame buffer.
+        self.statistics += self.debug_renderer.render(
+            state,
+            viewport,
+            &mut scene_associated_data.ldr_scene_framebuffer,
+            &scene.drawing_context,
+            camera,
+        );
+
+        for render_pass in self.scene_render_passes.iter() {
+            self.statistics += render_pass
+                .borrow_mut()
+                .on_ldr_render(SceneRenderPassContext {
+                    pipeline_state: state,
+                    texture_cache: &mut self.texture_cache,
+                    geometry_cache: &mut self.geometry_cache,
+                    quality_settings: &self.quality_settings,
+                    batch_storage: &self.batch_storage,
+                    viewport,
+                    scene,
+                    camera,
+                    scene_handle,
+                    white_dummy: self.white_dummy.clone(),
+                    normal_dummy: self.normal_dummy.clone(),
+                    metallic_dummy: self.metallic_dummy.clone(),
+                    environment_dummy: self.environment_dummy.clone(),
+                    black_dummy: self.black_dummy.clone(),
+                    depth_texture: scene_associated_data.gbuffer.depth(),
+                    normal_texture: scene_associated_data.gbuffer.normal_texture(),
+                    ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
+                    velocity_texture: velocity_texture.clone(),
+                    framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                    ui_renderer: &mut self.ui_renderer,
+                })?;
+        }
+
+        Ok(())
+    }
+
     fn render_frame(
         &mut self,
         scenes: &SceneContainer,
@@ -1398,6 +2117,8 @@ impl Renderer {
         // Make sure to drop associated data for destroyed scenes.
         self.scene_data_map
             .retain(|h, _| scenes.is_valid_handle(*h));
+        self.reflection_probe_renderer
+            .retain_scenes(|h| scenes.is_valid_handle(h));
 
         // We have to invalidate resource bindings cache because some textures or programs,
         // or other GL resources can be destroyed and then on their "names" some new resource
@@ -1406,6 +2127,7 @@ impl Renderer {
         self.state.invalidate_resource_bindings_cache();
         let dt = self.statistics.capped_frame_time;
         self.statistics.begin_frame();
+        self.post_processing_renderer.update(dt);
 
         let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
         self.backbuffer.clear(
@@ -1419,6 +2141,8 @@ impl Renderer {
         let backbuffer_width = self.frame_size.0 as f32;
         let backbuffer_height = self.frame_size.1 as f32;
 
+        let mut used_camera_targets = FxHashSet::default();
+
         for (scene_handle, scene) in scenes.pair_iter().filter(|(_, s)| s.enabled) {
             let graph = &scene.graph;
 
@@ -1440,30 +2164,43 @@ impl Renderer {
                 // Clamp to [1.0; infinity] range.
                 .sup(&Vector2::new(1.0, 1.0));
 
-            let state = &mut self.state;
-
             self.batch_storage.generate_batches(graph);
 
-            let scene_associated_data = self
-                .scene_data_map
-                .entry(scene_handle)
-                .and_modify(|data| {
-                    if data.gbuffer.width != frame_size.x as i32
-                        || data.gbuffer.height != frame_size.y as i32
-                    {
-                        let width = frame_size.x as usize;
-                        let height = frame_size.y as usize;
-
-                        Log::info(format!(
-                            "Associated scene rendering data was re-created for scene {}, because render frame size was changed. Old is {}x{}, new {}x{}!",
-                            scene_handle,
-                            data.gbuffer.width,data.gbuffer.height,width,height
-                        ));
-
-                        *data = AssociatedSceneData::new(state, width, height).unwrap();
-                    }
-                })
-                .or_insert_with(|| {
+            // Velocity buffer needs transforms of objects from the previous frame.
+            if self.quality_settings.use_velocity_buffer
+                || graph
+                    .linear_iter()
+                    .filter_map(|node| node.cast::<Camera>())
+                    .any(|camera| {
+                        camera.is_enabled() && camera.post_processing().motion_blur().is_some()
+                    })
+            {
+                self.velocity_renderer
+                    .update_history(scene_handle, &self.batch_storage);
+            }
+
+            self.capture_reflection_probes(scene, scene_handle)?;
+
+            let mut scene_associated_data = match self.scene_data_map.remove(&scene_handle) {
+                Some(data)
+                    if data.gbuffer.width == frame_size.x as i32
+                        && data.gbuffer.height == frame_size.y as i32 =>
+                {
+                    data
+                }
+                Some(data) => {
+                    let width = frame_size.x as usize;
+                    let height = frame_size.y as usize;
+
+                    Log::info(format!(
+                        "Associated scene rendering data was re-created for scene {}, because render frame size was changed. Old is {}x{}, new {}x{}!",
+                        scene_handle,
+                        data.gbuffer.width,data.gbuffer.height,width,height
+                    ));
+
+                    AssociatedSceneData::new(&mut self.state, width, height)?
+                }
+                None => {
                     let width = frame_size.x as usize;
                     let height = frame_size.y as usize;
 
@@ -1472,8 +2209,9 @@ impl Renderer {
                         scene_handle
                     ));
 
-                    AssociatedSceneData::new(state, width, height).unwrap()
-                });
+                    AssociatedSceneData::new(&mut self.state, width, height)?
+                }
+            };
 
             // If we specified a texture to draw to, we have to register it in texture cache
             // so it can be used in later on as texture. This is useful in case if you need
@@ -1491,225 +2229,87 @@ impl Renderer {
                 );
             }
 
-            for camera in graph.linear_iter().filter_map(|node| {
-                if let Some(camera) = node.cast::<Camera>() {
-                    if camera.is_enabled() {
-                        Some(camera)
-                    } else {
-                        None
+            let mut cameras = graph
+                .pair_iter()
+                .filter_map(|(handle, node)| node.cast::<Camera>().map(|camera| (handle, camera)))
+                .filter(|(_, camera)| camera.is_enabled())
+                .collect::<Vec<_>>();
+
+            // Cameras with render targets must be rendered first, so their results could be
+            // used by other cameras in the same frame. Then cameras are sorted by their render
+            // order, so viewports with higher order will be drawn on top of others.
+            cameras.sort_by_key(|(_, camera)| {
+                (camera.render_target().is_none(), camera.render_order())
+            });
+
+            for (camera_handle, camera) in cameras {
+                if let Some(render_target) = camera.render_target() {
+                    let rt_size = match render_target.data_ref().kind() {
+                        TextureKind::Rectangle { width, height } => {
+                            Vector2::new(width as f32, height as f32)
+                        }
+                        _ => {
+                            Log::err(
+                                "Only rectangle textures can be used as camera render target!",
+                            );
+                            continue;
+                        }
                     }
-                } else {
-                    None
-                }
-            }) {
-                let viewport = camera.viewport_pixels(frame_size);
-
-                self.statistics += scene_associated_data.gbuffer.fill(GBufferRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    batch_storage: &self.batch_storage,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    environment_dummy: self.environment_dummy.clone(),
-                    use_parallax_mapping: self.quality_settings.use_parallax_mapping,
-                    normal_dummy: self.normal_dummy.clone(),
-                    white_dummy: self.white_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                    graph,
-                });
-
-                scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
-
-                scene_associated_data.hdr_scene_framebuffer.clear(
-                    state,
-                    viewport,
-                    Some(self.backbuffer_clear_color),
-                    None, // Keep depth, we've just copied valid data in it.
-                    Some(0),
-                );
-
-                let (pass_stats, light_stats) =
-                    self.deferred_light_renderer
-                        .render(DeferredRendererContext {
-                            state,
-                            scene,
-                            camera,
-                            gbuffer: &mut scene_associated_data.gbuffer,
-                            white_dummy: self.white_dummy.clone(),
-                            ambient_color: scene.ambient_lighting_color,
-                            settings: &self.quality_settings,
-                            textures: &mut self.texture_cache,
-                            geometry_cache: &mut self.geometry_cache,
-                            batch_storage: &self.batch_storage,
-                            frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            shader_cache: &mut self.shader_cache,
-                            normal_dummy: self.normal_dummy.clone(),
-                            black_dummy: self.black_dummy.clone(),
-                        });
-
-                self.statistics.lighting += light_stats;
-                self.statistics.geometry += pass_stats;
-
-                let depth = scene_associated_data.gbuffer.depth();
-
-                self.statistics +=
-                    self.particle_system_renderer
-                        .render(ParticleSystemRenderContext {
-                            state,
-                            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            graph,
-                            camera,
-                            white_dummy: self.white_dummy.clone(),
-                            depth,
-                            frame_width: frame_size.x,
-                            frame_height: frame_size.y,
-                            viewport,
-                            texture_cache: &mut self.texture_cache,
-                        });
+                    .sup(&Vector2::new(1.0, 1.0));
 
-                self.statistics += self.sprite_renderer.render(SpriteRenderContext {
-                    state,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    graph,
-                    camera,
-                    white_dummy: self.white_dummy.clone(),
-                    viewport,
-                    textures: &mut self.texture_cache,
-                });
+                    let key = render_target.key();
 
-                self.statistics += self.renderer2d.render(
-                    state,
-                    camera,
-                    &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    graph,
-                    &mut self.texture_cache,
-                    self.white_dummy.clone(),
-                    scene.ambient_lighting_color,
-                )?;
-
-                self.statistics += self.forward_renderer.render(ForwardRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    batch_storage: &self.batch_storage,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    quality_settings: &self.quality_settings,
-                    white_dummy: self.white_dummy.clone(),
-                    normal_dummy: self.normal_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                });
-
-                for render_pass in self.scene_render_passes.iter() {
-                    self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_hdr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &self.batch_storage,
-                                viewport,
-                                scene,
-                                camera,
-                                scene_handle,
-                                white_dummy: self.white_dummy.clone(),
-                                normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
-                                environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
-                            })?;
-                }
-
-                let quad = &self.quad;
-
-                // Prepare glow map.
-                self.statistics.geometry += scene_associated_data.bloom_renderer.render(
-                    state,
-                    quad,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                );
-
-                // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
-                self.statistics.geometry += scene_associated_data.hdr_renderer.render(
-                    state,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                    scene_associated_data.bloom_renderer.result(),
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    viewport,
-                    quad,
-                    dt,
-                    camera.exposure(),
-                    camera.color_grading_lut_ref(),
-                    camera.color_grading_enabled(),
-                    &mut self.texture_cache,
-                );
-
-                // Apply FXAA if needed.
-                if self.quality_settings.fxaa {
-                    self.statistics.geometry += self.fxaa_renderer.render(
-                        state,
+                    let mut camera_data = match self.camera_data_map.remove(&key) {
+                        Some(data)
+                            if data.gbuffer.width == rt_size.x as i32
+                                && data.gbuffer.height == rt_size.y as i32 =>
+                        {
+                            data
+                        }
+                        _ => AssociatedSceneData::new(
+                            &mut self.state,
+                            rt_size.x as usize,
+                            rt_size.y as usize,
+                        )?,
+                    };
+
+                    let viewport = Rect::new(0, 0, rt_size.x as i32, rt_size.y as i32);
+
+                    self.render_camera(
+                        scene,
+                        scene_handle,
+                        camera_handle,
+                        camera,
+                        &mut camera_data,
+                        rt_size,
                         viewport,
-                        scene_associated_data.ldr_scene_frame_texture(),
-                        &mut scene_associated_data.ldr_temp_framebuffer,
+                        dt,
+                    )?;
+
+                    self.texture_cache.map.insert(
+                        key,
+                        CacheEntry {
+                            value: camera_data.ldr_scene_frame_texture(),
+                            time_to_live: f32::INFINITY,
+                            value_hash: 0,
+                        },
                     );
 
-                    let quad = &self.quad;
-                    let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
-                    self.statistics.geometry += blit_pixels(
-                        state,
-                        &mut scene_associated_data.ldr_scene_framebuffer,
-                        temp_frame_texture,
-                        &self.flat_shader,
+                    self.camera_data_map.insert(key, camera_data);
+                    used_camera_targets.insert(key);
+                } else {
+                    let viewport = camera.viewport_pixels(frame_size);
+
+                    self.render_camera(
+                        scene,
+                        scene_handle,
+                        camera_handle,
+                        camera,
+                        &mut scene_associated_data,
+                        frame_size,
                         viewport,
-                        quad,
-                    );
-                }
-
-                // Render debug geometry in the LDR frame buffer.
-                self.statistics += self.debug_renderer.render(
-                    state,
-                    viewport,
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    &scene.drawing_context,
-                    camera,
-                );
-
-                for render_pass in self.scene_render_passes.iter() {
-                    self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_ldr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &self.batch_storage,
-                                viewport,
-                                scene,
-                                camera,
-                                scene_handle,
-                                white_dummy: self.white_dummy.clone(),
-                                normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
-                                environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
-                            })?;
+                        dt,
+                    )?;
                 }
             }
 
@@ -1717,7 +2317,7 @@ impl Renderer {
             if scene.render_target.is_none() {
                 let quad = &self.quad;
                 self.statistics.geometry += blit_pixels(
-                    state,
+                    &mut self.state,
                     &mut self.backbuffer,
                     scene_associated_data.ldr_scene_frame_texture(),
                     &self.flat_shader,
@@ -1725,8 +2325,26 @@ impl Renderer {
                     quad,
                 );
             }
+
+            self.scene_data_map
+                .insert(scene_handle, scene_associated_data);
         }
 
+        // Make sure to drop associated data for cameras that do not render to textures anymore.
+        // Their textures have infinite lifetime in the texture cache, so they must be removed
+        // from the cache explicitly.
+        let texture_cache = &mut self.texture_cache;
+        self.camera_data_map.retain(|key, _| {
+            let used = used_camera_targets.contains(key);
+            if !used {
+                texture_cache.map.remove(key);
+            }
+            used
+        });
+
+        self.reflection_probe_renderer.end_frame();
+        self.velocity_renderer.end_frame();
+
         // Render UI on top of everything without gamma correction.
         self.statistics += self.ui_renderer.render(UiRenderContext {
             state: &mut self.state,
@@ -1748,14 +2366,17 @@ impl Renderer {
         scenes: &SceneContainer,
         drawing_context: &DrawingContext,
         context: &glutin::WindowedContext<glutin::PossiblyCurrent>,
-    ) -> Result<(), FrameworkError> {
+        pixel_positions: &[Vector2<f32>],
+    ) -> Result<Vec<Color>, FrameworkError> {
         self.render_frame(scenes, drawing_context)?;
+        let pixels = self.read_frame_pixels(pixel_positions);
         self.statistics.end_frame();
         context.swap_buffers()?;
         self.state.check_error();
         self.statistics.finalize();
         self.statistics.pipeline = self.state.pipeline_statistics();
-        Ok(())
+        self.statistics.memory = self.memory_statistics();
+        Ok(pixels)
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -1763,12 +2384,30 @@ impl Renderer {
         &mut self,
         scenes: &SceneContainer,
         drawing_context: &DrawingContext,
-    ) -> Result<(), FrameworkError> {
+        pixel_positions: &[Vector2<f32>],
+    ) -> Result<Vec<Color>, FrameworkError> {
         self.render_frame(scenes, drawing_context)?;
+        let pixels = self.read_frame_pixels(pixel_positions);
         self.statistics.end_frame();
         self.state.check_error();
         self.statistics.finalize();
         self.statistics.pipeline = self.state.pipeline_statistics();
-        Ok(())
+        self.statistics.memory = self.memory_statistics();
+        Ok(pixels)
+    }
+
+    /// Reads colors of the pixels of the rendered frame at the given positions (in screen
+    /// coordinates, origin at the top-left corner). Positions outside of the frame are clamped.
+    fn read_frame_pixels(&mut self, positions: &[Vector2<f32>]) -> Vec<Color> {
+        let (width, height) = self.frame_size;
+        positions
+            .iter()
+            .map(|position| {
+                let x = (position.x.max(0.0) as u32).min(width - 1);
+                let y = (position.y.max(0.0) as u32).min(height - 1);
+                self.state
+                    .read_backbuffer_pixel(x as i32, (height - 1 - y) as i32)
+            })
+            .collect()
     }
 }
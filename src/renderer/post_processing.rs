@@ -0,0 +1,321 @@
+//! Post-processing renderer applies effects from camera's post-processing stack to a frame in low
+//! dynamic range. See [`crate::scene::post_processing::PostProcessStack`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::Rect,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        apply_material, blit_pixels,
+        cache::{shader::ShaderCache, texture::TextureCache},
+        flat_shader::FlatShader,
+        framework::{
+            error::FrameworkError,
+            framebuffer::DrawParameters,
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::PipelineState,
+        },
+        make_viewport_matrix, AssociatedSceneData, MaterialContext, RenderPassStatistics,
+    },
+    scene::post_processing::{PostEffect, PostProcessStack},
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct PostProcessShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    frame_texture: UniformLocation,
+    velocity_texture: UniformLocation,
+    sample_count: UniformLocation,
+    effect_kind: UniformLocation,
+    intensity: UniformLocation,
+    smoothness: UniformLocation,
+    vignette_color: UniformLocation,
+    time: UniformLocation,
+}
+
+impl PostProcessShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/post_process_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "PostProcessShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            frame_texture: program
+                .uniform_location(state, &ImmutableString::new("frameTexture"))?,
+            velocity_texture: program
+                .uniform_location(state, &ImmutableString::new("velocityTexture"))?,
+            sample_count: program.uniform_location(state, &ImmutableString::new("sampleCount"))?,
+            effect_kind: program.uniform_location(state, &ImmutableString::new("effectKind"))?,
+            intensity: program.uniform_location(state, &ImmutableString::new("intensity"))?,
+            smoothness: program.uniform_location(state, &ImmutableString::new("smoothness"))?,
+            vignette_color: program
+                .uniform_location(state, &ImmutableString::new("vignetteColor"))?,
+            time: program.uniform_location(state, &ImmutableString::new("time"))?,
+            program,
+        })
+    }
+}
+
+/// Parameters of a single pass of built-in post-processing shader.
+struct BuiltInEffect {
+    kind: i32,
+    intensity: f32,
+    smoothness: f32,
+    color: Color,
+    sample_count: i32,
+}
+
+pub(crate) struct PostProcessingContext<'a> {
+    pub state: &'a mut PipelineState,
+    pub stack: &'a PostProcessStack,
+    pub scene_data: &'a mut AssociatedSceneData,
+    pub viewport: Rect<i32>,
+    pub quad: &'a GeometryBuffer,
+    pub flat_shader: &'a FlatShader,
+    pub texture_cache: &'a mut TextureCache,
+    pub shader_cache: &'a mut ShaderCache,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+    pub normal_dummy: Rc<RefCell<GpuTexture>>,
+    pub black_dummy: Rc<RefCell<GpuTexture>>,
+}
+
+pub(crate) struct PostProcessingRenderer {
+    shader: PostProcessShader,
+    render_pass_name: ImmutableString,
+    frame_texture_name: ImmutableString,
+    inverse_screen_size_name: ImmutableString,
+    time: f32,
+}
+
+impl PostProcessingRenderer {
+    pub(crate) fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: PostProcessShader::new(state)?,
+            render_pass_name: ImmutableString::new("PostProcess"),
+            frame_texture_name: ImmutableString::new("frameTexture"),
+            inverse_screen_size_name: ImmutableString::new("inverseScreenSize"),
+            time: 0.0,
+        })
+    }
+
+    fn draw_built_in(
+        &self,
+        state: &mut PipelineState,
+        scene_data: &mut AssociatedSceneData,
+        viewport: Rect<i32>,
+        quad: &GeometryBuffer,
+        effect: BuiltInEffect,
+    ) -> RenderPassStatistics {
+        let mut statistics = RenderPassStatistics::default();
+
+        let frame_texture = scene_data.ldr_scene_frame_texture();
+        let velocity_texture = scene_data.velocity_texture();
+        let frame_matrix = make_viewport_matrix(viewport);
+        let shader = &self.shader;
+
+        statistics += scene_data.ldr_temp_framebuffer.draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&shader.frame_texture, &frame_texture)
+                    .set_texture(&shader.velocity_texture, &velocity_texture)
+                    .set_i32(&shader.sample_count, effect.sample_count)
+                    .set_i32(&shader.effect_kind, effect.kind)
+                    .set_f32(&shader.intensity, effect.intensity)
+                    .set_f32(&shader.smoothness, effect.smoothness)
+                    .set_srgb_color(&shader.vignette_color, &effect.color)
+                    .set_f32(&shader.time, self.time);
+            },
+        );
+
+        statistics
+    }
+
+    /// Advances time of animated effects, must be called once per frame.
+    pub(crate) fn update(&mut self, dt: f32) {
+        // Keep the time in a reasonable range to prevent precision issues in the noise function.
+        self.time = (self.time + dt) % 1000.0;
+    }
+
+    pub(crate) fn render(&mut self, ctx: PostProcessingContext) -> RenderPassStatistics {
+        scope_profile!();
+
+        let PostProcessingContext {
+            state,
+            stack,
+            scene_data,
+            viewport,
+            quad,
+            flat_shader,
+            texture_cache,
+            shader_cache,
+            white_dummy,
+            normal_dummy,
+            black_dummy,
+        } = ctx;
+
+        let mut statistics = RenderPassStatistics::default();
+
+        for effect in stack.enabled_effects() {
+            let mut applied = true;
+
+            match effect {
+                // Bloom is applied in HDR.
+                PostEffect::Bloom(_) => applied = false,
+                PostEffect::Vignette(vignette) => {
+                    statistics += self.draw_built_in(
+                        state,
+                        scene_data,
+                        viewport,
+                        quad,
+                        BuiltInEffect {
+                            kind: 0,
+                            intensity: vignette.intensity,
+                            smoothness: vignette.smoothness,
+                            color: vignette.color,
+                            sample_count: 0,
+                        },
+                    );
+                }
+                PostEffect::ChromaticAberration(aberration) => {
+                    statistics += self.draw_built_in(
+                        state,
+                        scene_data,
+                        viewport,
+                        quad,
+                        BuiltInEffect {
+                            kind: 1,
+                            intensity: aberration.intensity,
+                            smoothness: 0.0,
+                            color: Color::BLACK,
+                            sample_count: 0,
+                        },
+                    );
+                }
+                PostEffect::FilmGrain(grain) => {
+                    statistics += self.draw_built_in(
+                        state,
+                        scene_data,
+                        viewport,
+                        quad,
+                        BuiltInEffect {
+                            kind: 2,
+                            intensity: grain.intensity,
+                            smoothness: 0.0,
+                            color: Color::BLACK,
+                            sample_count: 0,
+                        },
+                    );
+                }
+                PostEffect::MotionBlur(motion_blur) => {
+                    statistics += self.draw_built_in(
+                        state,
+                        scene_data,
+                        viewport,
+                        quad,
+                        BuiltInEffect {
+                            kind: 3,
+                            intensity: motion_blur.shutter,
+                            smoothness: 0.0,
+                            color: Color::BLACK,
+                            sample_count: motion_blur.sample_count.max(1) as i32,
+                        },
+                    );
+                }
+                PostEffect::Custom(custom) => {
+                    let material = custom.material.lock();
+                    match shader_cache
+                        .get(state, material.shader())
+                        .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
+                    {
+                        Some(render_pass) => {
+                            let frame_texture = scene_data.ldr_scene_frame_texture();
+                            let frame_matrix = make_viewport_matrix(viewport);
+                            let inverse_screen_size = Vector2::new(
+                                1.0 / viewport.w().max(1) as f32,
+                                1.0 / viewport.h().max(1) as f32,
+                            );
+
+                            statistics += scene_data.ldr_temp_framebuffer.draw(
+                                quad,
+                                state,
+                                viewport,
+                                &render_pass.program,
+                                &render_pass.draw_params,
+                                |mut program_binding| {
+                                    apply_material(MaterialContext {
+                                        material: &material,
+                                        program_binding: &mut program_binding,
+                                        texture_cache,
+                                        world_matrix: &Matrix4::identity(),
+                                        wvp_matrix: &frame_matrix,
+                                        bone_matrices: &[],
+                                        use_skeletal_animation: false,
+                                        camera_position: &Vector3::default(),
+                                        use_pom: false,
+                                        light_position: &Vector3::default(),
+                                        normal_dummy: normal_dummy.clone(),
+                                        white_dummy: white_dummy.clone(),
+                                        black_dummy: black_dummy.clone(),
+                                    });
+
+                                    if let Some(location) =
+                                        program_binding.uniform_location(&self.frame_texture_name)
+                                    {
+                                        program_binding.set_texture(&location, &frame_texture);
+                                    }
+                                    if let Some(location) = program_binding
+                                        .uniform_location(&self.inverse_screen_size_name)
+                                    {
+                                        program_binding
+                                            .set_vector2(&location, &inverse_screen_size);
+                                    }
+                                },
+                            );
+                        }
+                        None => applied = false,
+                    }
+                }
+            }
+
+            // Every effect renders into temporary frame buffer, copy the result back so the next
+            // effect will use it as input.
+            if applied {
+                let temp_frame_texture = scene_data.ldr_temp_frame_texture();
+                statistics += blit_pixels(
+                    state,
+                    &mut scene_data.ldr_scene_framebuffer,
+                    temp_frame_texture,
+                    flat_shader,
+                    viewport,
+                    quad,
+                );
+            }
+        }
+
+        statistics
+    }
+}
@@ -0,0 +1,50 @@
+use crate::core::sstorage::ImmutableString;
+use crate::renderer::framework::{
+    error::FrameworkError,
+    gpu_program::{GpuProgram, UniformLocation},
+    state::PipelineState,
+};
+
+pub struct ProceduralSkyShader {
+    pub program: GpuProgram,
+    pub wvp_matrix: UniformLocation,
+    pub sun_direction: UniformLocation,
+    pub sun_intensity: UniformLocation,
+    pub sun_angular_radius: UniformLocation,
+    pub rayleigh_scattering: UniformLocation,
+    pub mie_scattering: UniformLocation,
+    pub mie_anisotropy: UniformLocation,
+    pub rayleigh_scale_height: UniformLocation,
+    pub mie_scale_height: UniformLocation,
+}
+
+impl ProceduralSkyShader {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/procedural_sky_fs.glsl");
+        let vertex_source = include_str!("shaders/skybox_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "ProceduralSkyShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            sun_direction: program
+                .uniform_location(state, &ImmutableString::new("sunDirection"))?,
+            sun_intensity: program
+                .uniform_location(state, &ImmutableString::new("sunIntensity"))?,
+            sun_angular_radius: program
+                .uniform_location(state, &ImmutableString::new("sunAngularRadius"))?,
+            rayleigh_scattering: program
+                .uniform_location(state, &ImmutableString::new("rayleighScattering"))?,
+            mie_scattering: program
+                .uniform_location(state, &ImmutableString::new("mieScattering"))?,
+            mie_anisotropy: program
+                .uniform_location(state, &ImmutableString::new("mieAnisotropy"))?,
+            rayleigh_scale_height: program
+                .uniform_location(state, &ImmutableString::new("rayleighScaleHeight"))?,
+            mie_scale_height: program
+                .uniform_location(state, &ImmutableString::new("mieScaleHeight"))?,
+            program,
+        })
+    }
+}
@@ -0,0 +1,355 @@
+//! Reflection probes capture. Every probe has its own cube map, each face of the cube map is
+//! rendered using the same pipeline as a camera (up to the high dynamic range frame) and then
+//! copied into the cube map. See [`crate::scene::light::reflection_probe::ReflectionProbe`] docs
+//! for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        math::Matrix4Ext,
+        pool::Handle,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, FrameBuffer},
+            gpu_texture::{
+                Coordinate, CubeMapFace, GpuTexture, GpuTextureKind, MagnificationFilter,
+                MinificationFilter, PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        AssociatedSceneData,
+    },
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        light::reflection_probe::ReflectionProbe,
+        node::Node,
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, cmp::Ordering, collections::hash_map::Entry, rc::Rc};
+
+/// Maximum amount of reflection probes that could be used at the same time. Keep in sync with
+/// the ambient light shader.
+pub const MAX_REFLECTION_PROBES: usize = 4;
+
+// Order and orientation of the faces must match OpenGL cube map conventions.
+fn cube_map_faces() -> [(CubeMapFace, Vector3<f32>, Vector3<f32>); 6] {
+    [
+        (
+            CubeMapFace::PositiveX,
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        ),
+        (
+            CubeMapFace::NegativeX,
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        ),
+        (
+            CubeMapFace::PositiveY,
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ),
+        (
+            CubeMapFace::NegativeY,
+            Vector3::new(0.0, -1.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        ),
+        (
+            CubeMapFace::PositiveZ,
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        ),
+        (
+            CubeMapFace::NegativeZ,
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        ),
+    ]
+}
+
+pub(crate) struct CapturedProbe {
+    // The only color attachment is the cube map.
+    framebuffer: FrameBuffer,
+    resolution: usize,
+    alive: bool,
+}
+
+impl CapturedProbe {
+    fn new(state: &mut PipelineState, resolution: usize) -> Result<Self, FrameworkError> {
+        let mip_count = (resolution as f32).log2() as usize + 1;
+
+        let mut cube_map = GpuTexture::new(
+            state,
+            GpuTextureKind::Cube {
+                width: resolution,
+                height: resolution,
+            },
+            PixelKind::RGBA16F,
+            MinificationFilter::LinearMipMapLinear,
+            MagnificationFilter::Linear,
+            mip_count,
+            None,
+        )?;
+        cube_map
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::R, WrapMode::ClampToEdge);
+
+        Ok(Self {
+            framebuffer: FrameBuffer::new(
+                state,
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(cube_map)),
+                }],
+            )?,
+            resolution,
+            alive: true,
+        })
+    }
+
+    pub fn cube_map(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.color_attachments()[0].texture.clone()
+    }
+
+    pub fn framebuffer_mut(&mut self) -> &mut FrameBuffer {
+        &mut self.framebuffer
+    }
+}
+
+/// Reflection probes, that will be used by the ambient light shader for the current camera.
+#[derive(Default)]
+pub(crate) struct ActiveReflectionProbes {
+    pub cube_maps: Vec<Rc<RefCell<GpuTexture>>>,
+    pub inv_world_matrices: Vec<Matrix4<f32>>,
+    pub positions: Vec<Vector3<f32>>,
+    pub sizes: Vec<Vector3<f32>>,
+    /// - `x` - blend distance
+    /// - `y` - box projection (`1.0` - enabled, `0.0` - disabled)
+    /// - `z` - index of the last mip level of the cube map
+    pub params: Vec<Vector3<f32>>,
+}
+
+impl ActiveReflectionProbes {
+    fn clear(&mut self) {
+        self.cube_maps.clear();
+        self.inv_world_matrices.clear();
+        self.positions.clear();
+        self.sizes.clear();
+        self.params.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.cube_maps.len()
+    }
+}
+
+/// Stores captured cube maps of every reflection probe of every scene.
+#[derive(Default)]
+pub struct ReflectionProbeRenderer {
+    probes: FxHashMap<(Handle<Scene>, Handle<Node>), CapturedProbe>,
+    // Offscreen render targets for each capture resolution.
+    capture_targets: FxHashMap<usize, AssociatedSceneData>,
+    used_capture_targets: Vec<usize>,
+}
+
+impl ReflectionProbeRenderer {
+    /// Marks every captured probe of the scene as dead. Probes, that won't be prepared for a
+    /// capture with [`Self::prepare_capture`] until [`Self::end_scene`] will be destroyed.
+    pub(crate) fn begin_scene(&mut self, scene_handle: Handle<Scene>) {
+        for ((scene, _), probe) in self.probes.iter_mut() {
+            if *scene == scene_handle {
+                probe.alive = false;
+            }
+        }
+    }
+
+    /// Returns `true` if the probe must be captured on this frame. The probe is marked as
+    /// alive.
+    pub(crate) fn prepare_capture(
+        &mut self,
+        scene_handle: Handle<Scene>,
+        handle: Handle<Node>,
+        probe: &ReflectionProbe,
+    ) -> bool {
+        match self.probes.get_mut(&(scene_handle, handle)) {
+            Some(captured) if captured.resolution == probe.resolution() as usize => {
+                captured.alive = true;
+                probe.is_capture_pending()
+            }
+            _ => true,
+        }
+    }
+
+    /// Destroys every probe of the scene that wasn't prepared for capture since
+    /// [`Self::begin_scene`].
+    pub(crate) fn end_scene(&mut self, scene_handle: Handle<Scene>) {
+        self.probes
+            .retain(|(scene, _), probe| *scene != scene_handle || probe.alive);
+    }
+
+    /// Destroys every probe of scenes, that are not alive anymore.
+    pub(crate) fn retain_scenes<F>(&mut self, mut func: F)
+    where
+        F: FnMut(Handle<Scene>) -> bool,
+    {
+        self.probes.retain(|(scene, _), _| func(*scene));
+    }
+
+    /// Destroys unused capture targets.
+    pub(crate) fn end_frame(&mut self) {
+        let used = &self.used_capture_targets;
+        self.capture_targets
+            .retain(|resolution, _| used.contains(resolution));
+        self.used_capture_targets.clear();
+    }
+
+    /// Returns (and creates if needed) an offscreen target for capture and a cube map for the
+    /// probe.
+    pub(crate) fn capture_target(
+        &mut self,
+        state: &mut PipelineState,
+        scene_handle: Handle<Scene>,
+        handle: Handle<Node>,
+        resolution: usize,
+    ) -> Result<(&mut AssociatedSceneData, &mut CapturedProbe), FrameworkError> {
+        let key = (scene_handle, handle);
+        if self
+            .probes
+            .get(&key)
+            .map_or(true, |p| p.resolution != resolution)
+        {
+            self.probes
+                .insert(key, CapturedProbe::new(state, resolution)?);
+        }
+
+        if !self.used_capture_targets.contains(&resolution) {
+            self.used_capture_targets.push(resolution);
+        }
+
+        let capture_target = match self.capture_targets.entry(resolution) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(AssociatedSceneData::new(state, resolution, resolution)?)
+            }
+        };
+
+        Ok((capture_target, self.probes.get_mut(&key).unwrap()))
+    }
+
+    /// Selects probes with highest priority (and closest to the camera) for the ambient light
+    /// shader.
+    pub(crate) fn select(
+        &self,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        camera: &Camera,
+        active: &mut ActiveReflectionProbes,
+    ) {
+        active.clear();
+
+        if self.probes.is_empty() {
+            return;
+        }
+
+        let camera_position = camera.global_position();
+        let mut candidates = scene
+            .graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                let probe = node.cast::<ReflectionProbe>()?;
+                if !probe.global_visibility() {
+                    return None;
+                }
+                let captured = self.probes.get(&(scene_handle, handle))?;
+                let distance = probe
+                    .world_bounding_box()
+                    .center()
+                    .metric_distance(&camera_position);
+                Some((probe, captured, distance))
+            })
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|(a, _, a_distance), (b, _, b_distance)| {
+            b.priority().cmp(&a.priority()).then(
+                a_distance
+                    .partial_cmp(b_distance)
+                    .unwrap_or(Ordering::Equal),
+            )
+        });
+
+        for (probe, captured, _) in candidates.into_iter().take(MAX_REFLECTION_PROBES) {
+            let transform = probe.global_transform();
+            let cube_map = captured.cube_map();
+            let last_mip = cube_map.borrow().mip_count().saturating_sub(1);
+
+            active.cube_maps.push(cube_map);
+            active
+                .inv_world_matrices
+                .push(transform.try_inverse().unwrap_or_else(Matrix4::identity));
+            active.positions.push(probe.global_position());
+            active.sizes.push(Vector3::new(
+                transform.side().norm(),
+                transform.up().norm(),
+                transform.look().norm(),
+            ));
+            active.params.push(Vector3::new(
+                probe.blend_distance(),
+                if probe.is_box_projection_enabled() {
+                    1.0
+                } else {
+                    0.0
+                },
+                last_mip as f32,
+            ));
+        }
+    }
+}
+
+/// Creates a camera, that renders a face of the cube map of the probe. Skybox and procedural sky
+/// are copied from the given camera.
+pub(crate) fn make_capture_camera(
+    probe: &ReflectionProbe,
+    face_index: usize,
+    sky_source: Option<&Camera>,
+) -> (CubeMapFace, Camera) {
+    let (face, look, up) = cube_map_faces()[face_index];
+    let side = up.cross(&look);
+    let position = probe.global_position();
+
+    let mut builder = CameraBuilder::new(BaseBuilder::new())
+        .with_fov(std::f32::consts::FRAC_PI_2)
+        .with_z_near(probe.z_near())
+        .with_z_far(probe.z_far());
+    if let Some(sky_source) = sky_source {
+        if let Some(skybox) = sky_source.skybox_ref() {
+            builder = builder.with_skybox(skybox.clone());
+        }
+        if let Some(procedural_sky) = sky_source.procedural_sky_ref() {
+            builder = builder.with_procedural_sky(procedural_sky.clone());
+        }
+    }
+    let mut camera = builder.build_camera();
+
+    #[rustfmt::skip]
+    let transform = Matrix4::new(
+        side.x, up.x, look.x, position.x,
+        side.y, up.y, look.y, position.y,
+        side.z, up.z, look.z, position.z,
+        0.0, 0.0, 0.0, 1.0,
+    );
+    camera.global_transform.set(transform);
+
+    let resolution = probe.resolution() as f32;
+    camera.calculate_matrices(Vector2::new(resolution, resolution));
+    camera.visibility_cache = probe.visibility_cache().clone();
+
+    (face, camera)
+}
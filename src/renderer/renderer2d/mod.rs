@@ -3,9 +3,10 @@
 use crate::renderer::framework::framebuffer::BlendParameters;
 use crate::{
     core::{
-        algebra::{Vector2, Vector3, Vector4},
+        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
         color::Color,
-        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, Rect},
+        math::{frustum::Frustum, Rect},
+        scope_profile,
         sstorage::ImmutableString,
     },
     renderer::{
@@ -25,10 +26,9 @@ use crate::{
         graph::Graph,
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
     },
-    utils::value_as_u8_slice,
 };
-use fxhash::{FxHashMap, FxHasher};
-use std::{cell::RefCell, cmp::Ordering, hash::Hasher, rc::Rc};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
 
 mod cache;
 
@@ -76,13 +76,39 @@ pub(crate) struct Renderer2d {
     quad: Mesh,
     geometry_cache: GeometryCache,
     batch_storage: SpriteBatchStorage,
-    instance_data_set: Vec<InstanceData>,
 }
 
+/// View of a sorting layer, defined by the camera.
+#[derive(Copy, Clone)]
+struct LayerView {
+    view_projection: Matrix4<f32>,
+    frustum: Frustum,
+    depth_test: bool,
+}
+
+struct SortableInstance {
+    layer: i32,
+    order: i32,
+    depth: f32,
+    texture: Rc<RefCell<GpuTexture>>,
+    gpu_data: InstanceData,
+}
+
+struct Batch {
+    instances: Vec<InstanceData>,
+    texture: Rc<RefCell<GpuTexture>>,
+    layer: i32,
+    view: LayerView,
+}
+
+/// Batch storage collects visible rectangles, sorts them by sorting layer, order in layer and depth,
+/// and then merges consecutive rectangles with the same texture into a single batch (draw call).
 #[derive(Default)]
 struct SpriteBatchStorage {
+    instances: Vec<SortableInstance>,
+    layer_views: FxHashMap<i32, Option<LayerView>>,
     batches: Vec<Batch>,
-    index_map: FxHashMap<u64, usize>,
+    batch_count: usize,
 }
 
 impl SpriteBatchStorage {
@@ -90,18 +116,55 @@ impl SpriteBatchStorage {
         &mut self,
         state: &mut PipelineState,
         graph: &Graph,
+        camera: &Camera,
+        frame_size: Vector2<f32>,
         texture_cache: &mut TextureCache,
         white_dummy: Rc<RefCell<GpuTexture>>,
     ) {
-        self.index_map.clear();
-        for batch in self.batches.iter_mut() {
-            batch.instances.clear();
-        }
+        scope_profile!();
+
+        self.instances.clear();
+        self.layer_views.clear();
+
+        let default_view = LayerView {
+            view_projection: camera.view_projection_matrix(),
+            frustum: Frustum::from(camera.view_projection_matrix()).unwrap_or_default(),
+            depth_test: true,
+        };
 
-        let mut batch_index = 0;
         for node in graph.linear_iter() {
             if let Some(rectangle) = node.cast::<Rectangle>() {
-                if !rectangle.global_visibility() {
+                if !rectangle.global_visibility() || !camera.can_see_layer(rectangle.layer()) {
+                    continue;
+                }
+
+                let layer = rectangle.sorting_layer();
+
+                let view = *self.layer_views.entry(layer).or_insert_with(|| {
+                    match camera.sorting_layer_view(layer) {
+                        Some(layer_view) if !layer_view.visible => None,
+                        Some(layer_view) if layer_view.has_custom_view() => {
+                            let view_projection =
+                                camera.sorting_layer_view_projection_matrix(layer_view, frame_size);
+                            Some(LayerView {
+                                view_projection,
+                                frustum: Frustum::from(view_projection).unwrap_or_default(),
+                                depth_test: false,
+                            })
+                        }
+                        _ => Some(default_view),
+                    }
+                });
+
+                let view = match view {
+                    Some(view) => view,
+                    None => continue,
+                };
+
+                if !view
+                    .frustum
+                    .is_intersects_aabb(&rectangle.world_bounding_box())
+                {
                     continue;
                 }
 
@@ -114,71 +177,73 @@ impl SpriteBatchStorage {
                     },
                 );
 
-                let z = rectangle.global_position().z;
-
-                let mut hasher = FxHasher::default();
-                // Objects with different Z coordinate will go into separate batches.
-                hasher.write(value_as_u8_slice(&z));
-                // Objects with different textures will go into separate batches.
-                hasher.write_u64(&*texture.borrow() as *const _ as u64);
-                let batch_id = hasher.finish();
-
-                let index = *self.index_map.entry(batch_id).or_insert_with(|| {
-                    let index = batch_index;
-                    batch_index += 1;
-                    index as usize
-                });
-
-                // Reuse old batches to prevent redundant memory allocations
-                let batch = if let Some(batch) = self.batches.get_mut(index) {
-                    batch.texture = texture.clone();
-                    batch.z = z;
-                    batch
-                } else {
-                    self.batches.push(Batch {
-                        instances: Default::default(),
-                        texture: texture.clone(),
-                        z,
-                    });
-                    self.batches.last_mut().unwrap()
-                };
+                let depth = view
+                    .view_projection
+                    .transform_point(&Point3::from(rectangle.global_position()))
+                    .z;
 
                 let uv_rect = rectangle.uv_rect();
                 let uv_transform = Vector4::new(uv_rect.x(), uv_rect.y(), uv_rect.w(), uv_rect.h());
 
-                batch.instances.push(Instance {
+                self.instances.push(SortableInstance {
+                    layer,
+                    order: rectangle.order_in_layer(),
+                    depth,
+                    texture,
                     gpu_data: InstanceData {
                         color: rectangle.color().srgb_to_linear(),
                         uv_transform,
                         world_matrix: rectangle.global_transform(),
                     },
-                    aabb: rectangle.world_bounding_box(),
                 });
             }
         }
 
-        // Sort back-to-front for correct blending.
-        self.batches.sort_by(|a, b| {
-            if a.z < b.z {
-                Ordering::Greater
-            } else if a.z > b.z {
-                Ordering::Less
+        // Sort by layers first, then by order in layer and finally back-to-front for correct
+        // blending. Sorting is stable, so objects with equal keys keep their order in the graph.
+        self.instances.sort_by(|a, b| {
+            a.layer
+                .cmp(&b.layer)
+                .then(a.order.cmp(&b.order))
+                .then(b.depth.partial_cmp(&a.depth).unwrap_or(Ordering::Equal))
+        });
+
+        // Merge consecutive instances with the same texture into batches. Old batches are reused to
+        // prevent redundant memory allocations.
+        self.batch_count = 0;
+        for instance in self.instances.drain(..) {
+            if self.batch_count > 0 {
+                let batch = &mut self.batches[self.batch_count - 1];
+                if batch.layer == instance.layer && Rc::ptr_eq(&batch.texture, &instance.texture) {
+                    batch.instances.push(instance.gpu_data);
+                    continue;
+                }
+            }
+
+            let view = self.layer_views[&instance.layer].unwrap();
+
+            if let Some(batch) = self.batches.get_mut(self.batch_count) {
+                batch.instances.clear();
+                batch.instances.push(instance.gpu_data);
+                batch.texture = instance.texture;
+                batch.layer = instance.layer;
+                batch.view = view;
             } else {
-                Ordering::Equal
+                self.batches.push(Batch {
+                    instances: vec![instance.gpu_data],
+                    texture: instance.texture,
+                    layer: instance.layer,
+                    view,
+                });
             }
-        })
-    }
-}
 
-struct Instance {
-    gpu_data: InstanceData,
-    aabb: AxisAlignedBoundingBox,
-}
+            self.batch_count += 1;
+        }
+    }
 
-struct Batch {
-    instances: Vec<Instance>,
-    texture: Rc<RefCell<GpuTexture>>,
-    z: f32,
+    fn batches(&self) -> &[Batch] {
+        &self.batches[..self.batch_count]
+    }
 }
 
 impl Renderer2d {
@@ -188,7 +253,6 @@ impl Renderer2d {
             quad: Mesh::new_unit_quad(),
             geometry_cache: Default::default(),
             batch_storage: Default::default(),
-            instance_data_set: Default::default(),
         })
     }
 
@@ -210,10 +274,16 @@ impl Renderer2d {
         let mut stats = RenderPassStatistics::default();
         let quad = self.geometry_cache.get(state, &self.quad);
 
-        self.batch_storage
-            .generate_batches(state, graph, texture_cache, white_dummy);
+        let frame_size = Vector2::new(viewport.w() as f32, viewport.h() as f32);
 
-        let view_projection = camera.view_projection_matrix();
+        self.batch_storage.generate_batches(
+            state,
+            graph,
+            camera,
+            frame_size,
+            texture_cache,
+            white_dummy,
+        );
 
         let frustum = Frustum::from(camera.view_projection_matrix()).unwrap_or_default();
 
@@ -268,52 +338,40 @@ impl Renderer2d {
             }
         }
 
-        for batch in self.batch_storage.batches.iter() {
-            self.instance_data_set.clear();
-            for instance in batch.instances.iter() {
-                if frustum.is_intersects_aabb(&instance.aabb) {
-                    self.instance_data_set.push(instance.gpu_data.clone());
-                }
-            }
-
-            quad.set_buffer_data(state, 1, &self.instance_data_set);
-
-            if !self.instance_data_set.is_empty() {
-                let shader = &self.sprite_shader;
-                stats += frame_buffer.draw_instances(
-                    self.instance_data_set.len(),
-                    quad,
-                    state,
-                    viewport,
-                    &shader.program,
-                    &DrawParameters {
-                        cull_face: None,
-                        color_write: Default::default(),
-                        depth_write: true,
-                        stencil_test: None,
-                        depth_test: true,
-                        blend: Some(BlendParameters {
-                            func: BlendFunc::new(
-                                BlendFactor::SrcAlpha,
-                                BlendFactor::OneMinusSrcAlpha,
-                            ),
-                            ..Default::default()
-                        }),
-                        stencil_op: Default::default(),
-                    },
-                    |mut program_binding| {
-                        program_binding
-                            .set_matrix4(&shader.wvp_matrix, &view_projection)
-                            .set_texture(&shader.diffuse_texture, &batch.texture)
-                            .set_i32(&shader.light_count, light_count as i32)
-                            .set_vector4_slice(&shader.light_color_radius, &light_color_radius)
-                            .set_vector3_slice(&shader.light_direction, &light_direction)
-                            .set_vector3_slice(&shader.light_position, &light_position)
-                            .set_vector2_slice(&shader.light_parameters, &light_parameters)
-                            .set_vector3(&shader.ambient_light_color, &ambient_color.as_frgb());
-                    },
-                );
-            }
+        for batch in self.batch_storage.batches() {
+            quad.set_buffer_data(state, 1, &batch.instances);
+
+            let shader = &self.sprite_shader;
+            stats += frame_buffer.draw_instances(
+                batch.instances.len(),
+                quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: batch.view.depth_test,
+                    stencil_test: None,
+                    depth_test: batch.view.depth_test,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &batch.view.view_projection)
+                        .set_texture(&shader.diffuse_texture, &batch.texture)
+                        .set_i32(&shader.light_count, light_count as i32)
+                        .set_vector4_slice(&shader.light_color_radius, &light_color_radius)
+                        .set_vector3_slice(&shader.light_direction, &light_direction)
+                        .set_vector3_slice(&shader.light_position, &light_position)
+                        .set_vector2_slice(&shader.light_parameters, &light_parameters)
+                        .set_vector3(&shader.ambient_light_color, &ambient_color.as_frgb());
+                },
+            );
         }
 
         Ok(stats)
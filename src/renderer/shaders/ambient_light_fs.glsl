@@ -1,18 +1,200 @@
+// Keep in sync with MAX_PROBE_VOLUMES in renderer/light/probe.rs
+#define MAX_PROBE_VOLUMES 4
+// Keep in sync with MAX_REFLECTION_PROBES in renderer/reflection_probe.rs
+#define MAX_REFLECTION_PROBES 4
+
 uniform sampler2D diffuseTexture;
 uniform sampler2D aoSampler;
 uniform sampler2D ambientTexture;
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D probeTexture;
+uniform sampler2D materialTexture;
 uniform vec4 ambientColor;
+uniform mat4 invViewProj;
+uniform vec3 cameraPosition;
+uniform int probeVolumeCount;
+uniform mat4 probeInvWorld[MAX_PROBE_VOLUMES];
+// Four values per volume: resolution along X, Y, Z and first row in the probe texture.
+uniform int probeVolumeInfo[MAX_PROBE_VOLUMES * 4];
+// Reflection probes are sorted by priority.
+uniform int reflectionProbeCount;
+uniform samplerCube reflectionProbeTextures[MAX_REFLECTION_PROBES];
+uniform mat4 reflectionProbeInvWorld[MAX_REFLECTION_PROBES];
+uniform vec3 reflectionProbePosition[MAX_REFLECTION_PROBES];
+uniform vec3 reflectionProbeSize[MAX_REFLECTION_PROBES];
+// x - blend distance, y - box projection, z - index of the last mip level.
+uniform vec3 reflectionProbeParams[MAX_REFLECTION_PROBES];
+// Environment map of the camera is used for reflections that are not covered by probes.
+uniform bool environmentMapEnabled;
+uniform samplerCube environmentMap;
+uniform float environmentMapMaxLod;
 
 out vec4 FragColor;
 in vec2 texCoord;
 
+vec3 EvaluateProbe(int row, vec3 n)
+{
+    // Coefficients are pre-multiplied by diffuse convolution factors.
+    vec3 result = texelFetch(probeTexture, ivec2(0, row), 0).rgb * 0.282095;
+    result += texelFetch(probeTexture, ivec2(1, row), 0).rgb * 0.488603 * n.y;
+    result += texelFetch(probeTexture, ivec2(2, row), 0).rgb * 0.488603 * n.z;
+    result += texelFetch(probeTexture, ivec2(3, row), 0).rgb * 0.488603 * n.x;
+    result += texelFetch(probeTexture, ivec2(4, row), 0).rgb * 1.092548 * n.x * n.y;
+    result += texelFetch(probeTexture, ivec2(5, row), 0).rgb * 1.092548 * n.y * n.z;
+    result += texelFetch(probeTexture, ivec2(6, row), 0).rgb * 0.315392 * (3.0 * n.z * n.z - 1.0);
+    result += texelFetch(probeTexture, ivec2(7, row), 0).rgb * 1.092548 * n.x * n.z;
+    result += texelFetch(probeTexture, ivec2(8, row), 0).rgb * 0.546274 * (n.x * n.x - n.y * n.y);
+    return max(result, vec3(0.0));
+}
+
+bool SampleProbes(vec3 worldPosition, vec3 n, out vec3 diffuse)
+{
+    for (int i = 0; i < probeVolumeCount; ++i)
+    {
+        vec3 local = (probeInvWorld[i] * vec4(worldPosition, 1.0)).xyz;
+        if (any(lessThan(local, vec3(-0.5))) || any(greaterThan(local, vec3(0.5)))) {
+            continue;
+        }
+
+        ivec3 resolution = ivec3(probeVolumeInfo[i * 4], probeVolumeInfo[i * 4 + 1], probeVolumeInfo[i * 4 + 2]);
+        int firstRow = probeVolumeInfo[i * 4 + 3];
+
+        vec3 grid = clamp((local + 0.5) * vec3(resolution - 1), vec3(0.0), vec3(resolution - 1));
+        ivec3 cell = min(ivec3(floor(grid)), resolution - 2);
+        vec3 t = grid - vec3(cell);
+
+        diffuse = vec3(0.0);
+        for (int corner = 0; corner < 8; ++corner)
+        {
+            ivec3 offset = ivec3(corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            ivec3 probe = cell + offset;
+            vec3 w = mix(1.0 - t, t, vec3(offset));
+            int row = firstRow + (probe.z * resolution.y + probe.y) * resolution.x + probe.x;
+            diffuse += w.x * w.y * w.z * EvaluateProbe(row, n);
+        }
+        return true;
+    }
+    return false;
+}
+
+// Returns weight of the probe at the given point and the direction to fetch the cube map with.
+float ReflectionProbeWeight(int i, vec3 worldPosition, vec3 reflection, out vec3 direction)
+{
+    vec3 local = (reflectionProbeInvWorld[i] * vec4(worldPosition, 1.0)).xyz;
+    vec3 borderDistance = (0.5 - abs(local)) * reflectionProbeSize[i];
+    float distance = min(borderDistance.x, min(borderDistance.y, borderDistance.z));
+    if (distance <= 0.0) {
+        return 0.0;
+    }
+
+    direction = reflection;
+    if (reflectionProbeParams[i].y > 0.5) {
+        // Intersect the reflection ray with the box of the probe in its local space and fetch
+        // the cube map in the direction of the intersection point.
+        vec3 localReflection = (reflectionProbeInvWorld[i] * vec4(reflection, 0.0)).xyz;
+        vec3 first = (vec3(0.5) - local) / localReflection;
+        vec3 second = (vec3(-0.5) - local) / localReflection;
+        vec3 furthest = max(first, second);
+        float t = min(furthest.x, min(furthest.y, furthest.z));
+        direction = worldPosition + reflection * t - reflectionProbePosition[i];
+    }
+
+    float blendDistance = reflectionProbeParams[i].x;
+    return blendDistance > 0.0 ? clamp(distance / blendDistance, 0.0, 1.0) : 1.0;
+}
+
+vec3 SampleReflectionProbe(samplerCube cubeMap, int i, vec3 direction, float roughness)
+{
+    return textureLod(cubeMap, direction, roughness * reflectionProbeParams[i].z).rgb;
+}
+
+vec3 SampleReflectionProbes(vec3 worldPosition, vec3 reflection, float roughness, out float remaining)
+{
+    // Samplers could be indexed only with constant expressions, so the loop is unrolled. Probes
+    // with higher priority take as much weight as they can, the rest is left for the next ones.
+    vec3 result = vec3(0.0);
+    remaining = 1.0;
+    vec3 direction;
+    float weight;
+
+    if (reflectionProbeCount > 0) {
+        weight = ReflectionProbeWeight(0, worldPosition, reflection, direction) * remaining;
+        if (weight > 0.0) {
+            result += weight * SampleReflectionProbe(reflectionProbeTextures[0], 0, direction, roughness);
+            remaining -= weight;
+        }
+    }
+    if (reflectionProbeCount > 1 && remaining > 0.0) {
+        weight = ReflectionProbeWeight(1, worldPosition, reflection, direction) * remaining;
+        if (weight > 0.0) {
+            result += weight * SampleReflectionProbe(reflectionProbeTextures[1], 1, direction, roughness);
+            remaining -= weight;
+        }
+    }
+    if (reflectionProbeCount > 2 && remaining > 0.0) {
+        weight = ReflectionProbeWeight(2, worldPosition, reflection, direction) * remaining;
+        if (weight > 0.0) {
+            result += weight * SampleReflectionProbe(reflectionProbeTextures[2], 2, direction, roughness);
+            remaining -= weight;
+        }
+    }
+    if (reflectionProbeCount > 3 && remaining > 0.0) {
+        weight = ReflectionProbeWeight(3, worldPosition, reflection, direction) * remaining;
+        if (weight > 0.0) {
+            result += weight * SampleReflectionProbe(reflectionProbeTextures[3], 3, direction, roughness);
+            remaining -= weight;
+        }
+    }
+
+    return result;
+}
+
 void main()
 {
     float ambientOcclusion = texture(aoSampler, texCoord).r;
     vec4 ambientPixel = texture(ambientTexture, texCoord);
-    FragColor = (ambientColor + ambientPixel) * texture(diffuseTexture, texCoord);
+
+    vec4 albedo = texture(diffuseTexture, texCoord);
+
+    vec4 ambient = ambientColor;
+    vec3 specular = vec3(0.0);
+    if (probeVolumeCount > 0 || reflectionProbeCount > 0 || environmentMapEnabled) {
+        vec3 worldPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), invViewProj);
+        vec3 normal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+
+        vec3 probeDiffuse;
+        if (probeVolumeCount > 0 && SampleProbes(worldPosition, normal, probeDiffuse)) {
+            ambient = vec4(probeDiffuse, ambientColor.a);
+        }
+
+        if (reflectionProbeCount > 0 || environmentMapEnabled) {
+            vec3 material = texture(materialTexture, texCoord).rgb;
+            float metallic = material.x;
+            float roughness = material.y;
+
+            vec3 viewDirection = normalize(worldPosition - cameraPosition);
+            vec3 reflection = reflect(viewDirection, normal);
+
+            // Fresnel-Schlick with roughness.
+            vec3 F0 = mix(vec3(0.04), albedo.rgb, metallic);
+            float cosTheta = clamp(dot(normal, -viewDirection), 0.0, 1.0);
+            vec3 F = F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(1.0 - cosTheta, 5.0);
+
+            float remaining;
+            vec3 reflected = SampleReflectionProbes(worldPosition, reflection, roughness, remaining);
+            if (environmentMapEnabled && remaining > 0.0) {
+                reflected += remaining * textureLod(environmentMap, reflection, roughness * environmentMapMaxLod).rgb;
+            }
+
+            specular = F * reflected;
+        }
+    }
+
+    FragColor = (ambient + ambientPixel) * albedo;
+    FragColor.rgb += specular;
     FragColor.rgb *= ambientOcclusion;
     FragColor.a = ambientPixel.a;
 
     // TODO: Implement IBL.
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
 uniform sampler2D hdrSampler;
+uniform float threshold;
 
 in vec2 texCoord;
 
@@ -7,7 +8,7 @@ out vec4 outBrightColor;
 void main() {
     vec3 hdrPixel = texture(hdrSampler, texCoord).rgb;
 
-    if (S_Luminance(hdrPixel) > 1.0) {
+    if (S_Luminance(hdrPixel) > threshold) {
         outBrightColor = vec4(hdrPixel, 0.0);
     } else {
         outBrightColor = vec4(0.0);
@@ -0,0 +1,31 @@
+layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec4 outNormal;
+layout(location = 2) out vec4 outAmbient;
+layout(location = 3) out vec4 outMaterial;
+layout(location = 4) out uint outDecalMask;
+
+uniform sampler2D diffuseTexture;
+uniform sampler2D normalTexture;
+
+in vec3 normal;
+in vec3 tangent;
+in vec3 binormal;
+in vec2 texCoord;
+
+void main()
+{
+    outColor = texture(diffuseTexture, texCoord);
+    if (outColor.a < 0.5) {
+        discard;
+    }
+    outColor.a = 1.0;
+
+    mat3 tangentSpace = mat3(tangent, binormal, normal);
+    vec3 n = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    outNormal = vec4(normalize(tangentSpace * n) * 0.5 + 0.5, 1.0);
+
+    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+    // Metallic, roughness, ambient occlusion.
+    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+    outDecalMask = 0u;
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 1) in vec2 vertexTexCoord;
+layout(location = 2) in vec3 vertexNormal;
+layout(location = 3) in vec4 vertexTangent;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+layout(location = 7) in mat4 worldMatrix;
+// x - first frame, y - frame count, z - frame rate, w - animation time.
+layout(location = 11) in vec4 animation;
+layout(location = 12) in float looped;
+
+uniform mat4 viewProjection;
+uniform sampler2D animationTexture;
+
+out vec3 normal;
+out vec3 tangent;
+out vec3 binormal;
+out vec2 texCoord;
+
+// Each bone matrix is stored as three rows of an affine matrix.
+mat4 FetchBoneMatrix(int frame, int bone)
+{
+    int x = bone * 3;
+    vec4 row0 = texelFetch(animationTexture, ivec2(x, frame), 0);
+    vec4 row1 = texelFetch(animationTexture, ivec2(x + 1, frame), 0);
+    vec4 row2 = texelFetch(animationTexture, ivec2(x + 2, frame), 0);
+    return transpose(mat4(row0, row1, row2, vec4(0.0, 0.0, 0.0, 1.0)));
+}
+
+mat4 BlendedBoneMatrix(int frameA, int frameB, float t, int bone)
+{
+    return mix(FetchBoneMatrix(frameA, bone), FetchBoneMatrix(frameB, bone), t);
+}
+
+void main()
+{
+    float lastFrame = max(animation.y - 1.0, 0.0);
+    float frame = animation.w * animation.z;
+    if (looped > 0.5)
+    {
+        frame = lastFrame > 0.0 ? mod(frame, lastFrame) : 0.0;
+    }
+    else
+    {
+        frame = clamp(frame, 0.0, lastFrame);
+    }
+
+    int firstFrame = int(animation.x);
+    int frameA = firstFrame + int(floor(frame));
+    int frameB = firstFrame + min(int(floor(frame)) + 1, int(lastFrame));
+    float t = fract(frame);
+
+    mat4 m0 = BlendedBoneMatrix(frameA, frameB, t, int(boneIndices.x));
+    mat4 m1 = BlendedBoneMatrix(frameA, frameB, t, int(boneIndices.y));
+    mat4 m2 = BlendedBoneMatrix(frameA, frameB, t, int(boneIndices.z));
+    mat4 m3 = BlendedBoneMatrix(frameA, frameB, t, int(boneIndices.w));
+
+    vec4 vertex = vec4(vertexPosition, 1.0);
+    vec4 localPosition = m0 * vertex * boneWeights.x
+        + m1 * vertex * boneWeights.y
+        + m2 * vertex * boneWeights.z
+        + m3 * vertex * boneWeights.w;
+
+    vec3 localNormal = mat3(m0) * vertexNormal * boneWeights.x
+        + mat3(m1) * vertexNormal * boneWeights.y
+        + mat3(m2) * vertexNormal * boneWeights.z
+        + mat3(m3) * vertexNormal * boneWeights.w;
+
+    vec3 localTangent = mat3(m0) * vertexTangent.xyz * boneWeights.x
+        + mat3(m1) * vertexTangent.xyz * boneWeights.y
+        + mat3(m2) * vertexTangent.xyz * boneWeights.z
+        + mat3(m3) * vertexTangent.xyz * boneWeights.w;
+
+    mat3 nm = mat3(worldMatrix);
+    normal = normalize(nm * localNormal);
+    tangent = normalize(nm * localTangent);
+    binormal = normalize(vertexTangent.w * cross(tangent, normal));
+    texCoord = vertexTexCoord;
+
+    gl_Position = viewProjection * worldMatrix * localPosition;
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+// Debug visualization of a frame. The visualization is selected by mode uniform:
+// 0 - albedo, 1 - normals, 2 - roughness and metallic, 3 - overdraw heat map,
+// 4 - light count heat map, 5 - shadow cascades.
+
+uniform sampler2D depthTexture;
+uniform sampler2D colorTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+uniform sampler2D countTexture;
+uniform sampler2D frameTexture;
+uniform int mode;
+uniform float baseCount;
+uniform float maxCount;
+uniform mat4 invViewProj;
+uniform mat4 viewMatrix;
+uniform bool cascadesEnabled;
+uniform float cascadeDistances[3];
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+// Maps [0; 1] range to blue -> cyan -> green -> yellow -> red gradient.
+vec3 HeatMap(float t)
+{
+    t = clamp(t, 0.0, 1.0);
+    return clamp(vec3(
+        min(4.0 * t - 1.5, -4.0 * t + 4.5),
+        min(4.0 * t - 0.5, -4.0 * t + 3.5),
+        min(4.0 * t + 0.5, -4.0 * t + 2.5)
+    ), 0.0, 1.0);
+}
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+
+    vec3 color = vec3(0.0);
+    if (mode == 0) {
+        color = S_LinearToSRGB(vec4(texture(colorTexture, texCoord).rgb, 1.0)).rgb;
+    } else if (mode == 1) {
+        color = texture(normalTexture, texCoord).rgb;
+    } else if (mode == 2) {
+        vec2 material = texture(materialTexture, texCoord).xy;
+        color = vec3(material.x, material.y, 0.0);
+    } else if (mode == 3 || mode == 4) {
+        float count = texture(countTexture, texCoord).r;
+        if (mode == 4) {
+            count = depth >= 1.0 ? 0.0 : count + baseCount;
+        }
+        color = count > 0.0 ? HeatMap((count - 1.0) / max(maxCount - 1.0, 1.0)) : vec3(0.0);
+    } else if (mode == 5) {
+        color = texture(frameTexture, texCoord).rgb;
+
+        if (cascadesEnabled && depth < 1.0) {
+            vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), invViewProj);
+            float fragmentZViewSpace = abs((viewMatrix * vec4(fragmentPosition, 1.0)).z);
+
+            vec3 tint = vec3(0.0);
+            if (fragmentZViewSpace <= cascadeDistances[0]) {
+                tint = vec3(1.0, 0.0, 0.0);
+            } else if (fragmentZViewSpace <= cascadeDistances[1]) {
+                tint = vec3(0.0, 1.0, 0.0);
+            } else if (fragmentZViewSpace <= cascadeDistances[2]) {
+                tint = vec3(0.0, 0.0, 1.0);
+            }
+
+            if (tint != vec3(0.0)) {
+                color = mix(color, tint, 0.35);
+            }
+        }
+    }
+
+    FragColor = vec4(color, 1.0);
+}
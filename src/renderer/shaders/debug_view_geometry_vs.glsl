@@ -0,0 +1,26 @@
+// Transforms a vertex of a mesh (including skinned ones) for wireframe and overdraw debug views.
+
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+
+uniform mat4 worldViewProjection;
+uniform bool useSkeletalAnimation;
+uniform mat4 boneMatrices[60];
+
+void main()
+{
+    vec4 localPosition = vec4(vertexPosition, 1.0);
+
+    if (useSkeletalAnimation)
+    {
+        vec4 vertex = localPosition;
+
+        localPosition = boneMatrices[int(boneIndices.x)] * vertex * boneWeights.x
+            + boneMatrices[int(boneIndices.y)] * vertex * boneWeights.y
+            + boneMatrices[int(boneIndices.z)] * vertex * boneWeights.z
+            + boneMatrices[int(boneIndices.w)] * vertex * boneWeights.w;
+    }
+
+    gl_Position = worldViewProjection * localPosition;
+}
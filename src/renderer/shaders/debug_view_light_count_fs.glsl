@@ -0,0 +1,30 @@
+// Adds one to every pixel affected by a point or a spot light. Spot lights have cone angle cosine
+// greater than -1.0.
+
+uniform sampler2D depthTexture;
+uniform mat4 invViewProj;
+uniform vec3 lightPosition;
+uniform float lightRadius;
+uniform vec3 lightDirection;
+uniform float halfConeAngleCos;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), invViewProj);
+    vec3 fragmentToLight = lightPosition - fragmentPosition;
+    float distance = length(fragmentToLight);
+
+    if (distance > lightRadius || dot(lightDirection, fragmentToLight / max(distance, 0.00001)) < halfConeAngleCos) {
+        discard;
+    }
+
+    FragColor = vec4(1.0);
+}
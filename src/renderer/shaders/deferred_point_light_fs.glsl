@@ -2,7 +2,7 @@ uniform sampler2D depthTexture;
 uniform sampler2D colorTexture;
 uniform sampler2D normalTexture;
 uniform sampler2D materialTexture;
-uniform samplerCube pointShadowTexture;
+uniform sampler2D pointShadowTexture;
 
 uniform vec3 lightPos;
 uniform float lightRadius;
@@ -13,6 +13,7 @@ uniform bool softShadows;
 uniform bool shadowsEnabled;
 uniform float shadowBias;
 uniform float lightIntensity;
+uniform vec4 shadowAtlasFaces[6];
 
 in vec2 texCoord;
 out vec4 FragColor;
@@ -38,8 +39,8 @@ void main()
 
     float distanceAttenuation = S_LightDistanceAttenuation(distance, lightRadius);
 
-    float shadow = S_PointShadow(
-        shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, pointShadowTexture);
+    float shadow = S_PointShadowAtlas(
+        shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, shadowAtlasFaces, pointShadowTexture);
 
     FragColor = vec4(lightIntensity * distanceAttenuation * shadow * lighting, 1.0);
 }
@@ -17,6 +17,7 @@ uniform vec3 cameraPosition;
 uniform bool shadowsEnabled;
 uniform bool softShadows;
 uniform float shadowMapInvSize;
+uniform vec4 shadowAtlasRegion;
 uniform float shadowBias;
 uniform bool cookieEnabled;
 uniform float lightIntensity;
@@ -48,9 +49,9 @@ void main()
     float spotAngleCos = dot(lightDirection, ctx.fragmentToLight);
     float coneFactor = smoothstep(halfConeAngleCos, halfHotspotConeAngleCos, spotAngleCos);
 
-    float shadow = S_SpotShadowFactor(
+    float shadow = S_SpotShadowFactorAtlas(
         shadowsEnabled, softShadows, shadowBias, fragmentPosition,
-            lightViewProjMatrix, shadowMapInvSize, spotShadowTexture);
+            lightViewProjMatrix, shadowMapInvSize, shadowAtlasRegion, spotShadowTexture);
 
     vec4 cookieAttenuation = vec4(1.0);
     if (cookieEnabled) {
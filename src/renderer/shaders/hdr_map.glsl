@@ -1,6 +1,7 @@
 uniform sampler2D hdrSampler;
 uniform sampler2D lumSampler;
 uniform sampler2D bloomSampler;
+uniform float bloomIntensity;
 uniform sampler3D colorMapSampler;
 uniform bool useColorGrading;
 uniform float keyValue;
@@ -25,7 +26,7 @@ vec3 ColorGrading(vec3 color) {
 void main() {
     vec4 hdrColor = texture(hdrSampler, texCoord);
 
-    hdrColor += texture(bloomSampler, texCoord);
+    hdrColor += bloomIntensity * texture(bloomSampler, texCoord);
 
     float luminance = texture(lumSampler, vec2(0.5, 0.5)).r;
 
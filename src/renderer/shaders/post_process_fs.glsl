@@ -0,0 +1,47 @@
+// Built-in post-processing effects. Every effect is rendered in a separate pass, the effect is
+// selected by effectKind uniform:
+// 0 - vignette, 1 - chromatic aberration, 2 - film grain, 3 - motion blur.
+
+uniform sampler2D frameTexture;
+uniform sampler2D velocityTexture;
+uniform int sampleCount;
+uniform int effectKind;
+uniform float intensity;
+uniform float smoothness;
+uniform vec4 vignetteColor;
+uniform float time;
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+void main() {
+    vec4 color = texture(frameTexture, texCoord);
+
+    if (effectKind == 0) {
+        // Normalized distance to the center of the frame, 1.0 at corners.
+        float distance = length(texCoord - vec2(0.5)) * 1.41421356;
+        float start = 1.0 - max(smoothness, 0.0001);
+        float factor = clamp((distance - start) / max(smoothness, 0.0001), 0.0, 1.0);
+        color.rgb = mix(color.rgb, vignetteColor.rgb, factor * intensity);
+    } else if (effectKind == 1) {
+        vec2 direction = texCoord - vec2(0.5);
+        color.r = texture(frameTexture, texCoord + direction * intensity).r;
+        color.b = texture(frameTexture, texCoord - direction * intensity).b;
+    } else if (effectKind == 2) {
+        float noise = fract(sin(dot(texCoord + vec2(time), vec2(12.9898, 78.233))) * 43758.5453);
+        color.rgb = clamp(color.rgb + vec3((noise - 0.5) * intensity), 0.0, 1.0);
+    } else if (effectKind == 3 && sampleCount > 1) {
+        // Velocity points from the previous position of the pixel to the current one, blur is
+        // centered at the current position. Intensity is the shutter.
+        vec2 velocity = texture(velocityTexture, texCoord).xy * intensity;
+        vec3 sum = vec3(0.0);
+        for (int i = 0; i < sampleCount; ++i) {
+            float t = float(i) / float(sampleCount - 1) - 0.5;
+            sum += texture(frameTexture, texCoord - velocity * t).rgb;
+        }
+        color.rgb = sum / float(sampleCount);
+    }
+
+    FragColor = color;
+}
@@ -0,0 +1,117 @@
+// Single scattering atmosphere, keep in sync with scene/sky.rs
+
+#define PLANET_RADIUS 6371e3
+#define ATMOSPHERE_RADIUS 6471e3
+#define VIEWER_HEIGHT 1000.0
+#define PRIMARY_STEPS 16
+#define LIGHT_STEPS 8
+#define MIE_EXTINCTION_FACTOR 1.1
+#define PI 3.14159265359
+
+uniform vec3 sunDirection;
+uniform float sunIntensity;
+uniform float sunAngularRadius;
+uniform vec3 rayleighScattering;
+uniform float mieScattering;
+uniform float mieAnisotropy;
+uniform float rayleighScaleHeight;
+uniform float mieScaleHeight;
+
+out vec4 FragColor;
+
+in vec3 texCoord;
+
+// Returns distances to intersections of a ray and a sphere in the center of coordinates, x > y
+// means that there is no intersection.
+vec2 RaySphereIntersection(vec3 origin, vec3 direction, float radius)
+{
+    float b = dot(origin, direction);
+    float distance = length(origin);
+    float c = (distance - radius) * (distance + radius);
+    float discriminant = b * b - c;
+    if (discriminant < 0.0) {
+        return vec2(1.0, -1.0);
+    }
+    float root = sqrt(discriminant);
+    return vec2(-b - root, -b + root);
+}
+
+vec2 Densities(vec3 point)
+{
+    float height = max(length(point) - PLANET_RADIUS, 0.0);
+    return vec2(exp(-height / rayleighScaleHeight), exp(-height / mieScaleHeight));
+}
+
+vec3 Transmittance(vec2 depth)
+{
+    return exp(-(rayleighScattering * depth.x + vec3(mieScattering * MIE_EXTINCTION_FACTOR * depth.y)));
+}
+
+// Returns false if the planet blocks the direction.
+bool OpticalDepthToSpace(vec3 point, vec3 direction, out vec2 depth)
+{
+    depth = vec2(0.0);
+
+    vec2 planet = RaySphereIntersection(point, direction, PLANET_RADIUS);
+    if (planet.x <= planet.y && planet.x > 0.0) {
+        return false;
+    }
+
+    vec2 atmosphere = RaySphereIntersection(point, direction, ATMOSPHERE_RADIUS);
+    if (atmosphere.x > atmosphere.y) {
+        return false;
+    }
+
+    float stepSize = max(atmosphere.y, 0.0) / float(LIGHT_STEPS);
+    for (int i = 0; i < LIGHT_STEPS; ++i) {
+        depth += Densities(point + direction * ((float(i) + 0.5) * stepSize)) * stepSize;
+    }
+    return true;
+}
+
+void main()
+{
+    vec3 direction = normalize(texCoord);
+    vec3 sun = normalize(sunDirection);
+    vec3 origin = vec3(0.0, PLANET_RADIUS + VIEWER_HEIGHT, 0.0);
+
+    vec2 atmosphere = RaySphereIntersection(origin, direction, ATMOSPHERE_RADIUS);
+    float distance = atmosphere.y;
+    bool hitsGround = false;
+    vec2 planet = RaySphereIntersection(origin, direction, PLANET_RADIUS);
+    if (planet.x <= planet.y && planet.x > 0.0) {
+        distance = planet.x;
+        hitsGround = true;
+    }
+
+    float stepSize = distance / float(PRIMARY_STEPS);
+    vec2 viewDepth = vec2(0.0);
+    vec3 rayleighSum = vec3(0.0);
+    vec3 mieSum = vec3(0.0);
+    for (int i = 0; i < PRIMARY_STEPS; ++i) {
+        vec3 point = origin + direction * ((float(i) + 0.5) * stepSize);
+        vec2 density = Densities(point);
+        viewDepth += density * stepSize;
+
+        vec2 lightDepth;
+        if (OpticalDepthToSpace(point, sun, lightDepth)) {
+            vec3 attenuation = Transmittance(viewDepth + lightDepth);
+            rayleighSum += attenuation * density.x * stepSize;
+            mieSum += attenuation * density.y * stepSize;
+        }
+    }
+
+    float mu = dot(direction, sun);
+    float g = mieAnisotropy;
+    float rayleighPhase = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+    float miePhase = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+        / ((2.0 + g * g) * pow(max(1.0 + g * g - 2.0 * g * mu, 1.0e-4), 1.5));
+
+    vec3 radiance = (rayleighSum * rayleighScattering * rayleighPhase + mieSum * mieScattering * miePhase) * sunIntensity;
+
+    if (!hitsGround && sunAngularRadius > 0.0 && mu >= cos(sunAngularRadius)) {
+        radiance += Transmittance(viewDepth) * sunIntensity;
+    }
+
+    FragColor = vec4(radiance, 1.0);
+}
@@ -0,0 +1,35 @@
+uniform sampler2D sdfTexture;
+uniform vec4 color;
+uniform vec4 outlineColor;
+uniform float outlineWidth;
+uniform vec4 glowColor;
+uniform float glowWidth;
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+void main()
+{
+    // 0.5 is the edge of a glyph, the distance grows towards the inside.
+    float distance = texture(sdfTexture, texCoord).r;
+    float smoothing = max(fwidth(distance) * 0.5, 0.0001);
+
+    float fill = smoothstep(0.5 - smoothing, 0.5 + smoothing, distance);
+
+    float outlineEdge = 0.5 - outlineWidth;
+    float outline = smoothstep(outlineEdge - smoothing, outlineEdge + smoothing, distance);
+
+    vec4 body = outlineWidth > 0.0 ? mix(outlineColor, color, fill) : color;
+    body.a *= outline;
+
+    float glow = glowWidth > 0.0 ? smoothstep(outlineEdge - glowWidth, outlineEdge, distance) : 0.0;
+    float glowAlpha = glowColor.a * glow * (1.0 - body.a);
+
+    float alpha = body.a + glowAlpha;
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    FragColor = vec4((body.rgb * body.a + glowColor.rgb * glowAlpha) / alpha, alpha);
+}
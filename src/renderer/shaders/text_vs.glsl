@@ -0,0 +1,23 @@
+layout(location = 0) in vec2 vertexPosition;
+layout(location = 1) in vec2 vertexTexCoord;
+
+uniform mat4 viewProjectionMatrix;
+uniform mat4 worldMatrix;
+uniform vec3 cameraUpVector;
+uniform vec3 cameraSideVector;
+uniform bool billboard;
+
+out vec2 texCoord;
+
+void main()
+{
+    texCoord = vertexTexCoord;
+    vec4 worldPosition;
+    if (billboard) {
+        vec3 offset = vertexPosition.x * cameraSideVector + vertexPosition.y * cameraUpVector;
+        worldPosition = worldMatrix * vec4(offset, 1.0);
+    } else {
+        worldPosition = worldMatrix * vec4(vertexPosition, 0.0, 1.0);
+    }
+    gl_Position = viewProjectionMatrix * worldPosition;
+}
@@ -12,13 +12,26 @@ uniform int gradientPointCount;
 uniform vec4 gradientColors[16];
 uniform float gradientStops[16];
 
-// Begin point of linear gradient *or* center of radial gradient
+// Begin point of linear gradient *or* center of radial/conic gradient
 // in normalized coordinates
 uniform vec2 gradientOrigin;
 
 // End point of linear gradient in normalized coordinates.
 uniform vec2 gradientEnd;
 
+// Radius of radial gradient.
+uniform float gradientRadius;
+
+// Start angle of conic gradient (in radians).
+uniform float gradientAngle;
+
+// If true, brush coordinates are in screen-space pixels instead of normalized coordinates.
+uniform bool absoluteCoordinates;
+
+uniform sampler2D brushTexture;
+uniform mat3 brushTransform;
+uniform bool brushTiling;
+
 uniform vec2 resolution;
 uniform vec2 boundsMin;
 uniform vec2 boundsMax;
@@ -47,29 +60,45 @@ int find_stop_index(float t) {
 
 void main()
 {
+    vec2 screenPosition = vec2(gl_FragCoord.x, resolution.y - gl_FragCoord.y);
     vec2 size = vec2(boundsMax.x - boundsMin.x, boundsMax.y - boundsMin.y);
-    vec2 localPosition = (vec2(gl_FragCoord.x, resolution.y - gl_FragCoord.y) - boundsMin) / size;
+    vec2 brushPosition = absoluteCoordinates ? screenPosition : (screenPosition - boundsMin) / size;
 
     if (brushType == 0) {
         // Solid color
         fragColor = solidColor;
+    } else if (brushType == 4) {
+        // Texture
+        vec2 uv = (brushTransform * vec3(brushPosition, 1.0)).xy;
+        uv = brushTiling ? fract(uv) : clamp(uv, 0.0, 1.0);
+        fragColor = texture(brushTexture, uv);
     } else {
         // Gradient brush
         float t = 0.0;
 
         if (brushType == 1) {
             // Linear gradient
-            t = project_point(gradientOrigin, gradientEnd, localPosition);
+            t = project_point(gradientOrigin, gradientEnd, brushPosition);
         } else if (brushType == 2) {
             // Radial gradient
-            t = clamp(length(localPosition - gradientOrigin), 0.0, 1.0);
+            t = clamp(length(brushPosition - gradientOrigin) / max(gradientRadius, 0.00001), 0.0, 1.0);
+        } else if (brushType == 3) {
+            // Conic gradient
+            vec2 dir = brushPosition - gradientOrigin;
+            t = fract((atan(dir.y, dir.x) - gradientAngle) / (2.0 * PI));
         }
 
-        int current = find_stop_index(t);
-        int next = min(current + 1, gradientPointCount);
-        float delta = gradientStops[next] - gradientStops[current];
-        float mix_factor = (t - gradientStops[current]) / delta;
-        fragColor = mix(gradientColors[current], gradientColors[next], mix_factor);
+        if (gradientPointCount == 0) {
+            fragColor = vec4(1.0);
+        } else if (t <= gradientStops[0]) {
+            fragColor = gradientColors[0];
+        } else {
+            int current = find_stop_index(t);
+            int next = min(current + 1, gradientPointCount - 1);
+            float delta = gradientStops[next] - gradientStops[current];
+            float mix_factor = delta > 0.0 ? (t - gradientStops[current]) / delta : 0.0;
+            fragColor = mix(gradientColors[current], gradientColors[next], clamp(mix_factor, 0.0, 1.0));
+        }
     }
 
     vec4 diffuseColor = texture(diffuseTexture, texCoord);
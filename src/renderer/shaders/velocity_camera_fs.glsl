@@ -0,0 +1,20 @@
+// Writes screen-space motion of the background since the previous frame. Background is considered
+// to be infinitely far, so only rotation of the camera is taken into account.
+
+uniform mat4 reprojectionMatrix;
+
+in vec2 ndcPosition;
+
+out vec4 FragColor;
+
+void main()
+{
+    vec4 previous = reprojectionMatrix * vec4(ndcPosition, 1.0, 1.0);
+    if (previous.w <= 0.0) {
+        // The point was behind the camera on the previous frame, there's no meaningful motion.
+        FragColor = vec4(0.0);
+    } else {
+        vec2 previousNdc = previous.xy / previous.w;
+        FragColor = vec4((ndcPosition - previousNdc) * 0.5, 0.0, 0.0);
+    }
+}
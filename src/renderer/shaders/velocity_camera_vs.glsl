@@ -0,0 +1,16 @@
+// Full-screen quad at the far plane, it is used to write motion of the background (pixels that
+// weren't covered by any object) caused by rotation of the camera.
+
+layout(location = 0) in vec3 vertexPosition;
+
+uniform mat4 worldViewProjection;
+
+out vec2 ndcPosition;
+
+void main()
+{
+    gl_Position = worldViewProjection * vec4(vertexPosition, 1.0);
+    // Put the quad at the far plane, so only background pixels will pass the depth test.
+    gl_Position.z = gl_Position.w;
+    ndcPosition = gl_Position.xy / gl_Position.w;
+}
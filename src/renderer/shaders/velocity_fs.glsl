@@ -0,0 +1,14 @@
+// Writes screen-space motion of a fragment since the previous frame, in texture coordinates.
+
+in vec4 currentPosition;
+in vec4 previousPosition;
+
+out vec4 FragColor;
+
+void main()
+{
+    vec2 current = currentPosition.xy / currentPosition.w;
+    vec2 previous = previousPosition.xy / previousPosition.w;
+    // Convert from NDC [-1; 1] to texture coordinates [0; 1].
+    FragColor = vec4((current - previous) * 0.5, 0.0, 0.0);
+}
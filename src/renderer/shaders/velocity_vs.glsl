@@ -0,0 +1,44 @@
+// Transforms a vertex using both current and previous transforms of an object, so the fragment
+// shader could calculate screen-space motion of the vertex.
+
+layout(location = 0) in vec3 vertexPosition;
+layout(location = 4) in vec4 boneWeights;
+layout(location = 5) in vec4 boneIndices;
+
+uniform mat4 worldViewProjection;
+uniform mat4 prevWorldViewProjection;
+uniform bool useSkeletalAnimation;
+uniform mat4 boneMatrices[60];
+uniform mat4 prevBoneMatrices[60];
+
+out vec4 currentPosition;
+out vec4 previousPosition;
+
+void main()
+{
+    vec4 vertex = vec4(vertexPosition, 1.0);
+    vec4 localPosition = vertex;
+    vec4 prevLocalPosition = vertex;
+
+    if (useSkeletalAnimation)
+    {
+        int i0 = int(boneIndices.x);
+        int i1 = int(boneIndices.y);
+        int i2 = int(boneIndices.z);
+        int i3 = int(boneIndices.w);
+
+        localPosition = boneMatrices[i0] * vertex * boneWeights.x
+            + boneMatrices[i1] * vertex * boneWeights.y
+            + boneMatrices[i2] * vertex * boneWeights.z
+            + boneMatrices[i3] * vertex * boneWeights.w;
+
+        prevLocalPosition = prevBoneMatrices[i0] * vertex * boneWeights.x
+            + prevBoneMatrices[i1] * vertex * boneWeights.y
+            + prevBoneMatrices[i2] * vertex * boneWeights.z
+            + prevBoneMatrices[i3] * vertex * boneWeights.w;
+    }
+
+    currentPosition = worldViewProjection * localPosition;
+    previousPosition = prevWorldViewProjection * prevLocalPosition;
+    gl_Position = currentPosition;
+}
@@ -0,0 +1,519 @@
+//! Shadow atlas is a single large texture, that contains shadow maps of every point and spot
+//! light. Each light gets one (spot light) or six (point light, one per cube map face) square
+//! regions in the atlas. Size of the regions depends on on-screen importance of a light, so
+//! lights that are close to the camera have sharper shadows. Shadow maps are cached across
+//! frames and re-rendered only if a light or shadow casters around it have changed.
+
+use crate::{
+    core::{algebra::Vector4, color::Color, math::Rect},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, FrameBuffer},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        ShadowMapPrecision,
+    },
+    scene::base::InstanceId,
+};
+use fxhash::FxHashMap;
+use std::{cell::RefCell, rc::Rc};
+
+/// Minimal size (in pixels) of a region in the atlas.
+pub const MIN_REGION_SIZE: usize = 32;
+
+/// Returns the largest power of two, that is less or equal to the given number.
+pub fn prev_power_of_two(n: usize) -> usize {
+    if n == 0 {
+        0
+    } else {
+        1 << (usize::BITS - 1 - n.leading_zeros())
+    }
+}
+
+/// Returns actual size of an atlas for the requested size: the largest power of two, that is
+/// less or equal to the requested size, but not less than [`MIN_REGION_SIZE`].
+pub fn atlas_size(requested_size: usize) -> usize {
+    prev_power_of_two(requested_size.max(MIN_REGION_SIZE))
+}
+
+/// Square region of the atlas (in pixels).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasRegion {
+    pub x: usize,
+    pub y: usize,
+    pub size: usize,
+}
+
+impl AtlasRegion {
+    /// Returns viewport rectangle of the region.
+    pub fn rect(&self) -> Rect<i32> {
+        Rect::new(
+            self.x as i32,
+            self.y as i32,
+            self.size as i32,
+            self.size as i32,
+        )
+    }
+
+    /// Returns the region in normalized atlas coordinates packed as `(x, y, size, half texel)`,
+    /// where half texel is a half of a texel size in local coordinates of the region. This
+    /// format is used by shadow atlas functions in shaders.
+    pub fn normalized(&self, atlas_size: usize) -> Vector4<f32> {
+        let atlas_size = atlas_size as f32;
+        Vector4::new(
+            self.x as f32 / atlas_size,
+            self.y as f32 / atlas_size,
+            self.size as f32 / atlas_size,
+            0.5 / self.size as f32,
+        )
+    }
+}
+
+/// Quad-tree (buddy) allocator of square power-of-two regions of the atlas.
+pub struct AtlasAllocator {
+    size: usize,
+    // Free blocks per level, level 0 is the whole atlas, each next level has twice smaller
+    // blocks.
+    free: Vec<Vec<(usize, usize)>>,
+}
+
+impl AtlasAllocator {
+    /// Creates new allocator for an atlas of the given size. The size will be rounded down to the
+    /// nearest power of two.
+    pub fn new(size: usize) -> Self {
+        let size = atlas_size(size);
+        let levels = (size / MIN_REGION_SIZE).trailing_zeros() as usize + 1;
+        let mut free = vec![Vec::new(); levels];
+        free[0].push((0, 0));
+        Self { size, free }
+    }
+
+    /// Returns size of the atlas.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    fn level_of(&self, size: usize) -> usize {
+        (self.size / size).trailing_zeros() as usize
+    }
+
+    /// Tries to allocate a region of the given size. The size will be rounded down to the nearest
+    /// power of two and clamped to `[MIN_REGION_SIZE; atlas size]` range.
+    pub fn allocate(&mut self, size: usize) -> Option<AtlasRegion> {
+        let size = prev_power_of_two(size.clamp(MIN_REGION_SIZE, self.size));
+        let level = self.level_of(size);
+
+        // Find the smallest free block, that can contain the region.
+        let mut block_level = (0..=level).rev().find(|l| !self.free[*l].is_empty())?;
+        let (x, y) = self.free[block_level].pop().unwrap();
+
+        // Split the block until it has requested size.
+        while block_level < level {
+            block_level += 1;
+            let half = self.size >> block_level;
+            self.free[block_level].push((x + half, y));
+            self.free[block_level].push((x, y + half));
+            self.free[block_level].push((x + half, y + half));
+        }
+
+        Some(AtlasRegion { x, y, size })
+    }
+
+    /// Returns the region back to the allocator. Free neighbour blocks are merged.
+    pub fn free(&mut self, region: AtlasRegion) {
+        let mut level = self.level_of(region.size);
+        let (mut x, mut y) = (region.x, region.y);
+
+        while level > 0 {
+            let size = self.size >> level;
+            let parent_x = x - x % (size * 2);
+            let parent_y = y - y % (size * 2);
+            let siblings = [
+                (parent_x, parent_y),
+                (parent_x + size, parent_y),
+                (parent_x, parent_y + size),
+                (parent_x + size, parent_y + size),
+            ];
+
+            let free = &mut self.free[level];
+            if siblings
+                .iter()
+                .filter(|s| **s != (x, y))
+                .all(|s| free.contains(s))
+            {
+                free.retain(|block| !siblings.contains(block));
+                x = parent_x;
+                y = parent_y;
+                level -= 1;
+            } else {
+                break;
+            }
+        }
+
+        self.free[level].push((x, y));
+    }
+}
+
+/// Result of a shadow map request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadowMapAllocation {
+    /// Regions of the shadow map in the atlas, one for spot lights and six for point lights.
+    pub regions: Vec<AtlasRegion>,
+    /// Whether the shadow map must be rendered or its cached content could be used.
+    pub needs_render: bool,
+}
+
+struct AtlasEntry {
+    regions: Vec<AtlasRegion>,
+    content_hash: u64,
+    last_used: u64,
+}
+
+/// Layout of shadow maps in the atlas. It keeps track of shadow maps of every light and decides
+/// whether a shadow map must be re-rendered or not.
+pub struct ShadowAtlasLayout {
+    allocator: AtlasAllocator,
+    entries: FxHashMap<InstanceId, AtlasEntry>,
+    frame: u64,
+}
+
+impl ShadowAtlasLayout {
+    /// Creates new layout for an atlas of the given size.
+    pub fn new(size: usize) -> Self {
+        Self {
+            allocator: AtlasAllocator::new(size),
+            entries: Default::default(),
+            frame: 0,
+        }
+    }
+
+    /// Returns size of the atlas.
+    pub fn size(&self) -> usize {
+        self.allocator.size()
+    }
+
+    /// Must be called once before shadow map requests of a frame.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Requests `count` regions of the given size for a light. `content_hash` is a hash of
+    /// everything that affects the shadow map, the shadow map is re-rendered only if the hash
+    /// has changed. If there's not enough space, shadow maps of lights that were not used on the
+    /// current frame are evicted, and if it does not help, the size is halved. Returns `None` if
+    /// there's no space for the shadow map even at minimal size.
+    pub fn request(
+        &mut self,
+        light: InstanceId,
+        count: usize,
+        size: usize,
+        content_hash: u64,
+    ) -> Option<ShadowMapAllocation> {
+        let size = prev_power_of_two(size.clamp(MIN_REGION_SIZE, self.size()));
+
+        if let Some(entry) = self.entries.get_mut(&light) {
+            if entry.regions.len() == count && entry.regions[0].size == size {
+                let needs_render = entry.content_hash != content_hash;
+                entry.last_used = self.frame;
+                entry.content_hash = content_hash;
+                return Some(ShadowMapAllocation {
+                    regions: entry.regions.clone(),
+                    needs_render,
+                });
+            }
+        }
+
+        self.release(light);
+
+        let mut size = size;
+        loop {
+            if let Some(regions) = self.allocate_regions(count, size) {
+                self.entries.insert(
+                    light,
+                    AtlasEntry {
+                        regions: regions.clone(),
+                        content_hash,
+                        last_used: self.frame,
+                    },
+                );
+                return Some(ShadowMapAllocation {
+                    regions,
+                    needs_render: true,
+                });
+            }
+
+            if !self.evict_least_recently_used() {
+                if size / 2 < MIN_REGION_SIZE {
+                    return None;
+                }
+                size /= 2;
+            }
+        }
+    }
+
+    /// Frees regions of the given light.
+    pub fn release(&mut self, light: InstanceId) {
+        if let Some(entry) = self.entries.remove(&light) {
+            for region in entry.regions {
+                self.allocator.free(region);
+            }
+        }
+    }
+
+    fn allocate_regions(&mut self, count: usize, size: usize) -> Option<Vec<AtlasRegion>> {
+        let mut regions = Vec::with_capacity(count);
+        for _ in 0..count {
+            if let Some(region) = self.allocator.allocate(size) {
+                regions.push(region);
+            } else {
+                for region in regions {
+                    self.allocator.free(region);
+                }
+                return None;
+            }
+        }
+        Some(regions)
+    }
+
+    fn evict_least_recently_used(&mut self) -> bool {
+        let frame = self.frame;
+        if let Some(light) = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.last_used != frame)
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(light, _)| *light)
+        {
+            self.release(light);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Shadow atlas with depth attachment (used by spot lights) and distance color attachment (used
+/// by point lights).
+pub struct ShadowAtlas {
+    framebuffer: FrameBuffer,
+    layout: ShadowAtlasLayout,
+    depth_precision: ShadowMapPrecision,
+    distance_precision: ShadowMapPrecision,
+}
+
+impl ShadowAtlas {
+    pub fn new(
+        state: &mut PipelineState,
+        size: usize,
+        depth_precision: ShadowMapPrecision,
+        distance_precision: ShadowMapPrecision,
+    ) -> Result<Self, FrameworkError> {
+        let layout = ShadowAtlasLayout::new(size);
+        let size = layout.size();
+        let kind = GpuTextureKind::Rectangle {
+            width: size,
+            height: size,
+        };
+
+        let mut depth = GpuTexture::new(
+            state,
+            kind,
+            match depth_precision {
+                ShadowMapPrecision::Full => PixelKind::D32F,
+                ShadowMapPrecision::Half => PixelKind::D16,
+            },
+            MinificationFilter::Linear,
+            MagnificationFilter::Linear,
+            1,
+            None,
+        )?;
+        depth
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_border_color(Color::WHITE);
+
+        let mut distance = GpuTexture::new(
+            state,
+            kind,
+            match distance_precision {
+                ShadowMapPrecision::Full => PixelKind::F32,
+                ShadowMapPrecision::Half => PixelKind::F16,
+            },
+            MinificationFilter::Linear,
+            MagnificationFilter::Linear,
+            1,
+            None,
+        )?;
+        distance
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge);
+
+        Ok(Self {
+            framebuffer: FrameBuffer::new(
+                state,
+                Some(Attachment {
+                    kind: AttachmentKind::Depth,
+                    texture: Rc::new(RefCell::new(depth)),
+                }),
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(distance)),
+                }],
+            )?,
+            layout,
+            depth_precision,
+            distance_precision,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.layout.size()
+    }
+
+    pub fn depth_precision(&self) -> ShadowMapPrecision {
+        self.depth_precision
+    }
+
+    pub fn distance_precision(&self) -> ShadowMapPrecision {
+        self.distance_precision
+    }
+
+    pub fn layout_mut(&mut self) -> &mut ShadowAtlasLayout {
+        &mut self.layout
+    }
+
+    pub fn framebuffer_mut(&mut self) -> &mut FrameBuffer {
+        &mut self.framebuffer
+    }
+
+    /// Depth texture, that contains shadow maps of spot lights.
+    pub fn depth_texture(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.depth_attachment().unwrap().texture.clone()
+    }
+
+    /// Distance texture, that contains shadow maps of point lights.
+    pub fn distance_texture(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.color_attachments()[0].texture.clone()
+    }
+}
+
+/// Clears a region of the atlas frame buffer, other regions are left untouched.
+pub(crate) fn clear_region(
+    framebuffer: &mut FrameBuffer,
+    state: &mut PipelineState,
+    region: AtlasRegion,
+    color: Option<Color>,
+) {
+    let rect = region.rect();
+    state.set_scissor_test(true);
+    state.set_scissor_box(rect.x(), rect.y(), rect.w(), rect.h());
+    framebuffer.clear(state, rect, color, Some(1.0), None);
+    state.set_scissor_test(false);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::uuid::Uuid,
+        renderer::shadow::atlas::{
+            prev_power_of_two, AtlasAllocator, AtlasRegion, ShadowAtlasLayout, MIN_REGION_SIZE,
+        },
+        renderer::QualitySettings,
+        scene::base::InstanceId,
+    };
+
+    #[test]
+    fn test_prev_power_of_two() {
+        assert_eq!(prev_power_of_two(1), 1);
+        assert_eq!(prev_power_of_two(1000), 512);
+        assert_eq!(prev_power_of_two(1024), 1024);
+    }
+
+    #[test]
+    fn test_allocator_split_and_merge() {
+        let mut allocator = AtlasAllocator::new(1024);
+
+        let regions = (0..4)
+            .map(|_| allocator.allocate(512).unwrap())
+            .collect::<Vec<_>>();
+        assert!(allocator.allocate(MIN_REGION_SIZE).is_none());
+        for (i, a) in regions.iter().enumerate() {
+            for b in regions.iter().skip(i + 1) {
+                assert_ne!((a.x, a.y), (b.x, b.y));
+            }
+        }
+
+        for region in regions {
+            allocator.free(region);
+        }
+        assert_eq!(
+            allocator.allocate(1024),
+            Some(AtlasRegion {
+                x: 0,
+                y: 0,
+                size: 1024
+            })
+        );
+    }
+
+    #[test]
+    fn test_layout_caching_and_eviction() {
+        let a = InstanceId(Uuid::new_v4());
+        let b = InstanceId(Uuid::new_v4());
+
+        let mut layout = ShadowAtlasLayout::new(1024);
+
+        layout.begin_frame();
+        assert!(layout.request(a, 1, 1024, 1).unwrap().needs_render);
+
+        // The same content must be cached.
+        layout.begin_frame();
+        assert!(!layout.request(a, 1, 1024, 1).unwrap().needs_render);
+
+        // Changed content must be re-rendered.
+        layout.begin_frame();
+        assert!(layout.request(a, 1, 1024, 2).unwrap().needs_render);
+
+        // Atlas is full and `a` is used on the current frame, so there's no space for `b`.
+        assert!(layout.request(b, 6, 1024, 1).is_none());
+
+        // `a` is not used on the next frame, so it is evicted.
+        layout.begin_frame();
+        let allocation = layout.request(b, 6, 256, 1).unwrap();
+        assert_eq!(allocation.regions.len(), 6);
+        assert!(allocation.needs_render);
+        assert!(layout.request(a, 1, 1024, 2).unwrap().regions[0].size < 1024);
+    }
+
+    #[test]
+    fn test_quality_presets_fit_atlas() {
+        for settings in [
+            QualitySettings::ultra(),
+            QualitySettings::high(),
+            QualitySettings::medium(),
+            QualitySettings::low(),
+        ] {
+            let mut layout = ShadowAtlasLayout::new(settings.shadow_atlas_size);
+            layout.begin_frame();
+
+            let point_size = prev_power_of_two(settings.point_shadow_map_size.max(MIN_REGION_SIZE));
+            let point = layout
+                .request(InstanceId(Uuid::new_v4()), 6, point_size, 0)
+                .unwrap();
+            assert!(point.regions.iter().all(|r| r.size == point_size));
+
+            let spot_size = prev_power_of_two(settings.spot_shadow_map_size.max(MIN_REGION_SIZE));
+            let spot = layout
+                .request(InstanceId(Uuid::new_v4()), 1, spot_size, 0)
+                .unwrap();
+            assert_eq!(spot.regions[0].size, spot_size);
+        }
+    }
+}
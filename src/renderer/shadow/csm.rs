@@ -29,6 +29,28 @@ use crate::{
 };
 use std::{cell::RefCell, rc::Rc};
 
+/// Returns view-space distances that split camera frustum into cascades of the light: near plane
+/// of the camera followed by far plane of every cascade.
+pub(crate) fn cascade_split_distances(
+    light: &DirectionalLight,
+    camera: &Camera,
+) -> [f32; CSM_NUM_CASCADES + 1] {
+    match light.csm_options.split_options {
+        FrustumSplitOptions::Absolute { far_planes } => [
+            camera.projection().z_near(),
+            far_planes[0],
+            far_planes[1],
+            far_planes[2],
+        ],
+        FrustumSplitOptions::Relative { fractions } => [
+            camera.projection().z_near(),
+            camera.projection().z_far() * fractions[0],
+            camera.projection().z_far() * fractions[1],
+            camera.projection().z_far() * fractions[2],
+        ],
+    }
+}
+
 pub struct Cascade {
     pub frame_buffer: FrameBuffer,
     pub view_proj_matrix: Matrix4<f32>,
@@ -167,20 +189,7 @@ impl CsmRenderer {
             .try_normalize(f32::EPSILON)
             .unwrap_or_else(Vector3::z);
 
-        let z_values = match light.csm_options.split_options {
-            FrustumSplitOptions::Absolute { far_planes } => [
-                camera.projection().z_near(),
-                far_planes[0],
-                far_planes[1],
-                far_planes[2],
-            ],
-            FrustumSplitOptions::Relative { fractions } => [
-                camera.projection().z_near(),
-                camera.projection().z_far() * fractions[0],
-                camera.projection().z_far() * fractions[1],
-                camera.projection().z_far() * fractions[2],
-            ],
-        };
+        let z_values = cascade_split_distances(light, camera);
 
         for i in 0..CSM_NUM_CASCADES {
             let znear = z_values[i];
@@ -190,12 +199,15 @@ impl CsmRenderer {
                 zfar += 10.0 * f32::EPSILON;
             }
 
-            let projection_matrix = camera
-                .projection()
-                .clone()
-                .with_z_near(znear)
-                .with_z_far(zfar)
-                .matrix(frame_size);
+            // Arbitrary projection matrix cannot be split, so every cascade covers whole frustum.
+            let projection_matrix = camera.projection_override().unwrap_or_else(|| {
+                camera
+                    .projection()
+                    .clone()
+                    .with_z_near(znear)
+                    .with_z_far(zfar)
+                    .matrix(frame_size)
+            });
 
             let frustum =
                 Frustum::from(projection_matrix * camera.view_matrix()).unwrap_or_default();
@@ -3,19 +3,11 @@
 use crate::core::math::frustum::Frustum;
 use crate::renderer::batch::{SurfaceInstance, SurfaceInstanceFlags};
 
+pub mod atlas;
 pub mod csm;
 pub mod point;
 pub mod spot;
 
-pub fn cascade_size(base_size: usize, cascade: usize) -> usize {
-    match cascade {
-        0 => base_size,
-        1 => (base_size / 2).max(1),
-        2 => (base_size / 4).max(1),
-        _ => unreachable!(),
-    }
-}
-
 fn should_cast_shadows(surface_instance: &SurfaceInstance, light_frustum: &Frustum) -> bool {
     surface_instance
         .flags
@@ -2,7 +2,7 @@ use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector3},
         color::Color,
-        math::{frustum::Frustum, Rect},
+        math::frustum::Frustum,
         scope_profile,
         sstorage::ImmutableString,
     },
@@ -10,31 +10,23 @@ use crate::{
         apply_material,
         batch::BatchStorage,
         cache::{shader::ShaderCache, texture::TextureCache},
-        framework::{
-            error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, FrameBuffer},
-            gpu_texture::{
-                Coordinate, CubeMapFace, GpuTexture, GpuTextureKind, MagnificationFilter,
-                MinificationFilter, PixelKind, WrapMode,
-            },
-            state::PipelineState,
+        framework::{framebuffer::FrameBuffer, gpu_texture::GpuTexture, state::PipelineState},
+        shadow::{
+            atlas::{clear_region, AtlasRegion},
+            should_cast_shadows,
         },
-        shadow::{cascade_size, should_cast_shadows},
-        GeometryCache, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
+        GeometryCache, MaterialContext, RenderPassStatistics,
     },
 };
 use std::{cell::RefCell, rc::Rc};
 
 pub struct PointShadowMapRenderer {
-    precision: ShadowMapPrecision,
-    cascades: [FrameBuffer; 3],
-    size: usize,
     faces: [PointShadowCubeMapFace; 6],
     render_pass_name: ImmutableString,
 }
 
+// Order and orientation of the faces must match `S_PointShadowAtlas` shader function.
 struct PointShadowCubeMapFace {
-    face: CubeMapFace,
     look: Vector3<f32>,
     up: Vector3<f32>,
 }
@@ -44,7 +36,9 @@ pub(crate) struct PointShadowMapRenderContext<'a> {
     pub light_pos: Vector3<f32>,
     pub light_radius: f32,
     pub geom_cache: &'a mut GeometryCache,
-    pub cascade: usize,
+    pub framebuffer: &'a mut FrameBuffer,
+    /// Regions of the atlas for each cube map face.
+    pub regions: &'a [AtlasRegion],
     pub batch_storage: &'a BatchStorage,
     pub shader_cache: &'a mut ShaderCache,
     pub texture_cache: &'a mut TextureCache,
@@ -53,136 +47,41 @@ pub(crate) struct PointShadowMapRenderContext<'a> {
     pub black_dummy: Rc<RefCell<GpuTexture>>,
 }
 
-impl PointShadowMapRenderer {
-    pub fn new(
-        state: &mut PipelineState,
-        size: usize,
-        precision: ShadowMapPrecision,
-    ) -> Result<Self, FrameworkError> {
-        fn make_cascade(
-            state: &mut PipelineState,
-            size: usize,
-            precision: ShadowMapPrecision,
-        ) -> Result<FrameBuffer, FrameworkError> {
-            let depth = {
-                let kind = GpuTextureKind::Rectangle {
-                    width: size,
-                    height: size,
-                };
-                let mut texture = GpuTexture::new(
-                    state,
-                    kind,
-                    match precision {
-                        ShadowMapPrecision::Full => PixelKind::D32F,
-                        ShadowMapPrecision::Half => PixelKind::D16,
-                    },
-                    MinificationFilter::Nearest,
-                    MagnificationFilter::Nearest,
-                    1,
-                    None,
-                )?;
-                texture
-                    .bind_mut(state, 0)
-                    .set_minification_filter(MinificationFilter::Nearest)
-                    .set_magnification_filter(MagnificationFilter::Nearest)
-                    .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
-                texture
-            };
-
-            let cube_map = {
-                let kind = GpuTextureKind::Cube {
-                    width: size,
-                    height: size,
-                };
-                let mut texture = GpuTexture::new(
-                    state,
-                    kind,
-                    PixelKind::F16,
-                    MinificationFilter::Linear,
-                    MagnificationFilter::Linear,
-                    1,
-                    None,
-                )?;
-                texture
-                    .bind_mut(state, 0)
-                    .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::R, WrapMode::ClampToEdge);
-                texture
-            };
-
-            FrameBuffer::new(
-                state,
-                Some(Attachment {
-                    kind: AttachmentKind::Depth,
-                    texture: Rc::new(RefCell::new(depth)),
-                }),
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(cube_map)),
-                }],
-            )
-        }
-
-        Ok(Self {
-            precision,
-            cascades: [
-                make_cascade(state, cascade_size(size, 0), precision)?,
-                make_cascade(state, cascade_size(size, 1), precision)?,
-                make_cascade(state, cascade_size(size, 2), precision)?,
-            ],
-            size,
+impl Default for PointShadowMapRenderer {
+    fn default() -> Self {
+        Self {
             faces: [
                 PointShadowCubeMapFace {
-                    face: CubeMapFace::PositiveX,
                     look: Vector3::new(1.0, 0.0, 0.0),
                     up: Vector3::new(0.0, -1.0, 0.0),
                 },
                 PointShadowCubeMapFace {
-                    face: CubeMapFace::NegativeX,
                     look: Vector3::new(-1.0, 0.0, 0.0),
                     up: Vector3::new(0.0, -1.0, 0.0),
                 },
                 PointShadowCubeMapFace {
-                    face: CubeMapFace::PositiveY,
                     look: Vector3::new(0.0, 1.0, 0.0),
                     up: Vector3::new(0.0, 0.0, 1.0),
                 },
                 PointShadowCubeMapFace {
-                    face: CubeMapFace::NegativeY,
                     look: Vector3::new(0.0, -1.0, 0.0),
                     up: Vector3::new(0.0, 0.0, -1.0),
                 },
                 PointShadowCubeMapFace {
-                    face: CubeMapFace::PositiveZ,
                     look: Vector3::new(0.0, 0.0, 1.0),
                     up: Vector3::new(0.0, -1.0, 0.0),
                 },
                 PointShadowCubeMapFace {
-                    face: CubeMapFace::NegativeZ,
                     look: Vector3::new(0.0, 0.0, -1.0),
                     up: Vector3::new(0.0, -1.0, 0.0),
                 },
             ],
             render_pass_name: ImmutableString::new("PointShadow"),
-        })
-    }
-
-    pub fn base_size(&self) -> usize {
-        self.size
-    }
-
-    pub fn precision(&self) -> ShadowMapPrecision {
-        self.precision
-    }
-
-    pub fn cascade_texture(&self, cascade: usize) -> Rc<RefCell<GpuTexture>> {
-        self.cascades[cascade].color_attachments()[0]
-            .texture
-            .clone()
+        }
     }
+}
 
+impl PointShadowMapRenderer {
     pub(crate) fn render(&mut self, args: PointShadowMapRenderContext) -> RenderPassStatistics {
         scope_profile!();
 
@@ -193,7 +92,8 @@ impl PointShadowMapRenderer {
             light_pos,
             light_radius,
             geom_cache,
-            cascade,
+            framebuffer,
+            regions,
             batch_storage,
             shader_cache,
             texture_cache,
@@ -202,22 +102,13 @@ impl PointShadowMapRenderer {
             black_dummy,
         } = args;
 
-        let framebuffer = &mut self.cascades[cascade];
-        let cascade_size = cascade_size(self.size, cascade);
-
-        let viewport = Rect::new(0, 0, cascade_size as i32, cascade_size as i32);
-
         let light_projection_matrix =
             Matrix4::new_perspective(1.0, std::f32::consts::FRAC_PI_2, 0.01, light_radius);
 
-        for face in self.faces.iter() {
-            framebuffer.set_cubemap_face(state, 0, face.face).clear(
-                state,
-                viewport,
-                Some(Color::WHITE),
-                Some(1.0),
-                None,
-            );
+        for (face, region) in self.faces.iter().zip(regions.iter()) {
+            let viewport = region.rect();
+
+            clear_region(framebuffer, state, *region, Some(Color::WHITE));
 
             let light_look_at = light_pos + face.look;
             let light_view_matrix = Matrix4::look_at_rh(
@@ -110,7 +110,7 @@ impl SpriteRenderer {
         let camera_side = inv_view.side();
 
         for sprite in graph.linear_iter().filter_map(|node| {
-            if !node.global_visibility() {
+            if !node.global_visibility() || !camera.can_see_layer(node.layer()) {
                 return None;
             }
 
@@ -117,7 +117,7 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             texture
         };
 
-        let mut rng = crate::rand::thread_rng();
+        let mut rng = crate::core::random::new_rng();
 
         Ok(Self {
             blur: Blur::new(state, width, height)?,
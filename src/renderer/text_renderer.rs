@@ -0,0 +1,238 @@
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        math::{Matrix4Ext, Rect, TriangleDefinition},
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{BlendParameters, DrawParameters, FrameBuffer},
+            geometry_buffer::{
+                AttributeDefinition, AttributeKind, BufferBuilder, ElementKind, GeometryBuffer,
+                GeometryBufferBuilder, GeometryBufferKind,
+            },
+            gpu_program::{GpuProgram, UniformLocation},
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        RenderPassStatistics, TextureCache,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        text::{Text3D, TextMode},
+    },
+};
+
+struct TextShader {
+    program: GpuProgram,
+    view_projection_matrix: UniformLocation,
+    world_matrix: UniformLocation,
+    camera_side_vector: UniformLocation,
+    camera_up_vector: UniformLocation,
+    billboard: UniformLocation,
+    sdf_texture: UniformLocation,
+    color: UniformLocation,
+    outline_color: UniformLocation,
+    outline_width: UniformLocation,
+    glow_color: UniformLocation,
+    glow_width: UniformLocation,
+}
+
+impl TextShader {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/text_fs.glsl");
+        let vertex_source = include_str!("shaders/text_vs.glsl");
+        let program = GpuProgram::from_source(state, "TextShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            view_projection_matrix: program
+                .uniform_location(state, &ImmutableString::new("viewProjectionMatrix"))?,
+            world_matrix: program.uniform_location(state, &ImmutableString::new("worldMatrix"))?,
+            camera_side_vector: program
+                .uniform_location(state, &ImmutableString::new("cameraSideVector"))?,
+            camera_up_vector: program
+                .uniform_location(state, &ImmutableString::new("cameraUpVector"))?,
+            billboard: program.uniform_location(state, &ImmutableString::new("billboard"))?,
+            sdf_texture: program.uniform_location(state, &ImmutableString::new("sdfTexture"))?,
+            color: program.uniform_location(state, &ImmutableString::new("color"))?,
+            outline_color: program
+                .uniform_location(state, &ImmutableString::new("outlineColor"))?,
+            outline_width: program
+                .uniform_location(state, &ImmutableString::new("outlineWidth"))?,
+            glow_color: program.uniform_location(state, &ImmutableString::new("glowColor"))?,
+            glow_width: program.uniform_location(state, &ImmutableString::new("glowWidth"))?,
+            program,
+        })
+    }
+}
+
+#[repr(C)]
+struct Vertex {
+    position: Vector2<f32>,
+    tex_coord: Vector2<f32>,
+}
+
+/// Renders [`Text3D`] nodes. Glyph quads are generated every frame, so text could be changed at
+/// any time without additional cost.
+pub struct TextRenderer {
+    shader: TextShader,
+    geometry: GeometryBuffer,
+    vertices: Vec<Vertex>,
+    triangles: Vec<TriangleDefinition>,
+}
+
+pub(crate) struct TextRenderContext<'a, 'b, 'c> {
+    pub state: &'a mut PipelineState,
+    pub framebuffer: &'b mut FrameBuffer,
+    pub graph: &'c Graph,
+    pub camera: &'c Camera,
+    pub viewport: Rect<i32>,
+    pub textures: &'a mut TextureCache,
+}
+
+impl TextRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let geometry = GeometryBufferBuilder::new(ElementKind::Triangle)
+            .with_buffer_builder(
+                BufferBuilder::new::<Vertex>(GeometryBufferKind::DynamicDraw, None)
+                    .with_attribute(AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float2,
+                        normalized: false,
+                        divisor: 0,
+                    })
+                    .with_attribute(AttributeDefinition {
+                        location: 1,
+                        kind: AttributeKind::Float2,
+                        normalized: false,
+                        divisor: 0,
+                    }),
+            )
+            .build(state)?;
+
+        Ok(Self {
+            shader: TextShader::new(state)?,
+            geometry,
+            vertices: Default::default(),
+            triangles: Default::default(),
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn render(&mut self, args: TextRenderContext) -> RenderPassStatistics {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let TextRenderContext {
+            state,
+            framebuffer,
+            graph,
+            camera,
+            viewport,
+            textures,
+        } = args;
+
+        let view_projection = camera.view_projection_matrix();
+        let inv_view = camera.inv_view_matrix().unwrap();
+        let camera_up = inv_view.up();
+        let camera_side = inv_view.side();
+
+        for text in graph.linear_iter().filter_map(|node| {
+            if !node.global_visibility() || !camera.can_see_layer(node.layer()) {
+                return None;
+            }
+
+            node.cast::<Text3D>()
+        }) {
+            let font = text.font();
+            let sdf_texture = if let Some(texture) = textures.get(state, font.atlas()) {
+                texture
+            } else {
+                continue;
+            };
+
+            self.vertices.clear();
+            self.triangles.clear();
+            for quad in text.glyph_quads() {
+                let i = self.vertices.len() as u32;
+                let uv = quad.tex_coords;
+                // Top edge of a glyph is at the top of its rectangle in the atlas.
+                self.vertices.extend([
+                    Vertex {
+                        position: quad.min,
+                        tex_coord: Vector2::new(uv.x(), uv.y() + uv.h()),
+                    },
+                    Vertex {
+                        position: Vector2::new(quad.max.x, quad.min.y),
+                        tex_coord: Vector2::new(uv.x() + uv.w(), uv.y() + uv.h()),
+                    },
+                    Vertex {
+                        position: quad.max,
+                        tex_coord: Vector2::new(uv.x() + uv.w(), uv.y()),
+                    },
+                    Vertex {
+                        position: Vector2::new(quad.min.x, quad.max.y),
+                        tex_coord: Vector2::new(uv.x(), uv.y()),
+                    },
+                ]);
+                self.triangles.push(TriangleDefinition([i, i + 1, i + 2]));
+                self.triangles.push(TriangleDefinition([i, i + 2, i + 3]));
+            }
+
+            if self.triangles.is_empty() {
+                continue;
+            }
+
+            self.geometry.set_buffer_data(state, 0, &self.vertices);
+            self.geometry.bind(state).set_triangles(&self.triangles);
+
+            // Convert widths from font size units to distance field units.
+            let em_to_field = font.height() / (2.0 * font.spread().max(1.0));
+            let outline_width = text.outline_width() * em_to_field;
+            let glow_width = text.glow_width() * em_to_field;
+
+            let (world_matrix, billboard) = match text.mode() {
+                TextMode::Billboard => (Matrix4::new_translation(&text.global_position()), true),
+                TextMode::WorldOriented => (text.global_transform(), false),
+            };
+
+            let shader = &self.shader;
+            statistics += framebuffer.draw(
+                &self.geometry,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: true,
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    stencil_op: Default::default(),
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_texture(&shader.sdf_texture, &sdf_texture)
+                        .set_matrix4(&shader.view_projection_matrix, &view_projection)
+                        .set_matrix4(&shader.world_matrix, &world_matrix)
+                        .set_vector3(&shader.camera_up_vector, &camera_up)
+                        .set_vector3(&shader.camera_side_vector, &camera_side)
+                        .set_bool(&shader.billboard, billboard)
+                        .set_linear_color(&shader.color, &text.color())
+                        .set_linear_color(&shader.outline_color, &text.outline_color())
+                        .set_f32(&shader.outline_width, outline_width)
+                        .set_linear_color(&shader.glow_color, &text.glow_color())
+                        .set_f32(&shader.glow_width, glow_width);
+                },
+            );
+        }
+
+        statistics
+    }
+}
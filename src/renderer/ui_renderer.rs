@@ -4,7 +4,7 @@ use crate::renderer::framework::framebuffer::BlendParameters;
 use crate::{
     asset::Resource,
     core::{
-        algebra::{Matrix4, Vector2, Vector4},
+        algebra::{Matrix3, Matrix4, Vector2, Vector4},
         color::Color,
         math::Rect,
         parking_lot::Mutex,
@@ -12,7 +12,7 @@ use crate::{
         sstorage::ImmutableString,
     },
     gui::{
-        brush::Brush,
+        brush::{Brush, BrushCoordinates, MAX_GRADIENT_STOPS},
         draw::{CommandTexture, DrawingContext, SharedTexture},
     },
     renderer::{
@@ -48,6 +48,12 @@ struct UiShader {
     gradient_stops: UniformLocation,
     gradient_origin: UniformLocation,
     gradient_end: UniformLocation,
+    gradient_radius: UniformLocation,
+    gradient_angle: UniformLocation,
+    absolute_coordinates: UniformLocation,
+    brush_texture: UniformLocation,
+    brush_transform: UniformLocation,
+    brush_tiling: UniformLocation,
     resolution: UniformLocation,
     bounds_min: UniformLocation,
     bounds_max: UniformLocation,
@@ -76,6 +82,17 @@ impl UiShader {
             gradient_origin: program
                 .uniform_location(state, &ImmutableString::new("gradientOrigin"))?,
             gradient_end: program.uniform_location(state, &ImmutableString::new("gradientEnd"))?,
+            gradient_radius: program
+                .uniform_location(state, &ImmutableString::new("gradientRadius"))?,
+            gradient_angle: program
+                .uniform_location(state, &ImmutableString::new("gradientAngle"))?,
+            absolute_coordinates: program
+                .uniform_location(state, &ImmutableString::new("absoluteCoordinates"))?,
+            brush_texture: program
+                .uniform_location(state, &ImmutableString::new("brushTexture"))?,
+            brush_transform: program
+                .uniform_location(state, &ImmutableString::new("brushTransform"))?,
+            brush_tiling: program.uniform_location(state, &ImmutableString::new("brushTiling"))?,
             bounds_min: program.uniform_location(state, &ImmutableString::new("boundsMin"))?,
             bounds_max: program.uniform_location(state, &ImmutableString::new("boundsMax"))?,
             resolution: program.uniform_location(state, &ImmutableString::new("resolution"))?,
@@ -85,6 +102,15 @@ impl UiShader {
     }
 }
 
+fn fetch_texture(
+    state: &mut PipelineState,
+    texture_cache: &mut TextureCache,
+    texture: &SharedTexture,
+) -> Option<Rc<RefCell<GpuTexture>>> {
+    let texture = texture.clone().0.downcast::<Mutex<TextureState>>().ok()?;
+    texture_cache.get(state, &Texture(Resource::from(texture)))
+}
+
 /// User interface renderer allows you to render drawing context in specified render target.
 pub struct UiRenderer {
     shader: UiShader,
@@ -195,7 +221,7 @@ impl UiRenderer {
 
         state.set_scissor_test(true);
 
-        for cmd in drawing_context.get_commands() {
+        for cmd in drawing_context.get_batches() {
             let mut diffuse_texture = white_dummy.clone();
             let mut is_font_texture = false;
 
@@ -252,10 +278,10 @@ impl UiRenderer {
                     },
                 );
 
-                // Make sure main geometry will be drawn only on marked pixels.
+                // Make sure main geometry will be drawn only on pixels covered by every shape.
                 stencil_test = Some(StencilFunc {
                     func: CompareFunc::Equal,
-                    ref_value: 1,
+                    ref_value: clipping_geometry.shape_count,
                     ..Default::default()
                 });
             }
@@ -270,7 +296,11 @@ impl UiRenderer {
                                 width: size,
                                 height: size,
                             },
-                            TexturePixelKind::R8,
+                            if font.is_color() {
+                                TexturePixelKind::RGBA8
+                            } else {
+                                TexturePixelKind::R8
+                            },
                             font.atlas_pixels().to_vec(),
                             false,
                         ) {
@@ -289,27 +319,55 @@ impl UiRenderer {
                     if let Some(texture) = texture_cache.get(state, &Texture(Resource::from(tex))) {
                         diffuse_texture = texture;
                     }
-                    is_font_texture = true;
+                    // Color glyphs are drawn as regular images.
+                    is_font_texture = !font.is_color();
                 }
                 CommandTexture::Texture(texture) => {
-                    if let Ok(texture) = texture.clone().0.downcast::<Mutex<TextureState>>() {
-                        let resource = Resource::from(texture);
-                        if let Some(texture) = texture_cache.get(state, &Texture(resource)) {
-                            diffuse_texture = texture;
-                        }
+                    if let Some(texture) = fetch_texture(state, texture_cache, texture) {
+                        diffuse_texture = texture;
                     }
                 }
                 _ => (),
             }
 
-            let mut raw_stops = [0.0; 16];
-            let mut raw_colors = [Vector4::default(); 16];
+            let mut raw_stops = [0.0; MAX_GRADIENT_STOPS];
+            let mut raw_colors = [Vector4::default(); MAX_GRADIENT_STOPS];
+            let stops = cmd.brush.stops();
+            let stop_count = stops.len().min(MAX_GRADIENT_STOPS);
+            for (i, point) in stops.iter().take(stop_count).enumerate() {
+                raw_stops[i] = point.stop;
+                raw_colors[i] = point.color.as_frgba();
+            }
+
             let bounds_max = cmd.bounds.right_bottom_corner();
 
-            let (gradient_origin, gradient_end) = match cmd.brush {
-                Brush::Solid(_) => (Vector2::default(), Vector2::default()),
-                Brush::LinearGradient { from, to, .. } => (from, to),
-                Brush::RadialGradient { center, .. } => (center, Vector2::default()),
+            let (gradient_origin, gradient_end, gradient_radius, gradient_angle) = match cmd.brush {
+                Brush::Solid(_) | Brush::Texture { .. } => {
+                    (Vector2::default(), Vector2::default(), 0.0, 0.0)
+                }
+                Brush::LinearGradient { from, to, .. } => (from, to, 0.0, 0.0),
+                Brush::RadialGradient { center, radius, .. } => {
+                    (center, Vector2::default(), radius, 0.0)
+                }
+                Brush::ConicGradient { center, angle, .. } => {
+                    (center, Vector2::default(), 0.0, angle)
+                }
+            };
+
+            let mut brush_texture = white_dummy.clone();
+            let (brush_transform, brush_tiling) = match &cmd.brush {
+                Brush::Texture {
+                    texture,
+                    transform,
+                    tiling,
+                    ..
+                } => {
+                    if let Some(texture) = fetch_texture(state, texture_cache, texture) {
+                        brush_texture = texture;
+                    }
+                    (*transform, *tiling)
+                }
+                _ => (Matrix3::identity(), false),
             };
 
             let params = DrawParameters {
@@ -342,12 +400,15 @@ impl UiRenderer {
                         .set_vector2(&shader.bounds_min, &cmd.bounds.position)
                         .set_vector2(&shader.bounds_max, &bounds_max)
                         .set_bool(&shader.is_font, is_font_texture)
+                        .set_texture(&shader.brush_texture, &brush_texture)
                         .set_i32(
                             &shader.brush_type,
                             match cmd.brush {
                                 Brush::Solid(_) => 0,
                                 Brush::LinearGradient { .. } => 1,
                                 Brush::RadialGradient { .. } => 2,
+                                Brush::ConicGradient { .. } => 3,
+                                Brush::Texture { .. } => 4,
                             },
                         )
                         .set_srgb_color(
@@ -357,42 +418,19 @@ impl UiRenderer {
                                 _ => Color::WHITE,
                             },
                         )
+                        .set_bool(
+                            &shader.absolute_coordinates,
+                            cmd.brush.coordinates() == BrushCoordinates::Absolute,
+                        )
                         .set_vector2(&shader.gradient_origin, &gradient_origin)
                         .set_vector2(&shader.gradient_end, &gradient_end)
-                        .set_i32(
-                            &shader.gradient_point_count,
-                            match &cmd.brush {
-                                Brush::Solid(_) => 0,
-                                Brush::LinearGradient { stops, .. }
-                                | Brush::RadialGradient { stops, .. } => stops.len() as i32,
-                            },
-                        )
-                        .set_f32_slice(
-                            &shader.gradient_stops,
-                            match &cmd.brush {
-                                Brush::Solid(_) => &raw_stops,
-                                Brush::LinearGradient { stops, .. }
-                                | Brush::RadialGradient { stops, .. } => {
-                                    for (i, point) in stops.iter().enumerate() {
-                                        raw_stops[i] = point.stop;
-                                    }
-                                    &raw_stops
-                                }
-                            },
-                        )
-                        .set_vector4_slice(
-                            &shader.gradient_colors,
-                            match &cmd.brush {
-                                Brush::Solid(_) => &raw_colors,
-                                Brush::LinearGradient { stops, .. }
-                                | Brush::RadialGradient { stops, .. } => {
-                                    for (i, point) in stops.iter().enumerate() {
-                                        raw_colors[i] = point.color.as_frgba();
-                                    }
-                                    &raw_colors
-                                }
-                            },
-                        )
+                        .set_f32(&shader.gradient_radius, gradient_radius)
+                        .set_f32(&shader.gradient_angle, gradient_angle)
+                        .set_i32(&shader.gradient_point_count, stop_count as i32)
+                        .set_f32_slice(&shader.gradient_stops, &raw_stops)
+                        .set_vector4_slice(&shader.gradient_colors, &raw_colors)
+                        .set_matrix3(&shader.brush_transform, &brush_transform)
+                        .set_bool(&shader.brush_tiling, brush_tiling)
                         .set_f32(&shader.opacity, cmd.opacity);
                 },
             )?;
@@ -0,0 +1,315 @@
+//! Velocity buffer contains screen-space motion of every pixel since the previous frame. Motion is
+//! caused by both camera and objects (including skinned meshes). It is used by motion blur and
+//! could be used by custom render passes for effects like temporal reprojection, see
+//! [`crate::renderer::SceneRenderPassContext::velocity_texture`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Matrix4, arrayvec::ArrayVec, color::Color, math::Rect, pool::Handle,
+        scope_profile, sstorage::ImmutableString,
+    },
+    renderer::{
+        batch::{BatchStorage, BONE_MATRICES_COUNT},
+        cache::geometry::GeometryCache,
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            state::PipelineState,
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::{camera::Camera, node::Node, Scene},
+};
+use fxhash::FxHashMap;
+
+struct ObjectVelocityShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    prev_wvp_matrix: UniformLocation,
+    use_skeletal_animation: UniformLocation,
+    bone_matrices: UniformLocation,
+    prev_bone_matrices: UniformLocation,
+}
+
+impl ObjectVelocityShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/velocity_fs.glsl");
+        let vertex_source = include_str!("shaders/velocity_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "ObjectVelocityShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            prev_wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("prevWorldViewProjection"))?,
+            use_skeletal_animation: program
+                .uniform_location(state, &ImmutableString::new("useSkeletalAnimation"))?,
+            bone_matrices: program
+                .uniform_location(state, &ImmutableString::new("boneMatrices"))?,
+            prev_bone_matrices: program
+                .uniform_location(state, &ImmutableString::new("prevBoneMatrices"))?,
+            program,
+        })
+    }
+}
+
+struct CameraVelocityShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    reprojection_matrix: UniformLocation,
+}
+
+impl CameraVelocityShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/velocity_camera_fs.glsl");
+        let vertex_source = include_str!("shaders/velocity_camera_vs.glsl");
+
+        let program = GpuProgram::from_source(
+            state,
+            "CameraVelocityShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            reprojection_matrix: program
+                .uniform_location(state, &ImmutableString::new("reprojectionMatrix"))?,
+            program,
+        })
+    }
+}
+
+struct InstanceTransform {
+    world: Matrix4<f32>,
+    bone_matrices: ArrayVec<Matrix4<f32>, BONE_MATRICES_COUNT>,
+}
+
+#[derive(Default)]
+struct SceneHistory {
+    // Surface instances are identified by their owner and surface data.
+    previous: FxHashMap<(Handle<Node>, u64), InstanceTransform>,
+    current: FxHashMap<(Handle<Node>, u64), InstanceTransform>,
+    alive: bool,
+}
+
+#[derive(Copy, Clone)]
+struct CameraHistory {
+    view: Matrix4<f32>,
+    projection: Matrix4<f32>,
+    alive: bool,
+}
+
+pub(crate) struct VelocityRenderContext<'a, 'b> {
+    pub state: &'a mut PipelineState,
+    pub scene_handle: Handle<Scene>,
+    pub camera_handle: Handle<Node>,
+    pub camera: &'b Camera,
+    pub geom_cache: &'a mut GeometryCache,
+    pub batch_storage: &'a BatchStorage,
+    pub framebuffer: &'a mut FrameBuffer,
+    pub viewport: Rect<i32>,
+    pub quad: &'a GeometryBuffer,
+}
+
+/// Renders velocity buffer and keeps transforms of objects and cameras from the previous frame.
+pub(crate) struct VelocityRenderer {
+    object_shader: ObjectVelocityShader,
+    camera_shader: CameraVelocityShader,
+    scenes: FxHashMap<Handle<Scene>, SceneHistory>,
+    cameras: FxHashMap<(Handle<Scene>, Handle<Node>), CameraHistory>,
+}
+
+// Removes translation part of a view matrix, background is infinitely far, so only rotation of
+// the camera affects its motion.
+fn rotation_only(view: &Matrix4<f32>) -> Matrix4<f32> {
+    let mut rotation = *view;
+    rotation[12] = 0.0;
+    rotation[13] = 0.0;
+    rotation[14] = 0.0;
+    rotation
+}
+
+impl VelocityRenderer {
+    pub(crate) fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            object_shader: ObjectVelocityShader::new(state)?,
+            camera_shader: CameraVelocityShader::new(state)?,
+            scenes: Default::default(),
+            cameras: Default::default(),
+        })
+    }
+
+    /// Remembers transforms of every surface instance of the scene, current transforms become
+    /// previous ones. Must be called once per frame after batches were generated.
+    pub(crate) fn update_history(&mut self, scene_handle: Handle<Scene>, batches: &BatchStorage) {
+        scope_profile!();
+
+        let history = self.scenes.entry(scene_handle).or_default();
+
+        std::mem::swap(&mut history.previous, &mut history.current);
+        history.current.clear();
+        history.alive = true;
+
+        for batch in batches.batches.iter() {
+            let key = batch.data.key();
+            for instance in batch.instances.iter() {
+                history.current.insert(
+                    (instance.owner, key),
+                    InstanceTransform {
+                        world: instance.world_transform,
+                        bone_matrices: instance.bone_matrices.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Destroys history of every scene and camera that wasn't used on this frame.
+    pub(crate) fn end_frame(&mut self) {
+        self.scenes
+            .retain(|_, history| std::mem::replace(&mut history.alive, false));
+        self.cameras
+            .retain(|_, history| std::mem::replace(&mut history.alive, false));
+    }
+
+    pub(crate) fn render(&mut self, ctx: VelocityRenderContext) -> RenderPassStatistics {
+        scope_profile!();
+
+        let VelocityRenderContext {
+            state,
+            scene_handle,
+            camera_handle,
+            camera,
+            geom_cache,
+            batch_storage,
+            framebuffer,
+            viewport,
+            quad,
+        } = ctx;
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let view = camera.view_matrix();
+        let projection = camera.projection_matrix();
+        let current = CameraHistory {
+            view,
+            projection,
+            alive: true,
+        };
+        // Cameras that just appeared have no motion.
+        let previous = self
+            .cameras
+            .insert((scene_handle, camera_handle), current)
+            .unwrap_or(current);
+
+        framebuffer.clear(
+            state,
+            viewport,
+            Some(Color::from_rgba(0, 0, 0, 0)),
+            Some(1.0),
+            Some(0),
+        );
+
+        let history = self.scenes.get(&scene_handle);
+
+        for batch in batch_storage.batches.iter() {
+            let geometry = geom_cache.get(state, &batch.data);
+            let key = batch.data.key();
+
+            for instance in batch.instances.iter() {
+                if !camera.visibility_cache.is_visible(instance.owner) {
+                    continue;
+                }
+
+                // Objects that just appeared have no motion.
+                let previous_transform =
+                    history.and_then(|history| history.previous.get(&(instance.owner, key)));
+                let prev_world = previous_transform.map_or(instance.world_transform, |t| t.world);
+                let prev_bone_matrices = match previous_transform {
+                    Some(t) if t.bone_matrices.len() == instance.bone_matrices.len() => {
+                        &t.bone_matrices
+                    }
+                    _ => &instance.bone_matrices,
+                };
+
+                let (view_projection, prev_view_projection) = if instance.depth_offset != 0.0 {
+                    let mut current_projection = projection;
+                    current_projection[14] -= instance.depth_offset;
+                    let mut prev_projection = previous.projection;
+                    prev_projection[14] -= instance.depth_offset;
+                    (current_projection * view, prev_projection * previous.view)
+                } else {
+                    (projection * view, previous.projection * previous.view)
+                };
+
+                let shader = &self.object_shader;
+                statistics += framebuffer.draw(
+                    geometry,
+                    state,
+                    viewport,
+                    &shader.program,
+                    &DrawParameters {
+                        cull_face: None,
+                        color_write: Default::default(),
+                        depth_write: true,
+                        stencil_test: None,
+                        depth_test: true,
+                        blend: None,
+                        stencil_op: Default::default(),
+                    },
+                    |mut program_binding| {
+                        program_binding
+                            .set_matrix4(
+                                &shader.wvp_matrix,
+                                &(view_projection * instance.world_transform),
+                            )
+                            .set_matrix4(
+                                &shader.prev_wvp_matrix,
+                                &(prev_view_projection * prev_world),
+                            )
+                            .set_bool(&shader.use_skeletal_animation, batch.is_skinned)
+                            .set_matrix4_array(&shader.bone_matrices, &instance.bone_matrices)
+                            .set_matrix4_array(&shader.prev_bone_matrices, prev_bone_matrices);
+                    },
+                );
+            }
+        }
+
+        // Fill the rest of the frame with motion of the background.
+        let reprojection = previous.projection
+            * rotation_only(&previous.view)
+            * rotation_only(&view).try_inverse().unwrap_or_default()
+            * projection.try_inverse().unwrap_or_default();
+        let shader = &self.camera_shader;
+        statistics += framebuffer.draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &make_viewport_matrix(viewport))
+                    .set_matrix4(&shader.reprojection_matrix, &reprojection);
+            },
+        );
+
+        statistics
+    }
+}
@@ -0,0 +1,163 @@
+//! Localization resource holds a [`LocalizationBundle`] - a set of translated messages of a single
+//! language, loaded from a Fluent (`.ftl`) or gettext (`.po`) file.
+//!
+//! Language of a bundle is taken from import options, if it is not specified there, the name of
+//! the file (without extension) is used, so `data/locales/en-US.ftl` will be loaded as `en-US`
+//! bundle. Loaded bundles must be added to [`crate::gui::localization::Localization`] service to
+//! be used:
+//!
+//! ```rust,no_run
+//! # use fyrox::{engine::resource_manager::ResourceManager, gui::UserInterface};
+//! async fn load_translations(resource_manager: &ResourceManager, ui: &UserInterface) {
+//!     for path in ["data/locales/en-US.ftl", "data/locales/de.po"] {
+//!         if let Ok(resource) = resource_manager.request_localization(path).await {
+//!             ui.localization().add_bundle(resource.data_ref().bundle.clone());
+//!         }
+//!     }
+//!     ui.localization().set_language("en-US");
+//! }
+//! ```
+
+use crate::{
+    asset::{define_new_resource, Resource, ResourceData},
+    core::{
+        io::{self, FileLoadError},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::options::ImportOptions,
+    gui::localization::{LocalizationBundle, LocalizationError},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// An error that may occur during localization resource loading.
+#[derive(Debug)]
+pub enum LocalizationResourceError {
+    /// An i/o error has occurred.
+    Io(FileLoadError),
+
+    /// The file is not a valid UTF-8 text.
+    Utf8,
+
+    /// A syntax error in the file.
+    Syntax(LocalizationError),
+
+    /// The file has unsupported extension (only `ftl` and `po` are supported).
+    UnsupportedFormat,
+}
+
+impl Display for LocalizationResourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalizationResourceError::Io(v) => {
+                write!(f, "A file load error has occurred {:?}", v)
+            }
+            LocalizationResourceError::Utf8 => {
+                write!(f, "The file is not a valid UTF-8 text!")
+            }
+            LocalizationResourceError::Syntax(v) => {
+                write!(f, "{}", v)
+            }
+            LocalizationResourceError::UnsupportedFormat => {
+                write!(
+                    f,
+                    "Unsupported format, only ftl and po files are supported!"
+                )
+            }
+        }
+    }
+}
+
+impl From<FileLoadError> for LocalizationResourceError {
+    fn from(e: FileLoadError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<LocalizationError> for LocalizationResourceError {
+    fn from(e: LocalizationError) -> Self {
+        Self::Syntax(e)
+    }
+}
+
+/// State of the [`LocalizationResource`]
+#[derive(Debug, Default)]
+pub struct LocalizationResourceState {
+    pub(crate) path: PathBuf,
+    /// Translated messages.
+    pub bundle: LocalizationBundle,
+}
+
+impl Visit for LocalizationResourceState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        // Only path is serialized, the content is always loaded from the file.
+        let mut region = visitor.enter_region(name)?;
+        self.path.visit("Path", &mut region)
+    }
+}
+
+impl ResourceData for LocalizationResourceState {
+    fn path(&self) -> Cow<Path> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+}
+
+impl LocalizationResourceState {
+    /// Loads a localization bundle from the specific file path. `language` overrides the language
+    /// of the bundle, by default the name of the file is used.
+    pub async fn from_file(
+        path: &Path,
+        language: Option<String>,
+    ) -> Result<Self, LocalizationResourceError> {
+        let data = io::load_file(path).await?;
+        let source = String::from_utf8(data).map_err(|_| LocalizationResourceError::Utf8)?;
+
+        let language = language.unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let bundle = match extension.as_str() {
+            "ftl" => LocalizationBundle::from_fluent(language, &source)?,
+            "po" => LocalizationBundle::from_gettext(language, &source)?,
+            _ => return Err(LocalizationResourceError::UnsupportedFormat),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            bundle,
+        })
+    }
+}
+
+define_new_resource!(
+    /// See module docs.
+    #[derive(Reflect)]
+    #[reflect(hide_all)]
+    LocalizationResource<LocalizationResourceState, LocalizationResourceError>
+);
+
+/// Import options for localization resource.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct LocalizationImportOptions {
+    /// Language of the bundle, if it is `None`, the name of the file is used.
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl ImportOptions for LocalizationImportOptions {}
@@ -4,5 +4,6 @@
 
 pub mod curve;
 pub mod fbx;
+pub mod localization;
 pub mod model;
 pub mod texture;
@@ -197,6 +197,8 @@ pub struct TextureData {
     t_wrap_mode: TextureWrapMode,
     mip_count: u32,
     anisotropy: f32,
+    lod_bias: f32,
+    generate_mip_maps: bool,
     serialize_content: bool,
     data_hash: u64,
     is_render_target: bool,
@@ -229,6 +231,8 @@ impl Visit for TextureData {
         self.magnification_filter
             .visit("MagnificationFilter", &mut region)?;
         self.anisotropy.visit("Anisotropy", &mut region)?;
+        let _ = self.lod_bias.visit("LodBias", &mut region);
+        let _ = self.generate_mip_maps.visit("GenerateMipMaps", &mut region);
         self.s_wrap_mode.visit("SWrapMode", &mut region)?;
         self.t_wrap_mode.visit("TWrapMode", &mut region)?;
         self.mip_count.visit("MipCount", &mut region)?;
@@ -265,6 +269,8 @@ impl Default for TextureData {
             t_wrap_mode: TextureWrapMode::Repeat,
             mip_count: 1,
             anisotropy: 16.0,
+            lod_bias: 0.0,
+            generate_mip_maps: false,
             serialize_content: false,
             data_hash: 0,
             is_render_target: false,
@@ -286,6 +292,7 @@ impl Default for TextureData {
 ///     s_wrap_mode: Repeat,
 ///     t_wrap_mode: ClampToEdge,
 ///     anisotropy: 8.0,
+///     lod_bias: 0.0,
 ///     compression: NoCompression,    
 /// )
 /// ```
@@ -302,6 +309,8 @@ pub struct TextureImportOptions {
     #[serde(default)]
     pub(crate) anisotropy: f32,
     #[serde(default)]
+    pub(crate) lod_bias: f32,
+    #[serde(default)]
     pub(crate) compression: CompressionOptions,
 }
 
@@ -313,6 +322,7 @@ impl Default for TextureImportOptions {
             s_wrap_mode: TextureWrapMode::Repeat,
             t_wrap_mode: TextureWrapMode::Repeat,
             anisotropy: 16.0,
+            lod_bias: 0.0,
             compression: CompressionOptions::default(),
         }
     }
@@ -382,14 +392,27 @@ impl TextureImportOptions {
     /// Sets new anisotropy level which will be applied to every imported texture as
     /// default value.
     pub fn with_anisotropy(mut self, anisotropy: f32) -> Self {
-        self.anisotropy = anisotropy.min(1.0);
+        self.anisotropy = anisotropy.max(1.0);
         self
     }
 
     /// Sets new anisotropy level which will be applied to every imported texture as
     /// default value.
     pub fn set_anisotropy(&mut self, anisotropy: f32) {
-        self.anisotropy = anisotropy.min(1.0);
+        self.anisotropy = anisotropy.max(1.0);
+    }
+
+    /// Sets new level of detail bias which will be applied to every imported texture as
+    /// default value.
+    pub fn with_lod_bias(mut self, lod_bias: f32) -> Self {
+        self.lod_bias = lod_bias;
+        self
+    }
+
+    /// Sets new level of detail bias which will be applied to every imported texture as
+    /// default value.
+    pub fn set_lod_bias(&mut self, lod_bias: f32) {
+        self.lod_bias = lod_bias;
     }
 
     /// Sets desired texture compression.
@@ -431,6 +454,8 @@ impl Texture {
             t_wrap_mode: TextureWrapMode::Repeat,
             mip_count: 1,
             anisotropy: 1.0,
+            lod_bias: 0.0,
+            generate_mip_maps: false,
             serialize_content: false,
             data_hash: 0,
             is_render_target: true,
@@ -1333,6 +1358,32 @@ impl TextureData {
         self.anisotropy
     }
 
+    /// Sets new level of detail bias, that is added to the mip level selected by GPU when
+    /// sampling the texture. Positive values make the texture blurrier, negative - sharper.
+    /// Default value is 0.0. The bias is ignored on WebGL.
+    pub fn set_lod_bias(&mut self, lod_bias: f32) {
+        self.lod_bias = lod_bias;
+    }
+
+    /// Returns current level of detail bias.
+    pub fn lod_bias(&self) -> f32 {
+        self.lod_bias
+    }
+
+    /// Enables or disables runtime mip-map generation. When enabled, the renderer generates full
+    /// mip chain on GPU every time the texture data is uploaded. This is useful for dynamically
+    /// created textures (procedural textures, textures modified every frame, etc.), that have only
+    /// one mip level. Textures that already have mip levels and compressed textures are not
+    /// affected.
+    pub fn set_mip_map_generation(&mut self, enabled: bool) {
+        self.generate_mip_maps = enabled;
+    }
+
+    /// Returns `true` if runtime mip-map generation is enabled, `false` - otherwise.
+    pub fn is_mip_map_generation_enabled(&self) -> bool {
+        self.generate_mip_maps
+    }
+
     /// Sets new path to source file.
     pub fn set_path<P: AsRef<Path>>(&mut self, path: P) {
         self.path = path.as_ref().to_owned();
@@ -1,7 +1,7 @@
 #![allow(missing_docs)] // TODO
 
 use crate::{
-    animation::machine::Machine,
+    animation::machine::{Machine, PoseModifier, PoseModifierContext},
     core::{
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
@@ -28,6 +28,9 @@ pub struct AnimationBlendingStateMachine {
     animation_player: InheritableVariable<Handle<Node>>,
     #[visit(optional)]
     enabled: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pose_modifiers: Vec<Box<dyn PoseModifier>>,
 }
 
 impl Default for AnimationBlendingStateMachine {
@@ -37,6 +40,7 @@ impl Default for AnimationBlendingStateMachine {
             machine: Default::default(),
             animation_player: Default::default(),
             enabled: true,
+            pose_modifiers: Default::default(),
         }
     }
 }
@@ -66,6 +70,28 @@ impl AnimationBlendingStateMachine {
         self.enabled = enabled;
     }
 
+    /// Adds new pose modifier, that will be called after each evaluation of the machine, right
+    /// before the final pose is applied to the scene graph. Modifiers are called in the order of
+    /// addition.
+    pub fn add_pose_modifier<M: PoseModifier>(&mut self, modifier: M) {
+        self.pose_modifiers.push(Box::new(modifier));
+    }
+
+    /// Returns a reference to the pose modifiers of the machine.
+    pub fn pose_modifiers(&self) -> &[Box<dyn PoseModifier>] {
+        &self.pose_modifiers
+    }
+
+    /// Returns a reference to the pose modifiers of the machine.
+    pub fn pose_modifiers_mut(&mut self) -> &mut Vec<Box<dyn PoseModifier>> {
+        &mut self.pose_modifiers
+    }
+
+    /// Removes every pose modifier of the machine.
+    pub fn clear_pose_modifiers(&mut self) {
+        self.pose_modifiers.clear();
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
@@ -113,6 +139,15 @@ impl NodeTrait for AnimationBlendingStateMachine {
     fn update(&mut self, context: &mut UpdateContext) -> bool {
         if self.enabled {
             if let Some(animation_player) = context
+                .nodes
+                .try_borrow(*self.animation_player)
+                .and_then(|n| n.query_component_ref::<AnimationPlayer>())
+            {
+                self.machine
+                    .resolve_masks(&animation_player.animations, context.nodes);
+            }
+
+            let evaluated = if let Some(animation_player) = context
                 .nodes
                 .try_borrow_mut(*self.animation_player)
                 .and_then(|n| n.query_component_mut::<AnimationPlayer>())
@@ -121,11 +156,31 @@ impl NodeTrait for AnimationBlendingStateMachine {
                 // do than instead.
                 animation_player.set_auto_apply(false);
 
-                let pose = self
-                    .machine
+                self.machine
+                    .update_animation_playback(animation_player.animations.get_mut_silent());
+
+                self.machine
                     .get_mut_silent()
                     .evaluate_pose(&animation_player.animations, context.dt);
 
+                true
+            } else {
+                false
+            };
+
+            if evaluated {
+                let pose = self.machine.get_mut_silent().pose_mut();
+
+                // Modifiers are called when the animation player is not borrowed anymore, so they
+                // could access any node of the graph.
+                let modifier_context = PoseModifierContext {
+                    dt: context.dt,
+                    nodes: context.nodes,
+                };
+                for modifier in self.pose_modifiers.iter_mut() {
+                    modifier.modify_pose(pose, &modifier_context);
+                }
+
                 pose.apply_internal(context.nodes);
             }
         }
@@ -188,6 +243,7 @@ impl AnimationBlendingStateMachineBuilder {
             machine: self.machine.into(),
             animation_player: self.animation_player.into(),
             enabled: self.enabled,
+            pose_modifiers: Default::default(),
         })
     }
 
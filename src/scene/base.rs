@@ -13,7 +13,7 @@ use crate::{
     },
     engine::{resource_manager::ResourceManager, SerializationContext},
     resource::model::Model,
-    scene::{node::Node, transform::Transform},
+    scene::{graph::script_index::ScriptIndexRef, node::Node, transform::Transform},
     script::{Script, ScriptTrait},
     utils::log::Log,
 };
@@ -273,6 +273,60 @@ pub enum ScriptMessage {
     },
 }
 
+/// A script attached to a scene node. A node can have any number of scripts, they're initialized
+/// and updated in the order of their appearance in [`Base::scripts`].
+#[derive(Debug, Clone, Reflect)]
+pub struct ScriptRecord {
+    /// An instance of the script. It could be `None`, this is mostly used by the editor, when a
+    /// new record is added, but no script type was selected yet.
+    pub script: Option<Script>,
+
+    /// Disabled scripts won't be updated and won't receive OS events, but they still will be
+    /// initialized.
+    pub enabled: bool,
+}
+
+impl Default for ScriptRecord {
+    fn default() -> Self {
+        Self {
+            script: None,
+            enabled: true,
+        }
+    }
+}
+
+impl ScriptRecord {
+    /// Creates new enabled script record.
+    pub fn new(script: Script) -> Self {
+        Self {
+            script: Some(script),
+            enabled: true,
+        }
+    }
+}
+
+impl Visit for ScriptRecord {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.enabled.visit("Enabled", &mut region)?;
+
+        // Script visiting may fail for various reasons:
+        //
+        // 1) Data inside a script is not compatible with latest code (there is no backward
+        //    compatibility for the data)
+        // 2) Script was removed in the game.
+        //
+        // None of the reasons are fatal and we should still give an ability to load such node
+        // to edit or remove it.
+        if let Err(e) = visit_opt_script("Script", &mut self.script, &mut region) {
+            Log::err(format!("Unable to visit script. Reason: {:?}", e))
+        }
+
+        Ok(())
+    }
+}
+
 /// Unique id of the node. It can be shared across multiple resources (read - prefabs), to preserve parent-child
 /// links. It is useful to create various resources that can bind to any instance of the node. For example, an
 /// animation resource could be made for a specific node, but with the `instance_id` it can be retargetted to any
@@ -288,6 +342,9 @@ impl Visit for InstanceId {
     }
 }
 
+/// Default layer mask of scene nodes - every node belongs to the first layer only.
+pub const DEFAULT_LAYER: u32 = 1;
+
 /// Base scene graph node is a simplest possible node, it is used to build more complex ones using composition.
 /// It contains all fundamental properties for each scene graph nodes, like local and global transforms, name,
 /// lifetime, etc. Base node is a building block for all complex node hierarchies - it contains list of children
@@ -316,6 +373,9 @@ pub struct Base {
     #[reflect(hidden)]
     pub(crate) script_message_sender: Option<Sender<ScriptMessage>>,
 
+    #[reflect(hidden)]
+    pub(crate) script_index: ScriptIndexRef,
+
     #[reflect(setter = "set_name_internal")]
     pub(crate) name: InheritableVariable<String>,
 
@@ -324,6 +384,9 @@ pub struct Base {
     #[reflect(setter = "set_visibility")]
     visibility: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_enabled")]
+    enabled: InheritableVariable<bool>,
+
     // Maximum amount of Some(time) that node will "live" or None
     // if node has undefined lifetime.
     #[reflect(hidden)] // TEMPORARILY HIDDEN. It causes crashes when set from the editor.
@@ -342,6 +405,12 @@ pub struct Base {
     #[reflect(setter = "set_tag")]
     tag: InheritableVariable<String>,
 
+    #[reflect(setter = "set_tags")]
+    tags: InheritableVariable<Vec<String>>,
+
+    #[reflect(setter = "set_layer")]
+    pub(crate) layer: InheritableVariable<u32>,
+
     #[reflect(setter = "set_cast_shadows")]
     cast_shadows: InheritableVariable<bool>,
 
@@ -366,6 +435,9 @@ pub struct Base {
     #[reflect(hidden)]
     pub(crate) global_visibility: Cell<bool>,
 
+    #[reflect(hidden)]
+    pub(crate) global_enabled: Cell<bool>,
+
     #[reflect(hidden)]
     pub(crate) parent: Handle<Node>,
 
@@ -395,19 +467,19 @@ pub struct Base {
     #[reflect(hidden)]
     pub(crate) instance_id: InstanceId,
 
-    // Current script of the scene node.
+    // Current scripts of the scene node.
     //
     // # Important notes
     //
-    // WARNING: Setting a new script via reflection will break normal script destruction process!
+    // WARNING: Setting new scripts via reflection will break normal script destruction process!
     // Use it at your own risk only when you're completely sure what you are doing.
-    #[reflect(setter = "set_script_internal")]
-    pub(crate) script: Option<Script>,
+    #[reflect(setter = "set_scripts_internal")]
+    pub(crate) scripts: Vec<ScriptRecord>,
 }
 
 impl Drop for Base {
     fn drop(&mut self) {
-        self.remove_script();
+        self.remove_scripts();
     }
 }
 
@@ -549,6 +621,30 @@ impl Base {
         *self.visibility
     }
 
+    /// Enables or disables a node. Unlike visibility, which affects only rendering, disabled node
+    /// is fully excluded from the scene: it is not rendered and not updated, its physical entities
+    /// (rigid bodies, colliders, joints) are removed from physics world, its sound sources are paused
+    /// and its scripts are disabled (see [`crate::script::ScriptTrait::on_disable`]). Disabling a
+    /// node disables all its descendants too.
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) -> bool {
+        self.enabled.set(enabled)
+    }
+
+    /// Returns `true` if the node is enabled, `false` - otherwise. Keep in mind, that the node could
+    /// be enabled, but one of its ancestors could be disabled, use [`Self::is_globally_enabled`] to
+    /// check the final state.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        *self.enabled
+    }
+
+    /// Returns `true` if the node and all its ancestors are enabled, `false` - otherwise.
+    #[inline]
+    pub fn is_globally_enabled(&self) -> bool {
+        self.global_enabled.get()
+    }
+
     /// Returns current **local-space** bounding box. Keep in mind that this value is just
     /// a placeholder, because there is not information to calculate actual bounding box.
     #[inline]
@@ -583,6 +679,7 @@ impl Base {
     /// all its children will be invisible. It defines if object will be rendered. It is *not* the same as real
     /// visibility from point of view of a camera. To check if object is visible from some camera, use
     /// [VisibilityCache](super::visibility::VisibilityCache). However this still can't tell you if object is behind obstacle or not.
+    /// Disabled nodes (see [`Self::set_enabled`]) are always invisible.
     #[inline]
     pub fn global_visibility(&self) -> bool {
         self.global_visibility.get()
@@ -684,6 +781,77 @@ impl Base {
         self.tag.set(tag)
     }
 
+    /// Returns a set of general-purpose tags of the node. Unlike [`Self::tag`], a node can have
+    /// any number of such tags.
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Sets new set of tags. Duplicated tags will be removed.
+    #[inline]
+    pub fn set_tags(&mut self, mut tags: Vec<String>) -> Vec<String> {
+        let mut i = 0;
+        while i < tags.len() {
+            if tags[..i].contains(&tags[i]) {
+                tags.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        self.tags.set(tags)
+    }
+
+    /// Returns `true` if the node has the given tag.
+    #[inline]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Adds new tag to the node. Returns `false` if the node already has the tag.
+    #[inline]
+    pub fn add_tag<S: AsRef<str>>(&mut self, tag: S) -> bool {
+        if self.has_tag(tag.as_ref()) {
+            false
+        } else {
+            self.tags.get_mut().push(tag.as_ref().to_owned());
+            true
+        }
+    }
+
+    /// Removes the given tag from the node. Returns `false` if the node did not have the tag.
+    #[inline]
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        if let Some(index) = self.tags.iter().position(|t| t == tag) {
+            self.tags.get_mut().remove(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns layer mask of the node. Every bit of the mask defines whether the node belongs
+    /// to a respective layer or not. Layers are used by cameras (see
+    /// [`crate::scene::camera::Camera::set_layer_mask`]), ray casting and physics collision
+    /// filtering (see [`crate::scene::graph::physics::LayerCollisionMatrix`]). By default every
+    /// node belongs to the first layer only.
+    #[inline]
+    pub fn layer(&self) -> u32 {
+        *self.layer
+    }
+
+    /// Sets new layer mask of the node. See [`Self::layer`] for more info.
+    #[inline]
+    pub fn set_layer(&mut self, layer: u32) -> u32 {
+        self.layer.set(layer)
+    }
+
+    /// Returns `true` if the node belongs to a layer with the given index (`0..32`).
+    #[inline]
+    pub fn is_in_layer(&self, index: u32) -> bool {
+        index < u32::BITS && *self.layer & (1 << index) != 0
+    }
+
     /// Return the frustum_culling flag
     #[inline]
     pub fn frustum_culling(&self) -> bool {
@@ -724,40 +892,124 @@ impl Base {
         self.instance_id
     }
 
-    fn remove_script(&mut self) {
+    fn destroy_script(&self, script: Script) {
+        let type_id = script.as_any().type_id();
+        if !self
+            .script_instances()
+            .any(|s| s.as_any().type_id() == type_id)
+        {
+            self.script_index.remove(self.self_handle, Some(&script));
+        }
+
         // Send script to the graph to destroy script instances correctly.
-        if let Some(script) = self.script.take() {
-            if let Some(sender) = self.script_message_sender.as_ref() {
-                Log::verify(sender.send(ScriptMessage::DestroyScript {
-                    script,
-                    handle: self.self_handle,
-                }));
-            } else {
-                Log::warn(format!(
-                    "There is a script instance on a node {}, but no message sender. \
-                    The script won't be correctly destroyed!",
-                    self.name(),
-                ))
+        if let Some(sender) = self.script_message_sender.as_ref() {
+            Log::verify(sender.send(ScriptMessage::DestroyScript {
+                script,
+                handle: self.self_handle,
+            }));
+        } else {
+            Log::warn(format!(
+                "There is a script instance on a node {}, but no message sender. \
+                The script won't be correctly destroyed!",
+                self.name(),
+            ))
+        }
+    }
+
+    /// Removes every script from the scene node.
+    #[inline]
+    pub fn remove_scripts(&mut self) {
+        for record in std::mem::take(&mut self.scripts) {
+            if let Some(script) = record.script {
+                self.destroy_script(script);
             }
         }
     }
 
-    /// Sets new script for the scene node.
+    /// Removes a script at the given index and returns `true` if there was a record at the index.
+    /// Scripts that follow the removed one will be shifted.
     #[inline]
-    pub fn set_script(&mut self, script: Option<Script>) {
-        self.remove_script();
-        self.script = script;
-        if let Some(sender) = self.script_message_sender.as_ref() {
-            if self.script.is_some() {
-                Log::verify(sender.send(ScriptMessage::InitializeScript {
-                    handle: self.self_handle,
-                }));
+    pub fn remove_script_at(&mut self, index: usize) -> bool {
+        if index < self.scripts.len() {
+            if let Some(script) = self.scripts.remove(index).script {
+                self.destroy_script(script);
             }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds new script to the end of the list of scripts of the scene node and returns its
+    /// index. Scripts are initialized and updated in the order of their appearance in the list.
+    #[inline]
+    pub fn add_script(&mut self, script: Script) -> usize {
+        self.script_index.add(self.self_handle, Some(&script));
+        self.scripts.push(ScriptRecord::new(script));
+        if let Some(sender) = self.script_message_sender.as_ref() {
+            Log::verify(sender.send(ScriptMessage::InitializeScript {
+                handle: self.self_handle,
+            }));
+        }
+        self.scripts.len() - 1
+    }
+
+    /// Replaces every script of the scene node with the given script. `None` removes all scripts.
+    #[inline]
+    pub fn set_script(&mut self, script: Option<Script>) {
+        self.remove_scripts();
+        if let Some(script) = script {
+            self.add_script(script);
+        }
+    }
+
+    fn set_scripts_internal(&mut self, scripts: Vec<ScriptRecord>) -> Vec<ScriptRecord> {
+        for record in self.scripts.iter() {
+            self.script_index
+                .remove(self.self_handle, record.script.as_ref());
+        }
+        let prev = std::mem::replace(&mut self.scripts, scripts);
+        for record in self.scripts.iter() {
+            self.script_index
+                .add(self.self_handle, record.script.as_ref());
+        }
+        prev
+    }
+
+    /// Returns a slice with every script record of the scene node.
+    #[inline]
+    pub fn scripts(&self) -> &[ScriptRecord] {
+        &self.scripts
+    }
+
+    /// Returns an iterator over every script instance of the scene node.
+    #[inline]
+    pub fn script_instances(&self) -> impl Iterator<Item = &Script> {
+        self.scripts.iter().filter_map(|r| r.script.as_ref())
+    }
+
+    /// Returns total amount of script records of the scene node.
+    #[inline]
+    pub fn script_count(&self) -> usize {
+        self.scripts.len()
+    }
+
+    /// Enables or disables a script at the given index. Disabled scripts are not updated and do
+    /// not receive OS events. Returns `false` if there is no script at the index.
+    #[inline]
+    pub fn set_script_enabled(&mut self, index: usize, enabled: bool) -> bool {
+        if let Some(record) = self.scripts.get_mut(index) {
+            record.enabled = enabled;
+            true
+        } else {
+            false
         }
     }
 
-    fn set_script_internal(&mut self, script: Option<Script>) -> Option<Script> {
-        std::mem::replace(&mut self.script, script)
+    /// Returns `true` if a script at the given index exists and enabled.
+    #[inline]
+    pub fn is_script_enabled(&self, index: usize) -> bool {
+        self.scripts.get(index).map_or(false, |r| r.enabled)
     }
 
     /// Checks if the node has a script of a particular type. Returns `false` if there is no script
@@ -767,27 +1019,43 @@ impl Base {
         self.try_get_script::<T>().is_some()
     }
 
-    /// Tries to cast current script instance (if any) to given type and returns a shared reference
-    /// to it on successful cast.
+    /// Searches for a first script of the given type and returns a shared reference to it on
+    /// success.
     #[inline]
     pub fn try_get_script<T: ScriptTrait>(&self) -> Option<&T> {
-        self.script.as_ref().and_then(|s| s.cast::<T>())
+        self.script_instances().find_map(|s| s.cast::<T>())
     }
 
-    /// Tries to cast current script instance (if any) to given type and returns a mutable reference
-    /// to it on successful cast.
+    /// Searches for a first script of the given type and returns a mutable reference to it on
+    /// success.
     #[inline]
     pub fn try_get_script_mut<T: ScriptTrait>(&mut self) -> Option<&mut T> {
-        self.script.as_mut().and_then(|s| s.cast_mut::<T>())
+        self.scripts
+            .iter_mut()
+            .filter_map(|r| r.script.as_mut())
+            .find_map(|s| s.cast_mut::<T>())
+    }
+
+    /// Shortcut for [`Self::try_get_script`], that reads better in gameplay code:
+    /// `node.script_as::<Turret>()`.
+    #[inline]
+    pub fn script_as<T: ScriptTrait>(&self) -> Option<&T> {
+        self.try_get_script::<T>()
     }
 
-    /// Returns shared reference to current script instance.
+    /// Shortcut for [`Self::try_get_script_mut`].
+    #[inline]
+    pub fn script_as_mut<T: ScriptTrait>(&mut self) -> Option<&mut T> {
+        self.try_get_script_mut::<T>()
+    }
+
+    /// Returns shared reference to the first script instance.
     #[inline]
     pub fn script(&self) -> Option<&Script> {
-        self.script.as_ref()
+        self.script_instances().next()
     }
 
-    /// Returns mutable reference to current script instance.
+    /// Returns mutable reference to the first script instance.
     ///
     /// # Important notes
     ///
@@ -796,19 +1064,19 @@ impl Base {
     /// to replace the script.
     #[inline]
     pub fn script_mut(&mut self) -> Option<&mut Script> {
-        self.script.as_mut()
+        self.scripts.iter_mut().find_map(|r| r.script.as_mut())
     }
 
-    /// Returns a copy of the current script.
+    /// Returns a copy of the first script.
     #[inline]
     pub fn script_cloned(&self) -> Option<Script> {
-        self.script.clone()
+        self.script().cloned()
     }
 
     /// Internal. Do not use.
     #[inline]
-    pub fn script_inner(&mut self) -> &mut Option<Script> {
-        &mut self.script
+    pub fn scripts_inner(&mut self) -> &mut Vec<ScriptRecord> {
+        &mut self.scripts
     }
 
     /// Updates node lifetime and returns true if the node is still alive, false - otherwise.
@@ -822,8 +1090,8 @@ impl Base {
     }
 
     pub(crate) fn restore_resources(&mut self, resource_manager: ResourceManager) {
-        if let Some(script) = self.script.as_mut() {
-            script.restore_resources(resource_manager);
+        for script in self.scripts.iter_mut().filter_map(|r| r.script.as_mut()) {
+            script.restore_resources(resource_manager.clone());
         }
     }
 }
@@ -891,25 +1159,29 @@ impl Visit for Base {
         self.original_handle_in_resource
             .visit("Original", &mut region)?;
         self.tag.visit("Tag", &mut region)?;
+        let _ = self.tags.visit("Tags", &mut region);
+        let _ = self.layer.visit("Layer", &mut region);
         let _ = self.properties.visit("Properties", &mut region);
         let _ = self.frustum_culling.visit("FrustumCulling", &mut region);
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
         let _ = self.instance_id.visit("InstanceId", &mut region);
+        let _ = self.enabled.visit("Enabled", &mut region);
 
-        // Script visiting may fail for various reasons:
-        //
-        // 1) Data inside a script is not compatible with latest code (there is no backward
-        //    compatibility for the data)
-        // 2) Script was removed in the game.
-        //
-        // None of the reasons are fatal and we should still give an ability to load such node
-        // to edit or remove it.
-        if let Err(e) = visit_opt_script("Script", &mut self.script, &mut region) {
-            // Do not spam with error messages if there is missing `Script` field. It is ok
-            // for old scenes not to have script at all.
-            if !matches!(e, VisitError::RegionDoesNotExist(_)) {
-                Log::err(format!("Unable to visit script. Reason: {:?}", e))
+        if let Err(e) = self.scripts.visit("Scripts", &mut region) {
+            if !region.is_reading() {
+                return Err(e);
+            }
+
+            // Backward compatibility - older versions could have only one script per node.
+            let mut script = None;
+            if let Err(e) = visit_opt_script("Script", &mut script, &mut region) {
+                // Do not spam with error messages if there is missing `Script` field. It is ok
+                // for old scenes not to have script at all.
+                if !matches!(e, VisitError::RegionDoesNotExist(_)) {
+                    Log::err(format!("Unable to visit script. Reason: {:?}", e))
+                }
             }
+            self.scripts = script.map(ScriptRecord::new).into_iter().collect();
         }
 
         Ok(())
@@ -920,6 +1192,7 @@ impl Visit for Base {
 pub struct BaseBuilder {
     name: String,
     visibility: bool,
+    enabled: bool,
     local_transform: Transform,
     children: Vec<Handle<Node>>,
     lifetime: Option<f32>,
@@ -928,9 +1201,11 @@ pub struct BaseBuilder {
     mobility: Mobility,
     inv_bind_pose_transform: Matrix4<f32>,
     tag: String,
+    tags: Vec<String>,
+    layer: u32,
     frustum_culling: bool,
     cast_shadows: bool,
-    script: Option<Script>,
+    scripts: Vec<Script>,
     instance_id: InstanceId,
 }
 
@@ -947,6 +1222,7 @@ impl BaseBuilder {
         Self {
             name: Default::default(),
             visibility: true,
+            enabled: true,
             local_transform: Default::default(),
             children: Default::default(),
             lifetime: None,
@@ -955,9 +1231,11 @@ impl BaseBuilder {
             mobility: Mobility::Dynamic,
             inv_bind_pose_transform: Matrix4::identity(),
             tag: Default::default(),
+            tags: Default::default(),
+            layer: DEFAULT_LAYER,
             frustum_culling: true,
             cast_shadows: true,
-            script: None,
+            scripts: Default::default(),
             instance_id: InstanceId(Uuid::new_v4()),
         }
     }
@@ -1039,6 +1317,20 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets desired set of general-purpose tags.
+    #[inline]
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets desired layer mask. See [`Base::layer`] for more info.
+    #[inline]
+    pub fn with_layer(mut self, layer: u32) -> Self {
+        self.layer = layer;
+        self
+    }
+
     /// Sets desired frustum_culling flag.
     #[inline]
     pub fn with_frustum_culling(mut self, frustum_culling: bool) -> Self {
@@ -1053,10 +1345,18 @@ impl BaseBuilder {
         self
     }
 
-    /// Sets desired script of the node.
+    /// Adds a script to the node. Could be called multiple times to add multiple scripts, they
+    /// will be initialized and updated in the same order.
     #[inline]
     pub fn with_script(mut self, script: Script) -> Self {
-        self.script = Some(script);
+        self.scripts.push(script);
+        self
+    }
+
+    /// Sets whether the node should be enabled or not, see [`Base::set_enabled`] for more info.
+    #[inline]
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
         self
     }
 
@@ -1072,12 +1372,15 @@ impl BaseBuilder {
         Base {
             self_handle: Default::default(),
             script_message_sender: None,
+            script_index: Default::default(),
             name: self.name.into(),
             children: self.children,
             local_transform: self.local_transform,
             lifetime: self.lifetime.into(),
             visibility: self.visibility.into(),
             global_visibility: Cell::new(true),
+            enabled: self.enabled.into(),
+            global_enabled: Cell::new(true),
             parent: Handle::NONE,
             global_transform: Cell::new(Matrix4::identity()),
             inv_bind_pose_transform: self.inv_bind_pose_transform,
@@ -1088,11 +1391,13 @@ impl BaseBuilder {
             lod_group: self.lod_group.into(),
             mobility: self.mobility.into(),
             tag: self.tag.into(),
+            tags: self.tags.into(),
+            layer: self.layer.into(),
             properties: Default::default(),
             transform_modified: Cell::new(false),
             frustum_culling: self.frustum_culling.into(),
             cast_shadows: self.cast_shadows.into(),
-            script: self.script,
+            scripts: self.scripts.into_iter().map(ScriptRecord::new).collect(),
             instance_id: InstanceId(Uuid::new_v4()),
         }
     }
@@ -1123,8 +1428,11 @@ pub mod test {
     fn test_base_inheritance() {
         let parent = BaseBuilder::new()
             .with_visibility(false)
+            .with_enabled(false)
             .with_depth_offset(1.0)
             .with_tag("Tag".to_string())
+            .with_tags(vec!["Enemy".to_string()])
+            .with_layer(0b101)
             .with_name("Name")
             .with_lifetime(1.0)
             .with_frustum_culling(false)
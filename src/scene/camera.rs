@@ -1,13 +1,22 @@
 //! Contains all methods and structures to create and manage cameras.
 //!
-//! Camera allows you to see world from specific point in world. Currently only
-//! perspective projection is supported.
+//! Camera allows you to see world from specific point in world. There are three projection modes
+//! available: perspective, orthographic and off-axis (asymmetric) perspective. It is also possible
+//! to override projection matrix completely (see [`Camera::set_projection_override`]) and to replace
+//! near clipping plane with an arbitrary plane (see [`Camera::set_oblique_clip_plane`]).
 //!
 //! # Multiple cameras
 //!
 //! Fyrox supports multiple cameras per scene, it means that you can create split
 //! screen games, make picture-in-picture insertions in your main camera view and
-//! any other combinations you need.
+//! any other combinations you need. Cameras are drawn in the order defined by
+//! [`Camera::render_order`], so it is possible to control which viewport will be drawn on top.
+//!
+//! # Render to texture
+//!
+//! A camera can render into a texture instead of the screen, use [`Camera::set_render_target`]
+//! for that. The texture then can be used as any other texture - in materials (mirrors, security
+//! cameras, portals) or in user interface (minimaps).
 //!
 //! ## Performance
 //!
@@ -17,7 +26,7 @@
 use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
-        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, ray::Ray, Rect},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, plane::Plane, ray::Ray, Rect},
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
@@ -30,6 +39,8 @@ use crate::{
         base::{Base, BaseBuilder},
         graph::Graph,
         node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
+        post_processing::PostProcessStack,
+        sky::ProceduralSky,
         visibility::VisibilityCache,
     },
     utils::log::Log,
@@ -162,18 +173,155 @@ impl OrthographicProjection {
     }
 }
 
+/// Off-axis (asymmetric) perspective projection. Unlike [`PerspectiveProjection`], viewing frustum
+/// is not required to be symmetric around look axis. It is useful for head-tracked displays, VR-like
+/// setups or multi-monitor setups where each monitor shows a part of a single large frustum.
+///
+/// Frustum extents are defined on the near clipping plane, aspect ratio of a frame is ignored.
+#[derive(Reflect, Clone, Debug, PartialEq, Visit)]
+pub struct OffAxisProjection {
+    /// Left side of the frustum on the near clipping plane.
+    #[reflect(step = 0.01)]
+    pub left: f32,
+    /// Right side of the frustum on the near clipping plane.
+    #[reflect(step = 0.01)]
+    pub right: f32,
+    /// Bottom side of the frustum on the near clipping plane.
+    #[reflect(step = 0.01)]
+    pub bottom: f32,
+    /// Top side of the frustum on the near clipping plane.
+    #[reflect(step = 0.01)]
+    pub top: f32,
+    /// Location of the near clipping plane. If it is larger than [`Self::z_far`] then it will be
+    /// treated like far clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_near: f32,
+    /// Location of the far clipping plane. If it is less than [`Self::z_near`] then it will be
+    /// treated like near clipping plane.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub z_far: f32,
+}
+
+impl Default for OffAxisProjection {
+    fn default() -> Self {
+        Self::from_fov_and_shift(
+            75.0f32.to_radians(),
+            16.0 / 9.0,
+            Vector2::default(),
+            0.025,
+            2048.0,
+        )
+    }
+}
+
+impl OffAxisProjection {
+    /// Creates off-axis projection from vertical field of view, aspect ratio and lens shift. Lens
+    /// shift is defined in fractions of frustum size, for example `(0.5, 0.0)` shifts the frustum
+    /// to the right by half of its width.
+    pub fn from_fov_and_shift(
+        fov: f32,
+        aspect: f32,
+        shift: Vector2<f32>,
+        z_near: f32,
+        z_far: f32,
+    ) -> Self {
+        let half_height = z_near * (fov * 0.5).tan();
+        let half_width = half_height * aspect;
+        let dx = shift.x * 2.0 * half_width;
+        let dy = shift.y * 2.0 * half_height;
+        Self {
+            left: -half_width + dx,
+            right: half_width + dx,
+            bottom: -half_height + dy,
+            top: half_height + dy,
+            z_near,
+            z_far,
+        }
+    }
+
+    /// Returns approximate vertical field of view of the frustum.
+    #[inline]
+    pub fn fov(&self) -> f32 {
+        let z_near = self.z_near.max(f32::EPSILON);
+        (self.top / z_near).atan() - (self.bottom / z_near).atan()
+    }
+
+    /// Sets new near clipping plane distance. Frustum extents will be scaled accordingly so the
+    /// shape of the frustum will be preserved.
+    #[inline]
+    pub fn set_z_near(&mut self, z_near: f32) {
+        if self.z_near > f32::EPSILON {
+            let k = z_near / self.z_near;
+            self.left *= k;
+            self.right *= k;
+            self.bottom *= k;
+            self.top *= k;
+        }
+        self.z_near = z_near;
+    }
+
+    /// Returns off-axis projection matrix.
+    #[inline]
+    pub fn matrix(&self) -> Matrix4<f32> {
+        fn non_zero(value: f32, limit: f32) -> f32 {
+            if value.abs() < limit {
+                limit
+            } else {
+                value
+            }
+        }
+
+        let limit = 10.0 * f32::EPSILON;
+
+        let z_near = self.z_far.min(self.z_near).max(limit);
+        let mut z_far = self.z_far.max(self.z_near);
+
+        // Prevent planes from superimposing which could cause panic.
+        if z_far - z_near < limit {
+            z_far += limit;
+        }
+
+        let width = non_zero(self.right - self.left, limit);
+        let height = non_zero(self.top - self.bottom, limit);
+        let depth = z_far - z_near;
+
+        Matrix4::new(
+            2.0 * z_near / width,
+            0.0,
+            (self.right + self.left) / width,
+            0.0,
+            0.0,
+            2.0 * z_near / height,
+            (self.top + self.bottom) / height,
+            0.0,
+            0.0,
+            0.0,
+            -(z_far + z_near) / depth,
+            -2.0 * z_far * z_near / depth,
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        )
+    }
+}
+
 /// A method of projection. Different projection types suitable for different purposes:
 ///
 /// 1) Perspective projection most useful for 3D games, it makes a scene to look most natural,
 /// objects will look smaller with increasing distance.
 /// 2) Orthographic projection most useful for 2D games, objects won't look smaller with increasing
 /// distance.  
+/// 3) Off-axis projection is a perspective projection with asymmetric frustum, it is useful for
+/// head-tracked displays, VR-like or multi-monitor setups.
 #[derive(Reflect, Clone, Debug, PartialEq, Visit, AsRefStr, EnumString, EnumVariantNames)]
 pub enum Projection {
     /// See [`PerspectiveProjection`] docs.
     Perspective(PerspectiveProjection),
     /// See [`OrthographicProjection`] docs.
     Orthographic(OrthographicProjection),
+    /// See [`OffAxisProjection`] docs.
+    OffAxis(OffAxisProjection),
 }
 
 impl Projection {
@@ -184,6 +332,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_near = z_near,
             Projection::Orthographic(ref mut v) => v.z_near = z_near,
+            Projection::OffAxis(ref mut v) => v.set_z_near(z_near),
         }
         self
     }
@@ -195,6 +344,7 @@ impl Projection {
         match self {
             Projection::Perspective(ref mut v) => v.z_far = z_far,
             Projection::Orthographic(ref mut v) => v.z_far = z_far,
+            Projection::OffAxis(ref mut v) => v.z_far = z_far,
         }
         self
     }
@@ -205,6 +355,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near = z_near,
             Projection::Orthographic(v) => v.z_near = z_near,
+            Projection::OffAxis(v) => v.set_z_near(z_near),
         }
     }
 
@@ -214,6 +365,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far = z_far,
             Projection::Orthographic(v) => v.z_far = z_far,
+            Projection::OffAxis(v) => v.z_far = z_far,
         }
     }
 
@@ -223,6 +375,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_near,
             Projection::Orthographic(v) => v.z_near,
+            Projection::OffAxis(v) => v.z_near,
         }
     }
 
@@ -232,6 +385,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.z_far,
             Projection::Orthographic(v) => v.z_far,
+            Projection::OffAxis(v) => v.z_far,
         }
     }
 
@@ -241,6 +395,7 @@ impl Projection {
         match self {
             Projection::Perspective(v) => v.matrix(frame_size),
             Projection::Orthographic(v) => v.matrix(frame_size),
+            Projection::OffAxis(v) => v.matrix(),
         }
     }
 }
@@ -287,6 +442,44 @@ impl Default for Exposure {
     }
 }
 
+/// View settings of a sorting layer of 2D objects (see [`crate::scene::dim2::rectangle::Rectangle::set_sorting_layer`]).
+/// It allows to render some layers with their own orthographic projection and to scroll them with
+/// different speed, which could be used to create parallax backgrounds or in-game overlays that
+/// do not move with the camera.
+///
+/// Layers with custom view are rendered without depth test, which means that their order is defined
+/// solely by sorting layer index and order in layer.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct SortingLayerView {
+    /// Index of a sorting layer the settings are applied to.
+    pub layer: i32,
+    /// Whether the layer should be rendered by the camera or not.
+    pub visible: bool,
+    /// Optional orthographic projection of the layer. If not set, the projection of the camera is used.
+    pub projection: Option<OrthographicProjection>,
+    /// Scrolling speed of the layer relative to the camera movement. `(1.0, 1.0)` means that the
+    /// layer moves with the same speed as the camera, `(0.0, 0.0)` - the layer does not move at all.
+    pub parallax: Vector2<f32>,
+}
+
+impl Default for SortingLayerView {
+    fn default() -> Self {
+        Self {
+            layer: 0,
+            visible: true,
+            projection: None,
+            parallax: Vector2::new(1.0, 1.0),
+        }
+    }
+}
+
+impl SortingLayerView {
+    /// Returns `true` if the settings change the way the layer is viewed.
+    pub fn has_custom_view(&self) -> bool {
+        self.projection.is_some() || self.parallax != Vector2::new(1.0, 1.0)
+    }
+}
+
 /// See module docs.
 #[derive(Debug, Visit, Reflect, Clone)]
 pub struct Camera {
@@ -316,6 +509,38 @@ pub struct Camera {
     #[reflect(setter = "set_color_grading_enabled")]
     color_grading_enabled: InheritableVariable<bool>,
 
+    #[visit(optional)] // Backward compatibility
+    #[reflect(setter = "set_post_processing")]
+    post_processing: InheritableVariable<PostProcessStack>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(setter = "set_render_order")]
+    render_order: InheritableVariable<i32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    render_target: Option<Texture>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(setter = "set_layer_mask")]
+    layer_mask: InheritableVariable<u32>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(setter = "set_sorting_layer_views")]
+    sorting_layer_views: InheritableVariable<Vec<SortingLayerView>>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(hidden)]
+    projection_override: InheritableVariable<Option<Matrix4<f32>>>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(hidden)]
+    oblique_clip_plane: InheritableVariable<Option<Plane>>,
+
+    #[visit(optional)] // Backward compatibility
+    #[reflect(setter = "set_procedural_sky")]
+    procedural_sky: InheritableVariable<Option<ProceduralSky>>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     view_matrix: Matrix4<f32>,
@@ -366,7 +591,58 @@ impl Camera {
         let up = self.base.up_vector();
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
-        self.projection_matrix = self.projection.matrix(frame_size);
+
+        let mut projection_matrix = self
+            .projection_override
+            .unwrap_or_else(|| self.projection.matrix(frame_size));
+
+        if let Some(plane) = *self.oblique_clip_plane {
+            if let Some(oblique) =
+                make_oblique_projection(projection_matrix, self.view_matrix, plane)
+            {
+                projection_matrix = oblique;
+            }
+        }
+
+        self.projection_matrix = projection_matrix;
+    }
+
+    /// Sets a projection matrix that will be used instead of the matrix produced by current
+    /// [`Projection`]. The override is respected by everything that uses camera matrices: rendering,
+    /// culling, picking rays, etc. Pass `None` to remove the override.
+    ///
+    /// # Notes
+    ///
+    /// Cascaded shadow maps can't split an arbitrary frustum, so every cascade of a directional light
+    /// will cover whole camera frustum when the override is set.
+    #[inline]
+    pub fn set_projection_override(
+        &mut self,
+        projection_override: Option<Matrix4<f32>>,
+    ) -> Option<Matrix4<f32>> {
+        self.projection_override.set(projection_override)
+    }
+
+    /// Returns current projection matrix override (if any).
+    #[inline]
+    pub fn projection_override(&self) -> Option<Matrix4<f32>> {
+        *self.projection_override
+    }
+
+    /// Sets a world-space plane that will be used as near clipping plane (oblique near-plane
+    /// clipping). It is typically used to render planar reflections and portals, to clip everything
+    /// that is "behind" the reflection plane. The camera must be located on the negative side of the
+    /// plane, in other words the plane normal must point away from the camera. Pass `None` to use
+    /// regular near clipping plane.
+    #[inline]
+    pub fn set_oblique_clip_plane(&mut self, plane: Option<Plane>) -> Option<Plane> {
+        self.oblique_clip_plane.set(plane)
+    }
+
+    /// Returns current world-space oblique clipping plane (if any).
+    #[inline]
+    pub fn oblique_clip_plane(&self) -> Option<Plane> {
+        *self.oblique_clip_plane
     }
 
     /// Sets new viewport in resolution-independent format. In other words
@@ -389,6 +665,62 @@ impl Camera {
         *self.viewport
     }
 
+    /// Sets new render order of the camera. Cameras with lower order are rendered first, so
+    /// viewports of cameras with higher order will be drawn on top of them.
+    pub fn set_render_order(&mut self, order: i32) -> i32 {
+        self.render_order.set(order)
+    }
+
+    /// Returns current render order of the camera.
+    pub fn render_order(&self) -> i32 {
+        *self.render_order
+    }
+
+    /// Sets new layer mask of the camera. The camera will render only nodes that belong to at
+    /// least one layer of the mask, see [`Base::layer`] for more info. By default the camera
+    /// renders every layer.
+    pub fn set_layer_mask(&mut self, layer_mask: u32) -> u32 {
+        self.layer_mask.set(layer_mask)
+    }
+
+    /// Returns current layer mask of the camera.
+    pub fn layer_mask(&self) -> u32 {
+        *self.layer_mask
+    }
+
+    /// Returns `true` if the camera renders nodes with the given layer mask.
+    pub fn can_see_layer(&self, layer: u32) -> bool {
+        *self.layer_mask & layer != 0
+    }
+
+    /// Sets a texture the camera will render into. The texture must be created using
+    /// [`Texture::new_render_target`], otherwise it will be ignored. When render target is set,
+    /// the camera will render into the whole texture and the viewport is ignored. Cameras with
+    /// render targets are always rendered before other cameras of a scene, so their results
+    /// could be used in the same frame.
+    ///
+    /// Render target is not serialized, so it must be set again after the scene is loaded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fyrox::{resource::texture::Texture, scene::camera::Camera};
+    /// fn make_security_camera(camera: &mut Camera) -> Texture {
+    ///     let render_target = Texture::new_render_target(256, 256);
+    ///     camera.set_render_target(Some(render_target.clone()));
+    ///     // Now the texture can be used in a material or an image widget.
+    ///     render_target
+    /// }
+    /// ```
+    pub fn set_render_target(&mut self, render_target: Option<Texture>) -> Option<Texture> {
+        std::mem::replace(&mut self.render_target, render_target)
+    }
+
+    /// Returns a texture the camera renders into (if any).
+    pub fn render_target(&self) -> Option<&Texture> {
+        self.render_target.as_ref()
+    }
+
     /// Calculates viewport rectangle in pixels based on internal resolution-independent
     /// viewport. It is useful when you need to get real viewport rectangle in pixels.
     ///
@@ -490,6 +822,22 @@ impl Camera {
         std::mem::replace(self.sky_box.get_mut(), new)
     }
 
+    /// Sets new procedural sky. When it is set, the camera draws the procedural sky instead of
+    /// the skybox. See [`ProceduralSky`] docs for more info.
+    pub fn set_procedural_sky(&mut self, sky: Option<ProceduralSky>) -> Option<ProceduralSky> {
+        self.procedural_sky.set(sky)
+    }
+
+    /// Return optional shared reference to current procedural sky.
+    pub fn procedural_sky_ref(&self) -> Option<&ProceduralSky> {
+        self.procedural_sky.as_ref()
+    }
+
+    /// Return optional mutable reference to current procedural sky.
+    pub fn procedural_sky_mut(&mut self) -> Option<&mut ProceduralSky> {
+        self.procedural_sky.get_mut().as_mut()
+    }
+
     /// Sets new environment.
     pub fn set_environment(&mut self, environment: Option<Texture>) -> Option<Texture> {
         self.environment.set(environment)
@@ -586,6 +934,89 @@ impl Camera {
     pub fn exposure(&self) -> Exposure {
         *self.exposure
     }
+
+    /// Sets new post-processing stack. See [`PostProcessStack`] docs for more info.
+    pub fn set_post_processing(&mut self, post_processing: PostProcessStack) -> PostProcessStack {
+        self.post_processing.set(post_processing)
+    }
+
+    /// Returns a reference to current post-processing stack.
+    pub fn post_processing(&self) -> &PostProcessStack {
+        &self.post_processing
+    }
+
+    /// Returns a mutable reference to current post-processing stack.
+    pub fn post_processing_mut(&mut self) -> &mut PostProcessStack {
+        self.post_processing.get_mut()
+    }
+
+    /// Sets new per-layer view settings for 2D objects. See [`SortingLayerView`] docs for more info.
+    pub fn set_sorting_layer_views(
+        &mut self,
+        views: Vec<SortingLayerView>,
+    ) -> Vec<SortingLayerView> {
+        self.sorting_layer_views.set(views)
+    }
+
+    /// Returns a slice with per-layer view settings for 2D objects.
+    pub fn sorting_layer_views(&self) -> &[SortingLayerView] {
+        &self.sorting_layer_views
+    }
+
+    /// Returns view settings of the given sorting layer (if any).
+    pub fn sorting_layer_view(&self, layer: i32) -> Option<&SortingLayerView> {
+        self.sorting_layer_views.iter().find(|v| v.layer == layer)
+    }
+
+    /// Calculates view-projection matrix for the given sorting layer view settings.
+    pub fn sorting_layer_view_projection_matrix(
+        &self,
+        view: &SortingLayerView,
+        frame_size: Vector2<f32>,
+    ) -> Matrix4<f32> {
+        let projection = view
+            .projection
+            .as_ref()
+            .map_or(self.projection_matrix, |p| p.matrix(frame_size));
+
+        let view_matrix = match self.inv_view_matrix() {
+            Some(mut inv_view) => {
+                inv_view[12] *= view.parallax.x;
+                inv_view[13] *= view.parallax.y;
+                inv_view.try_inverse().unwrap_or(self.view_matrix)
+            }
+            None => self.view_matrix,
+        };
+
+        projection * view_matrix
+    }
+}
+
+/// Modifies given projection matrix so the near clipping plane will match the given world-space
+/// plane. See "Oblique View Frustum Depth Projection and Clipping" by Eric Lengyel for more info.
+fn make_oblique_projection(
+    projection: Matrix4<f32>,
+    view: Matrix4<f32>,
+    plane: Plane,
+) -> Option<Matrix4<f32>> {
+    // Planes are transformed by inverse transpose matrix.
+    let clip_plane = view.try_inverse()?.transpose()
+        * Vector4::new(plane.normal.x, plane.normal.y, plane.normal.z, plane.d);
+
+    let q = projection.try_inverse()?
+        * Vector4::new(clip_plane.x.signum(), clip_plane.y.signum(), 1.0, 1.0);
+
+    let denominator = clip_plane.dot(&q);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let c = clip_plane.scale(2.0 / denominator);
+
+    let mut result = projection;
+    let row = c.transpose() - projection.row(3);
+    result.set_row(2, &row);
+    Some(result)
 }
 
 impl NodeTrait for Camera {
@@ -606,6 +1037,10 @@ impl NodeTrait for Camera {
     fn restore_resources(&mut self, resource_manager: ResourceManager) {
         self.base.restore_resources(resource_manager.clone());
 
+        self.post_processing
+            .get_mut_silent()
+            .restore_resources(&resource_manager);
+
         let mut state = resource_manager.state();
         let texture_container = &mut state.containers_mut().textures;
         texture_container.try_restore_inheritable_resource(&mut self.environment);
@@ -625,7 +1060,19 @@ impl NodeTrait for Camera {
     }
 
     fn update(&mut self, context: &mut UpdateContext) -> bool {
-        self.calculate_matrices(context.frame_size);
+        let frame_size = self
+            .render_target
+            .as_ref()
+            .and_then(|rt| {
+                if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
+                    Some(Vector2::new(width as f32, height as f32))
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(context.frame_size);
+
+        self.calculate_matrices(frame_size);
 
         self.visibility_cache.clear();
         self.visibility_cache.update(
@@ -634,6 +1081,7 @@ impl NodeTrait for Camera {
             self.projection().z_near(),
             self.projection().z_far(),
             Some(&[&Frustum::from(self.view_projection_matrix()).unwrap_or_default()]),
+            *self.layer_mask,
         );
 
         self.base.update_lifetime(context.dt)
@@ -834,11 +1282,19 @@ pub struct CameraBuilder {
     viewport: Rect<f32>,
     enabled: bool,
     skybox: Option<SkyBox>,
+    procedural_sky: Option<ProceduralSky>,
     environment: Option<Texture>,
     exposure: Exposure,
     color_grading_lut: Option<ColorGradingLut>,
     color_grading_enabled: bool,
+    post_processing: PostProcessStack,
     projection: Projection,
+    render_order: i32,
+    render_target: Option<Texture>,
+    sorting_layer_views: Vec<SortingLayerView>,
+    projection_override: Option<Matrix4<f32>>,
+    oblique_clip_plane: Option<Plane>,
+    layer_mask: u32,
 }
 
 impl CameraBuilder {
@@ -852,11 +1308,19 @@ impl CameraBuilder {
             z_far: 2048.0,
             viewport: Rect::new(0.0, 0.0, 1.0, 1.0),
             skybox: None,
+            procedural_sky: None,
             environment: None,
             exposure: Exposure::Manual(std::f32::consts::E),
             color_grading_lut: None,
             color_grading_enabled: false,
+            post_processing: Default::default(),
             projection: Projection::default(),
+            render_order: 0,
+            render_target: None,
+            sorting_layer_views: Default::default(),
+            projection_override: None,
+            oblique_clip_plane: None,
+            layer_mask: u32::MAX,
         }
     }
 
@@ -896,6 +1360,12 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired procedural sky. See [`Camera::set_procedural_sky`] for more info.
+    pub fn with_procedural_sky(mut self, sky: ProceduralSky) -> Self {
+        self.procedural_sky = Some(sky);
+        self
+    }
+
     /// Sets desired environment map.
     pub fn with_environment(mut self, environment: Texture) -> Self {
         self.environment = Some(environment);
@@ -914,6 +1384,12 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired post-processing stack.
+    pub fn with_post_processing(mut self, post_processing: PostProcessStack) -> Self {
+        self.post_processing = post_processing;
+        self
+    }
+
     /// Sets desired exposure options.
     pub fn with_exposure(mut self, exposure: Exposure) -> Self {
         self.exposure = exposure;
@@ -926,6 +1402,42 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired render order. See [`Camera::set_render_order`] for more info.
+    pub fn with_render_order(mut self, order: i32) -> Self {
+        self.render_order = order;
+        self
+    }
+
+    /// Sets desired render target. See [`Camera::set_render_target`] for more info.
+    pub fn with_render_target(mut self, render_target: Texture) -> Self {
+        self.render_target = Some(render_target);
+        self
+    }
+
+    /// Sets desired per-layer view settings. See [`SortingLayerView`] docs for more info.
+    pub fn with_sorting_layer_views(mut self, views: Vec<SortingLayerView>) -> Self {
+        self.sorting_layer_views = views;
+        self
+    }
+
+    /// Sets desired projection matrix override. See [`Camera::set_projection_override`] for more info.
+    pub fn with_projection_override(mut self, projection_override: Matrix4<f32>) -> Self {
+        self.projection_override = Some(projection_override);
+        self
+    }
+
+    /// Sets desired oblique clipping plane. See [`Camera::set_oblique_clip_plane`] for more info.
+    pub fn with_oblique_clip_plane(mut self, plane: Plane) -> Self {
+        self.oblique_clip_plane = Some(plane);
+        self
+    }
+
+    /// Sets desired layer mask. See [`Camera::set_layer_mask`] for more info.
+    pub fn with_layer_mask(mut self, layer_mask: u32) -> Self {
+        self.layer_mask = layer_mask;
+        self
+    }
+
     /// Creates new instance of camera.
     pub fn build_camera(self) -> Camera {
         Camera {
@@ -943,6 +1455,14 @@ impl CameraBuilder {
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
+            post_processing: self.post_processing.into(),
+            render_order: self.render_order.into(),
+            render_target: self.render_target,
+            sorting_layer_views: self.sorting_layer_views.into(),
+            projection_override: self.projection_override.into(),
+            oblique_clip_plane: self.oblique_clip_plane.into(),
+            layer_mask: self.layer_mask.into(),
+            procedural_sky: self.procedural_sky.into(),
         }
     }
 
@@ -9,7 +9,7 @@ use crate::{
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
-        variable::InheritableVariable,
+        variable::{InheritableVariable, VariableFlags},
         visitor::prelude::*,
     },
     engine::resource_manager::ResourceManager,
@@ -555,6 +555,18 @@ impl Collider {
         &self.shape
     }
 
+    /// Forces the physics engine to rebuild native shape of the collider. It is useful for shapes
+    /// that use a scene node as a geometry source (trimesh, heightfield, polyhedron), when the
+    /// geometry of the node was changed.
+    pub fn invalidate_shape(&mut self) {
+        let mut flags = VariableFlags::NEED_SYNC;
+        if self.shape.is_modified() {
+            flags |= VariableFlags::MODIFIED;
+        }
+        let shape = self.shape.clone_inner();
+        self.shape.set_with_flags(shape, flags);
+    }
+
     /// Returns a copy of the collider shape.
     pub fn shape_value(&self) -> ColliderShape {
         (*self.shape).clone()
@@ -916,12 +928,15 @@ impl ColliderBuilder {
 
 #[cfg(test)]
 mod test {
-    use crate::core::{algebra::Vector2, reflect::Reflect};
+    use crate::core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        reflect::Reflect,
+    };
     use crate::scene::collider::BitMask;
     use crate::scene::{
         base::{test::check_inheritable_properties_equality, BaseBuilder},
         collider::{Collider, ColliderBuilder, ColliderShape, InteractionGroups},
-        graph::physics::CoefficientCombineRule,
+        graph::physics::{CoefficientCombineRule, OverlapOptions, QueryShape, ShapeCastOptions},
         graph::Graph,
         rigidbody::{RigidBodyBuilder, RigidBodyType},
     };
@@ -1012,4 +1027,54 @@ mod test {
                 .count()
         );
     }
+
+    #[test]
+    fn test_shape_queries() {
+        let mut graph = Graph::new();
+
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(0.5, 0.5, 0.5))
+            .build(&mut graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_children(&[collider]))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+
+        // need to call two times for the collider to get into the query pipeline
+        graph.update(Vector2::new(800.0, 600.0), 1.0);
+        graph.update(Vector2::new(800.0, 600.0), 1.0);
+
+        let overlap = |position| {
+            let mut colliders = Vec::new();
+            graph.physics.overlap(
+                &OverlapOptions {
+                    shape: QueryShape::Sphere { radius: 0.1 },
+                    position,
+                    rotation: UnitQuaternion::identity(),
+                    groups: Default::default(),
+                    layer_mask: u32::MAX,
+                },
+                &mut colliders,
+            );
+            colliders
+        };
+        assert_eq!(overlap(Vector3::new(0.0, 0.4, 0.0)), vec![collider]);
+        assert!(overlap(Vector3::new(5.0, 0.0, 0.0)).is_empty());
+
+        let hit = graph
+            .physics
+            .cast_shape(&ShapeCastOptions {
+                shape: QueryShape::Sphere { radius: 0.5 },
+                position: Vector3::new(5.0, 0.0, 0.0),
+                rotation: UnitQuaternion::identity(),
+                direction: Vector3::new(-2.0, 0.0, 0.0),
+                max_len: 10.0,
+                groups: Default::default(),
+                layer_mask: u32::MAX,
+            })
+            .unwrap();
+        assert_eq!(hit.collider, collider);
+        assert!((hit.toi - 4.0).abs() < 0.01);
+        assert!((hit.normal - Vector3::x()).norm() < 0.01);
+        assert!((hit.position.x - 0.5).abs() < 0.01);
+    }
 }
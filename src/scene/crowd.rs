@@ -0,0 +1,322 @@
+//! Crowd is a scene node that renders a lot of instances of a skinned surface, animated on GPU.
+//!
+//! For more info see [`Crowd`]
+
+use crate::{
+    animation::baking::BakedAnimations,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::Surface,
+        },
+        node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+/// A single member of a crowd.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct CrowdInstance {
+    /// Position of the instance in the local space of the crowd.
+    pub position: Vector3<f32>,
+    /// Rotation of the instance in the local space of the crowd.
+    pub rotation: UnitQuaternion<f32>,
+    /// Scale of the instance.
+    pub scale: Vector3<f32>,
+    /// Index of a baked clip, that will be played on the instance.
+    pub clip: u32,
+    /// Time offset (in seconds) of the animation. Use different offsets to desynchronize
+    /// instances that play the same clip.
+    pub time_offset: f32,
+    /// Playback speed of the animation.
+    pub speed: f32,
+}
+
+impl Default for CrowdInstance {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            rotation: Default::default(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+            clip: 0,
+            time_offset: 0.0,
+            speed: 1.0,
+        }
+    }
+}
+
+impl CrowdInstance {
+    /// Returns transformation matrix of the instance in the local space of the crowd.
+    pub fn local_transform(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&self.position)
+            * self.rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&self.scale)
+    }
+}
+
+/// Crowd renders large amount of instances of a single skinned surface. Unlike a skinned
+/// [`crate::scene::mesh::Mesh`], instances of a crowd do not have skeletons, instead they
+/// sample bone matrices from [`BakedAnimations`] in the vertex shader. This way, thousands of
+/// animated characters could be drawn using a single draw call.
+///
+/// # Animations
+///
+/// Animations should be baked first using [`crate::animation::baking::bake_animations`]. Each
+/// instance selects its clip by index and has its own time offset and playback speed.
+///
+/// # Limitations
+///
+/// Crowds are rendered only in the deferred render path and do not cast shadows. Only diffuse
+/// and normal textures of the material of the surface are used. Bounding box of the crowd is
+/// calculated using the bind pose of the surface, disable frustum culling if animations move
+/// vertices far away from it.
+///
+/// # Example
+///
+/// ```
+/// use fyrox::{
+///     animation::baking::BakedAnimations,
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{
+///         base::BaseBuilder,
+///         crowd::{CrowdBuilder, CrowdInstance},
+///         graph::Graph,
+///         mesh::surface::Surface,
+///         node::Node,
+///     },
+/// };
+///
+/// fn create_crowd(surface: Surface, animations: BakedAnimations, graph: &mut Graph) -> Handle<Node> {
+///     let instances = (0..1000)
+///         .map(|i| CrowdInstance {
+///             position: Vector3::new((i % 32) as f32, 0.0, (i / 32) as f32),
+///             clip: (i % animations.clips().len().max(1)) as u32,
+///             time_offset: i as f32 * 0.1,
+///             ..Default::default()
+///         })
+///         .collect();
+///
+///     CrowdBuilder::new(BaseBuilder::new())
+///         .with_surface(surface)
+///         .with_animations(animations)
+///         .with_instances(instances)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Default, Clone, Reflect)]
+pub struct Crowd {
+    base: Base,
+
+    surface: InheritableVariable<Surface>,
+
+    animations: InheritableVariable<BakedAnimations>,
+
+    instances: InheritableVariable<Vec<CrowdInstance>>,
+
+    #[reflect(hidden)]
+    time: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    surface_bounds: Option<AxisAlignedBoundingBox>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    local_bounding_box: AxisAlignedBoundingBox,
+}
+
+impl Deref for Crowd {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Crowd {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Crowd {
+    fn type_uuid() -> Uuid {
+        uuid!("3a5c2f1e-8b0d-4c27-9e61-5d4f7a2b9c13")
+    }
+}
+
+impl Crowd {
+    /// Sets new surface, that will be drawn for every instance.
+    pub fn set_surface(&mut self, surface: Surface) -> Surface {
+        self.surface_bounds = None;
+        self.surface.set(surface)
+    }
+
+    /// Returns current surface.
+    pub fn surface(&self) -> &Surface {
+        &self.surface
+    }
+
+    /// Sets new baked animations.
+    pub fn set_animations(&mut self, animations: BakedAnimations) -> BakedAnimations {
+        self.animations.set(animations)
+    }
+
+    /// Returns current baked animations.
+    pub fn animations(&self) -> &BakedAnimations {
+        &self.animations
+    }
+
+    /// Sets new instances of the crowd.
+    pub fn set_instances(&mut self, instances: Vec<CrowdInstance>) -> Vec<CrowdInstance> {
+        self.instances.set(instances)
+    }
+
+    /// Returns a slice with every instance of the crowd.
+    pub fn instances(&self) -> &[CrowdInstance] {
+        &self.instances
+    }
+
+    /// Returns a mutable reference to the instances of the crowd.
+    pub fn instances_mut(&mut self) -> &mut Vec<CrowdInstance> {
+        self.instances.get_mut()
+    }
+
+    /// Returns current playback time of the crowd (in seconds). Animation time of an instance is
+    /// `time * speed + time_offset`.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Sets current playback time of the crowd (in seconds).
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+}
+
+impl NodeTrait for Crowd {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box.transform(&self.global_transform())
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager.clone());
+
+        self.surface
+            .material()
+            .lock()
+            .resolve(resource_manager.clone());
+
+        self.animations
+            .get_mut_silent()
+            .restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) -> bool {
+        self.time += context.dt;
+
+        let surface = &self.surface;
+        let surface_bounds = *self.surface_bounds.get_or_insert_with(|| {
+            let mut bounds = AxisAlignedBoundingBox::default();
+            let data = surface.data();
+            let data = data.lock();
+            for view in data.vertex_buffer.iter() {
+                if let Ok(position) = view.read_3_f32(VertexAttributeUsage::Position) {
+                    bounds.add_point(position);
+                }
+            }
+            bounds
+        });
+
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        for instance in self.instances.iter() {
+            bounding_box.add_box(surface_bounds.transform(&instance.local_transform()));
+        }
+        self.local_bounding_box = bounding_box;
+
+        self.base.update_lifetime(context.dt)
+    }
+}
+
+/// Allows you to create a Crowd in a declarative manner.
+pub struct CrowdBuilder {
+    base_builder: BaseBuilder,
+    surface: Surface,
+    animations: BakedAnimations,
+    instances: Vec<CrowdInstance>,
+}
+
+impl CrowdBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            surface: Default::default(),
+            animations: Default::default(),
+            instances: Default::default(),
+        }
+    }
+
+    /// Sets desired surface.
+    pub fn with_surface(mut self, surface: Surface) -> Self {
+        self.surface = surface;
+        self
+    }
+
+    /// Sets desired baked animations.
+    pub fn with_animations(mut self, animations: BakedAnimations) -> Self {
+        self.animations = animations;
+        self
+    }
+
+    /// Sets desired instances.
+    pub fn with_instances(mut self, instances: Vec<CrowdInstance>) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    /// Creates new Crowd node.
+    pub fn build_crowd(self) -> Crowd {
+        Crowd {
+            base: self.base_builder.build_base(),
+            surface: self.surface.into(),
+            animations: self.animations.into(),
+            instances: self.instances.into(),
+            time: 0.0,
+            surface_bounds: None,
+            local_bounding_box: Default::default(),
+        }
+    }
+
+    /// Creates new Crowd node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_crowd())
+    }
+
+    /// Creates new instance of Crowd node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
@@ -15,6 +15,7 @@ use crate::{
         base::{Base, BaseBuilder},
         dim2::rigidbody::RigidBody,
         graph::Graph,
+        joint::JointMotor,
         node::{Node, NodeTrait, SyncContext, TypeUuidProvider},
         Scene,
     },
@@ -29,7 +30,8 @@ use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 /// Ball joint locks any translational moves between two objects on the axis between objects, but
 /// allows rigid bodies to perform relative rotations. The real world example is a human shoulder,
-/// pendulum, etc.
+/// pendulum, etc. In 2D ball joint is the same as revolute joint (see
+/// [`crate::scene::joint::RevoluteJoint`]), since there is only one rotation axis.
 #[derive(Clone, Debug, Visit, PartialEq, Reflect)]
 pub struct BallJoint {
     /// Whether angular limits are enabled or not. Default is `false`
@@ -41,6 +43,11 @@ pub struct BallJoint {
     #[reflect(description = "Allowed angles range for the joint (in radians).")]
     #[visit(optional)] // Backward compatibility
     pub limits_angles: Range<f32>,
+
+    /// Motor that drives relative rotation of the bodies.
+    #[reflect(description = "Motor that drives relative rotation of the bodies.")]
+    #[visit(optional)] // Backward compatibility
+    pub motor: JointMotor,
 }
 
 impl Default for BallJoint {
@@ -48,6 +55,7 @@ impl Default for BallJoint {
         Self {
             limits_enabled: false,
             limits_angles: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
         }
     }
 }
@@ -72,6 +80,11 @@ pub struct PrismaticJoint {
     #[reflect(description = "Allowed linear distance range along local X axis of the joint.")]
     #[visit(optional)] // Backward compatibility
     pub limits: Range<f32>,
+
+    /// Motor that drives the joint along local X axis of the joint.
+    #[reflect(description = "Motor that drives the joint along local X axis of the joint.")]
+    #[visit(optional)] // Backward compatibility
+    pub motor: JointMotor,
 }
 
 impl Default for PrismaticJoint {
@@ -79,6 +92,7 @@ impl Default for PrismaticJoint {
         Self {
             limits_enabled: false,
             limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
         }
     }
 }
@@ -119,6 +133,14 @@ pub struct Joint {
     #[reflect(setter = "set_contacts_enabled")]
     pub(crate) contacts_enabled: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_break_force")]
+    #[visit(optional)] // Backward compatibility
+    pub(crate) break_force: InheritableVariable<Option<f32>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    pub(crate) broken: Cell<bool>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: Cell<ImpulseJointHandle>,
@@ -136,6 +158,8 @@ impl Default for Joint {
             body1: Default::default(),
             body2: Default::default(),
             contacts_enabled: InheritableVariable::new(true),
+            break_force: Default::default(),
+            broken: Cell::new(false),
             // Do not copy. The copy will have its own native representation.
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
@@ -165,6 +189,8 @@ impl Clone for Joint {
             body1: self.body1.clone(),
             body2: self.body2.clone(),
             contacts_enabled: self.contacts_enabled.clone(),
+            break_force: self.break_force.clone(),
+            broken: self.broken.clone(),
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
         }
@@ -225,6 +251,30 @@ impl Joint {
     pub fn is_contacts_enabled(&self) -> bool {
         *self.contacts_enabled
     }
+
+    /// Sets the maximum force that the joint can withstand. If the force applied to the joint exceeds
+    /// the value, the joint breaks and [`crate::scene::joint::JointEventKind::Broken`] event is
+    /// emitted. `None` means that the joint is unbreakable.
+    pub fn set_break_force(&mut self, break_force: Option<f32>) -> Option<f32> {
+        self.break_force.set(break_force)
+    }
+
+    /// Returns the maximum force that the joint can withstand.
+    pub fn break_force(&self) -> Option<f32> {
+        *self.break_force
+    }
+
+    /// Returns `true` if the joint was broken, `false` - otherwise. Broken joint does not restrict
+    /// motion of its bodies.
+    pub fn is_broken(&self) -> bool {
+        self.broken.get()
+    }
+
+    /// Restores the joint after it was broken.
+    pub fn repair(&mut self) {
+        self.broken.set(false);
+        self.need_rebind.set(true);
+    }
 }
 
 impl NodeTrait for Joint {
@@ -304,6 +354,7 @@ pub struct JointBuilder {
     body1: Handle<Node>,
     body2: Handle<Node>,
     contacts_enabled: bool,
+    break_force: Option<f32>,
 }
 
 impl JointBuilder {
@@ -315,6 +366,7 @@ impl JointBuilder {
             body1: Default::default(),
             body2: Default::default(),
             contacts_enabled: true,
+            break_force: None,
         }
     }
 
@@ -344,6 +396,12 @@ impl JointBuilder {
         self
     }
 
+    /// Sets the maximum force that the joint can withstand, see [`Joint::set_break_force`] for more info.
+    pub fn with_break_force(mut self, break_force: Option<f32>) -> Self {
+        self.break_force = break_force;
+        self
+    }
+
     /// Creates new Joint node, but does not add it to the graph.
     pub fn build_joint(self) -> Joint {
         Joint {
@@ -352,6 +410,8 @@ impl JointBuilder {
             body1: self.body1.into(),
             body2: self.body2.into(),
             contacts_enabled: self.contacts_enabled.into(),
+            break_force: self.break_force.into(),
+            broken: Cell::new(false),
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
         }
@@ -381,6 +441,7 @@ mod test {
     fn test_joint_2d_inheritance() {
         let parent = JointBuilder::new(BaseBuilder::new())
             .with_params(JointParams::BallJoint(BallJoint::default()))
+            .with_break_force(Some(100.0))
             .build_node();
 
         let mut child = JointBuilder::new(BaseBuilder::new()).build_joint();
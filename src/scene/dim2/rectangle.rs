@@ -99,6 +99,15 @@ use std::ops::{Deref, DerefMut};
 /// image, but just changing portion for rendering. Keep in mind that the coordinates are normalized
 /// which means `[0; 0]` corresponds to top-left corner of the texture and `[1; 1]` corresponds to
 /// right-bottom corner.
+///
+/// # Drawing order
+///
+/// Rectangles are drawn in the order defined by their sorting layer (see [`Self::set_sorting_layer`]),
+/// order in layer (see [`Self::set_order_in_layer`]) and, finally, by distance to the camera (far
+/// rectangles are drawn first). Consecutive rectangles with the same texture are drawn using a single
+/// draw call, so it is better to put rectangles with the same texture on the same layer (or use
+/// texture atlases) to reduce the amount of draw calls. Sorting layers could also be viewed through
+/// their own orthographic projection, see [`crate::scene::camera::SortingLayerView`] docs.
 #[derive(Visit, Reflect, Debug, Clone)]
 pub struct Rectangle {
     base: Base,
@@ -113,6 +122,14 @@ pub struct Rectangle {
     #[reflect(setter = "set_uv_rect")]
     #[visit(optional)] // Backward compatibility
     uv_rect: InheritableVariable<Rect<f32>>,
+
+    #[reflect(setter = "set_sorting_layer")]
+    #[visit(optional)] // Backward compatibility
+    sorting_layer: InheritableVariable<i32>,
+
+    #[reflect(setter = "set_order_in_layer")]
+    #[visit(optional)] // Backward compatibility
+    order_in_layer: InheritableVariable<i32>,
 }
 
 impl Default for Rectangle {
@@ -122,6 +139,8 @@ impl Default for Rectangle {
             texture: Default::default(),
             color: Default::default(),
             uv_rect: InheritableVariable::new(Rect::new(0.0, 0.0, 1.0, 1.0)),
+            sorting_layer: Default::default(),
+            order_in_layer: Default::default(),
         }
     }
 }
@@ -190,6 +209,28 @@ impl Rectangle {
     pub fn set_uv_rect(&mut self, uv_rect: Rect<f32>) -> Rect<f32> {
         self.uv_rect.set(uv_rect)
     }
+
+    /// Sets sorting layer of the rectangle. Rectangles on layers with higher indices are drawn on
+    /// top of rectangles on layers with lower indices. Default layer is `0`.
+    pub fn set_sorting_layer(&mut self, layer: i32) -> i32 {
+        self.sorting_layer.set(layer)
+    }
+
+    /// Returns current sorting layer of the rectangle.
+    pub fn sorting_layer(&self) -> i32 {
+        *self.sorting_layer
+    }
+
+    /// Sets order of the rectangle in its sorting layer. Rectangles with higher order are drawn on
+    /// top of rectangles with lower order on the same layer.
+    pub fn set_order_in_layer(&mut self, order: i32) -> i32 {
+        self.order_in_layer.set(order)
+    }
+
+    /// Returns current order of the rectangle in its sorting layer.
+    pub fn order_in_layer(&self) -> i32 {
+        *self.order_in_layer
+    }
 }
 
 impl NodeTrait for Rectangle {
@@ -225,6 +266,8 @@ pub struct RectangleBuilder {
     texture: Option<Texture>,
     color: Color,
     uv_rect: Rect<f32>,
+    sorting_layer: i32,
+    order_in_layer: i32,
 }
 
 impl RectangleBuilder {
@@ -235,6 +278,8 @@ impl RectangleBuilder {
             texture: None,
             color: Color::WHITE,
             uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            sorting_layer: 0,
+            order_in_layer: 0,
         }
     }
 
@@ -257,6 +302,18 @@ impl RectangleBuilder {
         self
     }
 
+    /// Sets desired sorting layer. See [`Rectangle::set_sorting_layer`] for more info.
+    pub fn with_sorting_layer(mut self, layer: i32) -> Self {
+        self.sorting_layer = layer;
+        self
+    }
+
+    /// Sets desired order in layer. See [`Rectangle::set_order_in_layer`] for more info.
+    pub fn with_order_in_layer(mut self, order: i32) -> Self {
+        self.order_in_layer = order;
+        self
+    }
+
     /// Creates new [`Rectangle`] instance.
     pub fn build_rectangle(self) -> Rectangle {
         Rectangle {
@@ -264,6 +321,8 @@ impl RectangleBuilder {
             texture: self.texture.into(),
             color: self.color.into(),
             uv_rect: self.uv_rect.into(),
+            sorting_layer: self.sorting_layer.into(),
+            order_in_layer: self.order_in_layer.into(),
         }
     }
 
@@ -8,6 +8,10 @@
 //! using [`RigidBody::wake_up`]. By default any external action does **not** wakes up rigid body.
 //! You can also explicitly tell to rigid body that it cannot sleep, by calling
 //! [`RigidBody::set_can_sleep`] with `false` value.
+//!
+//! **Q:** Fast-moving rigid body (a bullet for example) passes through thin objects.
+//! **A:** Enable continuous collision detection for the body using [`RigidBody::enable_ccd`]. Keep
+//! in mind that CCD is quite expensive, so enable it only for bodies that really need it.
 use crate::{
     core::{
         algebra::{Matrix4, Vector2},
@@ -28,7 +32,7 @@ use crate::{
     },
     utils::log::Log,
 };
-use rapier2d::prelude::RigidBodyHandle;
+use rapier2d::{dynamics::RigidBodyActivation, prelude::RigidBodyHandle};
 use std::{
     cell::Cell,
     collections::VecDeque,
@@ -101,6 +105,20 @@ pub struct RigidBody {
     #[reflect(setter = "set_gravity_scale")]
     pub(crate) gravity_scale: InheritableVariable<f32>,
 
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_sleep_linear_threshold")]
+    pub(crate) sleep_linear_threshold: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_sleep_angular_threshold")]
+    pub(crate) sleep_angular_threshold: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_solver_iterations")]
+    pub(crate) solver_iterations: InheritableVariable<Option<u32>>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) sleeping: bool,
@@ -137,6 +155,13 @@ impl Default for RigidBody {
             can_sleep: InheritableVariable::new(true),
             dominance: Default::default(),
             gravity_scale: InheritableVariable::new(1.0),
+            sleep_linear_threshold: InheritableVariable::new(
+                RigidBodyActivation::default_linear_threshold(),
+            ),
+            sleep_angular_threshold: InheritableVariable::new(
+                RigidBodyActivation::default_angular_threshold(),
+            ),
+            solver_iterations: InheritableVariable::new(None),
             native: Cell::new(RigidBodyHandle::invalid()),
             actions: Default::default(),
         }
@@ -174,6 +199,9 @@ impl Clone for RigidBody {
             can_sleep: self.can_sleep.clone(),
             dominance: self.dominance.clone(),
             gravity_scale: self.gravity_scale.clone(),
+            sleep_linear_threshold: self.sleep_linear_threshold.clone(),
+            sleep_angular_threshold: self.sleep_angular_threshold.clone(),
+            solver_iterations: self.solver_iterations.clone(),
             // Do not copy. The copy will have its own native representation.
             native: Cell::new(RigidBodyHandle::invalid()),
             actions: Default::default(),
@@ -371,6 +399,39 @@ impl RigidBody {
         *self.can_sleep
     }
 
+    /// Sets linear velocity threshold below which the rigid body could fall asleep. Has no effect
+    /// if the rigid body cannot sleep (see [`Self::set_can_sleep`]).
+    pub fn set_sleep_linear_threshold(&mut self, threshold: f32) -> f32 {
+        self.sleep_linear_threshold.set(threshold.max(0.0))
+    }
+
+    /// Returns linear velocity threshold below which the rigid body could fall asleep.
+    pub fn sleep_linear_threshold(&self) -> f32 {
+        *self.sleep_linear_threshold
+    }
+
+    /// Sets angular velocity threshold below which the rigid body could fall asleep. Has no effect
+    /// if the rigid body cannot sleep (see [`Self::set_can_sleep`]).
+    pub fn set_sleep_angular_threshold(&mut self, threshold: f32) -> f32 {
+        self.sleep_angular_threshold.set(threshold.max(0.0))
+    }
+
+    /// Returns angular velocity threshold below which the rigid body could fall asleep.
+    pub fn sleep_angular_threshold(&self) -> f32 {
+        *self.sleep_angular_threshold
+    }
+
+    /// Sets amount of solver iterations that is required for the rigid body. See
+    /// [`crate::scene::rigidbody::RigidBody::set_solver_iterations`] for more info.
+    pub fn set_solver_iterations(&mut self, iterations: Option<u32>) -> Option<u32> {
+        self.solver_iterations.set(iterations)
+    }
+
+    /// Returns amount of solver iterations that is required for the rigid body.
+    pub fn solver_iterations(&self) -> Option<u32> {
+        *self.solver_iterations
+    }
+
     /// Wakes up rigid body, forcing it to return to participate in the simulation.
     pub fn wake_up(&mut self) {
         self.actions.get_mut().push_back(ApplyAction::WakeUp)
@@ -389,6 +450,9 @@ impl RigidBody {
             || self.can_sleep.need_sync()
             || self.dominance.need_sync()
             || self.gravity_scale.need_sync()
+            || self.sleep_linear_threshold.need_sync()
+            || self.sleep_angular_threshold.need_sync()
+            || self.solver_iterations.need_sync()
     }
 }
 
@@ -464,6 +528,9 @@ pub struct RigidBodyBuilder {
     can_sleep: bool,
     dominance: i8,
     gravity_scale: f32,
+    sleep_linear_threshold: f32,
+    sleep_angular_threshold: f32,
+    solver_iterations: Option<u32>,
 }
 
 impl RigidBodyBuilder {
@@ -484,6 +551,9 @@ impl RigidBodyBuilder {
             can_sleep: true,
             dominance: 0,
             gravity_scale: 1.0,
+            sleep_linear_threshold: RigidBodyActivation::default_linear_threshold(),
+            sleep_angular_threshold: RigidBodyActivation::default_angular_threshold(),
+            solver_iterations: None,
         }
     }
 
@@ -565,6 +635,25 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Sets desired linear velocity threshold below which the body could fall asleep.
+    pub fn with_sleep_linear_threshold(mut self, threshold: f32) -> Self {
+        self.sleep_linear_threshold = threshold;
+        self
+    }
+
+    /// Sets desired angular velocity threshold below which the body could fall asleep.
+    pub fn with_sleep_angular_threshold(mut self, threshold: f32) -> Self {
+        self.sleep_angular_threshold = threshold;
+        self
+    }
+
+    /// Sets desired amount of solver iterations for the body. See [`RigidBody::set_solver_iterations`]
+    /// for more info.
+    pub fn with_solver_iterations(mut self, iterations: Option<u32>) -> Self {
+        self.solver_iterations = iterations;
+        self
+    }
+
     /// Creates RigidBody node but does not add it to the graph.
     pub fn build_rigid_body(self) -> RigidBody {
         RigidBody {
@@ -582,6 +671,9 @@ impl RigidBodyBuilder {
             can_sleep: self.can_sleep.into(),
             dominance: self.dominance.into(),
             gravity_scale: self.gravity_scale.into(),
+            sleep_linear_threshold: self.sleep_linear_threshold.into(),
+            sleep_angular_threshold: self.sleep_angular_threshold.into(),
+            solver_iterations: self.solver_iterations.into(),
             native: Cell::new(RigidBodyHandle::invalid()),
             actions: Default::default(),
         }
@@ -626,6 +718,9 @@ mod test {
             .with_ang_damping(0.1)
             .with_dominance(123)
             .with_translation_locked(true)
+            .with_sleep_linear_threshold(0.1)
+            .with_sleep_angular_threshold(0.2)
+            .with_solver_iterations(Some(8))
             .build_node();
 
         let mut child = RigidBodyBuilder::new(BaseBuilder::new()).build_rigid_body();
@@ -1,5 +1,6 @@
-//! Graph event broadcaster allows you to receive graph events such as node deletion or addition.
-//! Check [GraphEventBroadcaster::subscribe] for examples.
+//! Graph event broadcaster allows you to receive graph events such as node deletion or addition,
+//! changes in hierarchy and global transform changes. Check [GraphEventBroadcaster::subscribe] for
+//! examples.
 
 use crate::{
     core::{pool::Handle, VecExtensions},
@@ -17,13 +18,34 @@ pub enum GraphEvent {
     Added(Handle<Node>),
     /// A node was removed.
     Removed(Handle<Node>),
+    /// A node was detached from one parent and attached to another. This event is deferred, see
+    /// [`GraphEventBroadcaster`] docs for more info.
+    Reparented {
+        /// A handle of the node that was re-attached.
+        node: Handle<Node>,
+        /// A handle of the previous parent of the node.
+        old_parent: Handle<Node>,
+        /// A handle of the new parent of the node.
+        new_parent: Handle<Node>,
+    },
+    /// Global transform of a node has changed. The event is emitted for every node whose global
+    /// transform was changed during hierarchy update, including descendants of a moved node. This
+    /// event is deferred, see [`GraphEventBroadcaster`] docs for more info.
+    TransformChanged(Handle<Node>),
 }
 
 /// Graph event broadcaster allows you to receive graph events such as node deletion or addition.
 /// Check [GraphEventBroadcaster::subscribe] for examples.
+///
+/// [`GraphEvent::Added`] and [`GraphEvent::Removed`] are sent immediately, while
+/// [`GraphEvent::Reparented`] and [`GraphEvent::TransformChanged`] are collected during the frame
+/// and sent at the end of [`super::Graph::update`]. This allows systems like spatial audio,
+/// networking or custom culling to react to changes incrementally instead of diffing the whole
+/// graph each frame.
 #[derive(Default)]
 pub struct GraphEventBroadcaster {
     senders: Vec<Sender<GraphEvent>>,
+    deferred: Vec<GraphEvent>,
 }
 
 impl Debug for GraphEventBroadcaster {
@@ -62,13 +84,43 @@ impl GraphEventBroadcaster {
     ///
     /// assert_eq!(rx.recv(), Ok(GraphEvent::Removed(handle)));
     ///
+    /// // Deferred events are delivered after the update tick.
+    /// let parent = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+    /// let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+    /// graph.link_nodes(child, parent);
+    /// graph.update(Default::default(), 1.0 / 60.0);
+    ///
+    /// assert!(rx.try_iter().any(|e| e
+    ///     == GraphEvent::Reparented {
+    ///         node: child,
+    ///         old_parent: graph.get_root(),
+    ///         new_parent: parent
+    ///     }));
     /// ```
     pub fn subscribe(&mut self, sender: Sender<GraphEvent>) {
         self.senders.push(sender);
     }
 
+    /// Returns `true` if there is at least one subscriber. Could be used to skip expensive
+    /// event gathering when nobody listens.
+    pub fn has_subscribers(&self) -> bool {
+        !self.senders.is_empty()
+    }
+
     pub(crate) fn broadcast(&mut self, event: GraphEvent) {
         self.senders
             .retain_mut_ext(|sender| sender.send(event.clone()).is_ok());
     }
+
+    pub(crate) fn broadcast_deferred(&mut self, event: GraphEvent) {
+        if self.has_subscribers() {
+            self.deferred.push(event);
+        }
+    }
+
+    pub(crate) fn flush_deferred(&mut self) {
+        for event in std::mem::take(&mut self.deferred) {
+            self.broadcast(event);
+        }
+    }
 }
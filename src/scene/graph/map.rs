@@ -84,6 +84,11 @@ impl NodeHandleMap {
         }
     }
 
+    /// Adds new `original -> copy` pair to the map, returns previous copy handle (if any).
+    pub fn insert(&mut self, original: Handle<Node>, copy: Handle<Node>) -> Option<Handle<Node>> {
+        self.map.insert(original, copy)
+    }
+
     /// Returns a shared reference to inner map.
     pub fn inner(&self) -> &FxHashMap<Handle<Node>, Handle<Node>> {
         &self.map
@@ -28,6 +28,8 @@ use crate::{
         algebra::{Matrix4, Rotation3, UnitQuaternion, Vector2, Vector3},
         instant,
         math::Matrix4Ext,
+        memory::{AllocationScope, Subsystem},
+        parking_lot::Mutex,
         pool::{Handle, MultiBorrowContext, Pool, Ticket},
         reflect::prelude::*,
         variable::try_inherit_properties,
@@ -36,14 +38,17 @@ use crate::{
     resource::model::{Model, NodeMapping},
     scene::{
         self,
-        base::ScriptMessage,
+        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        base::{InstanceId, ScriptMessage},
         camera::Camera,
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
             map::NodeHandleMap,
             physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            script_index::{ScriptIndex, ScriptIndexRef},
         },
+        joint::{Joint, JointEventKind},
         mesh::Mesh,
         node::{container::NodeContainer, Node, SyncContext, UpdateContext},
         pivot::Pivot,
@@ -55,15 +60,20 @@ use crate::{
 };
 use rapier3d::geometry::ColliderHandle;
 use std::{
+    any::TypeId,
     fmt::Debug,
     ops::{Index, IndexMut},
-    sync::mpsc::{channel, Receiver, Sender},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
     time::Duration,
 };
 
 pub mod event;
 pub mod map;
 pub mod physics;
+pub mod script_index;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -85,6 +95,10 @@ pub struct GraphPerformanceStatistics {
 
     /// A time which was required to render sounds.
     pub sound_update_time: Duration,
+
+    /// Amount of time that was needed to update animation players and animation blending state
+    /// machines.
+    pub animation_time: Duration,
 }
 
 impl GraphPerformanceStatistics {
@@ -95,6 +109,7 @@ impl GraphPerformanceStatistics {
             + self.physics.total()
             + self.physics2d.total()
             + self.sound_update_time
+            + self.animation_time
     }
 }
 
@@ -135,11 +150,14 @@ pub struct Graph {
     pub(crate) script_message_sender: Sender<ScriptMessage>,
     #[reflect(hidden)]
     pub(crate) script_message_receiver: Receiver<ScriptMessage>,
+    #[reflect(hidden)]
+    script_index: Arc<Mutex<ScriptIndex>>,
 }
 
 impl Default for Graph {
     fn default() -> Self {
         let (tx, rx) = channel();
+        let script_index = Arc::new(Mutex::new(ScriptIndex::default()));
 
         Self {
             physics: PhysicsWorld::new(),
@@ -152,6 +170,7 @@ impl Default for Graph {
             event_broadcaster: Default::default(),
             script_message_receiver: rx,
             script_message_sender: tx,
+            script_index: script_index.clone(),
         }
     }
 }
@@ -213,10 +232,12 @@ impl Graph {
     /// Creates new graph instance with single root node.
     pub fn new() -> Self {
         let (tx, rx) = channel();
+        let script_index = Arc::new(Mutex::new(ScriptIndex::default()));
 
         // Create root node.
         let mut root_node = Pivot::default();
         root_node.script_message_sender = Some(tx.clone());
+        root_node.script_index = ScriptIndexRef(Some(script_index.clone()));
         root_node.set_name("__ROOT__");
 
         // Add it to the pool.
@@ -235,6 +256,7 @@ impl Graph {
             event_broadcaster: Default::default(),
             script_message_receiver: rx,
             script_message_sender: tx,
+            script_index: script_index.clone(),
         }
     }
 
@@ -245,7 +267,7 @@ impl Graph {
     pub fn add_node(&mut self, mut node: Node) -> Handle<Node> {
         let children = node.children.clone();
         node.children.clear();
-        let has_script = node.script.is_some();
+        let has_script = node.script_count() > 0;
         let handle = self.pool.spawn(node);
         if self.root.is_some() {
             self.link_nodes(handle, self.root);
@@ -262,9 +284,14 @@ impl Graph {
         }
 
         let sender = self.script_message_sender.clone();
+        let script_index = self.script_index.clone();
         let node = &mut self[handle];
         node.self_handle = handle;
         node.script_message_sender = Some(sender);
+        node.script_index = ScriptIndexRef(Some(script_index));
+        for script in node.script_instances() {
+            node.script_index.add(handle, Some(script));
+        }
 
         handle
     }
@@ -373,21 +400,94 @@ impl Graph {
     /// Links specified child with specified parent.
     #[inline]
     pub fn link_nodes(&mut self, child: Handle<Node>, parent: Handle<Node>) {
+        let old_parent = self.pool[child].parent;
         self.unlink_internal(child);
         self.pool[child].parent = parent;
         self.pool[parent].children.push(child);
+
+        if old_parent.is_some() && old_parent != parent {
+            self.event_broadcaster
+                .broadcast_deferred(GraphEvent::Reparented {
+                    node: child,
+                    old_parent,
+                    new_parent: parent,
+                });
+        }
     }
 
     /// Unlinks specified node from its parent and attaches it to root graph node.
     #[inline]
     pub fn unlink_node(&mut self, node_handle: Handle<Node>) {
-        self.unlink_internal(node_handle);
         self.link_nodes(node_handle, self.root);
         self.pool[node_handle]
             .local_transform_mut()
             .set_position(Vector3::default());
     }
 
+    /// Attaches the given node to a socket of the given mesh. The node will be linked to the bone
+    /// of the socket and its local transform will follow the offset of the socket. Returns `false`
+    /// if there is no such mesh, socket or the bone of the socket does not exist.
+    pub fn attach_to_socket(
+        &mut self,
+        node: Handle<Node>,
+        mesh: Handle<Node>,
+        socket_name: &str,
+    ) -> bool {
+        let (bone, position, rotation) = match self
+            .try_get(mesh)
+            .and_then(|n| n.cast::<Mesh>())
+            .and_then(|m| m.socket(socket_name))
+        {
+            Some(socket) => (socket.bone, socket.position, socket.rotation),
+            None => return false,
+        };
+
+        if node == bone || !self.is_valid_handle(bone) || !self.is_valid_handle(node) {
+            return false;
+        }
+
+        let socket = self[mesh]
+            .cast_mut::<Mesh>()
+            .and_then(|m| m.socket_mut(socket_name))
+            .unwrap();
+        if !socket.attachments.contains(&node) {
+            socket.attachments.push(node);
+        }
+
+        self.link_nodes(node, bone);
+        self.pool[node]
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(rotation);
+
+        true
+    }
+
+    /// Detaches the given node from a socket of the given mesh and attaches it to the root node of
+    /// the graph. Returns `false` if the node was not attached to the socket.
+    pub fn detach_from_socket(
+        &mut self,
+        node: Handle<Node>,
+        mesh: Handle<Node>,
+        socket_name: &str,
+    ) -> bool {
+        let detached = self
+            .try_get_mut(mesh)
+            .and_then(|n| n.cast_mut::<Mesh>())
+            .and_then(|m| m.socket_mut(socket_name))
+            .map_or(false, |socket| {
+                let count = socket.attachments.len();
+                socket.attachments.retain(|&a| a != node);
+                count != socket.attachments.len()
+            });
+
+        if detached && self.is_valid_handle(node) {
+            self.unlink_node(node);
+        }
+
+        detached
+    }
+
     /// Tries to find a copy of `node_handle` in hierarchy tree starting from `root_handle`.
     pub fn find_copy_of(
         &self,
@@ -449,9 +549,76 @@ impl Graph {
     where
         S: ScriptTrait,
     {
-        self.find(root_node, &mut |n| {
-            n.script().and_then(|s| s.cast::<S>()).is_some()
-        })
+        self.find(root_node, &mut |n| n.try_get_script::<S>().is_some())
+    }
+
+    /// Returns an iterator over every node with a script of type `S` and the script itself. The
+    /// search uses a type index, which is maintained when scripts are attached or detached, so it
+    /// does not iterate over every node of the graph.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fyrox::{
+    /// #     core::{reflect::prelude::*, uuid::{uuid, Uuid}, visitor::prelude::*},
+    /// #     impl_component_provider,
+    /// #     scene::graph::Graph,
+    /// #     script::ScriptTrait,
+    /// # };
+    /// #[derive(Clone, Default, Debug, Visit, Reflect)]
+    /// struct Turret {
+    ///     ammo: u32,
+    /// }
+    ///
+    /// # impl_component_provider!(Turret);
+    /// # impl ScriptTrait for Turret {
+    /// #     fn id(&self) -> Uuid {
+    /// #         uuid!("8a2a1b0e-4c1d-4a5e-9a4b-3f1f5c0f6c8e")
+    /// #     }
+    /// # }
+    /// fn total_ammo(graph: &Graph) -> u32 {
+    ///     graph.scripts_of_type::<Turret>().map(|(_, turret)| turret.ammo).sum()
+    /// }
+    /// ```
+    pub fn scripts_of_type<S>(&self) -> impl Iterator<Item = (Handle<Node>, &S)>
+    where
+        S: ScriptTrait,
+    {
+        self.nodes_with_script::<S>()
+            .into_iter()
+            .filter_map(move |handle| {
+                self.pool
+                    .try_borrow(handle)
+                    .and_then(|n| n.try_get_script::<S>())
+                    .map(|s| (handle, s))
+            })
+    }
+
+    /// Returns handles of every node with a script of type `S`. Unlike [`Self::scripts_of_type`],
+    /// it does not borrow the graph, so it could be used to modify the scripts:
+    ///
+    /// ```rust,ignore
+    /// for handle in graph.nodes_with_script::<Turret>() {
+    ///     if let Some(turret) = graph[handle].script_as_mut::<Turret>() {
+    ///         turret.ammo += 1;
+    ///     }
+    /// }
+    /// ```
+    pub fn nodes_with_script<S>(&self) -> Vec<Handle<Node>>
+    where
+        S: ScriptTrait,
+    {
+        self.script_index
+            .lock()
+            .handles_of(TypeId::of::<S>())
+            .to_vec()
+    }
+
+    /// Searches for a node with the given instance id starting from root. Instance ids are
+    /// preserved when nodes are copied between graphs, so they could be used to reference nodes
+    /// across additively loaded scenes. If nothing was found, `Handle::NONE` is returned.
+    pub fn find_by_instance_id(&self, instance_id: InstanceId) -> Handle<Node> {
+        self.find_from_root(&mut |node| node.instance_id() == instance_id)
     }
 
     /// Searches node using specified compare closure starting from root. If nothing was found,
@@ -825,9 +992,14 @@ impl Graph {
     }
 
     fn restore_dynamic_node_data(&mut self) {
+        self.script_index.lock().clear();
         for (handle, node) in self.pool.pair_iter_mut() {
             node.self_handle = handle;
             node.script_message_sender = Some(self.script_message_sender.clone());
+            node.script_index = ScriptIndexRef(Some(self.script_index.clone()));
+            for script in node.script_instances() {
+                node.script_index.add(handle, Some(script));
+            }
         }
     }
 
@@ -852,7 +1024,7 @@ impl Graph {
         Log::writeln(MessageKind::Information, "Graph resolved successfully!");
     }
 
-    /// Calculates local and global transform, global visibility for each node in graph.
+    /// Calculates local and global transform, global visibility and enabled state for each node in graph.
     /// Normally you not need to call this method directly, it will be called automatically
     /// on each frame. However there is one use case - when you setup complex hierarchy and
     /// need to know global transform of nodes before entering update loop, then you can call
@@ -863,15 +1035,20 @@ impl Graph {
             sound_context: &mut SoundContext,
             physics: &mut PhysicsWorld,
             physics2d: &mut dim2::physics::PhysicsWorld,
+            event_broadcaster: &mut GraphEventBroadcaster,
             node_handle: Handle<Node>,
         ) {
             let node = &nodes[node_handle];
 
-            let (parent_global_transform, parent_visibility) =
+            let (parent_global_transform, parent_visibility, parent_enabled) =
                 if let Some(parent) = nodes.try_borrow(node.parent()) {
-                    (parent.global_transform(), parent.global_visibility())
+                    (
+                        parent.global_transform(),
+                        parent.global_visibility(),
+                        parent.is_globally_enabled(),
+                    )
                 } else {
-                    (Matrix4::identity(), true)
+                    (Matrix4::identity(), true, true)
                 };
 
             let new_global_transform = parent_global_transform * node.local_transform().matrix();
@@ -887,12 +1064,23 @@ impl Graph {
                 },
             );
 
-            node.global_transform.set(new_global_transform);
+            if node.global_transform.replace(new_global_transform) != new_global_transform {
+                event_broadcaster.broadcast_deferred(GraphEvent::TransformChanged(node_handle));
+            }
+            let global_enabled = parent_enabled && node.is_enabled();
+            node.global_enabled.set(global_enabled);
             node.global_visibility
-                .set(parent_visibility && node.visibility());
+                .set(parent_visibility && node.visibility() && global_enabled);
 
             for &child in node.children() {
-                update_recursively(nodes, sound_context, physics, physics2d, child);
+                update_recursively(
+                    nodes,
+                    sound_context,
+                    physics,
+                    physics2d,
+                    event_broadcaster,
+                    child,
+                );
             }
         }
 
@@ -901,6 +1089,7 @@ impl Graph {
             &mut self.sound_context,
             &mut self.physics,
             &mut self.physics2d,
+            &mut self.event_broadcaster,
             self.root,
         );
     }
@@ -923,6 +1112,40 @@ impl Graph {
         }
     }
 
+    // Physics worlds remove broken joints by themselves, joint nodes must be marked as broken to
+    // prevent re-creation of the native joints.
+    fn mark_broken_joints(&mut self) {
+        for event in self.physics.joint_events() {
+            if event.kind == JointEventKind::Broken {
+                if let Some(joint) = self
+                    .pool
+                    .try_borrow(event.joint)
+                    .and_then(|n| n.cast::<Joint>())
+                {
+                    joint.broken.set(true);
+                    joint
+                        .native
+                        .set(rapier3d::dynamics::ImpulseJointHandle::invalid());
+                }
+            }
+        }
+
+        for event in self.physics2d.joint_events() {
+            if event.kind == JointEventKind::Broken {
+                if let Some(joint) = self
+                    .pool
+                    .try_borrow(event.joint)
+                    .and_then(|n| n.cast::<dim2::joint::Joint>())
+                {
+                    joint.broken.set(true);
+                    joint
+                        .native
+                        .set(rapier2d::dynamics::ImpulseJointHandle::invalid());
+                }
+            }
+        }
+    }
+
     /// Updates nodes in graph using given delta time. There is no need to call it manually.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32) {
         let last_time = instant::Instant::now();
@@ -934,6 +1157,7 @@ impl Graph {
         self.sync_native();
         self.performance_statistics.sync_time = instant::Instant::now() - last_time;
 
+        let scope = AllocationScope::new(Subsystem::Physics);
         self.physics.performance_statistics.reset();
         self.physics.update(dt);
         self.performance_statistics.physics = self.physics.performance_statistics.clone();
@@ -941,15 +1165,39 @@ impl Graph {
         self.physics2d.performance_statistics.reset();
         self.physics2d.update(dt);
         self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
+        self.mark_broken_joints();
+        drop(scope);
 
-        self.sound_context.update(&self.pool);
+        let scope = AllocationScope::new(Subsystem::Sound);
+        self.sound_context.update(&self.pool, dt);
         self.performance_statistics.sound_update_time = self.sound_context.full_render_duration();
+        drop(scope);
 
+        self.performance_statistics.animation_time = Duration::default();
         for i in 0..self.pool.get_capacity() {
             let handle = self.pool.handle_from_index(i);
             if let Some((ticket, mut node)) = self.pool.try_take_reserve(handle) {
                 node.transform_modified.set(false);
 
+                // Disabled nodes are not updated, but they're still alive.
+                if !node.is_globally_enabled() {
+                    self.pool.put_back(ticket, node);
+                    continue;
+                }
+
+                // Measure only animation nodes, there could be lots of other nodes and measuring
+                // each of them is not free.
+                let animation_start = if node.cast::<AnimationPlayer>().is_some()
+                    || node.cast::<AnimationBlendingStateMachine>().is_some()
+                {
+                    Some((
+                        instant::Instant::now(),
+                        AllocationScope::new(Subsystem::Animation),
+                    ))
+                } else {
+                    None
+                };
+
                 let is_alive = node.update(&mut UpdateContext {
                     frame_size,
                     dt,
@@ -959,6 +1207,11 @@ impl Graph {
                     sound_context: &mut self.sound_context,
                 });
 
+                if let Some((animation_start, _scope)) = animation_start {
+                    self.performance_statistics.animation_time +=
+                        instant::Instant::now() - animation_start;
+                }
+
                 self.pool.put_back(ticket, node);
 
                 if !is_alive {
@@ -966,6 +1219,8 @@ impl Graph {
                 }
             }
         }
+
+        self.event_broadcaster.flush_deferred();
     }
 
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
@@ -1320,9 +1575,15 @@ impl Visit for Graph {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::pool::Handle,
-        scene::{graph::Graph, node::Node, pivot::Pivot},
+        core::{algebra::Vector3, pool::Handle},
+        scene::{
+            base::BaseBuilder,
+            graph::{event::GraphEvent, Graph},
+            node::Node,
+            pivot::{Pivot, PivotBuilder},
+        },
     };
+    use std::sync::mpsc::channel;
 
     #[test]
     fn graph_init_test() {
@@ -1339,4 +1600,48 @@ mod test {
         graph.add_node(Node::new(Pivot::default()));
         assert_eq!(graph.pool.alive_count(), 4);
     }
+
+    #[test]
+    fn graph_transform_changed_event_test() {
+        let mut graph = Graph::new();
+        let parent = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.link_nodes(child, parent);
+        graph.update_hierarchical_data();
+
+        let (tx, rx) = channel();
+        graph.event_broadcaster.subscribe(tx);
+
+        graph[parent]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 0.0, 0.0));
+        graph.update(Default::default(), 1.0 / 60.0);
+
+        let events = rx.try_iter().collect::<Vec<_>>();
+        assert!(events.contains(&GraphEvent::TransformChanged(parent)));
+        assert!(events.contains(&GraphEvent::TransformChanged(child)));
+
+        // Nothing has changed, so there must be no events.
+        graph.update(Default::default(), 1.0 / 60.0);
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn graph_enabled_propagation_test() {
+        let mut graph = Graph::new();
+        let parent = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.link_nodes(child, parent);
+
+        graph[parent].set_enabled(false);
+        graph.update_hierarchical_data();
+        assert!(graph[child].is_enabled());
+        assert!(!graph[child].is_globally_enabled());
+        assert!(!graph[child].global_visibility());
+
+        graph[parent].set_enabled(true);
+        graph.update_hierarchical_data();
+        assert!(graph[child].is_globally_enabled());
+        assert!(graph[child].global_visibility());
+    }
 }
@@ -21,7 +21,7 @@ use crate::{
         collider::{self, ColliderShape, GeometrySource},
         debug::SceneDrawingContext,
         graph::{isometric_global_transform, NodePool},
-        joint::JointParams,
+        joint::{JointEvent, JointEventKind, JointMotor, JointParams},
         mesh::{
             buffer::{VertexAttributeUsage, VertexReadTrait},
             Mesh,
@@ -35,8 +35,11 @@ use crate::{
         raw_mesh::{RawMeshBuilder, RawVertex},
     },
 };
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::parking_lot::Mutex;
-use rapier3d::pipeline::{DebugRenderPipeline, QueryFilter};
+use rapier3d::pipeline::{
+    ActiveEvents, ActiveHooks, DebugRenderPipeline, PairFilterContext, PhysicsHooks, QueryFilter,
+};
 use rapier3d::{
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
@@ -45,13 +48,13 @@ use rapier3d::{
     },
     geometry::{
         BroadPhase, Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid,
-        InteractionGroups, NarrowPhase, Ray, SharedShape,
+        InteractionGroups, NarrowPhase, Ray, SharedShape, SolverFlags,
     },
     pipeline::{EventHandler, PhysicsPipeline, QueryPipeline},
     prelude::JointAxis,
 };
 use std::{
-    cell::{Cell, RefCell},
+    cell::{Cell, RefCell, RefMut},
     cmp::Ordering,
     fmt::{Debug, Formatter},
     hash::Hash,
@@ -226,6 +229,111 @@ pub struct RayCastOptions {
 
     /// Whether to sort intersections from closest to farthest.
     pub sort_results: bool,
+
+    /// Layer mask to check. Colliders that do not belong to any layer of the mask will be
+    /// ignored. See [`crate::scene::base::Base::layer`] for more info. Use `u32::MAX` to check
+    /// all layers.
+    pub layer_mask: u32,
+}
+
+/// A shape that is used in shape queries (overlap tests and shape casts).
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryShape {
+    /// A sphere with given radius.
+    Sphere {
+        /// Radius of the sphere.
+        radius: f32,
+    },
+    /// A box with given half extents.
+    Box {
+        /// Half extents of the box.
+        half_extents: Vector3<f32>,
+    },
+    /// A capsule defined by its segment and radius. The segment is given in local coordinates
+    /// of the shape.
+    Capsule {
+        /// Beginning of the segment.
+        begin: Point3<f32>,
+        /// End of the segment.
+        end: Point3<f32>,
+        /// Radius of the capsule.
+        radius: f32,
+    },
+}
+
+impl QueryShape {
+    fn to_native(&self) -> SharedShape {
+        match self {
+            QueryShape::Sphere { radius } => SharedShape::ball(*radius),
+            QueryShape::Box { half_extents } => {
+                SharedShape::cuboid(half_extents.x, half_extents.y, half_extents.z)
+            }
+            QueryShape::Capsule { begin, end, radius } => {
+                SharedShape::capsule(*begin, *end, *radius)
+            }
+        }
+    }
+}
+
+/// A set of options for the overlap test.
+#[derive(Debug, Clone)]
+pub struct OverlapOptions {
+    /// A shape to test.
+    pub shape: QueryShape,
+
+    /// Position of the shape in world coordinates.
+    pub position: Vector3<f32>,
+
+    /// Rotation of the shape in world coordinates.
+    pub rotation: UnitQuaternion<f32>,
+
+    /// Groups to check.
+    pub groups: collider::InteractionGroups,
+
+    /// Layer mask to check. See [`RayCastOptions::layer_mask`] for more info.
+    pub layer_mask: u32,
+}
+
+/// A set of options for the shape cast.
+#[derive(Debug, Clone)]
+pub struct ShapeCastOptions {
+    /// A shape to cast.
+    pub shape: QueryShape,
+
+    /// Initial position of the shape in world coordinates.
+    pub position: Vector3<f32>,
+
+    /// Rotation of the shape in world coordinates.
+    pub rotation: UnitQuaternion<f32>,
+
+    /// A direction of the cast. Can be non-normalized.
+    pub direction: Vector3<f32>,
+
+    /// Maximum distance of cast.
+    pub max_len: f32,
+
+    /// Groups to check.
+    pub groups: collider::InteractionGroups,
+
+    /// Layer mask to check. See [`RayCastOptions::layer_mask`] for more info.
+    pub layer_mask: u32,
+}
+
+/// A result of the shape cast.
+#[derive(Debug, Clone)]
+pub struct ShapeCastHit {
+    /// A handle of the collider that was hit.
+    pub collider: Handle<Node>,
+
+    /// A position of the hit in world coordinates (on the surface of the collider).
+    pub position: Point3<f32>,
+
+    /// A normal of the collider surface at the hit position in world coordinates.
+    pub normal: Vector3<f32>,
+
+    /// Distance that the shape has travelled until the hit. It is zero if the shape was
+    /// intersecting the collider at its initial position.
+    pub toi: f32,
 }
 
 /// A trait for ray cast results storage. It has two implementations: Vec and ArrayVec.
@@ -333,6 +441,74 @@ pub struct IntersectionPair {
     pub has_any_active_contact: bool,
 }
 
+/// The kind of a collision event, see [`CollisionEvent`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CollisionEventKind {
+    /// Two colliders started colliding (or a collider started intersecting a sensor).
+    Started,
+    /// Two colliders stopped colliding (or a collider stopped intersecting a sensor).
+    Stopped,
+}
+
+/// A collision event that happened during the last physics step. Collision events allow you to
+/// react on collisions without polling contacts of every collider each frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CollisionEvent {
+    /// The first collider involved in the collision.
+    pub collider1: Handle<Node>,
+    /// The second collider involved in the collision.
+    pub collider2: Handle<Node>,
+    /// The kind of the event.
+    pub kind: CollisionEventKind,
+    /// `true` if at least one of the colliders is a sensor.
+    pub sensor: bool,
+}
+
+// Collects native collision events during a simulation step.
+pub(crate) struct CollisionEventCollector<E> {
+    events: Mutex<Vec<E>>,
+}
+
+impl<E> Default for CollisionEventCollector<E> {
+    fn default() -> Self {
+        Self {
+            events: Default::default(),
+        }
+    }
+}
+
+impl<E> CollisionEventCollector<E> {
+    pub(crate) fn push(&self, event: E) {
+        self.events.lock().push(event);
+    }
+
+    pub(crate) fn take(&self) -> Vec<E> {
+        std::mem::take(&mut *self.events.lock())
+    }
+}
+
+impl EventHandler for CollisionEventCollector<rapier3d::geometry::CollisionEvent> {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        event: rapier3d::geometry::CollisionEvent,
+        _contact_pair: Option<&rapier3d::geometry::ContactPair>,
+    ) {
+        self.push(event);
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: f32,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &rapier3d::geometry::ContactPair,
+        _total_force_magnitude: f32,
+    ) {
+    }
+}
+
 pub(super) struct Container<S, A>
 where
     A: Hash + Eq + Clone,
@@ -341,6 +517,40 @@ where
     map: BiDirHashMap<A, Handle<Node>>,
 }
 
+// Distance (or angle) to a joint limit at which the joint is considered to be at the limit.
+const JOINT_LIMIT_TOLERANCE: f32 = 1.0e-3;
+
+fn set_joint_motor(joint: &mut GenericJoint, axis: JointAxis, motor: &JointMotor) {
+    if motor.enabled {
+        joint
+            .set_motor(
+                axis,
+                motor.target_position,
+                motor.target_velocity,
+                motor.stiffness,
+                motor.damping,
+            )
+            .set_motor_max_force(axis, motor.max_force);
+    }
+}
+
+// Returns the free axis of the joint and its current position (if the joint has a single free axis).
+fn joint_position(
+    joint: &GenericJoint,
+    body1: &RigidBody,
+    body2: &RigidBody,
+) -> Option<(JointAxis, f32)> {
+    let relative =
+        (body1.position() * joint.local_frame1).inverse() * (body2.position() * joint.local_frame2);
+    if joint.locked_axes == JointAxesMask::LOCKED_PRISMATIC_AXES {
+        Some((JointAxis::X, relative.translation.vector.x))
+    } else if joint.locked_axes == JointAxesMask::LOCKED_REVOLUTE_AXES {
+        Some((JointAxis::AngX, relative.rotation.scaled_axis().x))
+    } else {
+        None
+    }
+}
+
 fn convert_joint_params(
     params: scene::joint::JointParams,
     local_frame1: Isometry3<f32>,
@@ -384,11 +594,13 @@ fn convert_joint_params(
             if v.limits_enabled {
                 joint.set_limits(JointAxis::X, [v.limits.start, v.limits.end]);
             }
+            set_joint_motor(&mut joint, JointAxis::X, &v.motor);
         }
         scene::joint::JointParams::RevoluteJoint(v) => {
             if v.limits_enabled {
                 joint.set_limits(JointAxis::AngX, [v.limits.start, v.limits.end]);
             }
+            set_joint_motor(&mut joint, JointAxis::AngX, &v.motor);
         }
     }
 
@@ -828,6 +1040,92 @@ impl Default for IntegrationParameters {
     }
 }
 
+/// Layer collision matrix defines which layers can collide with each other. See
+/// [`crate::scene::base::Base::layer`] for more info about layers. The matrix works on top of
+/// collision groups of colliders, both must allow a collision for it to happen. By default every
+/// layer collides with every other layer.
+#[derive(Clone, Debug, PartialEq, Eq, Visit, Reflect)]
+pub struct LayerCollisionMatrix {
+    /// Every row is a mask of layers that a layer with respective index collides with.
+    rows: Vec<u32>,
+}
+
+impl Default for LayerCollisionMatrix {
+    fn default() -> Self {
+        Self {
+            rows: vec![u32::MAX; u32::BITS as usize],
+        }
+    }
+}
+
+impl LayerCollisionMatrix {
+    /// Returns a mask of layers that a layer with the given index (`0..32`) collides with.
+    pub fn collision_mask(&self, layer_index: u32) -> u32 {
+        self.rows
+            .get(layer_index as usize)
+            .cloned()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Defines whether the layers with given indices (`0..32`) should collide or not. The
+    /// relation is symmetric.
+    pub fn set_layers_collide(&mut self, layer_a: u32, layer_b: u32, collide: bool) {
+        if layer_a >= u32::BITS || layer_b >= u32::BITS {
+            return;
+        }
+
+        self.rows.resize(u32::BITS as usize, u32::MAX);
+
+        for (row, column) in [(layer_a, layer_b), (layer_b, layer_a)] {
+            if collide {
+                self.rows[row as usize] |= 1 << column;
+            } else {
+                self.rows[row as usize] &= !(1 << column);
+            }
+        }
+    }
+
+    /// Returns `true` if the layers with given indices (`0..32`) collide with each other.
+    pub fn layers_collide(&self, layer_a: u32, layer_b: u32) -> bool {
+        layer_b < u32::BITS && self.collision_mask(layer_a) & (1 << layer_b) != 0
+    }
+
+    /// Returns `true` if the objects with the given layer masks can collide with each other.
+    pub fn can_collide(&self, layer_mask_a: u32, layer_mask_b: u32) -> bool {
+        (0..u32::BITS)
+            .filter(|i| layer_mask_a & (1 << i) != 0)
+            .any(|i| self.collision_mask(i) & layer_mask_b != 0)
+    }
+}
+
+struct LayerFilterHooks<'a> {
+    matrix: &'a LayerCollisionMatrix,
+}
+
+impl<'a> LayerFilterHooks<'a> {
+    fn can_collide(&self, context: &PairFilterContext) -> bool {
+        // Layer mask of a collider is stored in its user data, see `sync_to_collider_node`.
+        self.matrix.can_collide(
+            context.colliders[context.collider1].user_data as u32,
+            context.colliders[context.collider2].user_data as u32,
+        )
+    }
+}
+
+impl<'a> PhysicsHooks for LayerFilterHooks<'a> {
+    fn filter_contact_pair(&self, context: &PairFilterContext) -> Option<SolverFlags> {
+        if self.can_collide(context) {
+            Some(SolverFlags::COMPUTE_IMPULSES)
+        } else {
+            None
+        }
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext) -> bool {
+        self.can_collide(context)
+    }
+}
+
 /// Physics world is responsible for physics simulation in the engine. There is a very few public
 /// methods, mostly for ray casting. You should add physical entities using scene graph nodes, such
 /// as RigidBody, Collider, Joint.
@@ -842,6 +1140,11 @@ pub struct PhysicsWorld {
     /// Current gravity vector. Default is (0.0, -9.81, 0.0)
     pub gravity: Vector3<f32>,
 
+    /// Defines which layers can collide with each other. See [`LayerCollisionMatrix`] docs for
+    /// more info.
+    #[visit(optional)] // Backward compatibility
+    pub layer_collision_matrix: LayerCollisionMatrix,
+
     /// Performance statistics of a single simulation step.
     #[visit(skip)]
     #[reflect(hidden)]
@@ -887,13 +1190,45 @@ pub struct PhysicsWorld {
     // Event handler collects info about contacts and proximity events.
     #[visit(skip)]
     #[reflect(hidden)]
-    event_handler: Box<dyn EventHandler>,
+    event_handler: CollisionEventCollector<rapier3d::geometry::CollisionEvent>,
     #[visit(skip)]
     #[reflect(hidden)]
     query: RefCell<QueryPipeline>,
     #[visit(skip)]
     #[reflect(hidden)]
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
+    // Amounts of solver iterations requested by rigid bodies.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    body_solver_iterations: FxHashMap<RigidBodyHandle, u32>,
+    // Break forces of breakable joints.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    joint_break_forces: FxHashMap<ImpulseJointHandle, f32>,
+    // Joints that were at their limits at the last step.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    joints_at_limit: FxHashSet<ImpulseJointHandle>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    joint_events: Vec<JointEvent>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collision_events: Vec<CollisionEvent>,
+}
+
+fn set_sleep_thresholds(
+    activation: &mut RigidBodyActivation,
+    rigid_body_node: &scene::rigidbody::RigidBody,
+) {
+    if rigid_body_node.is_can_sleep() {
+        activation.linear_threshold = rigid_body_node.sleep_linear_threshold();
+        activation.angular_threshold = rigid_body_node.sleep_angular_threshold();
+    } else {
+        activation.sleeping = false;
+        activation.linear_threshold = -1.0;
+        activation.angular_threshold = -1.0;
+    }
 }
 
 fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry3<f32> {
@@ -932,6 +1267,7 @@ impl PhysicsWorld {
             enabled: true,
             pipeline: PhysicsPipeline::new(),
             gravity: Vector3::new(0.0, -9.81, 0.0),
+            layer_collision_matrix: Default::default(),
             integration_parameters: IntegrationParameters::default(),
             broad_phase: BroadPhase::new(),
             narrow_phase: NarrowPhase::new(),
@@ -953,17 +1289,38 @@ impl PhysicsWorld {
                 set: MultibodyJointSet::new(),
                 map: Default::default(),
             },
-            event_handler: Box::new(()),
+            event_handler: Default::default(),
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
+            body_solver_iterations: Default::default(),
+            joint_break_forces: Default::default(),
+            joints_at_limit: Default::default(),
+            joint_events: Default::default(),
+            collision_events: Default::default(),
         }
     }
 
     pub(super) fn update(&mut self, dt: f32) {
         let time = instant::Instant::now();
 
+        self.joint_events.clear();
+        self.collision_events.clear();
+
         if self.enabled {
+            let body_solver_iterations = self
+                .body_solver_iterations
+                .iter()
+                .filter_map(|(handle, iterations)| {
+                    self.bodies
+                        .set
+                        .get(*handle)
+                        .filter(|body| !body.is_sleeping())
+                        .map(|_| *iterations)
+                })
+                .max()
+                .unwrap_or_default();
+
             let integration_parameters = rapier3d::dynamics::IntegrationParameters {
                 dt: self.integration_parameters.dt.unwrap_or(dt),
                 min_ccd_dt: self.integration_parameters.min_ccd_dt,
@@ -974,8 +1331,10 @@ impl PhysicsWorld {
                 allowed_linear_error: self.integration_parameters.allowed_linear_error,
                 max_penetration_correction: self.integration_parameters.max_penetration_correction,
                 prediction_distance: self.integration_parameters.prediction_distance,
-                max_velocity_iterations: self.integration_parameters.max_velocity_iterations
-                    as usize,
+                max_velocity_iterations: self
+                    .integration_parameters
+                    .max_velocity_iterations
+                    .max(body_solver_iterations) as usize,
                 max_velocity_friction_iterations: self
                     .integration_parameters
                     .max_velocity_friction_iterations
@@ -1002,9 +1361,14 @@ impl PhysicsWorld {
                 &mut self.joints.set,
                 &mut self.multibody_joints.set,
                 &mut self.ccd_solver,
-                &(),
-                &*self.event_handler,
+                &LayerFilterHooks {
+                    matrix: &self.layer_collision_matrix,
+                },
+                &self.event_handler,
             );
+
+            self.update_joint_events(integration_parameters.dt);
+            self.update_collision_events();
         }
 
         self.performance_statistics.step_time += instant::Instant::now() - time;
@@ -1018,6 +1382,7 @@ impl PhysicsWorld {
 
     pub(crate) fn remove_body(&mut self, handle: RigidBodyHandle) {
         assert!(self.bodies.map.remove_by_key(&handle).is_some());
+        self.body_solver_iterations.remove(&handle);
         self.bodies.set.remove(
             handle,
             &mut self.islands,
@@ -1069,8 +1434,97 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn remove_joint(&mut self, handle: ImpulseJointHandle) {
-        assert!(self.joints.map.remove_by_key(&handle).is_some());
-        self.joints.set.remove(handle, false);
+        // A joint could be already removed, if it was broken or if the node was disabled.
+        if self.joints.map.remove_by_key(&handle).is_some() {
+            self.joint_break_forces.remove(&handle);
+            self.joints_at_limit.remove(&handle);
+            self.joints.set.remove(handle, false);
+        }
+    }
+
+    fn update_joint_events(&mut self, dt: f32) {
+        let mut broken = Vec::new();
+
+        for (handle, joint) in self.joints.set.iter() {
+            let node = match self.joints.map.value_of(&handle) {
+                Some(node) => *node,
+                None => continue,
+            };
+
+            if let Some(break_force) = self.joint_break_forces.get(&handle) {
+                if dt > 0.0 && joint.impulses.xyz().norm() / dt > *break_force {
+                    broken.push(handle);
+                    self.joint_events.push(JointEvent {
+                        joint: node,
+                        kind: JointEventKind::Broken,
+                    });
+                    continue;
+                }
+            }
+
+            let at_limit = match (
+                self.bodies.set.get(joint.body1),
+                self.bodies.set.get(joint.body2),
+            ) {
+                (Some(body1), Some(body2)) => joint_position(&joint.data, body1, body2)
+                    .and_then(|(axis, position)| {
+                        joint.data.limits(axis).map(|limits| {
+                            position <= limits.min + JOINT_LIMIT_TOLERANCE
+                                || position >= limits.max - JOINT_LIMIT_TOLERANCE
+                        })
+                    })
+                    .unwrap_or_default(),
+                _ => false,
+            };
+
+            if at_limit {
+                if self.joints_at_limit.insert(handle) {
+                    self.joint_events.push(JointEvent {
+                        joint: node,
+                        kind: JointEventKind::LimitReached,
+                    });
+                }
+            } else {
+                self.joints_at_limit.remove(&handle);
+            }
+        }
+
+        for handle in broken {
+            self.remove_joint(handle);
+        }
+    }
+
+    /// Returns joint events (limits reached, joints broken) that happened during the last physics
+    /// step. Broken joints are removed from the physics world automatically, and their nodes are
+    /// marked as broken (see [`scene::joint::Joint::is_broken`]).
+    pub fn joint_events(&self) -> &[JointEvent] {
+        &self.joint_events
+    }
+
+    fn update_collision_events(&mut self) {
+        for event in self.event_handler.take() {
+            // Events of removed colliders are ignored, their nodes are already gone.
+            if let (Some(collider1), Some(collider2)) = (
+                self.colliders.map.value_of(&event.collider1()),
+                self.colliders.map.value_of(&event.collider2()),
+            ) {
+                self.collision_events.push(CollisionEvent {
+                    collider1: *collider1,
+                    collider2: *collider2,
+                    kind: if event.started() {
+                        CollisionEventKind::Started
+                    } else {
+                        CollisionEventKind::Stopped
+                    },
+                    sensor: event.sensor(),
+                });
+            }
+        }
+    }
+
+    /// Returns collision events that happened during the last physics step.
+    pub fn collision_events(&self) -> &[CollisionEvent] {
+        &self.collision_events
     }
 
     /// Draws physics world. Very useful for debugging, it allows you to see where are
@@ -1086,10 +1540,7 @@ impl PhysicsWorld {
         );
     }
 
-    /// Casts a ray with given options.
-    pub fn cast_ray<S: QueryResultsStorage>(&self, opts: RayCastOptions, query_buffer: &mut S) {
-        let time = instant::Instant::now();
-
+    fn updated_query(&self) -> RefMut<QueryPipeline> {
         let mut query = self.query.borrow_mut();
 
         // TODO: Ideally this must be called once per frame, but it seems to be impossible because
@@ -1099,6 +1550,135 @@ impl PhysicsWorld {
         // of the frame.
         query.update(&self.islands, &self.bodies.set, &self.colliders.set);
 
+        query
+    }
+
+    fn add_ray_cast_time(&self, time: instant::Instant) {
+        self.performance_statistics.total_ray_cast_time.set(
+            self.performance_statistics.total_ray_cast_time.get()
+                + (instant::Instant::now() - time),
+        );
+    }
+
+    /// Casts a ray with given options.
+    pub fn cast_ray<S: QueryResultsStorage>(&self, opts: RayCastOptions, query_buffer: &mut S) {
+        let time = instant::Instant::now();
+
+        let query = self.updated_query();
+        self.cast_ray_with_query(&query, &opts, query_buffer);
+
+        self.add_ray_cast_time(time);
+    }
+
+    /// Casts a batch of rays, results of every ray are written in respective storage in
+    /// `query_buffers`. It is faster than casting rays one-by-one, because internal acceleration
+    /// structure is updated only once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the amount of rays is not equal to the amount of storages.
+    pub fn cast_rays<S: QueryResultsStorage>(
+        &self,
+        opts: &[RayCastOptions],
+        query_buffers: &mut [S],
+    ) {
+        assert_eq!(opts.len(), query_buffers.len());
+
+        let time = instant::Instant::now();
+
+        let query = self.updated_query();
+        for (opts, query_buffer) in opts.iter().zip(query_buffers.iter_mut()) {
+            self.cast_ray_with_query(&query, opts, query_buffer);
+        }
+
+        self.add_ray_cast_time(time);
+    }
+
+    /// Searches for colliders that intersect a shape with given options. Handles of the colliders
+    /// are written in `colliders`.
+    pub fn overlap(&self, opts: &OverlapOptions, colliders: &mut Vec<Handle<Node>>) {
+        let time = instant::Instant::now();
+
+        colliders.clear();
+
+        let query = self.updated_query();
+        let shape = opts.shape.to_native();
+        let layer_predicate = |_: ColliderHandle, collider: &Collider| {
+            collider.user_data as u32 & opts.layer_mask != 0
+        };
+        query.intersections_with_shape(
+            &self.bodies.set,
+            &self.colliders.set,
+            &Isometry3::from_parts(Translation3::from(opts.position), opts.rotation),
+            &*shape,
+            QueryFilter::new()
+                .groups(InteractionGroups::new(
+                    u32_to_group(opts.groups.memberships.0),
+                    u32_to_group(opts.groups.filter.0),
+                ))
+                .predicate(&layer_predicate),
+            |handle| {
+                colliders.push(self.colliders.map.value_of(&handle).cloned().unwrap());
+                true
+            },
+        );
+
+        self.add_ray_cast_time(time);
+    }
+
+    /// Moves a shape along a direction with given options and returns the first collider that
+    /// was hit by the shape (if any).
+    pub fn cast_shape(&self, opts: &ShapeCastOptions) -> Option<ShapeCastHit> {
+        let time = instant::Instant::now();
+
+        let query = self.updated_query();
+        let shape = opts.shape.to_native();
+        let layer_predicate = |_: ColliderHandle, collider: &Collider| {
+            collider.user_data as u32 & opts.layer_mask != 0
+        };
+        let result = query
+            .cast_shape(
+                &self.bodies.set,
+                &self.colliders.set,
+                &Isometry3::from_parts(Translation3::from(opts.position), opts.rotation),
+                &opts
+                    .direction
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default(),
+                &*shape,
+                opts.max_len,
+                // Shapes that are already intersecting are reported with zero time of impact.
+                true,
+                QueryFilter::new()
+                    .groups(InteractionGroups::new(
+                        u32_to_group(opts.groups.memberships.0),
+                        u32_to_group(opts.groups.filter.0),
+                    ))
+                    .predicate(&layer_predicate),
+            )
+            .and_then(|(handle, toi)| {
+                let collider = self.colliders.set.get(handle)?;
+                // Witness point and normal are given in local coordinates of the collider.
+                let position = collider.position();
+                Some(ShapeCastHit {
+                    collider: self.colliders.map.value_of(&handle).cloned()?,
+                    position: position * toi.witness1,
+                    normal: position * toi.normal1.into_inner(),
+                    toi: toi.toi,
+                })
+            });
+
+        self.add_ray_cast_time(time);
+
+        result
+    }
+
+    fn cast_ray_with_query<S: QueryResultsStorage>(
+        &self,
+        query: &QueryPipeline,
+        opts: &RayCastOptions,
+        query_buffer: &mut S,
+    ) {
         query_buffer.clear();
         let ray = Ray::new(
             opts.ray_origin,
@@ -1106,16 +1686,21 @@ impl PhysicsWorld {
                 .try_normalize(f32::EPSILON)
                 .unwrap_or_default(),
         );
+        let layer_predicate = |_: ColliderHandle, collider: &Collider| {
+            collider.user_data as u32 & opts.layer_mask != 0
+        };
         query.intersections_with_ray(
             &self.bodies.set,
             &self.colliders.set,
             &ray,
             opts.max_len,
             true,
-            QueryFilter::new().groups(InteractionGroups::new(
-                u32_to_group(opts.groups.memberships.0),
-                u32_to_group(opts.groups.filter.0),
-            )),
+            QueryFilter::new()
+                .groups(InteractionGroups::new(
+                    u32_to_group(opts.groups.memberships.0),
+                    u32_to_group(opts.groups.filter.0),
+                ))
+                .predicate(&layer_predicate),
             |handle, intersection| {
                 query_buffer.push(Intersection {
                     collider: self.colliders.map.value_of(&handle).cloned().unwrap(),
@@ -1137,11 +1722,6 @@ impl PhysicsWorld {
                 }
             })
         }
-
-        self.performance_statistics.total_ray_cast_time.set(
-            self.performance_statistics.total_ray_cast_time.get()
-                + (instant::Instant::now() - time),
-        );
     }
 
     pub(crate) fn set_rigid_body_position(
@@ -1205,6 +1785,16 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         rigid_body_node: &scene::rigidbody::RigidBody,
     ) {
+        // Native body of a disabled node is removed, it will be re-created from the node when the
+        // node is enabled back.
+        if !rigid_body_node.is_globally_enabled() {
+            if self.bodies.set.get(rigid_body_node.native.get()).is_some() {
+                self.remove_body(rigid_body_node.native.get());
+            }
+            rigid_body_node.native.set(RigidBodyHandle::invalid());
+            return;
+        }
+
         // Important notes!
         // 1) `get_mut` is **very** expensive because it forces physics engine to recalculate contacts
         //    and a lot of other stuff, this is why we need `anything_changed` flag.
@@ -1237,19 +1827,29 @@ impl PhysicsWorld {
                     rigid_body_node
                         .ccd_enabled
                         .try_sync_model(|v| native.enable_ccd(v));
-                    rigid_body_node.can_sleep.try_sync_model(|v| {
-                        let mut activation = native.activation_mut();
-                        if v {
-                            activation.linear_threshold =
-                                RigidBodyActivation::default_linear_threshold();
-                            activation.angular_threshold =
-                                RigidBodyActivation::default_angular_threshold();
-                        } else {
-                            activation.sleeping = false;
-                            activation.linear_threshold = -1.0;
-                            activation.angular_threshold = -1.0;
-                        };
-                    });
+                    // These properties define sleep thresholds together, so they're synced at once.
+                    let sleep_thresholds_changed = rigid_body_node.can_sleep.try_sync_model(|_| ())
+                        | rigid_body_node
+                            .sleep_linear_threshold
+                            .try_sync_model(|_| ())
+                        | rigid_body_node
+                            .sleep_angular_threshold
+                            .try_sync_model(|_| ());
+                    if sleep_thresholds_changed {
+                        set_sleep_thresholds(native.activation_mut(), rigid_body_node);
+                    }
+                    rigid_body_node
+                        .solver_iterations
+                        .try_sync_model(|v| match v {
+                            Some(iterations) => {
+                                self.body_solver_iterations
+                                    .insert(rigid_body_node.native.get(), iterations);
+                            }
+                            None => {
+                                self.body_solver_iterations
+                                    .remove(&rigid_body_node.native.get());
+                            }
+                        });
                     rigid_body_node
                         .translation_locked
                         .try_sync_model(|v| native.lock_translations(v, false));
@@ -1328,9 +1928,15 @@ impl PhysicsWorld {
                 builder = builder.lock_translations();
             }
 
-            rigid_body_node
-                .native
-                .set(self.add_body(handle, builder.build()));
+            let mut body = builder.build();
+            set_sleep_thresholds(body.activation_mut(), rigid_body_node);
+
+            let native = self.add_body(handle, body);
+            rigid_body_node.native.set(native);
+
+            if let Some(iterations) = rigid_body_node.solver_iterations() {
+                self.body_solver_iterations.insert(native, iterations);
+            }
 
             Log::writeln(
                 MessageKind::Information,
@@ -1348,6 +1954,12 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         collider_node: &scene::collider::Collider,
     ) {
+        if !collider_node.is_globally_enabled() {
+            self.remove_collider(collider_node.native.get());
+            collider_node.native.set(ColliderHandle::invalid());
+            return;
+        }
+
         let anything_changed =
             collider_node.transform_modified.get() || collider_node.needs_sync_model();
 
@@ -1408,6 +2020,9 @@ impl PhysicsWorld {
                     collider_node
                         .restitution_combine_rule
                         .try_sync_model(|v| native.set_restitution_combine_rule(v.into()));
+                    collider_node
+                        .layer
+                        .try_sync_model(|v| native.user_data = v as u128);
                 }
             }
         } else if let Some(parent_body) = nodes
@@ -1444,7 +2059,14 @@ impl PhysicsWorld {
                             u32_to_group(collider_node.solver_groups().memberships.0),
                             u32_to_group(collider_node.solver_groups().filter.0),
                         ))
-                        .sensor(collider_node.is_sensor());
+                        .sensor(collider_node.is_sensor())
+                        // Layer mask is used by `LayerFilterHooks` and ray casting.
+                        .user_data(collider_node.layer() as u128)
+                        .active_hooks(
+                            ActiveHooks::FILTER_CONTACT_PAIRS
+                                | ActiveHooks::FILTER_INTERSECTION_PAIR,
+                        )
+                        .active_events(ActiveEvents::COLLISION_EVENTS);
 
                     if let Some(density) = collider_node.density() {
                         builder = builder.density(density);
@@ -1473,6 +2095,16 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         joint: &scene::joint::Joint,
     ) {
+        if !joint.is_globally_enabled() {
+            self.remove_joint(joint.native.get());
+            joint.native.set(ImpulseJointHandle::invalid());
+            return;
+        }
+
+        if joint.is_broken() {
+            return;
+        }
+
         if let Some(native) = self.joints.set.get_mut(joint.native.get()) {
             joint.body1.try_sync_model(|v| {
                 if let Some(rigid_body_node) = nodes
@@ -1498,6 +2130,16 @@ impl PhysicsWorld {
             joint.contacts_enabled.try_sync_model(|v| {
                 native.data.set_contacts_enabled(v);
             });
+            let native_handle = joint.native.get();
+            let joint_break_forces = &mut self.joint_break_forces;
+            joint.break_force.try_sync_model(|v| match v {
+                Some(break_force) => {
+                    joint_break_forces.insert(native_handle, break_force);
+                }
+                None => {
+                    joint_break_forces.remove(&native_handle);
+                }
+            });
             if joint.need_rebind.get() {
                 if let (Some(body1), Some(body2)) = (
                     nodes
@@ -1540,6 +2182,9 @@ impl PhysicsWorld {
                 native_joint.contacts_enabled = joint.is_contacts_enabled();
                 let native_handle =
                     self.add_joint(handle, native_body1, native_body2, native_joint);
+                if let Some(break_force) = joint.break_force() {
+                    self.joint_break_forces.insert(native_handle, break_force);
+                }
 
                 joint.native.set(native_handle);
                 joint.need_rebind.set(false);
@@ -0,0 +1,77 @@
+//! Script index is a cache that maps script types to the nodes with scripts of the respective
+//! type. It allows to find all scripts of a particular type without iterating over every node
+//! of a graph. See [`super::Graph::scripts_of_type`] for more info.
+
+use crate::{
+    core::{parking_lot::Mutex, pool::Handle, reflect::Reflect},
+    scene::node::Node,
+    script::Script,
+};
+use fxhash::FxHashMap;
+use std::{any::TypeId, sync::Arc};
+
+/// Maps type ids of scripts to the handles of nodes with scripts of the respective type.
+#[derive(Default, Debug)]
+pub struct ScriptIndex {
+    map: FxHashMap<TypeId, Vec<Handle<Node>>>,
+}
+
+impl ScriptIndex {
+    /// Registers the script of the given node in the index.
+    pub fn add(&mut self, handle: Handle<Node>, script: &Script) {
+        let handles = self.map.entry(script.as_any().type_id()).or_default();
+        if !handles.contains(&handle) {
+            handles.push(handle);
+        }
+    }
+
+    /// Removes the script of the given node from the index.
+    pub fn remove(&mut self, handle: Handle<Node>, script: &Script) {
+        if let Some(handles) = self.map.get_mut(&script.as_any().type_id()) {
+            if let Some(position) = handles.iter().position(|h| *h == handle) {
+                handles.swap_remove(position);
+            }
+        }
+    }
+
+    /// Returns a slice with handles of nodes, that have a script of the given type.
+    pub fn handles_of(&self, type_id: TypeId) -> &[Handle<Node>] {
+        self.map
+            .get(&type_id)
+            .map(|h| h.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Removes everything from the index.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+/// A shared reference to a script index of a graph, that is stored in every node of the graph.
+/// Cloning the reference produces an empty reference, because a copy of a node does not belong
+/// to any graph until it is added to one.
+#[derive(Default, Debug)]
+pub struct ScriptIndexRef(pub(crate) Option<Arc<Mutex<ScriptIndex>>>);
+
+impl Clone for ScriptIndexRef {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl ScriptIndexRef {
+    pub(crate) fn add(&self, handle: Handle<Node>, script: Option<&Script>) {
+        if let (Some(index), Some(script)) = (self.0.as_ref(), script) {
+            if handle.is_some() {
+                index.lock().add(handle, script);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&self, handle: Handle<Node>, script: Option<&Script>) {
+        if let (Some(index), Some(script)) = (self.0.as_ref(), script) {
+            index.lock().remove(handle, script);
+        }
+    }
+}
@@ -27,6 +27,90 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
+/// Motor of a joint drives the free axis of a joint towards the target position and/or with the target
+/// velocity. It is used to make doors that open by themselves, elevators, conveyors and so on.
+///
+/// The force applied by the motor is `stiffness * (target_position - position) + damping *
+/// (target_velocity - velocity)`, clamped to `max_force`. Pure velocity motor could be defined
+/// by zero stiffness, pure position motor - by zero target velocity.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct JointMotor {
+    /// Whether the motor is enabled or not. Default is `false`
+    #[reflect(description = "Whether the motor is enabled or not.")]
+    pub enabled: bool,
+
+    /// Target velocity of the motor (in radians per second for angular axes, in units per second
+    /// for linear axes).
+    #[reflect(description = "Target velocity of the motor.")]
+    pub target_velocity: f32,
+
+    /// Target position of the motor (in radians for angular axes, in units for linear axes).
+    #[reflect(description = "Target position of the motor.")]
+    pub target_position: f32,
+
+    /// Stiffness of the motor, defines how strongly the motor pulls the joint to the target position.
+    #[reflect(
+        description = "Stiffness of the motor, defines how strongly the motor pulls the joint to the target position."
+    )]
+    pub stiffness: f32,
+
+    /// Damping of the motor, defines how strongly the motor pulls the joint to the target velocity.
+    #[reflect(
+        description = "Damping of the motor, defines how strongly the motor pulls the joint to the target velocity."
+    )]
+    pub damping: f32,
+
+    /// Maximum force (or torque for angular axes) that the motor can apply.
+    #[reflect(description = "Maximum force (or torque) that the motor can apply.")]
+    pub max_force: f32,
+}
+
+impl Default for JointMotor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_velocity: 0.0,
+            target_position: 0.0,
+            stiffness: 0.0,
+            damping: 1.0,
+            max_force: f32::MAX,
+        }
+    }
+}
+
+/// The kind of a joint event, see [`JointEvent`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JointEventKind {
+    /// The joint has reached one of its limits. The event is emitted once when the joint hits the limit,
+    /// it will be emitted again only if the joint leaves the limit and then hits it again. Only revolute
+    /// and prismatic joints emit this event.
+    LimitReached,
+    /// The force applied to the joint has exceeded its break force (see [`Joint::set_break_force`])
+    /// and the joint was broken.
+    Broken,
+}
+
+/// An event that happened with a joint during the last physics step. Joint events could be fetched
+/// from a physics world, for example in [`crate::script::ScriptTrait::on_update`]:
+///
+/// ```rust
+/// # use fyrox::scene::{graph::Graph, joint::JointEventKind};
+/// fn check_joints(graph: &Graph) {
+///     for event in graph.physics.joint_events() {
+///         if event.kind == JointEventKind::Broken {
+///             println!("Joint {} is broken!", event.joint);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct JointEvent {
+    /// A handle of the joint node.
+    pub joint: Handle<Node>,
+    /// The kind of the event.
+    pub kind: JointEventKind,
+}
+
 /// Ball joint locks any translational moves between two objects on the axis between objects, but
 /// allows rigid bodies to perform relative rotations. The real world example is a human shoulder,
 /// pendulum, etc.
@@ -96,6 +180,11 @@ pub struct PrismaticJoint {
     )]
     #[visit(optional)] // Backward compatibility
     pub limits: Range<f32>,
+
+    /// Motor that drives the joint along local X axis of the joint.
+    #[reflect(description = "Motor that drives the joint along local X axis of the joint.")]
+    #[visit(optional)] // Backward compatibility
+    pub motor: JointMotor,
 }
 
 impl Default for PrismaticJoint {
@@ -103,6 +192,7 @@ impl Default for PrismaticJoint {
         Self {
             limits_enabled: false,
             limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
         }
     }
 }
@@ -123,6 +213,11 @@ pub struct RevoluteJoint {
     #[reflect(description = "Allowed angle range around local X axis of the joint (in radians).")]
     #[visit(optional)] // Backward compatibility
     pub limits: Range<f32>,
+
+    /// Motor that drives the joint around local X axis of the joint.
+    #[reflect(description = "Motor that drives the joint around local X axis of the joint.")]
+    #[visit(optional)] // Backward compatibility
+    pub motor: JointMotor,
 }
 
 impl Default for RevoluteJoint {
@@ -130,6 +225,7 @@ impl Default for RevoluteJoint {
         Self {
             limits_enabled: false,
             limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
         }
     }
 }
@@ -172,6 +268,14 @@ pub struct Joint {
     #[visit(optional)] // Backward compatibility
     pub(crate) contacts_enabled: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_break_force")]
+    #[visit(optional)] // Backward compatibility
+    pub(crate) break_force: InheritableVariable<Option<f32>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    pub(crate) broken: Cell<bool>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: Cell<ImpulseJointHandle>,
@@ -189,6 +293,8 @@ impl Default for Joint {
             body1: Default::default(),
             body2: Default::default(),
             contacts_enabled: InheritableVariable::new(true),
+            break_force: Default::default(),
+            broken: Cell::new(false),
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
         }
@@ -217,6 +323,8 @@ impl Clone for Joint {
             body1: self.body1.clone(),
             body2: self.body2.clone(),
             contacts_enabled: self.contacts_enabled.clone(),
+            break_force: self.break_force.clone(),
+            broken: self.broken.clone(),
             // Do not copy. The copy will have its own native representation.
             native: Cell::new(ImpulseJointHandle::invalid()),
             // Rebind will happen automatically.
@@ -279,6 +387,30 @@ impl Joint {
     pub fn is_contacts_enabled(&self) -> bool {
         *self.contacts_enabled
     }
+
+    /// Sets the maximum force that the joint can withstand. If the force applied to the joint exceeds
+    /// the value, the joint breaks and [`JointEventKind::Broken`] event is emitted. `None` means that
+    /// the joint is unbreakable.
+    pub fn set_break_force(&mut self, break_force: Option<f32>) -> Option<f32> {
+        self.break_force.set(break_force)
+    }
+
+    /// Returns the maximum force that the joint can withstand.
+    pub fn break_force(&self) -> Option<f32> {
+        *self.break_force
+    }
+
+    /// Returns `true` if the joint was broken, `false` - otherwise. Broken joint does not restrict
+    /// motion of its bodies.
+    pub fn is_broken(&self) -> bool {
+        self.broken.get()
+    }
+
+    /// Restores the joint after it was broken.
+    pub fn repair(&mut self) {
+        self.broken.set(false);
+        self.need_rebind.set(true);
+    }
 }
 
 impl NodeTrait for Joint {
@@ -358,6 +490,7 @@ pub struct JointBuilder {
     body1: Handle<Node>,
     body2: Handle<Node>,
     contacts_enabled: bool,
+    break_force: Option<f32>,
 }
 
 impl JointBuilder {
@@ -369,6 +502,7 @@ impl JointBuilder {
             body1: Default::default(),
             body2: Default::default(),
             contacts_enabled: true,
+            break_force: None,
         }
     }
 
@@ -398,6 +532,12 @@ impl JointBuilder {
         self
     }
 
+    /// Sets the maximum force that the joint can withstand, see [`Joint::set_break_force`] for more info.
+    pub fn with_break_force(mut self, break_force: Option<f32>) -> Self {
+        self.break_force = break_force;
+        self
+    }
+
     /// Creates new Joint node, but does not add it to the graph.
     pub fn build_joint(self) -> Joint {
         Joint {
@@ -406,6 +546,8 @@ impl JointBuilder {
             body1: self.body1.into(),
             body2: self.body2.into(),
             contacts_enabled: self.contacts_enabled.into(),
+            break_force: self.break_force.into(),
+            broken: Cell::new(false),
             native: Cell::new(ImpulseJointHandle::invalid()),
             need_rebind: Cell::new(true),
         }
@@ -435,6 +577,7 @@ mod test {
     fn test_joint_inheritance() {
         let parent = JointBuilder::new(BaseBuilder::new())
             .with_params(JointParams::BallJoint(BallJoint::default()))
+            .with_break_force(Some(100.0))
             .build_node();
 
         let mut child = JointBuilder::new(BaseBuilder::new()).build_joint();
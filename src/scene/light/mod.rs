@@ -31,6 +31,8 @@ use std::ops::{Deref, DerefMut};
 
 pub mod directional;
 pub mod point;
+pub mod probe;
+pub mod reflection_probe;
 pub mod spot;
 
 /// Default amount of light scattering, it is set to 3% which is fairly
@@ -0,0 +1,422 @@
+//! Light probe volume is a grid of light probes, that stores baked indirect lighting. See
+//! [`LightProbeVolume`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+    },
+};
+use std::{
+    f32::consts::PI,
+    ops::{Deref, DerefMut},
+};
+
+/// Amount of coefficients of second order spherical harmonics.
+pub const SH_COEFFICIENT_COUNT: usize = 9;
+
+/// Second order (L2) spherical harmonics with RGB coefficients. It is used to store incoming
+/// radiance at a point in a compact form.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Visit)]
+pub struct SphericalHarmonics {
+    /// RGB coefficients of the spherical harmonics.
+    pub coefficients: [Vector3<f32>; SH_COEFFICIENT_COUNT],
+}
+
+impl SphericalHarmonics {
+    /// Calculates values of spherical harmonics basis functions for the given direction. The
+    /// direction must be normalized.
+    pub fn basis(direction: Vector3<f32>) -> [f32; SH_COEFFICIENT_COUNT] {
+        let (x, y, z) = (direction.x, direction.y, direction.z);
+        [
+            0.282095,
+            0.488603 * y,
+            0.488603 * z,
+            0.488603 * x,
+            1.092548 * x * y,
+            1.092548 * y * z,
+            0.315392 * (3.0 * z * z - 1.0),
+            1.092548 * x * z,
+            0.546274 * (x * x - y * y),
+        ]
+    }
+
+    /// Convolution factors of each coefficient with the clamped cosine lobe divided by `PI`. The
+    /// factors convert radiance coefficients into diffuse (irradiance / PI) coefficients.
+    pub fn diffuse_convolution_factors() -> [f32; SH_COEFFICIENT_COUNT] {
+        let a0 = 1.0;
+        let a1 = 2.0 / 3.0;
+        let a2 = 1.0 / 4.0;
+        [a0, a1, a1, a1, a2, a2, a2, a2, a2]
+    }
+
+    /// Adds a radiance sample coming from the given direction. `weight` is a solid angle of the
+    /// sample, for `N` uniformly distributed samples it is `4 * PI / N`.
+    pub fn add_sample(&mut self, direction: Vector3<f32>, radiance: Vector3<f32>, weight: f32) {
+        for (coefficient, basis) in self
+            .coefficients
+            .iter_mut()
+            .zip(Self::basis(direction).iter())
+        {
+            *coefficient += radiance.scale(basis * weight);
+        }
+    }
+
+    /// Evaluates radiance in the given direction.
+    pub fn evaluate(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        self.coefficients
+            .iter()
+            .zip(Self::basis(direction).iter())
+            .fold(Vector3::default(), |sum, (c, b)| sum + c.scale(*b))
+    }
+
+    /// Evaluates diffuse lighting (irradiance divided by `PI`) for a surface with the given
+    /// normal.
+    pub fn diffuse(&self, normal: Vector3<f32>) -> Vector3<f32> {
+        self.coefficients
+            .iter()
+            .zip(Self::basis(normal).iter())
+            .zip(Self::diffuse_convolution_factors().iter())
+            .fold(Vector3::default(), |sum, ((c, b), f)| {
+                sum + c.scale(*b * *f)
+            })
+    }
+
+    /// Evaluates irradiance for a surface with the given normal.
+    pub fn irradiance(&self, normal: Vector3<f32>) -> Vector3<f32> {
+        self.diffuse(normal).scale(PI)
+    }
+
+    /// Linearly interpolates between two sets of coefficients.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut result = *self;
+        for (a, b) in result
+            .coefficients
+            .iter_mut()
+            .zip(other.coefficients.iter())
+        {
+            *a = a.lerp(b, t);
+        }
+        result
+    }
+}
+
+/// Light probe volume is a box-shaped grid of light probes. Each probe stores indirect (bounced)
+/// lighting at its position as [`SphericalHarmonics`]. Probes are baked using
+/// [`crate::utils::light_probe::bake_light_probes`], the renderer interpolates the probes per
+/// pixel and uses the result instead of flat ambient lighting. This way dynamic objects pick up
+/// bounced light from the static environment.
+///
+/// # Size and transformations
+///
+/// Similar to [`crate::scene::decal::Decal`], the volume is a unit cube in local coordinates, its
+/// final size is defined by the scale of the node. Probes are distributed uniformly inside the
+/// cube, their amount along each axis is defined by [`Self::resolution`].
+///
+/// # Limitations
+///
+/// Probes affect only objects rendered in the deferred render path. Only four volumes could be
+/// used at the same time, the volumes closest to the camera are selected.
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct LightProbeVolume {
+    base: Base,
+
+    #[reflect(setter = "set_resolution")]
+    resolution: InheritableVariable<Vector3<u32>>,
+
+    #[reflect(hidden)]
+    probes: Vec<SphericalHarmonics>,
+}
+
+impl Default for LightProbeVolume {
+    fn default() -> Self {
+        LightProbeVolumeBuilder::new(BaseBuilder::new()).build_light_probe_volume()
+    }
+}
+
+impl Deref for LightProbeVolume {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for LightProbeVolume {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for LightProbeVolume {
+    fn type_uuid() -> Uuid {
+        uuid!("8d3b9a0e-62f4-4d1c-b5a7-1e9c0f7d2a48")
+    }
+}
+
+impl LightProbeVolume {
+    /// Sets amount of probes along each axis. Every component is clamped to `[2; 32]` range.
+    /// Baked probes are discarded if the resolution has changed, the volume must be baked again.
+    pub fn set_resolution(&mut self, resolution: Vector3<u32>) -> Vector3<u32> {
+        let resolution = resolution.map(|n| n.clamp(2, 32));
+        if resolution != *self.resolution {
+            self.probes.clear();
+        }
+        self.resolution.set(resolution)
+    }
+
+    /// Returns amount of probes along each axis.
+    pub fn resolution(&self) -> Vector3<u32> {
+        *self.resolution
+    }
+
+    /// Returns total amount of probes in the volume.
+    pub fn probe_count(&self) -> usize {
+        (self.resolution.x * self.resolution.y * self.resolution.z) as usize
+    }
+
+    /// Returns linear index of a probe with the given grid coordinates.
+    pub fn probe_index(&self, x: u32, y: u32, z: u32) -> usize {
+        ((z * self.resolution.y + y) * self.resolution.x + x) as usize
+    }
+
+    /// Returns position of a probe with the given grid coordinates in local coordinates of the
+    /// volume.
+    pub fn probe_local_position(&self, x: u32, y: u32, z: u32) -> Vector3<f32> {
+        let step = |i: u32, n: u32| i as f32 / (n - 1) as f32 - 0.5;
+        Vector3::new(
+            step(x, self.resolution.x),
+            step(y, self.resolution.y),
+            step(z, self.resolution.z),
+        )
+    }
+
+    /// Returns world-space positions of every probe in the volume, in the order of their linear
+    /// indices.
+    pub fn probe_world_positions(&self) -> Vec<Vector3<f32>> {
+        let transform = self.global_transform();
+        let mut positions = Vec::with_capacity(self.probe_count());
+        for z in 0..self.resolution.z {
+            for y in 0..self.resolution.y {
+                for x in 0..self.resolution.x {
+                    positions.push(
+                        transform
+                            .transform_point(&Point3::from(self.probe_local_position(x, y, z)))
+                            .coords,
+                    );
+                }
+            }
+        }
+        positions
+    }
+
+    /// Returns `true` if the volume has baked probes.
+    pub fn is_baked(&self) -> bool {
+        !self.probes.is_empty() && self.probes.len() == self.probe_count()
+    }
+
+    /// Returns baked probes. The slice is empty if the volume is not baked.
+    pub fn probes(&self) -> &[SphericalHarmonics] {
+        &self.probes
+    }
+
+    /// Sets baked probes. Amount of probes must match [`Self::probe_count`], otherwise the
+    /// probes will be ignored and `false` will be returned.
+    pub fn set_probes(&mut self, probes: Vec<SphericalHarmonics>) -> bool {
+        if probes.len() == self.probe_count() {
+            self.probes = probes;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes every baked probe.
+    pub fn clear_probes(&mut self) {
+        self.probes.clear();
+    }
+
+    /// Interpolates probes at the given world-space position. Returns `None` if the volume is
+    /// not baked or the position is outside of the volume.
+    pub fn sample(&self, world_position: Vector3<f32>) -> Option<SphericalHarmonics> {
+        if !self.is_baked() {
+            return None;
+        }
+
+        let local = self
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+            .transform_point(&Point3::from(world_position))
+            .coords;
+
+        if !AxisAlignedBoundingBox::unit().is_contains_point(local) {
+            return None;
+        }
+
+        let grid = |v: f32, n: u32| {
+            let f = ((v + 0.5) * (n - 1) as f32).clamp(0.0, (n - 1) as f32);
+            let i = (f.floor() as u32).min(n - 2);
+            (i, f - i as f32)
+        };
+
+        let (x, tx) = grid(local.x, self.resolution.x);
+        let (y, ty) = grid(local.y, self.resolution.y);
+        let (z, tz) = grid(local.z, self.resolution.z);
+
+        let probe =
+            |dx: u32, dy: u32, dz: u32| &self.probes[self.probe_index(x + dx, y + dy, z + dz)];
+
+        let lerp_x = |dy: u32, dz: u32| probe(0, dy, dz).lerp(probe(1, dy, dz), tx);
+        let lerp_y = |dz: u32| lerp_x(0, dz).lerp(&lerp_x(1, dz), ty);
+
+        Some(lerp_y(0).lerp(&lerp_y(1), tz))
+    }
+}
+
+impl NodeTrait for LightProbeVolume {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a light probe volume in a declarative manner.
+pub struct LightProbeVolumeBuilder {
+    base_builder: BaseBuilder,
+    resolution: Vector3<u32>,
+}
+
+impl LightProbeVolumeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            resolution: Vector3::new(4, 4, 4),
+        }
+    }
+
+    /// Sets desired amount of probes along each axis.
+    pub fn with_resolution(mut self, resolution: Vector3<u32>) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Creates new light probe volume.
+    pub fn build_light_probe_volume(self) -> LightProbeVolume {
+        LightProbeVolume {
+            base: self.base_builder.build_base(),
+            resolution: self.resolution.map(|n| n.clamp(2, 32)).into(),
+            probes: Default::default(),
+        }
+    }
+
+    /// Creates new light probe volume node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_light_probe_volume())
+    }
+
+    /// Creates new instance of light probe volume node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            light::probe::{LightProbeVolume, LightProbeVolumeBuilder, SphericalHarmonics},
+            transform::TransformBuilder,
+        },
+    };
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_spherical_harmonics_constant_radiance() {
+        // Project constant radiance from every direction using Fibonacci sphere.
+        let mut sh = SphericalHarmonics::default();
+        let count = 1024;
+        for i in 0..count {
+            let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+            let r = (1.0 - y * y).sqrt();
+            let phi = i as f32 * PI * (3.0 - 5.0f32.sqrt());
+            let dir = Vector3::new(r * phi.cos(), y, r * phi.sin());
+            sh.add_sample(dir, Vector3::new(1.0, 0.5, 0.25), 4.0 * PI / count as f32);
+        }
+
+        // Diffuse lighting from a uniform environment equals to its radiance.
+        let diffuse = sh.diffuse(Vector3::y());
+        assert!((diffuse.x - 1.0).abs() < 0.01);
+        assert!((diffuse.y - 0.5).abs() < 0.01);
+        assert!((diffuse.z - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_light_probe_volume_sampling() {
+        let mut graph = Graph::new();
+        let handle = LightProbeVolumeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_scale(Vector3::new(2.0, 2.0, 2.0))
+                    .build(),
+            ),
+        )
+        .with_resolution(Vector3::new(2, 2, 2))
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let volume = graph[handle].cast_mut::<LightProbeVolume>().unwrap();
+        assert!(volume.sample(Vector3::default()).is_none());
+
+        // Left probes are black, right probes are white.
+        let probes = (0..8)
+            .map(|i| {
+                let mut sh = SphericalHarmonics::default();
+                if i % 2 == 1 {
+                    sh.coefficients[0] = Vector3::new(1.0, 1.0, 1.0);
+                }
+                sh
+            })
+            .collect::<Vec<_>>();
+        assert!(!volume.set_probes(probes[..4].to_vec()));
+        assert!(volume.set_probes(probes));
+
+        let center = volume.sample(Vector3::default()).unwrap();
+        assert_eq!(center.coefficients[0], Vector3::new(0.5, 0.5, 0.5));
+        let right = volume.sample(Vector3::new(1.0, 0.0, 0.0)).unwrap();
+        assert_eq!(right.coefficients[0], Vector3::new(1.0, 1.0, 1.0));
+        assert!(volume.sample(Vector3::new(1.5, 0.0, 0.0)).is_none());
+
+        volume.set_resolution(Vector3::new(3, 2, 2));
+        assert!(!volume.is_baked());
+    }
+}
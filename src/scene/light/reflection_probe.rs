@@ -0,0 +1,449 @@
+//! Reflection probe captures surroundings into a cube map, that is used for specular reflections
+//! of nearby objects. See [`ReflectionProbe`] docs for more info.
+
+use crate::{
+    core::{
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
+        visibility::VisibilityCache,
+    },
+};
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines when a reflection probe captures its surroundings.
+#[derive(
+    Visit, Copy, Clone, PartialEq, Eq, Debug, Reflect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum ReflectionProbeUpdateMode {
+    /// The probe is captured once, when it is rendered for the first time. Use
+    /// [`ReflectionProbe::request_update`] to capture it again, for example when static
+    /// environment has changed. This is the default and the cheapest option.
+    Baked,
+
+    /// The probe is captured every `interval` frames. Live probes are expensive, every capture
+    /// renders the scene six times, use them only when reflections must follow dynamic objects.
+    Live {
+        /// Amount of frames between two captures. `1` means every frame.
+        #[reflect(min_value = 1.0)]
+        interval: u32,
+    },
+}
+
+impl Default for ReflectionProbeUpdateMode {
+    fn default() -> Self {
+        Self::Baked
+    }
+}
+
+/// Reflection probe captures the scene into a cube map and applies it as specular reflection to
+/// every pixel inside of its box. It makes shiny surfaces reflect their actual surroundings.
+///
+/// # Size and transformations
+///
+/// Similar to [`crate::scene::decal::Decal`], the probe is a unit cube in local coordinates, its
+/// final size is defined by the scale of the node. The scene is captured from the position of
+/// the node.
+///
+/// # Box projection
+///
+/// Cube map captured at a single point has reflections that look "infinitely far" away. Box
+/// projection corrects the reflection vector by intersecting it with the box of the probe, so
+/// reflections match the walls of a room, that has roughly the same size as the box. Box
+/// projection is enabled by default, disable it for outdoor probes.
+///
+/// # Blending
+///
+/// Overlapping probes are applied in priority order (see [`Self::set_priority`]), probes with
+/// the same priority are sorted by distance to the camera. Each probe smoothly fades out at the
+/// borders of its box within [`Self::blend_distance`], the remaining contribution is taken from
+/// the next probe.
+///
+/// # Limitations
+///
+/// Probes affect only objects rendered in the deferred render path. Only four probes could be
+/// used at the same time. Captured cube maps do not contain reflections of other probes.
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct ReflectionProbe {
+    base: Base,
+
+    #[reflect(setter = "set_resolution")]
+    resolution: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_update_mode")]
+    update_mode: InheritableVariable<ReflectionProbeUpdateMode>,
+
+    #[reflect(setter = "set_box_projection")]
+    box_projection: InheritableVariable<bool>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_blend_distance")]
+    blend_distance: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_priority")]
+    priority: InheritableVariable<i32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_z_near")]
+    z_near: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_z_far")]
+    z_far: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    update_requested: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    capture_pending: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    frames_since_capture: u32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    visibility_cache: VisibilityCache,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe()
+    }
+}
+
+impl Deref for ReflectionProbe {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReflectionProbe {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReflectionProbe {
+    fn type_uuid() -> Uuid {
+        uuid!("c6f04a1b-3e9d-4f72-8a15-7b2d90e4c3a6")
+    }
+}
+
+impl ReflectionProbe {
+    /// Sets size (in pixels) of each face of the cube map. The value is clamped to `[16; 2048]`
+    /// range. Changing the resolution triggers a new capture.
+    pub fn set_resolution(&mut self, resolution: u32) -> u32 {
+        self.update_requested = true;
+        self.resolution.set(resolution.clamp(16, 2048))
+    }
+
+    /// Returns size (in pixels) of each face of the cube map.
+    pub fn resolution(&self) -> u32 {
+        *self.resolution
+    }
+
+    /// Sets new update mode.
+    pub fn set_update_mode(
+        &mut self,
+        update_mode: ReflectionProbeUpdateMode,
+    ) -> ReflectionProbeUpdateMode {
+        self.frames_since_capture = 0;
+        self.update_mode.set(update_mode)
+    }
+
+    /// Returns current update mode.
+    pub fn update_mode(&self) -> ReflectionProbeUpdateMode {
+        *self.update_mode
+    }
+
+    /// Enables or disables box projection of reflections.
+    pub fn set_box_projection(&mut self, enabled: bool) -> bool {
+        self.box_projection.set(enabled)
+    }
+
+    /// Returns `true` if box projection is enabled.
+    pub fn is_box_projection_enabled(&self) -> bool {
+        *self.box_projection
+    }
+
+    /// Sets the distance (in world units) from the borders of the box, at which reflections of
+    /// the probe start to fade out.
+    pub fn set_blend_distance(&mut self, distance: f32) -> f32 {
+        self.blend_distance.set(distance.max(0.0))
+    }
+
+    /// Returns current blend distance.
+    pub fn blend_distance(&self) -> f32 {
+        *self.blend_distance
+    }
+
+    /// Sets priority of the probe. Probes with higher priority are applied first and take
+    /// precedence over overlapping probes with lower priority.
+    pub fn set_priority(&mut self, priority: i32) -> i32 {
+        self.priority.set(priority)
+    }
+
+    /// Returns priority of the probe.
+    pub fn priority(&self) -> i32 {
+        *self.priority
+    }
+
+    /// Sets near clipping distance of the capture. Triggers a new capture.
+    pub fn set_z_near(&mut self, z_near: f32) -> f32 {
+        self.update_requested = true;
+        self.z_near.set(z_near.max(0.001))
+    }
+
+    /// Returns near clipping distance of the capture.
+    pub fn z_near(&self) -> f32 {
+        *self.z_near
+    }
+
+    /// Sets far clipping distance of the capture. Triggers a new capture.
+    pub fn set_z_far(&mut self, z_far: f32) -> f32 {
+        self.update_requested = true;
+        self.z_far.set(z_far.max(0.001))
+    }
+
+    /// Returns far clipping distance of the capture.
+    pub fn z_far(&self) -> f32 {
+        *self.z_far
+    }
+
+    /// Requests the probe to capture its surroundings on the next frame. Useful for baked probes,
+    /// when static environment has changed.
+    pub fn request_update(&mut self) {
+        self.update_requested = true;
+    }
+
+    /// Returns `true` if the probe must be captured on the current frame.
+    pub fn is_capture_pending(&self) -> bool {
+        self.capture_pending
+    }
+
+    /// Returns visibility cache of the probe, it is updated only when the probe must be captured.
+    pub fn visibility_cache(&self) -> &VisibilityCache {
+        &self.visibility_cache
+    }
+}
+
+impl NodeTrait for ReflectionProbe {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) -> bool {
+        let live_capture = match *self.update_mode {
+            ReflectionProbeUpdateMode::Baked => false,
+            ReflectionProbeUpdateMode::Live { interval } => {
+                self.frames_since_capture += 1;
+                self.frames_since_capture >= interval.max(1)
+            }
+        };
+
+        self.capture_pending = std::mem::take(&mut self.update_requested) || live_capture;
+
+        if self.capture_pending {
+            // Any capture (including the requested one) restarts the interval of live updates.
+            self.frames_since_capture = 0;
+        }
+
+        if self.capture_pending {
+            // Frustums of the faces of the cube map cover everything around the probe, so there
+            // is no need to do frustum culling.
+            self.visibility_cache.update(
+                context.nodes,
+                self.global_position(),
+                *self.z_near,
+                *self.z_far,
+                None,
+                u32::MAX,
+            );
+        }
+
+        self.base.update_lifetime(context.dt)
+    }
+}
+
+/// Allows you to create a reflection probe in a declarative manner.
+pub struct ReflectionProbeBuilder {
+    base_builder: BaseBuilder,
+    resolution: u32,
+    update_mode: ReflectionProbeUpdateMode,
+    box_projection: bool,
+    blend_distance: f32,
+    priority: i32,
+    z_near: f32,
+    z_far: f32,
+}
+
+impl ReflectionProbeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            resolution: 128,
+            update_mode: Default::default(),
+            box_projection: true,
+            blend_distance: 0.5,
+            priority: 0,
+            z_near: 0.05,
+            z_far: 128.0,
+        }
+    }
+
+    /// Sets desired size (in pixels) of each face of the cube map.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets desired update mode.
+    pub fn with_update_mode(mut self, update_mode: ReflectionProbeUpdateMode) -> Self {
+        self.update_mode = update_mode;
+        self
+    }
+
+    /// Enables or disables box projection.
+    pub fn with_box_projection(mut self, enabled: bool) -> Self {
+        self.box_projection = enabled;
+        self
+    }
+
+    /// Sets desired blend distance.
+    pub fn with_blend_distance(mut self, distance: f32) -> Self {
+        self.blend_distance = distance;
+        self
+    }
+
+    /// Sets desired priority.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets desired near clipping distance of the capture.
+    pub fn with_z_near(mut self, z_near: f32) -> Self {
+        self.z_near = z_near;
+        self
+    }
+
+    /// Sets desired far clipping distance of the capture.
+    pub fn with_z_far(mut self, z_far: f32) -> Self {
+        self.z_far = z_far;
+        self
+    }
+
+    /// Creates new reflection probe.
+    pub fn build_reflection_probe(self) -> ReflectionProbe {
+        ReflectionProbe {
+            base: self.base_builder.build_base(),
+            resolution: self.resolution.clamp(16, 2048).into(),
+            update_mode: self.update_mode.into(),
+            box_projection: self.box_projection.into(),
+            blend_distance: self.blend_distance.max(0.0).into(),
+            priority: self.priority.into(),
+            z_near: self.z_near.max(0.001).into(),
+            z_far: self.z_far.max(0.001).into(),
+            update_requested: true,
+            capture_pending: false,
+            frames_since_capture: 0,
+            visibility_cache: Default::default(),
+        }
+    }
+
+    /// Creates new reflection probe node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reflection_probe())
+    }
+
+    /// Creates new instance of reflection probe node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            light::reflection_probe::{
+                ReflectionProbe, ReflectionProbeBuilder, ReflectionProbeUpdateMode,
+            },
+        },
+    };
+
+    fn update(graph: &mut Graph) {
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+    }
+
+    #[test]
+    fn test_reflection_probe_update_modes() {
+        let mut graph = Graph::new();
+        let baked = ReflectionProbeBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let live = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_update_mode(ReflectionProbeUpdateMode::Live { interval: 2 })
+            .build(&mut graph);
+
+        let pending = |graph: &Graph, handle| {
+            graph[handle]
+                .cast::<ReflectionProbe>()
+                .unwrap()
+                .is_capture_pending()
+        };
+
+        // Every probe is captured on the first frame.
+        update(&mut graph);
+        assert!(pending(&graph, baked));
+        assert!(pending(&graph, live));
+
+        update(&mut graph);
+        assert!(!pending(&graph, baked));
+        assert!(!pending(&graph, live));
+
+        update(&mut graph);
+        assert!(!pending(&graph, baked));
+        assert!(pending(&graph, live));
+
+        graph[baked]
+            .cast_mut::<ReflectionProbe>()
+            .unwrap()
+            .request_update();
+        update(&mut graph);
+        assert!(pending(&graph, baked));
+        assert!(!pending(&graph, live));
+    }
+}
@@ -1,18 +1,74 @@
 //! Async scene loader helper. See [`AsyncSceneLoader`] docs for more info.
 
 use crate::{
-    core::parking_lot::Mutex,
+    core::{parking_lot::Mutex, pool::Handle},
     engine::{resource_manager::ResourceManager, SerializationContext},
-    scene::{Scene, SceneLoader},
+    scene::{Scene, SceneContainer, SceneLoader},
 };
 use std::{path::PathBuf, sync::Arc};
 
+/// A stage of scene loading.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SceneLoadingStage {
+    /// Scene file is being read and deserialized.
+    ReadingFile,
+    /// Resources (models, textures, etc.) used by the scene are being loaded.
+    LoadingResources,
+    /// The scene is being resolved - instances of models are synchronized with their resources.
+    Resolving,
+    /// The scene is fully loaded and ready to be activated.
+    Finished,
+}
+
+impl SceneLoadingStage {
+    /// Returns overall loading progress (in percents) for the stage, when `done` of `total` items
+    /// of the stage are processed.
+    pub fn percent(self, done: usize, total: usize) -> f32 {
+        let (begin, end) = match self {
+            SceneLoadingStage::ReadingFile => (0.0, 10.0),
+            SceneLoadingStage::LoadingResources => (10.0, 90.0),
+            SceneLoadingStage::Resolving => (90.0, 100.0),
+            SceneLoadingStage::Finished => (100.0, 100.0),
+        };
+
+        if total == 0 {
+            begin
+        } else {
+            begin + (end - begin) * (done.min(total) as f32 / total as f32)
+        }
+    }
+}
+
+/// Scene loading progress.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneLoadingProgress {
+    /// Current stage of loading.
+    pub stage: SceneLoadingStage,
+    /// Overall loading progress in `[0; 100]` range.
+    pub percent: f32,
+    /// Path of a resource that is currently loading (if any).
+    pub current_resource: Option<PathBuf>,
+}
+
+impl Default for SceneLoadingProgress {
+    fn default() -> Self {
+        Self {
+            stage: SceneLoadingStage::ReadingFile,
+            percent: 0.0,
+            current_resource: None,
+        }
+    }
+}
+
 struct LoaderState {
     scene: Option<Result<Scene, String>>,
+    progress: SceneLoadingProgress,
 }
 
 /// Asynchronous scene loader is a cross-platform scene loader, including platforms
-/// with no true multi-threading (like WebAssembly). It is easy and straightforward to use:
+/// with no true multi-threading (like WebAssembly). The scene and all its resources are loaded
+/// in background, while the main loop keeps running, so you can show a loading screen with the
+/// current progress. It is easy and straightforward to use:
 ///
 /// ```rust
 /// use std::path::Path;
@@ -39,18 +95,24 @@ struct LoaderState {
 ///     // Step 2. Call this method in your game loop to continuously check loading progress.
 ///     fn check_loading_progress(&mut self, context: &mut PluginContext) {
 ///         if let Some(loader) = self.loader.as_ref() {
-///             if let Some(result) = loader.fetch_result() {
+///             // Activate the scene when it is loaded. It adds the scene to the engine, so it
+///             // will be included in engine processing pipeline.
+///             if let Some(result) = loader.activate(context.scenes) {
 ///                 // Loading could end in either successfully loaded scene or some error.
-///                 match result {
-///                     Ok(scene) => {
-///                         // Add the scene to the engine, so it will be included in engine processing pipeline.
-///                         context.scenes.add(scene);
-///                     }
-///                     Err(err) => Log::err(err),
+///                 if let Err(err) = result {
+///                     Log::err(err);
 ///                 }
 ///
 ///                 // Discard the loader once it is finished its job.
 ///                 self.loader = None;
+///             } else {
+///                 // While the scene is loading, we can show progress bar or even loading screen
+///                 // with useful information.
+///                 let progress = loader.progress();
+///                 Log::info(format!(
+///                     "Loading {:.0}%: {:?}",
+///                     progress.percent, progress.current_resource
+///                 ));
 ///             }
 ///         }
 ///     }
@@ -58,8 +120,7 @@ struct LoaderState {
 ///
 /// impl Plugin for Game {
 ///     fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {
-///         // Check whether the scene is loaded or not. While it is loading, we can show progress bar
-///         // or even loading screen with useful information.
+///         // Check whether the scene is loaded or not.
 ///         self.check_loading_progress(context)
 ///     }
 /// }
@@ -76,13 +137,22 @@ impl AsyncSceneLoader {
         serialization_context: Arc<SerializationContext>,
         resource_manager: ResourceManager,
     ) -> Self {
-        let state = Arc::new(Mutex::new(LoaderState { scene: None }));
+        let state = Arc::new(Mutex::new(LoaderState {
+            scene: None,
+            progress: Default::default(),
+        }));
 
         let inner_state = state.clone();
         let future = async move {
             match SceneLoader::from_file(&path, serialization_context).await {
                 Ok(loader) => {
-                    inner_state.lock().scene = Some(Ok(loader.finish(resource_manager).await));
+                    let progress_state = inner_state.clone();
+                    let scene = loader
+                        .finish_with_progress(resource_manager, move |progress| {
+                            progress_state.lock().progress = progress;
+                        })
+                        .await;
+                    inner_state.lock().scene = Some(Ok(scene));
                 }
                 Err(e) => {
                     inner_state.lock().scene = Some(Err(format!(
@@ -107,8 +177,28 @@ impl AsyncSceneLoader {
         Self { state }
     }
 
+    /// Returns current loading progress.
+    pub fn progress(&self) -> SceneLoadingProgress {
+        self.state.lock().progress.clone()
+    }
+
+    /// Returns `true` if the loading is finished (successfully or not) and the result wasn't
+    /// fetched yet.
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().scene.is_some()
+    }
+
     /// Tries to get scene loading result. See [`AsyncSceneLoader`] docs for usage examples.
     pub fn fetch_result(&self) -> Option<Result<Scene, String>> {
         self.state.lock().scene.take()
     }
+
+    /// Adds loaded scene to the given container at once and returns its handle. Returns `None`
+    /// if the scene is still loading. The scene becomes a part of the engine processing pipeline
+    /// only after this call, so the previous scene keeps running while the new one is loading.
+    /// See [`AsyncSceneLoader`] docs for usage examples.
+    pub fn activate(&self, scenes: &mut SceneContainer) -> Option<Result<Handle<Scene>, String>> {
+        self.fetch_result()
+            .map(|result| result.map(|scene| scenes.add(scene)))
+    }
 }
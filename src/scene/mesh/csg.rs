@@ -0,0 +1,617 @@
+//! Constructive Solid Geometry (CSG) - boolean operations (union, subtraction, intersection)
+//! between closed meshes. Could be used for destructible geometry, holes in walls, level-editor
+//! style brushes, etc.
+//!
+//! The implementation is based on BSP trees, see [csg.js](https://github.com/evanw/csg.js) for
+//! the description of the algorithm.
+//!
+//! # Example
+//!
+//! ```rust
+//! use fyrox::{
+//!     core::algebra::{Matrix4, Vector3},
+//!     scene::mesh::{csg::CsgMesh, surface::SurfaceData},
+//! };
+//!
+//! fn make_hole() -> SurfaceData {
+//!     let wall = CsgMesh::from_surface_data(
+//!         &SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(4.0, 3.0, 0.2))),
+//!         &Matrix4::identity(),
+//!     )
+//!     .unwrap();
+//!     let hole = CsgMesh::from_surface_data(
+//!         &SurfaceData::make_cube(Matrix4::new_scaling(0.5)),
+//!         &Matrix4::identity(),
+//!     )
+//!     .unwrap();
+//!
+//!     wall.subtract(&hole).to_surface_data(&Matrix4::identity())
+//! }
+//! ```
+//!
+//! # Limitations
+//!
+//! Operands must be closed (watertight) meshes, otherwise the result is undefined. Boolean
+//! operations are relatively slow, they're not intended to be done every frame.
+
+use crate::{
+    core::algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+    core::math::TriangleDefinition,
+    scene::mesh::{
+        buffer::{
+            TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError, VertexReadTrait,
+        },
+        surface::SurfaceData,
+        vertex::StaticVertex,
+        Mesh,
+    },
+};
+use fxhash::FxHashMap;
+
+/// Tolerance used to classify points relative to planes.
+const EPSILON: f64 = 1.0e-5;
+
+/// Precision of vertex welding.
+const WELD_PRECISION: f32 = 1.0e4;
+
+#[derive(Copy, Clone, Debug)]
+struct Vertex {
+    position: Vector3<f64>,
+    normal: Vector3<f32>,
+    tex_coord: Vector2<f32>,
+}
+
+impl Vertex {
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+    }
+
+    fn interpolate(&self, other: &Vertex, t: f64) -> Vertex {
+        Vertex {
+            position: self.position.lerp(&other.position, t),
+            normal: self.normal.lerp(&other.normal, t as f32),
+            tex_coord: self.tex_coord.lerp(&other.tex_coord, t as f32),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Plane {
+    normal: Vector3<f64>,
+    w: f64,
+}
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+impl Plane {
+    fn from_points(a: Vector3<f64>, b: Vector3<f64>, c: Vector3<f64>) -> Option<Self> {
+        let normal = (b - a).cross(&(c - a)).try_normalize(f64::EPSILON)?;
+        Some(Self {
+            normal,
+            w: normal.dot(&a),
+        })
+    }
+
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    fn classify(&self, point: &Vector3<f64>) -> u8 {
+        let t = self.normal.dot(point) - self.w;
+        if t < -EPSILON {
+            BACK
+        } else if t > EPSILON {
+            FRONT
+        } else {
+            COPLANAR
+        }
+    }
+
+    /// Splits the polygon by the plane if needed, then puts the polygon or polygon fragments in
+    /// the appropriate lists. Coplanar polygons go into either `coplanar_front` or `coplanar_back`
+    /// depending on their orientation with respect to this plane.
+    fn split_polygon(
+        &self,
+        polygon: Polygon,
+        coplanar_front: &mut Vec<Polygon>,
+        coplanar_back: &mut Vec<Polygon>,
+        front: &mut Vec<Polygon>,
+        back: &mut Vec<Polygon>,
+    ) {
+        let types = polygon
+            .vertices
+            .iter()
+            .map(|v| self.classify(&v.position))
+            .collect::<Vec<_>>();
+        let polygon_type = types.iter().fold(COPLANAR, |acc, t| acc | t);
+
+        match polygon_type {
+            COPLANAR => {
+                if self.normal.dot(&polygon.plane.normal) > 0.0 {
+                    coplanar_front.push(polygon)
+                } else {
+                    coplanar_back.push(polygon)
+                }
+            }
+            FRONT => front.push(polygon),
+            BACK => back.push(polygon),
+            _ => {
+                let count = polygon.vertices.len();
+                let mut f = Vec::with_capacity(count + 1);
+                let mut b = Vec::with_capacity(count + 1);
+                for i in 0..count {
+                    let j = (i + 1) % count;
+                    let (ti, tj) = (types[i], types[j]);
+                    let (vi, vj) = (&polygon.vertices[i], &polygon.vertices[j]);
+                    if ti != BACK {
+                        f.push(*vi);
+                    }
+                    if ti != FRONT {
+                        b.push(*vi);
+                    }
+                    if (ti | tj) == SPANNING {
+                        let t = (self.w - self.normal.dot(&vi.position))
+                            / self.normal.dot(&(vj.position - vi.position));
+                        let v = vi.interpolate(vj, t);
+                        f.push(v);
+                        b.push(v);
+                    }
+                }
+                if f.len() >= 3 {
+                    front.push(Polygon {
+                        vertices: f,
+                        plane: polygon.plane,
+                    });
+                }
+                if b.len() >= 3 {
+                    back.push(Polygon {
+                        vertices: b,
+                        plane: polygon.plane,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Polygon {
+    vertices: Vec<Vertex>,
+    plane: Plane,
+}
+
+impl Polygon {
+    fn new(vertices: Vec<Vertex>) -> Option<Self> {
+        let plane = Plane::from_points(
+            vertices[0].position,
+            vertices[1].position,
+            vertices[2].position,
+        )?;
+        Some(Self { vertices, plane })
+    }
+
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        for vertex in self.vertices.iter_mut() {
+            vertex.flip();
+        }
+        self.plane.flip();
+    }
+}
+
+/// A node of a BSP tree.
+#[derive(Default, Clone, Debug)]
+struct Node {
+    plane: Option<Plane>,
+    front: Option<Box<Node>>,
+    back: Option<Box<Node>>,
+    polygons: Vec<Polygon>,
+}
+
+impl Node {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = Node::default();
+        node.build(polygons);
+        node
+    }
+
+    /// Converts solid space to empty space and empty space to solid space.
+    fn invert(&mut self) {
+        for polygon in self.polygons.iter_mut() {
+            polygon.flip();
+        }
+        if let Some(plane) = self.plane.as_mut() {
+            plane.flip();
+        }
+        if let Some(front) = self.front.as_mut() {
+            front.invert();
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Removes all polygons in the list that are inside this BSP tree.
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let plane = match self.plane {
+            Some(plane) => plane,
+            None => return polygons,
+        };
+
+        let mut coplanar_front = Vec::new();
+        let mut coplanar_back = Vec::new();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for polygon in polygons {
+            plane.split_polygon(
+                polygon,
+                &mut coplanar_front,
+                &mut coplanar_back,
+                &mut front,
+                &mut back,
+            );
+        }
+        front.append(&mut coplanar_front);
+        back.append(&mut coplanar_back);
+
+        let mut front = match self.front.as_ref() {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        let back = match self.back.as_ref() {
+            Some(node) => node.clip_polygons(back),
+            None => Vec::new(),
+        };
+        front.extend(back);
+        front
+    }
+
+    /// Removes all polygons in this BSP tree that are inside the other BSP tree.
+    fn clip_to(&mut self, bsp: &Node) {
+        self.polygons = bsp.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = self.front.as_mut() {
+            front.clip_to(bsp);
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.clip_to(bsp);
+        }
+    }
+
+    fn all_polygons(&self, polygons: &mut Vec<Polygon>) {
+        polygons.extend(self.polygons.iter().cloned());
+        if let Some(front) = self.front.as_ref() {
+            front.all_polygons(polygons);
+        }
+        if let Some(back) = self.back.as_ref() {
+            back.all_polygons(polygons);
+        }
+    }
+
+    fn into_polygons(self) -> Vec<Polygon> {
+        let mut polygons = Vec::new();
+        self.all_polygons(&mut polygons);
+        polygons
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+
+        let plane = *self.plane.get_or_insert(polygons[0].plane);
+
+        let mut coplanar_front = Vec::new();
+        let mut coplanar_back = Vec::new();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for polygon in polygons {
+            plane.split_polygon(
+                polygon,
+                &mut coplanar_front,
+                &mut coplanar_back,
+                &mut front,
+                &mut back,
+            );
+        }
+        self.polygons.append(&mut coplanar_front);
+        self.polygons.append(&mut coplanar_back);
+
+        if !front.is_empty() {
+            self.front.get_or_insert_with(Default::default).build(front);
+        }
+        if !back.is_empty() {
+            self.back.get_or_insert_with(Default::default).build(back);
+        }
+    }
+}
+
+/// A solid that could be used in boolean operations. See module docs for more info.
+#[derive(Default, Clone, Debug)]
+pub struct CsgMesh {
+    polygons: Vec<Polygon>,
+}
+
+impl CsgMesh {
+    /// Creates a new solid from surface data. `transform` is applied to every vertex, it is
+    /// usually a global transform of the mesh which owns the surface, so every solid is in the
+    /// same (world) space.
+    pub fn from_surface_data(
+        data: &SurfaceData,
+        transform: &Matrix4<f32>,
+    ) -> Result<Self, VertexFetchError> {
+        let normal_matrix = transform.try_inverse().unwrap_or_default().transpose();
+
+        let vertex_buffer = &data.vertex_buffer;
+        let has_normals = vertex_buffer.has_attribute(VertexAttributeUsage::Normal);
+        let has_tex_coords = vertex_buffer.has_attribute(VertexAttributeUsage::TexCoord0);
+
+        let mut vertices = Vec::with_capacity(vertex_buffer.vertex_count() as usize);
+        for view in vertex_buffer.iter() {
+            let position = transform
+                .transform_point(&Point3::from(
+                    view.read_3_f32(VertexAttributeUsage::Position)?,
+                ))
+                .coords;
+            let normal = if has_normals {
+                normal_matrix
+                    .transform_vector(&view.read_3_f32(VertexAttributeUsage::Normal)?)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default()
+            } else {
+                Default::default()
+            };
+            let tex_coord = if has_tex_coords {
+                view.read_2_f32(VertexAttributeUsage::TexCoord0)?
+            } else {
+                Default::default()
+            };
+            vertices.push(Vertex {
+                position: position.cast::<f64>(),
+                normal,
+                tex_coord,
+            });
+        }
+
+        let polygons = data
+            .geometry_buffer
+            .iter()
+            .filter_map(|triangle| {
+                let mut polygon = Polygon::new(vec![
+                    vertices[triangle[0] as usize],
+                    vertices[triangle[1] as usize],
+                    vertices[triangle[2] as usize],
+                ])?;
+                if !has_normals {
+                    let normal = polygon.plane.normal.cast::<f32>();
+                    for vertex in polygon.vertices.iter_mut() {
+                        vertex.normal = normal;
+                    }
+                }
+                Some(polygon)
+            })
+            .collect();
+
+        Ok(Self { polygons })
+    }
+
+    /// Creates a new solid from every surface of the mesh, vertices are transformed to world
+    /// space.
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self, VertexFetchError> {
+        let transform = mesh.global_transform();
+        let mut polygons = Vec::new();
+        for surface in mesh.surfaces() {
+            polygons.extend(Self::from_surface_data(&surface.data().lock(), &transform)?.polygons);
+        }
+        Ok(Self { polygons })
+    }
+
+    /// Returns amount of polygons in the solid.
+    pub fn polygon_count(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Returns `true` if the solid is empty.
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    /// Returns a solid that occupies space of both solids.
+    pub fn union(&self, other: &CsgMesh) -> CsgMesh {
+        let mut a = Node::new(self.polygons.clone());
+        let mut b = Node::new(other.polygons.clone());
+        a.clip_to(&b);
+        b.clip_to(&a);
+        b.invert();
+        b.clip_to(&a);
+        b.invert();
+        a.build(b.into_polygons());
+        CsgMesh {
+            polygons: a.into_polygons(),
+        }
+    }
+
+    /// Returns a solid that occupies space of this solid, but not the space of the other.
+    pub fn subtract(&self, other: &CsgMesh) -> CsgMesh {
+        let mut a = Node::new(self.polygons.clone());
+        let mut b = Node::new(other.polygons.clone());
+        a.invert();
+        a.clip_to(&b);
+        b.clip_to(&a);
+        b.invert();
+        b.clip_to(&a);
+        b.invert();
+        a.build(b.into_polygons());
+        a.invert();
+        CsgMesh {
+            polygons: a.into_polygons(),
+        }
+    }
+
+    /// Returns a solid that occupies space that is shared by both solids.
+    pub fn intersect(&self, other: &CsgMesh) -> CsgMesh {
+        let mut a = Node::new(self.polygons.clone());
+        let mut b = Node::new(other.polygons.clone());
+        a.invert();
+        b.clip_to(&a);
+        b.invert();
+        a.clip_to(&b);
+        b.clip_to(&a);
+        a.build(b.into_polygons());
+        a.invert();
+        CsgMesh {
+            polygons: a.into_polygons(),
+        }
+    }
+
+    /// Converts the solid to surface data. `transform` is applied to every vertex, it is usually
+    /// an inverse global transform of a mesh that will own the surface. Vertices with the same
+    /// position, texture coordinates and source normal are welded and normals are regenerated,
+    /// so hard edges of the operands are preserved and new faces get correct shading.
+    pub fn to_surface_data(&self, transform: &Matrix4<f32>) -> SurfaceData {
+        let mut vertices = Vec::<StaticVertex>::new();
+        let mut triangles = Vec::new();
+        let mut welded = FxHashMap::default();
+
+        let quantize = |v: f32| (v * WELD_PRECISION).round() as i32;
+
+        for polygon in self.polygons.iter() {
+            let face_normal = polygon.plane.normal.cast::<f32>();
+
+            let indices = polygon
+                .vertices
+                .iter()
+                .map(|vertex| {
+                    let position = vertex.position.cast::<f32>();
+                    let key = [
+                        quantize(position.x),
+                        quantize(position.y),
+                        quantize(position.z),
+                        quantize(vertex.tex_coord.x),
+                        quantize(vertex.tex_coord.y),
+                        // Source normals are coarsely quantized, so smooth surfaces are welded.
+                        (vertex.normal.x * 100.0).round() as i32,
+                        (vertex.normal.y * 100.0).round() as i32,
+                        (vertex.normal.z * 100.0).round() as i32,
+                    ];
+                    *welded.entry(key).or_insert_with(|| {
+                        vertices.push(StaticVertex {
+                            position,
+                            tex_coord: vertex.tex_coord,
+                            normal: Vector3::default(),
+                            tangent: Vector4::default(),
+                        });
+                        vertices.len() as u32 - 1
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            // Triangulate as fan, polygons are always convex.
+            for i in 1..indices.len().saturating_sub(1) {
+                let triangle = [indices[0], indices[i], indices[i + 1]];
+                if triangle[0] == triangle[1]
+                    || triangle[1] == triangle[2]
+                    || triangle[0] == triangle[2]
+                {
+                    continue;
+                }
+                triangles.push(TriangleDefinition(triangle));
+            }
+
+            // Accumulate face normals, weighted by polygon size.
+            let weight = polygon_area(polygon);
+            for &index in indices.iter() {
+                vertices[index as usize].normal += face_normal.scale(weight);
+            }
+        }
+
+        for vertex in vertices.iter_mut() {
+            vertex.normal = vertex
+                .normal
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| Vector3::new(0.0, 1.0, 0.0));
+        }
+
+        let mut data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), StaticVertex::layout(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+            true,
+        );
+        data.calculate_tangents().unwrap();
+        data.transform_geometry(transform).unwrap();
+        data
+    }
+}
+
+fn polygon_area(polygon: &Polygon) -> f32 {
+    let first = polygon.vertices[0].position;
+    let mut area = 0.0;
+    for pair in polygon.vertices[1..].windows(2) {
+        area += (pair[0].position - first)
+            .cross(&(pair[1].position - first))
+            .norm()
+            * 0.5;
+    }
+    area as f32
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        scene::mesh::{csg::CsgMesh, surface::SurfaceData},
+    };
+
+    fn cube(offset: Vector3<f32>) -> CsgMesh {
+        CsgMesh::from_surface_data(
+            &SurfaceData::make_cube(Matrix4::new_translation(&offset)),
+            &Matrix4::identity(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_disjoint_operations() {
+        let a = cube(Vector3::default());
+        let b = cube(Vector3::new(2.0, 0.0, 0.0));
+
+        assert_eq!(a.polygon_count(), 12);
+        assert_eq!(a.union(&b).polygon_count(), 24);
+        assert_eq!(a.subtract(&b).polygon_count(), 12);
+        assert!(a.intersect(&b).is_empty());
+    }
+
+    #[test]
+    fn test_welding() {
+        let data = cube(Vector3::default()).to_surface_data(&Matrix4::identity());
+        // Each face of the cube has its own 4 vertices because of hard edges.
+        assert_eq!(data.vertex_buffer.vertex_count(), 24);
+        assert_eq!(data.geometry_buffer.len(), 12);
+    }
+
+    #[test]
+    fn test_subtract_makes_hole() {
+        let wall = CsgMesh::from_surface_data(
+            &SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                4.0, 4.0, 0.5,
+            ))),
+            &Matrix4::identity(),
+        )
+        .unwrap();
+        let hole = CsgMesh::from_surface_data(
+            &SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                1.0, 1.0, 2.0,
+            ))),
+            &Matrix4::identity(),
+        )
+        .unwrap();
+
+        let result = wall.subtract(&hole);
+        assert!(result.polygon_count() > wall.polygon_count());
+        assert!(wall.intersect(&hole).polygon_count() > 0);
+    }
+}
@@ -24,6 +24,7 @@ use crate::{
         graph::Graph,
         mesh::{
             buffer::{VertexAttributeUsage, VertexReadTrait},
+            socket::MeshSocket,
             surface::Surface,
         },
         node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
@@ -36,7 +37,11 @@ use std::{
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 pub mod buffer;
+pub mod csg;
+pub mod procedural;
+pub mod socket;
 pub mod surface;
+pub mod sweep;
 pub mod vertex;
 
 /// Defines a path that should be used to render a mesh.
@@ -83,6 +88,27 @@ impl RenderPath {
     }
 }
 
+/// Blend shape (morph target) weight of a mesh. Weights are usually driven by animations, see
+/// [`crate::animation::value::ValueBinding::BlendShape`].
+#[derive(Default, Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct BlendShape {
+    /// Name of the blend shape.
+    pub name: String,
+    /// Weight of the blend shape, usually in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub weight: f32,
+}
+
+impl BlendShape {
+    /// Creates new blend shape with the given name and weight.
+    pub fn new<S: AsRef<str>>(name: S, weight: f32) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            weight,
+        }
+    }
+}
+
 /// See module docs.
 #[derive(Debug, Reflect, Clone, Visit)]
 pub struct Mesh {
@@ -98,6 +124,14 @@ pub struct Mesh {
     #[reflect(setter = "set_decal_layer_index")]
     decal_layer_index: InheritableVariable<u8>,
 
+    #[reflect(setter = "set_blend_shapes")]
+    #[visit(optional)] // Backward compatibility
+    blend_shapes: InheritableVariable<Vec<BlendShape>>,
+
+    #[reflect(setter = "set_sockets")]
+    #[visit(optional)] // Backward compatibility
+    sockets: InheritableVariable<Vec<MeshSocket>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     local_bounding_box: Cell<AxisAlignedBoundingBox>,
@@ -121,6 +155,8 @@ impl Default for Mesh {
             local_bounding_box_dirty: Cell::new(true),
             render_path: InheritableVariable::new(RenderPath::Deferred),
             decal_layer_index: InheritableVariable::new(0),
+            blend_shapes: Default::default(),
+            sockets: Default::default(),
         }
     }
 }
@@ -257,6 +293,114 @@ impl Mesh {
     pub fn decal_layer_index(&self) -> u8 {
         *self.decal_layer_index
     }
+
+    /// Sets new set of blend shapes of the mesh.
+    pub fn set_blend_shapes(&mut self, blend_shapes: Vec<BlendShape>) -> Vec<BlendShape> {
+        self.blend_shapes.set(blend_shapes)
+    }
+
+    /// Returns a slice with blend shapes of the mesh.
+    pub fn blend_shapes(&self) -> &[BlendShape] {
+        &self.blend_shapes
+    }
+
+    /// Returns weight of a blend shape with the given name (if any).
+    pub fn blend_shape_weight(&self, name: &str) -> Option<f32> {
+        self.blend_shapes
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.weight)
+    }
+
+    /// Sets weight of a blend shape with the given name. Returns `false` if there is no such
+    /// blend shape.
+    pub fn set_blend_shape_weight(&mut self, name: &str, weight: f32) -> bool {
+        if let Some(blend_shape) = self
+            .blend_shapes
+            .get_mut()
+            .iter_mut()
+            .find(|s| s.name == name)
+        {
+            blend_shape.weight = weight;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets new set of attachment sockets of the mesh.
+    pub fn set_sockets(&mut self, sockets: Vec<MeshSocket>) -> Vec<MeshSocket> {
+        self.sockets.set(sockets)
+    }
+
+    /// Returns a slice with attachment sockets of the mesh.
+    pub fn sockets(&self) -> &[MeshSocket] {
+        &self.sockets
+    }
+
+    /// Adds new attachment socket to the mesh. Use [`Graph::attach_to_socket`] to attach nodes
+    /// to the socket.
+    pub fn add_socket(&mut self, socket: MeshSocket) {
+        self.sockets.get_mut().push(socket);
+    }
+
+    /// Removes a socket with the given name and returns it (if any). Nodes attached to the
+    /// socket will stay linked to the bone of the socket.
+    pub fn remove_socket(&mut self, name: &str) -> Option<MeshSocket> {
+        let index = self.sockets.iter().position(|s| s.name == name)?;
+        Some(self.sockets.get_mut().remove(index))
+    }
+
+    /// Returns a reference to a socket with the given name (if any).
+    pub fn socket(&self, name: &str) -> Option<&MeshSocket> {
+        self.sockets.iter().find(|s| s.name == name)
+    }
+
+    /// Returns a mutable reference to a socket with the given name (if any).
+    pub fn socket_mut(&mut self, name: &str) -> Option<&mut MeshSocket> {
+        self.sockets.get_mut().iter_mut().find(|s| s.name == name)
+    }
+
+    /// Returns world-space transform of a socket with the given name. Returns `None` if there
+    /// is no such socket or its bone does not exist.
+    pub fn socket_global_transform(&self, name: &str, graph: &Graph) -> Option<Matrix4<f32>> {
+        self.socket(name)?.global_transform(graph)
+    }
+
+    fn sync_socket_attachments(&mut self, context: &mut UpdateContext) {
+        let mut has_dangling_attachments = false;
+
+        for socket in self.sockets.iter() {
+            for &attachment in socket.attachments.iter() {
+                match context.nodes.try_borrow_mut(attachment) {
+                    // Attached node could be re-linked by the user, in this case it should not
+                    // follow the socket anymore.
+                    Some(node) if node.parent() == socket.bone => {
+                        let transform = node.local_transform_mut();
+                        if **transform.position() != socket.position {
+                            transform.set_position(socket.position);
+                        }
+                        if **transform.rotation() != socket.rotation {
+                            transform.set_rotation(socket.rotation);
+                        }
+                    }
+                    _ => has_dangling_attachments = true,
+                }
+            }
+        }
+
+        if has_dangling_attachments {
+            let nodes = &context.nodes;
+            for socket in self.sockets.get_mut_silent().iter_mut() {
+                let bone = socket.bone;
+                socket.attachments.retain(|&attachment| {
+                    nodes
+                        .try_borrow(attachment)
+                        .map_or(false, |node| node.parent() == bone)
+                });
+            }
+        }
+    }
 }
 
 impl NodeTrait for Mesh {
@@ -322,6 +466,8 @@ impl NodeTrait for Mesh {
             );
         }
 
+        self.sync_socket_attachments(context);
+
         self.base.update_lifetime(context.dt)
     }
 }
@@ -332,6 +478,8 @@ pub struct MeshBuilder {
     surfaces: Vec<Surface>,
     render_path: RenderPath,
     decal_layer_index: u8,
+    blend_shapes: Vec<BlendShape>,
+    sockets: Vec<MeshSocket>,
 }
 
 impl MeshBuilder {
@@ -342,6 +490,8 @@ impl MeshBuilder {
             surfaces: Default::default(),
             render_path: RenderPath::Deferred,
             decal_layer_index: 0,
+            blend_shapes: Default::default(),
+            sockets: Default::default(),
         }
     }
 
@@ -364,6 +514,18 @@ impl MeshBuilder {
         self
     }
 
+    /// Sets desired blend shapes.
+    pub fn with_blend_shapes(mut self, blend_shapes: Vec<BlendShape>) -> Self {
+        self.blend_shapes = blend_shapes;
+        self
+    }
+
+    /// Sets desired attachment sockets.
+    pub fn with_sockets(mut self, sockets: Vec<MeshSocket>) -> Self {
+        self.sockets = sockets;
+        self
+    }
+
     /// Creates new mesh.
     pub fn build_node(self) -> Node {
         Node::new(Mesh {
@@ -373,6 +535,8 @@ impl MeshBuilder {
             local_bounding_box_dirty: Cell::new(true),
             render_path: self.render_path.into(),
             decal_layer_index: self.decal_layer_index.into(),
+            blend_shapes: self.blend_shapes.into(),
+            sockets: self.sockets.into(),
             world_bounding_box: Default::default(),
         })
     }
@@ -0,0 +1,537 @@
+//! Procedural mesh builder. It is intended for runtime generated geometry (voxels, marching
+//! cubes, generated terrains, etc.).
+//!
+//! See [`ProceduralMesh`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    scene::{
+        collider::{Collider, ColliderShape, GeometrySource},
+        graph::Graph,
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexFetchError,
+            },
+            surface::{Surface, SurfaceData, SurfaceSharedData},
+            vertex::StaticVertex,
+            Mesh,
+        },
+        node::Node,
+    },
+    utils::uvgen,
+};
+use std::fmt::{Display, Formatter};
+
+/// An error that may occur during procedural mesh building.
+#[derive(Debug)]
+pub enum ProceduralMeshError {
+    /// Amount of indices is not a multiple of 3.
+    InvalidIndexCount(usize),
+    /// An index points to non-existent vertex.
+    IndexOutOfBounds {
+        /// Invalid index.
+        index: u32,
+        /// Actual amount of vertices.
+        vertex_count: usize,
+    },
+    /// Amount of texture coordinates does not match amount of positions.
+    TexCoordCountMismatch {
+        /// Amount of positions.
+        positions: usize,
+        /// Amount of texture coordinates.
+        tex_coords: usize,
+    },
+    /// A handle does not point to a mesh node.
+    NotAMesh(Handle<Node>),
+    /// A mesh does not have a surface with given index.
+    NoSuchSurface(usize),
+    /// Profiles of a loft (see [`super::sweep::loft`]) have different amount of points.
+    ProfileMismatch {
+        /// Amount of points in the profile of the first section.
+        expected: usize,
+        /// Amount of points in the mismatched profile.
+        actual: usize,
+    },
+    /// Unable to read or write vertex data.
+    VertexFetch(VertexFetchError),
+}
+
+impl Display for ProceduralMeshError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProceduralMeshError::InvalidIndexCount(count) => {
+                write!(f, "Amount of indices {} is not a multiple of 3!", count)
+            }
+            ProceduralMeshError::IndexOutOfBounds {
+                index,
+                vertex_count,
+            } => {
+                write!(
+                    f,
+                    "Index {} is out of bounds, there are only {} vertices!",
+                    index, vertex_count
+                )
+            }
+            ProceduralMeshError::TexCoordCountMismatch {
+                positions,
+                tex_coords,
+            } => {
+                write!(
+                    f,
+                    "Amount of texture coordinates {} does not match amount of positions {}!",
+                    tex_coords, positions
+                )
+            }
+            ProceduralMeshError::NotAMesh(handle) => {
+                write!(f, "Node {} is not a mesh!", handle)
+            }
+            ProceduralMeshError::NoSuchSurface(index) => {
+                write!(f, "There is no surface with index {}!", index)
+            }
+            ProceduralMeshError::ProfileMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Profile has {} points, but {} points were expected!",
+                    actual, expected
+                )
+            }
+            ProceduralMeshError::VertexFetch(err) => {
+                write!(f, "Vertex fetch error: {}", err)
+            }
+        }
+    }
+}
+
+impl From<VertexFetchError> for ProceduralMeshError {
+    fn from(err: VertexFetchError) -> Self {
+        Self::VertexFetch(err)
+    }
+}
+
+/// Defines how normals of a procedural mesh will be generated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NormalsMode {
+    /// Each vertex gets an average normal of the triangles that share the vertex. Gives smooth
+    /// look, suitable for terrains, marching cubes, etc.
+    Smooth,
+    /// Each triangle gets its own vertices with the normal of the triangle. Gives faceted look,
+    /// suitable for voxels and low-poly style. Increases amount of vertices.
+    Flat,
+}
+
+impl Default for NormalsMode {
+    fn default() -> Self {
+        Self::Smooth
+    }
+}
+
+/// Procedural mesh builder. Push positions and indices (and optionally texture coordinates),
+/// everything else (normals, tangents and lightmap texture coordinates) will be generated.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{graph::Graph, mesh::procedural::ProceduralMesh, node::Node},
+/// };
+///
+/// fn update_terrain_chunk(graph: &mut Graph, mesh: Handle<Node>) {
+///     let mut builder = ProceduralMesh::new();
+///     let a = builder.push_vertex(Vector3::new(0.0, 0.0, 0.0));
+///     let b = builder.push_vertex(Vector3::new(0.0, 0.0, 1.0));
+///     let c = builder.push_vertex(Vector3::new(1.0, 0.0, 0.0));
+///     builder.push_triangle(a, b, c);
+///
+///     // Replaces contents of the first surface of the mesh (or adds a new surface if the mesh
+///     // is empty).
+///     builder.apply(graph, mesh, 0).unwrap();
+/// }
+/// ```
+///
+/// # Texture coordinates
+///
+/// If texture coordinates are not specified, they will be generated using box projection with
+/// one texture repeat per one unit of space.
+#[derive(Clone, Debug, Default)]
+pub struct ProceduralMesh {
+    positions: Vec<Vector3<f32>>,
+    tex_coords: Vec<Vector2<f32>>,
+    indices: Vec<u32>,
+    normals_mode: NormalsMode,
+    skip_tangents: bool,
+    lightmap_uv_spacing: Option<f32>,
+}
+
+impl ProceduralMesh {
+    /// Creates new empty procedural mesh builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates new empty procedural mesh builder with preallocated memory for the given amount of
+    /// vertices and indices.
+    pub fn with_capacity(vertices: usize, indices: usize) -> Self {
+        Self {
+            positions: Vec::with_capacity(vertices),
+            indices: Vec::with_capacity(indices),
+            ..Default::default()
+        }
+    }
+
+    /// Sets desired normals generation mode.
+    pub fn with_normals_mode(mut self, mode: NormalsMode) -> Self {
+        self.normals_mode = mode;
+        self
+    }
+
+    /// Enables or disables tangents generation. Tangents are required for normal mapping, so
+    /// disable them only if your material does not use normal maps.
+    pub fn with_tangents(mut self, enabled: bool) -> Self {
+        self.skip_tangents = !enabled;
+        self
+    }
+
+    /// Enables generation of secondary texture coordinates that are used for lightmaps. `spacing`
+    /// defines a gap between UV islands (in [0; 1] range), 0.005 is a good default.
+    ///
+    /// # Performance
+    ///
+    /// The generator is slow, avoid using it for geometry that changes every frame.
+    pub fn with_lightmap_uvs(mut self, spacing: f32) -> Self {
+        self.lightmap_uv_spacing = Some(spacing);
+        self
+    }
+
+    /// Adds a new vertex and returns its index.
+    pub fn push_vertex(&mut self, position: Vector3<f32>) -> u32 {
+        self.positions.push(position);
+        self.positions.len() as u32 - 1
+    }
+
+    /// Adds a new vertex with texture coordinates and returns its index. Either all vertices or
+    /// none of them must have texture coordinates.
+    pub fn push_vertex_with_uv(&mut self, position: Vector3<f32>, tex_coord: Vector2<f32>) -> u32 {
+        self.tex_coords.push(tex_coord);
+        self.push_vertex(position)
+    }
+
+    /// Adds a new triangle, indices must be in counter-clockwise order.
+    pub fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    /// Adds a quad as two triangles, indices must be in counter-clockwise order.
+    pub fn push_quad(&mut self, a: u32, b: u32, c: u32, d: u32) {
+        self.push_triangle(a, b, c);
+        self.push_triangle(a, c, d);
+    }
+
+    /// Adds a set of indices, amount of indices must be a multiple of 3 at the time of building.
+    pub fn extend_indices(&mut self, indices: &[u32]) {
+        self.indices.extend_from_slice(indices);
+    }
+
+    /// Returns amount of vertices in the builder.
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns amount of triangles in the builder.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Returns `true` if there is no geometry in the builder.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Removes all geometry from the builder, keeps allocated memory and settings, so the builder
+    /// could be reused for the next portion of geometry.
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.tex_coords.clear();
+        self.indices.clear();
+    }
+
+    fn validate(&self) -> Result<(), ProceduralMeshError> {
+        if self.indices.len() % 3 != 0 {
+            return Err(ProceduralMeshError::InvalidIndexCount(self.indices.len()));
+        }
+
+        if !self.tex_coords.is_empty() && self.tex_coords.len() != self.positions.len() {
+            return Err(ProceduralMeshError::TexCoordCountMismatch {
+                positions: self.positions.len(),
+                tex_coords: self.tex_coords.len(),
+            });
+        }
+
+        if let Some(&index) = self
+            .indices
+            .iter()
+            .find(|&&i| i as usize >= self.positions.len())
+        {
+            return Err(ProceduralMeshError::IndexOutOfBounds {
+                index,
+                vertex_count: self.positions.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn make_vertices(&self) -> (Vec<StaticVertex>, Vec<TriangleDefinition>) {
+        let tex_coord = |i: usize| self.tex_coords.get(i).cloned();
+
+        match self.normals_mode {
+            NormalsMode::Smooth => {
+                let mut normals = vec![Vector3::default(); self.positions.len()];
+                for triangle in self.indices.chunks_exact(3) {
+                    // Not normalized, so larger triangles have larger weight.
+                    let normal = face_normal(
+                        self.positions[triangle[0] as usize],
+                        self.positions[triangle[1] as usize],
+                        self.positions[triangle[2] as usize],
+                    );
+                    for &i in triangle {
+                        normals[i as usize] += normal;
+                    }
+                }
+
+                let vertices = self
+                    .positions
+                    .iter()
+                    .zip(normals)
+                    .enumerate()
+                    .map(|(i, (&position, normal))| {
+                        make_vertex(position, normalize_or_up(normal), tex_coord(i))
+                    })
+                    .collect();
+
+                let triangles = self
+                    .indices
+                    .chunks_exact(3)
+                    .map(|t| TriangleDefinition([t[0], t[1], t[2]]))
+                    .collect();
+
+                (vertices, triangles)
+            }
+            NormalsMode::Flat => {
+                let mut vertices = Vec::with_capacity(self.indices.len());
+                let mut triangles = Vec::with_capacity(self.indices.len() / 3);
+                for triangle in self.indices.chunks_exact(3) {
+                    let normal = normalize_or_up(face_normal(
+                        self.positions[triangle[0] as usize],
+                        self.positions[triangle[1] as usize],
+                        self.positions[triangle[2] as usize],
+                    ));
+
+                    let first = vertices.len() as u32;
+                    for &i in triangle {
+                        vertices.push(make_vertex(
+                            self.positions[i as usize],
+                            normal,
+                            tex_coord(i as usize),
+                        ));
+                    }
+                    triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+                }
+
+                (vertices, triangles)
+            }
+        }
+    }
+
+    /// Builds surface data from the geometry in the builder.
+    pub fn build_surface_data(&self) -> Result<SurfaceData, ProceduralMeshError> {
+        self.validate()?;
+
+        let (vertices, triangles) = self.make_vertices();
+
+        let mut data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), StaticVertex::layout(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+            true,
+        );
+
+        if !self.skip_tangents {
+            data.calculate_tangents()?;
+        }
+
+        if let Some(spacing) = self.lightmap_uv_spacing {
+            data.vertex_buffer
+                .modify()
+                .add_attribute(
+                    VertexAttributeDescriptor {
+                        usage: VertexAttributeUsage::TexCoord1,
+                        data_type: VertexAttributeDataType::F32,
+                        size: 2,
+                        divisor: 0,
+                        shader_location: 6, // HACK: GBuffer renderer expects it to be at 6
+                    },
+                    Vector2::<f32>::default(),
+                )
+                .unwrap();
+            uvgen::generate_uvs(&mut data, spacing)?;
+        }
+
+        Ok(data)
+    }
+
+    /// Builds a new surface from the geometry in the builder.
+    pub fn build_surface(&self) -> Result<Surface, ProceduralMeshError> {
+        Ok(Surface::new(SurfaceSharedData::new(
+            self.build_surface_data()?,
+        )))
+    }
+
+    /// Replaces contents of a surface of the given mesh node with the geometry in the builder. If
+    /// `surface_index` is equal to amount of surfaces in the mesh, a new surface will be added.
+    /// The surface data is changed in place, so every other mesh that shares the data will be
+    /// changed too, material of the surface is preserved.
+    ///
+    /// Bounding box of the mesh will be recalculated and every collider that uses the mesh as
+    /// geometry source will rebuild its shape on the next update.
+    pub fn apply(
+        &self,
+        graph: &mut Graph,
+        mesh: Handle<Node>,
+        surface_index: usize,
+    ) -> Result<(), ProceduralMeshError> {
+        let new_data = self.build_surface_data()?;
+
+        let mesh_node = graph
+            .try_get_mut(mesh)
+            .and_then(|n| n.cast_mut::<Mesh>())
+            .ok_or(ProceduralMeshError::NotAMesh(mesh))?;
+
+        let surface_count = mesh_node.surfaces().len();
+        if surface_index == surface_count {
+            mesh_node.add_surface(Surface::new(SurfaceSharedData::new(new_data)));
+        } else if let Some(surface) = mesh_node.surfaces_mut().get(surface_index) {
+            surface.data().lock().set_content(new_data);
+        } else {
+            return Err(ProceduralMeshError::NoSuchSurface(surface_index));
+        }
+
+        refresh_colliders(graph, mesh);
+
+        Ok(())
+    }
+}
+
+/// Forces every collider that uses the given node as a geometry source to rebuild its shape.
+pub fn refresh_colliders(graph: &mut Graph, geometry_source: Handle<Node>) {
+    let source = GeometrySource(geometry_source);
+    for node in graph.linear_iter_mut() {
+        if let Some(collider) = node.cast_mut::<Collider>() {
+            let uses_source = match collider.shape() {
+                ColliderShape::Trimesh(trimesh) => trimesh.sources.contains(&source),
+                ColliderShape::Heightfield(heightfield) => heightfield.geometry_source == source,
+                ColliderShape::Polyhedron(polyhedron) => polyhedron.geometry_source == source,
+                _ => false,
+            };
+            if uses_source {
+                collider.invalidate_shape();
+            }
+        }
+    }
+}
+
+fn face_normal(a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) -> Vector3<f32> {
+    (b - a).cross(&(c - a))
+}
+
+fn normalize_or_up(v: Vector3<f32>) -> Vector3<f32> {
+    v.try_normalize(f32::EPSILON)
+        .unwrap_or_else(|| Vector3::new(0.0, 1.0, 0.0))
+}
+
+/// Box projection of a position using the dominant axis of the normal.
+fn box_projection(position: Vector3<f32>, normal: Vector3<f32>) -> Vector2<f32> {
+    let abs = normal.abs();
+    if abs.x >= abs.y && abs.x >= abs.z {
+        position.zy()
+    } else if abs.y >= abs.z {
+        position.xz()
+    } else {
+        position.xy()
+    }
+}
+
+fn make_vertex(
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+    tex_coord: Option<Vector2<f32>>,
+) -> StaticVertex {
+    StaticVertex {
+        position,
+        tex_coord: tex_coord.unwrap_or_else(|| box_projection(position, normal)),
+        normal,
+        tangent: Vector4::default(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            procedural::{NormalsMode, ProceduralMesh, ProceduralMeshError},
+        },
+    };
+
+    fn make_quad(mode: NormalsMode) -> ProceduralMesh {
+        let mut builder = ProceduralMesh::new().with_normals_mode(mode);
+        let a = builder.push_vertex(Vector3::new(0.0, 0.0, 0.0));
+        let b = builder.push_vertex(Vector3::new(0.0, 0.0, 1.0));
+        let c = builder.push_vertex(Vector3::new(1.0, 0.0, 1.0));
+        let d = builder.push_vertex(Vector3::new(1.0, 0.0, 0.0));
+        builder.push_quad(a, b, c, d);
+        builder
+    }
+
+    #[test]
+    fn test_smooth_normals() {
+        let data = make_quad(NormalsMode::Smooth).build_surface_data().unwrap();
+        assert_eq!(data.vertex_buffer.vertex_count(), 4);
+        assert_eq!(data.geometry_buffer.len(), 2);
+        for view in data.vertex_buffer.iter() {
+            assert_eq!(
+                view.read_3_f32(VertexAttributeUsage::Normal).unwrap(),
+                Vector3::new(0.0, 1.0, 0.0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_flat_normals() {
+        let data = make_quad(NormalsMode::Flat).build_surface_data().unwrap();
+        assert_eq!(data.vertex_buffer.vertex_count(), 6);
+        assert_eq!(data.geometry_buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut builder = make_quad(NormalsMode::Smooth);
+        builder.push_triangle(0, 1, 4);
+        assert!(matches!(
+            builder.build_surface_data(),
+            Err(ProceduralMeshError::IndexOutOfBounds { index: 4, .. })
+        ));
+
+        let mut builder = make_quad(NormalsMode::Smooth);
+        builder.extend_indices(&[0]);
+        assert!(matches!(
+            builder.build_surface_data(),
+            Err(ProceduralMeshError::InvalidIndexCount(7))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+//! Attachment sockets for skinned meshes.
+//!
+//! Socket is a named attachment point defined relative to a bone of a skinned mesh. It allows
+//! you to attach arbitrary nodes (a weapon in a hand, a hat on a head, etc.) to a mesh without
+//! manually searching for bone handles. See [`super::Mesh::add_socket`] and
+//! [`crate::scene::graph::Graph::attach_to_socket`] for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, node::Node},
+};
+
+/// Named attachment point, that is defined by a bone and an offset relative to the bone.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct MeshSocket {
+    /// Name of the socket. It is used to find sockets at runtime.
+    pub name: String,
+    /// A handle of a bone to which the socket is attached.
+    pub bone: Handle<Node>,
+    /// Offset of the socket in the local coordinates of the bone.
+    pub position: Vector3<f32>,
+    /// Rotation of the socket in the local coordinates of the bone.
+    pub rotation: UnitQuaternion<f32>,
+    /// A list of nodes attached to the socket. Do not modify it directly, use
+    /// [`Graph::attach_to_socket`] and [`Graph::detach_from_socket`] instead.
+    #[reflect(read_only)]
+    pub attachments: Vec<Handle<Node>>,
+}
+
+impl Default for MeshSocket {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            bone: Default::default(),
+            position: Default::default(),
+            rotation: UnitQuaternion::identity(),
+            attachments: Default::default(),
+        }
+    }
+}
+
+impl MeshSocket {
+    /// Creates new socket with the given name, that is attached to the given bone without any
+    /// offset.
+    pub fn new<S: AsRef<str>>(name: S, bone: Handle<Node>) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            bone,
+            ..Default::default()
+        }
+    }
+
+    /// Sets desired position offset of the socket relative to its bone.
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets desired rotation offset of the socket relative to its bone.
+    pub fn with_rotation(mut self, rotation: UnitQuaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns transform of the socket in the local coordinates of its bone.
+    pub fn local_transform(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&self.position) * self.rotation.to_homogeneous()
+    }
+
+    /// Returns world-space transform of the socket. Bones are usually animated, so the transform
+    /// is valid only after the graph has updated its hierarchical data for the current frame.
+    /// Returns `None` if the bone of the socket does not exist.
+    pub fn global_transform(&self, graph: &Graph) -> Option<Matrix4<f32>> {
+        graph
+            .try_get(self.bone)
+            .map(|bone| bone.global_transform() * self.local_transform())
+    }
+
+    /// Returns world-space position of the socket. See [`Self::global_transform`] for more info.
+    pub fn global_position(&self, graph: &Graph) -> Option<Vector3<f32>> {
+        self.global_transform(graph)
+            .map(|transform| Vector3::new(transform[12], transform[13], transform[14]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{socket::MeshSocket, Mesh, MeshBuilder},
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_socket_attachment() {
+        let mut graph = Graph::new();
+
+        let bone = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_sockets(vec![MeshSocket::new("Hand", bone)
+                .with_position(Vector3::new(0.0, 1.0, 0.0))
+                .with_rotation(UnitQuaternion::identity())])
+            .build(&mut graph);
+
+        let weapon = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        assert!(graph.attach_to_socket(weapon, mesh, "Hand"));
+        assert!(!graph.attach_to_socket(weapon, mesh, "Head"));
+        assert_eq!(graph[weapon].parent(), bone);
+
+        graph.update_hierarchical_data();
+
+        let socket_position = graph[mesh]
+            .cast::<Mesh>()
+            .unwrap()
+            .socket("Hand")
+            .unwrap()
+            .global_position(&graph)
+            .unwrap();
+        assert_eq!(socket_position, Vector3::new(1.0, 3.0, 3.0));
+        assert_eq!(graph[weapon].global_position(), socket_position);
+
+        assert!(graph.detach_from_socket(weapon, mesh, "Hand"));
+        assert_eq!(graph[weapon].parent(), graph.get_root());
+        assert!(graph[mesh]
+            .cast::<Mesh>()
+            .unwrap()
+            .socket("Hand")
+            .unwrap()
+            .attachments
+            .is_empty());
+    }
+}
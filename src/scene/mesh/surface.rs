@@ -839,6 +839,25 @@ impl SurfaceData {
         )
     }
 
+    /// Replaces vertex and index buffers with the buffers of the given data. GPU buffers are reused
+    /// if vertex layout stays the same.
+    pub fn set_content(&mut self, data: SurfaceData) {
+        let same_layout = self.vertex_buffer.vertex_size() == data.vertex_buffer.vertex_size()
+            && self.vertex_buffer.layout().len() == data.vertex_buffer.layout().len()
+            && self
+                .vertex_buffer
+                .layout()
+                .iter()
+                .zip(data.vertex_buffer.layout())
+                .all(|(a, b)| a.usage == b.usage && a.offset == b.offset);
+        if !same_layout {
+            self.cache_entry = AtomicIndex::unassigned();
+        }
+        self.vertex_buffer = data.vertex_buffer;
+        self.geometry_buffer = data.geometry_buffer;
+        self.is_procedural = data.is_procedural;
+    }
+
     /// Clears both vertex and index buffers.
     pub fn clear(&mut self) {
         self.geometry_buffer.modify().clear();
@@ -0,0 +1,464 @@
+//! Sweep generators - they make surfaces by moving a 2D profile through space: along a spline
+//! ([`extrude`]), around an axis ([`lathe`]) or through a set of sections ([`loft`]). They are
+//! intended for procedural level decoration - roads, pipes, fences, columns, etc.
+//!
+//! # Profiles
+//!
+//! A [`Profile`] is defined in XY plane, the profile is placed so its X axis is directed to the
+//! side of the path, and Y axis is directed up. A generated surface faces to the right side of
+//! the profile (looking along the direction of its points), so closed profiles with
+//! counter-clockwise points face outwards. Repeat a point of a profile to make a sharp edge at it.
+//!
+//! # Texture coordinates
+//!
+//! Texture coordinates are proportional to the distance along the profile (U) and along the path
+//! (V), `texture_size` defines the size of one texture repeat in local units, so textures tile
+//! without stretching.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        math::TriangleDefinition,
+    },
+    scene::{
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            procedural::ProceduralMeshError,
+            surface::SurfaceData,
+            vertex::StaticVertex,
+        },
+        spline::Spline,
+    },
+};
+
+/// A 2D cross-section of a swept surface. See module docs for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    points: Vec<Vector2<f32>>,
+    closed: bool,
+}
+
+impl Profile {
+    /// Creates a new profile from the given points. Closed profiles connect the last point with
+    /// the first one.
+    pub fn new(points: Vec<Vector2<f32>>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    /// Creates a closed circular profile with the center at the origin (pipes, columns, etc.).
+    pub fn circle(radius: f32, segments: usize) -> Self {
+        let segments = segments.max(3);
+        let points = (0..segments)
+            .map(|i| {
+                let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
+                Vector2::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+        Self::new(points, true)
+    }
+
+    /// Creates a closed rectangular profile with the center at the origin and sharp corners (rails,
+    /// fences, beams, etc.).
+    pub fn rectangle(width: f32, height: f32) -> Self {
+        let (w, h) = (width * 0.5, height * 0.5);
+        let corners = [
+            Vector2::new(w, -h),
+            Vector2::new(w, h),
+            Vector2::new(-w, h),
+            Vector2::new(-w, -h),
+        ];
+        let points = (0..corners.len())
+            .flat_map(|i| [corners[i], corners[(i + 1) % corners.len()]])
+            .collect();
+        Self::new(points, true)
+    }
+
+    /// Creates a flat open profile that faces up (roads, rivers, etc.).
+    pub fn strip(width: f32) -> Self {
+        Self::new(
+            vec![
+                Vector2::new(width * 0.5, 0.0),
+                Vector2::new(-width * 0.5, 0.0),
+            ],
+            false,
+        )
+    }
+
+    /// Returns points of the profile.
+    pub fn points(&self) -> &[Vector2<f32>] {
+        &self.points
+    }
+
+    /// Returns `true` if the profile is closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the points with the first point repeated at the end for closed profiles, and
+    /// distance along the profile for each of them.
+    fn unrolled(&self) -> (Vec<Vector2<f32>>, Vec<f32>) {
+        let mut points = self.points.clone();
+        if self.closed && !points.is_empty() {
+            points.push(points[0]);
+        }
+
+        let mut distance = 0.0;
+        let mut distances = Vec::with_capacity(points.len());
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                distance += point.metric_distance(&points[i - 1]);
+            }
+            distances.push(distance);
+        }
+
+        (points, distances)
+    }
+}
+
+/// A section of a lofted surface (see [`loft`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoftSection {
+    /// Profile of the section.
+    pub profile: Profile,
+    /// Transform of the section, the profile is placed in XY plane of it, and the surface goes
+    /// along its Z axis.
+    pub transform: Matrix4<f32>,
+}
+
+/// Extrudes the profile along the spline. The profile is oriented using the tangent and roll of
+/// the spline, its X axis is scaled by the width of the spline. The geometry is created in local
+/// coordinates of the spline, so a mesh with it should have the same transform as the spline (for
+/// example - be its child). Ends of the surface are not capped.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector2,
+///     scene::{
+///         mesh::{
+///             surface::{SurfaceBuilder, SurfaceSharedData},
+///             sweep::{self, Profile},
+///         },
+///         spline::Spline,
+///     },
+/// };
+///
+/// fn make_road(spline: &Spline) -> SurfaceBuilder {
+///     // 4 meters wide road with one texture repeat per 4 meters.
+///     let data = sweep::extrude(spline, &Profile::strip(4.0), 16, Vector2::new(4.0, 4.0));
+///     SurfaceBuilder::new(SurfaceSharedData::new(data))
+/// }
+/// ```
+pub fn extrude(
+    spline: &Spline,
+    profile: &Profile,
+    steps_per_segment: usize,
+    texture_size: Vector2<f32>,
+) -> SurfaceData {
+    let (points, u) = profile.unrolled();
+
+    let step_count = spline.segment_count() * steps_per_segment.max(1);
+    let mut rings = Vec::with_capacity(step_count + 1);
+    if step_count > 0 {
+        for i in 0..=step_count {
+            if let Some(sample) = spline.sample(i as f32 / step_count as f32) {
+                let orientation = sample.orientation();
+                rings.push(
+                    points
+                        .iter()
+                        .map(|p| {
+                            sample.position
+                                + orientation * Vector3::new(p.x * sample.width, p.y, 0.0)
+                        })
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+    }
+
+    let v = path_distances(&rings, &points);
+    build_grid(
+        &rings,
+        &u,
+        &v,
+        profile.is_closed(),
+        spline.is_closed(),
+        texture_size,
+    )
+}
+
+/// Rotates the profile around Y axis by the given angle (in radians, `2 * PI` gives a closed
+/// surface). X coordinate of the profile is the distance to the axis (vases, columns, bottles,
+/// etc.).
+pub fn lathe(
+    profile: &Profile,
+    segments: usize,
+    angle: f32,
+    texture_size: Vector2<f32>,
+) -> SurfaceData {
+    let (points, u) = profile.unrolled();
+    let segments = segments.max(1);
+
+    let mut rings = Vec::with_capacity(segments + 1);
+    for i in 0..=segments {
+        let (sin, cos) = (angle * i as f32 / segments as f32).sin_cos();
+        rings.push(
+            points
+                .iter()
+                .map(|p| Vector3::new(p.x * cos, p.y, p.x * sin))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    // Measure the path at the farthest point from the axis, the center of the profile could be
+    // on the axis.
+    let radius = points
+        .iter()
+        .fold(0.0f32, |radius, p| radius.max(p.x.abs()));
+    let v = (0..=segments)
+        .map(|i| radius * (angle * i as f32 / segments as f32).abs())
+        .collect::<Vec<_>>();
+    let closed = angle.abs() >= 2.0 * std::f32::consts::PI - f32::EPSILON;
+    build_grid(&rings, &u, &v, profile.is_closed(), closed, texture_size)
+}
+
+/// Connects the sections with a surface. Profiles of every section must have the same amount of
+/// points, the surface is closed across profiles if the profile of the first section is closed.
+pub fn loft(
+    sections: &[LoftSection],
+    texture_size: Vector2<f32>,
+) -> Result<SurfaceData, ProceduralMeshError> {
+    let first = match sections.first() {
+        Some(first) => &first.profile,
+        None => return Ok(build_grid(&[], &[], &[], false, false, texture_size)),
+    };
+
+    let mut rings = Vec::with_capacity(sections.len());
+    for section in sections {
+        if section.profile.points.len() != first.points.len() {
+            return Err(ProceduralMeshError::ProfileMismatch {
+                expected: first.points.len(),
+                actual: section.profile.points.len(),
+            });
+        }
+
+        let profile = Profile::new(section.profile.points.clone(), first.closed);
+        rings.push(
+            profile
+                .unrolled()
+                .0
+                .iter()
+                .map(|p| {
+                    section
+                        .transform
+                        .transform_point(&Point3::new(p.x, p.y, 0.0))
+                        .coords
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let (points, u) = first.unrolled();
+    let v = path_distances(&rings, &points);
+    Ok(build_grid(
+        &rings,
+        &u,
+        &v,
+        first.closed,
+        false,
+        texture_size,
+    ))
+}
+
+/// Calculates distance along the path for every ring, it is measured at the center of the profile
+/// (average of its points).
+fn path_distances(rings: &[Vec<Vector3<f32>>], points: &[Vector2<f32>]) -> Vec<f32> {
+    let count = points.len().max(1) as f32;
+    let centers = rings
+        .iter()
+        .map(|ring| ring.iter().sum::<Vector3<f32>>() / count)
+        .collect::<Vec<_>>();
+
+    let mut distance = 0.0;
+    let mut distances = Vec::with_capacity(rings.len());
+    for (i, center) in centers.iter().enumerate() {
+        if i > 0 {
+            distance += center.metric_distance(&centers[i - 1]);
+        }
+        distances.push(distance);
+    }
+    distances
+}
+
+/// Makes a surface from a grid of vertices, rows of the grid are rings (profiles placed along the
+/// path). Closed dimensions have the first column (row) repeated at the end to have proper texture
+/// coordinates, their normals are shared to hide the seam.
+fn build_grid(
+    rings: &[Vec<Vector3<f32>>],
+    u: &[f32],
+    v: &[f32],
+    wrap_u: bool,
+    wrap_v: bool,
+    texture_size: Vector2<f32>,
+) -> SurfaceData {
+    let columns = u.len();
+    let index = |row: usize, column: usize| row * columns + column;
+
+    let mut normals = vec![Vector3::default(); rings.len() * columns];
+    let mut triangles = Vec::new();
+    for row in 0..rings.len().saturating_sub(1) {
+        for column in 0..columns.saturating_sub(1) {
+            // Zero-length edges make sharp edges (or degenerate rings), skip them.
+            if u[column + 1] - u[column] <= f32::EPSILON || v[row + 1] - v[row] <= f32::EPSILON {
+                continue;
+            }
+
+            let quad = [
+                (row, column),
+                (row, column + 1),
+                (row + 1, column + 1),
+                (row + 1, column),
+            ];
+            for [a, b, c] in [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]] {
+                let pa = rings[a.0][a.1];
+                // Not normalized, so larger triangles have larger weight.
+                let normal = (rings[b.0][b.1] - pa).cross(&(rings[c.0][c.1] - pa));
+                for (r, c) in [a, b, c] {
+                    normals[index(r, c)] += normal;
+                }
+                triangles.push(TriangleDefinition([
+                    index(a.0, a.1) as u32,
+                    index(b.0, b.1) as u32,
+                    index(c.0, c.1) as u32,
+                ]));
+            }
+        }
+    }
+
+    if wrap_u && columns > 1 {
+        for row in 0..rings.len() {
+            let sum = normals[index(row, 0)] + normals[index(row, columns - 1)];
+            normals[index(row, 0)] = sum;
+            normals[index(row, columns - 1)] = sum;
+        }
+    }
+    if wrap_v && rings.len() > 1 {
+        let last = rings.len() - 1;
+        for column in 0..columns {
+            let sum = normals[index(0, column)] + normals[index(last, column)];
+            normals[index(0, column)] = sum;
+            normals[index(last, column)] = sum;
+        }
+    }
+
+    let mut vertices = Vec::with_capacity(normals.len());
+    for (row, ring) in rings.iter().enumerate() {
+        for (column, &position) in ring.iter().enumerate() {
+            vertices.push(StaticVertex {
+                position,
+                tex_coord: Vector2::new(u[column] / texture_size.x, v[row] / texture_size.y),
+                normal: normals[index(row, column)]
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y),
+                tangent: Vector4::default(),
+            });
+        }
+    }
+
+    let mut data = SurfaceData::new(
+        VertexBuffer::new(vertices.len(), StaticVertex::layout(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+        true,
+    );
+    data.calculate_tangents().unwrap();
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            mesh::{
+                buffer::{VertexAttributeUsage, VertexReadTrait},
+                procedural::ProceduralMeshError,
+                sweep::{self, LoftSection, Profile},
+            },
+            spline::{SplineBuilder, SplinePoint},
+        },
+    };
+
+    #[test]
+    fn test_extrude_strip() {
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_points(vec![
+                SplinePoint::new(Vector3::new(0.0, 0.0, 0.0)),
+                SplinePoint::new(Vector3::new(0.0, 0.0, 4.0)),
+            ])
+            .build_spline();
+
+        let data = sweep::extrude(&spline, &Profile::strip(2.0), 4, Vector2::new(1.0, 1.0));
+        assert_eq!(data.vertex_buffer.vertex_count(), 10);
+        assert_eq!(data.geometry_buffer.len(), 8);
+        for view in data.vertex_buffer.iter() {
+            let normal = view.read_3_f32(VertexAttributeUsage::Normal).unwrap();
+            assert!((normal - Vector3::y()).norm() < 1.0e-5);
+        }
+
+        // One texture repeat per unit.
+        let last = data.vertex_buffer.get(9).unwrap();
+        let uv = last.read_2_f32(VertexAttributeUsage::TexCoord0).unwrap();
+        assert!((uv - Vector2::new(2.0, 4.0)).norm() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_lathe_normals_face_outwards() {
+        let profile = Profile::new(vec![Vector2::new(1.0, 0.0), Vector2::new(1.0, 1.0)], false);
+        let data = sweep::lathe(
+            &profile,
+            8,
+            2.0 * std::f32::consts::PI,
+            Vector2::new(1.0, 1.0),
+        );
+        assert_eq!(data.geometry_buffer.len(), 16);
+        for view in data.vertex_buffer.iter() {
+            let position = view.read_3_f32(VertexAttributeUsage::Position).unwrap();
+            let normal = view.read_3_f32(VertexAttributeUsage::Normal).unwrap();
+            assert!(normal.dot(&Vector3::new(position.x, 0.0, position.z)) > 0.9);
+        }
+    }
+
+    #[test]
+    fn test_loft() {
+        let sections = [
+            LoftSection {
+                profile: Profile::rectangle(1.0, 1.0),
+                transform: Matrix4::identity(),
+            },
+            LoftSection {
+                profile: Profile::rectangle(2.0, 2.0),
+                transform: Matrix4::new_translation(&Vector3::new(0.0, 0.0, 1.0)),
+            },
+        ];
+        let data = sweep::loft(&sections, Vector2::new(1.0, 1.0)).unwrap();
+        // Sharp corners - 4 sides with 2 triangles each.
+        assert_eq!(data.geometry_buffer.len(), 8);
+
+        let sections = [
+            sections[0].clone(),
+            LoftSection {
+                profile: Profile::circle(1.0, 16),
+                transform: Matrix4::identity(),
+            },
+        ];
+        assert!(matches!(
+            sweep::loft(&sections, Vector2::new(1.0, 1.0)),
+            Err(ProceduralMeshError::ProfileMismatch {
+                expected: 8,
+                actual: 16
+            })
+        ));
+    }
+}
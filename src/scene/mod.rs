@@ -9,6 +9,7 @@ pub mod animation;
 pub mod base;
 pub mod camera;
 pub mod collider;
+pub mod crowd;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
@@ -20,11 +21,18 @@ pub mod mesh;
 pub mod node;
 pub mod particle_system;
 pub mod pivot;
+pub mod post_processing;
 pub mod rigidbody;
+pub mod sky;
 pub mod sound;
+pub mod spline;
 pub mod sprite;
+pub mod streaming;
 pub mod terrain;
+pub mod text;
+pub mod time_of_day;
 pub mod transform;
+pub mod validation;
 pub mod visibility;
 
 use crate::{
@@ -44,6 +52,7 @@ use crate::{
         camera::Camera,
         debug::SceneDrawingContext,
         graph::{map::NodeHandleMap, Graph, GraphPerformanceStatistics},
+        loader::{SceneLoadingProgress, SceneLoadingStage},
         mesh::buffer::{
             VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
             VertexWriteTrait,
@@ -51,6 +60,8 @@ use crate::{
         mesh::Mesh,
         node::Node,
         sound::SoundEngine,
+        time_of_day::TimeOfDay,
+        validation::Diagnostic,
     },
     utils::{lightmap::Lightmap, log::Log, log::MessageKind, navmesh::Navmesh},
 };
@@ -221,7 +232,8 @@ impl Display for PerformanceStatistics {
             \tPhysics 2D: {:?}\n\
             \t\tSimulation: {:?}\n\
             \t\tRay cast: {:?}\n\
-            \tHierarchy: {:?}",
+            \tHierarchy: {:?}\n\
+            \tAnimation: {:?}",
             self.graph.total(),
             self.graph.sync_time,
             self.graph.sound_update_time,
@@ -232,6 +244,7 @@ impl Display for PerformanceStatistics {
             self.graph.physics2d.step_time,
             self.graph.physics2d.total_ray_cast_time.get(),
             self.graph.hierarchical_properties_time,
+            self.graph.animation_time,
         )
     }
 }
@@ -274,6 +287,19 @@ impl SceneLoader {
 
     /// Finishes scene loading.
     pub async fn finish(self, resource_manager: ResourceManager) -> Scene {
+        self.finish_with_progress(resource_manager, |_| {}).await
+    }
+
+    /// Finishes scene loading and reports loading progress using the given callback. The callback
+    /// is called from the thread that drives the future, so it must be cheap.
+    pub async fn finish_with_progress<F>(
+        self,
+        resource_manager: ResourceManager,
+        mut on_progress: F,
+    ) -> Scene
+    where
+        F: FnMut(SceneLoadingProgress),
+    {
         let mut scene = self.scene;
 
         // Collect all model resources and wait for them. This step is crucial, because
@@ -290,7 +316,18 @@ impl SceneLoader {
             }
         }
 
-        let _ = join_all(resources).await;
+        // Resources are loading in parallel, so waiting for them one-by-one is fine and allows
+        // us to report which resource is currently loading.
+        let total = resources.len();
+        for (i, resource) in resources.into_iter().enumerate() {
+            on_progress(SceneLoadingProgress {
+                stage: SceneLoadingStage::LoadingResources,
+                percent: SceneLoadingStage::LoadingResources.percent(i, total),
+                current_resource: Some(resource.state().path().to_path_buf()),
+            });
+
+            let _ = resource.await;
+        }
 
         // Restore pointers to resources. Scene saves only paths to resources, here we must
         // find real resources instead.
@@ -323,9 +360,21 @@ impl SceneLoader {
         }
         join_all(skybox_textures).await;
 
+        on_progress(SceneLoadingProgress {
+            stage: SceneLoadingStage::Resolving,
+            percent: SceneLoadingStage::Resolving.percent(0, 1),
+            current_resource: None,
+        });
+
         // And do resolve to extract correct graphical data and so on.
         scene.resolve();
 
+        on_progress(SceneLoadingProgress {
+            stage: SceneLoadingStage::Finished,
+            percent: SceneLoadingStage::Finished.percent(0, 1),
+            current_resource: None,
+        });
+
         scene
     }
 }
@@ -362,6 +411,12 @@ impl Scene {
         self.graph.remove_node(handle)
     }
 
+    /// Checks the scene for common problems (dangling handles, missing resources, invalid
+    /// transforms, etc.). See [`validation::validate`] docs for more info.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validation::validate(self)
+    }
+
     /// Synchronizes the state of the scene with external resources.
     pub fn resolve(&mut self) {
         Log::writeln(MessageKind::Information, "Starting resolve...");
@@ -501,6 +556,16 @@ impl Scene {
     /// no need to call it directly, engine automatically updates all available scenes.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32) {
         self.graph.update(frame_size, dt);
+
+        if let Some(time_of_day) = self
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<TimeOfDay>())
+            .find(|time_of_day| time_of_day.is_driving_ambient_lighting())
+        {
+            self.ambient_lighting_color = time_of_day.ambient_color();
+        }
+
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
     }
 
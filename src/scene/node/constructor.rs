@@ -6,16 +6,27 @@ use crate::{
         self,
         animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
         camera::Camera,
+        crowd::Crowd,
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
-        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        light::{
+            directional::DirectionalLight, point::PointLight, probe::LightProbeVolume,
+            reflection_probe::ReflectionProbe, spot::SpotLight,
+        },
         mesh::Mesh,
         node::{Node, NodeTrait, TypeUuidProvider},
         particle_system::ParticleSystem,
         pivot::Pivot,
-        sound::{listener::Listener, Sound},
+        sound::{
+            environment::{AudioPortal, ReverbZone},
+            listener::Listener,
+            Sound,
+        },
+        spline::Spline,
         sprite::Sprite,
         terrain::Terrain,
+        text::Text3D,
+        time_of_day::TimeOfDay,
     },
 };
 use fxhash::FxHashMap;
@@ -41,18 +52,26 @@ impl NodeConstructorContainer {
         container.add::<DirectionalLight>();
         container.add::<PointLight>();
         container.add::<SpotLight>();
+        container.add::<LightProbeVolume>();
+        container.add::<ReflectionProbe>();
         container.add::<Mesh>();
         container.add::<ParticleSystem>();
         container.add::<Sound>();
         container.add::<Listener>();
+        container.add::<ReverbZone>();
+        container.add::<AudioPortal>();
         container.add::<Camera>();
         container.add::<scene::collider::Collider>();
         container.add::<Decal>();
+        container.add::<Crowd>();
         container.add::<scene::joint::Joint>();
         container.add::<Pivot>();
         container.add::<scene::rigidbody::RigidBody>();
+        container.add::<Spline>();
         container.add::<Sprite>();
         container.add::<Terrain>();
+        container.add::<Text3D>();
+        container.add::<TimeOfDay>();
         container.add::<AnimationPlayer>();
         container.add::<AnimationBlendingStateMachine>();
 
@@ -9,6 +9,23 @@ use crate::{
 };
 use std::ops::Range;
 
+/// Burst emits a fixed amount of particles at specific time, regardless of spawn rate of
+/// the emitter.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct EmitterBurst {
+    /// Time (in seconds) from the start of the emitter at which particles will be emitted.
+    pub time: f32,
+    /// Amount of particles to emit.
+    pub count: u32,
+}
+
+impl EmitterBurst {
+    /// Creates new burst.
+    pub fn new(time: f32, count: u32) -> Self {
+        Self { time, count }
+    }
+}
+
 /// See module docs.
 #[derive(Debug, Visit, PartialEq, Reflect)]
 pub struct BaseEmitter {
@@ -49,6 +66,13 @@ pub struct BaseEmitter {
     resurrect_particles: bool,
     #[reflect(hidden)]
     spawned_particles: u64,
+    /// A set of bursts, each burst emits a fixed amount of particles at specific time.
+    #[visit(optional)] // Backward compatibility
+    bursts: Vec<EmitterBurst>,
+    /// Time (in seconds) passed since the start of the emitter.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    elapsed: f32,
 }
 
 /// Emitter builder allows you to construct emitter in declarative manner.
@@ -66,6 +90,7 @@ pub struct BaseEmitterBuilder {
     rotation_speed: Range<f32>,
     rotation: Range<f32>,
     resurrect_particles: bool,
+    bursts: Vec<EmitterBurst>,
 }
 
 impl Default for BaseEmitterBuilder {
@@ -90,6 +115,7 @@ impl BaseEmitterBuilder {
             rotation_speed: -0.02..0.02,
             rotation: -std::f32::consts::PI..std::f32::consts::PI,
             resurrect_particles: true,
+            bursts: Default::default(),
         }
     }
 
@@ -165,6 +191,12 @@ impl BaseEmitterBuilder {
         self
     }
 
+    /// Sets desired bursts of particles.
+    pub fn with_bursts(mut self, bursts: Vec<EmitterBurst>) -> Self {
+        self.bursts = bursts;
+        self
+    }
+
     /// Creates new instance of emitter.
     pub fn build(self) -> BaseEmitter {
         BaseEmitter {
@@ -184,6 +216,8 @@ impl BaseEmitterBuilder {
             particles_to_spawn: 0,
             resurrect_particles: self.resurrect_particles,
             spawned_particles: 0,
+            bursts: self.bursts,
+            elapsed: 0.0,
         }
     }
 }
@@ -192,14 +226,25 @@ impl BaseEmitter {
     /// Updates emitter and emits required amount of particles each call. There is no
     /// need to call it manually, it will be automatically called by scene update call.
     pub fn tick(&mut self, dt: f32) {
-        self.time += dt;
-        let time_amount_per_particle = 1.0 / self.particle_spawn_rate as f32;
-        let mut particle_count = (self.time / time_amount_per_particle) as u32;
-        self.time -= time_amount_per_particle * particle_count as f32;
+        let mut particle_count = 0;
+        if self.particle_spawn_rate > 0 {
+            self.time += dt;
+            let time_amount_per_particle = 1.0 / self.particle_spawn_rate as f32;
+            particle_count = (self.time / time_amount_per_particle) as u32;
+            self.time -= time_amount_per_particle * particle_count as f32;
+        }
+        let prev_elapsed = self.elapsed;
+        self.elapsed += dt;
+        particle_count += self
+            .bursts
+            .iter()
+            .filter(|burst| burst.time >= prev_elapsed && burst.time < self.elapsed)
+            .map(|burst| burst.count)
+            .sum::<u32>();
         if let Some(max_particles) = self.max_particles {
             let alive_particles = self.alive_particles;
             if alive_particles < max_particles && alive_particles + particle_count > max_particles {
-                particle_count = max_particles - particle_count;
+                particle_count = max_particles - alive_particles;
             }
             if !self.resurrect_particles && self.spawned_particles > u64::from(max_particles) {
                 self.particles_to_spawn = 0;
@@ -210,6 +255,15 @@ impl BaseEmitter {
         self.spawned_particles += self.particles_to_spawn as u64;
     }
 
+    /// Restarts the emitter, so bursts will be emitted again.
+    pub(crate) fn reset(&mut self) {
+        self.alive_particles = 0;
+        self.time = 0.0;
+        self.elapsed = 0.0;
+        self.particles_to_spawn = 0;
+        self.spawned_particles = 0;
+    }
+
     /// Initializes particle with new state. Every custom emitter must call this method,
     /// otherwise you will get weird behavior of emitted particles.
     pub fn emit(&self, particle: &mut Particle) {
@@ -377,6 +431,17 @@ impl BaseEmitter {
     pub fn spawned_particles(&self) -> u64 {
         self.spawned_particles
     }
+
+    /// Sets new set of bursts of particles.
+    pub fn set_bursts(&mut self, bursts: Vec<EmitterBurst>) -> &mut Self {
+        self.bursts = bursts;
+        self
+    }
+
+    /// Returns current set of bursts of particles.
+    pub fn bursts(&self) -> &[EmitterBurst] {
+        &self.bursts
+    }
 }
 
 impl Clone for BaseEmitter {
@@ -398,6 +463,8 @@ impl Clone for BaseEmitter {
             particles_to_spawn: 0,
             resurrect_particles: self.resurrect_particles,
             spawned_particles: self.spawned_particles,
+            bursts: self.bursts.clone(),
+            elapsed: self.elapsed,
         }
     }
 }
@@ -421,6 +488,8 @@ impl Default for BaseEmitter {
             particles_to_spawn: 0,
             resurrect_particles: true,
             spawned_particles: 0,
+            bursts: Default::default(),
+            elapsed: 0.0,
         }
     }
 }
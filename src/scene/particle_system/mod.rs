@@ -11,6 +11,20 @@
 //!
 //! Particle system can contain multiple particle emitters, each emitter has its own
 //! set of properties and it defines law of change of particle parameters over time.
+//! Besides continuous emission with given spawn rate, emitters can emit bursts of particles
+//! at specific times (see [`emitter::base::EmitterBurst`]).
+//!
+//! # Prewarm
+//!
+//! Particle system can be simulated for some time at the moment when it starts, so effects
+//! like smoke or fire won't start empty. See [`ParticleSystem::set_prewarm_time`].
+//!
+//! # Determinism
+//!
+//! By default particles use random numbers of the current thread, so every instance of an effect
+//! looks different. A particle system can have its own seed of random numbers, in this case it
+//! produces identical particles on every run (assuming the same time steps), which is useful for
+//! replays and networked games. See [`ParticleSystem::set_rng_seed`].
 //!
 //! # Performance
 //!
@@ -77,12 +91,14 @@ use crate::{
         color_gradient::ColorGradient,
         math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
         pool::Handle,
+        random,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
         variable::InheritableVariable,
         visitor::prelude::*,
     },
     engine::resource_manager::ResourceManager,
+    rand::{rngs::StdRng, SeedableRng},
     resource::texture::Texture,
     scene::{
         base::{Base, BaseBuilder},
@@ -105,6 +121,9 @@ pub(crate) mod draw;
 pub mod emitter;
 pub mod particle;
 
+/// Time step (in seconds) that is used to simulate particle systems on prewarm.
+const PREWARM_TIME_STEP: f32 = 1.0 / 30.0;
+
 #[doc(hidden)]
 #[derive(PartialEq, Debug, Clone, Default, Reflect)]
 pub struct EmitterWrapper(#[reflect(display_name = "Emitter Type")] pub Emitter);
@@ -153,11 +172,27 @@ pub struct ParticleSystem {
     #[reflect(setter = "set_enabled")]
     enabled: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_prewarm_time")]
+    #[visit(optional)] // Backward compatibility
+    prewarm_time: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_rng_seed")]
+    #[visit(optional)] // Backward compatibility
+    rng_seed: InheritableVariable<Option<u64>>,
+
     #[reflect(hidden)]
     particles: Vec<Particle>,
 
     #[reflect(hidden)]
     free_particles: Vec<u32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rng: Option<StdRng>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prewarmed: bool,
 }
 
 impl Deref for ParticleSystem {
@@ -224,6 +259,30 @@ impl ParticleSystem {
         self.soft_boundary_sharpness_factor.set(factor)
     }
 
+    /// Sets amount of time (in seconds) the particle system will be simulated for at the moment
+    /// when it starts, so the effect won't start empty.
+    pub fn set_prewarm_time(&mut self, time: f32) -> f32 {
+        self.prewarm_time.set(time.max(0.0))
+    }
+
+    /// Returns current prewarm time in seconds.
+    pub fn prewarm_time(&self) -> f32 {
+        *self.prewarm_time
+    }
+
+    /// Sets a seed for random numbers of the particle system. Seeded particle system produces
+    /// identical particles on every run (assuming the same time steps). `None` means that random
+    /// numbers of the current thread will be used.
+    pub fn set_rng_seed(&mut self, seed: Option<u64>) -> Option<u64> {
+        self.rng = None;
+        self.rng_seed.set(seed)
+    }
+
+    /// Returns current seed of random numbers of the particle system.
+    pub fn rng_seed(&self) -> Option<u64> {
+        *self.rng_seed
+    }
+
     /// Removes all generated particles.
     pub fn clear_particles(&mut self) {
         self.particles.clear();
@@ -233,6 +292,100 @@ impl ParticleSystem {
         }
     }
 
+    /// Removes all generated particles and restarts the particle system: emitters will emit their
+    /// bursts again, prewarm will be performed again and random numbers will start from the seed
+    /// (if any).
+    pub fn reset(&mut self) {
+        self.clear_particles();
+        for emitter in self.emitters.get_mut_silent().iter_mut() {
+            emitter.reset();
+        }
+        self.rng = None;
+        self.prewarmed = false;
+    }
+
+    fn prewarm(&mut self) {
+        let prewarm_time = *self.prewarm_time;
+        if prewarm_time <= 0.0 || self.particles.iter().any(|p| p.alive) {
+            return;
+        }
+
+        let steps = (prewarm_time / PREWARM_TIME_STEP).ceil();
+        let dt = prewarm_time / steps;
+        for _ in 0..steps as usize {
+            self.simulate(dt);
+        }
+    }
+
+    fn simulate(&mut self, dt: f32) {
+        if let Some(seed) = *self.rng_seed {
+            let mut rng = self
+                .rng
+                .take()
+                .unwrap_or_else(|| StdRng::seed_from_u64(seed));
+            random::with_rng_override(&mut rng, || self.simulate_particles(dt));
+            self.rng = Some(rng);
+        } else {
+            self.simulate_particles(dt);
+        }
+    }
+
+    fn simulate_particles(&mut self, dt: f32) {
+        for emitter in self.emitters.get_mut_silent().iter_mut() {
+            emitter.tick(dt);
+        }
+
+        for (i, emitter) in self.emitters.get_mut_silent().iter_mut().enumerate() {
+            for _ in 0..emitter.particles_to_spawn {
+                let mut particle = Particle {
+                    emitter_index: i as u32,
+                    ..Particle::default()
+                };
+                emitter.alive_particles += 1;
+                emitter.emit(&mut particle);
+                if let Some(free_index) = self.free_particles.pop() {
+                    self.particles[free_index as usize] = particle;
+                } else {
+                    self.particles.push(particle);
+                }
+            }
+        }
+
+        let acceleration_offset = self.acceleration.scale(dt * dt);
+
+        for (i, particle) in self.particles.iter_mut().enumerate() {
+            if particle.alive {
+                particle.lifetime += dt;
+                if particle.lifetime >= particle.initial_lifetime {
+                    self.free_particles.push(i as u32);
+                    if let Some(emitter) = self
+                        .emitters
+                        .get_mut()
+                        .get_mut(particle.emitter_index as usize)
+                    {
+                        emitter.alive_particles -= 1;
+                    }
+                    particle.alive = false;
+                    particle.lifetime = particle.initial_lifetime;
+                } else {
+                    particle.velocity += acceleration_offset;
+                    particle.position += particle.velocity;
+                    particle.size += particle.size_modifier * dt;
+                    if particle.size < 0.0 {
+                        particle.size = 0.0;
+                    }
+                    particle.rotation += particle.rotation_speed * dt;
+                    if let Some(color_over_lifetime) = self.color_over_lifetime.as_ref() {
+                        let k = particle.lifetime / particle.initial_lifetime;
+                        particle.color = color_over_lifetime.get_color(k);
+                    } else {
+                        particle.color = Color::WHITE;
+                    }
+                }
+            }
+        }
+    }
+
     /// Generates new draw data for current frame. Should not be used directly, unless you
     /// absolutely need draw data before rendering. It is automatically called by renderer.
     pub fn generate_draw_data(
@@ -372,59 +525,12 @@ impl NodeTrait for ParticleSystem {
         let dt = context.dt;
 
         if *self.enabled {
-            for emitter in self.emitters.get_mut_silent().iter_mut() {
-                emitter.tick(dt);
-            }
-
-            for (i, emitter) in self.emitters.get_mut_silent().iter_mut().enumerate() {
-                for _ in 0..emitter.particles_to_spawn {
-                    let mut particle = Particle {
-                        emitter_index: i as u32,
-                        ..Particle::default()
-                    };
-                    emitter.alive_particles += 1;
-                    emitter.emit(&mut particle);
-                    if let Some(free_index) = self.free_particles.pop() {
-                        self.particles[free_index as usize] = particle;
-                    } else {
-                        self.particles.push(particle);
-                    }
-                }
+            if !self.prewarmed {
+                self.prewarmed = true;
+                self.prewarm();
             }
 
-            let acceleration_offset = self.acceleration.scale(dt * dt);
-
-            for (i, particle) in self.particles.iter_mut().enumerate() {
-                if particle.alive {
-                    particle.lifetime += dt;
-                    if particle.lifetime >= particle.initial_lifetime {
-                        self.free_particles.push(i as u32);
-                        if let Some(emitter) = self
-                            .emitters
-                            .get_mut()
-                            .get_mut(particle.emitter_index as usize)
-                        {
-                            emitter.alive_particles -= 1;
-                        }
-                        particle.alive = false;
-                        particle.lifetime = particle.initial_lifetime;
-                    } else {
-                        particle.velocity += acceleration_offset;
-                        particle.position += particle.velocity;
-                        particle.size += particle.size_modifier * dt;
-                        if particle.size < 0.0 {
-                            particle.size = 0.0;
-                        }
-                        particle.rotation += particle.rotation_speed * dt;
-                        if let Some(color_over_lifetime) = self.color_over_lifetime.as_ref() {
-                            let k = particle.lifetime / particle.initial_lifetime;
-                            particle.color = color_over_lifetime.get_color(k);
-                        } else {
-                            particle.color = Color::WHITE;
-                        }
-                    }
-                }
-            }
+            self.simulate(dt);
         }
 
         self.base.update_lifetime(dt)
@@ -442,6 +548,8 @@ pub struct ParticleSystemBuilder {
     color_over_lifetime: Option<ColorGradient>,
     soft_boundary_sharpness_factor: f32,
     enabled: bool,
+    prewarm_time: f32,
+    rng_seed: Option<u64>,
 }
 
 impl ParticleSystemBuilder {
@@ -456,6 +564,8 @@ impl ParticleSystemBuilder {
             color_over_lifetime: None,
             soft_boundary_sharpness_factor: 2.5,
             enabled: true,
+            prewarm_time: 0.0,
+            rng_seed: None,
         }
     }
 
@@ -508,6 +618,18 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets desired prewarm time in seconds, see [`ParticleSystem::set_prewarm_time`].
+    pub fn with_prewarm_time(mut self, time: f32) -> Self {
+        self.prewarm_time = time.max(0.0);
+        self
+    }
+
+    /// Sets desired seed of random numbers, see [`ParticleSystem::set_rng_seed`].
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -519,6 +641,10 @@ impl ParticleSystemBuilder {
             color_over_lifetime: self.color_over_lifetime.into(),
             soft_boundary_sharpness_factor: self.soft_boundary_sharpness_factor.into(),
             enabled: self.enabled.into(),
+            prewarm_time: self.prewarm_time.into(),
+            rng_seed: self.rng_seed.into(),
+            rng: None,
+            prewarmed: false,
         }
     }
 
@@ -542,10 +668,86 @@ mod test {
         resource::texture::test::create_test_texture,
         scene::{
             base::{test::check_inheritable_properties_equality, BaseBuilder},
-            particle_system::{ParticleSystem, ParticleSystemBuilder},
+            particle_system::{
+                emitter::{
+                    base::{BaseEmitterBuilder, EmitterBurst},
+                    sphere::SphereEmitterBuilder,
+                },
+                ParticleSystem, ParticleSystemBuilder,
+            },
         },
     };
 
+    fn make_particle_system(spawn_rate: u32, bursts: Vec<EmitterBurst>) -> ParticleSystemBuilder {
+        ParticleSystemBuilder::new(BaseBuilder::new()).with_emitters(vec![
+            SphereEmitterBuilder::new(
+                BaseEmitterBuilder::new()
+                    .with_spawn_rate(spawn_rate)
+                    .with_lifetime_range(10.0..20.0)
+                    .with_bursts(bursts),
+            )
+            .build(),
+        ])
+    }
+
+    fn alive_particles(particle_system: &ParticleSystem) -> Vec<Vector3<f32>> {
+        particle_system
+            .particles
+            .iter()
+            .filter(|p| p.alive)
+            .map(|p| p.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_particle_system_seed() {
+        let mut a = make_particle_system(50, vec![])
+            .with_rng_seed(123)
+            .build_particle_system();
+        let mut b = a.clone();
+        for _ in 0..10 {
+            a.simulate(0.1);
+            b.simulate(0.1);
+        }
+        assert!(!alive_particles(&a).is_empty());
+        assert_eq!(alive_particles(&a), alive_particles(&b));
+
+        // Reset must replay the same particles.
+        let particles = alive_particles(&a);
+        a.reset();
+        for _ in 0..10 {
+            a.simulate(0.1);
+        }
+        assert_eq!(alive_particles(&a), particles);
+    }
+
+    #[test]
+    fn test_particle_system_prewarm() {
+        let mut particle_system = make_particle_system(10, vec![])
+            .with_prewarm_time(2.0)
+            .build_particle_system();
+        particle_system.prewarm();
+        // 10 particles per second, none of them is dead after 2 seconds.
+        assert_eq!(alive_particles(&particle_system).len(), 20);
+    }
+
+    #[test]
+    fn test_particle_system_bursts() {
+        let mut particle_system = make_particle_system(
+            0,
+            vec![EmitterBurst::new(0.0, 5), EmitterBurst::new(1.0, 7)],
+        )
+        .build_particle_system();
+        particle_system.simulate(0.5);
+        assert_eq!(alive_particles(&particle_system).len(), 5);
+        particle_system.simulate(0.4);
+        assert_eq!(alive_particles(&particle_system).len(), 5);
+        particle_system.simulate(0.2);
+        assert_eq!(alive_particles(&particle_system).len(), 12);
+        particle_system.simulate(1.0);
+        assert_eq!(alive_particles(&particle_system).len(), 12);
+    }
+
     #[test]
     fn test_particle_system_inheritance() {
         let parent = ParticleSystemBuilder::new(BaseBuilder::new())
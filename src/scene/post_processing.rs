@@ -0,0 +1,332 @@
+//! Post-processing is a set of full-screen effects that are applied to a frame after the scene
+//! was rendered. See [`PostProcessStack`] docs for more info.
+
+use crate::{
+    core::{color::Color, reflect::prelude::*, visitor::prelude::*},
+    engine::resource_manager::ResourceManager,
+    material::SharedMaterial,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Bloom makes overly bright parts of a frame to "bleed" light into surrounding pixels, which
+/// creates glow effect. Unlike other effects, bloom is applied in high dynamic range, before tone
+/// mapping, so its position in the stack does not matter.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct BloomEffect {
+    /// Luminance threshold, pixels with luminance above this value will glow.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub threshold: f32,
+    /// Glow intensity multiplier.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub intensity: f32,
+}
+
+impl Default for BloomEffect {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Vignette darkens corners of a frame.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct VignetteEffect {
+    /// Strength of the effect in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub intensity: f32,
+    /// Defines how smooth the transition between clear and darkened areas is.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub smoothness: f32,
+    /// Color of darkened areas.
+    pub color: Color,
+}
+
+impl Default for VignetteEffect {
+    fn default() -> Self {
+        Self {
+            intensity: 0.4,
+            smoothness: 0.5,
+            color: Color::BLACK,
+        }
+    }
+}
+
+/// Chromatic aberration splits color channels of a frame towards its edges, imitating the effect
+/// of a cheap camera lens.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct ChromaticAberrationEffect {
+    /// Strength of the effect, in fractions of frame size.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub intensity: f32,
+}
+
+impl Default for ChromaticAberrationEffect {
+    fn default() -> Self {
+        Self { intensity: 0.005 }
+    }
+}
+
+/// Film grain adds animated noise to a frame.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct FilmGrainEffect {
+    /// Strength of the effect in `[0; 1]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub intensity: f32,
+}
+
+impl Default for FilmGrainEffect {
+    fn default() -> Self {
+        Self { intensity: 0.05 }
+    }
+}
+
+/// Motion blur smears moving parts of a frame along their screen-space motion. Both camera and
+/// object motion (including skinned meshes) are taken into account. The effect uses velocity
+/// buffer of the renderer, which is rendered automatically for cameras with this effect.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct MotionBlurEffect {
+    /// Amount of frame samples taken along motion of every pixel. More samples gives smoother
+    /// blur, but costs more.
+    #[reflect(min_value = 1.0, max_value = 64.0)]
+    pub sample_count: u32,
+    /// Fraction of frame time, during which the shutter is "open". `0.5` matches 180 degree
+    /// shutter of film cameras, `1.0` blurs along whole motion since the previous frame.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub shutter: f32,
+}
+
+impl Default for MotionBlurEffect {
+    fn default() -> Self {
+        Self {
+            sample_count: 8,
+            shutter: 0.5,
+        }
+    }
+}
+
+/// User-defined effect, that uses a material with a custom shader. The shader must have a render
+/// pass called `PostProcess`. The renderer provides the following uniforms in addition to
+/// material properties:
+///
+/// - `worldViewProjection` (`mat4`) - matrix to transform full-screen quad vertices.
+/// - `frameTexture` (`sampler2D`) - current frame.
+/// - `inverseScreenSize` (`vec2`) - inverse size of the frame in pixels.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq, Default)]
+pub struct CustomEffect {
+    /// A material that will be used to render the effect.
+    pub material: SharedMaterial,
+}
+
+/// A post-processing effect. See respective effect docs for more info.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq, AsRefStr, EnumString, EnumVariantNames)]
+pub enum PostEffect {
+    /// See [`BloomEffect`] docs.
+    Bloom(BloomEffect),
+    /// See [`VignetteEffect`] docs.
+    Vignette(VignetteEffect),
+    /// See [`ChromaticAberrationEffect`] docs.
+    ChromaticAberration(ChromaticAberrationEffect),
+    /// See [`FilmGrainEffect`] docs.
+    FilmGrain(FilmGrainEffect),
+    /// See [`MotionBlurEffect`] docs.
+    MotionBlur(MotionBlurEffect),
+    /// See [`CustomEffect`] docs.
+    Custom(CustomEffect),
+}
+
+impl Default for PostEffect {
+    fn default() -> Self {
+        Self::Vignette(Default::default())
+    }
+}
+
+/// An entry of post-processing stack.
+#[derive(Visit, Reflect, Clone, Debug, PartialEq, Default)]
+pub struct PostEffectEntry {
+    /// Whether the effect is enabled or not. Disabled effects are skipped by the renderer.
+    pub enabled: bool,
+    /// Actual effect.
+    pub effect: PostEffect,
+}
+
+impl PostEffectEntry {
+    /// Creates new enabled entry.
+    pub fn new(effect: PostEffect) -> Self {
+        Self {
+            enabled: true,
+            effect,
+        }
+    }
+}
+
+/// Post-processing stack is an ordered set of full-screen effects, that are applied to a frame of
+/// a camera after the scene was rendered. Effects are applied in the order they're stored in the
+/// stack (except bloom, see [`BloomEffect`] docs), each effect could be toggled individually.
+/// Color grading is not a part of the stack, it is configured on the camera itself (see
+/// [`crate::scene::camera::Camera::set_color_grading_lut`]).
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::scene::post_processing::{
+///     FilmGrainEffect, PostEffect, PostProcessStack, VignetteEffect,
+/// };
+///
+/// let mut stack = PostProcessStack::default();
+/// stack
+///     .push(PostEffect::Vignette(VignetteEffect::default()))
+///     .push(PostEffect::FilmGrain(FilmGrainEffect { intensity: 0.1 }));
+///
+/// // Disable film grain.
+/// stack.effects_mut()[2].enabled = false;
+/// ```
+#[derive(Visit, Reflect, Clone, Debug, PartialEq)]
+pub struct PostProcessStack {
+    effects: Vec<PostEffectEntry>,
+}
+
+impl Default for PostProcessStack {
+    fn default() -> Self {
+        Self {
+            effects: vec![PostEffectEntry::new(PostEffect::Bloom(Default::default()))],
+        }
+    }
+}
+
+impl PostProcessStack {
+    /// Creates new post-processing stack without any effects.
+    pub fn empty() -> Self {
+        Self {
+            effects: Default::default(),
+        }
+    }
+
+    /// Adds new enabled effect to the end of the stack.
+    pub fn push(&mut self, effect: PostEffect) -> &mut Self {
+        self.effects.push(PostEffectEntry::new(effect));
+        self
+    }
+
+    /// Inserts new enabled effect at the given position.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, effect: PostEffect) -> &mut Self {
+        self.effects.insert(index, PostEffectEntry::new(effect));
+        self
+    }
+
+    /// Removes an effect at the given position.
+    pub fn remove(&mut self, index: usize) -> Option<PostEffectEntry> {
+        if index < self.effects.len() {
+            Some(self.effects.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Moves an effect from one position to another. Does nothing if any of the indices is out of
+    /// bounds.
+    pub fn move_effect(&mut self, from: usize, to: usize) {
+        if from < self.effects.len() && to < self.effects.len() {
+            let entry = self.effects.remove(from);
+            self.effects.insert(to, entry);
+        }
+    }
+
+    /// Returns a slice with all effects of the stack.
+    pub fn effects(&self) -> &[PostEffectEntry] {
+        &self.effects
+    }
+
+    /// Returns a mutable reference to inner effects storage.
+    pub fn effects_mut(&mut self) -> &mut Vec<PostEffectEntry> {
+        &mut self.effects
+    }
+
+    /// Returns an iterator over enabled effects in the order they should be applied.
+    pub fn enabled_effects(&self) -> impl Iterator<Item = &PostEffect> {
+        self.effects.iter().filter(|e| e.enabled).map(|e| &e.effect)
+    }
+
+    /// Returns first enabled bloom effect (if any).
+    pub fn bloom(&self) -> Option<&BloomEffect> {
+        self.enabled_effects().find_map(|e| {
+            if let PostEffect::Bloom(bloom) = e {
+                Some(bloom)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns first enabled motion blur effect (if any).
+    pub fn motion_blur(&self) -> Option<&MotionBlurEffect> {
+        self.enabled_effects().find_map(|e| {
+            if let PostEffect::MotionBlur(motion_blur) = e {
+                Some(motion_blur)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub(crate) fn restore_resources(&mut self, resource_manager: &ResourceManager) {
+        for entry in self.effects.iter_mut() {
+            if let PostEffect::Custom(ref mut custom) = entry.effect {
+                custom.material.lock().resolve(resource_manager.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::post_processing::{
+        FilmGrainEffect, MotionBlurEffect, PostEffect, PostProcessStack, VignetteEffect,
+    };
+
+    #[test]
+    fn test_post_process_stack_ordering() {
+        let mut stack = PostProcessStack::empty();
+        assert!(stack.bloom().is_none());
+
+        stack
+            .push(PostEffect::Vignette(VignetteEffect::default()))
+            .push(PostEffect::FilmGrain(FilmGrainEffect::default()))
+            .insert(0, PostEffect::Bloom(Default::default()));
+
+        assert!(stack.bloom().is_some());
+        stack.effects_mut()[0].enabled = false;
+        assert!(stack.bloom().is_none());
+
+        stack.move_effect(2, 1);
+        assert!(matches!(
+            stack.effects()[1].effect,
+            PostEffect::FilmGrain(_)
+        ));
+        assert_eq!(stack.enabled_effects().count(), 2);
+
+        assert!(stack.remove(5).is_none());
+        assert!(stack.remove(0).is_some());
+        assert_eq!(stack.effects().len(), 2);
+    }
+
+    #[test]
+    fn test_motion_blur_lookup() {
+        let mut stack = PostProcessStack::empty();
+        assert!(stack.motion_blur().is_none());
+
+        stack.push(PostEffect::MotionBlur(MotionBlurEffect {
+            sample_count: 16,
+            shutter: 0.25,
+        }));
+        assert_eq!(stack.motion_blur().map(|m| m.sample_count), Some(16));
+
+        stack.effects_mut()[0].enabled = false;
+        assert!(stack.motion_blur().is_none());
+    }
+}
@@ -0,0 +1,345 @@
+//! Procedural sky with physically-based atmospheric scattering. See [`ProceduralSky`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        color::Color,
+        reflect::prelude::*,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    resource::texture::{Texture, TextureKind, TexturePixelKind},
+};
+use rayon::prelude::*;
+use std::f32::consts::PI;
+
+/// Radius of the planet in meters.
+pub const PLANET_RADIUS: f32 = 6371e3;
+
+/// Radius of the outer edge of the atmosphere in meters.
+pub const ATMOSPHERE_RADIUS: f32 = 6471e3;
+
+// Height of the viewer above the ground in meters. It can't be too small, otherwise precision of
+// 32-bit floats won't be enough to find intersections with the planet near the horizon. Keep in
+// sync with the procedural sky shader.
+const VIEWER_HEIGHT: f32 = 1000.0;
+const PRIMARY_STEPS: usize = 16;
+const LIGHT_STEPS: usize = 8;
+// Extinction of aerosols is a bit larger than their scattering because of absorption.
+const MIE_EXTINCTION_FACTOR: f32 = 1.1;
+
+/// Procedural sky is an alternative to cube map skyboxes. It computes the color of the sky from
+/// the position of the sun using single scattering of sunlight in the atmosphere of a planet
+/// (Rayleigh scattering on air molecules and Mie scattering on aerosols). It gives blue skies at
+/// noon and red sunsets with correct gradients for any position of the sun.
+///
+/// The same computations are done on GPU (to draw the sky) and on CPU, which allows you to get
+/// color of the sun ([`Self::sun_color`]), ambient lighting ([`Self::ambient_color`]) and to
+/// bake the sky into an environment map ([`Self::bake`]).
+///
+/// See [`crate::scene::time_of_day::TimeOfDay`] for a node, that animates the sky and lighting
+/// of a scene over a day.
+#[derive(Debug, Clone, PartialEq, Reflect, Visit)]
+pub struct ProceduralSky {
+    /// Direction **towards** the sun in world space. It does not need to be normalized.
+    pub sun_direction: Vector3<f32>,
+    /// Intensity of the sunlight at the edge of the atmosphere.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub sun_intensity: f32,
+    /// Angular radius of the sun disk (in radians). Zero disables the sun disk.
+    #[reflect(min_value = 0.0, max_value = 0.5, step = 0.001)]
+    pub sun_angular_radius: f32,
+    /// Rayleigh scattering coefficients (per meter) for red, green and blue light at sea level.
+    pub rayleigh_scattering: Vector3<f32>,
+    /// Mie scattering coefficient (per meter) at sea level. Larger values give hazier skies.
+    #[reflect(min_value = 0.0)]
+    pub mie_scattering: f32,
+    /// Anisotropy of Mie scattering in `[-1; 1]` range. Values closer to one give brighter halo
+    /// around the sun.
+    #[reflect(min_value = -0.999, max_value = 0.999, step = 0.01)]
+    pub mie_anisotropy: f32,
+    /// Height (in meters) at which density of air molecules drops by `e` times.
+    #[reflect(min_value = 1.0)]
+    pub rayleigh_scale_height: f32,
+    /// Height (in meters) at which density of aerosols drops by `e` times.
+    #[reflect(min_value = 1.0)]
+    pub mie_scale_height: f32,
+}
+
+impl Default for ProceduralSky {
+    fn default() -> Self {
+        Self {
+            sun_direction: Vector3::new(0.0, 0.5, 1.0),
+            sun_intensity: 22.0,
+            sun_angular_radius: 0.01,
+            rayleigh_scattering: Vector3::new(5.8e-6, 13.5e-6, 33.1e-6),
+            mie_scattering: 21e-6,
+            mie_anisotropy: 0.758,
+            rayleigh_scale_height: 8000.0,
+            mie_scale_height: 1200.0,
+        }
+    }
+}
+
+// Returns distances to intersections of a ray (with normalized direction) and a sphere in the
+// center of coordinates.
+fn ray_sphere_intersection(
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    radius: f32,
+) -> Option<(f32, f32)> {
+    let b = origin.dot(&direction);
+    // Factorized form loses less precision than `|origin|^2 - radius^2`.
+    let distance = origin.norm();
+    let c = (distance - radius) * (distance + radius);
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        None
+    } else {
+        let root = discriminant.sqrt();
+        Some((-b - root, -b + root))
+    }
+}
+
+fn viewer_position() -> Vector3<f32> {
+    Vector3::new(0.0, PLANET_RADIUS + VIEWER_HEIGHT, 0.0)
+}
+
+fn cube_map_face_direction(face: usize, s: f32, t: f32) -> Vector3<f32> {
+    // OpenGL cube map conventions, faces are +X, -X, +Y, -Y, +Z, -Z.
+    match face {
+        0 => Vector3::new(1.0, -t, -s),
+        1 => Vector3::new(-1.0, -t, s),
+        2 => Vector3::new(s, 1.0, t),
+        3 => Vector3::new(s, -1.0, -t),
+        4 => Vector3::new(s, -t, 1.0),
+        _ => Vector3::new(-s, -t, -1.0),
+    }
+}
+
+impl ProceduralSky {
+    fn normalized_sun_direction(&self) -> Vector3<f32> {
+        self.sun_direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y)
+    }
+
+    fn densities(&self, point: Vector3<f32>) -> (f32, f32) {
+        let height = (point.norm() - PLANET_RADIUS).max(0.0);
+        (
+            (-height / self.rayleigh_scale_height).exp(),
+            (-height / self.mie_scale_height).exp(),
+        )
+    }
+
+    // Returns optical depth (rayleigh, mie) from the point to the edge of the atmosphere along
+    // the direction or `None` if the planet blocks the direction.
+    fn optical_depth_to_space(
+        &self,
+        point: Vector3<f32>,
+        direction: Vector3<f32>,
+    ) -> Option<(f32, f32)> {
+        if let Some((near, _)) = ray_sphere_intersection(point, direction, PLANET_RADIUS) {
+            if near > 0.0 {
+                return None;
+            }
+        }
+
+        let (_, far) = ray_sphere_intersection(point, direction, ATMOSPHERE_RADIUS)?;
+        let step = far.max(0.0) / LIGHT_STEPS as f32;
+        let mut depth = (0.0, 0.0);
+        for i in 0..LIGHT_STEPS {
+            let (rayleigh, mie) = self.densities(point + direction * ((i as f32 + 0.5) * step));
+            depth.0 += rayleigh * step;
+            depth.1 += mie * step;
+        }
+        Some(depth)
+    }
+
+    fn transmittance(&self, rayleigh_depth: f32, mie_depth: f32) -> Vector3<f32> {
+        let tau = self.rayleigh_scattering * rayleigh_depth
+            + Vector3::repeat(self.mie_scattering * MIE_EXTINCTION_FACTOR * mie_depth);
+        tau.map(|t| (-t).exp())
+    }
+
+    /// Calculates linear radiance of the sky in the given world-space direction.
+    pub fn radiance(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        let direction = direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        let sun = self.normalized_sun_direction();
+        let origin = viewer_position();
+
+        let (_, atmosphere_far) =
+            match ray_sphere_intersection(origin, direction, ATMOSPHERE_RADIUS) {
+                Some(intersection) => intersection,
+                None => return Vector3::default(),
+            };
+        let mut distance = atmosphere_far;
+        let mut hits_ground = false;
+        if let Some((near, _)) = ray_sphere_intersection(origin, direction, PLANET_RADIUS) {
+            if near > 0.0 {
+                distance = near;
+                hits_ground = true;
+            }
+        }
+
+        let step = distance / PRIMARY_STEPS as f32;
+        let mut view_depth = (0.0, 0.0);
+        let mut rayleigh_sum = Vector3::default();
+        let mut mie_sum = Vector3::default();
+        for i in 0..PRIMARY_STEPS {
+            let point = origin + direction * ((i as f32 + 0.5) * step);
+            let (rayleigh, mie) = self.densities(point);
+            view_depth.0 += rayleigh * step;
+            view_depth.1 += mie * step;
+
+            if let Some(light_depth) = self.optical_depth_to_space(point, sun) {
+                let attenuation =
+                    self.transmittance(view_depth.0 + light_depth.0, view_depth.1 + light_depth.1);
+                rayleigh_sum += attenuation * (rayleigh * step);
+                mie_sum += attenuation * (mie * step);
+            }
+        }
+
+        let mu = direction.dot(&sun);
+        let g = self.mie_anisotropy;
+        let rayleigh_phase = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+        let mie_phase = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+            / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * mu).max(1.0e-4).powf(1.5));
+
+        let mut radiance = (rayleigh_sum.component_mul(&self.rayleigh_scattering) * rayleigh_phase
+            + mie_sum * (self.mie_scattering * mie_phase))
+            * self.sun_intensity;
+
+        if !hits_ground && self.sun_angular_radius > 0.0 && mu >= self.sun_angular_radius.cos() {
+            radiance += self.transmittance(view_depth.0, view_depth.1) * self.sun_intensity;
+        }
+
+        radiance
+    }
+
+    /// Returns linear color of the sunlight at the ground, each component is in `[0; 1]` range.
+    /// The color is black when the sun is below the horizon.
+    pub fn sun_light(&self) -> Vector3<f32> {
+        self.optical_depth_to_space(viewer_position(), self.normalized_sun_direction())
+            .map(|(rayleigh, mie)| self.transmittance(rayleigh, mie))
+            .unwrap_or_default()
+    }
+
+    /// Returns color of the sunlight at the ground in sRGB color space, it could be used as a
+    /// color of a directional light.
+    pub fn sun_color(&self) -> Color {
+        Color::from(self.sun_light().map(|c| c.powf(1.0 / 2.2)))
+    }
+
+    /// Calculates linear irradiance (divided by pi) that comes from the upper hemisphere of the
+    /// sky, excluding the sun disk.
+    pub fn ambient_light(&self) -> Vector3<f32> {
+        const SAMPLES: u32 = 64;
+
+        let mut sky = self.clone();
+        sky.sun_angular_radius = 0.0;
+
+        let mut sum = Vector3::default();
+        let mut total_weight = 0.0;
+        for i in 0..SAMPLES {
+            // Fibonacci hemisphere.
+            let y = 1.0 - (i as f32 + 0.5) / SAMPLES as f32;
+            let radius = (1.0 - y * y).max(0.0).sqrt();
+            let phi = i as f32 * PI * (3.0 - 5.0f32.sqrt());
+            let direction = Vector3::new(radius * phi.cos(), y, radius * phi.sin());
+            sum += sky.radiance(direction) * y;
+            total_weight += y;
+        }
+        sum / total_weight
+    }
+
+    /// Returns ambient lighting color in sRGB color space, it could be used as ambient lighting
+    /// color of a scene.
+    pub fn ambient_color(&self) -> Color {
+        Color::from(self.ambient_light().map(|c| c.powf(1.0 / 2.2)))
+    }
+
+    /// Bakes the sky into a cube map with linear high dynamic range colors (`RGB32F`). It could
+    /// be used as an environment map of a camera to get reflections of the sky. Baking is done
+    /// on CPU and uses all available cores. Content of the texture will be saved together with
+    /// a scene.
+    pub fn bake(&self, resolution: u32) -> Option<Texture> {
+        let resolution = resolution.max(1);
+        let texels_per_face = (resolution * resolution) as usize;
+
+        let texels = (0..6 * texels_per_face)
+            .into_par_iter()
+            .map(|index| {
+                let face = index / texels_per_face;
+                let texel = index % texels_per_face;
+                let x = (texel % resolution as usize) as f32;
+                let y = (texel / resolution as usize) as f32;
+                let s = 2.0 * (x + 0.5) / resolution as f32 - 1.0;
+                let t = 2.0 * (y + 0.5) / resolution as f32 - 1.0;
+                self.radiance(cube_map_face_direction(face, s, t))
+            })
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::with_capacity(texels.len() * 3 * std::mem::size_of::<f32>());
+        for texel in texels {
+            for component in texel.iter() {
+                bytes.extend_from_slice(&component.to_ne_bytes());
+            }
+        }
+
+        Texture::from_bytes(
+            TextureKind::Cube {
+                width: resolution,
+                height: resolution,
+            },
+            TexturePixelKind::RGB32F,
+            bytes,
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{core::algebra::Vector3, scene::sky::ProceduralSky};
+
+    #[test]
+    fn test_procedural_sky_colors() {
+        let noon = ProceduralSky {
+            sun_direction: Vector3::y(),
+            ..Default::default()
+        };
+
+        // Rayleigh scattering makes the sky blue.
+        let zenith = noon.radiance(Vector3::new(0.0, 1.0, 0.5));
+        assert!(zenith.z > zenith.x);
+
+        // Sunlight at noon is almost white, sunlight at sunset is red.
+        let noon_sun = noon.sun_light();
+        assert!(noon_sun.x > 0.8 && noon_sun.z > 0.5);
+
+        let sunset = ProceduralSky {
+            sun_direction: Vector3::new(1.0, 0.02, 0.0),
+            ..Default::default()
+        };
+        let sunset_sun = sunset.sun_light();
+        assert!(sunset_sun.x > sunset_sun.z);
+        assert!(sunset_sun.z < noon_sun.z);
+
+        // No light at night.
+        let night = ProceduralSky {
+            sun_direction: -Vector3::y(),
+            ..Default::default()
+        };
+        assert_eq!(night.sun_light(), Vector3::default());
+        assert!(night.ambient_light().norm() < noon.ambient_light().norm() * 0.01);
+
+        let texture = noon.bake(4).unwrap();
+        assert_eq!(
+            texture.data_ref().first_mip_level_data().len(),
+            6 * 4 * 4 * 3 * 4
+        );
+    }
+}
@@ -9,17 +9,25 @@ use crate::{
     resource::model::Model,
     scene::{
         graph::{map::NodeHandleMap, NodePool},
-        sound::{self, effect::Effect, Sound},
+        sound::{
+            self, effect::Effect, environment::AudioEnvironment, listener::HrtfMode, HrirSphere,
+            HrtfRenderer, Sound,
+        },
     },
     utils::log::{Log, MessageKind},
 };
 use fyrox_sound::{
     context::DistanceModel,
     effects::{reverb::Reverb, BaseEffect, EffectInput, InputFilter},
+    music::{MusicAsset, MusicPlayer},
     renderer::Renderer,
     source::{SoundSource, SoundSourceBuilder, Status},
+    voice::StealPolicy,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
 };
-use std::time::Duration;
 
 /// Sound context.
 #[derive(Debug, Visit, Reflect)]
@@ -36,6 +44,17 @@ pub struct SoundContext {
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    environment: AudioEnvironment,
+    // HRTF settings of the active listener, the renderer of the context is overridden if the
+    // mode is not `Inherit`.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    listener_hrtf: Option<(HrtfMode, PathBuf)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    music: MusicPlayer,
 }
 
 impl Default for SoundContext {
@@ -48,6 +67,9 @@ impl Default for SoundContext {
             effects: Default::default(),
             resource: None,
             native: fyrox_sound::context::SoundContext::new(),
+            environment: Default::default(),
+            listener_hrtf: None,
+            music: Default::default(),
         }
     }
 }
@@ -57,6 +79,24 @@ impl SoundContext {
         Default::default()
     }
 
+    /// Returns a reference to the music player of the context.
+    pub fn music(&self) -> &MusicPlayer {
+        &self.music
+    }
+
+    /// Returns a reference to the music player of the context. It could be used to control
+    /// interactive music from scripts, for example `music_mut().set_intensity(0.7)` or
+    /// `music_mut().transition_to("combat")`.
+    pub fn music_mut(&mut self) -> &mut MusicPlayer {
+        &mut self.music
+    }
+
+    /// Sets new music asset, currently playing music is stopped.
+    pub fn set_music(&mut self, asset: MusicAsset) {
+        self.music.stop(&mut self.native.state());
+        self.music.set_asset(asset);
+    }
+
     /// Adds new effect and returns its handle.
     pub fn add_effect(&mut self, effect: Effect) -> Handle<Effect> {
         self.effects.spawn(effect)
@@ -134,6 +174,48 @@ impl SoundContext {
         self.distance_model
     }
 
+    /// Sets maximum amount of sounds that could be heard at once, excessive sounds are virtualized.
+    /// `None` means no limit. See [`fyrox_sound::voice::VoiceManager`] docs for more info.
+    pub fn set_max_voices(&mut self, max_voices: Option<usize>) {
+        self.native
+            .state()
+            .voice_manager_mut()
+            .set_max_voices(max_voices);
+    }
+
+    /// Returns maximum amount of sounds that could be heard at once.
+    pub fn max_voices(&self) -> Option<usize> {
+        self.native.state().voice_manager().max_voices()
+    }
+
+    /// Sets maximum amount of sounds on a voice bus (see [`Sound::set_bus`]) that could be heard
+    /// at once. `None` removes the limit.
+    pub fn set_bus_voice_limit(&mut self, bus: &str, limit: Option<usize>) {
+        self.native
+            .state()
+            .voice_manager_mut()
+            .set_bus_limit(bus, limit);
+    }
+
+    /// Sets a policy that defines which sounds will be virtualized when global or bus voice limit
+    /// is exceeded.
+    pub fn set_voice_steal_policy(&mut self, policy: StealPolicy) {
+        self.native
+            .state()
+            .voice_manager_mut()
+            .set_steal_policy(policy);
+    }
+
+    /// Returns amount of audible and virtual voices at the last update.
+    pub fn voice_count(&self) -> (usize, usize) {
+        let state = self.native.state();
+        let voice_manager = state.voice_manager();
+        (
+            voice_manager.real_voice_count(),
+            voice_manager.virtual_voice_count(),
+        )
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -150,9 +232,48 @@ impl SoundContext {
         self.renderer.clone()
     }
 
-    /// Sets new renderer.
+    /// Sets new renderer. Active listener could override the renderer, see [`HrtfMode`] docs for
+    /// more info.
     pub fn set_renderer(&mut self, renderer: Renderer) -> Renderer {
-        self.native.state().set_renderer(renderer)
+        let prev = std::mem::replace(&mut self.renderer, renderer);
+        if self.listener_renderer().is_none() {
+            self.native.state().set_renderer(self.renderer.clone());
+        }
+        prev
+    }
+
+    fn listener_renderer(&self) -> Option<Renderer> {
+        match self.listener_hrtf.as_ref() {
+            None | Some((HrtfMode::Inherit, _)) => None,
+            Some((HrtfMode::Disabled, _)) => Some(Renderer::Default),
+            Some((HrtfMode::Enabled, path)) => {
+                match HrirSphere::from_file(path, fyrox_sound::context::SAMPLE_RATE) {
+                    Ok(hrir_sphere) => Some(Renderer::HrtfRenderer(HrtfRenderer::new(hrir_sphere))),
+                    Err(err) => {
+                        Log::err(format!(
+                            "Unable to load HRIR sphere {}. Reason: {:?}",
+                            path.display(),
+                            err
+                        ));
+                        Some(Renderer::Default)
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn set_listener_hrtf(&mut self, mode: HrtfMode, hrir_sphere: &Path) {
+        if let Some((current_mode, current_hrir_sphere)) = self.listener_hrtf.as_ref() {
+            if *current_mode == mode && current_hrir_sphere == hrir_sphere {
+                return;
+            }
+        }
+
+        self.listener_hrtf = Some((mode, hrir_sphere.to_owned()));
+        let renderer = self
+            .listener_renderer()
+            .unwrap_or_else(|| self.renderer.clone());
+        self.native.state().set_renderer(renderer);
     }
 
     /// Sets new master gain. Master gain is used to control total sound volume that will be passed to output
@@ -167,7 +288,7 @@ impl SoundContext {
         self.master_gain
     }
 
-    pub(crate) fn update(&mut self, nodes: &NodePool) {
+    pub(crate) fn update(&mut self, nodes: &NodePool, dt: f32) {
         let mut state = self.native.state();
 
         fn sync_effect_inputs(
@@ -232,6 +353,9 @@ impl SoundContext {
                 }
             }
         }
+
+        self.environment.update(nodes, &mut state);
+        self.music.update(&mut state, dt);
     }
 
     pub(crate) fn remove_sound(&mut self, sound: Handle<SoundSource>) {
@@ -286,6 +410,18 @@ impl SoundContext {
             sound
                 .spatial_blend
                 .try_sync_model(|v| source.set_spatial_blend(v));
+            sound.priority.try_sync_model(|v| {
+                source.set_priority(v);
+            });
+            sound.bus.try_sync_model(|v| {
+                source.set_bus(v);
+            });
+            sound.max_instances.try_sync_model(|v| {
+                source.set_max_instances(v);
+            });
+            sound.steal_policy.try_sync_model(|v| {
+                source.set_steal_policy(v);
+            });
             sound.status.try_sync_model(|v| match v {
                 Status::Stopped => {
                     Log::verify(source.stop());
@@ -297,6 +433,15 @@ impl SoundContext {
                     source.pause();
                 }
             });
+            // Sources of disabled nodes are paused, but the status of the node is preserved, so the
+            // source will continue playing when the node is enabled back.
+            if !sound.is_globally_enabled() {
+                if source.status() == Status::Playing {
+                    source.pause();
+                }
+            } else if sound.status() == Status::Playing && source.status() == Status::Paused {
+                source.play();
+            }
         } else {
             match SoundSourceBuilder::new()
                 .with_gain(sound.gain())
@@ -304,12 +449,22 @@ impl SoundContext {
                 .with_looping(sound.is_looping())
                 .with_panning(sound.panning())
                 .with_pitch(sound.pitch())
-                .with_status(sound.status())
+                .with_status(
+                    if !sound.is_globally_enabled() && sound.status() == Status::Playing {
+                        Status::Paused
+                    } else {
+                        sound.status()
+                    },
+                )
                 .with_playback_time(sound.playback_time())
                 .with_position(sound.global_position())
                 .with_radius(sound.radius())
                 .with_max_distance(sound.max_distance())
                 .with_rolloff_factor(sound.rolloff_factor())
+                .with_priority(sound.priority())
+                .with_bus(sound.bus())
+                .with_max_instances(sound.max_instances())
+                .with_steal_policy(sound.steal_policy())
                 .build()
             {
                 Ok(source) => {
@@ -0,0 +1,752 @@
+//! Audio environments - reverb zones and portals between them.
+//!
+//! See [`ReverbZone`] and [`AudioPortal`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    define_with,
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{Graph, NodePool},
+        node::{Node, NodeTrait, TypeUuidProvider},
+        sound::Sound,
+    },
+};
+use fyrox_sound::{
+    effects::{reverb::Reverb, BaseEffect, Effect, EffectInput},
+    source::SoundSource,
+};
+use std::{
+    cmp::Ordering,
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+const DEFAULT_FC: f32 = 0.25615; // 11296 Hz at 44100 Hz sample rate
+
+/// Reverb zone is a box-shaped volume that defines acoustic properties of an environment (a room,
+/// a cave, a hall, etc.). When the listener moves between zones, parameters of the environment
+/// reverb are smoothly crossfaded, the transition happens in `fade_distance` range around the
+/// volume. Outside of any zone there is no environment reverb at all.
+///
+/// All 3D sounds are automatically routed into the environment reverb, there is no need to
+/// set up effect inputs manually. Zones can be nested, the smallest zone that contains a point
+/// defines the environment of the point.
+///
+/// Sounds emitted in one zone and heard from another are attenuated by `isolation` of the zone,
+/// unless the zones are connected with an [`AudioPortal`].
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct ReverbZone {
+    base: Base,
+
+    #[reflect(setter = "set_size")]
+    size: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_fade_distance")]
+    fade_distance: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.05)]
+    #[reflect(setter = "set_reverb_gain")]
+    reverb_gain: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_wet")]
+    wet: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_decay_time")]
+    decay_time: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    #[reflect(setter = "set_fc")]
+    fc: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_isolation")]
+    isolation: InheritableVariable<f32>,
+}
+
+impl Default for ReverbZone {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            size: InheritableVariable::new(Vector3::new(10.0, 5.0, 10.0)),
+            fade_distance: InheritableVariable::new(2.0),
+            reverb_gain: InheritableVariable::new(0.5),
+            wet: InheritableVariable::new(0.5),
+            decay_time: InheritableVariable::new(2.0),
+            fc: InheritableVariable::new(DEFAULT_FC),
+            isolation: InheritableVariable::new(0.5),
+        }
+    }
+}
+
+impl Deref for ReverbZone {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReverbZone {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReverbZone {
+    fn type_uuid() -> Uuid {
+        uuid!("3a0fe2b4-6a1f-4a6e-9a89-6d3b0f3c5b12")
+    }
+}
+
+impl ReverbZone {
+    /// Sets new size of the zone (in local coordinates). The zone is centered at the position of
+    /// the node.
+    pub fn set_size(&mut self, size: Vector3<f32>) -> Vector3<f32> {
+        self.size.set(Vector3::new(
+            size.x.max(0.0),
+            size.y.max(0.0),
+            size.z.max(0.0),
+        ))
+    }
+
+    /// Returns current size of the zone.
+    pub fn size(&self) -> Vector3<f32> {
+        *self.size
+    }
+
+    /// Sets a distance outside of the zone at which the zone stops affecting the environment
+    /// reverb.
+    pub fn set_fade_distance(&mut self, distance: f32) -> f32 {
+        self.fade_distance.set(distance.max(0.0))
+    }
+
+    /// Returns current fade distance.
+    pub fn fade_distance(&self) -> f32 {
+        *self.fade_distance
+    }
+
+    /// Sets how loud the reverberation in the zone is.
+    pub fn set_reverb_gain(&mut self, gain: f32) -> f32 {
+        self.reverb_gain.set(gain.max(0.0))
+    }
+
+    /// Returns current loudness of the reverberation.
+    pub fn reverb_gain(&self) -> f32 {
+        *self.reverb_gain
+    }
+
+    /// Sets stereo mixing of the reverberation. See [`crate::scene::sound::effect::ReverbEffect::set_wet`]
+    /// for more info.
+    pub fn set_wet(&mut self, wet: f32) -> f32 {
+        self.wet.set(wet.clamp(0.0, 1.0))
+    }
+
+    /// Returns stereo mixing of the reverberation.
+    pub fn wet(&self) -> f32 {
+        *self.wet
+    }
+
+    /// Sets duration of the reverberation (in seconds), larger environments should have longer
+    /// reverberation.
+    pub fn set_decay_time(&mut self, decay_time: f32) -> f32 {
+        self.decay_time.set(decay_time.max(0.0))
+    }
+
+    /// Returns duration of the reverberation.
+    pub fn decay_time(&self) -> f32 {
+        *self.decay_time
+    }
+
+    /// Sets normalized cutoff frequency of reflections. See [`crate::scene::sound::effect::ReverbEffect::set_fc`]
+    /// for more info.
+    pub fn set_fc(&mut self, fc: f32) -> f32 {
+        self.fc.set(fc)
+    }
+
+    /// Returns normalized cutoff frequency of reflections.
+    pub fn fc(&self) -> f32 {
+        *self.fc
+    }
+
+    /// Sets how much sounds that are emitted in the zone are attenuated when they're heard from
+    /// another zone that is not connected with this zone via a portal. 0.0 - no attenuation,
+    /// 1.0 - full silence.
+    pub fn set_isolation(&mut self, isolation: f32) -> f32 {
+        self.isolation.set(isolation.clamp(0.0, 1.0))
+    }
+
+    /// Returns current isolation of the zone.
+    pub fn isolation(&self) -> f32 {
+        *self.isolation
+    }
+
+    fn local_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.global_transform()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+            .transform_point(&Point3::from(point))
+            .coords
+    }
+
+    /// Checks whether the given point (in world coordinates) is inside the zone.
+    pub fn contains(&self, point: Vector3<f32>) -> bool {
+        let local = self.local_point(point);
+        let half = self.size() * 0.5;
+        local.x.abs() <= half.x && local.y.abs() <= half.y && local.z.abs() <= half.z
+    }
+
+    /// Returns influence of the zone at the given point (in world coordinates). It is 1.0 inside
+    /// the zone and linearly falls to 0.0 in the fade distance around the zone.
+    pub fn weight(&self, point: Vector3<f32>) -> f32 {
+        let local = self.local_point(point);
+        let half = self.size() * 0.5;
+        let outside = Vector3::new(
+            (local.x.abs() - half.x).max(0.0),
+            (local.y.abs() - half.y).max(0.0),
+            (local.z.abs() - half.z).max(0.0),
+        );
+        let distance = outside.norm();
+        if distance <= 0.0 {
+            1.0
+        } else if self.fade_distance() <= 0.0 {
+            0.0
+        } else {
+            (1.0 - distance / self.fade_distance()).max(0.0)
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+}
+
+impl NodeTrait for ReverbZone {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let half = self.size() * 0.5;
+        AxisAlignedBoundingBox::from_min_max(-half, half)
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create reverb zones in declarative manner.
+pub struct ReverbZoneBuilder {
+    base_builder: BaseBuilder,
+    size: Vector3<f32>,
+    fade_distance: f32,
+    reverb_gain: f32,
+    wet: f32,
+    decay_time: f32,
+    fc: f32,
+    isolation: f32,
+}
+
+impl ReverbZoneBuilder {
+    /// Creates new reverb zone builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        let zone = ReverbZone::default();
+        Self {
+            base_builder,
+            size: zone.size(),
+            fade_distance: zone.fade_distance(),
+            reverb_gain: zone.reverb_gain(),
+            wet: zone.wet(),
+            decay_time: zone.decay_time(),
+            fc: zone.fc(),
+            isolation: zone.isolation(),
+        }
+    }
+
+    define_with!(
+        /// Sets desired size of the zone.
+        fn with_size(size: Vector3<f32>)
+    );
+
+    define_with!(
+        /// Sets desired fade distance of the zone.
+        fn with_fade_distance(fade_distance: f32)
+    );
+
+    define_with!(
+        /// Sets desired loudness of the reverberation.
+        fn with_reverb_gain(reverb_gain: f32)
+    );
+
+    define_with!(
+        /// Sets desired stereo mixing of the reverberation.
+        fn with_wet(wet: f32)
+    );
+
+    define_with!(
+        /// Sets desired duration of the reverberation (in seconds).
+        fn with_decay_time(decay_time: f32)
+    );
+
+    define_with!(
+        /// Sets desired normalized cutoff frequency of reflections.
+        fn with_fc(fc: f32)
+    );
+
+    define_with!(
+        /// Sets desired isolation of the zone.
+        fn with_isolation(isolation: f32)
+    );
+
+    /// Creates new reverb zone.
+    pub fn build_reverb_zone(self) -> ReverbZone {
+        ReverbZone {
+            base: self.base_builder.build_base(),
+            size: self.size.into(),
+            fade_distance: self.fade_distance.into(),
+            reverb_gain: self.reverb_gain.into(),
+            wet: self.wet.into(),
+            decay_time: self.decay_time.into(),
+            fc: self.fc.into(),
+            isolation: self.isolation.into(),
+        }
+    }
+
+    /// Creates new reverb zone node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reverb_zone())
+    }
+
+    /// Creates new reverb zone node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+/// Audio portal is an opening (a doorway, a window, etc.) that connects two [`ReverbZone`]s. When
+/// a sound is emitted in one zone and the listener is in another, the sound will be heard as if
+/// it comes from the portal and its loudness is scaled by `transmission` of the portal.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct AudioPortal {
+    base: Base,
+
+    #[reflect(setter = "set_zone_a")]
+    zone_a: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_zone_b")]
+    zone_b: InheritableVariable<Handle<Node>>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_transmission")]
+    transmission: InheritableVariable<f32>,
+}
+
+impl Default for AudioPortal {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            zone_a: Default::default(),
+            zone_b: Default::default(),
+            transmission: InheritableVariable::new(0.7),
+        }
+    }
+}
+
+impl Deref for AudioPortal {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for AudioPortal {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for AudioPortal {
+    fn type_uuid() -> Uuid {
+        uuid!("9d6c7e15-0f43-4b6e-8b0a-4f5e0d6d7a21")
+    }
+}
+
+impl AudioPortal {
+    /// Sets the first zone connected by the portal.
+    pub fn set_zone_a(&mut self, zone: Handle<Node>) -> Handle<Node> {
+        self.zone_a.set(zone)
+    }
+
+    /// Returns the first zone connected by the portal.
+    pub fn zone_a(&self) -> Handle<Node> {
+        *self.zone_a
+    }
+
+    /// Sets the second zone connected by the portal.
+    pub fn set_zone_b(&mut self, zone: Handle<Node>) -> Handle<Node> {
+        self.zone_b.set(zone)
+    }
+
+    /// Returns the second zone connected by the portal.
+    pub fn zone_b(&self) -> Handle<Node> {
+        *self.zone_b
+    }
+
+    /// Sets how much of the sound passes through the portal. 0.0 - nothing, 1.0 - everything.
+    pub fn set_transmission(&mut self, transmission: f32) -> f32 {
+        self.transmission.set(transmission.clamp(0.0, 1.0))
+    }
+
+    /// Returns current transmission of the portal.
+    pub fn transmission(&self) -> f32 {
+        *self.transmission
+    }
+
+    /// Checks whether the portal connects the given zones.
+    pub fn connects(&self, a: Handle<Node>, b: Handle<Node>) -> bool {
+        self.zone_a() == a && self.zone_b() == b || self.zone_a() == b && self.zone_b() == a
+    }
+}
+
+impl NodeTrait for AudioPortal {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create audio portals in declarative manner.
+pub struct AudioPortalBuilder {
+    base_builder: BaseBuilder,
+    zone_a: Handle<Node>,
+    zone_b: Handle<Node>,
+    transmission: f32,
+}
+
+impl AudioPortalBuilder {
+    /// Creates new audio portal builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            zone_a: Default::default(),
+            zone_b: Default::default(),
+            transmission: 0.7,
+        }
+    }
+
+    /// Sets zones that will be connected by the portal.
+    pub fn with_zones(mut self, zone_a: Handle<Node>, zone_b: Handle<Node>) -> Self {
+        self.zone_a = zone_a;
+        self.zone_b = zone_b;
+        self
+    }
+
+    define_with!(
+        /// Sets desired transmission of the portal.
+        fn with_transmission(transmission: f32)
+    );
+
+    /// Creates new audio portal.
+    pub fn build_audio_portal(self) -> AudioPortal {
+        AudioPortal {
+            base: self.base_builder.build_base(),
+            zone_a: self.zone_a.into(),
+            zone_b: self.zone_b.into(),
+            transmission: self.transmission.into(),
+        }
+    }
+
+    /// Creates new audio portal node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_audio_portal())
+    }
+
+    /// Creates new audio portal node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+/// Returns the smallest zone that contains the given point.
+fn find_zone(zones: &[(Handle<Node>, &ReverbZone)], point: Vector3<f32>) -> Handle<Node> {
+    zones
+        .iter()
+        .filter(|(_, zone)| zone.contains(point))
+        .min_by(|(_, a), (_, b)| {
+            a.volume()
+                .partial_cmp(&b.volume())
+                .unwrap_or(Ordering::Equal)
+        })
+        .map(|(handle, _)| *handle)
+        .unwrap_or_default()
+}
+
+/// Environment reverb parameters, blended from multiple zones.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+struct ReverbParameters {
+    gain: f32,
+    wet: f32,
+    decay_time: f32,
+    fc: f32,
+}
+
+impl ReverbParameters {
+    fn blend(zones: &[(Handle<Node>, &ReverbZone)], point: Vector3<f32>) -> Self {
+        let mut total_weight = 0.0;
+        let mut blended = ReverbParameters::default();
+        for (_, zone) in zones {
+            let weight = zone.weight(point);
+            if weight > 0.0 {
+                total_weight += weight;
+                blended.gain += zone.reverb_gain() * weight;
+                blended.wet += zone.wet() * weight;
+                blended.decay_time += zone.decay_time() * weight;
+                blended.fc += zone.fc() * weight;
+            }
+        }
+
+        if total_weight <= 0.0 {
+            return Default::default();
+        }
+
+        // Overlapping zones are averaged, single zone fades out to silence.
+        let fade = total_weight.min(1.0);
+        ReverbParameters {
+            gain: blended.gain / total_weight * fade,
+            wet: blended.wet / total_weight,
+            decay_time: blended.decay_time / total_weight,
+            fc: blended.fc / total_weight,
+        }
+    }
+
+    fn approx_eq(&self, other: &Self) -> bool {
+        (self.gain - other.gain).abs() < 0.001
+            && (self.wet - other.wet).abs() < 0.001
+            && (self.decay_time - other.decay_time).abs() < 0.01
+            && (self.fc - other.fc).abs() < 0.001
+    }
+}
+
+/// Environment state of a sound context.
+#[derive(Default, Debug)]
+pub(crate) struct AudioEnvironment {
+    reverb: Handle<Effect>,
+    inputs: Vec<Handle<SoundSource>>,
+    parameters: ReverbParameters,
+    /// Sounds whose native sources were modified by portals.
+    modified_sounds: Vec<Handle<Node>>,
+}
+
+impl AudioEnvironment {
+    pub(crate) fn update(&mut self, nodes: &NodePool, state: &mut fyrox_sound::context::State) {
+        let zones = nodes
+            .pair_iter()
+            .filter_map(|(h, n)| n.cast::<ReverbZone>().map(|z| (h, z)))
+            .collect::<Vec<_>>();
+
+        if zones.is_empty() {
+            self.reset(nodes, state);
+            return;
+        }
+
+        let listener_position = state.listener().position();
+
+        // Route every 3D sound into the environment reverb.
+        let inputs = nodes
+            .iter()
+            .filter_map(|n| n.cast::<Sound>())
+            .filter(|s| s.spatial_blend() > 0.0 && s.native.get().is_some())
+            .map(|s| s.native.get())
+            .collect::<Vec<_>>();
+
+        if self.reverb.is_none() {
+            let mut reverb = Reverb::new(BaseEffect::default());
+            reverb.set_dry(0.0);
+            reverb.set_gain(0.0);
+            self.reverb = state.add_effect(Effect::Reverb(reverb));
+            self.parameters = Default::default();
+            self.inputs.clear();
+        }
+
+        let parameters = ReverbParameters::blend(&zones, listener_position);
+
+        if let Effect::Reverb(reverb) = state.effect_mut(self.reverb) {
+            if inputs != self.inputs {
+                reverb.clear_inputs();
+                for &input in inputs.iter() {
+                    reverb.add_input(EffectInput::direct(input));
+                }
+                self.inputs = inputs;
+            }
+
+            if !parameters.approx_eq(&self.parameters) {
+                reverb.set_gain(parameters.gain);
+                reverb.set_wet(parameters.wet);
+                reverb.set_fc(parameters.fc);
+                if (parameters.decay_time - self.parameters.decay_time).abs() >= 0.01 {
+                    reverb.set_decay_time(Duration::from_secs_f32(parameters.decay_time));
+                }
+                self.parameters = parameters;
+            }
+        }
+
+        self.apply_portals(nodes, &zones, listener_position, state);
+    }
+
+    fn apply_portals(
+        &mut self,
+        nodes: &NodePool,
+        zones: &[(Handle<Node>, &ReverbZone)],
+        listener_position: Vector3<f32>,
+        state: &mut fyrox_sound::context::State,
+    ) {
+        let listener_zone = find_zone(zones, listener_position);
+
+        let mut modified_sounds = Vec::new();
+        for (handle, sound) in nodes
+            .pair_iter()
+            .filter_map(|(h, n)| n.cast::<Sound>().map(|s| (h, s)))
+        {
+            let source = match state.try_get_source_mut(sound.native.get()) {
+                Some(source) => source,
+                None => continue,
+            };
+
+            let position = sound.global_position();
+            let sound_zone = find_zone(zones, position);
+
+            let (position, gain) = if sound.spatial_blend() <= 0.0
+                || sound_zone.is_none()
+                || listener_zone.is_none()
+                || sound_zone == listener_zone
+            {
+                (position, sound.gain())
+            } else if let Some(portal) = nodes
+                .iter()
+                .filter_map(|n| n.cast::<AudioPortal>())
+                .find(|p| p.connects(sound_zone, listener_zone))
+            {
+                // The sound is heard through the portal.
+                (
+                    portal.global_position(),
+                    sound.gain() * portal.transmission(),
+                )
+            } else {
+                let isolation = zones
+                    .iter()
+                    .find(|(h, _)| *h == sound_zone)
+                    .map_or(0.0, |(_, z)| z.isolation());
+                (position, sound.gain() * (1.0 - isolation))
+            };
+
+            let is_modified = position != sound.global_position() || gain != sound.gain();
+            if is_modified || self.modified_sounds.contains(&handle) {
+                source.set_position(position);
+                source.set_gain(gain);
+            }
+            if is_modified {
+                modified_sounds.push(handle);
+            }
+        }
+        self.modified_sounds = modified_sounds;
+    }
+
+    /// Removes environment reverb and restores sounds modified by portals.
+    fn reset(&mut self, nodes: &NodePool, state: &mut fyrox_sound::context::State) {
+        if self.reverb.is_some() {
+            state.remove_effect(self.reverb);
+            self.reverb = Handle::NONE;
+            self.inputs.clear();
+            self.parameters = Default::default();
+        }
+
+        for handle in self.modified_sounds.drain(..) {
+            if let Some(sound) = nodes.try_borrow(handle).and_then(|n| n.cast::<Sound>()) {
+                if let Some(source) = state.try_get_source_mut(sound.native.get()) {
+                    source.set_position(sound.global_position());
+                    source.set_gain(sound.gain());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            sound::environment::{ReverbParameters, ReverbZoneBuilder},
+        },
+    };
+
+    #[test]
+    fn test_reverb_zone_weight() {
+        let zone = ReverbZoneBuilder::new(BaseBuilder::new())
+            .with_size(Vector3::new(2.0, 2.0, 2.0))
+            .with_fade_distance(2.0)
+            .build_reverb_zone();
+
+        assert!(zone.contains(Vector3::new(0.5, 0.5, 0.5)));
+        assert!(!zone.contains(Vector3::new(2.0, 0.0, 0.0)));
+        assert_eq!(zone.weight(Vector3::new(0.5, 0.0, 0.0)), 1.0);
+        assert_eq!(zone.weight(Vector3::new(2.0, 0.0, 0.0)), 0.5);
+        assert_eq!(zone.weight(Vector3::new(4.0, 0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_reverb_parameters_crossfade() {
+        let zone = ReverbZoneBuilder::new(BaseBuilder::new())
+            .with_size(Vector3::new(2.0, 2.0, 2.0))
+            .with_fade_distance(2.0)
+            .with_reverb_gain(0.8)
+            .build_reverb_zone();
+        let zones = [(Default::default(), &zone)];
+
+        let inside = ReverbParameters::blend(&zones, Vector3::default());
+        assert_eq!(inside.gain, 0.8);
+        assert_eq!(inside.decay_time, zone.decay_time());
+
+        let fading = ReverbParameters::blend(&zones, Vector3::new(2.0, 0.0, 0.0));
+        assert_eq!(fading.gain, 0.4);
+
+        let outside = ReverbParameters::blend(&zones, Vector3::new(10.0, 0.0, 0.0));
+        assert_eq!(outside, ReverbParameters::default());
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
         pool::Handle,
         reflect::prelude::*,
         uuid::{uuid, Uuid},
+        variable::InheritableVariable,
         visitor::prelude::*,
     },
     engine::resource_manager::ResourceManager,
@@ -18,7 +19,30 @@ use crate::{
         node::{Node, NodeTrait, SyncContext, TypeUuidProvider},
     },
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines how a listener renders spatial sounds.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum HrtfMode {
+    /// Listener uses the renderer of the sound context.
+    Inherit,
+    /// Listener uses the default distance/pan model.
+    Disabled,
+    /// Listener uses HRTF with the HRIR sphere specified in the listener.
+    Enabled,
+}
+
+impl Default for HrtfMode {
+    fn default() -> Self {
+        Self::Inherit
+    }
+}
 
 /// Listener represents directional microphone-like device. It receives sound from surroundings
 /// and plays it through output device (headphones, speakers, etc.). Orientation of the listener
@@ -34,9 +58,23 @@ use std::ops::{Deref, DerefMut};
 ///
 /// 2D sound sources (with spatial blend == 0.0) are not influenced by listener's position and
 /// orientation.
+///
+/// # HRTF
+///
+/// Each listener can override the renderer of the sound context, see [`HrtfMode`]. This allows
+/// you, for example, to use HRTF for a first-person camera and the default renderer for a
+/// top-down camera. HRIR spheres can be found [here](https://github.com/mrDIMAS/hrir_sphere_builder/tree/master/hrtf_base/IRCAM).
 #[derive(Visit, Reflect, Default, Clone, Debug)]
 pub struct Listener {
     base: Base,
+
+    #[reflect(setter = "set_hrtf_mode")]
+    #[visit(optional)] // Backward compatibility
+    hrtf_mode: InheritableVariable<HrtfMode>,
+
+    #[reflect(setter = "set_hrir_sphere")]
+    #[visit(optional)] // Backward compatibility
+    hrir_sphere: InheritableVariable<PathBuf>,
 }
 
 impl Deref for Listener {
@@ -59,6 +97,28 @@ impl TypeUuidProvider for Listener {
     }
 }
 
+impl Listener {
+    /// Sets new HRTF mode of the listener.
+    pub fn set_hrtf_mode(&mut self, mode: HrtfMode) -> HrtfMode {
+        self.hrtf_mode.set(mode)
+    }
+
+    /// Returns current HRTF mode of the listener.
+    pub fn hrtf_mode(&self) -> HrtfMode {
+        *self.hrtf_mode
+    }
+
+    /// Sets a path to the HRIR sphere that will be used when HRTF mode is [`HrtfMode::Enabled`].
+    pub fn set_hrir_sphere(&mut self, path: PathBuf) -> PathBuf {
+        self.hrir_sphere.set(path)
+    }
+
+    /// Returns a path to the HRIR sphere of the listener.
+    pub fn hrir_sphere(&self) -> &Path {
+        &self.hrir_sphere
+    }
+}
+
 impl NodeTrait for Listener {
     crate::impl_query_component!();
 
@@ -88,24 +148,49 @@ impl NodeTrait for Listener {
         let native = state.listener_mut();
         native.set_position(self.global_position());
         native.set_basis(self.global_transform().basis());
+        drop(state);
+
+        context
+            .sound_context
+            .set_listener_hrtf(self.hrtf_mode(), self.hrir_sphere());
     }
 }
 
 /// Allows you to create listener in declarative manner.
 pub struct ListenerBuilder {
     base_builder: BaseBuilder,
+    hrtf_mode: HrtfMode,
+    hrir_sphere: PathBuf,
 }
 
 impl ListenerBuilder {
     /// Creates new listner builder.
     pub fn new(base_builder: BaseBuilder) -> Self {
-        Self { base_builder }
+        Self {
+            base_builder,
+            hrtf_mode: Default::default(),
+            hrir_sphere: Default::default(),
+        }
+    }
+
+    /// Sets desired HRTF mode of the listener.
+    pub fn with_hrtf_mode(mut self, mode: HrtfMode) -> Self {
+        self.hrtf_mode = mode;
+        self
+    }
+
+    /// Sets desired HRIR sphere of the listener.
+    pub fn with_hrir_sphere<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.hrir_sphere = path.as_ref().to_owned();
+        self
     }
 
     /// Creates listener instance.
     pub fn build_listener(self) -> Listener {
         Listener {
             base: self.base_builder.build_base(),
+            hrtf_mode: self.hrtf_mode.into(),
+            hrir_sphere: self.hrir_sphere.into(),
         }
     }
 
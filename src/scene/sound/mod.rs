@@ -30,6 +30,7 @@ pub use fyrox_sound::{
     hrtf::HrirSphere,
     renderer::{hrtf::HrtfRenderer, Renderer},
     source::Status,
+    voice::{StealPolicy, VoiceManager},
 };
 
 use fyrox_sound::source::SoundSource;
@@ -41,6 +42,7 @@ use std::{
 
 pub mod context;
 pub mod effect;
+pub mod environment;
 pub mod listener;
 
 /// Sound source.
@@ -90,6 +92,22 @@ pub struct Sound {
     #[reflect(setter = "set_spatial_blend")]
     spatial_blend: InheritableVariable<f32>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_priority")]
+    priority: InheritableVariable<u32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_bus")]
+    bus: InheritableVariable<String>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_max_instances")]
+    max_instances: InheritableVariable<Option<u32>>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_steal_policy")]
+    steal_policy: InheritableVariable<StealPolicy>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -125,6 +143,10 @@ impl Default for Sound {
             rolloff_factor: InheritableVariable::new(1.0),
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new(1.0),
+            priority: InheritableVariable::new(0),
+            bus: Default::default(),
+            max_instances: InheritableVariable::new(None),
+            steal_policy: Default::default(),
             native: Default::default(),
         }
     }
@@ -146,6 +168,10 @@ impl Clone for Sound {
             rolloff_factor: self.rolloff_factor.clone(),
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
+            priority: self.priority.clone(),
+            bus: self.bus.clone(),
+            max_instances: self.max_instances.clone(),
+            steal_policy: self.steal_policy.clone(),
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -324,6 +350,50 @@ impl Sound {
     pub fn max_distance(&self) -> f32 {
         *self.max_distance
     }
+
+    /// Sets priority of the sound. Sounds with lower priority are virtualized first when voice
+    /// limits are exceeded and [`StealPolicy::LowestPriority`] is used.
+    pub fn set_priority(&mut self, priority: u32) -> u32 {
+        self.priority.set(priority)
+    }
+
+    /// Returns priority of the sound.
+    pub fn priority(&self) -> u32 {
+        *self.priority
+    }
+
+    /// Sets name of a voice bus of the sound. Buses could have their own voice limits, see
+    /// [`VoiceManager::set_bus_limit`].
+    pub fn set_bus(&mut self, bus: String) -> String {
+        self.bus.set(bus)
+    }
+
+    /// Returns name of a voice bus of the sound.
+    pub fn bus(&self) -> &str {
+        &self.bus
+    }
+
+    /// Sets maximum amount of simultaneously playing instances of the sound (sounds with the
+    /// same buffer). `None` means no limit.
+    pub fn set_max_instances(&mut self, max_instances: Option<u32>) -> Option<u32> {
+        self.max_instances.set(max_instances)
+    }
+
+    /// Returns maximum amount of simultaneously playing instances of the sound.
+    pub fn max_instances(&self) -> Option<u32> {
+        *self.max_instances
+    }
+
+    /// Sets a policy that defines which instances will be virtualized when the amount of instances
+    /// exceeds [`Self::max_instances`].
+    pub fn set_steal_policy(&mut self, policy: StealPolicy) -> StealPolicy {
+        self.steal_policy.set(policy)
+    }
+
+    /// Returns steal policy of the sound.
+    pub fn steal_policy(&self) -> StealPolicy {
+        *self.steal_policy
+    }
 }
 
 impl NodeTrait for Sound {
@@ -394,6 +464,10 @@ pub struct SoundBuilder {
     rolloff_factor: f32,
     playback_time: Duration,
     spatial_blend: f32,
+    priority: u32,
+    bus: String,
+    max_instances: Option<u32>,
+    steal_policy: StealPolicy,
 }
 
 impl SoundBuilder {
@@ -413,6 +487,10 @@ impl SoundBuilder {
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
             playback_time: Default::default(),
+            priority: 0,
+            bus: Default::default(),
+            max_instances: None,
+            steal_policy: Default::default(),
         }
     }
 
@@ -476,6 +554,26 @@ impl SoundBuilder {
         fn with_playback_time(playback_time: Duration)
     );
 
+    define_with!(
+        /// Sets desired priority. See [`Sound::set_priority`] for more info.
+        fn with_priority(priority: u32)
+    );
+
+    define_with!(
+        /// Sets desired voice bus. See [`Sound::set_bus`] for more info.
+        fn with_bus(bus: String)
+    );
+
+    define_with!(
+        /// Sets desired max instances. See [`Sound::set_max_instances`] for more info.
+        fn with_max_instances(max_instances: Option<u32>)
+    );
+
+    define_with!(
+        /// Sets desired steal policy. See [`Sound::set_steal_policy`] for more info.
+        fn with_steal_policy(steal_policy: StealPolicy)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -493,6 +591,10 @@ impl SoundBuilder {
             rolloff_factor: self.rolloff_factor.into(),
             playback_time: self.playback_time.into(),
             spatial_blend: self.spatial_blend.into(),
+            priority: self.priority.into(),
+            bus: self.bus.into(),
+            max_instances: self.max_instances.into(),
+            steal_policy: self.steal_policy.into(),
             native: Default::default(),
         }
     }
@@ -515,7 +617,7 @@ mod test {
     use crate::core::variable::try_inherit_properties;
     use crate::scene::{
         base::{test::check_inheritable_properties_equality, BaseBuilder},
-        sound::{Sound, SoundBuilder},
+        sound::{Sound, SoundBuilder, StealPolicy},
     };
     use fyrox_sound::source::Status;
     use std::time::Duration;
@@ -531,6 +633,10 @@ mod test {
             .with_looping(true)
             .with_play_once(true)
             .with_panning(0.1)
+            .with_priority(3)
+            .with_bus("sfx".to_owned())
+            .with_max_instances(Some(4))
+            .with_steal_policy(StealPolicy::Quietest)
             .build_node();
 
         let mut child = SoundBuilder::new(BaseBuilder::new()).build_sound();
@@ -0,0 +1,584 @@
+//! Spline is a smooth curve defined by a set of control points. It is useful for camera rails,
+//! moving platforms, road generation, etc. See [`Spline`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+    },
+};
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Amount of linear steps per segment that is used to calculate length of a spline.
+const LENGTH_STEPS: usize = 16;
+
+/// Defines how a spline is interpolated between control points.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum SplineKind {
+    /// Catmull-Rom spline passes through every control point, tangents are calculated
+    /// automatically from the neighbour points.
+    CatmullRom,
+    /// Cubic Bezier spline passes through every control point, tangents are defined by the
+    /// [`SplinePoint::tangent`] handles.
+    Bezier,
+}
+
+impl Default for SplineKind {
+    fn default() -> Self {
+        Self::CatmullRom
+    }
+}
+
+/// Control point of a spline.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct SplinePoint {
+    /// Position of the point in local coordinates of the spline.
+    pub position: Vector3<f32>,
+    /// Tangent handle of the point, it is used only by Bezier splines. The curve leaves the point
+    /// towards `position + tangent` and enters it from `position - tangent`.
+    pub tangent: Vector3<f32>,
+    /// Rotation (in radians) around the tangent of the spline at the point.
+    pub roll: f32,
+    /// Width of the spline at the point (for example - width of a road).
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub width: f32,
+}
+
+impl Default for SplinePoint {
+    fn default() -> Self {
+        Self::new(Vector3::default())
+    }
+}
+
+impl SplinePoint {
+    /// Creates a new control point at the given position with no roll and unit width.
+    pub fn new(position: Vector3<f32>) -> Self {
+        Self {
+            position,
+            tangent: Vector3::default(),
+            roll: 0.0,
+            width: 1.0,
+        }
+    }
+}
+
+/// Interpolated state of a spline at some point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplineSample {
+    /// Position of the point, in local space of the spline or in world space (see
+    /// [`Spline::sample_global`]).
+    pub position: Vector3<f32>,
+    /// Normalized direction of the spline. Could be zero for degenerate splines.
+    pub tangent: Vector3<f32>,
+    /// Interpolated roll (in radians) of the control points around the tangent.
+    pub roll: f32,
+    /// Interpolated width of the control points.
+    pub width: f32,
+}
+
+impl SplineSample {
+    /// Returns rotation, which local Z axis is directed along the tangent, and local Y axis is
+    /// directed up (`+Y`) rotated by the roll. It could be used to orient objects that move along
+    /// the spline.
+    pub fn orientation(&self) -> UnitQuaternion<f32> {
+        let roll = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), self.roll);
+        if self.tangent.norm_squared() <= f32::EPSILON {
+            return roll;
+        }
+
+        let up = if self.tangent.cross(&Vector3::y()).norm_squared() > f32::EPSILON {
+            Vector3::y()
+        } else {
+            Vector3::z()
+        };
+        UnitQuaternion::face_towards(&self.tangent, &up) * roll
+    }
+}
+
+/// Spline is a smooth curve defined by a set of control points (see [`SplinePoint`]). The curve
+/// could be interpolated using Catmull-Rom or Bezier segments (see [`SplineKind`]) and it could be
+/// closed. Besides position, every control point defines roll and width, they're interpolated
+/// linearly along the curve.
+///
+/// Every segment of the spline (between two adjacent control points) takes the same range of the
+/// parameter `t`, so the parameter is not proportional to the distance along the spline. Use
+/// [`Spline::t_at_distance`] to move along the spline with constant speed.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector3,
+///     scene::{
+///         base::BaseBuilder,
+///         graph::Graph,
+///         spline::{Spline, SplineBuilder, SplinePoint},
+///     },
+/// };
+///
+/// fn platform_position(graph: &mut Graph, distance: f32) -> Option<Vector3<f32>> {
+///     let handle = SplineBuilder::new(BaseBuilder::new())
+///         .with_points(vec![
+///             SplinePoint::new(Vector3::new(0.0, 0.0, 0.0)),
+///             SplinePoint::new(Vector3::new(5.0, 2.0, 0.0)),
+///             SplinePoint::new(Vector3::new(10.0, 0.0, 0.0)),
+///         ])
+///         .build(graph);
+///
+///     let spline = graph[handle].cast::<Spline>().unwrap();
+///     spline
+///         .sample_global(spline.t_at_distance(distance))
+///         .map(|sample| sample.position)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Spline {
+    base: Base,
+
+    #[reflect(setter = "set_points")]
+    points: InheritableVariable<Vec<SplinePoint>>,
+
+    #[reflect(setter = "set_kind")]
+    kind: InheritableVariable<SplineKind>,
+
+    #[reflect(setter = "set_closed")]
+    closed: InheritableVariable<bool>,
+}
+
+impl Default for Spline {
+    fn default() -> Self {
+        SplineBuilder::new(BaseBuilder::new()).build_spline()
+    }
+}
+
+impl Deref for Spline {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Spline {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Spline {
+    fn type_uuid() -> Uuid {
+        uuid!("9c3f6a2e-4b71-4d8a-b5e0-7f2d1c8a6e43")
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+impl Spline {
+    /// Sets new control points of the spline. Returns previous points.
+    pub fn set_points(&mut self, points: Vec<SplinePoint>) -> Vec<SplinePoint> {
+        self.points.set(points)
+    }
+
+    /// Returns control points of the spline.
+    pub fn points(&self) -> &[SplinePoint] {
+        &self.points
+    }
+
+    /// Inserts a control point at the given index (or at the end, if the index is out of
+    /// bounds).
+    pub fn insert_point(&mut self, index: usize, point: SplinePoint) {
+        let points = self.points.get_mut();
+        let index = index.min(points.len());
+        points.insert(index, point);
+    }
+
+    /// Removes a control point at the given index.
+    pub fn remove_point(&mut self, index: usize) -> Option<SplinePoint> {
+        if index < self.points.len() {
+            Some(self.points.get_mut().remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Sets interpolation kind of the spline. Returns previous kind.
+    pub fn set_kind(&mut self, kind: SplineKind) -> SplineKind {
+        self.kind.set(kind)
+    }
+
+    /// Returns interpolation kind of the spline.
+    pub fn kind(&self) -> SplineKind {
+        *self.kind
+    }
+
+    /// Defines whether the last point of the spline should be connected with the first one.
+    /// Returns previous value.
+    pub fn set_closed(&mut self, closed: bool) -> bool {
+        self.closed.set(closed)
+    }
+
+    /// Returns `true` if the last point of the spline is connected with the first one.
+    pub fn is_closed(&self) -> bool {
+        *self.closed
+    }
+
+    /// Returns amount of segments of the spline.
+    pub fn segment_count(&self) -> usize {
+        let count = self.points.len();
+        if count < 2 {
+            0
+        } else if *self.closed {
+            count
+        } else {
+            count - 1
+        }
+    }
+
+    fn point(&self, index: isize) -> &SplinePoint {
+        let count = self.points.len() as isize;
+        let index = if *self.closed {
+            index.rem_euclid(count)
+        } else {
+            index.clamp(0, count - 1)
+        };
+        &self.points[index as usize]
+    }
+
+    /// Samples the given segment at the given local parameter (in `[0; 1]` range). Returns local
+    /// position and local non-normalized derivative.
+    fn sample_segment(&self, segment: usize, t: f32) -> (Vector3<f32>, Vector3<f32>) {
+        let i = segment as isize;
+        let p1 = self.point(i);
+        let p2 = self.point(i + 1);
+
+        match *self.kind {
+            SplineKind::CatmullRom => {
+                let p0 = self.point(i - 1).position;
+                let p3 = self.point(i + 2).position;
+                let (p1, p2) = (p1.position, p2.position);
+
+                let a = p1 * 2.0;
+                let b = p2 - p0;
+                let c = p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3;
+                let d = -p0 + p1 * 3.0 - p2 * 3.0 + p3;
+
+                let position = (a + b * t + c * (t * t) + d * (t * t * t)) * 0.5;
+                let derivative = (b + c * (2.0 * t) + d * (3.0 * t * t)) * 0.5;
+                (position, derivative)
+            }
+            SplineKind::Bezier => {
+                let b0 = p1.position;
+                let b1 = p1.position + p1.tangent;
+                let b2 = p2.position - p2.tangent;
+                let b3 = p2.position;
+
+                let s = 1.0 - t;
+                let position = b0 * (s * s * s)
+                    + b1 * (3.0 * s * s * t)
+                    + b2 * (3.0 * s * t * t)
+                    + b3 * (t * t * t);
+                let derivative = (b1 - b0) * (3.0 * s * s)
+                    + (b2 - b1) * (6.0 * s * t)
+                    + (b3 - b2) * (3.0 * t * t);
+                (position, derivative)
+            }
+        }
+    }
+
+    /// Converts the global parameter into a segment index and the parameter in the segment.
+    fn locate(&self, t: f32) -> Option<(usize, f32)> {
+        let count = self.segment_count();
+        if count == 0 {
+            return None;
+        }
+
+        let t = if *self.closed {
+            t.rem_euclid(1.0)
+        } else {
+            t.clamp(0.0, 1.0)
+        };
+        let scaled = t * count as f32;
+        let segment = (scaled as usize).min(count - 1);
+        Some((segment, scaled - segment as f32))
+    }
+
+    /// Samples the spline in its local coordinates at the given parameter `t` in `[0; 1]` range
+    /// (it is wrapped for closed splines and clamped otherwise). Returns `None` if the spline has
+    /// less than two control points.
+    pub fn sample(&self, t: f32) -> Option<SplineSample> {
+        let (segment, local_t) = self.locate(t)?;
+        let (position, derivative) = self.sample_segment(segment, local_t);
+
+        let i = segment as isize;
+        let p1 = self.point(i);
+        let p2 = self.point(i + 1);
+
+        Some(SplineSample {
+            position,
+            tangent: derivative.try_normalize(f32::EPSILON).unwrap_or_default(),
+            roll: lerp(p1.roll, p2.roll, local_t),
+            width: lerp(p1.width, p2.width, local_t),
+        })
+    }
+
+    /// Samples the spline at the given parameter `t` (see [`Self::sample`]) in world coordinates.
+    pub fn sample_global(&self, t: f32) -> Option<SplineSample> {
+        let transform = self.global_transform();
+        self.sample(t).map(|sample| SplineSample {
+            position: transform
+                .transform_point(&Point3::from(sample.position))
+                .coords,
+            tangent: transform
+                .transform_vector(&sample.tangent)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default(),
+            ..sample
+        })
+    }
+
+    /// Returns local position of the spline at the given parameter `t` (see [`Self::sample`]).
+    pub fn position(&self, t: f32) -> Option<Vector3<f32>> {
+        self.sample(t).map(|sample| sample.position)
+    }
+
+    /// Returns normalized local tangent of the spline at the given parameter `t` (see
+    /// [`Self::sample`]).
+    pub fn tangent(&self, t: f32) -> Option<Vector3<f32>> {
+        self.sample(t).map(|sample| sample.tangent)
+    }
+
+    /// Returns a polyline that approximates the spline in local coordinates, every segment is split
+    /// in the given amount of steps.
+    pub fn polyline(&self, steps_per_segment: usize) -> Vec<Vector3<f32>> {
+        let count = self.segment_count();
+        let steps_per_segment = steps_per_segment.max(1);
+        let mut polyline = Vec::with_capacity(count * steps_per_segment + 1);
+        for segment in 0..count {
+            for step in 0..steps_per_segment {
+                let t = step as f32 / steps_per_segment as f32;
+                polyline.push(self.sample_segment(segment, t).0);
+            }
+        }
+        if count > 0 {
+            polyline.push(self.sample_segment(count - 1, 1.0).0);
+        }
+        polyline
+    }
+
+    /// Returns approximate length of the spline in local coordinates.
+    pub fn length(&self) -> f32 {
+        self.polyline(LENGTH_STEPS)
+            .windows(2)
+            .map(|pair| pair[0].metric_distance(&pair[1]))
+            .sum()
+    }
+
+    /// Returns the parameter `t` at the given distance (in local coordinates) along the spline from
+    /// its start. The distance is wrapped for closed splines and clamped otherwise.
+    pub fn t_at_distance(&self, distance: f32) -> f32 {
+        let polyline = self.polyline(LENGTH_STEPS);
+        if polyline.len() < 2 {
+            return 0.0;
+        }
+
+        let length = self.length();
+        if length <= f32::EPSILON {
+            return 0.0;
+        }
+
+        let mut distance = if *self.closed {
+            distance.rem_euclid(length)
+        } else {
+            distance.clamp(0.0, length)
+        };
+
+        let step_count = polyline.len() - 1;
+        for (i, pair) in polyline.windows(2).enumerate() {
+            let step_length = pair[0].metric_distance(&pair[1]);
+            if distance <= step_length {
+                let fraction = if step_length > f32::EPSILON {
+                    distance / step_length
+                } else {
+                    0.0
+                };
+                return (i as f32 + fraction) / step_count as f32;
+            }
+            distance -= step_length;
+        }
+
+        1.0
+    }
+}
+
+impl NodeTrait for Spline {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.points.is_empty() {
+            self.base.local_bounding_box()
+        } else {
+            let mut points = self.polyline(LENGTH_STEPS);
+            points.extend(self.points.iter().map(|p| p.position));
+            AxisAlignedBoundingBox::from_points(&points)
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a spline in a declarative manner.
+pub struct SplineBuilder {
+    base_builder: BaseBuilder,
+    points: Vec<SplinePoint>,
+    kind: SplineKind,
+    closed: bool,
+}
+
+impl SplineBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            points: Default::default(),
+            kind: Default::default(),
+            closed: false,
+        }
+    }
+
+    /// Sets desired control points.
+    pub fn with_points(mut self, points: Vec<SplinePoint>) -> Self {
+        self.points = points;
+        self
+    }
+
+    /// Sets desired interpolation kind.
+    pub fn with_kind(mut self, kind: SplineKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets whether the spline is closed or not.
+    pub fn with_closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// Creates new spline.
+    pub fn build_spline(self) -> Spline {
+        Spline {
+            base: self.base_builder.build_base(),
+            points: self.points.into(),
+            kind: self.kind.into(),
+            closed: self.closed.into(),
+        }
+    }
+
+    /// Creates new spline node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_spline())
+    }
+
+    /// Creates new instance of spline node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            spline::{SplineBuilder, SplineKind, SplinePoint},
+        },
+    };
+
+    fn line() -> Vec<SplinePoint> {
+        vec![
+            SplinePoint::new(Vector3::new(0.0, 0.0, 0.0)),
+            SplinePoint {
+                width: 3.0,
+                ..SplinePoint::new(Vector3::new(1.0, 0.0, 0.0))
+            },
+            SplinePoint::new(Vector3::new(2.0, 0.0, 0.0)),
+        ]
+    }
+
+    #[test]
+    fn test_catmull_rom_spline() {
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_points(line())
+            .build_spline();
+
+        // The spline passes through control points.
+        assert_eq!(spline.position(0.0), Some(Vector3::new(0.0, 0.0, 0.0)));
+        assert_eq!(spline.position(0.5), Some(Vector3::new(1.0, 0.0, 0.0)));
+        assert_eq!(spline.position(1.0), Some(Vector3::new(2.0, 0.0, 0.0)));
+
+        let sample = spline.sample(0.25).unwrap();
+        assert!((sample.tangent - Vector3::x()).norm() < 1.0e-5);
+        assert!((sample.width - 2.0).abs() < 1.0e-5);
+
+        assert!((spline.length() - 2.0).abs() < 1.0e-3);
+        assert!((spline.t_at_distance(1.0) - 0.5).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_bezier_spline() {
+        let mut points = line();
+        for point in points.iter_mut() {
+            point.tangent = Vector3::new(0.0, 1.0, 0.0);
+        }
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_points(points)
+            .with_kind(SplineKind::Bezier)
+            .build_spline();
+
+        // Endpoints of segments are control points, tangents follow the handles.
+        assert_eq!(spline.position(0.5), Some(Vector3::new(1.0, 0.0, 0.0)));
+        assert!((spline.tangent(0.0).unwrap() - Vector3::y()).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_closed_spline() {
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_points(line())
+            .with_closed(true)
+            .build_spline();
+
+        assert_eq!(spline.segment_count(), 3);
+        assert_eq!(spline.position(1.0), spline.position(0.0));
+        assert!(SplineBuilder::new(BaseBuilder::new())
+            .build_spline()
+            .sample(0.5)
+            .is_none());
+    }
+}
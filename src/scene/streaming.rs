@@ -0,0 +1,332 @@
+//! Scene streaming allows you to load multiple scenes additively into one scene at runtime and
+//! to unload them when they're not needed anymore. It is useful for open-world games, where a
+//! world is split into chunks (separate scenes) that are loaded and unloaded as the player moves.
+//! See [`SceneStreamer`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::{resource_manager::ResourceManager, SerializationContext},
+    scene::{
+        base::{BaseBuilder, InstanceId},
+        graph::Graph,
+        loader::{AsyncSceneLoader, SceneLoadingProgress},
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    utils::log::Log,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Copies every node of the `source` scene into the `dest` scene. Copied nodes are attached to a
+/// new pivot node (chunk root) placed at the given position and rotation, so the whole content of
+/// the source scene is offset by the anchor transform. Returns a handle of the chunk root, remove
+/// it to unload the content.
+///
+/// Only the graph is copied, scene-wide data of the source scene (navmeshes, lightmap, etc.)
+/// is ignored. Instance ids of the nodes are preserved, so nodes could be found using
+/// [`Graph::find_by_instance_id`] or [`InstanceRef`].
+pub fn merge_additive(
+    dest: &mut Scene,
+    source: &Scene,
+    name: &str,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+) -> Handle<Node> {
+    let chunk_root = PivotBuilder::new(
+        BaseBuilder::new().with_name(name).with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .with_local_rotation(rotation)
+                .build(),
+        ),
+    )
+    .build(&mut dest.graph);
+
+    for &child in source.graph[source.graph.get_root()].children() {
+        let (copy, _) = source
+            .graph
+            .copy_node(child, &mut dest.graph, &mut |_, _| true);
+        dest.graph.link_nodes(copy, chunk_root);
+    }
+
+    chunk_root
+}
+
+/// A reference to a node by its instance id. Unlike handles, instance ids are stable across
+/// additively loaded scenes, so the reference could point to a node from another chunk, that is
+/// not loaded yet. The handle is cached, so the search is performed only when the cached handle
+/// becomes invalid.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Reflect)]
+pub struct InstanceRef {
+    /// Instance id of the referenced node.
+    pub instance_id: InstanceId,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    handle: Handle<Node>,
+}
+
+impl InstanceRef {
+    /// Creates new reference to a node with the given instance id.
+    pub fn new(instance_id: InstanceId) -> Self {
+        Self {
+            instance_id,
+            handle: Default::default(),
+        }
+    }
+
+    /// Tries to find the referenced node in the graph. Returns `Handle::NONE` if the node does
+    /// not exist (for example, if a chunk with the node is not loaded yet).
+    pub fn resolve(&mut self, graph: &Graph) -> Handle<Node> {
+        if graph
+            .try_get(self.handle)
+            .map_or(true, |node| node.instance_id() != self.instance_id)
+        {
+            self.handle = graph.find_by_instance_id(self.instance_id);
+        }
+        self.handle
+    }
+}
+
+enum ChunkState {
+    Loading(AsyncSceneLoader),
+    Loaded(Handle<Node>),
+    Failed(String),
+}
+
+/// A scene, that is (or will be) additively loaded into another scene.
+pub struct StreamedChunk {
+    path: PathBuf,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    state: ChunkState,
+}
+
+impl StreamedChunk {
+    /// Returns a path of the scene of the chunk.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` if the chunk is still loading.
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, ChunkState::Loading(_))
+    }
+
+    /// Returns a handle of the chunk root in the target scene, or `Handle::NONE` if the chunk
+    /// is not loaded yet.
+    pub fn root(&self) -> Handle<Node> {
+        match self.state {
+            ChunkState::Loaded(root) => root,
+            _ => Handle::NONE,
+        }
+    }
+
+    /// Returns loading progress of the chunk, `None` if the chunk is not loading.
+    pub fn progress(&self) -> Option<SceneLoadingProgress> {
+        match self.state {
+            ChunkState::Loading(ref loader) => Some(loader.progress()),
+            _ => None,
+        }
+    }
+
+    /// Returns loading error, if any.
+    pub fn error(&self) -> Option<&str> {
+        match self.state {
+            ChunkState::Failed(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Scene streamer loads scenes (chunks) in background and merges them into a target scene once
+/// they're loaded. Content of each chunk is offset by an anchor transform.
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::{UnitQuaternion, Vector3}, pool::Handle},
+///     plugin::PluginContext,
+///     scene::{streaming::{SceneStreamer, StreamedChunk}, Scene},
+/// };
+///
+/// fn load_chunk(
+///     streamer: &mut SceneStreamer,
+///     context: &PluginContext,
+///     x: f32,
+///     z: f32,
+/// ) -> Handle<StreamedChunk> {
+///     streamer.load(
+///         "data/chunks/forest.rgs",
+///         Vector3::new(x, 0.0, z),
+///         UnitQuaternion::identity(),
+///         context.serialization_context.clone(),
+///         context.resource_manager.clone(),
+///     )
+/// }
+///
+/// // Must be called every frame.
+/// fn update_streaming(streamer: &mut SceneStreamer, world: &mut Scene) {
+///     streamer.update(world);
+/// }
+/// ```
+#[derive(Default)]
+pub struct SceneStreamer {
+    chunks: Pool<StreamedChunk>,
+}
+
+impl SceneStreamer {
+    /// Creates new scene streamer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begins loading of a scene at the given path. The scene will be merged into a target scene
+    /// on [`Self::update`] once it is loaded.
+    pub fn load<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+        serialization_context: Arc<SerializationContext>,
+        resource_manager: ResourceManager,
+    ) -> Handle<StreamedChunk> {
+        let path = path.as_ref().to_path_buf();
+        let loader =
+            AsyncSceneLoader::begin_loading(path.clone(), serialization_context, resource_manager);
+        self.chunks.spawn(StreamedChunk {
+            path,
+            position,
+            rotation,
+            state: ChunkState::Loading(loader),
+        })
+    }
+
+    /// Unloads the chunk. If the chunk is still loading, its loading result will be discarded.
+    /// Nodes of the chunk are removed from the scene, resources used only by the chunk will be
+    /// freed by the resource manager when they're not used anymore. Returns `false` if the
+    /// handle is invalid.
+    pub fn unload(&mut self, chunk: Handle<StreamedChunk>, scene: &mut Scene) -> bool {
+        if !self.chunks.is_valid_handle(chunk) {
+            return false;
+        }
+
+        if let ChunkState::Loaded(root) = self.chunks.free(chunk).state {
+            if scene.graph.is_valid_handle(root) {
+                scene.graph.remove_node(root);
+            }
+        }
+
+        true
+    }
+
+    /// Merges every loaded chunk into the given scene. Must be called every frame, so the chunks
+    /// will appear in the scene as soon as possible.
+    pub fn update(&mut self, scene: &mut Scene) {
+        for chunk in self.chunks.iter_mut() {
+            if let ChunkState::Loading(ref loader) = chunk.state {
+                if let Some(result) = loader.fetch_result() {
+                    chunk.state = match result {
+                        Ok(chunk_scene) => {
+                            let name = chunk
+                                .path
+                                .file_stem()
+                                .map(|s| s.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            ChunkState::Loaded(merge_additive(
+                                scene,
+                                &chunk_scene,
+                                &name,
+                                chunk.position,
+                                chunk.rotation,
+                            ))
+                        }
+                        Err(err) => {
+                            Log::err(err.clone());
+                            ChunkState::Failed(err)
+                        }
+                    };
+                }
+            }
+        }
+    }
+
+    /// Returns a reference to a chunk.
+    pub fn chunk(&self, chunk: Handle<StreamedChunk>) -> Option<&StreamedChunk> {
+        self.chunks.try_borrow(chunk)
+    }
+
+    /// Returns an iterator over every chunk and its handle.
+    pub fn chunks(&self) -> impl Iterator<Item = (Handle<StreamedChunk>, &StreamedChunk)> {
+        self.chunks.pair_iter()
+    }
+
+    /// Unloads every chunk.
+    pub fn clear(&mut self, scene: &mut Scene) {
+        let handles = self.chunks.pair_iter().map(|(h, _)| h).collect::<Vec<_>>();
+        for handle in handles {
+            self.unload(handle, scene);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        scene::{
+            base::BaseBuilder,
+            pivot::PivotBuilder,
+            streaming::{merge_additive, InstanceRef},
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+
+    #[test]
+    fn test_merge_additive() {
+        let mut chunk = Scene::new();
+        let tree = PivotBuilder::new(
+            BaseBuilder::new().with_name("Tree").with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut chunk.graph);
+        let tree_id = chunk.graph[tree].instance_id();
+
+        let mut world = Scene::new();
+        let mut tree_ref = InstanceRef::new(tree_id);
+        assert!(tree_ref.resolve(&world.graph).is_none());
+
+        let root = merge_additive(
+            &mut world,
+            &chunk,
+            "Chunk",
+            Vector3::new(10.0, 0.0, 0.0),
+            UnitQuaternion::identity(),
+        );
+        world.graph.update_hierarchical_data();
+
+        let copy = tree_ref.resolve(&world.graph);
+        assert!(copy.is_some());
+        assert_eq!(world.graph[copy].parent(), root);
+        assert_eq!(world.graph[copy].name(), "Tree");
+        assert_eq!(
+            world.graph[copy].global_position(),
+            Vector3::new(11.0, 0.0, 0.0)
+        );
+
+        world.graph.remove_node(root);
+        assert!(tree_ref.resolve(&world.graph).is_none());
+    }
+}
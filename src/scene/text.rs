@@ -0,0 +1,836 @@
+//! Text in 3D space (damage numbers, name tags, world-space labels, etc.). See [`Text3D`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
+        pool::Handle,
+        rectpack::RectPacker,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    gui::ttf::{Font, FontBuilder},
+    lazy_static::lazy_static,
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureWrapMode},
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+    },
+};
+use fxhash::FxHashMap;
+use std::{
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut, Range},
+    sync::Arc,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Height (in pixels) of glyphs of the built-in font.
+const BUILT_IN_FONT_HEIGHT: f32 = 64.0;
+/// Spread (in pixels) of the distance field of the built-in font.
+const BUILT_IN_FONT_SPREAD: usize = 8;
+
+/// A glyph of a [`SdfFont`]. Metrics are in pixels of the source font and include the spread of
+/// the distance field.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SdfGlyph {
+    /// Horizontal offset of the glyph image from the pen position.
+    pub left: f32,
+    /// Vertical offset of the bottom of the glyph image from the baseline (`+Y` is up).
+    pub bottom: f32,
+    /// Width of the glyph image (including the distance field padding).
+    pub width: f32,
+    /// Height of the glyph image (including the distance field padding).
+    pub height: f32,
+    /// Horizontal distance to the next glyph.
+    pub advance: f32,
+    /// Rectangle of the glyph in the atlas in normalized texture coordinates, top edge of the
+    /// glyph is at `y`.
+    pub tex_coords: Rect<f32>,
+}
+
+/// A font with glyphs stored as a signed distance field. Such glyphs stay crisp at any scale and
+/// allow to draw outlines and glow in a shader. The atlas is made once, so changing text does
+/// not require any baking.
+///
+/// Each texel of the atlas stores the distance to the nearest edge of a glyph, remapped to
+/// `[0; 1]` range so `0.5` is the edge, values above it are inside the glyph, and `spread` pixels
+/// away from the edge the value reaches `0.0` (outside) or `1.0` (inside).
+pub struct SdfFont {
+    glyphs: FxHashMap<u32, SdfGlyph>,
+    height: f32,
+    ascender: f32,
+    spread: f32,
+    atlas: Texture,
+}
+
+impl Debug for SdfFont {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SdfFont {{ glyphs: {}, height: {}, spread: {} }}",
+            self.glyphs.len(),
+            self.height,
+            self.spread
+        )
+    }
+}
+
+/// Shared SDF font, see [`SdfFont`].
+pub type SharedSdfFont = Arc<SdfFont>;
+
+lazy_static! {
+    static ref BUILT_IN_FONT: SharedSdfFont = Arc::new(SdfFont::from_font(
+        &FontBuilder::new()
+            .with_height(BUILT_IN_FONT_HEIGHT)
+            .build_builtin()
+            .unwrap(),
+        Font::default_char_set(),
+        BUILT_IN_FONT_SPREAD,
+    ));
+}
+
+impl SdfFont {
+    /// Makes a distance field font from glyphs of the given characters of a font. The font should
+    /// have large height (48-64 pixels is good), `spread` defines the distance (in pixels) from
+    /// the edge of a glyph that is covered by the distance field, it limits maximum width of the
+    /// outline and glow.
+    pub fn from_font(font: &Font, char_set: &[Range<u32>], spread: usize) -> Self {
+        struct Image {
+            unicode: u32,
+            width: usize,
+            height: usize,
+            pixels: Vec<u8>,
+        }
+
+        let mut glyphs = FxHashMap::default();
+        let mut images = Vec::new();
+        for range in char_set {
+            for unicode in range.start..range.end {
+                if let Some(glyph) = font.glyph(unicode) {
+                    let (width, height) = if glyph.bitmap_width > 0 && glyph.bitmap_height > 0 {
+                        (
+                            glyph.bitmap_width + 2 * spread,
+                            glyph.bitmap_height + 2 * spread,
+                        )
+                    } else {
+                        (0, 0)
+                    };
+
+                    glyphs.insert(
+                        unicode,
+                        SdfGlyph {
+                            left: glyph.left - spread as f32,
+                            bottom: glyph.top - spread as f32,
+                            width: width as f32,
+                            height: height as f32,
+                            advance: glyph.advance,
+                            tex_coords: Default::default(),
+                        },
+                    );
+
+                    if width > 0 {
+                        images.push(Image {
+                            unicode,
+                            width,
+                            height,
+                            pixels: make_distance_field(
+                                &glyph.pixels,
+                                glyph.bitmap_width,
+                                glyph.bitmap_height,
+                                spread,
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Tallest first gives denser packing.
+        images.sort_by_key(|i| std::cmp::Reverse(i.height));
+
+        let area = images
+            .iter()
+            .map(|image| (image.width + 1) * (image.height + 1))
+            .sum::<usize>();
+        let mut atlas_size = ((area as f32).sqrt() as usize).next_power_of_two().max(1);
+        let placements = loop {
+            let mut packer = RectPacker::new(atlas_size, atlas_size);
+            let placements = images
+                .iter()
+                .map(|image| packer.find_free(image.width + 1, image.height + 1))
+                .collect::<Option<Vec<_>>>();
+            match placements {
+                Some(placements) => break placements,
+                None => atlas_size *= 2,
+            }
+        };
+
+        let mut atlas = vec![0u8; atlas_size * atlas_size];
+        let k = 1.0 / atlas_size as f32;
+        for (image, bounds) in images.iter().zip(placements) {
+            for row in 0..image.height {
+                let dest = (bounds.y() + row) * atlas_size + bounds.x();
+                let src = row * image.width;
+                atlas[dest..dest + image.width]
+                    .copy_from_slice(&image.pixels[src..src + image.width]);
+            }
+
+            if let Some(glyph) = glyphs.get_mut(&image.unicode) {
+                glyph.tex_coords = Rect::new(
+                    bounds.x() as f32 * k,
+                    bounds.y() as f32 * k,
+                    image.width as f32 * k,
+                    image.height as f32 * k,
+                );
+            }
+        }
+
+        let atlas = Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: atlas_size as u32,
+                height: atlas_size as u32,
+            },
+            TexturePixelKind::R8,
+            atlas,
+            false,
+        )
+        .unwrap();
+        {
+            let mut data = atlas.data_ref();
+            data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+            data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+        }
+
+        Self {
+            glyphs,
+            height: font.height(),
+            ascender: font.ascender(),
+            spread: spread as f32,
+            atlas,
+        }
+    }
+
+    /// Returns the built-in font, it has Basic Latin and Latin-1 Supplement characters.
+    pub fn built_in() -> SharedSdfFont {
+        BUILT_IN_FONT.clone()
+    }
+
+    /// Returns a glyph of the given character.
+    pub fn glyph(&self, unicode: u32) -> Option<&SdfGlyph> {
+        self.glyphs.get(&unicode)
+    }
+
+    /// Returns height (in pixels) of the source font.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Returns ascender (in pixels) of the source font.
+    pub fn ascender(&self) -> f32 {
+        self.ascender
+    }
+
+    /// Returns spread (in pixels) of the distance field.
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
+    /// Returns the atlas texture (single channel) with distance fields of every glyph.
+    pub fn atlas(&self) -> &Texture {
+        &self.atlas
+    }
+}
+
+/// Value used instead of infinity by the distance transform (infinity gives NaNs there).
+const FAR: f32 = 1.0e20;
+
+/// One-dimensional squared Euclidean distance transform (Felzenszwalb and Huttenlocher).
+fn distance_transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let n = f.len();
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = -FAR;
+    z[1] = FAR;
+    for q in 1..n {
+        let mut s;
+        loop {
+            let r = v[k];
+            s = ((f[q] + (q * q) as f32) - (f[r] + (r * r) as f32)) / (2 * q - 2 * r) as f32;
+            // z[0] is "minus infinity", so the first parabola is never removed.
+            if s <= z[k] && k > 0 {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = FAR;
+    }
+
+    k = 0;
+    for (q, distance) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let r = v[k];
+        *distance = (q as f32 - r as f32).powi(2) + f[r];
+    }
+}
+
+/// Two-dimensional squared Euclidean distance transform, `grid` contains `0.0` for feature
+/// pixels and [`FAR`] for the rest of them.
+fn distance_transform_2d(grid: &mut [f32], width: usize, height: usize) {
+    let n = width.max(height);
+    let mut f = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    let mut v = vec![0; n];
+    let mut z = vec![0.0; n + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        distance_transform_1d(&f[..height], &mut d[..height], &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+
+    for y in 0..height {
+        let row = &mut grid[y * width..(y + 1) * width];
+        f[..width].copy_from_slice(row);
+        distance_transform_1d(&f[..width], &mut d[..width], &mut v, &mut z);
+        row.copy_from_slice(&d[..width]);
+    }
+}
+
+/// Makes a distance field of a coverage bitmap, the result has `spread` pixels of padding at
+/// each side.
+fn make_distance_field(pixels: &[u8], width: usize, height: usize, spread: usize) -> Vec<u8> {
+    let padded_width = width + 2 * spread;
+    let padded_height = height + 2 * spread;
+
+    let mut inside = vec![false; padded_width * padded_height];
+    for y in 0..height {
+        for x in 0..width {
+            inside[(y + spread) * padded_width + x + spread] = pixels[y * width + x] >= 128;
+        }
+    }
+
+    let mut to_inside = inside
+        .iter()
+        .map(|&inside| if inside { 0.0 } else { FAR })
+        .collect::<Vec<_>>();
+    distance_transform_2d(&mut to_inside, padded_width, padded_height);
+
+    let mut to_outside = inside
+        .iter()
+        .map(|&inside| if inside { FAR } else { 0.0 })
+        .collect::<Vec<_>>();
+    distance_transform_2d(&mut to_outside, padded_width, padded_height);
+
+    inside
+        .iter()
+        .zip(to_inside.iter().zip(to_outside.iter()))
+        .map(|(&inside, (&to_inside, &to_outside))| {
+            // The edge is between pixel centers.
+            let distance = if inside {
+                to_outside.sqrt() - 0.5
+            } else {
+                0.5 - to_inside.sqrt()
+            };
+            let value = 0.5 + distance / (2.0 * spread.max(1) as f32);
+            (value.clamp(0.0, 1.0) * 255.0) as u8
+        })
+        .collect()
+}
+
+/// Defines how text is oriented in space.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum TextMode {
+    /// Text always faces the camera. Rotation and scale of the node are ignored.
+    Billboard,
+    /// Text is placed in the local XY plane of the node, `+X` axis is directed along lines and
+    /// `+Y` is up.
+    WorldOriented,
+}
+
+impl Default for TextMode {
+    fn default() -> Self {
+        Self::Billboard
+    }
+}
+
+/// Horizontal alignment of lines of a text relative to the node position.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum TextAlignment {
+    /// Lines start at the node position.
+    Left,
+    /// Lines are centered around the node position.
+    Center,
+    /// Lines end at the node position.
+    Right,
+}
+
+impl Default for TextAlignment {
+    fn default() -> Self {
+        Self::Center
+    }
+}
+
+/// A rectangle of a single glyph of a laid out text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphQuad {
+    /// Bottom-left corner of the glyph in the text plane.
+    pub min: Vector2<f32>,
+    /// Top-right corner of the glyph in the text plane.
+    pub max: Vector2<f32>,
+    /// Texture coordinates of the glyph in the font atlas.
+    pub tex_coords: Rect<f32>,
+}
+
+/// Text in 3D space rendered using a signed distance field font (see [`SdfFont`]), so it is
+/// crisp at any scale. The text could have an outline and glow, and it is either always facing
+/// the camera or placed in the XY plane of the node (see [`TextMode`]).
+///
+/// The text block is centered vertically at the position of the node, lines are aligned
+/// horizontally using [`TextAlignment`]. Text is laid out every frame, so changing it is cheap -
+/// it is fine to update damage numbers or timers every frame.
+///
+/// # Fonts
+///
+/// By default the built-in font is used, a custom font could be set using [`Self::set_font`].
+/// Fonts are not serialized, a loaded text will use the built-in font.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, color::Color, pool::Handle},
+///     scene::{
+///         base::BaseBuilder, graph::Graph, node::Node, text::Text3DBuilder,
+///         transform::TransformBuilder,
+///     },
+/// };
+///
+/// fn create_name_tag(graph: &mut Graph, name: &str) -> Handle<Node> {
+///     Text3DBuilder::new(
+///         BaseBuilder::new().with_local_transform(
+///             TransformBuilder::new()
+///                 .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+///                 .build(),
+///         ),
+///     )
+///     .with_text(name)
+///     .with_font_size(0.25)
+///     .with_outline(Color::BLACK, 0.05)
+///     .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct Text3D {
+    base: Base,
+
+    #[reflect(setter = "set_text")]
+    text: InheritableVariable<String>,
+
+    #[reflect(setter = "set_font_size", min_value = 0.0, step = 0.05)]
+    font_size: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_color")]
+    color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_outline_color")]
+    outline_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_outline_width", min_value = 0.0, step = 0.01)]
+    outline_width: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_glow_color")]
+    glow_color: InheritableVariable<Color>,
+
+    #[reflect(setter = "set_glow_width", min_value = 0.0, step = 0.01)]
+    glow_width: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_mode")]
+    mode: InheritableVariable<TextMode>,
+
+    #[reflect(setter = "set_alignment")]
+    alignment: InheritableVariable<TextAlignment>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    font: SharedSdfFont,
+}
+
+impl Deref for Text3D {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Text3D {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for Text3D {
+    fn default() -> Self {
+        Text3DBuilder::new(BaseBuilder::new()).build_text()
+    }
+}
+
+impl TypeUuidProvider for Text3D {
+    fn type_uuid() -> Uuid {
+        uuid!("5e1d8b4a-93c2-4f07-a6d8-2b7e0c9f41a6")
+    }
+}
+
+impl Text3D {
+    /// Sets new text, returns the old one.
+    pub fn set_text(&mut self, text: String) -> String {
+        self.text.set(text)
+    }
+
+    /// Returns current text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Sets height of a line of the text in local units.
+    pub fn set_font_size(&mut self, font_size: f32) -> f32 {
+        self.font_size.set(font_size.max(0.0))
+    }
+
+    /// Returns height of a line of the text in local units.
+    pub fn font_size(&self) -> f32 {
+        *self.font_size
+    }
+
+    /// Sets color of the glyphs.
+    pub fn set_color(&mut self, color: Color) -> Color {
+        self.color.set(color)
+    }
+
+    /// Returns color of the glyphs.
+    pub fn color(&self) -> Color {
+        *self.color
+    }
+
+    /// Sets color of the outline.
+    pub fn set_outline_color(&mut self, color: Color) -> Color {
+        self.outline_color.set(color)
+    }
+
+    /// Returns color of the outline.
+    pub fn outline_color(&self) -> Color {
+        *self.outline_color
+    }
+
+    /// Sets width of the outline relative to the font size, zero disables the outline. Sum of
+    /// the outline and glow widths is limited by the spread of the font (0.125 for the built-in
+    /// font).
+    pub fn set_outline_width(&mut self, width: f32) -> f32 {
+        self.outline_width.set(width.max(0.0))
+    }
+
+    /// Returns width of the outline relative to the font size.
+    pub fn outline_width(&self) -> f32 {
+        *self.outline_width
+    }
+
+    /// Sets color of the glow, the glow fades out from this color to full transparency.
+    pub fn set_glow_color(&mut self, color: Color) -> Color {
+        self.glow_color.set(color)
+    }
+
+    /// Returns color of the glow.
+    pub fn glow_color(&self) -> Color {
+        *self.glow_color
+    }
+
+    /// Sets width of the glow around the outline relative to the font size, zero disables the
+    /// glow.
+    pub fn set_glow_width(&mut self, width: f32) -> f32 {
+        self.glow_width.set(width.max(0.0))
+    }
+
+    /// Returns width of the glow relative to the font size.
+    pub fn glow_width(&self) -> f32 {
+        *self.glow_width
+    }
+
+    /// Sets orientation mode of the text.
+    pub fn set_mode(&mut self, mode: TextMode) -> TextMode {
+        self.mode.set(mode)
+    }
+
+    /// Returns orientation mode of the text.
+    pub fn mode(&self) -> TextMode {
+        *self.mode
+    }
+
+    /// Sets horizontal alignment of lines.
+    pub fn set_alignment(&mut self, alignment: TextAlignment) -> TextAlignment {
+        self.alignment.set(alignment)
+    }
+
+    /// Returns horizontal alignment of lines.
+    pub fn alignment(&self) -> TextAlignment {
+        *self.alignment
+    }
+
+    /// Sets new font, returns the old one.
+    pub fn set_font(&mut self, font: SharedSdfFont) -> SharedSdfFont {
+        std::mem::replace(&mut self.font, font)
+    }
+
+    /// Returns current font.
+    pub fn font(&self) -> &SharedSdfFont {
+        &self.font
+    }
+
+    /// Lays the text out and returns rectangles of its glyphs in the text plane (in local units).
+    /// Characters without glyphs in the font are skipped.
+    pub fn glyph_quads(&self) -> Vec<GlyphQuad> {
+        let font = &*self.font;
+        let scale = if font.height > 0.0 {
+            *self.font_size / font.height
+        } else {
+            0.0
+        };
+
+        let line_count = self.text.lines().count().max(1);
+        let mut baseline = (line_count as f32 * font.height * 0.5 - font.ascender) * scale;
+
+        let mut quads = Vec::new();
+        for line in self.text.lines() {
+            let line_width = line
+                .chars()
+                .filter_map(|c| font.glyph(c as u32))
+                .map(|glyph| glyph.advance)
+                .sum::<f32>()
+                * scale;
+            let mut x = match *self.alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => -line_width * 0.5,
+                TextAlignment::Right => -line_width,
+            };
+
+            for glyph in line.chars().filter_map(|c| font.glyph(c as u32)) {
+                if glyph.width > 0.0 {
+                    let min = Vector2::new(x + glyph.left * scale, baseline + glyph.bottom * scale);
+                    quads.push(GlyphQuad {
+                        min,
+                        max: min + Vector2::new(glyph.width, glyph.height) * scale,
+                        tex_coords: glyph.tex_coords,
+                    });
+                }
+                x += glyph.advance * scale;
+            }
+
+            baseline -= font.height * scale;
+        }
+        quads
+    }
+}
+
+impl NodeTrait for Text3D {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let quads = self.glyph_quads();
+        if quads.is_empty() {
+            return AxisAlignedBoundingBox::from_radius(0.0);
+        }
+
+        let mut bounds = AxisAlignedBoundingBox::default();
+        for quad in quads {
+            bounds.add_point(Vector3::new(quad.min.x, quad.min.y, 0.0));
+            bounds.add_point(Vector3::new(quad.max.x, quad.max.y, 0.0));
+        }
+
+        match *self.mode {
+            // Billboards could be rotated in any way.
+            TextMode::Billboard => {
+                let radius = bounds.min.norm().max(bounds.max.norm());
+                AxisAlignedBoundingBox::from_radius(radius)
+            }
+            TextMode::WorldOriented => bounds,
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create [`Text3D`] nodes in declarative manner.
+pub struct Text3DBuilder {
+    base_builder: BaseBuilder,
+    text: String,
+    font_size: f32,
+    color: Color,
+    outline_color: Color,
+    outline_width: f32,
+    glow_color: Color,
+    glow_width: f32,
+    mode: TextMode,
+    alignment: TextAlignment,
+    font: Option<SharedSdfFont>,
+}
+
+impl Text3DBuilder {
+    /// Creates new builder with white text of 0.5 units height that faces the camera, without
+    /// outline and glow.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            text: Default::default(),
+            font_size: 0.5,
+            color: Color::WHITE,
+            outline_color: Color::BLACK,
+            outline_width: 0.0,
+            glow_color: Color::opaque(255, 200, 0),
+            glow_width: 0.0,
+            mode: Default::default(),
+            alignment: Default::default(),
+            font: None,
+        }
+    }
+
+    /// Sets desired text.
+    pub fn with_text<S: AsRef<str>>(mut self, text: S) -> Self {
+        self.text = text.as_ref().to_owned();
+        self
+    }
+
+    /// Sets desired height of a line in local units.
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Sets desired color of the glyphs.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets desired color and width of the outline, see [`Text3D::set_outline_width`].
+    pub fn with_outline(mut self, color: Color, width: f32) -> Self {
+        self.outline_color = color;
+        self.outline_width = width;
+        self
+    }
+
+    /// Sets desired color and width of the glow, see [`Text3D::set_glow_width`].
+    pub fn with_glow(mut self, color: Color, width: f32) -> Self {
+        self.glow_color = color;
+        self.glow_width = width;
+        self
+    }
+
+    /// Sets desired orientation mode.
+    pub fn with_mode(mut self, mode: TextMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets desired horizontal alignment of lines.
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets desired font, the built-in font is used by default.
+    pub fn with_font(mut self, font: SharedSdfFont) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    fn build_text(self) -> Text3D {
+        Text3D {
+            base: self.base_builder.build_base(),
+            text: self.text.into(),
+            font_size: self.font_size.max(0.0).into(),
+            color: self.color.into(),
+            outline_color: self.outline_color.into(),
+            outline_width: self.outline_width.max(0.0).into(),
+            glow_color: self.glow_color.into(),
+            glow_width: self.glow_width.max(0.0).into(),
+            mode: self.mode.into(),
+            alignment: self.alignment.into(),
+            font: self.font.unwrap_or_else(SdfFont::built_in),
+        }
+    }
+
+    /// Creates new text node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_text())
+    }
+
+    /// Creates new text node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scene::text::{distance_transform_2d, make_distance_field, FAR};
+
+    #[test]
+    fn test_distance_transform() {
+        let mut grid = vec![FAR; 5 * 3];
+        grid[7] = 0.0;
+        distance_transform_2d(&mut grid, 5, 3);
+        assert_eq!(grid[7], 0.0);
+        assert_eq!(grid[6], 1.0);
+        assert_eq!(grid[0], 5.0);
+        assert_eq!(grid[14], 5.0);
+    }
+
+    #[test]
+    fn test_distance_field() {
+        // 2x2 filled square in the middle of 4x4 bitmap.
+        #[rustfmt::skip]
+        let pixels = [
+            0, 0, 0, 0,
+            0, 255, 255, 0,
+            0, 255, 255, 0,
+            0, 0, 0, 0,
+        ];
+        let field = make_distance_field(&pixels, 4, 4, 2);
+        assert_eq!(field.len(), 8 * 8);
+        // Inside is above the edge value, outside is below it and fades out with distance.
+        assert!(field[3 * 8 + 3] > 128);
+        assert!(field[2 * 8 + 3] < 128);
+        assert!(field[0] < field[2 * 8 + 3]);
+    }
+}
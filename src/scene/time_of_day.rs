@@ -0,0 +1,489 @@
+//! Time of day controller animates the sun, sky and ambient lighting of a scene over a day. See
+//! [`TimeOfDay`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::{Base, BaseBuilder},
+        camera::Camera,
+        graph::Graph,
+        light::directional::DirectionalLight,
+        node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
+        sky::ProceduralSky,
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+/// Time of day controller moves the sun over the sky according to the current time, latitude
+/// and declination of the sun, and applies the result to the scene:
+///
+/// - The sky with the sun direction for the current time (see [`Self::current_sky`]) is copied to
+/// every camera that has a procedural sky (see [`Camera::set_procedural_sky`]), so every camera
+/// shows the same sky.
+/// - Rotation and color of the sun light (an optional directional light, see [`Self::set_sun`])
+/// follow the sun. The light must not be rotated by its parents.
+/// - Ambient lighting of the scene is set to the light of the sky, if
+/// [`Self::set_drive_ambient_lighting`] is enabled.
+///
+/// Coordinate system (see [`sun_direction`]): `+Y` points up, `+X` points east and `+Z` points
+/// south, so the sun rises at `+X` and at noon it is on the `+Z` side of the sky in the northern
+/// hemisphere.
+///
+/// Use [`ProceduralSky::bake`] with [`Self::current_sky`] to make an environment map of the
+/// current sky.
+#[derive(Debug, Visit, Clone, Reflect)]
+pub struct TimeOfDay {
+    base: Base,
+
+    #[reflect(setter = "set_time", min_value = 0.0, max_value = 24.0, step = 0.1)]
+    time: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_day_length", min_value = 0.0)]
+    day_length: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_latitude", min_value = -90.0, max_value = 90.0)]
+    latitude: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_declination", min_value = -23.44, max_value = 23.44)]
+    declination: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_sky")]
+    sky: InheritableVariable<ProceduralSky>,
+
+    #[reflect(setter = "set_sun")]
+    sun: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_drive_ambient_lighting")]
+    drive_ambient_lighting: InheritableVariable<bool>,
+
+    #[reflect(setter = "set_min_ambient_color")]
+    min_ambient_color: InheritableVariable<Color>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    current_sky: ProceduralSky,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    sun_color: Color,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    ambient_color: Color,
+
+    // Sky that was used to calculate colors, they're recalculated only when the sky changes.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    cached_sky: Option<ProceduralSky>,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        TimeOfDayBuilder::new(BaseBuilder::new()).build_time_of_day()
+    }
+}
+
+impl Deref for TimeOfDay {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for TimeOfDay {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for TimeOfDay {
+    fn type_uuid() -> Uuid {
+        uuid!("5b8e2c71-0d4f-4a3e-9c62-e1f7a4d8b093")
+    }
+}
+
+/// Calculates direction towards the sun for the given time (in hours), latitude and declination
+/// (in degrees). The direction is given in right-handed coordinates where `+X` points east, `+Y`
+/// points up and `+Z` points south (north is `-Z`).
+pub fn sun_direction(time: f32, latitude: f32, declination: f32) -> Vector3<f32> {
+    let hour_angle = (time - 12.0) / 24.0 * std::f32::consts::TAU;
+    let latitude = latitude.to_radians();
+    let declination = declination.to_radians();
+
+    let east = -declination.cos() * hour_angle.sin();
+    let north =
+        latitude.cos() * declination.sin() - latitude.sin() * declination.cos() * hour_angle.cos();
+    let up =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+
+    Vector3::new(east, up, -north)
+}
+
+impl TimeOfDay {
+    /// Sets current time in hours, it will be wrapped into `[0; 24)` range. Returns previous time.
+    pub fn set_time(&mut self, time: f32) -> f32 {
+        self.time.set(time.rem_euclid(24.0))
+    }
+
+    /// Returns current time in hours.
+    pub fn time(&self) -> f32 {
+        *self.time
+    }
+
+    /// Sets duration of a full day in seconds. Zero stops the time. Returns previous duration.
+    pub fn set_day_length(&mut self, day_length: f32) -> f32 {
+        self.day_length.set(day_length.max(0.0))
+    }
+
+    /// Returns duration of a full day in seconds.
+    pub fn day_length(&self) -> f32 {
+        *self.day_length
+    }
+
+    /// Sets latitude (in degrees) of the place. Returns previous latitude.
+    pub fn set_latitude(&mut self, latitude: f32) -> f32 {
+        self.latitude.set(latitude.clamp(-90.0, 90.0))
+    }
+
+    /// Returns latitude (in degrees) of the place.
+    pub fn latitude(&self) -> f32 {
+        *self.latitude
+    }
+
+    /// Sets declination of the sun (in degrees) - an angle between the sun and the celestial
+    /// equator. It defines the season: `23.44` is the summer solstice in the northern hemisphere,
+    /// `0.0` is an equinox, `-23.44` is the winter solstice. Returns previous declination.
+    pub fn set_declination(&mut self, declination: f32) -> f32 {
+        self.declination.set(declination)
+    }
+
+    /// Returns declination of the sun (in degrees).
+    pub fn declination(&self) -> f32 {
+        *self.declination
+    }
+
+    /// Sets atmosphere parameters of the sky. Sun direction of the sky is ignored, it is defined
+    /// by the time. Returns previous sky.
+    pub fn set_sky(&mut self, sky: ProceduralSky) -> ProceduralSky {
+        self.sky.set(sky)
+    }
+
+    /// Returns atmosphere parameters of the sky.
+    pub fn sky(&self) -> &ProceduralSky {
+        &self.sky
+    }
+
+    /// Returns the sky with the sun direction for the current time (as of the last update).
+    pub fn current_sky(&self) -> &ProceduralSky {
+        &self.current_sky
+    }
+
+    /// Sets a handle of a directional light, that will be used as the sun. Returns previous
+    /// handle.
+    pub fn set_sun(&mut self, sun: Handle<Node>) -> Handle<Node> {
+        self.sun.set(sun)
+    }
+
+    /// Returns a handle of the sun light.
+    pub fn sun(&self) -> Handle<Node> {
+        *self.sun
+    }
+
+    /// Defines whether the controller should set ambient lighting color of the scene or not.
+    /// Returns previous value.
+    pub fn set_drive_ambient_lighting(&mut self, drive: bool) -> bool {
+        self.drive_ambient_lighting.set(drive)
+    }
+
+    /// Returns `true` if the controller sets ambient lighting color of the scene.
+    pub fn is_driving_ambient_lighting(&self) -> bool {
+        *self.drive_ambient_lighting
+    }
+
+    /// Sets minimal ambient lighting color, that is used at night. Returns previous color.
+    pub fn set_min_ambient_color(&mut self, color: Color) -> Color {
+        self.min_ambient_color.set(color)
+    }
+
+    /// Returns minimal ambient lighting color.
+    pub fn min_ambient_color(&self) -> Color {
+        *self.min_ambient_color
+    }
+
+    /// Returns color of the sunlight (as of the last update).
+    pub fn sun_color(&self) -> Color {
+        self.sun_color
+    }
+
+    /// Returns ambient lighting color (as of the last update).
+    pub fn ambient_color(&self) -> Color {
+        self.ambient_color
+    }
+
+    fn update_sky(&mut self) {
+        let mut sky = (*self.sky).clone();
+        sky.sun_direction = sun_direction(*self.time, *self.latitude, *self.declination);
+
+        if self.cached_sky.as_ref() != Some(&sky) {
+            self.sun_color = sky.sun_color();
+            let ambient = sky.ambient_color();
+            let min = *self.min_ambient_color;
+            self.ambient_color = Color::opaque(
+                ambient.r.max(min.r),
+                ambient.g.max(min.g),
+                ambient.b.max(min.b),
+            );
+            self.cached_sky = Some(sky.clone());
+        }
+
+        self.current_sky = sky;
+    }
+}
+
+impl NodeTrait for TimeOfDay {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) -> bool {
+        if *self.day_length > 0.0 {
+            let time = *self.time + context.dt * 24.0 / *self.day_length;
+            self.time.set_silent(time.rem_euclid(24.0));
+        }
+
+        self.update_sky();
+
+        let sun_direction = self.current_sky.sun_direction;
+        if let Some(sun) = context.nodes.try_borrow_mut(*self.sun) {
+            if let Some(light) = sun.cast_mut::<DirectionalLight>() {
+                if light.base_light_ref().color() != self.sun_color {
+                    light.base_light_mut().set_color(self.sun_color);
+                }
+            }
+            // Directional lights shine along negative direction of their up vector.
+            let rotation = UnitQuaternion::rotation_between(&Vector3::y(), &sun_direction)
+                .unwrap_or_else(|| {
+                    UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+                });
+            if **sun.local_transform().rotation() != rotation {
+                sun.local_transform_mut().set_rotation(rotation);
+            }
+        }
+
+        for node in context.nodes.iter_mut() {
+            if let Some(camera) = node.cast_mut::<Camera>() {
+                if let Some(sky) = camera.procedural_sky_mut() {
+                    if *sky != self.current_sky {
+                        *sky = self.current_sky.clone();
+                    }
+                }
+            }
+        }
+
+        self.base.update_lifetime(context.dt)
+    }
+}
+
+/// Allows you to create a time of day controller in a declarative manner.
+pub struct TimeOfDayBuilder {
+    base_builder: BaseBuilder,
+    time: f32,
+    day_length: f32,
+    latitude: f32,
+    declination: f32,
+    sky: ProceduralSky,
+    sun: Handle<Node>,
+    drive_ambient_lighting: bool,
+    min_ambient_color: Color,
+}
+
+impl TimeOfDayBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            time: 12.0,
+            day_length: 1200.0,
+            latitude: 45.0,
+            declination: 0.0,
+            sky: Default::default(),
+            sun: Default::default(),
+            drive_ambient_lighting: true,
+            min_ambient_color: Color::opaque(10, 12, 20),
+        }
+    }
+
+    /// Sets desired time in hours.
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Sets desired duration of a full day in seconds.
+    pub fn with_day_length(mut self, day_length: f32) -> Self {
+        self.day_length = day_length;
+        self
+    }
+
+    /// Sets desired latitude in degrees.
+    pub fn with_latitude(mut self, latitude: f32) -> Self {
+        self.latitude = latitude;
+        self
+    }
+
+    /// Sets desired declination of the sun in degrees.
+    pub fn with_declination(mut self, declination: f32) -> Self {
+        self.declination = declination;
+        self
+    }
+
+    /// Sets desired atmosphere parameters.
+    pub fn with_sky(mut self, sky: ProceduralSky) -> Self {
+        self.sky = sky;
+        self
+    }
+
+    /// Sets desired sun light.
+    pub fn with_sun(mut self, sun: Handle<Node>) -> Self {
+        self.sun = sun;
+        self
+    }
+
+    /// Sets whether the controller should set ambient lighting of the scene or not.
+    pub fn with_drive_ambient_lighting(mut self, drive: bool) -> Self {
+        self.drive_ambient_lighting = drive;
+        self
+    }
+
+    /// Sets desired minimal ambient lighting color.
+    pub fn with_min_ambient_color(mut self, color: Color) -> Self {
+        self.min_ambient_color = color;
+        self
+    }
+
+    /// Creates new time of day controller.
+    pub fn build_time_of_day(self) -> TimeOfDay {
+        let mut time_of_day = TimeOfDay {
+            base: self.base_builder.build_base(),
+            time: self.time.rem_euclid(24.0).into(),
+            day_length: self.day_length.max(0.0).into(),
+            latitude: self.latitude.clamp(-90.0, 90.0).into(),
+            declination: self.declination.into(),
+            sky: self.sky.into(),
+            sun: self.sun.into(),
+            drive_ambient_lighting: self.drive_ambient_lighting.into(),
+            min_ambient_color: self.min_ambient_color.into(),
+            current_sky: Default::default(),
+            sun_color: Default::default(),
+            ambient_color: Default::default(),
+            cached_sky: None,
+        };
+        time_of_day.update_sky();
+        time_of_day
+    }
+
+    /// Creates new time of day controller node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_time_of_day())
+    }
+
+    /// Creates new instance of time of day controller node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            camera::{Camera, CameraBuilder},
+            light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+            sky::ProceduralSky,
+            time_of_day::{sun_direction, TimeOfDay, TimeOfDayBuilder},
+            Scene,
+        },
+    };
+
+    #[test]
+    fn test_sun_direction() {
+        // Equinox at the equator: the sun rises at the east, at noon it is at zenith.
+        assert!(sun_direction(6.0, 0.0, 0.0).x > 0.99);
+        assert!(sun_direction(12.0, 0.0, 0.0).y > 0.99);
+        assert!(sun_direction(18.0, 0.0, 0.0).x < -0.99);
+        assert!(sun_direction(0.0, 0.0, 0.0).y < -0.99);
+
+        // Northern hemisphere: noon sun is at the south (+Z), southern hemisphere - at the north.
+        let noon = sun_direction(12.0, 45.0, 0.0);
+        assert!(noon.z > 0.0 && noon.y > 0.0);
+        let noon = sun_direction(12.0, -45.0, 0.0);
+        assert!(noon.z < 0.0 && noon.y > 0.0);
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        let mut scene = Scene::new();
+
+        let sun = DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut scene.graph);
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_procedural_sky(ProceduralSky::default())
+            .build(&mut scene.graph);
+        let time_of_day = TimeOfDayBuilder::new(BaseBuilder::new())
+            .with_time(11.0)
+            .with_day_length(24.0)
+            .with_sun(sun)
+            .build(&mut scene.graph);
+
+        // One hour per second.
+        scene.update(Vector2::new(100.0, 100.0), 1.0);
+
+        let time_of_day = scene.graph[time_of_day].cast::<TimeOfDay>().unwrap();
+        assert!((time_of_day.time() - 12.0).abs() < 1.0e-4);
+
+        let sky = time_of_day.current_sky().clone();
+        let ambient = time_of_day.ambient_color();
+        assert_eq!(scene.ambient_lighting_color, ambient);
+        assert_eq!(
+            scene.graph[camera]
+                .cast::<Camera>()
+                .unwrap()
+                .procedural_sky_ref(),
+            Some(&sky)
+        );
+
+        // Sun light shines from the sun.
+        let up = scene.graph[sun]
+            .local_transform()
+            .rotation()
+            .transform_vector(&Vector3::y());
+        assert!((up - sky.sun_direction).norm() < 1.0e-4);
+    }
+}
@@ -0,0 +1,733 @@
+//! Scene validation. It is a pass, that looks for common problems in a scene, such as handles to
+//! nodes that do not exist, broken resources, invalid transforms and so on. See [`validate`] docs
+//! for more info.
+
+use crate::{
+    asset::{Resource, ResourceData, ResourceLoadError, ResourceState},
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    material::{PropertyValue, SharedMaterial},
+    scene::{
+        base::LodGroup, camera::Camera, decal::Decal, dim2, graph::Graph, joint::Joint, mesh::Mesh,
+        node::Node, particle_system::ParticleSystem, sound::Sound, sprite::Sprite,
+        terrain::Terrain, transform::Transform, Scene,
+    },
+};
+use fxhash::FxHashSet;
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// Defines how serious a problem is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticSeverity {
+    /// The scene could be used, but some parts of it could behave incorrectly.
+    Warning,
+    /// The scene is corrupted and most likely will cause panics or undefined behaviour.
+    Error,
+}
+
+/// A place, where a node stores a handle to other node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HandleReference {
+    /// Parent of a node.
+    Parent,
+    /// One of the children of a node.
+    Child,
+    /// First body of a joint (either 3D or 2D).
+    JointBody1,
+    /// Second body of a joint (either 3D or 2D).
+    JointBody2,
+    /// An object of a level of detail.
+    LodObject,
+}
+
+impl Display for HandleReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HandleReference::Parent => "parent",
+            HandleReference::Child => "child",
+            HandleReference::JointBody1 => "joint body 1",
+            HandleReference::JointBody2 => "joint body 2",
+            HandleReference::LodObject => "LOD object",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Kind of a problem.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticKind {
+    /// A node references other node, that does not exist.
+    DanglingHandle {
+        /// A place, where the handle is stored.
+        reference: HandleReference,
+        /// The handle, that does not point to a node.
+        target: Handle<Node>,
+    },
+    /// A surface of a mesh uses a bone, that does not exist.
+    UnresolvedBone {
+        /// Index of the surface.
+        surface: usize,
+        /// The handle of the bone.
+        bone: Handle<Node>,
+    },
+    /// A resource, that is used by a node, has failed to load.
+    MissingResource {
+        /// Path of the resource.
+        path: PathBuf,
+        /// Description of the load error.
+        reason: String,
+    },
+    /// Local transform of a node contains NaN or infinite values.
+    InvalidTransform,
+    /// A collider is not a direct child of a rigid body (of the same dimensionality), so it won't
+    /// take part in the simulation.
+    ColliderWithoutRigidBody,
+    /// A node is an ancestor of itself. The cycle is reported only once, for one of its nodes.
+    HierarchyCycle,
+}
+
+/// A problem found by [`validate`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// A node with the problem.
+    pub node: Handle<Node>,
+    /// Name of the node, it is stored here to be able to show meaningful messages after the
+    /// node was removed or a scene was cloned.
+    pub node_name: String,
+    /// Severity of the problem.
+    pub severity: DiagnosticSeverity,
+    /// Actual problem.
+    pub kind: DiagnosticKind,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}: node {} ({}): ",
+            self.severity, self.node_name, self.node
+        )?;
+        match &self.kind {
+            DiagnosticKind::DanglingHandle { reference, target } => {
+                write!(
+                    f,
+                    "{} handle {} does not point to a node",
+                    reference, target
+                )
+            }
+            DiagnosticKind::UnresolvedBone { surface, bone } => {
+                write!(
+                    f,
+                    "bone {} of surface {} does not point to a node",
+                    bone, surface
+                )
+            }
+            DiagnosticKind::MissingResource { path, reason } => {
+                write!(
+                    f,
+                    "resource {} failed to load. Reason: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            DiagnosticKind::InvalidTransform => {
+                write!(f, "local transform contains NaN or infinite values")
+            }
+            DiagnosticKind::ColliderWithoutRigidBody => {
+                write!(f, "collider is not a direct child of a rigid body")
+            }
+            DiagnosticKind::HierarchyCycle => write!(f, "node is an ancestor of itself"),
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(graph: &Graph, node: Handle<Node>, kind: DiagnosticKind) -> Self {
+        let severity = match kind {
+            DiagnosticKind::DanglingHandle {
+                reference: HandleReference::Parent,
+                ..
+            }
+            | DiagnosticKind::DanglingHandle {
+                reference: HandleReference::Child,
+                ..
+            }
+            | DiagnosticKind::InvalidTransform
+            | DiagnosticKind::HierarchyCycle => DiagnosticSeverity::Error,
+            _ => DiagnosticSeverity::Warning,
+        };
+
+        Self {
+            node,
+            node_name: graph[node].name_owned(),
+            severity,
+            kind,
+        }
+    }
+
+    /// Returns `true` if the problem could be fixed by [`Self::fix`].
+    pub fn can_fix(&self) -> bool {
+        matches!(
+            self.kind,
+            DiagnosticKind::DanglingHandle { .. }
+                | DiagnosticKind::InvalidTransform
+                | DiagnosticKind::HierarchyCycle
+        )
+    }
+
+    /// Tries to fix the problem in the given scene, it must be the scene the diagnostic was
+    /// produced for. The fix is trivial:
+    ///
+    /// - dangling handles are removed (or set to [`Handle::NONE`]), a node with dangling parent is
+    /// attached to the root of the graph;
+    /// - invalid parts of a transform are reset to their defaults;
+    /// - a node, that is an ancestor of itself, is attached to the root of the graph.
+    ///
+    /// Returns `true` if the problem was fixed.
+    pub fn fix(&self, scene: &mut Scene) -> bool {
+        self.fix_with_undo(scene).is_some()
+    }
+
+    /// Does the same as [`Self::fix`], but also returns the state of the scene before the fix,
+    /// which could be used to undo it (see [`DiagnosticFixUndo::undo`]). Returns `None` if the
+    /// problem wasn't fixed.
+    pub fn fix_with_undo(&self, scene: &mut Scene) -> Option<DiagnosticFixUndo> {
+        let graph = &scene.graph;
+        let node = graph.try_get(self.node)?;
+        let undo = DiagnosticFixUndo {
+            node: self.node,
+            parent: node.parent,
+            parent_children: graph.try_get(node.parent).map(|p| p.children.clone()),
+            root_children: graph[graph.get_root()].children.clone(),
+            children: node.children.clone(),
+            transform: node.local_transform().clone(),
+            lod_group: node.lod_group().cloned(),
+            joint_bodies: if let Some(joint) = node.cast::<Joint>() {
+                Some((joint.body1(), joint.body2()))
+            } else {
+                node.cast::<dim2::joint::Joint>()
+                    .map(|joint| (joint.body1(), joint.body2()))
+            },
+        };
+
+        if self.apply_fix(scene) {
+            Some(undo)
+        } else {
+            None
+        }
+    }
+
+    fn apply_fix(&self, scene: &mut Scene) -> bool {
+        let graph = &mut scene.graph;
+        if !graph.is_valid_handle(self.node) {
+            return false;
+        }
+
+        match self.kind {
+            DiagnosticKind::DanglingHandle { reference, target } => match reference {
+                HandleReference::Parent => {
+                    graph.unlink_node(self.node);
+                    true
+                }
+                HandleReference::Child => {
+                    graph[self.node].children.retain(|child| *child != target);
+                    true
+                }
+                HandleReference::JointBody1 | HandleReference::JointBody2 => {
+                    let node = &mut graph[self.node];
+                    let set_body1 = reference == HandleReference::JointBody1;
+                    if let Some(joint) = node.cast_mut::<Joint>() {
+                        if set_body1 {
+                            joint.set_body1(Handle::NONE);
+                        } else {
+                            joint.set_body2(Handle::NONE);
+                        }
+                        true
+                    } else if let Some(joint) = node.cast_mut::<dim2::joint::Joint>() {
+                        if set_body1 {
+                            joint.set_body1(Handle::NONE);
+                        } else {
+                            joint.set_body2(Handle::NONE);
+                        }
+                        true
+                    } else {
+                        false
+                    }
+                }
+                HandleReference::LodObject => {
+                    if let Some(lod_group) = graph[self.node].lod_group_mut() {
+                        for level in lod_group.levels.iter_mut() {
+                            level.objects.retain(|object| object.0 != target);
+                        }
+                        true
+                    } else {
+                        false
+                    }
+                }
+            },
+            DiagnosticKind::InvalidTransform => {
+                reset_invalid_transform(graph[self.node].local_transform_mut());
+                true
+            }
+            DiagnosticKind::HierarchyCycle => {
+                graph.unlink_node(self.node);
+                true
+            }
+            DiagnosticKind::UnresolvedBone { .. }
+            | DiagnosticKind::MissingResource { .. }
+            | DiagnosticKind::ColliderWithoutRigidBody => false,
+        }
+    }
+}
+
+/// State of a node (and its surroundings) before [`Diagnostic::fix_with_undo`].
+#[derive(Debug)]
+pub struct DiagnosticFixUndo {
+    node: Handle<Node>,
+    parent: Handle<Node>,
+    parent_children: Option<Vec<Handle<Node>>>,
+    root_children: Vec<Handle<Node>>,
+    children: Vec<Handle<Node>>,
+    transform: Transform,
+    lod_group: Option<LodGroup>,
+    joint_bodies: Option<(Handle<Node>, Handle<Node>)>,
+}
+
+impl DiagnosticFixUndo {
+    /// Restores the state of the scene as it was before the fix. It must be called for the same
+    /// scene and no other changes must be done to the scene after the fix, otherwise the result is
+    /// undefined.
+    pub fn undo(self, scene: &mut Scene) {
+        let graph = &mut scene.graph;
+        if !graph.is_valid_handle(self.node) {
+            return;
+        }
+
+        let root = graph.get_root();
+        graph[root].children = self.root_children;
+        if let (Some(parent_children), Some(parent)) =
+            (self.parent_children, graph.try_get_mut(self.parent))
+        {
+            parent.children = parent_children;
+        }
+
+        let node = &mut graph[self.node];
+        node.parent = self.parent;
+        node.children = self.children;
+        *node.local_transform_mut() = self.transform;
+        node.set_lod_group(self.lod_group);
+        if let Some((body1, body2)) = self.joint_bodies {
+            if let Some(joint) = node.cast_mut::<Joint>() {
+                joint.set_body1(body1);
+                joint.set_body2(body2);
+            } else if let Some(joint) = node.cast_mut::<dim2::joint::Joint>() {
+                joint.set_body1(body1);
+                joint.set_body2(body2);
+            }
+        }
+    }
+}
+
+fn is_finite_vector(v: &Vector3<f32>) -> bool {
+    v.iter().all(|c| c.is_finite())
+}
+
+fn is_finite_quaternion(q: &UnitQuaternion<f32>) -> bool {
+    q.coords.iter().all(|c| c.is_finite())
+}
+
+fn is_valid_transform(transform: &Transform) -> bool {
+    is_finite_vector(transform.position())
+        && is_finite_vector(transform.scale())
+        && is_finite_vector(transform.rotation_offset())
+        && is_finite_vector(transform.rotation_pivot())
+        && is_finite_vector(transform.scaling_offset())
+        && is_finite_vector(transform.scaling_pivot())
+        && is_finite_quaternion(transform.rotation())
+        && is_finite_quaternion(transform.pre_rotation())
+        && is_finite_quaternion(transform.post_rotation())
+}
+
+fn reset_invalid_transform(transform: &mut Transform) {
+    if !is_finite_vector(transform.position()) {
+        transform.set_position(Vector3::default());
+    }
+    if !is_finite_vector(transform.scale()) {
+        transform.set_scale(Vector3::new(1.0, 1.0, 1.0));
+    }
+    if !is_finite_vector(transform.rotation_offset()) {
+        transform.set_rotation_offset(Vector3::default());
+    }
+    if !is_finite_vector(transform.rotation_pivot()) {
+        transform.set_rotation_pivot(Vector3::default());
+    }
+    if !is_finite_vector(transform.scaling_offset()) {
+        transform.set_scaling_offset(Vector3::default());
+    }
+    if !is_finite_vector(transform.scaling_pivot()) {
+        transform.set_scaling_pivot(Vector3::default());
+    }
+    if !is_finite_quaternion(transform.rotation()) {
+        transform.set_rotation(UnitQuaternion::identity());
+    }
+    if !is_finite_quaternion(transform.pre_rotation()) {
+        transform.set_pre_rotation(UnitQuaternion::identity());
+    }
+    if !is_finite_quaternion(transform.post_rotation()) {
+        transform.set_post_rotation(UnitQuaternion::identity());
+    }
+}
+
+fn resource_error<T, E>(resource: &Resource<T, E>) -> Option<DiagnosticKind>
+where
+    T: ResourceData,
+    E: ResourceLoadError,
+{
+    match &*resource.state() {
+        ResourceState::LoadError { path, error } => Some(DiagnosticKind::MissingResource {
+            path: path.clone(),
+            reason: error
+                .as_ref()
+                .map(|e| format!("{:?}", e))
+                .unwrap_or_else(|| "Unknown".to_owned()),
+        }),
+        _ => None,
+    }
+}
+
+fn material_errors(material: &SharedMaterial, errors: &mut Vec<DiagnosticKind>) {
+    let material = material.lock();
+    errors.extend(resource_error(material.shader()));
+    for property in material.properties().values() {
+        if let PropertyValue::Sampler {
+            value: Some(texture),
+            ..
+        } = property
+        {
+            errors.extend(resource_error(texture));
+        }
+    }
+}
+
+fn resource_errors(node: &Node) -> Vec<DiagnosticKind> {
+    let mut errors = Vec::new();
+
+    if let Some(model) = node.resource() {
+        errors.extend(resource_error(&model));
+    }
+
+    if let Some(mesh) = node.cast::<Mesh>() {
+        for surface in mesh.surfaces() {
+            material_errors(surface.material(), &mut errors);
+        }
+    } else if let Some(terrain) = node.cast::<Terrain>() {
+        for layer in terrain.layers() {
+            material_errors(&layer.material, &mut errors);
+        }
+    } else if let Some(camera) = node.cast::<Camera>() {
+        if let Some(skybox) = camera.skybox_ref() {
+            for texture in skybox.textures().iter().flatten() {
+                errors.extend(resource_error(texture));
+            }
+        }
+    } else if let Some(sprite) = node.cast::<Sprite>() {
+        if let Some(texture) = sprite.texture_ref() {
+            errors.extend(resource_error(texture));
+        }
+    } else if let Some(particle_system) = node.cast::<ParticleSystem>() {
+        if let Some(texture) = particle_system.texture_ref() {
+            errors.extend(resource_error(texture));
+        }
+    } else if let Some(decal) = node.cast::<Decal>() {
+        for texture in decal
+            .diffuse_texture()
+            .into_iter()
+            .chain(decal.normal_texture())
+        {
+            errors.extend(resource_error(texture));
+        }
+    } else if let Some(rectangle) = node.cast::<dim2::rectangle::Rectangle>() {
+        if let Some(texture) = rectangle.texture() {
+            errors.extend(resource_error(texture));
+        }
+    } else if let Some(sound) = node.cast::<Sound>() {
+        if let Some(buffer) = sound.buffer() {
+            errors.extend(resource_error(&buffer));
+        }
+    }
+
+    // Shared materials and textures could be reported many times.
+    let mut unique = Vec::with_capacity(errors.len());
+    for error in errors {
+        if !unique.contains(&error) {
+            unique.push(error);
+        }
+    }
+    unique
+}
+
+fn check_handle(
+    graph: &Graph,
+    node: Handle<Node>,
+    reference: HandleReference,
+    target: Handle<Node>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if target.is_some() && !graph.is_valid_handle(target) {
+        diagnostics.push(Diagnostic::new(
+            graph,
+            node,
+            DiagnosticKind::DanglingHandle { reference, target },
+        ));
+    }
+}
+
+fn check_hierarchy(graph: &Graph, diagnostics: &mut Vec<Diagnostic>) {
+    let mut in_reported_cycle = FxHashSet::default();
+    let mut ancestors = FxHashSet::default();
+
+    for (handle, _) in graph.pair_iter() {
+        ancestors.clear();
+
+        let mut current = handle;
+        while let Some(node) = graph.try_get(current) {
+            if !ancestors.insert(current) {
+                // `current` is a part of a cycle, collect every node of it to report the cycle
+                // only once.
+                if !in_reported_cycle.contains(&current) {
+                    let mut member = current;
+                    loop {
+                        in_reported_cycle.insert(member);
+                        member = graph[member].parent();
+                        if member == current {
+                            break;
+                        }
+                    }
+                    diagnostics.push(Diagnostic::new(
+                        graph,
+                        current,
+                        DiagnosticKind::HierarchyCycle,
+                    ));
+                }
+                break;
+            }
+            current = node.parent();
+        }
+    }
+}
+
+/// Checks the given scene for common problems and returns a list of diagnostics, every diagnostic
+/// contains a handle of the node with the problem. The following problems are detected:
+///
+/// - handles of parent, children, joint bodies and LOD objects, that do not point to a node;
+/// - bones of mesh surfaces, that do not point to a node;
+/// - resources (models, textures, shaders, sound buffers), that failed to load;
+/// - NaN or infinite values in local transforms;
+/// - colliders, that are not direct children of a rigid body;
+/// - cycles in the hierarchy.
+///
+/// Some of the problems could be fixed automatically, see [`Diagnostic::fix`]. The pass does not
+/// modify the scene, so it could be called at any time, for example before saving a scene.
+pub fn validate(scene: &Scene) -> Vec<Diagnostic> {
+    let graph = &scene.graph;
+    let mut diagnostics = Vec::new();
+
+    for (handle, node) in graph.pair_iter() {
+        if handle != graph.get_root() {
+            let parent = node.parent();
+            if !graph.is_valid_handle(parent) {
+                diagnostics.push(Diagnostic::new(
+                    graph,
+                    handle,
+                    DiagnosticKind::DanglingHandle {
+                        reference: HandleReference::Parent,
+                        target: parent,
+                    },
+                ));
+            }
+        }
+
+        for &child in node.children() {
+            if !graph.is_valid_handle(child) {
+                diagnostics.push(Diagnostic::new(
+                    graph,
+                    handle,
+                    DiagnosticKind::DanglingHandle {
+                        reference: HandleReference::Child,
+                        target: child,
+                    },
+                ));
+            }
+        }
+
+        if let Some(lod_group) = node.lod_group() {
+            for level in lod_group.levels.iter() {
+                for object in level.objects.iter() {
+                    check_handle(
+                        graph,
+                        handle,
+                        HandleReference::LodObject,
+                        object.0,
+                        &mut diagnostics,
+                    );
+                }
+            }
+        }
+
+        if let Some(joint) = node.cast::<Joint>() {
+            check_handle(
+                graph,
+                handle,
+                HandleReference::JointBody1,
+                joint.body1(),
+                &mut diagnostics,
+            );
+            check_handle(
+                graph,
+                handle,
+                HandleReference::JointBody2,
+                joint.body2(),
+                &mut diagnostics,
+            );
+        } else if let Some(joint) = node.cast::<dim2::joint::Joint>() {
+            check_handle(
+                graph,
+                handle,
+                HandleReference::JointBody1,
+                joint.body1(),
+                &mut diagnostics,
+            );
+            check_handle(
+                graph,
+                handle,
+                HandleReference::JointBody2,
+                joint.body2(),
+                &mut diagnostics,
+            );
+        }
+
+        if let Some(mesh) = node.cast::<Mesh>() {
+            for (surface_index, surface) in mesh.surfaces().iter().enumerate() {
+                for &bone in surface.bones() {
+                    if !graph.is_valid_handle(bone) {
+                        diagnostics.push(Diagnostic::new(
+                            graph,
+                            handle,
+                            DiagnosticKind::UnresolvedBone {
+                                surface: surface_index,
+                                bone,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+
+        let parent = graph.try_get(node.parent());
+        if node.is_collider() && !parent.map_or(false, |p| p.is_rigid_body())
+            || node.is_collider2d() && !parent.map_or(false, |p| p.is_rigid_body2d())
+        {
+            diagnostics.push(Diagnostic::new(
+                graph,
+                handle,
+                DiagnosticKind::ColliderWithoutRigidBody,
+            ));
+        }
+
+        if !is_valid_transform(node.local_transform()) {
+            diagnostics.push(Diagnostic::new(
+                graph,
+                handle,
+                DiagnosticKind::InvalidTransform,
+            ));
+        }
+
+        for error in resource_errors(node) {
+            diagnostics.push(Diagnostic::new(graph, handle, error));
+        }
+    }
+
+    check_hierarchy(graph, &mut diagnostics);
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        scene::{
+            base::BaseBuilder,
+            collider::ColliderBuilder,
+            joint::JointBuilder,
+            pivot::PivotBuilder,
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+            validation::{DiagnosticKind, HandleReference},
+            Scene,
+        },
+    };
+
+    #[test]
+    fn test_validation_and_fix() {
+        let mut scene = Scene::new();
+
+        let removed = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        scene.graph.remove_node(removed);
+
+        let body = RigidBodyBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        let joint = JointBuilder::new(BaseBuilder::new())
+            .with_body1(body)
+            .with_body2(removed)
+            .build(&mut scene.graph);
+        let collider = ColliderBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        let nan = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(f32::NAN, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        let diagnostics = scene.validate();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics.iter().any(|d| d.node == joint
+            && d.kind
+                == DiagnosticKind::DanglingHandle {
+                    reference: HandleReference::JointBody2,
+                    target: removed
+                }));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.node == collider && d.kind == DiagnosticKind::ColliderWithoutRigidBody));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.node == nan && d.kind == DiagnosticKind::InvalidTransform));
+
+        let undos = diagnostics
+            .iter()
+            .filter_map(|diagnostic| {
+                let undo = diagnostic.fix_with_undo(&mut scene);
+                assert_eq!(undo.is_some(), diagnostic.can_fix());
+                undo
+            })
+            .collect::<Vec<_>>();
+
+        let fixed_diagnostics = scene.validate();
+        assert_eq!(fixed_diagnostics.len(), 1);
+        assert_eq!(fixed_diagnostics[0].node, collider);
+        assert!(scene.graph[joint].as_joint().body2() == Handle::NONE);
+
+        // Undo must bring the problems back.
+        for undo in undos.into_iter().rev() {
+            undo.undo(&mut scene);
+        }
+        assert_eq!(scene.validate(), diagnostics);
+    }
+}
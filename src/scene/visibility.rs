@@ -47,7 +47,8 @@ impl VisibilityCache {
     }
 
     /// Updates visibility cache - checks visibility for each node in given graph, also performs
-    /// frustum culling if frustum set is specified.
+    /// frustum culling if frustum set is specified. Nodes that do not belong to any layer of the
+    /// given layer mask are considered invisible.
     pub fn update(
         &mut self,
         nodes: &NodePool,
@@ -55,6 +56,7 @@ impl VisibilityCache {
         z_near: f32,
         z_far: f32,
         frustums: Option<&[&Frustum]>,
+        layer_mask: u32,
     ) {
         self.map.clear();
 
@@ -82,7 +84,7 @@ impl VisibilityCache {
             // We need to fill only unfilled entries, none of visibility flags of a node can
             // make it visible again if lod group hid it.
             self.map.entry(handle).or_insert_with(|| {
-                let mut visibility = node.global_visibility();
+                let mut visibility = node.global_visibility() && node.layer() & layer_mask != 0;
                 if visibility && node.frustum_culling() {
                     // If a node globally visible, check it with each frustum (if any).
                     if let Some(frustums) = frustums {
@@ -0,0 +1,508 @@
+//! Built-in camera controller scripts. See [`OrbitCameraController`], [`FlyCameraController`] and
+//! [`FollowCameraController`] docs for more info.
+//!
+//! All controllers in this module modify **local** transform of a node they're assigned to, so
+//! it is expected that the node is a direct child of the root node of the scene (or any other
+//! node with identity global transform).
+
+use crate::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    event::{
+        DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode,
+        WindowEvent,
+    },
+    impl_component_provider,
+    scene::{
+        collider::InteractionGroups,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        node::{Node, TypeUuidProvider},
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// Casts a ray from `origin` in `direction` and returns distance to the closest obstacle that is
+/// not a descendant of `ignore` node. Returns `None` if there is no such obstacle in `max_len`.
+fn probe_obstacle(
+    graph: &Graph,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    max_len: f32,
+    ignore: Handle<Node>,
+) -> Option<f32> {
+    let mut intersections: Vec<Intersection> = Vec::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(origin),
+            ray_direction: direction,
+            max_len,
+            groups: InteractionGroups::default(),
+            sort_results: true,
+            layer_mask: u32::MAX,
+        },
+        &mut intersections,
+    );
+    intersections
+        .into_iter()
+        .find(|i| !is_descendant_of(graph, i.collider, ignore))
+        .map(|i| i.toi)
+}
+
+fn is_descendant_of(graph: &Graph, mut node: Handle<Node>, ancestor: Handle<Node>) -> bool {
+    if ancestor.is_none() {
+        return false;
+    }
+    while let Some(node_ref) = graph.try_get(node) {
+        if node == ancestor {
+            return true;
+        }
+        node = node_ref.parent();
+    }
+    false
+}
+
+fn orientation(yaw: f32, pitch: f32) -> UnitQuaternion<f32> {
+    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw)
+        * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), pitch)
+}
+
+/// Mouse-driven orbit camera, that rotates around a target node. Hold right mouse button to
+/// rotate the camera, use mouse wheel to zoom in/out.
+///
+/// # Collisions
+///
+/// When [`Self::collide`] is set, the controller casts a ray from the target to the desired
+/// camera position and moves the camera closer to the target if there is an obstacle in between.
+/// Colliders that belong to the target (its descendants) are ignored.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct OrbitCameraController {
+    /// A node to orbit around. If the handle is invalid, the camera will orbit around
+    /// [`Self::pivot`] position.
+    pub target: Handle<Node>,
+    /// World-space point to orbit around when there is no target, or an offset from the target
+    /// position otherwise.
+    pub pivot: Vector3<f32>,
+    /// Desired distance between the camera and its pivot point.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub distance: f32,
+    /// Minimal distance to the pivot point.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub min_distance: f32,
+    /// Maximal distance to the pivot point.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub max_distance: f32,
+    /// Rotation angle around vertical axis, in radians.
+    pub yaw: f32,
+    /// Rotation angle around horizontal axis, in radians.
+    pub pitch: f32,
+    /// Minimal pitch angle, in radians.
+    pub min_pitch: f32,
+    /// Maximal pitch angle, in radians.
+    pub max_pitch: f32,
+    /// Mouse sensitivity of rotation.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub rotation_sensitivity: f32,
+    /// Mouse wheel sensitivity of zooming.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub zoom_sensitivity: f32,
+    /// Whether the camera should be pushed towards the pivot when there's an obstacle.
+    pub collide: bool,
+    /// A distance that is kept between the camera and an obstacle.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub collision_margin: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rotate: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    current_distance: f32,
+}
+
+impl Default for OrbitCameraController {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            pivot: Default::default(),
+            distance: 5.0,
+            min_distance: 1.0,
+            max_distance: 20.0,
+            yaw: 0.0,
+            pitch: 30.0f32.to_radians(),
+            min_pitch: -89.0f32.to_radians(),
+            max_pitch: 89.0f32.to_radians(),
+            rotation_sensitivity: 0.01,
+            zoom_sensitivity: 0.5,
+            collide: true,
+            collision_margin: 0.2,
+            rotate: false,
+            current_distance: 5.0,
+        }
+    }
+}
+
+impl_component_provider!(OrbitCameraController);
+
+impl TypeUuidProvider for OrbitCameraController {
+    fn type_uuid() -> Uuid {
+        uuid!("0c9a8a4b-d2c4-4c3a-8a2b-6f0e7d1e5a10")
+    }
+}
+
+impl OrbitCameraController {
+    /// Returns actual distance between the camera and the pivot point, it could be less than
+    /// [`Self::distance`] if there was an obstacle.
+    pub fn current_distance(&self) -> f32 {
+        self.current_distance
+    }
+}
+
+impl ScriptTrait for OrbitCameraController {
+    fn on_os_event(&mut self, event: &Event<()>, _ctx: &mut ScriptContext) {
+        match event {
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } if self.rotate => {
+                self.yaw -= delta.0 as f32 * self.rotation_sensitivity;
+                self.pitch = (self.pitch + delta.1 as f32 * self.rotation_sensitivity)
+                    .max(self.min_pitch)
+                    .min(self.max_pitch);
+            }
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::MouseInput { button, state, .. } => {
+                    if *button == MouseButton::Right {
+                        self.rotate = *state == ElementState::Pressed;
+                    }
+                }
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let amount = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y,
+                        MouseScrollDelta::PixelDelta(p) => p.y as f32 / 16.0,
+                    };
+                    self.distance = (self.distance - amount * self.zoom_sensitivity)
+                        .max(self.min_distance)
+                        .min(self.max_distance);
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &ctx.scene.graph;
+
+        let pivot = graph
+            .try_get(self.target)
+            .map(|t| t.global_position() + self.pivot)
+            .unwrap_or(self.pivot);
+
+        let rotation = orientation(self.yaw, self.pitch);
+        let back = rotation.transform_vector(&-Vector3::z());
+
+        let mut distance = self.distance.max(self.min_distance).min(self.max_distance);
+        if self.collide {
+            if let Some(toi) = probe_obstacle(graph, pivot, back, distance, self.target) {
+                distance = (toi - self.collision_margin).max(0.0);
+            }
+        }
+        self.current_distance = distance;
+
+        if let Some(node) = ctx.scene.graph.try_get_mut(ctx.handle) {
+            node.local_transform_mut()
+                .set_position(pivot + back.scale(distance))
+                .set_rotation(rotation);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+struct FlyInput {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+    boost: bool,
+    look: bool,
+}
+
+/// Free-fly camera. Use W/S/A/D to move, Q/E to move down/up, hold Shift to move faster and
+/// hold right mouse button to look around.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct FlyCameraController {
+    /// Movement speed in units per second.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub speed: f32,
+    /// Speed multiplier, that is used when boost key is held.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub boost_multiplier: f32,
+    /// Mouse sensitivity.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub sensitivity: f32,
+    /// Rotation angle around vertical axis, in radians.
+    pub yaw: f32,
+    /// Rotation angle around horizontal axis, in radians.
+    pub pitch: f32,
+    /// If set, the camera will rotate without holding right mouse button.
+    pub always_look: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    input: FlyInput,
+}
+
+impl Default for FlyCameraController {
+    fn default() -> Self {
+        Self {
+            speed: 5.0,
+            boost_multiplier: 4.0,
+            sensitivity: 0.005,
+            yaw: 0.0,
+            pitch: 0.0,
+            always_look: false,
+            input: Default::default(),
+        }
+    }
+}
+
+impl_component_provider!(FlyCameraController);
+
+impl TypeUuidProvider for FlyCameraController {
+    fn type_uuid() -> Uuid {
+        uuid!("9f6b2d5e-40f3-4a61-b35e-3d9b7c0e2f41")
+    }
+}
+
+impl ScriptTrait for FlyCameraController {
+    fn on_os_event(&mut self, event: &Event<()>, _ctx: &mut ScriptContext) {
+        match event {
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } if self.input.look || self.always_look => {
+                let limit = 89.0f32.to_radians();
+                self.yaw -= delta.0 as f32 * self.sensitivity;
+                self.pitch = (self.pitch + delta.1 as f32 * self.sensitivity).clamp(-limit, limit);
+            }
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::MouseInput { button, state, .. } => {
+                    if *button == MouseButton::Right {
+                        self.input.look = *state == ElementState::Pressed;
+                    }
+                }
+                WindowEvent::KeyboardInput { input, .. } => {
+                    let pressed = input.state == ElementState::Pressed;
+                    match input.virtual_keycode {
+                        Some(VirtualKeyCode::W) => self.input.forward = pressed,
+                        Some(VirtualKeyCode::S) => self.input.backward = pressed,
+                        Some(VirtualKeyCode::A) => self.input.left = pressed,
+                        Some(VirtualKeyCode::D) => self.input.right = pressed,
+                        Some(VirtualKeyCode::E) => self.input.up = pressed,
+                        Some(VirtualKeyCode::Q) => self.input.down = pressed,
+                        Some(VirtualKeyCode::LShift) | Some(VirtualKeyCode::RShift) => {
+                            self.input.boost = pressed
+                        }
+                        _ => (),
+                    }
+                }
+                WindowEvent::Focused(false) => {
+                    // Prevent "stuck" keys when the window loses focus.
+                    self.input = Default::default();
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let rotation = orientation(self.yaw, self.pitch);
+
+        let look = rotation.transform_vector(&Vector3::z());
+        let side = rotation.transform_vector(&Vector3::x());
+
+        let mut velocity = Vector3::default();
+        if self.input.forward {
+            velocity += look;
+        }
+        if self.input.backward {
+            velocity -= look;
+        }
+        if self.input.left {
+            velocity += side;
+        }
+        if self.input.right {
+            velocity -= side;
+        }
+        if self.input.up {
+            velocity += Vector3::y();
+        }
+        if self.input.down {
+            velocity -= Vector3::y();
+        }
+
+        let mut speed = self.speed;
+        if self.input.boost {
+            speed *= self.boost_multiplier;
+        }
+
+        let offset = velocity
+            .try_normalize(f32::EPSILON)
+            .map(|v| v.scale(speed * ctx.dt))
+            .unwrap_or_default();
+
+        if let Some(node) = ctx.scene.graph.try_get_mut(ctx.handle) {
+            let transform = node.local_transform_mut();
+            transform.offset(offset).set_rotation(rotation);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Smoothed third-person camera, that follows a target node. The camera is placed at
+/// [`Self::offset`] (in target's local coordinates) and looks at the point which is
+/// [`Self::look_at_height`] units above the target.
+///
+/// # Occlusion
+///
+/// When [`Self::handle_occlusion`] is set, the controller checks whether there is an obstacle
+/// between the look-at point and the desired camera position, and if so, moves the camera in
+/// front of the obstacle. Colliders that belong to the target (its descendants) are ignored.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct FollowCameraController {
+    /// A node to follow.
+    pub target: Handle<Node>,
+    /// Desired camera position in local coordinates of the target.
+    pub offset: Vector3<f32>,
+    /// Height of the look-at point above target's origin.
+    pub look_at_height: f32,
+    /// Defines how fast the camera reaches its desired position. Larger values makes the camera
+    /// more "stiff", zero disables smoothing at all.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub position_stiffness: f32,
+    /// Defines how fast the camera reaches its desired rotation. Larger values makes the camera
+    /// more "stiff", zero disables smoothing at all.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub rotation_stiffness: f32,
+    /// Whether the camera should be moved in front of obstacles that occlude the target.
+    pub handle_occlusion: bool,
+    /// A distance that is kept between the camera and an obstacle.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub collision_margin: f32,
+}
+
+impl Default for FollowCameraController {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            offset: Vector3::new(0.0, 2.0, -4.0),
+            look_at_height: 1.0,
+            position_stiffness: 8.0,
+            rotation_stiffness: 12.0,
+            handle_occlusion: true,
+            collision_margin: 0.2,
+        }
+    }
+}
+
+impl_component_provider!(FollowCameraController);
+
+impl TypeUuidProvider for FollowCameraController {
+    fn type_uuid() -> Uuid {
+        uuid!("5e7d3a2c-8b19-4f0e-a6c4-1b2f9d8e7c33")
+    }
+}
+
+/// Returns interpolation factor for exponential smoothing, that does not depend on frame rate.
+fn smoothing_factor(stiffness: f32, dt: f32) -> f32 {
+    if stiffness <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-stiffness * dt).exp()
+    }
+}
+
+impl ScriptTrait for FollowCameraController {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &ctx.scene.graph;
+
+        let target = match graph.try_get(self.target) {
+            Some(target) => target,
+            None => return,
+        };
+
+        let target_transform = target.global_transform();
+        let look_at = target.global_position() + Vector3::new(0.0, self.look_at_height, 0.0);
+        let mut desired_position = target_transform
+            .transform_point(&Point3::from(self.offset))
+            .coords;
+
+        if self.handle_occlusion {
+            let to_camera = desired_position - look_at;
+            let length = to_camera.norm();
+            if let Some(dir) = to_camera.try_normalize(f32::EPSILON) {
+                if let Some(toi) = probe_obstacle(graph, look_at, dir, length, self.target) {
+                    desired_position = look_at + dir.scale((toi - self.collision_margin).max(0.0));
+                }
+            }
+        }
+
+        if let Some(node) = ctx.scene.graph.try_get_mut(ctx.handle) {
+            let transform = node.local_transform_mut();
+
+            let position = transform.position().lerp(
+                &desired_position,
+                smoothing_factor(self.position_stiffness, ctx.dt),
+            );
+
+            let desired_rotation = (look_at - position)
+                .try_normalize(f32::EPSILON)
+                .map(|dir| UnitQuaternion::face_towards(&dir, &Vector3::y()))
+                .unwrap_or_else(|| **transform.rotation());
+
+            let rotation = transform.rotation().slerp(
+                &desired_rotation,
+                smoothing_factor(self.rotation_stiffness, ctx.dt),
+            );
+
+            transform.set_position(position).set_rotation(rotation);
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::script::camera::smoothing_factor;
+
+    #[test]
+    fn test_smoothing_factor() {
+        assert_eq!(smoothing_factor(0.0, 0.016), 1.0);
+        let a = smoothing_factor(10.0, 0.016);
+        assert!(a > 0.0 && a < 1.0);
+        // Two half-steps must give the same result as a single full step.
+        let b = smoothing_factor(10.0, 0.008);
+        assert!(((1.0 - b) * (1.0 - b) - (1.0 - a)).abs() < 1.0e-5);
+    }
+}
@@ -6,7 +6,10 @@ use crate::{
         uuid::Uuid,
     },
     scene::node::TypeUuidProvider,
-    script::{Script, ScriptTrait},
+    script::{
+        camera::{FlyCameraController, FollowCameraController, OrbitCameraController},
+        Script, ScriptTrait,
+    },
 };
 use std::collections::BTreeMap;
 
@@ -28,9 +31,16 @@ pub struct ScriptConstructorContainer {
 }
 
 impl ScriptConstructorContainer {
-    /// Creates default node constructor container with constructors for built-in engine nodes.
+    /// Creates default script constructor container with constructors for built-in engine scripts.
     pub fn new() -> Self {
-        ScriptConstructorContainer::default()
+        let container = ScriptConstructorContainer::default();
+
+        container
+            .add::<OrbitCameraController>("Orbit Camera Controller")
+            .add::<FlyCameraController>("Fly Camera Controller")
+            .add::<FollowCameraController>("Follow Camera Controller");
+
+        container
     }
 
     /// Adds new type constructor for a given type.
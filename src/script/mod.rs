@@ -3,6 +3,7 @@
 //! Script is used to add custom logic to scene nodes. See [ScriptTrait] for more info.
 
 use crate::{
+    console::Console,
     core::{
         pool::Handle,
         reflect::{Reflect, ReflectArray, ReflectList},
@@ -11,8 +12,10 @@ use crate::{
     },
     engine::resource_manager::ResourceManager,
     event::Event,
+    gui::localization::Localization,
     plugin::Plugin,
     scene::{node::Node, Scene},
+    settings::Settings,
     utils::{component::ComponentProvider, log::Log},
 };
 use fyrox_core::reflect::FieldInfo;
@@ -22,6 +25,7 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+pub mod camera;
 pub mod constructor;
 
 /// Base script trait is used to automatically implement some trait to reduce amount of boilerplate code.
@@ -70,6 +74,18 @@ pub struct ScriptContext<'a, 'b> {
 
     /// A reference to resource manager, use it to load resources.
     pub resource_manager: &'a ResourceManager,
+
+    /// Localization service of the user interface, use it to translate messages to current
+    /// language (for example `context.localization.tr("menu-start")`).
+    pub localization: &'a Localization,
+
+    /// A reference to the developer console of the engine, use it to read cvars or to execute
+    /// commands (for example `context.console.cvar_bool("god_mode")`).
+    pub console: &'a Console,
+
+    /// A reference to the settings of the engine and the game, use it to read settings sections
+    /// (for example `context.settings.get::<AudioSettings>()`).
+    pub settings: &'a Settings,
 }
 
 /// A set of data that will be passed to a script instance just before its destruction.
@@ -134,6 +150,27 @@ pub trait ScriptTrait: BaseScript + ComponentProvider {
     /// 60 times per second (this may change in future releases).
     fn on_update(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
 
+    /// The method is called when the script becomes active: right after [`ScriptTrait::on_start`]
+    /// if the script is enabled, or when the script (or the scene it belongs to) is enabled back
+    /// after being disabled. Active scripts are updated and receive OS events.
+    fn on_enable(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
+
+    /// The method is called when an active script is disabled, or when the scene it belongs to is
+    /// disabled. Disabled scripts are not updated and do not receive OS events. Keep in mind, that
+    /// the method is not called when the script is destroyed, use [`ScriptTrait::on_deinit`] for
+    /// that.
+    fn on_disable(&mut self, #[allow(unused_variables)] ctx: &mut ScriptContext) {}
+
+    /// Returns desired interval (in seconds) between two consecutive calls of
+    /// [`ScriptTrait::on_update`]. Zero (default) means that the script will be updated every
+    /// frame. It is useful for scripts that do not need to be updated every frame, for example
+    /// AI could "think" 10 times per second (`0.1` interval). The engine spreads the updates of
+    /// such scripts across frames, so scripts with the same interval won't be updated all at once.
+    /// [`ScriptContext::dt`] will contain the amount of time passed since the previous update.
+    fn update_interval(&self) -> f32 {
+        0.0
+    }
+
     /// Allows you to restore resources after deserialization.
     ///
     /// # Motivation
@@ -196,6 +233,12 @@ pub struct Script {
     instance: Box<dyn ScriptTrait>,
     pub(crate) initialized: bool,
     pub(crate) started: bool,
+    // Whether `on_enable` was called last (and not `on_disable`).
+    pub(crate) active: bool,
+    // Amount of time left until the next update, it is used only when update interval is set.
+    pub(crate) update_timer: f32,
+    // Amount of time passed since the last update.
+    pub(crate) time_since_update: f32,
 }
 
 impl Reflect for Script {
@@ -310,6 +353,9 @@ impl Clone for Script {
             instance: self.instance.clone_box(),
             initialized: false,
             started: false,
+            active: false,
+            update_timer: 0.0,
+            time_since_update: 0.0,
         }
     }
 }
@@ -322,6 +368,9 @@ impl Script {
             instance: Box::new(script_object),
             initialized: false,
             started: false,
+            active: false,
+            update_timer: 0.0,
+            time_since_update: 0.0,
         }
     }
 
@@ -362,7 +411,11 @@ mod test {
             variable::InheritableVariable, visitor::prelude::*,
         },
         impl_component_provider,
-        scene::base::Base,
+        scene::{
+            base::{Base, BaseBuilder},
+            graph::Graph,
+            pivot::PivotBuilder,
+        },
         script::{Script, ScriptTrait},
     };
 
@@ -383,13 +436,13 @@ mod test {
     fn test_script_property_inheritance_on_nodes() {
         let mut child = Base::default();
 
-        child.script = Some(Script::new(MyScript {
+        child.add_script(Script::new(MyScript {
             field: InheritableVariable::new(1.23),
         }));
 
         let mut parent = Base::default();
 
-        parent.script = Some(Script::new(MyScript {
+        parent.add_script(Script::new(MyScript {
             field: InheritableVariable::new(3.21),
         }));
 
@@ -433,4 +486,34 @@ mod test {
             3.21
         );
     }
+
+    #[test]
+    fn test_scripts_of_type() {
+        let mut graph = Graph::new();
+
+        let a = PivotBuilder::new(BaseBuilder::new().with_script(Script::new(MyScript {
+            field: InheritableVariable::new(1.0),
+        })))
+        .build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        assert_eq!(graph.nodes_with_script::<MyScript>(), vec![a]);
+
+        graph[b].set_script(Some(Script::new(MyScript {
+            field: InheritableVariable::new(2.0),
+        })));
+
+        let sum = graph
+            .scripts_of_type::<MyScript>()
+            .map(|(_, s)| *s.field)
+            .sum::<f32>();
+        assert_eq!(sum, 3.0);
+        assert_eq!(*graph[b].script_as::<MyScript>().unwrap().field, 2.0);
+
+        graph[a].set_script(None);
+        assert_eq!(graph.nodes_with_script::<MyScript>(), vec![b]);
+
+        graph.remove_node(b);
+        assert!(graph.nodes_with_script::<MyScript>().is_empty());
+    }
 }
@@ -0,0 +1,570 @@
+//! Settings system. Settings are split into sections - typed structures registered by engine
+//! modules and game code, every section is identified by a unique name. All sections are stored in
+//! a single config file, so a user has one place to tweak everything.
+//!
+//! # Sections
+//!
+//! A section is a structure that implements [`SettingsSection`] trait, it must be serializable
+//! (to be stored in the file) and reflectable (to be shown in [`view::SettingsView`]):
+//!
+//! ```rust
+//! use fyrox::{
+//!     core::reflect::prelude::*,
+//!     settings::{Settings, SettingsSection},
+//! };
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Clone, Debug, Default, Serialize, Deserialize, Reflect)]
+//! struct ControlsSettings {
+//!     mouse_sensitivity: f32,
+//!     invert_y: bool,
+//! }
+//!
+//! impl SettingsSection for ControlsSettings {
+//!     fn section_name() -> &'static str {
+//!         "controls"
+//!     }
+//! }
+//!
+//! let settings = Settings::new();
+//! settings.modify(|controls: &mut ControlsSettings| controls.invert_y = true);
+//! assert!(settings.get::<ControlsSettings>().invert_y);
+//! ```
+//!
+//! # Change events
+//!
+//! Every change of a section is reported to subscribers (see [`Settings::subscribe`]), so systems
+//! could react to changes immediately, for example the engine applies graphics quality settings
+//! and sound volume as soon as [`GraphicsSettings`] or [`AudioSettings`] are changed.
+//!
+//! # File format
+//!
+//! The file consists of sections, every section starts with a header line with the name of the
+//! section in square brackets, followed by RON representation of the section:
+//!
+//! ```text
+//! [audio]
+//! (
+//!     master_gain: 0.5,
+//! )
+//! ```
+//!
+//! Sections, that are present in the file, but not registered yet, are kept and applied when the
+//! section is registered, so the file could be loaded before plugins are initialized.
+
+pub mod view;
+
+use crate::{
+    core::{parking_lot::Mutex, reflect::prelude::*},
+    gui::inspector::{PropertyAction, PropertyChanged},
+    renderer::QualitySettings,
+    utils::log::Log,
+};
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    fmt::{Debug, Display, Formatter},
+    path::Path,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+};
+
+/// A typed section of settings. See module docs for more info.
+pub trait SettingsSection:
+    Reflect + Serialize + DeserializeOwned + Default + Clone + Debug + Send + 'static
+{
+    /// Returns unique name of the section, it is used in the settings file.
+    fn section_name() -> &'static str;
+}
+
+/// An error that may occur during settings loading or saving.
+#[derive(Debug)]
+pub enum SettingsError {
+    /// An i/o error has occurred.
+    Io(std::io::Error),
+    /// Serialization or deserialization error of a section.
+    Ron {
+        /// Name of the section.
+        section: String,
+        /// Error message.
+        message: String,
+    },
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::Io(v) => write!(f, "An i/o error has occurred {:?}", v),
+            SettingsError::Ron { section, message } => {
+                write!(f, "Invalid settings section {}: {}", section, message)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// An event, that is sent to subscribers when settings are changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsEvent {
+    /// A section with the given name was changed.
+    Changed(String),
+}
+
+trait AnySection: Send {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn as_reflect(&self) -> &dyn Reflect;
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect;
+    fn to_ron(&self) -> Result<String, String>;
+    fn load_ron(&mut self, source: &str) -> Result<(), String>;
+    fn reset(&mut self);
+}
+
+impl<T: SettingsSection> AnySection for T {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn as_reflect(&self) -> &dyn Reflect {
+        self
+    }
+
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect {
+        self
+    }
+
+    fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, PrettyConfig::default()).map_err(|e| e.to_string())
+    }
+
+    fn load_ron(&mut self, source: &str) -> Result<(), String> {
+        *self = ron::from_str(source).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        *self = T::default();
+    }
+}
+
+#[derive(Default)]
+struct SettingsState {
+    sections: BTreeMap<String, Box<dyn AnySection>>,
+    // Sections loaded from a file, but not registered yet.
+    pending: HashMap<String, String>,
+    listeners: Vec<Sender<SettingsEvent>>,
+}
+
+impl SettingsState {
+    fn notify(&mut self, section: &str) {
+        self.listeners.retain(|listener| {
+            listener
+                .send(SettingsEvent::Changed(section.to_owned()))
+                .is_ok()
+        });
+    }
+
+    fn section_mut<T: SettingsSection>(&mut self) -> &mut T {
+        let name = T::section_name();
+        if !self.sections.contains_key(name) {
+            let mut section = T::default();
+            if let Some(source) = self.pending.remove(name) {
+                if let Err(err) = AnySection::load_ron(&mut section, &source) {
+                    Log::err(format!(
+                        "Unable to load settings section {}, defaults will be used. Reason: {}",
+                        name, err
+                    ));
+                }
+            }
+            self.sections.insert(name.to_owned(), Box::new(section));
+        }
+
+        self.sections
+            .get_mut(name)
+            .and_then(|section| section.as_any_mut().downcast_mut::<T>())
+            .unwrap_or_else(|| panic!("Settings section name {} is not unique!", name))
+    }
+}
+
+/// Splits settings file into a set of (section name, section source) pairs.
+fn split_sections(source: &str) -> Vec<(String, String)> {
+    let mut sections = Vec::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in source.lines() {
+        let trimmed = line.trim_end();
+        let is_header = trimmed.len() > 2
+            && trimmed.starts_with('[')
+            && trimmed.ends_with(']')
+            && trimmed[1..trimmed.len() - 1]
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.');
+
+        if is_header {
+            sections.extend(current.take());
+            current = Some((trimmed[1..trimmed.len() - 1].to_owned(), String::new()));
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+
+    sections.extend(current);
+    sections
+}
+
+/// Shared settings storage. Every clone of it refers to the same sections. See module docs for
+/// more info.
+#[derive(Clone, Default)]
+pub struct Settings {
+    state: Arc<Mutex<SettingsState>>,
+}
+
+impl Settings {
+    /// Creates new empty settings storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a section. It is not mandatory, every method registers a section on first use,
+    /// but registered sections are saved to the file and shown in the settings view.
+    pub fn register<T: SettingsSection>(&self) {
+        self.state.lock().section_mut::<T>();
+    }
+
+    /// Returns `true` if a section with the given name is registered.
+    pub fn is_registered(&self, section: &str) -> bool {
+        self.state.lock().sections.contains_key(section)
+    }
+
+    /// Returns names of every registered section.
+    pub fn section_names(&self) -> Vec<String> {
+        self.state.lock().sections.keys().cloned().collect()
+    }
+
+    /// Returns a copy of a section.
+    pub fn get<T: SettingsSection>(&self) -> T {
+        self.state.lock().section_mut::<T>().clone()
+    }
+
+    /// Replaces a section with new value and notifies subscribers.
+    pub fn set<T: SettingsSection>(&self, value: T) {
+        self.modify(move |section: &mut T| *section = value);
+    }
+
+    /// Modifies a section using the given closure and notifies subscribers.
+    pub fn modify<T, F>(&self, func: F)
+    where
+        T: SettingsSection,
+        F: FnOnce(&mut T),
+    {
+        let mut state = self.state.lock();
+        func(state.section_mut::<T>());
+        state.notify(T::section_name());
+    }
+
+    /// Resets a section to its default value and notifies subscribers.
+    pub fn reset(&self, section: &str) {
+        let mut state = self.state.lock();
+        if let Some(value) = state.sections.get_mut(section) {
+            value.reset();
+            state.notify(section);
+        }
+    }
+
+    /// Resets every section to default values.
+    pub fn reset_all(&self) {
+        for name in self.section_names() {
+            self.reset(&name);
+        }
+    }
+
+    /// Calls the given closure with reflection of a section, returns `None` if there is no such
+    /// section.
+    pub fn with_section_reflect<F, R>(&self, section: &str, func: F) -> Option<R>
+    where
+        F: FnOnce(&dyn Reflect) -> R,
+    {
+        self.state
+            .lock()
+            .sections
+            .get(section)
+            .map(|value| func(value.as_reflect()))
+    }
+
+    /// Applies property change (produced by an inspector) to a section and notifies subscribers.
+    /// Returns `true` if the change was applied.
+    pub fn apply_property_changed(
+        &self,
+        section: &str,
+        property_changed: &PropertyChanged,
+    ) -> bool {
+        let mut state = self.state.lock();
+        let applied = match state.sections.get_mut(section) {
+            Some(value) => PropertyAction::from_field_kind(&property_changed.value)
+                .apply(&property_changed.path(), value.as_reflect_mut())
+                .is_ok(),
+            None => false,
+        };
+        if applied {
+            state.notify(section);
+        }
+        applied
+    }
+
+    /// Returns a receiver, that will receive an event on every change of settings.
+    pub fn subscribe(&self) -> Receiver<SettingsEvent> {
+        let (sender, receiver) = channel();
+        self.state.lock().listeners.push(sender);
+        receiver
+    }
+
+    /// Serializes every section (including loaded, but not registered ones) into a string.
+    pub fn save_to_string(&self) -> Result<String, SettingsError> {
+        let state = self.state.lock();
+
+        let mut sections = BTreeMap::new();
+        for (name, source) in state.pending.iter() {
+            sections.insert(name.clone(), source.trim_end().to_owned());
+        }
+        for (name, value) in state.sections.iter() {
+            let source = value.to_ron().map_err(|message| SettingsError::Ron {
+                section: name.clone(),
+                message,
+            })?;
+            sections.insert(name.clone(), source);
+        }
+
+        let mut result = String::new();
+        for (name, source) in sections {
+            result += &format!("[{}]\n{}\n\n", name, source);
+        }
+        Ok(result)
+    }
+
+    /// Loads sections from a string, that was produced by [`Self::save_to_string`]. Every
+    /// registered section present in the source is replaced and subscribers are notified. Invalid
+    /// sections are skipped, the first error is returned.
+    pub fn load_from_str(&self, source: &str) -> Result<(), SettingsError> {
+        let mut state = self.state.lock();
+        let mut result = Ok(());
+
+        for (name, body) in split_sections(source) {
+            match state.sections.get_mut(&name) {
+                Some(section) => match section.load_ron(&body) {
+                    Ok(_) => state.notify(&name),
+                    Err(message) => {
+                        if result.is_ok() {
+                            result = Err(SettingsError::Ron {
+                                section: name,
+                                message,
+                            });
+                        }
+                    }
+                },
+                None => {
+                    state.pending.insert(name, body);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Saves every section to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SettingsError> {
+        std::fs::write(path, self.save_to_string()?)?;
+        Ok(())
+    }
+
+    /// Loads sections from a file. See [`Self::load_from_str`] for more info.
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<(), SettingsError> {
+        self.load_from_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Graphics settings of the engine, they're applied to the renderer automatically.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct GraphicsSettings {
+    /// Quality settings of the renderer.
+    pub quality: QualitySettings,
+}
+
+impl SettingsSection for GraphicsSettings {
+    fn section_name() -> &'static str {
+        "graphics"
+    }
+}
+
+/// Audio settings of the engine, they're applied to the sound engine automatically.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Master gain of every sound in the engine.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub master_gain: f32,
+    /// Whether HRTF rendering is allowed or not. When disabled, sounds are rendered using the
+    /// default distance/pan model, which is much cheaper and useful for low-end devices. See
+    /// [`fyrox_sound::engine::SoundEngine::set_hrtf_allowed`].
+    pub hrtf: bool,
+    /// Amount of worker threads for HRTF rendering, `None` keeps the amount that was set on each
+    /// renderer. See [`fyrox_sound::renderer::hrtf::HrtfRenderer::set_parallel_workers`].
+    pub hrtf_parallel_workers: Option<u32>,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_gain: 1.0,
+            hrtf: true,
+            hrtf_parallel_workers: None,
+        }
+    }
+}
+
+impl SettingsSection for AudioSettings {
+    fn section_name() -> &'static str {
+        "audio"
+    }
+}
+
+/// Frame pacing settings of the engine, they're used by [`crate::engine::executor::Executor`] to
+/// decide how often frames should be rendered.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct FramePacingSettings {
+    /// Whether vertical synchronization is enabled or not. The engine changes swap interval of the
+    /// graphics context immediately, if the context allows it (EGL and WGL). Otherwise the change
+    /// takes effect only when the engine is created next time (see
+    /// [`crate::engine::EngineInitParams::vsync`]). Use [`crate::engine::Engine::is_vsync_enabled`]
+    /// to check the actual state.
+    pub vsync: bool,
+    /// Maximum amount of frames per second, when vertical synchronization is disabled. `None` means
+    /// that there is no limit.
+    pub max_fps: Option<u32>,
+    /// Maximum amount of frames per second, when the window is unfocused or minimized. `None` means
+    /// that the frame rate is not throttled.
+    pub background_fps: Option<u32>,
+}
+
+impl Default for FramePacingSettings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            max_fps: None,
+            background_fps: Some(10),
+        }
+    }
+}
+
+impl FramePacingSettings {
+    /// Returns the minimal interval between two consecutive frames in seconds for the given state of
+    /// the window, `None` means that frames should be rendered as fast as possible (or with the rate
+    /// defined by vertical synchronization). `vsync` is the actual state of vertical synchronization
+    /// (see [`crate::engine::Engine::is_vsync_enabled`]).
+    pub fn frame_interval(&self, vsync: bool, background: bool) -> Option<f32> {
+        let fps = if background {
+            self.background_fps
+        } else if vsync {
+            None
+        } else {
+            self.max_fps
+        };
+        fps.filter(|fps| *fps > 0).map(|fps| 1.0 / fps as f32)
+    }
+}
+
+impl SettingsSection for FramePacingSettings {
+    fn section_name() -> &'static str {
+        "frame_pacing"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::reflect::prelude::*,
+        settings::{AudioSettings, FramePacingSettings, Settings, SettingsEvent, SettingsSection},
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect)]
+    struct GameSettings {
+        difficulty: u32,
+        player_name: String,
+    }
+
+    impl SettingsSection for GameSettings {
+        fn section_name() -> &'static str {
+            "game"
+        }
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = Settings::new();
+        let events = settings.subscribe();
+
+        settings.set(AudioSettings {
+            master_gain: 0.25,
+            ..Default::default()
+        });
+        settings.modify(|game: &mut GameSettings| {
+            game.difficulty = 2;
+            game.player_name = "Player [1]".to_string();
+        });
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            vec![
+                SettingsEvent::Changed("audio".to_string()),
+                SettingsEvent::Changed("game".to_string())
+            ]
+        );
+
+        let source = settings.save_to_string().unwrap();
+
+        // Sections of unregistered types are kept until registration.
+        let loaded = Settings::new();
+        loaded.register::<AudioSettings>();
+        loaded.load_from_str(&source).unwrap();
+        assert_eq!(loaded.get::<AudioSettings>().master_gain, 0.25);
+        assert!(!loaded.is_registered("game"));
+        assert_eq!(loaded.save_to_string().unwrap(), source);
+        assert_eq!(loaded.get::<GameSettings>(), settings.get::<GameSettings>());
+
+        loaded.reset_all();
+        assert_eq!(loaded.get::<AudioSettings>(), AudioSettings::default());
+
+        assert!(Settings::new().load_from_str("[audio]\n(foo: 1)").is_ok());
+        let broken = Settings::new();
+        broken.register::<AudioSettings>();
+        assert!(broken
+            .load_from_str("[audio]\n(master_gain: \"x\")")
+            .is_err());
+    }
+
+    #[test]
+    fn test_frame_pacing() {
+        let settings = Settings::new();
+        settings.register::<FramePacingSettings>();
+        // Missing fields are taken from defaults.
+        settings
+            .load_from_str("[frame_pacing]\n(vsync: false, max_fps: Some(120))")
+            .unwrap();
+
+        let pacing = settings.get::<FramePacingSettings>();
+        assert!(!pacing.vsync);
+        assert_eq!(pacing.background_fps, Some(10));
+        assert_eq!(pacing.frame_interval(false, false), Some(1.0 / 120.0));
+        assert_eq!(pacing.frame_interval(true, false), None);
+        assert_eq!(pacing.frame_interval(true, true), Some(0.1));
+    }
+}
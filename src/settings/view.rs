@@ -0,0 +1,166 @@
+//! Automatically generated user interface for settings. See [`SettingsView`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    gui::{
+        expander::ExpanderBuilder,
+        inspector::{
+            editors::{
+                enumeration::EnumPropertyEditorDefinition,
+                inspectable::InspectablePropertyEditorDefinition,
+                PropertyEditorDefinitionContainer,
+            },
+            InspectorBuilder, InspectorContext, InspectorMessage,
+        },
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+    settings::{GraphicsSettings, Settings, SettingsEvent},
+};
+use std::{rc::Rc, sync::mpsc::Receiver};
+
+/// A flag of messages, that were produced by syncing the view with settings.
+const SYNC_FLAG: u64 = 1;
+
+/// A widget tree, that shows every registered section of settings in an inspector. Changes made
+/// by a user are applied to the settings immediately, changes of the settings made by other code
+/// are shown in the view when [`Self::update`] is called. It could be used as a content of a
+/// settings menu of a game.
+///
+/// Sections, that are registered after the view was created, are not shown. Nested structures and
+/// enumerations of custom sections require property editors, pass them using `definitions`
+/// argument (see [`Self::default_definitions`]).
+pub struct SettingsView {
+    settings: Settings,
+    root: Handle<UiNode>,
+    inspectors: Vec<(String, Handle<UiNode>)>,
+    definitions: Rc<PropertyEditorDefinitionContainer>,
+    receiver: Receiver<SettingsEvent>,
+    // Sections changed by the view itself, they do not need to be synced.
+    applied: Vec<String>,
+}
+
+impl SettingsView {
+    /// Returns a container with property editors for standard types and settings sections of the
+    /// engine.
+    pub fn default_definitions() -> PropertyEditorDefinitionContainer {
+        let container = PropertyEditorDefinitionContainer::new();
+        container.insert(InspectablePropertyEditorDefinition::<GraphicsSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+        container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
+        container
+    }
+
+    /// Creates new view for every registered section of the given settings.
+    pub fn new(
+        settings: Settings,
+        definitions: Rc<PropertyEditorDefinitionContainer>,
+        ctx: &mut BuildContext,
+    ) -> Self {
+        let mut inspectors = Vec::new();
+        let mut children = Vec::new();
+
+        for name in settings.section_names() {
+            let context = settings
+                .with_section_reflect(&name, |section| {
+                    InspectorContext::from_object(
+                        section,
+                        ctx,
+                        definitions.clone(),
+                        None,
+                        SYNC_FLAG,
+                        0,
+                    )
+                })
+                .unwrap_or_default();
+
+            let inspector = InspectorBuilder::new(WidgetBuilder::new())
+                .with_context(context)
+                .build(ctx);
+
+            children.push(
+                ExpanderBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                    .with_header(
+                        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+                            .with_text(&name)
+                            .build(ctx),
+                    )
+                    .with_content(inspector)
+                    .with_expanded(true)
+                    .build(ctx),
+            );
+
+            inspectors.push((name, inspector));
+        }
+
+        let root = StackPanelBuilder::new(WidgetBuilder::new().with_children(children)).build(ctx);
+
+        Self {
+            receiver: settings.subscribe(),
+            settings,
+            root,
+            inspectors,
+            definitions,
+            applied: Default::default(),
+        }
+    }
+
+    /// Returns a handle of the root widget of the view.
+    pub fn root(&self) -> Handle<UiNode> {
+        self.root
+    }
+
+    /// Applies changes made by a user to the settings.
+    pub fn handle_ui_message(&mut self, message: &UiMessage) {
+        if message.has_flags(SYNC_FLAG) || message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(InspectorMessage::PropertyChanged(property_changed)) = message.data() {
+            if let Some((name, _)) = self
+                .inspectors
+                .iter()
+                .find(|(_, inspector)| *inspector == message.destination())
+            {
+                if self.settings.apply_property_changed(name, property_changed) {
+                    self.applied.push(name.clone());
+                }
+            }
+        }
+    }
+
+    /// Syncs the view with the settings, must be called every frame.
+    pub fn update(&mut self, ui: &mut UserInterface) {
+        while let Ok(SettingsEvent::Changed(name)) = self.receiver.try_recv() {
+            if let Some(position) = self.applied.iter().position(|applied| *applied == name) {
+                self.applied.remove(position);
+                continue;
+            }
+
+            if let Some((_, inspector)) = self.inspectors.iter().find(|(n, _)| *n == name) {
+                let definitions = self.definitions.clone();
+                if let Some(context) = self.settings.with_section_reflect(&name, |section| {
+                    InspectorContext::from_object(
+                        section,
+                        &mut ui.build_ctx(),
+                        definitions,
+                        None,
+                        SYNC_FLAG,
+                        0,
+                    )
+                }) {
+                    ui.send_message(InspectorMessage::context(
+                        *inspector,
+                        MessageDirection::ToWidget,
+                        context,
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Golden image tests. A golden image is a reference image of a scene rendered by a known good
+//! version of the renderer. [`GoldenImageHarness`] renders a scene offscreen deterministically and
+//! compares the result with a golden image, so any changes of the renderer (shadows, SSAO,
+//! materials, etc.) could be validated automatically.
+//!
+//! Determinism is achieved by fixed time step of updates, fixed amount of updates and fixed seed
+//! of random numbers (see [`crate::core::random`]). Automatic exposure of cameras depends on real
+//! frame time, so cameras in tested scenes should use manual exposure. Small differences between
+//! GPUs and drivers are still possible, so comparison is done with a tolerance.
+//!
+//! If a golden image does not exist, it is created from the rendered frame and the test passes.
+//! Set `FYROX_UPDATE_GOLDEN_IMAGES` environment variable (or
+//! [`GoldenImageConfig::update_golden_images`]) to overwrite existing golden images after
+//! intentional changes of the renderer. When a comparison fails, the rendered frame and an image
+//! with highlighted differences are written next to the golden image (`<name>.actual.png` and
+//! `<name>.diff.png`).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use fyrox::{
+//!     event_loop::EventLoop,
+//!     scene::Scene,
+//!     utils::golden_image::{GoldenImageConfig, GoldenImageHarness},
+//! };
+//!
+//! fn make_scene() -> Scene {
+//!     // Create a scene with a camera here.
+//!     Scene::new()
+//! }
+//!
+//! // Tests usually run on non-main threads, use platform-specific `with_any_thread` of
+//! // `EventLoopBuilder` in this case.
+//! let event_loop = EventLoop::new();
+//! let mut harness = GoldenImageHarness::new(&event_loop, GoldenImageConfig::default()).unwrap();
+//! let difference = harness
+//!     .compare_with_golden(make_scene(), "tests/golden/shadows.png")
+//!     .unwrap();
+//! println!("{} pixels differ", difference.mismatched_pixels);
+//! ```
+
+use crate::{
+    core::{instant, pool::Handle, random},
+    dpi::PhysicalSize,
+    engine::{
+        error::EngineError, resource_manager::ResourceManager, Engine, EngineInitParams,
+        SerializationContext,
+    },
+    event_loop::{ControlFlow, EventLoop},
+    renderer::framework::error::FrameworkError,
+    resource::texture::Texture,
+    scene::Scene,
+    window::WindowBuilder,
+};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Name of an environment variable, that forces the harness to overwrite golden images.
+pub const UPDATE_GOLDEN_IMAGES_ENV: &str = "FYROX_UPDATE_GOLDEN_IMAGES";
+
+/// Settings of the harness.
+#[derive(Clone, Debug)]
+pub struct GoldenImageConfig {
+    /// Width of rendered frames.
+    pub width: u32,
+    /// Height of rendered frames.
+    pub height: u32,
+    /// Amount of updates of a scene before it is rendered. It allows particle systems, animations
+    /// and physics to advance to a known state.
+    pub update_count: usize,
+    /// Time step (in seconds) of each update.
+    pub dt: f32,
+    /// Seed of random numbers.
+    pub seed: u64,
+    /// Maximum difference (0-255) of a color channel, below which two pixels are considered equal.
+    pub tolerance: u8,
+    /// Maximum fraction (0.0 - 1.0) of mismatched pixels, below which two images are considered
+    /// equal.
+    pub max_mismatched_fraction: f32,
+    /// Maximum amount of time to wait until every resource of a scene is loaded.
+    pub loading_timeout: Duration,
+    /// Overwrite existing golden images instead of comparing. See also
+    /// [`UPDATE_GOLDEN_IMAGES_ENV`].
+    pub update_golden_images: bool,
+}
+
+impl Default for GoldenImageConfig {
+    fn default() -> Self {
+        Self {
+            width: 256,
+            height: 256,
+            update_count: 10,
+            dt: 1.0 / 60.0,
+            seed: 0,
+            tolerance: 2,
+            max_mismatched_fraction: 0.001,
+            loading_timeout: Duration::from_secs(30),
+            update_golden_images: std::env::var_os(UPDATE_GOLDEN_IMAGES_ENV).is_some(),
+        }
+    }
+}
+
+/// A frame in RGBA8 format, rows are ordered from top to bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameImage {
+    /// Width of the frame.
+    pub width: usize,
+    /// Height of the frame.
+    pub height: usize,
+    /// Pixels of the frame.
+    pub pixels: Vec<u8>,
+}
+
+/// Result of comparison of two images.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageDifference {
+    /// Amount of pixels, which differ more than the tolerance.
+    pub mismatched_pixels: usize,
+    /// Total amount of pixels.
+    pub total_pixels: usize,
+    /// Maximum difference of a color channel.
+    pub max_difference: u8,
+}
+
+impl ImageDifference {
+    /// Returns fraction (0.0 - 1.0) of mismatched pixels.
+    pub fn mismatched_fraction(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.mismatched_pixels as f32 / self.total_pixels as f32
+        }
+    }
+}
+
+/// An error, that could occur in the harness.
+#[derive(Debug)]
+pub enum GoldenImageError {
+    /// Unable to initialize the engine.
+    Engine(EngineError),
+    /// Rendering error.
+    Renderer(FrameworkError),
+    /// Unable to load or save an image.
+    Image(image::ImageError),
+    /// Resources of a scene weren't loaded in time.
+    LoadingTimeout,
+    /// A scene was not rendered, most likely it has no cameras.
+    NothingRendered,
+    /// Size of the rendered frame does not match the size of the golden image.
+    SizeMismatch {
+        /// Size of the rendered frame.
+        actual: (usize, usize),
+        /// Size of the golden image.
+        expected: (usize, usize),
+    },
+    /// The rendered frame differs from the golden image.
+    Mismatch {
+        /// Difference between the frame and the golden image.
+        difference: ImageDifference,
+        /// A path of an image with highlighted differences.
+        diff_path: PathBuf,
+    },
+}
+
+impl Display for GoldenImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenImageError::Engine(v) => Display::fmt(v, f),
+            GoldenImageError::Renderer(v) => Display::fmt(v, f),
+            GoldenImageError::Image(v) => Display::fmt(v, f),
+            GoldenImageError::LoadingTimeout => write!(f, "Resources weren't loaded in time."),
+            GoldenImageError::NothingRendered => write!(f, "Scene was not rendered."),
+            GoldenImageError::SizeMismatch { actual, expected } => write!(
+                f,
+                "Frame size {}x{} does not match golden image size {}x{}.",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            GoldenImageError::Mismatch {
+                difference,
+                diff_path,
+            } => write!(
+                f,
+                "{} of {} pixels differ (max difference {}). See {} for details.",
+                difference.mismatched_pixels,
+                difference.total_pixels,
+                difference.max_difference,
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl From<EngineError> for GoldenImageError {
+    fn from(e: EngineError) -> Self {
+        Self::Engine(e)
+    }
+}
+
+impl From<FrameworkError> for GoldenImageError {
+    fn from(e: FrameworkError) -> Self {
+        Self::Renderer(e)
+    }
+}
+
+impl From<image::ImageError> for GoldenImageError {
+    fn from(e: image::ImageError) -> Self {
+        Self::Image(e)
+    }
+}
+
+impl FrameImage {
+    /// Loads an image from a file and converts it to RGBA8.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GoldenImageError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels: image.into_raw(),
+        })
+    }
+
+    /// Saves the image to a file, format is defined by the extension of the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GoldenImageError> {
+        if let Some(parent) = path.as_ref().parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        image::save_buffer(
+            path,
+            &self.pixels,
+            self.width as u32,
+            self.height as u32,
+            image::ColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+
+    /// Compares the image with other image of the same size. Two pixels are equal if difference
+    /// of every color channel is less or equal to the tolerance.
+    pub fn compare(&self, other: &FrameImage, tolerance: u8) -> ImageDifference {
+        let mut difference = ImageDifference {
+            total_pixels: self.width * self.height,
+            ..Default::default()
+        };
+
+        for (a, b) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+        {
+            let max = a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| (*a as i16 - *b as i16).unsigned_abs() as u8)
+                .max()
+                .unwrap_or_default();
+            difference.max_difference = difference.max_difference.max(max);
+            if max > tolerance {
+                difference.mismatched_pixels += 1;
+            }
+        }
+
+        difference
+    }
+
+    /// Creates an image, where mismatched pixels are red and other pixels are dimmed grayscale
+    /// pixels of the image.
+    pub fn make_diff(&self, other: &FrameImage, tolerance: u8) -> FrameImage {
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for (a, b) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+        {
+            let mismatch = a
+                .iter()
+                .zip(b)
+                .any(|(a, b)| (*a as i16 - *b as i16).unsigned_abs() as u8 > tolerance);
+            if mismatch {
+                pixels.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                let luminance = ((a[0] as u32 + a[1] as u32 + a[2] as u32) / 3 / 4) as u8;
+                pixels.extend_from_slice(&[luminance, luminance, luminance, 255]);
+            }
+        }
+        FrameImage {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+/// See module docs.
+pub struct GoldenImageHarness {
+    engine: Engine,
+    config: GoldenImageConfig,
+}
+
+impl GoldenImageHarness {
+    /// Creates new harness with an engine, that has an invisible window. Random numbers of the
+    /// current thread are seeded, so the harness must be used from the thread it was created on.
+    pub fn new(
+        event_loop: &EventLoop<()>,
+        config: GoldenImageConfig,
+    ) -> Result<Self, GoldenImageError> {
+        // Seed before the engine is created, because the renderer uses random numbers too.
+        random::set_seed(Some(config.seed));
+
+        let serialization_context = Arc::new(SerializationContext::new());
+        let engine = Engine::new(EngineInitParams {
+            window_builder: WindowBuilder::new()
+                .with_title("Golden Image Test")
+                .with_inner_size(PhysicalSize::new(config.width, config.height))
+                .with_visible(false),
+            resource_manager: ResourceManager::new(serialization_context.clone()),
+            serialization_context,
+            events_loop: event_loop,
+            vsync: false,
+        })?;
+
+        Ok(Self { engine, config })
+    }
+
+    /// Returns a reference to the engine, it could be used to load resources.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Returns a reference to the engine, it could be used to change renderer settings.
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Returns current config.
+    pub fn config(&self) -> &GoldenImageConfig {
+        &self.config
+    }
+
+    /// Renders the given scene offscreen and returns the frame. The scene is updated
+    /// [`GoldenImageConfig::update_count`] times with fixed time step after all its resources are
+    /// loaded, then it is rendered and removed from the engine.
+    pub fn render(&mut self, mut scene: Scene) -> Result<FrameImage, GoldenImageError> {
+        random::set_seed(Some(self.config.seed));
+
+        let wait_context = self
+            .engine
+            .resource_manager
+            .state()
+            .containers()
+            .get_wait_context();
+        let start = instant::Instant::now();
+        while !wait_context.is_all_loaded() {
+            if instant::Instant::now() - start > self.config.loading_timeout {
+                return Err(GoldenImageError::LoadingTimeout);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        scene.render_target = Some(Texture::new_render_target(
+            self.config.width,
+            self.config.height,
+        ));
+        let handle = self.engine.scenes.add(scene);
+
+        let result = self.render_scene(handle);
+
+        self.engine.scenes.remove(handle);
+
+        result
+    }
+
+    fn render_scene(&mut self, handle: Handle<Scene>) -> Result<FrameImage, GoldenImageError> {
+        let mut control_flow = ControlFlow::Poll;
+        for _ in 0..self.config.update_count.max(1) {
+            self.engine
+                .update(self.config.dt, &mut control_flow, &mut 0.0);
+        }
+        self.engine.render()?;
+
+        self.engine
+            .renderer
+            .read_scene_frame(handle)
+            .map(|(width, height, pixels)| FrameImage {
+                width,
+                height,
+                pixels,
+            })
+            .ok_or(GoldenImageError::NothingRendered)
+    }
+
+    /// Renders the given scene and compares the frame with the golden image at the given path.
+    /// The golden image is created if it does not exist (or if golden images must be updated).
+    /// Returns the difference if the frame matches the golden image within tolerance. See module
+    /// docs for more info.
+    pub fn compare_with_golden<P: AsRef<Path>>(
+        &mut self,
+        scene: Scene,
+        golden_path: P,
+    ) -> Result<ImageDifference, GoldenImageError> {
+        let golden_path = golden_path.as_ref();
+        let frame = self.render(scene)?;
+
+        if self.config.update_golden_images || !golden_path.exists() {
+            frame.save(golden_path)?;
+            return Ok(ImageDifference {
+                total_pixels: frame.width * frame.height,
+                ..Default::default()
+            });
+        }
+
+        let golden = FrameImage::load(golden_path)?;
+        if golden.width != frame.width || golden.height != frame.height {
+            frame.save(sibling_path(golden_path, "actual"))?;
+            return Err(GoldenImageError::SizeMismatch {
+                actual: (frame.width, frame.height),
+                expected: (golden.width, golden.height),
+            });
+        }
+
+        let difference = frame.compare(&golden, self.config.tolerance);
+        if difference.mismatched_fraction() > self.config.max_mismatched_fraction {
+            let diff_path = sibling_path(golden_path, "diff");
+            frame.save(sibling_path(golden_path, "actual"))?;
+            frame
+                .make_diff(&golden, self.config.tolerance)
+                .save(&diff_path)?;
+            Err(GoldenImageError::Mismatch {
+                difference,
+                diff_path,
+            })
+        } else {
+            Ok(difference)
+        }
+    }
+}
+
+impl Drop for GoldenImageHarness {
+    fn drop(&mut self) {
+        random::set_seed(None);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::golden_image::FrameImage;
+
+    #[test]
+    fn test_compare() {
+        let a = FrameImage {
+            width: 2,
+            height: 1,
+            pixels: vec![10, 20, 30, 255, 0, 0, 0, 255],
+        };
+        let b = FrameImage {
+            width: 2,
+            height: 1,
+            pixels: vec![11, 20, 30, 255, 0, 100, 0, 255],
+        };
+
+        let difference = a.compare(&b, 2);
+        assert_eq!(difference.mismatched_pixels, 1);
+        assert_eq!(difference.total_pixels, 2);
+        assert_eq!(difference.max_difference, 100);
+
+        let diff = a.make_diff(&b, 2);
+        assert_eq!(&diff.pixels[4..], &[255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,254 @@
+//! Light probes baking. Light probes store indirect (bounced) lighting of static environment,
+//! that is used by the renderer instead of flat ambient lighting. See [`bake_light_probes`] docs
+//! for more info.
+//!
+//! # Performance
+//!
+//! Baking uses the same CPU ray tracer as the lightmapper, its performance is linear with core
+//! count of your CPU.
+
+use crate::{
+    core::{algebra::Vector3, math::ray::Ray, pool::Handle},
+    scene::{
+        light::probe::{LightProbeVolume, SphericalHarmonics},
+        mesh::Mesh,
+        node::Node,
+        Scene,
+    },
+    utils::lightmap::{
+        direct_lighting, trace_ray, CancellationToken, Instance, LightDefinition,
+        LightmapGenerationError, ProgressIndicator, ProgressStage,
+    },
+};
+use rayon::prelude::*;
+use std::f32::consts::PI;
+
+/// Fraction of light that is reflected by surfaces of the scene. Materials of surfaces are not
+/// used while baking, every surface is treated as gray diffuse surface.
+pub const DEFAULT_ALBEDO: f32 = 0.5;
+
+/// Maximum length of rays traced from probes.
+pub const MAX_RAY_DISTANCE: f32 = 1000.0;
+
+/// Returns `index`-th of `count` directions uniformly distributed over unit sphere (Fibonacci
+/// sphere).
+fn sphere_direction(index: u32, count: u32) -> Vector3<f32> {
+    let y = 1.0 - 2.0 * (index as f32 + 0.5) / count as f32;
+    let radius = (1.0 - y * y).max(0.0).sqrt();
+    let phi = index as f32 * PI * (3.0 - 5.0f32.sqrt());
+    Vector3::new(radius * phi.cos(), y, radius * phi.sin())
+}
+
+fn bake_probe(
+    position: Vector3<f32>,
+    rays_per_probe: u32,
+    lights: &[LightDefinition],
+    instances: &[Instance],
+    ambient_color: Vector3<f32>,
+) -> SphericalHarmonics {
+    let mut probe = SphericalHarmonics::default();
+    let weight = 4.0 * PI / rays_per_probe as f32;
+    for i in 0..rays_per_probe {
+        let direction = sphere_direction(i, rays_per_probe);
+        let ray = Ray::new(position, direction.scale(MAX_RAY_DISTANCE));
+        let radiance = match trace_ray(&ray, instances) {
+            Some((hit_position, hit_normal)) => {
+                if hit_normal.dot(&direction) < 0.0 {
+                    direct_lighting(hit_position, hit_normal, lights, instances)
+                        .scale(DEFAULT_ALBEDO)
+                } else {
+                    // Back face - the probe is most likely inside of an object.
+                    Vector3::default()
+                }
+            }
+            // Ray escaped the scene, use ambient lighting as "sky" lighting.
+            None => ambient_color,
+        };
+        probe.add_sample(direction, radiance, weight);
+    }
+    probe
+}
+
+/// Bakes every [`LightProbeVolume`] of the scene. For each probe, `rays_per_probe` rays are
+/// traced in all directions, direct lighting at intersection points is gathered and projected
+/// into spherical harmonics. Rays that do not hit anything receive ambient lighting of the
+/// scene. This method is blocking, however internally it uses massive parallelism to use all
+/// available CPU power efficiently.
+///
+/// Returns total amount of baked probes.
+pub fn bake_light_probes(
+    scene: &mut Scene,
+    rays_per_probe: u32,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+) -> Result<usize, LightmapGenerationError> {
+    scene.graph.update_hierarchical_data();
+
+    let rays_per_probe = rays_per_probe.max(1);
+
+    let light_count = scene
+        .graph
+        .pair_iter()
+        .filter(|(handle, node)| LightDefinition::from_node(*handle, node).is_some())
+        .count();
+    progress_indicator.set_stage(ProgressStage::LightsCaching, light_count as u32);
+
+    let mut lights = Vec::with_capacity(light_count);
+    for (handle, node) in scene.graph.pair_iter() {
+        if cancellation_token.is_cancelled() {
+            return Err(LightmapGenerationError::Cancelled);
+        }
+
+        if let Some(definition) = LightDefinition::from_node(handle, node) {
+            lights.push(definition);
+            progress_indicator.advance_progress();
+        }
+    }
+
+    let mut instances = Vec::new();
+    for (handle, node) in scene.graph.pair_iter() {
+        if let Some(mesh) = node.cast::<Mesh>() {
+            if mesh.global_visibility() {
+                for surface in mesh.surfaces() {
+                    instances.push(Instance::new(
+                        handle,
+                        surface.data(),
+                        mesh.global_transform(),
+                    ));
+                }
+            }
+        }
+    }
+
+    progress_indicator.set_stage(ProgressStage::GeometryCaching, instances.len() as u32);
+
+    instances
+        .par_iter_mut()
+        .map(|instance: &mut Instance| {
+            if cancellation_token.is_cancelled() {
+                Err(LightmapGenerationError::Cancelled)
+            } else {
+                instance.build_data();
+                progress_indicator.advance_progress();
+                Ok(())
+            }
+        })
+        .collect::<Result<(), LightmapGenerationError>>()?;
+
+    let volumes = scene
+        .graph
+        .pair_iter()
+        .filter_map(|(handle, node)| {
+            node.cast::<LightProbeVolume>()
+                .map(|volume| (handle, volume.probe_world_positions()))
+        })
+        .collect::<Vec<(Handle<Node>, Vec<Vector3<f32>>)>>();
+
+    let total_probes = volumes.iter().map(|(_, p)| p.len()).sum::<usize>();
+    progress_indicator.set_stage(ProgressStage::BakingProbes, total_probes as u32);
+
+    let ambient_color = scene.ambient_lighting_color.srgb_to_linear().as_frgb();
+
+    for (handle, positions) in volumes {
+        let probes = positions
+            .par_iter()
+            .map(|position| {
+                if cancellation_token.is_cancelled() {
+                    Err(LightmapGenerationError::Cancelled)
+                } else {
+                    let probe = bake_probe(
+                        *position,
+                        rays_per_probe,
+                        &lights,
+                        &instances,
+                        ambient_color,
+                    );
+                    progress_indicator.advance_progress();
+                    Ok(probe)
+                }
+            })
+            .collect::<Result<Vec<_>, LightmapGenerationError>>()?;
+
+        if let Some(volume) = scene.graph[handle].cast_mut::<LightProbeVolume>() {
+            volume.set_probes(probes);
+        }
+    }
+
+    Ok(total_probes)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            color::Color,
+        },
+        scene::{
+            base::BaseBuilder,
+            light::{
+                point::PointLightBuilder,
+                probe::{LightProbeVolume, LightProbeVolumeBuilder},
+                BaseLightBuilder,
+            },
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceSharedData},
+                MeshBuilder,
+            },
+            transform::TransformBuilder,
+            Scene,
+        },
+        utils::light_probe::bake_light_probes,
+    };
+
+    #[test]
+    fn test_bake_light_probes() {
+        let mut scene = Scene::new();
+        scene.ambient_lighting_color = Color::BLACK;
+
+        // Floor.
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceSharedData::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    10.0, 0.1, 10.0,
+                ))),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 3.0, 0.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(6.0)
+        .build(&mut scene.graph);
+
+        let volume = LightProbeVolumeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .with_local_scale(Vector3::new(2.0, 1.0, 2.0))
+                    .build(),
+            ),
+        )
+        .with_resolution(Vector3::new(2, 2, 2))
+        .build(&mut scene.graph);
+
+        let count =
+            bake_light_probes(&mut scene, 256, Default::default(), Default::default()).unwrap();
+        assert_eq!(count, 8);
+
+        let volume = scene.graph[volume].cast::<LightProbeVolume>().unwrap();
+        assert!(volume.is_baked());
+
+        // Light bounced from the floor comes from below, nothing comes from the "sky".
+        let probe = volume.sample(Vector3::new(0.0, 1.0, 0.0)).unwrap();
+        let from_floor = probe.diffuse(-Vector3::y());
+        let from_sky = probe.diffuse(Vector3::y());
+        assert!(from_floor.x > 0.01);
+        assert!(from_floor.x > from_sky.x);
+    }
+}
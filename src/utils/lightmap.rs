@@ -85,7 +85,7 @@ struct InstanceData {
     octree: Octree,
 }
 
-struct Instance {
+pub(crate) struct Instance {
     owner: Handle<Node>,
     source_data: SurfaceSharedData,
     data: Option<InstanceData>,
@@ -93,9 +93,80 @@ struct Instance {
 }
 
 impl Instance {
-    pub fn data(&self) -> &InstanceData {
+    /// Creates new instance of the surface data with the given world transform. World-space data
+    /// is not calculated, call [`Self::build_data`] to do that.
+    pub(crate) fn new(
+        owner: Handle<Node>,
+        source_data: SurfaceSharedData,
+        transform: Matrix4<f32>,
+    ) -> Self {
+        Self {
+            owner,
+            source_data,
+            data: None,
+            transform,
+        }
+    }
+
+    fn data(&self) -> &InstanceData {
         self.data.as_ref().unwrap()
     }
+
+    /// Calculates world-space vertices of the instance and builds an octree for ray tracing.
+    pub(crate) fn build_data(&mut self) {
+        let data = self.source_data.lock();
+
+        let normal_matrix = self
+            .transform
+            .basis()
+            .try_inverse()
+            .map(|m| m.transpose())
+            .unwrap_or_else(Matrix3::identity);
+
+        let world_vertices = data
+            .vertex_buffer
+            .iter()
+            .map(|view| {
+                let world_position = self
+                    .transform
+                    .transform_point(&Point3::from(
+                        view.read_3_f32(VertexAttributeUsage::Position).unwrap(),
+                    ))
+                    .coords;
+                let world_normal = (normal_matrix
+                    * view.read_3_f32(VertexAttributeUsage::Normal).unwrap())
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default();
+                WorldVertex {
+                    world_normal,
+                    world_position,
+                    // Secondary texture coordinates are missing if the instance is used only
+                    // for ray tracing (light probes baking).
+                    second_tex_coord: view
+                        .read_2_f32(VertexAttributeUsage::TexCoord1)
+                        .unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let world_triangles = data
+            .geometry_buffer
+            .iter()
+            .map(|tri| {
+                [
+                    world_vertices[tri[0] as usize].world_position,
+                    world_vertices[tri[1] as usize].world_position,
+                    world_vertices[tri[2] as usize].world_position,
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        self.data = Some(InstanceData {
+            vertices: world_vertices,
+            triangles: data.geometry_buffer.triangles_ref().to_vec(),
+            octree: Octree::new(&world_triangles, 64),
+        });
+    }
 }
 
 /// Small helper that allows you stop lightmap generation in any time.
@@ -131,6 +202,8 @@ pub enum ProgressStage {
     GeometryCaching = 2,
     /// Actual lightmap generation.
     CalculatingLight = 3,
+    /// Baking of light probes.
+    BakingProbes = 4,
 }
 
 /// Progress internals.
@@ -160,12 +233,13 @@ impl ProgressData {
             1 => ProgressStage::UvGeneration,
             2 => ProgressStage::GeometryCaching,
             3 => ProgressStage::CalculatingLight,
+            4 => ProgressStage::BakingProbes,
             _ => unreachable!(),
         }
     }
 
     /// Sets new stage with max iterations per stage.
-    fn set_stage(&self, stage: ProgressStage, max_iterations: u32) {
+    pub(crate) fn set_stage(&self, stage: ProgressStage, max_iterations: u32) {
         self.max_iterations
             .store(max_iterations, atomic::Ordering::SeqCst);
         self.progress.store(0, atomic::Ordering::SeqCst);
@@ -173,7 +247,7 @@ impl ProgressData {
     }
 
     /// Advances progress.
-    fn advance_progress(&self) {
+    pub(crate) fn advance_progress(&self) {
         self.progress.fetch_add(1, atomic::Ordering::SeqCst);
     }
 }
@@ -264,49 +338,10 @@ impl Lightmap {
                 return Err(LightmapGenerationError::Cancelled);
             }
 
-            if let Some(point) = light.cast::<PointLight>() {
-                lights.push(LightDefinition::Point(PointLightDefinition {
-                    handle,
-                    intensity: 1.0,
-                    position: light.global_position(),
-                    color: point.base_light_ref().color().srgb_to_linear().as_frgb(),
-                    radius: point.radius(),
-                    sqr_radius: point.radius() * point.radius(),
-                }))
-            } else if let Some(spot) = light.cast::<SpotLight>() {
-                lights.push(LightDefinition::Spot(SpotLightDefinition {
-                    handle,
-                    intensity: 1.0,
-                    edge0: ((spot.hotspot_cone_angle() + spot.falloff_angle_delta()) * 0.5).cos(),
-                    edge1: (spot.hotspot_cone_angle() * 0.5).cos(),
-                    color: spot.base_light_ref().color().srgb_to_linear().as_frgb(),
-                    direction: light
-                        .up_vector()
-                        .try_normalize(std::f32::EPSILON)
-                        .unwrap_or_else(Vector3::y),
-                    position: light.global_position(),
-                    distance: spot.distance(),
-                    sqr_distance: spot.distance() * spot.distance(),
-                }))
-            } else if let Some(directional) = light.cast::<DirectionalLight>() {
-                lights.push(LightDefinition::Directional(DirectionalLightDefinition {
-                    handle,
-                    intensity: 1.0,
-                    direction: light
-                        .up_vector()
-                        .try_normalize(std::f32::EPSILON)
-                        .unwrap_or_else(Vector3::y),
-                    color: directional
-                        .base_light_ref()
-                        .color()
-                        .srgb_to_linear()
-                        .as_frgb(),
-                }))
-            } else {
-                continue;
-            };
-
-            progress_indicator.advance_progress()
+            if let Some(definition) = LightDefinition::from_node(handle, light) {
+                lights.push(definition);
+                progress_indicator.advance_progress()
+            }
         }
 
         let mut instances = Vec::new();
@@ -324,13 +359,8 @@ impl Lightmap {
                     let key = &*data.lock() as *const _ as u64;
                     data_set.entry(key).or_insert_with(|| surface.data());
 
-                    instances.push(Instance {
-                        owner: handle,
-                        source_data: data.clone(),
-                        transform: global_transform,
-                        // Calculated down below.
-                        data: None,
-                    });
+                    // World-space data is calculated down below.
+                    instances.push(Instance::new(handle, data.clone(), global_transform));
                 }
             }
         }
@@ -359,56 +389,7 @@ impl Lightmap {
                 if cancellation_token.is_cancelled() {
                     Err(LightmapGenerationError::Cancelled)
                 } else {
-                    let data = instance.source_data.lock();
-
-                    let normal_matrix = instance
-                        .transform
-                        .basis()
-                        .try_inverse()
-                        .map(|m| m.transpose())
-                        .unwrap_or_else(Matrix3::identity);
-
-                    let world_vertices = data
-                        .vertex_buffer
-                        .iter()
-                        .map(|view| {
-                            let world_position = instance
-                                .transform
-                                .transform_point(&Point3::from(
-                                    view.read_3_f32(VertexAttributeUsage::Position).unwrap(),
-                                ))
-                                .coords;
-                            let world_normal = (normal_matrix
-                                * view.read_3_f32(VertexAttributeUsage::Normal).unwrap())
-                            .try_normalize(f32::EPSILON)
-                            .unwrap_or_default();
-                            WorldVertex {
-                                world_normal,
-                                world_position,
-                                second_tex_coord: view
-                                    .read_2_f32(VertexAttributeUsage::TexCoord1)
-                                    .unwrap(),
-                            }
-                        })
-                        .collect::<Vec<_>>();
-
-                    let world_triangles = data
-                        .geometry_buffer
-                        .iter()
-                        .map(|tri| {
-                            [
-                                world_vertices[tri[0] as usize].world_position,
-                                world_vertices[tri[1] as usize].world_position,
-                                world_vertices[tri[2] as usize].world_position,
-                            ]
-                        })
-                        .collect::<Vec<_>>();
-
-                    instance.data = Some(InstanceData {
-                        vertices: world_vertices,
-                        triangles: data.geometry_buffer.triangles_ref().to_vec(),
-                        octree: Octree::new(&world_triangles, 64),
-                    });
+                    instance.build_data();
 
                     progress_indicator.advance_progress();
 
@@ -520,6 +501,52 @@ pub enum LightDefinition {
 }
 
 impl LightDefinition {
+    /// Creates light definition from the given scene node. Returns `None` if the node is not a
+    /// light source.
+    pub fn from_node(handle: Handle<Node>, light: &Node) -> Option<Self> {
+        if let Some(point) = light.cast::<PointLight>() {
+            Some(LightDefinition::Point(PointLightDefinition {
+                handle,
+                intensity: 1.0,
+                position: light.global_position(),
+                color: point.base_light_ref().color().srgb_to_linear().as_frgb(),
+                radius: point.radius(),
+                sqr_radius: point.radius() * point.radius(),
+            }))
+        } else if let Some(spot) = light.cast::<SpotLight>() {
+            Some(LightDefinition::Spot(SpotLightDefinition {
+                handle,
+                intensity: 1.0,
+                edge0: ((spot.hotspot_cone_angle() + spot.falloff_angle_delta()) * 0.5).cos(),
+                edge1: (spot.hotspot_cone_angle() * 0.5).cos(),
+                color: spot.base_light_ref().color().srgb_to_linear().as_frgb(),
+                direction: light
+                    .up_vector()
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_else(Vector3::y),
+                position: light.global_position(),
+                distance: spot.distance(),
+                sqr_distance: spot.distance() * spot.distance(),
+            }))
+        } else {
+            light.cast::<DirectionalLight>().map(|directional| {
+                LightDefinition::Directional(DirectionalLightDefinition {
+                    handle,
+                    intensity: 1.0,
+                    direction: light
+                        .up_vector()
+                        .try_normalize(std::f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                    color: directional
+                        .base_light_ref()
+                        .color()
+                        .srgb_to_linear()
+                        .as_frgb(),
+                })
+            })
+        }
+    }
+
     fn handle(&self) -> Handle<Node> {
         match self {
             LightDefinition::Directional(v) => v.handle,
@@ -665,6 +692,132 @@ fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
     k * k * (3.0 - 2.0 * k)
 }
 
+/// Calculates direct lighting from the given light sources at a point with the given normal.
+/// Shadows are calculated by tracing rays from the point to each light through the given
+/// instances.
+pub(crate) fn direct_lighting(
+    world_position: Vector3<f32>,
+    world_normal: Vector3<f32>,
+    lights: &[LightDefinition],
+    instances: &[Instance],
+) -> Vector3<f32> {
+    let mut pixel_color = Vector3::default();
+    for light in lights {
+        let (light_color, mut attenuation, light_position) = match light {
+            LightDefinition::Directional(directional) => {
+                let attenuation =
+                    directional.intensity * lambertian(directional.direction, world_normal);
+                (directional.color, attenuation, Vector3::default())
+            }
+            LightDefinition::Spot(spot) => {
+                let d = spot.position - world_position;
+                let distance = d.norm();
+                let light_vec = d.scale(1.0 / distance);
+                let spot_angle_cos = light_vec.dot(&spot.direction);
+                let cone_factor = smoothstep(spot.edge0, spot.edge1, spot_angle_cos);
+                let attenuation = cone_factor
+                    * spot.intensity
+                    * lambertian(light_vec, world_normal)
+                    * distance_attenuation(distance, spot.sqr_distance);
+                (spot.color, attenuation, spot.position)
+            }
+            LightDefinition::Point(point) => {
+                let d = point.position - world_position;
+                let distance = d.norm();
+                let light_vec = d.scale(1.0 / distance);
+                let attenuation = point.intensity
+                    * lambertian(light_vec, world_normal)
+                    * distance_attenuation(distance, point.sqr_radius);
+                (point.color, attenuation, point.position)
+            }
+        };
+        // Shadows
+        if attenuation >= 0.01 {
+            let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
+            let shadow_bias = 0.01;
+            let ray = Ray::from_two_points(light_position, world_position);
+            'outer_loop: for other_instance in instances {
+                other_instance
+                    .data()
+                    .octree
+                    .ray_query_static(&ray, &mut query_buffer);
+                for &node in query_buffer.iter() {
+                    match other_instance.data().octree.node(node) {
+                        OctreeNode::Leaf { indices, .. } => {
+                            let other_data = other_instance.data();
+                            for &triangle_index in indices {
+                                let triangle = &other_data.triangles[triangle_index as usize];
+                                let va = other_data.vertices[triangle[0] as usize].world_position;
+                                let vb = other_data.vertices[triangle[1] as usize].world_position;
+                                let vc = other_data.vertices[triangle[2] as usize].world_position;
+                                if let Some(pt) = ray.triangle_intersection_point(&[va, vb, vc]) {
+                                    if ray.origin.metric_distance(&pt) + shadow_bias
+                                        < ray.dir.norm()
+                                    {
+                                        attenuation = 0.0;
+                                        break 'outer_loop;
+                                    }
+                                }
+                            }
+                        }
+                        OctreeNode::Branch { .. } => unreachable!(),
+                    }
+                }
+            }
+        }
+        pixel_color += light_color.scale(attenuation);
+    }
+    pixel_color
+}
+
+/// Traces a ray through the given instances and returns world position and interpolated normal
+/// at the closest intersection (if any).
+pub(crate) fn trace_ray(ray: &Ray, instances: &[Instance]) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
+    let mut closest: Option<(f32, Vector3<f32>, Vector3<f32>)> = None;
+    for instance in instances {
+        let data = instance.data();
+        data.octree.ray_query_static(ray, &mut query_buffer);
+        for &node in query_buffer.iter() {
+            if let OctreeNode::Leaf { indices, .. } = data.octree.node(node) {
+                for &triangle_index in indices {
+                    let triangle = &data.triangles[triangle_index as usize];
+                    let a = &data.vertices[triangle[0] as usize];
+                    let b = &data.vertices[triangle[1] as usize];
+                    let c = &data.vertices[triangle[2] as usize];
+                    if let Some(pt) = ray.triangle_intersection_point(&[
+                        a.world_position,
+                        b.world_position,
+                        c.world_position,
+                    ]) {
+                        let distance = ray.origin.metric_distance(&pt);
+                        if closest
+                            .map_or(true, |(closest_distance, _, _)| distance < closest_distance)
+                        {
+                            let barycentric = math::get_barycentric_coords(
+                                &pt,
+                                &a.world_position,
+                                &b.world_position,
+                                &c.world_position,
+                            );
+                            let normal = math::barycentric_to_world(
+                                barycentric,
+                                a.world_normal,
+                                b.world_normal,
+                                c.world_normal,
+                            )
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_default();
+                            closest = Some((distance, pt, normal));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    closest.map(|(_, position, normal)| (position, normal))
+}
+
 /// Generates lightmap for given surface data with specified transform.
 ///
 /// # Performance
@@ -698,78 +851,8 @@ fn generate_lightmap(
             let uv = Vector2::new(x as f32 * scale + half_pixel, y as f32 * scale + half_pixel);
 
             if let Some((world_position, world_normal)) = pick(uv, &grid, instance.data(), scale) {
-                let mut pixel_color = Vector3::default();
-                for light in lights {
-                    let (light_color, mut attenuation, light_position) = match light {
-                        LightDefinition::Directional(directional) => {
-                            let attenuation = directional.intensity
-                                * lambertian(directional.direction, world_normal);
-                            (directional.color, attenuation, Vector3::default())
-                        }
-                        LightDefinition::Spot(spot) => {
-                            let d = spot.position - world_position;
-                            let distance = d.norm();
-                            let light_vec = d.scale(1.0 / distance);
-                            let spot_angle_cos = light_vec.dot(&spot.direction);
-                            let cone_factor = smoothstep(spot.edge0, spot.edge1, spot_angle_cos);
-                            let attenuation = cone_factor
-                                * spot.intensity
-                                * lambertian(light_vec, world_normal)
-                                * distance_attenuation(distance, spot.sqr_distance);
-                            (spot.color, attenuation, spot.position)
-                        }
-                        LightDefinition::Point(point) => {
-                            let d = point.position - world_position;
-                            let distance = d.norm();
-                            let light_vec = d.scale(1.0 / distance);
-                            let attenuation = point.intensity
-                                * lambertian(light_vec, world_normal)
-                                * distance_attenuation(distance, point.sqr_radius);
-                            (point.color, attenuation, point.position)
-                        }
-                    };
-                    // Shadows
-                    if attenuation >= 0.01 {
-                        let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
-                        let shadow_bias = 0.01;
-                        let ray = Ray::from_two_points(light_position, world_position);
-                        'outer_loop: for other_instance in other_instances {
-                            other_instance
-                                .data()
-                                .octree
-                                .ray_query_static(&ray, &mut query_buffer);
-                            for &node in query_buffer.iter() {
-                                match other_instance.data().octree.node(node) {
-                                    OctreeNode::Leaf { indices, .. } => {
-                                        let other_data = other_instance.data();
-                                        for &triangle_index in indices {
-                                            let triangle =
-                                                &other_data.triangles[triangle_index as usize];
-                                            let va = other_data.vertices[triangle[0] as usize]
-                                                .world_position;
-                                            let vb = other_data.vertices[triangle[1] as usize]
-                                                .world_position;
-                                            let vc = other_data.vertices[triangle[2] as usize]
-                                                .world_position;
-                                            if let Some(pt) =
-                                                ray.triangle_intersection_point(&[va, vb, vc])
-                                            {
-                                                if ray.origin.metric_distance(&pt) + shadow_bias
-                                                    < ray.dir.norm()
-                                                {
-                                                    attenuation = 0.0;
-                                                    break 'outer_loop;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    OctreeNode::Branch { .. } => unreachable!(),
-                                }
-                            }
-                        }
-                    }
-                    pixel_color += light_color.scale(attenuation);
-                }
+                let pixel_color =
+                    direct_lighting(world_position, world_normal, lights, other_instances);
 
                 *pixel = Vector4::new(
                     (pixel_color.x.max(0.0).min(1.0) * 255.0) as u8,
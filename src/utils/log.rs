@@ -1,8 +1,31 @@
-//! Simple logger, it writes in file and in console at the same time.
+//! Structured logger. Every message has a kind (level), a category, a time stamp and an optional
+//! path of an asset it relates to. Messages are passed to a set of sinks - by default the log
+//! writes to stdout and to `fyrox.log` file (with rotation), other sinks could be added using
+//! [`Log::add_sink`].
+//!
+//! ```rust,no_run
+//! use fyrox::utils::log::{Log, MemorySink, MessageKind};
+//!
+//! // Keep last 100 messages in memory (for example to show them in a game UI).
+//! let memory = MemorySink::new(100);
+//! Log::add_sink(Box::new(memory.clone()));
+//!
+//! // Hide information messages of a noisy category.
+//! Log::set_category_verbosity("AI", MessageKind::Warning);
+//!
+//! Log::info_in("AI", "Path found.");
+//! Log::warn_in("AI", "Unable to find a path!");
+//! assert_eq!(memory.messages().len(), 1);
+//! ```
 
 use crate::core::parking_lot::Mutex;
 use crate::lazy_static::lazy_static;
-use std::fmt::Debug;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use fyrox_core::instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
@@ -22,29 +45,193 @@ extern "C" {
     fn log(s: &str);
 }
 
+/// Category of messages, that were written without explicit category.
+pub const GENERAL_CATEGORY: &str = "General";
+
+/// Category of messages related to resources (loading, reloading, etc.).
+pub const RESOURCES_CATEGORY: &str = "Resources";
+
 /// A message that could be sent by the logger to all listeners.
+#[derive(Clone, Debug)]
 pub struct LogMessage {
     /// Kind of the message: information, warning or error.
     pub kind: MessageKind,
+    /// Category of the message, for example [`GENERAL_CATEGORY`] or [`RESOURCES_CATEGORY`].
+    pub category: String,
     /// The source message without logger prefixes.
     pub content: String,
     /// Time point at which the message was recorded. It is relative to the moment when the
     /// logger was initialized.
     pub time: Duration,
+    /// A path of an asset the message relates to (if any).
+    pub asset: Option<PathBuf>,
 }
 
-lazy_static! {
-    static ref LOG: Mutex<Log> = Mutex::new(Log {
+impl LogMessage {
+    /// Formats the message as a single line with kind, time and category prefixes.
+    pub fn format(&self) -> String {
+        format!(
+            "{}[{:.3}s] [{}] {}",
+            self.kind.as_str(),
+            self.time.as_secs_f32(),
+            self.category,
+            self.content.trim_end_matches('\n')
+        )
+    }
+}
+
+/// Log sink receives every message passed the verbosity filters of the log.
+pub trait LogSink: Send {
+    /// Writes a message.
+    fn write(&mut self, message: &LogMessage);
+}
+
+impl LogSink for Sender<LogMessage> {
+    fn write(&mut self, message: &LogMessage) {
+        let _ = self.send(message.clone());
+    }
+}
+
+/// Writes messages to stdout (or browser console on WebAssembly).
+pub struct StdoutSink;
+
+impl LogSink for StdoutSink {
+    fn write(&mut self, message: &LogMessage) {
+        let mut line = message.format();
+        line.push('\n');
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            log(&line);
+        }
+
         #[cfg(not(target_arch = "wasm32"))]
-        file: std::fs::File::create("fyrox.log").unwrap(),
-        verbosity: MessageKind::Information,
-        listeners: Default::default(),
-        time_origin: Instant::now()
-    });
+        {
+            let _ = io::stdout().write_all(line.as_bytes());
+        }
+    }
+}
+
+/// Writes messages to a file. When size of the file exceeds the limit, the file is rotated:
+/// `fyrox.log` is renamed to `fyrox.log.1`, `fyrox.log.1` to `fyrox.log.2` and so on, the oldest
+/// file is removed.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileSink {
+    path: PathBuf,
+    file: Option<std::fs::File>,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSink {
+    /// Creates (or truncates) a log file at the given path. `max_size` is a maximum size of the
+    /// file in bytes (zero means unlimited), `max_files` is the amount of rotated files to keep.
+    pub fn new<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = std::fs::File::create(&path)?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            size: 0,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) {
+        // Close current file first, otherwise it cannot be renamed on some platforms.
+        self.file = None;
+
+        if self.max_files > 0 {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let _ = std::fs::rename(self.rotated_path(index), self.rotated_path(index + 1));
+            }
+            let _ = std::fs::rename(&self.path, self.rotated_path(1));
+        }
+
+        self.file = std::fs::File::create(&self.path).ok();
+        self.size = 0;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LogSink for FileSink {
+    fn write(&mut self, message: &LogMessage) {
+        let mut line = message.format();
+        line.push('\n');
+
+        if self.max_size > 0 && self.size + line.len() as u64 > self.max_size && self.size > 0 {
+            self.rotate();
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.size += line.len() as u64;
+            }
+        }
+    }
+}
+
+/// Keeps a limited amount of last messages in memory. The sink is shared, so a clone of it could
+/// be used to read messages after the sink was added to the log.
+#[derive(Clone)]
+pub struct MemorySink {
+    messages: Arc<Mutex<VecDeque<LogMessage>>>,
+    capacity: usize,
+}
+
+impl MemorySink {
+    /// Creates new sink, that keeps at most `capacity` last messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: Default::default(),
+            capacity,
+        }
+    }
+
+    /// Returns a copy of stored messages, oldest messages go first.
+    pub fn messages(&self) -> Vec<LogMessage> {
+        self.messages.lock().iter().cloned().collect()
+    }
+
+    /// Removes every stored message.
+    pub fn clear(&self) {
+        self.messages.lock().clear();
+    }
+}
+
+impl LogSink for MemorySink {
+    fn write(&mut self, message: &LogMessage) {
+        let mut messages = self.messages.lock();
+        if self.capacity == 0 {
+            return;
+        }
+        while messages.len() >= self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(message.clone());
+    }
+}
+
+/// A handle of a sink added to the log, it could be used to remove the sink.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LogSinkId(u64);
+
+lazy_static! {
+    static ref LOG: Mutex<Log> = Mutex::new(Log::new());
 }
 
 /// A kind of message.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash, Debug)]
 #[repr(u32)]
 pub enum MessageKind {
     /// Some useful information.
@@ -67,66 +254,94 @@ impl MessageKind {
 
 /// See module docs.
 pub struct Log {
-    #[cfg(not(target_arch = "wasm32"))]
-    file: std::fs::File,
+    sinks: Vec<(LogSinkId, Box<dyn LogSink>)>,
+    next_sink_id: u64,
     verbosity: MessageKind,
-    listeners: Vec<Sender<LogMessage>>,
+    category_verbosity: HashMap<String, MessageKind>,
     time_origin: Instant,
 }
 
 impl Log {
-    fn write_internal<S>(&mut self, kind: MessageKind, message: S)
-    where
-        S: AsRef<str>,
-    {
-        let mut msg = message.as_ref().to_owned();
-        if kind as u32 >= self.verbosity as u32 {
-            for listener in self.listeners.iter() {
-                let _ = listener.send(LogMessage {
-                    kind,
-                    content: msg.clone(),
-                    time: Instant::now() - self.time_origin,
-                });
-            }
+    fn new() -> Self {
+        let mut log = Self {
+            sinks: Default::default(),
+            next_sink_id: 0,
+            verbosity: MessageKind::Information,
+            category_verbosity: Default::default(),
+            time_origin: Instant::now(),
+        };
 
-            msg.insert_str(0, kind.as_str());
+        log.add_sink_internal(Box::new(StdoutSink));
 
-            #[cfg(target_arch = "wasm32")]
-            {
-                log(&msg);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Ok(file) = FileSink::new("fyrox.log", 16 * 1024 * 1024, 3) {
+                log.add_sink_internal(Box::new(file));
             }
+        }
+
+        log
+    }
+
+    fn add_sink_internal(&mut self, sink: Box<dyn LogSink>) -> LogSinkId {
+        let id = LogSinkId(self.next_sink_id);
+        self.next_sink_id += 1;
+        self.sinks.push((id, sink));
+        id
+    }
 
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                let _ = io::stdout().write_all(msg.as_bytes());
-                let _ = self.file.write_all(msg.as_bytes());
+    fn write_internal(
+        &mut self,
+        kind: MessageKind,
+        category: &str,
+        asset: Option<&Path>,
+        content: String,
+    ) {
+        let verbosity = self
+            .category_verbosity
+            .get(category)
+            .cloned()
+            .unwrap_or(self.verbosity);
+
+        if kind >= verbosity {
+            let message = LogMessage {
+                kind,
+                category: category.to_owned(),
+                content,
+                time: Instant::now() - self.time_origin,
+                asset: asset.map(|path| path.to_path_buf()),
+            };
+
+            for (_, sink) in self.sinks.iter_mut() {
+                sink.write(&message);
             }
         }
     }
 
-    fn writeln_internal<S>(&mut self, kind: MessageKind, message: S)
+    /// Writes string into every sink.
+    pub fn write<S>(kind: MessageKind, msg: S)
     where
         S: AsRef<str>,
     {
-        let mut msg = message.as_ref().to_owned();
-        msg.push('\n');
-        self.write_internal(kind, msg)
+        LOG.lock()
+            .write_internal(kind, GENERAL_CATEGORY, None, msg.as_ref().to_owned());
     }
 
-    /// Writes string into console and into file.
-    pub fn write<S>(kind: MessageKind, msg: S)
+    /// Writes line into every sink. Sinks are line-based, so it is the same as [`Self::write`].
+    pub fn writeln<S>(kind: MessageKind, msg: S)
     where
         S: AsRef<str>,
     {
-        LOG.lock().write_internal(kind, msg);
+        Self::write(kind, msg)
     }
 
-    /// Writes line into console and into file.
-    pub fn writeln<S>(kind: MessageKind, msg: S)
+    /// Writes a message of the given category, that optionally relates to an asset.
+    pub fn write_record<S>(kind: MessageKind, category: &str, asset: Option<&Path>, msg: S)
     where
         S: AsRef<str>,
     {
-        LOG.lock().writeln_internal(kind, msg);
+        LOG.lock()
+            .write_internal(kind, category, asset, msg.as_ref().to_owned());
     }
 
     /// Writes information message.
@@ -153,14 +368,90 @@ impl Log {
         Self::writeln(MessageKind::Error, msg)
     }
 
-    /// Sets verbosity level.
+    /// Writes information message of the given category.
+    pub fn info_in<S>(category: &str, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        Self::write_record(MessageKind::Information, category, None, msg)
+    }
+
+    /// Writes warning message of the given category.
+    pub fn warn_in<S>(category: &str, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        Self::write_record(MessageKind::Warning, category, None, msg)
+    }
+
+    /// Writes error message of the given category.
+    pub fn err_in<S>(category: &str, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        Self::write_record(MessageKind::Error, category, None, msg)
+    }
+
+    /// Writes information message related to an asset at the given path.
+    pub fn asset_info<S>(path: &Path, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        Self::write_record(
+            MessageKind::Information,
+            RESOURCES_CATEGORY,
+            Some(path),
+            msg,
+        )
+    }
+
+    /// Writes error message related to an asset at the given path.
+    pub fn asset_err<S>(path: &Path, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        Self::write_record(MessageKind::Error, RESOURCES_CATEGORY, Some(path), msg)
+    }
+
+    /// Sets verbosity level. Messages with lower level will be ignored.
     pub fn set_verbosity(kind: MessageKind) {
         LOG.lock().verbosity = kind;
     }
 
+    /// Sets verbosity level of the given category, it overrides global verbosity level.
+    pub fn set_category_verbosity(category: &str, kind: MessageKind) {
+        LOG.lock()
+            .category_verbosity
+            .insert(category.to_owned(), kind);
+    }
+
+    /// Removes verbosity level override of the given category.
+    pub fn reset_category_verbosity(category: &str) {
+        LOG.lock().category_verbosity.remove(category);
+    }
+
+    /// Adds new sink, returns its id, that could be used to remove the sink.
+    pub fn add_sink(sink: Box<dyn LogSink>) -> LogSinkId {
+        LOG.lock().add_sink_internal(sink)
+    }
+
+    /// Removes a sink, returns `true` if the sink existed.
+    pub fn remove_sink(id: LogSinkId) -> bool {
+        let mut log = LOG.lock();
+        let count = log.sinks.len();
+        log.sinks.retain(|(sink_id, _)| *sink_id != id);
+        log.sinks.len() != count
+    }
+
+    /// Removes every sink, including default ones (stdout and file). It could be used to replace
+    /// default sinks with custom ones.
+    pub fn clear_sinks() {
+        LOG.lock().sinks.clear();
+    }
+
     /// Adds a listener that will receive a copy of every message passed into the log.
     pub fn add_listener(listener: Sender<LogMessage>) {
-        LOG.lock().listeners.push(listener)
+        Self::add_sink(Box::new(listener));
     }
 
     /// Allows you to verify that the result of operation is Ok, or print the error in the log.
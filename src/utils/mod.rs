@@ -5,6 +5,9 @@
 pub mod astar;
 pub mod behavior;
 pub mod component;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod golden_image;
+pub mod light_probe;
 pub mod lightmap;
 pub mod log;
 pub mod navmesh;
@@ -14,10 +17,10 @@ pub mod watcher;
 
 use crate::core::algebra::Vector2;
 use crate::{
-    event::{ElementState, ModifiersState, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    event::{ElementState, Ime, ModifiersState, MouseScrollDelta, VirtualKeyCode, WindowEvent},
     gui::{
         draw,
-        message::{ButtonState, KeyCode, KeyboardModifiers, OsEvent},
+        message::{ButtonState, ImeEvent, KeyCode, KeyboardModifiers, OsEvent},
     },
     resource::texture::Texture,
 };
@@ -278,10 +281,24 @@ pub fn translate_event(event: &WindowEvent) -> Option<OsEvent> {
         &WindowEvent::ModifiersChanged(modifiers) => Some(OsEvent::KeyboardModifiers(
             translate_keyboard_modifiers(modifiers),
         )),
+        WindowEvent::Ime(ime) => Some(OsEvent::Ime(translate_ime(ime))),
         _ => None,
     }
 }
 
+/// Translates IME event to fyrox-ui IME event.
+pub fn translate_ime(ime: &Ime) -> ImeEvent {
+    match ime {
+        Ime::Enabled => ImeEvent::Enabled,
+        Ime::Preedit(text, cursor) => ImeEvent::Preedit {
+            text: text.clone(),
+            cursor: *cursor,
+        },
+        Ime::Commit(text) => ImeEvent::Commit(text.clone()),
+        Ime::Disabled => ImeEvent::Disabled,
+    }
+}
+
 /// Translates keyboard modifiers to fyrox-ui keyboard modifiers.
 pub fn translate_keyboard_modifiers(modifiers: ModifiersState) -> KeyboardModifiers {
     KeyboardModifiers {
@@ -0,0 +1,134 @@
+//! Built-in video decoders. Both decoders are pure-Rust and do not require any external libraries,
+//! use [`VideoDecoder`] trait to add support for other formats.
+
+use crate::video::{VideoDecoder, VideoError};
+use image::{codecs::gif, AnimationDecoder, ImageDecoder};
+use std::{io::Cursor, time::Duration};
+
+/// Default frame duration, that is used for GIF frames without delay (most of the browsers
+/// use the same value).
+const DEFAULT_GIF_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// Decoder for animated GIF files. GIF frames are stored as differences from previous frames, so
+/// the decoder composes every frame at creation. It requires `width * height * 4 * frame_count`
+/// bytes of memory, so it is suitable for short clips only (animated menu backgrounds, icons,
+/// etc.).
+pub struct GifVideoDecoder {
+    width: u32,
+    height: u32,
+    frames: Vec<(Vec<u8>, Duration)>,
+}
+
+impl GifVideoDecoder {
+    /// Tries to decode an animated GIF from given data.
+    pub fn new(data: &[u8]) -> Result<Self, VideoError> {
+        let decoder = gif::GifDecoder::new(Cursor::new(data))?;
+        let (width, height) = decoder.dimensions();
+
+        let frames = decoder
+            .into_frames()
+            .collect_frames()?
+            .into_iter()
+            .map(|frame| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let duration = if numer == 0 {
+                    DEFAULT_GIF_FRAME_DURATION
+                } else {
+                    Duration::from_secs_f64(numer as f64 / denom.max(1) as f64 / 1000.0)
+                };
+                (frame.into_buffer().into_raw(), duration)
+            })
+            .collect::<Vec<_>>();
+
+        if frames.is_empty() {
+            return Err(VideoError::NoFrames);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            frames,
+        })
+    }
+}
+
+impl VideoDecoder for GifVideoDecoder {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn frame_duration(&self, index: usize) -> Duration {
+        self.frames[index].1
+    }
+
+    fn decode_frame(&mut self, index: usize, pixels: &mut [u8]) -> Result<(), VideoError> {
+        pixels.copy_from_slice(&self.frames[index].0);
+        Ok(())
+    }
+}
+
+/// Decoder for a sequence of images (in any format supported by [`crate::resource::texture`]),
+/// played with constant frame rate. Frames are stored in encoded form and decoded on demand, so
+/// the decoder is suitable for long clips of compressed (PNG, JPEG) frames, basically this is
+/// Motion JPEG.
+pub struct ImageSequenceDecoder {
+    width: u32,
+    height: u32,
+    frames: Vec<Vec<u8>>,
+    frame_duration: Duration,
+}
+
+impl ImageSequenceDecoder {
+    /// Creates new decoder from a set of encoded images and desired frame rate (in frames per
+    /// second). Every image must have the same size as the first one.
+    pub fn new(frames: Vec<Vec<u8>>, frame_rate: f32) -> Result<Self, VideoError> {
+        let first = frames.first().ok_or(VideoError::NoFrames)?;
+        let (width, height) = image::load_from_memory(first)?.to_rgba8().dimensions();
+
+        Ok(Self {
+            width,
+            height,
+            frames,
+            frame_duration: Duration::from_secs_f32(1.0 / frame_rate.max(f32::EPSILON)),
+        })
+    }
+}
+
+impl VideoDecoder for ImageSequenceDecoder {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn frame_duration(&self, _index: usize) -> Duration {
+        self.frame_duration
+    }
+
+    fn decode_frame(&mut self, index: usize, pixels: &mut [u8]) -> Result<(), VideoError> {
+        let image = image::load_from_memory(&self.frames[index])?.to_rgba8();
+        if image.dimensions() != (self.width, self.height) {
+            return Err(VideoError::FrameSizeMismatch {
+                index,
+                width: image.width(),
+                height: image.height(),
+            });
+        }
+        pixels.copy_from_slice(image.as_raw());
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+//! Video playback. Video frames are decoded by a [`VideoDecoder`] and streamed into a texture, that
+//! could be used as any other texture - in a material of a mesh (for example a TV screen in a
+//! scene), or in an [`crate::gui::image::Image`] widget (for cutscenes and menu backgrounds).
+//!
+//! # Decoders
+//!
+//! The engine provides two pure-Rust decoders - [`GifVideoDecoder`] for animated GIFs and
+//! [`ImageSequenceDecoder`] for a sequence of compressed images (PNG, JPEG, etc.). Any other format
+//! could be supported by implementing [`VideoDecoder`] trait on top of a third-party library (for
+//! example ffmpeg bindings).
+//!
+//! # Audio
+//!
+//! Video decoders produce frames only, audio track of a video should be stored separately as a
+//! sound buffer. Create a [`crate::scene::sound::Sound`] node with the buffer and pass its handle to
+//! [`VideoPlayer::set_audio`], the player will keep the sound in sync with the video when
+//! [`VideoPlayer::sync_audio`] is called.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use fyrox::{
+//!     gui::{image::ImageBuilder, widget::WidgetBuilder, BuildContext},
+//!     utils::into_gui_texture,
+//!     video::{GifVideoDecoder, VideoPlayer},
+//! };
+//!
+//! fn create_menu_background(data: &[u8], ctx: &mut BuildContext) -> VideoPlayer {
+//!     let mut player = VideoPlayer::new(Box::new(GifVideoDecoder::new(data).unwrap())).unwrap();
+//!     player.set_looping(true);
+//!     player.play();
+//!
+//!     ImageBuilder::new(WidgetBuilder::new())
+//!         .with_texture(into_gui_texture(player.texture()))
+//!         .build(ctx);
+//!
+//!     // Call `player.update(dt)` every frame to advance the video.
+//!     player
+//! }
+//! ```
+
+pub mod decoder;
+
+pub use decoder::{GifVideoDecoder, ImageSequenceDecoder};
+
+use crate::{
+    core::pool::Handle,
+    resource::texture::{Texture, TextureKind, TexturePixelKind},
+    scene::{
+        graph::Graph,
+        node::Node,
+        sound::{Sound, Status},
+    },
+};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// An error that may occur during video decoding.
+#[derive(Debug)]
+pub enum VideoError {
+    /// A video does not have any frames.
+    NoFrames,
+    /// A frame has different size than the video.
+    FrameSizeMismatch {
+        /// Index of the frame.
+        index: usize,
+        /// Actual width of the frame.
+        width: u32,
+        /// Actual height of the frame.
+        height: u32,
+    },
+    /// Internal image crate error.
+    Image(image::ImageError),
+    /// Decoder-specific error.
+    Custom(String),
+}
+
+impl Display for VideoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoError::NoFrames => {
+                write!(f, "Video does not have any frames!")
+            }
+            VideoError::FrameSizeMismatch {
+                index,
+                width,
+                height,
+            } => {
+                write!(
+                    f,
+                    "Frame {} has size {}x{} which does not match video size!",
+                    index, width, height
+                )
+            }
+            VideoError::Image(v) => {
+                write!(f, "Image decoding error {}", v)
+            }
+            VideoError::Custom(v) => {
+                write!(f, "Video decoding error {}", v)
+            }
+        }
+    }
+}
+
+impl From<image::ImageError> for VideoError {
+    fn from(v: image::ImageError) -> Self {
+        Self::Image(v)
+    }
+}
+
+/// Video decoder is a source of video frames. Frames are requested by index, in most cases
+/// sequentially, but seeking may request any frame.
+pub trait VideoDecoder: Send {
+    /// Returns width of every frame in pixels.
+    fn width(&self) -> u32;
+
+    /// Returns height of every frame in pixels.
+    fn height(&self) -> u32;
+
+    /// Returns total amount of frames in the video.
+    fn frame_count(&self) -> usize;
+
+    /// Returns duration of a frame with given index.
+    fn frame_duration(&self, index: usize) -> Duration;
+
+    /// Decodes a frame with given index into the given buffer of `width * height` RGBA8 pixels.
+    fn decode_frame(&mut self, index: usize, pixels: &mut [u8]) -> Result<(), VideoError>;
+}
+
+/// Video player plays a video from a decoder into a texture. See module docs for more info.
+pub struct VideoPlayer {
+    decoder: Box<dyn VideoDecoder>,
+    texture: Texture,
+    // Start time of every frame.
+    frame_times: Vec<Duration>,
+    duration: Duration,
+    time: Duration,
+    status: Status,
+    looping: bool,
+    speed: f32,
+    current_frame: Option<usize>,
+    audio: Handle<Node>,
+    audio_needs_seek: bool,
+}
+
+impl VideoPlayer {
+    /// Creates new video player for given decoder. The player is stopped by default.
+    pub fn new(decoder: Box<dyn VideoDecoder>) -> Result<Self, VideoError> {
+        let frame_count = decoder.frame_count();
+        if frame_count == 0 {
+            return Err(VideoError::NoFrames);
+        }
+
+        let mut frame_times = Vec::with_capacity(frame_count);
+        let mut duration = Duration::default();
+        for i in 0..frame_count {
+            frame_times.push(duration);
+            duration += decoder.frame_duration(i);
+        }
+
+        let (width, height) = (decoder.width(), decoder.height());
+        let texture = Texture::from_bytes(
+            TextureKind::Rectangle { width, height },
+            TexturePixelKind::RGBA8,
+            vec![0; (width * height * 4) as usize],
+            false,
+        )
+        .ok_or_else(|| VideoError::Custom("Invalid video size!".to_string()))?;
+        // Frames do not have mip levels, generate them on GPU to prevent aliasing when the
+        // video is rendered in a scene.
+        texture.data_ref().set_mip_map_generation(true);
+
+        let mut player = Self {
+            decoder,
+            texture,
+            frame_times,
+            duration,
+            time: Duration::default(),
+            status: Status::Stopped,
+            looping: false,
+            speed: 1.0,
+            current_frame: None,
+            audio: Handle::NONE,
+            audio_needs_seek: false,
+        };
+
+        player.upload_current_frame()?;
+
+        Ok(player)
+    }
+
+    /// Returns a texture the video is played into. The texture is shared, every change of the
+    /// current frame is immediately visible to every user of the texture.
+    pub fn texture(&self) -> Texture {
+        self.texture.clone()
+    }
+
+    /// Starts or resumes playback. A video, that has reached its end, is played from the beginning.
+    pub fn play(&mut self) {
+        if self.time >= self.duration {
+            self.seek(Duration::default());
+        }
+        self.status = Status::Playing;
+    }
+
+    /// Pauses playback, current frame remains visible.
+    pub fn pause(&mut self) {
+        self.status = Status::Paused;
+    }
+
+    /// Stops playback and rewinds the video to the beginning.
+    pub fn stop(&mut self) {
+        self.status = Status::Stopped;
+        self.seek(Duration::default());
+    }
+
+    /// Returns current playback status.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Sets current playback position, it will be clamped to the duration of the video. Frame at
+    /// the position will be uploaded on next [`Self::update`] call.
+    pub fn seek(&mut self, time: Duration) {
+        self.time = time.min(self.duration);
+        self.audio_needs_seek = true;
+    }
+
+    /// Returns current playback position.
+    pub fn playback_time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns total duration of the video.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Enables or disables looping. Looping video starts from the beginning when it reaches the
+    /// end, otherwise the player stops at the last frame.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Returns `true` if the video is looping, `false` - otherwise.
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sets playback speed multiplier. Default is 1.0.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Returns current playback speed multiplier.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets a handle of a sound node, that plays audio track of the video. Pass [`Handle::NONE`]
+    /// to detach audio.
+    pub fn set_audio(&mut self, audio: Handle<Node>) {
+        self.audio = audio;
+        self.audio_needs_seek = true;
+    }
+
+    /// Returns a handle of a sound node, that plays audio track of the video.
+    pub fn audio(&self) -> Handle<Node> {
+        self.audio
+    }
+
+    /// Returns index of the frame, that is currently in the texture.
+    pub fn current_frame(&self) -> Option<usize> {
+        self.current_frame
+    }
+
+    /// Advances playback by given amount of time (in seconds) and uploads new frame into the
+    /// texture if needed. Must be called every frame.
+    pub fn update(&mut self, dt: f32) -> Result<(), VideoError> {
+        if self.status == Status::Playing {
+            self.time += Duration::from_secs_f32(dt * self.speed);
+
+            if self.time >= self.duration {
+                if self.looping {
+                    self.time = Duration::from_secs_f64(
+                        self.time.as_secs_f64() % self.duration.as_secs_f64().max(f64::EPSILON),
+                    );
+                    self.audio_needs_seek = true;
+                } else {
+                    self.time = self.duration;
+                    self.status = Status::Stopped;
+                }
+            }
+        }
+
+        self.upload_current_frame()
+    }
+
+    /// Syncs status and playback position of the audio sound node (if any) with the video.
+    pub fn sync_audio(&mut self, graph: &mut Graph) {
+        if let Some(sound) = graph
+            .try_get_mut(self.audio)
+            .and_then(|node| node.cast_mut::<Sound>())
+        {
+            if sound.status() != self.status {
+                sound.set_status(self.status);
+            }
+            if sound.is_looping() != self.looping {
+                sound.set_looping(self.looping);
+            }
+            if self.audio_needs_seek {
+                sound.set_playback_time(self.time);
+            }
+            self.audio_needs_seek = false;
+        }
+    }
+
+    fn frame_index(&self) -> usize {
+        match self.frame_times.binary_search(&self.time) {
+            Ok(index) => index,
+            Err(index) => index.saturating_sub(1),
+        }
+        .min(self.frame_times.len() - 1)
+    }
+
+    fn upload_current_frame(&mut self) -> Result<(), VideoError> {
+        let index = self.frame_index();
+        if self.current_frame == Some(index) {
+            return Ok(());
+        }
+
+        let mut texture = self.texture.data_ref();
+        let mut data = texture.modify();
+        self.decoder.decode_frame(index, data.data_mut())?;
+        self.current_frame = Some(index);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::video::{ImageSequenceDecoder, VideoPlayer};
+    use image::{ImageOutputFormat, Rgba, RgbaImage};
+    use std::{io::Cursor, time::Duration};
+
+    fn encode_frame(color: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        RgbaImage::from_pixel(2, 2, Rgba([color, color, color, 255]))
+            .write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_video_playback() {
+        let frames = vec![encode_frame(0), encode_frame(100), encode_frame(200)];
+        let decoder = ImageSequenceDecoder::new(frames, 10.0).unwrap();
+        let mut player = VideoPlayer::new(Box::new(decoder)).unwrap();
+
+        assert_eq!(player.current_frame(), Some(0));
+        assert!((player.duration().as_secs_f32() - 0.3).abs() < 1.0e-4);
+
+        // Stopped player does not advance.
+        player.update(0.15).unwrap();
+        assert_eq!(player.current_frame(), Some(0));
+
+        player.play();
+        player.update(0.15).unwrap();
+        assert_eq!(player.current_frame(), Some(1));
+        assert_eq!(player.texture().data_ref().data()[0], 100);
+
+        player.seek(Duration::from_secs_f32(0.25));
+        player.update(0.0).unwrap();
+        assert_eq!(player.current_frame(), Some(2));
+
+        // Non-looping video stops at the last frame.
+        player.update(1.0).unwrap();
+        assert_eq!(player.current_frame(), Some(2));
+        assert_eq!(player.playback_time(), player.duration());
+
+        player.set_looping(true);
+        player.play();
+        player.seek(Duration::from_secs_f32(0.25));
+        player.update(0.1).unwrap();
+        assert_eq!(player.current_frame(), Some(0));
+    }
+}
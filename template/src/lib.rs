@@ -0,0 +1,560 @@
+//! Fyrox Project Template Generator. Generates game projects and scripts, it is used by the
+//! `fyrox-template` command line tool and by the editor's project manager.
+
+use convert_case::{Case, Casing};
+use std::{
+    fs::{create_dir_all, remove_dir_all, File},
+    io::{self, ErrorKind, Write},
+    path::Path,
+    process::Command,
+};
+use uuid::Uuid;
+
+/// Supported project styles.
+pub const STYLES: [&str; 3] = ["2d", "3d", "minimal"];
+
+fn write_file<P: AsRef<Path>, S: AsRef<str>>(path: P, content: S) -> io::Result<()> {
+    File::create(path)?.write_all(content.as_ref().as_bytes())
+}
+
+fn write_file_binary<P: AsRef<Path>>(path: P, content: &[u8]) -> io::Result<()> {
+    File::create(path)?.write_all(content)
+}
+
+fn cargo_init(path: &Path, args: &[&str]) -> io::Result<()> {
+    let output = Command::new("cargo")
+        .arg("init")
+        .args(args)
+        .arg(path)
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            ErrorKind::Other,
+            format!(
+                "Unable to initialize {}. Reason: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        ))
+    }
+}
+
+fn init_game(base_path: &Path, name: &str, style: &str) -> io::Result<()> {
+    cargo_init(&base_path.join("game"), &["--lib", "--vcs", "none"])?;
+
+    // Write Cargo.toml
+    write_file(
+        base_path.join("game/Cargo.toml"),
+        format!(
+            r#"
+[package]
+name = "{}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+fyrox = "0.28""#,
+            name,
+        ),
+    )?;
+
+    // Write lib.rs
+    write_file(
+        base_path.join("game/src/lib.rs"),
+        format!(
+            r#"//! Game project.
+use fyrox::{{
+    core::pool::Handle,
+    event::Event,
+    event_loop::ControlFlow,
+    gui::message::UiMessage,
+    plugin::{{Plugin, PluginConstructor, PluginContext, PluginRegistrationContext}},
+    scene::{{Scene, loader::AsyncSceneLoader}},
+    utils::log::Log
+}};
+
+pub struct GameConstructor;
+
+impl PluginConstructor for GameConstructor {{
+    fn register(&self, _context: PluginRegistrationContext) {{
+        // Register your scripts here.
+    }}
+
+    fn create_instance(
+        &self,
+        override_scene: Handle<Scene>,
+        context: PluginContext,
+    ) -> Box<dyn Plugin> {{
+        Box::new(Game::new(override_scene, context))
+    }}
+}}
+
+pub struct Game {{
+    scene: Handle<Scene>,
+    loader: Option<AsyncSceneLoader>,
+}}
+
+impl Game {{
+    pub fn new(override_scene: Handle<Scene>, context: PluginContext) -> Self {{
+{}
+    }}
+}}
+
+impl Plugin for Game {{
+    fn on_deinit(&mut self, _context: PluginContext) {{
+        // Do a cleanup here.
+    }}
+
+    fn update(&mut self, context: &mut PluginContext, _control_flow: &mut ControlFlow) {{
+         if let Some(loader) = self.loader.as_ref() {{
+            if let Some(result) = loader.fetch_result() {{
+                match result {{
+                    Ok(scene) => {{
+                        self.scene = context.scenes.add(scene);
+                    }}
+                    Err(err) => Log::err(err),
+                }}
+            }}
+        }}
+    
+        // Add your global update code here.
+    }}
+
+    fn on_os_event(
+        &mut self,
+        _event: &Event<()>,
+        _context: PluginContext,
+        _control_flow: &mut ControlFlow,
+    ) {{
+        // Do something on OS event here.
+    }}
+
+    fn on_ui_message(
+        &mut self,
+        _context: &mut PluginContext,
+        _message: &UiMessage,
+        _control_flow: &mut ControlFlow,
+    ) {{
+        // Handle UI events here.
+    }}
+}}
+"#,
+            game_constructor_body(style)
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn init_executor(base_path: &Path, name: &str) -> io::Result<()> {
+    cargo_init(&base_path.join("executor"), &["--bin", "--vcs", "none"])?;
+
+    // Write Cargo.toml
+    write_file(
+        base_path.join("executor/Cargo.toml"),
+        format!(
+            r#"
+[package]
+name = "executor"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+fyrox = "0.28"
+{} = {{ path = "../game" }}"#,
+            name,
+        ),
+    )?;
+
+    // Write main.rs
+    write_file(
+        base_path.join("executor/src/main.rs"),
+        format!(
+            r#"//! Executor with your game connected to it as a plugin.
+use fyrox::engine::executor::Executor;
+use {}::GameConstructor;
+
+fn main() {{
+    let mut executor = Executor::new();
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}}"#,
+            name
+        ),
+    )
+}
+
+fn init_wasm_executor(base_path: &Path, name: &str) -> io::Result<()> {
+    cargo_init(
+        &base_path.join("executor-wasm"),
+        &["--lib", "--vcs", "none"],
+    )?;
+
+    // Write Cargo.toml
+    write_file(
+        base_path.join("executor-wasm/Cargo.toml"),
+        format!(
+            r#"
+[package]
+name = "executor-wasm"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib", "rlib"]
+
+[dependencies]
+fyrox = "0.28"
+{} = {{ path = "../game" }}"#,
+            name,
+        ),
+    )?;
+
+    // Write lib.rs
+    write_file(
+        base_path.join("executor-wasm/src/lib.rs"),
+        format!(
+            r#"//! Executor with your game connected to it as a plugin.
+use fyrox::engine::executor::Executor;
+use {}::GameConstructor;
+use fyrox::core::wasm_bindgen::{{self, prelude::*}};
+
+#[wasm_bindgen]
+extern "C" {{
+    #[wasm_bindgen(js_namespace = console)]
+    fn error(msg: String);
+
+    type Error;
+
+    #[wasm_bindgen(constructor)]
+    fn new() -> Error;
+
+    #[wasm_bindgen(structural, method, getter)]
+    fn stack(error: &Error) -> String;
+}}
+
+fn custom_panic_hook(info: &std::panic::PanicInfo) {{
+    let mut msg = info.to_string();
+    msg.push_str("\n\nStack:\n\n");
+    let e = Error::new();
+    let stack = e.stack();
+    msg.push_str(&stack);
+    msg.push_str("\n\n");
+    error(msg);
+}}
+
+#[inline]
+pub fn set_panic_hook() {{
+    use std::sync::Once;
+    static SET_HOOK: Once = Once::new();
+    SET_HOOK.call_once(|| {{
+        std::panic::set_hook(Box::new(custom_panic_hook));
+    }});
+}}
+
+#[wasm_bindgen]
+pub fn main() {{
+    set_panic_hook();
+    let mut executor = Executor::new();
+    executor.add_plugin_constructor(GameConstructor);
+    executor.run()
+}}"#,
+            name
+        ),
+    )?;
+
+    // Write "entry" point stuff. This includes:
+    //
+    // - Index page with a "Start" button. The button is needed to solve sound issues in some browsers.
+    //   Some browsers (mostly Chrome) prevent sound from playing until user click on something on the
+    //   game page.
+    // - Entry JavaScript code - basically a web launcher for your game.
+    // - Styles - to make "Start" button to look decent.
+    // - A readme file with build instructions.
+    write_file_binary(
+        base_path.join("executor-wasm/index.html"),
+        include_bytes!("wasm/index.html"),
+    )?;
+    write_file_binary(
+        base_path.join("executor-wasm/styles.css"),
+        include_bytes!("wasm/styles.css"),
+    )?;
+    write_file_binary(
+        base_path.join("executor-wasm/main.js"),
+        include_bytes!("wasm/main.js"),
+    )?;
+    write_file_binary(
+        base_path.join("executor-wasm/README.md"),
+        include_bytes!("wasm/README.md"),
+    )
+}
+
+fn init_editor(base_path: &Path, name: &str, style: &str) -> io::Result<()> {
+    cargo_init(&base_path.join("editor"), &["--bin", "--vcs", "none"])?;
+
+    // Write Cargo.toml
+    write_file(
+        base_path.join("editor/Cargo.toml"),
+        format!(
+            r#"
+[package]
+name = "editor"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+fyrox = "0.28"
+fyroxed_base = "0.15"
+{} = {{ path = "../game" }}"#,
+            name,
+        ),
+    )?;
+
+    write_file(
+        base_path.join("editor/src/main.rs"),
+        format!(
+            r#"//! Editor with your game connected to it as a plugin.
+use fyrox::event_loop::EventLoop;
+use fyroxed_base::{{Editor, StartupData}};
+use {}::GameConstructor;
+
+fn main() {{
+    let event_loop = EventLoop::new();
+    let mut editor = Editor::new(
+        &event_loop,
+        Some(StartupData {{
+            working_directory: Default::default(),
+            scene: "{}".into(),
+        }}),
+    );
+    editor.add_game_plugin(GameConstructor);
+    editor.run(event_loop)
+}}
+"#,
+            name,
+            startup_scene(style)
+        ),
+    )
+}
+
+fn init_workspace(base_path: &Path) -> io::Result<()> {
+    cargo_init(base_path, &["--vcs", "git"])?;
+
+    let src_path = base_path.join("src");
+    if src_path.exists() {
+        remove_dir_all(src_path)?;
+    }
+
+    // Write Cargo.toml
+    write_file(
+        base_path.join("Cargo.toml"),
+        r#"
+[workspace]
+members = ["editor", "executor", "executor-wasm", "game"]
+
+# Optimize the engine in debug builds, but leave project's code non-optimized.
+# By using this technique, you can still debug you code, but engine will be fully
+# optimized and debug builds won't be terribly slow. With this option, you can 
+# compile your game in debug mode, which is much faster (at least x3), than release.
+[profile.dev.package."*"]
+opt-level = 3
+"#,
+    )
+}
+
+fn init_data(base_path: &Path, style: &str) -> io::Result<()> {
+    let data_path = base_path.join("data");
+    create_dir_all(&data_path)?;
+
+    let scene_path = data_path.join("scene.rgs");
+    match style {
+        "2d" => write_file_binary(scene_path, include_bytes!("2d.rgs")),
+        "3d" => write_file_binary(scene_path, include_bytes!("3d.rgs")),
+        // Minimal project has no scene, the game creates an empty one at runtime.
+        _ => Ok(()),
+    }
+}
+
+/// Returns a code of the game plugin constructor for the given project style.
+fn game_constructor_body(style: &str) -> &'static str {
+    if style == "minimal" {
+        r#"        let scene = if override_scene.is_some() {
+            override_scene
+        } else {
+            context.scenes.add(Scene::new())
+        };
+
+        Self {
+            scene,
+            loader: None,
+        }"#
+    } else {
+        r#"        let mut loader = None;
+        let scene = if override_scene.is_some() {
+            override_scene
+        } else {
+            loader = Some(AsyncSceneLoader::begin_loading(
+                "data/scene.rgs".into(),
+                context.serialization_context.clone(),
+                context.resource_manager.clone(),
+            ));
+            Default::default()
+        };
+
+        Self { scene, loader }"#
+    }
+}
+
+/// Returns a path of a scene, that will be loaded by the editor at start.
+fn startup_scene(style: &str) -> &'static str {
+    if style == "minimal" {
+        ""
+    } else {
+        "data/scene.rgs"
+    }
+}
+
+/// Checks whether the given string can be used as a project name.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        Err("The project name cannot be empty.".to_string())
+    } else if name.contains('-') {
+        Err("The project name cannot contain `-`.".to_string())
+    } else if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || name.starts_with(|c: char| c.is_ascii_digit())
+    {
+        Err(format!(
+            "The project name `{}` is not a valid Rust identifier.",
+            name
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Initializes a new game project of the given name and style at the given path. The project
+/// consists of a workspace with a game (library), editor, executor and WebAssembly executor crates
+/// and the data folder.
+///
+/// `style` must be one of [`STYLES`]. `2d` and `3d` styles populate the data folder with a default
+/// scene that is loaded at start, `minimal` style creates an empty scene at runtime.
+///
+/// Keep in mind that the function uses `cargo` to initialize the crates, so it must be installed.
+pub fn init_project(base_path: &Path, name: &str, style: &str) -> io::Result<()> {
+    check_name(name).map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+
+    if !STYLES.contains(&style) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Unknown style: {}. Use one of the following: {}",
+                style,
+                STYLES.join(", ")
+            ),
+        ));
+    }
+
+    if base_path.exists() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists!", base_path.display()),
+        ));
+    }
+
+    init_workspace(base_path)?;
+    init_data(base_path, style)?;
+    init_game(base_path, name, style)?;
+    init_editor(base_path, name, style)?;
+    init_executor(base_path, name)?;
+    init_wasm_executor(base_path, name)
+}
+
+/// Adds a script with the given name to the `game/src` folder of a project at the given path.
+/// The name will be capitalized. Returns the name of the script.
+pub fn init_script(project_path: &Path, raw_name: &str) -> io::Result<String> {
+    let base_path = project_path.join("game/src/");
+    if !base_path.exists() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            "game/src directory does not exists!",
+        ));
+    }
+
+    let script_file_stem = raw_name.to_case(Case::Snake);
+    let script_name = raw_name.to_case(Case::UpperCamel);
+    let file_name = base_path.join(script_file_stem + ".rs");
+
+    if file_name.exists() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("Script {} already exists!", script_name),
+        ));
+    }
+
+    let script_uuid = Uuid::new_v4().to_string();
+
+    write_file(
+        file_name,
+        format!(
+            r#"
+use fyrox::{{
+    core::{{uuid::{{Uuid, uuid}}, visitor::prelude::*, reflect::Reflect}},
+    engine::resource_manager::ResourceManager,
+    event::Event, impl_component_provider,
+    scene::{{node::TypeUuidProvider}},
+    script::{{ScriptContext, ScriptDeinitContext, ScriptTrait}},
+}};
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct {name} {{
+    // Add fields here.
+}}
+
+impl_component_provider!({name});
+
+impl TypeUuidProvider for {name} {{
+    fn type_uuid() -> Uuid {{
+        uuid!("{id}")
+    }}
+}}
+
+impl ScriptTrait for {name} {{
+    fn on_init(&mut self, context: &mut ScriptContext) {{
+        // Put initialization logic here.
+    }}
+
+    fn on_start(&mut self, context: &mut ScriptContext) {{
+        // There should be a logic that depends on other scripts in scene.
+        // It is called right after **all** scripts were initialized.
+    }}
+
+    fn on_deinit(&mut self, context: &mut ScriptDeinitContext) {{
+        // Put de-initialization logic here.
+    }}
+
+    fn on_os_event(&mut self, event: &Event<()>, context: &mut ScriptContext) {{
+        // Respond to OS events here.
+    }}
+
+    fn on_update(&mut self, context: &mut ScriptContext) {{
+        // Put object logic here.
+    }}
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {{
+        // Restore resource handles here.
+    }}
+
+    fn id(&self) -> Uuid {{
+        Self::type_uuid()
+    }}
+}}
+    "#,
+            name = script_name,
+            id = script_uuid
+        ),
+    )?;
+
+    Ok(script_name)
+}